@@ -0,0 +1,6 @@
+-- Per-garderie branding applied to outgoing emails and exposed by /tenant/info
+ALTER TABLE public.garderies
+    ADD COLUMN IF NOT EXISTS brand_primary_color VARCHAR(7),
+    ADD COLUMN IF NOT EXISTS brand_accent_color  VARCHAR(7),
+    ADD COLUMN IF NOT EXISTS brand_footer_text   TEXT,
+    ADD COLUMN IF NOT EXISTS brand_reply_to      VARCHAR(255);
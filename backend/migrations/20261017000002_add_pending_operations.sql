@@ -0,0 +1,27 @@
+-- Destructive super-admin operations (garderie deletion, restore) wait here until a
+-- second super-admin confirms them or their cancel window elapses.
+CREATE TABLE IF NOT EXISTS public.pending_operations (
+    id            UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    kind          VARCHAR(32) NOT NULL CHECK (kind IN ('delete_garderie', 'restore')),
+    target_slug   VARCHAR(63),
+    payload       JSONB NOT NULL DEFAULT '{}',
+    status        VARCHAR(16) NOT NULL DEFAULT 'pending'
+                  CHECK (status IN ('pending', 'running', 'executed', 'cancelled', 'failed')),
+    requested_by  VARCHAR(255),
+    confirmed_by  VARCHAR(255),
+    cancelled_by  VARCHAR(255),
+    execute_after TIMESTAMPTZ NOT NULL,
+    error         TEXT,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    executed_at   TIMESTAMPTZ,
+    cancelled_at  TIMESTAMPTZ
+);
+
+-- At most one open operation of each kind per target.
+CREATE UNIQUE INDEX IF NOT EXISTS idx_pending_operations_open
+    ON public.pending_operations (kind, COALESCE(target_slug, ''))
+    WHERE status IN ('pending', 'running');
+
+CREATE INDEX IF NOT EXISTS idx_pending_operations_due
+    ON public.pending_operations (execute_after)
+    WHERE status = 'pending';
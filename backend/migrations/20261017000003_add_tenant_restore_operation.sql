@@ -0,0 +1,5 @@
+-- Single-garderie restores from S3 go through the same confirmation window
+ALTER TABLE public.pending_operations DROP CONSTRAINT IF EXISTS pending_operations_kind_check;
+ALTER TABLE public.pending_operations
+    ADD CONSTRAINT pending_operations_kind_check
+    CHECK (kind IN ('delete_garderie', 'restore', 'restore_garderie'));
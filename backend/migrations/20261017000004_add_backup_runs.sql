@@ -0,0 +1,14 @@
+-- Outcome of every per-garderie backup, scheduled or triggered by a super-admin
+CREATE TABLE IF NOT EXISTS public.backup_runs (
+    id          UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    slug        VARCHAR(63) NOT NULL,
+    trigger     VARCHAR(16) NOT NULL CHECK (trigger IN ('scheduled', 'manual')),
+    status      VARCHAR(16) NOT NULL CHECK (status IN ('succeeded', 'failed')),
+    backup_id   VARCHAR(15),
+    size_bytes  BIGINT,
+    error       TEXT,
+    started_at  TIMESTAMPTZ NOT NULL,
+    finished_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_backup_runs_slug ON public.backup_runs (slug, started_at DESC);
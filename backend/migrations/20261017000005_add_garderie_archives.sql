@@ -0,0 +1,19 @@
+-- Offboarded garderies can be archived instead of deleted: their schema and media are
+-- exported to S3 and removed, and the registry row stays until the archive is purged.
+ALTER TABLE public.garderies ADD COLUMN IF NOT EXISTS archived_at TIMESTAMPTZ;
+ALTER TABLE public.garderies ADD COLUMN IF NOT EXISTS archive_backup_id VARCHAR(15);
+ALTER TABLE public.garderies ADD COLUMN IF NOT EXISTS purge_after TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_garderies_purge_after
+    ON public.garderies (purge_after)
+    WHERE archived_at IS NOT NULL;
+
+ALTER TABLE public.pending_operations DROP CONSTRAINT IF EXISTS pending_operations_kind_check;
+ALTER TABLE public.pending_operations
+    ADD CONSTRAINT pending_operations_kind_check
+    CHECK (kind IN ('delete_garderie', 'restore', 'restore_garderie', 'archive_garderie'));
+
+ALTER TABLE public.backup_runs DROP CONSTRAINT IF EXISTS backup_runs_trigger_check;
+ALTER TABLE public.backup_runs
+    ADD CONSTRAINT backup_runs_trigger_check
+    CHECK (trigger IN ('scheduled', 'manual', 'archive'));
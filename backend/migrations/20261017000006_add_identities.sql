@@ -0,0 +1,16 @@
+-- A person across garderies, keyed by an email address they proved they own
+CREATE TABLE IF NOT EXISTS public.identities (
+    id         UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    email      VARCHAR(255) NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Tenant-local accounts of an identity, at most one per garderie
+CREATE TABLE IF NOT EXISTS public.identity_links (
+    identity_id UUID NOT NULL REFERENCES public.identities(id) ON DELETE CASCADE,
+    slug        VARCHAR(64) NOT NULL REFERENCES public.garderies(slug) ON DELETE CASCADE,
+    user_id     UUID NOT NULL,
+    linked_at   TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (identity_id, slug),
+    UNIQUE (slug, user_id)
+);
@@ -0,0 +1,11 @@
+-- Addresses reported undeliverable by the email provider (hard bounce or spam
+-- complaint). Shared by all garderies: nothing is sent to them until removed.
+CREATE TABLE IF NOT EXISTS public.email_suppressions (
+    email      VARCHAR(255) PRIMARY KEY,
+    reason     VARCHAR(16) NOT NULL CHECK (reason IN ('bounce', 'complaint')),
+    provider   VARCHAR(16) NOT NULL,
+    detail     TEXT,
+    events     INT NOT NULL DEFAULT 1,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
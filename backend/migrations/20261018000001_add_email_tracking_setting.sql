@@ -0,0 +1,3 @@
+-- Open and click tracking of announcements sent to parents. Off unless the garderie
+-- turns it on: it tells the garderie whether a family read its emails.
+ALTER TABLE public.garderies ADD COLUMN IF NOT EXISTS email_tracking_enabled BOOLEAN NOT NULL DEFAULT FALSE;
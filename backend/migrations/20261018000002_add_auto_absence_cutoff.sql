@@ -0,0 +1,3 @@
+-- Time (HH:MM) after which an expected child not yet checked in is marked absent in
+-- the day's journal and their parents asked to confirm. NULL: off.
+ALTER TABLE public.garderies ADD COLUMN IF NOT EXISTS auto_absence_cutoff VARCHAR(5);
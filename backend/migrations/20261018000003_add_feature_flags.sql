@@ -0,0 +1,14 @@
+-- Feature flags: a row without garderie is the platform-wide value, a row with one
+-- overrides it for that garderie. `maintenance_mode` is only read platform-wide.
+CREATE TABLE IF NOT EXISTS public.feature_flags (
+    id          UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    key         VARCHAR(64) NOT NULL,
+    garderie_id UUID REFERENCES public.garderies(id) ON DELETE CASCADE,
+    enabled     BOOLEAN NOT NULL,
+    updated_by  VARCHAR(255),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE UNIQUE INDEX IF NOT EXISTS feature_flags_global_idx
+    ON public.feature_flags (key) WHERE garderie_id IS NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS feature_flags_garderie_idx
+    ON public.feature_flags (key, garderie_id) WHERE garderie_id IS NOT NULL;
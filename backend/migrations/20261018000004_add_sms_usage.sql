@@ -0,0 +1,7 @@
+-- SMS sent by each garderie per calendar month, counted against its plan's credits.
+CREATE TABLE IF NOT EXISTS public.sms_usage (
+    garderie_id UUID NOT NULL REFERENCES public.garderies(id) ON DELETE CASCADE,
+    month       DATE NOT NULL,
+    sent        INT NOT NULL DEFAULT 0,
+    PRIMARY KEY (garderie_id, month)
+);
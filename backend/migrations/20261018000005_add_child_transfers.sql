@@ -0,0 +1,34 @@
+-- A child moving to another garderie: a super-admin requests the transfer and it runs
+-- once confirmed by a second one or after the cancel window, like other operations.
+ALTER TABLE public.pending_operations DROP CONSTRAINT IF EXISTS pending_operations_kind_check;
+ALTER TABLE public.pending_operations
+    ADD CONSTRAINT pending_operations_kind_check
+    CHECK (kind IN ('delete_garderie', 'restore', 'restore_garderie', 'archive_garderie', 'transfer_child'));
+
+-- Several children of one garderie may be transferred at once, but not the same one twice.
+DROP INDEX IF EXISTS public.idx_pending_operations_open;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_pending_operations_open
+    ON public.pending_operations (kind, COALESCE(target_slug, ''), COALESCE(payload->>'child_id', ''))
+    WHERE status IN ('pending', 'running');
+
+-- Record of every executed transfer, kept after either garderie is deleted.
+CREATE TABLE IF NOT EXISTS public.child_transfers (
+    id              UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    operation_id    UUID REFERENCES public.pending_operations(id) ON DELETE SET NULL,
+    source_slug     VARCHAR(63) NOT NULL,
+    source_child_id UUID NOT NULL,
+    target_slug     VARCHAR(63) NOT NULL,
+    target_child_id UUID NOT NULL,
+    child_name      VARCHAR(257) NOT NULL,
+    requested_by    VARCHAR(255),
+    confirmed_by    VARCHAR(255),
+    journals        INT NOT NULL DEFAULT 0,
+    journal_events  INT NOT NULL DEFAULT 0,
+    media           INT NOT NULL DEFAULT 0,
+    parents_linked  INT NOT NULL DEFAULT 0,
+    parents_invited INT NOT NULL DEFAULT 0,
+    transferred_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_child_transfers_source ON public.child_transfers (source_slug, transferred_at DESC);
+CREATE INDEX IF NOT EXISTS idx_child_transfers_target ON public.child_transfers (target_slug, transferred_at DESC);
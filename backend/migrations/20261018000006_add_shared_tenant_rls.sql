@@ -0,0 +1,179 @@
+-- Shared-schema isolation (TENANT_ISOLATION=shared): the tables of every garderie live
+-- in one schema, each row tagged with the garderie's id, and row-level security only
+-- lets a connection see and write the rows of the garderie named by its
+-- `app.tenant_id` setting. The API calls these functions on the shared schema around
+-- each run of the tenant migrations; per-garderie schemas never use them.
+
+-- Garderie of the current connection; NULL when unset, which no row matches.
+CREATE OR REPLACE FUNCTION public.current_tenant_id() RETURNS UUID
+LANGUAGE sql STABLE AS $$
+    SELECT NULLIF(current_setting('app.tenant_id', TRUE), '')::UUID
+$$;
+
+-- Copy one template row into a garderie, under a new id when the table has one.
+CREATE OR REPLACE FUNCTION public.insert_tenant_template(target TEXT, tbl TEXT, template JSONB, tenant UUID)
+RETURNS VOID LANGUAGE plpgsql AS $$
+DECLARE
+    fresh JSONB := template || jsonb_build_object('tenant_id', tenant);
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM pg_attribute
+        WHERE attrelid = format('%I.%I', target, tbl)::regclass AND attname = 'id' AND atttypid = 'uuid'::regtype
+    ) THEN
+        fresh := fresh || jsonb_build_object('id', public.uuid_generate_v4());
+    END IF;
+    EXECUTE format('INSERT INTO %I.%I SELECT * FROM jsonb_populate_record(NULL::%I.%I, $1)', target, tbl, target, tbl)
+        USING fresh;
+END;
+$$;
+
+-- Let the table owner (the API's role) bypass the policies while tenant migrations
+-- run, so that their backfills reach every garderie's rows and the rows they seed can
+-- be collected as templates. enable_tenant_rls forces them again in the same transaction.
+CREATE OR REPLACE FUNCTION public.suspend_tenant_rls(target TEXT) RETURNS VOID
+LANGUAGE plpgsql AS $$
+DECLARE
+    tbl RECORD;
+BEGIN
+    FOR tbl IN
+        SELECT c.relname FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = target AND c.relkind = 'r' AND c.relforcerowsecurity
+    LOOP
+        EXECUTE format('ALTER TABLE %I.%I NO FORCE ROW LEVEL SECURITY', target, tbl.relname);
+    END LOOP;
+END;
+$$;
+
+-- Bring every table of the shared schema under tenant isolation; idempotent, so new
+-- tables of later tenant migrations are picked up by the next call:
+--   * a `tenant_id` column defaulting to the connection's garderie, which the policy
+--     requires on every row written by the API;
+--   * natural keys (email, date, invoice number…) made unique per garderie, keeping
+--     their names so that `ON CONFLICT ON CONSTRAINT` works in both modes; keys with a
+--     uuid column already are, ids being unique across garderies;
+--   * the `tenant_isolation` policy, forced on the owner too.
+-- Rows a migration inserted without a garderie (seed data such as default ratio
+-- rules) become templates: moved to `tenant_templates`, copied into the garderies
+-- already seeded, and into each new one by seed_tenant.
+CREATE OR REPLACE FUNCTION public.enable_tenant_rls(target TEXT) RETURNS VOID
+LANGUAGE plpgsql AS $$
+DECLARE
+    tbl         RECORD;
+    idx         RECORD;
+    template    RECORD;
+    seeded      RECORD;
+    known       BIGINT;
+    previous    TEXT := current_setting('app.tenant_id', TRUE);
+BEGIN
+    PERFORM public.suspend_tenant_rls(target);
+    PERFORM set_config('app.tenant_id', '', TRUE);
+    EXECUTE format(
+        'CREATE TABLE IF NOT EXISTS %I.tenant_templates (
+            id         BIGSERIAL PRIMARY KEY,
+            table_name TEXT NOT NULL,
+            template   JSONB NOT NULL
+        )', target);
+    EXECUTE format(
+        'CREATE TABLE IF NOT EXISTS %I.tenant_seeds (
+            tenant_id UUID PRIMARY KEY DEFAULT public.current_tenant_id() REFERENCES public.garderies(id) ON DELETE CASCADE,
+            seeded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        )', target);
+    EXECUTE format('SELECT COALESCE(MAX(id), 0) FROM %I.tenant_templates', target) INTO known;
+
+    FOR tbl IN
+        SELECT c.relname FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = target AND c.relkind = 'r' AND c.relname NOT IN ('tenant_migrations', 'tenant_templates')
+    LOOP
+        -- Evaluated once for the rows already there: NULL, outside of any garderie
+        EXECUTE format(
+            'ALTER TABLE %I.%I ADD COLUMN IF NOT EXISTS tenant_id UUID
+               DEFAULT public.current_tenant_id() REFERENCES public.garderies(id) ON DELETE CASCADE',
+            target, tbl.relname);
+        EXECUTE format('CREATE INDEX IF NOT EXISTS %I ON %I.%I (tenant_id)', tbl.relname || '_tenant_idx', target, tbl.relname);
+
+        EXECUTE format(
+            'INSERT INTO %I.tenant_templates (table_name, template)
+             SELECT %L, to_jsonb(t) - ''tenant_id'' FROM %I.%I t WHERE tenant_id IS NULL',
+            target, tbl.relname, target, tbl.relname);
+        EXECUTE format('DELETE FROM %I.%I WHERE tenant_id IS NULL', target, tbl.relname);
+
+        FOR idx IN
+            SELECT i.relname AS name, x.indisprimary AS is_primary, pg_get_indexdef(x.indexrelid) AS def,
+                   EXISTS (SELECT 1 FROM pg_constraint k WHERE k.conindid = x.indexrelid) AS is_constraint
+            FROM pg_index x JOIN pg_class i ON i.oid = x.indexrelid
+            WHERE x.indrelid = format('%I.%I', target, tbl.relname)::regclass
+              AND x.indisunique
+              AND NOT EXISTS (
+                  SELECT 1 FROM pg_attribute a
+                  WHERE a.attrelid = x.indrelid AND a.attnum = ANY (x.indkey::INT2[])
+                    AND (a.attname = 'tenant_id' OR a.atttypid = 'uuid'::regtype)
+              )
+        LOOP
+            IF idx.is_constraint THEN
+                EXECUTE format('ALTER TABLE %I.%I DROP CONSTRAINT %I', target, tbl.relname, idx.name);
+            ELSE
+                EXECUTE format('DROP INDEX %I.%I', target, idx.name);
+            END IF;
+            EXECUTE regexp_replace(idx.def, ' USING (\w+) \(', ' USING \1 (tenant_id, ');
+            IF idx.is_constraint THEN
+                EXECUTE format('ALTER TABLE %I.%I ADD CONSTRAINT %I %s USING INDEX %I', target, tbl.relname, idx.name,
+                               CASE WHEN idx.is_primary THEN 'PRIMARY KEY' ELSE 'UNIQUE' END, idx.name);
+            END IF;
+        END LOOP;
+
+        EXECUTE format('ALTER TABLE %I.%I ENABLE ROW LEVEL SECURITY', target, tbl.relname);
+        IF NOT EXISTS (
+            SELECT 1 FROM pg_policies WHERE schemaname = target AND tablename = tbl.relname AND policyname = 'tenant_isolation'
+        ) THEN
+            EXECUTE format(
+                'CREATE POLICY tenant_isolation ON %I.%I
+                   USING (tenant_id = public.current_tenant_id())
+                   WITH CHECK (tenant_id = public.current_tenant_id())',
+                target, tbl.relname);
+        END IF;
+    END LOOP;
+
+    -- Templates of this run, for the garderies seeded by an earlier one
+    FOR seeded IN EXECUTE format('SELECT tenant_id FROM %I.tenant_seeds', target) LOOP
+        FOR template IN EXECUTE format('SELECT table_name, template FROM %I.tenant_templates WHERE id > $1 ORDER BY id', target)
+            USING known
+        LOOP
+            PERFORM public.insert_tenant_template(target, template.table_name, template.template, seeded.tenant_id);
+        END LOOP;
+    END LOOP;
+
+    FOR tbl IN
+        SELECT c.relname FROM pg_class c JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE n.nspname = target AND c.relkind = 'r' AND c.relrowsecurity
+    LOOP
+        EXECUTE format('ALTER TABLE %I.%I FORCE ROW LEVEL SECURITY', target, tbl.relname);
+    END LOOP;
+    PERFORM set_config('app.tenant_id', COALESCE(previous, ''), TRUE);
+END;
+$$;
+
+-- Give a new garderie its copy of the templates, once, and leave `app.tenant_id` set
+-- to it until the end of the caller's transaction so that it can go on filling the
+-- garderie (its first admin, cloned data…). Returns FALSE if it was already seeded.
+CREATE OR REPLACE FUNCTION public.seed_tenant(target TEXT, garderie_slug TEXT) RETURNS BOOLEAN
+LANGUAGE plpgsql AS $$
+DECLARE
+    tenant   UUID;
+    template RECORD;
+    added    INT;
+BEGIN
+    SELECT id INTO STRICT tenant FROM public.garderies WHERE slug = garderie_slug;
+    PERFORM set_config('app.tenant_id', tenant::TEXT, TRUE);
+
+    EXECUTE format('INSERT INTO %I.tenant_seeds DEFAULT VALUES ON CONFLICT DO NOTHING', target);
+    GET DIAGNOSTICS added = ROW_COUNT;
+    IF added = 0 THEN
+        RETURN FALSE;
+    END IF;
+
+    FOR template IN EXECUTE format('SELECT table_name, template FROM %I.tenant_templates ORDER BY id', target) LOOP
+        PERFORM public.insert_tenant_template(target, template.table_name, template.template, tenant);
+    END LOOP;
+    RETURN TRUE;
+END;
+$$;
@@ -4,17 +4,17 @@
 //! 1. Finds all media and documents with is_encrypted = false
 //! 2. For each file:
 //!    - Reads the plaintext file from disk
-//!    - Encrypts it with the tenant-specific key
+//!    - Encrypts it with the tenant-specific key of the current master key
 //!    - Writes the encrypted version back to the same path
-//!    - Updates the database with encryption metadata
+//!    - Updates the database with encryption metadata and the key version
 //! 3. Supports batching by tenant for large datasets
 //! 
 //! Usage:
-//!   cargo run --bin encrypt-existing-files [--tenant TENANT_SLUG]
+//!   cargo run --bin encrypt-existing-files [TENANT_SLUG]
 //! 
-//! Environment variables:
+//! Environment variables: the API's (see `Config::from_env`), in particular
 //!   DATABASE_URL - PostgreSQL connection string
-//!   ENCRYPTION_MASTER_KEY - 64-character hex encryption key
+//!   ENCRYPTION_MASTER_KEY, ENCRYPTION_KEY_VERSION - current master key and its version
 //!   MEDIA_DIR - Base directory for media files
 
 use anyhow::{Context, Result};
@@ -24,7 +24,10 @@ use std::path::PathBuf;
 use uuid::Uuid;
 
 // Import from the main crate
-use minispace_api::services::encryption;
+use minispace_api::config::Config;
+use minispace_api::db::tenant::schema_name;
+use minispace_api::db::tenant_db::TenantDb;
+use minispace_api::services::encryption::{self, KeyRing};
 
 #[derive(Debug)]
 struct UnencryptedMedia {
@@ -44,42 +47,28 @@ async fn main() -> Result<()> {
     // Load environment variables
     dotenvy::dotenv().ok();
     
-    let database_url = env::var("DATABASE_URL")
-        .context("DATABASE_URL environment variable required")?;
-    let encryption_master_key = env::var("ENCRYPTION_MASTER_KEY")
-        .context("ENCRYPTION_MASTER_KEY environment variable required")?;
-    let media_dir = env::var("MEDIA_DIR")
-        .context("MEDIA_DIR environment variable required")?;
+    let config = Config::from_env()?;
+    TenantDb::init(config.tenant_isolation)?;
+    // Same keys as the API, so files are tagged with the version they are written under
+    let keys = KeyRing::from_config(&config)?;
+    let media_dir = config.media_dir.as_str();
     
     // Optional: filter by specific tenant
     let target_tenant = env::args().nth(1);
     
-    // Decode and validate master key
-    let master_key_bytes = hex::decode(&encryption_master_key)
-        .context("Invalid ENCRYPTION_MASTER_KEY format (must be 64-character hex)")?;
-    if master_key_bytes.len() != 32 {
-        anyhow::bail!("ENCRYPTION_MASTER_KEY must be exactly 32 bytes (64 hex characters)");
-    }
-    let mut master_key = [0u8; 32];
-    master_key.copy_from_slice(&master_key_bytes);
-    
     // Connect to database
     println!("Connecting to database...");
-    let pool = PgPoolOptions::new()
+    let pool = TenantDb::pool_options(PgPoolOptions::new())
         .max_connections(5)
-        .connect(&database_url)
+        .connect(&config.database_url)
         .await
         .context("Failed to connect to database")?;
     
     // Get list of tenants
-    let tenants: Vec<(String,)> = sqlx::query_as(
-        "SELECT nspname FROM pg_namespace 
-         WHERE nspname NOT IN ('pg_catalog', 'information_schema', 'public')
-         AND nspname NOT LIKE 'pg_%'"
-    )
-    .fetch_all(&pool)
-    .await
-    .context("Failed to fetch tenant list")?;
+    let tenants: Vec<(String,)> = sqlx::query_as("SELECT slug FROM public.garderies ORDER BY slug")
+        .fetch_all(&pool)
+        .await
+        .context("Failed to fetch tenant list")?;
     
     let mut total_media_encrypted = 0;
     let mut total_documents_encrypted = 0;
@@ -94,16 +83,18 @@ async fn main() -> Result<()> {
         
         println!("\n=== Processing tenant: {} ===", tenant);
         
-        // Derive tenant-specific key
-        let tenant_key = encryption::derive_tenant_key(&master_key, &tenant)
+        // Tenant-specific key, derived from the slug like the API does
+        let tenant_key = keys.current_tenant_key(&tenant)
             .context(format!("Failed to derive key for tenant {}", tenant))?;
         
         // Process media files
-        let media_count = encrypt_media_files(&pool, &tenant, &media_dir, &tenant_key).await?;
+        let media_count =
+            TenantDb::scope(tenant.clone(), encrypt_media_files(&pool, &tenant, media_dir, tenant_key)).await?;
         total_media_encrypted += media_count;
         
         // Process document files
-        let docs_count = encrypt_document_files(&pool, &tenant, &media_dir, &tenant_key).await?;
+        let docs_count =
+            TenantDb::scope(tenant.clone(), encrypt_document_files(&pool, &tenant, media_dir, tenant_key)).await?;
         total_documents_encrypted += docs_count;
     }
     
@@ -119,9 +110,10 @@ async fn encrypt_media_files(
     pool: &sqlx::PgPool,
     tenant: &str,
     media_dir: &str,
-    tenant_key: &[u8; 32],
+    (key_version, tenant_key): (i32, [u8; 32]),
 ) -> Result<usize> {
-    let schema = format!("\"{}\"", tenant);
+    let tenant_key = &tenant_key;
+    let schema = format!("\"{}\"", schema_name(tenant));
     
     // Find all unencrypted media
     let unencrypted: Vec<UnencryptedMedia> = sqlx::query_as::<_, (Uuid, String, Option<String>)>(
@@ -205,14 +197,16 @@ async fn encrypt_media_files(
                  encryption_iv = $1, 
                  encryption_tag = $2,
                  thumbnail_encryption_iv = $3,
-                 thumbnail_encryption_tag = $4
-             WHERE id = $5",
+                 thumbnail_encryption_tag = $4,
+                 key_version = $5
+             WHERE id = $6",
             schema
         ))
         .bind(&iv)
         .bind(&tag)
         .bind(&thumb_iv)
         .bind(&thumb_tag)
+        .bind(key_version)
         .bind(media.id)
         .execute(pool)
         .await
@@ -233,9 +227,10 @@ async fn encrypt_document_files(
     pool: &sqlx::PgPool,
     tenant: &str,
     media_dir: &str,
-    tenant_key: &[u8; 32],
+    (key_version, tenant_key): (i32, [u8; 32]),
 ) -> Result<usize> {
-    let schema = format!("\"{}\"", tenant);
+    let tenant_key = &tenant_key;
+    let schema = format!("\"{}\"", schema_name(tenant));
     
     // Find all unencrypted documents
     let unencrypted: Vec<UnencryptedDocument> = sqlx::query_as::<_, (Uuid, String)>(
@@ -287,12 +282,14 @@ async fn encrypt_document_files(
             "UPDATE {}.documents 
              SET is_encrypted = true, 
                  encryption_iv = $1, 
-                 encryption_tag = $2
-             WHERE id = $3",
+                 encryption_tag = $2,
+                 key_version = $3
+             WHERE id = $4",
             schema
         ))
         .bind(&iv)
         .bind(&tag)
+        .bind(key_version)
         .bind(doc.id)
         .execute(pool)
         .await
@@ -0,0 +1,80 @@
+/// Apply pending tenant-schema migrations outside of the API startup, e.g. to migrate
+/// one garderie ahead of a deploy or retry one that failed.
+///
+/// The API applies the same migrations to every active garderie on startup; this tool
+/// also accepts archived tenants when named with --tenant.
+///
+/// Usage: migrate-tenant [--tenant SLUG] [--status]
+///
+/// Environment variables:
+///   DATABASE_URL
+///   TENANT_ISOLATION (`shared`: every garderie is migrated with the shared schema)
+
+use clap::Parser;
+use sqlx::postgres::PgPoolOptions;
+
+use minispace_api::db::tenant_db::{TenantDb, TenantIsolation};
+use minispace_api::db::tenant_migrations;
+
+#[derive(Parser)]
+#[command(name = "migrate-tenant", about = "Apply pending minispace tenant-schema migrations")]
+struct Args {
+    /// Tenant slug to migrate (optional, all active tenants if not specified)
+    #[arg(long)]
+    tenant: Option<String>,
+
+    /// Only list the migrations of each tenant and whether they are applied
+    #[arg(long)]
+    status: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .init();
+
+    let args = Args::parse();
+
+    let database_url = std::env::var("DATABASE_URL")
+        .expect("DATABASE_URL environment variable not set");
+    TenantDb::init(TenantIsolation::from_env()?)?;
+
+    let pool = TenantDb::pool_options(PgPoolOptions::new())
+        .max_connections(2)
+        .connect(&database_url)
+        .await?;
+
+    let tenants: Vec<String> = match args.tenant {
+        Some(tenant) => vec![tenant],
+        None => sqlx::query_scalar("SELECT slug FROM public.garderies WHERE is_active = TRUE ORDER BY slug")
+            .fetch_all(&pool)
+            .await?,
+    };
+
+    let mut pending = 0;
+    for tenant in tenants {
+        if args.status {
+            for m in tenant_migrations::status(&pool, &tenant).await? {
+                match m.applied_at {
+                    Some(at) => tracing::info!("{tenant}: {:>4} {:<32} applied {at}", m.version, m.name),
+                    None => {
+                        tracing::warn!("{tenant}: {:>4} {:<32} pending", m.version, m.name);
+                        pending += 1;
+                    }
+                }
+            }
+        } else {
+            let mut conn = pool.acquire().await?;
+            let applied = tenant_migrations::migrate_tenant(&mut conn, &tenant).await?;
+            tracing::info!("{tenant}: {} migration(s) applied", applied.len());
+        }
+    }
+
+    if pending > 0 {
+        tracing::warn!("Pending tenant migrations: {pending}");
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
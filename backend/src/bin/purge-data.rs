@@ -8,6 +8,9 @@ use clap::Parser;
 use sqlx::postgres::PgPoolOptions;
 use tracing_subscriber;
 
+use minispace_api::db::tenant_db::{TenantDb, TenantIsolation};
+use minispace_api::services::cron::CronService;
+
 #[derive(Parser)]
 #[command(name = "purge-data", about = "Purge expired data from minispace database")]
 struct Args {
@@ -29,8 +32,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let database_url = std::env::var("DATABASE_URL")
         .expect("DATABASE_URL environment variable not set");
 
+    TenantDb::init(TenantIsolation::from_env()?)?;
+
     // Create database connection pool
-    let pool = PgPoolOptions::new()
+    let pool = TenantDb::pool_options(PgPoolOptions::new())
         .max_connections(5)
         .connect(&database_url)
         .await?;
@@ -39,7 +44,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     if let Some(tenant) = args.tenant {
         // Purge specific tenant
-        minispace_api::services::cron::CronService::purge_expired_data(&pool, &tenant).await?;
+        TenantDb::scope(tenant.clone(), CronService::purge_expired_data(&pool, &tenant)).await?;
         tracing::info!("Completed purge for tenant: {}", tenant);
     } else {
         // Purge all active garderies
@@ -52,9 +57,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         tracing::info!("Purging {} active garderies", garderies.len());
 
         for slug in garderies {
-            if let Err(e) =
-                minispace_api::services::cron::CronService::purge_expired_data(&pool, &slug).await
-            {
+            if let Err(e) = TenantDb::scope(slug.clone(), CronService::purge_expired_data(&pool, &slug)).await {
                 tracing::error!("Error purging tenant {}: {}", slug, e);
             }
         }
@@ -0,0 +1,112 @@
+/// Re-encrypt tenant files under the current master key after a key rotation.
+///
+/// Rotation procedure:
+///   1. Generate a new key (openssl rand -hex 32)
+///   2. Move the old key to ENCRYPTION_PREVIOUS_KEYS (e.g. "1:<old hex>"), set
+///      ENCRYPTION_MASTER_KEY to the new key and bump ENCRYPTION_KEY_VERSION, restart the API
+///   3. Run this tool (or let the API's background job catch up) until --check reports 0
+///   4. Remove the old key from ENCRYPTION_PREVIOUS_KEYS
+///
+/// The API keeps serving files throughout: each file records the key version it was
+/// written under and is decrypted with that key.
+///
+/// Usage: rotate-keys [--tenant SLUG] [--batch-size N] [--check]
+///
+/// Environment variables:
+///   DATABASE_URL, MEDIA_DIR, ENCRYPTION_MASTER_KEY, ENCRYPTION_KEY_VERSION,
+///   ENCRYPTION_PREVIOUS_KEYS, TENANT_ISOLATION
+
+use clap::Parser;
+use sqlx::postgres::PgPoolOptions;
+
+use minispace_api::db::tenant_db::{TenantDb, TenantIsolation};
+use minispace_api::services::{
+    encryption::KeyRing,
+    key_rotation::{KeyRotationService, RotationCursor, DEFAULT_BATCH_SIZE},
+};
+
+#[derive(Parser)]
+#[command(name = "rotate-keys", about = "Re-encrypt minispace files under the current master key")]
+struct Args {
+    /// Tenant slug to rotate (optional, all if not specified)
+    #[arg(long)]
+    tenant: Option<String>,
+
+    /// Rows re-encrypted per table and per query
+    #[arg(long, default_value_t = DEFAULT_BATCH_SIZE)]
+    batch_size: i64,
+
+    /// Only report how many files are still under an old key
+    #[arg(long)]
+    check: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::INFO)
+        .init();
+
+    let args = Args::parse();
+
+    let database_url = std::env::var("DATABASE_URL")
+        .expect("DATABASE_URL environment variable not set");
+    let media_dir = std::env::var("MEDIA_DIR").unwrap_or_else(|_| "/data/media".into());
+    let keys = KeyRing::new(
+        &std::env::var("ENCRYPTION_MASTER_KEY").expect("ENCRYPTION_MASTER_KEY environment variable not set"),
+        std::env::var("ENCRYPTION_KEY_VERSION").unwrap_or_else(|_| "1".into()).parse()?,
+        std::env::var("ENCRYPTION_PREVIOUS_KEYS").ok().as_deref(),
+    )?;
+
+    TenantDb::init(TenantIsolation::from_env()?)?;
+
+    let pool = TenantDb::pool_options(PgPoolOptions::new())
+        .max_connections(5)
+        .connect(&database_url)
+        .await?;
+
+    let tenants: Vec<String> = match args.tenant {
+        Some(tenant) => vec![tenant],
+        None => sqlx::query_scalar("SELECT slug FROM public.garderies WHERE archived_at IS NULL ORDER BY slug")
+            .fetch_all(&pool)
+            .await?,
+    };
+
+    let mut remaining = 0;
+    for tenant in tenants {
+        if !args.check {
+            let mut cursor = RotationCursor::default();
+            while !cursor.is_done() {
+                let rotation =
+                    KeyRotationService::rotate_tenant(&pool, &media_dir, &keys, &tenant, args.batch_size, &mut cursor);
+                let stats = TenantDb::scope(tenant.clone(), rotation).await?;
+                tracing::info!(
+                    "{tenant}: re-encrypted {} media, {} documents, {} avatars ({} failed)",
+                    stats.media,
+                    stats.documents,
+                    stats.avatars,
+                    stats.failed
+                );
+            }
+        }
+
+        let pending =
+            TenantDb::scope(tenant.clone(), KeyRotationService::pending(&pool, &tenant, keys.current_version())).await?;
+        if pending.total() > 0 {
+            tracing::warn!(
+                "{tenant}: {} media, {} documents, {} avatars still under an old key",
+                pending.media,
+                pending.documents,
+                pending.avatars
+            );
+        }
+        remaining += pending.total();
+    }
+
+    tracing::info!("Files still under an old key: {remaining} (current key v{})", keys.current_version());
+    if remaining > 0 {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
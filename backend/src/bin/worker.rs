@@ -0,0 +1,88 @@
+//! Run the background jobs (schedulers, notification consumer, transcoding, scans,
+//! backups) outside the API process, with the same services and configuration.
+//!
+//! Start the API with RUN_BACKGROUND_JOBS=false so that jobs run here only; the API
+//! still applies the migrations. On SIGTERM or Ctrl-C, no new job starts and running
+//! ones get WORKER_SHUTDOWN_GRACE_SECS to finish.
+//!
+//! Usage: worker
+//!
+//! Environment variables: those of the API; WORKER_CONCURRENCY caps the jobs
+//! running at once and DB_JOBS_MAX_CONNECTIONS sizes the database pool.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::{info, warn};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+use minispace_api::{
+    config::Config,
+    db::{self, PoolClass},
+    services::{email::EmailService, notifications::NotificationService, sms::SmsService, worker},
+};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let _ = dotenvy::dotenv();
+
+    let config = Arc::new(Config::from_env()?);
+
+    let (json_logs, text_logs) = if config.log_json {
+        (Some(tracing_subscriber::fmt::layer().json().with_current_span(false)), None)
+    } else {
+        (None, Some(tracing_subscriber::fmt::layer()))
+    };
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::from_default_env())
+        .with(json_logs)
+        .with(text_logs)
+        .init();
+
+    let _sentry = config.sentry_dsn.as_deref().map(|dsn| {
+        sentry::init((
+            dsn,
+            sentry::ClientOptions {
+                release: sentry::release_name!(),
+                environment: Some(config.sentry_environment.clone().into()),
+                ..Default::default()
+            },
+        ))
+    });
+
+    db::tenant_db::TenantDb::init(config.tenant_isolation)?;
+    let pool = db::create_pool(&config, PoolClass::Jobs).await?;
+    db::tenant_db::TenantDb::check_role(&pool).await?;
+    let redis_client = redis::Client::open(config.redis_url.as_str())?;
+    // Fail early rather than inside every job
+    redis_client.get_multiplexed_async_connection().await?;
+
+    let email = EmailService::new(&config).map(|svc| Arc::new(svc.with_db(pool.clone())));
+    let sms = SmsService::new(&config).map(Arc::new);
+    let notifications = Arc::new(NotificationService::new(config.fcm_api_key.clone()));
+    info!(
+        "Worker starting: {} jobs at once, email {}, SMS {}",
+        config.worker_concurrency,
+        if email.is_some() { "on" } else { "off" },
+        if sms.is_some() { "on" } else { "off" },
+    );
+
+    worker::start_all(worker::Context {
+        pool: pool.clone(),
+        redis_client,
+        config: config.clone(),
+        email,
+        sms,
+        notifications,
+    });
+
+    worker::shutdown_signal().await;
+    info!("Worker stopping, waiting up to {}s for running jobs", config.worker_shutdown_grace_secs);
+    if worker::shutdown(Duration::from_secs(config.worker_shutdown_grace_secs)).await {
+        info!("Worker stopped");
+    } else {
+        warn!("Worker stopped with jobs still running");
+    }
+    pool.close().await;
+    Ok(())
+}
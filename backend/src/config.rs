@@ -1,14 +1,57 @@
 use std::env;
 
+use crate::db::tenant_db::TenantIsolation;
+
 #[derive(Debug, Clone)]
 pub struct Config {
     pub database_url: String,
+    /// Connections of the pool serving regular API requests.
+    pub db_max_connections: u32,
+    /// statement_timeout of API requests, in seconds (`0` disables it).
+    pub db_statement_timeout_secs: u64,
+    /// Connections of the pool serving media, exports and manual backups, so that
+    /// slow requests cannot take every connection.
+    pub db_bulk_max_connections: u32,
+    pub db_bulk_statement_timeout_secs: u64,
+    /// Connections of the pool used by migrations and background jobs.
+    pub db_jobs_max_connections: u32,
+    pub db_jobs_statement_timeout_secs: u64,
+    /// Statements slower than this are logged as warnings, with the request span.
+    pub db_slow_query_ms: u64,
+    /// A schema per garderie (`schema`, default) or one schema shared under row-level
+    /// security (`shared`), from TENANT_ISOLATION; see [`crate::db::tenant_db`].
+    pub tenant_isolation: TenantIsolation,
     pub redis_url: String,
+    /// HS256 secret of the access tokens, when `jwt_algorithm` is HS256.
     pub jwt_secret: String,
+    /// `kid` of the current access-token key.
+    pub jwt_key_id: String,
+    /// HS256, or RS256/EdDSA to let other services verify tokens with the public key.
+    pub jwt_algorithm: String,
+    pub jwt_private_key_path: Option<String>,
+    pub jwt_public_key_path: Option<String>,
+    /// Key taking over at `jwt_rotate_at`, as `kid:HS256:secret` or
+    /// `kid:ALG:private.pem:public.pem`; verified (and published) before then.
+    pub jwt_next_key: Option<String>,
+    /// RFC 3339 date from which `jwt_next_key` signs new tokens.
+    pub jwt_rotate_at: Option<String>,
+    /// Retired keys still accepted, as `kid:HS256:secret` or `kid:ALG:public.pem`,
+    /// comma-separated.
+    pub jwt_previous_keys: Option<String>,
     pub jwt_refresh_secret: String,
     pub jwt_expiry_seconds: u64,
     pub jwt_refresh_expiry_days: u64,
     pub media_dir: String,
+    /// Photos wider or taller than this many pixels are scaled down on upload; `0` keeps
+    /// their original size.
+    pub media_max_dimension: u32,
+    /// Encrypted files are decrypted in memory to be served; larger ones are refused.
+    pub media_max_decrypt_bytes: u64,
+    /// Refuse `/media/files` requests without a valid `exp`/`sig` pair; when off, they
+    /// are only logged, while clients move to the signed URLs of list responses.
+    pub media_require_signed_urls: bool,
+    /// Minimum validity of signed media URLs, in seconds.
+    pub media_url_ttl_secs: i64,
     pub host: String,
     pub port: u16,
     pub fcm_api_key: Option<String>,
@@ -24,15 +67,96 @@ pub struct Config {
     pub smtp_username: Option<String>,
     pub smtp_password: Option<String>,
     pub smtp_from: Option<String>,
+    /// Shared secret of the bounce/complaint webhook URL; the webhook is off when unset.
+    pub email_webhook_token: Option<String>,
+    // Twilio SMS (optional)
+    pub twilio_account_sid: Option<String>,
+    pub twilio_auth_token: Option<String>,
+    pub twilio_from_number: Option<String>,
+    /// Cloudflare Turnstile secret; public signup requires a CAPTCHA token when set.
+    pub turnstile_secret_key: Option<String>,
     pub encryption_master_key: String,
+    /// Version of `encryption_master_key`, recorded on every file it encrypts.
+    pub encryption_key_version: i32,
+    /// Retired master keys still needed to read files, as `version:hex,version:hex`.
+    pub encryption_previous_keys: Option<String>,
+    /// How long after sending a message its author may still edit or delete it.
+    pub message_edit_window_minutes: i64,
+    /// Replace the content of messages written by an erased user with the deletion tombstone.
+    pub erasure_scrub_messages: bool,
+    /// Minimum number of characters for a new password.
+    pub password_min_length: usize,
+    /// Reject passwords found in the Have I Been Pwned corpus (k-anonymity range API).
+    pub password_breach_check: bool,
+    /// Cancel window before a garderie deletion or restore runs without a second confirmation.
+    pub destructive_op_delay_minutes: i64,
+    /// How long an archived garderie can be restored before it is purged.
+    pub archive_retention_days: i64,
+    /// Days a garderie keeps full access after its trial expires, before going read-only.
+    pub trial_grace_days: i64,
+    // S3-compatible backup storage (optional)
+    pub s3_endpoint: Option<String>,
+    pub s3_region: String,
+    pub s3_bucket: Option<String>,
+    pub s3_access_key: Option<String>,
+    pub s3_secret_key: Option<String>,
+    /// Local time (`HH:MM`) of the nightly per-garderie backup; `off` disables it.
+    pub backup_schedule: String,
+    /// clamd address (`host:port`) used to scan uploads; scanning is off when unset.
+    pub clamav_addr: Option<String>,
+    /// Face detection service used to blur children without photo consent; when
+    /// unset, only regions drawn by staff are blurred.
+    pub face_detection_url: Option<String>,
+    /// Sentry DSN receiving panics and 5xx responses; reporting is off when unset.
+    pub sentry_dsn: Option<String>,
+    /// Environment name attached to Sentry events.
+    pub sentry_environment: String,
+    /// Log lines as JSON objects (LOG_FORMAT=json) for log aggregation, plain text otherwise.
+    pub log_json: bool,
+    /// Run the schedulers, notification consumer and other background jobs inside the
+    /// API process; turn off when the `worker` binary runs them.
+    pub run_background_jobs: bool,
+    /// Background jobs running at once (one scheduler tick or stream event each).
+    pub worker_concurrency: usize,
+    /// Seconds the worker waits for running jobs to finish when asked to stop.
+    pub worker_shutdown_grace_secs: u64,
 }
 
 impl Config {
     pub fn from_env() -> anyhow::Result<Self> {
         Ok(Self {
             database_url: required("DATABASE_URL")?,
+            db_max_connections: env::var("DB_MAX_CONNECTIONS")
+                .unwrap_or_else(|_| "20".into())
+                .parse()?,
+            db_statement_timeout_secs: env::var("DB_STATEMENT_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "15".into())
+                .parse()?,
+            db_bulk_max_connections: env::var("DB_BULK_MAX_CONNECTIONS")
+                .unwrap_or_else(|_| "5".into())
+                .parse()?,
+            db_bulk_statement_timeout_secs: env::var("DB_BULK_STATEMENT_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "120".into())
+                .parse()?,
+            db_jobs_max_connections: env::var("DB_JOBS_MAX_CONNECTIONS")
+                .unwrap_or_else(|_| "5".into())
+                .parse()?,
+            db_jobs_statement_timeout_secs: env::var("DB_JOBS_STATEMENT_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "0".into())
+                .parse()?,
+            db_slow_query_ms: env::var("DB_SLOW_QUERY_MS")
+                .unwrap_or_else(|_| "500".into())
+                .parse()?,
+            tenant_isolation: TenantIsolation::from_env()?,
             redis_url: env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".into()),
             jwt_secret: required("JWT_SECRET")?,
+            jwt_key_id: env::var("JWT_KEY_ID").unwrap_or_else(|_| "1".into()),
+            jwt_algorithm: env::var("JWT_ALGORITHM").unwrap_or_else(|_| "HS256".into()),
+            jwt_private_key_path: env::var("JWT_PRIVATE_KEY_PATH").ok().filter(|s| !s.is_empty()),
+            jwt_public_key_path: env::var("JWT_PUBLIC_KEY_PATH").ok().filter(|s| !s.is_empty()),
+            jwt_next_key: env::var("JWT_NEXT_KEY").ok().filter(|s| !s.is_empty()),
+            jwt_rotate_at: env::var("JWT_ROTATE_AT").ok().filter(|s| !s.is_empty()),
+            jwt_previous_keys: env::var("JWT_PREVIOUS_KEYS").ok().filter(|s| !s.is_empty()),
             jwt_refresh_secret: required("JWT_REFRESH_SECRET")?,
             jwt_expiry_seconds: env::var("JWT_EXPIRY_SECONDS")
                 .unwrap_or_else(|_| "900".into())
@@ -41,6 +165,20 @@ impl Config {
                 .unwrap_or_else(|_| "30".into())
                 .parse()?,
             media_dir: env::var("MEDIA_DIR").unwrap_or_else(|_| "/data/media".into()),
+            media_max_dimension: env::var("MEDIA_MAX_DIMENSION")
+                .unwrap_or_else(|_| "0".into())
+                .parse()?,
+            media_max_decrypt_bytes: env::var("MEDIA_MAX_DECRYPT_MB")
+                .unwrap_or_else(|_| "256".into())
+                .parse::<u64>()?
+                * 1024
+                * 1024,
+            media_require_signed_urls: env::var("MEDIA_REQUIRE_SIGNED_URLS")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            media_url_ttl_secs: env::var("MEDIA_URL_TTL_SECS")
+                .unwrap_or_else(|_| "21600".into())
+                .parse()?,
             host: env::var("HOST").unwrap_or_else(|_| "0.0.0.0".into()),
             port: env::var("PORT")
                 .unwrap_or_else(|_| "8080".into())
@@ -60,7 +198,57 @@ impl Config {
             smtp_username: env::var("SMTP_USERNAME").ok().filter(|s| !s.is_empty()),
             smtp_password: env::var("SMTP_PASSWORD").ok().filter(|s| !s.is_empty()),
             smtp_from: env::var("SMTP_FROM").ok().filter(|s| !s.is_empty()),
+            email_webhook_token: env::var("EMAIL_WEBHOOK_TOKEN").ok().filter(|s| !s.is_empty()),
+            twilio_account_sid: env::var("TWILIO_ACCOUNT_SID").ok().filter(|s| !s.is_empty()),
+            twilio_auth_token: env::var("TWILIO_AUTH_TOKEN").ok().filter(|s| !s.is_empty()),
+            twilio_from_number: env::var("TWILIO_FROM_NUMBER").ok().filter(|s| !s.is_empty()),
+            turnstile_secret_key: env::var("TURNSTILE_SECRET_KEY").ok().filter(|s| !s.is_empty()),
             encryption_master_key: required("ENCRYPTION_MASTER_KEY")?,
+            encryption_key_version: env::var("ENCRYPTION_KEY_VERSION")
+                .unwrap_or_else(|_| "1".into())
+                .parse()?,
+            encryption_previous_keys: env::var("ENCRYPTION_PREVIOUS_KEYS").ok().filter(|s| !s.is_empty()),
+            message_edit_window_minutes: env::var("MESSAGE_EDIT_WINDOW_MINUTES")
+                .unwrap_or_else(|_| "15".into())
+                .parse()?,
+            erasure_scrub_messages: env::var("ERASURE_SCRUB_MESSAGES")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(true),
+            password_min_length: env::var("PASSWORD_MIN_LENGTH")
+                .unwrap_or_else(|_| "10".into())
+                .parse()?,
+            password_breach_check: env::var("PASSWORD_BREACH_CHECK")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            destructive_op_delay_minutes: env::var("DESTRUCTIVE_OP_DELAY_MINUTES")
+                .unwrap_or_else(|_| "1440".into())
+                .parse()?,
+            archive_retention_days: env::var("ARCHIVE_RETENTION_DAYS")
+                .unwrap_or_else(|_| "90".into())
+                .parse()?,
+            trial_grace_days: env::var("TRIAL_GRACE_DAYS")
+                .unwrap_or_else(|_| "7".into())
+                .parse()?,
+            s3_endpoint: env::var("S3_ENDPOINT").ok().filter(|s| !s.is_empty()),
+            s3_region: env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".into()),
+            s3_bucket: env::var("S3_BUCKET").ok().filter(|s| !s.is_empty()),
+            s3_access_key: env::var("S3_ACCESS_KEY").ok().filter(|s| !s.is_empty()),
+            s3_secret_key: env::var("S3_SECRET_KEY").ok().filter(|s| !s.is_empty()),
+            backup_schedule: env::var("BACKUP_SCHEDULE").unwrap_or_else(|_| "02:00".into()),
+            clamav_addr: env::var("CLAMAV_ADDR").ok().filter(|s| !s.is_empty()),
+            face_detection_url: env::var("FACE_DETECTION_URL").ok().filter(|s| !s.is_empty()),
+            sentry_dsn: env::var("SENTRY_DSN").ok().filter(|s| !s.is_empty()),
+            sentry_environment: env::var("SENTRY_ENVIRONMENT").unwrap_or_else(|_| "production".into()),
+            log_json: env::var("LOG_FORMAT").is_ok_and(|f| f.eq_ignore_ascii_case("json")),
+            run_background_jobs: env::var("RUN_BACKGROUND_JOBS")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(true),
+            worker_concurrency: env::var("WORKER_CONCURRENCY")
+                .unwrap_or_else(|_| "8".into())
+                .parse()?,
+            worker_shutdown_grace_secs: env::var("WORKER_SHUTDOWN_GRACE_SECS")
+                .unwrap_or_else(|_| "30".into())
+                .parse()?,
         })
     }
 }
@@ -1,12 +1,47 @@
 pub mod tenant;
+pub mod tenant_db;
+pub mod tenant_migrations;
+#[cfg(test)]
+pub mod test_support;
 
-use sqlx::postgres::PgPoolOptions;
-use sqlx::PgPool;
+use std::str::FromStr;
+use std::time::Duration;
 
-pub async fn create_pool(database_url: &str) -> anyhow::Result<PgPool> {
-    let pool = PgPoolOptions::new()
-        .max_connections(20)
-        .connect(database_url)
+use log::LevelFilter;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::{ConnectOptions, PgPool};
+
+use crate::config::Config;
+
+/// The pools the API splits its connections between.
+#[derive(Debug, Clone, Copy)]
+pub enum PoolClass {
+    /// Regular API requests: short statement timeout.
+    Api,
+    /// Media, storage usage, exports and manual backups.
+    Bulk,
+    /// Migrations and background jobs.
+    Jobs,
+}
+
+/// Open a pool of the given class. Every connection gets the class's
+/// statement_timeout, and statements slower than DB_SLOW_QUERY_MS are logged with
+/// their SQL (tenant schema included) inside the current request span. In shared
+/// mode, connections are handed out set to the garderie of the task's
+/// [`TenantDb::scope`](tenant_db::TenantDb::scope).
+pub async fn create_pool(config: &Config, class: PoolClass) -> anyhow::Result<PgPool> {
+    let (max_connections, timeout_secs) = match class {
+        PoolClass::Api => (config.db_max_connections, config.db_statement_timeout_secs),
+        PoolClass::Bulk => (config.db_bulk_max_connections, config.db_bulk_statement_timeout_secs),
+        PoolClass::Jobs => (config.db_jobs_max_connections, config.db_jobs_statement_timeout_secs),
+    };
+    let options = PgConnectOptions::from_str(&config.database_url)?
+        .options([("statement_timeout", format!("{}s", timeout_secs))])
+        .log_slow_statements(LevelFilter::Warn, Duration::from_millis(config.db_slow_query_ms));
+
+    let pool = tenant_db::TenantDb::pool_options(PgPoolOptions::new())
+        .max_connections(max_connections)
+        .connect_with(options)
         .await?;
     Ok(pool)
 }
@@ -16,18 +51,3 @@ pub async fn run_migrations(pool: &PgPool) -> anyhow::Result<()> {
     sqlx::migrate!("./migrations").run(pool).await?;
     Ok(())
 }
-
-/// Re-provision all active tenant schemas (idempotent — safe to call on every startup).
-pub async fn migrate_all_existing_tenants(pool: &PgPool) -> anyhow::Result<()> {
-    let slugs: Vec<String> = sqlx::query_scalar(
-        "SELECT slug FROM garderies WHERE is_active = TRUE"
-    )
-    .fetch_all(pool)
-    .await?;
-
-    for slug in slugs {
-        tenant::provision_tenant_schema(pool, &slug).await?;
-        tracing::info!("Migrated tenant schema: garderie_{}", slug.to_lowercase().replace('-', "_"));
-    }
-    Ok(())
-}
@@ -1,17 +1,51 @@
-use sqlx::PgPool;
+use sqlx::{Executor, PgConnection, PgPool};
 
-/// Provision a new per-tenant PostgreSQL schema with all required tables.
-/// Called when a new garderie is created.
-pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Result<()> {
-    let schema = schema_name(slug);
+use super::tenant_db::TenantDb;
+
+/// Full-text documents of the staff search (`GET /search`). Queries must use the
+/// same expressions for PostgreSQL to pick the indexes built from them. Names use
+/// the `simple` configuration (no stemming); free text is stemmed as French.
+pub const CHILD_SEARCH_VECTOR: &str = "to_tsvector('simple', first_name || ' ' || last_name)";
+pub const USER_SEARCH_VECTOR: &str = "to_tsvector('simple', first_name || ' ' || last_name || ' ' || email)";
+pub const DOCUMENT_SEARCH_VECTOR: &str = "to_tsvector('french', title)";
+pub const MESSAGE_SEARCH_VECTOR: &str = "to_tsvector('french', content)";
+
+/// Provision a new per-tenant PostgreSQL schema, or bring an existing one up to date,
+/// by applying its pending [`tenant_migrations`](super::tenant_migrations).
+/// Returns the versions applied. In shared mode, the garderie (already in
+/// `public.garderies`) also gets its copy of the rows the migrations seed.
+pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Result<Vec<i64>> {
+    let mut conn = pool.acquire().await?;
+    provision_tenant_schema_in(&mut conn, slug).await
+}
+
+/// Same as [`provision_tenant_schema`] on a given connection, so a caller can run it
+/// inside its own transaction: DDL is transactional in PostgreSQL. In shared mode the
+/// rest of that transaction acts on behalf of the new garderie.
+pub async fn provision_tenant_schema_in(conn: &mut PgConnection, slug: &str) -> anyhow::Result<Vec<i64>> {
+    let applied = super::tenant_migrations::migrate_tenant(conn, slug).await?;
+    if TenantDb::is_shared() {
+        sqlx::query("SELECT public.seed_tenant($1, $2)")
+            .bind(schema_name(slug))
+            .bind(slug)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(applied)
+}
+
+/// Tenant migration 1: every table as of the introduction of versioned migrations.
+/// Frozen: it is written idempotently because it ran on every startup before that,
+/// and schemas created back then record it the first time the runner sees them.
+/// New DDL goes in a new file of `tenant_migrations/`.
+pub(crate) async fn baseline(conn: &mut PgConnection, schema: &str) -> anyhow::Result<()> {
 
     // --- Create schema ---
-    sqlx::raw_sql(&format!("CREATE SCHEMA IF NOT EXISTS \"{schema}\""))
-        .execute(pool)
+    conn.execute(sqlx::raw_sql(&format!("CREATE SCHEMA IF NOT EXISTS \"{schema}\"")))
         .await?;
 
     // --- Enum: user_role ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         "DO $$ BEGIN
            IF NOT EXISTS (
              SELECT 1 FROM pg_type t
@@ -22,12 +56,11 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
                ('super_admin','admin_garderie','educateur','parent');
            END IF;
          END $$"
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Users ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".users (
             id               UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
             email            VARCHAR(255) UNIQUE NOT NULL,
@@ -42,19 +75,18 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
             created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
             updated_at       TIMESTAMPTZ NOT NULL DEFAULT NOW()
         )"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // Ensure the column exists for existing tenant schemas (idempotent)
-    sqlx::raw_sql(&format!(
-        r#"ALTER TABLE "{schema}".users ADD COLUMN IF NOT EXISTS force_password_change BOOLEAN NOT NULL DEFAULT FALSE"#
-    ))
-    .execute(pool)
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"ALTER TABLE "{schema}".users ADD COLUMN IF NOT EXISTS force_password_change BOOLEAN NOT NULL DEFAULT FALSE;
+           ALTER TABLE "{schema}".users ADD COLUMN IF NOT EXISTS phone VARCHAR(32)"#
+    )))
     .await?;
 
     // --- Refresh tokens ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".refresh_tokens (
             id           UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
             user_id      UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
@@ -63,12 +95,20 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
             revoked      BOOLEAN NOT NULL DEFAULT FALSE,
             created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW()
         )"#
-    ))
-    .execute(pool)
+    )))
+    .await?;
+
+    // Idempotent: session metadata shown in the active-sessions list
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"ALTER TABLE "{schema}".refresh_tokens
+           ADD COLUMN IF NOT EXISTS user_agent   TEXT,
+           ADD COLUMN IF NOT EXISTS ip_address   VARCHAR(64),
+           ADD COLUMN IF NOT EXISTS last_used_at TIMESTAMPTZ"#
+    )))
     .await?;
 
     // --- Invitation tokens ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".invitation_tokens (
             id           UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
             email        VARCHAR(255) NOT NULL,
@@ -79,19 +119,24 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
             expires_at   TIMESTAMPTZ NOT NULL,
             created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW()
         )"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // Make invited_by nullable for existing tenant schemas (super-admin invitations)
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"ALTER TABLE "{schema}".invitation_tokens ALTER COLUMN invited_by DROP NOT NULL"#
-    ))
-    .execute(pool)
+    )))
+    .await?;
+
+    // Language of the invitation email, for invitees without an account yet
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"ALTER TABLE "{schema}".invitation_tokens
+           ADD COLUMN IF NOT EXISTS preferred_locale VARCHAR(8) NOT NULL DEFAULT 'fr'"#
+    )))
     .await?;
 
     // --- Groups ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".groups (
             id          UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
             name        VARCHAR(128) NOT NULL,
@@ -100,12 +145,23 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
             created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
             updated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
         )"#
-    ))
-    .execute(pool)
+    )))
+    .await?;
+
+    // --- Educator ↔ group assignments (scope what an educator can see and act on) ---
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"CREATE TABLE IF NOT EXISTS "{schema}".educator_groups (
+            user_id    UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
+            group_id   UUID NOT NULL REFERENCES "{schema}".groups(id) ON DELETE CASCADE,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            PRIMARY KEY (user_id, group_id)
+        );
+        CREATE INDEX IF NOT EXISTS educator_groups_group_idx ON "{schema}".educator_groups (group_id)"#
+    )))
     .await?;
 
     // --- Children ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".children (
             id             UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
             first_name     VARCHAR(128) NOT NULL,
@@ -120,53 +176,49 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
             created_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
             updated_at     TIMESTAMPTZ NOT NULL DEFAULT NOW()
         )"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // Idempotent: add schedule columns for existing schemas
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"ALTER TABLE "{schema}".children
            ADD COLUMN IF NOT EXISTS start_date    DATE,
-           ADD COLUMN IF NOT EXISTS schedule_days INTEGER[]"#
-    ))
-    .execute(pool)
+           ADD COLUMN IF NOT EXISTS schedule_days INTEGER[],
+           ADD COLUMN IF NOT EXISTS allergies     TEXT[] NOT NULL DEFAULT '{{}}'"#
+    )))
     .await?;
 
     // Idempotent: add avatar encryption columns for existing schemas
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"ALTER TABLE "{schema}".children
            ADD COLUMN IF NOT EXISTS avatar_iv  BYTEA,
            ADD COLUMN IF NOT EXISTS avatar_tag BYTEA"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // Idempotent: fix existing FK to use ON DELETE SET NULL instead of RESTRICT
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"ALTER TABLE "{schema}".children
            DROP CONSTRAINT IF EXISTS children_group_id_fkey;
            ALTER TABLE "{schema}".children
            ADD CONSTRAINT children_group_id_fkey
              FOREIGN KEY (group_id) REFERENCES "{schema}".groups(id) ON DELETE SET NULL"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Child–parent link ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".child_parents (
             child_id     UUID NOT NULL REFERENCES "{schema}".children(id) ON DELETE CASCADE,
             user_id      UUID NOT NULL REFERENCES "{schema}".users(id)    ON DELETE CASCADE,
             relationship VARCHAR(64) NOT NULL DEFAULT 'parent',
             PRIMARY KEY (child_id, user_id)
         )"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Pending parents (by email, for non-registered parents) ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".child_pending_parents (
             child_id     UUID NOT NULL REFERENCES "{schema}".children(id) ON DELETE CASCADE,
             email        VARCHAR(255) NOT NULL,
@@ -175,12 +227,11 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
             PRIMARY KEY (child_id, email)
         );
         CREATE INDEX IF NOT EXISTS idx_child_pending_parents_email ON "{schema}".child_pending_parents(email)"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Invited parents (invitation token linked to a child) ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".child_invitations (
             id                 UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
             child_id           UUID NOT NULL REFERENCES "{schema}".children(id) ON DELETE CASCADE,
@@ -190,12 +241,11 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
         );
         CREATE INDEX IF NOT EXISTS idx_child_invitations_child ON "{schema}".child_invitations(child_id);
         CREATE INDEX IF NOT EXISTS idx_child_invitations_token ON "{schema}".child_invitations(invitation_token_id)"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Enum: message_type ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         "DO $$ BEGIN
            IF NOT EXISTS (
              SELECT 1 FROM pg_type t
@@ -206,12 +256,11 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
                ('broadcast','group','individual');
            END IF;
          END $$"
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Enum: send_to_parents_scope ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         "DO $$ BEGIN
            IF NOT EXISTS (
              SELECT 1 FROM pg_type t
@@ -222,12 +271,11 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
                ('all_parents','child_parents','group_parents');
            END IF;
          END $$"
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Messages ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".messages (
             id                      UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
             sender_id               UUID NOT NULL REFERENCES "{schema}".users(id),
@@ -244,44 +292,106 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
             created_at              TIMESTAMPTZ NOT NULL DEFAULT NOW(),
             updated_at              TIMESTAMPTZ NOT NULL DEFAULT NOW()
         )"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Ensure columns exist for existing schemas (idempotent) ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"ALTER TABLE "{schema}".messages ADD COLUMN IF NOT EXISTS subject VARCHAR(255);
            ALTER TABLE "{schema}".messages ADD COLUMN IF NOT EXISTS send_to_parents_scope "{schema}".send_to_parents_scope;
            ALTER TABLE "{schema}".messages ADD COLUMN IF NOT EXISTS send_to_parents_child UUID REFERENCES "{schema}".children(id) ON DELETE SET NULL;
            ALTER TABLE "{schema}".messages ADD COLUMN IF NOT EXISTS send_to_parents_group UUID REFERENCES "{schema}".groups(id) ON DELETE SET NULL;
            ALTER TABLE "{schema}".messages ADD COLUMN IF NOT EXISTS email_sent BOOLEAN NOT NULL DEFAULT FALSE"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE INDEX IF NOT EXISTS messages_sender_idx    ON "{schema}".messages(sender_id);
            CREATE INDEX IF NOT EXISTS messages_group_idx     ON "{schema}".messages(group_id);
            CREATE INDEX IF NOT EXISTS messages_recipient_idx ON "{schema}".messages(recipient_id);
            CREATE INDEX IF NOT EXISTS messages_created_idx   ON "{schema}".messages(created_at DESC)"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Message attachments ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".message_attachments (
             id          UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
             message_id  UUID NOT NULL REFERENCES "{schema}".messages(id) ON DELETE CASCADE,
             media_id    UUID,
             document_id UUID
         )"#
-    ))
-    .execute(pool)
+    )))
+    .await?;
+
+    // --- Message edits (previous contents, newest last) ---
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"ALTER TABLE "{schema}".messages ADD COLUMN IF NOT EXISTS edited_at TIMESTAMPTZ;
+        CREATE TABLE IF NOT EXISTS "{schema}".message_edits (
+            id               UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+            message_id       UUID NOT NULL REFERENCES "{schema}".messages(id) ON DELETE CASCADE,
+            previous_content TEXT NOT NULL,
+            edited_by        UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
+            edited_at        TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        );
+        CREATE INDEX IF NOT EXISTS message_edits_message_idx ON "{schema}".message_edits(message_id)"#
+    )))
+    .await?;
+
+    // --- Per-recipient read receipts ---
+    // Created once; messages already flagged is_read are backfilled as read by every
+    // user other than the sender so that unread badges do not reappear on upgrade.
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"DO $$ BEGIN
+           IF to_regclass('"{schema}".message_reads') IS NULL THEN
+             CREATE TABLE "{schema}".message_reads (
+                 message_id UUID NOT NULL REFERENCES "{schema}".messages(id) ON DELETE CASCADE,
+                 user_id    UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
+                 read_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                 PRIMARY KEY (message_id, user_id)
+             );
+             CREATE INDEX message_reads_user_idx ON "{schema}".message_reads(user_id);
+             INSERT INTO "{schema}".message_reads (message_id, user_id, read_at)
+             SELECT m.id, u.id, m.updated_at
+             FROM "{schema}".messages m
+             JOIN "{schema}".users u ON u.id <> m.sender_id
+             WHERE m.is_read = TRUE AND m.message_type::text <> 'individual';
+           END IF;
+         END $$"#
+    )))
+    .await?;
+
+    // --- Message reactions (one row per user and emoji) ---
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"CREATE TABLE IF NOT EXISTS "{schema}".message_reactions (
+            message_id UUID NOT NULL REFERENCES "{schema}".messages(id) ON DELETE CASCADE,
+            user_id    UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
+            emoji      VARCHAR(16) NOT NULL,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            PRIMARY KEY (message_id, user_id, emoji)
+        );
+        CREATE INDEX IF NOT EXISTS message_reactions_user_idx ON "{schema}".message_reactions(user_id)"#
+    )))
+    .await?;
+
+    // --- Per-user thread state (archived, muted, pinned) ---
+    // thread_key is "broadcast", "group:<group id>" or "individual:<parent id>".
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"CREATE TABLE IF NOT EXISTS "{schema}".thread_states (
+            user_id    UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
+            thread_key TEXT NOT NULL,
+            archived   BOOLEAN NOT NULL DEFAULT FALSE,
+            muted      BOOLEAN NOT NULL DEFAULT FALSE,
+            pinned     BOOLEAN NOT NULL DEFAULT FALSE,
+            updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            PRIMARY KEY (user_id, thread_key)
+        );
+        CREATE INDEX IF NOT EXISTS thread_states_thread_idx ON "{schema}".thread_states(thread_key)"#
+    )))
     .await?;
 
     // --- Enum: media_type ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         "DO $$ BEGIN
            IF NOT EXISTS (
              SELECT 1 FROM pg_type t
@@ -291,12 +401,11 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
              CREATE TYPE \"{schema}\".media_type AS ENUM ('photo','video');
            END IF;
          END $$"
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Enum: media_visibility ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         "DO $$ BEGIN
            IF NOT EXISTS (
              SELECT 1 FROM pg_type t
@@ -306,12 +415,11 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
              CREATE TYPE \"{schema}\".media_visibility AS ENUM ('private','public','group','child');
            END IF;
          END $$"
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Enum: doc_category ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         "DO $$ BEGIN
            IF NOT EXISTS (
              SELECT 1 FROM pg_type t
@@ -322,12 +430,11 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
                ('formulaire','menu','politique','bulletin','autre');
            END IF;
          END $$"
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Enum: doc_visibility ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         "DO $$ BEGIN
            IF NOT EXISTS (
              SELECT 1 FROM pg_type t
@@ -338,12 +445,11 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
                ('private','public','group','child');
            END IF;
          END $$"
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Media ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".media (
             id                       UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
             uploader_id              UUID NOT NULL REFERENCES "{schema}".users(id),
@@ -367,12 +473,11 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
             thumbnail_encryption_tag BYTEA,
             created_at               TIMESTAMPTZ NOT NULL DEFAULT NOW()
         )"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // Idempotent: add columns for existing schemas
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"ALTER TABLE "{schema}".media
            ADD COLUMN IF NOT EXISTS visibility "{schema}".media_visibility NOT NULL DEFAULT 'private',
            ADD COLUMN IF NOT EXISTS is_encrypted BOOLEAN NOT NULL DEFAULT false,
@@ -380,37 +485,42 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
            ADD COLUMN IF NOT EXISTS encryption_tag BYTEA,
            ADD COLUMN IF NOT EXISTS thumbnail_encryption_iv BYTEA,
            ADD COLUMN IF NOT EXISTS thumbnail_encryption_tag BYTEA"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE INDEX IF NOT EXISTS media_group_idx      ON "{schema}".media(group_id);
            CREATE INDEX IF NOT EXISTS media_created_idx    ON "{schema}".media(created_at DESC);
            CREATE INDEX IF NOT EXISTS media_visibility_idx ON "{schema}".media(visibility)"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Media–children junction ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".media_children (
             media_id  UUID NOT NULL REFERENCES "{schema}".media(id) ON DELETE CASCADE,
             child_id  UUID NOT NULL REFERENCES "{schema}".children(id) ON DELETE CASCADE,
             PRIMARY KEY (media_id, child_id)
         )"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE INDEX IF NOT EXISTS media_children_child_idx ON "{schema}".media_children(child_id)"#
-    ))
-    .execute(pool)
+    )))
+    .await?;
+
+    // --- Media listing: keyset order and the parent visibility filter ---
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"CREATE INDEX IF NOT EXISTS media_created_id_idx ON "{schema}".media(created_at DESC, id DESC);
+           CREATE INDEX IF NOT EXISTS media_visibility_created_idx
+               ON "{schema}".media(visibility, created_at DESC, id DESC);
+           CREATE INDEX IF NOT EXISTS child_parents_user_idx ON "{schema}".child_parents(user_id, child_id)"#
+    )))
     .await?;
 
     // --- Documents ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".documents (
             id                UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
             uploader_id       UUID NOT NULL REFERENCES "{schema}".users(id),
@@ -429,23 +539,21 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
             created_at        TIMESTAMPTZ NOT NULL DEFAULT NOW(),
             updated_at        TIMESTAMPTZ NOT NULL DEFAULT NOW()
         )"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // Idempotent: add columns for existing schemas
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"ALTER TABLE "{schema}".documents
            ADD COLUMN IF NOT EXISTS visibility "{schema}".doc_visibility NOT NULL DEFAULT 'public',
            ADD COLUMN IF NOT EXISTS is_encrypted BOOLEAN NOT NULL DEFAULT false,
            ADD COLUMN IF NOT EXISTS encryption_iv BYTEA,
            ADD COLUMN IF NOT EXISTS encryption_tag BYTEA"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // Idempotent: fix visibility for existing rows based on group_id/child_id
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"UPDATE "{schema}".documents
            SET visibility = CASE
              WHEN child_id IS NOT NULL THEN 'child'::"{schema}".doc_visibility
@@ -454,12 +562,36 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
            END
            WHERE visibility = 'public'
              AND (child_id IS NOT NULL OR group_id IS NOT NULL)"#
-    ))
-    .execute(pool)
+    )))
+    .await?;
+
+    // --- Document e-signatures ---
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"ALTER TABLE "{schema}".documents
+           ADD COLUMN IF NOT EXISTS requires_signature BOOLEAN NOT NULL DEFAULT FALSE;
+        CREATE TABLE IF NOT EXISTS "{schema}".document_signatures (
+            id          UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+            document_id UUID NOT NULL REFERENCES "{schema}".documents(id) ON DELETE CASCADE,
+            user_id     UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
+            typed_name  VARCHAR(255) NOT NULL,
+            ip_address  VARCHAR(64),
+            signed_at   TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            UNIQUE (document_id, user_id)
+        );
+        CREATE INDEX IF NOT EXISTS document_signatures_document_idx ON "{schema}".document_signatures(document_id)"#
+    )))
+    .await?;
+
+    // --- Master key version each encrypted file was written under (key rotation) ---
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"ALTER TABLE "{schema}".media ADD COLUMN IF NOT EXISTS key_version INT NOT NULL DEFAULT 1;
+           ALTER TABLE "{schema}".documents ADD COLUMN IF NOT EXISTS key_version INT NOT NULL DEFAULT 1;
+           ALTER TABLE "{schema}".children ADD COLUMN IF NOT EXISTS avatar_key_version INT NOT NULL DEFAULT 1"#
+    )))
     .await?;
 
     // --- Password reset tokens ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".password_reset_tokens (
             id         UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
             user_id    UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
@@ -468,12 +600,11 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
             expires_at TIMESTAMPTZ NOT NULL,
             created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
         )"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Two-factor auth codes ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".two_factor_codes (
             id         UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
             user_id    UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
@@ -483,12 +614,18 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
             attempts   SMALLINT NOT NULL DEFAULT 0,
             created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
         )"#
-    ))
-    .execute(pool)
+    )))
+    .await?;
+
+    // Where the code was sent; an emailed code proves the user owns the address
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"ALTER TABLE "{schema}".two_factor_codes
+           ADD COLUMN IF NOT EXISTS channel VARCHAR(8) NOT NULL DEFAULT 'email'"#
+    )))
     .await?;
 
     // --- Trusted devices (2FA remember for 30 days) ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".trusted_devices (
             id         UUID PRIMARY KEY,
             user_id    UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
@@ -496,12 +633,19 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
             expires_at TIMESTAMPTZ NOT NULL,
             created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
         )"#
-    ))
-    .execute(pool)
+    )))
+    .await?;
+
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"ALTER TABLE "{schema}".trusted_devices
+           ADD COLUMN IF NOT EXISTS user_agent   TEXT,
+           ADD COLUMN IF NOT EXISTS ip_address   VARCHAR(64),
+           ADD COLUMN IF NOT EXISTS last_used_at TIMESTAMPTZ"#
+    )))
     .await?;
 
     // --- Push tokens ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".push_tokens (
             id         UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
             user_id    UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
@@ -510,12 +654,11 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
             created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
             UNIQUE (user_id, token)
         )"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Enum: weather_condition ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         "DO $$ BEGIN
            IF NOT EXISTS (
              SELECT 1 FROM pg_type t
@@ -526,12 +669,11 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
                ('ensoleille','nuageux','pluie','neige','orageux');
            END IF;
          END $$"
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Enum: appetit_level ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         "DO $$ BEGIN
            IF NOT EXISTS (
              SELECT 1 FROM pg_type t
@@ -542,12 +684,11 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
                ('comme_habitude','peu','beaucoup','refuse');
            END IF;
          END $$"
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Enum: humeur_level ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         "DO $$ BEGIN
            IF NOT EXISTS (
              SELECT 1 FROM pg_type t
@@ -558,12 +699,11 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
                ('tres_bien','bien','difficile','pleurs');
            END IF;
          END $$"
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Daily journals ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".daily_journals (
             id                 UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
             child_id           UUID NOT NULL REFERENCES "{schema}".children(id) ON DELETE CASCADE,
@@ -584,12 +724,29 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
             updated_at         TIMESTAMPTZ NOT NULL DEFAULT NOW(),
             UNIQUE (child_id, date)
         )"#
-    ))
-    .execute(pool)
+    )))
+    .await?;
+
+    // Time-stamped events of the day (naps, diaper changes…); times are local to the garderie
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"CREATE TABLE IF NOT EXISTS "{schema}".journal_events (
+            id          UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+            child_id    UUID NOT NULL REFERENCES "{schema}".children(id) ON DELETE CASCADE,
+            date        DATE NOT NULL,
+            event_type  VARCHAR(20) NOT NULL,
+            start_time  TIME NOT NULL,
+            end_time    TIME,
+            notes       TEXT,
+            created_by  UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+            created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            CHECK (end_time IS NULL OR end_time >= start_time)
+        );
+        CREATE INDEX IF NOT EXISTS journal_events_child_date_idx ON "{schema}".journal_events (child_id, date, start_time)"#
+    )))
     .await?;
 
     // --- Daily menus (garderie-level, one entry per date) ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".daily_menus (
             id                   UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
             date                 DATE NOT NULL UNIQUE,
@@ -602,23 +759,37 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
             created_at           TIMESTAMPTZ NOT NULL DEFAULT NOW(),
             updated_at           TIMESTAMPTZ NOT NULL DEFAULT NOW()
         )"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // Idempotent: add structured menu columns and make menu nullable for existing schemas
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"ALTER TABLE "{schema}".daily_menus
            ADD COLUMN IF NOT EXISTS collation_matin TEXT,
            ADD COLUMN IF NOT EXISTS diner TEXT,
            ADD COLUMN IF NOT EXISTS collation_apres_midi TEXT;
            ALTER TABLE "{schema}".daily_menus ALTER COLUMN menu DROP NOT NULL"#
-    ))
-    .execute(pool)
+    )))
+    .await?;
+
+    // Structured menu items (one row per dish, tagged with allergens)
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"CREATE TABLE IF NOT EXISTS "{schema}".menu_items (
+            id         UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+            menu_id    UUID NOT NULL REFERENCES "{schema}".daily_menus(id) ON DELETE CASCADE,
+            meal       VARCHAR(32) NOT NULL
+                       CHECK (meal IN ('collation_matin', 'diner', 'collation_apres_midi')),
+            name       TEXT NOT NULL,
+            allergens  TEXT[] NOT NULL DEFAULT '{{}}',
+            position   INTEGER NOT NULL DEFAULT 0,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        );
+        CREATE INDEX IF NOT EXISTS menu_items_menu_idx ON "{schema}".menu_items (menu_id, meal, position)"#
+    )))
     .await?;
 
     // --- Audit log (Loi 25 + security traceability) ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".audit_log (
             id             UUID        PRIMARY KEY DEFAULT public.uuid_generate_v4(),
             user_id        UUID        REFERENCES "{schema}".users(id) ON DELETE SET NULL,
@@ -632,12 +803,11 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
         );
         CREATE INDEX IF NOT EXISTS audit_log_created_at_idx ON "{schema}".audit_log (created_at DESC);
         CREATE INDEX IF NOT EXISTS audit_log_user_id_idx    ON "{schema}".audit_log (user_id)"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Consent records (Loi 25) ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".consent_records (
             id               UUID        PRIMARY KEY DEFAULT public.uuid_generate_v4(),
             user_id          UUID        NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
@@ -649,13 +819,23 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
             ip_address       VARCHAR(64),
             created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW()
         )"#
-    ))
-    .execute(pool)
+    )))
+    .await?;
+
+    // Photo consent given for one child; overrides the parents' own consent records
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"CREATE TABLE IF NOT EXISTS "{schema}".child_consents (
+            child_id        UUID        PRIMARY KEY REFERENCES "{schema}".children(id) ON DELETE CASCADE,
+            photos_accepted BOOLEAN     NOT NULL,
+            updated_by      UUID        REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+            updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        )"#
+    )))
     .await?;
 
     // --- Retention tracking columns (soft-delete + scheduled hard-delete per privacy policy) ---
     // Retention periods: children=1yr, media=6mo, messages=2yr, documents=7yr, audit_logs=90d
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"ALTER TABLE "{schema}".children ADD COLUMN IF NOT EXISTS is_deleted BOOLEAN NOT NULL DEFAULT FALSE;
            ALTER TABLE "{schema}".children ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
            ALTER TABLE "{schema}".media ADD COLUMN IF NOT EXISTS is_deleted BOOLEAN NOT NULL DEFAULT FALSE;
@@ -666,46 +846,303 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
            ALTER TABLE "{schema}".documents ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
            ALTER TABLE "{schema}".audit_log ADD COLUMN IF NOT EXISTS is_deleted BOOLEAN NOT NULL DEFAULT FALSE;
            ALTER TABLE "{schema}".audit_log ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Indexes for retention purge queries ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE INDEX IF NOT EXISTS children_deleted_at_idx ON "{schema}".children(deleted_at) WHERE is_deleted = TRUE;
            CREATE INDEX IF NOT EXISTS media_deleted_at_idx ON "{schema}".media(deleted_at) WHERE is_deleted = TRUE;
            CREATE INDEX IF NOT EXISTS messages_deleted_at_idx ON "{schema}".messages(deleted_at) WHERE is_deleted = TRUE;
            CREATE INDEX IF NOT EXISTS documents_deleted_at_idx ON "{schema}".documents(deleted_at) WHERE is_deleted = TRUE;
            CREATE INDEX IF NOT EXISTS audit_log_deleted_at_idx ON "{schema}".audit_log(deleted_at) WHERE is_deleted = TRUE"#
-    ))
-    .execute(pool)
+    )))
+    .await?;
+
+    // --- Waitlist ---
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"CREATE TABLE IF NOT EXISTS "{schema}".waitlist_entries (
+            id                 UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+            child_first_name   VARCHAR(128) NOT NULL,
+            child_last_name    VARCHAR(128) NOT NULL,
+            child_birth_date   DATE NOT NULL,
+            parent_first_name  VARCHAR(128) NOT NULL,
+            parent_last_name   VARCHAR(128) NOT NULL,
+            parent_email       VARCHAR(255) NOT NULL,
+            parent_phone       VARCHAR(32),
+            desired_start_date DATE,
+            age_group          VARCHAR(64),
+            priority           INT NOT NULL DEFAULT 0,
+            status             VARCHAR(20) NOT NULL DEFAULT 'waiting',
+            notes              TEXT,
+            offered_at         TIMESTAMPTZ,
+            child_id           UUID REFERENCES "{schema}".children(id) ON DELETE SET NULL,
+            created_at         TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            updated_at         TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        );
+        CREATE INDEX IF NOT EXISTS idx_waitlist_status ON "{schema}".waitlist_entries(status, priority DESC, created_at)"#
+    )))
+    .await?;
+
+    // --- Parent-teacher meetings ---
+    // At most one active booking per slot, enforced by the partial unique index.
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"CREATE TABLE IF NOT EXISTS "{schema}".meeting_slots (
+            id          UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+            educator_id UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
+            starts_at   TIMESTAMPTZ NOT NULL,
+            ends_at     TIMESTAMPTZ NOT NULL,
+            location    TEXT,
+            created_by  UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+            created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            CHECK (ends_at > starts_at)
+        );
+        CREATE INDEX IF NOT EXISTS meeting_slots_educator_idx ON "{schema}".meeting_slots (educator_id, starts_at);
+        CREATE TABLE IF NOT EXISTS "{schema}".meeting_bookings (
+            id               UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+            slot_id          UUID NOT NULL REFERENCES "{schema}".meeting_slots(id) ON DELETE CASCADE,
+            child_id         UUID NOT NULL REFERENCES "{schema}".children(id) ON DELETE CASCADE,
+            parent_id        UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
+            notes            TEXT,
+            status           VARCHAR(20) NOT NULL DEFAULT 'booked',
+            reminder_sent_at TIMESTAMPTZ,
+            created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            cancelled_at     TIMESTAMPTZ
+        );
+        CREATE UNIQUE INDEX IF NOT EXISTS meeting_bookings_active_slot_idx
+            ON "{schema}".meeting_bookings (slot_id) WHERE status = 'booked';
+        CREATE INDEX IF NOT EXISTS meeting_bookings_parent_idx ON "{schema}".meeting_bookings (parent_id)"#
+    )))
+    .await?;
+
+    // --- Development milestones & observations ---
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"CREATE TABLE IF NOT EXISTS "{schema}".milestones (
+            id          UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+            domain      VARCHAR(32) NOT NULL,
+            age_band    VARCHAR(16) NOT NULL,
+            title       VARCHAR(255) NOT NULL,
+            description TEXT,
+            position    INT NOT NULL DEFAULT 0,
+            is_active   BOOLEAN NOT NULL DEFAULT TRUE,
+            created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            updated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        );
+        CREATE TABLE IF NOT EXISTS "{schema}".child_observations (
+            id                 UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+            child_id           UUID NOT NULL REFERENCES "{schema}".children(id) ON DELETE CASCADE,
+            milestone_id       UUID REFERENCES "{schema}".milestones(id) ON DELETE SET NULL,
+            domain             VARCHAR(32) NOT NULL,
+            age_band           VARCHAR(16) NOT NULL,
+            observed_on        DATE NOT NULL DEFAULT CURRENT_DATE,
+            status             VARCHAR(16),
+            notes              TEXT,
+            media_id           UUID REFERENCES "{schema}".media(id) ON DELETE SET NULL,
+            visible_to_parents BOOLEAN NOT NULL DEFAULT TRUE,
+            created_by         UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
+            created_at         TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            updated_at         TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        );
+        CREATE INDEX IF NOT EXISTS child_observations_child_idx
+            ON "{schema}".child_observations (child_id, observed_on DESC)"#
+    )))
+    .await?;
+
+    // --- Garderie-specific email wording ---
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"CREATE TABLE IF NOT EXISTS "{schema}".email_templates (
+            id          UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+            kind        VARCHAR(32) NOT NULL,
+            locale      VARCHAR(8) NOT NULL DEFAULT 'fr',
+            subject     VARCHAR(255) NOT NULL,
+            body        TEXT NOT NULL,
+            updated_by  UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+            created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            updated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            UNIQUE (kind, locale)
+        )"#
+    )))
+    .await?;
+
+    // --- Photo albums & media tags ---
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"CREATE TABLE IF NOT EXISTS "{schema}".albums (
+            id              UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+            title           VARCHAR(255) NOT NULL,
+            description     TEXT,
+            cover_media_id  UUID REFERENCES "{schema}".media(id) ON DELETE SET NULL,
+            visibility      "{schema}".media_visibility NOT NULL DEFAULT 'private',
+            group_id        UUID REFERENCES "{schema}".groups(id) ON DELETE SET NULL,
+            child_id        UUID REFERENCES "{schema}".children(id) ON DELETE CASCADE,
+            created_by      UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
+            created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        );
+        CREATE TABLE IF NOT EXISTS "{schema}".album_media (
+            album_id  UUID NOT NULL REFERENCES "{schema}".albums(id) ON DELETE CASCADE,
+            media_id  UUID NOT NULL REFERENCES "{schema}".media(id) ON DELETE CASCADE,
+            added_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            PRIMARY KEY (album_id, media_id)
+        );
+        CREATE INDEX IF NOT EXISTS album_media_media_idx ON "{schema}".album_media (media_id);
+        CREATE TABLE IF NOT EXISTS "{schema}".media_tags (
+            media_id  UUID NOT NULL REFERENCES "{schema}".media(id) ON DELETE CASCADE,
+            tag       VARCHAR(50) NOT NULL,
+            PRIMARY KEY (media_id, tag)
+        );
+        CREATE INDEX IF NOT EXISTS media_tags_tag_idx ON "{schema}".media_tags (tag)"#
+    )))
+    .await?;
+
+    // --- Content hash of media, so duplicate uploads share one stored file ---
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"ALTER TABLE "{schema}".media ADD COLUMN IF NOT EXISTS content_sha256 VARCHAR(64);
+           CREATE INDEX IF NOT EXISTS media_content_sha256_idx ON "{schema}".media (content_sha256);
+           CREATE INDEX IF NOT EXISTS media_storage_path_idx ON "{schema}".media (storage_path)"#
+    )))
+    .await?;
+
+    // --- Antivirus scan state (pending → clean | infected | error) ---
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"ALTER TABLE "{schema}".media
+           ADD COLUMN IF NOT EXISTS scan_status    VARCHAR(16) NOT NULL DEFAULT 'pending',
+           ADD COLUMN IF NOT EXISTS scan_signature TEXT,
+           ADD COLUMN IF NOT EXISTS scanned_at     TIMESTAMPTZ;
+           ALTER TABLE "{schema}".documents
+           ADD COLUMN IF NOT EXISTS scan_status    VARCHAR(16) NOT NULL DEFAULT 'pending',
+           ADD COLUMN IF NOT EXISTS scan_signature TEXT,
+           ADD COLUMN IF NOT EXISTS scanned_at     TIMESTAMPTZ;
+           CREATE INDEX IF NOT EXISTS media_scan_status_idx
+               ON "{schema}".media (scan_status) WHERE scan_status <> 'clean';
+           CREATE INDEX IF NOT EXISTS documents_scan_status_idx
+               ON "{schema}".documents (scan_status) WHERE scan_status <> 'clean'"#
+    )))
+    .await?;
+
+    // --- Document folders and replaced document files ---
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"CREATE TABLE IF NOT EXISTS "{schema}".document_folders (
+            id         UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+            name       VARCHAR(255) NOT NULL,
+            parent_id  UUID REFERENCES "{schema}".document_folders(id) ON DELETE CASCADE,
+            created_by UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        );
+        CREATE UNIQUE INDEX IF NOT EXISTS document_folders_name_idx
+            ON "{schema}".document_folders (COALESCE(parent_id, '00000000-0000-0000-0000-000000000000'::uuid), LOWER(name));
+        ALTER TABLE "{schema}".documents
+            ADD COLUMN IF NOT EXISTS folder_id UUID REFERENCES "{schema}".document_folders(id) ON DELETE SET NULL,
+            ADD COLUMN IF NOT EXISTS version INT NOT NULL DEFAULT 1;
+        CREATE INDEX IF NOT EXISTS documents_folder_idx ON "{schema}".documents(folder_id);
+        CREATE TABLE IF NOT EXISTS "{schema}".document_versions (
+            id                UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+            document_id       UUID NOT NULL REFERENCES "{schema}".documents(id) ON DELETE CASCADE,
+            version           INT NOT NULL,
+            original_filename TEXT NOT NULL,
+            storage_path      TEXT NOT NULL,
+            content_type      VARCHAR(128) NOT NULL,
+            size_bytes        BIGINT NOT NULL,
+            is_encrypted      BOOLEAN NOT NULL DEFAULT false,
+            encryption_iv     BYTEA,
+            encryption_tag    BYTEA,
+            key_version       INT NOT NULL DEFAULT 1,
+            scan_status       VARCHAR(16) NOT NULL DEFAULT 'pending',
+            uploader_id       UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+            uploaded_at       TIMESTAMPTZ NOT NULL,
+            replaced_by       UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+            replaced_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            UNIQUE (document_id, version)
+        );
+        CREATE INDEX IF NOT EXISTS document_versions_path_idx ON "{schema}".document_versions(storage_path)"#
+    )))
+    .await?;
+
+    // --- Document expiry (renewal reminders) ---
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"ALTER TABLE "{schema}".documents
+           ADD COLUMN IF NOT EXISTS expires_at DATE,
+           ADD COLUMN IF NOT EXISTS expiry_reminder_days INT;
+           CREATE INDEX IF NOT EXISTS documents_expires_at_idx
+               ON "{schema}".documents (expires_at) WHERE expires_at IS NOT NULL"#
+    )))
+    .await?;
+
+    // --- Two-way group threads: parents may reply where the group allows it ---
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"ALTER TABLE "{schema}".groups
+           ADD COLUMN IF NOT EXISTS allow_parent_replies BOOLEAN NOT NULL DEFAULT FALSE"#
+    )))
+    .await?;
+
+    // --- Message drafts and reusable announcement templates ---
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"CREATE TABLE IF NOT EXISTS "{schema}".message_drafts (
+            id           UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+            owner_id     UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
+            name         VARCHAR(255) NOT NULL,
+            is_template  BOOLEAN NOT NULL DEFAULT FALSE,
+            subject      VARCHAR(255),
+            content      TEXT NOT NULL,
+            scope        VARCHAR(32),
+            group_id     UUID REFERENCES "{schema}".groups(id) ON DELETE SET NULL,
+            child_id     UUID REFERENCES "{schema}".children(id) ON DELETE SET NULL,
+            recipient_id UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+            created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            updated_at   TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        );
+        CREATE INDEX IF NOT EXISTS message_drafts_owner_idx ON "{schema}".message_drafts(owner_id)"#
+    )))
+    .await?;
+
+    // --- Journal amendments: corrections of entries already emailed ---
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"ALTER TABLE "{schema}".daily_journals ADD COLUMN IF NOT EXISTS amended_at TIMESTAMPTZ;
+        CREATE TABLE IF NOT EXISTS "{schema}".journal_amendments (
+            id          UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+            journal_id  UUID NOT NULL REFERENCES "{schema}".daily_journals(id) ON DELETE CASCADE,
+            amended_by  UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+            note        TEXT NOT NULL,
+            previous    JSONB NOT NULL,
+            created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        );
+        CREATE INDEX IF NOT EXISTS journal_amendments_journal_idx ON "{schema}".journal_amendments(journal_id, created_at)"#
+    )))
+    .await?;
+
+    // --- Full-text indexes for the staff search ---
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"CREATE INDEX IF NOT EXISTS children_search_idx ON "{schema}".children USING GIN ({CHILD_SEARCH_VECTOR});
+        CREATE INDEX IF NOT EXISTS users_search_idx ON "{schema}".users USING GIN ({USER_SEARCH_VECTOR});
+        CREATE INDEX IF NOT EXISTS documents_search_idx ON "{schema}".documents USING GIN ({DOCUMENT_SEARCH_VECTOR});
+        CREATE INDEX IF NOT EXISTS messages_search_idx ON "{schema}".messages USING GIN ({MESSAGE_SEARCH_VECTOR})
+            WHERE is_deleted = FALSE"#
+    )))
     .await?;
 
     // --- updated_at trigger function ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE OR REPLACE FUNCTION "{schema}".update_updated_at()
            RETURNS TRIGGER AS $fn$
            BEGIN NEW.updated_at = NOW(); RETURN NEW; END;
            $fn$ LANGUAGE plpgsql"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Triggers (one per table, idempotent via DROP IF EXISTS + CREATE) ---
-    for table in &["users", "children", "groups", "messages", "documents", "daily_journals", "daily_menus"] {
+    for table in &["users", "children", "groups", "messages", "documents", "daily_journals", "daily_menus", "waitlist_entries", "milestones", "child_observations", "email_templates", "albums", "document_folders", "message_drafts"] {
         let trigger = format!("{table}_updated_at");
-        sqlx::raw_sql(&format!(
+        conn.execute(sqlx::raw_sql(&format!(
             r#"DROP TRIGGER IF EXISTS "{trigger}" ON "{schema}"."{table}";
                CREATE TRIGGER "{trigger}"
                BEFORE UPDATE ON "{schema}"."{table}"
                FOR EACH ROW EXECUTE FUNCTION "{schema}".update_updated_at()"#
-        ))
-        .execute(pool)
+        )))
         .await?;
     }
 
     // --- Activities ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".activities (
             id           UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
             title        VARCHAR(255) NOT NULL,
@@ -730,22 +1167,20 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
         CREATE INDEX IF NOT EXISTS act_reg_activity_idx ON "{schema}".activity_registrations(activity_id);
         CREATE INDEX IF NOT EXISTS idx_activities_end_date ON "{schema}".activities(end_date);
         CREATE INDEX IF NOT EXISTS idx_activities_group_id ON "{schema}".activities(group_id)"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // Idempotent: add columns for existing schemas
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"ALTER TABLE "{schema}".activities
            ADD COLUMN IF NOT EXISTS end_date DATE,
            ADD COLUMN IF NOT EXISTS group_id UUID REFERENCES "{schema}".groups(id) ON DELETE SET NULL,
            ADD COLUMN IF NOT EXISTS type VARCHAR(20) NOT NULL DEFAULT 'sortie'"#
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Enum: attendance_status ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         "DO $$ BEGIN
            IF NOT EXISTS (
              SELECT 1 FROM pg_type t
@@ -756,12 +1191,11 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
                ('attendu','present','absent','malade','vacances','present_hors_contrat');
            END IF;
          END $$"
-    ))
-    .execute(pool)
+    )))
     .await?;
 
     // --- Attendance ---
-    sqlx::raw_sql(&format!(
+    conn.execute(sqlx::raw_sql(&format!(
         r#"CREATE TABLE IF NOT EXISTS "{schema}".attendance (
             id         UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
             child_id   UUID NOT NULL REFERENCES "{schema}".children(id) ON DELETE CASCADE,
@@ -773,15 +1207,127 @@ pub async fn provision_tenant_schema(pool: &PgPool, slug: &str) -> anyhow::Resul
             UNIQUE (child_id, date)
         );
         CREATE INDEX IF NOT EXISTS attendance_child_date_idx ON "{schema}".attendance(child_id, date)"#
-    ))
-    .execute(pool)
+    )))
+    .await?;
+
+    // --- Absences declared by parents ---
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"CREATE TABLE IF NOT EXISTS "{schema}".child_absences (
+            id          UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+            child_id    UUID NOT NULL REFERENCES "{schema}".children(id) ON DELETE CASCADE,
+            start_date  DATE NOT NULL,
+            end_date    DATE NOT NULL,
+            status      VARCHAR(16) NOT NULL DEFAULT 'absent',
+            reason      TEXT,
+            declared_by UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+            created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            CHECK (end_date >= start_date)
+        );
+        CREATE INDEX IF NOT EXISTS child_absences_child_idx ON "{schema}".child_absences(child_id, start_date)"#
+    )))
+    .await?;
+
+    // --- Parent billing ---
+    // Amounts are in cents. The monthly fee of a child and the fee of an activity feed
+    // the monthly batch; a batch invoice is unique per child and month unless voided.
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"ALTER TABLE "{schema}".children ADD COLUMN IF NOT EXISTS monthly_fee_cents BIGINT;
+        ALTER TABLE "{schema}".activities ADD COLUMN IF NOT EXISTS fee_cents BIGINT;
+        CREATE SEQUENCE IF NOT EXISTS "{schema}".invoice_number_seq;
+        CREATE TABLE IF NOT EXISTS "{schema}".invoices (
+            id                UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+            number            VARCHAR(32) NOT NULL UNIQUE,
+            child_id          UUID NOT NULL REFERENCES "{schema}".children(id) ON DELETE CASCADE,
+            period            DATE,
+            issue_date        DATE NOT NULL DEFAULT CURRENT_DATE,
+            due_date          DATE NOT NULL,
+            status            VARCHAR(16) NOT NULL DEFAULT 'draft'
+                              CHECK (status IN ('draft', 'sent', 'paid', 'void')),
+            total_cents       BIGINT NOT NULL DEFAULT 0,
+            notes             TEXT,
+            sent_at           TIMESTAMPTZ,
+            paid_at           TIMESTAMPTZ,
+            payment_method    VARCHAR(32),
+            payment_reference VARCHAR(255),
+            created_by        UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+            created_at        TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            updated_at        TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        );
+        CREATE INDEX IF NOT EXISTS invoices_child_idx ON "{schema}".invoices (child_id, issue_date DESC);
+        CREATE UNIQUE INDEX IF NOT EXISTS invoices_child_period_idx
+            ON "{schema}".invoices (child_id, period) WHERE period IS NOT NULL AND status <> 'void';
+        CREATE TABLE IF NOT EXISTS "{schema}".invoice_items (
+            id          UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+            invoice_id  UUID NOT NULL REFERENCES "{schema}".invoices(id) ON DELETE CASCADE,
+            kind        VARCHAR(16) NOT NULL CHECK (kind IN ('tuition', 'late_fee', 'activity', 'other')),
+            description VARCHAR(255) NOT NULL,
+            quantity    INT NOT NULL DEFAULT 1 CHECK (quantity > 0),
+            unit_cents  BIGINT NOT NULL,
+            position    INT NOT NULL DEFAULT 0
+        );
+        CREATE INDEX IF NOT EXISTS invoice_items_invoice_idx ON "{schema}".invoice_items (invoice_id, position);
+        DROP TRIGGER IF EXISTS "invoices_updated_at" ON "{schema}"."invoices";
+        CREATE TRIGGER "invoices_updated_at"
+        BEFORE UPDATE ON "{schema}"."invoices"
+        FOR EACH ROW EXECUTE FUNCTION "{schema}".update_updated_at()"#
+    )))
+    .await?;
+
+    // --- Year-end tax receipts (Relevé 24) ---
+    // One receipt per payer, child and year, numbered in sequence within the year.
+    // Names are copied so a receipt reads the same after the accounts change; a
+    // receipt is never deleted once numbered, only refreshed when regenerated.
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"ALTER TABLE "{schema}".invoices
+           ADD COLUMN IF NOT EXISTS payer_id UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL;
+        CREATE TABLE IF NOT EXISTS "{schema}".tax_receipts (
+            id               UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+            year             INT NOT NULL,
+            sequence         INT NOT NULL,
+            number           VARCHAR(32) NOT NULL UNIQUE,
+            payer_id         UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+            payer_name       VARCHAR(255) NOT NULL,
+            child_id         UUID REFERENCES "{schema}".children(id) ON DELETE SET NULL,
+            child_name       VARCHAR(255) NOT NULL,
+            child_birth_date DATE NOT NULL,
+            amount_cents     BIGINT NOT NULL,
+            invoice_count    INT NOT NULL,
+            generated_by     UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+            generated_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            updated_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            UNIQUE (year, sequence),
+            UNIQUE (year, payer_id, child_id)
+        );
+        DROP TRIGGER IF EXISTS "tax_receipts_updated_at" ON "{schema}"."tax_receipts";
+        CREATE TRIGGER "tax_receipts_updated_at"
+        BEFORE UPDATE ON "{schema}"."tax_receipts"
+        FOR EACH ROW EXECUTE FUNCTION "{schema}".update_updated_at()"#
+    )))
+    .await?;
+
+    // --- Right-to-be-forgotten erasures ---
+    // The user row is kept anonymized (messages and media still reference it); the
+    // certificate proves the erasure without keeping the address, only its hash.
+    conn.execute(sqlx::raw_sql(&format!(
+        r#"ALTER TABLE "{schema}".users ADD COLUMN IF NOT EXISTS erased_at TIMESTAMPTZ;
+        CREATE TABLE IF NOT EXISTS "{schema}".erasure_certificates (
+            id                UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+            user_id           UUID NOT NULL,
+            email_sha256      VARCHAR(64) NOT NULL,
+            role              VARCHAR(32) NOT NULL,
+            erased_by         UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+            messages_scrubbed BOOLEAN NOT NULL,
+            summary           JSONB NOT NULL DEFAULT '{{}}',
+            erased_at         TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        );
+        CREATE INDEX IF NOT EXISTS erasure_certificates_user_idx ON "{schema}".erasure_certificates(user_id)"#
+    )))
     .await?;
 
-    tracing::info!("Provisioned tenant schema: {schema}");
     Ok(())
 }
 
-/// Returns the PostgreSQL schema name for a given garderie slug.
+/// Returns the PostgreSQL schema name for a given garderie slug, see [`TenantDb::schema`].
 pub fn schema_name(slug: &str) -> String {
-    format!("garderie_{}", slug.to_lowercase().replace('-', "_"))
+    TenantDb::schema(slug)
 }
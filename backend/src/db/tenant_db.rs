@@ -0,0 +1,286 @@
+//! Where a garderie's rows live, chosen per deployment with `TENANT_ISOLATION`:
+//!
+//! - `schema` (default): a `garderie_<slug>` schema each, fine up to a few hundred
+//!   garderies;
+//! - `shared`: every garderie in the tables of [`SHARED_SCHEMA`], each row tagged
+//!   with its garderie's id. PostgreSQL row-level security only shows a connection
+//!   the rows of the garderie named by its `app.tenant_id` setting, see the
+//!   `add_shared_tenant_rls` migration.
+//!
+//! Code reaches tenant tables through [`schema_name`](super::tenant::schema_name),
+//! which asks [`TenantDb::schema`], and runs inside [`TenantDb::scope`] for the
+//! garderie it works on: in shared mode, the pools set `app.tenant_id` from that
+//! scope on every connection they hand out. Outside of a scope, a connection sees no
+//! garderie's rows and cannot write any.
+
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use axum::http::StatusCode;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{PgConnection, PgPool};
+
+use crate::error::ErrorStatus;
+
+/// Schema holding every garderie's tables in shared mode.
+pub const SHARED_SCHEMA: &str = "tenant_shared";
+
+/// How garderies' data is kept apart, `TENANT_ISOLATION`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TenantIsolation {
+    /// A PostgreSQL schema per garderie.
+    #[default]
+    Schema,
+    /// One schema for all, rows filtered by row-level security.
+    Shared,
+}
+
+impl TenantIsolation {
+    /// TENANT_ISOLATION, `schema` when unset.
+    pub fn from_env() -> anyhow::Result<Self> {
+        std::env::var("TENANT_ISOLATION").map_or(Ok(Self::Schema), |v| v.parse())
+    }
+}
+
+impl FromStr for TenantIsolation {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "schema" => Ok(Self::Schema),
+            "shared" => Ok(Self::Shared),
+            other => anyhow::bail!("TENANT_ISOLATION must be `schema` or `shared`, not `{other}`"),
+        }
+    }
+}
+
+/// Operations that copy, dump or replace a garderie's whole schema.
+#[derive(Debug, thiserror::Error)]
+#[error("Opération indisponible lorsque les garderies partagent un schéma (TENANT_ISOLATION=shared)")]
+pub struct SharedSchemaUnsupported;
+
+impl ErrorStatus for SharedSchemaUnsupported {
+    fn status(&self) -> StatusCode {
+        StatusCode::CONFLICT
+    }
+
+    fn code(&self) -> Option<&'static str> {
+        Some("shared_schema_unsupported")
+    }
+}
+
+static ISOLATION: OnceLock<TenantIsolation> = OnceLock::new();
+
+tokio::task_local! {
+    static TENANT: String;
+}
+
+/// The layer between the code and the tenant tables; see the module documentation.
+pub struct TenantDb;
+
+impl TenantDb {
+    /// Set the deployment's mode, before opening any pool. Processes that never call
+    /// it use per-garderie schemas.
+    pub fn init(isolation: TenantIsolation) -> anyhow::Result<()> {
+        let current = *ISOLATION.get_or_init(|| isolation);
+        anyhow::ensure!(current == isolation, "tenant isolation already set to {current:?}");
+        Ok(())
+    }
+
+    pub fn isolation() -> TenantIsolation {
+        ISOLATION.get().copied().unwrap_or_default()
+    }
+
+    pub fn is_shared() -> bool {
+        Self::isolation() == TenantIsolation::Shared
+    }
+
+    /// Schema of the garderie's tables.
+    pub fn schema(slug: &str) -> String {
+        match Self::isolation() {
+            TenantIsolation::Schema => format!("garderie_{}", slug.to_lowercase().replace('-', "_")),
+            TenantIsolation::Shared => SHARED_SCHEMA.to_string(),
+        }
+    }
+
+    /// Fails in shared mode, for the operations working on a whole tenant schema.
+    pub fn require_own_schema() -> Result<(), SharedSchemaUnsupported> {
+        match Self::isolation() {
+            TenantIsolation::Schema => Ok(()),
+            TenantIsolation::Shared => Err(SharedSchemaUnsupported),
+        }
+    }
+
+    /// Run `fut` on behalf of the garderie `slug`: in shared mode, the connections it
+    /// takes from a pool only see that garderie's rows. Tasks it spawns do not inherit
+    /// the scope and need their own.
+    pub fn scope<F: Future>(slug: impl Into<String>, fut: F) -> impl Future<Output = F::Output> {
+        TENANT.scope(slug.into(), fut)
+    }
+
+    /// Garderie of the enclosing [`scope`](Self::scope), if any.
+    pub fn current() -> Option<String> {
+        TENANT.try_with(Clone::clone).ok()
+    }
+
+    /// In shared mode, have the pool set `app.tenant_id` on each connection it hands
+    /// out, from the scope of the task taking it. Costs a round trip per checkout.
+    pub fn pool_options(options: PgPoolOptions) -> PgPoolOptions {
+        if !Self::is_shared() {
+            return options;
+        }
+        options
+            .after_connect(|conn, _| {
+                let slug = Self::current();
+                Box::pin(async move { set_tenant(conn, slug.as_deref()).await })
+            })
+            .before_acquire(|conn, _| {
+                let slug = Self::current();
+                Box::pin(async move { set_tenant(conn, slug.as_deref()).await.map(|_| true) })
+            })
+    }
+
+    /// Refuse shared mode to a database role that row-level security does not apply to.
+    pub async fn check_role(pool: &PgPool) -> anyhow::Result<()> {
+        if !Self::is_shared() {
+            return Ok(());
+        }
+        let (role, bypass): (String, bool) = sqlx::query_as(
+            "SELECT rolname::TEXT, rolsuper OR rolbypassrls FROM pg_roles WHERE rolname = current_user",
+        )
+        .fetch_one(pool)
+        .await?;
+        anyhow::ensure!(
+            !bypass,
+            "TENANT_ISOLATION=shared: role {role} bypasses row-level security (SUPERUSER or BYPASSRLS), use another one"
+        );
+        Ok(())
+    }
+}
+
+/// The garderie's id, or an empty setting that matches no row when it is unknown.
+async fn set_tenant(conn: &mut PgConnection, slug: Option<&str>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "SELECT set_config('app.tenant_id', COALESCE((SELECT id::TEXT FROM public.garderies WHERE slug = $1), ''), FALSE)",
+    )
+    .bind(slug)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::{scratch_pool, ScratchTenant};
+    use crate::db::tenant_migrations::migrate_schema;
+
+    #[test]
+    fn parses_the_isolation_mode() {
+        assert_eq!("schema".parse::<TenantIsolation>().unwrap(), TenantIsolation::Schema);
+        assert_eq!("shared".parse::<TenantIsolation>().unwrap(), TenantIsolation::Shared);
+        assert!("rls".parse::<TenantIsolation>().is_err());
+    }
+
+    #[tokio::test]
+    async fn scope_is_seen_by_the_future_only() {
+        assert_eq!(TenantDb::current(), None);
+        let inner = TenantDb::scope("alpha", async { TenantDb::current() }).await;
+        assert_eq!(inner.as_deref(), Some("alpha"));
+        assert_eq!(TenantDb::current(), None);
+    }
+
+    /// Two garderies in one scratch shared schema, queried as a role without
+    /// BYPASSRLS: each only sees and writes its own rows.
+    #[tokio::test]
+    #[ignore]
+    async fn shared_schema_keeps_garderies_apart() {
+        let pool = scratch_pool().await;
+        let schema = format!("tenant_shared_{}", uuid::Uuid::new_v4().simple().to_string().get(..8).unwrap());
+        let mut conn = pool.acquire().await.unwrap();
+        migrate_schema(&mut conn, &schema, TenantIsolation::Shared).await.unwrap();
+        drop(conn);
+        sqlx::raw_sql(&format!(
+            "DO $$ BEGIN
+               IF NOT EXISTS (SELECT 1 FROM pg_roles WHERE rolname = 'minispace_rls_test') THEN
+                 CREATE ROLE minispace_rls_test NOLOGIN NOSUPERUSER NOBYPASSRLS;
+               END IF;
+             END $$;
+             GRANT USAGE ON SCHEMA {schema} TO minispace_rls_test;
+             GRANT SELECT, INSERT, UPDATE, DELETE ON ALL TABLES IN SCHEMA {schema} TO minispace_rls_test"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let a = ScratchTenant::shared(&pool, "rlsa", &schema).await;
+        let b = ScratchTenant::shared(&pool, "rlsb", &schema).await;
+        // The same email in both: natural keys are unique per garderie
+        let alice = a.user("parent@example.com", "parent").await;
+        let bob = b.user("parent@example.com", "parent").await;
+
+        let mut tx = pool.begin().await.unwrap();
+        as_tenant(&mut tx, &a.slug).await;
+        let users: Vec<uuid::Uuid> = sqlx::query_scalar(&format!("SELECT id FROM {schema}.users"))
+            .fetch_all(&mut *tx)
+            .await
+            .unwrap();
+        assert_eq!(users, vec![alice]);
+        // Each got its own copy of the rows seeded by the migrations
+        let broadcast: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {schema}.threads WHERE key = 'broadcast'"))
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+        assert_eq!(broadcast, 1);
+        let updated = sqlx::query(&format!("UPDATE {schema}.users SET first_name = 'X' WHERE id = $1"))
+            .bind(bob)
+            .execute(&mut *tx)
+            .await
+            .unwrap()
+            .rows_affected();
+        assert_eq!(updated, 0);
+        tx.rollback().await.unwrap();
+
+        let b_id: uuid::Uuid = sqlx::query_scalar("SELECT id FROM public.garderies WHERE slug = $1")
+            .bind(&b.slug)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let mut tx = pool.begin().await.unwrap();
+        as_tenant(&mut tx, &a.slug).await;
+        let forged = sqlx::query(&format!(
+            "INSERT INTO {schema}.users (email, password_hash, first_name, last_name, tenant_id)
+             VALUES ('x@example.com', '-', 'X', 'X', $1)"
+        ))
+        .bind(b_id)
+        .execute(&mut *tx)
+        .await;
+        assert!(forged.is_err(), "row written into another garderie");
+        tx.rollback().await.unwrap();
+
+        // Outside of any garderie nothing is visible
+        let mut tx = pool.begin().await.unwrap();
+        sqlx::raw_sql("SET LOCAL ROLE minispace_rls_test").execute(&mut *tx).await.unwrap();
+        let seen: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {schema}.users"))
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+        assert_eq!(seen, 0);
+        tx.rollback().await.unwrap();
+
+        a.drop().await;
+        b.drop().await;
+        sqlx::raw_sql(&format!("DROP SCHEMA {schema} CASCADE")).execute(&pool).await.unwrap();
+    }
+
+    /// Act as garderie `slug` under the role of the test, until the end of the transaction.
+    async fn as_tenant(conn: &mut PgConnection, slug: &str) {
+        sqlx::query("SELECT set_config('app.tenant_id', (SELECT id::TEXT FROM public.garderies WHERE slug = $1), TRUE)")
+            .bind(slug)
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        sqlx::raw_sql("SET LOCAL ROLE minispace_rls_test").execute(&mut *conn).await.unwrap();
+    }
+}
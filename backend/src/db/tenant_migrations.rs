@@ -0,0 +1,318 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use sqlx::{Connection, Executor, PgConnection, PgPool};
+
+use super::tenant::{self, schema_name};
+use super::tenant_db::{TenantDb, TenantIsolation, SHARED_SCHEMA};
+
+/// A versioned change to every tenant schema. Applied versions are recorded in the
+/// schema's own `tenant_migrations` table, so each garderie is migrated independently.
+pub struct TenantMigration {
+    pub version: i64,
+    pub name: &'static str,
+    up: Up,
+}
+
+enum Up {
+    /// [`tenant::baseline`]
+    Baseline,
+    /// SQL script of `tenant_migrations/`, `{schema}` standing for the quoted schema name.
+    Sql(&'static str),
+}
+
+/// In order of version. Never edit a migration once released: add a new one.
+pub const MIGRATIONS: &[TenantMigration] = &[
+    TenantMigration { version: 1, name: "baseline", up: Up::Baseline },
+    TenantMigration {
+        version: 2,
+        name: "login_events",
+        up: Up::Sql(include_str!("../../tenant_migrations/0002_login_events.sql")),
+    },
+    TenantMigration {
+        version: 3,
+        name: "oidc_settings",
+        up: Up::Sql(include_str!("../../tenant_migrations/0003_oidc_settings.sql")),
+    },
+    TenantMigration {
+        version: 4,
+        name: "refresh_token_families",
+        up: Up::Sql(include_str!("../../tenant_migrations/0004_refresh_token_families.sql")),
+    },
+    TenantMigration {
+        version: 5,
+        name: "email_log",
+        up: Up::Sql(include_str!("../../tenant_migrations/0005_email_log.sql")),
+    },
+    TenantMigration {
+        version: 6,
+        name: "email_campaigns",
+        up: Up::Sql(include_str!("../../tenant_migrations/0006_email_campaigns.sql")),
+    },
+    TenantMigration {
+        version: 7,
+        name: "auto_absences",
+        up: Up::Sql(include_str!("../../tenant_migrations/0007_auto_absences.sql")),
+    },
+    TenantMigration {
+        version: 8,
+        name: "ratios",
+        up: Up::Sql(include_str!("../../tenant_migrations/0008_ratios.sql")),
+    },
+    TenantMigration {
+        version: 9,
+        name: "stats_rollups",
+        up: Up::Sql(include_str!("../../tenant_migrations/0009_stats_rollups.sql")),
+    },
+    TenantMigration {
+        version: 10,
+        name: "media_updated_at",
+        up: Up::Sql(include_str!("../../tenant_migrations/0010_media_updated_at.sql")),
+    },
+    TenantMigration {
+        version: 11,
+        name: "group_rollovers",
+        up: Up::Sql(include_str!("../../tenant_migrations/0011_group_rollovers.sql")),
+    },
+    TenantMigration {
+        version: 12,
+        name: "group_archive",
+        up: Up::Sql(include_str!("../../tenant_migrations/0012_group_archive.sql")),
+    },
+    TenantMigration {
+        version: 13,
+        name: "families",
+        up: Up::Sql(include_str!("../../tenant_migrations/0013_families.sql")),
+    },
+    TenantMigration {
+        version: 14,
+        name: "emergency_broadcasts",
+        up: Up::Sql(include_str!("../../tenant_migrations/0014_emergency_broadcasts.sql")),
+    },
+    TenantMigration {
+        version: 15,
+        name: "push_token_hygiene",
+        up: Up::Sql(include_str!("../../tenant_migrations/0015_push_token_hygiene.sql")),
+    },
+    TenantMigration {
+        version: 16,
+        name: "media_user_state",
+        up: Up::Sql(include_str!("../../tenant_migrations/0016_media_user_state.sql")),
+    },
+    TenantMigration {
+        version: 17,
+        name: "media_comments",
+        up: Up::Sql(include_str!("../../tenant_migrations/0017_media_comments.sql")),
+    },
+    TenantMigration {
+        version: 18,
+        name: "media_redactions",
+        up: Up::Sql(include_str!("../../tenant_migrations/0018_media_redactions.sql")),
+    },
+    TenantMigration {
+        version: 19,
+        name: "video_previews",
+        up: Up::Sql(include_str!("../../tenant_migrations/0019_video_previews.sql")),
+    },
+    TenantMigration {
+        version: 20,
+        name: "document_shares",
+        up: Up::Sql(include_str!("../../tenant_migrations/0020_document_shares.sql")),
+    },
+    TenantMigration {
+        version: 21,
+        name: "document_templates",
+        up: Up::Sql(include_str!("../../tenant_migrations/0021_document_templates.sql")),
+    },
+    TenantMigration {
+        version: 22,
+        name: "conversation_indexes",
+        up: Up::Sql(include_str!("../../tenant_migrations/0022_conversation_indexes.sql")),
+    },
+    TenantMigration {
+        version: 23,
+        name: "threads",
+        up: Up::Sql(include_str!("../../tenant_migrations/0023_threads.sql")),
+    },
+    TenantMigration {
+        version: 24,
+        name: "message_deliveries",
+        up: Up::Sql(include_str!("../../tenant_migrations/0024_message_deliveries.sql")),
+    },
+    TenantMigration {
+        version: 25,
+        name: "auto_replies",
+        up: Up::Sql(include_str!("../../tenant_migrations/0025_auto_replies.sql")),
+    },
+    TenantMigration {
+        version: 26,
+        name: "staff_threads",
+        up: Up::Sql(include_str!("../../tenant_migrations/0026_staff_threads.sql")),
+    },
+    TenantMigration {
+        version: 27,
+        name: "retention",
+        up: Up::Sql(include_str!("../../tenant_migrations/0027_retention.sql")),
+    },
+    TenantMigration {
+        version: 28,
+        name: "child_departures",
+        up: Up::Sql(include_str!("../../tenant_migrations/0028_child_departures.sql")),
+    },
+];
+
+/// A migration and when it was applied to a given tenant, `None` if pending.
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: &'static str,
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+/// Apply the pending migrations of one tenant, creating its schema if needed, and
+/// return the versions applied. In shared mode, every garderie has the same schema.
+pub async fn migrate_tenant(conn: &mut PgConnection, slug: &str) -> anyhow::Result<Vec<i64>> {
+    migrate_schema(conn, &schema_name(slug), TenantDb::isolation()).await
+}
+
+/// Apply the pending migrations of a tenant schema. Everything runs in one transaction
+/// (a savepoint when the caller already opened one) under an advisory lock on the
+/// schema, so two API instances starting together do not race and a failed migration
+/// leaves the schema as it was. A shared schema gets its row-level security brought
+/// up to date around them (see `add_shared_tenant_rls`).
+pub async fn migrate_schema(
+    conn: &mut PgConnection,
+    schema: &str,
+    isolation: TenantIsolation,
+) -> anyhow::Result<Vec<i64>> {
+    let mut tx = conn.begin().await?;
+
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('tenant_migrations:' || $1))")
+        .bind(schema)
+        .execute(&mut *tx)
+        .await?;
+    tx.execute(sqlx::raw_sql(&format!(
+        r#"CREATE SCHEMA IF NOT EXISTS "{schema}";
+        CREATE TABLE IF NOT EXISTS "{schema}".tenant_migrations (
+            version    BIGINT PRIMARY KEY,
+            name       TEXT NOT NULL,
+            applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        )"#
+    )))
+    .await?;
+
+    let done: Vec<i64> = sqlx::query_scalar(&format!(r#"SELECT version FROM "{schema}".tenant_migrations"#))
+        .fetch_all(&mut *tx)
+        .await?;
+    let rls = isolation == TenantIsolation::Shared && MIGRATIONS.iter().any(|m| !done.contains(&m.version));
+    if rls {
+        // The scripts act for no garderie: the rows they seed become templates
+        sqlx::query("SELECT public.suspend_tenant_rls($1), set_config('app.tenant_id', '', TRUE)")
+            .bind(schema)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let mut applied = Vec::new();
+    for migration in MIGRATIONS {
+        if done.contains(&migration.version) {
+            continue;
+        }
+        let result = match migration.up {
+            Up::Baseline => tenant::baseline(&mut tx, schema).await,
+            Up::Sql(sql) => tx
+                .execute(sqlx::raw_sql(&sql.replace("{schema}", schema)))
+                .await
+                .map(|_| ())
+                .map_err(Into::into),
+        };
+        result.with_context(|| format!("{schema}: migration {} ({})", migration.version, migration.name))?;
+        sqlx::query(&format!(r#"INSERT INTO "{schema}".tenant_migrations (version, name) VALUES ($1, $2)"#))
+            .bind(migration.version)
+            .bind(migration.name)
+            .execute(&mut *tx)
+            .await?;
+        applied.push(migration.version);
+    }
+
+    if rls {
+        sqlx::query("SELECT public.enable_tenant_rls($1)")
+            .bind(schema)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("{schema}: row-level security"))?;
+    }
+    tx.commit().await?;
+    if !applied.is_empty() {
+        tracing::info!("Migrated tenant schema {schema}: applied {applied:?}");
+    }
+    Ok(applied)
+}
+
+/// Apply pending migrations to every active garderie (called on startup), or to the
+/// shared schema.
+pub async fn migrate_all_tenants(pool: &PgPool) -> anyhow::Result<()> {
+    let mut conn = pool.acquire().await?;
+    if TenantDb::is_shared() {
+        migrate_schema(&mut conn, SHARED_SCHEMA, TenantIsolation::Shared).await?;
+        return Ok(());
+    }
+
+    let slugs: Vec<String> = sqlx::query_scalar("SELECT slug FROM garderies WHERE is_active = TRUE ORDER BY slug")
+        .fetch_all(&mut *conn)
+        .await?;
+    for slug in slugs {
+        migrate_tenant(&mut conn, &slug).await?;
+    }
+    Ok(())
+}
+
+/// Every known migration with its state for one tenant, without applying anything.
+pub async fn status(pool: &PgPool, slug: &str) -> anyhow::Result<Vec<MigrationStatus>> {
+    let schema = schema_name(slug);
+    let tracked: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM information_schema.tables WHERE table_schema = $1 AND table_name = 'tenant_migrations')",
+    )
+    .bind(&schema)
+    .fetch_one(pool)
+    .await?;
+
+    let done: Vec<(i64, DateTime<Utc>)> = if tracked {
+        sqlx::query_as(&format!(r#"SELECT version, applied_at FROM "{schema}".tenant_migrations"#))
+            .fetch_all(pool)
+            .await?
+    } else {
+        Vec::new()
+    };
+
+    Ok(MIGRATIONS
+        .iter()
+        .map(|m| MigrationStatus {
+            version: m.version,
+            name: m.name,
+            applied_at: done.iter().find(|(v, _)| *v == m.version).map(|(_, at)| *at),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_are_unique_and_increasing() {
+        assert_eq!(MIGRATIONS[0].version, 1);
+        assert!(matches!(MIGRATIONS[0].up, Up::Baseline));
+        for pair in MIGRATIONS.windows(2) {
+            assert!(pair[0].version < pair[1].version, "{} before {}", pair[0].name, pair[1].name);
+        }
+    }
+
+    #[test]
+    fn scripts_name_the_schema_through_the_placeholder() {
+        for m in MIGRATIONS {
+            if let Up::Sql(sql) = m.up {
+                assert!(sql.contains(r#""{schema}"."#), "{}", m.name);
+                assert!(!sql.contains("garderie_"), "{}", m.name);
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Scratch tenants for the `#[ignore]`d tests that need Postgres. Run them with
+//! `TEST_DATABASE_URL=postgres://… cargo test -- --ignored`.
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::tenant::{provision_tenant_schema, schema_name};
+
+/// Pool on `TEST_DATABASE_URL` with the public migrations applied.
+pub async fn scratch_pool() -> PgPool {
+    let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL");
+    let pool = PgPool::connect(&url).await.unwrap();
+    super::run_migrations(&pool).await.unwrap();
+    pool
+}
+
+/// A scratch tenant on a new pool, see `ScratchTenant::provision`.
+pub async fn scratch_tenant(prefix: &str) -> ScratchTenant {
+    ScratchTenant::provision(&scratch_pool().await, prefix).await
+}
+
+/// A freshly provisioned tenant schema under a unique slug.
+pub struct ScratchTenant {
+    pub pool: PgPool,
+    pub slug: String,
+    pub schema: String,
+    /// `false` for a garderie of a shared schema, see [`ScratchTenant::shared`].
+    pub own_schema: bool,
+}
+
+fn scratch_slug(prefix: &str) -> String {
+    format!("{prefix}{}", Uuid::new_v4().simple().to_string().get(..8).unwrap())
+}
+
+impl ScratchTenant {
+    /// Provision `{prefix}` followed by 8 random hex digits.
+    pub async fn provision(pool: &PgPool, prefix: &str) -> Self {
+        let slug = scratch_slug(prefix);
+        provision_tenant_schema(pool, &slug).await.unwrap();
+        let schema = schema_name(&slug);
+        Self { pool: pool.clone(), slug, schema, own_schema: true }
+    }
+
+    /// A registered garderie seeded in `schema`, a shared schema already migrated
+    /// with [`migrate_schema`](super::tenant_migrations::migrate_schema).
+    pub async fn shared(pool: &PgPool, prefix: &str, schema: &str) -> Self {
+        let tenant = Self { pool: pool.clone(), slug: scratch_slug(prefix), schema: schema.to_string(), own_schema: false };
+        tenant.register().await;
+        sqlx::query("SELECT public.seed_tenant($1, $2)")
+            .bind(schema)
+            .bind(&tenant.slug)
+            .execute(pool)
+            .await
+            .unwrap();
+        tenant
+    }
+
+    /// Add the garderie to `public.garderies`, for code that checks it exists.
+    pub async fn register(&self) {
+        sqlx::query("INSERT INTO public.garderies (slug, name) VALUES ($1, $1)")
+            .bind(&self.slug)
+            .execute(&self.pool)
+            .await
+            .unwrap();
+    }
+
+    /// Insert a user whose last name is their email; returns their id.
+    pub async fn user(&self, email: &str, role: &str) -> Uuid {
+        let schema = &self.schema;
+        let mut tx = self.pool.begin().await.unwrap();
+        if !self.own_schema {
+            sqlx::query("SELECT set_config('app.tenant_id', (SELECT id::TEXT FROM public.garderies WHERE slug = $1), TRUE)")
+                .bind(&self.slug)
+                .execute(&mut *tx)
+                .await
+                .unwrap();
+        }
+        let id = sqlx::query_scalar(&format!(
+            "INSERT INTO {schema}.users (email, password_hash, first_name, last_name, role)
+             VALUES ($1, '-', 'Test', $1, $2::{schema}.user_role) RETURNING id"
+        ))
+        .bind(email)
+        .bind(role)
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+        tx.commit().await.unwrap();
+        id
+    }
+
+    /// Drop the schema, and the garderie if it was registered (with its rows of a
+    /// shared schema).
+    pub async fn drop(self) {
+        if self.own_schema {
+            sqlx::raw_sql(&format!("DROP SCHEMA {} CASCADE", self.schema)).execute(&self.pool).await.unwrap();
+        }
+        sqlx::query("DELETE FROM public.garderies WHERE slug = $1").bind(&self.slug).execute(&self.pool).await.unwrap();
+    }
+}
@@ -0,0 +1,614 @@
+//! Error responses of the API.
+//!
+//! Handlers return [`ApiError`], which renders an RFC 7807 `application/problem+json`
+//! body. The `error` member repeats `detail` for clients written against the older
+//! `{"error": …}` bodies, and `key` names the message in
+//! [`crate::services::error_i18n::MESSAGES`] when it is catalogued.
+//!
+//! Service errors are typed (thiserror enums) and carry their status through
+//! [`ErrorStatus`]; anything else reaching a handler as `anyhow::Error` is a 500 whose
+//! cause is logged, not sent.
+
+use async_graphql::ErrorExtensions;
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde_json::{json, Map, Value};
+
+use crate::db::tenant_db::SharedSchemaUnsupported;
+use crate::middleware::precondition::{self, StaleVersion};
+use crate::services::{
+    absences::AbsenceError,
+    albums::InvalidAlbum,
+    auth::{AuthError, InvitationError, RefreshTokenReused},
+    auto_absences::AutoAbsenceError,
+    auto_replies::AutoReplyError,
+    backups::BackupError,
+    branding::InvalidBranding,
+    child_transfers::TransferError,
+    children::ImportError,
+    cloning::CloneError,
+    departures::DepartureError,
+    development::InvalidObservation,
+    document_shares::DocumentShareError,
+    documents::DocumentError,
+    email_log::EmailLogError,
+    email_suppressions::EmailWebhookError,
+    emergency::EmergencyError,
+    entitlements::EntitlementError,
+    email_templates::TemplateError,
+    erasure::ErasureError,
+    error_i18n::by_key,
+    families::FamilyError,
+    feature_flags::FeatureFlagError,
+    groups::{GroupError, OutOfScope, RolloverError},
+    invoices::InvoiceError,
+    journal::{JournalAmendError, JournalEventError, JournalSendError},
+    media::{InvalidCursor, UnsupportedImage},
+    media_comments::MediaCommentError,
+    meetings::MeetingError,
+    menu::InvalidMenu,
+    message_drafts::DraftError,
+    messages::{MessageChangeError, MessageSendError},
+    oidc::OidcError,
+    operations::OperationError,
+    password_policy::PasswordRejected,
+    ratios::RatioError,
+    reactions::ReactionError,
+    redaction::RedactionError,
+    retention::RetentionError,
+    search::SearchError,
+    stats::StatsError,
+    storage::QuotaExceeded,
+    tax_receipts::TaxReceiptError,
+    thread_states::UnknownThread,
+    upload_types::UploadRejected,
+    waitlist::WaitlistError,
+};
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// HTTP status of a typed service error.
+pub trait ErrorStatus: std::error::Error {
+    fn status(&self) -> StatusCode;
+
+    /// Machine-readable code for the client, when the variant needs one.
+    fn code(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Catalog key of the message, for the variants clients see translated.
+    fn key(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub detail: String,
+    pub key: Option<&'static str>,
+    pub code: Option<&'static str>,
+    /// Field-level details (`{"field": [{"code", "message"}]}`) of a 422.
+    pub errors: Option<Value>,
+    /// Further members the client acts on, e.g. the current version of a 409.
+    pub extensions: Map<String, Value>,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, detail: impl Into<String>) -> Self {
+        Self { status, detail: detail.into(), key: None, code: None, errors: None, extensions: Map::new() }
+    }
+
+    /// Error whose text comes from the message catalog.
+    pub fn from_key(status: StatusCode, key: &'static str) -> Self {
+        let detail = by_key(key).map_or(key, |m| m.fr);
+        Self { key: Some(key), ..Self::new(status, detail) }
+    }
+
+    pub fn forbidden() -> Self {
+        Self::from_key(StatusCode::FORBIDDEN, "forbidden")
+    }
+
+    pub fn not_found(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, detail)
+    }
+
+    pub fn conflict(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::CONFLICT, detail)
+    }
+
+    pub fn unprocessable(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNPROCESSABLE_ENTITY, detail)
+    }
+
+    pub fn internal() -> Self {
+        Self::from_key(StatusCode::INTERNAL_SERVER_ERROR, "internal_error")
+    }
+
+    pub fn with_errors(mut self, errors: Value) -> Self {
+        self.errors = Some(errors);
+        self
+    }
+
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_extension(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.extensions.insert(name.to_string(), value.into());
+        self
+    }
+
+    fn typed<E: ErrorStatus + ?Sized>(e: &E) -> Self {
+        Self { code: e.code(), key: e.key(), ..Self::new(e.status(), e.to_string()) }
+    }
+
+    /// The RFC 7807 body.
+    pub fn body(&self) -> Value {
+        let mut body = json!({
+            "type": "about:blank",
+            "title": self.status.canonical_reason().unwrap_or("Error"),
+            "status": self.status.as_u16(),
+            "detail": self.detail,
+            "error": self.detail,
+        });
+        if let Some(key) = self.key {
+            body["key"] = key.into();
+        }
+        if let Some(code) = self.code {
+            body["code"] = code.into();
+        }
+        if let Some(errors) = &self.errors {
+            body["errors"] = errors.clone();
+        }
+        for (name, value) in &self.extensions {
+            body[name] = value.clone();
+        }
+        body
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, [(header::CONTENT_TYPE, PROBLEM_JSON)], self.body().to_string()).into_response()
+    }
+}
+
+impl<E: ErrorStatus> From<E> for ApiError {
+    fn from(e: E) -> Self {
+        Self::typed(&e)
+    }
+}
+
+/// Typed errors are recognised through the `anyhow` chain; whatever else is internal.
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        macro_rules! typed {
+            ($($ty:ty),* $(,)?) => {
+                $(if let Some(typed) = e.downcast_ref::<$ty>() {
+                    return Self::typed(typed);
+                })*
+            };
+        }
+        typed!(
+            AuthError, InvitationError, RefreshTokenReused, PasswordRejected, AbsenceError, AutoAbsenceError, BackupError,
+            CloneError, DepartureError, DocumentError, DocumentShareError, EmailLogError, EmergencyError, EntitlementError, TemplateError,
+            ErasureError, FamilyError,
+            FeatureFlagError, InvoiceError, JournalAmendError, JournalEventError, JournalSendError, MediaCommentError,
+            MeetingError, DraftError,
+            MessageChangeError, OidcError, OperationError, RatioError, ReactionError, RedactionError, RetentionError,
+            SearchError,
+            GroupError, RolloverError, StatsError, TaxReceiptError, TransferError, UploadRejected, WaitlistError,
+            OutOfScope, InvalidAlbum, InvalidBranding, InvalidCursor, InvalidMenu, InvalidObservation, UnsupportedImage,
+            EmailWebhookError, MessageSendError, UnknownThread, AutoReplyError, ImportError, SharedSchemaUnsupported,
+        );
+        if let Some(stale) = e.downcast_ref::<StaleVersion>() {
+            return precondition::conflict(stale);
+        }
+        if let Some(quota) = e.downcast_ref::<QuotaExceeded>() {
+            return quota.into();
+        }
+        if let Some(db) = e.downcast_ref::<sqlx::Error>() {
+            if let Some(mapped) = database_error(db) {
+                return mapped;
+            }
+        }
+        tracing::error!("Unhandled error: {e:#}");
+        Self::internal()
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(e: sqlx::Error) -> Self {
+        database_error(&e).unwrap_or_else(|| {
+            tracing::error!("Database error: {e}");
+            Self::internal()
+        })
+    }
+}
+
+/// GraphQL resolvers answer with the same message and code as the REST routes.
+impl From<ApiError> for async_graphql::Error {
+    fn from(e: ApiError) -> Self {
+        let status = e.status.as_u16();
+        let code = e.code.or(e.key);
+        async_graphql::Error::new(e.detail).extend_with(|_, ext| {
+            ext.set("status", status);
+            if let Some(code) = code {
+                ext.set("code", code);
+            }
+        })
+    }
+}
+
+/// Uploads over the plan's storage; the client shows how much is used.
+impl From<&QuotaExceeded> for ApiError {
+    fn from(q: &QuotaExceeded) -> Self {
+        Self::from_key(StatusCode::PAYLOAD_TOO_LARGE, "storage_quota_reached")
+            .with_code("storage_quota_exceeded")
+        .with_extension("used_bytes", q.used_bytes)
+        .with_extension("quota_bytes", q.quota_bytes)
+    }
+}
+
+/// Constraint violations are the client's doing: a row not found, a duplicate, a
+/// reference to something missing.
+fn database_error(e: &sqlx::Error) -> Option<ApiError> {
+    match e {
+        sqlx::Error::RowNotFound => Some(ApiError::from_key(StatusCode::NOT_FOUND, "not_found")),
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            Some(ApiError::from_key(StatusCode::CONFLICT, "already_exists"))
+        }
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            Some(ApiError::from_key(StatusCode::UNPROCESSABLE_ENTITY, "invalid_reference"))
+        }
+        sqlx::Error::Database(db) if db.is_check_violation() => {
+            Some(ApiError::from_key(StatusCode::UNPROCESSABLE_ENTITY, "out_of_range"))
+        }
+        _ => None,
+    }
+}
+
+macro_rules! statuses {
+    ($ty:ty, |$e:ident| $body:expr $(, key |$k:ident| $key:expr)?) => {
+        impl ErrorStatus for $ty {
+            fn status(&self) -> StatusCode {
+                let $e = self;
+                $body
+            }
+            $(
+                fn key(&self) -> Option<&'static str> {
+                    let $k = self;
+                    $key
+                }
+            )?
+        }
+    };
+}
+
+statuses!(AuthError, |e| match e {
+    AuthError::TenantNotFound | AuthError::UserNotFound | AuthError::NoLinkedAccount => StatusCode::NOT_FOUND,
+    AuthError::InvalidCredentials
+    | AuthError::InvalidCode
+    | AuthError::CodeExpired
+    | AuthError::InvalidRefreshToken
+    | AuthError::RefreshTokenExpired => StatusCode::UNAUTHORIZED,
+    AuthError::WrongPassword | AuthError::WrongCurrentPassword => StatusCode::FORBIDDEN,
+    AuthError::TooManyCodeAttempts => StatusCode::TOO_MANY_REQUESTS,
+    AuthError::TwoFactorUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+    AuthError::TwoFactorSendFailed(_) | AuthError::InvitationSendFailed(_) => StatusCode::BAD_GATEWAY,
+    AuthError::InvalidResetToken | AuthError::InvalidInvitation => StatusCode::BAD_REQUEST,
+    AuthError::InvitationExpired => StatusCode::GONE,
+    AuthError::EmailTaken => StatusCode::CONFLICT,
+    AuthError::InvalidPhone => StatusCode::UNPROCESSABLE_ENTITY,
+}, key |e| match e {
+    AuthError::TenantNotFound => Some("unknown_garderie"),
+    AuthError::InvalidCredentials => Some("invalid_credentials"),
+    AuthError::TwoFactorUnavailable => Some("two_factor_unavailable"),
+    AuthError::CodeExpired => Some("two_factor_code_expired"),
+    AuthError::TooManyCodeAttempts => Some("two_factor_too_many_attempts"),
+    AuthError::InvalidCode => Some("two_factor_invalid_code"),
+    AuthError::InvalidRefreshToken => Some("session_invalid"),
+    AuthError::RefreshTokenExpired => Some("session_expired"),
+    AuthError::NoLinkedAccount => Some("no_linked_account"),
+    AuthError::InvalidResetToken => Some("reset_token_invalid"),
+    AuthError::InvalidInvitation => Some("invitation_invalid"),
+    AuthError::InvitationExpired => Some("invitation_expired"),
+    AuthError::UserNotFound => Some("user_not_found"),
+    AuthError::WrongCurrentPassword => Some("wrong_current_password"),
+    AuthError::WrongPassword => Some("wrong_password"),
+    AuthError::EmailTaken => Some("email_taken"),
+    AuthError::InvalidPhone => Some("invalid_phone"),
+    AuthError::TwoFactorSendFailed(_) | AuthError::InvitationSendFailed(_) => None,
+});
+impl ErrorStatus for RefreshTokenReused {
+    fn status(&self) -> StatusCode {
+        StatusCode::UNAUTHORIZED
+    }
+
+    fn code(&self) -> Option<&'static str> {
+        Some("refresh_token_reused")
+    }
+}
+statuses!(InvitationError, |e| match e {
+    InvitationError::NotFound => StatusCode::NOT_FOUND,
+    InvitationError::EmailUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+});
+impl ErrorStatus for PasswordRejected {
+    fn status(&self) -> StatusCode {
+        StatusCode::UNPROCESSABLE_ENTITY
+    }
+
+    fn code(&self) -> Option<&'static str> {
+        Some(PasswordRejected::code(self))
+    }
+}
+statuses!(AbsenceError, |e| match e {
+    AbsenceError::ChildNotFound => StatusCode::NOT_FOUND,
+    _ => StatusCode::UNPROCESSABLE_ENTITY,
+});
+statuses!(AutoAbsenceError, |e| match e {
+    AutoAbsenceError::NotFound => StatusCode::NOT_FOUND,
+    AutoAbsenceError::Reconciled => StatusCode::CONFLICT,
+});
+statuses!(BackupError, |e| match e {
+    BackupError::NotConfigured => StatusCode::SERVICE_UNAVAILABLE,
+    BackupError::GarderieNotFound | BackupError::BackupNotFound => StatusCode::NOT_FOUND,
+    BackupError::ChecksumMismatch(_) => StatusCode::UNPROCESSABLE_ENTITY,
+}, key |e| matches!(e, BackupError::GarderieNotFound).then_some("tenant_not_found"));
+statuses!(CloneError, |e| match e {
+    CloneError::SourceNotFound => StatusCode::NOT_FOUND,
+    CloneError::SlugTaken => StatusCode::CONFLICT,
+    CloneError::UnknownPart(_) => StatusCode::UNPROCESSABLE_ENTITY,
+});
+statuses!(TransferError, |e| match e {
+    TransferError::UnknownGarderie(_) | TransferError::ChildNotFound => StatusCode::NOT_FOUND,
+    TransferError::SameGarderie | TransferError::NoTargetAdmin => StatusCode::UNPROCESSABLE_ENTITY,
+});
+statuses!(DocumentError, |e| match e {
+    DocumentError::FolderNotFound => StatusCode::NOT_FOUND,
+    DocumentError::DuplicateFolder | DocumentError::AlreadySigned => StatusCode::CONFLICT,
+    _ => StatusCode::UNPROCESSABLE_ENTITY,
+});
+statuses!(DocumentShareError, |e| match e {
+    DocumentShareError::DocumentNotFound | DocumentShareError::NotFound => StatusCode::NOT_FOUND,
+    DocumentShareError::Expired | DocumentShareError::Revoked => StatusCode::GONE,
+    DocumentShareError::Quarantined => StatusCode::FORBIDDEN,
+    DocumentShareError::TooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+    _ => StatusCode::UNPROCESSABLE_ENTITY,
+});
+impl ErrorStatus for EntitlementError {
+    fn status(&self) -> StatusCode {
+        StatusCode::PAYMENT_REQUIRED
+    }
+
+    fn code(&self) -> Option<&'static str> {
+        Some("plan_limit_reached")
+    }
+}
+statuses!(UploadRejected, |e| match e {
+    UploadRejected::VideoTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+    _ => StatusCode::UNPROCESSABLE_ENTITY,
+});
+statuses!(EmailLogError, |e| match e {
+    EmailLogError::NotFound => StatusCode::NOT_FOUND,
+    EmailLogError::NotResendable | EmailLogError::Suppressed => StatusCode::CONFLICT,
+});
+statuses!(TemplateError, |e| match e {
+    TemplateError::Invalid(_) => StatusCode::UNPROCESSABLE_ENTITY,
+    TemplateError::AlreadyExists => StatusCode::CONFLICT,
+});
+statuses!(EmergencyError, |e| match e {
+    EmergencyError::NotFound => StatusCode::NOT_FOUND,
+});
+statuses!(ErasureError, |e| match e {
+    ErasureError::NotFound => StatusCode::NOT_FOUND,
+    ErasureError::AlreadyErased => StatusCode::CONFLICT,
+}, key |e| matches!(e, ErasureError::NotFound).then_some("user_not_found"));
+statuses!(FamilyError, |e| match e {
+    FamilyError::NotFound | FamilyError::ChildNotFound => StatusCode::NOT_FOUND,
+});
+statuses!(FeatureFlagError, |e| match e {
+    FeatureFlagError::UnknownGarderie | FeatureFlagError::NotFound => StatusCode::NOT_FOUND,
+    FeatureFlagError::InvalidKey | FeatureFlagError::MaintenancePerGarderie => StatusCode::UNPROCESSABLE_ENTITY,
+}, key |e| matches!(e, FeatureFlagError::UnknownGarderie).then_some("tenant_not_found"));
+statuses!(InvoiceError, |e| match e {
+    InvoiceError::NotFound | InvoiceError::ChildNotFound => StatusCode::NOT_FOUND,
+    InvoiceError::NotDraft | InvoiceError::AlreadyPaid | InvoiceError::Voided => StatusCode::CONFLICT,
+    _ => StatusCode::UNPROCESSABLE_ENTITY,
+});
+impl ErrorStatus for JournalAmendError {
+    fn status(&self) -> StatusCode {
+        match self {
+            JournalAmendError::NotFound => StatusCode::NOT_FOUND,
+            JournalAmendError::AlreadySent | JournalAmendError::NotSent => StatusCode::CONFLICT,
+            JournalAmendError::NoteRequired => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+
+    /// The client switches to the amend flow on this code.
+    fn code(&self) -> Option<&'static str> {
+        matches!(self, JournalAmendError::AlreadySent).then_some("journal_sent")
+    }
+}
+statuses!(JournalEventError, |e| match e {
+    JournalEventError::NotFound => StatusCode::NOT_FOUND,
+    _ => StatusCode::UNPROCESSABLE_ENTITY,
+});
+statuses!(JournalSendError, |e| match e {
+    JournalSendError::ChildNotFound => StatusCode::NOT_FOUND,
+    _ => StatusCode::UNPROCESSABLE_ENTITY,
+});
+statuses!(MediaCommentError, |e| match e {
+    MediaCommentError::MediaNotFound | MediaCommentError::NotFound => StatusCode::NOT_FOUND,
+    MediaCommentError::NotAParent | MediaCommentError::NotAuthor => StatusCode::FORBIDDEN,
+    MediaCommentError::Empty => StatusCode::UNPROCESSABLE_ENTITY,
+});
+statuses!(MeetingError, |e| match e {
+    MeetingError::SlotNotFound | MeetingError::BookingNotFound => StatusCode::NOT_FOUND,
+    MeetingError::SlotTaken | MeetingError::SlotBooked | MeetingError::Overlap => StatusCode::CONFLICT,
+    MeetingError::NotStaff => StatusCode::FORBIDDEN,
+    _ => StatusCode::UNPROCESSABLE_ENTITY,
+});
+statuses!(DraftError, |e| match e {
+    DraftError::NotFound => StatusCode::NOT_FOUND,
+    DraftError::Forbidden => StatusCode::FORBIDDEN,
+    DraftError::Invalid(_) => StatusCode::UNPROCESSABLE_ENTITY,
+});
+impl ErrorStatus for MessageChangeError {
+    fn status(&self) -> StatusCode {
+        match self {
+            MessageChangeError::NotFound => StatusCode::NOT_FOUND,
+            MessageChangeError::NotSender => StatusCode::FORBIDDEN,
+            MessageChangeError::WindowExpired => StatusCode::CONFLICT,
+        }
+    }
+
+    fn code(&self) -> Option<&'static str> {
+        matches!(self, MessageChangeError::WindowExpired).then_some("edit_window_expired")
+    }
+
+    fn key(&self) -> Option<&'static str> {
+        matches!(self, MessageChangeError::NotFound).then_some("message_not_found")
+    }
+}
+statuses!(OidcError, |e| match e {
+    OidcError::NotConfigured => StatusCode::NOT_FOUND,
+    OidcError::InvalidSettings(_) => StatusCode::UNPROCESSABLE_ENTITY,
+    OidcError::Provider(_) => StatusCode::BAD_GATEWAY,
+    OidcError::InvalidState => StatusCode::BAD_REQUEST,
+    _ => StatusCode::FORBIDDEN,
+});
+statuses!(OperationError, |e| match e {
+    OperationError::NotFound => StatusCode::NOT_FOUND,
+    OperationError::NotPending | OperationError::AlreadyPending => StatusCode::CONFLICT,
+    OperationError::OperatorRequired
+    | OperationError::RequesterRequired
+    | OperationError::SameOperator
+    | OperationError::UnidentifiedRequester => StatusCode::FORBIDDEN,
+});
+statuses!(RatioError, |e| match e {
+    RatioError::ShiftNotFound => StatusCode::NOT_FOUND,
+    _ => StatusCode::UNPROCESSABLE_ENTITY,
+});
+statuses!(RedactionError, |e| match e {
+    RedactionError::MediaNotFound | RedactionError::NotFound => StatusCode::NOT_FOUND,
+    RedactionError::DetectionDisabled => StatusCode::SERVICE_UNAVAILABLE,
+    _ => StatusCode::UNPROCESSABLE_ENTITY,
+});
+statuses!(ReactionError, |e| match e {
+    ReactionError::NotFound => StatusCode::NOT_FOUND,
+    ReactionError::UnsupportedEmoji => StatusCode::UNPROCESSABLE_ENTITY,
+}, key |e| matches!(e, ReactionError::NotFound).then_some("message_not_found"));
+statuses!(RetentionError, |_e| StatusCode::NOT_FOUND);
+
+statuses!(DepartureError, |e| match e {
+    DepartureError::ChildNotFound | DepartureError::NotScheduled | DepartureError::ExportNotFound => StatusCode::NOT_FOUND,
+    DepartureError::AlreadyDeparted | DepartureError::NotDeparted => StatusCode::CONFLICT,
+    DepartureError::AccessEnded | DepartureError::ExportExpired => StatusCode::GONE,
+});
+statuses!(SearchError, |_e| StatusCode::UNPROCESSABLE_ENTITY);
+impl ErrorStatus for GroupError {
+    fn status(&self) -> StatusCode {
+        match self {
+            GroupError::NotFound => StatusCode::NOT_FOUND,
+            _ => StatusCode::CONFLICT,
+        }
+    }
+
+    /// The composer locks read-only threads on this code.
+    fn code(&self) -> Option<&'static str> {
+        matches!(self, GroupError::Archived).then_some("group_archived")
+    }
+}
+statuses!(RolloverError, |_e| StatusCode::UNPROCESSABLE_ENTITY);
+statuses!(StatsError, |_e| StatusCode::UNPROCESSABLE_ENTITY);
+statuses!(TaxReceiptError, |e| match e {
+    TaxReceiptError::NotFound => StatusCode::NOT_FOUND,
+    TaxReceiptError::InvalidYear => StatusCode::UNPROCESSABLE_ENTITY,
+}, key |e| matches!(e, TaxReceiptError::InvalidYear).then_some("invalid_year"));
+statuses!(WaitlistError, |e| match e {
+    WaitlistError::NotFound => StatusCode::NOT_FOUND,
+    WaitlistError::AlreadyEnrolled => StatusCode::CONFLICT,
+});
+statuses!(OutOfScope, |_e| StatusCode::FORBIDDEN);
+statuses!(InvalidAlbum, |_e| StatusCode::UNPROCESSABLE_ENTITY);
+statuses!(InvalidBranding, |_e| StatusCode::UNPROCESSABLE_ENTITY);
+statuses!(InvalidCursor, |_e| StatusCode::UNPROCESSABLE_ENTITY);
+statuses!(InvalidMenu, |_e| StatusCode::UNPROCESSABLE_ENTITY);
+statuses!(InvalidObservation, |_e| StatusCode::UNPROCESSABLE_ENTITY);
+statuses!(MessageSendError, |_e| StatusCode::UNPROCESSABLE_ENTITY);
+statuses!(ImportError, |_e| StatusCode::UNPROCESSABLE_ENTITY);
+statuses!(UnknownThread, |_e| StatusCode::UNPROCESSABLE_ENTITY);
+statuses!(AutoReplyError, |_e| StatusCode::UNPROCESSABLE_ENTITY);
+statuses!(UnsupportedImage, |_e| StatusCode::UNSUPPORTED_MEDIA_TYPE);
+statuses!(EmailWebhookError, |e| match e {
+    EmailWebhookError::UnknownProvider => StatusCode::NOT_FOUND,
+    EmailWebhookError::InvalidPayload => StatusCode::BAD_REQUEST,
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_problem_body() {
+        let body = ApiError::forbidden().body();
+        assert_eq!(body["status"], 403);
+        assert_eq!(body["title"], "Forbidden");
+        assert_eq!(body["detail"], "Accès refusé");
+        assert_eq!(body["error"], body["detail"]);
+        assert_eq!(body["key"], "forbidden");
+        assert!(body.get("code").is_none());
+    }
+
+    #[test]
+    fn test_typed_errors_keep_their_status_through_anyhow() {
+        let e: ApiError = anyhow::Error::from(RatioError::ShiftNotFound).into();
+        assert_eq!(e.status, StatusCode::NOT_FOUND);
+        assert_eq!(e.detail, "Quart introuvable");
+
+        let e: ApiError = anyhow::Error::from(AuthError::EmailTaken).into();
+        assert_eq!(e.status, StatusCode::CONFLICT);
+
+        let e: ApiError = sqlx::Error::RowNotFound.into();
+        assert_eq!(e.status, StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_typed_errors_carry_their_catalog_key() {
+        let e = ApiError::from(AuthError::WrongPassword);
+        assert_eq!(e.key, Some("wrong_password"));
+        assert_eq!(e.body()["key"], "wrong_password");
+        assert!(ApiError::from(AuthError::TwoFactorSendFailed("smtp".into())).key.is_none());
+        for e in [
+            ApiError::from(AuthError::TenantNotFound),
+            ApiError::from(BackupError::GarderieNotFound),
+            ApiError::from(ErasureError::NotFound),
+            ApiError::from(MessageChangeError::NotFound),
+            ApiError::from(TaxReceiptError::InvalidYear),
+        ] {
+            assert!(by_key(e.key.unwrap()).is_some());
+        }
+    }
+
+    #[test]
+    fn test_unknown_errors_are_hidden() {
+        let e: ApiError = anyhow::anyhow!("connection refused to 10.0.0.3").into();
+        assert_eq!(e.status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(e.detail, "Erreur interne du serveur");
+    }
+
+    #[test]
+    fn test_quota_reports_usage() {
+        let e: ApiError = anyhow::Error::from(QuotaExceeded { used_bytes: 1, quota_bytes: 2 }).into();
+        assert_eq!(e.status, StatusCode::PAYLOAD_TOO_LARGE);
+        let body = e.body();
+        assert_eq!(body["code"], "storage_quota_exceeded");
+        assert_eq!(body["used_bytes"], 1);
+        assert_eq!(body["quota_bytes"], 2);
+    }
+}
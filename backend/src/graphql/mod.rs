@@ -0,0 +1,63 @@
+//! Read-only GraphQL API served at POST /graphql next to the REST routes.
+//!
+//! Resolvers call the same services as the REST handlers and apply the same role
+//! rules, so a client can fetch e.g. the whole parent home screen in one round trip.
+
+mod query;
+
+use std::sync::OnceLock;
+
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Guard, Schema};
+
+use crate::{
+    models::{auth::AuthenticatedUser, user::UserRole},
+    AppState,
+};
+
+pub use query::QueryRoot;
+
+pub type AppSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Per-request data: the caller and the garderie the request was made for.
+#[derive(Clone)]
+pub struct GqlContext {
+    pub state: AppState,
+    pub tenant: String,
+    pub user: AuthenticatedUser,
+}
+
+pub const STAFF: &[UserRole] = &[UserRole::SuperAdmin, UserRole::AdminGarderie, UserRole::Educateur];
+pub const ADMIN: &[UserRole] = &[UserRole::SuperAdmin, UserRole::AdminGarderie];
+
+/// Field guard: only the listed roles may resolve the field.
+pub struct RoleGuard {
+    roles: &'static [UserRole],
+}
+
+impl RoleGuard {
+    pub fn new(roles: &'static [UserRole]) -> Self {
+        Self { roles }
+    }
+}
+
+impl Guard for RoleGuard {
+    async fn check(&self, ctx: &Context<'_>) -> async_graphql::Result<()> {
+        let gql = ctx.data::<GqlContext>()?;
+        if self.roles.contains(&gql.user.role) {
+            Ok(())
+        } else {
+            Err("Accès refusé".into())
+        }
+    }
+}
+
+/// The schema is stateless (request data carries the context), so it is built once.
+pub fn schema() -> &'static AppSchema {
+    static SCHEMA: OnceLock<AppSchema> = OnceLock::new();
+    SCHEMA.get_or_init(|| {
+        Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+            .limit_depth(8)
+            .limit_complexity(500)
+            .finish()
+    })
+}
@@ -0,0 +1,184 @@
+use async_graphql::{Context, Object, Result, SimpleObject};
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+use super::{GqlContext, RoleGuard, ADMIN, STAFF};
+use crate::{
+    db::tenant::schema_name,
+    error::ApiError,
+    models::{
+        child::Child,
+        document::OutstandingSignature,
+        journal::DailyJournal,
+        media::{Media, MediaQuery},
+        message::{ConversationItem, Message, PaginationQuery},
+        user::UserRole,
+    },
+    services::{
+        children::ChildService,
+        documents::DocumentService,
+        groups::GroupService,
+        journal::JournalService,
+        media::MediaService,
+        media_urls::MediaUrlSigner,
+        messages::MessageService,
+        presence::PresenceService,
+        storage::{StorageService, StorageUsage},
+    },
+};
+
+/// The authenticated user (same fields as GET /auth/me).
+#[derive(SimpleObject, sqlx::FromRow)]
+pub struct Viewer {
+    pub id: Uuid,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub role: String,
+    pub avatar_url: Option<String>,
+    pub preferred_locale: String,
+}
+
+/// Public garderie details (same fields as GET /tenant/info).
+#[derive(SimpleObject, sqlx::FromRow)]
+pub struct TenantInfo {
+    pub name: String,
+    pub logo_url: Option<String>,
+    pub trial_expires_at: Option<DateTime<Utc>>,
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn me(&self, ctx: &Context<'_>) -> Result<Option<Viewer>> {
+        let gql = ctx.data::<GqlContext>()?;
+        let schema = schema_name(&gql.tenant);
+        let viewer = sqlx::query_as::<_, Viewer>(&format!(
+            "SELECT id, email, first_name, last_name, role::TEXT as role, avatar_url, preferred_locale
+             FROM {schema}.users WHERE id = $1"
+        ))
+        .bind(gql.user.user_id)
+        .fetch_optional(&gql.state.db)
+        .await
+        .map_err(ApiError::from)?;
+        Ok(viewer)
+    }
+
+    async fn tenant(&self, ctx: &Context<'_>) -> Result<Option<TenantInfo>> {
+        let gql = ctx.data::<GqlContext>()?;
+        let info = sqlx::query_as::<_, TenantInfo>(
+            "SELECT name, logo_url, trial_expires_at FROM public.garderies WHERE slug = $1 AND is_active = TRUE",
+        )
+        .bind(&gql.tenant)
+        .fetch_optional(&gql.state.db)
+        .await
+        .map_err(ApiError::from)?;
+        Ok(info)
+    }
+
+    /// Parents only see their own children, scoped educators their groups' children.
+    async fn children(&self, ctx: &Context<'_>) -> Result<Vec<Child>> {
+        let gql = ctx.data::<GqlContext>()?;
+        Ok(ChildService::list_visible_to(&gql.state.db, &gql.tenant, &gql.user).await.map_err(ApiError::from)?)
+    }
+
+    /// Journal entries for one child, Monday to Friday of the given week.
+    async fn journal_week(&self, ctx: &Context<'_>, child_id: Uuid, week_start: NaiveDate) -> Result<Vec<DailyJournal>> {
+        let gql = ctx.data::<GqlContext>()?;
+        if gql.user.role == UserRole::Parent
+            && !JournalService::assert_parent_access(&gql.state.db, &gql.tenant, child_id, gql.user.user_id)
+                .await
+                .map_err(ApiError::from)?
+        {
+            return Err("Accès refusé".into());
+        }
+        Ok(JournalService::list_week(&gql.state.db, &gql.tenant, child_id, week_start).await.map_err(ApiError::from)?)
+    }
+
+    /// Same filters as GET /media; parents only get media visible to them.
+    #[allow(clippy::too_many_arguments)]
+    async fn media(
+        &self,
+        ctx: &Context<'_>,
+        group_id: Option<Uuid>,
+        child_ids: Option<Vec<Uuid>>,
+        page: Option<i64>,
+        per_page: Option<i64>,
+        period: Option<String>,
+        date: Option<String>,
+        tags: Option<Vec<String>>,
+        album_id: Option<Uuid>,
+    ) -> Result<Vec<Media>> {
+        let gql = ctx.data::<GqlContext>()?;
+        let query = MediaQuery {
+            group_id,
+            child_ids: child_ids.map(|ids| ids.iter().map(Uuid::to_string).collect::<Vec<_>>().join(",")),
+            page,
+            per_page,
+            period,
+            date,
+            tags: tags.map(|tags| tags.join(",")),
+            album_id,
+            cursor: None,
+            favorites: None,
+            hidden: None,
+        };
+        let is_staff = gql.user.role != UserRole::Parent;
+        let scope = GroupService::educator_scope(&gql.state.db, &gql.tenant, &gql.user).await.map_err(ApiError::from)?;
+        let mut redis = gql.state.redis.clone();
+        let signer = MediaUrlSigner::from_config(&gql.state.config).map_err(ApiError::from)?;
+        let mut page =
+            MediaService::list(&gql.state.db, &mut redis, &gql.tenant, gql.user.user_id, is_staff, scope.as_deref(), &query)
+                .await
+                .map_err(ApiError::from)?;
+        page.items.iter_mut().for_each(|m| signer.sign_media(m));
+        Ok(page.items)
+    }
+
+    async fn messages(&self, ctx: &Context<'_>, page: Option<i64>, per_page: Option<i64>) -> Result<Vec<Message>> {
+        let gql = ctx.data::<GqlContext>()?;
+        let pagination = PaginationQuery { page, per_page };
+        Ok(MessageService::list_messages(
+            &gql.state.db,
+            &gql.tenant,
+            gql.user.user_id,
+            pagination.offset(),
+            pagination.per_page(),
+        )
+        .await
+        .map_err(ApiError::from)?)
+    }
+
+    async fn conversations(&self, ctx: &Context<'_>) -> Result<Vec<ConversationItem>> {
+        let gql = ctx.data::<GqlContext>()?;
+        let items = if gql.user.role == UserRole::Parent {
+            MessageService::get_conversations_parent(&gql.state.db, &gql.tenant, gql.user.user_id)
+                .await
+                .map_err(ApiError::from)?
+        } else {
+            let mut items = MessageService::get_conversations_staff(&gql.state.db, &gql.tenant, &gql.user)
+                .await
+                .map_err(ApiError::from)?;
+            PresenceService::annotate(&mut gql.state.redis.clone(), &gql.tenant, &mut items).await;
+            items
+        };
+        Ok(items)
+    }
+
+    #[graphql(guard = "RoleGuard::new(STAFF)")]
+    async fn outstanding_signatures(&self, ctx: &Context<'_>) -> Result<Vec<OutstandingSignature>> {
+        let gql = ctx.data::<GqlContext>()?;
+        Ok(DocumentService::list_outstanding_signatures(&gql.state.db, &gql.tenant, None)
+            .await
+            .map_err(ApiError::from)?)
+    }
+
+    #[graphql(guard = "RoleGuard::new(ADMIN)")]
+    async fn storage_usage(&self, ctx: &Context<'_>) -> Result<StorageUsage> {
+        let gql = ctx.data::<GqlContext>()?;
+        Ok(StorageService::usage(&gql.state.db, &mut gql.state.redis.clone(), &gql.tenant)
+            .await
+            .map_err(ApiError::from)?)
+    }
+}
@@ -1,6 +1,8 @@
 // Library exports for binary tools and tests
 pub mod config;
 pub mod db;
+pub mod error;
+pub mod graphql;
 pub mod middleware;
 pub mod models;
 pub mod routes;
@@ -12,15 +14,26 @@ use redis::Client as RedisClient;
 use sqlx::PgPool;
 
 use config::Config;
+use services::captcha::CaptchaService;
 use services::email::EmailService;
+use services::jwt_keys::JwtKeys;
 use services::notifications::NotificationService;
+use services::password_policy::PasswordPolicy;
+use services::sms::SmsService;
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
+    /// Separate pool for media, exports and backups (see `db::PoolClass::Bulk`).
+    pub db_bulk: PgPool,
     pub redis: redis::aio::MultiplexedConnection,
     pub redis_client: RedisClient,
     pub config: Arc<Config>,
     pub notifications: Arc<NotificationService>,
     pub email: Option<Arc<EmailService>>,
+    pub sms: Option<Arc<SmsService>>,
+    pub captcha: Option<Arc<CaptchaService>>,
+    pub password_policy: Arc<PasswordPolicy>,
+    /// Access-token keys, also available to extractors as a request extension.
+    pub jwt_keys: Arc<JwtKeys>,
 }
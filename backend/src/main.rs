@@ -1,5 +1,7 @@
 mod config;
 mod db;
+mod error;
+mod graphql;
 mod middleware;
 mod models;
 mod routes;
@@ -15,42 +17,76 @@ use axum::{
 };
 use redis::Client as RedisClient;
 use sqlx::PgPool;
+use tower_http::catch_panic::CatchPanicLayer;
 use tower_http::cors::{AllowHeaders, AllowOrigin, CorsLayer};
 use tower_http::trace::TraceLayer;
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use config::Config;
-use middleware::auth::JwtSecret;
+use services::captcha::CaptchaService;
 use services::email::EmailService;
+use services::jwt_keys::JwtKeys;
 use services::notifications::NotificationService;
+use services::password_policy::PasswordPolicy;
+use services::sms::SmsService;
 
 /// Application state shared across all handlers.
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
+    /// Separate pool for media, exports and backups (see `db::PoolClass::Bulk`).
+    pub db_bulk: PgPool,
     pub redis: redis::aio::MultiplexedConnection,
     pub redis_client: RedisClient,
     pub config: Arc<Config>,
     pub notifications: Arc<NotificationService>,
     pub email: Option<Arc<EmailService>>,
+    pub sms: Option<Arc<SmsService>>,
+    pub captcha: Option<Arc<CaptchaService>>,
+    pub password_policy: Arc<PasswordPolicy>,
+    /// Access-token keys, also available to extractors as a request extension.
+    pub jwt_keys: Arc<JwtKeys>,
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let _ = dotenvy::dotenv();
 
+    let config = Config::from_env()?;
+    let config = Arc::new(config);
+
+    // JSON lines carry the fields of the request span (request_id, tenant, user_id)
+    let (json_logs, text_logs) = if config.log_json {
+        (Some(tracing_subscriber::fmt::layer().json().with_current_span(false)), None)
+    } else {
+        (None, Some(tracing_subscriber::fmt::layer()))
+    };
     tracing_subscriber::registry()
         .with(tracing_subscriber::EnvFilter::from_default_env())
-        .with(tracing_subscriber::fmt::layer())
+        .with(json_logs)
+        .with(text_logs)
         .init();
 
-    let config = Config::from_env()?;
-    let config = Arc::new(config);
+    // Error reporting: panics and 5xx responses go to Sentry when SENTRY_DSN is set
+    let _sentry = config.sentry_dsn.as_deref().map(|dsn| {
+        sentry::init((
+            dsn,
+            sentry::ClientOptions {
+                release: sentry::release_name!(),
+                environment: Some(config.sentry_environment.clone().into()),
+                ..Default::default()
+            },
+        ))
+    });
 
-    let pool = db::create_pool(&config.database_url).await?;
-    db::run_migrations(&pool).await?;
-    db::migrate_all_existing_tenants(&pool).await?;
+    db::tenant_db::TenantDb::init(config.tenant_isolation)?;
+    let pool = db::create_pool(&config, db::PoolClass::Api).await?;
+    let bulk_pool = db::create_pool(&config, db::PoolClass::Bulk).await?;
+    let jobs_pool = db::create_pool(&config, db::PoolClass::Jobs).await?;
+    db::tenant_db::TenantDb::check_role(&jobs_pool).await?;
+    db::run_migrations(&jobs_pool).await?;
+    db::tenant_migrations::migrate_all_tenants(&jobs_pool).await?;
     info!("Database connected and migrations applied");
 
     let redis_client = RedisClient::open(config.redis_url.as_str())?;
@@ -61,30 +97,61 @@ async fn main() -> anyhow::Result<()> {
 
     let notifications = Arc::new(NotificationService::new(config.fcm_api_key.clone()));
 
-    let email = EmailService::new(&config).map(Arc::new);
+    let email = EmailService::new(&config).map(|svc| Arc::new(svc.with_db(pool.clone())));
     if email.is_some() {
         info!("SMTP email service configured");
     } else {
         info!("SMTP not configured — email features disabled");
     }
 
+    let sms = SmsService::new(&config).map(Arc::new);
+    if sms.is_some() {
+        info!("Twilio SMS service configured");
+    } else {
+        info!("Twilio not configured — SMS features disabled");
+    }
+
+    let captcha = CaptchaService::new(&config).map(Arc::new);
+    if captcha.is_some() {
+        info!("Turnstile CAPTCHA configured for signup");
+    } else {
+        info!("TURNSTILE_SECRET_KEY not set — signup CAPTCHA disabled");
+    }
+
+    let jwt_keys = Arc::new(JwtKeys::from_config(&config)?);
+    info!("Access tokens signed with JWT key '{}'", jwt_keys.signing_kid());
+
     let state = AppState {
         db: pool.clone(),
+        db_bulk: bulk_pool,
         redis: redis_conn,
         redis_client: redis_client.clone(),
         config: config.clone(),
         notifications,
         email: email.clone(),
+        sms,
+        captcha,
+        password_policy: Arc::new(PasswordPolicy::new(&config)),
+        jwt_keys: jwt_keys.clone(),
     };
 
-    // Start journal auto-send scheduler
-    services::journal_scheduler::start(pool.clone(), email.clone());
-
-    // Start trial expiry warning scheduler (daily at 9 AM)
-    services::trial_scheduler::start(pool.clone(), email.clone(), redis_client.clone());
+    // Start the schedulers, notification consumer and other background jobs, unless
+    // the `worker` binary runs them
+    if config.run_background_jobs {
+        services::worker::start_all(services::worker::Context {
+            pool: jobs_pool.clone(),
+            redis_client: redis_client.clone(),
+            config: config.clone(),
+            email: email.clone(),
+            sms: state.sms.clone(),
+            notifications: state.notifications.clone(),
+        });
+    } else {
+        info!("RUN_BACKGROUND_JOBS=false — background jobs left to the worker");
+    }
 
     // Start Prometheus business metrics collector
-    services::metrics::start(pool.clone());
+    services::metrics::start(jobs_pool.clone());
 
     // Build CORS: allow the app base domain and its subdomains (tenant subdomains).
     // In development (localhost), all origins are allowed.
@@ -123,74 +190,195 @@ async fn main() -> anyhow::Result<()> {
             header::AUTHORIZATION,
             header::CONTENT_TYPE,
             header::ACCEPT,
+            header::ACCEPT_LANGUAGE,
+            header::IF_MATCH,
+            header::IF_NONE_MATCH,
+            middleware::api_version::API_VERSION,
+            middleware::idempotency::IDEMPOTENCY_KEY,
             header::HeaderName::from_static("x-tenant"),
             header::HeaderName::from_static("x-super-admin-key"),
+            header::HeaderName::from_static("x-super-admin-email"),
+            header::HeaderName::from_static("x-super-admin-password"),
         ]))
+        .expose_headers([
+            middleware::request_id::X_REQUEST_ID.clone(),
+            header::ETAG,
+            middleware::idempotency::IDEMPOTENT_REPLAYED,
+        ])
         .allow_origin(cors_origin);
 
-    let jwt_secret = JwtSecret(config.jwt_secret.clone());
+    // POSTs a client may retry after a lost response (see Idempotency-Key)
+    let idempotent = axum::middleware::from_fn_with_state(state.clone(), middleware::idempotency::idempotent);
 
     let app = Router::new()
         .route("/health", get(routes::health::health_check))
         .route("/contact", post(routes::contact::submit_contact))
+        .route("/webhooks/email/{provider}", post(routes::email_webhooks::receive))
         .route("/signup", post(routes::signup::signup))
         .route("/signup/check-slug", get(routes::signup::check_slug))
         .route("/tenant/info", get(routes::tenant_info::get_tenant_info))
+        .route("/tenant/usage", get(routes::storage::get_tenant_usage))
         .route("/tenant/logo", post(routes::logo::upload_logo).delete(routes::logo::delete_logo))
+        .route("/tenant/waitlist", post(routes::waitlist::submit_waitlist))
         .route("/logos/{slug}", get(routes::logo::serve_logo))
+        .route("/t/{slug}/o/{token}", get(routes::email_tracking::open))
+        .route("/t/{slug}/c/{token}/{link_id}", get(routes::email_tracking::click))
+        .route("/shared/{token}", get(routes::document_shares::open_share))
+        .route("/departures/{token}", get(routes::departures::open_export))
         // Announcements
         .route("/announcement", get(routes::announcements::get_announcement))
         .route("/super-admin/announcement", put(routes::announcements::set_announcement).delete(routes::announcements::delete_announcement))
         // Auth
         .route("/auth/login", post(routes::auth::login))
+        .route("/.well-known/jwks.json", get(routes::auth::jwks))
         .route("/auth/refresh", post(routes::auth::refresh_token))
         .route("/auth/logout", post(routes::auth::logout))
+        .route("/auth/sessions", get(routes::auth::list_sessions))
+        .route("/auth/login-history", get(routes::auth::login_history))
+        .route("/auth/oidc/login", get(routes::oidc::start_login))
+        .route("/auth/oidc/callback", post(routes::oidc::callback))
+        .route("/auth/sessions/revoke-others", post(routes::auth::revoke_other_sessions))
+        .route("/auth/sessions/{id}", delete(routes::auth::revoke_session))
+        .route("/auth/tenants", get(routes::auth::list_linked_tenants))
+        .route("/auth/switch-tenant", post(routes::auth::switch_tenant))
         .route("/auth/invite", post(routes::auth::invite_user))
         .route("/auth/invitations", get(routes::auth::list_pending_invitations))
-        .route("/auth/invitations/{id}", delete(routes::auth::delete_invitation))
+        .route("/auth/invitations/bulk", post(routes::auth::bulk_invite))
+        .route("/auth/invitations/{id}", delete(routes::auth::delete_invitation).patch(routes::auth::extend_invitation))
         .route("/auth/invitations/{id}/resend", post(routes::auth::resend_invitation))
         .route("/auth/validate-token/{token}", get(routes::auth::validate_invitation_token))
         .route("/auth/register", post(routes::auth::register_from_invite))
         .route("/auth/me", get(routes::auth::me))
         .route("/auth/change-password", post(routes::auth::change_password))
         .route("/auth/update-email", post(routes::auth::update_email))
-        .route("/auth/push-token", post(routes::auth::register_push_token))
+        .route("/auth/phone", put(routes::auth::update_phone))
+        .route(
+            "/auth/push-token",
+            post(routes::auth::register_push_token).delete(routes::auth::unregister_push_token),
+        )
         .route("/auth/verify-2fa", post(routes::auth::verify_2fa))
         .route("/auth/forgot-password", post(routes::auth::forgot_password))
         .route("/auth/reset-password", post(routes::auth::reset_password))
-        .route("/auth/consent", get(routes::auth::get_consent).put(routes::auth::update_consent))
+        .route("/auth/consent", get(routes::auth::get_consent).put(routes::auth::update_consent).post(routes::auth::record_consent))
+        .route("/consents/report", get(routes::consents::compliance_report))
         .route("/auth/account/deletion-request", post(routes::auth::request_account_deletion))
         // Email
-        .route("/email/send-to-parents", post(routes::email::send_to_parents))
+        .route("/email/send-to-parents", post(routes::email::send_to_parents).layer(idempotent.clone()))
         // Messages
-        .route("/messages", get(routes::messages::list_messages).post(routes::messages::send_message))
-        .route("/messages/send-to-parents", post(routes::messages::send_to_parents))
+        .route("/graphql", post(routes::graphql::graphql))
+        .route(
+            "/messages",
+            get(routes::messages::list_messages).merge(post(routes::messages::send_message).layer(idempotent.clone())),
+        )
+        .route("/messages/send-to-parents", post(routes::messages::send_to_parents).layer(idempotent.clone()))
+        .route("/messages/unread-count", get(routes::messages::unread_count))
+        .route(
+            "/messages/emergency",
+            get(routes::emergency::list_emergencies).merge(post(routes::emergency::send_emergency).layer(idempotent.clone())),
+        )
+        .route("/messages/emergency/{id}", get(routes::emergency::get_emergency))
+        .route("/messages/emergency/{id}/acknowledge", post(routes::emergency::acknowledge_emergency))
+        .route(
+            "/messages/auto-reply",
+            get(routes::messages::get_auto_reply).put(routes::messages::update_auto_reply).delete(routes::messages::delete_auto_reply),
+        )
+        .route("/messages/drafts", get(routes::message_drafts::list_drafts).post(routes::message_drafts::create_draft))
+        .route("/messages/drafts/{id}", get(routes::message_drafts::get_draft).put(routes::message_drafts::update_draft).delete(routes::message_drafts::delete_draft))
+        .route("/messages/{id}", put(routes::messages::edit_message).delete(routes::messages::delete_message))
         .route("/messages/{id}/read", post(routes::messages::mark_read))
+        .route("/messages/{id}/edits", get(routes::messages::list_message_edits))
+        .route("/messages/{id}/reads", get(routes::messages::list_message_reads))
+        .route("/messages/{id}/deliveries", get(routes::messages::list_message_deliveries))
+        .route("/messages/{id}/reactions", post(routes::messages::add_reaction).delete(routes::messages::remove_reaction))
         .route("/messages/thread/mark-read", post(routes::messages::mark_thread_read))
+        .route("/messages/thread/state", put(routes::messages::update_thread_state))
         .route("/messages/conversation/{user_id}", get(routes::messages::get_conversation))
         .route("/messages/conversations", get(routes::messages::get_conversations))
         .route("/messages/thread/broadcast", get(routes::messages::get_broadcast_thread))
         .route("/messages/thread/group/{group_id}", get(routes::messages::get_group_thread))
         .route("/messages/thread/individual/{parent_id}", get(routes::messages::get_individual_thread))
+        .route("/messages/thread/staff", get(routes::messages::get_staff_thread))
+        .route("/messages/thread/staff/{group_id}", get(routes::messages::get_group_staff_thread))
         // Media
-        .route("/media", get(routes::media::list_media).post(routes::media::upload_media))
+        .route(
+            "/media",
+            get(routes::media::list_media).merge(post(routes::media::upload_media).layer(idempotent.clone())),
+        )
         .route("/media/bulk", post(routes::media::bulk_media))
+        .route("/media/tags", get(routes::media::list_media_tags))
         .route("/media/{id}", put(routes::media::update_media).delete(routes::media::delete_media))
+        .route("/media/{id}/favorite", post(routes::media::add_favorite).delete(routes::media::remove_favorite))
+        .route("/media/{id}/hide", post(routes::media::hide_media).delete(routes::media::unhide_media))
+        .route(
+            "/media/{id}/comments",
+            get(routes::media_comments::list_comments)
+                .merge(post(routes::media_comments::create_comment).layer(idempotent.clone())),
+        )
+        .route("/media/{id}/comments/{comment_id}", delete(routes::media_comments::delete_comment))
+        .route(
+            "/media/{id}/redaction",
+            get(routes::media_redactions::get_redaction)
+                .put(routes::media_redactions::request_redaction)
+                .delete(routes::media_redactions::delete_redaction),
+        )
         .route("/media/files/{*path}", get(routes::media::serve_media))
+        .route("/quarantine", get(routes::antivirus::list_quarantine))
+        // Albums
+        .route("/albums", get(routes::albums::list_albums).post(routes::albums::create_album))
+        .route("/albums/{id}", get(routes::albums::get_album).put(routes::albums::update_album).delete(routes::albums::delete_album))
+        .route("/albums/{id}/media", post(routes::albums::add_album_media))
+        .route("/albums/{id}/media/{media_id}", delete(routes::albums::remove_album_media))
+        .route("/albums/{id}/share", post(routes::albums::share_album))
         // Documents
         .route("/documents", get(routes::documents::list_documents).post(routes::documents::upload_document))
         .route("/documents/{id}", put(routes::documents::update_document).delete(routes::documents::delete_document))
+        .route("/documents/bulk", post(routes::documents::bulk_documents))
+        .route("/documents/expiring", get(routes::documents::list_expiring_documents))
+        .route("/documents/folders", get(routes::documents::list_folders).post(routes::documents::create_folder))
+        .route("/documents/folders/{id}", put(routes::documents::update_folder).delete(routes::documents::delete_folder))
+        .route("/documents/{id}/replace", post(routes::documents::replace_document))
+        .route("/documents/{id}/versions", get(routes::documents::list_document_versions))
+        .route("/documents/{id}/sign", post(routes::documents::sign_document))
+        .route("/documents/{id}/signatures", get(routes::documents::list_document_signatures))
+        .route(
+            "/documents/{id}/shares",
+            get(routes::document_shares::list_shares).post(routes::document_shares::create_share),
+        )
+        .route("/documents/{id}/shares/{share_id}", delete(routes::document_shares::revoke_share))
+        .route("/documents/{id}/shares/{share_id}/accesses", get(routes::document_shares::list_share_accesses))
+        .route("/documents/signatures/outstanding", get(routes::documents::list_outstanding_signatures))
+        .route("/documents/signatures/mine", get(routes::documents::list_my_signatures))
         // Groups
+        .route("/search", get(routes::search::search))
         .route("/groups", get(routes::groups::list_groups).post(routes::groups::create_group))
+        .route("/groups/rollover", post(routes::groups::rollover))
         .route("/groups/{id}", put(routes::groups::update_group).delete(routes::groups::delete_group))
+        .route("/groups/{id}/archive", post(routes::groups::archive_group))
+        .route("/groups/{id}/restore", post(routes::groups::restore_group))
         .route("/groups/{id}/children", put(routes::groups::set_group_children))
+        .route("/groups/{id}/educators", get(routes::groups::list_group_educators).put(routes::groups::set_group_educators))
+        // Families (households of siblings)
+        .route("/families", get(routes::families::list_families).post(routes::families::create_family))
+        .route("/families/suggestions", get(routes::families::family_suggestions))
+        .route(
+            "/families/{id}",
+            get(routes::families::get_family).put(routes::families::update_family).delete(routes::families::delete_family),
+        )
+        .route("/families/{id}/children", put(routes::families::set_family_children))
+        .route("/families/{id}/send-to-parents", post(routes::families::send_to_family).layer(idempotent.clone()))
         // Menus de la garderie
         .route("/menus", get(routes::menu::get_week).put(routes::menu::upsert_menu))
+        .route("/menus/week", get(routes::menu::get_week_view).put(routes::menu::plan_week))
         // Journal de bord
         .route("/journals", get(routes::journal::get_week).put(routes::journal::upsert_entry))
         .route("/journals/month", get(routes::journal::get_month_summary))
-        .route("/journals/send-all-to-parents", post(routes::journal::send_all_to_parents))
-        .route("/journals/{child_id}/send-to-parents", post(routes::journal::send_to_parents))
+        .route("/journals/amend", post(routes::journal::amend_entry))
+        .route("/journals/amendments", get(routes::journal::list_amendments))
+        .route("/journals/events", get(routes::journal::list_events).post(routes::journal::create_event))
+        .route("/journals/events/{id}", put(routes::journal::update_event).delete(routes::journal::delete_event))
+        .route("/journals/timeline", get(routes::journal::get_timeline))
+        .route("/journals/send-all-to-parents", post(routes::journal::send_all_to_parents).layer(idempotent.clone()))
+        .route("/journals/{child_id}/send-to-parents", post(routes::journal::send_to_parents).layer(idempotent.clone()))
         // Attendance
         .route("/attendance", get(routes::attendance::get_month).put(routes::attendance::set_attendance))
         .route("/attendance/bulk", put(routes::attendance::set_attendance_bulk))
@@ -203,12 +391,43 @@ async fn main() -> anyhow::Result<()> {
         .route("/activities/{id}/register/{child_id}", delete(routes::activities::unregister_child))
         // Settings
         .route("/settings", get(routes::settings::get_settings).put(routes::settings::update_settings))
+        .route(
+            "/settings/oidc",
+            get(routes::oidc::get_settings)
+                .put(routes::oidc::update_settings)
+                .delete(routes::oidc::delete_settings),
+        )
+        .route("/settings/branding", get(routes::settings::get_branding).put(routes::settings::update_branding))
+        .route("/email-templates", get(routes::email_templates::list_templates).post(routes::email_templates::create_template))
+        .route("/email-templates/preview", post(routes::email_templates::preview_template))
+        .route("/email-templates/{id}", put(routes::email_templates::update_template).delete(routes::email_templates::delete_template))
+
+        .route("/invoices", get(routes::invoices::list_invoices).post(routes::invoices::create_invoice))
+        .route("/invoices/generate", post(routes::invoices::generate_invoices))
+        .route("/invoices/{id}", get(routes::invoices::get_invoice).put(routes::invoices::update_invoice))
+        .route("/invoices/{id}/pdf", get(routes::invoices::get_invoice_pdf))
+        .route("/invoices/{id}/send", post(routes::invoices::send_invoice))
+        .route("/invoices/{id}/mark-paid", post(routes::invoices::mark_invoice_paid))
+        .route("/invoices/{id}/void", post(routes::invoices::void_invoice))
+        .route("/tax-receipts", get(routes::tax_receipts::list_tax_receipts))
+        .route("/tax-receipts/generate", post(routes::tax_receipts::generate_tax_receipts))
+        .route("/tax-receipts/{id}/pdf", get(routes::tax_receipts::get_tax_receipt_pdf))
+        .route("/waitlist", get(routes::waitlist::list_waitlist).post(routes::waitlist::create_waitlist_entry))
+        .route("/waitlist/{id}", put(routes::waitlist::update_waitlist_entry).delete(routes::waitlist::delete_waitlist_entry))
+        .route("/waitlist/{id}/convert", post(routes::waitlist::convert_waitlist_entry))
+        .route("/meetings/slots", get(routes::meetings::list_slots).post(routes::meetings::publish_slots))
+        .route("/meetings/slots/{id}", delete(routes::meetings::delete_slot))
+        .route("/meetings/slots/{id}/book", post(routes::meetings::book_slot))
+        .route("/meetings/bookings", get(routes::meetings::list_bookings))
+        .route("/meetings/bookings/{id}", delete(routes::meetings::cancel_booking))
+        .route("/meetings/agenda", get(routes::meetings::get_agenda))
         // Children
         .route("/children", get(routes::children::list_children).post(routes::children::create_child))
         .route("/children/import", post(routes::children::import_children))
         .route("/children/export", get(routes::children::export_all_children))
         .route("/children/available-invitations", get(routes::children::list_available_invitations))
         .route("/children/{id}", put(routes::children::update_child).delete(routes::children::delete_child))
+        .route("/children/{id}/group-history", get(routes::groups::child_group_history))
         .route("/children/{id}/parents", get(routes::children::list_parents).post(routes::children::assign_parent))
         .route("/children/{id}/parents/{user_id}", delete(routes::children::remove_parent))
         .route("/children/{id}/pending-parents", get(routes::children::list_pending_parents).post(routes::children::assign_pending_parent))
@@ -216,22 +435,77 @@ async fn main() -> anyhow::Result<()> {
         .route("/children/{id}/invited-parents", get(routes::children::list_invited_parents).post(routes::children::assign_invited_parent))
         .route("/children/{id}/invited-parents/{email}", delete(routes::children::remove_invited_parent))
         .route("/children/{id}/export", get(routes::children::export_child))
+        .route(
+            "/children/{id}/departure",
+            get(routes::departures::get_departure)
+                .put(routes::departures::schedule_departure)
+                .delete(routes::departures::cancel_departure),
+        )
+        .route("/children/{id}/departure/export", post(routes::departures::create_export))
+        .route("/children/{id}/absences", get(routes::attendance::list_absences).post(routes::attendance::declare_absence))
+        .route("/auto-absences", get(routes::attendance::list_auto_absences))
+        .route("/auto-absences/{id}/confirm", post(routes::attendance::confirm_auto_absence))
+        // Child:educator ratios
+        .route("/ratios", get(routes::ratios::current))
+        .route("/ratios/history", get(routes::ratios::history))
+        .route("/ratios/rules", get(routes::ratios::get_rules).put(routes::ratios::update_rules))
+        .route("/staff-shifts", get(routes::ratios::list_shifts).post(routes::ratios::create_shift))
+        .route("/staff-shifts/{id}", delete(routes::ratios::delete_shift))
+        // Data retention (Loi 25)
+        .route("/retention", get(routes::retention::get_settings).put(routes::retention::update_settings))
+        .route("/retention/upcoming", get(routes::retention::upcoming))
+        .route("/retention/runs", get(routes::retention::list_runs))
+        .route("/retention/holds", get(routes::retention::list_holds).post(routes::retention::create_hold))
+        .route("/retention/holds/{id}/release", post(routes::retention::release_hold))
+        // Tenant analytics (daily rollups)
+        .route("/stats", get(routes::stats::get_stats))
+        .route("/children/{id}/consent", get(routes::consents::get_child_consent).put(routes::consents::update_child_consent))
+        .route("/children/{id}/photo", post(routes::children::upload_child_avatar).delete(routes::children::delete_child_avatar))
         .route("/children/{id}/avatar", post(routes::children::upload_child_avatar).delete(routes::children::delete_child_avatar))
+        .route("/children/{id}/observations", get(routes::development::get_timeline).post(routes::development::create_observation))
+        .route("/children/{id}/observations/summary", get(routes::development::get_term_summary))
+        .route("/observations/{id}", put(routes::development::update_observation).delete(routes::development::delete_observation))
+        .route("/milestones", get(routes::development::list_milestones).post(routes::development::create_milestone))
+        .route("/milestones/{id}", put(routes::development::update_milestone).delete(routes::development::delete_milestone))
         // WebSocket
         .route("/ws", get(routes::websocket::ws_handler))
+        .route("/ws/ticket", post(routes::websocket::issue_ticket))
         // Tenant user management (admin_garderie)
         .route("/users", get(routes::users::list_users).post(routes::users::create_user))
         .route("/users/{id}", put(routes::users::update_user).delete(routes::users::deactivate_user))
         .route("/users/{id}/reset-password", post(routes::users::reset_user_password))
+        .route("/users/{id}/erase", post(routes::users::erase_user))
+        .route("/users/{id}/email-suppression", delete(routes::users::clear_email_suppression))
+        .route("/email-log", get(routes::email_log::list))
+        .route("/email-log/{id}/resend", post(routes::email_log::resend))
+        .route("/email-campaigns", get(routes::email_tracking::list))
+        .route("/email-campaigns/{id}", get(routes::email_tracking::get))
+        .route("/users/erasure-certificates", get(routes::users::list_erasure_certificates))
         // Super-admin
         .route("/super-admin/garderies", get(routes::tenants::list_garderies).post(routes::tenants::create_garderie))
         .route("/super-admin/garderies/{slug}", put(routes::tenants::update_garderie).delete(routes::tenants::delete_garderie))
         .route("/super-admin/garderies/{slug}/users", get(routes::tenants::list_garderie_users).post(routes::tenants::create_garderie_user_body))
         .route("/super-admin/garderies/{slug}/invite", post(routes::tenants::invite_garderie_user))
+        .route("/super-admin/garderies/{slug}/clone", post(routes::tenants::clone_garderie))
         .route("/super-admin/garderies/{slug}/users/{user_id}", delete(routes::tenants::deactivate_garderie_user))
+        .route("/super-admin/garderies/{slug}/backup", post(routes::tenants::backup_garderie))
+        .route("/super-admin/garderies/{slug}/backups", get(routes::tenants::list_garderie_backups))
+        .route("/super-admin/garderies/{slug}/restore", post(routes::tenants::restore_garderie))
+        .route("/super-admin/garderies/{slug}/unarchive", post(routes::tenants::unarchive_garderie))
+        .route("/super-admin/garderies/{slug}/children/{child_id}/transfer", post(routes::tenants::transfer_child))
+        .route("/super-admin/transfers", get(routes::tenants::list_child_transfers))
+        .route("/super-admin/usage", get(routes::storage::list_usage))
+        .route("/super-admin/stats", get(routes::stats::super_admin_stats))
+        .route("/super-admin/feature-flags", get(routes::feature_flags::list_flags))
+        .route("/super-admin/feature-flags/{key}", put(routes::feature_flags::set_flag).delete(routes::feature_flags::delete_flag))
+        .route("/super-admin/tax-receipts.csv", get(routes::tax_receipts::export_tax_receipts))
         .route("/super-admin/backup", post(routes::tenants::trigger_backup_all))
         .route("/super-admin/backups", get(routes::tenants::list_backups))
+        .route("/super-admin/backup-runs", get(routes::tenants::list_backup_runs))
         .route("/super-admin/restore", post(routes::tenants::trigger_restore))
+        .route("/super-admin/operations", get(routes::operations::list_operations))
+        .route("/super-admin/operations/{id}/confirm", post(routes::operations::confirm_operation))
+        .route("/super-admin/operations/{id}/cancel", post(routes::operations::cancel_operation))
         // Grafana SSO
         .route("/super-admin/audit-log", get(routes::audit_log::super_admin_global_audit_log))
         .route("/super-admin/audit-log/{slug}", get(routes::audit_log::super_admin_audit_log))
@@ -239,8 +513,15 @@ async fn main() -> anyhow::Result<()> {
         .route("/super-admin/grafana-auth", get(routes::grafana_auth::grafana_auth))
         // Prometheus metrics (internal — protected by nginx)
         .route("/metrics", get(routes::metrics::metrics_handler))
-        .layer(axum::Extension(jwt_secret))
+        .layer(axum::middleware::from_fn_with_state(state.clone(), middleware::trial::enforce_trial))
+        .layer(axum::middleware::from_fn_with_state(state.clone(), middleware::feature_flags::feature_flags))
+        .layer(axum::middleware::from_fn(middleware::error_i18n::localize_errors))
+        .layer(axum::middleware::from_fn(middleware::error_reporting::report_errors))
+        .layer(CatchPanicLayer::custom(middleware::error_reporting::panic_response))
+        .layer(axum::Extension(jwt_keys))
+        .layer(middleware::compression::layer())
         .layer(TraceLayer::new_for_http())
+        .layer(axum::middleware::from_fn_with_state(state.clone(), middleware::request_id::request_context))
         .layer(cors)
         // Global body size limit of 100 MB (covers media uploads)
         .layer(DefaultBodyLimit::max(100 * 1024 * 1024))
@@ -250,7 +531,17 @@ async fn main() -> anyhow::Result<()> {
     info!("minispace.app API listening on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(services::worker::shutdown_signal())
+        .await?;
+
+    // Background jobs running in this process get the same grace period as in the worker
+    if config.run_background_jobs {
+        let grace = std::time::Duration::from_secs(config.worker_shutdown_grace_secs);
+        if !services::worker::shutdown(grace).await {
+            tracing::warn!("API stopped with background jobs still running");
+        }
+    }
 
     Ok(())
 }
@@ -0,0 +1,39 @@
+use axum::{
+    extract::FromRequestParts,
+    http::{request::Parts, HeaderName, StatusCode},
+};
+
+use crate::error::ApiError;
+
+pub const API_VERSION: HeaderName = HeaderName::from_static("x-api-version");
+
+/// Newest representation of the list endpoints.
+pub const LATEST: u8 = 2;
+
+/// Representation a client asks for with `X-Api-Version`. 1, the default, returns
+/// full rows as before; 2 returns slim list items, without storage paths or
+/// encryption metadata (the signed URLs are all a client needs).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApiVersion(pub u8);
+
+impl ApiVersion {
+    pub fn slim(self) -> bool {
+        self.0 >= 2
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ApiVersion {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(raw) = parts.headers.get(&API_VERSION) else {
+            return Ok(ApiVersion(1));
+        };
+        raw.to_str()
+            .ok()
+            .and_then(|v| v.trim().parse::<u8>().ok())
+            .filter(|v| (1..=LATEST).contains(v))
+            .map(ApiVersion)
+            .ok_or_else(|| ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_api_version"))
+    }
+}
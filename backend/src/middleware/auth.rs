@@ -1,36 +1,42 @@
+use std::sync::Arc;
+
 use axum::{
     extract::FromRequestParts,
     http::{request::Parts, StatusCode},
 };
-use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
 
+use crate::error::ApiError;
 use crate::models::auth::{AuthenticatedUser, Claims};
 use crate::models::user::UserRole;
+use crate::services::jwt_keys::JwtKeys;
 
 impl<S> FromRequestParts<S> for AuthenticatedUser
 where
     S: Send + Sync,
 {
-    type Rejection = (StatusCode, &'static str);
+    type Rejection = ApiError;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
         let auth_header = parts
             .headers
             .get("Authorization")
             .and_then(|v| v.to_str().ok())
-            .ok_or((StatusCode::UNAUTHORIZED, "Missing Authorization header"))?;
+            .ok_or_else(|| ApiError::new(StatusCode::UNAUTHORIZED, "Missing Authorization header"))?;
 
         let token = auth_header
             .strip_prefix("Bearer ")
-            .ok_or((StatusCode::UNAUTHORIZED, "Invalid Authorization header format"))?;
+            .ok_or_else(|| ApiError::new(StatusCode::UNAUTHORIZED, "Invalid Authorization header format"))?;
 
-        let secret = parts
+        let keys = parts
             .extensions
-            .get::<JwtSecret>()
-            .ok_or((StatusCode::INTERNAL_SERVER_ERROR, "JWT secret not configured"))?;
+            .get::<Arc<JwtKeys>>()
+            .ok_or_else(|| {
+                tracing::error!("JWT keys not configured");
+                ApiError::internal()
+            })?;
 
-        let user = decode_access_token(token, &secret.0)
-            .map_err(|_| (StatusCode::UNAUTHORIZED, "Invalid or expired token"))?;
+        let user = decode_access_token(token, keys)
+            .map_err(|_| ApiError::from_key(StatusCode::UNAUTHORIZED, "invalid_access_token"))?;
 
         // Cross-tenant IDOR prevention: if an X-Tenant header is present and the user
         // is not a super-admin, the JWT tenant must match the requested tenant.
@@ -42,7 +48,7 @@ where
             {
                 let x_tenant_lower = x_tenant.to_lowercase();
                 if user.tenant != x_tenant_lower {
-                    return Err((StatusCode::FORBIDDEN, "Tenant mismatch"));
+                    return Err(ApiError::new(StatusCode::FORBIDDEN, "Tenant mismatch"));
                 }
             }
         }
@@ -51,21 +57,15 @@ where
     }
 }
 
-/// Extension type to carry the JWT secret through request extensions.
-#[derive(Clone)]
-pub struct JwtSecret(pub String);
-
-pub fn decode_access_token(token: &str, secret: &str) -> Result<AuthenticatedUser, anyhow::Error> {
-    let key = DecodingKey::from_secret(secret.as_bytes());
-    let mut validation = Validation::new(Algorithm::HS256);
-    validation.validate_exp = true;
-
-    let data = decode::<Claims>(token, &key, &validation)?;
-    let claims = data.claims;
+pub fn decode_access_token(token: &str, keys: &JwtKeys) -> Result<AuthenticatedUser, anyhow::Error> {
+    let claims: Claims = keys.decode(token)?;
 
     Ok(AuthenticatedUser {
         user_id: claims.sub.parse()?,
         tenant: claims.tenant,
         role: claims.role,
+        session_id: claims.sid,
+        identity_id: claims.identity,
+        locale: claims.locale,
     })
 }
@@ -0,0 +1,16 @@
+use axum::http::{header, Extensions, HeaderMap, StatusCode, Version};
+use tower_http::compression::{predicate::DefaultPredicate, CompressionLayer, Predicate};
+
+/// Only JSON and text are worth compressing: photos and videos already are, and
+/// files are served by range, which a compressed body would break.
+fn compressible(_: StatusCode, _: Version, headers: &HeaderMap, _: &Extensions) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json") || ct.starts_with("text/"))
+}
+
+/// gzip or brotli, as the client's `Accept-Encoding` allows.
+pub fn layer() -> CompressionLayer<impl Predicate> {
+    CompressionLayer::new().compress_when(DefaultPredicate::new().and(compressible))
+}
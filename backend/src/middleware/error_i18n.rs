@@ -0,0 +1,79 @@
+use axum::{
+    body::{to_bytes, Body, HttpBody},
+    extract::Request,
+    http::{header, Response},
+    middleware::Next,
+};
+use serde_json::Value;
+
+use crate::{
+    error::PROBLEM_JSON,
+    middleware::error_reporting::caller,
+    services::error_i18n::{by_key, response_locale},
+};
+
+/// Error bodies larger than this, or of unknown length, are passed through untouched.
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+/// Whether the whole body is known to fit in `MAX_ERROR_BODY_BYTES`, checked
+/// before buffering so that a larger or streamed body is never cut.
+fn fits_in_buffer(body: &Body) -> bool {
+    body.size_hint().upper().is_some_and(|len| len <= MAX_ERROR_BODY_BYTES as u64)
+}
+
+/// Translates the message of JSON error responses carrying a catalog `key` into the
+/// caller's language, see [`crate::services::error_i18n`]. Errors without a key go out
+/// as they are.
+pub async fn localize_errors(req: Request, next: Next) -> Response<Body> {
+    let (parts, body) = req.into_parts();
+    let headers = parts.headers.clone();
+    let preferred_locale = caller(&parts).and_then(|user| user.locale);
+
+    let response = next.run(Request::from_parts(parts, body)).await;
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json") || ct.starts_with(PROBLEM_JSON));
+    if response.status().as_u16() < 400 || !is_json || !fits_in_buffer(response.body()) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_ERROR_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!("Error body of a {} response could not be read: {e}", parts.status);
+            parts.headers.remove(header::CONTENT_LENGTH);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+    let mut json = match serde_json::from_slice::<Value>(&bytes) {
+        Ok(Value::Object(map)) => map,
+        _ => return Response::from_parts(parts, Body::from(bytes)),
+    };
+    let Some(message) = json.get("key").and_then(Value::as_str).and_then(by_key) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    let locale = response_locale(&headers, preferred_locale.as_deref());
+
+    json.insert("error".into(), message.text(locale).into());
+    if json.contains_key("detail") {
+        json.insert("detail".into(), message.text(locale).into());
+    }
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(Value::Object(json).to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_bodies_of_known_small_length_are_buffered() {
+        assert!(fits_in_buffer(&Body::from(r#"{"error":"Enfant introuvable"}"#)));
+        assert!(!fits_in_buffer(&Body::from(vec![b' '; MAX_ERROR_BODY_BYTES + 1])));
+        let stream = futures_util::stream::iter([Ok::<_, std::io::Error>(axum::body::Bytes::from_static(b"{}"))]);
+        assert!(!fits_in_buffer(&Body::from_stream(stream)));
+    }
+}
@@ -0,0 +1,100 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{MatchedPath, Request},
+    http::{request::Parts, Response},
+    middleware::Next,
+    response::IntoResponse,
+};
+use sentry::{protocol::Event, Hub, Level, SentryFutureExt};
+use serde_json::Value;
+
+use crate::middleware::{
+    auth::decode_access_token,
+    request_id::RequestId,
+    tenant::extract_slug,
+};
+use crate::error::ApiError;
+use crate::models::auth::AuthenticatedUser;
+use crate::services::jwt_keys::JwtKeys;
+
+/// Bodies of 5xx responses are read to report the error message; larger ones are not.
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+/// Report panics and 5xx responses to Sentry, tagged with the tenant, user and route.
+///
+/// Each request runs on its own hub so the panic integration picks up the same tags.
+/// A no-op when SENTRY_DSN is unset.
+pub async fn report_errors(req: Request, next: Next) -> Response<Body> {
+    if Hub::current().client().is_none() {
+        return next.run(req).await;
+    }
+
+    let (parts, body) = req.into_parts();
+    let route = parts
+        .extensions
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| parts.uri.path().to_string());
+    let transaction = format!("{} {route}", parts.method);
+
+    let user = caller(&parts);
+    let tenant = extract_slug(&parts).ok().or_else(|| user.as_ref().map(|u| u.tenant.clone()));
+    let request_id = parts.extensions.get::<RequestId>().map(|id| id.0.clone());
+
+    let hub = Arc::new(Hub::new_from_top(Hub::current()));
+    hub.configure_scope(|scope| {
+        scope.set_transaction(Some(&transaction));
+        scope.set_tag("route", &route);
+        if let Some(request_id) = request_id {
+            scope.set_tag("request_id", request_id);
+        }
+        if let Some(tenant) = tenant {
+            scope.set_tag("tenant", tenant);
+        }
+        if let Some(user) = user {
+            let user_id = user.user_id.to_string();
+            scope.set_tag("user_id", &user_id);
+            scope.set_user(Some(sentry::User { id: Some(user_id), ..Default::default() }));
+        }
+    });
+
+    let response = next
+        .run(Request::from_parts(parts, body))
+        .bind_hub(hub.clone())
+        .await;
+    if !response.status().is_server_error() {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = to_bytes(body, MAX_ERROR_BODY_BYTES).await.unwrap_or_default();
+    let error = serde_json::from_slice::<Value>(&bytes)
+        .ok()
+        .and_then(|v| v.get("error").and_then(Value::as_str).map(str::to_string));
+    hub.capture_event(Event {
+        level: Level::Error,
+        message: Some(error.unwrap_or_else(|| format!("HTTP {}", parts.status))),
+        transaction: Some(transaction),
+        ..Default::default()
+    });
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// Response for a handler that panicked; the panic itself was already reported by the hook.
+pub fn panic_response(_err: Box<dyn Any + Send + 'static>) -> Response<Body> {
+    ApiError::internal().into_response()
+}
+
+/// The caller if it sent a valid access token; no session check, this is only for tags.
+pub(crate) fn caller(parts: &Parts) -> Option<AuthenticatedUser> {
+    let token = parts
+        .headers
+        .get("Authorization")
+        .and_then(|v| v.to_str().ok())?
+        .strip_prefix("Bearer ")?;
+    let keys = parts.extensions.get::<Arc<JwtKeys>>()?;
+    decode_access_token(token, keys).ok()
+}
@@ -0,0 +1,47 @@
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::warn;
+
+use crate::{
+    error::ApiError,
+    middleware::tenant::extract_slug,
+    services::{
+        feature_flags::{FeatureFlagService, FeatureFlags, MAINTENANCE_MODE},
+    },
+    AppState,
+};
+
+/// Resolves the feature flags of the request's garderie into a [`FeatureFlags`] extension
+/// for handlers, and answers 503 to everything but super-admin traffic while the
+/// platform-wide `maintenance_mode` flag is on. Flags that can't be read count as off.
+pub async fn feature_flags(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let flags = FeatureFlagService::all(&state.db).await.unwrap_or_else(|e| {
+        warn!("Feature flags unavailable: {e}");
+        Vec::new()
+    });
+
+    let path = request.uri().path();
+    let super_admin = path.starts_with("/super-admin")
+        || path.starts_with("/health")
+        || request
+            .headers()
+            .get("X-Super-Admin-Key")
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|key| key == state.config.super_admin_key);
+    if !super_admin && FeatureFlags::resolve(&flags, None).is_enabled(MAINTENANCE_MODE) {
+        return (
+            [(header::RETRY_AFTER, "300")],
+            ApiError::from_key(StatusCode::SERVICE_UNAVAILABLE, "maintenance").with_code("maintenance"),
+        )
+            .into_response();
+    }
+
+    let (mut parts, body) = request.into_parts();
+    let slug = extract_slug(&parts).ok();
+    parts.extensions.insert(FeatureFlags::resolve(&flags, slug.as_deref()));
+    next.run(Request::from_parts(parts, body)).await
+}
@@ -0,0 +1,78 @@
+use std::collections::HashSet;
+
+use axum::{
+    extract::{FromRequestParts, Query},
+    http::request::Parts,
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Deserialize)]
+struct FieldsQuery {
+    fields: Option<String>,
+}
+
+/// `?fields=id,caption,thumbnail_url` of a list: the keys to keep in each item
+/// (sparse fieldset). `id` is always kept; unknown names are ignored, and no
+/// parameter keeps every field.
+#[derive(Debug, Clone, Default)]
+pub struct Fields(Option<HashSet<String>>);
+
+impl Fields {
+    pub fn parse(raw: &str) -> Self {
+        let names: HashSet<String> =
+            raw.split(',').map(str::trim).filter(|n| !n.is_empty()).map(str::to_string).collect();
+        Fields((!names.is_empty()).then_some(names))
+    }
+
+    /// Drop the keys not asked for from `items`, an array of objects (or a single one).
+    pub fn select(&self, items: &mut Value) {
+        let Some(keep) = &self.0 else {
+            return;
+        };
+        let retain = |item: &mut Value| {
+            if let Value::Object(map) = item {
+                map.retain(|key, _| key == "id" || keep.contains(key));
+            }
+        };
+        match items {
+            Value::Array(list) => list.iter_mut().for_each(retain),
+            item => retain(item),
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Fields {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Query::<FieldsQuery>::try_from_uri(&parts.uri)
+            .ok()
+            .and_then(|Query(q)| q.fields)
+            .map(|raw| Fields::parse(&raw))
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_select() {
+        let mut items = json!([
+            { "id": 1, "caption": "Pique-nique", "storage_path": "a/b.jpg", "url": "/media/files/a/b.jpg" },
+            { "id": 2, "caption": null, "storage_path": "a/c.jpg", "url": "/media/files/a/c.jpg" },
+        ]);
+        Fields::parse("caption, url,,nope").select(&mut items);
+        assert_eq!(items, json!([
+            { "id": 1, "caption": "Pique-nique", "url": "/media/files/a/b.jpg" },
+            { "id": 2, "caption": null, "url": "/media/files/a/c.jpg" },
+        ]));
+
+        let mut item = json!({ "id": 1, "caption": "x" });
+        Fields::parse("").select(&mut item);
+        assert_eq!(item, json!({ "id": 1, "caption": "x" }));
+    }
+}
@@ -0,0 +1,205 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::{error::ApiError, middleware::error_reporting::caller, AppState};
+
+pub const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+/// Set on a response served from the store instead of running the request again.
+pub const IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+/// How long a completed response can be replayed.
+const REPLAY_TTL_SECS: u64 = 24 * 60 * 60;
+/// How long a request holds its key while it runs; a crashed request frees it by then.
+const PENDING_TTL_SECS: u64 = 5 * 60;
+/// Larger responses are not kept (the request then runs again on retry).
+const MAX_STORED_BYTES: usize = 256 * 1024;
+/// Request bodies are read whole to be hashed, up to the API's body limit.
+const MAX_REQUEST_BYTES: usize = 100 * 1024 * 1024;
+/// Prefix of the value held while the request runs, followed by its body hash.
+const PENDING: &str = "pending:";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredResponse {
+    status: u16,
+    content_type: Option<String>,
+    body: String,
+    /// SHA-256 of the request body; absent from entries stored before it was kept.
+    #[serde(default)]
+    request_hash: Option<String>,
+}
+
+/// Replays POSTs sent again with the same `Idempotency-Key` header, so a client retrying
+/// after a lost response does not post the message or send the emails twice. Keys are
+/// per user and path; a successful (2xx) response is kept [`REPLAY_TTL_SECS`], anything
+/// else frees the key for a new attempt. A key reused with a different body is refused
+/// (422) rather than replayed. Requests without the header, without a valid access
+/// token or while Redis is unavailable go through as usual.
+pub async fn idempotent(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    if request.method() != axum::http::Method::POST {
+        return next.run(request).await;
+    }
+    let Some(key) = request.headers().get(&IDEMPOTENCY_KEY) else {
+        return next.run(request).await;
+    };
+    let Some(key) = key.to_str().ok().filter(|k| is_valid_key(k)).map(str::to_owned) else {
+        return ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_idempotency_key").into_response();
+    };
+    let (parts, body) = request.into_parts();
+    let Some(user) = caller(&parts) else {
+        return next.run(Request::from_parts(parts, body)).await;
+    };
+    let bytes = match to_bytes(body, MAX_REQUEST_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+    let request_hash = hex::encode(Sha256::digest(&bytes));
+    let request = Request::from_parts(parts, Body::from(bytes));
+    let redis_key = format!("idempotency:{}:{}:{}:{key}", user.tenant, user.user_id, request.uri().path());
+
+    let mut redis = state.redis.clone();
+    let claimed: Result<bool, _> = redis::cmd("SET")
+        .arg(&redis_key)
+        .arg(format!("{PENDING}{request_hash}"))
+        .arg("NX")
+        .arg("EX")
+        .arg(PENDING_TTL_SECS)
+        .query_async::<Option<String>>(&mut redis)
+        .await
+        .map(|ok| ok.is_some());
+    match claimed {
+        Ok(true) => {}
+        Ok(false) => {
+            let stored: Option<String> = redis.get(&redis_key).await.unwrap_or(None);
+            let (response, stored_hash) = parse_entry(stored.as_deref().unwrap_or_default());
+            if stored_hash.is_some_and(|hash| hash != request_hash) {
+                return ApiError::from_key(StatusCode::UNPROCESSABLE_ENTITY, "idempotency_key_reused").into_response();
+            }
+            return match response {
+                Some(stored) => replay(stored),
+                // Still running, or just freed by a failure: the client should retry later
+                None => (
+                    [(header::RETRY_AFTER, "1")],
+                    ApiError::from_key(StatusCode::CONFLICT, "idempotency_in_progress"),
+                )
+                    .into_response(),
+            };
+        }
+        Err(e) => {
+            warn!("Idempotency store unavailable: {e}");
+            return next.run(request).await;
+        }
+    }
+
+    let response = next.run(request).await;
+    if !response.status().is_success() {
+        let _: Result<(), _> = redis.del(&redis_key).await;
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            let _: Result<(), _> = redis.del(&redis_key).await;
+            warn!("Idempotent response unreadable: {e}");
+            return ApiError::internal().into_response();
+        }
+    };
+    let stored = (bytes.len() <= MAX_STORED_BYTES)
+        .then(|| String::from_utf8(bytes.to_vec()).ok())
+        .flatten()
+        .map(|body| StoredResponse {
+            status: parts.status.as_u16(),
+            content_type: parts.headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).map(str::to_owned),
+            body,
+            request_hash: Some(request_hash),
+        });
+    let saved: Result<(), _> = match stored {
+        Some(stored) => redis.set_ex(&redis_key, serde_json::to_string(&stored).unwrap_or_default(), REPLAY_TTL_SECS).await,
+        None => redis.del(&redis_key).await,
+    };
+    if let Err(e) = saved {
+        warn!("Idempotent response not stored: {e}");
+    }
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// The stored response, if the request completed, and the body hash it was sent with.
+fn parse_entry(value: &str) -> (Option<StoredResponse>, Option<String>) {
+    match serde_json::from_str::<StoredResponse>(value) {
+        Ok(response) => {
+            let hash = response.request_hash.clone();
+            (Some(response), hash)
+        }
+        Err(_) => (None, value.strip_prefix(PENDING).map(str::to_owned)),
+    }
+}
+
+fn replay(stored: StoredResponse) -> Response {
+    let status = StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK);
+    let mut response = (status, stored.body).into_response();
+    let headers = response.headers_mut();
+    if let Some(content_type) = stored.content_type.and_then(|v| HeaderValue::from_str(&v).ok()) {
+        headers.insert(header::CONTENT_TYPE, content_type);
+    }
+    headers.insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+    response
+}
+
+/// 1 to 255 visible ASCII characters, e.g. a UUID generated by the client.
+fn is_valid_key(key: &str) -> bool {
+    (1..=255).contains(&key.len()) && key.bytes().all(|b| b.is_ascii_graphic())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_key() {
+        assert!(is_valid_key("3f2b8c1e-7d4a-4e3b-9a51-0c6f2d7e8a90"));
+        assert!(!is_valid_key(""));
+        assert!(!is_valid_key("avec espace"));
+        assert!(!is_valid_key(&"a".repeat(256)));
+    }
+
+    #[test]
+    fn test_parse_entry() {
+        let (response, hash) = parse_entry("pending:abc");
+        assert!(response.is_none());
+        assert_eq!(hash.as_deref(), Some("abc"));
+
+        let stored = r#"{"status":201,"content_type":null,"body":"{}","request_hash":"abc"}"#;
+        let (response, hash) = parse_entry(stored);
+        assert_eq!(response.map(|r| r.status), Some(201));
+        assert_eq!(hash.as_deref(), Some("abc"));
+
+        // Stored before request hashes were kept: replayed whatever the body
+        let (response, hash) = parse_entry(r#"{"status":201,"content_type":null,"body":"{}"}"#);
+        assert!(response.is_some());
+        assert!(hash.is_none());
+    }
+
+    #[test]
+    fn test_replay() {
+        let stored = StoredResponse {
+            status: 201,
+            content_type: Some("application/json".into()),
+            body: r#"{"id":"1"}"#.into(),
+            request_hash: None,
+        };
+        let response = replay(stored);
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(response.headers()[IDEMPOTENT_REPLAYED], "true");
+    }
+}
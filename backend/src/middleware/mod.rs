@@ -1,4 +1,15 @@
+pub mod api_version;
 pub mod auth;
+pub mod compression;
+pub mod error_i18n;
+pub mod error_reporting;
+pub mod feature_flags;
+pub mod fields;
+pub mod idempotency;
+pub mod precondition;
 pub mod rate_limit;
+pub mod request_id;
 pub mod super_admin;
 pub mod tenant;
+pub mod trial;
+pub mod validation;
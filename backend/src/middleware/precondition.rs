@@ -0,0 +1,185 @@
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderMap, HeaderName, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::error::ApiError;
+
+/// `If-Match` of a conditional update: the version the client last read. Accepts the
+/// `ETag` of a previous response or the record's `updated_at` as is; `*` or no header
+/// means an unconditional write.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IfMatch(pub Option<DateTime<Utc>>);
+
+impl<S: Send + Sync> FromRequestParts<S> for IfMatch {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(raw) = parts.headers.get(header::IF_MATCH) else {
+            return Ok(IfMatch(None));
+        };
+        let raw = raw.to_str().unwrap_or_default().trim();
+        if raw == "*" {
+            return Ok(IfMatch(None));
+        }
+        parse_version(raw)
+            .map(|v| IfMatch(Some(v)))
+            .ok_or_else(|| ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_if_match"))
+    }
+}
+
+/// Strong `ETag` of a record version: its `updated_at` in microseconds, as stored.
+pub fn etag(updated_at: DateTime<Utc>) -> String {
+    format!("\"{}\"", updated_at.timestamp_micros())
+}
+
+fn parse_version(raw: &str) -> Option<DateTime<Utc>> {
+    let tag = raw.strip_prefix("W/").unwrap_or(raw).trim_matches('"');
+    match tag.parse::<i64>() {
+        Ok(micros) => DateTime::from_timestamp_micros(micros),
+        Err(_) => DateTime::parse_from_rfc3339(tag).ok().map(|d| d.with_timezone(&Utc)),
+    }
+}
+
+/// A response carrying the `ETag` of the record it returns.
+pub type Versioned<T> = ([(HeaderName, String); 1], Json<T>);
+
+pub fn versioned<T>(updated_at: DateTime<Utc>, body: T) -> Versioned<T> {
+    ([(header::ETAG, etag(updated_at))], Json(body))
+}
+
+/// The record changed since the client's `If-Match`; `current` is the stored version.
+#[derive(Debug, thiserror::Error)]
+#[error("Modifié entre-temps par quelqu'un d'autre : rechargez avant d'enregistrer")]
+pub struct StaleVersion {
+    pub current: Value,
+}
+
+/// 409 with the current version, so the client can show it or retry on top of it.
+pub fn conflict(stale: &StaleVersion) -> ApiError {
+    ApiError::from_key(StatusCode::CONFLICT, "version_conflict")
+        .with_code("version_conflict")
+        .with_extension("current", stale.current.clone())
+}
+
+/// How long a client may reuse a `GET` response before asking again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheClass {
+    /// Stored files: a storage path never gets other content.
+    File,
+    /// Child profile photos: who may see them follows group membership.
+    Avatar,
+    /// Lists: revalidated on every use, which costs a 304 when nothing changed.
+    List,
+}
+
+impl CacheClass {
+    pub fn header_value(self) -> &'static str {
+        match self {
+            CacheClass::File => "private, max-age=86400, immutable",
+            CacheClass::Avatar => "private, max-age=300",
+            CacheClass::List => "private, no-cache",
+        }
+    }
+}
+
+/// Strong `ETag` of a file: the SHA-256 of its plaintext, as in `media.content_sha256`.
+pub fn content_etag(bytes: &[u8]) -> String {
+    hash_etag(&hex::encode(Sha256::digest(bytes)))
+}
+
+/// `ETag` of an already known content hash.
+pub fn hash_etag(sha256: &str) -> String {
+    format!("\"{sha256}\"")
+}
+
+/// Strong `ETag` of a list: its length and newest `updated_at`, then a digest of the
+/// body for what those miss (a removed item replaced by an older one, per-user flags,
+/// signed URLs).
+fn list_etag(count: usize, last_updated: Option<DateTime<Utc>>, body: &[u8]) -> String {
+    let version = last_updated.map_or(0, |at| at.timestamp_micros());
+    let digest = hex::encode(&Sha256::digest(body)[..8]);
+    format!("\"{count}-{version}-{digest}\"")
+}
+
+/// Whether the client's `If-None-Match` already names `etag` (weak comparison, as
+/// this header asks for).
+pub fn not_modified(headers: &HeaderMap, etag: &str) -> bool {
+    let Some(raw) = headers.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    let etag = etag.trim_start_matches("W/");
+    raw.split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// 304 for a conditional `GET` whose copy is still current.
+pub fn not_modified_response(etag: &str, class: CacheClass) -> Response {
+    (
+        StatusCode::NOT_MODIFIED,
+        [(header::ETAG, etag.to_string()), (header::CACHE_CONTROL, class.header_value().to_string())],
+    )
+        .into_response()
+}
+
+/// A list answered with its `ETag`, or 304 when it is the one in `If-None-Match`.
+/// `updated` are the `updated_at` of its items.
+pub fn cached_list<T: Serialize>(
+    headers: &HeaderMap,
+    updated: impl IntoIterator<Item = DateTime<Utc>>,
+    body: &T,
+) -> Response {
+    let (count, last_updated) = updated
+        .into_iter()
+        .fold((0, None), |(n, last), at| (n + 1, Some(last.map_or(at, |l: DateTime<Utc>| l.max(at)))));
+    let bytes = serde_json::to_vec(body).unwrap_or_default();
+    let etag = list_etag(count, last_updated, &bytes);
+    if not_modified(headers, &etag) {
+        return not_modified_response(&etag, CacheClass::List);
+    }
+    (
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (header::ETAG, etag),
+            (header::CACHE_CONTROL, CacheClass::List.header_value().to_string()),
+        ],
+        bytes,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        let at = DateTime::parse_from_rfc3339("2026-10-18T14:03:07.123456Z").unwrap().with_timezone(&Utc);
+        assert_eq!(parse_version(&etag(at)), Some(at));
+        assert_eq!(parse_version(&format!("W/{}", etag(at))), Some(at));
+        assert_eq!(parse_version("2026-10-18T10:03:07.123456-04:00"), Some(at));
+        assert_eq!(parse_version("\"abc\""), None);
+    }
+
+    #[test]
+    fn test_cached_list() {
+        let at = DateTime::parse_from_rfc3339("2026-10-18T14:03:07Z").unwrap().with_timezone(&Utc);
+        let first = cached_list(&HeaderMap::new(), [at, at - chrono::Duration::hours(1)], &["a", "b"]);
+        assert_eq!(first.status(), StatusCode::OK);
+        let etag = first.headers()[header::ETAG].to_str().unwrap().to_string();
+        assert!(etag.starts_with(&format!("\"2-{}-", at.timestamp_micros())));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, format!("\"other\", W/{etag}").parse().unwrap());
+        assert_eq!(cached_list(&headers, [at, at - chrono::Duration::hours(1)], &["a", "b"]).status(), StatusCode::NOT_MODIFIED);
+        // Same count and newest version, different items
+        assert_eq!(cached_list(&headers, [at, at - chrono::Duration::hours(1)], &["a", "c"]).status(), StatusCode::OK);
+    }
+}
@@ -1,5 +1,6 @@
-use axum::{http::StatusCode, Json};
-use serde_json::json;
+use axum::http::StatusCode;
+
+use crate::error::ApiError;
 
 /// Checks an email-keyed rate limit stored in Redis.
 ///
@@ -12,7 +13,7 @@ pub async fn check_rate_limit(
     key: &str,
     max_attempts: u64,
     window_secs: u64,
-) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+) -> Result<(), ApiError> {
     let count: u64 = redis::cmd("INCR")
         .arg(key)
         .query_async(redis)
@@ -29,10 +30,7 @@ pub async fn check_rate_limit(
     }
 
     if count > max_attempts {
-        return Err((
-            StatusCode::TOO_MANY_REQUESTS,
-            Json(json!({ "error": "Trop de tentatives. Réessayez dans quelques minutes." })),
-        ));
+        return Err(ApiError::from_key(StatusCode::TOO_MANY_REQUESTS, "too_many_attempts"));
     }
 
     Ok(())
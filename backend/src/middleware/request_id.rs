@@ -0,0 +1,73 @@
+use axum::{
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::{field, Instrument};
+use uuid::Uuid;
+
+use crate::{
+    db::tenant_db::TenantDb,
+    middleware::{auth::decode_access_token, tenant::request_tenant},
+    AppState,
+};
+
+pub static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Id of the current request, also available to handlers as an extension.
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// An id from the proxy is kept if it is short and printable, so that nginx and API
+/// logs can be joined; otherwise a new one is generated.
+fn incoming_id(value: Option<&HeaderValue>) -> Option<String> {
+    let id = value?.to_str().ok()?;
+    let valid = !id.is_empty()
+        && id.len() <= 64
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    valid.then(|| id.to_string())
+}
+
+/// Opens the `request` span carrying the request id, tenant slug and user id, so
+/// that every log line of the request (TraceLayer's included) can be correlated,
+/// and runs the request in the [`TenantDb::scope`] of that tenant.
+/// The id is returned in `X-Request-Id`.
+pub async fn request_context(State(state): State<AppState>, mut req: Request, next: Next) -> Response {
+    let request_id = incoming_id(req.headers().get(&X_REQUEST_ID)).unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let span = tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        tenant = field::Empty,
+        user_id = field::Empty,
+    );
+
+    let (parts, body) = req.into_parts();
+    // No session check: an invalid token only leaves the field empty
+    let user = parts
+        .headers
+        .get("Authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .and_then(|token| decode_access_token(token, &state.jwt_keys).ok());
+    let tenant = request_tenant(&parts, user.as_ref());
+    if let Some(tenant) = &tenant {
+        span.record("tenant", tenant.as_str());
+    }
+    if let Some(user) = &user {
+        span.record("user_id", field::display(user.user_id));
+    }
+    req = Request::from_parts(parts, body);
+    req.extensions_mut().insert(RequestId(request_id.clone()));
+
+    let mut response = match tenant {
+        Some(tenant) => TenantDb::scope(tenant, next.run(req)).instrument(span).await,
+        None => next.run(req).instrument(span).await,
+    };
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(X_REQUEST_ID.clone(), value);
+    }
+    response
+}
+
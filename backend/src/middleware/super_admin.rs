@@ -3,13 +3,20 @@ use axum::{
     http::{request::Parts, StatusCode},
 };
 
-use crate::AppState;
+use crate::{error::ApiError, AppState};
 
 /// Extractor that validates the `X-Super-Admin-Key` header against `config.super_admin_key`.
-pub struct SuperAdminAuth;
+///
+/// The key is shared, so callers may also identify themselves with
+/// `X-Super-Admin-Email` and `X-Super-Admin-Password`, checked against the active row
+/// of `public.super_admins`. Only such an `operator` may request, confirm or cancel
+/// a pending operation.
+pub struct SuperAdminAuth {
+    pub operator: Option<String>,
+}
 
 impl FromRequestParts<AppState> for SuperAdminAuth {
-    type Rejection = (StatusCode, &'static str);
+    type Rejection = ApiError;
 
     async fn from_request_parts(
         parts: &mut Parts,
@@ -19,12 +26,39 @@ impl FromRequestParts<AppState> for SuperAdminAuth {
             .headers
             .get("X-Super-Admin-Key")
             .and_then(|v| v.to_str().ok())
-            .ok_or((StatusCode::UNAUTHORIZED, "Missing X-Super-Admin-Key header"))?;
+            .ok_or_else(|| ApiError::new(StatusCode::UNAUTHORIZED, "Missing X-Super-Admin-Key header"))?;
 
         if key != state.config.super_admin_key {
-            return Err((StatusCode::UNAUTHORIZED, "Invalid super-admin key"));
+            return Err(ApiError::new(StatusCode::UNAUTHORIZED, "Invalid super-admin key"));
+        }
+
+        let Some(email) = parts
+            .headers
+            .get("X-Super-Admin-Email")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_lowercase())
+            .filter(|v| !v.is_empty())
+        else {
+            return Ok(SuperAdminAuth { operator: None });
+        };
+
+        let password = parts
+            .headers
+            .get("X-Super-Admin-Password")
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| ApiError::new(StatusCode::UNAUTHORIZED, "Missing X-Super-Admin-Password header"))?;
+
+        let hash: Option<String> = sqlx::query_scalar(
+            "SELECT password_hash FROM public.super_admins WHERE LOWER(email) = $1 AND is_active = TRUE",
+        )
+        .bind(&email)
+        .fetch_optional(&state.db)
+        .await?;
+        let valid = hash.is_some_and(|hash| bcrypt::verify(password, &hash).unwrap_or(false));
+        if !valid {
+            return Err(ApiError::new(StatusCode::UNAUTHORIZED, "Invalid super-admin credentials"));
         }
 
-        Ok(SuperAdminAuth)
+        Ok(SuperAdminAuth { operator: Some(email) })
     }
 }
@@ -4,17 +4,17 @@ use axum::{
     middleware::Next,
     extract::Request,
     response::Response,
-    Json,
 };
-use chrono::Utc;
-use serde_json::{json, Value};
 
+use crate::error::ApiError;
+use crate::models::auth::AuthenticatedUser;
+use crate::models::user::UserRole;
 use crate::AppState;
 
 /// Validates that a slug only contains lowercase ASCII letters, digits and hyphens,
 /// does not start or end with a hyphen, and is between 2 and 63 characters.
 /// This prevents SQL injection via the tenant name used in format!() schema queries.
-fn is_valid_slug(s: &str) -> bool {
+pub(crate) fn is_valid_slug(s: &str) -> bool {
     let len = s.len();
     len >= 2
         && len <= 63
@@ -24,41 +24,37 @@ fn is_valid_slug(s: &str) -> bool {
 }
 
 /// Extracts the tenant slug from the `X-Tenant` header or first subdomain,
-/// then validates the tenant is active and its trial has not expired.
+/// then validates the tenant is active and not archived. An expired trial only
+/// restricts writes, see [`crate::middleware::trial`].
 #[derive(Debug, Clone)]
 pub struct TenantSlug(pub String);
 
 impl FromRequestParts<AppState> for TenantSlug {
-    type Rejection = (StatusCode, Json<Value>);
+    type Rejection = ApiError;
 
     async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
         let slug = extract_slug(parts)?;
 
-        // DB check: verify tenant exists, is active, and trial has not expired
-        let row: Option<(bool, Option<chrono::DateTime<Utc>>)> = sqlx::query_as(
-            "SELECT is_active, trial_expires_at FROM public.garderies WHERE slug = $1",
+        // DB check: verify tenant exists, is active and not archived
+        let row: Option<(bool, bool)> = sqlx::query_as(
+            "SELECT is_active, archived_at IS NOT NULL FROM public.garderies WHERE slug = $1",
         )
         .bind(&slug)
         .fetch_optional(&state.db)
-        .await
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "Database error" }))))?;
+        .await?;
 
         match row {
-            None => Err((StatusCode::NOT_FOUND, Json(json!({ "error": "Tenant not found" })))),
-            Some((false, _)) => Err((StatusCode::FORBIDDEN, Json(json!({ "error": "Account is inactive" })))),
-            Some((true, Some(expires_at))) if expires_at < Utc::now() => Err((
-                StatusCode::PAYMENT_REQUIRED,
-                Json(json!({
-                    "error": "La période d'essai est terminée. Veuillez contacter le support.",
-                    "code": "trial_expired"
-                })),
-            )),
+            None => Err(ApiError::from_key(StatusCode::NOT_FOUND, "tenant_not_found")),
+            Some((_, true)) => {
+                Err(ApiError::from_key(StatusCode::GONE, "garderie_archived").with_code("garderie_archived"))
+            }
+            Some((false, _)) => Err(ApiError::from_key(StatusCode::FORBIDDEN, "account_inactive")),
             Some(_) => Ok(TenantSlug(slug)),
         }
     }
 }
 
-fn extract_slug(parts: &Parts) -> Result<String, (StatusCode, Json<Value>)> {
+pub(crate) fn extract_slug(parts: &Parts) -> Result<String, ApiError> {
     // 1. X-Tenant header
     if let Some(tenant) = parts
         .headers
@@ -68,7 +64,7 @@ fn extract_slug(parts: &Parts) -> Result<String, (StatusCode, Json<Value>)> {
         .filter(|s| !s.is_empty())
     {
         if !is_valid_slug(&tenant) {
-            return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "Invalid tenant identifier" }))));
+            return Err(ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_tenant"));
         }
         return Ok(tenant);
     }
@@ -81,14 +77,33 @@ fn extract_slug(parts: &Parts) -> Result<String, (StatusCode, Json<Value>)> {
             let subdomain = parts_vec[0].to_lowercase();
             if subdomain != "www" && subdomain != "api" {
                 if !is_valid_slug(&subdomain) {
-                    return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "Invalid tenant identifier" }))));
+                    return Err(ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_tenant"));
                 }
                 return Ok(subdomain);
             }
         }
     }
 
-    Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "Missing X-Tenant header" }))))
+    Err(ApiError::from_key(StatusCode::BAD_REQUEST, "missing_tenant"))
+}
+
+/// Garderie a request works on, for [`TenantDb::scope`](crate::db::tenant_db::TenantDb::scope):
+/// the one in the path of the logo, tracking and super-admin garderie routes, else the
+/// signed-in user's, else the one named by the header or subdomain.
+pub(crate) fn request_tenant(parts: &Parts, user: Option<&AuthenticatedUser>) -> Option<String> {
+    let segments: Vec<&str> = parts.uri.path().trim_start_matches('/').split('/').collect();
+    let from_path = match segments.as_slice() {
+        ["logos", slug, ..] | ["t", slug, ..] => Some(*slug),
+        ["super-admin", "garderies" | "audit-log", slug, ..] => Some(*slug),
+        _ => None,
+    };
+    if let Some(slug) = from_path.filter(|s| is_valid_slug(s)) {
+        return Some(slug.to_string());
+    }
+    match user {
+        Some(user) if user.role != UserRole::SuperAdmin => Some(user.tenant.clone()),
+        _ => extract_slug(parts).ok(),
+    }
 }
 
 /// Middleware that ensures tenant resolution succeeds for protected routes.
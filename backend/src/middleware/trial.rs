@@ -0,0 +1,59 @@
+use axum::{
+    extract::{Request, State},
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+
+use crate::{error::ApiError, middleware::tenant::extract_slug, services::trial_scheduler::TrialPhase, AppState};
+
+/// Writes still accepted from a read-only garderie: signing in and out, and the
+/// account operations a user can't be denied (password, 2FA, deletion request).
+const READ_ONLY_ALLOWED: &[&str] = &[
+    "/auth/login",
+    "/auth/refresh",
+    "/auth/logout",
+    "/auth/verify-2fa",
+    "/auth/switch-tenant",
+    "/auth/forgot-password",
+    "/auth/reset-password",
+    "/auth/change-password",
+    "/auth/sessions",
+    "/auth/push-token",
+    "/auth/account/deletion-request",
+    "/graphql",
+];
+
+/// Puts a garderie in read-only mode once its trial has been expired for longer than
+/// TRIAL_GRACE_DAYS. The expiry date is read on every write, so extending the trial
+/// lifts the restriction immediately.
+pub async fn enforce_trial(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    if matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return next.run(request).await;
+    }
+    let path = request.uri().path();
+    if path.starts_with("/super-admin") || READ_ONLY_ALLOWED.iter().any(|p| path.starts_with(p)) {
+        return next.run(request).await;
+    }
+
+    let (parts, body) = request.into_parts();
+    // No tenant (public routes): the handler decides
+    let Ok(slug) = extract_slug(&parts) else {
+        return next.run(Request::from_parts(parts, body)).await;
+    };
+
+    let expires_at: Option<Option<DateTime<Utc>>> =
+        sqlx::query_scalar("SELECT trial_expires_at FROM public.garderies WHERE slug = $1")
+            .bind(&slug)
+            .fetch_optional(&state.db)
+            .await
+            .unwrap_or(None);
+
+    match expires_at.map(|e| TrialPhase::of(e, state.config.trial_grace_days, Utc::now())) {
+        Some(TrialPhase::ReadOnly) => ApiError::from_key(StatusCode::PAYMENT_REQUIRED, "trial_read_only")
+            .with_code("trial_read_only")
+            .into_response(),
+        _ => next.run(Request::from_parts(parts, body)).await,
+    }
+}
@@ -0,0 +1,161 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{FromRequest, OptionalFromRequest, Request},
+    http::{header, StatusCode},
+    Json,
+};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
+
+use crate::error::ApiError;
+
+/// Largest JSON body accepted by [`ValidJson`]; uploads go through multipart.
+pub const MAX_JSON_BODY_BYTES: usize = 1024 * 1024;
+
+/// `Json<T>` that also runs `T`'s [`Validate`] rules: a malformed body is a 400 (or 422
+/// when the JSON does not fit `T`), a body over [`MAX_JSON_BODY_BYTES`] a 413, and broken
+/// rules a 422 listing the offending fields under `errors`.
+pub struct ValidJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ValidJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let too_large = || ApiError::from_key(StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large");
+        let declared = req
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<usize>().ok());
+        if declared.is_some_and(|len| len > MAX_JSON_BODY_BYTES) {
+            return Err(too_large());
+        }
+        let (parts, body) = req.into_parts();
+        let bytes = to_bytes(body, MAX_JSON_BODY_BYTES).await.map_err(|_| too_large())?;
+
+        let Json(value) = <Json<T> as FromRequest<S>>::from_request(Request::from_parts(parts, Body::from(bytes)), state)
+            .await
+            .map_err(|rejection| {
+                let errors = json!({ "body": [{ "code": "invalid_json", "message": rejection.body_text() }] });
+                ApiError::from_key(rejection.status(), "invalid_json").with_errors(errors)
+            })?;
+        value.validate().map_err(|errors| {
+            ApiError::from_key(StatusCode::UNPROCESSABLE_ENTITY, "validation_failed").with_errors(field_errors(&errors))
+        })?;
+        Ok(ValidJson(value))
+    }
+}
+
+/// Like `Option<Json<T>>`: no body (no `Content-Type`) is `None`, anything else must validate.
+impl<T, S> OptionalFromRequest<S> for ValidJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Option<Self>, Self::Rejection> {
+        if !req.headers().contains_key(header::CONTENT_TYPE) {
+            return Ok(None);
+        }
+        <Self as FromRequest<S>>::from_request(req, state).await.map(Some)
+    }
+}
+
+/// `{"field": [{"code", "message"}]}`, nested fields as `items[0].label`.
+pub fn field_errors(errors: &ValidationErrors) -> Value {
+    let mut out = Map::new();
+    collect(errors, "", &mut out);
+    Value::Object(out)
+}
+
+fn collect(errors: &ValidationErrors, prefix: &str, out: &mut Map<String, Value>) {
+    for (field, kind) in errors.errors() {
+        let path = if prefix.is_empty() { field.to_string() } else { format!("{prefix}.{field}") };
+        match kind {
+            ValidationErrorsKind::Field(list) => {
+                let entries = list
+                    .iter()
+                    .map(|e| json!({ "code": e.code, "message": message(e) }))
+                    .collect();
+                out.insert(path, Value::Array(entries));
+            }
+            ValidationErrorsKind::Struct(inner) => collect(inner, &path, out),
+            ValidationErrorsKind::List(items) => {
+                for (i, inner) in items {
+                    collect(inner, &format!("{path}[{i}]"), out);
+                }
+            }
+        }
+    }
+}
+
+/// French text of a broken rule (the custom message when the rule has one).
+fn message(e: &ValidationError) -> String {
+    if let Some(message) = &e.message {
+        return message.to_string();
+    }
+    let param = |name: &str| e.params.get(name).map(Value::to_string);
+    match (e.code.as_ref(), param("min"), param("max")) {
+        ("email", ..) => "Adresse courriel invalide".into(),
+        ("url", ..) => "Adresse web invalide".into(),
+        ("required", ..) => "Champ requis".into(),
+        ("length", Some(min), Some(max)) => format!("Doit contenir entre {min} et {max} caractères"),
+        ("length", Some(min), None) if min == "1" => "Ne peut pas être vide".into(),
+        ("length", Some(min), None) => format!("Doit contenir au moins {min} caractères"),
+        ("length", None, Some(max)) => format!("Doit contenir au plus {max} caractères"),
+        ("range", Some(min), Some(max)) => format!("Doit être compris entre {min} et {max}"),
+        ("range", Some(min), None) => format!("Doit être au moins {min}"),
+        ("range", None, Some(max)) => format!("Doit être au plus {max}"),
+        _ => "Valeur invalide".into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::limits::MAX_NAME;
+    use serde::Deserialize;
+
+    #[derive(Deserialize, Validate)]
+    struct Item {
+        #[validate(length(min = 1, max = MAX_NAME))]
+        label: String,
+    }
+
+    #[derive(Deserialize, Validate)]
+    struct Form {
+        #[validate(email)]
+        email: String,
+        #[validate(length(max = 5))]
+        note: Option<String>,
+        #[validate(nested)]
+        items: Vec<Item>,
+    }
+
+    #[test]
+    fn test_field_errors() {
+        let form = Form {
+            email: "pas-un-courriel".into(),
+            note: Some("trop long".into()),
+            items: vec![Item { label: "ok".into() }, Item { label: String::new() }],
+        };
+        let errors = field_errors(&form.validate().unwrap_err());
+        assert_eq!(errors["email"][0]["code"], "email");
+        assert_eq!(errors["email"][0]["message"], "Adresse courriel invalide");
+        assert_eq!(errors["note"][0]["message"], "Doit contenir au plus 5 caractères");
+        assert_eq!(errors["items[1].label"][0]["code"], "length");
+        assert!(errors.get("items[0].label").is_none());
+    }
+
+    #[test]
+    fn test_valid_form() {
+        let form = Form { email: "a@b.ca".into(), note: None, items: vec![] };
+        assert!(form.validate().is_ok());
+    }
+}
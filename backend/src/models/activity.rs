@@ -2,6 +2,8 @@ use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use crate::models::limits::{MAX_NOTE, MAX_TITLE};
+use validator::Validate;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Activity {
@@ -15,6 +17,9 @@ pub struct Activity {
     #[sqlx(rename = "type")]
     #[serde(rename = "type")]
     pub activity_type: String,
+    /// Fee billed to registered children by the monthly invoice batch, in cents.
+    #[sqlx(default)]
+    pub fee_cents: Option<i64>,
     pub created_by: Uuid,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -45,9 +50,11 @@ pub struct ActivityRegistrationWithChild {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct CreateActivityRequest {
+    #[validate(length(max = MAX_TITLE))]
     pub title: String,
+    #[validate(length(max = MAX_NOTE))]
     pub description: Option<String>,
     pub date: String, // YYYY-MM-DD
     pub end_date: Option<String>, // YYYY-MM-DD
@@ -55,11 +62,14 @@ pub struct CreateActivityRequest {
     pub group_id: Option<Uuid>,
     #[serde(rename = "type")]
     pub activity_type: Option<String>, // "theme" | "sortie", default "sortie"
+    pub fee_cents: Option<i64>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct UpdateActivityRequest {
+    #[validate(length(max = MAX_TITLE))]
     pub title: Option<String>,
+    #[validate(length(max = MAX_NOTE))]
     pub description: Option<String>,
     pub date: Option<String>, // YYYY-MM-DD
     pub end_date: Option<String>, // YYYY-MM-DD
@@ -67,9 +77,10 @@ pub struct UpdateActivityRequest {
     pub group_id: Option<Uuid>,
     #[serde(rename = "type")]
     pub activity_type: Option<String>,
+    pub fee_cents: Option<i64>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct RegisterRequest {
     pub child_id: Uuid,
 }
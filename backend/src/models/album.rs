@@ -0,0 +1,61 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::models::limits::{MAX_BULK_IDS, MAX_NOTE, MAX_TITLE};
+use validator::Validate;
+
+/// A named collection of media, shared with the same scopes as media themselves.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Album {
+    pub id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub cover_media_id: Option<Uuid>,
+    /// Thumbnail of the cover, or of the latest media when no cover is chosen.
+    pub cover_thumbnail_path: Option<String>,
+    /// Signed, expiring URL of `cover_thumbnail_path`.
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cover_thumbnail_url: Option<String>,
+    /// "private" | "public" | "group" | "child"
+    pub visibility: String,
+    pub group_id: Option<Uuid>,
+    pub child_id: Option<Uuid>,
+    pub created_by: Uuid,
+    pub media_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Body for POST /albums and PUT /albums/{id}.
+#[derive(Debug, Deserialize, Validate)]
+pub struct AlbumInput {
+    #[validate(length(max = MAX_TITLE))]
+    pub title: String,
+    #[validate(length(max = MAX_NOTE))]
+    pub description: Option<String>,
+    /// Must be one of the album's media.
+    pub cover_media_id: Option<Uuid>,
+    /// Defaults to "private".
+    pub visibility: Option<String>,
+    /// Required for "group" visibility.
+    pub group_id: Option<Uuid>,
+    /// Required for "child" visibility.
+    pub child_id: Option<Uuid>,
+}
+
+/// Body for POST /albums/{id}/media.
+#[derive(Debug, Deserialize, Validate)]
+pub struct AlbumMediaRequest {
+    #[validate(length(max = MAX_BULK_IDS))]
+    pub media_ids: Vec<Uuid>,
+}
+
+/// Body for POST /albums/{id}/share.
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct ShareAlbumRequest {
+    /// Optional note added to the email.
+    #[validate(length(max = MAX_NOTE))]
+    pub message: Option<String>,
+}
@@ -2,6 +2,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use crate::models::limits::{MAX_NOTE, MAX_TITLE};
+use validator::Validate;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Announcement {
@@ -13,9 +15,11 @@ pub struct Announcement {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct SetAnnouncementRequest {
+    #[validate(length(max = MAX_NOTE))]
     pub message: String,
     /// "yellow" | "red"
+    #[validate(length(max = MAX_TITLE))]
     pub color: Option<String>,
 }
@@ -2,6 +2,8 @@ use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use crate::models::limits::{MAX_BULK_IDS, MAX_NOTE};
+use validator::Validate;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -56,16 +58,17 @@ pub struct AttendanceRecord {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct SetAttendanceRequest {
     pub child_id: Uuid,
     pub date: String, // YYYY-MM-DD
     pub status: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct BulkSetAttendanceRequest {
     pub child_id: Uuid,
+    #[validate(length(max = MAX_BULK_IDS))]
     pub dates: Vec<String>, // ["YYYY-MM-DD", ...]
     pub status: String,
 }
@@ -87,3 +90,65 @@ pub struct AttendanceMonthResponse {
     pub date: String,
     pub status: String,
 }
+
+/// Statuses a parent may declare an absence with.
+pub const ABSENCE_STATUSES: &[&str] = &["absent", "malade", "vacances"];
+
+/// An absence declared ahead by a parent (sick day, vacation…). Each scheduled day
+/// of the range is marked in the attendance and the journal.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ChildAbsence {
+    pub id: Uuid,
+    pub child_id: Uuid,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub status: String,
+    pub reason: Option<String>,
+    pub declared_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Body for POST /children/{id}/absences.
+#[derive(Debug, Deserialize, Validate)]
+pub struct DeclareAbsenceRequest {
+    pub start_date: String, // YYYY-MM-DD
+    /// Defaults to `start_date` (a single day).
+    pub end_date: Option<String>,
+    /// One of `ABSENCE_STATUSES`; defaults to `absent`.
+    pub status: Option<String>,
+    #[validate(length(max = MAX_NOTE))]
+    pub reason: Option<String>,
+}
+
+/// A child marked absent by the cutoff-time check because nobody checked them in.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AutoAbsence {
+    pub id: Uuid,
+    pub child_id: Uuid,
+    pub date: NaiveDate,
+    pub marked_at: DateTime<Utc>,
+    /// A parent or staff member confirmed the child was away.
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub confirmed_by: Option<Uuid>,
+    /// The child was checked in after all: the journal mark was lifted.
+    pub reconciled_at: Option<DateTime<Utc>>,
+}
+
+/// A parent to ask about an automatic absence.
+#[derive(Debug, Clone, FromRow)]
+pub struct AutoAbsenceNotice {
+    pub auto_absence_id: Uuid,
+    pub child_id: Uuid,
+    pub child_first_name: String,
+    pub date: NaiveDate,
+    pub user_id: Uuid,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AutoAbsenceQuery {
+    /// YYYY-MM-DD, staff only; defaults to today.
+    pub date: Option<String>,
+}
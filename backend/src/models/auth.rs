@@ -1,4 +1,6 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
 use uuid::Uuid;
 
 use super::user::UserRole;
@@ -11,6 +13,15 @@ pub struct Claims {
     pub role: UserRole,
     pub exp: usize,
     pub iat: usize,
+    /// Refresh token (session) this access token was issued with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<Uuid>,
+    /// Cross-garderie identity of the user, allowing it to switch garderies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity: Option<Uuid>,
+    /// The user's `preferred_locale` when the token was issued, for error messages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
 }
 
 /// Claims embedded in the JWT refresh token
@@ -28,4 +39,46 @@ pub struct AuthenticatedUser {
     pub user_id: Uuid,
     pub tenant: String,
     pub role: UserRole,
+    pub session_id: Option<Uuid>,
+    pub identity_id: Option<Uuid>,
+    pub locale: Option<String>,
+}
+
+/// Where a login or token refresh came from; stored on the session it creates.
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip_address: String,
+}
+
+/// An active refresh token or trusted device, as listed by GET /auth/sessions.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ActiveSession {
+    pub id: Uuid,
+    /// "session" (refresh token) or "device" (trusted device skipping 2FA).
+    pub kind: String,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+    #[sqlx(skip)]
+    pub current: bool,
+}
+
+/// One login attempt of the user, as listed by GET /auth/login-history.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct LoginEvent {
+    pub id: Uuid,
+    pub success: bool,
+    /// "2fa_email", "2fa_sms", "trusted_device" or "oidc"; "password" for a wrong password.
+    pub method: String,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoginHistoryQuery {
+    pub limit: Option<i64>,
 }
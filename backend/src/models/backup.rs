@@ -0,0 +1,64 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// One archive of a tenant backup, as stored in S3.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupFile {
+    /// `db` (pg_dump of the tenant schema) or `media` (tar of its media directory).
+    pub kind: String,
+    pub key: String,
+    pub size: u64,
+    /// Hex SHA-256, checked before anything is restored.
+    pub sha256: String,
+}
+
+/// `manifest.json` written last; a backup without one is incomplete and ignored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// `YYYYMMDD_HHMMSS`, also the backup's folder name.
+    pub id: String,
+    pub slug: String,
+    pub garderie_name: String,
+    pub created_at: DateTime<Utc>,
+    pub files: Vec<BackupFile>,
+}
+
+impl BackupManifest {
+    pub fn total_size(&self) -> u64 {
+        self.files.iter().map(|f| f.size).sum()
+    }
+}
+
+/// POST /super-admin/garderies/{slug}/restore — also the payload of a `restore_garderie` operation.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct TenantRestoreRequest {
+    pub backup_id: String,
+    /// Also restore photos, videos and documents (default true).
+    pub include_media: Option<bool>,
+}
+
+/// One attempt at backing up a garderie, kept in `public.backup_runs`.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct BackupRun {
+    pub id: Uuid,
+    pub slug: String,
+    /// `scheduled` (nightly), `manual` (super-admin) or `archive` (before offboarding).
+    pub trigger: String,
+    /// `succeeded` or `failed`.
+    pub status: String,
+    /// Manifest id of the backup, when it succeeded.
+    pub backup_id: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BackupRunQuery {
+    pub slug: Option<String>,
+    pub limit: Option<i64>,
+}
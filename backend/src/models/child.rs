@@ -2,8 +2,10 @@ use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use crate::models::limits::{MAX_BULK_IDS, MAX_EMAIL, MAX_NAME, MAX_NOTE};
+use validator::Validate;
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, async_graphql::SimpleObject)]
 pub struct Child {
     pub id: Uuid,
     pub first_name: String,
@@ -15,9 +17,17 @@ pub struct Child {
     pub is_active: bool,
     pub start_date: Option<NaiveDate>,
     pub schedule_days: Option<Vec<i32>>,
+    /// Allergen tags (see `models::menu::ALLERGENS`), checked against planned menus.
+    pub allergies: Vec<String>,
+    /// Monthly fee billed by the invoice batch, in cents; falls back to the batch default.
+    pub monthly_fee_cents: Option<i64>,
+    /// Household the child belongs to with its siblings (see `models::family`).
+    pub family_id: Option<Uuid>,
     #[serde(skip_serializing)]
+    #[graphql(skip)]
     pub avatar_iv: Option<Vec<u8>>,
     #[serde(skip_serializing)]
+    #[graphql(skip)]
     pub avatar_tag: Option<Vec<u8>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -30,30 +40,43 @@ pub struct ChildParent {
     pub relationship: String, // "parent", "guardian", etc.
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct CreateChildRequest {
+    #[validate(length(min = 1, max = MAX_NAME))]
     pub first_name: String,
+    #[validate(length(min = 1, max = MAX_NAME))]
     pub last_name: String,
     pub birth_date: NaiveDate,
     pub group_id: Option<Uuid>,
+    #[validate(length(max = MAX_NOTE))]
     pub notes: Option<String>,
     pub start_date: Option<NaiveDate>,
+    #[validate(length(max = MAX_BULK_IDS))]
     pub schedule_days: Option<Vec<i32>>,
+    #[validate(length(max = MAX_BULK_IDS))]
+    pub allergies: Option<Vec<String>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct UpdateChildRequest {
+    #[validate(length(max = MAX_NAME))]
     pub first_name: Option<String>,
+    #[validate(length(max = MAX_NAME))]
     pub last_name: Option<String>,
     pub birth_date: Option<NaiveDate>,
     pub group_id: Option<Uuid>,
+    #[validate(length(max = MAX_NOTE))]
     pub notes: Option<String>,
     pub is_active: Option<bool>,
     pub start_date: Option<NaiveDate>,
+    #[validate(length(max = MAX_BULK_IDS))]
     pub schedule_days: Option<Vec<i32>>,
+    #[validate(length(max = MAX_BULK_IDS))]
+    pub allergies: Option<Vec<String>>,
+    pub monthly_fee_cents: Option<i64>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct AssignParentRequest {
     pub user_id: Uuid,
     pub relationship: String,
@@ -76,8 +99,9 @@ pub struct PendingParent {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct AssignPendingParentRequest {
+    #[validate(email, length(max = MAX_EMAIL))]
     pub email: String,
     pub relationship: String,
 }
@@ -90,8 +114,9 @@ pub struct InvitedParent {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct AssignInvitedParentRequest {
+    #[validate(email, length(max = MAX_EMAIL))]
     pub email: String,
     pub role: String,
 }
@@ -104,6 +129,8 @@ pub struct ImportRowError {
 
 #[derive(Debug, Serialize)]
 pub struct ImportResult {
+    /// True when nothing was written: the counts describe what an import would do.
+    pub dry_run: bool,
     pub created_groups: usize,
     pub created_children: usize,
     pub added_pending_parents: usize,
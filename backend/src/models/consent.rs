@@ -0,0 +1,68 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Version of the privacy policy parents accept today.
+pub const CURRENT_POLICY_VERSION: &str = "1.0";
+
+/// A parent's acceptance of the privacy policy and of photo sharing.
+#[derive(Debug, Serialize, FromRow)]
+pub struct ConsentRecord {
+    pub id: Uuid,
+    pub privacy_accepted: bool,
+    pub photos_accepted: bool,
+    pub accepted_at: DateTime<Utc>,
+    pub policy_version: String,
+    pub language: String,
+}
+
+/// Photo consent in force for a child.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ChildConsent {
+    pub child_id: Uuid,
+    pub photos_accepted: bool,
+    /// "child" when set for this child, "parent" when taken from a parent's
+    /// consent record, "none" when nobody consented yet.
+    pub source: String,
+    pub updated_by: Option<Uuid>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateChildConsentRequest {
+    pub photos_accepted: bool,
+}
+
+/// A parent without a consent record for the current policy.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ParentWithoutConsent {
+    pub id: Uuid,
+    pub name: String,
+    pub email: String,
+    /// Version they last accepted, if any.
+    pub policy_version: Option<String>,
+}
+
+/// An active child whose photos may not be shared.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ChildWithoutPhotoConsent {
+    pub id: Uuid,
+    pub first_name: String,
+    pub last_name: String,
+    pub group_id: Option<Uuid>,
+}
+
+/// GET /consents/report — where the garderie stands on Loi 25 consents.
+#[derive(Debug, Clone, Serialize)]
+pub struct ComplianceReport {
+    pub policy_version: &'static str,
+    pub parents_total: i64,
+    pub parents_consented: i64,
+    pub parents_photos_accepted: i64,
+    pub children_total: i64,
+    pub children_photos_accepted: i64,
+    pub parents_without_consent: Vec<ParentWithoutConsent>,
+    pub children_without_photo_consent: Vec<ChildWithoutPhotoConsent>,
+}
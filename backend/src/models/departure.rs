@@ -0,0 +1,65 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::media::Media;
+
+/// Days parents keep their account after the departure when none is given.
+pub const DEFAULT_PARENT_ACCESS_DAYS: i64 = 30;
+
+/// Body for PUT /children/{id}/departure.
+#[derive(Debug, Deserialize, Validate)]
+pub struct ScheduleDepartureRequest {
+    /// Last day at the garderie; today or earlier applies the departure right away.
+    pub departure_date: NaiveDate,
+    /// Days after the departure during which parents can still sign in and download
+    /// the final export (default 30).
+    #[validate(range(min = 1, max = 365))]
+    pub parent_access_days: Option<i64>,
+}
+
+/// Departure of a child, scheduled or applied.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ChildDeparture {
+    pub child_id: Uuid,
+    pub first_name: String,
+    pub last_name: String,
+    pub is_active: bool,
+    pub departed_on: Option<NaiveDate>,
+    /// Parents without another child at the garderie are deactivated after this day.
+    pub parent_access_until: Option<NaiveDate>,
+    /// When the child was deactivated; `None` while the departure is only scheduled.
+    pub departure_applied_at: Option<DateTime<Utc>>,
+    pub parent_access_ended_at: Option<DateTime<Utc>>,
+}
+
+/// A final export link sent to the parents; the token is only known to them.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct DepartureExport {
+    pub id: Uuid,
+    pub child_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    /// Parents the link was emailed to.
+    pub emailed_to: i32,
+    pub access_count: i32,
+    pub last_accessed_at: Option<DateTime<Utc>>,
+}
+
+/// What GET /departures/{token} returns: the child's journals and the photos and
+/// videos showing only them, with signed download URLs.
+#[derive(Debug, Serialize)]
+pub struct DepartureArchive {
+    pub first_name: String,
+    pub last_name: String,
+    pub birth_date: NaiveDate,
+    pub departed_on: Option<NaiveDate>,
+    pub expires_at: DateTime<Utc>,
+    pub journals: Value,
+    pub journal_events: Value,
+    pub media: Vec<Media>,
+}
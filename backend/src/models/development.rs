@@ -0,0 +1,194 @@
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::models::limits::{MAX_NOTE, MAX_TITLE};
+use validator::Validate;
+
+/// Developmental domains observations are filed under.
+pub const DOMAINS: &[&str] = &[
+    "langage",
+    "motricite_globale",
+    "motricite_fine",
+    "cognitif",
+    "socio_affectif",
+    "autonomie",
+];
+
+/// Age bands as (code, first month, first month of the next band).
+pub const AGE_BANDS: &[(&str, u32, u32)] = &[
+    ("0_12_mois", 0, 12),
+    ("12_24_mois", 12, 24),
+    ("24_36_mois", 24, 36),
+    ("36_48_mois", 36, 48),
+    ("48_60_mois", 48, 60),
+    ("60_mois_plus", 60, u32::MAX),
+];
+
+/// Progress on a milestone, from first signs to mastered.
+pub const OBSERVATION_STATUSES: &[&str] = &["emergent", "en_voie", "acquis"];
+
+/// Terms of the daycare year, as (code, first month, last month).
+pub const TERMS: &[(&str, u32, u32)] = &[
+    ("hiver", 1, 3),
+    ("printemps", 4, 6),
+    ("ete", 7, 8),
+    ("automne", 9, 12),
+];
+
+/// Age band of a child born on `birth_date`, on the given day.
+pub fn age_band_for(birth_date: NaiveDate, on: NaiveDate) -> &'static str {
+    let mut months = (on.year() - birth_date.year()) * 12 + on.month() as i32 - birth_date.month() as i32;
+    if on.day() < birth_date.day() {
+        months -= 1;
+    }
+    let months = months.max(0) as u32;
+    AGE_BANDS
+        .iter()
+        .find(|(_, from, to)| months >= *from && months < *to)
+        .map(|(code, _, _)| *code)
+        .unwrap_or("60_mois_plus")
+}
+
+/// Term code (`2026-automne`) containing the given day.
+pub fn term_for(date: NaiveDate) -> String {
+    let name = TERMS
+        .iter()
+        .find(|(_, first, last)| (*first..=*last).contains(&date.month()))
+        .map(|(name, _, _)| *name)
+        .unwrap_or("automne");
+    format!("{}-{name}", date.year())
+}
+
+/// First and last day of a term code such as `2026-automne`.
+pub fn term_range(term: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let (year, name) = term.split_once('-')?;
+    let year: i32 = year.parse().ok()?;
+    let (_, first, last) = TERMS.iter().find(|(n, _, _)| *n == name)?;
+    let from = NaiveDate::from_ymd_opt(year, *first, 1)?;
+    let to = if *last == 12 {
+        NaiveDate::from_ymd_opt(year, 12, 31)?
+    } else {
+        NaiveDate::from_ymd_opt(year, last + 1, 1)?.pred_opt()?
+    };
+    Some((from, to))
+}
+
+/// A milestone of the garderie's catalogue, e.g. "Dit ses premiers mots".
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Milestone {
+    pub id: Uuid,
+    pub domain: String,
+    pub age_band: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub position: i32,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Body for POST /milestones and PUT /milestones/{id}.
+#[derive(Debug, Deserialize, Validate)]
+pub struct MilestoneInput {
+    pub domain: String,
+    pub age_band: String,
+    #[validate(length(max = MAX_TITLE))]
+    pub title: String,
+    #[validate(length(max = MAX_NOTE))]
+    pub description: Option<String>,
+    pub position: Option<i32>,
+}
+
+/// Query params for GET /milestones.
+#[derive(Debug, Deserialize)]
+pub struct MilestoneQuery {
+    pub domain: Option<String>,
+    pub age_band: Option<String>,
+}
+
+/// An educator's note on a child's development, optionally tied to a milestone and a photo.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Observation {
+    pub id: Uuid,
+    pub child_id: Uuid,
+    pub milestone_id: Option<Uuid>,
+    pub milestone_title: Option<String>,
+    pub domain: String,
+    /// Child's age band on `observed_on`.
+    pub age_band: String,
+    pub observed_on: NaiveDate,
+    pub status: Option<String>,
+    pub notes: Option<String>,
+    pub media_id: Option<Uuid>,
+    pub media_type: Option<String>,
+    pub visible_to_parents: bool,
+    pub created_by: Uuid,
+    pub author_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Body for POST /children/{id}/observations and PUT /observations/{id}.
+/// `domain` may be omitted when a milestone is given.
+#[derive(Debug, Deserialize, Validate)]
+pub struct ObservationInput {
+    pub milestone_id: Option<Uuid>,
+    pub domain: Option<String>,
+    /// Defaults to today.
+    pub observed_on: Option<NaiveDate>,
+    pub status: Option<String>,
+    #[validate(length(max = MAX_NOTE))]
+    pub notes: Option<String>,
+    pub media_id: Option<Uuid>,
+    /// Defaults to true.
+    pub visible_to_parents: Option<bool>,
+}
+
+/// Query params for GET /children/{id}/observations.
+#[derive(Debug, Deserialize)]
+pub struct TimelineQuery {
+    pub domain: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+/// Query params for GET /children/{id}/observations/summary.
+#[derive(Debug, Deserialize)]
+pub struct SummaryQuery {
+    /// e.g. `2026-automne`; defaults to the current term.
+    pub term: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ObservationNote {
+    pub observed_on: NaiveDate,
+    pub notes: String,
+}
+
+/// One domain of a termly report.
+#[derive(Debug, Clone, Serialize)]
+pub struct DomainSummary {
+    pub domain: String,
+    pub observation_count: usize,
+    /// Milestones reached this term.
+    pub acquired: Vec<String>,
+    /// Milestones emerging or on their way.
+    pub in_progress: Vec<String>,
+    /// Milestones of the child's age band not observed yet.
+    pub upcoming: Vec<String>,
+    /// Most recent educator notes, newest first.
+    pub notes: Vec<ObservationNote>,
+}
+
+/// Termly development report for one child.
+#[derive(Debug, Clone, Serialize)]
+pub struct TermSummary {
+    pub child_id: Uuid,
+    pub child_name: String,
+    pub term: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub age_band: String,
+    pub domains: Vec<DomainSummary>,
+}
@@ -1,7 +1,9 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use crate::models::limits::{MAX_BULK_IDS, MAX_NAME, MAX_TITLE};
+use validator::Validate;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -58,23 +60,253 @@ pub struct Document {
     pub is_encrypted: bool,
     pub encryption_iv: Option<Vec<u8>>,
     pub encryption_tag: Option<Vec<u8>>,
+    pub requires_signature: bool,
+    pub folder_id: Option<Uuid>,
+    /// Starts at 1 and goes up each time the file is replaced.
+    pub version: i32,
+    /// Date after which the document (medical form, authorization…) must be renewed.
+    pub expires_at: Option<NaiveDate>,
+    /// Copied into the garderies cloned from this one.
+    pub is_template: bool,
+    /// Signed, expiring URL of `storage_path`.
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// A document as listed with `X-Api-Version: 2`: no storage path or encryption
+/// metadata, only the signed URL.
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentSummary {
+    pub id: Uuid,
+    pub uploader_id: Uuid,
+    pub title: String,
+    pub category: String,
+    pub original_filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub group_id: Option<Uuid>,
+    pub child_id: Option<Uuid>,
+    pub visibility: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub requires_signature: bool,
+    pub folder_id: Option<Uuid>,
+    pub version: i32,
+    pub expires_at: Option<NaiveDate>,
+    pub is_template: bool,
+    pub url: Option<String>,
+}
+
+impl From<Document> for DocumentSummary {
+    fn from(d: Document) -> Self {
+        Self {
+            id: d.id,
+            uploader_id: d.uploader_id,
+            title: d.title,
+            category: d.category,
+            original_filename: d.original_filename,
+            content_type: d.content_type,
+            size_bytes: d.size_bytes,
+            group_id: d.group_id,
+            child_id: d.child_id,
+            visibility: d.visibility,
+            created_at: d.created_at,
+            updated_at: d.updated_at,
+            requires_signature: d.requires_signature,
+            folder_id: d.folder_id,
+            version: d.version,
+            expires_at: d.expires_at,
+            is_template: d.is_template,
+            url: d.url,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct DocumentQuery {
     pub category: Option<String>,
+    pub folder_id: Option<Uuid>,
     pub group_id: Option<Uuid>,
     pub child_id: Option<Uuid>,
     pub page: Option<i64>,
     pub per_page: Option<i64>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct UpdateDocumentRequest {
+    #[validate(length(max = MAX_TITLE))]
     pub title: String,
+    #[validate(length(max = MAX_TITLE))]
     pub category: String,
     /// "private" | "public" | "group" | "child"
     pub visibility: String,
     pub group_id: Option<Uuid>,
     pub child_id: Option<Uuid>,
+    /// Leaves the flag unchanged when omitted.
+    pub requires_signature: Option<bool>,
+    /// Leaves the folder unchanged when omitted; use POST /documents/bulk to move back to the root.
+    pub folder_id: Option<Uuid>,
+    /// "YYYY-MM-DD"; an empty string clears the date, omitted leaves it unchanged.
+    pub expires_at: Option<String>,
+    /// Leaves the flag unchanged when omitted.
+    pub is_template: Option<bool>,
+}
+
+/// Query params for GET /documents/expiring.
+#[derive(Debug, Deserialize)]
+pub struct ExpiringQuery {
+    /// Horizon in days (default 30); already expired documents are always included.
+    pub days: Option<i32>,
+}
+
+/// A document expiring within the horizon, with the child it belongs to.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ExpiringDocument {
+    pub document_id: Uuid,
+    pub title: String,
+    pub category: String,
+    pub child_id: Option<Uuid>,
+    pub child_first_name: Option<String>,
+    pub child_last_name: Option<String>,
+    pub expires_at: NaiveDate,
+    /// Negative once expired.
+    pub days_left: i32,
+    /// Threshold (days before expiry) of the last reminder emailed to the parents.
+    pub reminder_sent_days: Option<i32>,
+}
+
+/// A parent to remind about one of their child's documents.
+#[derive(Debug, Clone, FromRow)]
+pub struct ExpiryReminderRow {
+    pub document_id: Uuid,
+    pub title: String,
+    pub expires_at: NaiveDate,
+    pub days_left: i32,
+    pub reminder_sent_days: Option<i32>,
+    pub child_first_name: String,
+    pub user_id: Uuid,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+}
+
+/// Body for POST /documents/bulk.
+#[derive(Debug, Deserialize, Validate)]
+pub struct BulkDocumentRequest {
+    /// "delete" | "assign" | "move"
+    pub action: String,
+    #[validate(length(max = MAX_BULK_IDS))]
+    pub document_ids: Vec<Uuid>,
+    /// For "assign" action
+    pub visibility: Option<String>,
+    pub group_id: Option<Uuid>,
+    pub child_id: Option<Uuid>,
+    /// For "move" action: `None` moves the documents back to the root
+    pub folder_id: Option<Uuid>,
+}
+
+/// A folder documents can be filed in; folders nest through `parent_id`.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DocumentFolder {
+    pub id: Uuid,
+    pub name: String,
+    pub parent_id: Option<Uuid>,
+    pub document_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct FolderRequest {
+    #[validate(length(min = 1, max = MAX_NAME))]
+    pub name: String,
+    pub parent_id: Option<Uuid>,
+}
+
+/// A file a document held before it was replaced.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DocumentVersion {
+    pub id: Uuid,
+    pub document_id: Uuid,
+    pub version: i32,
+    pub original_filename: String,
+    pub storage_path: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub uploader_id: Option<Uuid>,
+    pub uploaded_at: DateTime<Utc>,
+    pub replaced_by: Option<Uuid>,
+    pub replaced_at: DateTime<Utc>,
+    /// Signed, expiring URL of `storage_path`.
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DocumentSignature {
+    pub id: Uuid,
+    pub document_id: Uuid,
+    pub user_id: Uuid,
+    pub typed_name: String,
+    pub ip_address: Option<String>,
+    pub signed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SignDocumentRequest {
+    /// Full name typed by the parent as their signature.
+    #[validate(length(min = 1, max = MAX_NAME))]
+    pub typed_name: String,
+}
+
+/// A parent who can see a document requiring signature but has not signed it yet.
+#[derive(Debug, Clone, Serialize, FromRow, async_graphql::SimpleObject)]
+pub struct OutstandingSignature {
+    pub document_id: Uuid,
+    pub document_title: String,
+    pub user_id: Uuid,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub document_created_at: DateTime<Utc>,
+}
+
+/// An expiring link to a document for someone without an account; the token
+/// itself is only returned once, when the link is created.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DocumentShare {
+    pub id: Uuid,
+    pub document_id: Uuid,
+    /// "view" (opened in the browser) | "download"
+    pub scope: String,
+    pub recipient: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub access_count: i32,
+    pub last_accessed_at: Option<DateTime<Utc>>,
+}
+
+/// Body for POST /documents/{id}/shares.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateDocumentShareRequest {
+    /// "view" (default) | "download"
+    pub scope: Option<String>,
+    /// Who the link is for, e.g. "Inspection MFA".
+    #[validate(length(max = MAX_TITLE))]
+    pub recipient: Option<String>,
+    /// Validity of the link; 7 days by default, 30 days at most.
+    pub expires_in_hours: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DocumentShareAccess {
+    pub id: Uuid,
+    pub share_id: Uuid,
+    pub accessed_at: DateTime<Utc>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
 }
@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Open and click totals of a tracked announcement, as listed by GET /email-campaigns.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct EmailCampaignStats {
+    pub id: Uuid,
+    pub subject: String,
+    /// The broadcast message the emails announced, if sent from the messages page.
+    pub message_id: Option<Uuid>,
+    pub sent_by: Option<Uuid>,
+    pub recipients: i64,
+    /// Emails opened at least once.
+    pub opened: i64,
+    pub opens: i64,
+    /// Emails with at least one link clicked.
+    pub clicked: i64,
+    pub clicks: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct EmailCampaignLink {
+    pub url: String,
+    pub clicks: i32,
+}
@@ -0,0 +1,38 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+pub const EMAIL_STATUS_SENT: &str = "sent";
+pub const EMAIL_STATUS_FAILED: &str = "failed";
+/// Not sent: the address is on the bounce/complaint suppression list.
+pub const EMAIL_STATUS_SUPPRESSED: &str = "suppressed";
+
+/// An email sent (or not) to one recipient, as listed by GET /email-log.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct EmailLogEntry {
+    pub id: Uuid,
+    pub recipient: String,
+    pub template: String,
+    pub subject: String,
+    pub status: String,
+    pub message_id: Option<String>,
+    /// The SMTP relay's reply, which carries the provider's own message id.
+    pub provider_response: Option<String>,
+    pub error: Option<String>,
+    pub resendable: bool,
+    pub resent_from: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmailLogQuery {
+    pub page: Option<i64>,
+    pub limit: Option<i64>,
+    /// Part of the recipient address, case-insensitive.
+    pub recipient: Option<String>,
+    pub template: Option<String>,
+    pub status: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,73 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::models::limits::{MAX_TEMPLATE, MAX_TITLE};
+use validator::Validate;
+
+pub const TEMPLATE_INVITATION: &str = "invitation";
+pub const TEMPLATE_ANNOUNCEMENT: &str = "announcement";
+
+/// Customizable emails with the placeholders each one may use.
+pub const TEMPLATE_KINDS: &[(&str, &[&str])] = &[
+    (TEMPLATE_INVITATION, &["garderie", "role", "invite_url"]),
+    (TEMPLATE_ANNOUNCEMENT, &["garderie", "recipient_name", "subject", "body"]),
+];
+
+/// Locales a template can be written in.
+pub const TEMPLATE_LOCALES: &[&str] = &["fr", "en"];
+
+/// A garderie's wording for one email in one language, replacing the built-in text.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EmailTemplate {
+    pub id: Uuid,
+    pub kind: String,
+    pub locale: String,
+    pub subject: String,
+    /// Plain text; blank lines separate paragraphs.
+    pub body: String,
+    pub updated_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Body for POST /email-templates.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateEmailTemplate {
+    pub kind: String,
+    /// Defaults to `fr`.
+    pub locale: Option<String>,
+    #[validate(length(max = MAX_TITLE))]
+    pub subject: String,
+    #[validate(length(max = MAX_TEMPLATE))]
+    pub body: String,
+}
+
+/// Body for PUT /email-templates/{id}.
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateEmailTemplate {
+    #[validate(length(max = MAX_TITLE))]
+    pub subject: String,
+    #[validate(length(max = MAX_TEMPLATE))]
+    pub body: String,
+}
+
+/// Body for POST /email-templates/preview. Missing subject or body use the
+/// garderie's saved template, then the built-in one.
+#[derive(Debug, Deserialize, Validate)]
+pub struct PreviewEmailTemplate {
+    pub kind: String,
+    pub locale: Option<String>,
+    #[validate(length(max = MAX_TITLE))]
+    pub subject: Option<String>,
+    #[validate(length(max = MAX_TEMPLATE))]
+    pub body: Option<String>,
+}
+
+/// An email as it would be sent.
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
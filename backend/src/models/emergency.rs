@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::models::limits::{MAX_MESSAGE, MAX_TITLE};
+use validator::Validate;
+
+/// Body for POST /messages/emergency.
+#[derive(Debug, Deserialize, Validate)]
+pub struct EmergencyRequest {
+    #[validate(length(min = 1, max = MAX_TITLE))]
+    pub subject: String,
+    #[validate(length(min = 1, max = MAX_MESSAGE))]
+    pub content: String,
+}
+
+/// An emergency broadcast with its delivery and acknowledgement counts.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct EmergencySummary {
+    pub id: Uuid,
+    pub message_id: Option<Uuid>,
+    pub sender_id: Option<Uuid>,
+    pub subject: String,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    /// Parents reached on at least one channel.
+    pub recipients: i64,
+    pub acknowledged: i64,
+    /// Deliveries (one per parent and channel) by status.
+    pub sent: i64,
+    pub pending: i64,
+    pub failed: i64,
+    /// For a parent: when they acknowledged it.
+    pub acknowledged_at: Option<DateTime<Utc>>,
+}
+
+/// One parent of an emergency broadcast: each channel's status (`None` when the
+/// channel does not apply, e.g. no phone number) and the acknowledgement.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct EmergencyRecipient {
+    pub user_id: Uuid,
+    pub name: String,
+    pub email: String,
+    pub phone: Option<String>,
+    pub email_status: Option<String>,
+    pub push_status: Option<String>,
+    pub sms_status: Option<String>,
+    /// Last error of a failed channel.
+    pub error: Option<String>,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+}
+
+/// GET /messages/emergency/{id}.
+#[derive(Debug, Clone, Serialize)]
+pub struct EmergencyDetail {
+    #[serde(flatten)]
+    pub summary: EmergencySummary,
+    pub parents: Vec<EmergencyRecipient>,
+}
@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::models::limits::MAX_PASSWORD;
+use validator::Validate;
+
+/// Proof that a user's personal data was erased, kept after the erasure.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ErasureCertificate {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// SHA-256 of the erased address, so the garderie can answer "was I erased?"
+    /// without keeping the address itself.
+    pub email_sha256: String,
+    pub role: String,
+    pub erased_by: Option<Uuid>,
+    pub messages_scrubbed: bool,
+    /// Number of rows removed or scrubbed, per kind of data.
+    pub summary: serde_json::Value,
+    pub erased_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct EraseUserRequest {
+    /// Password of the admin performing the erasure.
+    #[validate(length(max = MAX_PASSWORD))]
+    pub password: String,
+    /// Overrides ERASURE_SCRUB_MESSAGES for this erasure.
+    pub scrub_messages: Option<bool>,
+}
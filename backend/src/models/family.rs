@@ -0,0 +1,102 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::models::limits::{MAX_BULK_IDS, MAX_MESSAGE, MAX_NAME, MAX_NOTE, MAX_TITLE};
+use validator::Validate;
+
+/// A household: siblings enrolled together. Its guardians are the parents linked
+/// to any of its children.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Family {
+    pub id: Uuid,
+    pub name: String,
+    pub notes: Option<String>,
+    /// The monthly invoice batch bills all the family's children on one invoice.
+    pub combined_billing: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct FamilyChild {
+    pub id: Uuid,
+    pub family_id: Uuid,
+    pub first_name: String,
+    pub last_name: String,
+    pub birth_date: NaiveDate,
+    pub group_id: Option<Uuid>,
+    pub is_active: bool,
+}
+
+/// A parent linked to at least one child of the family.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct FamilyGuardian {
+    pub family_id: Uuid,
+    pub user_id: Uuid,
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+    pub phone: Option<String>,
+    /// Children of the family this guardian is linked to.
+    pub child_ids: Vec<Uuid>,
+}
+
+/// GET /families/{id}: the household with its children and guardians.
+#[derive(Debug, Clone, Serialize)]
+pub struct FamilyView {
+    #[serde(flatten)]
+    pub family: Family,
+    pub children: Vec<FamilyChild>,
+    pub guardians: Vec<FamilyGuardian>,
+}
+
+/// Children not yet in a family who share a parent, offered as a family to create.
+#[derive(Debug, Clone, Serialize)]
+pub struct FamilySuggestion {
+    /// Last name of the eldest child.
+    pub name: String,
+    pub child_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateFamilyRequest {
+    #[validate(length(min = 1, max = MAX_NAME))]
+    pub name: String,
+    #[validate(length(max = MAX_NOTE))]
+    pub notes: Option<String>,
+    #[serde(default = "default_combined_billing")]
+    pub combined_billing: bool,
+    #[serde(default)]
+    #[validate(length(max = MAX_BULK_IDS))]
+    pub child_ids: Vec<Uuid>,
+}
+
+fn default_combined_billing() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateFamilyRequest {
+    #[validate(length(min = 1, max = MAX_NAME))]
+    pub name: Option<String>,
+    #[validate(length(max = MAX_NOTE))]
+    pub notes: Option<String>,
+    pub combined_billing: Option<bool>,
+}
+
+/// Body for PUT /families/{id}/children — replaces the family's children.
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetFamilyChildrenRequest {
+    #[validate(length(max = MAX_BULK_IDS))]
+    pub child_ids: Vec<Uuid>,
+}
+
+/// Body for POST /families/{id}/send-to-parents — a message emailed to the family's guardians.
+#[derive(Debug, Deserialize, Validate)]
+pub struct FamilyMessageRequest {
+    #[validate(length(max = MAX_TITLE))]
+    pub subject: String,
+    #[validate(length(min = 1, max = MAX_MESSAGE))]
+    pub content: String,
+}
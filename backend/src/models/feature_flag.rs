@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::models::limits::MAX_TITLE;
+use validator::Validate;
+
+/// A platform-wide value (`slug` is `None`) or a garderie override.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct FeatureFlag {
+    pub id: Uuid,
+    pub key: String,
+    pub slug: Option<String>,
+    pub enabled: bool,
+    pub updated_by: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetFeatureFlagRequest {
+    pub enabled: bool,
+    /// Garderie to override; platform-wide when absent.
+    #[validate(length(max = MAX_TITLE))]
+    pub slug: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeatureFlagScope {
+    pub slug: Option<String>,
+}
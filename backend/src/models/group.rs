@@ -1,7 +1,9 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use crate::models::limits::{MAX_BULK_IDS, MAX_NAME, MAX_NOTE, MAX_TITLE};
+use validator::Validate;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Group {
@@ -9,20 +11,111 @@ pub struct Group {
     pub name: String,
     pub description: Option<String>,
     pub color: Option<String>, // hex color for UI
+    /// Parents of the group may post in its thread, not only read it.
+    pub allow_parent_replies: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Hidden from pickers and closed to new messages; history stays browsable.
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Deserialize)]
+/// Query params for GET /groups.
+#[derive(Debug, Default, Deserialize)]
+pub struct GroupListQuery {
+    /// Also list archived groups (default false).
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+#[derive(Debug, Deserialize, Validate)]
 pub struct CreateGroupRequest {
+    #[validate(length(min = 1, max = MAX_NAME))]
     pub name: String,
+    #[validate(length(max = MAX_NOTE))]
     pub description: Option<String>,
+    #[validate(length(max = MAX_TITLE))]
     pub color: Option<String>,
+    #[serde(default)]
+    pub allow_parent_replies: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct UpdateGroupRequest {
+    #[validate(length(max = MAX_NAME))]
     pub name: Option<String>,
+    #[validate(length(max = MAX_NOTE))]
     pub description: Option<String>,
+    #[validate(length(max = MAX_TITLE))]
     pub color: Option<String>,
+    pub allow_parent_replies: Option<bool>,
+}
+
+/// An educator assigned to a group.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct GroupEducator {
+    pub user_id: Uuid,
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+}
+
+/// Body for POST /groups/rollover: either `mappings` or `age_rules`.
+#[derive(Debug, Deserialize, Validate)]
+pub struct RolloverRequest {
+    /// Every child of a source group moves to its target.
+    #[serde(default)]
+    #[validate(nested, length(max = MAX_BULK_IDS))]
+    pub mappings: Vec<GroupMapping>,
+    /// Each active child moves to the group of the band its age falls in; children
+    /// outside every band stay where they are.
+    #[serde(default)]
+    #[validate(nested, length(max = MAX_BULK_IDS))]
+    pub age_rules: Vec<AgeRule>,
+    /// Date ages are computed at; defaults to today.
+    pub as_of: Option<NaiveDate>,
+    /// Returns the moves without applying them.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Emails the parents of the children moved (default true).
+    pub notify_parents: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct GroupMapping {
+    pub source_group_id: Uuid,
+    /// `None` takes the children out of any group (e.g. leaving for school).
+    pub target_group_id: Option<Uuid>,
+}
+
+/// Children aged `[min_age_months, max_age_months)` go to `group_id`.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct AgeRule {
+    #[validate(range(min = 0))]
+    pub min_age_months: i32,
+    pub max_age_months: Option<i32>,
+    pub group_id: Uuid,
+}
+
+/// One child moved by a rollover.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChildMove {
+    pub child_id: Uuid,
+    pub from_group_id: Option<Uuid>,
+    pub to_group_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RolloverResult {
+    /// `None` for a dry run.
+    pub rollover_id: Option<Uuid>,
+    pub moves: Vec<ChildMove>,
+}
+
+/// A group a child has left.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ChildGroupHistory {
+    pub group_id: Option<Uuid>,
+    pub group_name: String,
+    pub left_at: DateTime<Utc>,
+    pub rollover_id: Option<Uuid>,
 }
@@ -0,0 +1,131 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::models::limits::{MAX_BULK_IDS, MAX_NOTE, MAX_TITLE};
+use validator::Validate;
+
+/// Valid values for the kind of an invoice line.
+pub const INVOICE_ITEM_KINDS: &[&str] = &["tuition", "late_fee", "activity", "other"];
+
+/// An invoice addressed to the parents of one child, or of a family's children on a
+/// family invoice (`child_id` is then the first child billed). Amounts are in cents.
+/// `status` is draft | sent | paid | void; parents never see drafts.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Invoice {
+    pub id: Uuid,
+    pub number: String,
+    pub child_id: Uuid,
+    pub child_name: String,
+    pub family_id: Option<Uuid>,
+    pub family_name: Option<String>,
+    /// First day of the billed month, for invoices from the monthly batch.
+    pub period: Option<NaiveDate>,
+    pub issue_date: NaiveDate,
+    pub due_date: NaiveDate,
+    pub status: String,
+    /// Sent, unpaid and past its due date.
+    pub overdue: bool,
+    pub total_cents: i64,
+    pub notes: Option<String>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub paid_at: Option<DateTime<Utc>>,
+    pub payment_method: Option<String>,
+    pub payment_reference: Option<String>,
+    /// Parent who paid, for the year-end tax receipts.
+    pub payer_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[sqlx(skip)]
+    pub items: Vec<InvoiceItem>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct InvoiceItem {
+    pub id: Uuid,
+    pub kind: String,
+    pub description: String,
+    pub quantity: i32,
+    pub unit_cents: i64,
+    pub amount_cents: i64,
+    /// Child billed by the line, on family invoices.
+    pub child_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct InvoiceItemInput {
+    pub kind: String,
+    #[validate(length(max = MAX_NOTE))]
+    pub description: String,
+    pub quantity: Option<i32>,
+    pub unit_cents: i64,
+    /// Set by the monthly batch on family invoices.
+    #[serde(skip_deserializing)]
+    pub child_id: Option<Uuid>,
+}
+
+/// Body for POST /invoices — a one-off invoice, created as a draft.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateInvoiceRequest {
+    pub child_id: Uuid,
+    /// Defaults to 30 days after today.
+    pub due_date: Option<NaiveDate>,
+    #[validate(length(max = MAX_NOTE))]
+    pub notes: Option<String>,
+    #[validate(nested, length(max = MAX_BULK_IDS))]
+    pub items: Vec<InvoiceItemInput>,
+}
+
+/// Body for PUT /invoices/{id} — drafts only; `items` replaces every line.
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateInvoiceRequest {
+    pub due_date: Option<NaiveDate>,
+    #[validate(length(max = MAX_NOTE))]
+    pub notes: Option<String>,
+    #[validate(nested, length(max = MAX_BULK_IDS))]
+    pub items: Option<Vec<InvoiceItemInput>>,
+}
+
+/// Body for POST /invoices/{id}/mark-paid — a payment received outside the app.
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct MarkPaidRequest {
+    pub paid_at: Option<DateTime<Utc>>,
+    /// e.g. "cheque", "virement", "comptant".
+    pub method: Option<String>,
+    #[validate(length(max = MAX_TITLE))]
+    pub reference: Option<String>,
+    /// Parent who paid; the tax receipt goes to them. Defaults to the child's first parent.
+    pub payer_id: Option<Uuid>,
+}
+
+/// Query params for GET /invoices.
+#[derive(Debug, Deserialize)]
+pub struct InvoiceQuery {
+    pub child_id: Option<Uuid>,
+    pub status: Option<String>,
+    /// Issue month, YYYY-MM.
+    pub month: Option<String>,
+}
+
+/// Body for POST /invoices/generate.
+#[derive(Debug, Deserialize, Validate)]
+pub struct GenerateInvoicesRequest {
+    /// Billed month, YYYY-MM.
+    pub month: String,
+    /// Monthly fee for children without their own `monthly_fee_cents`.
+    pub default_tuition_cents: Option<i64>,
+    /// Days between the issue date and the due date; 30 by default.
+    pub due_days: Option<i64>,
+    /// Email the new invoices to parents right away instead of leaving drafts.
+    #[serde(default)]
+    pub send: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GenerateInvoicesResult {
+    pub created: Vec<Invoice>,
+    /// Active children already invoiced for the month.
+    pub already_invoiced: usize,
+    /// Active children with nothing to bill (no fee and no paid activity).
+    pub nothing_to_bill: usize,
+}
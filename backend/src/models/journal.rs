@@ -1,7 +1,9 @@
-use chrono::{DateTime, NaiveDate, Utc};
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use crate::models::limits::{MAX_NOTE, MAX_TITLE};
+use validator::Validate;
 
 /// Valid values for the temperature field.
 pub const WEATHER_CONDITIONS: &[&str] =
@@ -15,9 +17,13 @@ pub const APPETIT_LEVELS: &[&str] =
 pub const HUMEUR_LEVELS: &[&str] =
     &["tres_bien", "bien", "difficile", "pleurs"];
 
+/// Valid values for the event_type of a journal event.
+pub const JOURNAL_EVENT_TYPES: &[&str] =
+    &["sieste", "couche", "repas", "biberon", "toilette", "activite", "medicament", "autre"];
+
 /// One day's journal entry for a child.
 /// Enum columns are cast to TEXT in SQL so sqlx maps them as Option<String>.
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, async_graphql::SimpleObject)]
 pub struct DailyJournal {
     pub id: Uuid,
     pub child_id: Uuid,
@@ -35,26 +41,61 @@ pub struct DailyJournal {
     pub created_by: Uuid,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Set once the entry was emailed to the parents; from then on it can only be
+    /// changed through POST /journals/amend.
+    pub sent_at: Option<DateTime<Utc>>,
+    pub amended_at: Option<DateTime<Utc>>,
 }
 
 /// Body for PUT /journals (create or update a single day).
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct UpsertJournalRequest {
     pub child_id: Uuid,
     pub date: NaiveDate,
+    #[validate(length(max = MAX_TITLE))]
     pub temperature: Option<String>,
+    #[validate(length(max = MAX_NOTE))]
     pub menu: Option<String>,
+    #[validate(length(max = MAX_TITLE))]
     pub appetit: Option<String>,
+    #[validate(length(max = MAX_TITLE))]
     pub humeur: Option<String>,
     pub sommeil_minutes: Option<i16>,
     #[serde(default)]
     pub absent: bool,
+    #[validate(length(max = MAX_NOTE))]
     pub sante: Option<String>,
+    #[validate(length(max = MAX_NOTE))]
     pub medicaments: Option<String>,
+    #[validate(length(max = MAX_NOTE))]
     pub message_educatrice: Option<String>,
+    #[validate(length(max = MAX_NOTE))]
     pub observations: Option<String>,
 }
 
+/// Body for POST /journals/amend: the corrected entry of a day already emailed,
+/// with a note telling the parents what changed.
+#[derive(Debug, Deserialize, Validate)]
+pub struct AmendJournalRequest {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub entry: UpsertJournalRequest,
+    #[validate(length(max = MAX_NOTE))]
+    pub note: String,
+}
+
+/// A correction of a sent journal entry, with the entry as it was before.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct JournalAmendment {
+    pub id: Uuid,
+    pub journal_id: Uuid,
+    pub amended_by: Option<Uuid>,
+    pub amended_by_name: Option<String>,
+    pub note: String,
+    pub previous: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
 /// Query params for GET /journals.
 #[derive(Debug, Deserialize)]
 pub struct JournalWeekQuery {
@@ -62,3 +103,81 @@ pub struct JournalWeekQuery {
     /// Monday of the desired week (ISO 8601 date, e.g. "2025-06-02").
     pub week_start: NaiveDate,
 }
+
+/// Query params for GET /journals/events and GET /journals/timeline.
+#[derive(Debug, Deserialize)]
+pub struct JournalDayQuery {
+    pub child_id: Uuid,
+    pub date: NaiveDate,
+}
+
+/// A time-stamped moment of a child's day: nap, diaper change, bottle…
+/// Times are local to the garderie; `end_time` is only set for events with a duration.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct JournalEvent {
+    pub id: Uuid,
+    pub child_id: Uuid,
+    pub date: NaiveDate,
+    pub event_type: String,
+    pub start_time: NaiveTime,
+    pub end_time: Option<NaiveTime>,
+    pub notes: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl JournalEvent {
+    /// Length of the event in minutes, when it has an end.
+    pub fn duration_minutes(&self) -> Option<i64> {
+        self.end_time.map(|end| (end - self.start_time).num_minutes())
+    }
+}
+
+/// Body for POST /journals/events.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateJournalEventRequest {
+    pub child_id: Uuid,
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub event: JournalEventFields,
+}
+
+/// Body for PUT /journals/events/{id}; replaces every field.
+#[derive(Debug, Deserialize, Validate)]
+pub struct JournalEventFields {
+    pub event_type: String,
+    #[serde(deserialize_with = "time_of_day")]
+    pub start_time: NaiveTime,
+    #[serde(default, deserialize_with = "opt_time_of_day")]
+    pub end_time: Option<NaiveTime>,
+    #[validate(length(max = MAX_NOTE))]
+    pub notes: Option<String>,
+}
+
+/// GET /journals/timeline — the day's journal entry and events in time order.
+#[derive(Debug, Serialize)]
+pub struct JournalTimeline {
+    pub child_id: Uuid,
+    pub date: NaiveDate,
+    pub journal: Option<DailyJournal>,
+    pub events: Vec<JournalEvent>,
+    /// Total of the day's naps with an end time.
+    pub sleep_minutes: i64,
+}
+
+/// Accepts "HH:MM" (what time inputs send) as well as "HH:MM:SS".
+fn parse_time(s: &str) -> Result<NaiveTime, chrono::ParseError> {
+    NaiveTime::parse_from_str(s, "%H:%M").or_else(|_| NaiveTime::parse_from_str(s, "%H:%M:%S"))
+}
+
+fn time_of_day<'de, D: Deserializer<'de>>(d: D) -> Result<NaiveTime, D::Error> {
+    parse_time(&String::deserialize(d)?).map_err(serde::de::Error::custom)
+}
+
+fn opt_time_of_day<'de, D: Deserializer<'de>>(d: D) -> Result<Option<NaiveTime>, D::Error> {
+    match Option::<String>::deserialize(d)? {
+        Some(s) if !s.is_empty() => parse_time(&s).map(Some).map_err(serde::de::Error::custom),
+        _ => Ok(None),
+    }
+}
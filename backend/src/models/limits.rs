@@ -0,0 +1,21 @@
+//! Length caps of request fields, checked by [`crate::middleware::validation::ValidJson`].
+
+/// Names of people, groups, albums, folders…
+pub const MAX_NAME: u64 = 100;
+/// Titles, subjects, labels and other one-line texts.
+pub const MAX_TITLE: u64 = 255;
+/// Email addresses (RFC 5321 path limit).
+pub const MAX_EMAIL: u64 = 254;
+pub const MAX_PHONE: u64 = 32;
+/// Passwords are hashed: anything longer is refused before bcrypt sees it.
+pub const MAX_PASSWORD: u64 = 256;
+/// Notes, descriptions, journal fields.
+pub const MAX_NOTE: u64 = 5_000;
+/// Message bodies and announcements.
+pub const MAX_MESSAGE: u64 = 20_000;
+/// Email template bodies (HTML).
+pub const MAX_TEMPLATE: u64 = 100_000;
+/// FCM registration tokens (a few hundred characters in practice).
+pub const MAX_PUSH_TOKEN: u64 = 4_096;
+/// Ids of one bulk request.
+pub const MAX_BULK_IDS: u64 = 500;
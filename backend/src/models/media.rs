@@ -2,6 +2,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use crate::models::limits::{MAX_BULK_IDS, MAX_NOTE, MAX_TITLE};
+use validator::Validate;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -27,7 +29,7 @@ impl std::str::FromStr for MediaType {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, async_graphql::SimpleObject)]
 pub struct Media {
     pub id: Uuid,
     pub uploader_id: Uuid,
@@ -40,16 +42,56 @@ pub struct Media {
     pub width: Option<i32>,
     pub height: Option<i32>,
     pub duration_secs: Option<f64>,
+    /// Video scrubbing preview: `sprite_frames` frames of `sprite_frame_width`×`sprite_frame_height`
+    /// pixels, `sprite_columns` per row, one every `sprite_interval_secs` seconds.
+    pub sprite_path: Option<String>,
+    pub sprite_columns: Option<i32>,
+    pub sprite_frames: Option<i32>,
+    pub sprite_interval_secs: Option<f64>,
+    pub sprite_frame_width: Option<i32>,
+    pub sprite_frame_height: Option<i32>,
+    /// Videos only: "pending" until the poster (`thumbnail_path`) and sprite are extracted,
+    /// then "ready" or "failed".
+    pub preview_status: Option<String>,
     pub group_id: Option<Uuid>,
     pub child_id: Option<Uuid>,
     pub caption: Option<String>,
     pub visibility: String,
     pub child_ids: Vec<Uuid>,
+    pub tags: Vec<String>,
     pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Starred by the user listing the media.
+    #[sqlx(default)]
+    pub is_favorite: bool,
+    /// Hidden from the gallery of the user listing the media.
+    #[sqlx(default)]
+    pub is_hidden: bool,
+    /// Users who starred the media (staff listings only).
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub favorite_count: Option<i64>,
+    /// The paths point at a blurred copy: a child shown has no photo consent.
+    #[sqlx(default)]
+    pub is_redacted: bool,
+    /// Signed, expiring URLs of `storage_path`, `thumbnail_path` and `sprite_path`.
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_url: Option<String>,
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sprite_url: Option<String>,
     pub is_encrypted: bool,
+    #[graphql(skip)]
     pub encryption_iv: Option<Vec<u8>>,
+    #[graphql(skip)]
     pub encryption_tag: Option<Vec<u8>>,
+    #[graphql(skip)]
     pub thumbnail_encryption_iv: Option<Vec<u8>>,
+    #[graphql(skip)]
     pub thumbnail_encryption_tag: Option<Vec<u8>>,
 }
 
@@ -64,24 +106,218 @@ pub struct MediaQuery {
     pub period: Option<String>,
     /// Reference date "YYYY-MM-DD" (defaults to today)
     pub date: Option<String>,
+    /// Comma-separated tags "sortie,peinture" — only media carrying all of them
+    pub tags: Option<String>,
+    /// Only media attached to this album
+    pub album_id: Option<Uuid>,
+    /// `next_cursor` of the previous page; takes precedence over `page`
+    pub cursor: Option<String>,
+    /// 1 — only media the user starred
+    pub favorites: Option<u8>,
+    /// 1 — only media the user hid, which are otherwise left out
+    pub hidden: Option<u8>,
 }
 
-#[derive(Debug, Deserialize)]
+/// A user's own mark on a media, set with POST and removed with DELETE.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MediaMark {
+    Favorite,
+    Hidden,
+}
+
+impl MediaMark {
+    pub fn column(self) -> &'static str {
+        match self {
+            MediaMark::Favorite => "favorite",
+            MediaMark::Hidden => "hidden",
+        }
+    }
+}
+
+/// A media as listed with `X-Api-Version: 2`: no storage paths or encryption
+/// metadata, only the signed URLs.
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaSummary {
+    pub id: Uuid,
+    pub uploader_id: Uuid,
+    pub media_type: String,
+    pub original_filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub duration_secs: Option<f64>,
+    pub sprite_columns: Option<i32>,
+    pub sprite_frames: Option<i32>,
+    pub sprite_interval_secs: Option<f64>,
+    pub sprite_frame_width: Option<i32>,
+    pub sprite_frame_height: Option<i32>,
+    pub preview_status: Option<String>,
+    pub group_id: Option<Uuid>,
+    pub child_id: Option<Uuid>,
+    pub caption: Option<String>,
+    pub visibility: String,
+    pub child_ids: Vec<Uuid>,
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub is_favorite: bool,
+    pub is_hidden: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub favorite_count: Option<i64>,
+    pub is_redacted: bool,
+    pub url: Option<String>,
+    pub thumbnail_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sprite_url: Option<String>,
+}
+
+impl From<Media> for MediaSummary {
+    fn from(m: Media) -> Self {
+        Self {
+            id: m.id,
+            uploader_id: m.uploader_id,
+            media_type: m.media_type,
+            original_filename: m.original_filename,
+            content_type: m.content_type,
+            size_bytes: m.size_bytes,
+            width: m.width,
+            height: m.height,
+            duration_secs: m.duration_secs,
+            sprite_columns: m.sprite_columns,
+            sprite_frames: m.sprite_frames,
+            sprite_interval_secs: m.sprite_interval_secs,
+            sprite_frame_width: m.sprite_frame_width,
+            sprite_frame_height: m.sprite_frame_height,
+            preview_status: m.preview_status,
+            group_id: m.group_id,
+            child_id: m.child_id,
+            caption: m.caption,
+            visibility: m.visibility,
+            child_ids: m.child_ids,
+            tags: m.tags,
+            created_at: m.created_at,
+            updated_at: m.updated_at,
+            is_favorite: m.is_favorite,
+            is_hidden: m.is_hidden,
+            favorite_count: m.favorite_count,
+            is_redacted: m.is_redacted,
+            url: m.url,
+            thumbnail_url: m.thumbnail_url,
+            sprite_url: m.sprite_url,
+        }
+    }
+}
+
+/// GET /media — one page of media, newest first.
+#[derive(Debug, Serialize)]
+pub struct MediaPage<T = Media> {
+    pub items: Vec<T>,
+    /// Pass as `cursor` to get the next page; absent on the last one.
+    pub next_cursor: Option<String>,
+    /// Media matching the filters, across all pages.
+    pub total_count: i64,
+}
+
+impl MediaPage {
+    pub fn slim(self) -> MediaPage<MediaSummary> {
+        MediaPage {
+            items: self.items.into_iter().map(MediaSummary::from).collect(),
+            next_cursor: self.next_cursor,
+            total_count: self.total_count,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
 pub struct UpdateMediaRequest {
+    #[validate(length(max = MAX_TITLE))]
     pub caption: Option<String>,
     /// "private" | "public" | "group" | "child"
     pub visibility: String,
     pub group_id: Option<Uuid>,
+    #[validate(length(max = MAX_BULK_IDS))]
     pub child_ids: Option<Vec<Uuid>>,
+    /// Replaces the media's tags when present
+    #[validate(length(max = MAX_BULK_IDS))]
+    pub tags: Option<Vec<String>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct BulkMediaRequest {
-    /// "delete" | "assign"
+    /// "delete" | "assign" | "tag"
     pub action: String,
+    #[validate(length(max = MAX_BULK_IDS))]
     pub media_ids: Vec<Uuid>,
     /// For "assign" action
     pub visibility: Option<String>,
     pub group_id: Option<Uuid>,
+    #[validate(length(max = MAX_BULK_IDS))]
     pub child_ids: Option<Vec<Uuid>>,
+    /// For "tag" action: added to each media's existing tags
+    #[validate(length(max = MAX_BULK_IDS))]
+    pub tags: Option<Vec<String>>,
+}
+
+/// A tag in use, with how many media carry it.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct MediaTag {
+    pub tag: String,
+    pub count: i64,
+}
+
+/// A comment under a photo or video, seen by staff and the parents of the children in it.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct MediaComment {
+    pub id: Uuid,
+    pub media_id: Uuid,
+    /// `None` once the author's account is deleted.
+    pub author_id: Option<Uuid>,
+    pub author_name: Option<String>,
+    pub author_role: Option<String>,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Body for POST /media/{id}/comments.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateMediaCommentRequest {
+    #[validate(length(min = 1, max = MAX_NOTE))]
+    pub content: String,
+}
+
+/// An area of a photo to blur, in fractions (0–1) of its width and height.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct RedactionRegion {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// The blurred copy of a photo shown where a child without photo consent appears.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct MediaRedaction {
+    pub media_id: Uuid,
+    /// `RedactionRegion`s blurred in the copy (found by detection once it ran).
+    pub regions: serde_json::Value,
+    /// "manual" | "detected"
+    pub source: String,
+    /// "pending" | "ready" | "failed"
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub requested_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Body for PUT /media/{id}/redaction: regions drawn by staff, or `detect` to let the
+/// face detection service find them.
+#[derive(Debug, Deserialize, Validate)]
+pub struct RedactionRequest {
+    #[serde(default)]
+    pub regions: Vec<RedactionRegion>,
+    #[serde(default)]
+    pub detect: bool,
 }
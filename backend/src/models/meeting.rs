@@ -0,0 +1,85 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::models::limits::{MAX_BULK_IDS, MAX_NOTE, MAX_TITLE};
+use validator::Validate;
+
+/// A time slot an educator is available for a parent-teacher meeting.
+/// Parents only learn whether it is taken; staff also see who booked it.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct MeetingSlot {
+    pub id: Uuid,
+    pub educator_id: Uuid,
+    pub educator_name: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub location: Option<String>,
+    pub booked: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub booking_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub child_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_name: Option<String>,
+}
+
+/// A booked meeting with everything needed for agendas and emails.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct MeetingDetails {
+    pub booking_id: Uuid,
+    pub slot_id: Uuid,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub location: Option<String>,
+    pub notes: Option<String>,
+    pub status: String,
+    pub child_id: Uuid,
+    pub child_name: String,
+    pub educator_id: Uuid,
+    pub educator_name: String,
+    pub parent_id: Uuid,
+    pub parent_name: String,
+    pub parent_email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct SlotInput {
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+/// Body for POST /meetings/slots — publish several slots for one educator.
+#[derive(Debug, Deserialize, Validate)]
+pub struct PublishSlotsRequest {
+    pub educator_id: Uuid,
+    #[validate(length(max = MAX_TITLE))]
+    pub location: Option<String>,
+    #[validate(nested, length(max = MAX_BULK_IDS))]
+    pub slots: Vec<SlotInput>,
+}
+
+/// Body for POST /meetings/slots/{id}/book.
+#[derive(Debug, Deserialize, Validate)]
+pub struct BookSlotRequest {
+    pub child_id: Uuid,
+    #[validate(length(max = MAX_NOTE))]
+    pub notes: Option<String>,
+}
+
+/// Query params for GET /meetings/slots.
+#[derive(Debug, Deserialize)]
+pub struct SlotQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub educator_id: Option<Uuid>,
+}
+
+/// Query params for GET /meetings/agenda.
+#[derive(Debug, Deserialize)]
+pub struct AgendaQuery {
+    /// Defaults to today.
+    pub date: Option<NaiveDate>,
+    /// Admins only; educators always get their own agenda.
+    pub educator_id: Option<Uuid>,
+}
@@ -2,6 +2,28 @@ use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use crate::models::limits::{MAX_BULK_IDS, MAX_NAME, MAX_NOTE, MAX_TITLE};
+use validator::Validate;
+
+/// Meal slots a menu item can belong to, in serving order.
+pub const MEALS: &[&str] = &["collation_matin", "diner", "collation_apres_midi"];
+
+/// Allergen tags accepted on menu items and child allergy profiles
+/// (Health Canada priority food allergens).
+pub const ALLERGENS: &[&str] = &[
+    "arachides",
+    "noix",
+    "lait",
+    "oeufs",
+    "ble",
+    "soya",
+    "poisson",
+    "crustaces",
+    "mollusques",
+    "sesame",
+    "moutarde",
+    "sulfites",
+];
 
 /// One day's garderie-level menu entry (split into 3 sections).
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -17,20 +39,89 @@ pub struct DailyMenu {
     pub created_by: Uuid,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Structured items, loaded separately from `menu_items`.
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub items: Vec<MenuItem>,
+}
+
+/// A single dish served at one meal, tagged with the allergens it contains.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MenuItem {
+    pub id: Uuid,
+    pub menu_id: Uuid,
+    pub meal: String,
+    pub name: String,
+    pub allergens: Vec<String>,
+    pub position: i32,
 }
 
 /// Body for PUT /menus (create or update menu for a specific date).
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct UpsertMenuRequest {
     pub date: NaiveDate,
+    #[validate(length(max = MAX_TITLE))]
     pub weather: Option<String>, // garderie-wide weather
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(max = MAX_NOTE))]
     pub menu: Option<String>, // Deprecated
+    #[validate(length(max = MAX_NOTE))]
     pub collation_matin: Option<String>,
+    #[validate(length(max = MAX_NOTE))]
     pub diner: Option<String>,
+    #[validate(length(max = MAX_NOTE))]
     pub collation_apres_midi: Option<String>,
 }
 
+/// One dish in a planner request.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct MenuItemInput {
+    #[validate(length(min = 1, max = MAX_NAME))]
+    pub meal: String,
+    #[validate(length(min = 1, max = MAX_NAME))]
+    pub name: String,
+    #[serde(default)]
+    pub allergens: Vec<String>,
+}
+
+/// One day of the weekly planner.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct PlanDayRequest {
+    pub date: NaiveDate,
+    #[validate(length(max = MAX_TITLE))]
+    pub weather: Option<String>,
+    /// Replaces every item of that day; an empty list clears the day's menu.
+    #[validate(nested, length(max = MAX_BULK_IDS))]
+    pub items: Vec<MenuItemInput>,
+}
+
+/// Body for PUT /menus/week.
+#[derive(Debug, Deserialize, Validate)]
+pub struct PlanWeekRequest {
+    #[validate(nested, length(max = MAX_BULK_IDS))]
+    pub days: Vec<PlanDayRequest>,
+}
+
+/// A menu item containing an allergen listed on a child's allergy profile.
+#[derive(Debug, Clone, Serialize)]
+pub struct AllergenConflict {
+    pub child_id: Uuid,
+    pub child_name: String,
+    pub date: NaiveDate,
+    pub meal: String,
+    pub item: String,
+    pub allergens: Vec<String>,
+}
+
+/// Response of GET /menus/week: the week's menus and the allergen conflicts
+/// for the children visible to the caller.
+#[derive(Debug, Serialize)]
+pub struct MenuWeekView {
+    pub week_start: NaiveDate,
+    pub days: Vec<DailyMenu>,
+    pub conflicts: Vec<AllergenConflict>,
+}
+
 /// Query params for GET /menus.
 #[derive(Debug, Deserialize)]
 pub struct MenuWeekQuery {
@@ -1,7 +1,9 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use crate::models::limits::{MAX_MESSAGE, MAX_TITLE};
+use validator::Validate;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -9,6 +11,8 @@ pub enum MessageType {
     Broadcast,
     Group,
     Individual,
+    /// Internal staff channel: all staff, or the staff of `group_id`.
+    Staff,
 }
 
 impl std::fmt::Display for MessageType {
@@ -17,6 +21,7 @@ impl std::fmt::Display for MessageType {
             MessageType::Broadcast => "broadcast",
             MessageType::Group => "group",
             MessageType::Individual => "individual",
+            MessageType::Staff => "staff",
         };
         write!(f, "{s}")
     }
@@ -29,6 +34,7 @@ impl std::str::FromStr for MessageType {
             "broadcast" => Ok(MessageType::Broadcast),
             "group" => Ok(MessageType::Group),
             "individual" => Ok(MessageType::Individual),
+            "staff" => Ok(MessageType::Staff),
             _ => Err(anyhow::anyhow!("Unknown message_type: {s}")),
         }
     }
@@ -41,6 +47,7 @@ pub enum SendToParentsScope {
     AllParents,           // À tous les parents du tenant
     ChildParents,         // À tous les parents d'un enfant
     GroupParents,         // À tous les parents d'un groupe
+    FamilyParents,        // À tous les parents d'une famille
 }
 
 impl std::fmt::Display for SendToParentsScope {
@@ -49,6 +56,7 @@ impl std::fmt::Display for SendToParentsScope {
             SendToParentsScope::AllParents => "all_parents",
             SendToParentsScope::ChildParents => "child_parents",
             SendToParentsScope::GroupParents => "group_parents",
+            SendToParentsScope::FamilyParents => "family_parents",
         };
         write!(f, "{s}")
     }
@@ -61,12 +69,13 @@ impl std::str::FromStr for SendToParentsScope {
             "all_parents" => Ok(SendToParentsScope::AllParents),
             "child_parents" => Ok(SendToParentsScope::ChildParents),
             "group_parents" => Ok(SendToParentsScope::GroupParents),
+            "family_parents" => Ok(SendToParentsScope::FamilyParents),
             _ => Err(anyhow::anyhow!("Unknown send_to_parents_scope: {s}")),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, async_graphql::SimpleObject)]
 pub struct Message {
     pub id: Uuid,
     pub sender_id: Uuid,
@@ -77,6 +86,10 @@ pub struct Message {
     pub is_read: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub edited_at: Option<DateTime<Utc>>,
+    pub is_deleted: bool,
+    /// `None` only on legacy messages outside any thread.
+    pub thread_id: Option<Uuid>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -87,25 +100,34 @@ pub struct MessageAttachment {
     pub document_id: Option<Uuid>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct CreateMessageRequest {
     pub message_type: MessageType,
     pub group_id: Option<Uuid>,
     pub recipient_id: Option<Uuid>,
+    #[validate(length(min = 1, max = MAX_MESSAGE))]
     pub content: String,
+    /// Staff only: also text the message to parents who have a phone number
+    /// (broadcast and group messages).
+    #[serde(default)]
+    pub urgent: bool,
 }
 
 /// Request pour envoyer un message à des parents avec notification email
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct SendToParentsRequest {
+    #[validate(length(max = MAX_TITLE))]
     pub subject: String,
+    #[validate(length(min = 1, max = MAX_MESSAGE))]
     pub content: String,
     pub scope: SendToParentsScope,
     pub child_id: Option<Uuid>,    // Required si scope = ChildParents
     pub group_id: Option<Uuid>,    // Required si scope = GroupParents
+    #[serde(default)]
+    pub family_id: Option<Uuid>,   // Required si scope = FamilyParents
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, async_graphql::SimpleObject)]
 pub struct MessageWithSender {
     pub id: Uuid,
     pub sender_id: Uuid,
@@ -117,17 +139,193 @@ pub struct MessageWithSender {
     pub content: String,
     pub is_read: bool,
     pub created_at: DateTime<Utc>,
+    pub edited_at: Option<DateTime<Utc>>,
+    pub is_deleted: bool,
+    pub thread_id: Option<Uuid>,
+    /// Number of recipients who have read a broadcast/group message (staff views only).
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_count: Option<i64>,
+    /// Reaction counts, filled in by `ReactionService::annotate` on thread views.
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub reactions: Vec<ReactionCount>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateMessageRequest {
+    #[validate(length(min = 1, max = MAX_MESSAGE))]
+    pub content: String,
+}
+
+/// Contenu d'un message avant une modification (historique des éditions)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MessageEdit {
+    pub id: Uuid,
+    pub message_id: Uuid,
+    pub previous_content: String,
+    pub edited_by: Uuid,
+    pub edited_at: DateTime<Utc>,
+}
+
+/// A recipient who has read a message.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MessageReader {
+    pub user_id: Uuid,
+    pub first_name: String,
+    pub last_name: String,
+    pub read_at: DateTime<Utc>,
+}
+
+/// One notification of a message to one recipient.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MessageDelivery {
+    pub user_id: Uuid,
+    pub first_name: String,
+    pub last_name: String,
+    /// "email" | "push" | "sms"
+    pub channel: String,
+    /// "sent" | "failed" | "skipped"
+    pub status: String,
+    pub detail: Option<String>,
+    pub attempted_at: DateTime<Utc>,
+}
+
+/// GET /messages/:id/deliveries — the email, push and SMS sent for a message.
+#[derive(Debug, Serialize)]
+pub struct MessageDeliveryReport {
+    pub message_id: Uuid,
+    pub sent: i64,
+    pub failed: i64,
+    pub skipped: i64,
+    pub deliveries: Vec<MessageDelivery>,
+}
+
+/// GET /messages/:id/reads — who read a message, out of how many recipients.
+#[derive(Debug, Serialize)]
+pub struct MessageReadReceipts {
+    pub message_id: Uuid,
+    pub read_count: i64,
+    pub recipient_count: i64,
+    pub readers: Vec<MessageReader>,
+}
+
+/// How many users reacted to a message with one emoji.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, async_graphql::SimpleObject)]
+pub struct ReactionCount {
+    pub emoji: String,
+    pub count: i64,
+    /// The viewer is among them.
+    pub reacted: bool,
+}
+
+/// Body for POST /messages/:id/reactions.
+#[derive(Debug, Deserialize, Validate)]
+pub struct ReactionRequest {
+    #[validate(length(max = MAX_TITLE))]
+    pub emoji: String,
+}
+
+/// Query params for DELETE /messages/:id/reactions.
+#[derive(Debug, Deserialize)]
+pub struct ReactionQuery {
+    pub emoji: String,
+}
+
+/// Payload of the "message_reactions" WebSocket event. `reactions` is seen from the
+/// user who reacted; other clients only take the counts from it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReactionEvent {
+    pub message_id: Uuid,
+    pub user_id: Uuid,
+    pub emoji: String,
+    pub added: bool,
+    pub reactions: Vec<ReactionCount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, async_graphql::SimpleObject)]
 pub struct ConversationItem {
     pub kind: String,
     pub id: Option<String>,
+    /// Row of `threads`, absent until the thread's first message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<Uuid>,
     pub name: String,
     pub color: Option<String>,
     pub last_message: Option<String>,
     pub last_at: Option<DateTime<Utc>>,
     pub unread_count: i64,
+    /// Presence of the parent on an individual thread (staff views only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub online: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_seen_at: Option<DateTime<Utc>>,
+    /// Set when the staff member of the thread is out of office, see `AutoReplyService::annotate`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub away: Option<AwayStatus>,
+    /// The viewer's own thread state, see `ThreadStateService::annotate`.
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub muted: bool,
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// Query params for GET /messages/conversations.
+#[derive(Debug, Default, Deserialize)]
+pub struct ConversationQuery {
+    /// Archived threads only when true; hidden unless asked for.
+    #[serde(default)]
+    pub archived: bool,
+    pub muted: Option<bool>,
+    pub pinned: Option<bool>,
+}
+
+/// Body for PUT /messages/thread/state — unset flags are left as they are.
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateThreadStateRequest {
+    /// "broadcast" | "group" | "individual", as in GET /messages/conversations
+    pub kind: String,
+    pub id: Option<String>,
+    pub archived: Option<bool>,
+    pub muted: Option<bool>,
+    pub pinned: Option<bool>,
+}
+
+/// A user's own settings for one thread.
+#[derive(Debug, Clone, Default, Serialize, FromRow)]
+pub struct ThreadState {
+    pub archived: bool,
+    pub muted: bool,
+    pub pinned: bool,
+}
+
+/// A staff member's out-of-office: parents writing to them between the two dates
+/// get `message` back, once per thread.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AutoReply {
+    pub starts_on: NaiveDate,
+    pub ends_on: NaiveDate,
+    pub message: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateAutoReplyRequest {
+    pub starts_on: NaiveDate,
+    pub ends_on: NaiveDate,
+    #[validate(length(min = 1, max = MAX_MESSAGE))]
+    pub message: String,
+}
+
+/// Staff member of a conversation who is away today.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, async_graphql::SimpleObject)]
+pub struct AwayStatus {
+    pub user_id: Uuid,
+    pub name: String,
+    /// Last day of the absence.
+    pub until: NaiveDate,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -137,6 +335,24 @@ pub struct WsMessage {
     pub payload: serde_json::Value,
 }
 
+/// Client → server WebSocket events.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", content = "payload", rename_all = "snake_case")]
+pub enum WsClientEvent {
+    Typing(TypingEvent),
+    /// Keeps the sender's presence alive while the tab is idle.
+    Ping,
+}
+
+/// The user started or stopped typing in a thread; fanned out to the thread's audience.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypingEvent {
+    /// "broadcast", "group", "individual" or "staff".
+    pub thread_kind: String,
+    pub thread_id: Option<Uuid>,
+    pub is_typing: bool,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct PaginationQuery {
     pub page: Option<i64>,
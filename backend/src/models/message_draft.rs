@@ -0,0 +1,67 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::models::limits::{MAX_MESSAGE, MAX_NAME, MAX_TITLE};
+use validator::Validate;
+
+/// Variables a draft or template may use, filled in for each recipient at send time.
+pub const DRAFT_VARIABLES: &[&str] = &["child_name", "group_name"];
+
+/// Where a draft is meant to be sent: a message type or a send-to-parents scope.
+pub const DRAFT_SCOPES: &[&str] =
+    &["broadcast", "group", "individual", "all_parents", "child_parents", "group_parents"];
+
+/// A message saved for later. Drafts are private to their author; templates are
+/// shared with all staff so a weekly reminder is written once.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MessageDraft {
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    pub name: String,
+    pub is_template: bool,
+    /// Email subject, for send-to-parents announcements.
+    pub subject: Option<String>,
+    pub content: String,
+    pub scope: Option<String>,
+    pub group_id: Option<Uuid>,
+    pub child_id: Option<Uuid>,
+    pub recipient_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Body for POST and PUT /messages/drafts.
+#[derive(Debug, Deserialize, Validate)]
+pub struct MessageDraftRequest {
+    #[validate(length(min = 1, max = MAX_NAME))]
+    pub name: String,
+    #[serde(default)]
+    pub is_template: bool,
+    #[validate(length(max = MAX_TITLE))]
+    pub subject: Option<String>,
+    #[validate(length(min = 1, max = MAX_MESSAGE))]
+    pub content: String,
+    pub scope: Option<String>,
+    pub group_id: Option<Uuid>,
+    pub child_id: Option<Uuid>,
+    pub recipient_id: Option<Uuid>,
+}
+
+/// Query for GET /messages/drafts: `templates=true` lists only shared templates,
+/// `templates=false` only the caller's drafts; both when omitted.
+#[derive(Debug, Deserialize)]
+pub struct DraftQuery {
+    pub templates: Option<bool>,
+}
+
+/// A parent receiving an announcement, with the names their `{child_name}` and
+/// `{group_name}` variables expand to.
+#[derive(Debug, Clone, FromRow)]
+pub struct ParentRecipient {
+    pub email: String,
+    pub name: String,
+    pub locale: String,
+    pub child_names: String,
+    pub group_names: String,
+}
@@ -1,13 +1,38 @@
+pub mod album;
 pub mod announcement;
 pub mod activity;
 pub mod attendance;
 pub mod auth;
+pub mod backup;
 pub mod child;
+pub mod consent;
+pub mod departure;
+pub mod development;
 pub mod document;
+pub mod email_campaign;
+pub mod email_log;
+pub mod email_template;
+pub mod emergency;
+pub mod erasure;
+pub mod family;
+pub mod feature_flag;
 pub mod group;
+pub mod invoice;
 pub mod journal;
+pub mod limits;
 pub mod media;
 pub mod menu;
+pub mod meeting;
 pub mod message;
+pub mod message_draft;
+pub mod oidc;
+pub mod operation;
+pub mod ratio;
+pub mod retention;
+pub mod search;
+pub mod stats;
+pub mod tax_receipt;
 pub mod tenant;
+pub mod transfer;
 pub mod user;
+pub mod waitlist;
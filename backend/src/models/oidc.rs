@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use crate::models::limits::{MAX_BULK_IDS, MAX_TITLE};
+use validator::Validate;
+
+/// Identity providers with a preset issuer; "custom" takes any OIDC issuer.
+pub const OIDC_PROVIDERS: &[&str] = &["google", "microsoft", "custom"];
+
+/// Roles a staff member created on first SSO login may receive.
+pub const JIT_ROLES: &[&str] = &["educateur", "admin_garderie"];
+
+/// Single sign-on configuration of a garderie, as shown to its admins. The client
+/// secret is write-only.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct OidcSettings {
+    pub provider: String,
+    pub issuer_url: String,
+    pub client_id: String,
+    pub has_client_secret: bool,
+    pub enabled: bool,
+    /// Email domains of the organisation, lowercase; empty accepts any verified email.
+    pub allowed_domains: Vec<String>,
+    /// Create a staff account on first login for unknown emails of `allowed_domains`.
+    pub jit_provisioning: bool,
+    pub jit_role: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateOidcSettingsRequest {
+    pub provider: String,
+    /// Required for "custom" and "microsoft" (tenant-specific issuer).
+    #[validate(length(max = MAX_TITLE))]
+    pub issuer_url: Option<String>,
+    #[validate(length(max = MAX_TITLE))]
+    pub client_id: String,
+    /// Keeps the stored secret when omitted.
+    #[validate(length(max = MAX_TITLE))]
+    pub client_secret: Option<String>,
+    pub enabled: bool,
+    #[serde(default)]
+    #[validate(length(max = MAX_BULK_IDS))]
+    pub allowed_domains: Vec<String>,
+    #[serde(default)]
+    pub jit_provisioning: bool,
+    pub jit_role: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OidcStartQuery {
+    /// Locale of the web app page the provider redirects back to.
+    pub locale: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct OidcCallbackRequest {
+    pub code: String,
+    pub state: String,
+}
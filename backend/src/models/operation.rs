@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+pub const OP_DELETE_GARDERIE: &str = "delete_garderie";
+pub const OP_RESTORE: &str = "restore";
+pub const OP_RESTORE_GARDERIE: &str = "restore_garderie";
+pub const OP_ARCHIVE_GARDERIE: &str = "archive_garderie";
+pub const OP_TRANSFER_CHILD: &str = "transfer_child";
+
+/// A destructive super-admin operation awaiting confirmation or the end of its cancel window.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct PendingOperation {
+    pub id: Uuid,
+    /// `delete_garderie`, `archive_garderie`, `restore` (whole host backup),
+    /// `restore_garderie` (one tenant from S3) or `transfer_child` (from `target_slug`).
+    pub kind: String,
+    pub target_slug: Option<String>,
+    pub payload: serde_json::Value,
+    /// pending → running → executed | failed, or pending → cancelled.
+    pub status: String,
+    /// Email of the super-admin who asked for it, when they identified themselves.
+    pub requested_by: Option<String>,
+    pub confirmed_by: Option<String>,
+    pub cancelled_by: Option<String>,
+    /// Runs on its own after this moment unless cancelled.
+    pub execute_after: DateTime<Utc>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub executed_at: Option<DateTime<Utc>>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+}
+
+/// POST /super-admin/restore — also stored as the payload of a `restore` operation.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct RestoreRequest {
+    pub db_file: String,
+    pub media_file: Option<String>,
+}
@@ -0,0 +1,85 @@
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::models::limits::{MAX_BULK_IDS, MAX_NAME};
+use validator::Validate;
+
+/// Children allowed per educator for an age band `[min_age_months, max_age_months)`.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RatioRule {
+    pub id: Uuid,
+    pub label: String,
+    pub min_age_months: i32,
+    /// `None`: no upper bound.
+    pub max_age_months: Option<i32>,
+    pub children_per_educator: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct RatioRuleInput {
+    #[validate(length(min = 1, max = MAX_NAME))]
+    pub label: String,
+    pub min_age_months: i32,
+    pub max_age_months: Option<i32>,
+    pub children_per_educator: i32,
+}
+
+/// Body for PUT /ratios/rules: the full set of bands, replacing the current one.
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateRatioRulesRequest {
+    #[validate(nested, length(max = MAX_BULK_IDS))]
+    pub rules: Vec<RatioRuleInput>,
+}
+
+/// An educator on duty in a group.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct StaffShift {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub user_name: String,
+    pub group_id: Uuid,
+    pub date: NaiveDate,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateShiftRequest {
+    pub user_id: Uuid,
+    pub group_id: Uuid,
+    pub date: NaiveDate,
+    /// HH:MM
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RatioPeriodQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub group_id: Option<Uuid>,
+}
+
+/// Present children of one age band.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BandCount {
+    pub label: String,
+    pub children_per_educator: i32,
+    pub children: i64,
+}
+
+/// Children and educators of a group at a given time (or over a day).
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupRatio {
+    pub group_id: Uuid,
+    pub group_name: String,
+    pub date: NaiveDate,
+    pub children: i64,
+    pub educators: i64,
+    pub required_educators: i64,
+    pub compliant: bool,
+    pub bands: Vec<BandCount>,
+}
@@ -0,0 +1,97 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::limits::MAX_NOTE;
+
+/// Retention periods of a garderie; the defaults until an admin changes them.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct RetentionSettings {
+    /// Daily journals and their events are deleted this many years after their date.
+    pub journal_years: i16,
+    /// Media of children who all left are deleted this many years after the last departure.
+    pub media_years: i16,
+    /// Messages are deleted this many years after they were sent.
+    pub message_years: i16,
+    /// Off: the nightly job only reports what it would delete.
+    pub purge_enabled: bool,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl Default for RetentionSettings {
+    fn default() -> Self {
+        Self { journal_years: 3, media_years: 1, message_years: 2, purge_enabled: false, updated_at: None }
+    }
+}
+
+/// Body for PUT /retention.
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateRetentionRequest {
+    #[validate(range(min = 1, max = 50))]
+    pub journal_years: i16,
+    #[validate(range(min = 0, max = 50))]
+    pub media_years: i16,
+    #[validate(range(min = 1, max = 50))]
+    pub message_years: i16,
+    pub purge_enabled: bool,
+}
+
+/// What a purge deletes, or would delete, given its cutoff dates.
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionReport {
+    /// Journals dated before this day.
+    pub journal_cutoff: NaiveDate,
+    /// Media whose children all left before this day.
+    pub media_cutoff: NaiveDate,
+    /// Messages sent before this day.
+    pub message_cutoff: NaiveDate,
+    pub journals: i64,
+    pub journal_events: i64,
+    pub media: i64,
+    pub messages: i64,
+    /// A garderie-wide legal hold suspends everything.
+    pub held: bool,
+    pub dry_run: bool,
+}
+
+/// GET /retention/upcoming?days= — deletions due within `days` (default 30).
+#[derive(Debug, Deserialize)]
+pub struct UpcomingQuery {
+    pub days: Option<i64>,
+}
+
+/// One pass of the nightly job.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct RetentionRun {
+    pub id: Uuid,
+    pub dry_run: bool,
+    pub journals: i32,
+    pub journal_events: i32,
+    pub media: i32,
+    pub messages: i32,
+    pub held: bool,
+    pub ran_at: DateTime<Utc>,
+}
+
+/// Suspends the purge for one child, or for the whole garderie when `child_id` is `None`.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct LegalHold {
+    pub id: Uuid,
+    pub child_id: Option<Uuid>,
+    pub child_name: Option<String>,
+    pub reason: String,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub released_by: Option<Uuid>,
+    pub released_at: Option<DateTime<Utc>>,
+}
+
+/// Body for POST /retention/holds.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateLegalHoldRequest {
+    pub child_id: Option<Uuid>,
+    #[validate(length(min = 1, max = MAX_NOTE))]
+    pub reason: String,
+}
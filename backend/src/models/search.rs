@@ -0,0 +1,32 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Kinds of results the staff search returns.
+pub const SEARCH_KINDS: &[&str] = &["child", "parent", "document", "message"];
+
+/// Query for GET /search.
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    /// Comma-separated subset of `SEARCH_KINDS`; all kinds when omitted.
+    pub types: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SearchHit {
+    pub kind: String,
+    pub id: Uuid,
+    pub title: String,
+    /// Group of a child, email of a parent, file name of a document, or the matching
+    /// excerpt of a message with the matched words wrapped in `**`.
+    pub snippet: Option<String>,
+    pub rank: f32,
+    pub date: DateTime<Utc>,
+    /// For messages: the thread to open (`broadcast`, `group`, `individual` or
+    /// `staff`) and its group or parent id.
+    pub thread_kind: Option<String>,
+    pub thread_id: Option<Uuid>,
+}
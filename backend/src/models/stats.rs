@@ -0,0 +1,103 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::tenant::PlanType;
+
+/// Rollup of one completed day.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct StatsDay {
+    pub date: NaiveDate,
+    /// Parents with a successful login in the 30 days ending on `date`.
+    pub active_parents: i32,
+    pub messages: i32,
+    /// Media stored at the end of the day.
+    pub media_bytes: i64,
+    pub logins: i32,
+    pub failed_logins: i32,
+    pub invitations_sent: i32,
+    /// Invitations sent that day and accepted since.
+    pub invitations_accepted: i32,
+}
+
+/// Journals an educator sent over the period, out of the children present in their groups.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct EducatorCompletion {
+    pub user_id: Uuid,
+    pub first_name: String,
+    pub last_name: String,
+    pub expected: i64,
+    pub completed: i64,
+    /// Percent, `None` when no child was expected.
+    #[sqlx(skip)]
+    pub completion_rate: Option<f64>,
+}
+
+/// Figures over the whole period.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct StatsTotals {
+    pub active_parents: i32,
+    pub messages: i64,
+    pub media_bytes: i64,
+    pub logins: i64,
+    pub failed_logins: i64,
+    pub invitations_sent: i64,
+    pub invitations_accepted: i64,
+    /// Percent, `None` when no invitation was sent.
+    pub invitation_acceptance_rate: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatsQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+/// Activity signals of one garderie, as seen by the platform team.
+#[derive(Debug, Clone, Serialize)]
+pub struct TenantHealth {
+    pub slug: String,
+    pub name: String,
+    pub plan: PlanType,
+    pub is_active: bool,
+    pub trial_expires_at: Option<DateTime<Utc>>,
+    /// Whole days left in the trial; negative once it lapsed, `None` without a trial.
+    pub trial_days_left: Option<i64>,
+    pub active_users: i64,
+    /// Distinct users signed in over the last 24 hours.
+    pub dau: i64,
+    /// Distinct users signed in over the last 7 days.
+    pub wau: i64,
+    pub last_admin_login: Option<DateTime<Utc>>,
+    pub storage_percent: f64,
+    pub used_bytes: i64,
+    pub emails_7d: i64,
+    pub email_failures_7d: i64,
+    /// 0 (churning) to 100 (healthy).
+    pub health_score: u8,
+    pub health: HealthLevel,
+    /// Signals that lowered the score, e.g. `no_admin_login_14d`.
+    pub risks: Vec<&'static str>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthLevel {
+    Healthy,
+    AtRisk,
+    Critical,
+}
+
+/// Platform-wide figures for GET /super-admin/stats.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct PlatformTotals {
+    pub garderies: i64,
+    pub in_trial: i64,
+    pub dau: i64,
+    pub wau: i64,
+    pub used_bytes: i64,
+    pub email_failures_7d: i64,
+    pub at_risk: i64,
+    pub critical: i64,
+}
@@ -0,0 +1,55 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Year-end childcare expense receipt (Relevé 24) for one payer and one child.
+/// `amount_cents` totals the eligible lines of the invoices paid during the year.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TaxReceipt {
+    pub id: Uuid,
+    pub year: i32,
+    /// "RL24-2026-0001", sequential within the year.
+    pub number: String,
+    pub payer_id: Option<Uuid>,
+    pub payer_name: String,
+    pub child_id: Option<Uuid>,
+    pub child_name: String,
+    pub child_birth_date: NaiveDate,
+    pub amount_cents: i64,
+    pub invoice_count: i32,
+    pub generated_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Query params for GET /tax-receipts.
+#[derive(Debug, Deserialize)]
+pub struct TaxReceiptQuery {
+    pub year: Option<i32>,
+}
+
+/// Body for POST /tax-receipts/generate.
+#[derive(Debug, Deserialize, Validate)]
+pub struct GenerateTaxReceiptsRequest {
+    pub year: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GenerateTaxReceiptsResult {
+    pub receipts: Vec<TaxReceipt>,
+    pub created: usize,
+    pub updated: usize,
+    /// Receipts of earlier runs whose payments have since been voided or moved; kept at zero.
+    pub zeroed: usize,
+    /// Paid invoices of the year whose child has no parent to address a receipt to.
+    pub unassigned_invoices: i64,
+}
+
+/// Query params for GET /super-admin/tax-receipts.csv.
+#[derive(Debug, Deserialize)]
+pub struct TaxReceiptExportQuery {
+    pub year: i32,
+    /// Garderie slug; every active garderie when absent.
+    pub garderie: Option<String>,
+}
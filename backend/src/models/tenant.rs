@@ -2,6 +2,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use crate::models::limits::{MAX_EMAIL, MAX_NAME, MAX_NOTE, MAX_PASSWORD, MAX_PHONE, MAX_TITLE};
+use validator::Validate;
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "plan_type", rename_all = "snake_case")]
@@ -27,25 +29,111 @@ pub struct Garderie {
     pub plan: PlanType,
     pub is_active: bool,
     pub trial_expires_at: Option<DateTime<Utc>>,
+    /// Set once the garderie is archived: its data only lives in the archive backup.
+    pub archived_at: Option<DateTime<Utc>>,
+    pub archive_backup_id: Option<String>,
+    /// The archive is purged for good after this moment unless restored.
+    pub purge_after: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+/// Look of a garderie's outgoing emails and web pages, on top of its logo.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, FromRow)]
+pub struct TenantBranding {
+    pub logo_url: Option<String>,
+    /// Buttons and links, as `#rrggbb`.
+    pub primary_color: Option<String>,
+    /// Highlight stripe above the email body, as `#rrggbb`.
+    pub accent_color: Option<String>,
+    pub footer_text: Option<String>,
+    /// Where replies to garderie emails go instead of the no-reply sender.
+    pub reply_to: Option<String>,
+}
+
+impl TenantBranding {
+    pub const DEFAULT_PRIMARY_COLOR: &'static str = "#2563eb";
+
+    pub fn primary_color(&self) -> &str {
+        self.primary_color.as_deref().unwrap_or(Self::DEFAULT_PRIMARY_COLOR)
+    }
+}
+
+/// PUT /settings/branding — replaces every field; missing or empty ones are cleared.
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateBrandingRequest {
+    #[validate(length(max = MAX_TITLE))]
+    pub primary_color: Option<String>,
+    #[validate(length(max = MAX_TITLE))]
+    pub accent_color: Option<String>,
+    #[validate(length(max = MAX_NOTE))]
+    pub footer_text: Option<String>,
+    #[validate(length(max = MAX_EMAIL))]
+    pub reply_to: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
 pub struct CreateGarderieRequest {
+    #[validate(length(max = MAX_TITLE))]
     pub slug: String,
+    #[validate(length(min = 1, max = MAX_NAME))]
     pub name: String,
+    #[validate(length(max = MAX_TITLE))]
     pub address_line1: Option<String>,
+    #[validate(length(max = MAX_TITLE))]
     pub city: Option<String>,
+    #[validate(length(max = MAX_TITLE))]
     pub province: Option<String>,
+    #[validate(length(max = MAX_TITLE))]
     pub postal_code: Option<String>,
+    #[validate(length(max = MAX_PHONE))]
     pub phone: Option<String>,
+    #[validate(length(max = MAX_EMAIL))]
     pub email: Option<String>,
     pub plan: Option<PlanType>,
 }
 
+/// What cloning a garderie can copy; children, parents, messages and other personal
+/// data never are.
+pub const CLONE_PARTS: &[&str] = &["groups", "documents", "menus"];
+
+/// Body for POST /super-admin/garderies/{slug}/clone.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CloneGarderieRequest {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub garderie: CreateGarderieRequest,
+    /// First administrator of the new garderie; copied documents and menus are theirs.
+    #[validate(nested)]
+    pub admin: GarderieAdmin,
+    /// Subset of `CLONE_PARTS`; everything when omitted.
+    pub include: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GarderieAdmin {
+    #[validate(email, length(max = MAX_EMAIL))]
+    pub email: String,
+    #[validate(length(min = 1, max = MAX_NAME))]
+    pub first_name: String,
+    #[validate(length(min = 1, max = MAX_NAME))]
+    pub last_name: String,
+    #[validate(length(min = 8, max = MAX_PASSWORD))]
+    pub password: String,
+}
+
+/// What a clone copied into the new garderie.
+#[derive(Debug, Serialize)]
+pub struct CloneSummary {
+    pub garderie: Garderie,
+    pub admin_id: Uuid,
+    pub groups: usize,
+    pub documents: usize,
+    pub menus: usize,
+}
+
 /// Loi 25 — consentement enregistré lors du signup admin garderie.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct SignupConsentPayload {
     pub privacy_accepted: bool,
     pub parents_commitment_accepted: Option<bool>,
@@ -54,20 +142,35 @@ pub struct SignupConsentPayload {
     pub language: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct SignupRequest {
+    #[validate(length(max = MAX_TITLE))]
     pub slug: String,
+    #[validate(length(min = 1, max = MAX_NAME))]
     pub name: String,
+    #[validate(length(max = MAX_PHONE))]
     pub phone: Option<String>,
+    #[validate(length(max = MAX_TITLE))]
     pub address_line1: Option<String>,
+    #[validate(length(max = MAX_TITLE))]
     pub city: Option<String>,
+    #[validate(length(max = MAX_TITLE))]
     pub province: Option<String>,
+    #[validate(length(max = MAX_TITLE))]
     pub postal_code: Option<String>,
+    #[validate(length(min = 1, max = MAX_NAME))]
     pub first_name: String,
+    #[validate(length(min = 1, max = MAX_NAME))]
     pub last_name: String,
+    #[validate(email, length(max = MAX_EMAIL))]
     pub email: String,
+    #[validate(length(max = MAX_PASSWORD))]
     pub password: String,
     /// Loi 25 — métadonnées de consentement horodatées.
     /// Optionnel pour rétrocompatibilité mais persisté si fourni.
+    #[validate(nested)]
     pub consent: Option<SignupConsentPayload>,
+    /// Cloudflare Turnstile token; required when TURNSTILE_SECRET_KEY is set.
+    #[serde(alias = "cf-turnstile-response")]
+    pub captcha_token: Option<String>,
 }
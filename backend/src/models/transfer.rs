@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::limits::MAX_TITLE;
+
+/// POST /super-admin/garderies/{slug}/children/{child_id}/transfer — also stored,
+/// with the child, as the payload of a `transfer_child` operation.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct TransferChildRequest {
+    #[serde(default)]
+    pub child_id: Uuid,
+    /// Slug of the garderie the child moves to.
+    #[validate(length(min = 1, max = MAX_TITLE))]
+    pub target_slug: String,
+}
+
+/// An executed transfer, as recorded in `public.child_transfers`.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ChildTransfer {
+    pub id: Uuid,
+    pub operation_id: Option<Uuid>,
+    pub source_slug: String,
+    pub source_child_id: Uuid,
+    pub target_slug: String,
+    pub target_child_id: Uuid,
+    pub child_name: String,
+    pub requested_by: Option<String>,
+    pub confirmed_by: Option<String>,
+    pub journals: i32,
+    pub journal_events: i32,
+    /// Photos and videos of the child alone, copied only when photo consent was given.
+    pub media: i32,
+    /// Parents who already had an account in the target garderie.
+    pub parents_linked: i32,
+    /// Parents left to register through an invitation.
+    pub parents_invited: i32,
+    pub transferred_at: DateTime<Utc>,
+}
@@ -2,6 +2,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use crate::models::limits::{MAX_BULK_IDS, MAX_EMAIL, MAX_MESSAGE, MAX_NAME, MAX_PASSWORD, MAX_PHONE, MAX_PUSH_TOKEN, MAX_TITLE};
+use validator::Validate;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -53,6 +55,8 @@ pub struct User {
     pub is_active: bool,
     pub force_password_change: bool,
     pub preferred_locale: String,
+    /// E.164 number for SMS notifications and the SMS 2FA fallback.
+    pub phone: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -65,6 +69,12 @@ pub struct RefreshToken {
     pub expires_at: DateTime<Utc>,
     pub revoked: bool,
     pub created_at: DateTime<Utc>,
+    /// ID of the first token of the session; NULL on tokens issued before families.
+    pub family_id: Option<Uuid>,
+    /// Token this one was rotated from.
+    pub parent_id: Option<Uuid>,
+    /// When the token was exchanged for its successor.
+    pub rotated_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -87,13 +97,19 @@ pub struct PushToken {
     pub platform: String, // "android" | "ios"
     pub token: String,
     pub created_at: DateTime<Utc>,
+    /// Last registration by the app or push delivered to it.
+    pub last_used_at: DateTime<Utc>,
 }
 
 // Request/Response DTOs
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct LoginRequest {
+    #[validate(length(max = MAX_EMAIL))]
     pub email: String,
+    #[validate(length(max = MAX_PASSWORD))]
     pub password: String,
+    /// "sms" to receive the 2FA code by text message (defaults to email).
+    pub two_factor_channel: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -114,6 +130,7 @@ pub struct UserProfile {
     pub avatar_url: Option<String>,
     pub force_password_change: bool,
     pub preferred_locale: String,
+    pub phone: Option<String>,
 }
 
 impl From<User> for UserProfile {
@@ -127,12 +144,13 @@ impl From<User> for UserProfile {
             avatar_url: u.avatar_url,
             force_password_change: u.force_password_change,
             preferred_locale: u.preferred_locale,
+            phone: u.phone,
         }
     }
 }
 
 /// Loi 25 — consentement enregistré lors de l'inscription d'un parent via invitation.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct ParentConsentPayload {
     pub privacy_accepted: bool,
     pub photos_accepted: bool,
@@ -141,53 +159,90 @@ pub struct ParentConsentPayload {
     pub language: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct RegisterFromInviteRequest {
     pub token: String,
+    #[validate(length(min = 1, max = MAX_NAME))]
     pub first_name: String,
+    #[validate(length(min = 1, max = MAX_NAME))]
     pub last_name: String,
+    #[validate(length(max = MAX_PASSWORD))]
     pub password: String,
     pub preferred_locale: Option<String>,
     /// Loi 25 — métadonnées de consentement horodatées.
     /// Optionnel pour rétrocompatibilité (ex. démo), mais persisté si fourni.
+    #[validate(nested)]
     pub consent: Option<ParentConsentPayload>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct InviteUserRequest {
+    #[validate(email, length(max = MAX_EMAIL))]
     pub email: String,
     pub role: UserRole,
+    /// Language of the invitation email; French when omitted.
+    pub preferred_locale: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct BulkInviteRequest {
+    #[validate(nested, length(max = MAX_BULK_IDS))]
+    pub invitations: Vec<InviteUserRequest>,
+}
+
+/// Outcome of one address of a bulk invitation.
+#[derive(Debug, Serialize)]
+pub struct BulkInviteResult {
+    pub email: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ExtendInvitationRequest {
+    pub days: i32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct RefreshTokenRequest {
     pub refresh_token: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct RegisterPushTokenRequest {
     pub platform: String,
+    #[validate(length(min = 1, max = MAX_PUSH_TOKEN))]
+    pub token: String,
+}
+
+/// Body for DELETE /auth/push-token, sent by the app when signing out.
+#[derive(Debug, Deserialize, Validate)]
+pub struct UnregisterPushTokenRequest {
+    #[validate(length(min = 1, max = MAX_PUSH_TOKEN))]
     pub token: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct ForgotPasswordRequest {
+    #[validate(length(max = MAX_EMAIL))]
     pub email: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct ResetPasswordRequest {
     pub token: String,
+    #[validate(length(max = MAX_PASSWORD))]
     pub new_password: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct AdminResetPasswordRequest {
     pub method: Option<String>, // "email" or "temp_password"
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct DeleteUserRequest {
+    #[validate(length(max = MAX_PASSWORD))]
     pub password: String,
 }
 
@@ -197,22 +252,41 @@ pub struct AdminResetPasswordResponse {
     pub temp_password: Option<String>, // Only if method is "temp_password"
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct SendEmailRequest {
+    #[validate(length(max = MAX_TITLE))]
     pub subject: String,
+    #[validate(length(max = MAX_MESSAGE))]
     pub body: String,
     pub recipient_id: Option<Uuid>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
+pub struct SwitchTenantRequest {
+    #[validate(length(max = MAX_TITLE))]
+    pub tenant: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdatePhoneRequest {
+    /// None or empty clears the number.
+    #[validate(length(max = MAX_PHONE))]
+    pub phone: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
 pub struct ChangePasswordRequest {
+    #[validate(length(max = MAX_PASSWORD))]
     pub current_password: String,
+    #[validate(length(max = MAX_PASSWORD))]
     pub new_password: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct UpdateEmailRequest {
+    #[validate(email, length(max = MAX_EMAIL))]
     pub new_email: String,
+    #[validate(length(max = MAX_PASSWORD))]
     pub password: String, // Verify password for security
 }
 
@@ -221,11 +295,14 @@ pub struct UpdateEmailRequest {
 pub struct LoginStep1Response {
     pub status: String, // always "2fa_required"
     pub garderie_name: String,
+    /// Where the code was sent: "email" or "sms".
+    pub channel: String,
 }
 
 /// Request body for the 2FA verification step.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct VerifyTwoFactorRequest {
+    #[validate(length(max = MAX_EMAIL))]
     pub email: String,
     pub code: String,
 }
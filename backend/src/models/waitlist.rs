@@ -0,0 +1,93 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::models::limits::{MAX_BULK_IDS, MAX_EMAIL, MAX_NAME, MAX_NOTE, MAX_PHONE, MAX_TITLE};
+use validator::Validate;
+
+/// Lifecycle of a waitlist entry. `enrolled` is set by the conversion action only.
+pub const WAITLIST_STATUSES: &[&str] = &["waiting", "offered", "accepted", "declined", "withdrawn", "enrolled"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WaitlistEntry {
+    pub id: Uuid,
+    pub child_first_name: String,
+    pub child_last_name: String,
+    pub child_birth_date: NaiveDate,
+    pub parent_first_name: String,
+    pub parent_last_name: String,
+    pub parent_email: String,
+    pub parent_phone: Option<String>,
+    pub desired_start_date: Option<NaiveDate>,
+    pub age_group: Option<String>,
+    pub priority: i32,
+    pub status: String,
+    pub notes: Option<String>,
+    pub offered_at: Option<DateTime<Utc>>,
+    pub child_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Used both by the public submission form and by admins; `priority` is ignored on the public route.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateWaitlistEntryRequest {
+    #[validate(length(min = 1, max = MAX_NAME))]
+    pub child_first_name: String,
+    #[validate(length(min = 1, max = MAX_NAME))]
+    pub child_last_name: String,
+    pub child_birth_date: NaiveDate,
+    #[validate(length(min = 1, max = MAX_NAME))]
+    pub parent_first_name: String,
+    #[validate(length(min = 1, max = MAX_NAME))]
+    pub parent_last_name: String,
+    #[validate(email, length(max = MAX_EMAIL))]
+    pub parent_email: String,
+    #[validate(length(max = MAX_PHONE))]
+    pub parent_phone: Option<String>,
+    pub desired_start_date: Option<NaiveDate>,
+    #[validate(length(max = MAX_TITLE))]
+    pub age_group: Option<String>,
+    pub priority: Option<i32>,
+    #[validate(length(max = MAX_NOTE))]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateWaitlistEntryRequest {
+    #[validate(length(max = MAX_NAME))]
+    pub child_first_name: Option<String>,
+    #[validate(length(max = MAX_NAME))]
+    pub child_last_name: Option<String>,
+    pub child_birth_date: Option<NaiveDate>,
+    #[validate(length(max = MAX_NAME))]
+    pub parent_first_name: Option<String>,
+    #[validate(length(max = MAX_NAME))]
+    pub parent_last_name: Option<String>,
+    #[validate(length(max = MAX_EMAIL))]
+    pub parent_email: Option<String>,
+    #[validate(length(max = MAX_PHONE))]
+    pub parent_phone: Option<String>,
+    pub desired_start_date: Option<NaiveDate>,
+    #[validate(length(max = MAX_TITLE))]
+    pub age_group: Option<String>,
+    pub priority: Option<i32>,
+    pub status: Option<String>,
+    #[validate(length(max = MAX_NOTE))]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WaitlistQuery {
+    pub status: Option<String>,
+    pub age_group: Option<String>,
+}
+
+/// Enrolls the child: creates the child record and invites the parent.
+#[derive(Debug, Deserialize, Validate)]
+pub struct ConvertWaitlistRequest {
+    pub group_id: Option<Uuid>,
+    pub start_date: Option<NaiveDate>,
+    #[validate(length(max = MAX_BULK_IDS))]
+    pub schedule_days: Option<Vec<i32>>,
+}
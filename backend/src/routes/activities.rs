@@ -9,6 +9,7 @@ use uuid::Uuid;
 
 use crate::{
     db::tenant::schema_name,
+    error::ApiError,
     middleware::tenant::TenantSlug,
     models::{
         activity::{
@@ -20,6 +21,7 @@ use crate::{
     },
     AppState,
 };
+use crate::middleware::validation::ValidJson;
 
 /// GET /activities?month=YYYY-MM&child_id=...
 /// Returns activities for a given month
@@ -29,35 +31,26 @@ pub async fn list_activities(
     TenantSlug(tenant): TenantSlug,
     _user: AuthenticatedUser,
     Query(params): Query<ActivitiesListQuery>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     // Parse month
     let month_parts: Vec<&str> = params.month.split('-').collect();
     if month_parts.len() != 2 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": "Invalid month format. Use YYYY-MM" })),
-        ));
+        return Err(ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_month"));
     }
 
     let year = month_parts[0]
         .parse::<i32>()
-        .map_err(|_| (StatusCode::BAD_REQUEST, Json(json!({ "error": "Invalid year" }))))?;
+        .map_err(|_| ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_year"))?;
     let month = month_parts[1]
         .parse::<u32>()
-        .map_err(|_| (StatusCode::BAD_REQUEST, Json(json!({ "error": "Invalid month" }))))?;
+        .map_err(|_| ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_month"))?;
 
     if month < 1 || month > 12 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": "Month must be 1-12" })),
-        ));
+        return Err(ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_month"));
     }
 
     let start_date = NaiveDate::from_ymd_opt(year, month, 1).ok_or_else(|| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": "Invalid date" })),
-        )
+        ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_date")
     })?;
 
     let end_date = if month == 12 {
@@ -73,7 +66,7 @@ pub async fn list_activities(
     // or span across the month (end_date >= first_day)
     let mut activities = sqlx::query_as::<_, Activity>(
         &format!(
-            "SELECT a.id, a.title, a.description, a.date, a.end_date, a.capacity, a.group_id, a.type, a.created_by, a.created_at, a.updated_at,
+            "SELECT a.id, a.title, a.description, a.date, a.end_date, a.capacity, a.group_id, a.type, a.fee_cents, a.created_by, a.created_at, a.updated_at,
                     CAST(COUNT(ar.id) AS INT) as registration_count
              FROM {}.activities a
              LEFT JOIN {}.activity_registrations ar ON a.id = ar.activity_id
@@ -86,13 +79,7 @@ pub async fn list_activities(
     .bind(start_date)
     .bind(end_date)
     .fetch_all(&state.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })?;
+    .await?;
 
     // If child_id is provided, add registration status
     if let Some(child_id) = params.child_id {
@@ -106,13 +93,7 @@ pub async fn list_activities(
             .bind(activity.id)
             .bind(child_id)
             .fetch_one(&state.db)
-            .await
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({ "error": e.to_string() })),
-                )
-            })?;
+            .await?;
             activity.is_registered = Some(is_registered);
         }
     }
@@ -127,22 +108,19 @@ pub async fn create_activity(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-    Json(req): Json<CreateActivityRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(req): ValidJson<CreateActivityRequest>,
+) -> Result<Json<Value>, ApiError> {
     // Admin only
     if !matches!(user.role, UserRole::AdminGarderie) {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(json!({ "error": "Only admin can create activities" })),
-        ));
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "Only admin can create activities"));
     }
 
     let date = NaiveDate::parse_from_str(&req.date, "%Y-%m-%d")
-        .map_err(|_| (StatusCode::BAD_REQUEST, Json(json!({ "error": "Invalid date format" }))))?;
+        .map_err(|_| ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_date"))?;
 
     let end_date = if let Some(end_date_str) = &req.end_date {
         Some(NaiveDate::parse_from_str(end_date_str, "%Y-%m-%d")
-            .map_err(|_| (StatusCode::BAD_REQUEST, Json(json!({ "error": "Invalid end_date format" }))))?)
+            .map_err(|_| ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_date"))?)
     } else {
         None
     };
@@ -154,8 +132,8 @@ pub async fn create_activity(
 
     sqlx::query(
         &format!(
-            "INSERT INTO {}.activities (id, title, description, date, end_date, capacity, group_id, type, created_by, created_at, updated_at)
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW(), NOW())",
+            "INSERT INTO {}.activities (id, title, description, date, end_date, capacity, group_id, type, created_by, fee_cents, created_at, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, NOW(), NOW())",
             schema
         ),
     )
@@ -168,14 +146,9 @@ pub async fn create_activity(
     .bind(req.group_id)
     .bind(activity_type)
     .bind(user.user_id)
+    .bind(req.fee_cents)
     .execute(&state.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })?;
+    .await?;
 
     Ok(Json(json!({ "id": activity_id })))
 }
@@ -188,14 +161,11 @@ pub async fn update_activity(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path(activity_id): Path<Uuid>,
-    Json(req): Json<UpdateActivityRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(req): ValidJson<UpdateActivityRequest>,
+) -> Result<Json<Value>, ApiError> {
     // Admin only
     if !matches!(user.role, UserRole::AdminGarderie) {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(json!({ "error": "Only admin can update activities" })),
-        ));
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "Only admin can update activities"));
     }
 
     let schema = schema_name(&tenant);
@@ -205,16 +175,10 @@ pub async fn update_activity(
     )
     .bind(activity_id)
     .fetch_one(&state.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })?;
+    .await?;
 
     if !activity_exists {
-        return Err((StatusCode::NOT_FOUND, Json(json!({ "error": "Activity not found" }))));
+        return Err(ApiError::from_key(StatusCode::NOT_FOUND, "activity_not_found"));
     }
 
     // Update each field individually if provided
@@ -223,13 +187,7 @@ pub async fn update_activity(
             .bind(title)
             .bind(activity_id)
             .execute(&state.db)
-            .await
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({ "error": e.to_string() })),
-                )
-            })?;
+            .await?;
     }
 
     if let Some(description) = req.description.as_ref() {
@@ -237,29 +195,17 @@ pub async fn update_activity(
             .bind(description)
             .bind(activity_id)
             .execute(&state.db)
-            .await
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({ "error": e.to_string() })),
-                )
-            })?;
+            .await?;
     }
 
     if let Some(date_str) = &req.date {
         let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
-            .map_err(|_| (StatusCode::BAD_REQUEST, Json(json!({ "error": "Invalid date format" }))))?;
+            .map_err(|_| ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_date"))?;
         sqlx::query(&format!("UPDATE {}.activities SET date = $1, updated_at = NOW() WHERE id = $2", schema))
             .bind(date)
             .bind(activity_id)
             .execute(&state.db)
-            .await
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({ "error": e.to_string() })),
-                )
-            })?;
+            .await?;
     }
 
     if let Some(capacity) = req.capacity {
@@ -267,29 +213,17 @@ pub async fn update_activity(
             .bind(capacity)
             .bind(activity_id)
             .execute(&state.db)
-            .await
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({ "error": e.to_string() })),
-                )
-            })?;
+            .await?;
     }
 
     if let Some(end_date_str) = &req.end_date {
         let end_date = NaiveDate::parse_from_str(end_date_str, "%Y-%m-%d")
-            .map_err(|_| (StatusCode::BAD_REQUEST, Json(json!({ "error": "Invalid end_date format" }))))?;
+            .map_err(|_| ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_date"))?;
         sqlx::query(&format!("UPDATE {}.activities SET end_date = $1, updated_at = NOW() WHERE id = $2", schema))
             .bind(end_date)
             .bind(activity_id)
             .execute(&state.db)
-            .await
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({ "error": e.to_string() })),
-                )
-            })?;
+            .await?;
     }
 
     if let Some(group_id) = req.group_id {
@@ -297,13 +231,7 @@ pub async fn update_activity(
             .bind(group_id)
             .bind(activity_id)
             .execute(&state.db)
-            .await
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({ "error": e.to_string() })),
-                )
-            })?;
+            .await?;
     }
 
     if let Some(activity_type) = &req.activity_type {
@@ -311,13 +239,15 @@ pub async fn update_activity(
             .bind(activity_type)
             .bind(activity_id)
             .execute(&state.db)
-            .await
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({ "error": e.to_string() })),
-                )
-            })?;
+            .await?;
+    }
+
+    if let Some(fee_cents) = req.fee_cents {
+        sqlx::query(&format!("UPDATE {}.activities SET fee_cents = $1, updated_at = NOW() WHERE id = $2", schema))
+            .bind(fee_cents)
+            .bind(activity_id)
+            .execute(&state.db)
+            .await?;
     }
 
     Ok(Json(json!({ "success": true })))
@@ -331,13 +261,10 @@ pub async fn delete_activity(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path(activity_id): Path<Uuid>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     // Admin only
     if !matches!(user.role, UserRole::AdminGarderie) {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(json!({ "error": "Only admin can delete activities" })),
-        ));
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "Only admin can delete activities"));
     }
 
     let schema = schema_name(&tenant);
@@ -345,16 +272,10 @@ pub async fn delete_activity(
     let result = sqlx::query(&format!("DELETE FROM {}.activities WHERE id = $1", schema))
         .bind(activity_id)
         .execute(&state.db)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })?;
+        .await?;
 
     if result.rows_affected() == 0 {
-        return Err((StatusCode::NOT_FOUND, Json(json!({ "error": "Activity not found" }))));
+        return Err(ApiError::from_key(StatusCode::NOT_FOUND, "activity_not_found"));
     }
 
     Ok(Json(json!({ "success": true })))
@@ -368,8 +289,8 @@ pub async fn register_child(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path(activity_id): Path<Uuid>,
-    Json(req): Json<RegisterRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(req): ValidJson<RegisterRequest>,
+) -> Result<Json<Value>, ApiError> {
     let schema = schema_name(&tenant);
 
     // Check access: parent can only register own children
@@ -383,19 +304,10 @@ pub async fn register_child(
         .bind(req.child_id)
         .bind(user.user_id)
         .fetch_one(&state.db)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })?;
+        .await?;
 
         if !is_linked {
-            return Err((
-                StatusCode::FORBIDDEN,
-                Json(json!({ "error": "Child not linked to parent" })),
-            ));
+            return Err(ApiError::from_key(StatusCode::FORBIDDEN, "child_not_linked"));
         }
     }
 
@@ -405,27 +317,15 @@ pub async fn register_child(
     )
     .bind(activity_id)
     .fetch_optional(&state.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })?;
+    .await?;
 
     let (capacity, activity_type) = activity.ok_or_else(|| {
-        (
-            StatusCode::NOT_FOUND,
-            Json(json!({ "error": "Activity not found" })),
-        )
+        ApiError::from_key(StatusCode::NOT_FOUND, "activity_not_found")
     })?;
 
     // Cannot register for theme activities
     if activity_type == "theme" {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": "Cannot register for theme activities" })),
-        ));
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Cannot register for theme activities"));
     }
 
     // If capacity is limited, check current registration count
@@ -438,19 +338,10 @@ pub async fn register_child(
         )
         .bind(activity_id)
         .fetch_one(&state.db)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })?;
+        .await?;
 
         if current_count >= cap as i64 {
-            return Err((
-                StatusCode::CONFLICT,
-                Json(json!({ "error": "Activity is at capacity" })),
-            ));
+            return Err(ApiError::conflict("Activity is at capacity"));
         }
     }
 
@@ -469,13 +360,7 @@ pub async fn register_child(
     .bind(req.child_id)
     .bind(user.user_id)
     .execute(&state.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })?;
+    .await?;
 
     Ok(Json(json!({ "id": registration_id })))
 }
@@ -488,7 +373,7 @@ pub async fn unregister_child(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path((activity_id, child_id)): Path<(Uuid, Uuid)>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     let schema = schema_name(&tenant);
 
     // Check access: parent can only unregister own children
@@ -502,19 +387,10 @@ pub async fn unregister_child(
         .bind(child_id)
         .bind(user.user_id)
         .fetch_one(&state.db)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })?;
+        .await?;
 
         if !is_linked {
-            return Err((
-                StatusCode::FORBIDDEN,
-                Json(json!({ "error": "Child not linked to parent" })),
-            ));
+            return Err(ApiError::from_key(StatusCode::FORBIDDEN, "child_not_linked"));
         }
     }
 
@@ -527,19 +403,10 @@ pub async fn unregister_child(
     .bind(activity_id)
     .bind(child_id)
     .execute(&state.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })?;
+    .await?;
 
     if result.rows_affected() == 0 {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(json!({ "error": "Registration not found" })),
-        ));
+        return Err(ApiError::not_found("Registration not found"));
     }
 
     Ok(Json(json!({ "success": true })))
@@ -553,13 +420,10 @@ pub async fn get_activity_registrations(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path(activity_id): Path<Uuid>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     // Admin and educateurs only
     if !matches!(user.role, UserRole::AdminGarderie | UserRole::Educateur) {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(json!({ "error": "Only admin and educateurs can view registrations" })),
-        ));
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "Only admin and educateurs can view registrations"));
     }
 
     let schema = schema_name(&tenant);
@@ -576,13 +440,7 @@ pub async fn get_activity_registrations(
     )
     .bind(activity_id)
     .fetch_all(&state.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })?;
+    .await?;
 
     Ok(Json(json!({ "registrations": registrations })))
 }
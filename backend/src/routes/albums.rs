@@ -0,0 +1,280 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    db::{tenant::schema_name, tenant_db::TenantDb},
+    error::ApiError,
+    middleware::tenant::TenantSlug,
+    models::{
+        album::{Album, AlbumInput, AlbumMediaRequest, ShareAlbumRequest},
+        auth::AuthenticatedUser,
+        media::MediaQuery,
+        user::UserRole,
+    },
+    services::{
+        albums::{AlbumService, InvalidAlbum},
+        branding::BrandingService,
+        groups::GroupService,
+        media::MediaService,
+        media_urls::MediaUrlSigner,
+    },
+    AppState,
+};
+use crate::middleware::validation::ValidJson;
+
+fn require_staff(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::Parent => Err(ApiError::forbidden()),
+        _ => Ok(()),
+    }
+}
+
+fn not_found() -> ApiError {
+    ApiError::not_found("Album introuvable")
+}
+
+/// The album if the user may see it; 404 otherwise, so hidden albums are not revealed.
+async fn load_album(
+    state: &AppState,
+    tenant: &str,
+    user: &AuthenticatedUser,
+    id: Uuid,
+) -> Result<(Album, Option<Vec<Uuid>>), ApiError> {
+    let scope = GroupService::educator_scope(&state.db, tenant, user)
+        .await?;
+    let is_staff = user.role != UserRole::Parent;
+    let album = AlbumService::get(&state.db, tenant, id, user.user_id, is_staff, scope.as_deref())
+        .await?
+        .ok_or_else(not_found)?;
+    Ok((album, scope))
+}
+
+/// Scoped educators may only share albums with their own groups and children.
+async fn ensure_scope(
+    state: &AppState,
+    tenant: &str,
+    user: &AuthenticatedUser,
+    body: &AlbumInput,
+) -> Result<(), ApiError> {
+    match body.visibility.as_deref() {
+        Some("group") => match body.group_id {
+            Some(group_id) => GroupService::ensure_group_access(&state.db, tenant, user, group_id).await,
+            None => Ok(()),
+        },
+        Some("child") => match body.child_id {
+            Some(child_id) => GroupService::ensure_child_access(&state.db, tenant, user, child_id).await,
+            None => Ok(()),
+        },
+        _ => Ok(()),
+    }
+    .map_err(ApiError::from)
+}
+
+/// GET /albums — parents only see albums shared with them
+pub async fn list_albums(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    let scope = GroupService::educator_scope(&state.db, &tenant, &user)
+        .await?;
+    let is_staff = user.role != UserRole::Parent;
+    let signer = MediaUrlSigner::from_config(&state.config)?;
+    AlbumService::list(&state.db, &tenant, user.user_id, is_staff, scope.as_deref())
+        .await
+        .map(|mut items| {
+            for album in &mut items {
+                album.cover_thumbnail_url = album.cover_thumbnail_path.as_deref().map(|p| signer.sign(p));
+            }
+            Json(serde_json::to_value(items).unwrap())
+        })
+        .map_err(ApiError::from)
+}
+
+/// GET /albums/{id} — the album with the media of it the user may see
+pub async fn get_album(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    let (album, scope) = load_album(&state, &tenant, &user, id).await?;
+    let query = MediaQuery {
+        group_id: None,
+        child_ids: None,
+        page: None,
+        per_page: Some(200),
+        period: None,
+        date: None,
+        tags: None,
+        album_id: Some(id),
+        cursor: None,
+        favorites: None,
+        hidden: None,
+    };
+    let is_staff = user.role != UserRole::Parent;
+    let signer = MediaUrlSigner::from_config(&state.config)?;
+    let mut redis = state.redis.clone();
+    let mut media = MediaService::list(&state.db, &mut redis, &tenant, user.user_id, is_staff, scope.as_deref(), &query)
+        .await?
+        .items;
+    media.iter_mut().for_each(|m| signer.sign_media(m));
+    Ok(Json(json!({ "album": album, "media": media })))
+}
+
+/// POST /albums — staff only
+pub async fn create_album(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<AlbumInput>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_staff(&user)?;
+    ensure_scope(&state, &tenant, &user, &body).await?;
+    AlbumService::create(&state.db, &tenant, &body, user.user_id)
+        .await
+        .map(|a| (StatusCode::CREATED, Json(serde_json::to_value(a).unwrap())))
+        .map_err(ApiError::from)
+}
+
+/// PUT /albums/{id} — staff only
+pub async fn update_album(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidJson(body): ValidJson<AlbumInput>,
+) -> Result<Json<Value>, ApiError> {
+    require_staff(&user)?;
+    load_album(&state, &tenant, &user, id).await?;
+    ensure_scope(&state, &tenant, &user, &body).await?;
+    AlbumService::update(&state.db, &tenant, id, &body)
+        .await?
+        .map(|a| Json(serde_json::to_value(a).unwrap()))
+        .ok_or_else(not_found)
+}
+
+/// DELETE /albums/{id} — staff only; the media stay in the gallery
+pub async fn delete_album(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    require_staff(&user)?;
+    load_album(&state, &tenant, &user, id).await?;
+    match AlbumService::delete(&state.db, &tenant, id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(not_found()),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+/// POST /albums/{id}/media — staff only
+pub async fn add_album_media(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidJson(body): ValidJson<AlbumMediaRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_staff(&user)?;
+    load_album(&state, &tenant, &user, id).await?;
+    AlbumService::add_media(&state.db, &tenant, id, &body.media_ids)
+        .await
+        .map(|added| Json(json!({ "added": added })))
+        .map_err(ApiError::from)
+}
+
+/// DELETE /albums/{id}/media/{media_id} — staff only; the media itself is kept
+pub async fn remove_album_media(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path((id, media_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    require_staff(&user)?;
+    load_album(&state, &tenant, &user, id).await?;
+    match AlbumService::remove_media(&state.db, &tenant, id, media_id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(ApiError::not_found("Média absent de l'album")),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+/// POST /albums/{id}/share — staff only; emails the parents the album is shared with
+pub async fn share_album(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidJson(body): ValidJson<ShareAlbumRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_staff(&user)?;
+    let (album, _) = load_album(&state, &tenant, &user, id).await?;
+    if album.visibility == "private" {
+        return Err(
+            InvalidAlbum("Cet album est privé : choisissez avec qui le partager avant de l'envoyer".into()).into(),
+        );
+    }
+
+    let recipients = AlbumService::share_recipients(&state.db, &tenant, &album)
+        .await?;
+    let count = recipients.len();
+
+    if tenant != "demo" {
+        if let Some(email_svc) = state.email.clone() {
+            let pool = state.db.clone();
+            let base = state.config.app_base_url.clone();
+            let sharer_id = user.user_id;
+
+            tokio::spawn(TenantDb::scope(tenant.clone(), async move {
+                let s = schema_name(&tenant);
+                let (garderie_name, branding) = BrandingService::for_email(&pool, &tenant).await;
+
+                let sharer_name: String = sqlx::query_scalar(&format!(
+                    "SELECT CONCAT(first_name, ' ', last_name) FROM {s}.users WHERE id = $1"
+                ))
+                .bind(sharer_id)
+                .fetch_optional(&pool)
+                .await
+                .unwrap_or_default()
+                .unwrap_or_else(|| "Un éducateur".to_string());
+
+                let app_url = if let Some(idx) = base.find("://") {
+                    let scheme = &base[..idx];
+                    let domain = &base[idx + 3..];
+                    format!("{scheme}://{tenant}.{domain}/fr/parent/media?album={}", album.id)
+                } else {
+                    format!("https://{tenant}.{base}/fr/parent/media?album={}", album.id)
+                };
+
+                for (email, name) in recipients {
+                    if let Err(e) = email_svc
+                        .send_album_shared(
+                            &tenant,
+                            &email,
+                            &name,
+                            &sharer_name,
+                            &album.title,
+                            body.message.as_deref(),
+                            &app_url,
+                            &garderie_name,
+                            &branding,
+                        )
+                        .await
+                    {
+                        tracing::warn!("Album share email to {email} failed: {e}");
+                    }
+                }
+            }));
+        }
+    }
+
+    Ok(Json(json!({ "recipients": count })))
+}
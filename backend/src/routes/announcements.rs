@@ -1,15 +1,16 @@
 use axum::{
     extract::State,
-    http::StatusCode,
     Json,
 };
 use serde_json::{json, Value};
 
 use crate::{
+    error::ApiError,
     middleware::super_admin::SuperAdminAuth,
     models::announcement::{Announcement, SetAnnouncementRequest},
     AppState,
 };
+use crate::middleware::validation::ValidJson;
 
 /// GET /announcement — public endpoint, returns the active announcement or null.
 pub async fn get_announcement(
@@ -32,15 +33,14 @@ pub async fn get_announcement(
 pub async fn set_announcement(
     State(state): State<AppState>,
     _auth: SuperAdminAuth,
-    Json(body): Json<SetAnnouncementRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<SetAnnouncementRequest>,
+) -> Result<Json<Value>, ApiError> {
     let color = body.color.as_deref().unwrap_or("yellow");
 
     // Deactivate any existing announcements, then insert the new one
     sqlx::query("UPDATE announcements SET is_active = FALSE WHERE is_active = TRUE")
         .execute(&state.db)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+        .await?;
 
     let announcement = sqlx::query_as::<_, Announcement>(
         "INSERT INTO announcements (message, color, is_active) VALUES ($1, $2, TRUE) RETURNING *",
@@ -48,8 +48,7 @@ pub async fn set_announcement(
     .bind(&body.message)
     .bind(color)
     .fetch_one(&state.db)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+    .await?;
 
     Ok(Json(serde_json::to_value(announcement).unwrap()))
 }
@@ -58,11 +57,10 @@ pub async fn set_announcement(
 pub async fn delete_announcement(
     State(state): State<AppState>,
     _auth: SuperAdminAuth,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     sqlx::query("UPDATE announcements SET is_active = FALSE WHERE is_active = TRUE")
         .execute(&state.db)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+        .await?;
 
     Ok(Json(json!({ "ok": true })))
 }
@@ -0,0 +1,33 @@
+use axum::{extract::State, Json};
+use serde_json::{json, Value};
+
+use crate::{
+    error::ApiError,
+    middleware::tenant::TenantSlug,
+    models::{auth::AuthenticatedUser, user::UserRole},
+    services::antivirus::AntivirusService,
+    AppState,
+};
+
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
+        _ => Err(ApiError::forbidden()),
+    }
+}
+
+/// GET /quarantine — admin only; uploads the antivirus flagged, and how many await a scan
+pub async fn list_quarantine(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let items = AntivirusService::quarantined(&state.db, &tenant).await?;
+    let pending = AntivirusService::pending_count(&state.db, &tenant).await?;
+    Ok(Json(json!({
+        "items": items,
+        "pending": pending,
+        "scanner_enabled": state.config.clamav_addr.is_some(),
+    })))
+}
@@ -1,5 +1,5 @@
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
@@ -8,15 +8,49 @@ use serde_json::{json, Value};
 use uuid::Uuid;
 
 use crate::{
-    db::tenant::schema_name,
+    db::{tenant::schema_name, tenant_db::TenantDb},
+    error::ApiError,
     middleware::tenant::TenantSlug,
     models::{
-        attendance::{AttendanceMonthAllQuery, AttendanceMonthQuery, AttendanceMonthResponse, BulkSetAttendanceRequest, SetAttendanceRequest},
+        attendance::{
+            AttendanceMonthAllQuery, AttendanceMonthQuery, AttendanceMonthResponse, AutoAbsenceQuery,
+            BulkSetAttendanceRequest, ChildAbsence, DeclareAbsenceRequest, SetAttendanceRequest,
+        },
         auth::AuthenticatedUser,
+        message::WsMessage,
         user::UserRole,
     },
+    services::{
+        absences::AbsenceService,
+        auto_absences::AutoAbsenceService,
+        children::ChildService,
+        groups::GroupService,
+        realtime::RealtimeService,
+    },
     AppState,
 };
+use crate::middleware::validation::ValidJson;
+
+/// Parents may see and declare absences for their own children; educators for the
+/// children of their groups.
+async fn ensure_absence_access(
+    state: &AppState,
+    tenant: &str,
+    user: &AuthenticatedUser,
+    child_id: Uuid,
+) -> Result<(), ApiError> {
+    if let UserRole::Parent = user.role {
+        let linked = ChildService::is_parent_of(&state.db, tenant, child_id, user.user_id)
+            .await?;
+        if !linked {
+            return Err(ApiError::forbidden());
+        }
+        return Ok(());
+    }
+    GroupService::ensure_child_access(&state.db, tenant, user, child_id)
+        .await
+        .map_err(ApiError::from)
+}
 
 /// GET /attendance?child_id=...&month=YYYY-MM
 /// Returns attendance records for a single child in a given month
@@ -26,7 +60,7 @@ pub async fn get_month(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Query(params): Query<AttendanceMonthQuery>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     // Parent check: can only view their own children
     if let UserRole::Parent = user.role {
         let schema = schema_name(&tenant);
@@ -36,51 +70,33 @@ pub async fn get_month(
         .bind(params.child_id)
         .bind(user.user_id)
         .fetch_one(&state.db)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })?;
+        .await?;
 
         if !is_linked {
-            return Err((
-                StatusCode::FORBIDDEN,
-                Json(json!({ "error": "Accès refusé" })),
-            ));
+            return Err(ApiError::forbidden());
         }
     }
 
     // Parse month to get start and end dates
     let month_parts: Vec<&str> = params.month.split('-').collect();
     if month_parts.len() != 2 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": "Invalid month format. Use YYYY-MM" })),
-        ));
+        return Err(ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_month"));
     }
 
     let year = month_parts[0]
         .parse::<i32>()
-        .map_err(|_| (StatusCode::BAD_REQUEST, Json(json!({ "error": "Invalid year" }))))?;
+        .map_err(|_| ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_year"))?;
     let month = month_parts[1]
         .parse::<u32>()
-        .map_err(|_| (StatusCode::BAD_REQUEST, Json(json!({ "error": "Invalid month" }))))?;
+        .map_err(|_| ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_month"))?;
 
     if month < 1 || month > 12 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": "Month must be 1-12" })),
-        ));
+        return Err(ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_month"));
     }
 
     // Construct start and end dates
     let start_date = NaiveDate::from_ymd_opt(year, month, 1).ok_or_else(|| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": "Invalid date" })),
-        )
+        ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_date")
     })?;
 
     let end_date = if month == 12 {
@@ -101,13 +117,7 @@ pub async fn get_month(
     .bind(start_date)
     .bind(end_date)
     .fetch_all(&state.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })?;
+    .await?;
 
     let attendance_map: std::collections::HashMap<String, String> =
         records.into_iter().map(|(date, status)| (date.to_string(), status)).collect();
@@ -122,10 +132,10 @@ pub async fn set_attendance(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-    Json(req): Json<SetAttendanceRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(req): ValidJson<SetAttendanceRequest>,
+) -> Result<Json<Value>, ApiError> {
     let date = NaiveDate::parse_from_str(&req.date, "%Y-%m-%d")
-        .map_err(|_| (StatusCode::BAD_REQUEST, Json(json!({ "error": "Invalid date format" }))))?;
+        .map_err(|_| ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_date"))?;
 
     let schema = schema_name(&tenant);
 
@@ -139,29 +149,20 @@ pub async fn set_attendance(
         "present_hors_contrat",
     ];
     if !valid_statuses.contains(&req.status.as_str()) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": "Invalid status" })),
-        ));
+        return Err(ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_status"));
     }
 
     // Check if parent and apply restrictions
     if let UserRole::Parent = user.role {
         // Parents can only set absent or present
         if !["absent", "present"].contains(&req.status.as_str()) {
-            return Err((
-                StatusCode::FORBIDDEN,
-                Json(json!({ "error": "Parents can only set absent or present status" })),
-            ));
+            return Err(ApiError::from_key(StatusCode::FORBIDDEN, "parent_attendance_status"));
         }
 
         // Parents can only set future dates
         let today = chrono::Local::now().naive_local().date();
         if date < today {
-            return Err((
-                StatusCode::FORBIDDEN,
-                Json(json!({ "error": "Cannot change attendance for past dates" })),
-            ));
+            return Err(ApiError::from_key(StatusCode::FORBIDDEN, "attendance_past_date"));
         }
 
         // Verify child belongs to parent
@@ -174,19 +175,10 @@ pub async fn set_attendance(
         .bind(req.child_id)
         .bind(user.user_id)
         .fetch_one(&state.db)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })?;
+        .await?;
 
         if !is_linked {
-            return Err((
-                StatusCode::FORBIDDEN,
-                Json(json!({ "error": "Child not linked to parent" })),
-            ));
+            return Err(ApiError::from_key(StatusCode::FORBIDDEN, "child_not_linked"));
         }
     }
 
@@ -204,13 +196,7 @@ pub async fn set_attendance(
     .bind(&req.status)
     .bind(user.user_id)
     .execute(&state.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })?;
+    .await?;
 
     // Update journal absent flag if status is absent
     let is_absent = req.status == "absent";
@@ -227,13 +213,11 @@ pub async fn set_attendance(
     .bind(is_absent)
     .bind(user.user_id)
     .execute(&state.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })?;
+    .await?;
+
+    // A late check-in lifts an automatic absence; an absence status confirms it
+    AutoAbsenceService::resolve(&state.db, &tenant, req.child_id, &[date], &req.status, user.user_id)
+        .await?;
 
     Ok(Json(json!({ "success": true })))
 }
@@ -245,14 +229,14 @@ pub async fn set_attendance_bulk(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-    Json(req): Json<BulkSetAttendanceRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(req): ValidJson<BulkSetAttendanceRequest>,
+) -> Result<Json<Value>, ApiError> {
     let schema = schema_name(&tenant);
 
     // Validate status
     let valid_statuses = ["attendu", "present", "absent", "malade", "vacances", "present_hors_contrat"];
     if !valid_statuses.contains(&req.status.as_str()) {
-        return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "Invalid status" }))));
+        return Err(ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_status"));
     }
 
     // Parse and validate all dates first
@@ -260,18 +244,18 @@ pub async fn set_attendance_bulk(
     let mut parsed_dates: Vec<NaiveDate> = Vec::new();
     for date_str in &req.dates {
         let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
-            .map_err(|_| (StatusCode::BAD_REQUEST, Json(json!({ "error": format!("Invalid date: {date_str}") }))))?;
+            .map_err(|_| ApiError::new(StatusCode::BAD_REQUEST, format!("Invalid date: {date_str}")))?;
         parsed_dates.push(date);
     }
 
     // Parent restrictions
     if let UserRole::Parent = user.role {
         if !["absent", "present"].contains(&req.status.as_str()) {
-            return Err((StatusCode::FORBIDDEN, Json(json!({ "error": "Parents can only set absent or present status" }))));
+            return Err(ApiError::from_key(StatusCode::FORBIDDEN, "parent_attendance_status"));
         }
         for date in &parsed_dates {
             if *date < today {
-                return Err((StatusCode::FORBIDDEN, Json(json!({ "error": "Cannot change attendance for past dates" }))));
+                return Err(ApiError::from_key(StatusCode::FORBIDDEN, "attendance_past_date"));
             }
         }
         let is_linked = sqlx::query_scalar::<_, bool>(
@@ -280,11 +264,10 @@ pub async fn set_attendance_bulk(
         .bind(req.child_id)
         .bind(user.user_id)
         .fetch_one(&state.db)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+        .await?;
 
         if !is_linked {
-            return Err((StatusCode::FORBIDDEN, Json(json!({ "error": "Child not linked to parent" }))));
+            return Err(ApiError::from_key(StatusCode::FORBIDDEN, "child_not_linked"));
         }
     }
 
@@ -301,8 +284,7 @@ pub async fn set_attendance_bulk(
         .bind(&req.status)
         .bind(user.user_id)
         .execute(&state.db)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+        .await?;
 
         sqlx::query(&format!(
             "INSERT INTO {schema}.daily_journals (child_id, date, absent, created_by, created_at, updated_at)
@@ -314,10 +296,12 @@ pub async fn set_attendance_bulk(
         .bind(is_absent)
         .bind(user.user_id)
         .execute(&state.db)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+        .await?;
     }
 
+    AutoAbsenceService::resolve(&state.db, &tenant, req.child_id, &parsed_dates, &req.status, user.user_id)
+        .await?;
+
     Ok(Json(json!({ "success": true, "updated": parsed_dates.len() })))
 }
 
@@ -329,46 +313,34 @@ pub async fn get_month_all_children(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Query(params): Query<AttendanceMonthAllQuery>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     // Only staff can access this
     match user.role {
         UserRole::SuperAdmin | UserRole::AdminGarderie | UserRole::Educateur => {}
         UserRole::Parent => {
-            return Err((
-                StatusCode::FORBIDDEN,
-                Json(json!({ "error": "Only staff can access this endpoint" })),
-            ))
+            return Err(ApiError::from_key(StatusCode::FORBIDDEN, "staff_only"))
         }
     }
 
     // Parse month
     let month_parts: Vec<&str> = params.month.split('-').collect();
     if month_parts.len() != 2 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": "Invalid month format. Use YYYY-MM" })),
-        ));
+        return Err(ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_month"));
     }
 
     let year = month_parts[0]
         .parse::<i32>()
-        .map_err(|_| (StatusCode::BAD_REQUEST, Json(json!({ "error": "Invalid year" }))))?;
+        .map_err(|_| ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_year"))?;
     let month = month_parts[1]
         .parse::<u32>()
-        .map_err(|_| (StatusCode::BAD_REQUEST, Json(json!({ "error": "Invalid month" }))))?;
+        .map_err(|_| ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_month"))?;
 
     if month < 1 || month > 12 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": "Month must be 1-12" })),
-        ));
+        return Err(ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_month"));
     }
 
     let start_date = NaiveDate::from_ymd_opt(year, month, 1).ok_or_else(|| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": "Invalid date" })),
-        )
+        ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_date")
     })?;
 
     let end_date = if month == 12 {
@@ -425,13 +397,7 @@ pub async fn get_month_all_children(
     .bind(start_date)
     .bind(end_date)
     .fetch_all(&state.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })?;
+    .await?;
 
     // Convert tuples to proper response structs
     let attendance: Vec<AttendanceMonthResponse> = records
@@ -443,5 +409,152 @@ pub async fn get_month_all_children(
         })
         .collect();
 
-    Ok(Json(json!({ "attendance": attendance })))
+    let absences = AbsenceService::list_between(&state.db, &tenant, start_date, end_date)
+        .await?;
+
+    Ok(Json(json!({ "attendance": attendance, "absences": absences })))
+}
+
+/// POST /children/{id}/absences
+/// Declare an absence over a date range: every expected day is marked in the
+/// attendance and as absent in the journal, and the group's educators are notified
+/// Access: Parent (own children, from today on) + Staff (any date)
+pub async fn declare_absence(
+    State(mut state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(child_id): Path<Uuid>,
+    ValidJson(req): ValidJson<DeclareAbsenceRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    ensure_absence_access(&state, &tenant, &user, child_id).await?;
+
+    let allow_past = !matches!(user.role, UserRole::Parent);
+    let (absence, days) = AbsenceService::declare(&state.db, &tenant, child_id, user.user_id, &req, allow_past)
+        .await?;
+
+    notify_absence(&mut state, &tenant, &absence).await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({ "absence": absence, "days": days })),
+    ))
+}
+
+/// GET /children/{id}/absences
+/// Access: Parent (own children) + Staff
+pub async fn list_absences(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(child_id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    ensure_absence_access(&state, &tenant, &user, child_id).await?;
+    AbsenceService::list(&state.db, &tenant, child_id)
+        .await
+        .map(|absences| Json(serde_json::to_value(absences).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// GET /auto-absences?date=YYYY-MM-DD
+/// Children marked absent for not being checked in by the cutoff time
+/// Access: Parent (their children's, awaiting confirmation) + Staff (the day's, default today)
+pub async fn list_auto_absences(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(q): Query<AutoAbsenceQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let today = chrono::Local::now().date_naive();
+    let absences = if let UserRole::Parent = user.role {
+        AutoAbsenceService::pending_for_parent(&state.db, &tenant, user.user_id, today).await
+    } else {
+        let date = match q.date.as_deref() {
+            Some(d) => NaiveDate::parse_from_str(d, "%Y-%m-%d")
+                .map_err(|_| ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_date"))?,
+            None => today,
+        };
+        AutoAbsenceService::list_for_date(&state.db, &tenant, date).await
+    };
+    absences
+        .map(|absences| Json(serde_json::to_value(absences).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// POST /auto-absences/{id}/confirm
+/// Confirm the child was away that day: the attendance is marked absent
+/// Access: Parent (own children) + Staff
+pub async fn confirm_auto_absence(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    let absence = AutoAbsenceService::get(&state.db, &tenant, id).await?;
+    ensure_absence_access(&state, &tenant, &user, absence.child_id).await?;
+    AutoAbsenceService::confirm(&state.db, &tenant, id, user.user_id)
+        .await
+        .map(|absence| Json(serde_json::to_value(absence).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// Tell the group's educators (or the admins, for a group without any), on their open
+/// screens (WebSocket) and their devices (push).
+/// The absence is already recorded, so failures are only logged.
+async fn notify_absence(state: &mut AppState, tenant: &str, absence: &ChildAbsence) {
+    let recipients = match AbsenceService::recipients(&state.db, tenant, absence.child_id).await {
+        Ok(r) => r,
+        Err(e) => {
+            tracing::warn!("Absence {} in '{tenant}' not notified: {e}", absence.id);
+            return;
+        }
+    };
+
+    let event = WsMessage {
+        kind: "absence_declared".to_string(),
+        payload: json!({
+            "id": absence.id,
+            "child_id": absence.child_id,
+            "group_id": recipients.group_id,
+            "start_date": absence.start_date,
+            "end_date": absence.end_date,
+            "status": absence.status,
+        }),
+    };
+    let payload = serde_json::to_string(&event).unwrap_or_default();
+    RealtimeService::publish_to_users(&mut state.redis, tenant, &recipients.staff_ids, &payload).await;
+
+    if state.notifications.fcm_api_key.is_none() || tenant == "demo" {
+        return;
+    }
+    let title = format!("Absence : {}", recipients.child_name);
+    let range = if absence.start_date == absence.end_date {
+        format!("le {}", absence.start_date.format("%d/%m"))
+    } else {
+        format!("du {} au {}", absence.start_date.format("%d/%m"), absence.end_date.format("%d/%m"))
+    };
+    let label = match absence.status.as_str() {
+        "malade" => "Malade",
+        "vacances" => "En vacances",
+        _ => "Absent(e)",
+    };
+    let body = match &absence.reason {
+        Some(reason) => format!("{label} {range} — {reason}"),
+        None => format!("{label} {range}"),
+    };
+    // FCM data values must be strings.
+    let data = json!({ "type": "absence", "child_id": absence.child_id.to_string() });
+
+    let pool = state.db.clone();
+    let notifications = state.notifications.clone();
+    let tenant = tenant.to_string();
+    tokio::spawn(TenantDb::scope(tenant.clone(), async move {
+        for user_id in recipients.staff_ids {
+            if let Err(e) = notifications
+                .notify_user(&pool, &tenant, user_id, &title, &body, Some(data.clone()), None)
+                .await
+            {
+                tracing::warn!("Absence push to {user_id} in '{tenant}' failed: {e}");
+            }
+        }
+    }));
 }
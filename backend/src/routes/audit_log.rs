@@ -1,11 +1,12 @@
-use axum::{extract::{Path, Query, State}, http::StatusCode, Json};
+use axum::{extract::{Path, Query, State}, Json};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
 use crate::{
-    db::tenant::schema_name,
+    db::{tenant::schema_name, tenant_db::TenantDb},
+    error::ApiError,
     middleware::{super_admin::SuperAdminAuth, tenant::TenantSlug},
     models::{auth::AuthenticatedUser, user::UserRole},
     AppState,
@@ -55,11 +56,11 @@ pub async fn list_audit_log(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Query(params): Query<AuditQuery>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     // Admin only
     match user.role {
         UserRole::AdminGarderie | UserRole::SuperAdmin => {}
-        _ => return Err((StatusCode::FORBIDDEN, Json(json!({ "error": "Accès refusé" })))),
+        _ => return Err(ApiError::forbidden()),
     }
 
     let schema = schema_name(&tenant);
@@ -79,8 +80,7 @@ pub async fn list_audit_log(
         .bind(limit)
         .bind(offset)
         .fetch_all(&state.db)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+        .await?;
 
         let total: i64 = sqlx::query_scalar(&format!(
             "SELECT COUNT(*) FROM {schema}.audit_log WHERE action LIKE $1"
@@ -101,8 +101,7 @@ pub async fn list_audit_log(
         .bind(limit)
         .bind(offset)
         .fetch_all(&state.db)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+        .await?;
 
         let total: i64 = sqlx::query_scalar(&format!(
             "SELECT COUNT(*) FROM {schema}.audit_log"
@@ -129,7 +128,7 @@ pub async fn super_admin_audit_log(
     _auth: SuperAdminAuth,
     Path(slug): Path<String>,
     Query(params): Query<AuditQuery>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     let schema = schema_name(&slug);
     let limit  = params.limit.unwrap_or(100).min(500);
     let page   = params.page.unwrap_or(1).max(1);
@@ -147,8 +146,7 @@ pub async fn super_admin_audit_log(
         .bind(limit)
         .bind(offset)
         .fetch_all(&state.db)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+        .await?;
 
         let total: i64 = sqlx::query_scalar(&format!(
             "SELECT COUNT(*) FROM {schema}.audit_log WHERE action LIKE $1"
@@ -169,8 +167,7 @@ pub async fn super_admin_audit_log(
         .bind(limit)
         .bind(offset)
         .fetch_all(&state.db)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+        .await?;
 
         let total: i64 = sqlx::query_scalar(&format!(
             "SELECT COUNT(*) FROM {schema}.audit_log"
@@ -197,7 +194,7 @@ pub async fn super_admin_global_audit_log(
     State(state): State<AppState>,
     _auth: SuperAdminAuth,
     Query(params): Query<GlobalAuditQuery>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     let limit  = params.limit.unwrap_or(50).min(200);
     let page   = params.page.unwrap_or(1).max(1);
     let offset = (page - 1) * limit;
@@ -207,8 +204,7 @@ pub async fn super_admin_global_audit_log(
         "SELECT slug FROM public.garderies WHERE is_active = TRUE ORDER BY slug"
     )
     .fetch_all(&state.db)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+    .await?;
 
     if slugs.is_empty() {
         return Ok(Json(json!({
@@ -235,6 +231,12 @@ pub async fn super_admin_global_audit_log(
         slugs.iter().map(|(s,)| s.clone()).collect()
     };
 
+    // A shared schema only shows the rows of the garderie in scope, one at a time
+    if filtered_slugs.len() > 1 {
+        TenantDb::require_own_schema()?;
+    }
+    let scope = filtered_slugs[0].clone();
+
     // Build UNION ALL query dynamically
     let mut union_queries = Vec::new();
     for slug in &filtered_slugs {
@@ -266,8 +268,7 @@ pub async fn super_admin_global_audit_log(
 
     // Get total count
     let total_sql = format!("SELECT COUNT(*) as count FROM ({}) as combined", union_sql);
-    let total: i64 = sqlx::query_scalar(&total_sql)
-        .fetch_one(&state.db)
+    let total: i64 = TenantDb::scope(scope.clone(), sqlx::query_scalar(&total_sql).fetch_one(&state.db))
         .await
         .unwrap_or(0);
 
@@ -276,10 +277,8 @@ pub async fn super_admin_global_audit_log(
         "{} ORDER BY created_at DESC LIMIT {} OFFSET {}",
         union_sql, limit, offset
     );
-    let entries: Vec<GlobalAuditLogRow> = sqlx::query_as(&entries_sql)
-        .fetch_all(&state.db)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+    let entries: Vec<GlobalAuditLogRow> = TenantDb::scope(scope, sqlx::query_as(&entries_sql).fetch_all(&state.db))
+        .await?;
 
     Ok(Json(json!({
         "entries": entries,
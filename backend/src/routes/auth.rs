@@ -1,31 +1,45 @@
 use axum::{
     body::Body,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{header, HeaderMap, StatusCode},
     response::Response,
     Json,
 };
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use uuid::Uuid;
 use serde_json::{json, Value};
 
 use crate::{
+    error::ApiError,
     middleware::{rate_limit::check_rate_limit, tenant::TenantSlug},
     models::{
-        auth::AuthenticatedUser,
+        auth::{AuthenticatedUser, ClientInfo, LoginHistoryQuery},
+        consent::{ConsentRecord, CURRENT_POLICY_VERSION},
         user::{
-            ChangePasswordRequest, ForgotPasswordRequest, InviteUserRequest, LoginRequest,
-            RefreshTokenRequest, RegisterFromInviteRequest, RegisterPushTokenRequest,
-            ResetPasswordRequest, UpdateEmailRequest, VerifyTwoFactorRequest,
+            BulkInviteRequest, BulkInviteResult, ChangePasswordRequest, ExtendInvitationRequest,
+            ForgotPasswordRequest, InviteUserRequest, LoginRequest, ParentConsentPayload, RefreshTokenRequest,
+            RegisterFromInviteRequest, RegisterPushTokenRequest, UnregisterPushTokenRequest, ResetPasswordRequest,
+            SwitchTenantRequest, UpdateEmailRequest, UpdatePhoneRequest, UserRole, VerifyTwoFactorRequest,
         },
     },
-    services::{auth::{AuthService, LoginOutcome}, notifications::NotificationService},
+    services::{
+        auth::{AuthService, InvitationError, LoginOutcome, RefreshTokenReused},
+        consents::ConsentService,
+        identities::IdentityService,
+        notifications::NotificationService,
+    },
     AppState,
 };
+use validator::Validate;
+use crate::middleware::validation::ValidJson;
+
+/// Largest list accepted by the bulk invitation endpoint.
+const MAX_BULK_INVITATIONS: usize = 100;
+
+const MAX_INVITATION_EXTENSION_DAYS: i32 = 30;
 
 /// Extract the real client IP from nginx-forwarded headers.
-fn real_client_ip(headers: &HeaderMap) -> String {
+pub(crate) fn real_client_ip(headers: &HeaderMap) -> String {
     if let Some(ip) = headers.get("x-real-ip").and_then(|v| v.to_str().ok()) {
         return ip.to_string();
     }
@@ -37,6 +51,17 @@ fn real_client_ip(headers: &HeaderMap) -> String {
     "unknown".to_string()
 }
 
+/// User agent and IP recorded on the session a login or refresh creates.
+pub(crate) fn client_info(headers: &HeaderMap) -> ClientInfo {
+    ClientInfo {
+        user_agent: headers
+            .get(header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .map(|ua| ua.chars().take(512).collect()),
+        ip_address: real_client_ip(headers),
+    }
+}
+
 /// Extract a named cookie value from request headers.
 fn get_cookie(headers: &HeaderMap, name: &str) -> Option<String> {
     let prefix = format!("{name}=");
@@ -74,8 +99,8 @@ pub async fn login(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     headers: HeaderMap,
-    Json(body): Json<LoginRequest>,
-) -> Result<Response, (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<LoginRequest>,
+) -> Result<Response, ApiError> {
     // Rate limit: 5 attempts per 15 min per email+tenant
     let rate_key = format!("rate:login:{}:{}", tenant, body.email.to_lowercase());
     let mut redis = state.redis.clone();
@@ -86,11 +111,14 @@ pub async fn login(
     match AuthService::login(
         &state.db,
         state.email.as_deref(),
+        state.sms.as_deref(),
         &tenant,
         &body.email,
         &body.password,
+        body.two_factor_channel.as_deref(),
         device_token.as_deref(),
-        &state.config.jwt_secret,
+        &client_info(&headers),
+        &state.jwt_keys,
         &state.config.jwt_refresh_secret,
         state.config.jwt_expiry_seconds,
         state.config.jwt_refresh_expiry_days,
@@ -128,7 +156,7 @@ pub async fn login(
                 resource_label: Some(body.email.clone()),
                 ip_address:     real_client_ip(&headers),
             });
-            Err((StatusCode::UNAUTHORIZED, Json(json!({ "error": e.to_string() }))))
+            Err(e.into())
         }
     }
 }
@@ -136,8 +164,9 @@ pub async fn login(
 pub async fn verify_2fa(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
-    Json(body): Json<VerifyTwoFactorRequest>,
-) -> Result<Response, (StatusCode, Json<Value>)> {
+    headers: HeaderMap,
+    ValidJson(body): ValidJson<VerifyTwoFactorRequest>,
+) -> Result<Response, ApiError> {
     // Rate limit: 10 attempts per 15 min per email+tenant
     let rate_key = format!("rate:2fa:{}:{}", tenant, body.email.to_lowercase());
     let mut redis = state.redis.clone();
@@ -145,10 +174,12 @@ pub async fn verify_2fa(
 
     AuthService::verify_2fa(
         &state.db,
+        state.email.as_deref(),
         &tenant,
         &body.email,
         &body.code,
-        &state.config.jwt_secret,
+        &client_info(&headers),
+        &state.jwt_keys,
         &state.config.jwt_refresh_secret,
         state.config.jwt_expiry_seconds,
         state.config.jwt_refresh_expiry_days,
@@ -161,23 +192,23 @@ pub async fn verify_2fa(
     })
     .map_err(|e| {
         crate::services::metrics::LOGINS_COUNTER.with_label_values(&[&tenant, "failed"]).inc();
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(json!({ "error": e.to_string() })),
-        )
+        ApiError::from(e)
     })
 }
 
 pub async fn refresh_token(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
-    Json(body): Json<RefreshTokenRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    headers: HeaderMap,
+    ValidJson(body): ValidJson<RefreshTokenRequest>,
+) -> Result<Json<Value>, ApiError> {
     AuthService::refresh(
         &state.db,
+        state.email.as_deref(),
         &tenant,
         &body.refresh_token,
-        &state.config.jwt_secret,
+        &client_info(&headers),
+        &state.jwt_keys,
         &state.config.jwt_refresh_secret,
         state.config.jwt_expiry_seconds,
         state.config.jwt_refresh_expiry_days,
@@ -185,19 +216,77 @@ pub async fn refresh_token(
     .await
     .map(|res| Json(serde_json::to_value(res).unwrap()))
     .map_err(|e| {
-        (
-            StatusCode::UNAUTHORIZED,
-            Json(json!({ "error": e.to_string() })),
-        )
+        if let Some(reuse) = e.downcast_ref::<RefreshTokenReused>() {
+            crate::services::audit::log(state.db.clone(), &tenant, crate::services::audit::AuditEntry {
+                user_id:        Some(reuse.user_id),
+                user_name:      None,
+                action:         "auth.refresh_token_reuse".to_string(),
+                resource_type:  Some("session".to_string()),
+                resource_id:    Some(reuse.family_id.to_string()),
+                resource_label: Some(format!("{} jeton(s) révoqué(s)", reuse.revoked)),
+                ip_address:     real_client_ip(&headers),
+            });
+        }
+        ApiError::from(e)
     })
 }
 
+/// GET /auth/tenants — garderies the caller's identity has an account in, for the
+/// garderie switcher; empty until the account is linked.
+pub async fn list_linked_tenants(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    let Some(identity_id) = user.identity_id else {
+        return Ok(Json(json!([])));
+    };
+    IdentityService::accounts(&state.db, identity_id, &user.tenant)
+        .await
+        .map(|accounts| Json(serde_json::to_value(accounts).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// POST /auth/switch-tenant — tokens for the caller's account in another linked garderie
+pub async fn switch_tenant(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    headers: HeaderMap,
+    ValidJson(body): ValidJson<SwitchTenantRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let Some(identity_id) = user.identity_id else {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "Ce compte n'est lié à aucune autre garderie"));
+    };
+    let target = body.tenant.to_lowercase();
+    let response = AuthService::switch_tenant(
+        &state.db,
+        identity_id,
+        &target,
+        &client_info(&headers),
+        &state.jwt_keys,
+        &state.config.jwt_refresh_secret,
+        state.config.jwt_expiry_seconds,
+        state.config.jwt_refresh_expiry_days,
+    )
+    .await?;
+
+    crate::services::audit::log(state.db.clone(), &target, crate::services::audit::AuditEntry {
+        user_id:        Some(response.user.id),
+        user_name:      Some(response.user.email.clone()),
+        action:         "auth.tenant_switch".to_string(),
+        resource_type:  None,
+        resource_id:    None,
+        resource_label: Some(user.tenant.clone()),
+        ip_address:     real_client_ip(&headers),
+    });
+    Ok(Json(serde_json::to_value(response).unwrap()))
+}
+
 pub async fn logout(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     headers: HeaderMap,
-    Json(body): Json<RefreshTokenRequest>,
-) -> Result<Response, (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<RefreshTokenRequest>,
+) -> Result<Response, ApiError> {
     let device_token = get_cookie(&headers, "tdt");
 
     AuthService::logout(
@@ -217,20 +306,106 @@ pub async fn logout(
             .body(Body::from(r#"{"message":"Logged out"}"#))
             .unwrap()
     })
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })
+    .map_err(ApiError::from)
+}
+
+/// GET /auth/sessions — the user's active sessions and trusted devices.
+pub async fn list_sessions(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    headers: HeaderMap,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    let current_device = get_cookie(&headers, "tdt").as_deref().and_then(AuthService::device_token_id);
+
+    AuthService::list_sessions(&state.db, &tenant, user.user_id, user.session_id, current_device)
+        .await
+        .map(|sessions| Json(serde_json::to_value(sessions).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// GET /.well-known/jwks.json — public keys verifying access tokens signed with RS256 or
+/// EdDSA; empty while tokens are signed with a shared secret.
+pub async fn jwks(State(state): State<AppState>) -> Json<Value> {
+    Json(state.jwt_keys.jwks())
+}
+
+/// GET /auth/login-history — the user's latest login attempts, successful or not.
+pub async fn login_history(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(q): Query<LoginHistoryQuery>,
+) -> Result<Json<Value>, ApiError> {
+    AuthService::login_history(&state.db, &tenant, user.user_id, q.limit)
+        .await
+        .map(|events| Json(serde_json::to_value(events).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// DELETE /auth/sessions/:id — revoke a session or forget a trusted device.
+pub async fn revoke_session(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    headers: HeaderMap,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    match AuthService::revoke_session(&state.db, &tenant, user.user_id, id).await {
+        Ok(true) => {
+            crate::services::audit::log(state.db.clone(), &tenant, crate::services::audit::AuditEntry {
+                user_id:        Some(user.user_id),
+                user_name:      None,
+                action:         "auth.session_revoke".to_string(),
+                resource_type:  Some("session".to_string()),
+                resource_id:    Some(id.to_string()),
+                resource_label: None,
+                ip_address:     real_client_ip(&headers),
+            });
+            Ok(Json(json!({ "message": "Session révoquée" })))
+        }
+        Ok(false) => Err(ApiError::from_key(StatusCode::NOT_FOUND, "session_not_found")),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+/// POST /auth/sessions/revoke-others — sign out everywhere except this session and device.
+pub async fn revoke_other_sessions(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    headers: HeaderMap,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    let current_device = get_cookie(&headers, "tdt").as_deref().and_then(AuthService::device_token_id);
+
+    let revoked = AuthService::revoke_other_sessions(
+        &state.db,
+        &tenant,
+        user.user_id,
+        user.session_id,
+        current_device,
+    )
+    .await?;
+
+    crate::services::audit::log(state.db.clone(), &tenant, crate::services::audit::AuditEntry {
+        user_id:        Some(user.user_id),
+        user_name:      None,
+        action:         "auth.session_revoke_others".to_string(),
+        resource_type:  Some("session".to_string()),
+        resource_id:    None,
+        resource_label: Some(format!("{revoked} sessions")),
+        ip_address:     real_client_ip(&headers),
+    });
+
+    Ok(Json(json!({ "revoked": revoked })))
 }
 
 pub async fn invite_user(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-    Json(body): Json<InviteUserRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<InviteUserRequest>,
+) -> Result<Json<Value>, ApiError> {
     AuthService::create_invitation(
         &state.db,
         state.email.as_deref(),
@@ -238,6 +413,7 @@ pub async fn invite_user(
         &body.email,
         body.role,
         Some(user.user_id),
+        body.preferred_locale.as_deref(),
         &state.config.app_base_url,
     )
     .await
@@ -245,19 +421,14 @@ pub async fn invite_user(
         crate::services::metrics::INVITATIONS_COUNTER.with_label_values(&[&tenant]).inc();
         Json(json!({ "message": format!("Invitation envoyée à {}", body.email) }))
     })
-    .map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })
+    .map_err(ApiError::from)
 }
 
 pub async fn validate_invitation_token(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     Path(token): Path<String>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     use crate::db::tenant::schema_name;
     use crate::models::user::InvitationToken;
 
@@ -272,24 +443,15 @@ pub async fn validate_invitation_token(
     {
         Ok(Some(invite)) => {
             if invite.expires_at < chrono::Utc::now() {
-                return Err((
-                    StatusCode::BAD_REQUEST,
-                    Json(json!({ "error": "Invitation token expired" })),
-                ));
+                return Err(ApiError::new(StatusCode::BAD_REQUEST, "Invitation token expired"));
             }
             Ok(Json(json!({
                 "email": invite.email,
                 "role": invite.role,
             })))
         }
-        Ok(None) => Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": "Invalid or already-used invitation token" })),
-        )),
-        Err(_) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": "Failed to validate token" })),
-        )),
+        Ok(None) => Err(ApiError::new(StatusCode::BAD_REQUEST, "Invalid or already-used invitation token")),
+        Err(e) => Err(e.into()),
     }
 }
 
@@ -297,8 +459,8 @@ pub async fn register_from_invite(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     headers: HeaderMap,
-    Json(body): Json<RegisterFromInviteRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<RegisterFromInviteRequest>,
+) -> Result<Json<Value>, ApiError> {
     let ip = real_client_ip(&headers);
 
     AuthService::register_from_invite(
@@ -311,49 +473,39 @@ pub async fn register_from_invite(
         body.preferred_locale.as_deref().unwrap_or("fr"),
         body.consent.as_ref(),
         &ip,
+        &state.password_policy,
     )
     .await
     .map(|profile| Json(serde_json::to_value(profile).unwrap()))
-    .map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })
+    .map_err(ApiError::from)
 }
 
 pub async fn me(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     use crate::{db::tenant::schema_name, models::user::User};
     let schema = schema_name(&tenant);
     sqlx::query_as::<_, User>(&format!(
         "SELECT id, email, password_hash, first_name, last_name,
-            role::TEXT as role, avatar_url, is_active, force_password_change, preferred_locale,
+            role::TEXT as role, avatar_url, is_active, force_password_change, preferred_locale, phone,
             created_at, updated_at
          FROM {schema}.users WHERE id = $1"
     ))
     .bind(user.user_id)
     .fetch_optional(&state.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })?
+    .await?
     .map(|u| Json(serde_json::to_value(crate::models::user::UserProfile::from(u)).unwrap()))
-    .ok_or((StatusCode::NOT_FOUND, Json(json!({ "error": "User not found" }))))
+    .ok_or_else(|| ApiError::from_key(StatusCode::NOT_FOUND, "user_not_found"))
 }
 
 /// Always returns 200 to avoid leaking account existence.
 pub async fn forgot_password(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
-    Json(body): Json<ForgotPasswordRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<ForgotPasswordRequest>,
+) -> Result<Json<Value>, ApiError> {
     // Rate limit: 3 attempts per 30 min per email+tenant
     let rate_key = format!("rate:forgot:{}:{}", tenant, body.email.to_lowercase());
     let mut redis = state.redis.clone();
@@ -371,36 +523,26 @@ pub async fn forgot_password(
         crate::services::metrics::PASSWORD_RESETS_COUNTER.with_label_values(&[&tenant]).inc();
         Json(json!({ "message": "Si un compte existe, un email a été envoyé." }))
     })
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })
+    .map_err(ApiError::from)
 }
 
 pub async fn reset_password(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
-    Json(body): Json<ResetPasswordRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    AuthService::reset_password(&state.db, &tenant, &body.token, &body.new_password)
+    ValidJson(body): ValidJson<ResetPasswordRequest>,
+) -> Result<Json<Value>, ApiError> {
+    AuthService::reset_password(&state.db, &tenant, &body.token, &body.new_password, &state.password_policy)
         .await
         .map(|_| Json(json!({ "message": "Mot de passe réinitialisé avec succès." })))
-        .map_err(|e| {
-            (
-                StatusCode::BAD_REQUEST,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })
+        .map_err(ApiError::from)
 }
 
 pub async fn register_push_token(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-    Json(body): Json<RegisterPushTokenRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<RegisterPushTokenRequest>,
+) -> Result<Json<Value>, ApiError> {
     NotificationService::register_push_token(
         &state.db,
         &tenant,
@@ -410,12 +552,20 @@ pub async fn register_push_token(
     )
     .await
     .map(|_| Json(json!({ "message": "Push token registered" })))
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })
+    .map_err(ApiError::from)
+}
+
+/// DELETE /auth/push-token — the app signs out: stop pushing to this device
+pub async fn unregister_push_token(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<UnregisterPushTokenRequest>,
+) -> Result<StatusCode, ApiError> {
+    NotificationService::unregister_push_token(&state.db, &tenant, user.user_id, &body.token)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(ApiError::from)
 }
 
 pub async fn change_password(
@@ -423,14 +573,15 @@ pub async fn change_password(
     TenantSlug(tenant): TenantSlug,
     headers: HeaderMap,
     user: AuthenticatedUser,
-    Json(body): Json<ChangePasswordRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<ChangePasswordRequest>,
+) -> Result<Json<Value>, ApiError> {
     let result = AuthService::change_password(
         &state.db,
         &tenant,
         user.user_id,
         &body.current_password,
         &body.new_password,
+        &state.password_policy,
     )
     .await;
 
@@ -448,15 +599,15 @@ pub async fn change_password(
 
     result
         .map(|_| Json(json!({ "message": "Mot de passe modifié avec succès" })))
-        .map_err(|e| (StatusCode::BAD_REQUEST, Json(json!({ "error": e.to_string() }))))
+        .map_err(ApiError::from)
 }
 
 pub async fn update_email(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-    Json(body): Json<UpdateEmailRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<UpdateEmailRequest>,
+) -> Result<Json<Value>, ApiError> {
     AuthService::update_email(
         &state.db,
         &tenant,
@@ -466,49 +617,129 @@ pub async fn update_email(
     )
     .await
     .map(|_| Json(json!({ "message": "Email modifié avec succès" })))
-    .map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })
+    .map_err(ApiError::from)
+}
+
+/// PUT /auth/phone — set or clear the caller's number for SMS notifications.
+pub async fn update_phone(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<UpdatePhoneRequest>,
+) -> Result<Json<Value>, ApiError> {
+    AuthService::update_phone(&state.db, &tenant, user.user_id, body.phone.as_deref())
+        .await
+        .map(|phone| Json(json!({ "phone": phone })))
+        .map_err(ApiError::from)
 }
 
 pub async fn list_pending_invitations(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     _user: AuthenticatedUser,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     AuthService::list_pending_invitations(&state.db, &tenant)
         .await
         .map(|invitations| Json(serde_json::to_value(invitations).unwrap()))
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })
+        .map_err(ApiError::from)
 }
 
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
+        _ => Err(ApiError::forbidden()),
+    }
+}
+
+/// POST /auth/invitations/{id}/resend — new token and expiry, old link revoked
 pub async fn resend_invitation(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     _user: AuthenticatedUser,
     Path(id): Path<Uuid>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    match AuthService::resend_invitation(&state.db, state.email.as_deref(), &tenant, id, &state.config.app_base_url).await {
-        Ok(()) => Ok(Json(json!({ "success": true, "message": "Invitation renvoyée avec succès" }))),
-        Err(e) => {
-            let status = if e.to_string().contains("not found") {
-                StatusCode::NOT_FOUND
-            } else if e.to_string().contains("email non configuré") {
-                StatusCode::SERVICE_UNAVAILABLE
-            } else {
-                StatusCode::INTERNAL_SERVER_ERROR
-            };
-            Err((status, Json(json!({ "error": e.to_string() }))))
+) -> Result<Json<Value>, ApiError> {
+    AuthService::resend_invitation(&state.db, state.email.as_deref(), &tenant, id, &state.config.app_base_url)
+        .await
+        .map(|()| Json(json!({ "success": true, "message": "Invitation renvoyée avec succès" })))
+        .map_err(ApiError::from)
+}
+
+/// PATCH /auth/invitations/{id} — admin only; extends the expiry of the current link
+pub async fn extend_invitation(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidJson(body): ValidJson<ExtendInvitationRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    if !(1..=MAX_INVITATION_EXTENSION_DAYS).contains(&body.days) {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            format!("La prolongation doit être comprise entre 1 et {MAX_INVITATION_EXTENSION_DAYS} jours"),
+        ));
+    }
+    AuthService::extend_invitation(&state.db, &tenant, id, body.days)
+        .await
+        .map(|expires_at| Json(json!({ "expires_at": expires_at })))
+        .map_err(ApiError::from)
+}
+
+/// POST /auth/invitations/bulk — admin only; invites each address independently and
+/// reports which ones failed.
+pub async fn bulk_invite(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<BulkInviteRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    if body.invitations.len() > MAX_BULK_INVITATIONS {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            format!("{MAX_BULK_INVITATIONS} invitations au maximum par envoi"),
+        ));
+    }
+    if state.email.is_none() {
+        return Err(ApiError::from(InvitationError::EmailUnavailable));
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    let mut results = Vec::with_capacity(body.invitations.len());
+    for invite in &body.invitations {
+        let email = invite.email.trim().to_lowercase();
+        let outcome = if !email.contains('@') {
+            Err("Adresse email invalide".to_string())
+        } else if !seen.insert(email.clone()) {
+            Err("Adresse en double dans la liste".to_string())
+        } else if invite.role == UserRole::SuperAdmin {
+            Err("Rôle non autorisé".to_string())
+        } else {
+            AuthService::create_invitation(
+                &state.db,
+                state.email.as_deref(),
+                &tenant,
+                &email,
+                invite.role.clone(),
+                Some(user.user_id),
+                invite.preferred_locale.as_deref(),
+                &state.config.app_base_url,
+            )
+            .await
+            .map_err(|e| ApiError::from(e).detail)
+        };
+        if outcome.is_ok() {
+            crate::services::metrics::INVITATIONS_COUNTER.with_label_values(&[&tenant]).inc();
         }
+        results.push(BulkInviteResult {
+            email: invite.email.clone(),
+            success: outcome.is_ok(),
+            error: outcome.err(),
+        });
     }
+
+    let sent = results.iter().filter(|r| r.success).count();
+    Ok(Json(json!({ "sent": sent, "failed": results.len() - sent, "results": results })))
 }
 
 pub async fn delete_invitation(
@@ -516,33 +747,17 @@ pub async fn delete_invitation(
     TenantSlug(tenant): TenantSlug,
     _user: AuthenticatedUser,
     Path(id): Path<Uuid>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     match AuthService::delete_invitation(&state.db, &tenant, id).await {
         Ok(true) => Ok(Json(json!({ "success": true }))),
-        Ok(false) => Err((
-            StatusCode::NOT_FOUND,
-            Json(json!({ "error": "Invitation not found or already used" })),
-        )),
-        Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )),
+        Ok(false) => Err(ApiError::not_found("Invitation not found or already used")),
+        Err(e) => Err(ApiError::from(e)),
     }
 }
 
 // --- Consent Management ---
 
-#[derive(Debug, Serialize, sqlx::FromRow)]
-pub struct ConsentRecord {
-    pub id: Uuid,
-    pub privacy_accepted: bool,
-    pub photos_accepted: bool,
-    pub accepted_at: DateTime<Utc>,
-    pub policy_version: String,
-    pub language: String,
-}
-
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct UpdateConsentRequest {
     pub photos_accepted: bool,
 }
@@ -551,16 +766,12 @@ pub async fn get_consent(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-) -> Result<Json<ConsentRecord>, (StatusCode, Json<Value>)> {
+) -> Result<Json<ConsentRecord>, ApiError> {
     use crate::db::tenant::schema_name;
 
     // Only parents can access their own consent
-    use crate::models::user::UserRole;
     if user.role != UserRole::Parent {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(json!({ "error": "Only parents can access consent records" })),
-        ));
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "Only parents can access consent records"));
     }
 
     let schema = schema_name(&tenant);
@@ -577,16 +788,10 @@ pub async fn get_consent(
         .await
     {
         Ok(Some(record)) => Ok(Json(record)),
-        Ok(None) => Err((
-            StatusCode::NOT_FOUND,
-            Json(json!({ "error": "No consent record found" })),
-        )),
+        Ok(None) => Err(ApiError::not_found("No consent record found")),
         Err(e) => {
             tracing::error!("Failed to fetch consent: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": "Failed to fetch consent" })),
-            ))
+            Err(ApiError::internal())
         }
     }
 }
@@ -595,24 +800,20 @@ pub async fn update_consent(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-    Json(body): Json<UpdateConsentRequest>,
-) -> Result<Json<ConsentRecord>, (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<UpdateConsentRequest>,
+) -> Result<Json<ConsentRecord>, ApiError> {
     use crate::db::tenant::schema_name;
 
     // Only parents can update their consent
-    use crate::models::user::UserRole;
     if user.role != UserRole::Parent {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(json!({ "error": "Only parents can update consent records" })),
-        ));
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "Only parents can update consent records"));
     }
 
     let schema = schema_name(&tenant);
     let query = format!(
         "INSERT INTO {}.consent_records
             (user_id, privacy_accepted, photos_accepted, accepted_at, policy_version, language)
-         VALUES ($1, TRUE, $2, NOW(), '1.0', $3)
+         VALUES ($1, TRUE, $2, NOW(), $4, $3)
          RETURNING id, privacy_accepted, photos_accepted, accepted_at, policy_version, language",
         schema
     );
@@ -628,10 +829,7 @@ pub async fn update_consent(
         .await
         .map_err(|e| {
             tracing::error!("Failed to fetch user locale: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": "Failed to update consent" })),
-            )
+            ApiError::internal()
         })?;
 
     let language = user_record.unwrap_or_else(|| "fr".to_string());
@@ -640,6 +838,7 @@ pub async fn update_consent(
         .bind(user.user_id)
         .bind(body.photos_accepted)
         .bind(&language)
+        .bind(CURRENT_POLICY_VERSION)
         .fetch_one(&state.db)
         .await
     {
@@ -662,29 +861,54 @@ pub async fn update_consent(
         }
         Err(e) => {
             tracing::error!("Failed to update consent: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": "Failed to update consent" })),
-            ))
+            Err(ApiError::internal())
         }
     }
 }
 
+/// POST /auth/consent — a parent accepts the privacy policy, e.g. a new version of it
+pub async fn record_consent(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    headers: HeaderMap,
+    ValidJson(body): ValidJson<ParentConsentPayload>,
+) -> Result<Json<ConsentRecord>, ApiError> {
+    if user.role != UserRole::Parent {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "Only parents can update consent records"));
+    }
+    if !body.privacy_accepted {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "La politique de confidentialité doit être acceptée"));
+    }
+
+    let ip_address = real_client_ip(&headers);
+    let record = ConsentService::record(&state.db, &tenant, user.user_id, &body, &ip_address)
+        .await?;
+
+    crate::services::audit::log(state.db.clone(), &tenant, crate::services::audit::AuditEntry {
+        user_id:        Some(user.user_id),
+        user_name:      None,
+        action:         "consent.record".to_string(),
+        resource_type:  Some("user".to_string()),
+        resource_id:    Some(user.user_id.to_string()),
+        resource_label: Some(record.policy_version.clone()),
+        ip_address,
+    });
+    Ok(Json(record))
+}
+
 pub async fn request_account_deletion(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     use crate::db::tenant::schema_name;
 
     // Check if email service is configured
     let email_service = match state.email.as_ref() {
         Some(service) => service,
         None => {
-            return Err((
-                StatusCode::SERVICE_UNAVAILABLE,
-                Json(json!({ "error": "Email non configuré" })),
-            ))
+            return Err(ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Email non configuré"))
         }
     };
 
@@ -701,19 +925,13 @@ pub async fn request_account_deletion(
         .await
         .map_err(|e| {
             tracing::error!("Failed to fetch user info: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": "Failed to process request" })),
-            )
+            ApiError::internal()
         })?;
 
     let (first_name, last_name, user_email) = match user_info {
         Some(info) => info,
         None => {
-            return Err((
-                StatusCode::NOT_FOUND,
-                Json(json!({ "error": "User not found" })),
-            ))
+            return Err(ApiError::from_key(StatusCode::NOT_FOUND, "user_not_found"))
         }
     };
 
@@ -725,19 +943,13 @@ pub async fn request_account_deletion(
         .await
         .map_err(|e| {
             tracing::error!("Failed to fetch garderie info: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": "Failed to process request" })),
-            )
+            ApiError::internal()
         })?;
 
     let (_, garderie_email) = match garderie_info {
         Some(info) => info,
         None => {
-            return Err((
-                StatusCode::NOT_FOUND,
-                Json(json!({ "error": "Garderie not found" })),
-            ))
+            return Err(ApiError::from_key(StatusCode::NOT_FOUND, "tenant_not_found"))
         }
     };
 
@@ -754,19 +966,13 @@ pub async fn request_account_deletion(
             .await
             .map_err(|e| {
                 tracing::error!("Failed to fetch admin email: {}", e);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(json!({ "error": "Failed to process request" })),
-                )
+                ApiError::internal()
             })?
             .unwrap_or_default()
     };
 
     if admin_email.is_empty() {
-        return Err((
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(json!({ "error": "Admin email not configured" })),
-        ));
+        return Err(ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Admin email not configured"));
     }
 
     // Send email to admin
@@ -785,10 +991,7 @@ pub async fn request_account_deletion(
         .await
         .map_err(|e| {
             tracing::error!("Failed to send deletion request email: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": "Failed to send request" })),
-            )
+            ApiError::internal()
         })?;
 
     // Log audit event
@@ -1,26 +1,33 @@
+use anyhow::Context;
 use axum::{
     body::Body,
-    extract::{Multipart, Path, State},
+    extract::{Multipart, Path, Query, State},
     http::{header, HeaderMap, Response, StatusCode},
     Json,
 };
 use chrono::{DateTime, NaiveDate, Utc, Local};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use uuid::Uuid;
-use std::io::Write;
 
 use crate::{
     db::tenant::schema_name,
-    middleware::tenant::TenantSlug,
+    error::ApiError,
+    middleware::{fields::Fields, precondition, tenant::TenantSlug},
     models::{
         auth::AuthenticatedUser,
         child::{AssignInvitedParentRequest, AssignParentRequest, AssignPendingParentRequest, CreateChildRequest, UpdateChildRequest},
         user::UserRole,
     },
-    services::{audit::{self, AuditEntry}, children::ChildService, cron::CronService},
+    services::{
+        audit::{self, AuditEntry},
+        children::ChildService,
+        cron::CronService,
+        upload_types::{self, UploadKind},
+    },
     AppState,
 };
+use crate::middleware::validation::ValidJson;
 
 #[derive(Debug, Serialize, sqlx::FromRow)]
 struct ExportJournal {
@@ -89,19 +96,19 @@ fn client_ip(h: &HeaderMap) -> String {
         .to_string()
 }
 
-fn forbid_parent(user: &AuthenticatedUser) -> Option<(StatusCode, Json<Value>)> {
+fn forbid_parent(user: &AuthenticatedUser) -> Result<(), ApiError> {
     if let UserRole::Parent = user.role {
-        Some((StatusCode::FORBIDDEN, Json(json!({ "error": "Accès refusé" }))))
+        Err(ApiError::forbidden())
     } else {
-        None
+        Ok(())
     }
 }
 
 /// Only admin_garderie and super_admin may perform write operations.
-fn require_admin(user: &AuthenticatedUser) -> Option<(StatusCode, Json<Value>)> {
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
     match user.role {
-        UserRole::AdminGarderie | UserRole::SuperAdmin => None,
-        _ => Some((StatusCode::FORBIDDEN, Json(json!({ "error": "Accès refusé" })))),
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
+        _ => Err(ApiError::forbidden()),
     }
 }
 
@@ -109,22 +116,17 @@ pub async fn list_children(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    let children = match user.role {
-        UserRole::Parent => {
-            ChildService::list_for_parent(&state.db, &tenant, user.user_id).await
-        }
-        _ => ChildService::list(&state.db, &tenant).await,
-    };
-
-    children
-        .map(|c| Json(serde_json::to_value(c).unwrap()))
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
+    fields: Fields,
+    headers: HeaderMap,
+) -> Result<Response<Body>, ApiError> {
+    ChildService::list_visible_to(&state.db, &tenant, &user)
+        .await
+        .map(|c| {
+            let mut body = serde_json::to_value(&c).unwrap();
+            fields.select(&mut body);
+            precondition::cached_list(&headers, c.iter().map(|child| child.updated_at), &body)
         })
+        .map_err(ApiError::from)
 }
 
 pub async fn create_child(
@@ -132,11 +134,9 @@ pub async fn create_child(
     TenantSlug(tenant): TenantSlug,
     headers: HeaderMap,
     user: AuthenticatedUser,
-    Json(body): Json<CreateChildRequest>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    if let Some(err) = require_admin(&user) {
-        return Err(err);
-    }
+    ValidJson(body): ValidJson<CreateChildRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&user)?;
 
     let result = ChildService::create(&state.db, &tenant, &body).await;
 
@@ -155,7 +155,7 @@ pub async fn create_child(
 
     result
         .map(|child| (StatusCode::CREATED, Json(serde_json::to_value(child).unwrap())))
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))
+        .map_err(ApiError::from)
 }
 
 pub async fn update_child(
@@ -164,11 +164,9 @@ pub async fn update_child(
     headers: HeaderMap,
     user: AuthenticatedUser,
     Path(id): Path<Uuid>,
-    Json(body): Json<UpdateChildRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    if let Some(err) = require_admin(&user) {
-        return Err(err);
-    }
+    ValidJson(body): ValidJson<UpdateChildRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
 
     let result = ChildService::update(&state.db, &tenant, id, &body).await;
 
@@ -187,7 +185,7 @@ pub async fn update_child(
 
     result
         .map(|child| Json(serde_json::to_value(child).unwrap()))
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))
+        .map_err(ApiError::from)
 }
 
 pub async fn list_parents(
@@ -195,15 +193,13 @@ pub async fn list_parents(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path(child_id): Path<Uuid>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    if let Some(err) = require_admin(&user) {
-        return Err(err);
-    }
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
 
     ChildService::list_parents_for_child(&state.db, &tenant, child_id)
         .await
         .map(|p| Json(serde_json::to_value(p).unwrap()))
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))
+        .map_err(ApiError::from)
 }
 
 pub async fn assign_parent(
@@ -211,16 +207,14 @@ pub async fn assign_parent(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path(child_id): Path<Uuid>,
-    Json(body): Json<AssignParentRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    if let Some(err) = require_admin(&user) {
-        return Err(err);
-    }
+    ValidJson(body): ValidJson<AssignParentRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
 
     ChildService::assign_parent(&state.db, &tenant, child_id, &body)
         .await
         .map(|_| Json(json!({ "message": "Parent assigné" })))
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))
+        .map_err(ApiError::from)
 }
 
 pub async fn remove_parent(
@@ -228,15 +222,13 @@ pub async fn remove_parent(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path((child_id, user_id)): Path<(Uuid, Uuid)>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    if let Some(err) = require_admin(&user) {
-        return Err(err);
-    }
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
 
     ChildService::remove_parent(&state.db, &tenant, child_id, user_id)
         .await
         .map(|_| Json(json!({ "message": "Parent retiré" })))
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))
+        .map_err(ApiError::from)
 }
 
 pub async fn delete_child(
@@ -245,10 +237,8 @@ pub async fn delete_child(
     headers: HeaderMap,
     user: AuthenticatedUser,
     Path(id): Path<Uuid>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    if let Some(err) = require_admin(&user) {
-        return Err(err);
-    }
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
 
     let result = ChildService::delete(&state.db, &tenant, id).await;
 
@@ -271,7 +261,7 @@ pub async fn delete_child(
 
     result
         .map(|_| Json(json!({ "message": "Enfant supprimé" })))
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))
+        .map_err(ApiError::from)
 }
 
 pub async fn export_child(
@@ -280,16 +270,16 @@ pub async fn export_child(
     headers: HeaderMap,
     user: AuthenticatedUser,
     Path(child_id): Path<Uuid>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     // Check access: Educateur cannot export, Parent must be parent of this child
     if let UserRole::Educateur = user.role {
-        return Err((StatusCode::FORBIDDEN, Json(json!({ "error": "Accès refusé" }))));
+        return Err(ApiError::forbidden());
     }
 
     if let UserRole::Parent = user.role {
         match ChildService::is_parent_of(&state.db, &tenant, child_id, user.user_id).await {
-            Ok(false) => return Err((StatusCode::FORBIDDEN, Json(json!({ "error": "Accès refusé" })))),
-            Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))),
+            Ok(false) => return Err(ApiError::forbidden()),
+            Err(e) => return Err(ApiError::from(e)),
             _ => {}
         }
     }
@@ -305,8 +295,8 @@ pub async fn export_child(
     .await
     {
         Ok(Some(val)) => val,
-        Ok(None) => return Err((StatusCode::NOT_FOUND, Json(json!({ "error": "Enfant non trouvé" })))),
-        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))),
+        Ok(None) => return Err(ApiError::not_found("Enfant non trouvé")),
+        Err(e) => return Err(ApiError::from(e)),
     };
 
     // Query 2: Get parents linked to this child
@@ -322,7 +312,7 @@ pub async fn export_child(
     .await
     {
         Ok(p) => p,
-        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))),
+        Err(e) => return Err(ApiError::from(e)),
     };
 
     // Query 3: Get journals
@@ -339,7 +329,7 @@ pub async fn export_child(
     .await
     {
         Ok(j) => j,
-        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))),
+        Err(e) => return Err(ApiError::from(e)),
     };
 
     // Query 4: Get media metadata
@@ -356,7 +346,7 @@ pub async fn export_child(
     .await
     {
         Ok(m) => m,
-        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))),
+        Err(e) => return Err(ApiError::from(e)),
     };
 
     // Query 5: Get consents (if parents non-empty)
@@ -374,7 +364,7 @@ pub async fn export_child(
         .await
         {
             Ok(c) => c,
-            Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))),
+            Err(e) => return Err(ApiError::from(e)),
         };
     }
 
@@ -413,15 +403,13 @@ pub async fn list_pending_parents(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path(child_id): Path<Uuid>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    if let Some(err) = require_admin(&user) {
-        return Err(err);
-    }
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
 
     ChildService::list_pending_parents_for_child(&state.db, &tenant, child_id)
         .await
         .map(|p| Json(serde_json::to_value(p).unwrap()))
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))
+        .map_err(ApiError::from)
 }
 
 pub async fn assign_pending_parent(
@@ -430,11 +418,9 @@ pub async fn assign_pending_parent(
     headers: HeaderMap,
     user: AuthenticatedUser,
     Path(child_id): Path<Uuid>,
-    Json(body): Json<AssignPendingParentRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    if let Some(err) = require_admin(&user) {
-        return Err(err);
-    }
+    ValidJson(body): ValidJson<AssignPendingParentRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
 
     let result = ChildService::assign_pending_parent(&state.db, &tenant, child_id, &body).await;
 
@@ -452,7 +438,7 @@ pub async fn assign_pending_parent(
 
     result
         .map(|_| Json(json!({ "message": "Parent en attente assigné" })))
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))
+        .map_err(ApiError::from)
 }
 
 pub async fn remove_pending_parent(
@@ -461,10 +447,8 @@ pub async fn remove_pending_parent(
     headers: HeaderMap,
     user: AuthenticatedUser,
     Path((child_id, email)): Path<(Uuid, String)>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    if let Some(err) = require_admin(&user) {
-        return Err(err);
-    }
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
 
     let result = ChildService::remove_pending_parent(&state.db, &tenant, child_id, &email).await;
 
@@ -482,7 +466,7 @@ pub async fn remove_pending_parent(
 
     result
         .map(|_| Json(json!({ "message": "Parent en attente retiré" })))
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))
+        .map_err(ApiError::from)
 }
 
 pub async fn list_invited_parents(
@@ -490,15 +474,13 @@ pub async fn list_invited_parents(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path(child_id): Path<Uuid>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    if let Some(err) = require_admin(&user) {
-        return Err(err);
-    }
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
 
     ChildService::list_invited_parents_for_child(&state.db, &tenant, child_id)
         .await
         .map(|p| Json(serde_json::to_value(p).unwrap()))
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))
+        .map_err(ApiError::from)
 }
 
 pub async fn assign_invited_parent(
@@ -507,11 +489,9 @@ pub async fn assign_invited_parent(
     headers: HeaderMap,
     user: AuthenticatedUser,
     Path(child_id): Path<Uuid>,
-    Json(body): Json<AssignInvitedParentRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    if let Some(err) = require_admin(&user) {
-        return Err(err);
-    }
+    ValidJson(body): ValidJson<AssignInvitedParentRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
 
     let schema = schema_name(&tenant);
 
@@ -522,11 +502,10 @@ pub async fn assign_invited_parent(
     .bind(&body.email)
     .bind(&body.role)
     .fetch_optional(&state.db)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+    .await?;
 
     let token_id = token_id
-        .ok_or_else(|| (StatusCode::NOT_FOUND, Json(json!({ "error": "Invitation non trouvée" }))))?;
+        .ok_or_else(|| ApiError::from_key(StatusCode::NOT_FOUND, "invitation_not_found"))?;
 
     let result = ChildService::assign_invited_parent(&state.db, &tenant, child_id, token_id).await;
 
@@ -544,7 +523,7 @@ pub async fn assign_invited_parent(
 
     result
         .map(|_| Json(json!({ "message": "Parent invité assigné" })))
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))
+        .map_err(ApiError::from)
 }
 
 pub async fn remove_invited_parent(
@@ -553,10 +532,8 @@ pub async fn remove_invited_parent(
     headers: HeaderMap,
     user: AuthenticatedUser,
     Path((child_id, email)): Path<(Uuid, String)>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    if let Some(err) = require_admin(&user) {
-        return Err(err);
-    }
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
 
     let schema = schema_name(&tenant);
 
@@ -566,11 +543,10 @@ pub async fn remove_invited_parent(
     ))
     .bind(&email)
     .fetch_optional(&state.db)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+    .await?;
 
     let token_id = token_id
-        .ok_or_else(|| (StatusCode::NOT_FOUND, Json(json!({ "error": "Invitation non trouvée" }))))?;
+        .ok_or_else(|| ApiError::from_key(StatusCode::NOT_FOUND, "invitation_not_found"))?;
 
     let result = ChildService::remove_invited_parent(&state.db, &tenant, child_id, token_id).await;
 
@@ -588,37 +564,43 @@ pub async fn remove_invited_parent(
 
     result
         .map(|_| Json(json!({ "message": "Parent invité retiré" })))
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))
+        .map_err(ApiError::from)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportQuery {
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
-/// POST /children/import — import children from an xlsx/csv file (admin only)
+/// POST /children/import?dry_run=true — import children from an xlsx/csv file (admin only).
+/// With `dry_run`, rows are validated and counted without writing anything.
 pub async fn import_children(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     headers: HeaderMap,
     user: AuthenticatedUser,
+    Query(params): Query<ImportQuery>,
     mut multipart: Multipart,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    if let Some(err) = require_admin(&user) {
-        return Err(err);
-    }
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&user)?;
 
     // Extract the file field from multipart
     let mut file_bytes: Option<Vec<u8>> = None;
     while let Some(field) = multipart.next_field().await.map_err(|e| {
-        (StatusCode::BAD_REQUEST, Json(json!({ "error": format!("Erreur multipart: {e}") })))
+        ApiError::new(StatusCode::BAD_REQUEST, format!("Erreur multipart: {e}"))
     })? {
         let name = field.name().unwrap_or("").to_string();
         if name == "file" || file_bytes.is_none() {
             let bytes = field.bytes().await.map_err(|e| {
-                (StatusCode::BAD_REQUEST, Json(json!({ "error": format!("Erreur lecture fichier: {e}") })))
+                ApiError::new(StatusCode::BAD_REQUEST, format!("Erreur lecture fichier: {e}"))
             })?;
             file_bytes = Some(bytes.to_vec());
         }
     }
 
     let bytes = file_bytes.ok_or_else(|| {
-        (StatusCode::BAD_REQUEST, Json(json!({ "error": "Aucun fichier fourni (champ 'file' manquant)" })))
+        ApiError::from_key(StatusCode::BAD_REQUEST, "missing_file")
     })?;
 
     let result = ChildService::import_from_excel(
@@ -628,9 +610,13 @@ pub async fn import_children(
         state.email.clone(),
         &state.config.app_base_url,
         Some(user.user_id),
+        params.dry_run,
     )
-    .await
-    .map_err(|e| (StatusCode::BAD_REQUEST, Json(json!({ "error": e.to_string() }))))?;
+    .await?;
+
+    if result.dry_run {
+        return Ok((StatusCode::OK, Json(serde_json::to_value(&result).unwrap())));
+    }
 
     let label = format!("{} enfants créés", result.created_children);
     audit::log(state.db.clone(), &tenant, AuditEntry {
@@ -652,14 +638,11 @@ pub async fn export_all_children(
     TenantSlug(tenant): TenantSlug,
     headers: HeaderMap,
     user: AuthenticatedUser,
-) -> Result<Response<Body>, (StatusCode, Json<Value>)> {
-    if let Some(err) = require_admin(&user) {
-        return Err(err);
-    }
+) -> Result<Response<Body>, ApiError> {
+    require_admin(&user)?;
 
     let csv_bytes = ChildService::export_all_as_csv(&state.db, &tenant)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+        .await?;
 
     let today = Local::now().format("%Y-%m-%d").to_string();
     let filename = format!("enfants-{today}.csv");
@@ -691,10 +674,8 @@ pub async fn list_available_invitations(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    if let Some(err) = require_admin(&user) {
-        return Err(err);
-    }
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
 
     let schema = schema_name(&tenant);
 
@@ -709,8 +690,7 @@ pub async fn list_available_invitations(
          ORDER BY it.created_at DESC"
     ))
     .fetch_all(&state.db)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+    .await?;
 
     Ok(Json(serde_json::to_value(invitations).unwrap()))
 }
@@ -722,14 +702,14 @@ pub async fn upload_child_avatar(
     user: AuthenticatedUser,
     Path(child_id): Path<Uuid>,
     multipart: Multipart,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+) -> Result<(StatusCode, Json<Value>), ApiError> {
     // Permission check: Admin → allow. Parent → must be parent of child. Educateur → 403.
     match user.role {
-        UserRole::Educateur => return Err((StatusCode::FORBIDDEN, Json(json!({ "error": "Accès refusé" })))),
+        UserRole::Educateur => return Err(ApiError::forbidden()),
         UserRole::Parent => {
             match ChildService::is_parent_of(&state.db, &tenant, child_id, user.user_id).await {
-                Ok(false) => return Err((StatusCode::FORBIDDEN, Json(json!({ "error": "Accès refusé" })))),
-                Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))),
+                Ok(false) => return Err(ApiError::forbidden()),
+                Err(e) => return Err(ApiError::from(e)),
                 _ => {}
             }
         }
@@ -739,41 +719,41 @@ pub async fn upload_child_avatar(
     // Parse multipart and extract file
     let mut file_bytes: Option<Vec<u8>> = None;
     let mut content_type: Option<String> = None;
+    let mut filename = String::new();
     let mut mpart = multipart;
 
     while let Some(field) = mpart.next_field().await.map_err(|e| {
-        (StatusCode::BAD_REQUEST, Json(json!({ "error": format!("Erreur multipart: {e}") })))
+        ApiError::new(StatusCode::BAD_REQUEST, format!("Erreur multipart: {e}"))
     })? {
         let field_name = field.name().unwrap_or("").to_string();
         let field_content_type = field.content_type().map(|ct| ct.to_string());
 
         if field_name == "file" {
             content_type = field_content_type;
+            filename = field.file_name().unwrap_or("").to_string();
             let bytes = field.bytes().await.map_err(|e| {
-                (StatusCode::BAD_REQUEST, Json(json!({ "error": format!("Erreur lecture fichier: {e}") })))
+                ApiError::new(StatusCode::BAD_REQUEST, format!("Erreur lecture fichier: {e}"))
             })?;
             file_bytes = Some(bytes.to_vec());
         }
     }
 
     let file_bytes = file_bytes.ok_or_else(|| {
-        (StatusCode::BAD_REQUEST, Json(json!({ "error": "Aucun fichier fourni (champ 'file' manquant)" })))
+        ApiError::from_key(StatusCode::BAD_REQUEST, "missing_file")
     })?;
 
-    // Validate content type (jpeg, png, webp)
+    // JPEG, PNG or WebP, going by the bytes
     let content_type = content_type.unwrap_or_default();
-    if !["image/jpeg", "image/png", "image/webp"].contains(&content_type.as_str()) {
-        return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "Format d'image invalide (JPEG, PNG ou WebP attendu)" }))));
-    }
+    upload_types::validate(UploadKind::Image, &file_bytes, &content_type, &filename)?;
 
     // Validate file size (max 5 MB)
     if file_bytes.len() > 5 * 1024 * 1024 {
-        return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "Fichier trop volumineux (5 MB maximum)" }))));
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Fichier trop volumineux (5 MB maximum)"));
     }
 
     // Decode, resize to max 512×512, encode as JPEG
     let img = image::load_from_memory(&file_bytes)
-        .map_err(|e| (StatusCode::BAD_REQUEST, Json(json!({ "error": format!("Erreur décodage image: {e}") }))))?;
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, format!("Erreur décodage image: {e}")))?;
 
     let resized = if img.width() > 512 || img.height() > 512 {
         img.resize(512, 512, image::imageops::FilterType::Lanczos3)
@@ -784,22 +764,15 @@ pub async fn upload_child_avatar(
     let mut jpeg_bytes = Vec::new();
     resized
         .write_to(&mut std::io::Cursor::new(&mut jpeg_bytes), image::ImageFormat::Jpeg)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("Erreur encodage JPEG: {e}") }))))?;
+        .context("Erreur encodage JPEG")?;
 
     // Encrypt with tenant key
-    let master_key_bytes = hex::decode(&state.config.encryption_master_key)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("Clé invalide: {e}") }))))?;
-    if master_key_bytes.len() != 32 {
-        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "Clé maître invalide" }))));
-    }
-    let mut master_key = [0u8; 32];
-    master_key.copy_from_slice(&master_key_bytes);
-
-    let tenant_key = crate::services::encryption::derive_tenant_key(&master_key, &tenant)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("Dérivation clé échouée: {e}") }))))?;
+    let (key_version, tenant_key) = crate::services::encryption::KeyRing::from_config(&state.config)
+        .and_then(|keys| keys.current_tenant_key(&tenant))
+        .context("Dérivation clé échouée")?;
 
     let (encrypted_data, iv, tag) = crate::services::encryption::encrypt_file(&jpeg_bytes, &tenant_key)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("Chiffrement échoué: {e}") }))))?;
+        .context("Chiffrement échoué")?;
 
     // Generate random filename and build path
     let random_hex = hex::encode(rand::random::<[u8; 16]>());
@@ -809,35 +782,29 @@ pub async fn upload_child_avatar(
 
     tokio::fs::create_dir_all(&avatars_dir)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("Erreur création répertoire: {e}") }))))?;
+        .context("Erreur création répertoire")?;
 
     let file_path = avatars_dir.join(&random_hex);
     tokio::fs::write(&file_path, &encrypted_data)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("Erreur écriture fichier: {e}") }))))?;
-
-    // Delete old avatar if exists
-    let schema = schema_name(&tenant);
-    let old_url: Option<String> = sqlx::query_scalar(&format!(
-        "SELECT photo_url FROM {schema}.children WHERE id = $1"
-    ))
-    .bind(child_id)
-    .fetch_optional(&state.db)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?
-    .flatten();
+        .context("Erreur écriture fichier")?;
 
-    if let Some(old_path) = old_url {
+    // Point the child at the new file, then remove the one it replaced; if the
+    // update fails, the new file is dropped and the current photo stays
+    let photo_url = format!("{tenant}/avatars/{random_hex}");
+    let (updated_child, old_url) =
+        match ChildService::update_avatar(&state.db, &tenant, child_id, &photo_url, iv, tag, key_version).await {
+            Ok(updated) => updated,
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&file_path).await;
+                return Err(ApiError::from(e));
+            }
+        };
+    if let Some(old_path) = old_url.filter(|old| *old != photo_url) {
         let old_file = std::path::PathBuf::from(&state.config.media_dir).join(&old_path);
         let _ = tokio::fs::remove_file(old_file).await;
     }
 
-    // Update avatar in database
-    let photo_url = format!("{tenant}/avatars/{random_hex}");
-    let updated_child = ChildService::update_avatar(&state.db, &tenant, child_id, &photo_url, iv, tag)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
-
     // Audit log
     audit::log(state.db.clone(), &tenant, AuditEntry {
         user_id:        Some(user.user_id),
@@ -858,15 +825,12 @@ pub async fn delete_child_avatar(
     headers: HeaderMap,
     user: AuthenticatedUser,
     Path(child_id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, Json<Value>)> {
-    if let Some(err) = require_admin(&user) {
-        return Err(err);
-    }
+) -> Result<StatusCode, ApiError> {
+    require_admin(&user)?;
 
     // Get old photo_url and delete avatar
     let old_url = ChildService::delete_avatar(&state.db, &tenant, child_id)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+        .await?;
 
     // Delete physical file if exists
     if let Some(old_path) = old_url {
@@ -0,0 +1,107 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    error::ApiError,
+    middleware::tenant::TenantSlug,
+    models::{auth::AuthenticatedUser, consent::UpdateChildConsentRequest, user::UserRole},
+    services::{
+        children::ChildService,
+        consents::ConsentService,
+        groups::GroupService,
+    },
+    AppState,
+};
+use crate::middleware::validation::ValidJson;
+
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
+        _ => Err(ApiError::forbidden()),
+    }
+}
+
+fn not_found() -> ApiError {
+    ApiError::not_found("Enfant introuvable")
+}
+
+async fn ensure_parent_of(
+    state: &AppState,
+    tenant: &str,
+    user: &AuthenticatedUser,
+    child_id: Uuid,
+) -> Result<(), ApiError> {
+    let linked = ChildService::is_parent_of(&state.db, tenant, child_id, user.user_id)
+        .await?;
+    if linked {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden())
+    }
+}
+
+/// GET /children/{id}/consent — parents of the child and staff in charge of it
+pub async fn get_child_consent(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(child_id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    if user.role == UserRole::Parent {
+        ensure_parent_of(&state, &tenant, &user, child_id).await?;
+    } else {
+        GroupService::ensure_child_access(&state.db, &tenant, &user, child_id)
+            .await?;
+    }
+    ConsentService::child_consent(&state.db, &tenant, child_id)
+        .await?
+        .map(|c| Json(serde_json::to_value(c).unwrap()))
+        .ok_or_else(not_found)
+}
+
+/// PUT /children/{id}/consent — a parent of the child, or an admin recording a
+/// consent given on paper
+pub async fn update_child_consent(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(child_id): Path<Uuid>,
+    ValidJson(body): ValidJson<UpdateChildConsentRequest>,
+) -> Result<Json<Value>, ApiError> {
+    if user.role == UserRole::Parent {
+        ensure_parent_of(&state, &tenant, &user, child_id).await?;
+    } else {
+        require_admin(&user)?;
+    }
+    let consent = ConsentService::set_child_consent(&state.db, &tenant, child_id, body.photos_accepted, user.user_id)
+        .await?
+        .ok_or_else(not_found)?;
+
+    crate::services::audit::log(state.db.clone(), &tenant, crate::services::audit::AuditEntry {
+        user_id:        Some(user.user_id),
+        user_name:      None,
+        action:         "consent.child_update".to_string(),
+        resource_type:  Some("child".to_string()),
+        resource_id:    Some(child_id.to_string()),
+        resource_label: Some(format!("photos_accepted={}", body.photos_accepted)),
+        ip_address:     String::new(),
+    });
+    Ok(Json(serde_json::to_value(consent).unwrap()))
+}
+
+/// GET /consents/report — admin only; Loi 25 compliance overview
+pub async fn compliance_report(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    ConsentService::compliance_report(&state.db, &tenant)
+        .await
+        .map(|r| Json(serde_json::to_value(r).unwrap()))
+        .map_err(ApiError::from)
+}
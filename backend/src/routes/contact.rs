@@ -5,13 +5,20 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{middleware::rate_limit::check_rate_limit, AppState};
+use crate::{error::ApiError, middleware::rate_limit::check_rate_limit, AppState};
+use crate::models::limits::{MAX_EMAIL, MAX_NAME, MAX_PHONE};
+use validator::Validate;
+use crate::middleware::validation::ValidJson;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Validate)]
 pub struct ContactRequest {
+    #[validate(length(min = 1, max = MAX_NAME))]
     pub name: String,
+    #[validate(email, length(max = MAX_EMAIL))]
     pub email: String,
+    #[validate(length(min = 1, max = MAX_NAME))]
     pub garderie: String,
+    #[validate(length(max = MAX_PHONE))]
     pub phone: String,
 }
 
@@ -23,8 +30,8 @@ pub struct ContactResponse {
 pub async fn submit_contact(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Json(payload): Json<ContactRequest>,
-) -> Result<Json<ContactResponse>, (StatusCode, Json<serde_json::Value>)> {
+    ValidJson(payload): ValidJson<ContactRequest>,
+) -> Result<Json<ContactResponse>, ApiError> {
     // Rate limit by IP: max 5 requests per hour
     // Extract real client IP from X-Real-IP header (set by nginx from Cloudflare)
     let ip = headers
@@ -39,10 +46,10 @@ pub async fn submit_contact(
 
     check_rate_limit(&mut redis, &rate_limit_key, 5, 3600).await?;
 
-    let email_service = state.email.as_ref().ok_or((
-        StatusCode::SERVICE_UNAVAILABLE,
-        Json(serde_json::json!({"error": "Email service unavailable"})),
-    ))?;
+    let email_service = state
+        .email
+        .as_ref()
+        .ok_or_else(|| ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Email service unavailable"))?;
 
     email_service
         .send_contact_request(
@@ -54,10 +61,7 @@ pub async fn submit_contact(
         .await
         .map_err(|e| {
             tracing::error!("Failed to send contact request email: {e}");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({"error": "Failed to process request"})),
-            )
+            ApiError::internal()
         })?;
 
     Ok(Json(ContactResponse { success: true }))
@@ -0,0 +1,147 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use chrono::Local;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    error::ApiError,
+    middleware::{tenant::TenantSlug, validation::ValidJson},
+    models::{auth::AuthenticatedUser, departure::ScheduleDepartureRequest, user::UserRole},
+    services::{
+        audit::{self, AuditEntry},
+        departures::{export_url, DepartureService},
+        media_urls::MediaUrlSigner,
+    },
+    AppState,
+};
+
+fn client_ip(h: &HeaderMap) -> String {
+    h.get("x-real-ip").and_then(|v| v.to_str().ok())
+        .or_else(|| h.get("x-forwarded-for").and_then(|v| v.to_str().ok())
+            .and_then(|s| s.split(',').next()).map(|s| s.trim()))
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
+        _ => Err(ApiError::forbidden()),
+    }
+}
+
+fn audit_entry(user: &AuthenticatedUser, action: &str, child_id: Uuid, label: String, ip: String) -> AuditEntry {
+    AuditEntry {
+        user_id:        Some(user.user_id),
+        user_name:      None,
+        action:         action.to_string(),
+        resource_type:  Some("child".to_string()),
+        resource_id:    Some(child_id.to_string()),
+        resource_label: Some(label),
+        ip_address:     ip,
+    }
+}
+
+/// GET /children/{id}/departure — admin only; departure date, parents' access and
+/// the export links sent
+pub async fn get_departure(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(child_id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let departure = DepartureService::get(&state.db, &tenant, child_id).await?;
+    let exports = DepartureService::exports(&state.db, &tenant, child_id).await?;
+    Ok(Json(json!({ "departure": departure, "exports": exports })))
+}
+
+/// PUT /children/{id}/departure — admin only; a date already reached applies the
+/// departure right away, otherwise the nightly job does on the day
+pub async fn schedule_departure(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    headers: HeaderMap,
+    Path(child_id): Path<Uuid>,
+    ValidJson(body): ValidJson<ScheduleDepartureRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let mut departure = DepartureService::schedule(&state.db, &tenant, child_id, &body).await?;
+    let today = Local::now().date_naive();
+    if body.departure_date <= today {
+        DepartureService::apply_due(&state.db, state.email.as_deref(), &state.config.app_base_url, &tenant, today)
+            .await?;
+        departure = DepartureService::get(&state.db, &tenant, child_id).await?;
+    }
+    let label = format!("{} {}", departure.first_name, departure.last_name);
+    audit::log(
+        state.db.clone(),
+        &tenant,
+        audit_entry(&user, "child.departure.schedule", child_id, label, client_ip(&headers)),
+    );
+    Ok(Json(json!(departure)))
+}
+
+/// DELETE /children/{id}/departure — admin only; until the departure takes place
+pub async fn cancel_departure(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    headers: HeaderMap,
+    Path(child_id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let departure = DepartureService::cancel(&state.db, &tenant, child_id).await?;
+    let label = format!("{} {}", departure.first_name, departure.last_name);
+    audit::log(
+        state.db.clone(),
+        &tenant,
+        audit_entry(&user, "child.departure.cancel", child_id, label, client_ip(&headers)),
+    );
+    Ok(Json(json!(departure)))
+}
+
+/// POST /children/{id}/departure/export — admin only; a new final export link,
+/// emailed to the parents and only returned (with its token) in this response
+pub async fn create_export(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    headers: HeaderMap,
+    Path(child_id): Path<Uuid>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&user)?;
+    let (mut export, token) = DepartureService::create_export(&state.db, &tenant, child_id, Some(user.user_id)).await?;
+    if let Some(email_svc) = state.email.as_deref() {
+        export.emailed_to =
+            DepartureService::email_export(&state.db, email_svc, &state.config.app_base_url, &tenant, &export, &token)
+                .await?;
+    }
+    let departure = DepartureService::get(&state.db, &tenant, child_id).await?;
+    let label = format!("{} {}", departure.first_name, departure.last_name);
+    audit::log(
+        state.db.clone(),
+        &tenant,
+        audit_entry(&user, "child.departure.export", child_id, label, client_ip(&headers)),
+    );
+    let url = export_url(&state.config.app_base_url, &tenant, &token);
+    Ok((StatusCode::CREATED, Json(json!({ "export": export, "url": url }))))
+}
+
+/// GET /departures/{token} — public; the departed child's journals and their own
+/// photos and videos, on the garderie's subdomain. Expired links answer 410.
+pub async fn open_export(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    Path(token): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let signer = MediaUrlSigner::from_config(&state.config)?;
+    let archive =
+        DepartureService::open(&state.db_bulk, &tenant, &signer, &state.config.app_base_url, &token).await?;
+    Ok(Json(json!(archive)))
+}
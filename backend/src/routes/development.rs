@@ -0,0 +1,215 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Local;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    error::ApiError,
+    middleware::tenant::TenantSlug,
+    models::{
+        auth::AuthenticatedUser,
+        development::{term_for, MilestoneInput, MilestoneQuery, ObservationInput, SummaryQuery, TimelineQuery},
+        user::UserRole,
+    },
+    services::{
+        children::ChildService,
+        development::DevelopmentService,
+        groups::GroupService,
+    },
+    AppState,
+};
+use crate::middleware::validation::ValidJson;
+
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
+        _ => Err(ApiError::forbidden()),
+    }
+}
+
+fn not_found(what: &str) -> ApiError {
+    ApiError::not_found(format!("{what} introuvable"))
+}
+
+/// Parents may read their own children; staff the children in their groups.
+async fn ensure_can_read(
+    state: &AppState,
+    tenant: &str,
+    user: &AuthenticatedUser,
+    child_id: Uuid,
+) -> Result<(), ApiError> {
+    if user.role == UserRole::Parent {
+        let linked = ChildService::is_parent_of(&state.db, tenant, child_id, user.user_id)
+            .await?;
+        if !linked {
+            return Err(ApiError::forbidden());
+        }
+        return Ok(());
+    }
+    GroupService::ensure_child_access(&state.db, tenant, user, child_id)
+        .await
+        .map_err(ApiError::from)
+}
+
+/// Staff only, within their groups.
+async fn ensure_can_write(
+    state: &AppState,
+    tenant: &str,
+    user: &AuthenticatedUser,
+    child_id: Uuid,
+) -> Result<(), ApiError> {
+    if user.role == UserRole::Parent {
+        return Err(ApiError::forbidden());
+    }
+    GroupService::ensure_child_access(&state.db, tenant, user, child_id)
+        .await
+        .map_err(ApiError::from)
+}
+
+// ─── Milestone catalogue ──────────────────────────────────────────────────────
+
+/// GET /milestones?domain=&age_band= — all authenticated users
+pub async fn list_milestones(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    _user: AuthenticatedUser,
+    Query(query): Query<MilestoneQuery>,
+) -> Result<Json<Value>, ApiError> {
+    DevelopmentService::list_milestones(&state.db, &tenant, &query)
+        .await
+        .map(|items| Json(serde_json::to_value(items).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// POST /milestones — admin only
+pub async fn create_milestone(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<MilestoneInput>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&user)?;
+    DevelopmentService::create_milestone(&state.db, &tenant, &body)
+        .await
+        .map(|m| (StatusCode::CREATED, Json(serde_json::to_value(m).unwrap())))
+        .map_err(ApiError::from)
+}
+
+/// PUT /milestones/{id} — admin only
+pub async fn update_milestone(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidJson(body): ValidJson<MilestoneInput>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    DevelopmentService::update_milestone(&state.db, &tenant, id, &body)
+        .await?
+        .map(|m| Json(serde_json::to_value(m).unwrap()))
+        .ok_or_else(|| not_found("Jalon"))
+}
+
+/// DELETE /milestones/{id} — admin only; past observations keep the milestone
+pub async fn delete_milestone(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    require_admin(&user)?;
+    match DevelopmentService::deactivate_milestone(&state.db, &tenant, id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(not_found("Jalon")),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+// ─── Observations ─────────────────────────────────────────────────────────────
+
+/// GET /children/{id}/observations?domain=&from=&to= — the child's development timeline
+pub async fn get_timeline(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(child_id): Path<Uuid>,
+    Query(query): Query<TimelineQuery>,
+) -> Result<Json<Value>, ApiError> {
+    ensure_can_read(&state, &tenant, &user, child_id).await?;
+    let parent_view = user.role == UserRole::Parent;
+    DevelopmentService::timeline(&state.db, &tenant, child_id, parent_view, &query)
+        .await
+        .map(|items| Json(serde_json::to_value(items).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// GET /children/{id}/observations/summary?term=2026-automne — termly report
+pub async fn get_term_summary(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(child_id): Path<Uuid>,
+    Query(query): Query<SummaryQuery>,
+) -> Result<Json<Value>, ApiError> {
+    ensure_can_read(&state, &tenant, &user, child_id).await?;
+    let term = query.term.unwrap_or_else(|| term_for(Local::now().date_naive()));
+    let parent_view = user.role == UserRole::Parent;
+    DevelopmentService::term_summary(&state.db, &tenant, child_id, &term, parent_view)
+        .await?
+        .map(|summary| Json(serde_json::to_value(summary).unwrap()))
+        .ok_or_else(|| not_found("Enfant"))
+}
+
+/// POST /children/{id}/observations — staff only
+pub async fn create_observation(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(child_id): Path<Uuid>,
+    ValidJson(body): ValidJson<ObservationInput>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    ensure_can_write(&state, &tenant, &user, child_id).await?;
+    DevelopmentService::create_observation(&state.db, &tenant, child_id, &body, user.user_id)
+        .await
+        .map(|o| (StatusCode::CREATED, Json(serde_json::to_value(o).unwrap())))
+        .map_err(ApiError::from)
+}
+
+/// PUT /observations/{id} — staff only
+pub async fn update_observation(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidJson(body): ValidJson<ObservationInput>,
+) -> Result<Json<Value>, ApiError> {
+    let existing = DevelopmentService::get_observation(&state.db, &tenant, id)
+        .await?
+        .ok_or_else(|| not_found("Observation"))?;
+    ensure_can_write(&state, &tenant, &user, existing.child_id).await?;
+    DevelopmentService::update_observation(&state.db, &tenant, &existing, &body)
+        .await
+        .map(|o| Json(serde_json::to_value(o).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// DELETE /observations/{id} — staff only
+pub async fn delete_observation(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let existing = DevelopmentService::get_observation(&state.db, &tenant, id)
+        .await?
+        .ok_or_else(|| not_found("Observation"))?;
+    ensure_can_write(&state, &tenant, &user, existing.child_id).await?;
+    DevelopmentService::delete_observation(&state.db, &tenant, id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(ApiError::from)
+}
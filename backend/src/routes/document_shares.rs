@@ -0,0 +1,157 @@
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, HeaderMap, Response, StatusCode},
+    Json,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    error::ApiError,
+    middleware::{tenant::TenantSlug, validation::ValidJson},
+    models::{auth::AuthenticatedUser, document::CreateDocumentShareRequest, user::UserRole},
+    services::{
+        audit::{self, AuditEntry},
+        document_shares::{share_url, DocumentShareService},
+        encryption::KeyRing,
+    },
+    AppState,
+};
+
+fn client_ip(h: &HeaderMap) -> String {
+    h.get("x-real-ip").and_then(|v| v.to_str().ok())
+        .or_else(|| h.get("x-forwarded-for").and_then(|v| v.to_str().ok())
+            .and_then(|s| s.split(',').next()).map(|s| s.trim()))
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+/// Links leave the garderie, so only its administrators manage them.
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
+        _ => Err(ApiError::forbidden()),
+    }
+}
+
+fn audit_entry(user: &AuthenticatedUser, action: &str, document_id: Uuid, label: Option<String>, ip: String) -> AuditEntry {
+    AuditEntry {
+        user_id:        Some(user.user_id),
+        user_name:      None,
+        action:         action.to_string(),
+        resource_type:  Some("document".to_string()),
+        resource_id:    Some(document_id.to_string()),
+        resource_label: label,
+        ip_address:     ip,
+    }
+}
+
+/// Header-safe file name: quotes, backslashes and non-ASCII characters become `_`.
+fn disposition_name(filename: &str) -> String {
+    filename
+        .chars()
+        .map(|c| if c.is_ascii_graphic() && c != '"' && c != '\\' || c == ' ' { c } else { '_' })
+        .collect()
+}
+
+/// GET /documents/{id}/shares — admin only
+pub async fn list_shares(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(document_id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let shares = DocumentShareService::list(&state.db, &tenant, document_id).await?;
+    Ok(Json(json!(shares)))
+}
+
+/// POST /documents/{id}/shares — admin only; the link (and its token) is only
+/// returned in this response
+pub async fn create_share(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    headers: HeaderMap,
+    Path(document_id): Path<Uuid>,
+    ValidJson(body): ValidJson<CreateDocumentShareRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&user)?;
+    let (share, token) = DocumentShareService::create(&state.db, &tenant, document_id, user.user_id, &body).await?;
+    audit::log(
+        state.db.clone(),
+        &tenant,
+        audit_entry(&user, "document.share.create", document_id, share.recipient.clone(), client_ip(&headers)),
+    );
+    let url = share_url(&state.config.app_base_url, &tenant, &token);
+    Ok((StatusCode::CREATED, Json(json!({ "share": share, "url": url }))))
+}
+
+/// DELETE /documents/{id}/shares/{share_id} — admin only; the link stops working
+/// but stays listed with its access log
+pub async fn revoke_share(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    headers: HeaderMap,
+    Path((document_id, share_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let share = DocumentShareService::revoke(&state.db, &tenant, document_id, share_id).await?;
+    audit::log(
+        state.db.clone(),
+        &tenant,
+        audit_entry(&user, "document.share.revoke", document_id, share.recipient.clone(), client_ip(&headers)),
+    );
+    Ok(Json(json!(share)))
+}
+
+/// GET /documents/{id}/shares/{share_id}/accesses — admin only
+pub async fn list_share_accesses(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path((document_id, share_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let accesses = DocumentShareService::accesses(&state.db, &tenant, document_id, share_id).await?;
+    Ok(Json(json!(accesses)))
+}
+
+/// GET /shared/{token} — public; serves the document of a valid link, on the
+/// garderie's subdomain. Expired and revoked links answer 410.
+pub async fn open_share(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    headers: HeaderMap,
+    Path(token): Path<String>,
+) -> Result<Response<Body>, ApiError> {
+    let keys = KeyRing::from_config(&state.config)?;
+    let user_agent = headers.get(header::USER_AGENT).and_then(|v| v.to_str().ok());
+    let doc = DocumentShareService::open(
+        &state.db_bulk,
+        &tenant,
+        &state.config.media_dir,
+        &keys,
+        state.config.media_max_decrypt_bytes,
+        &token,
+        &client_ip(&headers),
+        user_agent,
+    )
+    .await?;
+
+    let disposition = if doc.download { "attachment" } else { "inline" };
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, doc.content_type)
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("{disposition}; filename=\"{}\"", disposition_name(&doc.filename)),
+        )
+        .header(header::CACHE_CONTROL, "no-store, max-age=0")
+        .header("X-Robots-Tag", "noindex, nofollow")
+        .body(Body::from(doc.bytes))
+        .unwrap())
+}
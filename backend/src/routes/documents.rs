@@ -1,61 +1,68 @@
 use axum::{
     extract::{Multipart, Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
+    response::Response,
     Json,
 };
 use serde_json::{json, Value};
 use uuid::Uuid;
 
 use crate::{
-    db::tenant::schema_name,
-    middleware::tenant::TenantSlug,
-    models::{auth::AuthenticatedUser, document::{DocumentQuery, UpdateDocumentRequest}, user::UserRole},
-    services::documents::DocumentService,
+    db::{tenant::schema_name, tenant_db::TenantDb},
+    error::ApiError,
+    middleware::{api_version::ApiVersion, fields::Fields, precondition, tenant::TenantSlug},
+    models::{
+        auth::AuthenticatedUser,
+        document::{
+            BulkDocumentRequest, Document, DocumentQuery, DocumentSummary, ExpiringQuery, FolderRequest,
+            SignDocumentRequest, UpdateDocumentRequest,
+        },
+        user::UserRole,
+    },
+    services::{
+        audit::{self, AuditEntry},
+        branding::BrandingService,
+        documents::DocumentService,
+        encryption::KeyRing,
+        media_urls::MediaUrlSigner,
+        storage::StorageService,
+    },
     AppState,
 };
+use crate::middleware::validation::ValidJson;
 
-pub async fn upload_document(
-    State(state): State<AppState>,
-    TenantSlug(tenant): TenantSlug,
-    user: AuthenticatedUser,
-    multipart: Multipart,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    let doc = DocumentService::upload(
-        &state.db,
-        &tenant,
-        user.user_id,
-        &state.config.media_dir,
-        &state.config.encryption_master_key,
-        multipart,
-    )
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+fn client_ip(h: &HeaderMap) -> String {
+    h.get("x-real-ip").and_then(|v| v.to_str().ok())
+        .or_else(|| h.get("x-forwarded-for").and_then(|v| v.to_str().ok())
+            .and_then(|s| s.split(',').next()).map(|s| s.trim()))
+        .unwrap_or("unknown")
+        .to_string()
+}
 
-    // Email notifications aux parents concernés (async, non-bloquant, cooldown 1h par parent)
-    if tenant != "demo" {
+fn url_signer(state: &AppState) -> Result<MediaUrlSigner, ApiError> {
+    MediaUrlSigner::from_config(&state.config)
+        .map_err(ApiError::from)
+}
+
+/// Email the parents who can see a document (async, non-blocking, 1h cooldown per parent).
+fn notify_parents(state: &AppState, tenant: &str, uploader_id: Uuid, doc: &Document) {
+    if tenant == "demo" {
+        return;
+    }
     if let Some(email_svc) = state.email.clone() {
         if doc.visibility != "private" {
             let pool = state.db.clone();
-            let tenant_c = tenant.clone();
+            let tenant_c = tenant.to_string();
             let visibility = doc.visibility.clone();
             let group_id = doc.group_id;
             let child_id = doc.child_id;
-            let uploader_id = user.user_id;
             let mut redis = state.redis.clone();
             let base = state.config.app_base_url.clone();
 
-            tokio::spawn(async move {
+            tokio::spawn(TenantDb::scope(tenant_c.clone(), async move {
                 let s = schema_name(&tenant_c);
 
-                let (garderie_name, logo_url): (String, Option<String>) = sqlx::query_as(
-                    "SELECT name, logo_url FROM public.garderies WHERE slug = $1",
-                )
-                .bind(&tenant_c)
-                .fetch_optional(&pool)
-                .await
-                .unwrap_or_default()
-                .unwrap_or_else(|| (tenant_c.clone(), None));
-                let logo_url = logo_url.unwrap_or_default();
+                let (garderie_name, branding) = BrandingService::for_email(&pool, &tenant_c).await;
 
                 let uploader_name: String = sqlx::query_scalar(&format!(
                     "SELECT CONCAT(first_name, ' ', last_name) FROM {s}.users WHERE id = $1"
@@ -131,24 +138,54 @@ pub async fn upload_document(
                     if newly_set.is_some() {
                         let _ = email_svc
                             .send_media_notification(
+                                &tenant_c,
                                 &email,
                                 &name,
                                 &uploader_name,
                                 "un nouveau document",
                                 &app_url,
                                 &garderie_name,
-                                &logo_url,
+                                &branding,
                             )
                             .await;
                     }
                 }
-            });
+            }));
         }
     }
-    } // end if tenant != "demo"
+}
+
+pub async fn upload_document(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    multipart: Multipart,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let keys = KeyRing::from_config(&state.config)?;
+    let signer = url_signer(&state)?;
+    let mut docs = DocumentService::upload(
+        &state.db,
+        &mut state.redis.clone(),
+        &tenant,
+        user.user_id,
+        &state.config.media_dir,
+        &keys,
+        multipart,
+    )
+    .await?;
+
+    StorageService::check_thresholds(state.db.clone(), state.redis.clone(), state.email.clone(), tenant.clone());
+
+    // Email notifications aux parents concernés (le cooldown n'envoie qu'un email par lot)
+    for doc in &docs {
+        notify_parents(&state, &tenant, user.user_id, doc);
+    }
 
-    crate::services::metrics::DOCUMENT_UPLOADS_COUNTER.with_label_values(&[&tenant]).inc();
-    Ok((StatusCode::CREATED, Json(serde_json::to_value(doc).unwrap())))
+    crate::services::metrics::DOCUMENT_UPLOADS_COUNTER
+        .with_label_values(&[&tenant])
+        .inc_by(docs.len() as f64);
+    docs.iter_mut().for_each(|d| signer.sign_document(d));
+    Ok((StatusCode::CREATED, Json(serde_json::to_value(docs).unwrap())))
 }
 
 pub async fn update_document(
@@ -156,162 +193,313 @@ pub async fn update_document(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path(id): Path<Uuid>,
-    Json(req): Json<UpdateDocumentRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(req): ValidJson<UpdateDocumentRequest>,
+) -> Result<Json<Value>, ApiError> {
     let is_staff = !matches!(user.role, UserRole::Parent);
     let doc = match DocumentService::update(&state.db, &tenant, id, user.user_id, is_staff, &req).await {
         Ok(Some(d)) => d,
-        Ok(None) => return Err((StatusCode::NOT_FOUND, Json(json!({ "error": "not found" })))),
-        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))),
+        Ok(None) => return Err(ApiError::from_key(StatusCode::NOT_FOUND, "not_found")),
+        Err(e) => return Err(ApiError::from(e)),
     };
 
     // Notify parents when document becomes visible (visibility != private)
-    if tenant != "demo" {
-    if let Some(email_svc) = state.email.clone() {
-        if doc.visibility != "private" {
-            let pool = state.db.clone();
-            let tenant_c = tenant.clone();
-            let visibility = doc.visibility.clone();
-            let group_id = doc.group_id;
-            let child_id = doc.child_id;
-            let uploader_id = user.user_id;
-            let mut redis = state.redis.clone();
-            let base = state.config.app_base_url.clone();
+    notify_parents(&state, &tenant, user.user_id, &doc);
 
-            tokio::spawn(async move {
-                let s = schema_name(&tenant_c);
+    Ok(Json(serde_json::to_value(doc).unwrap()))
+}
 
-                let (garderie_name, logo_url): (String, Option<String>) = sqlx::query_as(
-                    "SELECT name, logo_url FROM public.garderies WHERE slug = $1",
-                )
-                .bind(&tenant_c)
-                .fetch_optional(&pool)
-                .await
-                .unwrap_or_default()
-                .unwrap_or_else(|| (tenant_c.clone(), None));
-                let logo_url = logo_url.unwrap_or_default();
+pub async fn delete_document(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let is_staff = !matches!(user.role, UserRole::Parent);
+    match DocumentService::delete(&state.db, &tenant, id, user.user_id, is_staff, &state.config.media_dir).await {
+        Ok(true) => {
+            StorageService::invalidate(&mut state.redis.clone(), &tenant).await;
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Ok(false) => Err(ApiError::from_key(StatusCode::NOT_FOUND, "not_found")),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
 
-                let uploader_name: String = sqlx::query_scalar(&format!(
-                    "SELECT CONCAT(first_name, ' ', last_name) FROM {s}.users WHERE id = $1"
-                ))
-                .bind(uploader_id)
-                .fetch_optional(&pool)
-                .await
-                .unwrap_or_default()
-                .unwrap_or_else(|| "Un éducateur".to_string());
+pub async fn list_documents(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(query): Query<DocumentQuery>,
+    version: ApiVersion,
+    fields: Fields,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let is_staff = !matches!(user.role, UserRole::Parent);
+    let signer = url_signer(&state)?;
+    DocumentService::list(&state.db, &tenant, user.user_id, is_staff, &query)
+        .await
+        .map(|mut docs| {
+            docs.iter_mut().for_each(|d| signer.sign_document(d));
+            let updated: Vec<_> = docs.iter().map(|d| d.updated_at).collect();
+            let mut body = if version.slim() {
+                serde_json::to_value(docs.into_iter().map(DocumentSummary::from).collect::<Vec<_>>()).unwrap()
+            } else {
+                serde_json::to_value(docs).unwrap()
+            };
+            fields.select(&mut body);
+            precondition::cached_list(&headers, updated, &body)
+        })
+        .map_err(ApiError::from)
+}
 
-                let app_url = if let Some(idx) = base.find("://") {
-                    let scheme = &base[..idx];
-                    let domain = &base[idx + 3..];
-                    format!("{scheme}://{tenant_c}.{domain}/fr/parent/documents")
-                } else {
-                    format!("https://{tenant_c}.{base}/fr/parent/documents")
-                };
+/// POST /documents/bulk — staff bulk delete, visibility change or move to a folder.
+pub async fn bulk_documents(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(req): ValidJson<BulkDocumentRequest>,
+) -> Result<Json<Value>, ApiError> {
+    if matches!(user.role, UserRole::Parent) {
+        return Err(ApiError::forbidden());
+    }
 
-                let recipients: Vec<(Uuid, String, String)> = match visibility.as_str() {
-                    "public" => sqlx::query_as(&format!(
-                        "SELECT id, email, CONCAT(first_name, ' ', last_name)
-                         FROM {s}.users WHERE role::text = 'parent' AND is_active = TRUE"
-                    ))
-                    .fetch_all(&pool)
-                    .await
-                    .unwrap_or_default(),
+    match DocumentService::bulk(&state.db_bulk, &tenant, &req, &state.config.media_dir).await {
+        Ok(count) => {
+            if req.action == "delete" {
+                StorageService::invalidate(&mut state.redis.clone(), &tenant).await;
+            }
+            Ok(Json(json!({ "affected": count })))
+        }
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
 
-                    "group" => match group_id {
-                        Some(gid) => sqlx::query_as(&format!(
-                            "SELECT DISTINCT u.id, u.email, CONCAT(u.first_name, ' ', u.last_name)
-                             FROM {s}.users u
-                             JOIN {s}.child_parents cp ON cp.user_id = u.id
-                             JOIN {s}.children c ON c.id = cp.child_id
-                             WHERE c.group_id = $1 AND u.is_active = TRUE"
-                        ))
-                        .bind(gid)
-                        .fetch_all(&pool)
-                        .await
-                        .unwrap_or_default(),
-                        None => vec![],
-                    },
+/// GET /documents/expiring — admin dashboard: documents expiring within `days`
+/// (default 30) or already expired, per child.
+pub async fn list_expiring_documents(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(query): Query<ExpiringQuery>,
+) -> Result<Json<Value>, ApiError> {
+    if !matches!(user.role, UserRole::AdminGarderie | UserRole::SuperAdmin) {
+        return Err(ApiError::forbidden());
+    }
 
-                    "child" => match child_id {
-                        Some(cid) => sqlx::query_as(&format!(
-                            "SELECT DISTINCT u.id, u.email, CONCAT(u.first_name, ' ', u.last_name)
-                             FROM {s}.users u
-                             JOIN {s}.child_parents cp ON cp.user_id = u.id
-                             WHERE cp.child_id = $1 AND u.is_active = TRUE"
-                        ))
-                        .bind(cid)
-                        .fetch_all(&pool)
-                        .await
-                        .unwrap_or_default(),
-                        None => vec![],
-                    },
+    let days = query.days.unwrap_or(30).clamp(0, 365);
+    DocumentService::list_expiring(&state.db, &tenant, days)
+        .await
+        .map(|rows| Json(serde_json::to_value(rows).unwrap()))
+        .map_err(ApiError::from)
+}
 
-                    _ => vec![],
-                };
+// ─── Versions ─────────────────────────────────────────────────────────────────
 
-                for (parent_id, email, name) in recipients {
-                    let cooldown_key =
-                        format!("notif_cooldown:{tenant_c}:doc_upload:{parent_id}");
-                    let newly_set: Option<String> = redis::cmd("SET")
-                        .arg(&cooldown_key)
-                        .arg("1")
-                        .arg("NX")
-                        .arg("EX")
-                        .arg(3600u64)
-                        .query_async(&mut redis)
-                        .await
-                        .unwrap_or(None);
+/// POST /documents/{id}/replace — upload a new file; the previous one is kept as a version.
+pub async fn replace_document(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    multipart: Multipart,
+) -> Result<Json<Value>, ApiError> {
+    let is_staff = !matches!(user.role, UserRole::Parent);
+    let keys = KeyRing::from_config(&state.config)?;
+    let signer = url_signer(&state)?;
+    let mut doc = DocumentService::replace(
+        &state.db,
+        &mut state.redis.clone(),
+        &tenant,
+        id,
+        user.user_id,
+        is_staff,
+        &state.config.media_dir,
+        &keys,
+        multipart,
+    )
+    .await?
+    .ok_or_else(|| ApiError::from_key(StatusCode::NOT_FOUND, "not_found"))?;
 
-                    if newly_set.is_some() {
-                        let _ = email_svc
-                            .send_media_notification(
-                                &email,
-                                &name,
-                                &uploader_name,
-                                "un nouveau document",
-                                &app_url,
-                                &garderie_name,
-                                &logo_url,
-                            )
-                            .await;
-                    }
-                }
-            });
-        }
-    }
-    } // end if tenant != "demo"
+    StorageService::invalidate(&mut state.redis.clone(), &tenant).await;
+    StorageService::check_thresholds(state.db.clone(), state.redis.clone(), state.email.clone(), tenant.clone());
+    notify_parents(&state, &tenant, user.user_id, &doc);
 
+    signer.sign_document(&mut doc);
     Ok(Json(serde_json::to_value(doc).unwrap()))
 }
 
-pub async fn delete_document(
+/// GET /documents/{id}/versions — previous files of a document (staff only).
+pub async fn list_document_versions(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
+    if user.role == UserRole::Parent {
+        return Err(ApiError::forbidden());
+    }
+
+    let signer = url_signer(&state)?;
+    DocumentService::list_versions(&state.db, &tenant, id)
+        .await
+        .map(|mut rows| {
+            for row in &mut rows {
+                row.url = Some(signer.sign(&row.storage_path));
+            }
+            Json(serde_json::to_value(rows).unwrap())
+        })
+        .map_err(ApiError::from)
+}
+
+// ─── Folders ──────────────────────────────────────────────────────────────────
+
+/// GET /documents/folders
+pub async fn list_folders(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
     let is_staff = !matches!(user.role, UserRole::Parent);
-    match DocumentService::delete(&state.db, &tenant, id, user.user_id, is_staff, &state.config.media_dir).await {
+    DocumentService::list_folders(&state.db, &tenant, user.user_id, is_staff)
+        .await
+        .map(|rows| Json(serde_json::to_value(rows).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// POST /documents/folders
+pub async fn create_folder(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(req): ValidJson<FolderRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    if user.role == UserRole::Parent {
+        return Err(ApiError::forbidden());
+    }
+
+    DocumentService::create_folder(&state.db, &tenant, user.user_id, &req)
+        .await
+        .map(|folder| (StatusCode::CREATED, Json(serde_json::to_value(folder).unwrap())))
+        .map_err(ApiError::from)
+}
+
+/// PUT /documents/folders/{id} — rename and/or move under another folder.
+pub async fn update_folder(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidJson(req): ValidJson<FolderRequest>,
+) -> Result<Json<Value>, ApiError> {
+    if user.role == UserRole::Parent {
+        return Err(ApiError::forbidden());
+    }
+
+    match DocumentService::update_folder(&state.db, &tenant, id, &req).await {
+        Ok(Some(folder)) => Ok(Json(serde_json::to_value(folder).unwrap())),
+        Ok(None) => Err(ApiError::from_key(StatusCode::NOT_FOUND, "not_found")),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+/// DELETE /documents/folders/{id} — sub-folders go with it, documents move back to the root.
+pub async fn delete_folder(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    if user.role == UserRole::Parent {
+        return Err(ApiError::forbidden());
+    }
+
+    match DocumentService::delete_folder(&state.db, &tenant, id).await {
         Ok(true) => Ok(StatusCode::NO_CONTENT),
-        Ok(false) => Err((StatusCode::NOT_FOUND, Json(json!({ "error": "not found" })))),
-        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))),
+        Ok(false) => Err(ApiError::from_key(StatusCode::NOT_FOUND, "not_found")),
+        Err(e) => Err(ApiError::from(e)),
     }
 }
 
-pub async fn list_documents(
+// ─── E-signatures ─────────────────────────────────────────────────────────────
+
+/// POST /documents/{id}/sign — parent acknowledges a document with their typed name.
+pub async fn sign_document(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
+    headers: HeaderMap,
     user: AuthenticatedUser,
-    Query(query): Query<DocumentQuery>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    let is_staff = !matches!(user.role, UserRole::Parent);
-    DocumentService::list(&state.db, &tenant, user.user_id, is_staff, &query)
+    Path(id): Path<Uuid>,
+    ValidJson(body): ValidJson<SignDocumentRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    if user.role != UserRole::Parent {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "Seuls les parents peuvent signer un document"));
+    }
+    if body.typed_name.trim().is_empty() {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Le nom est requis pour signer"));
+    }
+
+    let ip = client_ip(&headers);
+    match DocumentService::sign(&state.db, &tenant, id, user.user_id, &body.typed_name, &ip).await {
+        Ok(Some(signature)) => {
+            audit::log(state.db.clone(), &tenant, AuditEntry {
+                user_id:        Some(user.user_id),
+                user_name:      Some(body.typed_name.trim().to_string()),
+                action:         "document.sign".to_string(),
+                resource_type:  Some("document".to_string()),
+                resource_id:    Some(id.to_string()),
+                resource_label: None,
+                ip_address:     ip,
+            });
+            Ok((StatusCode::CREATED, Json(serde_json::to_value(signature).unwrap())))
+        }
+        Ok(None) => Err(ApiError::from_key(StatusCode::NOT_FOUND, "not_found")),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// GET /documents/{id}/signatures — staff view of who signed and who is still missing.
+pub async fn list_document_signatures(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    if user.role == UserRole::Parent {
+        return Err(ApiError::forbidden());
+    }
+
+    let signed = DocumentService::list_signatures(&state.db, &tenant, id)
+        .await?;
+    let outstanding = DocumentService::list_outstanding_signatures(&state.db, &tenant, Some(id))
+        .await?;
+
+    Ok(Json(json!({ "signed": signed, "outstanding": outstanding })))
+}
+
+/// GET /documents/signatures/outstanding — every unsigned (document, parent) pair.
+pub async fn list_outstanding_signatures(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    if user.role == UserRole::Parent {
+        return Err(ApiError::forbidden());
+    }
+
+    DocumentService::list_outstanding_signatures(&state.db, &tenant, None)
         .await
-        .map(|docs| Json(serde_json::to_value(docs).unwrap()))
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })
+        .map(|rows| Json(serde_json::to_value(rows).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// GET /documents/signatures/mine — signatures of the current parent.
+pub async fn list_my_signatures(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    DocumentService::list_signatures_for_user(&state.db, &tenant, user.user_id)
+        .await
+        .map(|rows| Json(serde_json::to_value(rows).unwrap()))
+        .map_err(ApiError::from)
 }
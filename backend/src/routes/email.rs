@@ -1,25 +1,31 @@
-use axum::{extract::State, http::StatusCode, Json};
+use axum::{extract::State, Json};
 use serde_json::{json, Value};
 
 use crate::{
     db::tenant::schema_name,
+    error::ApiError,
     middleware::tenant::TenantSlug,
-    models::{auth::AuthenticatedUser, user::UserRole, user::SendEmailRequest},
+    models::{
+        auth::AuthenticatedUser, email_template::TEMPLATE_ANNOUNCEMENT, message_draft::ParentRecipient,
+        user::UserRole, user::SendEmailRequest,
+    },
+    services::{
+        branding::BrandingService, email_templates::EmailTemplateService, email_tracking::EmailTrackingService,
+        message_drafts::recipient_cols,
+    },
     AppState,
 };
+use crate::middleware::validation::ValidJson;
 
 pub async fn send_to_parents(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-    Json(body): Json<SendEmailRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<SendEmailRequest>,
+) -> Result<Json<Value>, ApiError> {
     // Only admin_garderie and educateur may send emails
     if user.role == UserRole::Parent {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(json!({ "error": "Accès refusé" })),
-        ));
+        return Err(ApiError::forbidden());
     }
 
     let email_svc = match state.email.as_deref() {
@@ -31,62 +37,45 @@ pub async fn send_to_parents(
     };
 
     let schema = schema_name(&tenant);
+    let cols = recipient_cols(&schema, "");
 
     // Fetch recipients
-    let recipients: Vec<(String, String)> = if let Some(rid) = body.recipient_id {
+    let recipients: Vec<ParentRecipient> = if let Some(rid) = body.recipient_id {
         sqlx::query_as(&format!(
-            "SELECT email, first_name || ' ' || last_name
-             FROM {schema}.users
-             WHERE id = $1 AND role::TEXT = 'parent' AND is_active = TRUE"
+            "SELECT {cols}
+             FROM {schema}.users u
+             WHERE u.id = $1 AND u.role::TEXT = 'parent' AND u.is_active = TRUE"
         ))
         .bind(rid)
         .fetch_all(&state.db)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })?
+        .await?
     } else {
         sqlx::query_as(&format!(
-            "SELECT email, first_name || ' ' || last_name
-             FROM {schema}.users
-             WHERE role::TEXT = 'parent' AND is_active = TRUE"
+            "SELECT {cols}
+             FROM {schema}.users u
+             WHERE u.role::TEXT = 'parent' AND u.is_active = TRUE"
         ))
         .fetch_all(&state.db)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })?
+        .await?
     };
 
     if recipients.is_empty() {
         return Ok(Json(json!({ "message": "Aucun destinataire trouvé" })));
     }
 
-    let (garderie_name, logo_url): (String, Option<String>) = sqlx::query_as(
-        "SELECT name, logo_url FROM public.garderies WHERE slug = $1"
-    )
-    .bind(&tenant)
-    .fetch_optional(&state.db)
-    .await
-    .ok()
-    .flatten()
-    .unwrap_or_else(|| (tenant.clone(), None));
-    let logo_url = logo_url.unwrap_or_default();
+    let (garderie_name, branding) = BrandingService::for_email(&state.db, &tenant).await;
+    let templates = EmailTemplateService::overrides_for(&state.db, &tenant, TEMPLATE_ANNOUNCEMENT).await;
+    let campaign =
+        EmailTrackingService::start(&state.db, &tenant, &state.config.app_base_url, &body.subject, user.user_id, None)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Email tracking unavailable for {tenant}: {e}");
+                None
+            });
 
     email_svc
-        .send_to_parents(recipients, &body.subject, &body.body, &garderie_name, &logo_url)
+        .send_to_parents(&tenant, recipients, &body.subject, &body.body, &garderie_name, &branding, &templates, campaign)
         .await
         .map(|_| Json(json!({ "message": "Emails envoyés avec succès" })))
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })
+        .map_err(ApiError::from)
 }
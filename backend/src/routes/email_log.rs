@@ -0,0 +1,67 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    error::ApiError,
+    middleware::tenant::TenantSlug,
+    models::{auth::AuthenticatedUser, email_log::EmailLogQuery, user::UserRole},
+    routes::auth::real_client_ip,
+    services::email_log::EmailLogService,
+    AppState,
+};
+
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
+        _ => Err(ApiError::forbidden()),
+    }
+}
+
+/// GET /email-log — admin only; filters: recipient, template, status, from, to
+pub async fn list(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(q): Query<EmailLogQuery>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let (entries, total) = EmailLogService::list(&state.db, &tenant, &q)
+        .await?;
+    Ok(Json(json!({
+        "entries": entries,
+        "total":   total,
+        "page":    q.page.unwrap_or(1).max(1),
+        "limit":   q.limit.unwrap_or(50).clamp(1, 200),
+    })))
+}
+
+/// POST /email-log/{id}/resend — admin only; sends the logged email again to its recipient
+pub async fn resend(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    require_admin(&user)?;
+    let Some(email_svc) = state.email.as_deref() else {
+        return Err(ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "Courriel non configuré"));
+    };
+    email_svc.resend(&tenant, id).await?;
+
+    crate::services::audit::log(state.db.clone(), &tenant, crate::services::audit::AuditEntry {
+        user_id:        Some(user.user_id),
+        user_name:      None,
+        action:         "email.resend".to_string(),
+        resource_type:  Some("email_log".to_string()),
+        resource_id:    Some(id.to_string()),
+        resource_label: None,
+        ip_address:     real_client_ip(&headers),
+    });
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,172 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    error::ApiError,
+    middleware::tenant::TenantSlug,
+    models::{
+        auth::AuthenticatedUser,
+        email_template::{
+            CreateEmailTemplate, EmailTemplate, PreviewEmailTemplate, UpdateEmailTemplate, TEMPLATE_INVITATION,
+            TEMPLATE_KINDS, TEMPLATE_LOCALES,
+        },
+        user::UserRole,
+    },
+    services::{
+        auth::build_tenant_invite_url,
+        branding::BrandingService,
+        email::EmailService,
+        email_i18n::Locale,
+        email_templates::{builtin, parse_locale, validate, EmailTemplateService, TemplateError},
+    },
+    AppState,
+};
+use crate::middleware::validation::ValidJson;
+
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
+        _ => Err(ApiError::forbidden()),
+    }
+}
+
+fn not_found() -> ApiError {
+    ApiError::not_found("Modèle introuvable")
+}
+
+/// GET /email-templates — the garderie's overrides, plus each email's placeholders and built-in wording
+pub async fn list_templates(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let templates = EmailTemplateService::list(&state.db, &tenant)
+        .await?;
+
+    let kinds: Vec<Value> = TEMPLATE_KINDS
+        .iter()
+        .map(|(kind, placeholders)| {
+            let defaults: serde_json::Map<String, Value> = TEMPLATE_LOCALES
+                .iter()
+                .filter_map(|tag| {
+                    let (subject, body) = builtin(kind, Locale::from_tag(tag))?;
+                    Some((tag.to_string(), json!({ "subject": subject, "body": body })))
+                })
+                .collect();
+            json!({ "kind": kind, "placeholders": placeholders, "defaults": defaults })
+        })
+        .collect();
+
+    Ok(Json(json!({ "templates": templates, "kinds": kinds })))
+}
+
+/// POST /email-templates — admin only
+pub async fn create_template(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<CreateEmailTemplate>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&user)?;
+    EmailTemplateService::create(&state.db, &tenant, &body, user.user_id)
+        .await
+        .map(|t| (StatusCode::CREATED, Json(serde_json::to_value(t).unwrap())))
+        .map_err(ApiError::from)
+}
+
+/// PUT /email-templates/{id} — admin only
+pub async fn update_template(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidJson(body): ValidJson<UpdateEmailTemplate>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let existing = EmailTemplateService::get(&state.db, &tenant, id)
+        .await?
+        .ok_or_else(not_found)?;
+    EmailTemplateService::update(&state.db, &tenant, &existing, &body, user.user_id)
+        .await
+        .map(|t| Json(serde_json::to_value(t).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// DELETE /email-templates/{id} — admin only; the built-in wording applies again
+pub async fn delete_template(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    require_admin(&user)?;
+    match EmailTemplateService::delete(&state.db, &tenant, id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(not_found()),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+/// POST /email-templates/preview — render a draft, the saved template or the built-in
+/// email with sample values, exactly as it would be sent
+pub async fn preview_template(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<PreviewEmailTemplate>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let tag = parse_locale(body.locale.as_deref())?;
+    let locale = Locale::from_tag(&tag);
+    let (default_subject, default_body) = builtin(&body.kind, locale).ok_or_else(|| {
+        ApiError::from(TemplateError::Invalid(format!("Type d'email inconnu : {}", body.kind)))
+    })?;
+
+    let saved = EmailTemplateService::find(&state.db, &tenant, &body.kind, &tag)
+        .await?;
+    let template = if body.subject.is_some() || body.body.is_some() {
+        let subject = body
+            .subject
+            .or_else(|| saved.as_ref().map(|t| t.subject.clone()))
+            .unwrap_or(default_subject);
+        let text = body
+            .body
+            .or_else(|| saved.as_ref().map(|t| t.body.clone()))
+            .unwrap_or(default_body);
+        validate(&body.kind, &subject, &text)?;
+        let now = Utc::now();
+        Some(EmailTemplate {
+            id: Uuid::nil(),
+            kind: body.kind.clone(),
+            locale: tag,
+            subject,
+            body: text,
+            updated_by: Some(user.user_id),
+            created_at: now,
+            updated_at: now,
+        })
+    } else {
+        saved
+    };
+
+    let (garderie_name, branding) = BrandingService::for_email(&state.db, &tenant).await;
+    let rendered = if body.kind == TEMPLATE_INVITATION {
+        let invite_url = build_tenant_invite_url(&state.config.app_base_url, &tenant, "apercu");
+        EmailService::render_invitation(&invite_url, &garderie_name, "parent", &branding, locale, template.as_ref())
+    } else {
+        let (name, subject, message) = match locale {
+            Locale::Fr => ("Marie Tremblay", "Sortie au parc jeudi", "Pensez à prévoir des vêtements adaptés à la météo."),
+            Locale::En => ("Mary Smith", "Park outing on Thursday", "Please dress your child for the weather."),
+        };
+        EmailService::render_announcement(name, subject, message, &garderie_name, &branding, locale, template.as_ref())
+    };
+
+    Ok(Json(serde_json::to_value(rendered).unwrap()))
+}
@@ -0,0 +1,97 @@
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, Response, StatusCode},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    error::ApiError,
+    middleware::tenant::{is_valid_slug, TenantSlug},
+    models::{auth::AuthenticatedUser, user::UserRole},
+    services::email_tracking::{EmailTrackingService, PIXEL_GIF},
+    AppState,
+};
+
+fn require_staff(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::Parent => Err(ApiError::forbidden()),
+        _ => Ok(()),
+    }
+}
+
+/// GET /t/{slug}/o/{token} — public; open pixel of a tracked announcement
+pub async fn open(State(state): State<AppState>, Path((slug, token)): Path<(String, Uuid)>) -> Response<Body> {
+    if is_valid_slug(&slug) {
+        if let Err(e) = EmailTrackingService::record_open(&state.db, &slug, token).await {
+            tracing::debug!("Email open not recorded for {slug}: {e}");
+        }
+    }
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "image/gif")
+        .header(header::CACHE_CONTROL, "no-store, max-age=0")
+        .body(Body::from(PIXEL_GIF))
+        .unwrap()
+}
+
+/// GET /t/{slug}/c/{token}/{link_id} — public; redirects to the link of a tracked announcement
+pub async fn click(
+    State(state): State<AppState>,
+    Path((slug, token, link_id)): Path<(String, Uuid, Uuid)>,
+) -> Result<Response<Body>, StatusCode> {
+    if !is_valid_slug(&slug) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let url = EmailTrackingService::record_click(&state.db, &slug, token, link_id)
+        .await
+        .map_err(|e| {
+            tracing::debug!("Email click not recorded for {slug}: {e}");
+            StatusCode::NOT_FOUND
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Response::builder()
+        .status(StatusCode::FOUND)
+        .header(header::LOCATION, url)
+        .header(header::CACHE_CONTROL, "no-store")
+        .body(Body::empty())
+        .unwrap())
+}
+
+#[derive(Deserialize)]
+pub struct CampaignListQuery {
+    pub limit: Option<i64>,
+}
+
+/// GET /email-campaigns — staff only; open and click totals of tracked announcements
+pub async fn list(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(q): Query<CampaignListQuery>,
+) -> Result<Json<Value>, ApiError> {
+    require_staff(&user)?;
+    let enabled = EmailTrackingService::is_enabled(&state.db, &tenant)
+        .await?;
+    let campaigns = EmailTrackingService::list(&state.db, &tenant, q.limit.unwrap_or(50).clamp(1, 200))
+        .await?;
+    Ok(Json(json!({ "tracking_enabled": enabled, "campaigns": campaigns })))
+}
+
+/// GET /email-campaigns/{id} — staff only; totals with the clicks of each link
+pub async fn get(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    require_staff(&user)?;
+    let (campaign, links) = EmailTrackingService::get(&state.db, &tenant, id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Envoi introuvable"))?;
+    Ok(Json(json!({ "campaign": campaign, "links": links })))
+}
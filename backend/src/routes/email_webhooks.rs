@@ -0,0 +1,61 @@
+use anyhow::Context;
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    error::ApiError,
+    services::email_suppressions::{
+        is_sns_url, parse_event, EmailProvider, EmailSuppressionService, EmailWebhookError, WebhookEvent,
+    },
+    AppState,
+};
+
+#[derive(Deserialize)]
+pub struct WebhookQuery {
+    pub token: Option<String>,
+}
+
+/// POST /webhooks/email/{provider}?token=… — public; bounce and complaint events of
+/// the email provider (ses, mailgun, postmark). The body is read raw because SNS
+/// posts JSON as text/plain.
+pub async fn receive(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    Query(q): Query<WebhookQuery>,
+    body: Bytes,
+) -> Result<StatusCode, ApiError> {
+    let Some(expected) = state.config.email_webhook_token.as_deref() else {
+        return Err(ApiError::from_key(StatusCode::NOT_FOUND, "not_found"));
+    };
+    if q.token.as_deref() != Some(expected) {
+        return Err(ApiError::new(StatusCode::UNAUTHORIZED, "Jeton invalide"));
+    }
+
+    let provider = EmailProvider::parse(&provider)?;
+    let payload: Value = serde_json::from_slice(&body)
+        .map_err(|_| ApiError::from(EmailWebhookError::InvalidPayload))?;
+
+    match parse_event(provider, &payload)? {
+        WebhookEvent::Failures(failures) => {
+            EmailSuppressionService::record(&state.db, provider, &failures)
+                .await?;
+        }
+        WebhookEvent::ConfirmSubscription(url) => {
+            if !is_sns_url(&url) {
+                return Err(ApiError::from(EmailWebhookError::InvalidPayload));
+            }
+            reqwest::get(&url)
+                .await
+                .and_then(|r| r.error_for_status())
+                .context("confirm SNS subscription")?;
+            tracing::info!("SNS subscription of the email webhook confirmed");
+        }
+        WebhookEvent::Ignored => {}
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
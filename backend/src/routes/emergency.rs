@@ -0,0 +1,122 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    db::tenant_db::TenantDb,
+    error::ApiError,
+    middleware::{tenant::TenantSlug, validation::ValidJson},
+    models::{auth::AuthenticatedUser, emergency::EmergencyRequest, user::UserRole},
+    services::{
+        audit::{self, AuditEntry},
+        emergency::{Channels, EmergencyService},
+        realtime::RealtimeService,
+        unread::UnreadService,
+    },
+    AppState,
+};
+
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
+        _ => Err(ApiError::forbidden()),
+    }
+}
+
+pub fn channels(state: &AppState) -> Channels {
+    Channels {
+        email: state.email.clone(),
+        sms: state.sms.clone(),
+        notifications: state.notifications.clone(),
+        app_base_url: state.config.app_base_url.clone(),
+    }
+}
+
+/// POST /messages/emergency — admins only. Posts the message in the broadcast thread
+/// and sends it to every active parent by email, push and SMS at once, regardless of
+/// muted threads and SMS cooldowns. Deliveries are queued, sent right away and
+/// retried by the emergency scheduler.
+pub async fn send_emergency(
+    State(mut state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<EmergencyRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&user)?;
+    let (id, msg) = EmergencyService::create(&state.db, &tenant, user.user_id, &body).await?;
+
+    let payload = serde_json::to_string(&msg).unwrap_or_default();
+    RealtimeService::publish_to_tenant(&mut state.redis, &tenant, &payload).await;
+    match UnreadService::delivery(&state.db, &tenant, "broadcast", user.user_id, None, None).await {
+        Ok(Some(delivery)) => UnreadService::message_sent(&mut state.redis, &tenant, &delivery).await,
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Unread counters not updated for emergency {id} in '{tenant}': {e}"),
+    }
+
+    audit::log(state.db.clone(), &tenant, AuditEntry {
+        user_id:        Some(user.user_id),
+        user_name:      None,
+        action:         "message.emergency".to_string(),
+        resource_type:  Some("emergency".to_string()),
+        resource_id:    Some(id.to_string()),
+        resource_label: Some(body.subject.clone()),
+        ip_address:     "unknown".to_string(),
+    });
+
+    let pool = state.db.clone();
+    let channels = channels(&state);
+    let slug = tenant.clone();
+    tokio::spawn(TenantDb::scope(slug.clone(), async move {
+        if let Err(e) = EmergencyService::process_due(&pool, &slug, &channels).await {
+            tracing::warn!("Emergency {id} in '{slug}' not fully sent, the scheduler will retry: {e}");
+        }
+    }));
+
+    let detail = EmergencyService::get(&state.db, &tenant, id, user.user_id).await?;
+    Ok((StatusCode::CREATED, Json(json!(detail))))
+}
+
+/// GET /messages/emergency — admins see every broadcast, parents the ones sent to them
+pub async fn list_emergencies(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    let parent_only = match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => false,
+        UserRole::Parent => true,
+        _ => return Err(ApiError::forbidden()),
+    };
+    let broadcasts = EmergencyService::list(&state.db, &tenant, user.user_id, parent_only).await?;
+    Ok(Json(json!(broadcasts)))
+}
+
+/// GET /messages/emergency/{id} — admins only; per-parent delivery and acknowledgement
+pub async fn get_emergency(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let detail = EmergencyService::get(&state.db, &tenant, id, user.user_id).await?;
+    Ok(Json(json!(detail)))
+}
+
+/// POST /messages/emergency/{id}/acknowledge — a parent confirms they received the broadcast
+pub async fn acknowledge_emergency(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    if user.role != UserRole::Parent {
+        return Err(ApiError::forbidden());
+    }
+    EmergencyService::acknowledge(&state.db, &tenant, id, user.user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
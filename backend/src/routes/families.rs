@@ -0,0 +1,164 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    error::ApiError,
+    middleware::{tenant::TenantSlug, validation::ValidJson},
+    models::{
+        auth::AuthenticatedUser,
+        family::{CreateFamilyRequest, FamilyMessageRequest, SetFamilyChildrenRequest, UpdateFamilyRequest},
+        message::{SendToParentsRequest, SendToParentsScope},
+        user::UserRole,
+    },
+    services::{
+        audit::{self, AuditEntry},
+        families::{FamilyError, FamilyService},
+    },
+    AppState,
+};
+
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
+        _ => Err(ApiError::forbidden()),
+    }
+}
+
+fn audit_family(state: &AppState, tenant: &str, user: &AuthenticatedUser, action: &str, id: Uuid, label: &str) {
+    audit::log(state.db.clone(), tenant, AuditEntry {
+        user_id:        Some(user.user_id),
+        user_name:      None,
+        action:         action.to_string(),
+        resource_type:  Some("family".to_string()),
+        resource_id:    Some(id.to_string()),
+        resource_label: Some(label.to_string()),
+        ip_address:     "unknown".to_string(),
+    });
+}
+
+/// GET /families — admins see every family, parents their own
+pub async fn list_families(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    let parent_id = match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => None,
+        UserRole::Parent => Some(user.user_id),
+        _ => return Err(ApiError::forbidden()),
+    };
+    let families = FamilyService::list(&state.db, &tenant, parent_id).await?;
+    Ok(Json(json!(families)))
+}
+
+/// GET /families/suggestions — admins only; children without a family who share a parent
+pub async fn family_suggestions(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let suggestions = FamilyService::suggestions(&state.db, &tenant).await?;
+    Ok(Json(json!(suggestions)))
+}
+
+/// GET /families/{id} — the family with its children and guardians; parents only their own
+pub async fn get_family(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => {}
+        UserRole::Parent => {
+            if !FamilyService::is_guardian(&state.db, &tenant, id, user.user_id).await? {
+                return Err(FamilyError::NotFound.into());
+            }
+        }
+        _ => return Err(ApiError::forbidden()),
+    }
+    let family = FamilyService::get(&state.db, &tenant, id).await?;
+    Ok(Json(json!(family)))
+}
+
+/// POST /families — admins only
+pub async fn create_family(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<CreateFamilyRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&user)?;
+    let family = FamilyService::create(&state.db, &tenant, &body).await?;
+    audit_family(&state, &tenant, &user, "family.create", family.family.id, &family.family.name);
+    Ok((StatusCode::CREATED, Json(json!(family))))
+}
+
+/// PUT /families/{id} — admins only
+pub async fn update_family(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidJson(body): ValidJson<UpdateFamilyRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let family = FamilyService::update(&state.db, &tenant, id, &body).await?;
+    Ok(Json(json!(family)))
+}
+
+/// DELETE /families/{id} — admins only; the children stay enrolled
+pub async fn delete_family(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    require_admin(&user)?;
+    FamilyService::delete(&state.db, &tenant, id).await?;
+    audit_family(&state, &tenant, &user, "family.delete", id, &id.to_string());
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PUT /families/{id}/children — admins only; replaces the family's children
+pub async fn set_family_children(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidJson(body): ValidJson<SetFamilyChildrenRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let family = FamilyService::set_children(&state.db, &tenant, id, &body.child_ids).await?;
+    Ok(Json(json!(family)))
+}
+
+/// POST /families/{id}/send-to-parents — staff; shortcut for POST /messages/send-to-parents
+/// with the `family_parents` scope
+pub async fn send_to_family(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidJson(body): ValidJson<FamilyMessageRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    if user.role == UserRole::Parent {
+        return Err(ApiError::forbidden());
+    }
+    FamilyService::get(&state.db, &tenant, id).await?;
+    let request = SendToParentsRequest {
+        subject: body.subject,
+        content: body.content,
+        scope: SendToParentsScope::FamilyParents,
+        child_id: None,
+        group_id: None,
+        family_id: Some(id),
+    };
+    crate::routes::messages::send_to_parents(State(state), TenantSlug(tenant), user, ValidJson(request)).await
+}
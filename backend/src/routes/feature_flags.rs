@@ -0,0 +1,60 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde_json::Value;
+
+use crate::{
+    error::ApiError,
+    middleware::super_admin::SuperAdminAuth,
+    models::feature_flag::{FeatureFlagScope, SetFeatureFlagRequest},
+    services::feature_flags::{FeatureFlagService, MAINTENANCE_MODE},
+    AppState,
+};
+use crate::middleware::validation::ValidJson;
+
+/// GET /super-admin/feature-flags — platform-wide values and garderie overrides
+pub async fn list_flags(
+    State(state): State<AppState>,
+    _auth: SuperAdminAuth,
+) -> Result<Json<Value>, ApiError> {
+    FeatureFlagService::all(&state.db)
+        .await
+        .map(|flags| Json(serde_json::to_value(flags).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// PUT /super-admin/feature-flags/{key} — set the platform-wide value, or the override
+/// of one garderie when `slug` is given
+pub async fn set_flag(
+    State(state): State<AppState>,
+    auth: SuperAdminAuth,
+    Path(key): Path<String>,
+    ValidJson(body): ValidJson<SetFeatureFlagRequest>,
+) -> Result<StatusCode, ApiError> {
+    FeatureFlagService::set(&state.db, &key, body.slug.as_deref(), body.enabled, auth.operator.as_deref())
+        .await?;
+    if key == MAINTENANCE_MODE {
+        tracing::warn!(
+            "Maintenance mode {} by {}",
+            if body.enabled { "enabled" } else { "disabled" },
+            auth.operator.as_deref().unwrap_or("super-admin key")
+        );
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /super-admin/feature-flags/{key}?slug= — remove the platform-wide value, or a
+/// garderie override (which then follows the platform-wide value again)
+pub async fn delete_flag(
+    State(state): State<AppState>,
+    _auth: SuperAdminAuth,
+    Path(key): Path<String>,
+    Query(scope): Query<FeatureFlagScope>,
+) -> Result<StatusCode, ApiError> {
+    FeatureFlagService::remove(&state.db, &key, scope.slug.as_deref())
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(ApiError::from)
+}
@@ -9,6 +9,7 @@ use redis::AsyncCommands;
 use serde_json::json;
 
 use crate::{
+    error::ApiError,
     middleware::auth::decode_access_token,
     models::user::UserRole,
     AppState,
@@ -31,7 +32,7 @@ fn is_authorized(headers: &HeaderMap, state: &AppState) -> bool {
         .and_then(|v| v.to_str().ok())
         .and_then(|v| v.strip_prefix("Bearer "))
     {
-        if let Ok(user) = decode_access_token(bearer, &state.config.jwt_secret) {
+        if let Ok(user) = decode_access_token(bearer, &state.jwt_keys) {
             return user.role == UserRole::SuperAdmin || user.role == UserRole::AdminGarderie;
         }
     }
@@ -47,10 +48,7 @@ pub async fn grafana_access(
     headers: HeaderMap,
 ) -> impl IntoResponse {
     if !is_authorized(&headers, &state) {
-        return (
-            StatusCode::FORBIDDEN,
-            Json(json!({ "error": "Accès refusé" })),
-        )
+        return ApiError::forbidden()
             .into_response();
     }
 
@@ -69,10 +67,7 @@ pub async fn grafana_access(
         .await
     {
         tracing::warn!("grafana_access: Redis error: {}", e);
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": "Erreur interne" })),
-        )
+        return ApiError::internal()
             .into_response();
     }
 
@@ -0,0 +1,20 @@
+use async_graphql::{BatchRequest, BatchResponse};
+use axum::{extract::State, Json};
+
+use crate::{
+    graphql::{self, GqlContext},
+    middleware::tenant::TenantSlug,
+    models::auth::AuthenticatedUser,
+    AppState,
+};
+
+/// POST /graphql — accepts a single query or a batch (JSON array) of queries.
+pub async fn graphql(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Json(request): Json<BatchRequest>,
+) -> Json<BatchResponse> {
+    let ctx = GqlContext { state, tenant, user };
+    Json(graphql::schema().execute_batch(request.data(ctx)).await)
+}
@@ -1,67 +1,75 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
+use chrono::Local;
 use serde::Deserialize;
 use serde_json::{json, Value};
 use uuid::Uuid;
 
 use crate::{
+    db::tenant_db::TenantDb,
+    error::ApiError,
     middleware::tenant::TenantSlug,
     models::{
         auth::AuthenticatedUser,
-        group::{CreateGroupRequest, UpdateGroupRequest},
+        group::{CreateGroupRequest, GroupListQuery, RolloverRequest, UpdateGroupRequest},
         user::UserRole,
     },
-    services::groups::GroupService,
+    services::{
+        audit::{self, AuditEntry},
+        groups::GroupService,
+    },
     AppState,
 };
+use crate::models::limits::MAX_BULK_IDS;
+use validator::Validate;
+use crate::middleware::validation::ValidJson;
 
-fn require_admin(user: &AuthenticatedUser) -> Option<(StatusCode, Json<Value>)> {
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
     match user.role {
-        UserRole::AdminGarderie | UserRole::SuperAdmin => None,
-        _ => Some((StatusCode::FORBIDDEN, Json(json!({ "error": "Accès refusé" })))),
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
+        _ => Err(ApiError::forbidden()),
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Validate)]
 pub struct SetChildrenRequest {
+    #[validate(length(max = MAX_BULK_IDS))]
     pub child_ids: Vec<Uuid>,
 }
 
+#[derive(Deserialize, Validate)]
+pub struct SetEducatorsRequest {
+    #[validate(length(max = MAX_BULK_IDS))]
+    pub user_ids: Vec<Uuid>,
+}
+
+/// GET /groups[?include_archived=true] — archived groups are left out of pickers by default
 pub async fn list_groups(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     _user: AuthenticatedUser,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    GroupService::list(&state.db, &tenant)
+    Query(q): Query<GroupListQuery>,
+) -> Result<Json<Value>, ApiError> {
+    GroupService::list(&state.db, &tenant, q.include_archived)
         .await
         .map(|groups| Json(serde_json::to_value(groups).unwrap()))
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })
+        .map_err(ApiError::from)
 }
 
 pub async fn create_group(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-    Json(body): Json<CreateGroupRequest>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    if let Some(err) = require_admin(&user) { return Err(err); }
+    ValidJson(body): ValidJson<CreateGroupRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&user)?;
     GroupService::create(&state.db, &tenant, &body)
         .await
         .map(|group| (StatusCode::CREATED, Json(serde_json::to_value(group).unwrap())))
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })
+        .map_err(ApiError::from)
 }
 
 pub async fn update_group(
@@ -69,18 +77,13 @@ pub async fn update_group(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path(id): Path<Uuid>,
-    Json(body): Json<UpdateGroupRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    if let Some(err) = require_admin(&user) { return Err(err); }
+    ValidJson(body): ValidJson<UpdateGroupRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
     GroupService::update(&state.db, &tenant, id, &body)
         .await
         .map(|group| Json(serde_json::to_value(group).unwrap()))
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })
+        .map_err(ApiError::from)
 }
 
 pub async fn set_group_children(
@@ -88,34 +91,128 @@ pub async fn set_group_children(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path(id): Path<Uuid>,
-    Json(body): Json<SetChildrenRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    if let Some(err) = require_admin(&user) { return Err(err); }
-    GroupService::set_children(&state.db, &tenant, id, &body.child_ids)
+    ValidJson(body): ValidJson<SetChildrenRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    GroupService::set_children(&state.db, &tenant, id, &body.child_ids).await?;
+    Ok(Json(json!({ "message": "Children updated" })))
+}
+
+/// GET /groups/{id}/educators — admins only
+pub async fn list_group_educators(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    GroupService::list_educators(&state.db, &tenant, id)
         .await
-        .map(|_| Json(json!({ "message": "Children updated" })))
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })
+        .map(|educators| Json(serde_json::to_value(educators).unwrap()))
+        .map_err(ApiError::from)
 }
 
-pub async fn delete_group(
+/// PUT /groups/{id}/educators — admins only; replaces the group's educators
+pub async fn set_group_educators(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, Json<Value>)> {
-    if let Some(err) = require_admin(&user) { return Err(err); }
-    GroupService::delete(&state.db, &tenant, id)
+    ValidJson(body): ValidJson<SetEducatorsRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    GroupService::set_educators(&state.db, &tenant, id, &body.user_ids)
         .await
-        .map(|_| StatusCode::NO_CONTENT)
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })
+        .map(|educators| Json(serde_json::to_value(educators).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// DELETE /groups/{id} — admins only; refused (409) once the group has history, archive it instead
+pub async fn delete_group(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    require_admin(&user)?;
+    GroupService::delete(&state.db, &tenant, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /groups/{id}/archive — admins only; the group must have no active children left
+pub async fn archive_group(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let group = GroupService::archive(&state.db, &tenant, id).await?;
+    Ok(Json(json!(group)))
+}
+
+/// POST /groups/{id}/restore — admins only
+pub async fn restore_group(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let group = GroupService::restore(&state.db, &tenant, id).await?;
+    Ok(Json(json!(group)))
+}
+
+/// POST /groups/rollover — admins only; moves the active children between groups at
+/// once (school-year change), by source → target groups or by age bands. `dry_run`
+/// only lists the moves. Parents of the children moved are emailed in the background.
+pub async fn rollover(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<RolloverRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let as_of = body.as_of.unwrap_or_else(|| Local::now().date_naive());
+    let result = GroupService::rollover(&state.db, &tenant, &body, as_of, user.user_id).await?;
+
+    let Some(rollover_id) = result.rollover_id else {
+        return Ok(Json(json!({ "dry_run": body.dry_run, "rollover_id": null, "moves": result.moves })));
+    };
+    audit::log(state.db.clone(), &tenant, AuditEntry {
+        user_id:        Some(user.user_id),
+        user_name:      None,
+        action:         "group.rollover".to_string(),
+        resource_type:  Some("group_rollover".to_string()),
+        resource_id:    Some(rollover_id.to_string()),
+        resource_label: Some(format!("{} enfant(s)", result.moves.len())),
+        ip_address:     "unknown".to_string(),
+    });
+
+    let notify = body.notify_parents.unwrap_or(true) && tenant != "demo";
+    if let (true, Some(email_svc)) = (notify, state.email.clone()) {
+        let pool = state.db.clone();
+        let moves = result.moves.clone();
+        let tenant = tenant.clone();
+        tokio::spawn(TenantDb::scope(tenant.clone(), async move {
+            if let Err(e) = GroupService::notify_rollover(&pool, &email_svc, &tenant, &moves).await {
+                tracing::warn!("Rollover notifications for {tenant} failed: {e}");
+            }
+        }));
+    }
+    Ok(Json(json!({ "dry_run": false, "rollover_id": rollover_id, "moves": result.moves })))
+}
+
+/// GET /children/{id}/group-history — staff only; the groups the child has left
+pub async fn child_group_history(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(child_id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    if user.role == UserRole::Parent {
+        return Err(ApiError::forbidden());
+    }
+    let history = GroupService::child_history(&state.db, &tenant, child_id).await?;
+    Ok(Json(json!(history)))
 }
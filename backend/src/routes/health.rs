@@ -9,9 +9,12 @@ pub async fn health_check(State(state): State<AppState>) -> (StatusCode, Json<Va
             StatusCode::OK,
             Json(json!({ "status": "ok", "db": "connected" })),
         ),
-        Err(e) => (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(json!({ "status": "error", "db": e.to_string() })),
-        ),
+        Err(e) => {
+            tracing::error!("Health check failed: {e}");
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(json!({ "status": "error", "db": "unavailable" })),
+            )
+        }
     }
 }
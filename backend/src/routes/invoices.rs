@@ -0,0 +1,264 @@
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, Response, StatusCode},
+    Json,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    error::ApiError,
+    middleware::tenant::TenantSlug,
+    models::{
+        auth::AuthenticatedUser,
+        invoice::{
+            CreateInvoiceRequest, GenerateInvoicesRequest, Invoice, InvoiceQuery, MarkPaidRequest,
+            UpdateInvoiceRequest,
+        },
+        user::UserRole,
+    },
+    services::{
+        audit::{self, AuditEntry},
+        invoices::{parse_month, InvoiceError, InvoiceService, DEFAULT_DUE_DAYS},
+    },
+    AppState,
+};
+use crate::middleware::validation::ValidJson;
+
+fn client_ip(h: &HeaderMap) -> String {
+    h.get("x-real-ip").and_then(|v| v.to_str().ok())
+        .or_else(|| h.get("x-forwarded-for").and_then(|v| v.to_str().ok())
+            .and_then(|s| s.split(',').next()).map(|s| s.trim()))
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
+        _ => Err(ApiError::forbidden()),
+    }
+}
+
+fn email_unavailable() -> ApiError {
+    ApiError::from_key(StatusCode::SERVICE_UNAVAILABLE, "email_not_configured")
+}
+
+fn audit_invoice(state: &AppState, tenant: &str, user: &AuthenticatedUser, headers: &HeaderMap, action: &str, invoice: &Invoice) {
+    audit::log(state.db.clone(), tenant, AuditEntry {
+        user_id:        Some(user.user_id),
+        user_name:      None,
+        action:         action.to_string(),
+        resource_type:  Some("invoice".to_string()),
+        resource_id:    Some(invoice.id.to_string()),
+        resource_label: Some(format!("{} — {}", invoice.number, invoice.child_name)),
+        ip_address:     client_ip(headers),
+    });
+}
+
+/// The invoice if `user` may read it: admins see all, parents the sent invoices billing their children.
+async fn readable_invoice(
+    state: &AppState,
+    tenant: &str,
+    user: &AuthenticatedUser,
+    id: Uuid,
+) -> Result<Invoice, ApiError> {
+    let invoice = InvoiceService::get(&state.db, tenant, id).await?;
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(invoice),
+        UserRole::Parent if invoice.status != "draft" => {
+            let is_parent = InvoiceService::is_billed_to(&state.db, tenant, id, user.user_id)
+                .await?;
+            if is_parent {
+                Ok(invoice)
+            } else {
+                Err(ApiError::from(InvoiceError::NotFound))
+            }
+        }
+        UserRole::Parent => Err(ApiError::from(InvoiceError::NotFound)),
+        _ => Err(ApiError::forbidden()),
+    }
+}
+
+/// GET /invoices?child_id=&status=&month=YYYY-MM — parents only get their children's sent invoices.
+pub async fn list_invoices(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(query): Query<InvoiceQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let parent_id = match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => None,
+        UserRole::Parent => Some(user.user_id),
+        _ => return Err(ApiError::forbidden()),
+    };
+    InvoiceService::list(&state.db, &tenant, &query, parent_id)
+        .await
+        .map(|invoices| Json(serde_json::to_value(invoices).unwrap()))
+        .map_err(ApiError::from)
+}
+
+pub async fn get_invoice(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    let invoice = readable_invoice(&state, &tenant, &user, id).await?;
+    Ok(Json(serde_json::to_value(invoice).unwrap()))
+}
+
+/// GET /invoices/{id}/pdf
+pub async fn get_invoice_pdf(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response<Body>, ApiError> {
+    let invoice = readable_invoice(&state, &tenant, &user, id).await?;
+    let pdf = InvoiceService::render_pdf(&state.db, &tenant, &invoice)
+        .await?;
+
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/pdf")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("inline; filename=\"facture-{}.pdf\"", invoice.number),
+        )
+        .body(Body::from(pdf))
+        .unwrap();
+
+    Ok(response)
+}
+
+/// POST /invoices — a one-off draft invoice.
+pub async fn create_invoice(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    headers: HeaderMap,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<CreateInvoiceRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&user)?;
+    let invoice = InvoiceService::create(&state.db, &tenant, &body, user.user_id)
+        .await?;
+    audit_invoice(&state, &tenant, &user, &headers, "invoice.create", &invoice);
+    Ok((StatusCode::CREATED, Json(serde_json::to_value(invoice).unwrap())))
+}
+
+/// PUT /invoices/{id} — drafts only.
+pub async fn update_invoice(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidJson(body): ValidJson<UpdateInvoiceRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    InvoiceService::update(&state.db, &tenant, id, &body)
+        .await
+        .map(|invoice| Json(serde_json::to_value(invoice).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// POST /invoices/{id}/send — email the PDF to the child's parents.
+pub async fn send_invoice(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    headers: HeaderMap,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let email = state.email.as_deref().ok_or_else(email_unavailable)?;
+    let recipients = InvoiceService::send(&state.db, email, &tenant, id)
+        .await?;
+    let invoice = InvoiceService::get(&state.db, &tenant, id).await?;
+    audit_invoice(&state, &tenant, &user, &headers, "invoice.send", &invoice);
+    Ok(Json(json!({ "invoice": invoice, "recipients": recipients })))
+}
+
+/// POST /invoices/{id}/mark-paid
+pub async fn mark_invoice_paid(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    headers: HeaderMap,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    body: Option<ValidJson<MarkPaidRequest>>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let body = body.map(|ValidJson(b)| b).unwrap_or_default();
+    let invoice = InvoiceService::mark_paid(&state.db, &tenant, id, &body)
+        .await?;
+    audit_invoice(&state, &tenant, &user, &headers, "invoice.mark_paid", &invoice);
+    Ok(Json(serde_json::to_value(invoice).unwrap()))
+}
+
+/// POST /invoices/{id}/void
+pub async fn void_invoice(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    headers: HeaderMap,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let invoice = InvoiceService::void(&state.db, &tenant, id)
+        .await?;
+    audit_invoice(&state, &tenant, &user, &headers, "invoice.void", &invoice);
+    Ok(Json(serde_json::to_value(invoice).unwrap()))
+}
+
+/// POST /invoices/generate — draft the month's invoices, optionally emailing them.
+pub async fn generate_invoices(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    headers: HeaderMap,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<GenerateInvoicesRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let month = parse_month(&body.month)?;
+    let due_days = body.due_days.unwrap_or(DEFAULT_DUE_DAYS);
+    if !(0..=365).contains(&due_days) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Délai de paiement invalide"));
+    }
+    let email = if body.send {
+        Some(state.email.as_deref().ok_or_else(email_unavailable)?)
+    } else {
+        None
+    };
+
+    let result = InvoiceService::generate_month(&state.db, &tenant, month, body.default_tuition_cents, due_days, user.user_id)
+        .await?;
+
+    let mut not_sent = Vec::new();
+    if let Some(email) = email {
+        for invoice in &result.created {
+            if let Err(e) = InvoiceService::send(&state.db, email, &tenant, invoice.id).await {
+                tracing::warn!("Invoice {} not sent: {e}", invoice.number);
+                not_sent.push(json!({ "id": invoice.id, "number": invoice.number, "error": e.to_string() }));
+            }
+        }
+    }
+
+    audit::log(state.db.clone(), &tenant, AuditEntry {
+        user_id:        Some(user.user_id),
+        user_name:      None,
+        action:         "invoice.generate".to_string(),
+        resource_type:  Some("invoice".to_string()),
+        resource_id:    None,
+        resource_label: Some(format!("{} ({} factures)", body.month, result.created.len())),
+        ip_address:     client_ip(&headers),
+    });
+
+    Ok(Json(json!({
+        "created": result.created,
+        "already_invoiced": result.already_invoiced,
+        "nothing_to_bill": result.nothing_to_bill,
+        "not_sent": not_sent,
+    })))
+}
@@ -1,7 +1,7 @@
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
-    Json,
+    Extension, Json,
 };
 use chrono::NaiveDate;
 use serde::{Deserialize};
@@ -10,15 +10,29 @@ use uuid::Uuid;
 
 use crate::{
     db::tenant::schema_name,
-    middleware::tenant::TenantSlug,
+    error::ApiError,
+    middleware::{
+        precondition::{versioned, IfMatch, Versioned},
+        tenant::TenantSlug,
+    },
     models::{
         auth::AuthenticatedUser,
-        journal::{JournalWeekQuery, UpsertJournalRequest},
+        journal::{
+            AmendJournalRequest, CreateJournalEventRequest, JournalDayQuery, JournalEventFields, JournalWeekQuery,
+            UpsertJournalRequest,
+        },
         user::UserRole,
     },
-    services::journal::JournalService,
+    services::{
+        events::{self, DomainEvent},
+        feature_flags::{FeatureFlags, LIVE_JOURNAL_UPDATES},
+        groups::GroupService,
+        journal::JournalService,
+    },
     AppState,
 };
+use validator::Validate;
+use crate::middleware::validation::ValidJson;
 
 /// GET /journals?child_id=...&week_start=YYYY-MM-DD
 pub async fn get_week(
@@ -26,35 +40,21 @@ pub async fn get_week(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Query(params): Query<JournalWeekQuery>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     // Parents may only read journals for their own children.
     if let UserRole::Parent = user.role {
         let linked =
             JournalService::assert_parent_access(&state.db, &tenant, params.child_id, user.user_id)
-                .await
-                .map_err(|e| {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(json!({ "error": e.to_string() })),
-                    )
-                })?;
+                .await?;
         if !linked {
-            return Err((
-                StatusCode::FORBIDDEN,
-                Json(json!({ "error": "Accès refusé" })),
-            ));
+            return Err(ApiError::forbidden());
         }
     }
 
     JournalService::list_week(&state.db, &tenant, params.child_id, params.week_start)
         .await
         .map(|entries| Json(serde_json::to_value(entries).unwrap()))
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })
+        .map_err(ApiError::from)
 }
 
 /// GET /journals/month?child_id=...&month=YYYY-MM
@@ -64,54 +64,36 @@ pub async fn get_month_summary(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Query(params): Query<MonthSummaryQuery>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     // Parents may only read journals for their own children.
     if let UserRole::Parent = user.role {
         let linked =
             JournalService::assert_parent_access(&state.db, &tenant, params.child_id, user.user_id)
-                .await
-                .map_err(|e| {
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(json!({ "error": e.to_string() })),
-                    )
-                })?;
+                .await?;
         if !linked {
-            return Err((
-                StatusCode::FORBIDDEN,
-                Json(json!({ "error": "Accès refusé" })),
-            ));
+            return Err(ApiError::forbidden());
         }
     }
 
     // Parse month
     let month_parts: Vec<&str> = params.month.split('-').collect();
     if month_parts.len() != 2 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": "Invalid month format. Use YYYY-MM" })),
-        ));
+        return Err(ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_month"));
     }
 
     let year = month_parts[0]
         .parse::<i32>()
-        .map_err(|_| (StatusCode::BAD_REQUEST, Json(json!({ "error": "Invalid year" }))))?;
+        .map_err(|_| ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_year"))?;
     let month = month_parts[1]
         .parse::<u32>()
-        .map_err(|_| (StatusCode::BAD_REQUEST, Json(json!({ "error": "Invalid month" }))))?;
+        .map_err(|_| ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_month"))?;
 
     if month < 1 || month > 12 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": "Month must be 1-12" })),
-        ));
+        return Err(ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_month"));
     }
 
     let start_date = NaiveDate::from_ymd_opt(year, month, 1).ok_or_else(|| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": "Invalid date" })),
-        )
+        ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_date")
     })?;
 
     let end_date = if month == 12 {
@@ -134,13 +116,7 @@ pub async fn get_month_summary(
     .bind(start_date)
     .bind(end_date)
     .fetch_all(&state.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })?;
+    .await?;
 
     let journal_dates: Vec<serde_json::Value> = records
         .into_iter()
@@ -156,92 +132,230 @@ pub struct MonthSummaryQuery {
     pub month: String, // YYYY-MM
 }
 
+/// Let the parents follow the child's day as it is written, when the garderie has
+/// live journal updates on; the notification consumer refreshes and pushes.
+async fn publish_live(
+    state: &mut AppState,
+    features: &FeatureFlags,
+    tenant: &str,
+    child_id: Uuid,
+    date: NaiveDate,
+    event_type: Option<String>,
+) {
+    if features.is_enabled(LIVE_JOURNAL_UPDATES) {
+        events::publish(&mut state.redis, tenant, DomainEvent::JournalUpdated { child_id, date, event_type }).await;
+    }
+}
+
 /// PUT /journals — staff only
 pub async fn upsert_entry(
+    State(mut state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    Extension(features): Extension<FeatureFlags>,
+    user: AuthenticatedUser,
+    IfMatch(expected): IfMatch,
+    ValidJson(body): ValidJson<UpsertJournalRequest>,
+) -> Result<Versioned<Value>, ApiError> {
+    if let UserRole::Parent = user.role {
+        return Err(ApiError::forbidden());
+    }
+
+    // Educators assigned to groups may only write journals for their groups' children.
+    GroupService::ensure_child_access(&state.db, &tenant, &user, body.child_id).await?;
+
+    let entry = JournalService::upsert(&state.db, &tenant, &body, user.user_id, expected)
+        .await?;
+    publish_live(&mut state, &features, &tenant, entry.child_id, entry.date, None).await;
+    Ok(versioned(entry.updated_at, serde_json::to_value(&entry).unwrap()))
+}
+
+/// POST /journals/amend — correct an entry already emailed to the parents. The
+/// previous version is kept with the note and the parents get the corrected journal.
+pub async fn amend_entry(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-    Json(body): Json<UpsertJournalRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<AmendJournalRequest>,
+) -> Result<Json<Value>, ApiError> {
+    ensure_can_write(&state, &tenant, &user, body.entry.child_id).await?;
+
+    let (journal, emails_sent) =
+        JournalService::amend(&state.db, state.email.as_deref(), &state.config, &tenant, &body, user.user_id)
+            .await?;
+    Ok(Json(json!({ "journal": journal, "emails_sent": emails_sent })))
+}
+
+/// GET /journals/amendments?child_id=...&date=YYYY-MM-DD — admin only
+pub async fn list_amendments(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(params): Query<JournalDayQuery>,
+) -> Result<Json<Value>, ApiError> {
+    if !matches!(user.role, UserRole::AdminGarderie | UserRole::SuperAdmin) {
+        return Err(ApiError::forbidden());
+    }
+    JournalService::list_amendments(&state.db, &tenant, params.child_id, params.date)
+        .await
+        .map(|amendments| Json(serde_json::to_value(amendments).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// Parents may read their own children's days; staff those of children in their groups.
+async fn ensure_can_read(
+    state: &AppState,
+    tenant: &str,
+    user: &AuthenticatedUser,
+    child_id: Uuid,
+) -> Result<(), ApiError> {
     if let UserRole::Parent = user.role {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(json!({ "error": "Accès refusé" })),
-        ));
+        let linked = JournalService::assert_parent_access(&state.db, tenant, child_id, user.user_id)
+            .await?;
+        if !linked {
+            return Err(ApiError::forbidden());
+        }
+        return Ok(());
     }
+    GroupService::ensure_child_access(&state.db, tenant, user, child_id)
+        .await
+        .map_err(ApiError::from)
+}
 
-    JournalService::upsert(&state.db, &tenant, &body, user.user_id)
+/// Staff only, limited to children of their groups.
+async fn ensure_can_write(
+    state: &AppState,
+    tenant: &str,
+    user: &AuthenticatedUser,
+    child_id: Uuid,
+) -> Result<(), ApiError> {
+    if let UserRole::Parent = user.role {
+        return Err(ApiError::forbidden());
+    }
+    GroupService::ensure_child_access(&state.db, tenant, user, child_id)
         .await
-        .map(|entry| Json(serde_json::to_value(entry).unwrap()))
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })
+        .map_err(ApiError::from)
 }
 
-#[derive(Deserialize)]
+/// GET /journals/events?child_id=...&date=YYYY-MM-DD
+pub async fn list_events(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(params): Query<JournalDayQuery>,
+) -> Result<Json<Value>, ApiError> {
+    ensure_can_read(&state, &tenant, &user, params.child_id).await?;
+    JournalService::list_events(&state.db, &tenant, params.child_id, params.date)
+        .await
+        .map(|events| Json(serde_json::to_value(events).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// POST /journals/events — staff log a nap, diaper change… as it happens
+pub async fn create_event(
+    State(mut state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    Extension(features): Extension<FeatureFlags>,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<CreateJournalEventRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    ensure_can_write(&state, &tenant, &user, body.child_id).await?;
+    let event = JournalService::add_event(&state.db, &tenant, &body, user.user_id)
+        .await?;
+    publish_live(&mut state, &features, &tenant, event.child_id, event.date, Some(event.event_type.clone())).await;
+    Ok((StatusCode::CREATED, Json(serde_json::to_value(event).unwrap())))
+}
+
+/// PUT /journals/events/:id
+pub async fn update_event(
+    State(mut state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    Extension(features): Extension<FeatureFlags>,
+    user: AuthenticatedUser,
+    Path(event_id): Path<Uuid>,
+    ValidJson(body): ValidJson<JournalEventFields>,
+) -> Result<Json<Value>, ApiError> {
+    let child_id = JournalService::event_child(&state.db, &tenant, event_id).await?;
+    ensure_can_write(&state, &tenant, &user, child_id).await?;
+    let event = JournalService::update_event(&state.db, &tenant, event_id, &body)
+        .await?;
+    publish_live(&mut state, &features, &tenant, event.child_id, event.date, Some(event.event_type.clone())).await;
+    Ok(Json(serde_json::to_value(event).unwrap()))
+}
+
+/// DELETE /journals/events/:id
+pub async fn delete_event(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(event_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let child_id = JournalService::event_child(&state.db, &tenant, event_id).await?;
+    ensure_can_write(&state, &tenant, &user, child_id).await?;
+    JournalService::delete_event(&state.db, &tenant, event_id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(ApiError::from)
+}
+
+/// GET /journals/timeline?child_id=...&date=YYYY-MM-DD — journal entry and events merged
+pub async fn get_timeline(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(params): Query<JournalDayQuery>,
+) -> Result<Json<Value>, ApiError> {
+    ensure_can_read(&state, &tenant, &user, params.child_id).await?;
+    JournalService::timeline(&state.db, &tenant, params.child_id, params.date)
+        .await
+        .map(|timeline| Json(serde_json::to_value(timeline).unwrap()))
+        .map_err(ApiError::from)
+}
+
+#[derive(Deserialize, Validate)]
 pub struct SendJournalRequest {
     pub week_start: NaiveDate,
 }
 
 /// POST /journals/send-all-to-parents — send this week's journals for ALL children
 pub async fn send_all_to_parents(
-    State(state): State<AppState>,
+    State(mut state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-    Json(body): Json<SendJournalRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<SendJournalRequest>,
+) -> Result<Json<Value>, ApiError> {
     if let UserRole::Parent = user.role {
-        return Err((StatusCode::FORBIDDEN, Json(json!({ "error": "Accès refusé" }))));
+        return Err(ApiError::forbidden());
     }
 
-    JournalService::send_all_journals_to_parents(
-        &state.db,
-        state.email.as_deref(),
-        &tenant,
-        body.week_start,
-    )
-    .await
-    .map(|msg| Json(json!({ "message": msg })))
-    .map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })
+    let (child_ids, total_sent, skipped) = JournalService::weeks_to_send(&state.db, &tenant, body.week_start)
+        .await?;
+    // The journals are emailed by the notification consumer
+    events::publish(&mut state.redis, &tenant, DomainEvent::JournalSent { child_ids, week_start: body.week_start }).await;
+    Ok(Json(json!({
+        "message": format!("Journaux envoyés à {} parent(s) ({} enfant(s) ignoré(s))", total_sent, skipped)
+    })))
 }
 
 /// POST /journals/:child_id/send-to-parents — send weekly journal to all parents
 pub async fn send_to_parents(
-    State(state): State<AppState>,
+    State(mut state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path(child_id): Path<Uuid>,
-    Json(body): Json<SendJournalRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<SendJournalRequest>,
+) -> Result<Json<Value>, ApiError> {
     // Only educators and admins can send journals
     if let UserRole::Parent = user.role {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(json!({ "error": "Accès refusé" })),
-        ));
+        return Err(ApiError::forbidden());
     }
 
-    JournalService::send_journal_to_parents(
-        &state.db,
-        state.email.as_deref(),
-        &tenant,
-        child_id,
-        body.week_start,
-    )
-    .await
-    .map(|msg| Json(json!({ "message": msg })))
-    .map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": e.to_string() })),
-        )
+    let total = JournalService::journal_recipients(&state.db, &tenant, child_id, body.week_start)
+        .await?;
+    // The journal is emailed by the notification consumer
+    events::publish(&mut state.redis, &tenant, DomainEvent::JournalSent {
+        child_ids: vec![child_id],
+        week_start: body.week_start,
     })
+    .await;
+    Ok(Json(json!({ "message": format!("Journal envoyé à {} parent(s)", total) })))
 }
@@ -1,3 +1,4 @@
+use anyhow::Context;
 use axum::{
     body::Body,
     extract::{Multipart, Path, State},
@@ -9,43 +10,25 @@ use serde_json::{json, Value};
 use std::path::PathBuf;
 
 use crate::{
+    error::ApiError,
     middleware::tenant::TenantSlug,
     models::{auth::AuthenticatedUser, user::UserRole},
+    services::upload_types::{self, UploadKind},
     AppState,
 };
 
-fn detect_image_ext(content_type: &str, filename: &str) -> Option<&'static str> {
-    match content_type {
-        "image/png" => return Some("png"),
-        "image/jpeg" | "image/jpg" => return Some("jpg"),
-        "image/webp" => return Some("webp"),
-        "image/gif" => return Some("gif"),
-        _ => {}
-    }
-    let ext = filename.rsplit('.').next()?.to_lowercase();
-    match ext.as_str() {
-        "png" => Some("png"),
-        "jpg" | "jpeg" => Some("jpg"),
-        "webp" => Some("webp"),
-        "gif" => Some("gif"),
-        _ => None,
-    }
-}
-
 pub async fn upload_logo(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     mut multipart: Multipart,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     if !matches!(user.role, UserRole::AdminGarderie | UserRole::SuperAdmin) {
-        return Err((StatusCode::FORBIDDEN, Json(json!({ "error": "Forbidden" }))));
+        return Err(ApiError::forbidden());
     }
 
     let logo_dir = PathBuf::from(&state.config.media_dir).join(&tenant);
-    tokio::fs::create_dir_all(&logo_dir).await.map_err(|e| {
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
-    })?;
+    tokio::fs::create_dir_all(&logo_dir).await.context("create logo directory")?;
 
     // Remove any existing logo file
     if let Ok(mut dir) = tokio::fs::read_dir(&logo_dir).await {
@@ -58,33 +41,23 @@ pub async fn upload_logo(
     }
 
     while let Some(field) = multipart.next_field().await.map_err(|e| {
-        (StatusCode::BAD_REQUEST, Json(json!({ "error": e.to_string() })))
+        ApiError::new(StatusCode::BAD_REQUEST, e.to_string())
     })? {
         let ct = field.content_type().unwrap_or("").to_string();
         let fname = field.file_name().unwrap_or("").to_string();
 
-        let file_ext = detect_image_ext(&ct, &fname).ok_or_else(|| {
-            (
-                StatusCode::BAD_REQUEST,
-                Json(json!({ "error": "Format non supporté. Utilisez PNG, JPG, WebP ou GIF." })),
-            )
-        })?;
+        let data = field.bytes().await.map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e.to_string()))?;
 
-        let data = field.bytes().await.map_err(|e| {
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
-        })?;
+        // The extension (and so the served content type) comes from the bytes
+        let file_ext = upload_types::validate(UploadKind::Logo, &data, &ct, &fname)?
+            .extensions[0];
 
         if data.len() > 5 * 1024 * 1024 {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(json!({ "error": "Fichier trop volumineux (max 5 Mo)" })),
-            ));
+            return Err(ApiError::new(StatusCode::BAD_REQUEST, "Fichier trop volumineux (max 5 Mo)"));
         }
 
         let file_path = logo_dir.join(format!("logo.{file_ext}"));
-        tokio::fs::write(&file_path, &data).await.map_err(|e| {
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
-        })?;
+        tokio::fs::write(&file_path, &data).await.context("write logo")?;
 
         let logo_url = format!(
             "{}/api/logos/{}",
@@ -97,24 +70,21 @@ pub async fn upload_logo(
         .bind(&logo_url)
         .bind(&tenant)
         .execute(&state.db)
-        .await
-        .map_err(|e| {
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
-        })?;
+        .await?;
 
         return Ok(Json(json!({ "logo_url": logo_url })));
     }
 
-    Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "Aucun fichier fourni" }))))
+    Err(ApiError::new(StatusCode::BAD_REQUEST, "Aucun fichier fourni"))
 }
 
 pub async fn delete_logo(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     if !matches!(user.role, UserRole::AdminGarderie | UserRole::SuperAdmin) {
-        return Err((StatusCode::FORBIDDEN, Json(json!({ "error": "Forbidden" }))));
+        return Err(ApiError::forbidden());
     }
 
     let logo_dir = PathBuf::from(&state.config.media_dir).join(&tenant);
@@ -132,10 +102,7 @@ pub async fn delete_logo(
     )
     .bind(&tenant)
     .execute(&state.db)
-    .await
-    .map_err(|e| {
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
-    })?;
+    .await?;
 
     Ok(Json(json!({ "ok": true })))
 }
@@ -179,6 +146,7 @@ pub async fn serve_logo(
     Ok(Response::builder()
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, content_type)
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
         .header(header::CACHE_CONTROL, "public, max-age=3600")
         .body(Body::from(data))
         .unwrap())
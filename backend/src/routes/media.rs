@@ -1,3 +1,4 @@
+use anyhow::Context;
 use axum::{
     body::Body,
     extract::{Multipart, Path, Query, State},
@@ -7,159 +8,73 @@ use axum::{
 };
 use serde::Deserialize;
 use serde_json::{json, Value};
+use std::io::SeekFrom;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
 use uuid::Uuid;
 
 use crate::{
     db::tenant::schema_name,
-    middleware::tenant::TenantSlug,
+    error::ApiError,
+    middleware::{
+        api_version::ApiVersion,
+        auth::decode_access_token,
+        fields::Fields,
+        precondition::{self, versioned, CacheClass, IfMatch, StaleVersion, Versioned},
+        tenant::TenantSlug,
+    },
     models::{
         auth::AuthenticatedUser,
-        media::{BulkMediaRequest, MediaQuery, UpdateMediaRequest},
+        media::{BulkMediaRequest, MediaMark, MediaQuery, UpdateMediaRequest},
         user::UserRole,
     },
-    services::{encryption, media::MediaService},
+    services::{
+        antivirus::SCAN_INFECTED,
+        children::ChildService,
+        encryption::{self, KeyRing},
+        events::{self, DomainEvent},
+        groups::GroupService,
+        media::MediaService,
+        media_urls::{MediaUrlSigner, UrlDenied},
+        storage::StorageService,
+    },
     AppState,
 };
+use crate::middleware::validation::ValidJson;
 
 pub async fn upload_media(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     multipart: Multipart,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
-    let media = MediaService::upload(
-        &state.db,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let keys = KeyRing::from_config(&state.config)?;
+    let signer = MediaUrlSigner::from_config(&state.config)?;
+    let mut media = MediaService::upload(
+        &state.db_bulk,
+        &mut state.redis.clone(),
         &tenant,
         user.user_id,
         &state.config.media_dir,
-        &state.config.encryption_master_key,
+        state.config.media_max_dimension,
+        &keys,
         multipart,
     )
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
-
-    // Email notifications aux parents concernés (async, non-bloquant, cooldown 1h par parent)
-    if tenant != "demo" {
-    if let Some(email_svc) = state.email.clone() {
-        if media.visibility != "private" {
-            let pool = state.db.clone();
-            let tenant_c = tenant.clone();
-            let visibility = media.visibility.clone();
-            let group_id = media.group_id;
-            let media_id = media.id;
-            let media_type_str = media.media_type.clone();
-            let uploader_id = user.user_id;
-            let mut redis = state.redis.clone();
-            let base = state.config.app_base_url.clone();
-
-            tokio::spawn(async move {
-                let s = schema_name(&tenant_c);
-
-                let (garderie_name, logo_url): (String, Option<String>) = sqlx::query_as(
-                    "SELECT name, logo_url FROM public.garderies WHERE slug = $1",
-                )
-                .bind(&tenant_c)
-                .fetch_optional(&pool)
-                .await
-                .unwrap_or_default()
-                .unwrap_or_else(|| (tenant_c.clone(), None));
-                let logo_url = logo_url.unwrap_or_default();
-
-                let uploader_name: String = sqlx::query_scalar(&format!(
-                    "SELECT CONCAT(first_name, ' ', last_name) FROM {s}.users WHERE id = $1"
-                ))
-                .bind(uploader_id)
-                .fetch_optional(&pool)
-                .await
-                .unwrap_or_default()
-                .unwrap_or_else(|| "Un éducateur".to_string());
-
-                let app_url = if let Some(idx) = base.find("://") {
-                    let scheme = &base[..idx];
-                    let domain = &base[idx + 3..];
-                    format!("{scheme}://{tenant_c}.{domain}/fr/parent/media")
-                } else {
-                    format!("https://{tenant_c}.{base}/fr/parent/media")
-                };
-
-                let recipients: Vec<(Uuid, String, String)> = match visibility.as_str() {
-                    "public" => sqlx::query_as(&format!(
-                        "SELECT id, email, CONCAT(first_name, ' ', last_name)
-                         FROM {s}.users WHERE role::text = 'parent' AND is_active = TRUE"
-                    ))
-                    .fetch_all(&pool)
-                    .await
-                    .unwrap_or_default(),
-
-                    "group" => match group_id {
-                        Some(gid) => sqlx::query_as(&format!(
-                            "SELECT DISTINCT u.id, u.email, CONCAT(u.first_name, ' ', u.last_name)
-                             FROM {s}.users u
-                             JOIN {s}.child_parents cp ON cp.user_id = u.id
-                             JOIN {s}.children c ON c.id = cp.child_id
-                             WHERE c.group_id = $1 AND u.is_active = TRUE"
-                        ))
-                        .bind(gid)
-                        .fetch_all(&pool)
-                        .await
-                        .unwrap_or_default(),
-                        None => vec![],
-                    },
-
-                    "child" => sqlx::query_as(&format!(
-                        "SELECT DISTINCT u.id, u.email, CONCAT(u.first_name, ' ', u.last_name)
-                         FROM {s}.users u
-                         JOIN {s}.child_parents cp ON cp.user_id = u.id
-                         JOIN {s}.media_children mc ON mc.child_id = cp.child_id
-                         WHERE mc.media_id = $1 AND u.is_active = TRUE"
-                    ))
-                    .bind(media_id)
-                    .fetch_all(&pool)
-                    .await
-                    .unwrap_or_default(),
-
-                    _ => vec![],
-                };
-
-                let content_kind = if media_type_str == "video" {
-                    "une vidéo"
-                } else {
-                    "de nouvelles photos"
-                };
-
-                for (parent_id, email, name) in recipients {
-                    let cooldown_key =
-                        format!("notif_cooldown:{tenant_c}:media_upload:{parent_id}");
-                    let newly_set: Option<String> = redis::cmd("SET")
-                        .arg(&cooldown_key)
-                        .arg("1")
-                        .arg("NX")
-                        .arg("EX")
-                        .arg(3600u64) // 1 heure
-                        .query_async(&mut redis)
-                        .await
-                        .unwrap_or(None);
-
-                    if newly_set.is_some() {
-                        let _ = email_svc
-                            .send_media_notification(
-                                &email,
-                                &name,
-                                &uploader_name,
-                                content_kind,
-                                &app_url,
-                                &garderie_name,
-                                &logo_url,
-                            )
-                            .await;
-                    }
-                }
-            });
-        }
+    .await?;
+
+    StorageService::check_thresholds(state.db_bulk.clone(), state.redis.clone(), state.email.clone(), tenant.clone());
+
+    // Parents who can see it are emailed by the notification consumer
+    if media.visibility != "private" {
+        events::publish(&mut state.redis.clone(), &tenant, DomainEvent::MediaPublished {
+            media_id: media.id,
+            uploader_id: user.user_id,
+        })
+        .await;
     }
-    } // end if tenant != "demo"
 
     crate::services::metrics::MEDIA_UPLOADS_COUNTER.with_label_values(&[&tenant]).inc();
+    signer.sign_media(&mut media);
     Ok((StatusCode::CREATED, Json(serde_json::to_value(media).unwrap())))
 }
 
@@ -168,58 +83,90 @@ pub async fn list_media(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Query(query): Query<MediaQuery>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    version: ApiVersion,
+    fields: Fields,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
     let is_staff = !matches!(user.role, UserRole::Parent);
-    MediaService::list(&state.db, &tenant, user.user_id, is_staff, &query)
+    let scope = GroupService::educator_scope(&state.db_bulk, &tenant, &user)
+        .await?;
+    let signer = MediaUrlSigner::from_config(&state.config)?;
+    let mut redis = state.redis.clone();
+    MediaService::list(&state.db_bulk, &mut redis, &tenant, user.user_id, is_staff, scope.as_deref(), &query)
         .await
-        .map(|items| Json(serde_json::to_value(items).unwrap()))
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
+        .map(|mut page| {
+            page.items.iter_mut().for_each(|m| signer.sign_media(m));
+            let updated: Vec<_> = page.items.iter().map(|m| m.updated_at).collect();
+            let mut body = if version.slim() {
+                serde_json::to_value(page.slim()).unwrap()
+            } else {
+                serde_json::to_value(page).unwrap()
+            };
+            fields.select(&mut body["items"]);
+            precondition::cached_list(&headers, updated, &body)
         })
+        .map_err(ApiError::from)
 }
 
-#[derive(Deserialize)]
-pub struct ServeMediaQuery {
-    pub download: Option<u8>,
+/// GET /media/tags — tags in use, for the gallery's tag filter
+pub async fn list_media_tags(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    _user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    MediaService::list_tags(&state.db_bulk, &tenant)
+        .await
+        .map(|tags| Json(serde_json::to_value(tags).unwrap()))
+        .map_err(ApiError::from)
 }
 
-/// Serve a media or document file with HTTP range support (for video streaming).
-/// Add ?download=1 to get Content-Disposition: attachment.
-///
-/// No auth header required — file paths contain opaque UUIDs and files are
-/// encrypted at rest, so the path itself acts as the access token.
-pub async fn serve_media(
-    State(state): State<AppState>,
-    Path(path): Path<String>,
-    Query(params): Query<ServeMediaQuery>,
-    headers: HeaderMap,
-) -> Result<Response, (StatusCode, Json<Value>)> {
-    let file_path = std::path::PathBuf::from(&state.config.media_dir).join(&path);
+fn quarantined() -> ApiError {
+    ApiError::new(StatusCode::FORBIDDEN, "Ce fichier a été mis en quarantaine par l'antivirus")
+        .with_code("file_quarantined")
+}
 
-    // Security: ensure the path doesn't escape the media directory
-    let canonical_media = std::fs::canonicalize(&state.config.media_dir)
-        .map_err(|_| (StatusCode::NOT_FOUND, Json(json!({"error": "media directory not found"}))))?;
-    let canonical_file = match std::fs::canonicalize(&file_path) {
-        Ok(p) => p,
-        Err(_) => return Err((StatusCode::NOT_FOUND, Json(json!({"error": "file not found"})))),
-    };
-    if !canonical_file.starts_with(&canonical_media) {
-        return Err((StatusCode::FORBIDDEN, Json(json!({"error": "invalid path"}))));
+/// A file ready to be served: plaintext files stay on disk and are streamed.
+enum ServedFile {
+    Disk(tokio::fs::File),
+    Decrypted(Vec<u8>),
+}
+
+struct LoadedFile {
+    file: ServedFile,
+    size: u64,
+    content_type: String,
+    /// Hash of the plaintext, see [`precondition::content_etag`].
+    etag: String,
+}
+
+/// SHA-256 `ETag` of a plaintext file on disk, read back from the start.
+async fn hash_file(file: &mut tokio::fs::File) -> std::io::Result<String> {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
     }
+    file.seek(SeekFrom::Start(0)).await?;
+    Ok(precondition::hash_etag(&hex::encode(hasher.finalize())))
+}
 
-    // Extract tenant slug from the first path segment (e.g. "gbtest/2026/02/uuid.jpg" → "gbtest")
-    let tenant_slug = path
-        .split('/')
-        .next()
-        .filter(|s| !s.is_empty())
-        .ok_or_else(|| (StatusCode::BAD_REQUEST, Json(json!({"error": "invalid path"}))))?;
+/// Look up a file's encryption metadata (media, then documents, then child avatars),
+/// then open it, or read and decrypt it with the key version it was written under.
+async fn load_file(
+    state: &AppState,
+    tenant_slug: &str,
+    storage_path: &str,
+    file_path: &std::path::Path,
+) -> Result<LoadedFile, ApiError> {
     let schema = schema_name(tenant_slug);
-    let storage_path = path.as_str();
 
-    // --- Look up encryption metadata in media table ---
+    // --- Look up encryption metadata in media table (or a photo's blurred copy) ---
     #[derive(sqlx::FromRow)]
     struct MediaRow {
         is_encrypted: bool,
@@ -227,176 +174,341 @@ pub async fn serve_media(
         encryption_tag: Option<Vec<u8>>,
         thumbnail_encryption_iv: Option<Vec<u8>>,
         thumbnail_encryption_tag: Option<Vec<u8>>,
+        sprite_path: Option<String>,
+        sprite_encryption_iv: Option<Vec<u8>>,
+        sprite_encryption_tag: Option<Vec<u8>>,
         content_type: String,
         storage_path: String,
+        key_version: i32,
+        scan_status: String,
+        content_sha256: Option<String>,
     }
 
     let media_row = sqlx::query_as::<_, MediaRow>(&format!(
         r#"
         SELECT m.is_encrypted, m.encryption_iv, m.encryption_tag,
                m.thumbnail_encryption_iv, m.thumbnail_encryption_tag,
-               m.content_type, m.storage_path
+               m.sprite_path, m.sprite_encryption_iv, m.sprite_encryption_tag,
+               m.content_type, m.storage_path, m.key_version, m.scan_status, m.content_sha256
         FROM "{schema}".media m
-        WHERE m.storage_path = $1 OR m.thumbnail_path = $1
+        WHERE m.storage_path = $1 OR m.thumbnail_path = $1 OR m.sprite_path = $1
+        UNION ALL
+        SELECT TRUE, r.encryption_iv, r.encryption_tag, NULL::BYTEA, NULL::BYTEA,
+               NULL::TEXT, NULL::BYTEA, NULL::BYTEA,
+               'image/jpeg', r.redacted_path, r.key_version, m.scan_status, NULL::VARCHAR
+        FROM "{schema}".media_redactions r
+        JOIN "{schema}".media m ON m.id = r.media_id
+        WHERE r.redacted_path = $1
+        LIMIT 1
         "#,
         schema = schema
     ))
     .bind(storage_path)
-    .fetch_optional(&state.db)
-    .await
-    .map_err(|e| (
-        StatusCode::INTERNAL_SERVER_ERROR,
-        Json(json!({"error": format!("database error: {}", e)})),
-    ))?;
-
-    // Determine (is_encrypted, iv, tag, content_type) from media or documents
-    let (is_encrypted, enc_iv, enc_tag, content_type) = if let Some(row) = media_row {
-        // Is this request for the thumbnail or the main file?
+    .fetch_optional(&state.db_bulk)
+    .await?;
+
+    // Determine (is_encrypted, iv, tag, key_version, content_type) from media or documents;
+    // the content hash is only stored for a media's main file
+    let mut known_sha256 = None;
+    let (is_encrypted, enc_iv, enc_tag, key_version, content_type) = if let Some(row) = media_row {
+        if row.scan_status == SCAN_INFECTED {
+            return Err(quarantined());
+        }
+        // Is this request for the video sprite, the thumbnail or the main file?
         let is_thumbnail = row.storage_path != storage_path;
-        if is_thumbnail {
-            (row.is_encrypted, row.thumbnail_encryption_iv, row.thumbnail_encryption_tag, "image/jpeg".to_string())
+        if row.sprite_path.as_deref() == Some(storage_path) {
+            (row.is_encrypted, row.sprite_encryption_iv, row.sprite_encryption_tag, row.key_version, "image/jpeg".to_string())
+        } else if is_thumbnail {
+            (row.is_encrypted, row.thumbnail_encryption_iv, row.thumbnail_encryption_tag, row.key_version, "image/jpeg".to_string())
         } else {
-            (row.is_encrypted, row.encryption_iv, row.encryption_tag, row.content_type)
+            known_sha256 = row.content_sha256;
+            (row.is_encrypted, row.encryption_iv, row.encryption_tag, row.key_version, row.content_type)
         }
     } else {
-        // Fall back to documents table
+        // Fall back to documents table (current file or a replaced version)
         #[derive(sqlx::FromRow)]
         struct DocRow {
             is_encrypted: bool,
             encryption_iv: Option<Vec<u8>>,
             encryption_tag: Option<Vec<u8>>,
             content_type: String,
+            key_version: i32,
+            scan_status: String,
         }
 
         let doc = sqlx::query_as::<_, DocRow>(&format!(
             r#"
-            SELECT d.is_encrypted, d.encryption_iv, d.encryption_tag, d.content_type
+            SELECT d.is_encrypted, d.encryption_iv, d.encryption_tag, d.content_type, d.key_version, d.scan_status
             FROM "{schema}".documents d
             WHERE d.storage_path = $1
+            UNION ALL
+            SELECT v.is_encrypted, v.encryption_iv, v.encryption_tag, v.content_type, v.key_version, v.scan_status
+            FROM "{schema}".document_versions v
+            WHERE v.storage_path = $1
+            LIMIT 1
             "#,
             schema = schema
         ))
         .bind(storage_path)
-        .fetch_optional(&state.db)
-        .await
-        .map_err(|e| (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": format!("database error: {}", e)})),
-        ))?;
+        .fetch_optional(&state.db_bulk)
+        .await?;
 
         if let Some(doc) = doc {
-            (doc.is_encrypted, doc.encryption_iv, doc.encryption_tag, doc.content_type)
+            if doc.scan_status == SCAN_INFECTED {
+                return Err(quarantined());
+            }
+            (doc.is_encrypted, doc.encryption_iv, doc.encryption_tag, doc.key_version, doc.content_type)
         } else {
             // Third fallback: check children table for avatar
             #[derive(sqlx::FromRow)]
             struct ChildAvatarRow {
                 avatar_iv: Vec<u8>,
                 avatar_tag: Vec<u8>,
+                avatar_key_version: i32,
             }
 
             let avatar = sqlx::query_as::<_, ChildAvatarRow>(&format!(
                 r#"
-                SELECT avatar_iv, avatar_tag FROM "{schema}".children
+                SELECT avatar_iv, avatar_tag, avatar_key_version FROM "{schema}".children
                 WHERE photo_url = $1 AND avatar_iv IS NOT NULL AND avatar_tag IS NOT NULL
                 "#,
                 schema = schema
             ))
             .bind(storage_path)
-            .fetch_optional(&state.db)
-            .await
-            .map_err(|e| (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"error": format!("database error: {}", e)})),
-            ))?
-            .ok_or_else(|| (StatusCode::NOT_FOUND, Json(json!({"error": "file not found in database"}))))?;
-
-            (true, Some(avatar.avatar_iv), Some(avatar.avatar_tag), "image/jpeg".to_string())
+            .fetch_optional(&state.db_bulk)
+            .await?
+            .ok_or_else(|| ApiError::not_found("file not found in database"))?;
+
+            (true, Some(avatar.avatar_iv), Some(avatar.avatar_tag), avatar.avatar_key_version, "image/jpeg".to_string())
         }
     };
 
-    // Read file from disk
-    let file_bytes = tokio::fs::read(&file_path)
+    let mut file = tokio::fs::File::open(file_path)
         .await
-        .map_err(|_| (StatusCode::NOT_FOUND, Json(json!({"error": "file not found on disk"}))))?;
-
-    // Decrypt if needed
-    let decrypted_bytes = if is_encrypted {
-        let iv = enc_iv.ok_or_else(|| (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": "missing encryption IV"})),
-        ))?;
-        let tag = enc_tag.ok_or_else(|| (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"error": "missing encryption tag"})),
-        ))?;
-
-        let master_key_bytes = hex::decode(&state.config.encryption_master_key)
-            .map_err(|e| (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"error": format!("invalid master key: {}", e)})),
-            ))?;
-        if master_key_bytes.len() != 32 {
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": "master key must be 32 bytes"}))));
-        }
-        let mut master_key = [0u8; 32];
-        master_key.copy_from_slice(&master_key_bytes);
-
-        let tenant_key = encryption::derive_tenant_key(&master_key, tenant_slug)
-            .map_err(|e| (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"error": format!("key derivation failed: {}", e)})),
-            ))?;
-
-        encryption::decrypt_file(&file_bytes, &iv, &tag, &tenant_key)
-            .map_err(|e| (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"error": format!("decryption failed: {}", e)})),
-            ))?
-    } else {
-        file_bytes
+        .map_err(|_| ApiError::not_found("file not found on disk"))?;
+    let disk_size = file
+        .metadata()
+        .await
+        .map_err(|_| ApiError::not_found("file not found on disk"))?
+        .len();
+
+    if !is_encrypted {
+        let etag = match known_sha256 {
+            Some(sha256) => precondition::hash_etag(&sha256),
+            None => hash_file(&mut file)
+                .await
+                .map_err(|_| ApiError::not_found("file not found on disk"))?,
+        };
+        return Ok(LoadedFile { file: ServedFile::Disk(file), size: disk_size, content_type, etag });
+    }
+
+    // AES-GCM authenticates the whole file, so it is decrypted in memory
+    if disk_size > state.config.media_max_decrypt_bytes {
+        return Err(ApiError::new(StatusCode::PAYLOAD_TOO_LARGE, "Fichier chiffré trop volumineux pour être servi"));
+    }
+    let iv = enc_iv.context("missing encryption IV")?;
+    let tag = enc_tag.context("missing encryption tag")?;
+
+    let tenant_key = KeyRing::from_config(&state.config)
+        .and_then(|keys| keys.tenant_key(key_version, tenant_slug))
+        .context("key derivation failed")?;
+
+    let mut file_bytes = Vec::with_capacity(disk_size as usize);
+    file.read_to_end(&mut file_bytes)
+        .await
+        .map_err(|_| ApiError::not_found("file not found on disk"))?;
+
+    let decrypted_bytes = encryption::decrypt_file(&file_bytes, &iv, &tag, &tenant_key)
+        .context("decryption failed")?;
+    let size = decrypted_bytes.len() as u64;
+    let etag = match known_sha256 {
+        Some(sha256) => precondition::hash_etag(&sha256),
+        None => precondition::content_etag(&decrypted_bytes),
     };
 
-    let file_size = decrypted_bytes.len() as u64;
-    let download = params.download.unwrap_or(0) != 0;
+    Ok(LoadedFile { file: ServedFile::Decrypted(decrypted_bytes), size, content_type, etag })
+}
 
-    // Handle Range request (video streaming)
-    if let Some(range_header) = headers.get(header::RANGE) {
-        let range_str = range_header
-            .to_str()
-            .map_err(|_| (StatusCode::BAD_REQUEST, Json(json!({"error": "invalid range header"}))))?;
+#[derive(Deserialize)]
+pub struct ServeMediaQuery {
+    pub download: Option<u8>,
+    /// Access token for child photos, which images cannot send as a header.
+    pub token: Option<String>,
+    /// Expiry (Unix seconds) and HMAC of a signed URL.
+    pub exp: Option<i64>,
+    pub sig: Option<String>,
+}
 
-        if let Some((start, end)) = parse_range(range_str, file_size) {
-            let length = (end - start + 1) as usize;
-            let chunk = decrypted_bytes[start as usize..=end as usize].to_vec();
+/// Verify the signature of a `/media/files` request. Failures are logged and
+/// counted; they are only refused once signed URLs are required.
+fn check_signature(
+    state: &AppState,
+    tenant_slug: &str,
+    path: &str,
+    params: &ServeMediaQuery,
+) -> Result<(), ApiError> {
+    let signer = MediaUrlSigner::from_config(&state.config)?;
+    let Err(denied) = signer.verify(path, params.exp, params.sig.as_deref()) else {
+        return Ok(());
+    };
+    let enforced = state.config.media_require_signed_urls;
+    crate::services::metrics::MEDIA_URL_DENIED_COUNTER
+        .with_label_values(&[tenant_slug, denied.as_str(), if enforced { "true" } else { "false" }])
+        .inc();
+    if !enforced {
+        tracing::info!(tenant = tenant_slug, path, reason = denied.as_str(), "unsigned media access allowed");
+        return Ok(());
+    }
+    tracing::warn!(tenant = tenant_slug, path, reason = denied.as_str(), "media access denied");
+    let status = match denied {
+        UrlDenied::Expired => StatusCode::GONE,
+        UrlDenied::Unsigned | UrlDenied::BadSignature => StatusCode::FORBIDDEN,
+    };
+    Err(ApiError::from_key(status, "forbidden"))
+}
 
-            let mut builder = Response::builder()
-                .status(StatusCode::PARTIAL_CONTENT)
-                .header(header::CONTENT_TYPE, content_type.as_str())
-                .header(header::CONTENT_LENGTH, length.to_string())
-                .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, file_size))
-                .header(header::ACCEPT_RANGES, "bytes");
-
-            if download {
-                let fname = file_path.file_name().and_then(|n| n.to_str()).unwrap_or("download");
-                builder = builder.header(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", fname));
-            }
-            return Ok(builder.body(Body::from(chunk)).unwrap());
+/// Child profile photos are only served to the child's parents and to the staff
+/// who may see the child, authenticated by bearer header or `?token=`.
+async fn authorize_child_photo(
+    state: &AppState,
+    tenant_slug: &str,
+    path: &str,
+    headers: &HeaderMap,
+    token: Option<&str>,
+) -> Result<(), ApiError> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    let user = bearer
+        .or(token)
+        .and_then(|t| decode_access_token(t, &state.jwt_keys).ok())
+        .filter(|u| u.tenant == tenant_slug)
+        .ok_or_else(|| ApiError::from_key(StatusCode::UNAUTHORIZED, "authentication_required"))?;
+
+    let child_id = ChildService::child_for_photo(&state.db, tenant_slug, path)
+        .await?
+        .ok_or_else(|| ApiError::not_found("file not found in database"))?;
+    let allowed = match user.role {
+        UserRole::Parent => ChildService::is_parent_of(&state.db, tenant_slug, child_id, user.user_id)
+            .await?,
+        _ => GroupService::ensure_child_access(&state.db, tenant_slug, &user, child_id).await.is_ok(),
+    };
+    if !allowed {
+        return Err(ApiError::forbidden());
+    }
+    Ok(())
+}
+
+/// Serve a media or document file with HTTP range support (for video streaming).
+/// Add ?download=1 to get Content-Disposition: attachment. Answers 304 to an
+/// `If-None-Match` naming the file's content hash.
+///
+/// No auth header required — list endpoints hand out URLs signed with an expiry
+/// (`?exp=&sig=`, see [`MediaUrlSigner`]). Unsigned requests are refused when
+/// `MEDIA_REQUIRE_SIGNED_URLS` is set, and only logged otherwise. Child profile
+/// photos (`{tenant}/avatars/...`) are authorized by token instead, see
+/// [`authorize_child_photo`].
+pub async fn serve_media(
+    State(state): State<AppState>,
+    Path(path): Path<String>,
+    Query(params): Query<ServeMediaQuery>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let file_path = std::path::PathBuf::from(&state.config.media_dir).join(&path);
+
+    // Security: ensure the path doesn't escape the media directory
+    let canonical_media = std::fs::canonicalize(&state.config.media_dir)
+        .map_err(|_| ApiError::not_found("media directory not found"))?;
+    let canonical_file = match std::fs::canonicalize(&file_path) {
+        Ok(p) => p,
+        Err(_) => return Err(ApiError::not_found("file not found")),
+    };
+    if !canonical_file.starts_with(&canonical_media) {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "invalid path"));
+    }
+
+    // Extract tenant slug from the first path segment (e.g. "gbtest/2026/02/uuid.jpg" → "gbtest")
+    let tenant_slug = path
+        .split('/')
+        .next()
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| ApiError::new(StatusCode::BAD_REQUEST, "invalid path"))?;
+    let is_avatar = path.split('/').nth(1) == Some("avatars");
+    if is_avatar {
+        authorize_child_photo(&state, tenant_slug, &path, &headers, params.token.as_deref()).await?;
+    } else {
+        check_signature(&state, tenant_slug, &path, &params)?;
+    }
+    // A key rotation may re-encrypt the file between the metadata read and the file
+    // read; the mismatch fails decryption, so read both again once before giving up.
+    let loaded = match load_file(&state, tenant_slug, &path, &file_path).await {
+        Err(e) if e.status == StatusCode::INTERNAL_SERVER_ERROR => {
+            load_file(&state, tenant_slug, &path, &file_path).await?
         }
+        other => other?,
+    };
+    let LoadedFile { file, size: file_size, content_type, etag } = loaded;
+    let cache = if is_avatar { CacheClass::Avatar } else { CacheClass::File };
+    // Takes precedence over Range: the client already has the whole file
+    if precondition::not_modified(&headers, &etag) {
+        return Ok(precondition::not_modified_response(&etag, cache));
     }
+    let download = params.download.unwrap_or(0) != 0;
 
-    // Full file response
     let mut builder = Response::builder()
-        .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, content_type.as_str())
-        .header(header::CONTENT_LENGTH, file_size.to_string())
-        .header(header::ACCEPT_RANGES, "bytes");
-
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ETAG, etag)
+        .header(header::CACHE_CONTROL, cache.header_value());
     if download {
         let fname = file_path.file_name().and_then(|n| n.to_str()).unwrap_or("download");
         builder = builder.header(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", fname));
     }
 
-    Ok(builder.body(Body::from(decrypted_bytes)).unwrap())
+    // Handle Range request (video streaming)
+    let range = match headers.get(header::RANGE) {
+        Some(range_header) => {
+            let range_str = range_header
+                .to_str()
+                .map_err(|_| ApiError::new(StatusCode::BAD_REQUEST, "invalid range header"))?;
+            parse_range(range_str, file_size)
+        }
+        None => None,
+    };
+    let (start, end) = match range {
+        Some((start, end)) => {
+            builder = builder
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, file_size));
+            (start, end)
+        }
+        // Full file response
+        None => {
+            builder = builder.status(StatusCode::OK);
+            (0, file_size.saturating_sub(1))
+        }
+    };
+    let length = if file_size == 0 { 0 } else { end - start + 1 };
+    builder = builder.header(header::CONTENT_LENGTH, length.to_string());
+
+    let body = match file {
+        ServedFile::Disk(mut file) => {
+            if start > 0 {
+                file.seek(SeekFrom::Start(start))
+                    .await
+                    .map_err(|_| ApiError::not_found("file not found on disk"))?;
+            }
+            Body::from_stream(ReaderStream::new(file.take(length)))
+        }
+        ServedFile::Decrypted(bytes) if range.is_some() => {
+            Body::from(bytes[start as usize..=end as usize].to_vec())
+        }
+        ServedFile::Decrypted(bytes) => Body::from(bytes),
+    };
+
+    Ok(builder.body(body).unwrap())
 }
 
 pub async fn update_media(
@@ -404,137 +516,29 @@ pub async fn update_media(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path(id): Path<Uuid>,
-    Json(req): Json<UpdateMediaRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    IfMatch(expected): IfMatch,
+    ValidJson(req): ValidJson<UpdateMediaRequest>,
+) -> Result<Versioned<Value>, ApiError> {
     let is_staff = !matches!(user.role, UserRole::Parent);
-    let media = match MediaService::update(&state.db, &tenant, id, user.user_id, is_staff, &req).await {
+    let media = match MediaService::update(&state.db_bulk, &tenant, id, user.user_id, is_staff, &req, expected).await {
         Ok(Some(m)) => m,
-        Ok(None) => return Err((StatusCode::NOT_FOUND, Json(json!({ "error": "not found" })))),
-        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))),
+        Ok(None) => return Err(ApiError::from_key(StatusCode::NOT_FOUND, "not_found")),
+        Err(e) => match e.downcast_ref::<StaleVersion>() {
+            Some(stale) => return Err(precondition::conflict(stale)),
+            None => return Err(ApiError::from(e)),
+        },
     };
 
     // Notify parents when media becomes visible (visibility != private)
-    if tenant != "demo" {
-    if let Some(email_svc) = state.email.clone() {
-        if media.visibility != "private" {
-            let pool = state.db.clone();
-            let tenant_c = tenant.clone();
-            let visibility = media.visibility.clone();
-            let group_id = media.group_id;
-            let media_id = media.id;
-            let media_type_str = media.media_type.clone();
-            let uploader_id = user.user_id;
-            let mut redis = state.redis.clone();
-            let base = state.config.app_base_url.clone();
-
-            tokio::spawn(async move {
-                let s = schema_name(&tenant_c);
-
-                let (garderie_name, logo_url): (String, Option<String>) = sqlx::query_as(
-                    "SELECT name, logo_url FROM public.garderies WHERE slug = $1",
-                )
-                .bind(&tenant_c)
-                .fetch_optional(&pool)
-                .await
-                .unwrap_or_default()
-                .unwrap_or_else(|| (tenant_c.clone(), None));
-                let logo_url = logo_url.unwrap_or_default();
-
-                let uploader_name: String = sqlx::query_scalar(&format!(
-                    "SELECT CONCAT(first_name, ' ', last_name) FROM {s}.users WHERE id = $1"
-                ))
-                .bind(uploader_id)
-                .fetch_optional(&pool)
-                .await
-                .unwrap_or_default()
-                .unwrap_or_else(|| "Un éducateur".to_string());
-
-                let app_url = if let Some(idx) = base.find("://") {
-                    let scheme = &base[..idx];
-                    let domain = &base[idx + 3..];
-                    format!("{scheme}://{tenant_c}.{domain}/fr/parent/media")
-                } else {
-                    format!("https://{tenant_c}.{base}/fr/parent/media")
-                };
-
-                let recipients: Vec<(Uuid, String, String)> = match visibility.as_str() {
-                    "public" => sqlx::query_as(&format!(
-                        "SELECT id, email, CONCAT(first_name, ' ', last_name)
-                         FROM {s}.users WHERE role::text = 'parent' AND is_active = TRUE"
-                    ))
-                    .fetch_all(&pool)
-                    .await
-                    .unwrap_or_default(),
-
-                    "group" => match group_id {
-                        Some(gid) => sqlx::query_as(&format!(
-                            "SELECT DISTINCT u.id, u.email, CONCAT(u.first_name, ' ', u.last_name)
-                             FROM {s}.users u
-                             JOIN {s}.child_parents cp ON cp.user_id = u.id
-                             JOIN {s}.children c ON c.id = cp.child_id
-                             WHERE c.group_id = $1 AND u.is_active = TRUE"
-                        ))
-                        .bind(gid)
-                        .fetch_all(&pool)
-                        .await
-                        .unwrap_or_default(),
-                        None => vec![],
-                    },
-
-                    "child" => sqlx::query_as(&format!(
-                        "SELECT DISTINCT u.id, u.email, CONCAT(u.first_name, ' ', u.last_name)
-                         FROM {s}.users u
-                         JOIN {s}.child_parents cp ON cp.user_id = u.id
-                         JOIN {s}.media_children mc ON mc.child_id = cp.child_id
-                         WHERE mc.media_id = $1 AND u.is_active = TRUE"
-                    ))
-                    .bind(media_id)
-                    .fetch_all(&pool)
-                    .await
-                    .unwrap_or_default(),
-
-                    _ => vec![],
-                };
-
-                let content_kind = if media_type_str == "video" {
-                    "une vidéo"
-                } else {
-                    "de nouvelles photos"
-                };
-
-                for (parent_id, email, name) in recipients {
-                    let cooldown_key =
-                        format!("notif_cooldown:{tenant_c}:media_upload:{parent_id}");
-                    let newly_set: Option<String> = redis::cmd("SET")
-                        .arg(&cooldown_key)
-                        .arg("1")
-                        .arg("NX")
-                        .arg("EX")
-                        .arg(3600u64)
-                        .query_async(&mut redis)
-                        .await
-                        .unwrap_or(None);
-
-                    if newly_set.is_some() {
-                        let _ = email_svc
-                            .send_media_notification(
-                                &email,
-                                &name,
-                                &uploader_name,
-                                content_kind,
-                                &app_url,
-                                &garderie_name,
-                                &logo_url,
-                            )
-                            .await;
-                    }
-                }
-            });
-        }
+    if media.visibility != "private" {
+        events::publish(&mut state.redis.clone(), &tenant, DomainEvent::MediaPublished {
+            media_id: media.id,
+            uploader_id: user.user_id,
+        })
+        .await;
     }
-    } // end if tenant != "demo"
 
-    Ok(Json(serde_json::to_value(media).unwrap()))
+    Ok(versioned(media.updated_at, serde_json::to_value(media).unwrap()))
 }
 
 pub async fn delete_media(
@@ -542,29 +546,97 @@ pub async fn delete_media(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, Json<Value>)> {
+) -> Result<StatusCode, ApiError> {
+    let is_staff = !matches!(user.role, UserRole::Parent);
+    match MediaService::delete(&state.db_bulk, &tenant, id, user.user_id, is_staff, &state.config.media_dir).await {
+        Ok(true) => {
+            StorageService::invalidate(&mut state.redis.clone(), &tenant).await;
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Ok(false) => Err(ApiError::from_key(StatusCode::NOT_FOUND, "not_found")),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+/// POST /media/{id}/favorite — star a media for oneself
+pub async fn add_favorite(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    set_mark(state, tenant, user, id, MediaMark::Favorite, true).await
+}
+
+/// DELETE /media/{id}/favorite
+pub async fn remove_favorite(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    set_mark(state, tenant, user, id, MediaMark::Favorite, false).await
+}
+
+/// POST /media/{id}/hide — leave a media out of one's own gallery (see `hidden=1`)
+pub async fn hide_media(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    set_mark(state, tenant, user, id, MediaMark::Hidden, true).await
+}
+
+/// DELETE /media/{id}/hide
+pub async fn unhide_media(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    set_mark(state, tenant, user, id, MediaMark::Hidden, false).await
+}
+
+/// Marks are the user's own, on media they may see.
+async fn set_mark(
+    state: AppState,
+    tenant: String,
+    user: AuthenticatedUser,
+    id: Uuid,
+    mark: MediaMark,
+    on: bool,
+) -> Result<StatusCode, ApiError> {
     let is_staff = !matches!(user.role, UserRole::Parent);
-    match MediaService::delete(&state.db, &tenant, id, user.user_id, is_staff, &state.config.media_dir).await {
-        Ok(true) => Ok(StatusCode::NO_CONTENT),
-        Ok(false) => Err((StatusCode::NOT_FOUND, Json(json!({ "error": "not found" })))),
-        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))),
+    let scope = GroupService::educator_scope(&state.db, &tenant, &user).await?;
+    let visible = MediaService::is_visible(&state.db, &tenant, id, user.user_id, is_staff, scope.as_deref())
+        .await?;
+    if !visible {
+        return Err(ApiError::from_key(StatusCode::NOT_FOUND, "not_found"));
     }
+    MediaService::mark(&state.db, &tenant, id, user.user_id, mark, on).await?;
+    Ok(StatusCode::NO_CONTENT)
 }
 
 pub async fn bulk_media(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-    Json(req): Json<BulkMediaRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(req): ValidJson<BulkMediaRequest>,
+) -> Result<Json<Value>, ApiError> {
     // Only staff can perform bulk operations
     if matches!(user.role, UserRole::Parent) {
-        return Err((StatusCode::FORBIDDEN, Json(json!({ "error": "forbidden" }))));
+        return Err(ApiError::forbidden());
     }
 
-    match MediaService::bulk(&state.db, &tenant, &req, &state.config.media_dir).await {
-        Ok(count) => Ok(Json(json!({ "affected": count }))),
-        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))),
+    match MediaService::bulk(&state.db_bulk, &tenant, &req, &state.config.media_dir).await {
+        Ok(count) => {
+            if req.action == "delete" {
+                StorageService::invalidate(&mut state.redis.clone(), &tenant).await;
+            }
+            Ok(Json(json!({ "affected": count })))
+        }
+        Err(e) => Err(ApiError::from(e)),
     }
 }
 
@@ -0,0 +1,65 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    error::ApiError,
+    middleware::{tenant::TenantSlug, validation::ValidJson},
+    models::{auth::AuthenticatedUser, media::CreateMediaCommentRequest},
+    services::{
+        audit::{self, AuditEntry},
+        events::{self, DomainEvent},
+        media_comments::MediaCommentService,
+    },
+    AppState,
+};
+
+/// GET /media/{id}/comments — staff, and the parents of the children in the media
+pub async fn list_comments(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(media_id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    let comments = MediaCommentService::list(&state.db, &tenant, &user, media_id).await?;
+    Ok(Json(json!(comments)))
+}
+
+/// POST /media/{id}/comments — the uploader and earlier commenters are notified
+pub async fn create_comment(
+    State(mut state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(media_id): Path<Uuid>,
+    ValidJson(body): ValidJson<CreateMediaCommentRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let comment = MediaCommentService::create(&state.db, &tenant, &user, media_id, &body.content).await?;
+    events::publish(&mut state.redis, &tenant, DomainEvent::MediaCommented { media_id, comment_id: comment.id }).await;
+    Ok((StatusCode::CREATED, Json(json!(comment))))
+}
+
+/// DELETE /media/{id}/comments/{comment_id} — the author, or an admin moderating
+pub async fn delete_comment(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path((media_id, comment_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, ApiError> {
+    let comment = MediaCommentService::delete(&state.db, &tenant, &user, media_id, comment_id).await?;
+    if comment.author_id != Some(user.user_id) {
+        audit::log(state.db.clone(), &tenant, AuditEntry {
+            user_id:        Some(user.user_id),
+            user_name:      None,
+            action:         "media_comment.remove".to_string(),
+            resource_type:  Some("media".to_string()),
+            resource_id:    Some(media_id.to_string()),
+            resource_label: Some(comment.content.chars().take(100).collect()),
+            ip_address:     "unknown".to_string(),
+        });
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
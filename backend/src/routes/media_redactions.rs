@@ -0,0 +1,117 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    db::tenant_db::TenantDb,
+    error::ApiError,
+    middleware::{tenant::TenantSlug, validation::ValidJson},
+    models::{auth::AuthenticatedUser, media::RedactionRequest, user::UserRole},
+    services::{
+        audit::{self, AuditEntry},
+        encryption::KeyRing,
+        groups::GroupService,
+        media::MediaService,
+        redaction::{FaceDetector, RedactionError, RedactionService},
+    },
+    AppState,
+};
+
+/// Blurred copies are managed by staff who can see the photo.
+async fn require_staff(state: &AppState, tenant: &str, user: &AuthenticatedUser, media_id: Uuid) -> Result<(), ApiError> {
+    if user.role == UserRole::Parent {
+        return Err(ApiError::forbidden());
+    }
+    let scope = GroupService::educator_scope(&state.db, tenant, user).await?;
+    if !MediaService::is_visible(&state.db, tenant, media_id, user.user_id, true, scope.as_deref()).await? {
+        return Err(RedactionError::MediaNotFound.into());
+    }
+    Ok(())
+}
+
+fn audit_entry(user: &AuthenticatedUser, action: &str, media_id: Uuid) -> AuditEntry {
+    AuditEntry {
+        user_id:        Some(user.user_id),
+        user_name:      None,
+        action:         action.to_string(),
+        resource_type:  Some("media".to_string()),
+        resource_id:    Some(media_id.to_string()),
+        resource_label: None,
+        ip_address:     "unknown".to_string(),
+    }
+}
+
+/// GET /media/{id}/redaction — staff only; `redaction` is null when none was requested
+pub async fn get_redaction(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(media_id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    require_staff(&state, &tenant, &user, media_id).await?;
+    let redaction = RedactionService::get(&state.db, &tenant, media_id).await?;
+    Ok(Json(json!({
+        "redaction": redaction,
+        "detection_enabled": state.config.face_detection_url.is_some(),
+    })))
+}
+
+/// PUT /media/{id}/redaction — staff only. Blur the regions drawn, or the faces the
+/// detection service finds (`detect`). The copy is rendered in the background and then
+/// shown to parents instead of the photo wherever a child without photo consent appears.
+pub async fn request_redaction(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(media_id): Path<Uuid>,
+    ValidJson(body): ValidJson<RedactionRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_staff(&state, &tenant, &user, media_id).await?;
+    let redaction = RedactionService::request(
+        &state.db,
+        &tenant,
+        media_id,
+        user.user_id,
+        &body.regions,
+        body.detect,
+        state.config.face_detection_url.is_some(),
+    )
+    .await?;
+    audit::log(state.db.clone(), &tenant, audit_entry(&user, "media.redaction.request", media_id));
+
+    // Render right away; the redaction job picks it up again if this fails
+    let pool = state.db_bulk.clone();
+    let config = state.config.clone();
+    let slug = tenant.clone();
+    tokio::spawn(TenantDb::scope(slug.clone(), async move {
+        let result = async {
+            let keys = KeyRing::from_config(&config)?;
+            let detector = FaceDetector::new(&config);
+            RedactionService::process_pending(&pool, &config.media_dir, &keys, detector.as_ref(), &slug).await
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::warn!("Redaction of media {media_id} in '{slug}' deferred to the redaction job: {e}");
+        }
+    }));
+
+    Ok((StatusCode::ACCEPTED, Json(json!(redaction))))
+}
+
+/// DELETE /media/{id}/redaction — staff only; the photo is hidden again from parents
+/// of other children wherever a child without photo consent appears
+pub async fn delete_redaction(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(media_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    require_staff(&state, &tenant, &user, media_id).await?;
+    RedactionService::remove(&state.db, &tenant, &state.config.media_dir, media_id).await?;
+    audit::log(state.db.clone(), &tenant, audit_entry(&user, "media.redaction.remove", media_id));
+    Ok(StatusCode::NO_CONTENT)
+}
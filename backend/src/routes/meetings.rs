@@ -0,0 +1,181 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Local;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    db::tenant_db::TenantDb,
+    error::ApiError,
+    middleware::tenant::TenantSlug,
+    models::{
+        auth::AuthenticatedUser,
+        meeting::{AgendaQuery, BookSlotRequest, MeetingDetails, PublishSlotsRequest, SlotQuery},
+        user::UserRole,
+    },
+    services::{
+        branding::BrandingService,
+        children::ChildService,
+        meetings::{MeetingError, MeetingService},
+    },
+    AppState,
+};
+use crate::middleware::validation::ValidJson;
+
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
+        _ => Err(ApiError::forbidden()),
+    }
+}
+
+/// Email the parent about a booking change in the background (non-fatal).
+fn notify_parent(state: &AppState, tenant: &str, meeting: MeetingDetails, cancelled: bool) {
+    let Some(svc) = state.email.clone() else {
+        return;
+    };
+    if tenant == "demo" {
+        return;
+    }
+    let pool = state.db.clone();
+    let tenant = tenant.to_string();
+    tokio::spawn(TenantDb::scope(tenant.clone(), async move {
+        let (garderie_name, branding) = BrandingService::for_email(&pool, &tenant).await;
+        let sent = if cancelled {
+            svc.send_meeting_cancellation(&tenant, &meeting, &garderie_name, &branding).await
+        } else {
+            svc.send_meeting_confirmation(&tenant, &meeting, &garderie_name, &branding).await
+        };
+        if let Err(e) = sent {
+            tracing::warn!("Meeting email to {} failed: {e}", meeting.parent_email);
+        }
+    }));
+}
+
+/// GET /meetings/slots?from=&to=&educator_id= — all authenticated users
+pub async fn list_slots(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(query): Query<SlotQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let is_staff = user.role != UserRole::Parent;
+    MeetingService::list_slots(&state.db, &tenant, &query, is_staff)
+        .await
+        .map(|slots| Json(serde_json::to_value(slots).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// POST /meetings/slots — admins publish available slots for an educator
+pub async fn publish_slots(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<PublishSlotsRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&user)?;
+    MeetingService::publish_slots(&state.db, &tenant, &body, user.user_id)
+        .await
+        .map(|created| (StatusCode::CREATED, Json(json!({ "created": created }))))
+        .map_err(ApiError::from)
+}
+
+/// DELETE /meetings/slots/{id} — admins only, unbooked slots only
+pub async fn delete_slot(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    require_admin(&user)?;
+    MeetingService::delete_slot(&state.db, &tenant, id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(ApiError::from)
+}
+
+/// POST /meetings/slots/{id}/book — parents book a slot for one of their children
+pub async fn book_slot(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidJson(body): ValidJson<BookSlotRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    if user.role != UserRole::Parent {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "Seuls les parents peuvent réserver une rencontre"));
+    }
+    let is_parent = ChildService::is_parent_of(&state.db, &tenant, body.child_id, user.user_id)
+        .await?;
+    if !is_parent {
+        return Err(ApiError::forbidden());
+    }
+
+    let meeting = MeetingService::book(&state.db, &tenant, id, user.user_id, &body)
+        .await?;
+    notify_parent(&state, &tenant, meeting.clone(), false);
+    Ok((StatusCode::CREATED, Json(serde_json::to_value(meeting).unwrap())))
+}
+
+/// GET /meetings/bookings — a parent's upcoming meetings (all upcoming ones for staff)
+pub async fn list_bookings(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    let parent_id = (user.role == UserRole::Parent).then_some(user.user_id);
+    MeetingService::list_upcoming(&state.db, &tenant, parent_id)
+        .await
+        .map(|bookings| Json(serde_json::to_value(bookings).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// DELETE /meetings/bookings/{id} — the booking parent or an admin cancels
+pub async fn cancel_booking(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    let booking = MeetingService::get_booking(&state.db, &tenant, id)
+        .await?
+        .ok_or_else(|| ApiError::from(MeetingError::BookingNotFound))?;
+
+    let allowed = match user.role {
+        UserRole::Parent => booking.parent_id == user.user_id,
+        _ => require_admin(&user).is_ok(),
+    };
+    if !allowed {
+        return Err(ApiError::forbidden());
+    }
+
+    let meeting = MeetingService::cancel(&state.db, &tenant, id)
+        .await?;
+    notify_parent(&state, &tenant, meeting.clone(), true);
+    Ok(Json(serde_json::to_value(meeting).unwrap()))
+}
+
+/// GET /meetings/agenda?date=YYYY-MM-DD — an educator's booked meetings for the day.
+/// Admins may pass `educator_id`, or omit it to see every educator's meetings.
+pub async fn get_agenda(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(query): Query<AgendaQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let educator_id = match user.role {
+        UserRole::Parent => {
+            return Err(ApiError::forbidden());
+        }
+        UserRole::Educateur => Some(user.user_id),
+        _ => query.educator_id,
+    };
+    let date = query.date.unwrap_or_else(|| Local::now().date_naive());
+    MeetingService::agenda(&state.db, &tenant, educator_id, date)
+        .await
+        .map(|meetings| Json(json!({ "date": date, "meetings": meetings })))
+        .map_err(ApiError::from)
+}
@@ -1,20 +1,24 @@
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
     Json,
 };
-use serde_json::{json, Value};
+use serde_json::Value;
 
 use crate::{
+    error::ApiError,
     middleware::tenant::TenantSlug,
     models::{
         auth::AuthenticatedUser,
-        menu::{MenuWeekQuery, UpsertMenuRequest},
+        menu::{MenuWeekQuery, PlanWeekRequest, UpsertMenuRequest},
         user::UserRole,
     },
-    services::menu::MenuService,
+    services::{
+        children::ChildService,
+        menu::MenuService,
+    },
     AppState,
 };
+use crate::middleware::validation::ValidJson;
 
 /// GET /menus?week_start=YYYY-MM-DD — all authenticated users (parents included)
 pub async fn get_week(
@@ -22,16 +26,11 @@ pub async fn get_week(
     TenantSlug(tenant): TenantSlug,
     _user: AuthenticatedUser,
     Query(params): Query<MenuWeekQuery>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     MenuService::list_week(&state.db, &tenant, params.week_start)
         .await
         .map(|entries| Json(serde_json::to_value(entries).unwrap()))
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })
+        .map_err(ApiError::from)
 }
 
 /// PUT /menus — educators and admins only
@@ -39,22 +38,49 @@ pub async fn upsert_menu(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-    Json(body): Json<UpsertMenuRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<UpsertMenuRequest>,
+) -> Result<Json<Value>, ApiError> {
     if let UserRole::Parent = user.role {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(json!({ "error": "Accès refusé" })),
-        ));
+        return Err(ApiError::forbidden());
     }
 
     MenuService::upsert(&state.db, &tenant, &body, user.user_id)
         .await
         .map(|entry| Json(serde_json::to_value(entry).unwrap()))
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })
+        .map_err(ApiError::from)
+}
+
+/// GET /menus/week?week_start=YYYY-MM-DD — the week's menus with allergen
+/// conflicts for the children visible to the caller
+pub async fn get_week_view(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(params): Query<MenuWeekQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let children = ChildService::list_visible_to(&state.db, &tenant, &user).await;
+
+    let view = match children {
+        Ok(children) => MenuService::week_view(&state.db, &tenant, params.week_start, &children).await,
+        Err(e) => Err(e),
+    };
+
+    view.map(|v| Json(serde_json::to_value(v).unwrap())).map_err(ApiError::from)
+}
+
+/// PUT /menus/week — weekly planner, educators and admins only
+pub async fn plan_week(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<PlanWeekRequest>,
+) -> Result<Json<Value>, ApiError> {
+    if let UserRole::Parent = user.role {
+        return Err(ApiError::forbidden());
+    }
+
+    MenuService::plan_week(&state.db, &tenant, &body, user.user_id)
+        .await
+        .map(|menus| Json(serde_json::to_value(menus).unwrap()))
+        .map_err(ApiError::from)
 }
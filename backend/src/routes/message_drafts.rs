@@ -0,0 +1,117 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    error::ApiError,
+    middleware::tenant::TenantSlug,
+    models::{
+        auth::AuthenticatedUser,
+        message_draft::{DraftQuery, MessageDraft, MessageDraftRequest, DRAFT_VARIABLES},
+        user::UserRole,
+    },
+    services::message_drafts::{can_edit, can_view, DraftError, MessageDraftService},
+    AppState,
+};
+use crate::middleware::validation::ValidJson;
+
+fn require_staff(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::Parent => Err(ApiError::forbidden()),
+        _ => Ok(()),
+    }
+}
+
+/// A draft the user may see; another educator's private draft reads as missing.
+async fn visible_draft(
+    state: &AppState,
+    tenant: &str,
+    user: &AuthenticatedUser,
+    id: Uuid,
+) -> Result<MessageDraft, ApiError> {
+    MessageDraftService::get(&state.db, tenant, id)
+        .await?
+        .filter(|d| can_view(user, d))
+        .ok_or_else(|| ApiError::from(DraftError::NotFound))
+}
+
+/// GET /messages/drafts — the caller's drafts and the shared templates, with the
+/// variables they may use
+pub async fn list_drafts(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(q): Query<DraftQuery>,
+) -> Result<Json<Value>, ApiError> {
+    require_staff(&user)?;
+    let drafts = MessageDraftService::list(&state.db, &tenant, user.user_id, q.templates)
+        .await?;
+    Ok(Json(json!({ "drafts": drafts, "variables": DRAFT_VARIABLES })))
+}
+
+/// POST /messages/drafts — staff only
+pub async fn create_draft(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<MessageDraftRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_staff(&user)?;
+    MessageDraftService::create(&state.db, &tenant, user.user_id, &body)
+        .await
+        .map(|d| (StatusCode::CREATED, Json(serde_json::to_value(d).unwrap())))
+        .map_err(ApiError::from)
+}
+
+/// GET /messages/drafts/{id}
+pub async fn get_draft(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    require_staff(&user)?;
+    let draft = visible_draft(&state, &tenant, &user, id).await?;
+    Ok(Json(serde_json::to_value(draft).unwrap()))
+}
+
+/// PUT /messages/drafts/{id} — the author, or an admin for a shared template
+pub async fn update_draft(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidJson(body): ValidJson<MessageDraftRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_staff(&user)?;
+    let existing = visible_draft(&state, &tenant, &user, id).await?;
+    if !can_edit(&user, &existing) {
+        return Err(ApiError::from(DraftError::Forbidden));
+    }
+    MessageDraftService::update(&state.db, &tenant, id, &body)
+        .await
+        .map(|d| Json(serde_json::to_value(d).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// DELETE /messages/drafts/{id} — the author, or an admin for a shared template
+pub async fn delete_draft(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    require_staff(&user)?;
+    let existing = visible_draft(&state, &tenant, &user, id).await?;
+    if !can_edit(&user, &existing) {
+        return Err(ApiError::from(DraftError::Forbidden));
+    }
+    MessageDraftService::delete(&state.db, &tenant, id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(ApiError::from)
+}
@@ -1,38 +1,91 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     Json,
 };
-use redis::AsyncCommands;
 use serde_json::{json, Value};
 use uuid::Uuid;
 
 use serde::Deserialize;
 
 use crate::{
-    db::tenant::schema_name,
-    middleware::tenant::TenantSlug,
+    db::{tenant::schema_name, tenant_db::TenantDb},
+    error::ApiError,
+    middleware::{fields::Fields, tenant::TenantSlug},
     models::{
         auth::AuthenticatedUser,
-        message::{CreateMessageRequest, MessageType, PaginationQuery, SendToParentsRequest},
+        email_template::TEMPLATE_ANNOUNCEMENT,
+        message::{
+            ConversationQuery, CreateMessageRequest, MessageType, MessageWithSender, PaginationQuery,
+            ReactionQuery, ReactionRequest, SendToParentsRequest, UpdateAutoReplyRequest, UpdateMessageRequest,
+            UpdateThreadStateRequest, WsMessage,
+        },
         user::UserRole,
     },
-    services::messages::MessageService,
+    services::{
+        audit::{self, AuditEntry},
+        auto_replies::AutoReplyService,
+        branding::BrandingService,
+        email_templates::EmailTemplateService,
+        email_tracking::EmailTrackingService,
+        events::{self, DomainEvent},
+        groups::GroupService,
+        message_deliveries::MessageDeliveryService,
+        message_drafts::MessageDraftService,
+        messages::{MessageChangeError, MessageService},
+        presence::PresenceService,
+        realtime::RealtimeService,
+        reactions::ReactionService,
+        thread_states::{self, ThreadStateService},
+        unread::UnreadService,
+    },
     AppState,
 };
+use validator::Validate;
+use crate::middleware::validation::ValidJson;
+
+fn client_ip(h: &HeaderMap) -> String {
+    h.get("x-real-ip").and_then(|v| v.to_str().ok())
+        .or_else(|| h.get("x-forwarded-for").and_then(|v| v.to_str().ok())
+            .and_then(|s| s.split(',').next()).map(|s| s.trim()))
+        .unwrap_or("unknown")
+        .to_string()
+}
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Validate)]
 pub struct MarkThreadReadRequest {
     pub kind: String,
     pub id: Option<String>,
 }
 
+/// Attach the viewer's reaction counts to a thread page.
+async fn with_reactions(
+    state: &AppState,
+    tenant: &str,
+    viewer: Uuid,
+    mut msgs: Vec<MessageWithSender>,
+) -> Result<Json<Value>, ApiError> {
+    ReactionService::annotate(&state.db, tenant, viewer, &mut msgs)
+        .await?;
+    Ok(Json(serde_json::to_value(msgs).unwrap()))
+}
+
+/// Publish a typed event (e.g. "message_updated") so open WebSocket clients update in place.
+async fn publish_event(state: &mut AppState, tenant: &str, kind: &str, msg: &MessageWithSender) {
+    let event = WsMessage {
+        kind: kind.to_string(),
+        payload: serde_json::to_value(msg).unwrap_or_default(),
+    };
+    let payload = serde_json::to_string(&event).unwrap_or_default();
+    RealtimeService::publish_message(&state.db, &mut state.redis, tenant, msg, &payload).await;
+}
+
 pub async fn list_messages(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Query(pagination): Query<PaginationQuery>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     MessageService::list_messages(
         &state.db,
         &tenant,
@@ -42,27 +95,25 @@ pub async fn list_messages(
     )
     .await
     .map(|msgs| Json(serde_json::to_value(msgs).unwrap()))
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })
+    .map_err(ApiError::from)
 }
 
 pub async fn send_message(
     State(mut state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-    Json(body): Json<CreateMessageRequest>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    ValidJson(mut body): ValidJson<CreateMessageRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
     // Permission checks pour les parents
     if let UserRole::Parent = user.role {
+        if body.urgent {
+            return Err(ApiError::new(StatusCode::FORBIDDEN, "Seul le personnel peut envoyer un message urgent"));
+        }
         if let MessageType::Broadcast = body.message_type {
-            return Err((
-                StatusCode::FORBIDDEN,
-                Json(json!({ "error": "Les parents ne peuvent pas envoyer de diffusion générale" })),
-            ));
+            return Err(ApiError::new(StatusCode::FORBIDDEN, "Les parents ne peuvent pas envoyer de diffusion générale"));
+        }
+        if let MessageType::Staff = body.message_type {
+            return Err(ApiError::forbidden());
         }
         if let MessageType::Group = body.message_type {
             if let Some(group_id) = body.group_id {
@@ -81,213 +132,67 @@ pub async fn send_message(
                 .unwrap_or(false);
 
                 if !belongs {
-                    return Err((
-                        StatusCode::FORBIDDEN,
-                        Json(json!({ "error": "Accès refusé à ce groupe" })),
-                    ));
+                    return Err(ApiError::new(StatusCode::FORBIDDEN, "Accès refusé à ce groupe"));
+                }
+
+                let replies_allowed = GroupService::parent_replies_allowed(&state.db, &tenant, group_id)
+                    .await
+                    .unwrap_or(false);
+                if !replies_allowed {
+                    return Err(ApiError::new(StatusCode::FORBIDDEN, "Les parents ne peuvent pas écrire dans ce groupe")
+                        .with_code("group_replies_disabled"));
                 }
             }
         }
     }
 
-    let msg = MessageService::create_message(&state.db, &tenant, user.user_id, &body)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })?;
-
-    // Publish to Redis for real-time delivery
-    let payload = serde_json::to_string(&msg).unwrap_or_default();
-    let channel = format!("tenant:{}:messages", tenant);
-    let _ = state.redis.publish::<_, _, ()>(&channel, &payload).await;
-
-    // Email notifications asynchrones avec cooldown par fil (15 min)
-    // Désactivé pour le tenant demo (adresses email fictives)
-    if tenant != "demo" {
-    if let Some(email_svc) = state.email.clone() {
-        let pool = state.db.clone();
-        let tenant_c = tenant.clone();
-        let msg_clone = msg.clone();
-        let base = &state.config.app_base_url;
-        let app_url = if let Some(idx) = base.find("://") {
-            let scheme = &base[..idx];
-            let domain = &base[idx + 3..];
-            format!("{scheme}://{tenant}.{domain}/fr/dashboard/messages")
-        } else {
-            format!("https://{tenant}.{base}/fr/dashboard/messages")
-        };
-        let sender_name = format!("{} {}", msg.sender_first_name, msg.sender_last_name);
-        let mut redis = state.redis.clone();
-
-        tokio::spawn(async move {
-            let s = schema_name(&tenant_c);
-
-            // Nom et logo de la garderie pour les emails
-            let (garderie_name, logo_url): (String, Option<String>) = sqlx::query_as(
-                "SELECT name, logo_url FROM public.garderies WHERE slug = $1"
-            )
-            .bind(&tenant_c)
-            .fetch_optional(&pool)
+    // Educators assigned to groups may only post in their groups' threads (and staff
+    // channels); archived groups' threads are read-only.
+    if let (MessageType::Group | MessageType::Staff, Some(group_id)) = (&body.message_type, body.group_id) {
+        GroupService::ensure_group_access(&state.db, &tenant, &user, group_id)
+            .await?;
+        GroupService::ensure_not_archived(&state.db, &tenant, group_id)
+            .await?;
+    }
+
+    // Staff messages may come from a template: fill in {child_name} / {group_name}
+    if user.role != UserRole::Parent && body.content.contains('{') {
+        let vars = MessageDraftService::variables_for(&state.db, &tenant, body.group_id, body.recipient_id)
             .await
-            .unwrap_or_default()
-            .unwrap_or_else(|| (tenant_c.clone(), None));
-            let logo_url = logo_url.unwrap_or_default();
-
-            // Clé cooldown unique par fil — empêche les doublons pendant 15 min
-            let cooldown_key = match msg_clone.message_type.as_str() {
-                "broadcast" => format!("notif_cooldown:{tenant_c}:broadcast"),
-                "group" => match msg_clone.group_id {
-                    Some(gid) => format!("notif_cooldown:{tenant_c}:group:{gid}"),
-                    None => return,
-                },
-                "individual" => {
-                    // thread identifié par l'ID du parent (recipient si admin→parent, sender si parent→admin)
-                    let thread_id = msg_clone.recipient_id.unwrap_or(msg_clone.sender_id);
-                    format!("notif_cooldown:{tenant_c}:individual:{thread_id}")
-                }
-                _ => return,
-            };
-
-            // SET NX EX : retourne "OK" si la clé est nouvellement posée, None si elle existait déjà
-            let newly_set: Option<String> = redis::cmd("SET")
-                .arg(&cooldown_key)
-                .arg("1")
-                .arg("NX")
-                .arg("EX")
-                .arg(900u64) // 15 minutes
-                .query_async(&mut redis)
-                .await
-                .unwrap_or(None);
+            .unwrap_or_default();
+        body.content = vars.expand(&body.content);
+    }
 
-            if newly_set.is_none() {
-                // Notification déjà envoyée récemment pour ce fil → on skip
-                return;
-            }
+    let msg = MessageService::create_message(&state.db, &tenant, user.user_id, &body)
+        .await?;
+
+    // Real-time update, unread counters, push, urgent SMS and email notifications
+    // (cooldown par fil) are sent by the notification consumer
+    events::publish(&mut state.redis, &tenant, DomainEvent::MessageSent {
+        message: Box::new(msg.clone()),
+        urgent: body.urgent,
+        notify_email: true,
+    })
+    .await;
 
-            match msg_clone.message_type.as_str() {
-                "broadcast" => {
-                    let recipients: Vec<(String, String)> = sqlx::query_as(&format!(
-                        "SELECT email, CONCAT(first_name, ' ', last_name)
-                         FROM {s}.users
-                         WHERE role::text = 'parent' AND is_active = TRUE"
-                    ))
-                    .fetch_all(&pool)
-                    .await
-                    .unwrap_or_default();
-
-                    for (email, name) in recipients {
-                        let _ = email_svc
-                            .send_message_notification(
-                                &email,
-                                &name,
-                                &sender_name,
-                                "Tous les parents",
-                                &app_url,
-                                &garderie_name,
-                                &logo_url,
-                            )
-                            .await;
-                    }
-                }
-                "group" => {
-                    if let Some(group_id) = msg_clone.group_id {
-                        let recipients: Vec<(String, String)> = sqlx::query_as(&format!(
-                            "SELECT DISTINCT u.email, CONCAT(u.first_name, ' ', u.last_name)
-                             FROM {s}.users u
-                             JOIN {s}.child_parents cp ON cp.user_id = u.id
-                             JOIN {s}.children c ON c.id = cp.child_id
-                             WHERE c.group_id = $1 AND u.is_active = TRUE"
-                        ))
-                        .bind(group_id)
-                        .fetch_all(&pool)
-                        .await
-                        .unwrap_or_default();
-
-                        let group_name: String = sqlx::query_scalar(&format!(
-                            "SELECT name FROM {s}.groups WHERE id = $1"
-                        ))
-                        .bind(group_id)
-                        .fetch_optional(&pool)
-                        .await
-                        .unwrap_or_default()
-                        .unwrap_or_else(|| "Groupe".to_string());
-
-                        for (email, name) in recipients {
-                            let _ = email_svc
-                                .send_message_notification(
-                                    &email,
-                                    &name,
-                                    &sender_name,
-                                    &group_name,
-                                    &app_url,
-                                    &garderie_name,
-                                    &logo_url,
-                                )
-                                .await;
-                        }
-                    }
-                }
-                "individual" => {
-                    if let Some(recipient_id) = msg_clone.recipient_id {
-                        // Admin → parent
-                        let recipient: Option<(String, String)> = sqlx::query_as(&format!(
-                            "SELECT email, CONCAT(first_name, ' ', last_name)
-                             FROM {s}.users WHERE id = $1"
-                        ))
-                        .bind(recipient_id)
-                        .fetch_optional(&pool)
-                        .await
-                        .unwrap_or_default();
-
-                        if let Some((email, name)) = recipient {
-                            let _ = email_svc
-                                .send_message_notification(
-                                    &email,
-                                    &name,
-                                    &sender_name,
-                                    "Message privé",
-                                    &app_url,
-                                    &garderie_name,
-                                    &logo_url,
-                                )
-                                .await;
-                        }
-                    } else {
-                        // Parent → admin (recipient_id IS NULL)
-                        let admins: Vec<(String, String)> = sqlx::query_as(&format!(
-                            "SELECT email, CONCAT(first_name, ' ', last_name)
-                             FROM {s}.users
-                             WHERE role::text = 'admin_garderie' AND is_active = TRUE"
-                        ))
-                        .fetch_all(&pool)
-                        .await
-                        .unwrap_or_default();
-
-                        for (email, name) in admins {
-                            let _ = email_svc
-                                .send_message_notification(
-                                    &email,
-                                    &name,
-                                    &sender_name,
-                                    "Message privé",
-                                    &app_url,
-                                    &garderie_name,
-                                    &logo_url,
-                                )
-                                .await;
-                        }
-                    }
-                }
-                _ => {}
+    crate::services::metrics::MESSAGES_COUNTER.with_label_values(&[&tenant]).inc();
+
+    // Out-of-office of the staff member the parent is writing to
+    if user.role == UserRole::Parent {
+        match AutoReplyService::reply(&state.db, &tenant, &msg).await {
+            Ok(Some(reply)) => {
+                events::publish(&mut state.redis, &tenant, DomainEvent::MessageSent {
+                    message: Box::new(reply),
+                    urgent: false,
+                    notify_email: true,
+                })
+                .await;
             }
-        });
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Auto-reply to message {} in '{tenant}' failed: {e}", msg.id),
+        }
     }
-    } // end if tenant != "demo"
 
-    crate::services::metrics::MESSAGES_COUNTER.with_label_values(&[&tenant]).inc();
     Ok((
         StatusCode::CREATED,
         Json(serde_json::to_value(msg).unwrap()),
@@ -295,38 +200,225 @@ pub async fn send_message(
 }
 
 pub async fn mark_read(
+    State(mut state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(message_id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    let change = MessageService::mark_read(&state.db, &tenant, message_id, user.user_id)
+        .await?;
+    if let Some(change) = change {
+        if let Err(e) = UnreadService::message_read(&state.db, &mut state.redis, &tenant, &user, &change).await {
+            tracing::warn!("Unread counters not updated for a read in '{tenant}': {e}");
+        }
+    }
+    Ok(Json(json!({ "message": "Marked as read" })))
+}
+
+/// PUT /messages/:id — the sender may edit within the configured window.
+pub async fn edit_message(
+    State(mut state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(message_id): Path<Uuid>,
+    ValidJson(body): ValidJson<UpdateMessageRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let content = body.content.trim();
+    if content.is_empty() {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Le message ne peut pas être vide"));
+    }
+
+    let msg = MessageService::edit_message(
+        &state.db,
+        &tenant,
+        message_id,
+        user.user_id,
+        content,
+        state.config.message_edit_window_minutes,
+    )
+    .await?;
+
+    publish_event(&mut state, &tenant, "message_updated", &msg).await;
+
+    Ok(Json(serde_json::to_value(msg).unwrap()))
+}
+
+/// DELETE /messages/:id — the sender may delete within the configured window, and
+/// staff may remove parents' messages from group threads at any time (moderation);
+/// threads then show a tombstone in its place.
+pub async fn delete_message(
+    State(mut state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    headers: HeaderMap,
+    user: AuthenticatedUser,
+    Path(message_id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    let deleted = MessageService::delete_message(
+        &state.db,
+        &tenant,
+        message_id,
+        user.user_id,
+        state.config.message_edit_window_minutes,
+    )
+    .await;
+    let msg = match deleted {
+        Err(e) if user.role != UserRole::Parent
+            && matches!(e.downcast_ref::<MessageChangeError>(), Some(MessageChangeError::NotSender)) =>
+        {
+            let msg = MessageService::moderate_message(&state.db, &tenant, &user, message_id)
+                .await?;
+            audit::log(state.db.clone(), &tenant, AuditEntry {
+                user_id:        Some(user.user_id),
+                user_name:      None,
+                action:         "message.moderate".to_string(),
+                resource_type:  Some("message".to_string()),
+                resource_id:    Some(message_id.to_string()),
+                resource_label: Some(format!("{} {}", msg.sender_first_name, msg.sender_last_name)),
+                ip_address:     client_ip(&headers),
+            });
+            msg
+        }
+        deleted => deleted?,
+    };
+
+    if let Err(e) = UnreadService::message_removed(&state.db, &mut state.redis, &tenant, &msg).await {
+        tracing::warn!("Unread counters not updated for a deleted message in '{tenant}': {e}");
+    }
+    publish_event(&mut state, &tenant, "message_deleted", &msg).await;
+
+    Ok(Json(serde_json::to_value(msg).unwrap()))
+}
+
+/// GET /messages/:id/edits — edit history, visible to the sender and staff.
+pub async fn list_message_edits(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path(message_id): Path<Uuid>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    MessageService::mark_read(&state.db, &tenant, message_id, user.user_id)
+) -> Result<Json<Value>, ApiError> {
+    let msg = MessageService::get_with_sender(&state.db, &tenant, message_id)
+        .await?
+        .ok_or_else(|| ApiError::from_key(StatusCode::NOT_FOUND, "message_not_found"))?;
+
+    if matches!(user.role, UserRole::Parent) && msg.sender_id != user.user_id {
+        return Err(ApiError::forbidden());
+    }
+
+    MessageService::list_edits(&state.db, &tenant, message_id)
         .await
-        .map(|_| Json(json!({ "message": "Marked as read" })))
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })
+        .map(|edits| Json(serde_json::to_value(edits).unwrap()))
+        .map_err(ApiError::from)
 }
 
-pub async fn mark_thread_read(
+/// GET /messages/:id/reads — read receipts, staff only.
+pub async fn list_message_reads(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-    Json(body): Json<MarkThreadReadRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    Path(message_id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    if let UserRole::Parent = user.role {
+        return Err(ApiError::forbidden());
+    }
+
+    MessageService::list_reads(&state.db, &tenant, message_id)
+        .await?
+        .map(|receipts| Json(serde_json::to_value(receipts).unwrap()))
+        .ok_or_else(|| ApiError::from_key(StatusCode::NOT_FOUND, "message_not_found"))
+}
+
+/// GET /messages/:id/deliveries — email, push and SMS sent per recipient, staff only.
+pub async fn list_message_deliveries(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(message_id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    if let UserRole::Parent = user.role {
+        return Err(ApiError::forbidden());
+    }
+
+    MessageDeliveryService::report(&state.db, &tenant, message_id)
+        .await?
+        .map(|report| Json(serde_json::to_value(report).unwrap()))
+        .ok_or_else(|| ApiError::from_key(StatusCode::NOT_FOUND, "message_not_found"))
+}
+
+/// POST /messages/:id/reactions — react to a message the caller can see.
+pub async fn add_reaction(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(message_id): Path<Uuid>,
+    ValidJson(body): ValidJson<ReactionRequest>,
+) -> Result<Json<Value>, ApiError> {
+    set_reaction(state, tenant, user, message_id, &body.emoji, true).await
+}
+
+/// DELETE /messages/:id/reactions?emoji= — withdraw the caller's reaction.
+pub async fn remove_reaction(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(message_id): Path<Uuid>,
+    Query(query): Query<ReactionQuery>,
+) -> Result<Json<Value>, ApiError> {
+    set_reaction(state, tenant, user, message_id, &query.emoji, false).await
+}
+
+async fn set_reaction(
+    mut state: AppState,
+    tenant: String,
+    user: AuthenticatedUser,
+    message_id: Uuid,
+    emoji: &str,
+    added: bool,
+) -> Result<Json<Value>, ApiError> {
+    let event = ReactionService::set(&state.db, &tenant, &user, message_id, emoji, added)
+        .await?;
+
+    let payload = serde_json::to_string(&WsMessage {
+        kind: "message_reactions".to_string(),
+        payload: serde_json::to_value(&event).unwrap_or_default(),
+    })
+    .unwrap_or_default();
+    if let Ok(Some(msg)) = MessageService::get_with_sender(&state.db, &tenant, message_id).await {
+        RealtimeService::publish_message(&state.db, &mut state.redis, &tenant, &msg, &payload).await;
+    }
+
+    Ok(Json(serde_json::to_value(event.reactions).unwrap()))
+}
+
+pub async fn mark_thread_read(
+    State(mut state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<MarkThreadReadRequest>,
+) -> Result<Json<Value>, ApiError> {
+    if user.role == UserRole::Parent && body.kind == "staff" {
+        return Err(ApiError::forbidden());
+    }
     let thread_id = body.id.as_deref().and_then(|s| s.parse::<Uuid>().ok());
     MessageService::mark_thread_read(&state.db, &tenant, user.user_id, &body.kind, thread_id)
+        .await?;
+    if let Err(e) =
+        UnreadService::thread_read(&state.db, &mut state.redis, &tenant, user.user_id, &body.kind, thread_id).await
+    {
+        tracing::warn!("Unread counters not reset for a thread in '{tenant}': {e}");
+    }
+    Ok(Json(json!({ "message": "Thread marked as read" })))
+}
+
+/// GET /messages/unread-count — per-thread and total unread messages of the caller
+pub async fn unread_count(
+    State(mut state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    UnreadService::counts(&state.db, &mut state.redis, &tenant, &user)
         .await
-        .map(|_| Json(json!({ "message": "Thread marked as read" })))
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })
+        .map(|counts| Json(serde_json::to_value(counts).unwrap()))
+        .map_err(ApiError::from)
 }
 
 pub async fn get_conversation(
@@ -335,7 +427,7 @@ pub async fn get_conversation(
     user: AuthenticatedUser,
     Path(other_user_id): Path<Uuid>,
     Query(pagination): Query<PaginationQuery>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     MessageService::get_conversation(
         &state.db,
         &tenant,
@@ -346,107 +438,207 @@ pub async fn get_conversation(
     )
     .await
     .map(|msgs| Json(serde_json::to_value(msgs).unwrap()))
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })
+    .map_err(ApiError::from)
 }
 
-/// GET /messages/conversations
+/// GET /messages/conversations?archived=&muted=&pinned= — archived threads are
+/// only listed with archived=true.
 pub async fn get_conversations(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    Query(query): Query<ConversationQuery>,
+    fields: Fields,
+) -> Result<Json<Value>, ApiError> {
     let result = if matches!(user.role, UserRole::Parent) {
         MessageService::get_conversations_parent(&state.db, &tenant, user.user_id).await
     } else {
-        MessageService::get_conversations_admin(&state.db, &tenant, user.user_id).await
+        let mut result = MessageService::get_conversations_staff(&state.db, &tenant, &user).await;
+        if let Ok(items) = result.as_mut() {
+            PresenceService::annotate(&mut state.redis.clone(), &tenant, items).await;
+        }
+        result
     };
 
-    result
-        .map(|convs| Json(serde_json::to_value(convs).unwrap()))
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })
+    let mut convs = result?;
+    if let Err(e) = AutoReplyService::annotate(&state.db, &tenant, &mut convs).await {
+        tracing::warn!("Away status not shown in '{tenant}': {e}");
+    }
+    ThreadStateService::annotate(&state.db, &tenant, user.user_id, &mut convs)
+        .await?;
+    convs.retain(|c| thread_states::matches(c, &query));
+
+    let mut body = serde_json::to_value(convs).unwrap();
+    fields.select(&mut body);
+    Ok(Json(body))
+}
+
+/// GET /messages/auto-reply — the caller's out-of-office, `null` if none (staff only).
+pub async fn get_auto_reply(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    if let UserRole::Parent = user.role {
+        return Err(ApiError::forbidden());
+    }
+    AutoReplyService::get(&state.db, &tenant, user.user_id)
+        .await
+        .map(|reply| Json(serde_json::to_value(reply).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// PUT /messages/auto-reply — set the caller's out-of-office dates and message.
+pub async fn update_auto_reply(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<UpdateAutoReplyRequest>,
+) -> Result<Json<Value>, ApiError> {
+    if let UserRole::Parent = user.role {
+        return Err(ApiError::forbidden());
+    }
+    AutoReplyService::set(&state.db, &tenant, user.user_id, &body)
+        .await
+        .map(|reply| Json(serde_json::to_value(reply).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// DELETE /messages/auto-reply — back in the office.
+pub async fn delete_auto_reply(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<StatusCode, ApiError> {
+    if let UserRole::Parent = user.role {
+        return Err(ApiError::forbidden());
+    }
+    match AutoReplyService::clear(&state.db, &tenant, user.user_id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(ApiError::from_key(StatusCode::NOT_FOUND, "not_found")),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+/// PUT /messages/thread/state — archive, mute or pin a thread for the caller only.
+pub async fn update_thread_state(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<UpdateThreadStateRequest>,
+) -> Result<Json<Value>, ApiError> {
+    ThreadStateService::update(&state.db, &tenant, user.user_id, &body)
+        .await
+        .map(|thread_state| Json(serde_json::to_value(thread_state).unwrap()))
+        .map_err(ApiError::from)
 }
 
 /// GET /messages/thread/broadcast
 pub async fn get_broadcast_thread(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
-    _user: AuthenticatedUser,
+    user: AuthenticatedUser,
     Query(pagination): Query<PaginationQuery>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    MessageService::get_broadcast_thread(
+) -> Result<Json<Value>, ApiError> {
+    let msgs = MessageService::get_broadcast_thread(
         &state.db,
         &tenant,
         pagination.per_page(),
         pagination.offset(),
+        user.role != UserRole::Parent,
     )
-    .await
-    .map(|msgs| Json(serde_json::to_value(msgs).unwrap()))
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })
+    .await?;
+
+    with_reactions(&state, &tenant, user.user_id, msgs).await
 }
 
 /// GET /messages/thread/group/:group_id
 pub async fn get_group_thread(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
-    _user: AuthenticatedUser,
+    user: AuthenticatedUser,
     Path(group_id): Path<Uuid>,
     Query(pagination): Query<PaginationQuery>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    MessageService::get_group_thread(
+) -> Result<Json<Value>, ApiError> {
+    GroupService::ensure_group_access(&state.db, &tenant, &user, group_id)
+        .await?;
+
+    let msgs = MessageService::get_group_thread(
         &state.db,
         &tenant,
         group_id,
         pagination.per_page(),
         pagination.offset(),
+        user.role != UserRole::Parent,
     )
-    .await
-    .map(|msgs| Json(serde_json::to_value(msgs).unwrap()))
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })
+    .await?;
+
+    with_reactions(&state, &tenant, user.user_id, msgs).await
 }
 
 /// GET /messages/thread/individual/:parent_id
 pub async fn get_individual_thread(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
-    _user: AuthenticatedUser,
+    user: AuthenticatedUser,
     Path(parent_id): Path<Uuid>,
     Query(pagination): Query<PaginationQuery>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    MessageService::get_individual_thread(
+) -> Result<Json<Value>, ApiError> {
+    let msgs = MessageService::get_individual_thread(
         &state.db,
         &tenant,
         parent_id,
         pagination.per_page(),
         pagination.offset(),
     )
-    .await
-    .map(|msgs| Json(serde_json::to_value(msgs).unwrap()))
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })
+    .await?;
+
+    with_reactions(&state, &tenant, user.user_id, msgs).await
+}
+
+/// GET /messages/thread/staff — the all-staff channel (staff only).
+pub async fn get_staff_thread(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(pagination): Query<PaginationQuery>,
+) -> Result<Json<Value>, ApiError> {
+    staff_thread(state, tenant, user, None, pagination).await
+}
+
+/// GET /messages/thread/staff/:group_id — the staff channel of a group (staff only).
+pub async fn get_group_staff_thread(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(group_id): Path<Uuid>,
+    Query(pagination): Query<PaginationQuery>,
+) -> Result<Json<Value>, ApiError> {
+    GroupService::ensure_group_access(&state.db, &tenant, &user, group_id)
+        .await?;
+    staff_thread(state, tenant, user, Some(group_id), pagination).await
+}
+
+async fn staff_thread(
+    state: AppState,
+    tenant: String,
+    user: AuthenticatedUser,
+    group_id: Option<Uuid>,
+    pagination: PaginationQuery,
+) -> Result<Json<Value>, ApiError> {
+    if let UserRole::Parent = user.role {
+        return Err(ApiError::forbidden());
+    }
+    let msgs = MessageService::get_staff_thread(
+        &state.db,
+        &tenant,
+        group_id,
+        pagination.per_page(),
+        pagination.offset(),
+    )
+    .await?;
+
+    with_reactions(&state, &tenant, user.user_id, msgs).await
 }
 
 /// POST /messages/send-to-parents — envoyer un message à des parents avec email automatique
@@ -454,21 +646,15 @@ pub async fn send_to_parents(
     State(mut state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-    Json(body): Json<SendToParentsRequest>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<SendToParentsRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
     // Only educators and admins can send messages to parents
     if let UserRole::Parent = user.role {
-        return Err((StatusCode::FORBIDDEN, Json(json!({ "error": "Accès refusé" }))));
+        return Err(ApiError::forbidden());
     }
 
     let (msg, recipients) = MessageService::send_to_parents(&state.db, &tenant, user.user_id, &body)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::BAD_REQUEST,
-                Json(json!({ "error": e.to_string() })),
-            )
-        })?;
+        .await?;
 
     // Send emails asynchronously
     if let Some(email_svc) = state.email.clone() {
@@ -476,24 +662,37 @@ pub async fn send_to_parents(
         let tenant_c = tenant.clone();
         let subject = body.subject.clone();
         let content = body.content.clone();
-        tokio::spawn(async move {
-            let (garderie_name, logo_url): (String, Option<String>) = sqlx::query_as(
-                "SELECT name, logo_url FROM public.garderies WHERE slug = $1",
-            )
-            .bind(&tenant_c)
-            .fetch_optional(&pool)
-            .await
-            .unwrap_or_default()
-            .unwrap_or_else(|| (tenant_c.clone(), None));
-            let logo_url = logo_url.unwrap_or_default();
-            let _ = email_svc.send_to_parents(recipients, &subject, &content, &garderie_name, &logo_url).await;
-        });
+        let app_base_url = state.config.app_base_url.clone();
+        let (sender_id, message_id) = (user.user_id, msg.id);
+        tokio::spawn(TenantDb::scope(tenant_c.clone(), async move {
+            let (garderie_name, branding) = BrandingService::for_email(&pool, &tenant_c).await;
+            let templates = EmailTemplateService::overrides_for(&pool, &tenant_c, TEMPLATE_ANNOUNCEMENT).await;
+            let campaign =
+                EmailTrackingService::start(&pool, &tenant_c, &app_base_url, &subject, sender_id, Some(message_id))
+                    .await
+                    .unwrap_or_else(|e| {
+                        tracing::warn!("Email tracking unavailable for {tenant_c}: {e}");
+                        None
+                    });
+            let _ = email_svc
+                .send_to_parents(&tenant_c, recipients, &subject, &content, &garderie_name, &branding, &templates, campaign)
+                .await;
+        }));
     }
 
-    // Publish to Redis for real-time delivery
-    let payload = serde_json::to_string(&msg).unwrap_or_default();
-    let channel = format!("tenant:{}:messages", tenant);
-    let _ = state.redis.publish::<_, _, ()>(&channel, &payload).await;
+    // Real-time update, unread counters and push; the message itself was emailed above
+    match MessageService::get_with_sender(&state.db, &tenant, msg.id).await {
+        Ok(Some(message)) => {
+            events::publish(&mut state.redis, &tenant, DomainEvent::MessageSent {
+                message: Box::new(message),
+                urgent: false,
+                notify_email: false,
+            })
+            .await
+        }
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Message {} in '{tenant}' not announced: {e}", msg.id),
+    }
 
     Ok((
         StatusCode::CREATED,
@@ -1,24 +1,52 @@
+pub mod albums;
 pub mod announcements;
+pub mod antivirus;
 pub mod attendance;
 pub mod activities;
 pub mod audit_log;
 pub mod grafana_auth;
 pub mod metrics;
+pub mod search;
 pub mod settings;
 pub mod auth;
 pub mod logo;
 pub mod children;
+pub mod consents;
 pub mod contact;
+pub mod departures;
+pub mod development;
+pub mod document_shares;
 pub mod documents;
 pub mod email;
+pub mod email_log;
+pub mod email_templates;
+pub mod email_tracking;
+pub mod email_webhooks;
+pub mod emergency;
+pub mod families;
+pub mod feature_flags;
+pub mod graphql;
 pub mod groups;
 pub mod health;
+pub mod invoices;
 pub mod journal;
 pub mod media;
+pub mod media_comments;
+pub mod media_redactions;
+pub mod meetings;
 pub mod menu;
+pub mod message_drafts;
 pub mod messages;
+pub mod oidc;
+pub mod operations;
+pub mod ratios;
+pub mod retention;
 pub mod signup;
+pub mod stats;
+pub mod storage;
+pub mod tax_receipts;
 pub mod tenant_info;
 pub mod tenants;
 pub mod users;
+pub mod waitlist;
 pub mod websocket;
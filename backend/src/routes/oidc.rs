@@ -0,0 +1,164 @@
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use serde_json::{json, Value};
+
+use crate::{
+    error::ApiError,
+    middleware::{rate_limit::check_rate_limit, tenant::TenantSlug},
+    models::{
+        auth::AuthenticatedUser,
+        oidc::{OidcCallbackRequest, OidcStartQuery, UpdateOidcSettingsRequest},
+        user::UserRole,
+    },
+    routes::auth::{client_info, real_client_ip},
+    services::{
+        auth::AuthService,
+        oidc::{OidcError, OidcService},
+    },
+    AppState,
+};
+use crate::middleware::validation::ValidJson;
+
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
+        _ => Err(ApiError::forbidden()),
+    }
+}
+
+/// GET /settings/oidc — admin only; `null` when SSO was never configured
+pub async fn get_settings(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    OidcService::get_settings(&state.db, &tenant)
+        .await
+        .map(|s| Json(serde_json::to_value(s).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// PUT /settings/oidc — admin only
+pub async fn update_settings(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    headers: HeaderMap,
+    ValidJson(body): ValidJson<UpdateOidcSettingsRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let settings = OidcService::update_settings(&state.db, &state.config, &tenant, user.user_id, &body)
+        .await?;
+    crate::services::audit::log(state.db.clone(), &tenant, crate::services::audit::AuditEntry {
+        user_id:        Some(user.user_id),
+        user_name:      None,
+        action:         "settings.oidc_update".to_string(),
+        resource_type:  Some("oidc_settings".to_string()),
+        resource_id:    None,
+        resource_label: Some(settings.provider.clone()),
+        ip_address:     real_client_ip(&headers),
+    });
+    Ok(Json(serde_json::to_value(settings).unwrap()))
+}
+
+/// DELETE /settings/oidc — admin only; staff go back to password login
+pub async fn delete_settings(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    headers: HeaderMap,
+) -> Result<StatusCode, ApiError> {
+    require_admin(&user)?;
+    match OidcService::delete_settings(&state.db, &tenant).await {
+        Ok(true) => {
+            crate::services::audit::log(state.db.clone(), &tenant, crate::services::audit::AuditEntry {
+                user_id:        Some(user.user_id),
+                user_name:      None,
+                action:         "settings.oidc_delete".to_string(),
+                resource_type:  Some("oidc_settings".to_string()),
+                resource_id:    None,
+                resource_label: None,
+                ip_address:     real_client_ip(&headers),
+            });
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Ok(false) => Err(ApiError::from(OidcError::NotConfigured)),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+/// GET /auth/oidc/login — public; the URL of the identity provider to send the browser to
+pub async fn start_login(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    headers: HeaderMap,
+    Query(q): Query<OidcStartQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let mut redis = state.redis.clone();
+    let rate_key = format!("rate:oidc:{}:{}", tenant, real_client_ip(&headers));
+    check_rate_limit(&mut redis, &rate_key, 20, 900).await?;
+
+    let locale = q.locale.as_deref().unwrap_or("fr");
+    OidcService::start(&state.db, &mut redis, &state.config, &tenant, locale)
+        .await
+        .map(|url| Json(json!({ "authorization_url": url })))
+        .map_err(ApiError::from)
+}
+
+/// POST /auth/oidc/callback — public; exchanges the code the web app received for the
+/// same token pair as a password login
+pub async fn callback(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    headers: HeaderMap,
+    ValidJson(body): ValidJson<OidcCallbackRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let mut redis = state.redis.clone();
+    let user = match OidcService::finish(&state.db, &mut redis, &state.config, &tenant, &body.code, &body.state).await
+    {
+        Ok(user) => user,
+        Err(e) => {
+            crate::services::metrics::LOGINS_COUNTER.with_label_values(&[&tenant, "failed"]).inc();
+            crate::services::audit::log(state.db.clone(), &tenant, crate::services::audit::AuditEntry {
+                user_id:        None,
+                user_name:      None,
+                action:         "auth.oidc_login_failure".to_string(),
+                resource_type:  None,
+                resource_id:    None,
+                resource_label: Some(e.to_string()),
+                ip_address:     real_client_ip(&headers),
+            });
+            return Err(ApiError::from(e));
+        }
+    };
+
+    let email = user.email.clone();
+    let user_id = user.id;
+    let response = AuthService::login_with_sso(
+        &state.db,
+        &tenant,
+        user,
+        &client_info(&headers),
+        &state.jwt_keys,
+        &state.config.jwt_refresh_secret,
+        state.config.jwt_expiry_seconds,
+        state.config.jwt_refresh_expiry_days,
+    )
+    .await?;
+
+    crate::services::metrics::LOGINS_COUNTER.with_label_values(&[&tenant, "success"]).inc();
+    crate::services::audit::log(state.db.clone(), &tenant, crate::services::audit::AuditEntry {
+        user_id:        Some(user_id),
+        user_name:      Some(email.clone()),
+        action:         "auth.oidc_login".to_string(),
+        resource_type:  None,
+        resource_id:    None,
+        resource_label: Some(email),
+        ip_address:     real_client_ip(&headers),
+    });
+    Ok(Json(serde_json::to_value(response).unwrap()))
+}
@@ -0,0 +1,74 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    error::ApiError,
+    middleware::super_admin::SuperAdminAuth,
+    models::operation::PendingOperation,
+    services::operations::OperationService,
+    AppState,
+};
+
+/// Email every active super-admin that a destructive operation was requested (non-fatal).
+pub(crate) fn alert_super_admins(state: &AppState, op: &PendingOperation) {
+    let Some(svc) = state.email.clone() else {
+        return;
+    };
+    let pool = state.db.clone();
+    let op = op.clone();
+    tokio::spawn(async move {
+        let recipients = match OperationService::alert_recipients(&pool).await {
+            Ok(r) => r,
+            Err(e) => {
+                tracing::warn!("Could not list super-admins to alert about operation {}: {e}", op.id);
+                return;
+            }
+        };
+        if recipients.is_empty() {
+            tracing::warn!("No active super-admin to alert about operation {}", op.id);
+        }
+        for to in recipients {
+            if let Err(e) = svc.send_pending_operation_alert(&to, &op).await {
+                tracing::warn!("Operation alert to {to} failed: {e}");
+            }
+        }
+    });
+}
+
+/// GET /super-admin/operations — open operations first, then recent history
+pub async fn list_operations(
+    State(state): State<AppState>,
+    _auth: SuperAdminAuth,
+) -> Result<Json<Value>, ApiError> {
+    let ops = OperationService::list(&state.db).await?;
+    Ok(Json(serde_json::to_value(ops).unwrap()))
+}
+
+/// POST /super-admin/operations/{id}/confirm — a second, identified super-admin runs it now
+pub async fn confirm_operation(
+    State(state): State<AppState>,
+    auth: SuperAdminAuth,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    let op = OperationService::confirm(&state.db, &state.config, id, auth.operator.as_deref()).await?;
+    if let Some(ref e) = op.error {
+        // Kept on the operation, which the listing shows
+        tracing::error!("Operation {} failed: {e}", op.id);
+        return Err(ApiError::internal());
+    }
+    Ok(Json(serde_json::to_value(op).unwrap()))
+}
+
+/// POST /super-admin/operations/{id}/cancel — any super-admin, while still pending
+pub async fn cancel_operation(
+    State(state): State<AppState>,
+    auth: SuperAdminAuth,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    let op = OperationService::cancel(&state.db, id, auth.operator.as_deref()).await?;
+    Ok(Json(serde_json::to_value(op).unwrap()))
+}
@@ -0,0 +1,139 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Local;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    error::ApiError,
+    middleware::tenant::TenantSlug,
+    models::{
+        auth::AuthenticatedUser,
+        ratio::{CreateShiftRequest, RatioPeriodQuery, UpdateRatioRulesRequest},
+        user::UserRole,
+    },
+    services::ratios::RatioService,
+    AppState,
+};
+use crate::middleware::validation::ValidJson;
+
+fn require_staff(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::Parent => Err(ApiError::forbidden()),
+        _ => Ok(()),
+    }
+}
+
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
+        _ => Err(ApiError::forbidden()),
+    }
+}
+
+/// GET /ratios — staff; each group right now: children present, educators on duty,
+/// educators required by the age bands
+pub async fn current(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    require_staff(&user)?;
+    let now = Local::now();
+    let today = now.date_naive();
+    let groups = RatioService::snapshots(&state.db, &tenant, today, today, Some(now.time()), None)
+        .await?;
+    let out_of_ratio = groups.iter().filter(|g| !g.compliant).count();
+    Ok(Json(json!({ "at": now.to_rfc3339(), "groups": groups, "out_of_ratio": out_of_ratio })))
+}
+
+/// GET /ratios/history?from=&to=&group_id= — staff; one entry per group and day, counting
+/// the educators with a shift that day (default: the last 30 days)
+pub async fn history(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(q): Query<RatioPeriodQuery>,
+) -> Result<Json<Value>, ApiError> {
+    require_staff(&user)?;
+    let to = q.to.unwrap_or_else(|| Local::now().date_naive());
+    let from = q.from.unwrap_or(to - chrono::Duration::days(29));
+    RatioService::snapshots(&state.db, &tenant, from, to, None, q.group_id)
+        .await
+        .map(|days| Json(json!({ "from": from, "to": to, "days": days })))
+        .map_err(ApiError::from)
+}
+
+/// GET /ratios/rules — staff
+pub async fn get_rules(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    require_staff(&user)?;
+    RatioService::rules(&state.db, &tenant)
+        .await
+        .map(|rules| Json(serde_json::to_value(rules).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// PUT /ratios/rules — admin only; replaces every age band
+pub async fn update_rules(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<UpdateRatioRulesRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    RatioService::replace_rules(&state.db, &tenant, &body.rules)
+        .await
+        .map(|rules| Json(serde_json::to_value(rules).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// GET /staff-shifts?from=&to=&group_id= — staff (default: today)
+pub async fn list_shifts(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(q): Query<RatioPeriodQuery>,
+) -> Result<Json<Value>, ApiError> {
+    require_staff(&user)?;
+    let from = q.from.unwrap_or_else(|| Local::now().date_naive());
+    let to = q.to.unwrap_or(from);
+    RatioService::list_shifts(&state.db, &tenant, from, to, q.group_id)
+        .await
+        .map(|shifts| Json(serde_json::to_value(shifts).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// POST /staff-shifts — admin only
+pub async fn create_shift(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<CreateShiftRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&user)?;
+    RatioService::create_shift(&state.db, &tenant, &body, user.user_id)
+        .await
+        .map(|shift| (StatusCode::CREATED, Json(serde_json::to_value(shift).unwrap())))
+        .map_err(ApiError::from)
+}
+
+/// DELETE /staff-shifts/{id} — admin only
+pub async fn delete_shift(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    require_admin(&user)?;
+    RatioService::delete_shift(&state.db, &tenant, id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(ApiError::from)
+}
@@ -0,0 +1,125 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{Duration, Local};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    error::ApiError,
+    middleware::tenant::TenantSlug,
+    models::{
+        auth::AuthenticatedUser,
+        retention::{CreateLegalHoldRequest, UpcomingQuery, UpdateRetentionRequest},
+        user::UserRole,
+    },
+    services::retention::RetentionService,
+    AppState,
+};
+use crate::middleware::validation::ValidJson;
+
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
+        _ => Err(ApiError::forbidden()),
+    }
+}
+
+/// GET /retention — admin only; retention periods and whether purging is on
+pub async fn get_settings(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    RetentionService::settings(&state.db, &tenant)
+        .await
+        .map(|settings| Json(serde_json::to_value(settings).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// PUT /retention — admin only
+pub async fn update_settings(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<UpdateRetentionRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    RetentionService::update(&state.db, &tenant, user.user_id, &body)
+        .await
+        .map(|settings| Json(serde_json::to_value(settings).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// GET /retention/upcoming?days= — admin only; what the purge will have deleted
+/// `days` from now (default 30, up to a year), and what is due today
+pub async fn upcoming(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(q): Query<UpcomingQuery>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let today = Local::now().date_naive();
+    let days = q.days.unwrap_or(30).clamp(0, 366);
+    let due = RetentionService::preview(&state.db, &tenant, today).await?;
+    let upcoming = RetentionService::preview(&state.db, &tenant, today + Duration::days(days)).await?;
+    Ok(Json(json!({ "days": days, "due": due, "upcoming": upcoming })))
+}
+
+/// GET /retention/runs — admin only; recent passes of the nightly purge, dry runs included
+pub async fn list_runs(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    RetentionService::runs(&state.db, &tenant)
+        .await
+        .map(|runs| Json(serde_json::to_value(runs).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// GET /retention/holds — admin only
+pub async fn list_holds(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    RetentionService::holds(&state.db, &tenant)
+        .await
+        .map(|holds| Json(serde_json::to_value(holds).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// POST /retention/holds — admin only; for one child, or the whole garderie without `child_id`
+pub async fn create_hold(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<CreateLegalHoldRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&user)?;
+    RetentionService::create_hold(&state.db, &tenant, user.user_id, &body)
+        .await
+        .map(|hold| (StatusCode::CREATED, Json(serde_json::to_value(hold).unwrap())))
+        .map_err(ApiError::from)
+}
+
+/// POST /retention/holds/{id}/release — admin only; the hold stays listed as released
+pub async fn release_hold(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    RetentionService::release_hold(&state.db, &tenant, id, user.user_id)
+        .await
+        .map(|hold| Json(serde_json::to_value(hold).unwrap()))
+        .map_err(ApiError::from)
+}
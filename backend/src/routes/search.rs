@@ -0,0 +1,37 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde_json::{json, Value};
+
+use crate::{
+    error::ApiError,
+    middleware::tenant::TenantSlug,
+    models::{auth::AuthenticatedUser, search::SearchQuery, user::UserRole},
+    services::search::{parse_kinds, SearchService, DEFAULT_LIMIT},
+    AppState,
+};
+
+/// GET /search?q=...&types=child,parent,document,message&limit=20 — staff only.
+/// Results of all kinds are ranked together, best match first.
+pub async fn search(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(params): Query<SearchQuery>,
+) -> Result<Json<Value>, ApiError> {
+    if let UserRole::Parent = user.role {
+        return Err(ApiError::forbidden());
+    }
+    let kinds = parse_kinds(params.types.as_deref())?;
+    let results = SearchService::search(
+        &state.db,
+        &tenant,
+        &user,
+        &params.q,
+        &kinds,
+        params.limit.unwrap_or(DEFAULT_LIMIT),
+    )
+    .await?;
+    Ok(Json(json!({ "results": results })))
+}
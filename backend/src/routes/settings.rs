@@ -3,10 +3,14 @@ use serde::Deserialize;
 use serde_json::{json, Value};
 
 use crate::{
+    error::ApiError,
     middleware::tenant::TenantSlug,
-    models::{auth::AuthenticatedUser, user::UserRole},
+    models::{auth::AuthenticatedUser, tenant::UpdateBrandingRequest, user::UserRole},
+    services::branding::BrandingService,
     AppState,
 };
+use validator::Validate;
+use crate::middleware::validation::ValidJson;
 
 /// GET /settings — any authenticated user
 pub async fn get_settings(
@@ -14,24 +18,41 @@ pub async fn get_settings(
     TenantSlug(tenant): TenantSlug,
     _user: AuthenticatedUser,
 ) -> (StatusCode, Json<Value>) {
-    let time: Option<String> = sqlx::query_scalar(
-        "SELECT journal_auto_send_time FROM public.garderies WHERE slug = $1",
+    let row: Option<(Option<String>, bool, Option<String>)> = sqlx::query_as(
+        "SELECT journal_auto_send_time, email_tracking_enabled, auto_absence_cutoff FROM public.garderies WHERE slug = $1",
     )
     .bind(&tenant)
     .fetch_optional(&state.db)
     .await
     .ok()
     .flatten();
+    let (time, email_tracking_enabled, auto_absence_cutoff) = row.unwrap_or((None, false, None));
 
     (
         StatusCode::OK,
-        Json(json!({ "journal_auto_send_time": time.unwrap_or_else(|| "16:30".into()) })),
+        Json(json!({
+            "journal_auto_send_time": time.unwrap_or_else(|| "16:30".into()),
+            "email_tracking_enabled": email_tracking_enabled,
+            "auto_absence_cutoff": auto_absence_cutoff,
+        })),
     )
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Validate)]
 pub struct UpdateSettingsRequest {
-    pub journal_auto_send_time: String,
+    pub journal_auto_send_time: Option<String>,
+    /// Open and click tracking of announcements to parents.
+    pub email_tracking_enabled: Option<bool>,
+    /// HH:MM after which children not checked in are marked absent; "" turns it off.
+    pub auto_absence_cutoff: Option<String>,
+}
+
+/// HH:MM, 00:00 to 23:59.
+fn is_valid_time(time: &str) -> bool {
+    let parts: Vec<&str> = time.split(':').collect();
+    parts.len() == 2
+        && parts[0].parse::<u32>().map(|h| h <= 23).unwrap_or(false)
+        && parts[1].parse::<u32>().map(|m| m <= 59).unwrap_or(false)
 }
 
 /// PUT /settings — admin only
@@ -39,46 +60,76 @@ pub async fn update_settings(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-    Json(body): Json<UpdateSettingsRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<UpdateSettingsRequest>,
+) -> Result<Json<Value>, ApiError> {
     match user.role {
         UserRole::AdminGarderie | UserRole::SuperAdmin => {}
         _ => {
-            return Err((
-                StatusCode::FORBIDDEN,
-                Json(json!({ "error": "Accès refusé" })),
-            ))
+            return Err(ApiError::forbidden())
         }
     }
 
     // Validate HH:MM format
-    let parts: Vec<&str> = body.journal_auto_send_time.split(':').collect();
-    let valid = parts.len() == 2
-        && parts[0].parse::<u32>().map(|h| h <= 23).unwrap_or(false)
-        && parts[1].parse::<u32>().map(|m| m <= 59).unwrap_or(false);
-
-    if !valid {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": "Format invalide — utilisez HH:MM (ex: 16:30)" })),
-        ));
+    let cutoff = body.auto_absence_cutoff.as_deref().map(str::trim);
+    let times = [body.journal_auto_send_time.as_deref(), cutoff.filter(|c| !c.is_empty())];
+    if times.into_iter().flatten().any(|time| !is_valid_time(time)) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Format invalide — utilisez HH:MM (ex: 16:30)"));
     }
 
-    sqlx::query(
-        "UPDATE public.garderies SET journal_auto_send_time = $1 WHERE slug = $2",
+    let (time, email_tracking_enabled, auto_absence_cutoff): (Option<String>, bool, Option<String>) = sqlx::query_as(
+        "UPDATE public.garderies
+         SET journal_auto_send_time = COALESCE($1, journal_auto_send_time),
+             email_tracking_enabled = COALESCE($2, email_tracking_enabled),
+             auto_absence_cutoff = CASE WHEN $3::TEXT IS NULL THEN auto_absence_cutoff ELSE NULLIF($3, '') END
+         WHERE slug = $4
+         RETURNING journal_auto_send_time, email_tracking_enabled, auto_absence_cutoff",
     )
     .bind(&body.journal_auto_send_time)
+    .bind(body.email_tracking_enabled)
+    .bind(cutoff)
     .bind(&tenant)
-    .execute(&state.db)
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })?;
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(Json(json!({
+        "journal_auto_send_time": time.unwrap_or_else(|| "16:30".into()),
+        "email_tracking_enabled": email_tracking_enabled,
+        "auto_absence_cutoff": auto_absence_cutoff,
+    })))
+}
+
+/// GET /settings/branding — admin only
+pub async fn get_branding(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+
+    BrandingService::get(&state.db, &tenant)
+        .await?
+        .map(|branding| Json(serde_json::to_value(branding).unwrap()))
+        .ok_or_else(|| ApiError::from_key(StatusCode::NOT_FOUND, "tenant_not_found"))
+}
 
-    Ok(Json(
-        json!({ "journal_auto_send_time": body.journal_auto_send_time }),
-    ))
+/// PUT /settings/branding — admin only
+pub async fn update_branding(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<UpdateBrandingRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+
+    BrandingService::update(&state.db, &tenant, &body)
+        .await
+        .map(|branding| Json(serde_json::to_value(branding).unwrap()))
+        .map_err(ApiError::from)
+}
+
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
+        _ => Err(ApiError::forbidden()),
+    }
 }
@@ -1,3 +1,4 @@
+use anyhow::Context;
 use axum::{
     extract::{Query, State},
     http::{HeaderMap, StatusCode},
@@ -8,11 +9,13 @@ use serde::Deserialize;
 use serde_json::{json, Value};
 
 use crate::{
-    db::tenant::{provision_tenant_schema, schema_name},
+    db::tenant::{provision_tenant_schema_in, schema_name},
+    error::ApiError,
     middleware::rate_limit::check_rate_limit,
     models::tenant::SignupRequest,
     AppState,
 };
+use crate::middleware::validation::ValidJson;
 
 const RESERVED_SLUGS: &[&str] = &[
     "www", "api", "demo", "super-admin", "app", "admin", "login", "signup",
@@ -51,7 +54,7 @@ pub async fn check_slug(
     State(state): State<AppState>,
     headers: HeaderMap,
     Query(params): Query<CheckSlugQuery>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+) -> Result<(StatusCode, Json<Value>), ApiError> {
     // Rate limit: 30/min per IP (nginx already limits to 20/min, this is a backstop)
     {
         let ip = real_ip(&headers);
@@ -95,8 +98,8 @@ pub async fn check_slug(
 pub async fn signup(
     State(state): State<AppState>,
     headers: HeaderMap,
-    Json(body): Json<SignupRequest>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<SignupRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
     let ip = real_ip(&headers);
     let mut redis = state.redis.clone();
 
@@ -106,42 +109,64 @@ pub async fn signup(
     // Rate limit 2: 20 signups/hour globally (total cap across all IPs)
     check_rate_limit(&mut redis, "rate:signup:global", 20, 3600).await?;
 
+    // Rate limit 3: 3 signups/day per address (same person rotating IPs)
+    let email_key = body.email.trim().to_lowercase();
+    check_rate_limit(&mut redis, &format!("rate:signup:email:{email_key}"), 3, 86400).await?;
+
     // Validate inputs
     let slug = body.slug.to_lowercase();
 
     if !is_valid_signup_slug(&slug) {
-        return Err((
+        return Err(ApiError::new(
             StatusCode::BAD_REQUEST,
-            Json(json!({ "error": "L'identifiant doit contenir entre 3 et 32 caractères (lettres minuscules, chiffres, tirets), sans commencer ni finir par un tiret." })),
+            "L'identifiant doit contenir entre 3 et 32 caractères (lettres minuscules, chiffres, tirets), sans commencer ni finir par un tiret.",
         ));
     }
 
     if RESERVED_SLUGS.contains(&slug.as_str()) {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": "Cet identifiant est réservé." })),
-        ));
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Cet identifiant est réservé."));
     }
 
     if !body.email.contains('@') {
-        return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "Adresse courriel invalide." }))));
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Adresse courriel invalide."));
     }
 
-    if body.password.len() < 8 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": "Le mot de passe doit contenir au moins 8 caractères." })),
-        ));
-    }
+    state.password_policy.check(&body.password, "fr").await?;
 
     if body.name.trim().is_empty() {
-        return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "Le nom de la garderie est requis." }))));
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Le nom de la garderie est requis."));
     }
 
     if body.first_name.trim().is_empty() || body.last_name.trim().is_empty() {
-        return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "Le prénom et le nom sont requis." }))));
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Le prénom et le nom sont requis."));
     }
 
+    // CAPTCHA last: the token is single-use, spend it only on an otherwise valid request
+    if let Some(captcha) = &state.captcha {
+        let Some(token) = body.captcha_token.as_deref().filter(|t| !t.is_empty()) else {
+            return Err(ApiError::from_key(StatusCode::BAD_REQUEST, "captcha_required"));
+        };
+        let remote_ip = (ip != "unknown").then_some(ip.as_str());
+        match captcha.verify(token, remote_ip).await {
+            Ok(true) => {}
+            Ok(false) => {
+                return Err(ApiError::new(
+                    StatusCode::BAD_REQUEST,
+                    "La vérification anti-robot a échoué. Veuillez réessayer.",
+                ));
+            }
+            Err(e) => {
+                tracing::warn!("Turnstile verification unavailable: {e}");
+                return Err(ApiError::from_key(StatusCode::SERVICE_UNAVAILABLE, "captcha_unavailable"));
+            }
+        }
+    }
+
+    let password_hash = bcrypt::hash(&body.password, 12).context("hash password")?;
+
+    // Garderie, schema, admin and consent are created together or not at all
+    let mut tx = state.db.begin().await?;
+
     // 1. Insert garderie with 30-day trial
     let trial_expires_at = Utc::now() + chrono::Duration::days(30);
 
@@ -159,38 +184,27 @@ pub async fn signup(
     .bind(body.postal_code.as_deref().filter(|s| !s.trim().is_empty()))
     .bind(&body.email)
     .bind(trial_expires_at)
-    .fetch_one(&state.db)
+    .fetch_one(&mut *tx)
     .await;
 
-    let (_id, created_slug, expires_at) = match garderie_result {
+    let (garderie_id, created_slug, expires_at) = match garderie_result {
         Ok(row) => row,
         Err(e) => {
             let msg = e.to_string();
             if msg.contains("unique") || msg.contains("duplicate") || msg.contains("already exists") {
-                return Err((
-                    StatusCode::CONFLICT,
-                    Json(json!({ "error": "Cet identifiant est déjà pris. Choisissez-en un autre." })),
-                ));
+                return Err(ApiError::conflict("Cet identifiant est déjà pris. Choisissez-en un autre."));
             }
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": msg }))));
+            return Err(e.into());
         }
     };
 
     // 2. Provision tenant schema
-    provision_tenant_schema(&state.db, &created_slug)
+    provision_tenant_schema_in(&mut tx, &created_slug)
         .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "error": format!("Schema provisioning failed: {e}") })),
-            )
-        })?;
+        .context("Schema provisioning failed")?;
 
     // 3. Create admin user
     let schema = schema_name(&created_slug);
-    let password_hash = bcrypt::hash(&body.password, 12)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
-
     sqlx::query(&format!(
         r#"INSERT INTO "{schema}".users (email, password_hash, first_name, last_name, role)
            VALUES ($1, $2, $3, $4, 'admin_garderie'::"{schema}".user_role)"#
@@ -199,44 +213,30 @@ pub async fn signup(
     .bind(&password_hash)
     .bind(body.first_name.trim())
     .bind(body.last_name.trim())
-    .execute(&state.db)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+    .execute(&mut *tx)
+    .await?;
 
-    // 4. Persist consent record (Loi 25) — fire-and-forget to not block signup
+    // 4. Persist consent record (Loi 25)
     if let Some(ref c) = body.consent {
-        let garderie_id = _id;
-        let privacy   = c.privacy_accepted;
-        let commitment = c.parents_commitment_accepted;
-        let accepted_at = c.accepted_at;
-        let policy_ver  = c.policy_version.clone();
-        let lang        = c.language.clone().unwrap_or_else(|| "fr".to_string());
-        let ip_addr     = ip.clone();
-        let db          = state.db.clone();
-
-        tokio::spawn(async move {
-            let res = sqlx::query(
-                "INSERT INTO public.consent_records
-                    (entity_type, entity_id, privacy_accepted, parents_commitment_accepted,
-                     accepted_at, policy_version, language, ip_address)
-                 VALUES ('garderie_signup', $1, $2, $3, $4, $5, $6, $7)"
-            )
-            .bind(garderie_id)
-            .bind(privacy)
-            .bind(commitment)
-            .bind(accepted_at)
-            .bind(&policy_ver)
-            .bind(&lang)
-            .bind(&ip_addr)
-            .execute(&db)
-            .await;
-
-            if let Err(e) = res {
-                tracing::warn!("Failed to persist consent record for garderie {garderie_id}: {e}");
-            }
-        });
+        sqlx::query(
+            "INSERT INTO public.consent_records
+                (entity_type, entity_id, privacy_accepted, parents_commitment_accepted,
+                 accepted_at, policy_version, language, ip_address)
+             VALUES ('garderie_signup', $1, $2, $3, $4, $5, $6, $7)"
+        )
+        .bind(garderie_id)
+        .bind(c.privacy_accepted)
+        .bind(c.parents_commitment_accepted)
+        .bind(c.accepted_at)
+        .bind(&c.policy_version)
+        .bind(c.language.as_deref().unwrap_or("fr"))
+        .bind(&ip)
+        .execute(&mut *tx)
+        .await?;
     }
 
+    tx.commit().await?;
+
     // Build login URL from base URL: https://minispace.app → https://{slug}.minispace.app/fr/login
     let login_url = {
         let base = &state.config.app_base_url;
@@ -0,0 +1,54 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{Duration, Local};
+use serde_json::{json, Value};
+
+use crate::{
+    error::ApiError,
+    middleware::{super_admin::SuperAdminAuth, tenant::TenantSlug},
+    models::{auth::AuthenticatedUser, stats::StatsQuery, user::UserRole},
+    services::{
+        platform_stats::PlatformStatsService,
+        stats::{totals, StatsService},
+    },
+    AppState,
+};
+
+/// GET /stats?from=&to= — admin only; active parents, messages, media storage, logins,
+/// invitation acceptance and journal completion per educator, read from the daily rollups
+/// (default: the last 30 completed days)
+pub async fn get_stats(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(q): Query<StatsQuery>,
+) -> Result<Json<Value>, ApiError> {
+    if !matches!(user.role, UserRole::AdminGarderie | UserRole::SuperAdmin) {
+        return Err(ApiError::forbidden());
+    }
+    let today = Local::now().date_naive();
+    let to = q.to.unwrap_or(today - Duration::days(1));
+    let from = q.from.unwrap_or(to - Duration::days(29));
+    StatsService::check_period(from, to, today)?;
+
+    let days = StatsService::days(&state.db, &tenant, from, to).await?;
+    let educators = StatsService::educators(&state.db, &tenant, from, to).await?;
+    Ok(Json(json!({
+        "from": from,
+        "to": to,
+        "totals": totals(&days),
+        "days": days,
+        "educators": educators,
+    })))
+}
+
+/// GET /super-admin/stats — activity and health score of every garderie, riskiest first
+pub async fn super_admin_stats(
+    State(state): State<AppState>,
+    _auth: SuperAdminAuth,
+) -> Result<Json<Value>, ApiError> {
+    let (totals, garderies) = PlatformStatsService::tenants(&state.db_bulk, &mut state.redis.clone()).await?;
+    Ok(Json(json!({ "totals": totals, "garderies": garderies })))
+}
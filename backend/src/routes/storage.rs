@@ -0,0 +1,54 @@
+use axum::{extract::State, Json};
+use serde_json::{json, Value};
+
+use crate::{
+    db::tenant_db::TenantDb,
+    error::ApiError,
+    middleware::{super_admin::SuperAdminAuth, tenant::TenantSlug},
+    models::{auth::AuthenticatedUser, tenant::PlanType, user::UserRole},
+    services::storage::StorageService,
+    AppState,
+};
+
+/// GET /tenant/usage — storage used by the garderie versus its plan quota (admin only).
+pub async fn get_tenant_usage(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => {}
+        _ => return Err(ApiError::forbidden()),
+    }
+
+    StorageService::usage(&state.db_bulk, &mut state.redis.clone(), &tenant)
+        .await
+        .map(|usage| Json(serde_json::to_value(usage).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// GET /super-admin/usage — storage usage of every garderie, largest first.
+pub async fn list_usage(
+    State(state): State<AppState>,
+    _auth: SuperAdminAuth,
+) -> Result<Json<Value>, ApiError> {
+    let garderies: Vec<(String, String, PlanType)> =
+        sqlx::query_as("SELECT slug, name, plan FROM public.garderies WHERE archived_at IS NULL ORDER BY name")
+            .fetch_all(&state.db_bulk)
+            .await?;
+
+    let mut redis = state.redis.clone();
+    let mut rows: Vec<(i64, Value)> = Vec::with_capacity(garderies.len());
+    for (slug, name, plan) in garderies {
+        match TenantDb::scope(slug.clone(), StorageService::usage(&state.db_bulk, &mut redis, &slug)).await {
+            Ok(usage) => rows.push((
+                usage.used_bytes,
+                json!({ "slug": slug, "name": name, "plan": plan, "usage": usage }),
+            )),
+            Err(e) => tracing::warn!("Storage usage unavailable for '{slug}': {e}"),
+        }
+    }
+    rows.sort_by_key(|(used, _)| std::cmp::Reverse(*used));
+
+    Ok(Json(json!(rows.into_iter().map(|(_, v)| v).collect::<Vec<_>>())))
+}
@@ -0,0 +1,143 @@
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, Response, StatusCode},
+    Json,
+};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    error::ApiError,
+    middleware::{super_admin::SuperAdminAuth, tenant::TenantSlug},
+    models::{
+        auth::AuthenticatedUser,
+        tax_receipt::{GenerateTaxReceiptsRequest, TaxReceiptExportQuery, TaxReceiptQuery},
+        user::UserRole,
+    },
+    services::{
+        audit::{self, AuditEntry},
+        tax_receipts::{TaxReceiptError, TaxReceiptService},
+    },
+    AppState,
+};
+use crate::middleware::validation::ValidJson;
+
+fn client_ip(h: &HeaderMap) -> String {
+    h.get("x-real-ip").and_then(|v| v.to_str().ok())
+        .or_else(|| h.get("x-forwarded-for").and_then(|v| v.to_str().ok())
+            .and_then(|s| s.split(',').next()).map(|s| s.trim()))
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
+        _ => Err(ApiError::forbidden()),
+    }
+}
+
+/// GET /tax-receipts?year= — parents only get the receipts addressed to them.
+pub async fn list_tax_receipts(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(query): Query<TaxReceiptQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let payer_id = match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => None,
+        UserRole::Parent => Some(user.user_id),
+        _ => return Err(ApiError::forbidden()),
+    };
+    TaxReceiptService::list(&state.db, &tenant, query.year, payer_id)
+        .await
+        .map(|receipts| Json(serde_json::to_value(receipts).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// POST /tax-receipts/generate — build or refresh the receipts of a year.
+pub async fn generate_tax_receipts(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    headers: HeaderMap,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<GenerateTaxReceiptsRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    let result = TaxReceiptService::generate(&state.db_bulk, &tenant, body.year, user.user_id)
+        .await?;
+
+    audit::log(state.db.clone(), &tenant, AuditEntry {
+        user_id:        Some(user.user_id),
+        user_name:      None,
+        action:         "tax_receipt.generate".to_string(),
+        resource_type:  Some("tax_receipt".to_string()),
+        resource_id:    None,
+        resource_label: Some(format!("RL-24 {} ({} relevés)", body.year, result.receipts.len())),
+        ip_address:     client_ip(&headers),
+    });
+
+    Ok(Json(serde_json::to_value(result).unwrap()))
+}
+
+/// GET /tax-receipts/{id}/pdf — admins, or the parent the receipt is addressed to.
+pub async fn get_tax_receipt_pdf(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<Response<Body>, ApiError> {
+    let receipt = TaxReceiptService::get(&state.db, &tenant, id)
+        .await?;
+    let allowed = match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => true,
+        UserRole::Parent => receipt.payer_id == Some(user.user_id) && receipt.amount_cents > 0,
+        _ => false,
+    };
+    if !allowed {
+        return Err(ApiError::from(TaxReceiptError::NotFound));
+    }
+
+    let pdf = TaxReceiptService::render_pdf(&state.db, &tenant, &receipt)
+        .await?;
+
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/pdf")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("inline; filename=\"releve-24-{}.pdf\"", receipt.number),
+        )
+        .body(Body::from(pdf))
+        .unwrap();
+
+    Ok(response)
+}
+
+/// GET /super-admin/tax-receipts.csv?year=&garderie= — receipts of every garderie, for accounting.
+pub async fn export_tax_receipts(
+    State(state): State<AppState>,
+    _auth: SuperAdminAuth,
+    Query(query): Query<TaxReceiptExportQuery>,
+) -> Result<Response<Body>, ApiError> {
+    let csv_bytes = TaxReceiptService::export_csv(&state.db_bulk, query.year, query.garderie.as_deref())
+        .await?;
+
+    let filename = match &query.garderie {
+        Some(slug) => format!("releves-24-{}-{slug}.csv", query.year),
+        None => format!("releves-24-{}.csv", query.year),
+    };
+
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/csv; charset=utf-8")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{filename}\""),
+        )
+        .body(Body::from(csv_bytes))
+        .unwrap();
+
+    Ok(response)
+}
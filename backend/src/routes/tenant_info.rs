@@ -1,16 +1,34 @@
-use axum::{extract::State, http::StatusCode, Json};
+use axum::{extract::State, http::StatusCode, Extension, Json};
 use chrono::{DateTime, Utc};
 use serde_json::{json, Value};
 
-use crate::{middleware::tenant::TenantSlug, AppState};
+use crate::{
+    error::ApiError,
+    middleware::tenant::TenantSlug,
+    models::tenant::TenantBranding,
+    services::{
+        branding::BRANDING_COLS, entitlements::EntitlementService, feature_flags::FeatureFlags, oidc::OidcService, trial_scheduler::TrialPhase,
+    },
+    AppState,
+};
 
 pub async fn get_tenant_info(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
-) -> (StatusCode, Json<Value>) {
-    let row: Option<(String, Option<String>, Option<DateTime<Utc>>)> = sqlx::query_as(
-        "SELECT name, logo_url, trial_expires_at FROM public.garderies WHERE slug = $1 AND is_active = TRUE",
-    )
+    Extension(features): Extension<FeatureFlags>,
+) -> Result<Json<Value>, ApiError> {
+    #[derive(sqlx::FromRow)]
+    struct Row {
+        name: String,
+        trial_expires_at: Option<DateTime<Utc>>,
+        #[sqlx(flatten)]
+        branding: TenantBranding,
+    }
+
+    let row: Option<Row> = sqlx::query_as(&format!(
+        "SELECT name, trial_expires_at, {BRANDING_COLS}
+         FROM public.garderies WHERE slug = $1 AND is_active = TRUE"
+    ))
     .bind(&tenant)
     .fetch_optional(&state.db)
     .await
@@ -18,13 +36,32 @@ pub async fn get_tenant_info(
     .flatten();
 
     match row {
-        Some((name, logo_url, trial_expires_at)) => (
-            StatusCode::OK,
-            Json(json!({ "name": name, "logo_url": logo_url, "trial_expires_at": trial_expires_at })),
-        ),
-        None => (
-            StatusCode::NOT_FOUND,
-            Json(json!({ "error": "Tenant not found" })),
-        ),
+        Some(Row { name, trial_expires_at, branding }) => {
+            let phase = TrialPhase::of(trial_expires_at, state.config.trial_grace_days, Utc::now());
+            let read_only_at = match phase {
+                TrialPhase::Grace { read_only_at } => Some(read_only_at),
+                _ => None,
+            };
+            let sso_enabled = OidcService::is_enabled(&state.db, &tenant).await;
+            let mut redis = state.redis.clone();
+            let entitlements = EntitlementService::current(&state.db, &mut redis, &tenant)
+                .await
+                .map_err(|e| tracing::warn!("Failed to load entitlements for tenant {tenant}: {e}"))
+                .ok();
+            Ok(Json(json!({
+                "name": name,
+                "logo_url": branding.logo_url,
+                "trial_expires_at": trial_expires_at,
+                "trial_phase": phase.as_str(),
+                "read_only_at": read_only_at,
+                "primary_color": branding.primary_color,
+                "accent_color": branding.accent_color,
+                "footer_text": branding.footer_text,
+                "sso_enabled": sso_enabled,
+                "features": features,
+                "entitlements": entitlements,
+            })))
+        }
+        None => Err(ApiError::from_key(StatusCode::NOT_FOUND, "tenant_not_found")),
     }
 }
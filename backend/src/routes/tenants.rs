@@ -1,5 +1,5 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
@@ -9,33 +9,59 @@ use serde_json::{json, Value};
 use uuid::Uuid;
 
 use crate::{
-    db::tenant::{provision_tenant_schema, schema_name},
+    db::{
+        tenant::{provision_tenant_schema, schema_name},
+        tenant_db::TenantDb,
+    },
+    error::ApiError,
     middleware::super_admin::SuperAdminAuth,
-    models::{tenant::CreateGarderieRequest, user::InviteUserRequest},
-    services::auth::AuthService,
+    models::{
+        backup::{BackupManifest, BackupRunQuery, TenantRestoreRequest},
+        operation::{
+            RestoreRequest, OP_ARCHIVE_GARDERIE, OP_DELETE_GARDERIE, OP_RESTORE, OP_RESTORE_GARDERIE, OP_TRANSFER_CHILD,
+        },
+        tenant::{CloneGarderieRequest, CreateGarderieRequest},
+        transfer::TransferChildRequest,
+        user::InviteUserRequest,
+    },
+    routes::operations::alert_super_admins,
+    services::{
+        auth::AuthService,
+        backups::{BackupError, BackupService},
+        child_transfers::TransferService,
+        cloning::CloneService,
+        encryption::KeyRing,
+        object_store::ObjectStore,
+        operations::{validate_restore, OperationService, BACKUP_DIR},
+        trial_scheduler,
+    },
     AppState,
 };
+use anyhow::Context;
+use crate::models::limits::{MAX_EMAIL, MAX_NAME, MAX_PASSWORD, MAX_PHONE, MAX_TITLE};
+use validator::Validate;
+use crate::middleware::validation::ValidJson;
 
 // ─── Garderie CRUD ────────────────────────────────────────────────────────────
 
 pub async fn list_garderies(
     State(state): State<AppState>,
     _auth: SuperAdminAuth,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     sqlx::query_as::<_, crate::models::tenant::Garderie>(
         "SELECT * FROM garderies ORDER BY name",
     )
     .fetch_all(&state.db)
     .await
     .map(|items| Json(serde_json::to_value(items).unwrap()))
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))
+    .map_err(ApiError::from)
 }
 
 pub async fn create_garderie(
     State(state): State<AppState>,
     _auth: SuperAdminAuth,
-    Json(body): Json<CreateGarderieRequest>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<CreateGarderieRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
     let garderie = sqlx::query_as::<_, crate::models::tenant::Garderie>(
         "INSERT INTO garderies (slug, name, address_line1, city, province, postal_code, phone, email, plan)
          VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
@@ -51,59 +77,163 @@ pub async fn create_garderie(
     .bind(&body.email)
     .bind(body.plan.unwrap_or(crate::models::tenant::PlanType::Free))
     .fetch_one(&state.db)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+    .await?;
 
     provision_tenant_schema(&state.db, &body.slug)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("Schema provisioning failed: {e}") }))))?;
+        .context("Schema provisioning failed")?;
 
     Ok((StatusCode::CREATED, Json(serde_json::to_value(garderie).unwrap())))
 }
 
-pub async fn delete_garderie(
+/// Create a garderie pre-filled from another one (groups, template documents,
+/// menus) for onboarding; no personal data is copied.
+pub async fn clone_garderie(
     State(state): State<AppState>,
     _auth: SuperAdminAuth,
     Path(slug): Path<String>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    let schema = schema_name(&slug);
+    ValidJson(body): ValidJson<CloneGarderieRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let keys = KeyRing::from_config(&state.config)?;
+    let summary = CloneService::clone(&state.db, &state.config.media_dir, &keys, &slug, &body).await?;
 
-    // Drop tenant schema (cascades to all tables/types/functions in it)
-    sqlx::raw_sql(&format!("DROP SCHEMA IF EXISTS {schema} CASCADE"))
-        .execute(&state.db)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+    Ok((StatusCode::CREATED, Json(serde_json::to_value(summary).unwrap())))
+}
 
-    // Remove from garderies registry
-    let deleted = sqlx::query("DELETE FROM garderies WHERE slug = $1")
-        .bind(&slug)
-        .execute(&state.db)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+#[derive(Deserialize)]
+pub struct DeleteGarderieQuery {
+    /// `delete` (default) drops everything; `archive` exports the garderie to S3 first
+    /// and keeps it restorable for `ARCHIVE_RETENTION_DAYS`.
+    pub mode: Option<String>,
+}
 
-    if deleted.rows_affected() == 0 {
-        return Err((StatusCode::NOT_FOUND, Json(json!({ "error": "Garderie introuvable" }))));
-    }
+/// Queue the deletion or archiving of a garderie. Nothing is dropped until a second
+/// super-admin confirms it or the cancel window (`DESTRUCTIVE_OP_DELAY_MINUTES`) elapses.
+pub async fn delete_garderie(
+    State(state): State<AppState>,
+    auth: SuperAdminAuth,
+    Path(slug): Path<String>,
+    Query(query): Query<DeleteGarderieQuery>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let kind = match query.mode.as_deref() {
+        None | Some("delete") => OP_DELETE_GARDERIE,
+        Some("archive") => {
+            TenantDb::require_own_schema()?;
+            object_store(&state)?;
+            OP_ARCHIVE_GARDERIE
+        }
+        Some(_) => return Err(ApiError::new(StatusCode::BAD_REQUEST, "Mode inconnu : delete ou archive")),
+    };
 
-    // Delete physical files (photos, videos, documents) for this tenant
-    let tenant_media_dir = std::path::PathBuf::from(&state.config.media_dir).join(&slug);
-    if tenant_media_dir.exists() {
-        if let Err(e) = tokio::fs::remove_dir_all(&tenant_media_dir).await {
-            // Log but don't fail — DB is already cleaned up
-            tracing::warn!("Could not delete media directory {:?}: {}", tenant_media_dir, e);
+    let archived: Option<bool> = sqlx::query_scalar("SELECT archived_at IS NOT NULL FROM garderies WHERE slug = $1")
+        .bind(&slug)
+        .fetch_optional(&state.db)
+        .await?;
+    match archived {
+        None => return Err(ApiError::from_key(StatusCode::NOT_FOUND, "tenant_not_found")),
+        Some(true) if kind == OP_ARCHIVE_GARDERIE => {
+            return Err(ApiError::conflict("Cette garderie est déjà archivée"));
         }
+        Some(_) => {}
     }
 
-    Ok(Json(json!({ "message": "Garderie supprimée" })))
+    let op = OperationService::request(
+        &state.db,
+        kind,
+        Some(&slug),
+        json!({}),
+        auth.operator.as_deref(),
+        state.config.destructive_op_delay_minutes,
+    )
+    .await?;
+    alert_super_admins(&state, &op);
+
+    Ok((StatusCode::ACCEPTED, Json(serde_json::to_value(op).unwrap())))
+}
+
+/// POST /super-admin/garderies/{slug}/unarchive — queue the restore of the archive
+/// backup. Nothing is overwritten, so it runs on the next scheduler pass.
+pub async fn unarchive_garderie(
+    State(state): State<AppState>,
+    auth: SuperAdminAuth,
+    Path(slug): Path<String>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    object_store(&state)?;
+    let backup_id: Option<Option<String>> =
+        sqlx::query_scalar("SELECT archive_backup_id FROM garderies WHERE slug = $1 AND archived_at IS NOT NULL")
+            .bind(&slug)
+            .fetch_optional(&state.db)
+            .await?;
+    let Some(Some(backup_id)) = backup_id else {
+        return Err(ApiError::not_found("Aucune archive pour cette garderie"));
+    };
+
+    let req = TenantRestoreRequest { backup_id, include_media: Some(true) };
+    let op = OperationService::request(
+        &state.db,
+        OP_RESTORE_GARDERIE,
+        Some(&slug),
+        serde_json::to_value(&req).unwrap(),
+        auth.operator.as_deref(),
+        0,
+    )
+    .await?;
+    alert_super_admins(&state, &op);
+
+    Ok((StatusCode::ACCEPTED, Json(serde_json::to_value(op).unwrap())))
+}
+
+/// POST /super-admin/garderies/{slug}/children/{child_id}/transfer — queue the move of
+/// a child to another garderie, run once a second super-admin confirms it or the
+/// cancel window elapses. Parents must give photo consent again in the new garderie.
+pub async fn transfer_child(
+    State(state): State<AppState>,
+    auth: SuperAdminAuth,
+    Path((slug, child_id)): Path<(String, Uuid)>,
+    ValidJson(body): ValidJson<TransferChildRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let req = TransferChildRequest { child_id, ..body };
+    TransferService::check(&state.db, &slug, &req).await?;
+
+    let op = OperationService::request(
+        &state.db,
+        OP_TRANSFER_CHILD,
+        Some(&slug),
+        serde_json::to_value(&req).unwrap(),
+        auth.operator.as_deref(),
+        state.config.destructive_op_delay_minutes,
+    )
+    .await?;
+    alert_super_admins(&state, &op);
+
+    Ok((StatusCode::ACCEPTED, Json(serde_json::to_value(op).unwrap())))
+}
+
+#[derive(Deserialize)]
+pub struct TransferQuery {
+    /// Only the transfers from or to this garderie.
+    pub slug: Option<String>,
+}
+
+/// GET /super-admin/transfers?slug= — executed child transfers, most recent first
+pub async fn list_child_transfers(
+    State(state): State<AppState>,
+    _auth: SuperAdminAuth,
+    Query(query): Query<TransferQuery>,
+) -> Result<Json<Value>, ApiError> {
+    TransferService::list(&state.db, query.slug.as_deref())
+        .await
+        .map(|transfers| Json(serde_json::to_value(transfers).unwrap()))
+        .map_err(ApiError::from)
 }
 
 pub async fn update_garderie(
     State(state): State<AppState>,
     _auth: SuperAdminAuth,
     Path(slug): Path<String>,
-    Json(body): Json<UpdateGarderieRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    sqlx::query_as::<_, crate::models::tenant::Garderie>(
+    ValidJson(body): ValidJson<UpdateGarderieRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let garderie = sqlx::query_as::<_, crate::models::tenant::Garderie>(
         "UPDATE garderies SET
            name          = COALESCE($2, name),
            address_line1 = COALESCE($3, address_line1),
@@ -133,20 +263,32 @@ pub async fn update_garderie(
     .bind(body.remove_trial_expires.unwrap_or(false))
     .bind(body.trial_expires_at)
     .fetch_optional(&state.db)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?
-    .map(|g| Json(serde_json::to_value(g).unwrap()))
-    .ok_or((StatusCode::NOT_FOUND, Json(json!({ "error": "Garderie not found" }))))
+    .await?
+    .ok_or_else(|| ApiError::from_key(StatusCode::NOT_FOUND, "tenant_not_found"))?;
+
+    // A new expiry date starts a new trial: its warnings must go out again
+    if body.trial_expires_at.is_some() || body.remove_trial_expires == Some(true) {
+        trial_scheduler::reset_warnings(&mut state.redis.clone(), &slug).await;
+    }
+
+    Ok(Json(serde_json::to_value(garderie).unwrap()))
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Validate)]
 pub struct UpdateGarderieRequest {
+    #[validate(length(max = MAX_NAME))]
     pub name: Option<String>,
+    #[validate(length(max = MAX_TITLE))]
     pub address_line1: Option<String>,
+    #[validate(length(max = MAX_TITLE))]
     pub city: Option<String>,
+    #[validate(length(max = MAX_TITLE))]
     pub province: Option<String>,
+    #[validate(length(max = MAX_TITLE))]
     pub postal_code: Option<String>,
+    #[validate(length(max = MAX_PHONE))]
     pub phone: Option<String>,
+    #[validate(length(max = MAX_EMAIL))]
     pub email: Option<String>,
     pub is_active: Option<bool>,
     /// Set a new trial expiry date.
@@ -162,7 +304,7 @@ pub async fn list_garderie_users(
     State(state): State<AppState>,
     _auth: SuperAdminAuth,
     Path(slug): Path<String>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     let schema = schema_name(&slug);
 
     let users = sqlx::query(&format!(
@@ -172,8 +314,7 @@ pub async fn list_garderie_users(
          ORDER BY role, last_name, first_name"
     ))
     .fetch_all(&state.db)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+    .await?;
 
     let result: Vec<Value> = users
         .iter()
@@ -194,11 +335,15 @@ pub async fn list_garderie_users(
     Ok(Json(json!(result)))
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Validate)]
 pub struct CreateGarderieUserRequest {
+    #[validate(email, length(max = MAX_EMAIL))]
     pub email: String,
+    #[validate(length(min = 1, max = MAX_NAME))]
     pub first_name: String,
+    #[validate(length(min = 1, max = MAX_NAME))]
     pub last_name: String,
+    #[validate(length(max = MAX_PASSWORD))]
     pub password: String,
     pub role: Option<String>, // defaults to "admin_garderie"
     pub preferred_locale: Option<String>,
@@ -209,30 +354,29 @@ pub async fn create_garderie_user(
     State(_state): State<AppState>,
     _auth: SuperAdminAuth,
     Path(slug): Path<String>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+) -> Result<(StatusCode, Json<Value>), ApiError> {
     // Body extracted manually to work around Path + Json extractor ordering
     // (handled via separate extractor — see create_garderie_user_inner)
     let _ = slug;
-    Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "use create_garderie_user_body" }))))
+    Err(ApiError::internal())
 }
 
 pub async fn create_garderie_user_body(
     State(state): State<AppState>,
     _auth: SuperAdminAuth,
     Path(slug): Path<String>,
-    Json(body): Json<CreateGarderieUserRequest>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<CreateGarderieUserRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
     let schema = schema_name(&slug);
 
     // Validate role
     let role = body.role.as_deref().unwrap_or("admin_garderie");
     let valid_roles = ["super_admin", "admin_garderie", "educateur", "parent"];
     if !valid_roles.contains(&role) {
-        return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "Invalid role" }))));
+        return Err(ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_role"));
     }
 
-    let password_hash = bcrypt::hash(&body.password, 12)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+    let password_hash = bcrypt::hash(&body.password, 12).context("Password hashing failed")?;
 
     let locale = body.preferred_locale.as_deref().unwrap_or("fr");
 
@@ -248,8 +392,7 @@ pub async fn create_garderie_user_body(
     .bind(role)
     .bind(locale)
     .fetch_one(&state.db)
-    .await
-    .map_err(|e| (StatusCode::BAD_REQUEST, Json(json!({ "error": e.to_string() }))))?;
+    .await?;
 
     Ok((StatusCode::CREATED, Json(json!({
         "id": user_id.to_string(),
@@ -267,8 +410,8 @@ pub async fn invite_garderie_user(
     State(state): State<AppState>,
     _auth: SuperAdminAuth,
     Path(slug): Path<String>,
-    Json(body): Json<InviteUserRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<InviteUserRequest>,
+) -> Result<Json<Value>, ApiError> {
     AuthService::create_invitation(
         &state.db,
         state.email.as_deref(),
@@ -276,16 +419,11 @@ pub async fn invite_garderie_user(
         &body.email,
         body.role,
         None, // invited_by is null for super-admin invitations
+        body.preferred_locale.as_deref(),
         &state.config.app_base_url,
     )
-    .await
-    .map(|_| Json(json!({ "message": format!("Invitation envoyée à {}", body.email) })))
-    .map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })
+    .await?;
+    Ok(Json(json!({ "message": format!("Invitation envoyée à {}", body.email) })))
 }
 
 /// Deactivate (soft-delete) a user in a garderie's tenant schema.
@@ -293,7 +431,7 @@ pub async fn deactivate_garderie_user(
     State(state): State<AppState>,
     _auth: SuperAdminAuth,
     Path((slug, user_id)): Path<(String, Uuid)>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     let schema = schema_name(&slug);
 
     sqlx::query(&format!(
@@ -301,24 +439,130 @@ pub async fn deactivate_garderie_user(
     ))
     .bind(user_id)
     .execute(&state.db)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+    .await?;
 
     Ok(Json(json!({ "message": "Utilisateur désactivé" })))
 }
 
+// ─── Per-garderie backups (S3) ────────────────────────────────────────────────
+
+fn object_store(state: &AppState) -> Result<ObjectStore, ApiError> {
+    ObjectStore::new(&state.config).ok_or_else(|| BackupError::NotConfigured.into())
+}
+
+fn manifest_json(m: &BackupManifest) -> Value {
+    json!({
+        "id": m.id,
+        "slug": m.slug,
+        "garderie_name": m.garderie_name,
+        "created_at": m.created_at,
+        "size": m.total_size(),
+        "files": m.files,
+    })
+}
+
+/// POST /super-admin/garderies/{slug}/backup — stream this garderie's schema and media to S3
+pub async fn backup_garderie(
+    State(state): State<AppState>,
+    _auth: SuperAdminAuth,
+    Path(slug): Path<String>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let store = object_store(&state)?;
+    BackupService::run_and_record(&state.db_bulk, &state.config, &store, &slug, "manual")
+        .await
+        .map(|m| (StatusCode::CREATED, Json(manifest_json(&m))))
+        .map_err(ApiError::from)
+}
+
+/// GET /super-admin/garderies/{slug}/backups — newest first, with size and checksums
+pub async fn list_garderie_backups(
+    State(state): State<AppState>,
+    _auth: SuperAdminAuth,
+    Path(slug): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let store = object_store(&state)?;
+    BackupService::list(&store, &slug)
+        .await
+        .map(|list| Json(json!(list.iter().map(manifest_json).collect::<Vec<_>>())))
+        .map_err(ApiError::from)
+}
+
+/// GET /super-admin/backup-runs?slug=&limit= — outcome of recent scheduled and manual backups
+pub async fn list_backup_runs(
+    State(state): State<AppState>,
+    _auth: SuperAdminAuth,
+    Query(query): Query<BackupRunQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+    BackupService::list_runs(&state.db, query.slug.as_deref(), limit)
+        .await
+        .map(|runs| Json(serde_json::to_value(runs).unwrap()))
+        .map_err(ApiError::from)
+}
+
+/// POST /super-admin/garderies/{slug}/restore — queued like every destructive operation
+pub async fn restore_garderie(
+    State(state): State<AppState>,
+    auth: SuperAdminAuth,
+    Path(slug): Path<String>,
+    ValidJson(body): ValidJson<TenantRestoreRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    TenantDb::require_own_schema()?;
+    let store = object_store(&state)?;
+    let exists = BackupService::list(&store, &slug)
+        .await?
+        .iter()
+        .any(|m| m.id == body.backup_id);
+    if !exists {
+        return Err(BackupError::BackupNotFound.into());
+    }
+
+    let op = OperationService::request(
+        &state.db,
+        OP_RESTORE_GARDERIE,
+        Some(&slug),
+        serde_json::to_value(&body).unwrap(),
+        auth.operator.as_deref(),
+        state.config.destructive_op_delay_minutes,
+    )
+    .await?;
+    alert_super_admins(&state, &op);
+
+    Ok((StatusCode::ACCEPTED, Json(serde_json::to_value(op).unwrap())))
+}
+
 // ─── Global backup ────────────────────────────────────────────────────────────
 
+/// Back up every garderie to S3 when it is configured; otherwise, or when the
+/// garderies share a schema, fall back to a full local dump next to the host's
+/// nightly archives.
 pub async fn trigger_backup_all(
     State(state): State<AppState>,
     _auth: SuperAdminAuth,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
+    if let Some(store) = ObjectStore::new(&state.config).filter(|_| !TenantDb::is_shared()) {
+        let slugs: Vec<String> = sqlx::query_scalar("SELECT slug FROM garderies WHERE archived_at IS NULL ORDER BY slug")
+            .fetch_all(&state.db)
+            .await?;
+
+        let mut results = Vec::with_capacity(slugs.len());
+        for slug in slugs {
+            match BackupService::run_and_record(&state.db_bulk, &state.config, &store, &slug, "manual").await {
+                Ok(m) => results.push(json!({ "slug": slug, "backup": manifest_json(&m) })),
+                Err(e) => {
+                    tracing::error!("Backup of '{slug}' failed: {e}");
+                    results.push(json!({ "slug": slug, "error": e.to_string() }));
+                }
+            }
+        }
+        return Ok(Json(json!({ "status": "Backup completed", "garderies": results })));
+    }
+
     let now = chrono::Utc::now();
     let timestamp = now.format("%Y%m%d_%H%M%S").to_string();
 
-    let backup_dir = std::path::PathBuf::from("/backup/host/backups");
-    tokio::fs::create_dir_all(&backup_dir).await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("Could not create backup dir: {}", e) }))))?;
+    let backup_dir = std::path::PathBuf::from(BACKUP_DIR);
+    tokio::fs::create_dir_all(&backup_dir).await.context("Could not create backup dir")?;
 
     // Full DB dump (all schemas) — --clean adds DROP statements so restores are clean
     let db_file = backup_dir.join(format!("db_{}.sql.gz", timestamp));
@@ -332,12 +576,12 @@ pub async fn trigger_backup_all(
         .arg(&db_file)
         .output()
         .await
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "pg_dump command failed" }))))?;
+        .context("pg_dump command failed")?;
 
     if !db_output.status.success() {
         let stderr = String::from_utf8_lossy(&db_output.stderr);
         tracing::error!("pg_dump failed: {}", stderr);
-        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("pg_dump failed: {}", stderr.trim()) }))));
+        return Err(ApiError::internal());
     }
 
     // Full media archive
@@ -353,12 +597,12 @@ pub async fn trigger_backup_all(
             .arg(media_dir.file_name().unwrap_or_default())
             .output()
             .await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("tar failed: {}", e) }))))?;
+            .context("tar failed")?;
 
         if !tar_output.status.success() {
             let stderr = String::from_utf8_lossy(&tar_output.stderr);
             tracing::error!("tar failed: {}", stderr);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("tar failed: {}", stderr.trim()) }))));
+            return Err(ApiError::internal());
         }
         media_file = Some(media_path.to_string_lossy().to_string());
     }
@@ -405,18 +649,16 @@ fn format_timestamp(ts: &str) -> String {
 pub async fn list_backups(
     _state: State<AppState>,
     _auth: SuperAdminAuth,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    let backup_dir = std::path::PathBuf::from("/backup/host/backups");
+) -> Result<Json<Value>, ApiError> {
+    let backup_dir = std::path::PathBuf::from(BACKUP_DIR);
 
     let mut db_map: std::collections::HashMap<String, String> = std::collections::HashMap::new();
     let mut media_map: std::collections::HashMap<String, String> = std::collections::HashMap::new();
 
     if backup_dir.exists() {
-        let mut entries = tokio::fs::read_dir(&backup_dir).await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+        let mut entries = tokio::fs::read_dir(&backup_dir).await.context("Could not list backups")?;
 
-        while let Some(entry) = entries.next_entry().await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))? {
+        while let Some(entry) = entries.next_entry().await.context("Could not list backups")? {
             let name = entry.file_name().to_string_lossy().to_string();
             if let Some(ts) = extract_timestamp(&name) {
                 if name.ends_with(".sql.gz") {
@@ -444,81 +686,35 @@ pub async fn list_backups(
     Ok(Json(json!(entries)))
 }
 
-#[derive(Deserialize)]
-pub struct RestoreRequest {
-    pub db_file: String,
-    pub media_file: Option<String>,
-}
-
+/// Queue a restore from the host backups; it runs like a garderie deletion,
+/// after a second confirmation or the cancel window.
 pub async fn trigger_restore(
     State(state): State<AppState>,
-    _auth: SuperAdminAuth,
-    Json(body): Json<RestoreRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    let backup_dir = std::path::PathBuf::from("/backup/host/backups");
-
-    // Validate filenames — no path traversal
-    let invalid = |f: &str| f.contains('/') || f.contains("..") || f.contains('\0');
-    if invalid(&body.db_file) || !body.db_file.ends_with(".sql.gz") {
-        return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "Invalid db_file" }))));
+    auth: SuperAdminAuth,
+    ValidJson(body): ValidJson<RestoreRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    validate_restore(&body).map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))?;
+
+    let backup_dir = std::path::PathBuf::from(BACKUP_DIR);
+    if !backup_dir.join(&body.db_file).exists() {
+        return Err(ApiError::not_found("DB backup file not found"));
     }
-    if let Some(ref mf) = body.media_file {
-        if invalid(mf) || !mf.ends_with(".tar.gz") {
-            return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "Invalid media_file" }))));
+    if let Some(ref media_file) = body.media_file {
+        if !backup_dir.join(media_file).exists() {
+            return Err(ApiError::not_found("Media backup file not found"));
         }
     }
 
-    let db_path = backup_dir.join(&body.db_file);
-    if !db_path.exists() {
-        return Err((StatusCode::NOT_FOUND, Json(json!({ "error": "DB backup file not found" }))));
-    }
-
-    // Restore DB: gunzip | psql
-    let restore_cmd = format!(
-        "gunzip -c '{}' | psql '{}'",
-        db_path.display(),
-        &state.config.database_url
-    );
-    let db_output = tokio::process::Command::new("sh")
-        .arg("-c")
-        .arg(&restore_cmd)
-        .output()
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("restore failed: {}", e) }))))?;
-
-    if !db_output.status.success() {
-        let stderr = String::from_utf8_lossy(&db_output.stderr);
-        tracing::error!("DB restore failed: {}", stderr);
-        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("DB restore failed: {}", stderr.trim()) }))));
-    }
-
-    // Restore media if requested
-    let mut media_restored = false;
-    if let Some(media_file) = &body.media_file {
-        let media_path = backup_dir.join(media_file);
-        if !media_path.exists() {
-            return Err((StatusCode::NOT_FOUND, Json(json!({ "error": "Media backup file not found" }))));
-        }
-        let tar_output = tokio::process::Command::new("tar")
-            .arg("-xzf")
-            .arg(&media_path)
-            .arg("-C")
-            .arg("/data")
-            .output()
-            .await
-            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("tar failed: {}", e) }))))?;
-
-        if !tar_output.status.success() {
-            let stderr = String::from_utf8_lossy(&tar_output.stderr);
-            tracing::error!("Media restore failed: {}", stderr);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("Media restore failed: {}", stderr.trim()) }))));
-        }
-        media_restored = true;
-    }
+    let op = OperationService::request(
+        &state.db,
+        OP_RESTORE,
+        None,
+        serde_json::to_value(&body).unwrap(),
+        auth.operator.as_deref(),
+        state.config.destructive_op_delay_minutes,
+    )
+    .await?;
+    alert_super_admins(&state, &op);
 
-    Ok(Json(json!({
-        "status": "Restore completed",
-        "db_file": body.db_file,
-        "media_restored": media_restored,
-    })))
+    Ok((StatusCode::ACCEPTED, Json(serde_json::to_value(op).unwrap())))
 }
@@ -1,20 +1,34 @@
+use anyhow::Context;
 use axum::{
     extract::{Path, State, Query},
     http::{HeaderMap, StatusCode},
     Json,
 };
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use serde_json::{json, Value};
 use uuid::Uuid;
 
 use crate::{
     db::tenant::schema_name,
-    middleware::tenant::TenantSlug,
+    error::ApiError,
+    middleware::{
+        precondition::{self, versioned, IfMatch, StaleVersion, Versioned},
+        tenant::TenantSlug,
+    },
     models::auth::AuthenticatedUser,
-    models::user::UserRole,
-    services::audit::{self, AuditEntry},
+    models::user::{User, UserRole},
+    services::{
+        audit::{self, AuditEntry},
+        email_suppressions::EmailSuppressionService,
+        entitlements::{EntitlementService, Resource},
+        sms::normalize_phone,
+    },
     AppState,
 };
+use crate::models::limits::{MAX_EMAIL, MAX_NAME, MAX_PASSWORD, MAX_PHONE};
+use validator::Validate;
+use crate::middleware::validation::ValidJson;
 
 fn client_ip(h: &HeaderMap) -> String {
     h.get("x-real-ip").and_then(|v| v.to_str().ok())
@@ -24,10 +38,10 @@ fn client_ip(h: &HeaderMap) -> String {
         .to_string()
 }
 
-fn require_admin(user: &AuthenticatedUser) -> Result<(), (StatusCode, Json<Value>)> {
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
     match user.role {
         UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
-        _ => Err((StatusCode::FORBIDDEN, Json(json!({ "error": "Accès refusé" })))),
+        _ => Err(ApiError::forbidden()),
     }
 }
 
@@ -36,16 +50,18 @@ pub async fn list_users(
     State(state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+) -> Result<Json<Value>, ApiError> {
     require_admin(&user)?;
     let schema = schema_name(&tenant);
 
     let rows = sqlx::query(&format!(
         "SELECT u.id, u.email, u.first_name, u.last_name, u.role::TEXT as role,
-                u.is_active, u.preferred_locale, u.created_at, u.updated_at,
+                u.is_active, u.preferred_locale, u.phone, u.created_at, u.updated_at,
                 COALESCE(c.privacy_accepted, false) as privacy_accepted,
-                COALESCE(c.photos_accepted, false) as photos_accepted
+                COALESCE(c.photos_accepted, false) as photos_accepted,
+                s.reason as email_suppression
          FROM {schema}.users u
+         LEFT JOIN public.email_suppressions s ON s.email = LOWER(u.email)
          LEFT JOIN (
            SELECT DISTINCT ON (user_id) user_id, privacy_accepted, photos_accepted
            FROM {schema}.consent_records
@@ -54,8 +70,7 @@ pub async fn list_users(
          ORDER BY u.role, u.last_name, u.first_name"
     ))
     .fetch_all(&state.db)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+    .await?;
 
     // Get deletion requests from audit log
     let deletion_requests = sqlx::query_scalar::<_, String>(&format!(
@@ -82,9 +97,12 @@ pub async fn list_users(
                 "role": row.get::<String, _>("role"),
                 "is_active": row.get::<bool, _>("is_active"),
                 "preferred_locale": row.get::<String, _>("preferred_locale"),
+                "phone": row.get::<Option<String>, _>("phone"),
                 "privacy_accepted": row.get::<bool, _>("privacy_accepted"),
                 "photos_accepted": row.get::<bool, _>("photos_accepted"),
                 "deletion_requested": deletion_set.contains(&user_id),
+                // "bounce" or "complaint": nothing is emailed to this address
+                "email_undeliverable": row.get::<Option<String>, _>("email_suppression"),
             })
         })
         .collect();
@@ -92,11 +110,52 @@ pub async fn list_users(
     Ok(Json(json!(result)))
 }
 
-#[derive(Deserialize)]
+/// Resume sending to a user's address reported undeliverable, e.g. once the parent
+/// confirmed their mailbox works again.
+pub async fn clear_email_suppression(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    headers: HeaderMap,
+    user: AuthenticatedUser,
+    Path(target_id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    require_admin(&user)?;
+    let schema = schema_name(&tenant);
+
+    let email: String = sqlx::query_scalar(&format!("SELECT email FROM {schema}.users WHERE id = $1"))
+        .bind(target_id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| ApiError::from_key(StatusCode::NOT_FOUND, "user_not_found"))?;
+
+    let cleared = EmailSuppressionService::clear(&state.db, &email)
+        .await?;
+    if !cleared {
+        return Err(ApiError::not_found("Adresse non bloquée"));
+    }
+
+    audit::log(state.db.clone(), &tenant, AuditEntry {
+        user_id:        Some(user.user_id),
+        user_name:      None,
+        action:         "user.email_suppression_clear".to_string(),
+        resource_type:  Some("user".to_string()),
+        resource_id:    Some(target_id.to_string()),
+        resource_label: Some(email),
+        ip_address:     client_ip(&headers),
+    });
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize, Validate)]
 pub struct CreateUserRequest {
+    #[validate(email, length(max = MAX_EMAIL))]
     pub email: String,
+    #[validate(length(min = 1, max = MAX_NAME))]
     pub first_name: String,
+    #[validate(length(min = 1, max = MAX_NAME))]
     pub last_name: String,
+    #[validate(length(max = MAX_PASSWORD))]
     pub password: String,
     pub role: Option<String>,
     pub preferred_locale: Option<String>,
@@ -108,19 +167,23 @@ pub async fn create_user(
     TenantSlug(tenant): TenantSlug,
     headers: HeaderMap,
     user: AuthenticatedUser,
-    Json(body): Json<CreateUserRequest>,
-) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<CreateUserRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
     require_admin(&user)?;
     let schema = schema_name(&tenant);
 
     let role = body.role.as_deref().unwrap_or("parent");
     let valid_roles = ["admin_garderie", "educateur", "parent"];
     if !valid_roles.contains(&role) {
-        return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "Rôle invalide" }))));
+        return Err(ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_role"));
+    }
+
+    if role != "parent" {
+        EntitlementService::ensure_can_add(&state.db, &tenant, Resource::Staff, 1)
+            .await?;
     }
 
-    let password_hash = bcrypt::hash(&body.password, 12)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+    let password_hash = bcrypt::hash(&body.password, 12).context("hash password")?;
 
     let locale = body.preferred_locale.as_deref().unwrap_or("fr");
 
@@ -136,8 +199,7 @@ pub async fn create_user(
     .bind(role)
     .bind(locale)
     .fetch_one(&state.db)
-    .await
-    .map_err(|e| (StatusCode::BAD_REQUEST, Json(json!({ "error": e.to_string() }))))?;
+    .await?;
 
     audit::log(state.db.clone(), &tenant, AuditEntry {
         user_id:        Some(user.user_id),
@@ -160,13 +222,18 @@ pub async fn create_user(
     }))))
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Validate)]
 pub struct UpdateUserRequest {
+    #[validate(length(max = MAX_NAME))]
     pub first_name: Option<String>,
+    #[validate(length(max = MAX_NAME))]
     pub last_name: Option<String>,
     pub role: Option<String>,
     pub is_active: Option<bool>,
     pub preferred_locale: Option<String>,
+    /// Empty string clears the number.
+    #[validate(length(max = MAX_PHONE))]
+    pub phone: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -180,8 +247,9 @@ pub async fn update_user(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path(target_id): Path<Uuid>,
-    Json(body): Json<UpdateUserRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    IfMatch(expected): IfMatch,
+    ValidJson(body): ValidJson<UpdateUserRequest>,
+) -> Result<Versioned<Value>, ApiError> {
     require_admin(&user)?;
     let schema = schema_name(&tenant);
 
@@ -189,10 +257,19 @@ pub async fn update_user(
     if let Some(ref r) = body.role {
         let valid_roles = ["admin_garderie", "educateur", "parent"];
         if !valid_roles.contains(&r.as_str()) {
-            return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "Rôle invalide" }))));
+            return Err(ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_role"));
         }
     }
 
+    let phone: Option<Option<String>> = match body.phone.as_deref().map(str::trim) {
+        Some("") => Some(None),
+        Some(raw) => Some(Some(
+            normalize_phone(raw)
+                .ok_or_else(|| ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_phone"))?,
+        )),
+        None => None,
+    };
+
     // Build dynamic UPDATE — only update provided fields
     let mut sets: Vec<String> = vec![];
     if body.first_name.is_some() { sets.push("first_name = $__".into()); }
@@ -200,9 +277,10 @@ pub async fn update_user(
     if body.role.is_some()       { sets.push(format!("role = $__::\"{schema}\".user_role")); }
     if body.is_active.is_some()  { sets.push("is_active = $__".into()); }
     if body.preferred_locale.is_some() { sets.push("preferred_locale = $__".into()); }
+    if phone.is_some()           { sets.push("phone = $__".into()); }
 
     if sets.is_empty() {
-        return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "Aucune modification fournie" }))));
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Aucune modification fournie"));
     }
 
     // Replace $__ placeholders with sequential $n (starting at $2; $1 = id)
@@ -216,22 +294,43 @@ pub async fn update_user(
         })
         .collect();
 
+    // Conditional on the version the client last read, when it sent one
     let sql = format!(
-        "UPDATE {schema}.users SET {}, updated_at = NOW() WHERE id = $1 RETURNING id",
+        "UPDATE {schema}.users SET {}, updated_at = NOW()
+         WHERE id = $1 AND (${param_idx}::timestamptz IS NULL OR updated_at = ${param_idx})
+         RETURNING updated_at",
         sets_sql.join(", ")
     );
 
-    let mut q = sqlx::query_scalar::<_, Uuid>(&sql).bind(target_id);
+    let mut q = sqlx::query_scalar::<_, DateTime<Utc>>(&sql).bind(target_id);
     if let Some(v) = &body.first_name      { q = q.bind(v); }
     if let Some(v) = &body.last_name       { q = q.bind(v); }
     if let Some(v) = &body.role            { q = q.bind(v); }
     if let Some(v) = body.is_active        { q = q.bind(v); }
     if let Some(v) = &body.preferred_locale { q = q.bind(v); }
-
-    q.fetch_optional(&state.db)
+    if let Some(v) = &phone                { q = q.bind(v); }
+    q = q.bind(expected);
+
+    let db_error = |e: sqlx::Error| ApiError::from(e);
+    let Some(updated_at) = q.fetch_optional(&state.db).await.map_err(db_error)? else {
+        // Missing, or changed since the client's version
+        let current = sqlx::query_as::<_, User>(&format!(
+            "SELECT id, email, password_hash, first_name, last_name,
+                    role::TEXT as role, avatar_url, is_active, force_password_change, preferred_locale, phone,
+                    created_at, updated_at
+             FROM {schema}.users WHERE id = $1"
+        ))
+        .bind(target_id)
+        .fetch_optional(&state.db)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?
-        .ok_or((StatusCode::NOT_FOUND, Json(json!({ "error": "Utilisateur introuvable" }))))?;
+        .map_err(db_error)?;
+        return Err(match current {
+            Some(current) if expected.is_some() => precondition::conflict(&StaleVersion {
+                current: serde_json::to_value(current).unwrap(),
+            }),
+            _ => ApiError::from_key(StatusCode::NOT_FOUND, "user_not_found"),
+        });
+    };
 
     audit::log(state.db.clone(), &tenant, AuditEntry {
         user_id:        Some(user.user_id),
@@ -243,7 +342,7 @@ pub async fn update_user(
         ip_address:     "unknown".to_string(),
     });
 
-    Ok(Json(json!({ "message": "Utilisateur mis à jour" })))
+    Ok(versioned(updated_at, json!({ "message": "Utilisateur mis à jour", "updated_at": updated_at })))
 }
 
 use crate::models::user::DeleteUserRequest;
@@ -255,13 +354,13 @@ pub async fn deactivate_user(
     user: AuthenticatedUser,
     Path(target_id): Path<Uuid>,
     Query(query): Query<DeleteQuery>,
-    Json(body): Json<DeleteUserRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<DeleteUserRequest>,
+) -> Result<Json<Value>, ApiError> {
     require_admin(&user)?;
 
     // Prevent self-deactivation
     if target_id == user.user_id {
-        return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "Impossible de se désactiver soi-même" }))));
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Impossible de se désactiver soi-même"));
     }
 
     // Verify admin's password
@@ -275,14 +374,14 @@ pub async fn deactivate_user(
     .await
     .map_err(|e| {
         tracing::error!("deactivate_user: db error fetching admin hash: {}", e);
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+        ApiError::from(e)
     })?;
 
     let admin_hash = match admin_hash {
         Some(h) => h,
         None => {
             tracing::warn!("deactivate_user: admin user not found in tenant schema: {}", user.user_id);
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "Admin non trouvé" }))));
+            return Err(ApiError::internal());
         }
     };
 
@@ -290,11 +389,11 @@ pub async fn deactivate_user(
         Ok(true) => tracing::info!("deactivate_user: password verified for admin_id={}", user.user_id),
         Ok(false) => {
             tracing::info!("deactivate_user: password mismatch for admin_id={}", user.user_id);
-            return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "Mot de passe incorrect" }))));
+            return Err(ApiError::from_key(StatusCode::BAD_REQUEST, "wrong_password"));
         }
         Err(e) => {
             tracing::error!("deactivate_user: bcrypt verify error for admin_id={}: {}", user.user_id, e);
-            return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "Mot de passe incorrect" }))));
+            return Err(ApiError::from_key(StatusCode::BAD_REQUEST, "wrong_password"));
         }
     }
 
@@ -304,16 +403,12 @@ pub async fn deactivate_user(
     ))
     .bind(target_id)
     .fetch_optional(&state.db)
-    .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+    .await?;
 
     let (first_name, last_name, email) = match target_info {
         Some(info) => info,
         None => {
-            return Err((
-                StatusCode::NOT_FOUND,
-                Json(json!({ "error": "Utilisateur non trouvé" })),
-            ))
+            return Err(ApiError::from_key(StatusCode::NOT_FOUND, "user_not_found"))
         }
     };
 
@@ -326,8 +421,7 @@ pub async fn deactivate_user(
         ))
         .bind(target_id)
         .execute(&state.db)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+        .await?;
 
         audit::log(state.db.clone(), &tenant, AuditEntry {
             user_id:        Some(user.user_id),
@@ -345,8 +439,7 @@ pub async fn deactivate_user(
         ))
         .bind(target_id)
         .execute(&state.db)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))))?;
+        .await?;
 
         audit::log(state.db.clone(), &tenant, AuditEntry {
             user_id:        Some(user.user_id),
@@ -370,13 +463,13 @@ pub async fn reset_user_password(
     TenantSlug(tenant): TenantSlug,
     user: AuthenticatedUser,
     Path(target_id): Path<Uuid>,
-    Json(body): Json<AdminResetPasswordRequest>,
-) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    ValidJson(body): ValidJson<AdminResetPasswordRequest>,
+) -> Result<Json<Value>, ApiError> {
     require_admin(&user)?;
 
     let method = body.method.as_deref().unwrap_or("email");
     if !["email", "temp_password"].contains(&method) {
-        return Err((StatusCode::BAD_REQUEST, Json(json!({ "error": "Méthode invalide" }))));
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Méthode invalide"));
     }
 
     let (message, temp_password) = AuthService::reset_user_password_as_admin(
@@ -387,13 +480,7 @@ pub async fn reset_user_password(
         Some(method),
         &state.config.app_base_url,
     )
-    .await
-    .map_err(|e| {
-        (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": e.to_string() })),
-        )
-    })?;
+    .await?;
 
     let response = AdminResetPasswordResponse {
         message,
@@ -403,3 +490,62 @@ pub async fn reset_user_password(
     Ok(Json(serde_json::to_value(response).unwrap()))
 }
 
+
+use crate::models::erasure::EraseUserRequest;
+use crate::services::erasure::ErasureService;
+
+/// POST /users/{id}/erase — admin only; right-to-be-forgotten erasure of a user's
+/// personal data. Returns the erasure certificate.
+pub async fn erase_user(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    headers: HeaderMap,
+    Path(target_id): Path<Uuid>,
+    ValidJson(body): ValidJson<EraseUserRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+
+    if target_id == user.user_id {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Impossible d'effacer son propre compte"));
+    }
+
+    let schema = schema_name(&tenant);
+    let admin_hash: Option<String> = sqlx::query_scalar(&format!(
+        "SELECT password_hash FROM {schema}.users WHERE id = $1 AND is_active = TRUE"
+    ))
+    .bind(user.user_id)
+    .fetch_optional(&state.db)
+    .await?;
+    if !admin_hash.is_some_and(|h| bcrypt::verify(&body.password, &h).unwrap_or(false)) {
+        return Err(ApiError::from_key(StatusCode::BAD_REQUEST, "wrong_password"));
+    }
+
+    let scrub_messages = body.scrub_messages.unwrap_or(state.config.erasure_scrub_messages);
+    let certificate = ErasureService::erase(&state.db, &tenant, target_id, user.user_id, scrub_messages)
+        .await?;
+
+    audit::log(state.db.clone(), &tenant, AuditEntry {
+        user_id:        Some(user.user_id),
+        user_name:      None,
+        action:         "user.erase".to_string(),
+        resource_type:  Some("user".to_string()),
+        resource_id:    Some(target_id.to_string()),
+        resource_label: Some(format!("certificate={}", certificate.id)),
+        ip_address:     client_ip(&headers),
+    });
+    Ok(Json(serde_json::to_value(certificate).unwrap()))
+}
+
+/// GET /users/erasure-certificates — admin only
+pub async fn list_erasure_certificates(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    ErasureService::certificates(&state.db, &tenant)
+        .await
+        .map(|c| Json(serde_json::to_value(c).unwrap()))
+        .map_err(ApiError::from)
+}
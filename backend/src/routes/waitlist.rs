@@ -0,0 +1,209 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{
+    error::ApiError,
+    middleware::{rate_limit::check_rate_limit, tenant::TenantSlug},
+    models::{
+        auth::AuthenticatedUser,
+        user::UserRole,
+        waitlist::{
+            ConvertWaitlistRequest, CreateWaitlistEntryRequest, UpdateWaitlistEntryRequest, WaitlistQuery,
+            WAITLIST_STATUSES,
+        },
+    },
+    services::{
+        audit::{self, AuditEntry},
+        auth::AuthService,
+        waitlist::WaitlistService,
+    },
+    AppState,
+};
+use crate::middleware::validation::ValidJson;
+
+fn client_ip(h: &HeaderMap) -> String {
+    h.get("x-real-ip").and_then(|v| v.to_str().ok())
+        .or_else(|| h.get("x-forwarded-for").and_then(|v| v.to_str().ok())
+            .and_then(|s| s.split(',').next()).map(|s| s.trim()))
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+fn require_admin(user: &AuthenticatedUser) -> Result<(), ApiError> {
+    match user.role {
+        UserRole::AdminGarderie | UserRole::SuperAdmin => Ok(()),
+        _ => Err(ApiError::forbidden()),
+    }
+}
+
+fn validate_entry(req: &CreateWaitlistEntryRequest) -> Result<(), ApiError> {
+    let names = [
+        &req.child_first_name,
+        &req.child_last_name,
+        &req.parent_first_name,
+        &req.parent_last_name,
+    ];
+    if names.iter().any(|n| n.trim().is_empty()) || !req.parent_email.contains('@') {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Nom de l'enfant, nom du parent et courriel valide requis"));
+    }
+    Ok(())
+}
+
+/// POST /tenant/waitlist — public form, no account required.
+pub async fn submit_waitlist(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    headers: HeaderMap,
+    ValidJson(mut body): ValidJson<CreateWaitlistEntryRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let rate_limit_key = format!("waitlist:{}:{}", tenant, client_ip(&headers));
+    check_rate_limit(&mut state.redis.clone(), &rate_limit_key, 5, 3600).await?;
+
+    validate_entry(&body)?;
+    body.priority = None;
+
+    WaitlistService::create(&state.db, &tenant, &body)
+        .await
+        .map(|_| (StatusCode::CREATED, Json(json!({ "success": true }))))
+        .map_err(ApiError::from)
+}
+
+/// GET /waitlist?status=&age_group=
+pub async fn list_waitlist(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Query(query): Query<WaitlistQuery>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+    WaitlistService::list(&state.db, &tenant, &query)
+        .await
+        .map(|entries| Json(serde_json::to_value(entries).unwrap()))
+        .map_err(ApiError::from)
+}
+
+pub async fn create_waitlist_entry(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    ValidJson(body): ValidJson<CreateWaitlistEntryRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&user)?;
+    validate_entry(&body)?;
+    WaitlistService::create(&state.db, &tenant, &body)
+        .await
+        .map(|entry| (StatusCode::CREATED, Json(serde_json::to_value(entry).unwrap())))
+        .map_err(ApiError::from)
+}
+
+/// PUT /waitlist/{id} — moving an entry to `offered` emails the parent.
+pub async fn update_waitlist_entry(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidJson(body): ValidJson<UpdateWaitlistEntryRequest>,
+) -> Result<Json<Value>, ApiError> {
+    require_admin(&user)?;
+
+    if let Some(status) = body.status.as_deref() {
+        if status == "enrolled" || !WAITLIST_STATUSES.contains(&status) {
+            return Err(ApiError::from_key(StatusCode::BAD_REQUEST, "invalid_status"));
+        }
+    }
+
+    let previous = WaitlistService::get(&state.db, &tenant, id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Inscription introuvable"))?;
+
+    let entry = WaitlistService::update(&state.db, &tenant, id, &body)
+        .await?;
+
+    if entry.status == "offered" && previous.status != "offered" {
+        match state.email.as_deref() {
+            Some(email) => {
+                if let Err(e) = WaitlistService::send_offer(&state.db, email, &tenant, &entry).await {
+                    tracing::warn!("Failed to send waitlist offer for entry {id}: {e}");
+                }
+            }
+            None => tracing::warn!("Email service unavailable, waitlist offer for entry {id} not sent"),
+        }
+    }
+
+    Ok(Json(serde_json::to_value(entry).unwrap()))
+}
+
+pub async fn delete_waitlist_entry(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    require_admin(&user)?;
+    WaitlistService::delete(&state.db, &tenant, id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(ApiError::from)
+}
+
+/// POST /waitlist/{id}/convert — create the child record and invite the parent.
+pub async fn convert_waitlist_entry(
+    State(state): State<AppState>,
+    TenantSlug(tenant): TenantSlug,
+    headers: HeaderMap,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    ValidJson(body): ValidJson<ConvertWaitlistRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    require_admin(&user)?;
+
+    let conversion = WaitlistService::convert(&state.db, &tenant, id, &body)
+        .await?;
+
+    let child = &conversion.child;
+    audit::log(state.db.clone(), &tenant, AuditEntry {
+        user_id:        Some(user.user_id),
+        user_name:      None,
+        action:         "waitlist.convert".to_string(),
+        resource_type:  Some("child".to_string()),
+        resource_id:    Some(child.id.to_string()),
+        resource_label: Some(format!("{} {}", child.first_name, child.last_name)),
+        ip_address:     client_ip(&headers),
+    });
+
+    let mut invitation_sent = false;
+    if conversion.needs_invitation {
+        match AuthService::create_invitation(
+            &state.db,
+            state.email.as_deref(),
+            &tenant,
+            &conversion.entry.parent_email,
+            UserRole::Parent,
+            Some(user.user_id),
+            None,
+            &state.config.app_base_url,
+        )
+        .await
+        {
+            Ok(()) => {
+                crate::services::metrics::INVITATIONS_COUNTER.with_label_values(&[&tenant]).inc();
+                invitation_sent = true;
+            }
+            Err(e) => tracing::warn!("Failed to invite parent for waitlist entry {id}: {e}"),
+        }
+    }
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "entry": conversion.entry,
+            "child": conversion.child,
+            "invitation_sent": invitation_sent,
+        })),
+    ))
+}
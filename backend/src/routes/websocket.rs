@@ -3,45 +3,78 @@ use axum::{
         ws::{Message, WebSocket, WebSocketUpgrade},
         Query, State,
     },
-    response::Response,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
 };
 use futures_util::{SinkExt, StreamExt};
 use serde::Deserialize;
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
 use tracing::{error, info};
+use uuid::Uuid;
 
 use crate::{
-    middleware::auth::decode_access_token,
+    db::tenant_db::TenantDb,
+    error::ApiError,
     middleware::tenant::TenantSlug,
+    models::{auth::AuthenticatedUser, message::WsClientEvent},
+    services::{
+        presence::{PresenceService, HEARTBEAT_SECS},
+        realtime::{tenant_channel, user_channel, RealtimeService, MAX_CONNECTIONS_PER_USER},
+    },
     AppState,
 };
 
+/// A socket that sent nothing (not even a pong) for this long is closed.
+const IDLE_TIMEOUT_SECS: u64 = 3 * HEARTBEAT_SECS;
+
 #[derive(Debug, Deserialize)]
 pub struct WsQueryParams {
-    pub token: String,
+    pub ticket: String,
 }
 
+/// POST /ws/ticket — a single-use ticket, valid 30 seconds, for opening GET /ws
+pub async fn issue_ticket(
+    State(mut state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    RealtimeService::issue_ticket(&mut state.redis, &user)
+        .await
+        .map(|ticket| Json(json!({ "ticket": ticket })))
+        .map_err(ApiError::from)
+}
+
+/// GET /ws?ticket=… — authenticated by a ticket from POST /ws/ticket, for this garderie.
+/// A user may keep `MAX_CONNECTIONS_PER_USER` sockets open.
 pub async fn ws_handler(
     ws: WebSocketUpgrade,
-    State(state): State<AppState>,
+    State(mut state): State<AppState>,
     TenantSlug(tenant): TenantSlug,
     Query(params): Query<WsQueryParams>,
 ) -> Response {
-    let jwt_secret = state.config.jwt_secret.clone();
-    let auth_user = decode_access_token(&params.token, &jwt_secret);
+    let user_id = match RealtimeService::redeem_ticket(&mut state.redis, &params.ticket).await {
+        Some(ticket) if ticket.tenant == tenant => ticket.user_id,
+        _ => {
+            return ApiError::new(StatusCode::UNAUTHORIZED, "Ticket WebSocket invalide ou expiré")
+                .into_response()
+        }
+    };
+    if !RealtimeService::open_connection(&mut state.redis, &tenant, user_id).await {
+        return ApiError::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            format!("Trop de connexions ouvertes (maximum {MAX_CONNECTIONS_PER_USER})"),
+        )
+        .with_code("too_many_connections")
+        .into_response();
+    }
 
     ws.on_upgrade(move |socket| async move {
-        match auth_user {
-            Ok(user) => {
-                info!(
-                    "WebSocket connected: user={} tenant={}",
-                    user.user_id, tenant
-                );
-                handle_socket(socket, state, tenant, user.user_id.to_string()).await;
-            }
-            Err(e) => {
-                error!("WebSocket auth failed: {}", e);
-            }
-        }
+        info!("WebSocket connected: user={} tenant={}", user_id, tenant);
+        let mut redis = state.redis.clone();
+        handle_socket(socket, state, tenant.clone(), user_id).await;
+        RealtimeService::close_connection(&mut redis, &tenant, user_id).await;
     })
 }
 
@@ -49,12 +82,12 @@ async fn handle_socket(
     socket: WebSocket,
     state: AppState,
     tenant: String,
-    user_id: String,
+    user_id: Uuid,
 ) {
     let (mut sender, mut receiver) = socket.split();
 
-    // Create a dedicated pub/sub connection from the client
-    let channel = format!("tenant:{}:messages", tenant);
+    // Create a dedicated pub/sub connection from the client: garderie-wide events
+    // and the user's own
     let mut pubsub = match state.redis_client.get_async_pubsub().await {
         Ok(c) => c,
         Err(e) => {
@@ -63,52 +96,122 @@ async fn handle_socket(
         }
     };
 
-    if let Err(e) = pubsub.subscribe(&channel).await {
-        error!("Redis subscribe error: {}", e);
-        return;
+    for channel in [tenant_channel(&tenant), user_channel(&tenant, user_id)] {
+        if let Err(e) = pubsub.subscribe(&channel).await {
+            error!("Redis subscribe error: {}", e);
+            return;
+        }
     }
 
+    let mut redis = state.redis.clone();
+    PresenceService::connect(&mut redis, &tenant, user_id).await;
+
+    // Frames the server sends on its own (pings, close)
+    let (control_tx, mut control_rx) = mpsc::channel::<Message>(4);
+
     // Spawn task: Redis Pub/Sub → WebSocket
+    let own_id = user_id.to_string();
     let mut redis_task = tokio::spawn(async move {
         let mut pubsub_stream = pubsub.on_message();
-        while let Some(msg) = pubsub_stream.next().await {
-            let payload: String = match msg.get_payload() {
-                Ok(p) => p,
-                Err(_) => continue,
+        loop {
+            let ws_msg = tokio::select! {
+                control = control_rx.recv() => match control {
+                    Some(frame) => frame,
+                    None => break,
+                },
+                msg = pubsub_stream.next() => {
+                    let Some(msg) = msg else { break };
+                    let payload: String = match msg.get_payload() {
+                        Ok(p) => p,
+                        Err(_) => continue,
+                    };
+                    let value = serde_json::from_str::<serde_json::Value>(&payload)
+                        .unwrap_or(serde_json::Value::String(payload));
+                    // Typed events (message_updated, message_deleted) are published ready to
+                    // forward; plain message payloads are new messages.
+                    let event = if value.get("type").is_some() && value.get("payload").is_some() {
+                        // Don't echo a user's own typing indicator back to them.
+                        if value["type"] == "typing" && value["payload"]["user_id"] == own_id.as_str() {
+                            continue;
+                        }
+                        value
+                    } else {
+                        serde_json::json!({ "type": "new_message", "payload": value })
+                    };
+                    Message::Text(event.to_string().into())
+                }
             };
-            let ws_msg = serde_json::json!({
-                "type": "new_message",
-                "payload": serde_json::from_str::<serde_json::Value>(&payload)
-                    .unwrap_or(serde_json::Value::String(payload))
-            });
-            if sender
-                .send(Message::Text(ws_msg.to_string().into()))
-                .await
-                .is_err()
-            {
+            let closing = matches!(ws_msg, Message::Close(_));
+            if sender.send(ws_msg).await.is_err() || closing {
                 break;
             }
         }
     });
 
-    // Receive messages from the client
-    let mut client_task = tokio::spawn(async move {
-        while let Some(Ok(msg)) = receiver.next().await {
-            match msg {
-                Message::Text(text) => {
-                    info!("WS message from {}: {}", user_id, text);
+    // Receive events from the client, refreshing presence and pinging while the socket
+    // stays open; close it once the client goes quiet
+    let client_tenant = tenant.clone();
+    let mut client_redis = redis.clone();
+    let client_db = state.db.clone();
+    let mut client_task = tokio::spawn(TenantDb::scope(tenant.clone(), async move {
+        let mut heartbeat = tokio::time::interval(Duration::from_secs(HEARTBEAT_SECS));
+        let mut last_seen = Instant::now();
+        loop {
+            tokio::select! {
+                _ = heartbeat.tick() => {
+                    if last_seen.elapsed() > Duration::from_secs(IDLE_TIMEOUT_SECS) {
+                        info!("WebSocket idle: user={} tenant={}", user_id, client_tenant);
+                        let _ = control_tx.send(Message::Close(None)).await;
+                        break;
+                    }
+                    PresenceService::heartbeat(&mut client_redis, &client_tenant, user_id).await;
+                    RealtimeService::refresh_connection(&mut client_redis, &client_tenant, user_id).await;
+                    if control_tx.send(Message::Ping(Default::default())).await.is_err() {
+                        break;
+                    }
+                }
+                msg = receiver.next() => {
+                    if let Some(Ok(_)) = msg {
+                        last_seen = Instant::now();
+                    }
+                    match msg {
+                        Some(Ok(Message::Text(text))) => match serde_json::from_str::<WsClientEvent>(&text) {
+                            Ok(WsClientEvent::Typing(event)) => {
+                                if let Err(e) = PresenceService::publish_typing(
+                                    &client_db,
+                                    &mut client_redis,
+                                    &client_tenant,
+                                    user_id,
+                                    &event,
+                                )
+                                .await
+                                {
+                                    info!("Typing event from {} dropped: {}", user_id, e);
+                                }
+                            }
+                            Ok(WsClientEvent::Ping) => {
+                                PresenceService::heartbeat(&mut client_redis, &client_tenant, user_id).await;
+                            }
+                            Err(_) => info!("WS message from {}: {}", user_id, text),
+                        },
+                        Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                        Some(Ok(_)) => {}
+                    }
                 }
-                Message::Ping(_) => {}
-                Message::Close(_) => break,
-                _ => {}
             }
         }
-    });
+    }));
 
     tokio::select! {
         _ = (&mut redis_task) => client_task.abort(),
-        _ = (&mut client_task) => redis_task.abort(),
+        // Let the close frame go out before the socket drops
+        _ = (&mut client_task) => {
+            let _ = tokio::time::timeout(Duration::from_secs(1), &mut redis_task).await;
+            redis_task.abort();
+        }
     }
 
+    PresenceService::disconnect(&mut redis, &tenant, user_id).await;
+
     info!("WebSocket disconnected");
 }
@@ -0,0 +1,145 @@
+use chrono::{Local, NaiveDate, Timelike};
+use serde_json::json;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::db::tenant_db::TenantDb;
+use crate::services::auto_absences::AutoAbsenceService;
+use crate::services::branding::BrandingService;
+use crate::services::email::EmailService;
+use crate::services::notifications::NotificationService;
+
+/// Spawn a background task that wakes up every minute and, for any tenant whose
+/// `auto_absence_cutoff` matches the current local time, marks absent in the journal
+/// the children expected today but not checked in, then asks their parents to confirm
+/// (email and push). A late check-in lifts the mark, see [`AutoAbsenceService::resolve`].
+pub fn start(
+    pool: PgPool,
+    email: Option<Arc<EmailService>>,
+    notifications: Arc<NotificationService>,
+    app_base_url: String,
+) {
+    tokio::spawn(async move {
+        // Day each tenant was last checked, so a slow minute never runs it twice
+        let mut last_run: HashMap<String, NaiveDate> = HashMap::new();
+
+        loop {
+            let secs_past = Local::now().second() as u64;
+            let sleep_secs = if secs_past == 0 { 60 } else { 60 - secs_past };
+            tokio::time::sleep(tokio::time::Duration::from_secs(sleep_secs)).await;
+            let Some(_job) = crate::services::worker::job().await else {
+                break;
+            };
+
+            let now = Local::now();
+            let current_time = format!("{:02}:{:02}", now.hour(), now.minute());
+            let today = now.date_naive();
+
+            let tenants: Vec<String> = match sqlx::query_scalar(
+                "SELECT slug FROM public.garderies
+                 WHERE is_active = TRUE AND archived_at IS NULL AND slug != 'demo' AND auto_absence_cutoff = $1",
+            )
+            .bind(&current_time)
+            .fetch_all(&pool)
+            .await
+            {
+                Ok(rows) => rows,
+                Err(e) => {
+                    warn!("Absence scheduler: failed to query tenants: {e}");
+                    continue;
+                }
+            };
+
+            for slug in tenants {
+                TenantDb::scope(slug.clone(), async {
+                    if last_run.get(&slug) == Some(&today) {
+                        return;
+                    }
+                    last_run.insert(slug.clone(), today);
+
+                    let marked = match AutoAbsenceService::mark_missing(&pool, &slug, today).await {
+                        Ok(marked) => marked,
+                        Err(e) => {
+                            warn!("Absence scheduler: failed to mark absences for '{slug}': {e}");
+                            return;
+                        }
+                    };
+                    if marked.is_empty() {
+                        return;
+                    }
+                    info!("Absence scheduler: {} child(ren) marked absent for '{slug}'", marked.len());
+
+                    let ids: Vec<_> = marked.iter().map(|m| m.id).collect();
+                    ask_parents(&pool, email.as_deref(), &notifications, &app_base_url, &slug, &ids).await;
+                })
+                .await;
+            }
+        }
+    });
+}
+
+async fn ask_parents(
+    pool: &PgPool,
+    email: Option<&EmailService>,
+    notifications: &NotificationService,
+    app_base_url: &str,
+    slug: &str,
+    ids: &[uuid::Uuid],
+) {
+    let notices = match AutoAbsenceService::notices(pool, slug, ids).await {
+        Ok(notices) => notices,
+        Err(e) => {
+            warn!("Absence scheduler: failed to list parents for '{slug}': {e}");
+            return;
+        }
+    };
+
+    let app_url = if let Some(idx) = app_base_url.find("://") {
+        let scheme = &app_base_url[..idx];
+        let domain = &app_base_url[idx + 3..];
+        format!("{scheme}://{slug}.{domain}/fr/parent/journal")
+    } else {
+        format!("https://{slug}.{app_base_url}/fr/parent/journal")
+    };
+    let (garderie_name, branding) = BrandingService::for_email(pool, slug).await;
+
+    for notice in notices {
+        if let Some(email_svc) = email {
+            let name = format!("{} {}", notice.first_name, notice.last_name);
+            if let Err(e) = email_svc
+                .send_absence_check(
+                    slug,
+                    &notice.email,
+                    &name,
+                    &notice.child_first_name,
+                    notice.date,
+                    &app_url,
+                    &garderie_name,
+                    &branding,
+                )
+                .await
+            {
+                warn!("Absence scheduler: failed to email {}: {e}", notice.email);
+            }
+        }
+
+        if notifications.fcm_api_key.is_some() {
+            // FCM data values must be strings.
+            let data = json!({
+                "type": "auto_absence",
+                "auto_absence_id": notice.auto_absence_id.to_string(),
+                "child_id": notice.child_id.to_string(),
+            });
+            let title = format!("Absence : {}", notice.child_first_name);
+            let body = "Pas encore arrivé(e) à la garderie : confirmez l'absence ou prévenez l'équipe.";
+            if let Err(e) = notifications
+                .notify_user(pool, slug, notice.user_id, &title, body, Some(data), None)
+                .await
+            {
+                warn!("Absence scheduler: push to {} in '{slug}' failed: {e}", notice.user_id);
+            }
+        }
+    }
+}
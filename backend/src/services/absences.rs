@@ -0,0 +1,280 @@
+use chrono::{Datelike, NaiveDate};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::attendance::{ChildAbsence, DeclareAbsenceRequest, ABSENCE_STATUSES},
+};
+
+/// Longest range a single declaration may cover.
+pub const MAX_ABSENCE_DAYS: i64 = 60;
+
+const MAX_REASON_CHARS: usize = 500;
+
+const ABSENCE_COLS: &str = "id, child_id, start_date, end_date, status, reason, declared_by, created_at";
+
+#[derive(Debug, thiserror::Error)]
+pub enum AbsenceError {
+    #[error("Date invalide : {0} (format AAAA-MM-JJ)")]
+    InvalidDate(String),
+    #[error("La date de fin précède la date de début")]
+    EndBeforeStart,
+    #[error("Une absence ne peut pas être déclarée pour une date passée")]
+    PastDate,
+    #[error("Une absence ne peut pas dépasser {MAX_ABSENCE_DAYS} jours")]
+    TooLong,
+    #[error("Motif d'absence inconnu : {0}")]
+    InvalidStatus(String),
+    #[error("Le motif ne doit pas dépasser {MAX_REASON_CHARS} caractères")]
+    ReasonTooLong,
+    #[error("Aucun jour de garde prévu sur cette période")]
+    NoScheduledDay,
+    #[error("Enfant introuvable")]
+    ChildNotFound,
+}
+
+/// Who to tell about a declared absence.
+pub struct AbsenceRecipients {
+    pub child_name: String,
+    pub group_id: Option<Uuid>,
+    /// Educators of the child's group, or the admins when nobody is assigned to it.
+    pub staff_ids: Vec<Uuid>,
+}
+
+fn parse_date(s: &str) -> Result<NaiveDate, AbsenceError> {
+    NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").map_err(|_| AbsenceError::InvalidDate(s.to_string()))
+}
+
+/// Dates, status and reason of a declaration. Parents may not declare past days;
+/// staff recording a phone call after the fact may.
+pub fn parse_request(
+    req: &DeclareAbsenceRequest,
+    today: NaiveDate,
+    allow_past: bool,
+) -> Result<(NaiveDate, NaiveDate, String, Option<String>), AbsenceError> {
+    let start = parse_date(&req.start_date)?;
+    let end = match req.end_date.as_deref().filter(|s| !s.trim().is_empty()) {
+        Some(s) => parse_date(s)?,
+        None => start,
+    };
+    if end < start {
+        return Err(AbsenceError::EndBeforeStart);
+    }
+    if !allow_past && start < today {
+        return Err(AbsenceError::PastDate);
+    }
+    if (end - start).num_days() >= MAX_ABSENCE_DAYS {
+        return Err(AbsenceError::TooLong);
+    }
+    let status = req.status.as_deref().map(str::trim).filter(|s| !s.is_empty()).unwrap_or("absent");
+    if !ABSENCE_STATUSES.contains(&status) {
+        return Err(AbsenceError::InvalidStatus(status.to_string()));
+    }
+    let reason = req.reason.as_deref().map(str::trim).filter(|s| !s.is_empty());
+    if reason.is_some_and(|r| r.chars().count() > MAX_REASON_CHARS) {
+        return Err(AbsenceError::ReasonTooLong);
+    }
+    Ok((start, end, status.to_string(), reason.map(str::to_string)))
+}
+
+/// Days of the range the child is expected: their schedule days (ISO weekday
+/// numbers), or Monday to Friday when no schedule is set.
+pub fn absence_days(start: NaiveDate, end: NaiveDate, schedule_days: Option<&[i32]>) -> Vec<NaiveDate> {
+    let schedule = schedule_days.filter(|d| !d.is_empty());
+    start
+        .iter_days()
+        .take_while(|d| *d <= end)
+        .filter(|d| {
+            let weekday = d.weekday().number_from_monday() as i32;
+            match schedule {
+                Some(days) => days.contains(&weekday),
+                None => weekday <= 5,
+            }
+        })
+        .collect()
+}
+
+pub struct AbsenceService;
+
+impl AbsenceService {
+    /// Record the absence and mark each expected day in the attendance (with the
+    /// declared status) and as absent in the journal, in one transaction.
+    pub async fn declare(
+        pool: &PgPool,
+        tenant: &str,
+        child_id: Uuid,
+        declared_by: Uuid,
+        req: &DeclareAbsenceRequest,
+        allow_past: bool,
+    ) -> anyhow::Result<(ChildAbsence, Vec<NaiveDate>)> {
+        let today = chrono::Local::now().date_naive();
+        let (start, end, status, reason) = parse_request(req, today, allow_past)?;
+
+        let schema = schema_name(tenant);
+        let schedule: Option<Option<Vec<i32>>> = sqlx::query_scalar(&format!(
+            "SELECT schedule_days FROM {schema}.children WHERE id = $1 AND is_active = TRUE"
+        ))
+        .bind(child_id)
+        .fetch_optional(pool)
+        .await?;
+        let Some(schedule) = schedule else {
+            return Err(AbsenceError::ChildNotFound.into());
+        };
+        let days = absence_days(start, end, schedule.as_deref());
+        if days.is_empty() {
+            return Err(AbsenceError::NoScheduledDay.into());
+        }
+
+        let mut tx = pool.begin().await?;
+        let absence = sqlx::query_as::<_, ChildAbsence>(&format!(
+            "INSERT INTO {schema}.child_absences (child_id, start_date, end_date, status, reason, declared_by)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             RETURNING {ABSENCE_COLS}"
+        ))
+        .bind(child_id)
+        .bind(start)
+        .bind(end)
+        .bind(&status)
+        .bind(&reason)
+        .bind(declared_by)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(&format!(
+            "INSERT INTO {schema}.attendance (child_id, date, status, marked_by, created_at, updated_at)
+             SELECT $1, d, $3::{schema}.attendance_status, $4, NOW(), NOW() FROM UNNEST($2::DATE[]) AS d
+             ON CONFLICT (child_id, date) DO UPDATE SET status = $3::{schema}.attendance_status, marked_by = $4, updated_at = NOW()"
+        ))
+        .bind(child_id)
+        .bind(&days)
+        .bind(&status)
+        .bind(declared_by)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(&format!(
+            "INSERT INTO {schema}.daily_journals (child_id, date, absent, created_by, created_at, updated_at)
+             SELECT $1, d, TRUE, $3, NOW(), NOW() FROM UNNEST($2::DATE[]) AS d
+             ON CONFLICT (child_id, date) DO UPDATE SET absent = TRUE, updated_at = NOW()"
+        ))
+        .bind(child_id)
+        .bind(&days)
+        .bind(declared_by)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok((absence, days))
+    }
+
+    pub async fn list(pool: &PgPool, tenant: &str, child_id: Uuid) -> anyhow::Result<Vec<ChildAbsence>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, ChildAbsence>(&format!(
+            "SELECT {ABSENCE_COLS} FROM {schema}.child_absences
+             WHERE child_id = $1
+             ORDER BY start_date DESC, created_at DESC"
+        ))
+        .bind(child_id)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Absences overlapping `[start, end)`, for the monthly attendance report.
+    pub async fn list_between(
+        pool: &PgPool,
+        tenant: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> anyhow::Result<Vec<ChildAbsence>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, ChildAbsence>(&format!(
+            "SELECT {ABSENCE_COLS} FROM {schema}.child_absences
+             WHERE start_date < $2 AND end_date >= $1
+             ORDER BY child_id, start_date"
+        ))
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    pub async fn recipients(pool: &PgPool, tenant: &str, child_id: Uuid) -> anyhow::Result<AbsenceRecipients> {
+        let schema = schema_name(tenant);
+        let (child_name, group_id): (String, Option<Uuid>) = sqlx::query_as(&format!(
+            "SELECT first_name, group_id FROM {schema}.children WHERE id = $1"
+        ))
+        .bind(child_id)
+        .fetch_one(pool)
+        .await?;
+        let staff_ids: Vec<Uuid> = sqlx::query_scalar(&format!(
+            "WITH educators AS (
+                 SELECT u.id FROM {schema}.educator_groups eg
+                 JOIN {schema}.users u ON u.id = eg.user_id
+                 WHERE eg.group_id = $1 AND u.is_active = TRUE
+             )
+             SELECT id FROM educators
+             UNION
+             SELECT id FROM {schema}.users
+             WHERE role = 'admin_garderie' AND is_active = TRUE AND NOT EXISTS (SELECT 1 FROM educators)"
+        ))
+        .bind(group_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(AbsenceRecipients { child_name, group_id, staff_ids })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn request(start: &str, end: Option<&str>, status: Option<&str>) -> DeclareAbsenceRequest {
+        DeclareAbsenceRequest {
+            start_date: start.into(),
+            end_date: end.map(Into::into),
+            status: status.map(Into::into),
+            reason: Some("  Gastro  ".into()),
+        }
+    }
+
+    #[test]
+    fn absence_days_follow_the_schedule() {
+        // Friday 2026-10-16 to Tuesday 2026-10-20
+        let (start, end) = (date("2026-10-16"), date("2026-10-20"));
+        assert_eq!(absence_days(start, end, None), vec![date("2026-10-16"), date("2026-10-19"), date("2026-10-20")]);
+        assert_eq!(absence_days(start, end, Some(&[])).len(), 3);
+        assert_eq!(absence_days(start, end, Some(&[2, 3])), vec![date("2026-10-20")]);
+        assert!(absence_days(date("2026-10-17"), date("2026-10-18"), None).is_empty());
+    }
+
+    #[test]
+    fn requests_are_validated() {
+        let today = date("2026-10-18");
+        let (start, end, status, reason) = parse_request(&request("2026-10-19", None, None), today, false).unwrap();
+        assert_eq!((start, end), (date("2026-10-19"), date("2026-10-19")));
+        assert_eq!(status, "absent");
+        assert_eq!(reason.as_deref(), Some("Gastro"));
+
+        assert!(parse_request(&request("2026-10-19", Some("2026-10-23"), Some("malade")), today, false).is_ok());
+        assert!(matches!(
+            parse_request(&request("2026-10-20", Some("2026-10-19"), None), today, false),
+            Err(AbsenceError::EndBeforeStart)
+        ));
+        assert!(matches!(parse_request(&request("2026-10-17", None, None), today, false), Err(AbsenceError::PastDate)));
+        assert!(parse_request(&request("2026-10-17", None, None), today, true).is_ok());
+        assert!(matches!(
+            parse_request(&request("2026-10-19", Some("2026-12-31"), None), today, false),
+            Err(AbsenceError::TooLong)
+        ));
+        assert!(matches!(
+            parse_request(&request("2026-10-19", None, Some("present")), today, false),
+            Err(AbsenceError::InvalidStatus(_))
+        ));
+        assert!(matches!(parse_request(&request("19/10/2026", None, None), today, false), Err(AbsenceError::InvalidDate(_))));
+    }
+}
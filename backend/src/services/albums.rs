@@ -0,0 +1,311 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::album::{Album, AlbumInput},
+    services::notifications::has_active_child_sql,
+};
+
+const MAX_TITLE_CHARS: usize = 255;
+const VISIBILITIES: &[&str] = &["private", "public", "group", "child"];
+
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct InvalidAlbum(pub String);
+
+/// Explicit column list for Album. All queries must alias the albums table as `a`.
+/// Without a chosen cover, the latest shared photo of the album stands in.
+fn album_cols(schema: &str) -> String {
+    format!(
+        "a.id, a.title, a.description, a.cover_media_id,
+         COALESCE(
+             (SELECT cm.thumbnail_path FROM \"{schema}\".media cm WHERE cm.id = a.cover_media_id),
+             (SELECT lm.thumbnail_path FROM \"{schema}\".album_media am
+              JOIN \"{schema}\".media lm ON lm.id = am.media_id
+              WHERE am.album_id = a.id AND lm.thumbnail_path IS NOT NULL AND lm.visibility != 'private'
+              ORDER BY lm.created_at DESC LIMIT 1)
+         ) as cover_thumbnail_path,
+         a.visibility::TEXT as visibility, a.group_id, a.child_id, a.created_by,
+         (SELECT COUNT(*) FROM \"{schema}\".album_media am WHERE am.album_id = a.id) as media_count,
+         a.created_at, a.updated_at"
+    )
+}
+
+/// Albums a user may see, mirroring media visibility. Binds `$1` to the user id
+/// and `$2` to the educator's groups.
+fn visible_albums(schema: &str, is_staff: bool, scoped: bool) -> String {
+    if is_staff && !scoped {
+        "TRUE".to_string()
+    } else if is_staff {
+        format!(
+            "(a.visibility = 'public'
+              OR a.created_by = $1
+              OR a.group_id = ANY($2::uuid[])
+              OR a.child_id IN (SELECT c.id FROM \"{schema}\".children c WHERE c.group_id = ANY($2::uuid[])))"
+        )
+    } else {
+        format!(
+            "(a.visibility = 'public'
+              OR (a.visibility = 'group' AND a.group_id IN (
+                  SELECT c.group_id FROM \"{schema}\".child_parents cp
+                  JOIN \"{schema}\".children c ON c.id = cp.child_id
+                  WHERE cp.user_id = $1 AND c.group_id IS NOT NULL
+              ))
+              OR (a.visibility = 'child' AND a.child_id IN (
+                  SELECT cp.child_id FROM \"{schema}\".child_parents cp WHERE cp.user_id = $1
+              )))"
+        )
+    }
+}
+
+/// Checked title and visibility; the group or child is kept only for the scope that uses it.
+pub fn validate(input: &AlbumInput) -> Result<(String, String, Option<Uuid>, Option<Uuid>), InvalidAlbum> {
+    let title = input.title.trim();
+    if title.is_empty() {
+        return Err(InvalidAlbum("Le titre de l'album est requis".into()));
+    }
+    if title.chars().count() > MAX_TITLE_CHARS {
+        return Err(InvalidAlbum(format!("Le titre ne doit pas dépasser {MAX_TITLE_CHARS} caractères")));
+    }
+    let visibility = input.visibility.as_deref().unwrap_or("private");
+    if !VISIBILITIES.contains(&visibility) {
+        return Err(InvalidAlbum(format!("Visibilité inconnue : {visibility}")));
+    }
+    let group_id = if visibility == "group" { input.group_id } else { None };
+    let child_id = if visibility == "child" { input.child_id } else { None };
+    if visibility == "group" && group_id.is_none() {
+        return Err(InvalidAlbum("Choisissez le groupe avec lequel partager l'album".into()));
+    }
+    if visibility == "child" && child_id.is_none() {
+        return Err(InvalidAlbum("Choisissez l'enfant avec lequel partager l'album".into()));
+    }
+    Ok((title.to_string(), visibility.to_string(), group_id, child_id))
+}
+
+pub struct AlbumService;
+
+impl AlbumService {
+    pub async fn list(
+        pool: &PgPool,
+        tenant: &str,
+        user_id: Uuid,
+        is_staff: bool,
+        group_scope: Option<&[Uuid]>,
+    ) -> anyhow::Result<Vec<Album>> {
+        let schema = schema_name(tenant);
+        let cols = album_cols(&schema);
+        let filter = visible_albums(&schema, is_staff, group_scope.is_some());
+        Ok(sqlx::query_as::<_, Album>(&format!(
+            "SELECT {cols} FROM \"{schema}\".albums a
+             WHERE {filter}
+             ORDER BY a.created_at DESC"
+        ))
+        .bind(user_id)
+        .bind(group_scope.unwrap_or_default())
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// The album, if it exists and the user may see it.
+    pub async fn get(
+        pool: &PgPool,
+        tenant: &str,
+        id: Uuid,
+        user_id: Uuid,
+        is_staff: bool,
+        group_scope: Option<&[Uuid]>,
+    ) -> anyhow::Result<Option<Album>> {
+        let schema = schema_name(tenant);
+        let cols = album_cols(&schema);
+        let filter = visible_albums(&schema, is_staff, group_scope.is_some());
+        Ok(sqlx::query_as::<_, Album>(&format!(
+            "SELECT {cols} FROM \"{schema}\".albums a
+             WHERE a.id = $3 AND {filter}"
+        ))
+        .bind(user_id)
+        .bind(group_scope.unwrap_or_default())
+        .bind(id)
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    pub async fn create(pool: &PgPool, tenant: &str, input: &AlbumInput, user_id: Uuid) -> anyhow::Result<Album> {
+        let (title, visibility, group_id, child_id) = validate(input)?;
+        if input.cover_media_id.is_some() {
+            return Err(InvalidAlbum("Ajoutez des photos à l'album avant de choisir sa couverture".into()).into());
+        }
+
+        let schema = schema_name(tenant);
+        let cols = album_cols(&schema);
+        Ok(sqlx::query_as::<_, Album>(&format!(
+            "INSERT INTO \"{schema}\".albums AS a (title, description, visibility, group_id, child_id, created_by)
+             VALUES ($1, $2, $3::\"{schema}\".media_visibility, $4, $5, $6)
+             RETURNING {cols}"
+        ))
+        .bind(&title)
+        .bind(input.description.as_deref().map(str::trim).filter(|d| !d.is_empty()))
+        .bind(&visibility)
+        .bind(group_id)
+        .bind(child_id)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?)
+    }
+
+    pub async fn update(pool: &PgPool, tenant: &str, id: Uuid, input: &AlbumInput) -> anyhow::Result<Option<Album>> {
+        let (title, visibility, group_id, child_id) = validate(input)?;
+
+        let schema = schema_name(tenant);
+        if let Some(cover) = input.cover_media_id {
+            let in_album: bool = sqlx::query_scalar(&format!(
+                "SELECT EXISTS (SELECT 1 FROM \"{schema}\".album_media WHERE album_id = $1 AND media_id = $2)"
+            ))
+            .bind(id)
+            .bind(cover)
+            .fetch_one(pool)
+            .await?;
+            if !in_album {
+                return Err(InvalidAlbum("La couverture doit être une photo de l'album".into()).into());
+            }
+        }
+
+        let cols = album_cols(&schema);
+        Ok(sqlx::query_as::<_, Album>(&format!(
+            "UPDATE \"{schema}\".albums a
+             SET title = $2, description = $3, cover_media_id = $4,
+                 visibility = $5::\"{schema}\".media_visibility, group_id = $6, child_id = $7
+             WHERE a.id = $1
+             RETURNING {cols}"
+        ))
+        .bind(id)
+        .bind(&title)
+        .bind(input.description.as_deref().map(str::trim).filter(|d| !d.is_empty()))
+        .bind(input.cover_media_id)
+        .bind(&visibility)
+        .bind(group_id)
+        .bind(child_id)
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    /// Delete an album; its media stay in the gallery.
+    pub async fn delete(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<bool> {
+        let schema = schema_name(tenant);
+        let deleted = sqlx::query(&format!("DELETE FROM \"{schema}\".albums WHERE id = $1"))
+            .bind(id)
+            .execute(pool)
+            .await?
+            .rows_affected();
+        Ok(deleted > 0)
+    }
+
+    /// Attach media to an album; unknown ids and media already in it are skipped.
+    /// Returns how many were added.
+    pub async fn add_media(pool: &PgPool, tenant: &str, id: Uuid, media_ids: &[Uuid]) -> anyhow::Result<u64> {
+        let schema = schema_name(tenant);
+        let added = sqlx::query(&format!(
+            "INSERT INTO \"{schema}\".album_media (album_id, media_id)
+             SELECT $1, m.id FROM \"{schema}\".media m WHERE m.id = ANY($2)
+             ON CONFLICT DO NOTHING"
+        ))
+        .bind(id)
+        .bind(media_ids)
+        .execute(pool)
+        .await?
+        .rows_affected();
+        Ok(added)
+    }
+
+    /// Detach a media from an album, clearing the cover if it was that media.
+    pub async fn remove_media(pool: &PgPool, tenant: &str, id: Uuid, media_id: Uuid) -> anyhow::Result<bool> {
+        let schema = schema_name(tenant);
+        let removed = sqlx::query(&format!(
+            "DELETE FROM \"{schema}\".album_media WHERE album_id = $1 AND media_id = $2"
+        ))
+        .bind(id)
+        .bind(media_id)
+        .execute(pool)
+        .await?
+        .rows_affected();
+
+        sqlx::query(&format!(
+            "UPDATE \"{schema}\".albums SET cover_media_id = NULL WHERE id = $1 AND cover_media_id = $2"
+        ))
+        .bind(id)
+        .bind(media_id)
+        .execute(pool)
+        .await?;
+
+        Ok(removed > 0)
+    }
+
+    /// (email, name) of the active parents an album is shared with.
+    pub async fn share_recipients(pool: &PgPool, tenant: &str, album: &Album) -> anyhow::Result<Vec<(String, String)>> {
+        let s = schema_name(tenant);
+        let recipients = match album.visibility.as_str() {
+            "public" => {
+                sqlx::query_as(&format!(
+                    "SELECT email, CONCAT(first_name, ' ', last_name)
+                     FROM \"{s}\".users WHERE role::text = 'parent' AND is_active = TRUE AND {}",
+                    has_active_child_sql(&s, "users")
+                ))
+                .fetch_all(pool)
+                .await?
+            }
+            "group" => {
+                sqlx::query_as(&format!(
+                    "SELECT DISTINCT u.email, CONCAT(u.first_name, ' ', u.last_name)
+                     FROM \"{s}\".users u
+                     JOIN \"{s}\".child_parents cp ON cp.user_id = u.id
+                     JOIN \"{s}\".children c ON c.id = cp.child_id
+                     WHERE c.group_id = $1 AND c.is_active = TRUE AND u.is_active = TRUE"
+                ))
+                .bind(album.group_id)
+                .fetch_all(pool)
+                .await?
+            }
+            "child" => {
+                sqlx::query_as(&format!(
+                    "SELECT DISTINCT u.email, CONCAT(u.first_name, ' ', u.last_name)
+                     FROM \"{s}\".users u
+                     JOIN \"{s}\".child_parents cp ON cp.user_id = u.id
+                     WHERE cp.child_id = $1 AND u.is_active = TRUE"
+                ))
+                .bind(album.child_id)
+                .fetch_all(pool)
+                .await?
+            }
+            _ => vec![],
+        };
+        Ok(recipients)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(visibility: &str, group_id: Option<Uuid>, child_id: Option<Uuid>) -> AlbumInput {
+        AlbumInput {
+            title: "  Sortie à la ferme ".into(),
+            description: None,
+            cover_media_id: None,
+            visibility: Some(visibility.into()),
+            group_id,
+            child_id,
+        }
+    }
+
+    #[test]
+    fn album_scope_must_name_its_group_or_child() {
+        let id = Some(Uuid::new_v4());
+        let (title, visibility, group_id, child_id) = validate(&input("group", id, id)).unwrap();
+        assert_eq!((title.as_str(), visibility.as_str()), ("Sortie à la ferme", "group"));
+        assert_eq!((group_id, child_id), (id, None));
+        assert!(validate(&input("group", None, id)).is_err());
+        assert!(validate(&input("child", id, None)).is_err());
+        assert_eq!(validate(&input("public", id, id)).unwrap().2, None);
+        assert!(validate(&input("everyone", None, None)).is_err());
+    }
+}
@@ -0,0 +1,307 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::db::tenant::schema_name;
+use crate::db::tenant_db::TenantDb;
+use crate::services::encryption::{self, KeyRing};
+
+pub const SCAN_PENDING: &str = "pending";
+pub const SCAN_CLEAN: &str = "clean";
+pub const SCAN_INFECTED: &str = "infected";
+pub const SCAN_ERROR: &str = "error";
+
+/// Files scanned per table and per tenant in one pass.
+const BATCH_SIZE: i64 = 20;
+
+/// Pause between two passes over the pending uploads.
+const JOB_INTERVAL_SECS: u64 = 30;
+
+/// clamd's default StreamMaxLength is 25 MB; chunks stay well below it.
+const CHUNK_BYTES: usize = 64 * 1024;
+
+const SCAN_TIMEOUT_SECS: u64 = 120;
+
+/// Tables holding scanned uploads.
+const SCANNED_TABLES: &[&str] = &["media", "documents"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanVerdict {
+    Clean,
+    Infected(String),
+    /// clamd could not scan this file (size limit, corrupt archive…).
+    Failed(String),
+}
+
+/// Parse a clamd reply such as `stream: OK` or `stream: Eicar-Signature FOUND`.
+fn parse_reply(reply: &str) -> ScanVerdict {
+    let reply = reply.trim_end_matches(['\0', '\n']).trim();
+    let body = reply.strip_prefix("stream:").unwrap_or(reply).trim();
+    if body == "OK" {
+        ScanVerdict::Clean
+    } else if let Some(signature) = body.strip_suffix("FOUND") {
+        ScanVerdict::Infected(signature.trim().to_string())
+    } else {
+        ScanVerdict::Failed(body.strip_suffix("ERROR").unwrap_or(body).trim().to_string())
+    }
+}
+
+/// Minimal clamd client speaking the INSTREAM command over TCP.
+pub struct ClamdClient {
+    addr: String,
+}
+
+impl ClamdClient {
+    /// Returns None if CLAMAV_ADDR is not configured.
+    pub fn new(config: &Config) -> Option<Self> {
+        config.clamav_addr.clone().map(|addr| Self { addr })
+    }
+
+    /// Fails only when clamd cannot be reached; per-file problems are a [`ScanVerdict::Failed`].
+    pub async fn scan(&self, data: &[u8]) -> anyhow::Result<ScanVerdict> {
+        let scan = async {
+            let mut stream = TcpStream::connect(&self.addr).await?;
+            stream.write_all(b"zINSTREAM\0").await?;
+            for chunk in data.chunks(CHUNK_BYTES) {
+                stream.write_all(&(chunk.len() as u32).to_be_bytes()).await?;
+                stream.write_all(chunk).await?;
+            }
+            stream.write_all(&0u32.to_be_bytes()).await?;
+
+            let mut reply = Vec::new();
+            stream.read_to_end(&mut reply).await?;
+            Ok::<_, anyhow::Error>(parse_reply(&String::from_utf8_lossy(&reply)))
+        };
+        tokio::time::timeout(tokio::time::Duration::from_secs(SCAN_TIMEOUT_SECS), scan)
+            .await
+            .map_err(|_| anyhow::anyhow!("clamd at {} timed out", self.addr))?
+    }
+}
+
+/// An upload held back because the scanner found malware in it.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct QuarantinedFile {
+    /// "media" | "document"
+    pub kind: String,
+    pub id: Uuid,
+    pub original_filename: String,
+    pub uploader_id: Uuid,
+    pub uploader_name: Option<String>,
+    pub scan_signature: Option<String>,
+    pub scanned_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct ScanStats {
+    pub clean: usize,
+    pub infected: usize,
+    pub failed: usize,
+}
+
+#[derive(sqlx::FromRow)]
+struct PendingFile {
+    id: Uuid,
+    storage_path: String,
+    is_encrypted: bool,
+    encryption_iv: Option<Vec<u8>>,
+    encryption_tag: Option<Vec<u8>>,
+    key_version: i32,
+}
+
+pub struct AntivirusService;
+
+impl AntivirusService {
+    /// Quarantined media and documents of a tenant, newest first.
+    pub async fn quarantined(pool: &PgPool, tenant: &str) -> anyhow::Result<Vec<QuarantinedFile>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, QuarantinedFile>(&format!(
+            r#"SELECT f.kind, f.id, f.original_filename, f.uploader_id,
+                      CONCAT(u.first_name, ' ', u.last_name) AS uploader_name,
+                      f.scan_signature, f.scanned_at, f.created_at
+               FROM (
+                   SELECT 'media' AS kind, id, original_filename, uploader_id, scan_signature, scanned_at, created_at
+                   FROM "{schema}".media WHERE scan_status = $1
+                   UNION ALL
+                   SELECT 'document', id, original_filename, uploader_id, scan_signature, scanned_at, created_at
+                   FROM "{schema}".documents WHERE scan_status = $1
+               ) f
+               LEFT JOIN "{schema}".users u ON u.id = f.uploader_id
+               ORDER BY f.scanned_at DESC NULLS LAST"#
+        ))
+        .bind(SCAN_INFECTED)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Uploads of a tenant still waiting for a scan.
+    pub async fn pending_count(pool: &PgPool, tenant: &str) -> anyhow::Result<i64> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_scalar(&format!(
+            r#"SELECT (SELECT COUNT(*) FROM "{schema}".media WHERE scan_status = $1)
+                    + (SELECT COUNT(*) FROM "{schema}".documents WHERE scan_status = $1)"#
+        ))
+        .bind(SCAN_PENDING)
+        .fetch_one(pool)
+        .await?)
+    }
+
+    /// Scan up to [`BATCH_SIZE`] pending uploads per table of one tenant.
+    ///
+    /// Stops at the first file clamd cannot be reached for, leaving the rest pending
+    /// for the next pass. Unreadable files are marked `error` rather than retried.
+    pub async fn scan_tenant(
+        pool: &PgPool,
+        clamd: &ClamdClient,
+        media_dir: &str,
+        keys: &KeyRing,
+        tenant: &str,
+    ) -> anyhow::Result<ScanStats> {
+        let schema = schema_name(tenant);
+        let mut stats = ScanStats::default();
+
+        for table in SCANNED_TABLES {
+            let pending: Vec<PendingFile> = sqlx::query_as(&format!(
+                r#"SELECT id, storage_path, is_encrypted, encryption_iv, encryption_tag, key_version
+                   FROM "{schema}".{table}
+                   WHERE scan_status = $1
+                   ORDER BY created_at
+                   LIMIT $2"#
+            ))
+            .bind(SCAN_PENDING)
+            .bind(BATCH_SIZE)
+            .fetch_all(pool)
+            .await?;
+
+            for file in pending {
+                let verdict = match Self::read_plaintext(media_dir, keys, tenant, &file).await {
+                    Ok(data) => clamd.scan(&data).await?,
+                    Err(e) => ScanVerdict::Failed(format!("Lecture impossible : {e}")),
+                };
+                let (status, signature) = match &verdict {
+                    ScanVerdict::Clean => {
+                        stats.clean += 1;
+                        (SCAN_CLEAN, None)
+                    }
+                    ScanVerdict::Infected(signature) => {
+                        stats.infected += 1;
+                        warn!("Antivirus: {table} {} of '{tenant}' quarantined ({signature})", file.id);
+                        (SCAN_INFECTED, Some(signature.as_str()))
+                    }
+                    ScanVerdict::Failed(reason) => {
+                        stats.failed += 1;
+                        warn!("Antivirus: could not scan {table} {} of '{tenant}': {reason}", file.id);
+                        (SCAN_ERROR, Some(reason.as_str()))
+                    }
+                };
+                sqlx::query(&format!(
+                    r#"UPDATE "{schema}".{table}
+                       SET scan_status = $1, scan_signature = $2, scanned_at = NOW()
+                       WHERE id = $3"#
+                ))
+                .bind(status)
+                .bind(signature)
+                .bind(file.id)
+                .execute(pool)
+                .await?;
+            }
+        }
+
+        Ok(stats)
+    }
+
+    async fn read_plaintext(
+        media_dir: &str,
+        keys: &KeyRing,
+        tenant: &str,
+        file: &PendingFile,
+    ) -> anyhow::Result<Vec<u8>> {
+        let bytes = tokio::fs::read(Path::new(media_dir).join(&file.storage_path)).await?;
+        if !file.is_encrypted {
+            return Ok(bytes);
+        }
+        let (Some(iv), Some(tag)) = (&file.encryption_iv, &file.encryption_tag) else {
+            anyhow::bail!("missing encryption metadata");
+        };
+        let key = keys.tenant_key(file.key_version, tenant)?;
+        encryption::decrypt_file(&bytes, iv, tag, &key)
+    }
+}
+
+/// Spawn a background task that scans pending uploads of every garderie with clamd
+/// and quarantines infected files. Does nothing when CLAMAV_ADDR is unset.
+pub fn start(pool: PgPool, config: Arc<Config>) {
+    let Some(clamd) = ClamdClient::new(&config) else {
+        info!("Antivirus scanning disabled (CLAMAV_ADDR not set)");
+        return;
+    };
+
+    tokio::spawn(async move {
+        let keys = match KeyRing::from_config(&config) {
+            Ok(k) => k,
+            Err(e) => {
+                error!("Antivirus job disabled: {e}");
+                return;
+            }
+        };
+
+        loop {
+            tokio::time::sleep(tokio::time::Duration::from_secs(JOB_INTERVAL_SECS)).await;
+            let Some(_job) = crate::services::worker::job().await else {
+                break;
+            };
+
+            let tenants: Vec<String> = match sqlx::query_scalar("SELECT slug FROM public.garderies WHERE archived_at IS NULL ORDER BY slug")
+                .fetch_all(&pool)
+                .await
+            {
+                Ok(rows) => rows,
+                Err(e) => {
+                    warn!("Antivirus job: failed to query tenants: {e}");
+                    continue;
+                }
+            };
+
+            for slug in &tenants {
+                match TenantDb::scope(slug.clone(), AntivirusService::scan_tenant(&pool, &clamd, &config.media_dir, &keys, slug)).await {
+                    Ok(stats) if stats.infected > 0 || stats.failed > 0 => info!(
+                        "Antivirus: '{slug}' — {} clean, {} quarantined, {} failed",
+                        stats.clean, stats.infected, stats.failed
+                    ),
+                    Ok(_) => {}
+                    Err(e) => {
+                        // clamd down or database error: retry everything on the next pass
+                        warn!("Antivirus job: scan of '{slug}' interrupted: {e}");
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamd_replies_are_parsed() {
+        assert_eq!(parse_reply("stream: OK\0"), ScanVerdict::Clean);
+        assert_eq!(
+            parse_reply("stream: Win.Test.EICAR_HDB-1 FOUND\0"),
+            ScanVerdict::Infected("Win.Test.EICAR_HDB-1".into())
+        );
+        assert_eq!(
+            parse_reply("INSTREAM size limit exceeded. ERROR\0"),
+            ScanVerdict::Failed("INSTREAM size limit exceeded.".into())
+        );
+    }
+}
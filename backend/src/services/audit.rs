@@ -2,6 +2,7 @@ use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::db::tenant::schema_name;
+use crate::db::tenant_db::TenantDb;
 
 /// An audit log entry to record.
 pub struct AuditEntry {
@@ -20,7 +21,7 @@ pub struct AuditEntry {
 pub fn log(pool: PgPool, tenant: &str, entry: AuditEntry) {
     let schema = schema_name(tenant);
 
-    tokio::spawn(async move {
+    tokio::spawn(TenantDb::scope(tenant.to_string(), async move {
         let res = sqlx::query(&format!(
             "INSERT INTO {schema}.audit_log
                 (user_id, user_name, action, resource_type, resource_id, resource_label, ip_address)
@@ -39,5 +40,5 @@ pub fn log(pool: PgPool, tenant: &str, entry: AuditEntry) {
         if let Err(e) = res {
             tracing::warn!("audit log insert failed for schema {schema}: {e}");
         }
-    });
+    }));
 }
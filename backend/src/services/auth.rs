@@ -1,20 +1,60 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
 use crate::{
-    db::tenant::schema_name,
+    db::{tenant::schema_name, tenant_db::TenantDb},
     models::{
-        auth::{Claims, RefreshClaims},
+        auth::{ActiveSession, Claims, ClientInfo, LoginEvent, RefreshClaims},
+        email_template::TEMPLATE_INVITATION,
         user::{
             InvitationToken, LoginResponse, LoginStep1Response, PendingInvitationDto, RefreshToken, User, UserProfile,
             UserRole,
         },
     },
-    services::{children::ChildService, email::EmailService},
+    services::{
+        branding::BrandingService,
+        children::ChildService,
+        consents::ConsentService,
+        email::EmailService,
+        email_i18n::Locale,
+        email_templates::EmailTemplateService,
+        entitlements::{EntitlementService, Resource},
+        identities::IdentityService,
+        jwt_keys::JwtKeys,
+        password_policy::PasswordPolicy,
+        sms::{normalize_phone, SmsService},
+    },
 };
 
+pub const LOGIN_HISTORY_DEFAULT_LIMIT: i64 = 50;
+pub const LOGIN_HISTORY_MAX_LIMIT: i64 = 200;
+
+/// Seconds after a rotation during which presenting the old refresh token again is
+/// taken for a concurrent refresh (two tabs) rather than a replay.
+const REFRESH_REUSE_GRACE_SECS: i64 = 10;
+
+/// A refresh token was presented again after being rotated: someone else may hold a
+/// copy, so the whole session family has been revoked.
+#[derive(Debug, thiserror::Error)]
+#[error("Session révoquée : ce jeton de rafraîchissement a déjà été utilisé")]
+pub struct RefreshTokenReused {
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    /// Live tokens of the family revoked by the detection.
+    pub revoked: u64,
+}
+
+/// Whether presenting `stored` again is a replay of a token already exchanged, as
+/// opposed to a token revoked by a logout or a refresh racing its own rotation.
+fn is_replay(stored: &RefreshToken, now: DateTime<Utc>) -> bool {
+    stored.revoked
+        && stored
+            .rotated_at
+            .is_some_and(|at| now - at > chrono::Duration::seconds(REFRESH_REUSE_GRACE_SECS))
+}
+
 /// Result of login step 1.
 pub enum LoginOutcome {
     TwoFactorRequired(LoginStep1Response),
@@ -41,20 +81,79 @@ pub fn build_tenant_invite_url(base_url: &str, tenant: &str, token: &str) -> Str
     }
 }
 
+/// Days an invitation link stays valid after it is sent.
+const INVITATION_VALID_DAYS: i64 = 7;
+
+/// Why signing in, refreshing a session or changing account details was refused.
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    #[error("Garderie introuvable : vérifiez l'identifiant garderie")]
+    TenantNotFound,
+    #[error("Identifiants invalides")]
+    InvalidCredentials,
+    #[error("Service email non configuré (SMTP requis pour la 2FA)")]
+    TwoFactorUnavailable,
+    #[error("Impossible d'envoyer le code 2FA : {0}")]
+    TwoFactorSendFailed(String),
+    #[error("Code invalide ou expiré. Veuillez vous reconnecter.")]
+    CodeExpired,
+    #[error("Trop de tentatives. Veuillez vous reconnecter pour obtenir un nouveau code.")]
+    TooManyCodeAttempts,
+    #[error("Code invalide")]
+    InvalidCode,
+    #[error("Session invalide ou révoquée")]
+    InvalidRefreshToken,
+    #[error("Session expirée")]
+    RefreshTokenExpired,
+    #[error("Aucun compte lié dans cette garderie")]
+    NoLinkedAccount,
+    #[error("Impossible d'envoyer l'invitation : {0}")]
+    InvitationSendFailed(String),
+    #[error("Token invalide ou expiré")]
+    InvalidResetToken,
+    #[error("Invitation invalide ou déjà utilisée")]
+    InvalidInvitation,
+    #[error("Invitation expirée")]
+    InvitationExpired,
+    #[error("Utilisateur introuvable")]
+    UserNotFound,
+    #[error("Mot de passe actuel incorrect")]
+    WrongCurrentPassword,
+    #[error("Mot de passe incorrect")]
+    WrongPassword,
+    #[error("Cet email est déjà utilisé")]
+    EmailTaken,
+    #[error("Numéro de téléphone invalide")]
+    InvalidPhone,
+}
+
+/// Why an invitation could not be resent or extended.
+#[derive(Debug, thiserror::Error)]
+pub enum InvitationError {
+    #[error("Invitation introuvable ou déjà utilisée")]
+    NotFound,
+    #[error("Service email non configuré (SMTP requis pour les invitations)")]
+    EmailUnavailable,
+}
+
 pub struct AuthService;
 
 impl AuthService {
     /// Step 1 of login: validate credentials.
     /// If a valid trusted-device cookie is provided, skip 2FA and return tokens directly.
-    /// Otherwise send 2FA code by email and return TwoFactorRequired.
+    /// Otherwise send 2FA code by email (or SMS when requested, or when email is
+    /// unavailable and the user has a phone number) and return TwoFactorRequired.
     pub async fn login(
         pool: &PgPool,
         email_svc: Option<&EmailService>,
+        sms_svc: Option<&SmsService>,
         tenant: &str,
         email: &str,
         password: &str,
+        two_factor_channel: Option<&str>,
         device_token: Option<&str>,
-        jwt_secret: &str,
+        client: &ClientInfo,
+        jwt_keys: &JwtKeys,
         refresh_secret: &str,
         access_ttl: u64,
         refresh_ttl_days: u64,
@@ -69,48 +168,39 @@ impl AuthService {
         .fetch_one(pool)
         .await?;
         if !schema_exists {
-            anyhow::bail!("Garderie introuvable : vérifiez l'identifiant garderie");
+            return Err(AuthError::TenantNotFound.into());
         }
 
         let user = sqlx::query_as::<_, User>(&format!(
             "SELECT id, email, password_hash, first_name, last_name,
-                role::TEXT as role, avatar_url, is_active, force_password_change, preferred_locale,
+                role::TEXT as role, avatar_url, is_active, force_password_change, preferred_locale, phone,
                 created_at, updated_at
              FROM {schema}.users WHERE email = $1 AND is_active = TRUE"
         ))
         .bind(email)
         .fetch_optional(pool)
         .await?
-        .ok_or_else(|| anyhow::anyhow!("Identifiants invalides"))?;
+        .ok_or(AuthError::InvalidCredentials)?;
 
         let valid = bcrypt::verify(password, &user.password_hash)
-            .map_err(|_| anyhow::anyhow!("Identifiants invalides"))?;
+            .map_err(|_| AuthError::InvalidCredentials)?;
         if !valid {
-            anyhow::bail!("Identifiants invalides");
+            Self::record_login(pool, &schema, user.id, false, "password", client).await;
+            return Err(AuthError::InvalidCredentials.into());
         }
 
         // Check trusted device cookie — skip 2FA if valid
         if let Some(cookie_val) = device_token {
             if Self::validate_device_token(pool, &schema, user.id, cookie_val).await {
                 let role: UserRole = user.role.parse().unwrap_or(UserRole::Parent);
+                let (refresh_token_str, refresh_id) = Self::issue_refresh_token(
+                    pool, &schema, user.id, refresh_secret, refresh_ttl_days, client, None,
+                )
+                .await?;
+                let identity = IdentityService::identity_of(pool, tenant, user.id).await?;
                 let access_token = Self::generate_access_token_with_role(
-                    &user, role, tenant, jwt_secret, access_ttl,
+                    &user, role, tenant, jwt_keys, access_ttl, refresh_id, identity,
                 )?;
-                let (refresh_token_str, refresh_id) =
-                    Self::generate_refresh_token(&user.id, refresh_secret, refresh_ttl_days)?;
-
-                let hash = bcrypt::hash(&refresh_token_str, 8)?;
-                let expires_at = Utc::now() + chrono::Duration::days(refresh_ttl_days as i64);
-                sqlx::query(&format!(
-                    "INSERT INTO {schema}.refresh_tokens (id, user_id, token_hash, expires_at)
-                     VALUES ($1, $2, $3, $4)"
-                ))
-                .bind(refresh_id)
-                .bind(user.id)
-                .bind(hash)
-                .bind(expires_at)
-                .execute(pool)
-                .await?;
 
                 let garderie_name: Option<String> = sqlx::query_scalar(
                     "SELECT name FROM public.garderies WHERE slug = $1"
@@ -121,9 +211,14 @@ impl AuthService {
                 .ok()
                 .flatten();
 
-                // Issue a fresh device token (rolling 30-day window)
+                // Issue a fresh device token (rolling 30-day window) in place of the old one
                 let new_device_token =
-                    Self::generate_device_token(pool, &schema, user.id).await?;
+                    Self::generate_device_token(pool, &schema, user.id, client, Some(cookie_val)).await?;
+
+                // A copied cookie still works elsewhere, so the browser is checked too
+                if Self::record_login(pool, &schema, user.id, true, "trusted_device", client).await {
+                    Self::alert_new_device(pool, email_svc, tenant, &user, client).await;
+                }
 
                 return Ok(LoginOutcome::Authenticated {
                     response: LoginResponse {
@@ -138,8 +233,10 @@ impl AuthService {
         }
 
         // No valid trusted device — require 2FA
-        let email_svc = email_svc
-            .ok_or_else(|| anyhow::anyhow!("Service email non configuré (SMTP requis pour la 2FA)"))?;
+        let sms_target = sms_svc.zip(user.phone.as_deref());
+        if email_svc.is_none() && sms_target.is_none() {
+            return Err(AuthError::TwoFactorUnavailable.into());
+        }
 
         // Invalidate previous unused 2FA codes for this user
         sqlx::query(&format!(
@@ -166,31 +263,65 @@ impl AuthService {
         .execute(pool)
         .await?;
 
-        let (garderie_name, logo_url): (String, Option<String>) = sqlx::query_as(
-            "SELECT name, logo_url FROM public.garderies WHERE slug = $1"
-        )
-        .bind(tenant)
-        .fetch_optional(pool)
-        .await
-        .ok()
-        .flatten()
-        .unwrap_or_else(|| (tenant.to_string(), None));
+        let (garderie_name, branding) = BrandingService::for_email(pool, tenant).await;
+
+        // Send the code — not a graceful degradation here; 2FA is mandatory.
+        // SMS is used when asked for, and as a fallback when email cannot be sent.
+        let wants_sms = two_factor_channel == Some("sms");
+        let email_result = match email_svc {
+            Some(svc) if !(wants_sms && sms_target.is_some()) => Some(
+                svc.send_2fa_code(tenant, email, &code_str, &garderie_name, &branding, Locale::from_tag(&user.preferred_locale))
+                    .await,
+            ),
+            _ => None,
+        };
 
-        // Send the code — not a graceful degradation here; 2FA is mandatory
-        email_svc
-            .send_2fa_code(email, &code_str, &garderie_name, logo_url.as_deref().unwrap_or(""))
-            .await
-            .map_err(|e| anyhow::anyhow!("Impossible d'envoyer le code 2FA : {e}"))?;
+        let channel = match (email_result, sms_target) {
+            (Some(Ok(())), _) => "email",
+            (Some(Err(e)), None) => return Err(AuthError::TwoFactorSendFailed(e.to_string()).into()),
+            (email_result, Some((sms, phone))) => {
+                if let Some(Err(e)) = email_result {
+                    tracing::warn!("2FA email to user {} failed, falling back to SMS: {e}", user.id);
+                }
+                sms.send_2fa_code(phone, &code_str, &garderie_name)
+                    .await
+                    .map_err(|e| AuthError::TwoFactorSendFailed(e.to_string()))?;
+                "sms"
+            }
+            (None, None) => return Err(AuthError::TwoFactorUnavailable.into()),
+        };
+        if channel == "sms" {
+            sqlx::query(&format!(
+                "UPDATE {schema}.two_factor_codes SET channel = 'sms' WHERE user_id = $1 AND used = FALSE"
+            ))
+            .bind(user.id)
+            .execute(pool)
+            .await?;
+        }
 
         Ok(LoginOutcome::TwoFactorRequired(LoginStep1Response {
             status: "2fa_required".to_string(),
             garderie_name,
+            channel: channel.to_string(),
         }))
     }
 
+    /// Row ID of a trusted device cookie value ("{uuid}.{secret}").
+    pub fn device_token_id(cookie_value: &str) -> Option<Uuid> {
+        cookie_value.split_once('.')?.0.parse().ok()
+    }
+
     /// Generate a new trusted device token, store its hash, return cookie value.
     /// Format: "{uuid}.{random48}" — uuid is the DB row ID for fast lookup.
-    async fn generate_device_token(pool: &PgPool, schema: &str, user_id: Uuid) -> anyhow::Result<String> {
+    /// When `replaces` is the cookie being rolled over, its row is removed and the
+    /// device keeps its original creation time.
+    async fn generate_device_token(
+        pool: &PgPool,
+        schema: &str,
+        user_id: Uuid,
+        client: &ClientInfo,
+        replaces: Option<&str>,
+    ) -> anyhow::Result<String> {
         use rand::Rng;
         let id = Uuid::new_v4();
         let secret: String = rand::thread_rng()
@@ -201,14 +332,30 @@ impl AuthService {
         let cookie_value = format!("{id}.{secret}");
         let hash = bcrypt::hash(&secret, 8)?;
         let expires_at = Utc::now() + chrono::Duration::days(30);
+
+        let started_at: Option<DateTime<Utc>> = match replaces.and_then(Self::device_token_id) {
+            Some(old_id) => sqlx::query_scalar(&format!(
+                "DELETE FROM {schema}.trusted_devices WHERE id = $1 AND user_id = $2 RETURNING created_at"
+            ))
+            .bind(old_id)
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?,
+            None => None,
+        };
+
         sqlx::query(&format!(
-            "INSERT INTO {schema}.trusted_devices (id, user_id, token_hash, expires_at)
-             VALUES ($1, $2, $3, $4)"
+            "INSERT INTO {schema}.trusted_devices
+                (id, user_id, token_hash, expires_at, user_agent, ip_address, created_at, last_used_at)
+             VALUES ($1, $2, $3, $4, $5, $6, COALESCE($7, NOW()), NOW())"
         ))
         .bind(id)
         .bind(user_id)
         .bind(hash)
         .bind(expires_at)
+        .bind(&client.user_agent)
+        .bind(&client.ip_address)
+        .bind(started_at)
         .execute(pool)
         .await?;
         Ok(cookie_value)
@@ -216,13 +363,12 @@ impl AuthService {
 
     /// Validate a device token cookie value against the DB. Returns true if valid.
     async fn validate_device_token(pool: &PgPool, schema: &str, user_id: Uuid, cookie_value: &str) -> bool {
-        let parts: Vec<&str> = cookie_value.splitn(2, '.').collect();
-        if parts.len() != 2 { return false; }
-        let id: Uuid = match parts[0].parse() {
-            Ok(u) => u,
-            Err(_) => return false,
+        let Some((id, secret)) = cookie_value.split_once('.') else {
+            return false;
+        };
+        let Ok(id) = id.parse::<Uuid>() else {
+            return false;
         };
-        let secret = parts[1];
         let row: Option<(String,)> = sqlx::query_as(&format!(
             "SELECT token_hash FROM {schema}.trusted_devices
              WHERE id = $1 AND user_id = $2 AND expires_at > NOW()"
@@ -240,11 +386,8 @@ impl AuthService {
 
     /// Revoke a trusted device by cookie value (best-effort, does not fail if missing).
     async fn revoke_device_token(pool: &PgPool, schema: &str, cookie_value: &str) {
-        let parts: Vec<&str> = cookie_value.splitn(2, '.').collect();
-        if parts.len() != 2 { return; }
-        let id: Uuid = match parts[0].parse() {
-            Ok(u) => u,
-            Err(_) => return,
+        let Some(id) = Self::device_token_id(cookie_value) else {
+            return;
         };
         let _ = sqlx::query(&format!(
             "DELETE FROM {schema}.trusted_devices WHERE id = $1"
@@ -257,10 +400,12 @@ impl AuthService {
     /// Step 2 of login: verify the 2FA code, return JWT pair + new device token cookie value.
     pub async fn verify_2fa(
         pool: &PgPool,
+        email_svc: Option<&EmailService>,
         tenant: &str,
         email: &str,
         code: &str,
-        jwt_secret: &str,
+        client: &ClientInfo,
+        jwt_keys: &JwtKeys,
         refresh_secret: &str,
         access_ttl: u64,
         refresh_ttl_days: u64,
@@ -269,18 +414,18 @@ impl AuthService {
 
         let user = sqlx::query_as::<_, User>(&format!(
             "SELECT id, email, password_hash, first_name, last_name,
-                role::TEXT as role, avatar_url, is_active, force_password_change, preferred_locale,
+                role::TEXT as role, avatar_url, is_active, force_password_change, preferred_locale, phone,
                 created_at, updated_at
              FROM {schema}.users WHERE email = $1 AND is_active = TRUE"
         ))
         .bind(email)
         .fetch_optional(pool)
         .await?
-        .ok_or_else(|| anyhow::anyhow!("Identifiants invalides"))?;
+        .ok_or(AuthError::InvalidCredentials)?;
 
         // Fetch the most recent active code for this user
-        let row: Option<(Uuid, String, i16)> = sqlx::query_as(&format!(
-            "SELECT id, code, attempts FROM {schema}.two_factor_codes
+        let row: Option<(Uuid, String, i16, String)> = sqlx::query_as(&format!(
+            "SELECT id, code, attempts, channel FROM {schema}.two_factor_codes
              WHERE user_id = $1 AND used = FALSE AND expires_at > NOW()
              ORDER BY created_at DESC LIMIT 1"
         ))
@@ -288,11 +433,11 @@ impl AuthService {
         .fetch_optional(pool)
         .await?;
 
-        let (code_id, stored_code, attempts) =
-            row.ok_or_else(|| anyhow::anyhow!("Code invalide ou expiré. Veuillez vous reconnecter."))?;
+        let (code_id, stored_code, attempts, channel) =
+            row.ok_or(AuthError::CodeExpired)?;
 
         if attempts >= 3 {
-            anyhow::bail!("Trop de tentatives. Veuillez vous reconnecter pour obtenir un nouveau code.");
+            return Err(AuthError::TooManyCodeAttempts.into());
         }
 
         // Increment attempts
@@ -303,8 +448,10 @@ impl AuthService {
         .execute(pool)
         .await?;
 
+        let method = format!("2fa_{channel}");
         if code != stored_code {
-            anyhow::bail!("Code invalide");
+            Self::record_login(pool, &schema, user.id, false, &method, client).await;
+            return Err(AuthError::InvalidCode.into());
         }
 
         // Mark code as used
@@ -315,25 +462,28 @@ impl AuthService {
         .execute(pool)
         .await?;
 
-        // Issue tokens
+        // An emailed code proves the parent owns the address: link the account to the
+        // identity of that email so other garderies using it can be switched to.
         let role: UserRole = user.role.parse().unwrap_or(UserRole::Parent);
-        let access_token = Self::generate_access_token_with_role(&user, role, tenant, jwt_secret, access_ttl)?;
-        let (refresh_token_str, refresh_id) =
-            Self::generate_refresh_token(&user.id, refresh_secret, refresh_ttl_days)?;
-
-        let hash = bcrypt::hash(&refresh_token_str, 8)?;
-        let expires_at = Utc::now() + chrono::Duration::days(refresh_ttl_days as i64);
+        let identity = if role == UserRole::Parent && channel == "email" {
+            match IdentityService::link(pool, tenant, user.id, &user.email).await {
+                Ok(id) => Some(id),
+                Err(e) => {
+                    tracing::warn!("Failed to link user {} to its identity: {e}", user.id);
+                    None
+                }
+            }
+        } else {
+            IdentityService::identity_of(pool, tenant, user.id).await?
+        };
 
-        sqlx::query(&format!(
-            "INSERT INTO {schema}.refresh_tokens (id, user_id, token_hash, expires_at)
-             VALUES ($1, $2, $3, $4)"
-        ))
-        .bind(refresh_id)
-        .bind(user.id)
-        .bind(hash)
-        .bind(expires_at)
-        .execute(pool)
-        .await?;
+        // Issue tokens
+        let (refresh_token_str, refresh_id) =
+            Self::issue_refresh_token(pool, &schema, user.id, refresh_secret, refresh_ttl_days, client, None)
+                .await?;
+        let access_token = Self::generate_access_token_with_role(
+            &user, role, tenant, jwt_keys, access_ttl, refresh_id, identity,
+        )?;
 
         let garderie_name: Option<String> = sqlx::query_scalar(
             "SELECT name FROM public.garderies WHERE slug = $1"
@@ -345,10 +495,14 @@ impl AuthService {
         .flatten();
 
         // Generate and store a trusted device token
-        let device_token = Self::generate_device_token(pool, &schema, user.id)
+        let device_token = Self::generate_device_token(pool, &schema, user.id, client, None)
             .await
             .unwrap_or_default();
 
+        if Self::record_login(pool, &schema, user.id, true, &method, client).await {
+            Self::alert_new_device(pool, email_svc, tenant, &user, client).await;
+        }
+
         Ok((
             LoginResponse {
                 access_token,
@@ -360,22 +514,62 @@ impl AuthService {
         ))
     }
 
+    /// Sign in a staff member already authenticated by the garderie's identity
+    /// provider: the same token pair as a password login, without the 2FA step.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn login_with_sso(
+        pool: &PgPool,
+        tenant: &str,
+        user: User,
+        client: &ClientInfo,
+        jwt_keys: &JwtKeys,
+        refresh_secret: &str,
+        access_ttl: u64,
+        refresh_ttl_days: u64,
+    ) -> anyhow::Result<LoginResponse> {
+        let schema = schema_name(tenant);
+        let role: UserRole = user.role.parse()?;
+        let (refresh_token, refresh_id) =
+            Self::issue_refresh_token(pool, &schema, user.id, refresh_secret, refresh_ttl_days, client, None).await?;
+        let identity = IdentityService::identity_of(pool, tenant, user.id).await?;
+        let access_token =
+            Self::generate_access_token_with_role(&user, role, tenant, jwt_keys, access_ttl, refresh_id, identity)?;
+        Self::record_login(pool, &schema, user.id, true, "oidc", client).await;
+
+        let garderie_name: Option<String> = sqlx::query_scalar("SELECT name FROM public.garderies WHERE slug = $1")
+            .bind(tenant)
+            .fetch_optional(pool)
+            .await
+            .ok()
+            .flatten();
+        Ok(LoginResponse {
+            access_token,
+            refresh_token,
+            user: user.into(),
+            garderie_name: garderie_name.unwrap_or_else(|| tenant.to_string()),
+        })
+    }
+
     pub fn generate_access_token(
         user: &User,
         tenant: &str,
-        secret: &str,
+        keys: &JwtKeys,
         ttl_seconds: u64,
+        session_id: Uuid,
+        identity: Option<Uuid>,
     ) -> anyhow::Result<String> {
         let role: UserRole = user.role.parse().unwrap_or(UserRole::Parent);
-        Self::generate_access_token_with_role(user, role, tenant, secret, ttl_seconds)
+        Self::generate_access_token_with_role(user, role, tenant, keys, ttl_seconds, session_id, identity)
     }
 
     pub fn generate_access_token_with_role(
         user: &User,
         role: UserRole,
         tenant: &str,
-        secret: &str,
+        keys: &JwtKeys,
         ttl_seconds: u64,
+        session_id: Uuid,
+        identity: Option<Uuid>,
     ) -> anyhow::Result<String> {
         let now = Utc::now().timestamp() as usize;
         let claims = Claims {
@@ -384,13 +578,11 @@ impl AuthService {
             role,
             iat: now,
             exp: now + ttl_seconds as usize,
+            sid: Some(session_id),
+            identity,
+            locale: Some(user.preferred_locale.clone()),
         };
-        let token = encode(
-            &Header::new(Algorithm::HS256),
-            &claims,
-            &EncodingKey::from_secret(secret.as_bytes()),
-        )?;
-        Ok(token)
+        keys.encode(&claims)
     }
 
     fn generate_refresh_token(
@@ -414,12 +606,54 @@ impl AuthService {
         Ok((token, jti))
     }
 
-    /// Rotate refresh token: revoke old, issue new pair.
+    /// Store a refresh token for a new or rotated session and return it with its ID.
+    /// A rotated token inherits the login time and family of `rotated_from`; a new
+    /// session starts a family named after its first token.
+    async fn issue_refresh_token(
+        pool: &PgPool,
+        schema: &str,
+        user_id: Uuid,
+        refresh_secret: &str,
+        ttl_days: u64,
+        client: &ClientInfo,
+        rotated_from: Option<&RefreshToken>,
+    ) -> anyhow::Result<(String, Uuid)> {
+        let (token, jti) = Self::generate_refresh_token(&user_id, refresh_secret, ttl_days)?;
+        let hash = bcrypt::hash(&token, 8)?;
+        let expires_at = Utc::now() + chrono::Duration::days(ttl_days as i64);
+        let family_id = rotated_from.map_or(jti, |parent| parent.family_id.unwrap_or(parent.id));
+
+        sqlx::query(&format!(
+            "INSERT INTO {schema}.refresh_tokens
+                (id, user_id, token_hash, expires_at, user_agent, ip_address, created_at, last_used_at,
+                 family_id, parent_id)
+             VALUES ($1, $2, $3, $4, $5, $6, COALESCE($7, NOW()), NOW(), $8, $9)"
+        ))
+        .bind(jti)
+        .bind(user_id)
+        .bind(hash)
+        .bind(expires_at)
+        .bind(&client.user_agent)
+        .bind(&client.ip_address)
+        .bind(rotated_from.map(|parent| parent.created_at))
+        .bind(family_id)
+        .bind(rotated_from.map(|parent| parent.id))
+        .execute(pool)
+        .await?;
+        Ok((token, jti))
+    }
+
+    /// Rotate refresh token: revoke old, issue new pair. Replaying a token that was
+    /// already rotated revokes its whole family, alerts the user and fails with
+    /// [`RefreshTokenReused`].
+    #[allow(clippy::too_many_arguments)]
     pub async fn refresh(
         pool: &PgPool,
+        email_svc: Option<&EmailService>,
         tenant: &str,
         refresh_token_str: &str,
-        jwt_secret: &str,
+        client: &ClientInfo,
+        jwt_keys: &JwtKeys,
         refresh_secret: &str,
         access_ttl: u64,
         refresh_ttl_days: u64,
@@ -438,34 +672,45 @@ impl AuthService {
 
         let schema = schema_name(tenant);
 
-        // Fetch the stored token
+        // Fetch the stored token, revoked or not: a revoked one may be a replay
         let stored: RefreshToken = sqlx::query_as(&format!(
-            "SELECT * FROM {schema}.refresh_tokens WHERE id = $1 AND revoked = FALSE"
+            "SELECT * FROM {schema}.refresh_tokens WHERE id = $1 AND user_id = $2"
         ))
         .bind(jti)
+        .bind(user_id)
         .fetch_optional(pool)
         .await?
-        .ok_or_else(|| anyhow::anyhow!("Refresh token not found or revoked"))?;
+        .ok_or(AuthError::InvalidRefreshToken)?;
 
-        if stored.expires_at < Utc::now() {
-            anyhow::bail!("Refresh token expired");
-        }
         if !bcrypt::verify(refresh_token_str, &stored.token_hash)? {
-            anyhow::bail!("Refresh token invalid");
+            return Err(AuthError::InvalidRefreshToken.into());
+        }
+        if is_replay(&stored, Utc::now()) {
+            return Err(Self::revoke_reused_family(pool, email_svc, tenant, &stored, client).await?.into());
+        }
+        if stored.revoked {
+            return Err(AuthError::InvalidRefreshToken.into());
+        }
+        if stored.expires_at < Utc::now() {
+            return Err(AuthError::RefreshTokenExpired.into());
         }
 
-        // Revoke old token
-        sqlx::query(&format!(
-            "UPDATE {schema}.refresh_tokens SET revoked = TRUE WHERE id = $1"
+        // Revoke old token; a concurrent refresh of the same token loses here
+        let rotated = sqlx::query(&format!(
+            "UPDATE {schema}.refresh_tokens SET revoked = TRUE, rotated_at = NOW()
+             WHERE id = $1 AND revoked = FALSE"
         ))
         .bind(jti)
         .execute(pool)
         .await?;
+        if rotated.rows_affected() == 0 {
+            return Err(AuthError::InvalidRefreshToken.into());
+        }
 
         // Fetch user
         let user = sqlx::query_as::<_, User>(&format!(
             "SELECT id, email, password_hash, first_name, last_name,
-                role::TEXT as role, avatar_url, is_active, force_password_change, preferred_locale,
+                role::TEXT as role, avatar_url, is_active, force_password_change, preferred_locale, phone,
                 created_at, updated_at
              FROM {schema}.users WHERE id = $1 AND is_active = TRUE"
         ))
@@ -473,23 +718,18 @@ impl AuthService {
         .fetch_one(pool)
         .await?;
 
-        let access_token = Self::generate_access_token(&user, tenant, jwt_secret, access_ttl)?;
-        let (new_refresh, new_jti) =
-            Self::generate_refresh_token(&user.id, refresh_secret, refresh_ttl_days)?;
-
-        let hash = bcrypt::hash(&new_refresh, 8)?;
-        let expires_at = Utc::now() + chrono::Duration::days(refresh_ttl_days as i64);
-
-        sqlx::query(&format!(
-            "INSERT INTO {schema}.refresh_tokens (id, user_id, token_hash, expires_at)
-             VALUES ($1, $2, $3, $4)"
-        ))
-        .bind(new_jti)
-        .bind(user.id)
-        .bind(hash)
-        .bind(expires_at)
-        .execute(pool)
+        let (new_refresh, new_jti) = Self::issue_refresh_token(
+            pool,
+            &schema,
+            user.id,
+            refresh_secret,
+            refresh_ttl_days,
+            client,
+            Some(&stored),
+        )
         .await?;
+        let identity = IdentityService::identity_of(pool, tenant, user.id).await?;
+        let access_token = Self::generate_access_token(&user, tenant, jwt_keys, access_ttl, new_jti, identity)?;
 
         let garderie_name: Option<String> = sqlx::query_scalar(
             "SELECT name FROM public.garderies WHERE slug = $1"
@@ -508,6 +748,54 @@ impl AuthService {
         })
     }
 
+    /// Open a session in another garderie the caller's identity is linked to, without
+    /// asking for its password again.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn switch_tenant(
+        pool: &PgPool,
+        identity_id: Uuid,
+        target: &str,
+        client: &ClientInfo,
+        jwt_keys: &JwtKeys,
+        refresh_secret: &str,
+        access_ttl: u64,
+        refresh_ttl_days: u64,
+    ) -> anyhow::Result<LoginResponse> {
+        let account = IdentityService::accounts(pool, identity_id, target)
+            .await?
+            .into_iter()
+            .find(|a| a.current)
+            .ok_or(AuthError::NoLinkedAccount)?;
+
+        // The session is opened in the target garderie
+        TenantDb::scope(target.to_string(), async {
+            let schema = schema_name(target);
+            let user = sqlx::query_as::<_, User>(&format!(
+                "SELECT id, email, password_hash, first_name, last_name,
+                    role::TEXT as role, avatar_url, is_active, force_password_change, preferred_locale, phone,
+                    created_at, updated_at
+                 FROM {schema}.users WHERE id = $1 AND is_active = TRUE"
+            ))
+            .bind(account.user_id)
+            .fetch_one(pool)
+            .await?;
+
+            let (refresh_token, refresh_id) =
+                Self::issue_refresh_token(pool, &schema, user.id, refresh_secret, refresh_ttl_days, client, None)
+                    .await?;
+            let access_token =
+                Self::generate_access_token(&user, target, jwt_keys, access_ttl, refresh_id, Some(identity_id))?;
+
+            Ok(LoginResponse {
+                access_token,
+                refresh_token,
+                user: user.into(),
+                garderie_name: account.name,
+            })
+        })
+        .await
+    }
+
     /// Revoke a refresh token and trusted device token (logout).
     pub async fn logout(
         pool: &PgPool,
@@ -541,7 +829,259 @@ impl AuthService {
         Ok(())
     }
 
+    /// Record a login attempt of a known user. Returns true for a successful login
+    /// from a browser (user agent) the user never signed in from before, except for
+    /// the first login ever recorded. Failures are logged, never returned: the
+    /// history must not block a login.
+    async fn record_login(
+        pool: &PgPool,
+        schema: &str,
+        user_id: Uuid,
+        success: bool,
+        method: &str,
+        client: &ClientInfo,
+    ) -> bool {
+        let recorded: anyhow::Result<bool> = async {
+            let new_device: bool = if success {
+                sqlx::query_scalar(&format!(
+                    "SELECT EXISTS (SELECT 1 FROM {schema}.login_events WHERE user_id = $1 AND success)
+                        AND NOT EXISTS (
+                            SELECT 1 FROM {schema}.login_events
+                            WHERE user_id = $1 AND success AND user_agent IS NOT DISTINCT FROM $2
+                        )"
+                ))
+                .bind(user_id)
+                .bind(&client.user_agent)
+                .fetch_one(pool)
+                .await?
+            } else {
+                false
+            };
+            sqlx::query(&format!(
+                "INSERT INTO {schema}.login_events (user_id, success, method, ip_address, user_agent)
+                 VALUES ($1, $2, $3, $4, $5)"
+            ))
+            .bind(user_id)
+            .bind(success)
+            .bind(method)
+            .bind(&client.ip_address)
+            .bind(&client.user_agent)
+            .execute(pool)
+            .await?;
+            Ok(new_device)
+        }
+        .await;
+        recorded.unwrap_or_else(|e| {
+            tracing::warn!("Failed to record login of user {user_id}: {e}");
+            false
+        })
+    }
+
+    /// A refresh token was presented again after being rotated: someone holds a
+    /// copy. Revokes every live token of the family `reused` belongs to, signing
+    /// out both the owner and the copy, and warns the owner by email.
+    async fn revoke_reused_family(
+        pool: &PgPool,
+        email_svc: Option<&EmailService>,
+        tenant: &str,
+        reused: &RefreshToken,
+        client: &ClientInfo,
+    ) -> anyhow::Result<RefreshTokenReused> {
+        let schema = schema_name(tenant);
+        let family_id = reused.family_id.unwrap_or(reused.id);
+        let revoked = sqlx::query(&format!(
+            "UPDATE {schema}.refresh_tokens SET revoked = TRUE
+             WHERE (family_id = $1 OR id = $1) AND revoked = FALSE"
+        ))
+        .bind(family_id)
+        .execute(pool)
+        .await?
+        .rows_affected();
+        tracing::warn!(
+            "Refresh token {} of user {} replayed in '{tenant}': revoked {revoked} token(s) of family {family_id}",
+            reused.id,
+            reused.user_id
+        );
+
+        if let Some(svc) = email_svc {
+            let user = sqlx::query_as::<_, User>(&format!(
+                "SELECT id, email, password_hash, first_name, last_name,
+                    role::TEXT as role, avatar_url, is_active, force_password_change, preferred_locale, phone,
+                    created_at, updated_at
+                 FROM {schema}.users WHERE id = $1"
+            ))
+            .bind(reused.user_id)
+            .fetch_optional(pool)
+            .await?;
+            if let Some(user) = user {
+                let (garderie_name, branding) = BrandingService::for_email(pool, tenant).await;
+                if let Err(e) = svc
+                    .send_token_reuse_alert(
+                        tenant,
+                        &user.email,
+                        &user.first_name,
+                        &garderie_name,
+                        &branding,
+                        Locale::from_tag(&user.preferred_locale),
+                        Utc::now(),
+                        &client.ip_address,
+                        client.user_agent.as_deref(),
+                    )
+                    .await
+                {
+                    tracing::warn!("Token reuse alert to user {} failed: {e}", user.id);
+                }
+            }
+        }
+
+        Ok(RefreshTokenReused { user_id: reused.user_id, family_id, revoked })
+    }
+
+    /// Tell the user by email that their account was just used from a new device.
+    async fn alert_new_device(
+        pool: &PgPool,
+        email_svc: Option<&EmailService>,
+        tenant: &str,
+        user: &User,
+        client: &ClientInfo,
+    ) {
+        let Some(svc) = email_svc else { return };
+        let (garderie_name, branding) = BrandingService::for_email(pool, tenant).await;
+        if let Err(e) = svc
+            .send_new_device_alert(
+                tenant,
+                &user.email,
+                &user.first_name,
+                &garderie_name,
+                &branding,
+                Locale::from_tag(&user.preferred_locale),
+                Utc::now(),
+                &client.ip_address,
+                client.user_agent.as_deref(),
+            )
+            .await
+        {
+            tracing::warn!("New device alert to user {} failed: {e}", user.id);
+        }
+    }
+
+    /// The user's latest login attempts, newest first.
+    pub async fn login_history(
+        pool: &PgPool,
+        tenant: &str,
+        user_id: Uuid,
+        limit: Option<i64>,
+    ) -> anyhow::Result<Vec<LoginEvent>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, LoginEvent>(&format!(
+            "SELECT id, success, method, ip_address, user_agent, created_at
+             FROM {schema}.login_events
+             WHERE user_id = $1
+             ORDER BY created_at DESC
+             LIMIT $2"
+        ))
+        .bind(user_id)
+        .bind(limit.unwrap_or(LOGIN_HISTORY_DEFAULT_LIMIT).clamp(1, LOGIN_HISTORY_MAX_LIMIT))
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Active refresh tokens and trusted devices of a user, most recently used first.
+    pub async fn list_sessions(
+        pool: &PgPool,
+        tenant: &str,
+        user_id: Uuid,
+        current_session: Option<Uuid>,
+        current_device: Option<Uuid>,
+    ) -> anyhow::Result<Vec<ActiveSession>> {
+        let schema = schema_name(tenant);
+        let mut sessions = sqlx::query_as::<_, ActiveSession>(&format!(
+            "SELECT id, 'session' AS kind, user_agent, ip_address, created_at, last_used_at, expires_at
+             FROM {schema}.refresh_tokens
+             WHERE user_id = $1 AND revoked = FALSE AND expires_at > NOW()
+             UNION ALL
+             SELECT id, 'device' AS kind, user_agent, ip_address, created_at, last_used_at, expires_at
+             FROM {schema}.trusted_devices
+             WHERE user_id = $1 AND expires_at > NOW()
+             ORDER BY last_used_at DESC NULLS LAST, created_at DESC"
+        ))
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+        for s in &mut sessions {
+            let current = if s.kind == "device" { current_device } else { current_session };
+            s.current = current == Some(s.id);
+        }
+        Ok(sessions)
+    }
+
+    /// Revoke one of the user's sessions or trusted devices. Returns false if none matched.
+    pub async fn revoke_session(
+        pool: &PgPool,
+        tenant: &str,
+        user_id: Uuid,
+        id: Uuid,
+    ) -> anyhow::Result<bool> {
+        let schema = schema_name(tenant);
+        let revoked = sqlx::query(&format!(
+            "UPDATE {schema}.refresh_tokens SET revoked = TRUE
+             WHERE id = $1 AND user_id = $2 AND revoked = FALSE"
+        ))
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await?
+        .rows_affected();
+        if revoked > 0 {
+            return Ok(true);
+        }
+
+        let deleted = sqlx::query(&format!(
+            "DELETE FROM {schema}.trusted_devices WHERE id = $1 AND user_id = $2"
+        ))
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await?
+        .rows_affected();
+        Ok(deleted > 0)
+    }
+
+    /// Revoke every session and trusted device of the user except the current ones.
+    /// Access tokens already issued stay valid until they expire.
+    pub async fn revoke_other_sessions(
+        pool: &PgPool,
+        tenant: &str,
+        user_id: Uuid,
+        keep_session: Option<Uuid>,
+        keep_device: Option<Uuid>,
+    ) -> anyhow::Result<u64> {
+        let schema = schema_name(tenant);
+        let sessions = sqlx::query(&format!(
+            "UPDATE {schema}.refresh_tokens SET revoked = TRUE
+             WHERE user_id = $1 AND revoked = FALSE AND id IS DISTINCT FROM $2"
+        ))
+        .bind(user_id)
+        .bind(keep_session)
+        .execute(pool)
+        .await?
+        .rows_affected();
+
+        let devices = sqlx::query(&format!(
+            "DELETE FROM {schema}.trusted_devices WHERE user_id = $1 AND id IS DISTINCT FROM $2"
+        ))
+        .bind(user_id)
+        .bind(keep_device)
+        .execute(pool)
+        .await?
+        .rows_affected();
+
+        Ok(sessions + devices)
+    }
+
     /// Create an invitation token and send the invitation email.
+    #[allow(clippy::too_many_arguments)]
     pub async fn create_invitation(
         pool: &PgPool,
         email_svc: Option<&EmailService>,
@@ -549,10 +1089,14 @@ impl AuthService {
         email: &str,
         role: UserRole,
         invited_by: Option<Uuid>,
+        locale: Option<&str>,
         base_url: &str,
     ) -> anyhow::Result<()> {
-        let email_svc = email_svc
-            .ok_or_else(|| anyhow::anyhow!("Service email non configuré (SMTP requis pour les invitations)"))?;
+        let email_svc = email_svc.ok_or(InvitationError::EmailUnavailable)?;
+        let locale = Locale::from_tag(locale.unwrap_or("fr"));
+        if matches!(role, UserRole::AdminGarderie | UserRole::Educateur) {
+            EntitlementService::ensure_can_add(pool, tenant, Resource::Staff, 1).await?;
+        }
 
         use rand::Rng;
         let schema = schema_name(tenant);
@@ -562,36 +1106,30 @@ impl AuthService {
             .map(char::from)
             .collect();
 
-        let expires_at = Utc::now() + chrono::Duration::days(7);
+        let expires_at = Utc::now() + chrono::Duration::days(INVITATION_VALID_DAYS);
 
         sqlx::query(&format!(
-            "INSERT INTO {schema}.invitation_tokens (email, token, role, invited_by, expires_at)
-             VALUES ($1, $2, $3::\"{schema}\".user_role, $4, $5)"
+            "INSERT INTO {schema}.invitation_tokens (email, token, role, invited_by, expires_at, preferred_locale)
+             VALUES ($1, $2, $3::\"{schema}\".user_role, $4, $5, $6)"
         ))
         .bind(email)
         .bind(&token)
         .bind(role.to_string())
         .bind(invited_by)
         .bind(expires_at)
+        .bind(locale.tag())
         .execute(pool)
         .await?;
 
-        let (garderie_name, logo_url): (String, Option<String>) = sqlx::query_as(
-            "SELECT name, logo_url FROM public.garderies WHERE slug = $1"
-        )
-        .bind(tenant)
-        .fetch_optional(pool)
-        .await
-        .ok()
-        .flatten()
-        .unwrap_or_else(|| (tenant.to_string(), None));
+        let (garderie_name, branding) = BrandingService::for_email(pool, tenant).await;
 
         let invite_url = build_tenant_invite_url(base_url, tenant, &token);
+        let template = EmailTemplateService::override_for(pool, tenant, TEMPLATE_INVITATION, locale).await;
 
         email_svc
-            .send_invitation(email, &invite_url, &garderie_name, &role.to_string(), logo_url.as_deref().unwrap_or(""))
+            .send_invitation(tenant, email, &invite_url, &garderie_name, &role.to_string(), &branding, locale, template.as_ref())
             .await
-            .map_err(|e| anyhow::anyhow!("Impossible d'envoyer l'invitation : {e}"))?;
+            .map_err(|e| AuthError::InvitationSendFailed(e.to_string()))?;
 
         Ok(())
     }
@@ -606,15 +1144,15 @@ impl AuthService {
     ) -> anyhow::Result<()> {
         let schema = schema_name(tenant);
 
-        let user_opt: Option<(Uuid, String, String)> = sqlx::query_as(&format!(
-            "SELECT id, first_name, last_name FROM {schema}.users
+        let user_opt: Option<(Uuid, String, String, String)> = sqlx::query_as(&format!(
+            "SELECT id, first_name, last_name, preferred_locale FROM {schema}.users
              WHERE email = $1 AND is_active = TRUE"
         ))
         .bind(email)
         .fetch_optional(pool)
         .await?;
 
-        if let Some((user_id, first_name, last_name)) = user_opt {
+        if let Some((user_id, first_name, last_name, locale)) = user_opt {
             use rand::Rng;
             let token: String = rand::thread_rng()
                 .sample_iter(&rand::distributions::Alphanumeric)
@@ -635,21 +1173,13 @@ impl AuthService {
             .await?;
 
             if let Some(svc) = email_svc {
-                let (garderie_name, logo_url): (String, Option<String>) = sqlx::query_as(
-                    "SELECT name, logo_url FROM public.garderies WHERE slug = $1"
-                )
-                .bind(tenant)
-                .fetch_optional(pool)
-                .await
-                .ok()
-                .flatten()
-                .unwrap_or_else(|| (tenant.to_string(), None));
+                let (garderie_name, branding) = BrandingService::for_email(pool, tenant).await;
 
                 let reset_url = build_tenant_reset_url(base_url, tenant, &token);
                 let display_name = format!("{first_name} {last_name}");
                 // Ignore send errors — graceful degradation
                 let _ = svc
-                    .send_password_reset(email, &display_name, &reset_url, &garderie_name, logo_url.as_deref().unwrap_or(""))
+                    .send_password_reset(tenant, email, &display_name, &reset_url, &garderie_name, &branding, Locale::from_tag(&locale))
                     .await;
             }
         }
@@ -663,19 +1193,24 @@ impl AuthService {
         tenant: &str,
         token_str: &str,
         new_password: &str,
+        policy: &PasswordPolicy,
     ) -> anyhow::Result<()> {
         let schema = schema_name(tenant);
 
-        let row: Option<(Uuid, Uuid)> = sqlx::query_as(&format!(
-            "SELECT id, user_id FROM {schema}.password_reset_tokens
-             WHERE token = $1 AND used = FALSE AND expires_at > NOW()"
+        let row: Option<(Uuid, Uuid, String)> = sqlx::query_as(&format!(
+            "SELECT t.id, t.user_id, u.preferred_locale
+             FROM {schema}.password_reset_tokens t
+             JOIN {schema}.users u ON u.id = t.user_id
+             WHERE t.token = $1 AND t.used = FALSE AND t.expires_at > NOW()"
         ))
         .bind(token_str)
         .fetch_optional(pool)
         .await?;
 
-        let (token_id, user_id) =
-            row.ok_or_else(|| anyhow::anyhow!("Token invalide ou expiré"))?;
+        let (token_id, user_id, locale) =
+            row.ok_or(AuthError::InvalidResetToken)?;
+
+        policy.check(new_password, &locale).await?;
 
         let password_hash = bcrypt::hash(new_password, 12)?;
 
@@ -715,6 +1250,7 @@ impl AuthService {
         preferred_locale: &str,
         consent: Option<&crate::models::user::ParentConsentPayload>,
         ip_address: &str,
+        policy: &PasswordPolicy,
     ) -> anyhow::Result<UserProfile> {
         let schema = schema_name(tenant);
 
@@ -725,12 +1261,14 @@ impl AuthService {
         .bind(token_str)
         .fetch_optional(pool)
         .await?
-        .ok_or_else(|| anyhow::anyhow!("Invalid or already-used invitation token"))?;
+        .ok_or(AuthError::InvalidInvitation)?;
 
         if invite.expires_at < Utc::now() {
-            anyhow::bail!("Invitation token expired");
+            return Err(AuthError::InvitationExpired.into());
         }
 
+        policy.check(password, preferred_locale).await?;
+
         let password_hash = bcrypt::hash(password, 12)?;
 
         let user: User = sqlx::query_as(&format!(
@@ -738,7 +1276,7 @@ impl AuthService {
              VALUES ($1, $2, $3, $4, $5::\"{schema}\".user_role, $6)
              RETURNING id, email, password_hash, first_name, last_name,
                        role::TEXT as role, avatar_url, is_active, force_password_change,
-                       preferred_locale, created_at, updated_at"
+                       preferred_locale, phone, created_at, updated_at"
         ))
         .bind(&invite.email)
         .bind(password_hash)
@@ -759,25 +1297,18 @@ impl AuthService {
 
         // Persist consent record (Loi 25)
         if let Some(c) = consent {
-            if let Err(e) = sqlx::query(&format!(
-                "INSERT INTO {schema}.consent_records
-                    (user_id, privacy_accepted, photos_accepted, accepted_at, policy_version, language, ip_address)
-                 VALUES ($1, $2, $3, $4, $5, $6, $7)"
-            ))
-            .bind(user.id)
-            .bind(c.privacy_accepted)
-            .bind(c.photos_accepted)
-            .bind(c.accepted_at)
-            .bind(&c.policy_version)
-            .bind(c.language.as_deref().unwrap_or("fr"))
-            .bind(ip_address)
-            .execute(pool)
-            .await
-            {
+            if let Err(e) = ConsentService::record(pool, tenant, user.id, c, ip_address).await {
                 tracing::warn!("Failed to persist consent record for user {}: {e}", user.id);
             }
         }
 
+        // The invitation link proved the address
+        if invite.role == "parent" {
+            if let Err(e) = IdentityService::link(pool, tenant, user.id, &invite.email).await {
+                tracing::warn!("Failed to link user {} to its identity: {e}", user.id);
+            }
+        }
+
         // Promote pending parents to registered parents
         if let Err(e) = ChildService::promote_pending_parents(pool, tenant, user.id, &invite.email).await {
             tracing::warn!("Failed to promote pending parents for user {}: {e}", user.id);
@@ -805,15 +1336,15 @@ impl AuthService {
         let method = method.unwrap_or("email");
 
         // Fetch target user details
-        let user_opt: Option<(String, String, String)> = sqlx::query_as(&format!(
-            "SELECT email, first_name, last_name FROM {schema}.users WHERE id = $1 AND is_active = TRUE"
+        let user_opt: Option<(String, String, String, String)> = sqlx::query_as(&format!(
+            "SELECT email, first_name, last_name, preferred_locale FROM {schema}.users WHERE id = $1 AND is_active = TRUE"
         ))
         .bind(target_user_id)
         .fetch_optional(pool)
         .await?;
 
-        let (email, first_name, last_name) = user_opt
-            .ok_or_else(|| anyhow::anyhow!("Utilisateur non trouvé"))?;
+        let (email, first_name, last_name, locale) = user_opt
+            .ok_or(AuthError::UserNotFound)?;
 
         if method == "temp_password" {
             // Generate and set a temporary password
@@ -868,21 +1399,13 @@ impl AuthService {
             .await?;
 
             if let Some(svc) = email_svc {
-                let (garderie_name, logo_url): (String, Option<String>) = sqlx::query_as(
-                    "SELECT name, logo_url FROM public.garderies WHERE slug = $1"
-                )
-                .bind(tenant)
-                .fetch_optional(pool)
-                .await
-                .ok()
-                .flatten()
-                .unwrap_or_else(|| (tenant.to_string(), None));
+                let (garderie_name, branding) = BrandingService::for_email(pool, tenant).await;
 
                 let reset_url = build_tenant_reset_url(base_url, tenant, &token);
                 let display_name = format!("{first_name} {last_name}");
                 // Ignore send errors — graceful degradation
                 let _ = svc
-                    .send_password_reset(&email, &display_name, &reset_url, &garderie_name, logo_url.as_deref().unwrap_or(""))
+                    .send_password_reset(tenant, &email, &display_name, &reset_url, &garderie_name, &branding, Locale::from_tag(&locale))
                     .await;
             }
 
@@ -900,25 +1423,28 @@ impl AuthService {
         user_id: Uuid,
         current_password: &str,
         new_password: &str,
+        policy: &PasswordPolicy,
     ) -> anyhow::Result<()> {
         let schema = schema_name(tenant);
 
         // Fetch current password hash
-        let password_hash: String = sqlx::query_scalar(&format!(
-            "SELECT password_hash FROM {schema}.users WHERE id = $1 AND is_active = TRUE"
+        let (password_hash, locale): (String, String) = sqlx::query_as(&format!(
+            "SELECT password_hash, preferred_locale FROM {schema}.users WHERE id = $1 AND is_active = TRUE"
         ))
         .bind(user_id)
         .fetch_optional(pool)
         .await?
-        .ok_or_else(|| anyhow::anyhow!("Utilisateur non trouvé"))?;
+        .ok_or(AuthError::UserNotFound)?;
 
         // Verify current password
         let valid = bcrypt::verify(current_password, &password_hash)
-            .map_err(|_| anyhow::anyhow!("Mot de passe actuel incorrect"))?;
+            .map_err(|_| AuthError::WrongCurrentPassword)?;
         if !valid {
-            anyhow::bail!("Mot de passe actuel incorrect");
+            return Err(AuthError::WrongCurrentPassword.into());
         }
 
+        policy.check(new_password, &locale).await?;
+
         // Hash and update new password
         let new_hash = bcrypt::hash(new_password, 12)?;
         sqlx::query(&format!(
@@ -957,7 +1483,7 @@ impl AuthService {
         .bind(user_id)
         .fetch_optional(pool)
         .await?
-        .ok_or_else(|| anyhow::anyhow!("Utilisateur non trouvé"))?;
+        .ok_or(AuthError::UserNotFound)?;
 
         // Diagnostic log (no password value)
         tracing::info!("update_email: verifying password for user_id={} in tenant={}", user_id, tenant);
@@ -967,12 +1493,12 @@ impl AuthService {
             Ok(v) => v,
             Err(e) => {
                 tracing::error!("update_email: bcrypt verify error for user_id={}: {}", user_id, e);
-                anyhow::bail!("Mot de passe incorrect");
+                return Err(AuthError::WrongPassword.into());
             }
         };
         if !valid {
             tracing::info!("update_email: password mismatch for user_id={}", user_id);
-            anyhow::bail!("Mot de passe incorrect");
+            return Err(AuthError::WrongPassword.into());
         }
         tracing::info!("update_email: password verified for user_id={}", user_id);
 
@@ -986,7 +1512,7 @@ impl AuthService {
         .await?;
 
         if exists {
-            anyhow::bail!("Cet email est déjà utilisé");
+            return Err(AuthError::EmailTaken.into());
         }
 
         // Update email
@@ -998,9 +1524,39 @@ impl AuthService {
         .execute(pool)
         .await?;
 
+        // The new address is not proven yet; the next emailed 2FA code links it again
+        IdentityService::unlink(pool, tenant, user_id).await?;
+
         Ok(())
     }
 
+    /// Set or clear the user's phone number (used for SMS). Returns the stored E.164 value.
+    pub async fn update_phone(
+        pool: &PgPool,
+        tenant: &str,
+        user_id: Uuid,
+        phone: Option<&str>,
+    ) -> anyhow::Result<Option<String>> {
+        let schema = schema_name(tenant);
+
+        let phone = match phone.map(str::trim).filter(|p| !p.is_empty()) {
+            Some(raw) => Some(
+                normalize_phone(raw).ok_or(AuthError::InvalidPhone)?,
+            ),
+            None => None,
+        };
+
+        sqlx::query(&format!(
+            "UPDATE {schema}.users SET phone = $1, updated_at = NOW() WHERE id = $2"
+        ))
+        .bind(&phone)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+        Ok(phone)
+    }
+
     /// List all pending (unused) invitations for a tenant.
     pub async fn list_pending_invitations(
         pool: &PgPool,
@@ -1056,8 +1612,7 @@ impl AuthService {
         Ok(invitations)
     }
 
-    /// Delete a pending invitation by ID (only if not yet used).
-    /// Resend an invitation email with a new token.
+    /// Resend an invitation email with a new token, expired invitations included.
     pub async fn resend_invitation(
         pool: &PgPool,
         email_svc: Option<&EmailService>,
@@ -1065,30 +1620,23 @@ impl AuthService {
         invitation_id: Uuid,
         base_url: &str,
     ) -> anyhow::Result<()> {
-        let email_svc = email_svc
-            .ok_or_else(|| anyhow::anyhow!("Service email non configuré (SMTP requis pour les invitations)"))?;
+        let email_svc = email_svc.ok_or(InvitationError::EmailUnavailable)?;
 
         let schema = schema_name(tenant);
 
-        // Get the pending invitation
-        let invitation = sqlx::query(
-            &format!(
-                r#"
-                SELECT id, email, role::TEXT as role FROM {} . invitation_tokens
-                WHERE id = $1 AND used = FALSE AND expires_at > NOW()
-                "#,
-                &schema
-            )
-        )
+        // Get the unused invitation
+        let inv = sqlx::query(&format!(
+            r#"SELECT id, email, role::TEXT as role, preferred_locale FROM {schema}.invitation_tokens
+               WHERE id = $1 AND used = FALSE"#
+        ))
         .bind(invitation_id)
         .fetch_optional(pool)
-        .await?;
-
-        let inv = invitation
-            .ok_or_else(|| anyhow::anyhow!("Invitation not found or already expired/used"))?;
+        .await?
+        .ok_or(InvitationError::NotFound)?;
 
         let email: String = inv.get("email");
         let role: String = inv.get("role");
+        let locale: String = inv.get("preferred_locale");
 
         // Generate new token
         use rand::Rng;
@@ -1098,9 +1646,9 @@ impl AuthService {
             .map(char::from)
             .collect();
 
-        let expires_at = Utc::now() + chrono::Duration::days(7);
+        let expires_at = Utc::now() + chrono::Duration::days(INVITATION_VALID_DAYS);
 
-        // Update invitation with new token and expiry
+        // Update invitation with new token and expiry; the old link stops working
         sqlx::query(&format!(
             "UPDATE {schema}.invitation_tokens SET token = $1, expires_at = $2, created_at = $3 WHERE id = $4"
         ))
@@ -1111,26 +1659,44 @@ impl AuthService {
         .execute(pool)
         .await?;
 
-        let (garderie_name, logo_url): (String, Option<String>) = sqlx::query_as(
-            "SELECT name, logo_url FROM public.garderies WHERE slug = $1"
-        )
-        .bind(tenant)
-        .fetch_optional(pool)
-        .await
-        .ok()
-        .flatten()
-        .unwrap_or_else(|| (tenant.to_string(), None));
+        let (garderie_name, branding) = BrandingService::for_email(pool, tenant).await;
 
         let invite_url = build_tenant_invite_url(base_url, tenant, &token);
+        let locale = Locale::from_tag(&locale);
+        let template = EmailTemplateService::override_for(pool, tenant, TEMPLATE_INVITATION, locale).await;
 
         email_svc
-            .send_invitation(&email, &invite_url, &garderie_name, &role, logo_url.as_deref().unwrap_or(""))
+            .send_invitation(tenant, &email, &invite_url, &garderie_name, &role, &branding, locale, template.as_ref())
             .await
-            .map_err(|e| anyhow::anyhow!("Impossible d'envoyer l'invitation : {e}"))?;
+            .map_err(|e| AuthError::InvitationSendFailed(e.to_string()))?;
 
         Ok(())
     }
 
+    /// Push back the expiry of an unused invitation by `days`, counted from now if it
+    /// already expired. The link itself is unchanged and no email is sent.
+    pub async fn extend_invitation(
+        pool: &PgPool,
+        tenant: &str,
+        invitation_id: Uuid,
+        days: i32,
+    ) -> anyhow::Result<DateTime<Utc>> {
+        let schema = schema_name(tenant);
+        let expires_at = sqlx::query_scalar(&format!(
+            r#"UPDATE {schema}.invitation_tokens
+               SET expires_at = GREATEST(expires_at, NOW()) + make_interval(days => $1)
+               WHERE id = $2 AND used = FALSE
+               RETURNING expires_at"#
+        ))
+        .bind(days)
+        .bind(invitation_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(InvitationError::NotFound)?;
+        Ok(expires_at)
+    }
+
+    /// Delete a pending invitation by ID (only if not yet used).
     pub async fn delete_invitation(
         pool: &PgPool,
         tenant: &str,
@@ -1148,3 +1714,36 @@ impl AuthService {
         Ok(result.rows_affected() > 0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(revoked: bool, rotated_secs_ago: Option<i64>, now: DateTime<Utc>) -> RefreshToken {
+        RefreshToken {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            token_hash: String::new(),
+            expires_at: now + chrono::Duration::days(30),
+            revoked,
+            created_at: now - chrono::Duration::days(1),
+            family_id: None,
+            parent_id: None,
+            rotated_at: rotated_secs_ago.map(|s| now - chrono::Duration::seconds(s)),
+        }
+    }
+
+    #[test]
+    fn a_rotated_token_presented_again_is_a_replay() {
+        let now = Utc::now();
+        assert!(is_replay(&token(true, Some(3600), now), now));
+    }
+
+    #[test]
+    fn live_logged_out_and_racing_tokens_are_not_replays() {
+        let now = Utc::now();
+        assert!(!is_replay(&token(false, None, now), now));
+        assert!(!is_replay(&token(true, None, now), now));
+        assert!(!is_replay(&token(true, Some(2), now), now));
+    }
+}
@@ -0,0 +1,243 @@
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::attendance::{AutoAbsence, AutoAbsenceNotice, ABSENCE_STATUSES},
+};
+
+const AUTO_ABSENCE_COLS: &str = "id, child_id, date, marked_at, confirmed_at, confirmed_by, reconciled_at";
+
+/// How far back a parent is still asked about unconfirmed automatic absences.
+const PENDING_DAYS: i64 = 14;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AutoAbsenceError {
+    #[error("Absence introuvable")]
+    NotFound,
+    #[error("L'enfant a été accueilli ce jour-là : l'absence a été levée")]
+    Reconciled,
+}
+
+/// What an attendance status set for a day says about an automatic absence.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Resolution {
+    /// Late check-in: the child was there after all.
+    CheckedIn,
+    /// An absence status: the child was indeed away.
+    Confirmed,
+}
+
+pub fn resolution_for(status: &str) -> Option<Resolution> {
+    match status {
+        "present" | "present_hors_contrat" => Some(Resolution::CheckedIn),
+        s if ABSENCE_STATUSES.contains(&s) => Some(Resolution::Confirmed),
+        _ => None,
+    }
+}
+
+/// Absences marked when an expected child is not checked in by the garderie's cutoff.
+pub struct AutoAbsenceService;
+
+impl AutoAbsenceService {
+    /// Mark absent in the journal every active child expected on `date` (their schedule
+    /// days, Monday to Friday without one) whose attendance was not recorded, and
+    /// return the new marks. Children already marked that day are skipped.
+    pub async fn mark_missing(pool: &PgPool, tenant: &str, date: NaiveDate) -> anyhow::Result<Vec<AutoAbsence>> {
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+
+        let marked: Vec<AutoAbsence> = sqlx::query_as(&format!(
+            r#"INSERT INTO "{schema}".auto_absences (child_id, date)
+               SELECT c.id, $1 FROM "{schema}".children c
+               WHERE c.is_active = TRUE
+                 AND (c.start_date IS NULL OR c.start_date <= $1)
+                 AND CASE WHEN COALESCE(cardinality(c.schedule_days), 0) = 0
+                          THEN EXTRACT(ISODOW FROM $1::DATE) <= 5
+                          ELSE EXTRACT(ISODOW FROM $1::DATE)::INT = ANY(c.schedule_days) END
+                 AND NOT EXISTS (
+                     SELECT 1 FROM "{schema}".attendance a
+                     WHERE a.child_id = c.id AND a.date = $1 AND a.status::TEXT <> 'attendu'
+                 )
+               ON CONFLICT (child_id, date) DO NOTHING
+               RETURNING {AUTO_ABSENCE_COLS}"#
+        ))
+        .bind(date)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let children: Vec<Uuid> = marked.iter().map(|m| m.child_id).collect();
+        sqlx::query(&format!(
+            r#"UPDATE "{schema}".daily_journals SET absent = TRUE, updated_at = NOW()
+               WHERE child_id = ANY($1) AND date = $2"#
+        ))
+        .bind(&children)
+        .bind(date)
+        .execute(&mut *tx)
+        .await?;
+        // Journals not started yet are created in the name of the garderie's first admin.
+        sqlx::query(&format!(
+            r#"INSERT INTO "{schema}".daily_journals (child_id, date, absent, created_by)
+               SELECT c, $2, TRUE, admin.id FROM UNNEST($1::UUID[]) AS c,
+                   (SELECT id FROM "{schema}".users WHERE role = 'admin_garderie' AND is_active = TRUE
+                    ORDER BY created_at LIMIT 1) AS admin
+               ON CONFLICT (child_id, date) DO NOTHING"#
+        ))
+        .bind(&children)
+        .bind(date)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(marked)
+    }
+
+    /// Active parents of the marked children.
+    pub async fn notices(pool: &PgPool, tenant: &str, ids: &[Uuid]) -> anyhow::Result<Vec<AutoAbsenceNotice>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as(&format!(
+            r#"SELECT aa.id AS auto_absence_id, c.id AS child_id, c.first_name AS child_first_name, aa.date,
+                      u.id AS user_id, u.email, u.first_name, u.last_name
+               FROM "{schema}".auto_absences aa
+               JOIN "{schema}".children c ON c.id = aa.child_id
+               JOIN "{schema}".child_parents cp ON cp.child_id = c.id
+               JOIN "{schema}".users u ON u.id = cp.user_id
+               WHERE aa.id = ANY($1) AND u.is_active = TRUE
+               ORDER BY aa.id"#
+        ))
+        .bind(ids)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Settle the automatic absences of `child_id` on `dates` from the attendance just
+    /// recorded. The journal's absent flag follows the attendance on its own.
+    pub async fn resolve(
+        pool: &PgPool,
+        tenant: &str,
+        child_id: Uuid,
+        dates: &[NaiveDate],
+        status: &str,
+        user_id: Uuid,
+    ) -> anyhow::Result<()> {
+        let schema = schema_name(tenant);
+        match resolution_for(status) {
+            Some(Resolution::CheckedIn) => {
+                sqlx::query(&format!(
+                    r#"UPDATE "{schema}".auto_absences SET reconciled_at = NOW()
+                       WHERE child_id = $1 AND date = ANY($2) AND reconciled_at IS NULL"#
+                ))
+                .bind(child_id)
+                .bind(dates)
+                .execute(pool)
+                .await?;
+            }
+            Some(Resolution::Confirmed) => {
+                sqlx::query(&format!(
+                    r#"UPDATE "{schema}".auto_absences
+                       SET confirmed_at = COALESCE(confirmed_at, NOW()), confirmed_by = COALESCE(confirmed_by, $3),
+                           reconciled_at = NULL
+                       WHERE child_id = $1 AND date = ANY($2)"#
+                ))
+                .bind(child_id)
+                .bind(dates)
+                .bind(user_id)
+                .execute(pool)
+                .await?;
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    pub async fn get(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<AutoAbsence> {
+        let schema = schema_name(tenant);
+        let absence: Option<AutoAbsence> = sqlx::query_as(&format!(
+            r#"SELECT {AUTO_ABSENCE_COLS} FROM "{schema}".auto_absences WHERE id = $1"#
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(absence.ok_or(AutoAbsenceError::NotFound)?)
+    }
+
+    /// Confirm the child was away: the day is recorded absent in the attendance too.
+    pub async fn confirm(pool: &PgPool, tenant: &str, id: Uuid, user_id: Uuid) -> anyhow::Result<AutoAbsence> {
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+        let absence = sqlx::query_as::<_, AutoAbsence>(&format!(
+            r#"UPDATE "{schema}".auto_absences
+               SET confirmed_at = COALESCE(confirmed_at, NOW()), confirmed_by = COALESCE(confirmed_by, $2)
+               WHERE id = $1 AND reconciled_at IS NULL
+               RETURNING {AUTO_ABSENCE_COLS}"#
+        ))
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AutoAbsenceError::Reconciled)?;
+
+        sqlx::query(&format!(
+            r#"INSERT INTO "{schema}".attendance (child_id, date, status, marked_by)
+               VALUES ($1, $2, 'absent', $3)
+               ON CONFLICT (child_id, date) DO UPDATE
+                   SET status = 'absent', marked_by = $3, updated_at = NOW()
+                   WHERE attendance.status = 'attendu'"#
+        ))
+        .bind(absence.child_id)
+        .bind(absence.date)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(absence)
+    }
+
+    /// Every automatic absence of a day, for the staff.
+    pub async fn list_for_date(pool: &PgPool, tenant: &str, date: NaiveDate) -> anyhow::Result<Vec<AutoAbsence>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as(&format!(
+            r#"SELECT {AUTO_ABSENCE_COLS} FROM "{schema}".auto_absences WHERE date = $1 ORDER BY marked_at"#
+        ))
+        .bind(date)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Recent absences of a parent's children still awaiting their confirmation.
+    pub async fn pending_for_parent(
+        pool: &PgPool,
+        tenant: &str,
+        parent_id: Uuid,
+        today: NaiveDate,
+    ) -> anyhow::Result<Vec<AutoAbsence>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as(&format!(
+            r#"SELECT {AUTO_ABSENCE_COLS} FROM "{schema}".auto_absences
+               WHERE confirmed_at IS NULL AND reconciled_at IS NULL AND date > $2
+                 AND child_id IN (SELECT child_id FROM "{schema}".child_parents WHERE user_id = $1)
+               ORDER BY date DESC"#
+        ))
+        .bind(parent_id)
+        .bind(today - chrono::Duration::days(PENDING_DAYS))
+        .fetch_all(pool)
+        .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attendance_settles_automatic_absences() {
+        assert_eq!(resolution_for("present"), Some(Resolution::CheckedIn));
+        assert_eq!(resolution_for("present_hors_contrat"), Some(Resolution::CheckedIn));
+        assert_eq!(resolution_for("absent"), Some(Resolution::Confirmed));
+        assert_eq!(resolution_for("malade"), Some(Resolution::Confirmed));
+        assert_eq!(resolution_for("vacances"), Some(Resolution::Confirmed));
+        assert_eq!(resolution_for("attendu"), None);
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+
+use chrono::{Local, NaiveDate};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::message::{
+        AutoReply, AwayStatus, ConversationItem, CreateMessageRequest, MessageType, MessageWithSender,
+        UpdateAutoReplyRequest,
+    },
+    services::messages::MessageService,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum AutoReplyError {
+    #[error("La date de fin précède la date de début")]
+    InvalidRange,
+}
+
+/// Out-of-office replies of the staff. A parent's message to the garderie is for the
+/// staff member they wrote to, or else the last one who answered in their thread.
+pub struct AutoReplyService;
+
+impl AutoReplyService {
+    pub async fn get(pool: &PgPool, tenant: &str, user_id: Uuid) -> anyhow::Result<Option<AutoReply>> {
+        let schema = schema_name(tenant);
+        let reply = sqlx::query_as::<_, AutoReply>(&format!(
+            "SELECT starts_on, ends_on, message, updated_at FROM {schema}.auto_replies WHERE user_id = $1"
+        ))
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(reply)
+    }
+
+    /// Set the user's out-of-office. Threads answered under the previous one get the
+    /// new reply too.
+    pub async fn set(
+        pool: &PgPool,
+        tenant: &str,
+        user_id: Uuid,
+        req: &UpdateAutoReplyRequest,
+    ) -> anyhow::Result<AutoReply> {
+        if req.ends_on < req.starts_on {
+            return Err(AutoReplyError::InvalidRange.into());
+        }
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+        let reply = sqlx::query_as::<_, AutoReply>(&format!(
+            "INSERT INTO {schema}.auto_replies (user_id, starts_on, ends_on, message)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (user_id) DO UPDATE SET
+                 starts_on = EXCLUDED.starts_on, ends_on = EXCLUDED.ends_on,
+                 message = EXCLUDED.message, updated_at = NOW()
+             RETURNING starts_on, ends_on, message, updated_at"
+        ))
+        .bind(user_id)
+        .bind(req.starts_on)
+        .bind(req.ends_on)
+        .bind(req.message.trim())
+        .fetch_one(&mut *tx)
+        .await?;
+        sqlx::query(&format!("DELETE FROM {schema}.auto_reply_threads WHERE user_id = $1"))
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(reply)
+    }
+
+    /// Turn the user's out-of-office off. Returns whether one was set.
+    pub async fn clear(pool: &PgPool, tenant: &str, user_id: Uuid) -> anyhow::Result<bool> {
+        let schema = schema_name(tenant);
+        let deleted = sqlx::query(&format!("DELETE FROM {schema}.auto_replies WHERE user_id = $1"))
+            .bind(user_id)
+            .execute(pool)
+            .await?
+            .rows_affected();
+        Ok(deleted > 0)
+    }
+
+    /// Answer a parent's message in their own thread when its staff member is away,
+    /// once per thread and absence. Returns the auto-reply posted.
+    pub async fn reply(
+        pool: &PgPool,
+        tenant: &str,
+        msg: &MessageWithSender,
+    ) -> anyhow::Result<Option<MessageWithSender>> {
+        let Some(thread_id) = msg.thread_id.filter(|_| msg.message_type == "individual") else {
+            return Ok(None);
+        };
+        let schema = schema_name(tenant);
+        let away: Option<(Uuid, String)> = sqlx::query_as(&format!(
+            "SELECT a.user_id, a.message
+             FROM {schema}.threads t
+             JOIN {schema}.auto_replies a ON a.user_id = COALESCE($2, (
+                 SELECT m.sender_id FROM {schema}.messages m
+                 WHERE m.thread_id = t.id AND m.sender_id != t.parent_id
+                 ORDER BY m.created_at DESC LIMIT 1
+             ))
+             WHERE t.id = $1 AND t.parent_id = $3
+               AND $4 BETWEEN a.starts_on AND a.ends_on"
+        ))
+        .bind(thread_id)
+        .bind(msg.recipient_id)
+        .bind(msg.sender_id)
+        .bind(Local::now().date_naive())
+        .fetch_optional(pool)
+        .await?;
+        let Some((staff_id, message)) = away else {
+            return Ok(None);
+        };
+
+        let claimed = sqlx::query(&format!(
+            "INSERT INTO {schema}.auto_reply_threads (user_id, thread_id) VALUES ($1, $2)
+             ON CONFLICT (user_id, thread_id) DO NOTHING"
+        ))
+        .bind(staff_id)
+        .bind(thread_id)
+        .execute(pool)
+        .await?
+        .rows_affected()
+            > 0;
+        if !claimed {
+            return Ok(None);
+        }
+
+        let reply = MessageService::create_message(pool, tenant, staff_id, &CreateMessageRequest {
+            message_type: MessageType::Individual,
+            group_id: None,
+            recipient_id: Some(msg.sender_id),
+            content: message,
+            urgent: false,
+        })
+        .await?;
+        Ok(Some(reply))
+    }
+
+    /// Set `away` on the conversations whose staff member is out of office today:
+    /// the last one who answered in an individual thread, an educator of the group.
+    pub async fn annotate(pool: &PgPool, tenant: &str, items: &mut [ConversationItem]) -> anyhow::Result<()> {
+        let schema = schema_name(tenant);
+        let away: Vec<(Uuid, String, NaiveDate)> = sqlx::query_as(&format!(
+            "SELECT a.user_id, CONCAT(u.first_name, ' ', u.last_name), a.ends_on
+             FROM {schema}.auto_replies a
+             JOIN {schema}.users u ON u.id = a.user_id
+             WHERE $1 BETWEEN a.starts_on AND a.ends_on AND u.is_active = TRUE"
+        ))
+        .bind(Local::now().date_naive())
+        .fetch_all(pool)
+        .await?;
+        if away.is_empty() {
+            return Ok(());
+        }
+        let away: HashMap<Uuid, AwayStatus> = away
+            .into_iter()
+            .map(|(user_id, name, until)| (user_id, AwayStatus { user_id, name, until }))
+            .collect();
+        let away_ids: Vec<Uuid> = away.keys().copied().collect();
+
+        let individual: Vec<Uuid> =
+            items.iter().filter(|i| i.kind == "individual").filter_map(|i| i.thread_id).collect();
+        let answered_by: HashMap<Uuid, Uuid> = sqlx::query_as::<_, (Uuid, Uuid)>(&format!(
+            "SELECT t.id, last.sender_id
+             FROM {schema}.threads t
+             CROSS JOIN LATERAL (
+                 SELECT m.sender_id FROM {schema}.messages m
+                 WHERE m.thread_id = t.id AND m.sender_id != t.parent_id
+                 ORDER BY m.created_at DESC LIMIT 1
+             ) last
+             WHERE t.id = ANY($1) AND last.sender_id = ANY($2)"
+        ))
+        .bind(&individual)
+        .bind(&away_ids)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .collect();
+
+        let educators: Vec<(Uuid, Uuid)> = sqlx::query_as(&format!(
+            "SELECT group_id, user_id FROM {schema}.educator_groups WHERE user_id = ANY($1) ORDER BY user_id"
+        ))
+        .bind(&away_ids)
+        .fetch_all(pool)
+        .await?;
+
+        for item in items.iter_mut() {
+            let staff = match item.kind.as_str() {
+                "individual" => item.thread_id.and_then(|t| answered_by.get(&t).copied()),
+                "group" => item
+                    .id
+                    .as_deref()
+                    .and_then(|id| id.parse::<Uuid>().ok())
+                    .and_then(|group_id| educators.iter().find(|(g, _)| *g == group_id).map(|(_, u)| *u)),
+                _ => None,
+            };
+            item.away = staff.and_then(|id| away.get(&id).cloned());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::scratch_tenant;
+
+    #[tokio::test]
+    #[ignore]
+    async fn away_educator_answers_once_per_thread() {
+        let tenant = scratch_tenant("away").await;
+        let (pool, slug) = (tenant.pool.clone(), tenant.slug.clone());
+        let educator = tenant.user("educ@away.test", "educateur").await;
+        let parent = tenant.user("parent@away.test", "parent").await;
+        let send = |sender: Uuid, recipient: Option<Uuid>| {
+            let (pool, slug) = (pool.clone(), slug.clone());
+            async move {
+                MessageService::create_message(&pool, &slug, sender, &CreateMessageRequest {
+                    message_type: MessageType::Individual,
+                    group_id: None,
+                    recipient_id: recipient,
+                    content: "Bonjour".into(),
+                    urgent: false,
+                })
+                .await
+                .unwrap()
+            }
+        };
+
+        send(educator, Some(parent)).await;
+        let today = Local::now().date_naive();
+        AutoReplyService::set(&pool, &slug, educator, &UpdateAutoReplyRequest {
+            starts_on: today,
+            ends_on: today + chrono::Duration::days(7),
+            message: "En vacances, de retour lundi".into(),
+        })
+        .await
+        .unwrap();
+
+        let question = send(parent, None).await;
+        let reply = AutoReplyService::reply(&pool, &slug, &question).await.unwrap().unwrap();
+        assert_eq!((reply.sender_id, reply.thread_id), (educator, question.thread_id));
+        assert_eq!(reply.content, "En vacances, de retour lundi");
+        let again = send(parent, None).await;
+        assert!(AutoReplyService::reply(&pool, &slug, &again).await.unwrap().is_none());
+
+        let mut items = MessageService::get_conversations_parent(&pool, &slug, parent).await.unwrap();
+        AutoReplyService::annotate(&pool, &slug, &mut items).await.unwrap();
+        let away = items.last().unwrap().away.as_ref().unwrap();
+        assert_eq!((away.user_id, away.until), (educator, today + chrono::Duration::days(7)));
+
+        tenant.drop().await;
+    }
+}
@@ -0,0 +1,118 @@
+use chrono::{Local, Timelike};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::config::Config;
+use crate::db::tenant_db::TenantDb;
+use crate::services::backups::BackupService;
+use crate::services::email::EmailService;
+use crate::services::object_store::ObjectStore;
+use crate::services::operations::OperationService;
+
+/// Seconds after midnight for a `HH:MM` schedule; `None` when disabled or malformed.
+pub fn parse_daily_time(schedule: &str) -> Option<u32> {
+    let (h, m) = schedule.trim().split_once(':')?;
+    let (h, m): (u32, u32) = (h.parse().ok()?, m.parse().ok()?);
+    (h < 24 && m < 60).then_some(h * 3600 + m * 60)
+}
+
+/// Seconds to sleep from `secs_today` until the next `target` (tomorrow if already past).
+pub(crate) fn secs_until(secs_today: u32, target: u32) -> u64 {
+    if secs_today < target {
+        (target - secs_today) as u64
+    } else {
+        (86400 - secs_today + target) as u64
+    }
+}
+
+/// Spawn a background task that backs up every garderie to S3 once a day at
+/// `BACKUP_SCHEDULE`, records each outcome and emails super-admins on failure.
+pub fn start(pool: PgPool, config: Arc<Config>, email: Option<Arc<EmailService>>) {
+    let Some(target) = parse_daily_time(&config.backup_schedule) else {
+        info!("Backup scheduler disabled (BACKUP_SCHEDULE={})", config.backup_schedule);
+        return;
+    };
+    if TenantDb::is_shared() {
+        info!("Backup scheduler disabled: garderies share a schema, back up the whole database instead");
+        return;
+    }
+
+    tokio::spawn(async move {
+        loop {
+            let now = Local::now();
+            let secs_today = now.hour() * 3600 + now.minute() * 60 + now.second();
+            tokio::time::sleep(tokio::time::Duration::from_secs(secs_until(secs_today, target))).await;
+            let Some(_job) = crate::services::worker::job().await else {
+                break;
+            };
+
+            let Some(store) = ObjectStore::new(&config) else {
+                warn!("Backup scheduler: S3 storage not configured, skipping nightly backups");
+                continue;
+            };
+
+            let tenants: Vec<String> = match sqlx::query_scalar(
+                "SELECT slug FROM public.garderies WHERE slug != 'demo' AND archived_at IS NULL ORDER BY slug",
+            )
+            .fetch_all(&pool)
+            .await
+            {
+                Ok(rows) => rows,
+                Err(e) => {
+                    warn!("Backup scheduler: failed to query tenants: {e}");
+                    continue;
+                }
+            };
+
+            let mut failures = Vec::new();
+            for slug in &tenants {
+                if let Err(e) = BackupService::run_and_record(&pool, &config, &store, slug, "scheduled").await {
+                    warn!("Backup scheduler: backup of '{slug}' failed: {e}");
+                    failures.push((slug.clone(), e.to_string()));
+                }
+            }
+            info!(
+                "Backup scheduler: {} of {} garderie(s) backed up",
+                tenants.len() - failures.len(),
+                tenants.len()
+            );
+
+            if failures.is_empty() {
+                continue;
+            }
+            let Some(ref email_svc) = email else {
+                continue;
+            };
+            let recipients = match OperationService::alert_recipients(&pool).await {
+                Ok(r) => r,
+                Err(e) => {
+                    warn!("Backup scheduler: failed to list super-admins: {e}");
+                    continue;
+                }
+            };
+            for to in recipients {
+                if let Err(e) = email_svc.send_backup_failure_alert(&to, &failures, tenants.len()).await {
+                    warn!("Backup scheduler: failure alert to {to} failed: {e}");
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_schedule_and_waits_until_next_run() {
+        assert_eq!(parse_daily_time("02:30"), Some(2 * 3600 + 30 * 60));
+        assert_eq!(parse_daily_time("off"), None);
+        assert_eq!(parse_daily_time("24:00"), None);
+
+        let two_am = 2 * 3600;
+        assert_eq!(secs_until(3600, two_am), 3600);
+        assert_eq!(secs_until(two_am, two_am), 86400);
+        assert_eq!(secs_until(23 * 3600, two_am), 3 * 3600);
+    }
+}
@@ -0,0 +1,340 @@
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::{
+    config::Config,
+    db::{tenant::schema_name, tenant_db::TenantDb},
+    models::backup::{BackupFile, BackupManifest, BackupRun, TenantRestoreRequest},
+    services::object_store::{ObjectStore, StoredObject},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum BackupError {
+    #[error("Stockage S3 non configuré")]
+    NotConfigured,
+    #[error("Garderie introuvable")]
+    GarderieNotFound,
+    #[error("Sauvegarde introuvable")]
+    BackupNotFound,
+    #[error("Somme de contrôle invalide pour {0}")]
+    ChecksumMismatch(String),
+}
+
+/// Folder holding every backup of one garderie.
+fn tenant_prefix(slug: &str) -> String {
+    format!("tenants/{slug}/")
+}
+
+/// Backup ids are timestamps; anything else could escape the tenant's folder.
+fn valid_backup_id(id: &str) -> bool {
+    id.len() == 15
+        && id.char_indices().all(|(i, c)| if i == 8 { c == '_' } else { c.is_ascii_digit() })
+}
+
+pub struct BackupService;
+
+impl BackupService {
+    /// Stream a pg_dump of the tenant schema and a tar of its media to S3,
+    /// then write the manifest that makes the backup visible.
+    pub async fn backup_tenant(
+        pool: &PgPool,
+        config: &Config,
+        store: &ObjectStore,
+        slug: &str,
+    ) -> anyhow::Result<BackupManifest> {
+        TenantDb::require_own_schema()?;
+        let garderie_name: String = sqlx::query_scalar("SELECT name FROM garderies WHERE slug = $1")
+            .bind(slug)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(BackupError::GarderieNotFound)?;
+
+        let now = Utc::now();
+        let id = now.format("%Y%m%d_%H%M%S").to_string();
+        let folder = format!("{}{id}/", tenant_prefix(slug));
+        let mut files = Vec::new();
+
+        // --clean drops the schema first so a restore replaces it entirely
+        let mut dump = Command::new("pg_dump");
+        dump.arg("-d")
+            .arg(&config.database_url)
+            .arg(format!("--schema={}", schema_name(slug)))
+            .arg("--clean")
+            .arg("--if-exists")
+            .arg("--no-owner")
+            .arg("--compress=gzip");
+        let db = stream_command(store, &format!("{folder}db.sql.gz"), dump).await?;
+        files.push(backup_file("db", db));
+
+        let media_root = PathBuf::from(&config.media_dir);
+        if media_root.join(slug).exists() {
+            let mut tar = Command::new("tar");
+            tar.arg("-czf").arg("-").arg("-C").arg(&media_root).arg(slug);
+            let media = stream_command(store, &format!("{folder}media.tar.gz"), tar).await?;
+            files.push(backup_file("media", media));
+        }
+
+        let manifest = BackupManifest {
+            id,
+            slug: slug.to_string(),
+            garderie_name,
+            created_at: now,
+            files,
+        };
+        store
+            .put(&format!("{folder}manifest.json"), serde_json::to_vec_pretty(&manifest)?)
+            .await?;
+        Ok(manifest)
+    }
+
+    /// `backup_tenant`, with its outcome recorded in `backup_runs`.
+    pub async fn run_and_record(
+        pool: &PgPool,
+        config: &Config,
+        store: &ObjectStore,
+        slug: &str,
+        trigger: &str,
+    ) -> anyhow::Result<BackupManifest> {
+        let started_at = Utc::now();
+        let result = Self::backup_tenant(pool, config, store, slug).await;
+        let recorded = match &result {
+            Ok(m) => Self::record(pool, slug, trigger, started_at, Some(m), None).await,
+            Err(e) => Self::record(pool, slug, trigger, started_at, None, Some(&e.to_string())).await,
+        };
+        if let Err(e) = recorded {
+            tracing::warn!("Could not record backup run for '{slug}': {e}");
+        }
+        result
+    }
+
+    /// Latest backup attempts, optionally for one garderie.
+    pub async fn list_runs(pool: &PgPool, slug: Option<&str>, limit: i64) -> anyhow::Result<Vec<BackupRun>> {
+        let runs = sqlx::query_as::<_, BackupRun>(
+            "SELECT id, slug, trigger, status, backup_id, size_bytes, error, started_at, finished_at
+             FROM public.backup_runs
+             WHERE ($1::TEXT IS NULL OR slug = $1)
+             ORDER BY started_at DESC
+             LIMIT $2",
+        )
+        .bind(slug)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+        Ok(runs)
+    }
+
+    async fn record(
+        pool: &PgPool,
+        slug: &str,
+        trigger: &str,
+        started_at: DateTime<Utc>,
+        manifest: Option<&BackupManifest>,
+        error: Option<&str>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO public.backup_runs (slug, trigger, status, backup_id, size_bytes, error, started_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(slug)
+        .bind(trigger)
+        .bind(if manifest.is_some() { "succeeded" } else { "failed" })
+        .bind(manifest.map(|m| m.id.as_str()))
+        .bind(manifest.map(|m| m.total_size() as i64))
+        .bind(error)
+        .bind(started_at)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Complete backups of a garderie, newest first.
+    pub async fn list(store: &ObjectStore, slug: &str) -> anyhow::Result<Vec<BackupManifest>> {
+        let mut manifests = Vec::new();
+        for key in store.list_keys(&tenant_prefix(slug)).await? {
+            if !key.ends_with("/manifest.json") {
+                continue;
+            }
+            let body = store.get(&key).await?.bytes().await?;
+            match serde_json::from_slice::<BackupManifest>(&body) {
+                Ok(m) => manifests.push(m),
+                Err(e) => tracing::warn!("Skipping unreadable backup manifest {key}: {e}"),
+            }
+        }
+        manifests.sort_by(|a, b| b.id.cmp(&a.id));
+        Ok(manifests)
+    }
+
+    /// Delete every backup of a garderie.
+    pub async fn delete_all(store: &ObjectStore, slug: &str) -> anyhow::Result<()> {
+        for key in store.list_keys(&tenant_prefix(slug)).await? {
+            store.delete(&key).await?;
+        }
+        Ok(())
+    }
+
+    /// Replace a garderie's schema (and optionally media) with one of its backups.
+    /// Every archive is downloaded and checked against its manifest before anything is touched.
+    pub async fn restore_tenant(
+        pool: &PgPool,
+        config: &Config,
+        store: &ObjectStore,
+        slug: &str,
+        req: &TenantRestoreRequest,
+    ) -> anyhow::Result<BackupManifest> {
+        TenantDb::require_own_schema()?;
+        let manifest = Self::manifest(store, slug, &req.backup_id).await?;
+        let include_media = req.include_media.unwrap_or(true);
+
+        let mut downloads = Vec::new();
+        for file in &manifest.files {
+            if file.kind == "media" && !include_media {
+                continue;
+            }
+            let path = std::env::temp_dir().join(format!("minispace-restore-{}", uuid::Uuid::new_v4()));
+            if let Err(e) = download_verified(store, file, &path).await {
+                cleanup(&downloads).await;
+                let _ = tokio::fs::remove_file(&path).await;
+                return Err(e);
+            }
+            downloads.push((file.kind.clone(), path));
+        }
+
+        let result = apply(pool, config, &manifest, &downloads).await;
+        cleanup(&downloads).await;
+        result.map(|_| manifest)
+    }
+
+    async fn manifest(store: &ObjectStore, slug: &str, id: &str) -> anyhow::Result<BackupManifest> {
+        if !valid_backup_id(id) {
+            return Err(BackupError::BackupNotFound.into());
+        }
+        let key = format!("{}{id}/manifest.json", tenant_prefix(slug));
+        let body = store
+            .get(&key)
+            .await
+            .map_err(|_| BackupError::BackupNotFound)?
+            .bytes()
+            .await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
+
+fn backup_file(kind: &str, stored: StoredObject) -> BackupFile {
+    BackupFile {
+        kind: kind.to_string(),
+        key: stored.key,
+        size: stored.size,
+        sha256: stored.sha256,
+    }
+}
+
+/// Run `cmd` and upload its stdout to `key` as it is produced.
+async fn stream_command(store: &ObjectStore, key: &str, mut cmd: Command) -> anyhow::Result<StoredObject> {
+    let mut child = cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let uploaded = store.upload_stream(key, &mut stdout).await;
+    drop(stdout);
+    let output = child.wait_with_output().await?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        tracing::error!("Backup command for {key} failed: {}", stderr.trim());
+        anyhow::bail!("Backup command failed: {}", stderr.trim());
+    }
+    uploaded
+}
+
+async fn download_verified(store: &ObjectStore, file: &BackupFile, path: &Path) -> anyhow::Result<()> {
+    let mut resp = store.get(&file.key).await?;
+    let mut out = tokio::fs::File::create(path).await?;
+    let mut hasher = Sha256::new();
+    let mut size = 0u64;
+    while let Some(chunk) = resp.chunk().await? {
+        hasher.update(&chunk);
+        size += chunk.len() as u64;
+        out.write_all(&chunk).await?;
+    }
+    out.flush().await?;
+
+    if size != file.size || hex::encode(hasher.finalize()) != file.sha256 {
+        return Err(BackupError::ChecksumMismatch(file.key.clone()).into());
+    }
+    Ok(())
+}
+
+async fn apply(
+    pool: &PgPool,
+    config: &Config,
+    manifest: &BackupManifest,
+    downloads: &[(String, PathBuf)],
+) -> anyhow::Result<()> {
+    for (kind, path) in downloads {
+        match kind.as_str() {
+            "db" => {
+                let restore_cmd = format!(
+                    "gunzip -c '{}' | psql --single-transaction -v ON_ERROR_STOP=1 '{}'",
+                    path.display(),
+                    &config.database_url
+                );
+                let output = Command::new("sh").arg("-c").arg(&restore_cmd).output().await?;
+                if !output.status.success() {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    anyhow::bail!("DB restore failed: {}", stderr.trim());
+                }
+            }
+            "media" => {
+                let media_root = PathBuf::from(&config.media_dir);
+                let tenant_dir = media_root.join(&manifest.slug);
+                if tenant_dir.exists() {
+                    tokio::fs::remove_dir_all(&tenant_dir).await?;
+                }
+                tokio::fs::create_dir_all(&media_root).await?;
+                let output = Command::new("tar")
+                    .arg("-xzf")
+                    .arg(path)
+                    .arg("-C")
+                    .arg(&media_root)
+                    .output()
+                    .await?;
+                if !output.status.success() {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    anyhow::bail!("Media restore failed: {}", stderr.trim());
+                }
+            }
+            other => tracing::warn!("Skipping unknown backup file kind {other}"),
+        }
+    }
+
+    // A garderie deleted since the backup needs its registry row back.
+    sqlx::query("INSERT INTO garderies (slug, name) VALUES ($1, $2) ON CONFLICT (slug) DO NOTHING")
+        .bind(&manifest.slug)
+        .bind(&manifest.garderie_name)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+async fn cleanup(downloads: &[(String, PathBuf)]) {
+    for (_, path) in downloads {
+        let _ = tokio::fs::remove_file(path).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backup_ids_are_timestamps_only() {
+        assert!(valid_backup_id("20261017_020000"));
+        assert!(!valid_backup_id("../other/2026"));
+        assert!(!valid_backup_id("20261017-020000"));
+        assert!(!valid_backup_id("20261017_02000"));
+    }
+}
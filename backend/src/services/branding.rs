@@ -0,0 +1,118 @@
+use sqlx::PgPool;
+
+use crate::models::tenant::{TenantBranding, UpdateBrandingRequest};
+
+/// Columns of `public.garderies` selected into a [`TenantBranding`].
+pub const BRANDING_COLS: &str = "logo_url, brand_primary_color AS primary_color, \
+     brand_accent_color AS accent_color, brand_footer_text AS footer_text, brand_reply_to AS reply_to";
+
+const MAX_FOOTER_CHARS: usize = 500;
+
+/// Rejected branding update, with a message for the admin.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct InvalidBranding(pub String);
+
+pub struct BrandingService;
+
+impl BrandingService {
+    pub async fn get(pool: &PgPool, tenant: &str) -> anyhow::Result<Option<TenantBranding>> {
+        Ok(sqlx::query_as::<_, TenantBranding>(&format!(
+            "SELECT {BRANDING_COLS} FROM public.garderies WHERE slug = $1"
+        ))
+        .bind(tenant)
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    /// Garderie name and branding for outgoing emails; falls back to the slug and
+    /// default look if the garderie cannot be read.
+    pub async fn for_email(pool: &PgPool, tenant: &str) -> (String, TenantBranding) {
+        #[derive(sqlx::FromRow)]
+        struct Row {
+            name: String,
+            #[sqlx(flatten)]
+            branding: TenantBranding,
+        }
+
+        sqlx::query_as::<_, Row>(&format!(
+            "SELECT name, {BRANDING_COLS} FROM public.garderies WHERE slug = $1"
+        ))
+        .bind(tenant)
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten()
+        .map(|r| (r.name, r.branding))
+        .unwrap_or_else(|| (tenant.to_string(), TenantBranding::default()))
+    }
+
+    /// Validate and store the branding of a garderie (the logo has its own endpoint).
+    pub async fn update(
+        pool: &PgPool,
+        tenant: &str,
+        req: &UpdateBrandingRequest,
+    ) -> anyhow::Result<TenantBranding> {
+        let primary_color = color(req.primary_color.as_deref(), "principale")?;
+        let accent_color = color(req.accent_color.as_deref(), "d'accent")?;
+        let footer_text = non_empty(req.footer_text.as_deref());
+        if footer_text.as_ref().is_some_and(|f| f.chars().count() > MAX_FOOTER_CHARS) {
+            return Err(InvalidBranding(format!(
+                "Le pied de page ne doit pas dépasser {MAX_FOOTER_CHARS} caractères"
+            ))
+            .into());
+        }
+        let reply_to = non_empty(req.reply_to.as_deref());
+        if reply_to.as_ref().is_some_and(|r| r.parse::<lettre::Address>().is_err()) {
+            return Err(InvalidBranding("Adresse de réponse invalide".into()).into());
+        }
+
+        Ok(sqlx::query_as::<_, TenantBranding>(&format!(
+            "UPDATE public.garderies
+             SET brand_primary_color = $1, brand_accent_color = $2, brand_footer_text = $3,
+                 brand_reply_to = $4, updated_at = NOW()
+             WHERE slug = $5
+             RETURNING {BRANDING_COLS}"
+        ))
+        .bind(primary_color)
+        .bind(accent_color)
+        .bind(footer_text)
+        .bind(reply_to)
+        .bind(tenant)
+        .fetch_one(pool)
+        .await?)
+    }
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value.map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+}
+
+/// `#rrggbb` (lowercased), or None when cleared.
+fn color(value: Option<&str>, label: &str) -> Result<Option<String>, InvalidBranding> {
+    let Some(value) = non_empty(value) else {
+        return Ok(None);
+    };
+    let valid = value.len() == 7
+        && value.starts_with('#')
+        && value[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err(InvalidBranding(format!(
+            "Couleur {label} invalide — utilisez le format #RRGGBB"
+        )));
+    }
+    Ok(Some(value.to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_must_be_hex_triplets() {
+        assert_eq!(color(Some("#1A2b3C"), "principale").unwrap(), Some("#1a2b3c".into()));
+        assert_eq!(color(Some("  "), "principale").unwrap(), None);
+        assert!(color(Some("blue"), "principale").is_err());
+        assert!(color(Some("#12345g"), "principale").is_err());
+    }
+}
@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use reqwest::Client;
+use serde::Deserialize;
+
+use crate::config::Config;
+
+const SITEVERIFY_URL: &str = "https://challenges.cloudflare.com/turnstile/v0/siteverify";
+
+/// Cloudflare Turnstile check for public forms.
+pub struct CaptchaService {
+    client: Client,
+    secret_key: String,
+}
+
+#[derive(Deserialize)]
+struct SiteverifyResponse {
+    success: bool,
+    #[serde(default, rename = "error-codes")]
+    error_codes: Vec<String>,
+}
+
+impl CaptchaService {
+    /// Returns None if TURNSTILE_SECRET_KEY is not set.
+    pub fn new(config: &Config) -> Option<Self> {
+        Some(Self {
+            client: Client::builder()
+                .timeout(Duration::from_secs(5))
+                .build()
+                .unwrap_or_default(),
+            secret_key: config.turnstile_secret_key.clone()?,
+        })
+    }
+
+    /// Whether Cloudflare accepts the token produced by the widget for this client.
+    /// A failure to reach Cloudflare is an error, not a pass.
+    pub async fn verify(&self, token: &str, remote_ip: Option<&str>) -> anyhow::Result<bool> {
+        let mut form = vec![("secret", self.secret_key.as_str()), ("response", token)];
+        if let Some(ip) = remote_ip {
+            form.push(("remoteip", ip));
+        }
+        let res: SiteverifyResponse = self
+            .client
+            .post(SITEVERIFY_URL)
+            .form(&form)
+            .send()
+            .await
+            .context("Turnstile request failed")?
+            .error_for_status()
+            .context("Turnstile request failed")?
+            .json()
+            .await
+            .context("Invalid Turnstile response")?;
+
+        if !res.success {
+            tracing::info!("Turnstile rejected a token: {:?}", res.error_codes);
+        }
+        Ok(res.success)
+    }
+}
@@ -0,0 +1,648 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::{tenant::schema_name, tenant_db::TenantDb},
+    models::{
+        operation::PendingOperation,
+        transfer::{ChildTransfer, TransferChildRequest},
+        user::UserRole,
+    },
+    services::{
+        audit::{self, AuditEntry},
+        auth::AuthService,
+        consents::photo_consent_sql,
+        documents::remove_files,
+        email::EmailService,
+        encryption::{self, KeyRing},
+        entitlements::{EntitlementService, Resource},
+        media::random_storage_name,
+        video_previews::PREVIEW_PENDING,
+    },
+};
+
+const TRANSFER_COLS: &str = "id, operation_id, source_slug, source_child_id, target_slug, target_child_id, \
+     child_name, requested_by, confirmed_by, journals, journal_events, media, parents_linked, parents_invited, \
+     transferred_at";
+
+#[derive(Debug, thiserror::Error)]
+pub enum TransferError {
+    #[error("Garderie introuvable : {0}")]
+    UnknownGarderie(String),
+    #[error("Enfant introuvable")]
+    ChildNotFound,
+    #[error("L'enfant est déjà inscrit dans cette garderie")]
+    SameGarderie,
+    #[error("La garderie d'accueil n'a aucun administrateur actif")]
+    NoTargetAdmin,
+}
+
+#[derive(sqlx::FromRow)]
+struct ChildRow {
+    first_name: String,
+    last_name: String,
+    birth_date: chrono::NaiveDate,
+    notes: Option<String>,
+    start_date: Option<chrono::NaiveDate>,
+    schedule_days: Option<Vec<i32>>,
+    allergies: Vec<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct MediaRow {
+    media_type: String,
+    original_filename: String,
+    storage_path: String,
+    thumbnail_path: Option<String>,
+    content_type: String,
+    width: Option<i32>,
+    height: Option<i32>,
+    duration_secs: Option<f64>,
+    caption: Option<String>,
+    is_encrypted: bool,
+    encryption_iv: Option<Vec<u8>>,
+    encryption_tag: Option<Vec<u8>>,
+    thumbnail_encryption_iv: Option<Vec<u8>>,
+    thumbnail_encryption_tag: Option<Vec<u8>>,
+    key_version: i32,
+    content_sha256: Option<String>,
+    created_at: chrono::DateTime<Utc>,
+}
+
+/// A media file re-encrypted for the target garderie.
+struct CopiedFile {
+    storage_path: String,
+    size_bytes: i64,
+    iv: Vec<u8>,
+    tag: Vec<u8>,
+    key_version: i32,
+}
+
+/// Moves a child from one garderie (tenant schema) to another. The source keeps a
+/// deactivated record; the target gets the profile, the journals and, when photo
+/// consent was given, the media of the child alone. Photo consent starts refused in
+/// the target until a parent gives it again there.
+pub struct TransferService;
+
+impl TransferService {
+    /// Validate a transfer before it is queued. Returns the child's name.
+    pub async fn check(pool: &PgPool, source: &str, req: &TransferChildRequest) -> anyhow::Result<String> {
+        TenantDb::require_own_schema()?;
+        if source == req.target_slug {
+            return Err(TransferError::SameGarderie.into());
+        }
+        for slug in [source, req.target_slug.as_str()] {
+            let active: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM public.garderies WHERE slug = $1 AND is_active = TRUE AND archived_at IS NULL)",
+            )
+            .bind(slug)
+            .fetch_one(pool)
+            .await?;
+            if !active {
+                return Err(TransferError::UnknownGarderie(slug.to_string()).into());
+            }
+        }
+        let from = schema_name(source);
+        let name: Option<String> = sqlx::query_scalar(&format!(
+            "SELECT CONCAT(first_name, ' ', last_name) FROM {from}.children
+             WHERE id = $1 AND is_active = TRUE AND is_deleted = FALSE"
+        ))
+        .bind(req.child_id)
+        .fetch_optional(pool)
+        .await?;
+        name.ok_or_else(|| TransferError::ChildNotFound.into())
+    }
+
+    /// Run a confirmed `transfer_child` operation. Everything in the target is created
+    /// together with the deactivation of the source record, or not at all.
+    pub async fn transfer(
+        pool: &PgPool,
+        media_dir: &str,
+        keys: &KeyRing,
+        source: &str,
+        req: &TransferChildRequest,
+        op: &PendingOperation,
+    ) -> anyhow::Result<ChildTransfer> {
+        let child_name = Self::check(pool, source, req).await?;
+        let target = req.target_slug.as_str();
+        EntitlementService::ensure_can_add(pool, target, Resource::Children, 1).await?;
+
+        let (from, to) = (schema_name(source), schema_name(target));
+        let admin_id: Uuid = sqlx::query_scalar(&format!(
+            "SELECT id FROM {to}.users WHERE role = 'admin_garderie' AND is_active = TRUE ORDER BY created_at LIMIT 1"
+        ))
+        .fetch_optional(pool)
+        .await?
+        .ok_or(TransferError::NoTargetAdmin)?;
+        let consented: bool = sqlx::query_scalar(&format!("SELECT {}", photo_consent_sql(&from, "$1")))
+            .bind(req.child_id)
+            .fetch_one(pool)
+            .await?;
+
+        let mut tx = pool.begin().await?;
+        let child: ChildRow = sqlx::query_as(&format!(
+            "SELECT first_name, last_name, birth_date, notes, start_date, schedule_days, allergies
+             FROM {from}.children WHERE id = $1 FOR UPDATE"
+        ))
+        .bind(req.child_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        let child_id: Uuid = sqlx::query_scalar(&format!(
+            "INSERT INTO {to}.children (first_name, last_name, birth_date, notes, start_date, schedule_days, allergies)
+             VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id"
+        ))
+        .bind(&child.first_name)
+        .bind(&child.last_name)
+        .bind(child.birth_date)
+        .bind(&child.notes)
+        .bind(child.start_date)
+        .bind(&child.schedule_days)
+        .bind(&child.allergies)
+        .fetch_one(&mut *tx)
+        .await?;
+        sqlx::query(&format!(
+            "INSERT INTO {to}.child_consents (child_id, photos_accepted) VALUES ($1, FALSE)"
+        ))
+        .bind(child_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let (parents_linked, parents_invited) = Self::copy_parents(&mut tx, source, target, req.child_id, child_id).await?;
+
+        let journals = sqlx::query(&format!(
+            r#"INSERT INTO {to}.daily_journals
+               (child_id, date, temperature, menu, appetit, humeur, sommeil_minutes, absent, sante, medicaments,
+                message_educatrice, observations, sent_at, created_by, created_at, updated_at, amended_at)
+               SELECT $1, date, temperature::TEXT::"{to}".weather_condition, menu,
+                      appetit::TEXT::"{to}".appetit_level, humeur::TEXT::"{to}".humeur_level, sommeil_minutes,
+                      absent, sante, medicaments, message_educatrice, observations, sent_at, $2,
+                      created_at, updated_at, amended_at
+               FROM {from}.daily_journals WHERE child_id = $3"#
+        ))
+        .bind(child_id)
+        .bind(admin_id)
+        .bind(req.child_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        let journal_events = sqlx::query(&format!(
+            "INSERT INTO {to}.journal_events (child_id, date, event_type, start_time, end_time, notes, created_at)
+             SELECT $1, date, event_type, start_time, end_time, notes, created_at
+             FROM {from}.journal_events WHERE child_id = $2"
+        ))
+        .bind(child_id)
+        .bind(req.child_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        let mut written = Vec::new();
+        let result = async {
+            let media = if consented {
+                Self::copy_media(pool, &mut tx, media_dir, keys, source, target, req.child_id, child_id, admin_id, &mut written)
+                    .await?
+            } else {
+                0
+            };
+
+            sqlx::query(&format!("UPDATE {from}.children SET is_active = FALSE, departed_on = CURRENT_DATE, updated_at = NOW() WHERE id = $1"))
+                .bind(req.child_id)
+                .execute(&mut *tx)
+                .await?;
+            let transfer = sqlx::query_as::<_, ChildTransfer>(&format!(
+                "INSERT INTO public.child_transfers
+                 (operation_id, source_slug, source_child_id, target_slug, target_child_id, child_name,
+                  requested_by, confirmed_by, journals, journal_events, media, parents_linked, parents_invited)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+                 RETURNING {TRANSFER_COLS}"
+            ))
+            .bind(op.id)
+            .bind(source)
+            .bind(req.child_id)
+            .bind(target)
+            .bind(child_id)
+            .bind(&child_name)
+            .bind(&op.requested_by)
+            .bind(&op.confirmed_by)
+            .bind(journals as i32)
+            .bind(journal_events as i32)
+            .bind(media as i32)
+            .bind(parents_linked as i32)
+            .bind(parents_invited as i32)
+            .fetch_one(&mut *tx)
+            .await?;
+            tx.commit().await?;
+            anyhow::Ok(transfer)
+        }
+        .await;
+        let transfer = match result {
+            Ok(transfer) => transfer,
+            Err(e) => {
+                remove_files(media_dir, &written).await;
+                return Err(e);
+            }
+        };
+
+        let operator = op.confirmed_by.clone().or_else(|| op.requested_by.clone());
+        for (tenant, action, id) in [
+            (source, "child.transferred_out", transfer.source_child_id),
+            (target, "child.transferred_in", transfer.target_child_id),
+        ] {
+            audit::log(pool.clone(), tenant, AuditEntry {
+                user_id: None,
+                user_name: operator.clone(),
+                action: action.to_string(),
+                resource_type: Some("child".to_string()),
+                resource_id: Some(id.to_string()),
+                resource_label: Some(format!("{} ({source} → {target})", transfer.child_name)),
+                ip_address: "".to_string(),
+            });
+        }
+        tracing::info!(
+            "Child {} transferred from {source} to {target}: {journals} journals, {journal_events} events, {} media",
+            transfer.source_child_id,
+            transfer.media
+        );
+        Ok(transfer)
+    }
+
+    /// Invite the parents of a transferred child who have no account in the target
+    /// garderie yet; registering links them to the child. Failures are logged only.
+    pub async fn invite_parents(pool: &PgPool, config: &Config, transfer: &ChildTransfer) -> anyhow::Result<usize> {
+        let to = schema_name(&transfer.target_slug);
+        let emails: Vec<String> = sqlx::query_scalar(&format!(
+            "SELECT p.email FROM {to}.child_pending_parents p
+             WHERE p.child_id = $1
+               AND NOT EXISTS (SELECT 1 FROM {to}.invitation_tokens i
+                               WHERE i.email = p.email AND i.used = FALSE AND i.expires_at > NOW())
+             ORDER BY p.email"
+        ))
+        .bind(transfer.target_child_id)
+        .fetch_all(pool)
+        .await?;
+
+        let email_svc = EmailService::new(config);
+        let mut sent = 0;
+        for email in emails {
+            match AuthService::create_invitation(
+                pool,
+                email_svc.as_ref(),
+                &transfer.target_slug,
+                &email,
+                UserRole::Parent,
+                None,
+                None,
+                &config.app_base_url,
+            )
+            .await
+            {
+                Ok(()) => sent += 1,
+                Err(e) => tracing::warn!("Failed to invite parent of transferred child {}: {e}", transfer.target_child_id),
+            }
+        }
+        Ok(sent)
+    }
+
+    /// Executed transfers, most recent first, optionally those from or to one garderie.
+    pub async fn list(pool: &PgPool, slug: Option<&str>) -> anyhow::Result<Vec<ChildTransfer>> {
+        let transfers = sqlx::query_as::<_, ChildTransfer>(&format!(
+            "SELECT {TRANSFER_COLS} FROM public.child_transfers
+             WHERE $1::TEXT IS NULL OR source_slug = $1 OR target_slug = $1
+             ORDER BY transferred_at DESC
+             LIMIT 200"
+        ))
+        .bind(slug)
+        .fetch_all(pool)
+        .await?;
+        Ok(transfers)
+    }
+
+    /// Link the parents who already have an account in the target garderie (same
+    /// email) and leave the others pending. Returns (linked, pending).
+    async fn copy_parents(
+        conn: &mut PgConnection,
+        source: &str,
+        target: &str,
+        source_child: Uuid,
+        target_child: Uuid,
+    ) -> anyhow::Result<(usize, usize)> {
+        let (from, to) = (schema_name(source), schema_name(target));
+        let parents: Vec<(String, String)> = sqlx::query_as(&format!(
+            "SELECT LOWER(u.email), cp.relationship FROM {from}.child_parents cp
+             JOIN {from}.users u ON u.id = cp.user_id
+             WHERE cp.child_id = $1 AND u.is_active = TRUE
+             UNION
+             SELECT LOWER(email), relationship FROM {from}.child_pending_parents WHERE child_id = $1"
+        ))
+        .bind(source_child)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let (mut linked, mut pending) = (0, 0);
+        for (email, relationship) in parents {
+            let user_id: Option<Uuid> = sqlx::query_scalar(&format!(
+                "SELECT id FROM {to}.users WHERE LOWER(email) = $1"
+            ))
+            .bind(&email)
+            .fetch_optional(&mut *conn)
+            .await?;
+            if let Some(user_id) = user_id {
+                linked += sqlx::query(&format!(
+                    "INSERT INTO {to}.child_parents (child_id, user_id, relationship) VALUES ($1, $2, $3)
+                     ON CONFLICT (child_id, user_id) DO NOTHING"
+                ))
+                .bind(target_child)
+                .bind(user_id)
+                .bind(&relationship)
+                .execute(&mut *conn)
+                .await?
+                .rows_affected() as usize;
+            } else {
+                pending += sqlx::query(&format!(
+                    "INSERT INTO {to}.child_pending_parents (child_id, email, relationship) VALUES ($1, $2, $3)
+                     ON CONFLICT (child_id, email) DO NOTHING"
+                ))
+                .bind(target_child)
+                .bind(&email)
+                .bind(&relationship)
+                .execute(&mut *conn)
+                .await?
+                .rows_affected() as usize;
+            }
+        }
+        Ok((linked, pending))
+    }
+
+    /// Copy the clean photos and videos tagged with this child only, re-encrypting
+    /// their files (and thumbnails) with the target's key. They are visible to the
+    /// child's parents; video previews are generated again. Written files are pushed
+    /// to `written` for cleanup.
+    #[allow(clippy::too_many_arguments)]
+    async fn copy_media(
+        pool: &PgPool,
+        conn: &mut PgConnection,
+        media_dir: &str,
+        keys: &KeyRing,
+        source: &str,
+        target: &str,
+        source_child: Uuid,
+        target_child: Uuid,
+        uploader_id: Uuid,
+        written: &mut Vec<String>,
+    ) -> anyhow::Result<usize> {
+        let (from, to) = (schema_name(source), schema_name(target));
+        let rows: Vec<MediaRow> = sqlx::query_as(&format!(
+            "SELECT m.media_type::TEXT AS media_type, m.original_filename, m.storage_path, m.thumbnail_path,
+                    m.content_type, m.width, m.height, m.duration_secs, m.caption, m.is_encrypted,
+                    m.encryption_iv, m.encryption_tag, m.thumbnail_encryption_iv, m.thumbnail_encryption_tag,
+                    m.key_version, m.content_sha256, m.created_at
+             FROM {from}.media m
+             WHERE m.is_deleted = FALSE AND m.scan_status = 'clean'
+               AND (SELECT array_agg(mc.child_id) FROM {from}.media_children mc WHERE mc.media_id = m.id) = ARRAY[$1]
+             ORDER BY m.created_at"
+        ))
+        .bind(source_child)
+        .fetch_all(pool)
+        .await?;
+
+        for m in &rows {
+            let encryption = (m.is_encrypted, m.key_version);
+            let file = Self::copy_file(
+                media_dir,
+                keys,
+                source,
+                target,
+                &m.storage_path,
+                m.encryption_iv.as_deref().zip(m.encryption_tag.as_deref()),
+                encryption,
+                written,
+            )
+            .await?;
+            let thumbnail = match &m.thumbnail_path {
+                Some(path) => Some(
+                    Self::copy_file(
+                        media_dir,
+                        keys,
+                        source,
+                        target,
+                        path,
+                        m.thumbnail_encryption_iv.as_deref().zip(m.thumbnail_encryption_tag.as_deref()),
+                        encryption,
+                        written,
+                    )
+                    .await?,
+                ),
+                None => None,
+            };
+
+            let media_id: Uuid = sqlx::query_scalar(&format!(
+                "INSERT INTO \"{to}\".media
+                 (uploader_id, media_type, original_filename, storage_path, thumbnail_path, content_type, size_bytes,
+                  width, height, duration_secs, caption, visibility, is_encrypted, encryption_iv, encryption_tag,
+                  thumbnail_encryption_iv, thumbnail_encryption_tag, key_version, content_sha256, preview_status,
+                  scan_status, created_at)
+                 VALUES ($1, $2::\"{to}\".media_type, $3, $4, $5, $6, $7, $8, $9, $10, $11, 'child', TRUE, $12, $13,
+                         $14, $15, $16, $17, $18, 'clean', $19)
+                 RETURNING id"
+            ))
+            .bind(uploader_id)
+            .bind(&m.media_type)
+            .bind(&m.original_filename)
+            .bind(&file.storage_path)
+            .bind(thumbnail.as_ref().map(|t| &t.storage_path))
+            .bind(&m.content_type)
+            .bind(file.size_bytes)
+            .bind(m.width)
+            .bind(m.height)
+            .bind(m.duration_secs)
+            .bind(&m.caption)
+            .bind(&file.iv)
+            .bind(&file.tag)
+            .bind(thumbnail.as_ref().map(|t| &t.iv))
+            .bind(thumbnail.as_ref().map(|t| &t.tag))
+            .bind(file.key_version)
+            .bind(&m.content_sha256)
+            .bind((m.media_type == "video").then_some(PREVIEW_PENDING))
+            .bind(m.created_at)
+            .fetch_one(&mut *conn)
+            .await?;
+            sqlx::query(&format!("INSERT INTO {to}.media_children (media_id, child_id) VALUES ($1, $2)"))
+                .bind(media_id)
+                .bind(target_child)
+                .execute(&mut *conn)
+                .await?;
+        }
+        Ok(rows.len())
+    }
+
+    /// Decrypt one file of the source garderie and write it, encrypted with the
+    /// target's current key, under `{target}/{year}/{month}/` like uploads.
+    #[allow(clippy::too_many_arguments)]
+    async fn copy_file(
+        media_dir: &str,
+        keys: &KeyRing,
+        source: &str,
+        target: &str,
+        path: &str,
+        iv_tag: Option<(&[u8], &[u8])>,
+        (is_encrypted, key_version): (bool, i32),
+        written: &mut Vec<String>,
+    ) -> anyhow::Result<CopiedFile> {
+        let mut bytes = tokio::fs::read(PathBuf::from(media_dir).join(path)).await?;
+        if is_encrypted {
+            let Some((iv, tag)) = iv_tag else {
+                anyhow::bail!("missing encryption IV or tag for {path}");
+            };
+            bytes = encryption::decrypt_file(&bytes, iv, tag, &keys.tenant_key(key_version, source)?)?;
+        }
+
+        let dir = format!("{target}/{}", Utc::now().format("%Y/%m"));
+        tokio::fs::create_dir_all(PathBuf::from(media_dir).join(&dir)).await?;
+        let storage_path = format!("{dir}/{}", random_storage_name());
+        let (key_version, key) = keys.current_tenant_key(target)?;
+        let (encrypted, iv, tag) = encryption::encrypt_file(&bytes, &key)?;
+        tokio::fs::write(PathBuf::from(media_dir).join(&storage_path), &encrypted).await?;
+        written.push(storage_path.clone());
+
+        Ok(CopiedFile { storage_path, size_bytes: encrypted.len() as i64, iv, tag, key_version })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::{scratch_pool, ScratchTenant};
+
+    #[tokio::test]
+    #[ignore]
+    async fn child_moves_with_journals_consented_media_and_parents() {
+        let pool = scratch_pool().await;
+        let from_tenant = ScratchTenant::provision(&pool, "from").await;
+        let to_tenant = ScratchTenant::provision(&pool, "to").await;
+        from_tenant.register().await;
+        to_tenant.register().await;
+        let (source, target) = (from_tenant.slug.clone(), to_tenant.slug.clone());
+        let (from, to) = (from_tenant.schema.clone(), to_tenant.schema.clone());
+        let educator = from_tenant.user("educ@from.test", "educateur").await;
+        let parent = from_tenant.user("parent@famille.test", "parent").await;
+        to_tenant.user("admin@to.test", "admin_garderie").await;
+        let parent_there = to_tenant.user("parent@famille.test", "parent").await;
+
+        let child: Uuid = sqlx::query_scalar(&format!(
+            "INSERT INTO {from}.children (first_name, last_name, birth_date, allergies)
+             VALUES ('Léa', 'Tremblay', '2023-04-01', ARRAY['arachides']) RETURNING id"
+        ))
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        sqlx::raw_sql(&format!(
+            "INSERT INTO {from}.child_parents (child_id, user_id) VALUES ('{child}', '{parent}');
+             INSERT INTO {from}.child_pending_parents (child_id, email) VALUES ('{child}', 'autre@famille.test');
+             INSERT INTO {from}.child_consents (child_id, photos_accepted) VALUES ('{child}', TRUE);
+             INSERT INTO {from}.daily_journals (child_id, date, appetit, humeur, created_by)
+             VALUES ('{child}', '2026-10-01', 'comme_habitude', 'bien', '{educator}');
+             INSERT INTO {from}.journal_events (child_id, date, event_type, start_time)
+             VALUES ('{child}', '2026-10-01', 'nap', '13:00')"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let media_dir = std::env::temp_dir().join(format!("minispace-transfer-{source}"));
+        let media_dir = media_dir.to_str().unwrap();
+        let keys = KeyRing::new(&"11".repeat(32), 1, None).unwrap();
+        let (_, key) = keys.current_tenant_key(&source).unwrap();
+        let (encrypted, iv, tag) = encryption::encrypt_file(b"photo", &key).unwrap();
+        tokio::fs::create_dir_all(PathBuf::from(media_dir).join(&source)).await.unwrap();
+        tokio::fs::write(PathBuf::from(media_dir).join(&source).join("photo"), &encrypted).await.unwrap();
+        sqlx::raw_sql(&format!(
+            "WITH m AS (
+                 INSERT INTO {from}.media (uploader_id, media_type, original_filename, storage_path, content_type,
+                                           size_bytes, visibility, is_encrypted, encryption_iv, encryption_tag, scan_status)
+                 VALUES ('{educator}', 'photo', 'a.jpg', '{source}/photo', 'image/jpeg', 5, 'child', TRUE,
+                         '\\x{}', '\\x{}', 'clean')
+                 RETURNING id)
+             INSERT INTO {from}.media_children (media_id, child_id) SELECT id, '{child}' FROM m",
+            hex::encode(&iv),
+            hex::encode(&tag)
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let req = TransferChildRequest { child_id: child, target_slug: target.clone() };
+        assert!(matches!(
+            TransferService::check(&pool, &source, &TransferChildRequest { target_slug: source.clone(), ..req.clone() })
+                .await
+                .unwrap_err()
+                .downcast_ref(),
+            Some(TransferError::SameGarderie)
+        ));
+        let op = crate::services::operations::OperationService::request(
+            &pool,
+            crate::models::operation::OP_TRANSFER_CHILD,
+            Some(&source),
+            serde_json::to_value(&req).unwrap(),
+            Some("a@ops.test"),
+            60,
+        )
+        .await
+        .unwrap();
+        let op = PendingOperation { status: "running".into(), confirmed_by: Some("b@ops.test".into()), ..op };
+        let transfer = TransferService::transfer(&pool, media_dir, &keys, &source, &req, &op).await.unwrap();
+        assert_eq!(
+            (transfer.journals, transfer.journal_events, transfer.media),
+            (1, 1, 1)
+        );
+        assert_eq!((transfer.parents_linked, transfer.parents_invited), (1, 1));
+        assert_eq!(transfer.child_name, "Léa Tremblay");
+
+        let moved = transfer.target_child_id;
+        let consent: bool = sqlx::query_scalar(&format!(
+            "SELECT photos_accepted FROM {to}.child_consents WHERE child_id = $1"
+        ))
+        .bind(moved)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(!consent, "photos wait for a new consent");
+        let linked: Uuid = sqlx::query_scalar(&format!("SELECT user_id FROM {to}.child_parents WHERE child_id = $1"))
+            .bind(moved)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(linked, parent_there);
+        let (path, iv, tag): (String, Vec<u8>, Vec<u8>) = sqlx::query_as(&format!(
+            "SELECT m.storage_path, m.encryption_iv, m.encryption_tag FROM {to}.media m
+             JOIN {to}.media_children mc ON mc.media_id = m.id WHERE mc.child_id = $1"
+        ))
+        .bind(moved)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(path.starts_with(&format!("{target}/")));
+        let bytes = tokio::fs::read(PathBuf::from(media_dir).join(&path)).await.unwrap();
+        let (_, key) = keys.current_tenant_key(&target).unwrap();
+        assert_eq!(encryption::decrypt_file(&bytes, &iv, &tag, &key).unwrap(), b"photo");
+        let still_active: bool = sqlx::query_scalar(&format!("SELECT is_active FROM {from}.children WHERE id = $1"))
+            .bind(child)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(!still_active);
+        assert!(matches!(
+            TransferService::check(&pool, &source, &req).await.unwrap_err().downcast_ref(),
+            Some(TransferError::ChildNotFound)
+        ));
+
+        let _ = tokio::fs::remove_dir_all(media_dir).await;
+        sqlx::query("DELETE FROM public.child_transfers WHERE id = $1").bind(transfer.id).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM public.pending_operations WHERE id = $1").bind(op.id).execute(&pool).await.unwrap();
+        from_tenant.drop().await;
+        to_tenant.drop().await;
+    }
+}
@@ -8,12 +8,48 @@ use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::{
-    db::tenant::schema_name,
+    db::{tenant::schema_name, tenant_db::TenantDb},
+    models::auth::AuthenticatedUser,
+    models::user::UserRole,
     models::child::{AssignParentRequest, AssignPendingParentRequest, Child, ChildParentUser, CreateChildRequest, ImportResult, ImportRowError, InvitedParent, PendingParent, UpdateChildRequest},
+    models::email_template::TEMPLATE_INVITATION,
     models::group::CreateGroupRequest,
-    services::{email::EmailService, groups::GroupService},
+    services::{branding::BrandingService, email::EmailService, email_i18n::Locale, email_templates::EmailTemplateService, entitlements::{EntitlementError, EntitlementService, Resource}, groups::GroupService, menu::validate_allergens},
 };
 
+/// Invitation emails sent per batch after an import, and the pause between batches,
+/// so a large onboarding doesn't hit the SMTP relay all at once.
+const INVITE_BATCH_SIZE: usize = 10;
+const INVITE_BATCH_PAUSE_SECS: u64 = 2;
+
+/// Why an import file was refused as a whole.
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("Impossible d'ouvrir le fichier Excel: {0}")]
+    Unreadable(String),
+    #[error("Le fichier ne contient aucune feuille")]
+    NoSheet,
+    #[error("Impossible de lire la feuille: {0}")]
+    UnreadableSheet(String),
+    #[error("Colonnes requises manquantes: Prénom, Nom et Date de naissance")]
+    MissingColumns,
+}
+
+/// A data row of an import file that passed validation.
+#[derive(Debug)]
+struct ImportRow {
+    row: usize,
+    first_name: String,
+    last_name: String,
+    birth_date: NaiveDate,
+    start_date: Option<NaiveDate>,
+    schedule_days: Option<Vec<i32>>,
+    group_name: Option<String>,
+    notes: Option<String>,
+    /// (email, relationship)
+    parents: Vec<(String, String)>,
+}
+
 pub struct ChildService;
 
 impl ChildService {
@@ -45,15 +81,52 @@ impl ChildService {
         Ok(children)
     }
 
+    pub async fn list_in_groups(
+        pool: &PgPool,
+        tenant: &str,
+        group_ids: &[Uuid],
+    ) -> anyhow::Result<Vec<Child>> {
+        let schema = schema_name(tenant);
+        let children = sqlx::query_as::<_, Child>(&format!(
+            "SELECT * FROM {schema}.children
+             WHERE is_active = TRUE AND group_id = ANY($1)
+             ORDER BY last_name, first_name"
+        ))
+        .bind(group_ids)
+        .fetch_all(pool)
+        .await?;
+        Ok(children)
+    }
+
+    /// Children the user may see: their own for parents, their assigned
+    /// groups for scoped educators, every active child otherwise.
+    pub async fn list_visible_to(
+        pool: &PgPool,
+        tenant: &str,
+        user: &AuthenticatedUser,
+    ) -> anyhow::Result<Vec<Child>> {
+        if user.role == UserRole::Parent {
+            return Self::list_for_parent(pool, tenant, user.user_id).await;
+        }
+        match GroupService::educator_scope(pool, tenant, user).await? {
+            Some(groups) => Self::list_in_groups(pool, tenant, &groups).await,
+            None => Self::list(pool, tenant).await,
+        }
+    }
+
     pub async fn create(
         pool: &PgPool,
         tenant: &str,
         req: &CreateChildRequest,
     ) -> anyhow::Result<Child> {
+        if let Some(ref allergies) = req.allergies {
+            validate_allergens(allergies)?;
+        }
+        EntitlementService::ensure_can_add(pool, tenant, Resource::Children, 1).await?;
         let schema = schema_name(tenant);
         let child = sqlx::query_as::<_, Child>(&format!(
-            "INSERT INTO {schema}.children (first_name, last_name, birth_date, group_id, notes, start_date, schedule_days)
-             VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "INSERT INTO {schema}.children (first_name, last_name, birth_date, group_id, notes, start_date, schedule_days, allergies)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, COALESCE($8, '{{}}'))
              RETURNING *"
         ))
         .bind(&req.first_name)
@@ -63,6 +136,7 @@ impl ChildService {
         .bind(&req.notes)
         .bind(req.start_date)
         .bind(&req.schedule_days)
+        .bind(&req.allergies)
         .fetch_one(pool)
         .await?;
         Ok(child)
@@ -74,6 +148,9 @@ impl ChildService {
         id: Uuid,
         req: &UpdateChildRequest,
     ) -> anyhow::Result<Child> {
+        if let Some(ref allergies) = req.allergies {
+            validate_allergens(allergies)?;
+        }
         let schema = schema_name(tenant);
         let child = sqlx::query_as::<_, Child>(&format!(
             "UPDATE {schema}.children
@@ -83,8 +160,16 @@ impl ChildService {
                  group_id      = COALESCE($4, group_id),
                  notes         = COALESCE($5, notes),
                  is_active     = COALESCE($6, is_active),
+                 departed_on   = CASE WHEN $6 IS NULL THEN departed_on
+                                      WHEN $6 THEN NULL
+                                      ELSE LEAST(COALESCE(departed_on, CURRENT_DATE), CURRENT_DATE) END,
+                 parent_access_until    = CASE WHEN $6 THEN NULL ELSE parent_access_until END,
+                 departure_applied_at   = CASE WHEN $6 THEN NULL ELSE departure_applied_at END,
+                 parent_access_ended_at = CASE WHEN $6 THEN NULL ELSE parent_access_ended_at END,
                  start_date    = COALESCE($8, start_date),
                  schedule_days = COALESCE($9, schedule_days),
+                 allergies     = COALESCE($10, allergies),
+                 monthly_fee_cents = COALESCE($11, monthly_fee_cents),
                  updated_at    = NOW()
              WHERE id = $7
              RETURNING *"
@@ -98,6 +183,8 @@ impl ChildService {
         .bind(id)
         .bind(req.start_date)
         .bind(&req.schedule_days)
+        .bind(&req.allergies)
+        .bind(req.monthly_fee_cents)
         .fetch_one(pool)
         .await?;
         Ok(child)
@@ -167,7 +254,8 @@ impl ChildService {
         let schema = schema_name(tenant);
         sqlx::query(&format!(
             "UPDATE {schema}.children
-             SET is_active = FALSE, is_deleted = TRUE, deleted_at = NOW(), updated_at = NOW()
+             SET is_active = FALSE, is_deleted = TRUE, deleted_at = NOW(),
+                 departed_on = LEAST(COALESCE(departed_on, CURRENT_DATE), CURRENT_DATE), updated_at = NOW()
              WHERE id = $1"
         ))
         .bind(child_id)
@@ -351,7 +439,9 @@ impl ChildService {
     }
 
     /// Import children from an Excel (.xlsx) or CSV file.
-    /// Returns an ImportResult with counts and skipped-row errors.
+    /// With `dry_run`, rows are only validated and the counts describe what an
+    /// import would do; nothing is written and no email is sent.
+    /// Parent invitation emails are sent in batches from a background task.
     pub async fn import_from_excel(
         pool: &PgPool,
         tenant: &str,
@@ -359,306 +449,234 @@ impl ChildService {
         email_svc: Option<Arc<EmailService>>,
         base_url: &str,
         invited_by: Option<Uuid>,
+        dry_run: bool,
     ) -> anyhow::Result<ImportResult> {
-        // Parse all rows to Vec<Vec<String>> regardless of format
-        let all_rows: Vec<Vec<String>> = if bytes.starts_with(b"PK") {
-            // xlsx (ZIP-based)
-            let cursor = Cursor::new(bytes);
-            let mut workbook: Xlsx<_> = open_workbook_from_rs(cursor)
-                .map_err(|e| anyhow::anyhow!("Impossible d'ouvrir le fichier Excel: {e}"))?;
-            let sheet_names = workbook.sheet_names().to_vec();
-            let sheet_name = sheet_names
-                .first()
-                .cloned()
-                .ok_or_else(|| anyhow::anyhow!("Le fichier ne contient aucune feuille"))?;
-            let range = workbook
-                .worksheet_range(&sheet_name)
-                .map_err(|e| anyhow::anyhow!("Impossible de lire la feuille: {e}"))?;
-            range
-                .rows()
-                .map(|row| row.iter().map(cell_str).collect())
-                .collect()
-        } else {
-            // CSV (strip UTF-8 BOM if present)
-            let data = if bytes.starts_with(b"\xef\xbb\xbf") { &bytes[3..] } else { &bytes[..] };
-            let mut rdr = csv::ReaderBuilder::new()
-                .flexible(true)
-                .from_reader(data);
-            let mut rows: Vec<Vec<String>> = vec![];
-            // Push headers as first row
-            if let Ok(headers) = rdr.headers() {
-                rows.push(headers.iter().map(str::to_string).collect());
-            }
-            for record in rdr.records().flatten() {
-                rows.push(record.iter().map(str::to_string).collect());
+        let schema = schema_name(tenant);
+        let (rows, skipped_rows) = parse_import_rows(read_import_file(bytes)?)?;
+        let mut result = ImportResult { dry_run, skipped_rows, ..ImportResult::default() };
+
+        // Resolve groups once per distinct name (case-insensitive), creating missing ones
+        let mut group_cache: HashMap<String, Option<Uuid>> = HashMap::new();
+        for name in rows.iter().filter_map(|r| r.group_name.as_deref()) {
+            let key = name.to_lowercase();
+            if group_cache.contains_key(&key) {
+                continue;
             }
-            rows
-        };
-
-        let mut row_iter = all_rows.into_iter();
-
-        // Read header row and build column index map
-        let headers: Vec<String> = match row_iter.next() {
-            Some(row) => row.iter().map(|s| s.trim().to_lowercase()).collect(),
-            None => return Ok(ImportResult::default()),
-        };
-
-        fn col_idx(headers: &[String], names: &[&str]) -> Option<usize> {
-            headers.iter().position(|h| names.iter().any(|n| h == n))
-        }
-
-        let idx_groupe       = col_idx(&headers, &["groupe", "group"]);
-        let idx_prenom       = col_idx(&headers, &["prénom", "prenom", "first_name", "firstname"]);
-        let idx_nom          = col_idx(&headers, &["nom", "last_name", "lastname"]);
-        let idx_naissance    = col_idx(&headers, &["date de naissance", "birth_date", "naissance", "dob"]);
-        let idx_debut        = col_idx(&headers, &["date de début", "date de debut", "start_date", "debut"]);
-        let idx_jours        = col_idx(&headers, &["jours", "days", "schedule_days"]);
-        let idx_parent1_email = col_idx(&headers, &["email parent 1", "email_parent1", "parent1_email", "parent 1"]);
-        let idx_parent1_rel  = col_idx(&headers, &["relation parent 1", "relation_parent1", "parent1_relation"]);
-        let idx_parent2_email = col_idx(&headers, &["email parent 2", "email_parent2", "parent2_email", "parent 2"]);
-        let idx_parent2_rel  = col_idx(&headers, &["relation parent 2", "relation_parent2", "parent2_relation"]);
-        let idx_notes        = col_idx(&headers, &["notes", "note"]);
-
-        // Require at minimum prenom + nom + naissance columns
-        if idx_prenom.is_none() || idx_nom.is_none() || idx_naissance.is_none() {
-            return Err(anyhow::anyhow!(
-                "Colonnes requises manquantes: Prénom, Nom et Date de naissance"
-            ));
-        }
-
-        let mut result = ImportResult::default();
-        // Cache: lowercase group name → Uuid
-        let mut group_cache: HashMap<String, Uuid> = HashMap::new();
-
-        for (data_row_idx, row) in row_iter.enumerate() {
-            let row_num = data_row_idx + 2; // 1-indexed, header is row 1
+            let existing: Option<Uuid> = sqlx::query_scalar(&format!(
+                "SELECT id FROM {schema}.groups WHERE lower(name) = lower($1) LIMIT 1"
+            ))
+            .bind(name)
+            .fetch_optional(pool)
+            .await?;
 
-            let get = |idx: Option<usize>| -> String {
-                idx.and_then(|i| row.get(i))
-                    .map(|s| s.trim().to_string())
-                    .unwrap_or_default()
+            let id = match existing {
+                Some(id) => Some(id),
+                None if dry_run => None,
+                None => {
+                    let g = GroupService::create(
+                        pool,
+                        tenant,
+                        &CreateGroupRequest { name: name.to_string(), description: None, color: None, allow_parent_replies: false },
+                    )
+                    .await?;
+                    Some(g.id)
+                }
             };
+            if existing.is_none() {
+                result.created_groups += 1;
+            }
+            group_cache.insert(key, id);
+        }
 
-            let first_name = get(idx_prenom);
-            let last_name  = get(idx_nom);
+        // email → invitation created by this import, so siblings share one invitation
+        let mut invitations: HashMap<String, Option<Uuid>> = HashMap::new();
+        let mut outgoing: Vec<(String, String)> = Vec::new();
 
-            if first_name.is_empty() && last_name.is_empty() {
-                // Silently skip blank rows
-                continue;
-            }
-            if first_name.is_empty() || last_name.is_empty() {
+        for row in rows {
+            let exists: bool = sqlx::query_scalar(&format!(
+                "SELECT EXISTS(SELECT 1 FROM {schema}.children
+                               WHERE lower(first_name) = lower($1) AND lower(last_name) = lower($2)
+                                 AND birth_date = $3)"
+            ))
+            .bind(&row.first_name)
+            .bind(&row.last_name)
+            .bind(row.birth_date)
+            .fetch_one(pool)
+            .await?;
+            if exists {
                 result.skipped_rows.push(ImportRowError {
-                    row: row_num,
-                    reason: "Prénom ou Nom manquant".to_string(),
+                    row: row.row,
+                    reason: format!("{} {} existe déjà", row.first_name, row.last_name),
                 });
                 continue;
             }
 
-            // Parse birth_date
-            let birth_date_str = get(idx_naissance);
-            let birth_date = parse_date(&birth_date_str);
-            let birth_date = match birth_date {
-                Some(d) => d,
-                None => {
-                    result.skipped_rows.push(ImportRowError {
-                        row: row_num,
-                        reason: format!(
-                            "Date de naissance invalide: '{birth_date_str}' (attendu AAAA-MM-JJ ou JJ/MM/AAAA)"
-                        ),
-                    });
-                    continue;
-                }
-            };
-
-            // Parse optional start_date
-            let start_date_str = get(idx_debut);
-            let start_date = if start_date_str.is_empty() {
-                None
-            } else {
-                parse_date(&start_date_str)
-            };
+            // Rows past the plan's limit are reported like invalid ones; a dry run
+            // counts the children it would already have added.
+            let adding = if dry_run { result.created_children as i64 + 1 } else { 1 };
+            if let Err(e) = EntitlementService::ensure_can_add(pool, tenant, Resource::Children, adding).await {
+                let Some(limit) = e.downcast_ref::<EntitlementError>() else {
+                    return Err(e);
+                };
+                result.skipped_rows.push(ImportRowError { row: row.row, reason: limit.to_string() });
+                continue;
+            }
 
-            // Parse schedule_days (comma-separated ints 1–5)
-            let jours_str = get(idx_jours);
-            let schedule_days: Option<Vec<i32>> = if jours_str.is_empty() {
-                None
-            } else {
-                let days: Vec<i32> = jours_str
-                    .split(',')
-                    .filter_map(|s| s.trim().parse::<i32>().ok())
-                    .filter(|&d| (1..=7).contains(&d))
-                    .collect();
-                if days.is_empty() { None } else { Some(days) }
-            };
+            result.created_children += 1;
+            result.added_pending_parents += row.parents.len();
 
-            // Resolve or create group
-            let groupe_name = get(idx_groupe);
-            let group_id: Option<Uuid> = if groupe_name.is_empty() {
-                None
-            } else {
-                let key = groupe_name.to_lowercase();
-                if let Some(&id) = group_cache.get(&key) {
-                    Some(id)
-                } else {
-                    // Try to find existing group (case-insensitive)
-                    let schema = schema_name(tenant);
-                    let existing: Option<Uuid> = sqlx::query_scalar(&format!(
-                        "SELECT id FROM {schema}.groups WHERE lower(name) = lower($1) LIMIT 1"
+            if dry_run {
+                for (email, _) in &row.parents {
+                    if invitations.contains_key(email) {
+                        continue;
+                    }
+                    let already_invited: bool = sqlx::query_scalar(&format!(
+                        "SELECT EXISTS(SELECT 1 FROM {schema}.invitation_tokens WHERE email = $1 AND used = FALSE)"
                     ))
-                    .bind(&groupe_name)
-                    .fetch_optional(pool)
-                    .await?;
-
-                    let id = if let Some(id) = existing {
-                        id
-                    } else {
-                        let g = GroupService::create(
-                            pool,
-                            tenant,
-                            &CreateGroupRequest {
-                                name: groupe_name.clone(),
-                                description: None,
-                                color: None,
-                            },
-                        )
-                        .await?;
-                        result.created_groups += 1;
-                        g.id
-                    };
-                    group_cache.insert(key, id);
-                    Some(id)
+                    .bind(email)
+                    .fetch_one(pool)
+                    .await
+                    .unwrap_or(false);
+                    if !already_invited {
+                        result.invited_parents += 1;
+                    }
+                    invitations.insert(email.clone(), None);
                 }
-            };
+                continue;
+            }
 
-            let notes = get(idx_notes);
-            let notes = if notes.is_empty() { None } else { Some(notes) };
+            let group_id = row
+                .group_name
+                .as_deref()
+                .and_then(|name| group_cache.get(&name.to_lowercase()).copied().flatten());
 
-            // Create child
             let child = ChildService::create(
                 pool,
                 tenant,
                 &CreateChildRequest {
-                    first_name,
-                    last_name,
-                    birth_date,
+                    first_name: row.first_name,
+                    last_name: row.last_name,
+                    birth_date: row.birth_date,
                     group_id,
-                    notes,
-                    start_date,
-                    schedule_days,
+                    notes: row.notes,
+                    start_date: row.start_date,
+                    schedule_days: row.schedule_days,
+                    allergies: None,
                 },
             )
             .await?;
-            result.created_children += 1;
-
-            // Add pending parents + create invitations
-            let schema = schema_name(tenant);
-            for (email_idx, rel_idx) in [
-                (idx_parent1_email, idx_parent1_rel),
-                (idx_parent2_email, idx_parent2_rel),
-            ] {
-                let email = get(email_idx);
-                if email.is_empty() || !email.contains('@') {
-                    continue;
-                }
-                let relationship = {
-                    let r = get(rel_idx);
-                    if r.is_empty() { "parent".to_string() } else { r }
-                };
 
+            for (email, relationship) in row.parents {
                 // Add to pending parents (for journal delivery before registration)
                 ChildService::assign_pending_parent(
                     pool,
                     tenant,
                     child.id,
-                    &AssignPendingParentRequest {
-                        email: email.clone(),
-                        relationship: relationship.clone(),
-                    },
+                    &AssignPendingParentRequest { email: email.clone(), relationship },
                 )
                 .await?;
-                result.added_pending_parents += 1;
 
                 // Create invitation token so parent appears in "utilisateurs en attente"
-                // Skip if an unused invitation already exists for this email
-                let already_invited: bool = sqlx::query_scalar(&format!(
-                    "SELECT EXISTS(SELECT 1 FROM {schema}.invitation_tokens WHERE email = $1 AND used = FALSE)"
-                ))
-                .bind(&email)
-                .fetch_one(pool)
-                .await
-                .unwrap_or(false);
-
-                if !already_invited {
-                    use rand::Rng;
-                    let token: String = rand::thread_rng()
-                        .sample_iter(&rand::distributions::Alphanumeric)
-                        .take(48)
-                        .map(char::from)
-                        .collect();
-                    let expires_at = Utc::now() + chrono::Duration::days(7);
-
-                    let inv_id: Option<Uuid> = sqlx::query_scalar(&format!(
-                        "INSERT INTO {schema}.invitation_tokens (email, token, role, invited_by, expires_at)
-                         VALUES ($1, $2, 'parent'::\"{schema}\".user_role, $3, $4)
-                         ON CONFLICT DO NOTHING RETURNING id"
-                    ))
-                    .bind(&email)
-                    .bind(&token)
-                    .bind(invited_by)
-                    .bind(expires_at)
-                    .fetch_optional(pool)
-                    .await
-                    .unwrap_or(None);
-
-                    if let Some(inv_id) = inv_id {
-                        // Link invitation to this child
-                        let _ = sqlx::query(&format!(
-                            "INSERT INTO {schema}.child_invitations (child_id, invitation_token_id)
-                             VALUES ($1, $2) ON CONFLICT DO NOTHING"
+                // Skip if an unused invitation already existed before this import
+                let inv_id = match invitations.get(&email) {
+                    Some(inv_id) => *inv_id,
+                    None => {
+                        let already_invited: bool = sqlx::query_scalar(&format!(
+                            "SELECT EXISTS(SELECT 1 FROM {schema}.invitation_tokens WHERE email = $1 AND used = FALSE)"
                         ))
-                        .bind(child.id)
-                        .bind(inv_id)
-                        .execute(pool)
-                        .await;
-
-                        // Send invitation email if service is available (non-fatal)
-                        if tenant != "demo" {
-                            if let Some(ref svc) = email_svc {
-                                let (garderie_name, logo_url): (String, Option<String>) =
-                                    sqlx::query_as(
-                                        "SELECT name, logo_url FROM public.garderies WHERE slug = $1",
-                                    )
-                                    .bind(tenant)
-                                    .fetch_optional(pool)
-                                    .await
-                                    .ok()
-                                    .flatten()
-                                    .unwrap_or_else(|| (tenant.to_string(), None));
-
-                                let invite_url = crate::services::auth::build_tenant_invite_url(
-                                    base_url, tenant, &token,
-                                );
-                                let _ = svc
-                                    .send_invitation(
-                                        &email,
-                                        &invite_url,
-                                        &garderie_name,
-                                        "parent",
-                                        logo_url.as_deref().unwrap_or(""),
-                                    )
-                                    .await;
-                            }
-                        }
-
-                        result.invited_parents += 1;
+                        .bind(&email)
+                        .fetch_one(pool)
+                        .await
+                        .unwrap_or(false);
+
+                        let inv_id = if already_invited {
+                            None
+                        } else {
+                            Self::create_import_invitation(pool, &schema, &email, invited_by)
+                                .await
+                                .map(|(inv_id, token)| {
+                                    outgoing.push((email.clone(), token));
+                                    inv_id
+                                })
+                        };
+                        invitations.insert(email.clone(), inv_id);
+                        inv_id
                     }
+                };
+
+                if let Some(inv_id) = inv_id {
+                    // Link invitation to this child
+                    let _ = sqlx::query(&format!(
+                        "INSERT INTO {schema}.child_invitations (child_id, invitation_token_id)
+                         VALUES ($1, $2) ON CONFLICT DO NOTHING"
+                    ))
+                    .bind(child.id)
+                    .bind(inv_id)
+                    .execute(pool)
+                    .await;
                 }
             }
         }
+        result.invited_parents += outgoing.len();
+
+        // Send invitation emails if service is available (non-fatal)
+        if let Some(svc) = email_svc.filter(|_| !outgoing.is_empty() && tenant != "demo") {
+            let (garderie_name, branding) = BrandingService::for_email(pool, tenant).await;
+            let template = EmailTemplateService::override_for(pool, tenant, TEMPLATE_INVITATION, Locale::Fr).await;
+            let base_url = base_url.to_string();
+            let tenant = tenant.to_string();
+            tokio::spawn(TenantDb::scope(tenant.clone(), async move {
+                for (i, batch) in outgoing.chunks(INVITE_BATCH_SIZE).enumerate() {
+                    if i > 0 {
+                        tokio::time::sleep(std::time::Duration::from_secs(INVITE_BATCH_PAUSE_SECS)).await;
+                    }
+                    for (email, token) in batch {
+                        let invite_url =
+                            crate::services::auth::build_tenant_invite_url(&base_url, &tenant, token);
+                        if let Err(e) = svc
+                            .send_invitation(&tenant, email, &invite_url, &garderie_name, "parent", &branding, Locale::Fr, template.as_ref())
+                            .await
+                        {
+                            tracing::warn!("Import invitation to {email} failed: {e}");
+                        }
+                    }
+                }
+            }));
+        }
 
         Ok(result)
     }
 
+    /// Insert a 7-day parent invitation; returns its id and token.
+    async fn create_import_invitation(
+        pool: &PgPool,
+        schema: &str,
+        email: &str,
+        invited_by: Option<Uuid>,
+    ) -> Option<(Uuid, String)> {
+        use rand::Rng;
+        let token: String = rand::thread_rng()
+            .sample_iter(&rand::distributions::Alphanumeric)
+            .take(48)
+            .map(char::from)
+            .collect();
+        let expires_at = Utc::now() + chrono::Duration::days(7);
+
+        let inv_id: Option<Uuid> = sqlx::query_scalar(&format!(
+            "INSERT INTO {schema}.invitation_tokens (email, token, role, invited_by, expires_at)
+             VALUES ($1, $2, 'parent'::\"{schema}\".user_role, $3, $4)
+             ON CONFLICT DO NOTHING RETURNING id"
+        ))
+        .bind(email)
+        .bind(&token)
+        .bind(invited_by)
+        .bind(expires_at)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None);
+        inv_id.map(|id| (id, token))
+    }
+
     /// Export all active children as CSV bytes.
     /// Format matches the import template.
     pub async fn export_all_as_csv(pool: &PgPool, tenant: &str) -> anyhow::Result<Vec<u8>> {
@@ -790,7 +808,9 @@ impl ChildService {
         Ok(())
     }
 
-    /// Update child avatar photo_url and encryption metadata (iv, tag).
+    /// Update child avatar photo_url and encryption metadata (iv, tag, key version).
+    /// Returns the child and the photo it replaced, read under the same row lock so
+    /// that concurrent uploads each get back the file they superseded.
     pub async fn update_avatar(
         pool: &PgPool,
         tenant: &str,
@@ -798,21 +818,27 @@ impl ChildService {
         photo_url: &str,
         iv: Vec<u8>,
         tag: Vec<u8>,
-    ) -> anyhow::Result<Child> {
+        key_version: i32,
+    ) -> anyhow::Result<(Child, Option<String>)> {
+        use sqlx::{FromRow, Row};
+
         let schema = schema_name(tenant);
-        let child = sqlx::query_as::<_, Child>(&format!(
-            "UPDATE {schema}.children
-             SET photo_url = $1, avatar_iv = $2, avatar_tag = $3, updated_at = NOW()
-             WHERE id = $4
-             RETURNING *"
+        let row = sqlx::query(&format!(
+            "WITH old AS (SELECT id, photo_url FROM {schema}.children WHERE id = $4 FOR UPDATE)
+             UPDATE {schema}.children c
+             SET photo_url = $1, avatar_iv = $2, avatar_tag = $3, avatar_key_version = $5, updated_at = NOW()
+             FROM old
+             WHERE c.id = old.id
+             RETURNING c.*, old.photo_url AS previous_photo_url"
         ))
         .bind(photo_url)
         .bind(&iv)
         .bind(&tag)
         .bind(child_id)
+        .bind(key_version)
         .fetch_one(pool)
         .await?;
-        Ok(child)
+        Ok((Child::from_row(&row)?, row.try_get("previous_photo_url")?))
     }
 
     /// Delete child avatar: clear photo_url and encryption fields, return old photo_url.
@@ -823,10 +849,12 @@ impl ChildService {
     ) -> anyhow::Result<Option<String>> {
         let schema = schema_name(tenant);
         let old_url: Option<String> = sqlx::query_scalar(&format!(
-            "UPDATE {schema}.children
+            "WITH old AS (SELECT id, photo_url FROM {schema}.children WHERE id = $1 FOR UPDATE)
+             UPDATE {schema}.children c
              SET photo_url = NULL, avatar_iv = NULL, avatar_tag = NULL, updated_at = NOW()
-             WHERE id = $1
-             RETURNING photo_url"
+             FROM old
+             WHERE c.id = old.id
+             RETURNING old.photo_url"
         ))
         .bind(child_id)
         .fetch_optional(pool)
@@ -835,6 +863,15 @@ impl ChildService {
         Ok(old_url)
     }
 
+    /// The child whose profile photo is stored at `photo_url`.
+    pub async fn child_for_photo(pool: &PgPool, tenant: &str, photo_url: &str) -> anyhow::Result<Option<Uuid>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_scalar(&format!("SELECT id FROM {schema}.children WHERE photo_url = $1"))
+            .bind(photo_url)
+            .fetch_optional(pool)
+            .await?)
+    }
+
     /// Fetch avatar encryption metadata for serve_media fallback.
     /// Returns (photo_url, iv, tag) if avatar exists and is encrypted.
     pub async fn get_avatar_storage_path(
@@ -865,6 +902,7 @@ impl ChildService {
 impl Default for crate::models::child::ImportResult {
     fn default() -> Self {
         Self {
+            dry_run: false,
             created_groups: 0,
             created_children: 0,
             added_pending_parents: 0,
@@ -918,3 +956,209 @@ fn parse_date(s: &str) -> Option<NaiveDate> {
     }
     None
 }
+
+/// Read an .xlsx or CSV upload into rows of cells, header row first.
+fn read_import_file(bytes: Vec<u8>) -> anyhow::Result<Vec<Vec<String>>> {
+    if bytes.starts_with(b"PK") {
+        // xlsx (ZIP-based)
+        let cursor = Cursor::new(bytes);
+        let mut workbook: Xlsx<_> = open_workbook_from_rs(cursor)
+            .map_err(|e: calamine::XlsxError| ImportError::Unreadable(e.to_string()))?;
+        let sheet_names = workbook.sheet_names().to_vec();
+        let sheet_name = sheet_names
+            .first()
+            .cloned()
+            .ok_or(ImportError::NoSheet)?;
+        let range = workbook
+            .worksheet_range(&sheet_name)
+            .map_err(|e| ImportError::UnreadableSheet(e.to_string()))?;
+        Ok(range
+            .rows()
+            .map(|row| row.iter().map(cell_str).collect())
+            .collect())
+    } else {
+        // CSV (strip UTF-8 BOM if present)
+        let data = if bytes.starts_with(b"\xef\xbb\xbf") { &bytes[3..] } else { &bytes[..] };
+        let mut rdr = csv::ReaderBuilder::new()
+            .flexible(true)
+            .from_reader(data);
+        let mut rows: Vec<Vec<String>> = vec![];
+        // Push headers as first row
+        if let Ok(headers) = rdr.headers() {
+            rows.push(headers.iter().map(str::to_string).collect());
+        }
+        for record in rdr.records().flatten() {
+            rows.push(record.iter().map(str::to_string).collect());
+        }
+        Ok(rows)
+    }
+}
+
+/// Validate the rows of an import file. Returns the importable rows and the
+/// rejected ones with their reason; fails only if required columns are missing.
+fn parse_import_rows(all_rows: Vec<Vec<String>>) -> anyhow::Result<(Vec<ImportRow>, Vec<ImportRowError>)> {
+    let mut row_iter = all_rows.into_iter();
+
+    // Read header row and build column index map
+    let headers: Vec<String> = match row_iter.next() {
+        Some(row) => row.iter().map(|s| s.trim().to_lowercase()).collect(),
+        None => return Ok((vec![], vec![])),
+    };
+
+    fn col_idx(headers: &[String], names: &[&str]) -> Option<usize> {
+        headers.iter().position(|h| names.iter().any(|n| h == n))
+    }
+
+    let idx_groupe       = col_idx(&headers, &["groupe", "group"]);
+    let idx_prenom       = col_idx(&headers, &["prénom", "prenom", "first_name", "firstname"]);
+    let idx_nom          = col_idx(&headers, &["nom", "last_name", "lastname"]);
+    let idx_naissance    = col_idx(&headers, &["date de naissance", "birth_date", "naissance", "dob"]);
+    let idx_debut        = col_idx(&headers, &["date de début", "date de debut", "start_date", "debut"]);
+    let idx_jours        = col_idx(&headers, &["jours", "days", "schedule_days"]);
+    let idx_parent1_email = col_idx(&headers, &["email parent 1", "email_parent1", "parent1_email", "parent 1"]);
+    let idx_parent1_rel  = col_idx(&headers, &["relation parent 1", "relation_parent1", "parent1_relation"]);
+    let idx_parent2_email = col_idx(&headers, &["email parent 2", "email_parent2", "parent2_email", "parent 2"]);
+    let idx_parent2_rel  = col_idx(&headers, &["relation parent 2", "relation_parent2", "parent2_relation"]);
+    let idx_notes        = col_idx(&headers, &["notes", "note"]);
+
+    // Require at minimum prenom + nom + naissance columns
+    if idx_prenom.is_none() || idx_nom.is_none() || idx_naissance.is_none() {
+        return Err(ImportError::MissingColumns.into());
+    }
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+
+    for (data_row_idx, row) in row_iter.enumerate() {
+        let row_num = data_row_idx + 2; // 1-indexed, header is row 1
+
+        let get = |idx: Option<usize>| -> String {
+            idx.and_then(|i| row.get(i))
+                .map(|s| s.trim().to_string())
+                .unwrap_or_default()
+        };
+        let mut reject = |reason: String| errors.push(ImportRowError { row: row_num, reason });
+
+        let first_name = get(idx_prenom);
+        let last_name  = get(idx_nom);
+
+        if first_name.is_empty() && last_name.is_empty() {
+            // Silently skip blank rows
+            continue;
+        }
+        if first_name.is_empty() || last_name.is_empty() {
+            reject("Prénom ou Nom manquant".to_string());
+            continue;
+        }
+
+        // Parse birth_date
+        let birth_date_str = get(idx_naissance);
+        let Some(birth_date) = parse_date(&birth_date_str) else {
+            reject(format!(
+                "Date de naissance invalide: '{birth_date_str}' (attendu AAAA-MM-JJ ou JJ/MM/AAAA)"
+            ));
+            continue;
+        };
+
+        // Parse optional start_date
+        let start_date_str = get(idx_debut);
+        let start_date = if start_date_str.is_empty() {
+            None
+        } else {
+            match parse_date(&start_date_str) {
+                Some(d) => Some(d),
+                None => {
+                    reject(format!("Date de début invalide: '{start_date_str}'"));
+                    continue;
+                }
+            }
+        };
+
+        // Parse schedule_days (comma-separated ints 1–5)
+        let jours_str = get(idx_jours);
+        let schedule_days: Option<Vec<i32>> = if jours_str.is_empty() {
+            None
+        } else {
+            let days: Vec<i32> = jours_str
+                .split(',')
+                .filter_map(|s| s.trim().parse::<i32>().ok())
+                .filter(|&d| (1..=7).contains(&d))
+                .collect();
+            if days.is_empty() { None } else { Some(days) }
+        };
+
+        let mut parents = Vec::new();
+        let mut bad_email = None;
+        for (email_idx, rel_idx) in [
+            (idx_parent1_email, idx_parent1_rel),
+            (idx_parent2_email, idx_parent2_rel),
+        ] {
+            let email = get(email_idx).to_lowercase();
+            if email.is_empty() {
+                continue;
+            }
+            if !email.contains('@') {
+                bad_email = Some(email);
+                break;
+            }
+            let relationship = {
+                let r = get(rel_idx);
+                if r.is_empty() { "parent".to_string() } else { r }
+            };
+            parents.push((email, relationship));
+        }
+        if let Some(email) = bad_email {
+            reject(format!("Courriel parent invalide: '{email}'"));
+            continue;
+        }
+
+        let group_name = Some(get(idx_groupe)).filter(|g| !g.is_empty());
+        let notes = Some(get(idx_notes)).filter(|n| !n.is_empty());
+
+        rows.push(ImportRow {
+            row: row_num,
+            first_name,
+            last_name,
+            birth_date,
+            start_date,
+            schedule_days,
+            group_name,
+            notes,
+            parents,
+        });
+    }
+
+    Ok((rows, errors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(lines: &[&[&str]]) -> Vec<Vec<String>> {
+        lines.iter().map(|l| l.iter().map(|c| c.to_string()).collect()).collect()
+    }
+
+    #[test]
+    fn import_rows_report_errors_per_row() {
+        let (ok, errors) = parse_import_rows(rows(&[
+            &["Prénom", "Nom", "Date de naissance", "Groupe", "Email parent 1"],
+            &["Léa", "Tremblay", "2022-03-01", "Poussins", "Parent@Example.com"],
+            &["", "", "", "", ""],
+            &["Noah", "", "2021-05-10", "", ""],
+            &["Zoé", "Roy", "31/02/2022", "", ""],
+            &["Émile", "Roy", "15/06/2021", "", "pas-un-courriel"],
+        ]))
+        .unwrap();
+
+        assert_eq!(ok.len(), 1);
+        assert_eq!(ok[0].group_name.as_deref(), Some("Poussins"));
+        assert_eq!(ok[0].parents, vec![("parent@example.com".to_string(), "parent".to_string())]);
+        assert_eq!(errors.iter().map(|e| e.row).collect::<Vec<_>>(), vec![4, 5, 6]);
+    }
+
+    #[test]
+    fn import_requires_name_and_birth_date_columns() {
+        assert!(parse_import_rows(rows(&[&["Prénom", "Groupe"]])).is_err());
+    }
+}
@@ -0,0 +1,371 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    db::{
+        tenant::{provision_tenant_schema_in, schema_name},
+        tenant_db::TenantDb,
+    },
+    models::tenant::{CloneGarderieRequest, CloneSummary, Garderie, PlanType, CLONE_PARTS},
+    services::{
+        documents::{remove_files, store_file},
+        encryption::{self, KeyRing},
+    },
+};
+
+/// Weeks of menus copied; they repeat as a cycle from the Monday after the clone.
+const MENU_WEEKS: i64 = 4;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CloneError {
+    #[error("Garderie source introuvable")]
+    SourceNotFound,
+    #[error("Cet identifiant est déjà pris")]
+    SlugTaken,
+    #[error("Élément à copier inconnu : {0} (groups, documents ou menus)")]
+    UnknownPart(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Parts {
+    groups: bool,
+    documents: bool,
+    menus: bool,
+}
+
+impl Parts {
+    fn parse(include: Option<&[String]>) -> Result<Self, CloneError> {
+        let Some(include) = include else {
+            return Ok(Self { groups: true, documents: true, menus: true });
+        };
+        if let Some(unknown) = include.iter().find(|p| !CLONE_PARTS.contains(&p.as_str())) {
+            return Err(CloneError::UnknownPart(unknown.clone()));
+        }
+        let has = |part: &str| include.iter().any(|p| p == part);
+        Ok(Self { groups: has("groups"), documents: has("documents"), menus: has("menus") })
+    }
+}
+
+/// Dates of the source menus to copy (from the Monday `MENU_WEEKS - 1` weeks back
+/// to the end of the current week) and how many days to move them forward so the
+/// cycle starts next Monday.
+fn menu_window(today: NaiveDate) -> (NaiveDate, NaiveDate, i64) {
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let start = monday - Duration::weeks(MENU_WEEKS - 1);
+    (start, monday + Duration::weeks(1), MENU_WEEKS * 7)
+}
+
+/// Visibility of a copied template: group documents follow their copied group,
+/// anything tied to a child or to a group left behind becomes private.
+fn template_visibility(visibility: &str, group: Option<Uuid>) -> (&str, Option<Uuid>) {
+    match (visibility, group) {
+        ("public", _) => ("public", None),
+        ("group", Some(group)) => ("group", Some(group)),
+        _ => ("private", None),
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct GroupRow {
+    id: Uuid,
+    name: String,
+    description: Option<String>,
+    color: Option<String>,
+    allow_parent_replies: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct TemplateRow {
+    title: String,
+    category: String,
+    original_filename: String,
+    storage_path: String,
+    content_type: String,
+    visibility: String,
+    group_id: Option<Uuid>,
+    requires_signature: bool,
+    is_encrypted: bool,
+    encryption_iv: Option<Vec<u8>>,
+    encryption_tag: Option<Vec<u8>>,
+    key_version: i32,
+}
+
+#[derive(sqlx::FromRow)]
+struct MenuRow {
+    id: Uuid,
+    date: NaiveDate,
+    menu: Option<String>,
+    collation_matin: Option<String>,
+    diner: Option<String>,
+    collation_apres_midi: Option<String>,
+}
+
+pub struct CloneService;
+
+impl CloneService {
+    /// Create a garderie with its administrator and copy the source's structure into
+    /// it: active groups, documents marked as templates, and its recent menus.
+    /// Everything is created together or not at all.
+    pub async fn clone(
+        pool: &PgPool,
+        media_dir: &str,
+        keys: &KeyRing,
+        source: &str,
+        req: &CloneGarderieRequest,
+    ) -> anyhow::Result<CloneSummary> {
+        TenantDb::require_own_schema()?;
+        let parts = Parts::parse(req.include.as_deref())?;
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM public.garderies WHERE slug = $1)")
+            .bind(source)
+            .fetch_one(pool)
+            .await?;
+        if !exists {
+            return Err(CloneError::SourceNotFound.into());
+        }
+        let password_hash = bcrypt::hash(&req.admin.password, 12)?;
+
+        let g = &req.garderie;
+        let mut tx = pool.begin().await?;
+        let garderie = sqlx::query_as::<_, Garderie>(
+            "INSERT INTO public.garderies (slug, name, address_line1, city, province, postal_code, phone, email, plan)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+             RETURNING *",
+        )
+        .bind(&g.slug)
+        .bind(&g.name)
+        .bind(&g.address_line1)
+        .bind(&g.city)
+        .bind(&g.province)
+        .bind(&g.postal_code)
+        .bind(&g.phone)
+        .bind(&g.email)
+        .bind(g.plan.clone().unwrap_or(PlanType::Free))
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db) if db.is_unique_violation() => CloneError::SlugTaken.into(),
+            e => anyhow::Error::from(e),
+        })?;
+        provision_tenant_schema_in(&mut tx, &g.slug).await?;
+
+        let to = schema_name(&g.slug);
+        let admin_id: Uuid = sqlx::query_scalar(&format!(
+            r#"INSERT INTO "{to}".users (email, password_hash, first_name, last_name, role)
+               VALUES ($1, $2, $3, $4, 'admin_garderie'::"{to}".user_role)
+               RETURNING id"#
+        ))
+        .bind(req.admin.email.trim().to_lowercase())
+        .bind(&password_hash)
+        .bind(req.admin.first_name.trim())
+        .bind(req.admin.last_name.trim())
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let groups = if parts.groups { Self::copy_groups(pool, &mut tx, source, &g.slug).await? } else { HashMap::new() };
+        let menus = if parts.menus {
+            Self::copy_menus(pool, &mut tx, source, &g.slug, admin_id, Utc::now().date_naive()).await?
+        } else {
+            0
+        };
+
+        let mut written = Vec::new();
+        let result = async {
+            let documents = if parts.documents {
+                Self::copy_templates(pool, &mut tx, media_dir, keys, source, &g.slug, admin_id, &groups, &mut written)
+                    .await?
+            } else {
+                0
+            };
+            tx.commit().await?;
+            anyhow::Ok(documents)
+        }
+        .await;
+        let documents = match result {
+            Ok(documents) => documents,
+            Err(e) => {
+                remove_files(media_dir, &written).await;
+                return Err(e);
+            }
+        };
+
+        tracing::info!(
+            "Garderie {} cloned from {source}: {} groups, {documents} documents, {menus} menus",
+            g.slug,
+            groups.len()
+        );
+        Ok(CloneSummary { garderie, admin_id, groups: groups.len(), documents, menus })
+    }
+
+    /// Copy the active groups and return the new id of each.
+    async fn copy_groups(
+        pool: &PgPool,
+        conn: &mut PgConnection,
+        source: &str,
+        target: &str,
+    ) -> anyhow::Result<HashMap<Uuid, Uuid>> {
+        let (from, to) = (schema_name(source), schema_name(target));
+        let rows: Vec<GroupRow> = sqlx::query_as(&format!(
+            "SELECT id, name, description, color, allow_parent_replies
+             FROM {from}.groups WHERE archived_at IS NULL ORDER BY name"
+        ))
+        .fetch_all(pool)
+        .await?;
+
+        let mut ids = HashMap::with_capacity(rows.len());
+        for GroupRow { id, name, description, color, allow_parent_replies } in rows {
+            let new_id: Uuid = sqlx::query_scalar(&format!(
+                "INSERT INTO {to}.groups (name, description, color, allow_parent_replies)
+                 VALUES ($1, $2, $3, $4) RETURNING id"
+            ))
+            .bind(name)
+            .bind(description)
+            .bind(color)
+            .bind(allow_parent_replies)
+            .fetch_one(&mut *conn)
+            .await?;
+            ids.insert(id, new_id);
+        }
+        Ok(ids)
+    }
+
+    /// Copy the last `MENU_WEEKS` weeks of menus, dishes and allergens included, as
+    /// a cycle starting next Monday.
+    async fn copy_menus(
+        pool: &PgPool,
+        conn: &mut PgConnection,
+        source: &str,
+        target: &str,
+        created_by: Uuid,
+        today: NaiveDate,
+    ) -> anyhow::Result<usize> {
+        let (from, to) = (schema_name(source), schema_name(target));
+        let (start, end, shift) = menu_window(today);
+        let menus: Vec<MenuRow> = sqlx::query_as(&format!(
+            "SELECT id, date, menu, collation_matin, diner, collation_apres_midi
+             FROM {from}.daily_menus WHERE date >= $1 AND date < $2 ORDER BY date"
+        ))
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+        .await?;
+
+        for menu in &menus {
+            let new_id: Uuid = sqlx::query_scalar(&format!(
+                "INSERT INTO {to}.daily_menus (date, menu, collation_matin, diner, collation_apres_midi, created_by)
+                 VALUES ($1, $2, $3, $4, $5, $6) RETURNING id"
+            ))
+            .bind(menu.date + Duration::days(shift))
+            .bind(&menu.menu)
+            .bind(&menu.collation_matin)
+            .bind(&menu.diner)
+            .bind(&menu.collation_apres_midi)
+            .bind(created_by)
+            .fetch_one(&mut *conn)
+            .await?;
+            sqlx::query(&format!(
+                "INSERT INTO {to}.menu_items (menu_id, meal, name, allergens, position)
+                 SELECT $1, meal, name, allergens, position FROM {from}.menu_items WHERE menu_id = $2"
+            ))
+            .bind(new_id)
+            .bind(menu.id)
+            .execute(&mut *conn)
+            .await?;
+        }
+        Ok(menus.len())
+    }
+
+    /// Copy the documents marked as templates, re-encrypting their files with the
+    /// new garderie's key. Written files are pushed to `written` for cleanup.
+    #[allow(clippy::too_many_arguments)]
+    async fn copy_templates(
+        pool: &PgPool,
+        conn: &mut PgConnection,
+        media_dir: &str,
+        keys: &KeyRing,
+        source: &str,
+        target: &str,
+        uploader_id: Uuid,
+        groups: &HashMap<Uuid, Uuid>,
+        written: &mut Vec<String>,
+    ) -> anyhow::Result<usize> {
+        let (from, to) = (schema_name(source), schema_name(target));
+        let templates: Vec<TemplateRow> = sqlx::query_as(&format!(
+            "SELECT title, category::TEXT AS category, original_filename, storage_path, content_type,
+                    visibility::TEXT AS visibility, group_id, requires_signature,
+                    is_encrypted, encryption_iv, encryption_tag, key_version
+             FROM {from}.documents WHERE is_template = TRUE ORDER BY created_at"
+        ))
+        .fetch_all(pool)
+        .await?;
+
+        for doc in &templates {
+            let mut bytes = tokio::fs::read(PathBuf::from(media_dir).join(&doc.storage_path)).await?;
+            if doc.is_encrypted {
+                let (Some(iv), Some(tag)) = (&doc.encryption_iv, &doc.encryption_tag) else {
+                    anyhow::bail!("missing encryption IV or tag for {}", doc.storage_path);
+                };
+                bytes = encryption::decrypt_file(&bytes, iv, tag, &keys.tenant_key(doc.key_version, source)?)?;
+            }
+            let stored = store_file(media_dir, target, keys, &bytes).await?;
+            written.push(stored.storage_path.clone());
+
+            let (visibility, group_id) =
+                template_visibility(&doc.visibility, doc.group_id.and_then(|g| groups.get(&g).copied()));
+            sqlx::query(&format!(
+                "INSERT INTO {to}.documents
+                 (uploader_id, title, category, original_filename, storage_path, content_type, size_bytes, group_id,
+                  visibility, is_encrypted, encryption_iv, encryption_tag, requires_signature, key_version)
+                 VALUES ($1, $2, $3::\"{to}\".doc_category, $4, $5, $6, $7, $8, $9::\"{to}\".doc_visibility,
+                         TRUE, $10, $11, $12, $13)"
+            ))
+            .bind(uploader_id)
+            .bind(&doc.title)
+            .bind(&doc.category)
+            .bind(&doc.original_filename)
+            .bind(&stored.storage_path)
+            .bind(&doc.content_type)
+            .bind(stored.size_bytes)
+            .bind(group_id)
+            .bind(visibility)
+            .bind(&stored.iv)
+            .bind(&stored.tag)
+            .bind(doc.requires_signature)
+            .bind(stored.key_version)
+            .execute(&mut *conn)
+            .await?;
+        }
+        Ok(templates.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn menus_of_the_last_weeks_start_again_next_monday() {
+        // Thursday 2026-10-15
+        let (start, end, shift) = menu_window(date("2026-10-15"));
+        assert_eq!(start, date("2026-09-21"));
+        assert_eq!(end, date("2026-10-19"));
+        assert_eq!(start + Duration::days(shift), date("2026-10-19"));
+        assert_eq!(menu_window(date("2026-10-19")).0, date("2026-09-28"));
+    }
+
+    #[test]
+    fn include_lists_known_parts_only() {
+        assert_eq!(Parts::parse(None).unwrap(), Parts { groups: true, documents: true, menus: true });
+        let only_menus = Parts::parse(Some(&["menus".to_string()])).unwrap();
+        assert_eq!(only_menus, Parts { groups: false, documents: false, menus: true });
+        assert!(matches!(Parts::parse(Some(&["children".to_string()])), Err(CloneError::UnknownPart(_))));
+        assert_eq!(template_visibility("child", None), ("private", None));
+        assert_eq!(template_visibility("group", None), ("private", None));
+    }
+}
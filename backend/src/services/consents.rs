@@ -0,0 +1,175 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::{
+        consent::{
+            ChildConsent, ChildWithoutPhotoConsent, ComplianceReport, ConsentRecord, ParentWithoutConsent,
+            CURRENT_POLICY_VERSION,
+        },
+        user::ParentConsentPayload,
+    },
+};
+
+/// SQL expression telling whether photos of the child `child` (a column or placeholder)
+/// may be shared. A consent set for the child wins; otherwise the latest record of each
+/// linked parent counts, and a single refusal is enough. No answer at all means no.
+pub fn photo_consent_sql(schema: &str, child: &str) -> String {
+    format!(
+        "COALESCE(
+            (SELECT cc.photos_accepted FROM \"{schema}\".child_consents cc WHERE cc.child_id = {child}),
+            (SELECT bool_and(latest.photos_accepted)
+             FROM \"{schema}\".child_parents cp
+             JOIN LATERAL (
+                 SELECT cr.photos_accepted FROM \"{schema}\".consent_records cr
+                 WHERE cr.user_id = cp.user_id
+                 ORDER BY cr.accepted_at DESC LIMIT 1
+             ) latest ON TRUE
+             WHERE cp.child_id = {child}),
+            FALSE)"
+    )
+}
+
+pub struct ConsentService;
+
+impl ConsentService {
+    /// Store the consent a parent gave (at registration or when the policy changes).
+    pub async fn record(
+        pool: &PgPool,
+        tenant: &str,
+        user_id: Uuid,
+        consent: &ParentConsentPayload,
+        ip_address: &str,
+    ) -> anyhow::Result<ConsentRecord> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, ConsentRecord>(&format!(
+            "INSERT INTO {schema}.consent_records
+                (user_id, privacy_accepted, photos_accepted, accepted_at, policy_version, language, ip_address)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             RETURNING id, privacy_accepted, photos_accepted, accepted_at, policy_version, language"
+        ))
+        .bind(user_id)
+        .bind(consent.privacy_accepted)
+        .bind(consent.photos_accepted)
+        .bind(consent.accepted_at)
+        .bind(&consent.policy_version)
+        .bind(consent.language.as_deref().unwrap_or("fr"))
+        .bind(ip_address)
+        .fetch_one(pool)
+        .await?)
+    }
+
+    /// Photo consent in force for a child; None if the child does not exist.
+    pub async fn child_consent(pool: &PgPool, tenant: &str, child_id: Uuid) -> anyhow::Result<Option<ChildConsent>> {
+        let schema = schema_name(tenant);
+        let effective = photo_consent_sql(&schema, "c.id");
+        Ok(sqlx::query_as::<_, ChildConsent>(&format!(
+            "SELECT c.id AS child_id,
+                    {effective} AS photos_accepted,
+                    CASE
+                        WHEN cc.child_id IS NOT NULL THEN 'child'
+                        WHEN EXISTS (
+                            SELECT 1 FROM {schema}.child_parents cp
+                            JOIN {schema}.consent_records cr ON cr.user_id = cp.user_id
+                            WHERE cp.child_id = c.id
+                        ) THEN 'parent'
+                        ELSE 'none'
+                    END AS source,
+                    cc.updated_by, cc.updated_at
+             FROM {schema}.children c
+             LEFT JOIN {schema}.child_consents cc ON cc.child_id = c.id
+             WHERE c.id = $1"
+        ))
+        .bind(child_id)
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    /// Set the photo consent of one child, overriding its parents' records.
+    pub async fn set_child_consent(
+        pool: &PgPool,
+        tenant: &str,
+        child_id: Uuid,
+        photos_accepted: bool,
+        updated_by: Uuid,
+    ) -> anyhow::Result<Option<ChildConsent>> {
+        let schema = schema_name(tenant);
+        sqlx::query(&format!(
+            "INSERT INTO {schema}.child_consents (child_id, photos_accepted, updated_by)
+             SELECT id, $2, $3 FROM {schema}.children WHERE id = $1
+             ON CONFLICT (child_id) DO UPDATE
+             SET photos_accepted = EXCLUDED.photos_accepted, updated_by = EXCLUDED.updated_by, updated_at = NOW()"
+        ))
+        .bind(child_id)
+        .bind(photos_accepted)
+        .bind(updated_by)
+        .execute(pool)
+        .await?;
+        Self::child_consent(pool, tenant, child_id).await
+    }
+
+    /// Parents missing a consent for the current policy and children whose photos
+    /// may not be shared.
+    pub async fn compliance_report(pool: &PgPool, tenant: &str) -> anyhow::Result<ComplianceReport> {
+        let schema = schema_name(tenant);
+
+        let latest = format!(
+            "SELECT DISTINCT ON (cr.user_id) cr.user_id, cr.privacy_accepted, cr.photos_accepted, cr.policy_version
+             FROM {schema}.consent_records cr
+             ORDER BY cr.user_id, cr.accepted_at DESC"
+        );
+
+        let (parents_total, parents_consented, parents_photos_accepted): (i64, i64, i64) =
+            sqlx::query_as(&format!(
+                "SELECT COUNT(*),
+                        COUNT(*) FILTER (WHERE l.privacy_accepted AND l.policy_version = $1),
+                        COUNT(*) FILTER (WHERE l.photos_accepted)
+                 FROM {schema}.users u
+                 LEFT JOIN ({latest}) l ON l.user_id = u.id
+                 WHERE u.role = 'parent' AND u.is_active = TRUE"
+            ))
+            .bind(CURRENT_POLICY_VERSION)
+            .fetch_one(pool)
+            .await?;
+
+        let parents_without_consent = sqlx::query_as::<_, ParentWithoutConsent>(&format!(
+            "SELECT u.id, CONCAT(u.first_name, ' ', u.last_name) AS name, u.email, l.policy_version
+             FROM {schema}.users u
+             LEFT JOIN ({latest}) l ON l.user_id = u.id
+             WHERE u.role = 'parent' AND u.is_active = TRUE
+               AND (l.user_id IS NULL OR NOT l.privacy_accepted OR l.policy_version <> $1)
+             ORDER BY u.last_name, u.first_name"
+        ))
+        .bind(CURRENT_POLICY_VERSION)
+        .fetch_all(pool)
+        .await?;
+
+        let effective = photo_consent_sql(&schema, "c.id");
+        let children_total: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM {schema}.children c WHERE c.is_active = TRUE"
+        ))
+        .fetch_one(pool)
+        .await?;
+
+        let children_without_photo_consent = sqlx::query_as::<_, ChildWithoutPhotoConsent>(&format!(
+            "SELECT c.id, c.first_name, c.last_name, c.group_id
+             FROM {schema}.children c
+             WHERE c.is_active = TRUE AND NOT {effective}
+             ORDER BY c.last_name, c.first_name"
+        ))
+        .fetch_all(pool)
+        .await?;
+
+        Ok(ComplianceReport {
+            policy_version: CURRENT_POLICY_VERSION,
+            parents_total,
+            parents_consented,
+            parents_photos_accepted,
+            children_total,
+            children_photos_accepted: children_total - children_without_photo_consent.len() as i64,
+            parents_without_consent,
+            children_without_photo_consent,
+        })
+    }
+}
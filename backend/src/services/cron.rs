@@ -7,6 +7,8 @@
 /// - Messages: is_deleted + 2 years → hard-delete
 /// - Admin/financial docs: is_deleted + 7 years → hard-delete
 /// - Audit logs: created + 90 days → hard-delete
+/// - Login history: created + 90 days → hard-delete
+/// - Email log: created + 90 days → hard-delete
 /// - Technical logs: created + 90 days → hard-delete
 
 use chrono::{Duration, Utc};
@@ -117,6 +119,40 @@ impl CronService {
             );
         }
 
+        // 6. Login history: created + 90 days
+        let login_count = sqlx::query(&format!(
+            "DELETE FROM {schema}.login_events WHERE created_at < $1"
+        ))
+        .bind(audit_expiry)
+        .execute(pool)
+        .await
+        .map(|r| r.rows_affected())
+        .unwrap_or(0);
+
+        if login_count > 0 {
+            tracing::info!(
+                "Purged {} login events from {schema} (older than 90 days)",
+                login_count
+            );
+        }
+
+        // 7. Email log: created + 90 days
+        let email_count = sqlx::query(&format!(
+            "DELETE FROM {schema}.email_log WHERE created_at < $1"
+        ))
+        .bind(audit_expiry)
+        .execute(pool)
+        .await
+        .map(|r| r.rows_affected())
+        .unwrap_or(0);
+
+        if email_count > 0 {
+            tracing::info!(
+                "Purged {} email log entries from {schema} (older than 90 days)",
+                email_count
+            );
+        }
+
         Ok(())
     }
 
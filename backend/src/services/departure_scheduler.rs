@@ -0,0 +1,63 @@
+use chrono::{Local, Timelike};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::config::Config;
+use crate::db::tenant_db::TenantDb;
+use crate::services::backup_scheduler::secs_until;
+use crate::services::departures::DepartureService;
+use crate::services::email::EmailService;
+
+/// Local time of the nightly pass (seconds after midnight): 0:30.
+const DEPARTURES_AT: u32 = 30 * 60;
+
+/// Spawn a background task that, every night, deactivates the children leaving
+/// that day and emails their parents the final export link, then closes the
+/// parents' access once it has run out.
+pub fn start(pool: PgPool, email: Option<Arc<EmailService>>, config: Arc<Config>) {
+    tokio::spawn(async move {
+        loop {
+            let now = Local::now();
+            let secs_today = now.hour() * 3600 + now.minute() * 60 + now.second();
+            tokio::time::sleep(tokio::time::Duration::from_secs(secs_until(secs_today, DEPARTURES_AT))).await;
+            let Some(_job) = crate::services::worker::job().await else {
+                break;
+            };
+
+            let tenants: Vec<String> = match sqlx::query_scalar(
+                "SELECT slug FROM public.garderies WHERE is_active = TRUE AND slug != 'demo'",
+            )
+            .fetch_all(&pool)
+            .await
+            {
+                Ok(rows) => rows,
+                Err(e) => {
+                    warn!("Departure scheduler: failed to query tenants: {e}");
+                    continue;
+                }
+            };
+
+            let today = Local::now().date_naive();
+            for slug in &tenants {
+                TenantDb::scope(slug.clone(), async {
+                    match DepartureService::apply_due(&pool, email.as_deref(), &config.app_base_url, slug, today).await {
+                        Ok(applied) if !applied.is_empty() => {
+                            info!("Departure scheduler: {} child(ren) left '{slug}'", applied.len())
+                        }
+                        Ok(_) => {}
+                        Err(e) => warn!("Departure scheduler: departures failed for '{slug}': {e}"),
+                    }
+                    match DepartureService::end_parent_access(&pool, slug, today).await {
+                        Ok(parents) if !parents.is_empty() => {
+                            info!("Departure scheduler: {} parent account(s) closed in '{slug}'", parents.len())
+                        }
+                        Ok(_) => {}
+                        Err(e) => warn!("Departure scheduler: parent access not closed for '{slug}': {e}"),
+                    }
+                })
+                .await;
+            }
+        }
+    });
+}
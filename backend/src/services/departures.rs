@@ -0,0 +1,493 @@
+use chrono::{Duration, NaiveDate};
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::departure::{
+        ChildDeparture, DepartureArchive, DepartureExport, ScheduleDepartureRequest, DEFAULT_PARENT_ACCESS_DAYS,
+    },
+    services::{
+        branding::BrandingService,
+        document_shares::{hash_token, is_token},
+        email::EmailService,
+        media::MediaService,
+        media_urls::MediaUrlSigner,
+        notification_consumer::app_url,
+    },
+};
+
+const DEPARTURE_COLS: &str = "id AS child_id, first_name, last_name, is_active, departed_on, parent_access_until,
+     departure_applied_at, parent_access_ended_at";
+
+const EXPORT_COLS: &str = "id, child_id, expires_at, created_by, created_at, emailed_to, access_count, last_accessed_at";
+
+#[derive(Debug, thiserror::Error)]
+pub enum DepartureError {
+    #[error("Enfant introuvable")]
+    ChildNotFound,
+    #[error("Le départ de cet enfant a déjà eu lieu")]
+    AlreadyDeparted,
+    #[error("Aucun départ n'est prévu pour cet enfant")]
+    NotScheduled,
+    #[error("Le départ de cet enfant n'a pas encore eu lieu")]
+    NotDeparted,
+    #[error("L'accès des parents à cet enfant a pris fin")]
+    AccessEnded,
+    #[error("Lien d'exportation introuvable")]
+    ExportNotFound,
+    #[error("Ce lien d'exportation a expiré")]
+    ExportExpired,
+}
+
+/// Last day parents can sign in after a departure on `departure_date`.
+fn parent_access_until(departure_date: NaiveDate, days: Option<i64>) -> NaiveDate {
+    departure_date + Duration::days(days.unwrap_or(DEFAULT_PARENT_ACCESS_DAYS))
+}
+
+/// Public address of a final export, on the garderie's subdomain.
+pub fn export_url(base: &str, tenant: &str, token: &str) -> String {
+    app_url(base, tenant, &format!("/api/departures/{token}"))
+}
+
+#[derive(sqlx::FromRow)]
+struct ExportRow {
+    child_id: Uuid,
+    expires_at: chrono::DateTime<chrono::Utc>,
+    expired: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct ArchiveChild {
+    first_name: String,
+    last_name: String,
+    birth_date: NaiveDate,
+    departed_on: Option<NaiveDate>,
+}
+
+/// Departure of a child: deactivation on the day, a final export link for the
+/// parents, and the end of their access once it runs out.
+pub struct DepartureService;
+
+impl DepartureService {
+    pub async fn get(pool: &PgPool, tenant: &str, child_id: Uuid) -> anyhow::Result<ChildDeparture> {
+        let schema = schema_name(tenant);
+        sqlx::query_as::<_, ChildDeparture>(&format!(
+            "SELECT {DEPARTURE_COLS} FROM {schema}.children WHERE id = $1 AND is_deleted = FALSE"
+        ))
+        .bind(child_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| DepartureError::ChildNotFound.into())
+    }
+
+    /// Set the departure date and the parents' access period. Applying it is left
+    /// to `apply_due`, right away for a date already reached.
+    pub async fn schedule(
+        pool: &PgPool,
+        tenant: &str,
+        child_id: Uuid,
+        req: &ScheduleDepartureRequest,
+    ) -> anyhow::Result<ChildDeparture> {
+        let schema = schema_name(tenant);
+        let until = parent_access_until(req.departure_date, req.parent_access_days);
+        let departure = sqlx::query_as::<_, ChildDeparture>(&format!(
+            "UPDATE {schema}.children
+             SET departed_on = $2, parent_access_until = $3, updated_at = NOW()
+             WHERE id = $1 AND is_deleted = FALSE AND departure_applied_at IS NULL
+             RETURNING {DEPARTURE_COLS}"
+        ))
+        .bind(child_id)
+        .bind(req.departure_date)
+        .bind(until)
+        .fetch_optional(pool)
+        .await?;
+        match departure {
+            Some(departure) => Ok(departure),
+            None => {
+                Self::get(pool, tenant, child_id).await?;
+                Err(DepartureError::AlreadyDeparted.into())
+            }
+        }
+    }
+
+    /// Cancel a departure that has not taken place yet. A child deactivated by
+    /// other means keeps their departure date for the retention periods.
+    pub async fn cancel(pool: &PgPool, tenant: &str, child_id: Uuid) -> anyhow::Result<ChildDeparture> {
+        let schema = schema_name(tenant);
+        let departure = sqlx::query_as::<_, ChildDeparture>(&format!(
+            "UPDATE {schema}.children
+             SET departed_on = CASE WHEN is_active THEN NULL ELSE LEAST(departed_on, CURRENT_DATE) END,
+                 parent_access_until = NULL, updated_at = NOW()
+             WHERE id = $1 AND is_deleted = FALSE AND departure_applied_at IS NULL
+               AND parent_access_until IS NOT NULL
+             RETURNING {DEPARTURE_COLS}"
+        ))
+        .bind(child_id)
+        .fetch_optional(pool)
+        .await?;
+        match departure {
+            Some(departure) => Ok(departure),
+            None => match Self::get(pool, tenant, child_id).await?.departure_applied_at {
+                Some(_) => Err(DepartureError::AlreadyDeparted.into()),
+                None => Err(DepartureError::NotScheduled.into()),
+            },
+        }
+    }
+
+    /// Deactivate the children whose departure date is reached, and email their
+    /// parents a final export link when email is configured.
+    pub async fn apply_due(
+        pool: &PgPool,
+        email_svc: Option<&EmailService>,
+        app_base_url: &str,
+        tenant: &str,
+        today: NaiveDate,
+    ) -> anyhow::Result<Vec<ChildDeparture>> {
+        let schema = schema_name(tenant);
+        let applied = sqlx::query_as::<_, ChildDeparture>(&format!(
+            "UPDATE {schema}.children
+             SET is_active = FALSE, departure_applied_at = NOW(), updated_at = NOW()
+             WHERE departure_applied_at IS NULL AND parent_access_until IS NOT NULL
+               AND departed_on <= $1 AND is_deleted = FALSE
+             RETURNING {DEPARTURE_COLS}"
+        ))
+        .bind(today)
+        .fetch_all(pool)
+        .await?;
+
+        if let Some(email_svc) = email_svc {
+            for departure in &applied {
+                let sent = match Self::create_export(pool, tenant, departure.child_id, None).await {
+                    Ok((export, token)) => Self::email_export(pool, email_svc, app_base_url, tenant, &export, &token).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = sent {
+                    tracing::warn!("Final export of child {} in '{tenant}' not sent: {e}", departure.child_id);
+                }
+            }
+        }
+        Ok(applied)
+    }
+
+    /// Create a final export link, valid until the parents' access ends, and
+    /// return it with its token, which is not stored.
+    pub async fn create_export(
+        pool: &PgPool,
+        tenant: &str,
+        child_id: Uuid,
+        created_by: Option<Uuid>,
+    ) -> anyhow::Result<(DepartureExport, String)> {
+        let schema = schema_name(tenant);
+        let token = {
+            use rand::Rng;
+            hex::encode(rand::thread_rng().gen::<[u8; 32]>())
+        };
+        let export = sqlx::query_as::<_, DepartureExport>(&format!(
+            "INSERT INTO {schema}.departure_exports (child_id, token_sha256, expires_at, created_by)
+             SELECT id, $2, (parent_access_until + 1)::TIMESTAMPTZ, $3
+             FROM {schema}.children
+             WHERE id = $1 AND is_deleted = FALSE AND departure_applied_at IS NOT NULL
+               AND parent_access_until >= CURRENT_DATE
+             RETURNING {EXPORT_COLS}"
+        ))
+        .bind(child_id)
+        .bind(hash_token(&token))
+        .bind(created_by)
+        .fetch_optional(pool)
+        .await?;
+        match export {
+            Some(export) => Ok((export, token)),
+            None => match Self::get(pool, tenant, child_id).await?.departure_applied_at {
+                Some(_) => Err(DepartureError::AccessEnded.into()),
+                None => Err(DepartureError::NotDeparted.into()),
+            },
+        }
+    }
+
+    /// Email an export link to the child's active parents; returns how many got it.
+    pub async fn email_export(
+        pool: &PgPool,
+        email_svc: &EmailService,
+        app_base_url: &str,
+        tenant: &str,
+        export: &DepartureExport,
+        token: &str,
+    ) -> anyhow::Result<i32> {
+        let schema = schema_name(tenant);
+        let departure = Self::get(pool, tenant, export.child_id).await?;
+        let parents: Vec<(String, String)> = sqlx::query_as(&format!(
+            "SELECT u.email, CONCAT(u.first_name, ' ', u.last_name)
+             FROM {schema}.users u
+             JOIN {schema}.child_parents cp ON cp.user_id = u.id
+             WHERE cp.child_id = $1 AND u.is_active = TRUE"
+        ))
+        .bind(export.child_id)
+        .fetch_all(pool)
+        .await?;
+
+        let (garderie_name, branding) = BrandingService::for_email(pool, tenant).await;
+        let child_name = format!("{} {}", departure.first_name, departure.last_name);
+        let url = export_url(app_base_url, tenant, token);
+        let until = departure.parent_access_until.unwrap_or_else(|| export.expires_at.date_naive());
+        let mut sent = 0;
+        for (email, name) in &parents {
+            match email_svc
+                .send_departure_export(tenant, email, name, &child_name, &url, until, &garderie_name, &branding)
+                .await
+            {
+                Ok(()) => sent += 1,
+                Err(e) => tracing::warn!("Final export of child {} in '{tenant}' not emailed: {e}", export.child_id),
+            }
+        }
+
+        sqlx::query(&format!("UPDATE {schema}.departure_exports SET emailed_to = emailed_to + $2 WHERE id = $1"))
+            .bind(export.id)
+            .bind(sent)
+            .execute(pool)
+            .await?;
+        Ok(sent)
+    }
+
+    /// Export links of a child, newest first.
+    pub async fn exports(pool: &PgPool, tenant: &str, child_id: Uuid) -> anyhow::Result<Vec<DepartureExport>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, DepartureExport>(&format!(
+            "SELECT {EXPORT_COLS} FROM {schema}.departure_exports WHERE child_id = $1 ORDER BY created_at DESC"
+        ))
+        .bind(child_id)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Check a token and gather the child's journals and their own photos and
+    /// videos, with signed URLs on the garderie's subdomain.
+    pub async fn open(
+        pool: &PgPool,
+        tenant: &str,
+        signer: &MediaUrlSigner,
+        app_base_url: &str,
+        token: &str,
+    ) -> anyhow::Result<DepartureArchive> {
+        if !is_token(token) {
+            return Err(DepartureError::ExportNotFound.into());
+        }
+        let schema = schema_name(tenant);
+        let export = sqlx::query_as::<_, ExportRow>(&format!(
+            "SELECT child_id, expires_at, expires_at <= NOW() AS expired
+             FROM {schema}.departure_exports WHERE token_sha256 = $1"
+        ))
+        .bind(hash_token(token))
+        .fetch_optional(pool)
+        .await?
+        .ok_or(DepartureError::ExportNotFound)?;
+        if export.expired {
+            return Err(DepartureError::ExportExpired.into());
+        }
+
+        let child = sqlx::query_as::<_, ArchiveChild>(&format!(
+            "SELECT first_name, last_name, birth_date, departed_on
+             FROM {schema}.children WHERE id = $1 AND is_deleted = FALSE"
+        ))
+        .bind(export.child_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(DepartureError::ExportNotFound)?;
+
+        let journals: Value = sqlx::query_scalar(&format!(
+            "SELECT COALESCE(json_agg(j ORDER BY j.date), '[]'::JSON) FROM (
+                 SELECT date, temperature::TEXT AS temperature, menu, appetit::TEXT AS appetit,
+                        humeur::TEXT AS humeur, sommeil_minutes, absent, sante, medicaments,
+                        message_educatrice, observations
+                 FROM {schema}.daily_journals WHERE child_id = $1
+             ) j"
+        ))
+        .bind(export.child_id)
+        .fetch_one(pool)
+        .await?;
+        let journal_events: Value = sqlx::query_scalar(&format!(
+            "SELECT COALESCE(json_agg(e ORDER BY e.date, e.start_time), '[]'::JSON) FROM (
+                 SELECT date, event_type, start_time, end_time, notes
+                 FROM {schema}.journal_events WHERE child_id = $1
+             ) e"
+        ))
+        .bind(export.child_id)
+        .fetch_one(pool)
+        .await?;
+
+        let absolute = |url: Option<String>| url.map(|u| app_url(app_base_url, tenant, &format!("/api{u}")));
+        let mut media = MediaService::of_child_alone(pool, tenant, export.child_id).await?;
+        for m in &mut media {
+            signer.sign_media(m);
+            m.url = absolute(m.url.take());
+            m.thumbnail_url = absolute(m.thumbnail_url.take());
+            m.sprite_url = absolute(m.sprite_url.take());
+        }
+
+        sqlx::query(&format!(
+            "UPDATE {schema}.departure_exports
+             SET access_count = access_count + 1, last_accessed_at = NOW()
+             WHERE token_sha256 = $1"
+        ))
+        .bind(hash_token(token))
+        .execute(pool)
+        .await?;
+
+        Ok(DepartureArchive {
+            first_name: child.first_name,
+            last_name: child.last_name,
+            birth_date: child.birth_date,
+            departed_on: child.departed_on,
+            expires_at: export.expires_at,
+            journals,
+            journal_events,
+            media,
+        })
+    }
+
+    /// Close the access periods that ended before `today`: parents left without an
+    /// active child, or one still in its access period, are deactivated, signed out
+    /// and dropped from push notifications. Returns the parents deactivated.
+    pub async fn end_parent_access(pool: &PgPool, tenant: &str, today: NaiveDate) -> anyhow::Result<Vec<Uuid>> {
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+        let ended: Vec<Uuid> = sqlx::query_scalar(&format!(
+            "UPDATE {schema}.children SET parent_access_ended_at = NOW()
+             WHERE departure_applied_at IS NOT NULL AND parent_access_ended_at IS NULL
+               AND parent_access_until < $1
+             RETURNING id"
+        ))
+        .bind(today)
+        .fetch_all(&mut *tx)
+        .await?;
+        if ended.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let parents: Vec<Uuid> = sqlx::query_scalar(&format!(
+            "UPDATE {schema}.users u SET is_active = FALSE, updated_at = NOW()
+             WHERE u.role::text = 'parent' AND u.is_active = TRUE
+               AND u.id IN (SELECT cp.user_id FROM {schema}.child_parents cp WHERE cp.child_id = ANY($1))
+               AND NOT EXISTS (
+                   SELECT 1 FROM {schema}.child_parents cp
+                   JOIN {schema}.children c ON c.id = cp.child_id
+                   WHERE cp.user_id = u.id AND c.is_deleted = FALSE
+                     AND (c.is_active OR (c.departure_applied_at IS NOT NULL AND c.parent_access_until >= $2))
+               )
+             RETURNING u.id"
+        ))
+        .bind(&ended)
+        .bind(today)
+        .fetch_all(&mut *tx)
+        .await?;
+        if !parents.is_empty() {
+            sqlx::query(&format!(
+                "UPDATE {schema}.refresh_tokens SET revoked = TRUE WHERE user_id = ANY($1) AND revoked = FALSE"
+            ))
+            .bind(&parents)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query(&format!("DELETE FROM {schema}.push_tokens WHERE user_id = ANY($1)"))
+                .bind(&parents)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(parents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::scratch_tenant;
+
+    #[test]
+    fn parents_keep_access_thirty_days_by_default() {
+        let day = NaiveDate::from_ymd_opt(2026, 6, 26).unwrap();
+        assert_eq!(parent_access_until(day, None), NaiveDate::from_ymd_opt(2026, 7, 26).unwrap());
+        assert_eq!(parent_access_until(day, Some(7)), NaiveDate::from_ymd_opt(2026, 7, 3).unwrap());
+    }
+
+    #[test]
+    fn export_links_live_on_the_garderie_subdomain() {
+        assert_eq!(
+            export_url("https://minispace.app", "soleil", "ab12"),
+            "https://soleil.minispace.app/api/departures/ab12"
+        );
+    }
+
+    /// A scheduled departure deactivates the child on the day, stops notifications
+    /// to their parents, hands out an export link and ends a parent's access only
+    /// once no child is left.
+    #[tokio::test]
+    #[ignore]
+    async fn departure_applies_and_ends_parent_access() {
+        let tenant = scratch_tenant("dep").await;
+        let (pool, slug, schema) = (tenant.pool.clone(), tenant.slug.clone(), tenant.schema.clone());
+        let parents = [tenant.user("solo@dep.test", "parent").await, tenant.user("both@dep.test", "parent").await];
+        let mut children = vec![];
+        for name in ["Léa", "Noé"] {
+            let id: Uuid = sqlx::query_scalar(&format!(
+                "INSERT INTO {schema}.children (first_name, last_name, birth_date) VALUES ($1, 'T', '2022-01-01') RETURNING id"
+            ))
+            .bind(name)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            children.push(id);
+        }
+        // Both parents have Léa, who leaves; the second one also has Noé, who stays
+        for (parent, child) in [(parents[0], children[0]), (parents[1], children[0]), (parents[1], children[1])] {
+            sqlx::query(&format!("INSERT INTO {schema}.child_parents (child_id, user_id) VALUES ($1, $2)"))
+                .bind(child)
+                .bind(parent)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let today = chrono::Local::now().date_naive();
+        let req = ScheduleDepartureRequest { departure_date: today + Duration::days(1), parent_access_days: Some(10) };
+        let scheduled = DepartureService::schedule(&pool, &slug, children[0], &req).await.unwrap();
+        assert!(scheduled.is_active);
+        assert_eq!(scheduled.parent_access_until, Some(today + Duration::days(11)));
+        let early = DepartureService::create_export(&pool, &slug, children[0], None).await.unwrap_err();
+        assert!(matches!(early.downcast_ref(), Some(DepartureError::NotDeparted)));
+
+        // Not due today, due tomorrow
+        assert!(DepartureService::apply_due(&pool, None, "", &slug, today).await.unwrap().is_empty());
+        let applied = DepartureService::apply_due(&pool, None, "", &slug, today + Duration::days(1)).await.unwrap();
+        assert_eq!(applied.len(), 1);
+        assert!(!applied[0].is_active);
+        let cancel = DepartureService::cancel(&pool, &slug, children[0]).await.unwrap_err();
+        assert!(matches!(cancel.downcast_ref(), Some(DepartureError::AlreadyDeparted)));
+
+        // Only the parent with a child still at the garderie gets broadcasts
+        let notified: Vec<Uuid> = sqlx::query_scalar(&format!(
+            "SELECT id FROM {schema}.users WHERE role::text = 'parent' AND is_active = TRUE AND {}",
+            crate::services::notifications::has_active_child_sql(&schema, "users")
+        ))
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(notified, vec![parents[1]]);
+
+        // The export link opens until the access period ends
+        let signer = MediaUrlSigner::new("0000000000000000000000000000000000000000000000000000000000000001", 3600).unwrap();
+        let (export, token) = DepartureService::create_export(&pool, &slug, children[0], None).await.unwrap();
+        assert!(export.expires_at > chrono::Utc::now() + Duration::days(10));
+        let archive = DepartureService::open(&pool, &slug, &signer, "https://minispace.app", &token).await.unwrap();
+        assert_eq!(archive.first_name, "Léa");
+        assert_eq!(archive.journals, serde_json::json!([]));
+        assert!(DepartureService::open(&pool, &slug, &signer, "", &"00".repeat(32)).await.is_err());
+
+        // Access ends the day after parent_access_until, for the parent left without a child
+        assert!(DepartureService::end_parent_access(&pool, &slug, today + Duration::days(11)).await.unwrap().is_empty());
+        let ended = DepartureService::end_parent_access(&pool, &slug, today + Duration::days(12)).await.unwrap();
+        assert_eq!(ended, vec![parents[0]]);
+
+        tenant.drop().await;
+    }
+}
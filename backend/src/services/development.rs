@@ -0,0 +1,493 @@
+use chrono::{Local, NaiveDate};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::development::{
+        age_band_for, term_range, DomainSummary, Milestone, MilestoneInput, MilestoneQuery,
+        Observation, ObservationInput, ObservationNote, TermSummary, TimelineQuery, AGE_BANDS,
+        DOMAINS, OBSERVATION_STATUSES,
+    },
+};
+
+/// How many educator notes each domain of a termly summary keeps.
+const SUMMARY_NOTES_PER_DOMAIN: usize = 3;
+
+const MILESTONE_COLS: &str =
+    "id, domain, age_band, title, description, position, is_active, created_at, updated_at";
+
+/// Rejected milestone or observation data, with a message for the user.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct InvalidObservation(pub String);
+
+fn validate_domain(domain: &str) -> Result<(), InvalidObservation> {
+    if DOMAINS.contains(&domain) {
+        Ok(())
+    } else {
+        Err(InvalidObservation(format!("Domaine inconnu : {domain}")))
+    }
+}
+
+fn validate_milestone(input: &MilestoneInput) -> Result<(), InvalidObservation> {
+    validate_domain(&input.domain)?;
+    if !AGE_BANDS.iter().any(|(code, _, _)| *code == input.age_band) {
+        return Err(InvalidObservation(format!("Tranche d'âge inconnue : {}", input.age_band)));
+    }
+    if input.title.trim().is_empty() {
+        return Err(InvalidObservation("Le titre est requis".into()));
+    }
+    Ok(())
+}
+
+fn observation_select(schema: &str) -> String {
+    format!(
+        r#"SELECT o.id, o.child_id, o.milestone_id, m.title AS milestone_title, o.domain, o.age_band,
+                  o.observed_on, o.status, o.notes, o.media_id, md.media_type::TEXT AS media_type,
+                  o.visible_to_parents, o.created_by,
+                  NULLIF(TRIM(COALESCE(u.first_name, '') || ' ' || COALESCE(u.last_name, '')), '') AS author_name,
+                  o.created_at, o.updated_at
+           FROM "{schema}".child_observations o
+           LEFT JOIN "{schema}".milestones m ON m.id = o.milestone_id
+           LEFT JOIN "{schema}".media md ON md.id = o.media_id
+           LEFT JOIN "{schema}".users u ON u.id = o.created_by"#
+    )
+}
+
+pub struct DevelopmentService;
+
+impl DevelopmentService {
+    /// Active milestones of the catalogue, by domain, age band and position.
+    pub async fn list_milestones(
+        pool: &PgPool,
+        tenant: &str,
+        query: &MilestoneQuery,
+    ) -> anyhow::Result<Vec<Milestone>> {
+        let schema = schema_name(tenant);
+        let milestones = sqlx::query_as::<_, Milestone>(&format!(
+            r#"SELECT {MILESTONE_COLS} FROM "{schema}".milestones
+               WHERE is_active = TRUE
+                 AND ($1::TEXT IS NULL OR domain = $1)
+                 AND ($2::TEXT IS NULL OR age_band = $2)
+               ORDER BY domain, age_band, position, title"#
+        ))
+        .bind(&query.domain)
+        .bind(&query.age_band)
+        .fetch_all(pool)
+        .await?;
+        Ok(milestones)
+    }
+
+    pub async fn create_milestone(
+        pool: &PgPool,
+        tenant: &str,
+        input: &MilestoneInput,
+    ) -> anyhow::Result<Milestone> {
+        validate_milestone(input)?;
+        let schema = schema_name(tenant);
+        let milestone = sqlx::query_as::<_, Milestone>(&format!(
+            r#"INSERT INTO "{schema}".milestones (domain, age_band, title, description, position)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING {MILESTONE_COLS}"#
+        ))
+        .bind(&input.domain)
+        .bind(&input.age_band)
+        .bind(input.title.trim())
+        .bind(&input.description)
+        .bind(input.position.unwrap_or(0))
+        .fetch_one(pool)
+        .await?;
+        Ok(milestone)
+    }
+
+    pub async fn update_milestone(
+        pool: &PgPool,
+        tenant: &str,
+        id: Uuid,
+        input: &MilestoneInput,
+    ) -> anyhow::Result<Option<Milestone>> {
+        validate_milestone(input)?;
+        let schema = schema_name(tenant);
+        let milestone = sqlx::query_as::<_, Milestone>(&format!(
+            r#"UPDATE "{schema}".milestones
+               SET domain = $2, age_band = $3, title = $4, description = $5,
+                   position = COALESCE($6, position)
+               WHERE id = $1 AND is_active = TRUE
+               RETURNING {MILESTONE_COLS}"#
+        ))
+        .bind(id)
+        .bind(&input.domain)
+        .bind(&input.age_band)
+        .bind(input.title.trim())
+        .bind(&input.description)
+        .bind(input.position)
+        .fetch_optional(pool)
+        .await?;
+        Ok(milestone)
+    }
+
+    /// Retire a milestone; observations that reference it keep their history.
+    pub async fn deactivate_milestone(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<bool> {
+        let schema = schema_name(tenant);
+        let result = sqlx::query(&format!(
+            r#"UPDATE "{schema}".milestones SET is_active = FALSE WHERE id = $1 AND is_active = TRUE"#
+        ))
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn get_observation(
+        pool: &PgPool,
+        tenant: &str,
+        id: Uuid,
+    ) -> anyhow::Result<Option<Observation>> {
+        let schema = schema_name(tenant);
+        let observation = sqlx::query_as::<_, Observation>(&format!(
+            "{} WHERE o.id = $1",
+            observation_select(&schema)
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(observation)
+    }
+
+    /// Record an observation. The domain comes from the milestone when one is
+    /// given, and the age band from the child's age on the observation day.
+    pub async fn create_observation(
+        pool: &PgPool,
+        tenant: &str,
+        child_id: Uuid,
+        input: &ObservationInput,
+        created_by: Uuid,
+    ) -> anyhow::Result<Observation> {
+        let schema = schema_name(tenant);
+        let observed_on = input.observed_on.unwrap_or_else(|| Local::now().date_naive());
+        let (domain, age_band) = Self::resolve(pool, &schema, child_id, input, observed_on).await?;
+
+        let id: Uuid = sqlx::query_scalar(&format!(
+            r#"INSERT INTO "{schema}".child_observations
+                 (child_id, milestone_id, domain, age_band, observed_on, status, notes, media_id, visible_to_parents, created_by)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+               RETURNING id"#
+        ))
+        .bind(child_id)
+        .bind(input.milestone_id)
+        .bind(&domain)
+        .bind(&age_band)
+        .bind(observed_on)
+        .bind(&input.status)
+        .bind(&input.notes)
+        .bind(input.media_id)
+        .bind(input.visible_to_parents.unwrap_or(true))
+        .bind(created_by)
+        .fetch_one(pool)
+        .await?;
+
+        Self::get_observation(pool, tenant, id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("observation {id} vanished after insert"))
+    }
+
+    /// Replace an observation's content; it stays attached to the same child.
+    pub async fn update_observation(
+        pool: &PgPool,
+        tenant: &str,
+        existing: &Observation,
+        input: &ObservationInput,
+    ) -> anyhow::Result<Observation> {
+        let schema = schema_name(tenant);
+        let observed_on = input.observed_on.unwrap_or(existing.observed_on);
+        let (domain, age_band) = Self::resolve(pool, &schema, existing.child_id, input, observed_on).await?;
+
+        sqlx::query(&format!(
+            r#"UPDATE "{schema}".child_observations
+               SET milestone_id = $2, domain = $3, age_band = $4, observed_on = $5, status = $6,
+                   notes = $7, media_id = $8, visible_to_parents = $9
+               WHERE id = $1"#
+        ))
+        .bind(existing.id)
+        .bind(input.milestone_id)
+        .bind(&domain)
+        .bind(&age_band)
+        .bind(observed_on)
+        .bind(&input.status)
+        .bind(&input.notes)
+        .bind(input.media_id)
+        .bind(input.visible_to_parents.unwrap_or(existing.visible_to_parents))
+        .execute(pool)
+        .await?;
+
+        Self::get_observation(pool, tenant, existing.id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("observation {} vanished after update", existing.id))
+    }
+
+    pub async fn delete_observation(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<()> {
+        let schema = schema_name(tenant);
+        sqlx::query(&format!(r#"DELETE FROM "{schema}".child_observations WHERE id = $1"#))
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// A child's observations, newest first. Parents only see those shared with them.
+    pub async fn timeline(
+        pool: &PgPool,
+        tenant: &str,
+        child_id: Uuid,
+        parent_view: bool,
+        query: &TimelineQuery,
+    ) -> anyhow::Result<Vec<Observation>> {
+        let schema = schema_name(tenant);
+        let observations = sqlx::query_as::<_, Observation>(&format!(
+            "{} WHERE o.child_id = $1
+                 AND ($2 = FALSE OR o.visible_to_parents = TRUE)
+                 AND ($3::TEXT IS NULL OR o.domain = $3)
+                 AND ($4::DATE IS NULL OR o.observed_on >= $4)
+                 AND ($5::DATE IS NULL OR o.observed_on <= $5)
+               ORDER BY o.observed_on DESC, o.created_at DESC",
+            observation_select(&schema)
+        ))
+        .bind(child_id)
+        .bind(parent_view)
+        .bind(&query.domain)
+        .bind(query.from)
+        .bind(query.to)
+        .fetch_all(pool)
+        .await?;
+        Ok(observations)
+    }
+
+    /// Termly development report: per domain, what was reached, what is on its
+    /// way, what remains for the child's age band, and the latest notes.
+    pub async fn term_summary(
+        pool: &PgPool,
+        tenant: &str,
+        child_id: Uuid,
+        term: &str,
+        parent_view: bool,
+    ) -> anyhow::Result<Option<TermSummary>> {
+        let (from, to) = term_range(term)
+            .ok_or_else(|| InvalidObservation(format!("Trimestre invalide : {term} (ex. 2026-automne)")))?;
+
+        let schema = schema_name(tenant);
+        let child: Option<(String, String, NaiveDate)> = sqlx::query_as(&format!(
+            r#"SELECT first_name, last_name, birth_date FROM "{schema}".children WHERE id = $1"#
+        ))
+        .bind(child_id)
+        .fetch_optional(pool)
+        .await?;
+        let Some((first_name, last_name, birth_date)) = child else {
+            return Ok(None);
+        };
+
+        let age_band = age_band_for(birth_date, to).to_string();
+        let query = TimelineQuery { domain: None, from: Some(from), to: Some(to) };
+        let observations = Self::timeline(pool, tenant, child_id, parent_view, &query).await?;
+        let milestones = Self::list_milestones(
+            pool,
+            tenant,
+            &MilestoneQuery { domain: None, age_band: Some(age_band.clone()) },
+        )
+        .await?;
+        let reached: Vec<Uuid> = sqlx::query_scalar(&format!(
+            r#"SELECT DISTINCT milestone_id FROM "{schema}".child_observations
+               WHERE child_id = $1 AND milestone_id IS NOT NULL AND observed_on <= $2"#
+        ))
+        .bind(child_id)
+        .bind(to)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(Some(TermSummary {
+            child_id,
+            child_name: format!("{first_name} {last_name}"),
+            term: term.to_string(),
+            from,
+            to,
+            age_band,
+            domains: build_domain_summaries(&observations, &milestones, &reached),
+        }))
+    }
+
+    /// Validate the input and work out the observation's domain and age band.
+    async fn resolve(
+        pool: &PgPool,
+        schema: &str,
+        child_id: Uuid,
+        input: &ObservationInput,
+        observed_on: NaiveDate,
+    ) -> anyhow::Result<(String, String)> {
+        if let Some(ref status) = input.status {
+            if !OBSERVATION_STATUSES.contains(&status.as_str()) {
+                return Err(InvalidObservation(format!("Statut inconnu : {status}")).into());
+            }
+        }
+
+        let domain = match input.milestone_id {
+            Some(milestone_id) => sqlx::query_scalar::<_, String>(&format!(
+                r#"SELECT domain FROM "{schema}".milestones WHERE id = $1"#
+            ))
+            .bind(milestone_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| InvalidObservation("Jalon introuvable".into()))?,
+            None => input
+                .domain
+                .clone()
+                .ok_or_else(|| InvalidObservation("Le domaine est requis sans jalon".into()))?,
+        };
+        validate_domain(&domain)?;
+
+        if let Some(media_id) = input.media_id {
+            let exists: bool = sqlx::query_scalar(&format!(
+                r#"SELECT EXISTS(SELECT 1 FROM "{schema}".media WHERE id = $1)"#
+            ))
+            .bind(media_id)
+            .fetch_one(pool)
+            .await?;
+            if !exists {
+                return Err(InvalidObservation("Média introuvable".into()).into());
+            }
+        }
+
+        let birth_date: NaiveDate = sqlx::query_scalar(&format!(
+            r#"SELECT birth_date FROM "{schema}".children WHERE id = $1"#
+        ))
+        .bind(child_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| InvalidObservation("Enfant introuvable".into()))?;
+
+        Ok((domain, age_band_for(birth_date, observed_on).to_string()))
+    }
+}
+
+/// Group a term's observations by domain, in `DOMAINS` order. `reached` lists every
+/// milestone observed up to the end of the term, so older ones are not "upcoming".
+pub fn build_domain_summaries(
+    observations: &[Observation],
+    milestones: &[Milestone],
+    reached: &[Uuid],
+) -> Vec<DomainSummary> {
+    DOMAINS
+        .iter()
+        .map(|domain| {
+            let in_domain: Vec<&Observation> =
+                observations.iter().filter(|o| o.domain == *domain).collect();
+
+            let mut acquired = Vec::new();
+            let mut in_progress = Vec::new();
+            for o in &in_domain {
+                let Some(ref title) = o.milestone_title else {
+                    continue;
+                };
+                let bucket = if o.status.as_deref() == Some("acquis") {
+                    &mut acquired
+                } else {
+                    &mut in_progress
+                };
+                if !bucket.contains(title) {
+                    bucket.push(title.clone());
+                }
+            }
+            in_progress.retain(|t| !acquired.contains(t));
+
+            let upcoming = milestones
+                .iter()
+                .filter(|m| m.domain == *domain && !reached.contains(&m.id))
+                .map(|m| m.title.clone())
+                .collect();
+
+            let notes = in_domain
+                .iter()
+                .filter_map(|o| {
+                    o.notes
+                        .as_ref()
+                        .filter(|n| !n.trim().is_empty())
+                        .map(|n| ObservationNote { observed_on: o.observed_on, notes: n.clone() })
+                })
+                .take(SUMMARY_NOTES_PER_DOMAIN)
+                .collect();
+
+            DomainSummary {
+                domain: domain.to_string(),
+                observation_count: in_domain.len(),
+                acquired,
+                in_progress,
+                upcoming,
+                notes,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::development::{term_for, term_range};
+    use chrono::Utc;
+
+    fn observation(domain: &str, milestone: Option<&str>, status: Option<&str>, day: u32) -> Observation {
+        Observation {
+            id: Uuid::new_v4(),
+            child_id: Uuid::nil(),
+            milestone_id: milestone.map(|_| Uuid::new_v4()),
+            milestone_title: milestone.map(Into::into),
+            domain: domain.into(),
+            age_band: "24_36_mois".into(),
+            observed_on: NaiveDate::from_ymd_opt(2026, 10, day).unwrap(),
+            status: status.map(Into::into),
+            notes: Some(format!("Note du {day}")),
+            media_id: None,
+            media_type: None,
+            visible_to_parents: true,
+            created_by: Uuid::nil(),
+            author_name: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn age_bands_and_terms() {
+        let birth = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+        assert_eq!(age_band_for(birth, NaiveDate::from_ymd_opt(2025, 3, 14).unwrap()), "0_12_mois");
+        assert_eq!(age_band_for(birth, NaiveDate::from_ymd_opt(2025, 3, 15).unwrap()), "12_24_mois");
+        assert_eq!(age_band_for(birth, NaiveDate::from_ymd_opt(2026, 10, 17).unwrap()), "24_36_mois");
+
+        assert_eq!(term_for(NaiveDate::from_ymd_opt(2026, 10, 17).unwrap()), "2026-automne");
+        assert_eq!(
+            term_range("2026-printemps"),
+            Some((NaiveDate::from_ymd_opt(2026, 4, 1).unwrap(), NaiveDate::from_ymd_opt(2026, 6, 30).unwrap()))
+        );
+        assert_eq!(term_range("2026-mars"), None);
+    }
+
+    #[test]
+    fn summary_splits_acquired_from_in_progress() {
+        let observations = vec![
+            observation("langage", Some("Phrases de 3 mots"), Some("acquis"), 14),
+            observation("langage", Some("Phrases de 3 mots"), Some("en_voie"), 2),
+            observation("langage", Some("Nomme les couleurs"), Some("emergent"), 9),
+            observation("autonomie", None, None, 5),
+        ];
+        let summaries = build_domain_summaries(&observations, &[], &[]);
+
+        let langage = summaries.iter().find(|d| d.domain == "langage").unwrap();
+        assert_eq!(langage.observation_count, 3);
+        assert_eq!(langage.acquired, vec!["Phrases de 3 mots".to_string()]);
+        assert_eq!(langage.in_progress, vec!["Nomme les couleurs".to_string()]);
+        assert_eq!(langage.notes.len(), 3);
+
+        let autonomie = summaries.iter().find(|d| d.domain == "autonomie").unwrap();
+        assert_eq!(autonomie.observation_count, 1);
+        assert!(autonomie.acquired.is_empty());
+        assert_eq!(summaries.len(), DOMAINS.len());
+    }
+}
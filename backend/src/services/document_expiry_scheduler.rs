@@ -0,0 +1,134 @@
+use chrono::{Local, Timelike};
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::models::document::ExpiryReminderRow;
+use crate::models::tenant::TenantBranding;
+use crate::db::tenant_db::TenantDb;
+use crate::services::branding::BrandingService;
+use crate::services::documents::{due_expiry_reminder, DocumentService};
+use crate::services::email::EmailService;
+
+/// Spawn a background task that wakes up daily at 8:00 AM and emails the parents
+/// of each child whose documents (medical forms, authorizations…) expire within
+/// 30 days, again at 7 days and on expiry.
+/// The last threshold reached is stored on the document, so each reminder goes out
+/// once; renewing the document (new expiry date) starts over.
+pub fn start(pool: PgPool, email: Option<Arc<EmailService>>, app_base_url: String) {
+    tokio::spawn(async move {
+        loop {
+            // Sleep until next 8:00 AM
+            let now = Local::now();
+            let target_secs = 8 * 3600;
+            let secs_today = now.hour() * 3600 + now.minute() * 60 + now.second();
+            let sleep_secs = if secs_today < target_secs {
+                (target_secs - secs_today) as u64
+            } else {
+                (86400 - secs_today + target_secs) as u64
+            };
+            tokio::time::sleep(tokio::time::Duration::from_secs(sleep_secs)).await;
+            let Some(_job) = crate::services::worker::job().await else {
+                break;
+            };
+
+            let Some(ref email_svc) = email else {
+                continue;
+            };
+
+            let tenants: Vec<String> = match sqlx::query_scalar(
+                "SELECT slug FROM public.garderies WHERE is_active = TRUE AND slug != 'demo'",
+            )
+            .fetch_all(&pool)
+            .await
+            {
+                Ok(rows) => rows,
+                Err(e) => {
+                    warn!("Document expiry scheduler: failed to query tenants: {e}");
+                    continue;
+                }
+            };
+
+            for slug in tenants {
+                TenantDb::scope(slug.clone(), async {
+                    let (garderie_name, branding) = BrandingService::for_email(&pool, &slug).await;
+                    let sent = remind_tenant(&pool, email_svc, &app_base_url, &slug, &garderie_name, &branding).await;
+                    if sent > 0 {
+                        info!("Document expiry scheduler: {sent} reminder(s) sent for '{slug}'");
+                    }
+                })
+                .await;
+            }
+        }
+    });
+}
+
+async fn remind_tenant(
+    pool: &PgPool,
+    email_svc: &EmailService,
+    app_base_url: &str,
+    slug: &str,
+    garderie_name: &str,
+    branding: &TenantBranding,
+) -> usize {
+    let rows = match DocumentService::expiry_reminder_rows(pool, slug).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            warn!("Document expiry scheduler: failed to list expiring documents for '{slug}': {e}");
+            return 0;
+        }
+    };
+
+    let app_url = if let Some(idx) = app_base_url.find("://") {
+        let scheme = &app_base_url[..idx];
+        let domain = &app_base_url[idx + 3..];
+        format!("{scheme}://{slug}.{domain}/fr/parent/documents")
+    } else {
+        format!("https://{slug}.{app_base_url}/fr/parent/documents")
+    };
+
+    let mut by_document: BTreeMap<Uuid, Vec<ExpiryReminderRow>> = BTreeMap::new();
+    for row in rows {
+        by_document.entry(row.document_id).or_default().push(row);
+    }
+
+    let mut sent = 0;
+    for (document_id, parents) in by_document {
+        let first = &parents[0];
+        let Some(threshold) = due_expiry_reminder(first.days_left, first.reminder_sent_days) else {
+            continue;
+        };
+
+        for row in &parents {
+            let name = format!("{} {}", row.first_name, row.last_name);
+            match email_svc
+                .send_document_expiry_reminder(
+                    slug,
+                    &row.email,
+                    &name,
+                    &row.child_first_name,
+                    &row.title,
+                    row.expires_at,
+                    &app_url,
+                    garderie_name,
+                    branding,
+                )
+                .await
+            {
+                Ok(_) => sent += 1,
+                Err(e) => warn!(
+                    "Document expiry scheduler: failed to remind {} for document {document_id}: {e}",
+                    row.email
+                ),
+            }
+        }
+
+        if let Err(e) = DocumentService::mark_expiry_reminded(pool, slug, document_id, threshold).await {
+            warn!("Document expiry scheduler: failed to record reminder for document {document_id}: {e}");
+        }
+    }
+
+    sent
+}
@@ -0,0 +1,331 @@
+use std::path::PathBuf;
+
+use chrono::{Duration, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::document::{CreateDocumentShareRequest, DocumentShare, DocumentShareAccess},
+    services::{
+        antivirus::SCAN_INFECTED,
+        encryption::{self, KeyRing},
+        notification_consumer::app_url,
+    },
+};
+
+/// Validity of a link when none is given: one week.
+const DEFAULT_TTL_HOURS: i64 = 7 * 24;
+/// Longest validity of a link: 30 days.
+const MAX_TTL_HOURS: i64 = 30 * 24;
+
+const SHARE_COLS: &str = "id, document_id, scope, recipient, expires_at, revoked_at, created_by,
+     created_at, access_count, last_accessed_at";
+
+#[derive(Debug, thiserror::Error)]
+pub enum DocumentShareError {
+    #[error("Document introuvable")]
+    DocumentNotFound,
+    #[error("Lien de partage introuvable")]
+    NotFound,
+    #[error("Ce lien de partage a expiré")]
+    Expired,
+    #[error("Ce lien de partage a été révoqué")]
+    Revoked,
+    #[error("Portée inconnue : {0} (view ou download)")]
+    InvalidScope(String),
+    #[error("Durée de validité invalide (1 à {MAX_TTL_HOURS} heures)")]
+    InvalidExpiry,
+    #[error("Ce fichier a été mis en quarantaine par l'antivirus")]
+    Quarantined,
+    #[error("Fichier chiffré trop volumineux pour être servi")]
+    TooLarge,
+}
+
+/// SHA-256 of a share token, as stored; the token itself is never kept.
+pub(crate) fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Tokens are 32 random bytes in hex; anything else is refused without a lookup.
+pub(crate) fn is_token(token: &str) -> bool {
+    token.len() == 64 && token.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn share_scope(scope: Option<&str>) -> Result<&'static str, DocumentShareError> {
+    match scope.map(str::trim) {
+        None | Some("") | Some("view") => Ok("view"),
+        Some("download") => Ok("download"),
+        Some(other) => Err(DocumentShareError::InvalidScope(other.to_string())),
+    }
+}
+
+fn share_ttl(hours: Option<i64>) -> Result<Duration, DocumentShareError> {
+    match hours.unwrap_or(DEFAULT_TTL_HOURS) {
+        h @ 1..=MAX_TTL_HOURS => Ok(Duration::hours(h)),
+        _ => Err(DocumentShareError::InvalidExpiry),
+    }
+}
+
+/// Public address of a share link, on the garderie's subdomain.
+pub fn share_url(base: &str, tenant: &str, token: &str) -> String {
+    app_url(base, tenant, &format!("/api/shared/{token}"))
+}
+
+/// A shared document, decrypted and ready to be sent.
+pub struct SharedDocument {
+    pub filename: String,
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+    /// Sent as an attachment rather than shown in the browser.
+    pub download: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct ShareFileRow {
+    share_id: Uuid,
+    scope: String,
+    expired: bool,
+    revoked: bool,
+    original_filename: String,
+    storage_path: String,
+    content_type: String,
+    is_encrypted: bool,
+    encryption_iv: Option<Vec<u8>>,
+    encryption_tag: Option<Vec<u8>>,
+    key_version: i32,
+    scan_status: String,
+}
+
+pub struct DocumentShareService;
+
+impl DocumentShareService {
+    /// Create a link to a document and return it with its token, which is not
+    /// stored and cannot be shown again.
+    pub async fn create(
+        pool: &PgPool,
+        tenant: &str,
+        document_id: Uuid,
+        created_by: Uuid,
+        req: &CreateDocumentShareRequest,
+    ) -> anyhow::Result<(DocumentShare, String)> {
+        let schema = schema_name(tenant);
+        let scope = share_scope(req.scope.as_deref())?;
+        let expires_at = Utc::now() + share_ttl(req.expires_in_hours)?;
+        let recipient = req.recipient.as_deref().map(str::trim).filter(|r| !r.is_empty());
+
+        let exists: bool = sqlx::query_scalar(&format!(
+            r#"SELECT EXISTS(SELECT 1 FROM "{schema}".documents WHERE id = $1)"#
+        ))
+        .bind(document_id)
+        .fetch_one(pool)
+        .await?;
+        if !exists {
+            return Err(DocumentShareError::DocumentNotFound.into());
+        }
+
+        let token = {
+            use rand::Rng;
+            hex::encode(rand::thread_rng().gen::<[u8; 32]>())
+        };
+        let share = sqlx::query_as::<_, DocumentShare>(&format!(
+            r#"INSERT INTO "{schema}".document_shares
+                   (document_id, token_sha256, scope, recipient, expires_at, created_by)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               RETURNING {SHARE_COLS}"#
+        ))
+        .bind(document_id)
+        .bind(hash_token(&token))
+        .bind(scope)
+        .bind(recipient)
+        .bind(expires_at)
+        .bind(created_by)
+        .fetch_one(pool)
+        .await?;
+        Ok((share, token))
+    }
+
+    /// Links of a document, newest first, revoked and expired ones included.
+    pub async fn list(pool: &PgPool, tenant: &str, document_id: Uuid) -> anyhow::Result<Vec<DocumentShare>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, DocumentShare>(&format!(
+            r#"SELECT {SHARE_COLS} FROM "{schema}".document_shares
+               WHERE document_id = $1 ORDER BY created_at DESC"#
+        ))
+        .bind(document_id)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Revoke a link; revoking it again keeps the first revocation date.
+    pub async fn revoke(pool: &PgPool, tenant: &str, document_id: Uuid, share_id: Uuid) -> anyhow::Result<DocumentShare> {
+        let schema = schema_name(tenant);
+        sqlx::query_as::<_, DocumentShare>(&format!(
+            r#"UPDATE "{schema}".document_shares SET revoked_at = COALESCE(revoked_at, NOW())
+               WHERE id = $1 AND document_id = $2
+               RETURNING {SHARE_COLS}"#
+        ))
+        .bind(share_id)
+        .bind(document_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| DocumentShareError::NotFound.into())
+    }
+
+    /// Access log of a link, newest first.
+    pub async fn accesses(
+        pool: &PgPool,
+        tenant: &str,
+        document_id: Uuid,
+        share_id: Uuid,
+    ) -> anyhow::Result<Vec<DocumentShareAccess>> {
+        let schema = schema_name(tenant);
+        let exists: bool = sqlx::query_scalar(&format!(
+            r#"SELECT EXISTS(SELECT 1 FROM "{schema}".document_shares WHERE id = $1 AND document_id = $2)"#
+        ))
+        .bind(share_id)
+        .bind(document_id)
+        .fetch_one(pool)
+        .await?;
+        if !exists {
+            return Err(DocumentShareError::NotFound.into());
+        }
+        Ok(sqlx::query_as::<_, DocumentShareAccess>(&format!(
+            r#"SELECT id, share_id, accessed_at, ip_address, user_agent
+               FROM "{schema}".document_share_accesses
+               WHERE share_id = $1 ORDER BY accessed_at DESC LIMIT 500"#
+        ))
+        .bind(share_id)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Check a token, read and decrypt the current file of its document, and log
+    /// the access. A replaced document is served in its latest version.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn open(
+        pool: &PgPool,
+        tenant: &str,
+        media_dir: &str,
+        keys: &KeyRing,
+        max_decrypt_bytes: u64,
+        token: &str,
+        ip_address: &str,
+        user_agent: Option<&str>,
+    ) -> anyhow::Result<SharedDocument> {
+        if !is_token(token) {
+            return Err(DocumentShareError::NotFound.into());
+        }
+        let schema = schema_name(tenant);
+        let token_sha256 = hash_token(token);
+
+        // A key rotation may re-encrypt the file between the row and the file
+        // reads; decryption then fails, so read both again once.
+        let mut attempt = 0;
+        let (row, bytes) = loop {
+            let row = sqlx::query_as::<_, ShareFileRow>(&format!(
+                r#"SELECT s.id AS share_id, s.scope, s.expires_at <= NOW() AS expired,
+                          s.revoked_at IS NOT NULL AS revoked,
+                          d.original_filename, d.storage_path, d.content_type, d.is_encrypted,
+                          d.encryption_iv, d.encryption_tag, d.key_version, d.scan_status
+                   FROM "{schema}".document_shares s
+                   JOIN "{schema}".documents d ON d.id = s.document_id
+                   WHERE s.token_sha256 = $1"#
+            ))
+            .bind(&token_sha256)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(DocumentShareError::NotFound)?;
+            if row.revoked {
+                return Err(DocumentShareError::Revoked.into());
+            }
+            if row.expired {
+                return Err(DocumentShareError::Expired.into());
+            }
+            if row.scan_status == SCAN_INFECTED {
+                return Err(DocumentShareError::Quarantined.into());
+            }
+
+            match read_file(media_dir, tenant, keys, max_decrypt_bytes, &row).await {
+                Ok(bytes) => break (row, bytes),
+                Err(e) if attempt == 0 && e.downcast_ref::<DocumentShareError>().is_none() => attempt += 1,
+                Err(e) => return Err(e),
+            }
+        };
+
+        let mut tx = pool.begin().await?;
+        sqlx::query(&format!(
+            r#"INSERT INTO "{schema}".document_share_accesses (share_id, ip_address, user_agent)
+               VALUES ($1, $2, $3)"#
+        ))
+        .bind(row.share_id)
+        .bind(ip_address)
+        .bind(user_agent.map(|ua| ua.chars().take(512).collect::<String>()))
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(&format!(
+            r#"UPDATE "{schema}".document_shares
+               SET access_count = access_count + 1, last_accessed_at = NOW()
+               WHERE id = $1"#
+        ))
+        .bind(row.share_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(SharedDocument {
+            filename: row.original_filename,
+            content_type: row.content_type,
+            bytes,
+            download: row.scope == "download",
+        })
+    }
+}
+
+async fn read_file(
+    media_dir: &str,
+    tenant: &str,
+    keys: &KeyRing,
+    max_decrypt_bytes: u64,
+    row: &ShareFileRow,
+) -> anyhow::Result<Vec<u8>> {
+    let path = PathBuf::from(media_dir).join(&row.storage_path);
+    if !row.is_encrypted {
+        return Ok(tokio::fs::read(path).await?);
+    }
+    if tokio::fs::metadata(&path).await?.len() > max_decrypt_bytes {
+        return Err(DocumentShareError::TooLarge.into());
+    }
+    let (Some(iv), Some(tag)) = (&row.encryption_iv, &row.encryption_tag) else {
+        anyhow::bail!("missing encryption IV or tag for {}", row.storage_path);
+    };
+    let key = keys.tenant_key(row.key_version, tenant)?;
+    let ciphertext = tokio::fs::read(path).await?;
+    encryption::decrypt_file(&ciphertext, iv, tag, &key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ttl_defaults_to_a_week_and_is_capped() {
+        assert_eq!(share_ttl(None).unwrap(), Duration::hours(168));
+        assert_eq!(share_ttl(Some(720)).unwrap(), Duration::days(30));
+        assert!(matches!(share_ttl(Some(0)), Err(DocumentShareError::InvalidExpiry)));
+        assert!(matches!(share_ttl(Some(721)), Err(DocumentShareError::InvalidExpiry)));
+    }
+
+    #[test]
+    fn tokens_are_hashed_and_checked_before_lookup() {
+        let token = "ab".repeat(32);
+        assert!(is_token(&token));
+        assert!(!is_token("not-a-token"));
+        assert!(!is_token(&"zz".repeat(32)));
+        assert_eq!(hash_token(&token).len(), 64);
+        assert_ne!(hash_token(&token), token);
+        assert_eq!(share_scope(None).unwrap(), "view");
+        assert!(share_scope(Some("edit")).is_err());
+    }
+}
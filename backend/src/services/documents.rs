@@ -1,4 +1,4 @@
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
 /// Generate a cryptographically random 32-char hex filename (no extension).
 fn random_storage_name() -> String {
@@ -8,53 +8,203 @@ fn random_storage_name() -> String {
 }
 
 use axum::extract::Multipart;
+use chrono::NaiveDate;
 use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::{
     db::tenant::schema_name,
-    models::document::{Document, DocumentQuery, UpdateDocumentRequest},
-    services::encryption,
+    models::document::{
+        BulkDocumentRequest, Document, DocumentFolder, DocumentQuery, DocumentSignature, DocumentVersion,
+        ExpiringDocument, ExpiryReminderRow, FolderRequest, OutstandingSignature, UpdateDocumentRequest,
+    },
+    services::{
+        encryption::{self, KeyRing},
+        storage::StorageService,
+        upload_types::{self, UploadKind},
+    },
 };
 
 /// Explicit column list for Document — casts category and visibility enums to TEXT.
 const DOC_COLS: &str =
     "id, uploader_id, title, category::TEXT as category, original_filename,
      storage_path, content_type, size_bytes, group_id, child_id, visibility::TEXT as visibility,
-     created_at, updated_at, is_encrypted, encryption_iv, encryption_tag";
+     created_at, updated_at, is_encrypted, encryption_iv, encryption_tag, requires_signature,
+     folder_id, version, expires_at, is_template";
+
+/// SQL predicate (over `d` = documents, `u` = users) matching the parents who can
+/// see a document — same rules as the parent branch of `DocumentService::list`.
+fn parent_can_see(schema: &str) -> String {
+    format!(
+        "d.visibility != 'private'
+         AND (
+           d.visibility = 'public'
+           OR (d.visibility = 'group' AND EXISTS (
+               SELECT 1 FROM {schema}.child_parents cp
+               JOIN {schema}.children c ON c.id = cp.child_id
+               WHERE cp.user_id = u.id AND c.group_id = d.group_id
+           ))
+           OR (d.visibility = 'child' AND EXISTS (
+               SELECT 1 FROM {schema}.child_parents cp
+               WHERE cp.user_id = u.id AND cp.child_id = d.child_id
+           ))
+         )"
+    )
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DocumentError {
+    #[error("Aucun fichier dans l'envoi")]
+    NoFile,
+    #[error("Dossier introuvable")]
+    FolderNotFound,
+    #[error("Nom de dossier invalide")]
+    InvalidFolderName,
+    #[error("Un dossier du même nom existe déjà à cet endroit")]
+    DuplicateFolder,
+    #[error("Un dossier ne peut pas être déplacé dans un de ses sous-dossiers")]
+    FolderCycle,
+    #[error("Action inconnue : {0}")]
+    UnknownAction(String),
+    #[error("Date d'expiration invalide (AAAA-MM-JJ)")]
+    InvalidExpiry,
+    #[error("Document déjà signé")]
+    AlreadySigned,
+}
+
+/// Reminders go out this many days before a document expires, then on expiry.
+pub const EXPIRY_REMINDER_DAYS: &[i32] = &[30, 7, 0];
+
+/// An expiry date from a form field or JSON body; empty means no expiry.
+fn parse_expiry(raw: &str) -> Result<Option<NaiveDate>, DocumentError> {
+    match raw.trim() {
+        "" => Ok(None),
+        date => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map(Some)
+            .map_err(|_| DocumentError::InvalidExpiry),
+    }
+}
+
+/// The reminder threshold reached by a document `days_left` from expiry, unless the
+/// parents were already reminded at that threshold (or a closer one).
+pub fn due_expiry_reminder(days_left: i32, last_sent: Option<i32>) -> Option<i32> {
+    let threshold = EXPIRY_REMINDER_DAYS
+        .iter()
+        .copied()
+        .filter(|t| days_left <= *t)
+        .min()?;
+    last_sent.is_none_or(|l| l > threshold).then_some(threshold)
+}
+
+/// Title of one document of an upload: the `title` field only applies when a
+/// single file is sent, otherwise each document is named after its file.
+fn upload_title(title: Option<&str>, filename: &str, file_count: usize) -> String {
+    match title.map(str::trim) {
+        Some(t) if !t.is_empty() && file_count == 1 => t.to_string(),
+        _ => filename.to_string(),
+    }
+}
+
+/// Trimmed folder name, or `None` if it is empty, too long or contains a slash.
+fn folder_name(name: &str) -> Option<&str> {
+    let name = name.trim();
+    let valid = !name.is_empty() && name.chars().count() <= 255 && !name.contains('/');
+    valid.then_some(name)
+}
+
+fn folder_write_error(e: sqlx::Error) -> anyhow::Error {
+    match e {
+        sqlx::Error::Database(ref db) if db.is_unique_violation() => DocumentError::DuplicateFolder.into(),
+        e => e.into(),
+    }
+}
+
+struct UploadedFile {
+    bytes: Vec<u8>,
+    filename: String,
+    content_type: String,
+}
+
+/// Read a file field, keeping the content type found in its bytes rather than the
+/// client's (PDF and Word documents only).
+async fn read_file(field: axum::extract::multipart::Field<'_>) -> anyhow::Result<UploadedFile> {
+    let filename = field.file_name().unwrap_or("document").to_string();
+    let declared_type = field.content_type().unwrap_or("").to_string();
+    let bytes = field.bytes().await?.to_vec();
+    let content_type = upload_types::validate(UploadKind::Document, &bytes, &declared_type, &filename)?
+        .content_type
+        .to_string();
+    Ok(UploadedFile { bytes, filename, content_type })
+}
+
+/// An encrypted file written under `{tenant}/documents/`.
+pub(crate) struct StoredFile {
+    pub storage_path: String,
+    pub size_bytes: i64,
+    pub iv: Vec<u8>,
+    pub tag: Vec<u8>,
+    pub key_version: i32,
+}
+
+pub(crate) async fn store_file(media_dir: &str, tenant: &str, keys: &KeyRing, bytes: &[u8]) -> anyhow::Result<StoredFile> {
+    let doc_dir = PathBuf::from(media_dir).join(tenant).join("documents");
+    tokio::fs::create_dir_all(&doc_dir).await?;
+
+    let storage_filename = random_storage_name();
+
+    // Derive tenant key from the current master key
+    let (key_version, tenant_key) = keys.current_tenant_key(tenant)?;
+
+    // Encrypt file data
+    let (encrypted_bytes, iv, tag) = encryption::encrypt_file(bytes, &tenant_key)?;
+
+    // Write encrypted file to disk
+    tokio::fs::write(doc_dir.join(&storage_filename), &encrypted_bytes).await?;
+
+    Ok(StoredFile {
+        storage_path: format!("{}/documents/{}", tenant, storage_filename),
+        size_bytes: encrypted_bytes.len() as i64,
+        iv,
+        tag,
+        key_version,
+    })
+}
+
+pub(crate) async fn remove_files(media_dir: &str, storage_paths: &[String]) {
+    for path in storage_paths {
+        let _ = tokio::fs::remove_file(PathBuf::from(media_dir).join(path)).await;
+    }
+}
 
 pub struct DocumentService;
 
 impl DocumentService {
+    /// Store every "file" field of the request as its own document; the other
+    /// fields (category, visibility, folder…) apply to all of them.
     pub async fn upload(
         pool: &PgPool,
+        redis: &mut redis::aio::MultiplexedConnection,
         tenant: &str,
         uploader_id: Uuid,
         media_dir: &str,
-        encryption_master_key: &str,
+        keys: &KeyRing,
         mut multipart: Multipart,
-    ) -> anyhow::Result<Document> {
-        let doc_dir = PathBuf::from(media_dir).join(tenant).join("documents");
-        tokio::fs::create_dir_all(&doc_dir).await?;
-
-        let mut file_data: Option<(Vec<u8>, String, String)> = None;
+    ) -> anyhow::Result<Vec<Document>> {
+        let mut files: Vec<UploadedFile> = Vec::new();
         let mut title: Option<String> = None;
         let mut category = "autre".to_string();
         let mut visibility = "private".to_string();
         let mut group_id: Option<Uuid> = None;
         let mut child_id: Option<Uuid> = None;
+        let mut folder_id: Option<Uuid> = None;
+        let mut expires_at: Option<NaiveDate> = None;
+        let mut requires_signature = false;
 
         while let Some(field) = multipart.next_field().await? {
             let name = field.name().unwrap_or("").to_string();
             match name.as_str() {
                 "file" => {
-                    let filename = field.file_name().unwrap_or("document").to_string();
-                    let ct = field
-                        .content_type()
-                        .unwrap_or("application/octet-stream")
-                        .to_string();
-                    let bytes = field.bytes().await?.to_vec();
-                    file_data = Some((bytes, filename, ct));
+                    files.push(read_file(field).await?);
                 }
                 "title" => {
                     title = Some(field.text().await?);
@@ -76,67 +226,82 @@ impl DocumentService {
                 "child_id" => {
                     child_id = field.text().await?.parse().ok();
                 }
+                "folder_id" => {
+                    folder_id = field.text().await?.parse().ok();
+                }
+                "expires_at" => {
+                    expires_at = parse_expiry(&field.text().await?)?;
+                }
+                "requires_signature" => {
+                    requires_signature = matches!(field.text().await?.as_str(), "true" | "1");
+                }
                 _ => {}
             }
         }
 
-        let (bytes, original_filename, content_type) =
-            file_data.ok_or_else(|| anyhow::anyhow!("No file field in upload"))?;
-
-        let ext = Path::new(&original_filename)
-            .extension()
-            .and_then(|e| e.to_str())
-            .unwrap_or("bin");
-
-        let file_id = Uuid::new_v4();
-        let storage_filename = random_storage_name();
-        let storage_path_full = doc_dir.join(&storage_filename);
-        let storage_path_rel = format!("{}/documents/{}", tenant, storage_filename);
-
-        // Decode master key and derive tenant key
-        let master_key_bytes = hex::decode(encryption_master_key)?;
-        if master_key_bytes.len() != 32 {
-            anyhow::bail!("Master key must be 32 bytes");
+        if files.is_empty() {
+            return Err(DocumentError::NoFile.into());
         }
-        let mut master_key = [0u8; 32];
-        master_key.copy_from_slice(&master_key_bytes);
-        let tenant_key = encryption::derive_tenant_key(&master_key, tenant)?;
 
-        // Encrypt file data
-        let (encrypted_bytes, iv, tag) = encryption::encrypt_file(&bytes, &tenant_key)?;
+        let schema = schema_name(tenant);
+        if let Some(folder_id) = folder_id {
+            Self::ensure_folder(pool, &schema, folder_id).await?;
+        }
 
-        // Write encrypted file to disk
-        tokio::fs::write(&storage_path_full, &encrypted_bytes).await?;
+        let total_bytes: i64 = files.iter().map(|f| f.bytes.len() as i64).sum();
+        StorageService::ensure_capacity(pool, redis, tenant, total_bytes).await?;
 
         // Resolve group_id/child_id based on visibility
         let db_group_id = if visibility == "group" { group_id } else { None };
         let db_child_id = if visibility == "child" { child_id } else { None };
 
-        let schema = schema_name(tenant);
-        let doc = sqlx::query_as::<_, Document>(&format!(
-            "INSERT INTO {schema}.documents
-             (uploader_id, title, category, original_filename, storage_path, content_type, size_bytes, group_id, child_id,
-              visibility, is_encrypted, encryption_iv, encryption_tag)
-             VALUES ($1, $2, $3::\"{schema}\".doc_category, $4, $5, $6, $7, $8, $9, $10::\"{schema}\".doc_visibility, $11, $12, $13)
-             RETURNING {DOC_COLS}"
-        ))
-        .bind(uploader_id)
-        .bind(title.unwrap_or_else(|| original_filename.clone()))
-        .bind(&category)
-        .bind(&original_filename)
-        .bind(&storage_path_rel)
-        .bind(&content_type)
-        .bind(encrypted_bytes.len() as i64)
-        .bind(db_group_id)
-        .bind(db_child_id)
-        .bind(&visibility)
-        .bind(true) // is_encrypted
-        .bind(&iv)
-        .bind(&tag)
-        .fetch_one(pool)
-        .await?;
+        let mut written: Vec<String> = Vec::new();
+        let result = async {
+            let mut tx = pool.begin().await?;
+            let mut docs = Vec::with_capacity(files.len());
+            for file in &files {
+                let stored = store_file(media_dir, tenant, keys, &file.bytes).await?;
+                written.push(stored.storage_path.clone());
 
-        Ok(doc)
+                let doc = sqlx::query_as::<_, Document>(&format!(
+                    "INSERT INTO {schema}.documents
+                     (uploader_id, title, category, original_filename, storage_path, content_type, size_bytes, group_id, child_id,
+                      visibility, is_encrypted, encryption_iv, encryption_tag, requires_signature, key_version, folder_id,
+                      expires_at)
+                     VALUES ($1, $2, $3::\"{schema}\".doc_category, $4, $5, $6, $7, $8, $9, $10::\"{schema}\".doc_visibility, $11, $12, $13, $14, $15, $16, $17)
+                     RETURNING {DOC_COLS}"
+                ))
+                .bind(uploader_id)
+                .bind(upload_title(title.as_deref(), &file.filename, files.len()))
+                .bind(&category)
+                .bind(&file.filename)
+                .bind(&stored.storage_path)
+                .bind(&file.content_type)
+                .bind(stored.size_bytes)
+                .bind(db_group_id)
+                .bind(db_child_id)
+                .bind(&visibility)
+                .bind(true) // is_encrypted
+                .bind(&stored.iv)
+                .bind(&stored.tag)
+                .bind(requires_signature)
+                .bind(stored.key_version)
+                .bind(folder_id)
+                .bind(expires_at)
+                .fetch_one(&mut *tx)
+                .await?;
+                docs.push(doc);
+            }
+            tx.commit().await?;
+            anyhow::Ok(docs)
+        }
+        .await;
+
+        // All or nothing: files of a failed batch are not left behind
+        if result.is_err() {
+            remove_files(media_dir, &written).await;
+        }
+        result
     }
 
     pub async fn list(
@@ -156,6 +321,7 @@ impl DocumentService {
                  WHERE ($1::text IS NULL OR d.category::text = $1)
                    AND ($2::uuid IS NULL OR d.group_id = $2)
                    AND ($3::uuid IS NULL OR d.child_id = $3)
+                   AND ($6::uuid IS NULL OR d.folder_id = $6)
                  ORDER BY d.created_at DESC
                  LIMIT $4 OFFSET $5"
             ))
@@ -164,6 +330,7 @@ impl DocumentService {
             .bind(query.child_id)
             .bind(per_page)
             .bind(offset)
+            .bind(query.folder_id)
             .fetch_all(pool)
             .await?
         } else {
@@ -190,6 +357,7 @@ impl DocumentService {
                    AND ($2::text IS NULL OR d.category::text = $2)
                    AND ($3::uuid IS NULL OR d.group_id = $3)
                    AND ($4::uuid IS NULL OR d.child_id = $4)
+                   AND ($7::uuid IS NULL OR d.folder_id = $7)
                  ORDER BY d.created_at DESC
                  LIMIT $5 OFFSET $6"
             ))
@@ -199,6 +367,7 @@ impl DocumentService {
             .bind(query.child_id)
             .bind(per_page)
             .bind(offset)
+            .bind(query.folder_id)
             .fetch_all(pool)
             .await?
         };
@@ -227,12 +396,22 @@ impl DocumentService {
             _ => (None, None),
         };
 
+        if let Some(folder_id) = req.folder_id {
+            Self::ensure_folder(pool, &schema, folder_id).await?;
+        }
+        let expires_at = req.expires_at.as_deref().map(parse_expiry).transpose()?;
+
         let doc = if is_staff {
             sqlx::query_as::<_, Document>(&format!(
                 "UPDATE {schema}.documents
                  SET title = $2, category = $3::\"{schema}\".doc_category,
                      group_id = $4, child_id = $5,
-                     visibility = $6::\"{schema}\".doc_visibility
+                     visibility = $6::\"{schema}\".doc_visibility,
+                     requires_signature = COALESCE($7, requires_signature),
+                     folder_id = COALESCE($8, folder_id),
+                     expires_at = CASE WHEN $9 THEN $10 ELSE expires_at END,
+                     expiry_reminder_days = CASE WHEN $9 THEN NULL ELSE expiry_reminder_days END,
+                     is_template = COALESCE($11, is_template)
                  WHERE id = $1
                  RETURNING {DOC_COLS}"
             ))
@@ -242,6 +421,11 @@ impl DocumentService {
             .bind(new_group_id)
             .bind(new_child_id)
             .bind(&req.visibility)
+            .bind(req.requires_signature)
+            .bind(req.folder_id)
+            .bind(expires_at.is_some())
+            .bind(expires_at.flatten())
+            .bind(req.is_template)
             .fetch_optional(pool)
             .await?
         } else {
@@ -249,7 +433,11 @@ impl DocumentService {
                 "UPDATE {schema}.documents
                  SET title = $2, category = $3::\"{schema}\".doc_category,
                      group_id = $4, child_id = $5,
-                     visibility = $6::\"{schema}\".doc_visibility
+                     visibility = $6::\"{schema}\".doc_visibility,
+                     requires_signature = COALESCE($8, requires_signature),
+                     folder_id = COALESCE($9, folder_id),
+                     expires_at = CASE WHEN $10 THEN $11 ELSE expires_at END,
+                     expiry_reminder_days = CASE WHEN $10 THEN NULL ELSE expiry_reminder_days END
                  WHERE id = $1 AND uploader_id = $7
                  RETURNING {DOC_COLS}"
             ))
@@ -260,6 +448,10 @@ impl DocumentService {
             .bind(new_child_id)
             .bind(&req.visibility)
             .bind(user_id)
+            .bind(req.requires_signature)
+            .bind(req.folder_id)
+            .bind(expires_at.is_some())
+            .bind(expires_at.flatten())
             .fetch_optional(pool)
             .await?
         };
@@ -298,6 +490,8 @@ impl DocumentService {
         let Some((storage_path,)) = row else {
             return Ok(false);
         };
+        let mut paths = Self::version_paths(pool, &schema, &[doc_id]).await?;
+        paths.push(storage_path);
 
         if is_staff {
             sqlx::query(&format!("DELETE FROM {schema}.documents WHERE id = $1"))
@@ -314,10 +508,562 @@ impl DocumentService {
             .await?;
         }
 
-        let _ = tokio::fs::remove_file(
-            PathBuf::from(media_dir).join(&storage_path)
-        ).await;
+        remove_files(media_dir, &paths).await;
 
         Ok(true)
     }
+
+    /// Staff bulk action over several documents: "delete", "assign" (visibility)
+    /// or "move" (folder). Returns the number of documents affected.
+    pub async fn bulk(
+        pool: &PgPool,
+        tenant: &str,
+        req: &BulkDocumentRequest,
+        media_dir: &str,
+    ) -> anyhow::Result<usize> {
+        let schema = schema_name(tenant);
+
+        match req.action.as_str() {
+            "delete" => {
+                let mut paths: Vec<String> = sqlx::query_scalar(&format!(
+                    "SELECT storage_path FROM {schema}.documents WHERE id = ANY($1)"
+                ))
+                .bind(&req.document_ids)
+                .fetch_all(pool)
+                .await?;
+                let count = paths.len();
+                paths.extend(Self::version_paths(pool, &schema, &req.document_ids).await?);
+
+                sqlx::query(&format!("DELETE FROM {schema}.documents WHERE id = ANY($1)"))
+                    .bind(&req.document_ids)
+                    .execute(pool)
+                    .await?;
+
+                remove_files(media_dir, &paths).await;
+                Ok(count)
+            }
+            "assign" => {
+                let visibility = req.visibility.as_deref().unwrap_or("private");
+                let db_group_id = if visibility == "group" { req.group_id } else { None };
+                let db_child_id = if visibility == "child" { req.child_id } else { None };
+
+                let updated = sqlx::query(&format!(
+                    "UPDATE {schema}.documents
+                     SET visibility = $1::\"{schema}\".doc_visibility, group_id = $2, child_id = $3
+                     WHERE id = ANY($4)"
+                ))
+                .bind(visibility)
+                .bind(db_group_id)
+                .bind(db_child_id)
+                .bind(&req.document_ids)
+                .execute(pool)
+                .await?;
+                Ok(updated.rows_affected() as usize)
+            }
+            "move" => {
+                if let Some(folder_id) = req.folder_id {
+                    Self::ensure_folder(pool, &schema, folder_id).await?;
+                }
+                let updated = sqlx::query(&format!(
+                    "UPDATE {schema}.documents SET folder_id = $1 WHERE id = ANY($2)"
+                ))
+                .bind(req.folder_id)
+                .bind(&req.document_ids)
+                .execute(pool)
+                .await?;
+                Ok(updated.rows_affected() as usize)
+            }
+            _ => Err(DocumentError::UnknownAction(req.action.clone()).into()),
+        }
+    }
+
+    // ─── Versions ─────────────────────────────────────────────────────────────
+
+    /// Replace the file of a document, keeping the previous one in its history.
+    /// An "expires_at" field renews the document: the new date replaces the old one
+    /// and expiry reminders start over.
+    /// Returns `Ok(None)` if the document does not exist or the user may not edit it.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn replace(
+        pool: &PgPool,
+        redis: &mut redis::aio::MultiplexedConnection,
+        tenant: &str,
+        doc_id: Uuid,
+        user_id: Uuid,
+        is_staff: bool,
+        media_dir: &str,
+        keys: &KeyRing,
+        mut multipart: Multipart,
+    ) -> anyhow::Result<Option<Document>> {
+        let schema = schema_name(tenant);
+
+        let editable: Option<Uuid> = sqlx::query_scalar(&format!(
+            "SELECT id FROM {schema}.documents WHERE id = $1 AND ($2 OR uploader_id = $3)"
+        ))
+        .bind(doc_id)
+        .bind(is_staff)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+        if editable.is_none() {
+            return Ok(None);
+        }
+
+        let mut file: Option<UploadedFile> = None;
+        let mut expires_at: Option<Option<NaiveDate>> = None;
+        while let Some(field) = multipart.next_field().await? {
+            match field.name() {
+                Some("file") => file = Some(read_file(field).await?),
+                Some("expires_at") => expires_at = Some(parse_expiry(&field.text().await?)?),
+                _ => {}
+            }
+        }
+        let file = file.ok_or(DocumentError::NoFile)?;
+
+        StorageService::ensure_capacity(pool, redis, tenant, file.bytes.len() as i64).await?;
+        let stored = store_file(media_dir, tenant, keys, &file.bytes).await?;
+
+        let result = async {
+            let mut tx = pool.begin().await?;
+
+            let locked: Option<Uuid> = sqlx::query_scalar(&format!(
+                "SELECT id FROM {schema}.documents WHERE id = $1 FOR UPDATE"
+            ))
+            .bind(doc_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+            if locked.is_none() {
+                return Ok(None);
+            }
+
+            // The current file becomes a version; it was uploaded by whoever
+            // replaced the version before it, or by the document's uploader.
+            sqlx::query(&format!(
+                "INSERT INTO {schema}.document_versions
+                 (document_id, version, original_filename, storage_path, content_type, size_bytes,
+                  is_encrypted, encryption_iv, encryption_tag, key_version, scan_status,
+                  uploader_id, uploaded_at, replaced_by)
+                 SELECT d.id, d.version, d.original_filename, d.storage_path, d.content_type, d.size_bytes,
+                        d.is_encrypted, d.encryption_iv, d.encryption_tag, d.key_version, d.scan_status,
+                        COALESCE(p.replaced_by, d.uploader_id), COALESCE(p.replaced_at, d.created_at), $2
+                 FROM {schema}.documents d
+                 LEFT JOIN {schema}.document_versions p
+                        ON p.document_id = d.id AND p.version = d.version - 1
+                 WHERE d.id = $1"
+            ))
+            .bind(doc_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+            let doc = sqlx::query_as::<_, Document>(&format!(
+                "UPDATE {schema}.documents
+                 SET original_filename = $2, storage_path = $3, content_type = $4, size_bytes = $5,
+                     is_encrypted = TRUE, encryption_iv = $6, encryption_tag = $7, key_version = $8,
+                     version = version + 1,
+                     scan_status = 'pending', scan_signature = NULL, scanned_at = NULL,
+                     expires_at = CASE WHEN $9 THEN $10 ELSE expires_at END,
+                     expiry_reminder_days = CASE WHEN $9 THEN NULL ELSE expiry_reminder_days END
+                 WHERE id = $1
+                 RETURNING {DOC_COLS}"
+            ))
+            .bind(doc_id)
+            .bind(&file.filename)
+            .bind(&stored.storage_path)
+            .bind(&file.content_type)
+            .bind(stored.size_bytes)
+            .bind(&stored.iv)
+            .bind(&stored.tag)
+            .bind(stored.key_version)
+            .bind(expires_at.is_some())
+            .bind(expires_at.flatten())
+            .fetch_one(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            anyhow::Ok(Some(doc))
+        }
+        .await;
+
+        if !matches!(result, Ok(Some(_))) {
+            remove_files(media_dir, &[stored.storage_path]).await;
+        }
+        result
+    }
+
+    /// Previous files of a document, newest first.
+    pub async fn list_versions(
+        pool: &PgPool,
+        tenant: &str,
+        doc_id: Uuid,
+    ) -> anyhow::Result<Vec<DocumentVersion>> {
+        let schema = schema_name(tenant);
+        let rows = sqlx::query_as::<_, DocumentVersion>(&format!(
+            "SELECT id, document_id, version, original_filename, storage_path, content_type, size_bytes,
+                    uploader_id, uploaded_at, replaced_by, replaced_at
+             FROM {schema}.document_versions
+             WHERE document_id = $1
+             ORDER BY version DESC"
+        ))
+        .bind(doc_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
+    }
+
+    async fn version_paths(pool: &PgPool, schema: &str, doc_ids: &[Uuid]) -> anyhow::Result<Vec<String>> {
+        let paths = sqlx::query_scalar(&format!(
+            "SELECT storage_path FROM {schema}.document_versions WHERE document_id = ANY($1)"
+        ))
+        .bind(doc_ids)
+        .fetch_all(pool)
+        .await?;
+        Ok(paths)
+    }
+
+    // ─── Folders ──────────────────────────────────────────────────────────────
+
+    /// Every folder of the tenant, flat; clients rebuild the tree from `parent_id`.
+    /// Parents only count the documents they can see.
+    pub async fn list_folders(
+        pool: &PgPool,
+        tenant: &str,
+        user_id: Uuid,
+        is_staff: bool,
+    ) -> anyhow::Result<Vec<DocumentFolder>> {
+        let schema = schema_name(tenant);
+        let visible = parent_can_see(&schema);
+        let rows = sqlx::query_as::<_, DocumentFolder>(&format!(
+            "SELECT f.id, f.name, f.parent_id,
+                    (SELECT COUNT(*) FROM {schema}.documents d
+                     JOIN {schema}.users u ON u.id = $1
+                     WHERE d.folder_id = f.id AND ($2 OR {visible})) AS document_count,
+                    f.created_at, f.updated_at
+             FROM {schema}.document_folders f
+             ORDER BY LOWER(f.name)"
+        ))
+        .bind(user_id)
+        .bind(is_staff)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
+    }
+
+    pub async fn create_folder(
+        pool: &PgPool,
+        tenant: &str,
+        user_id: Uuid,
+        req: &FolderRequest,
+    ) -> anyhow::Result<DocumentFolder> {
+        let name = folder_name(&req.name).ok_or(DocumentError::InvalidFolderName)?;
+        let schema = schema_name(tenant);
+        if let Some(parent_id) = req.parent_id {
+            Self::ensure_folder(pool, &schema, parent_id).await?;
+        }
+
+        sqlx::query_as::<_, DocumentFolder>(&format!(
+            "INSERT INTO {schema}.document_folders (name, parent_id, created_by)
+             VALUES ($1, $2, $3)
+             RETURNING id, name, parent_id, 0::BIGINT AS document_count, created_at, updated_at"
+        ))
+        .bind(name)
+        .bind(req.parent_id)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .map_err(folder_write_error)
+    }
+
+    /// Rename a folder and/or move it under another one (`parent_id: None` is the root).
+    pub async fn update_folder(
+        pool: &PgPool,
+        tenant: &str,
+        folder_id: Uuid,
+        req: &FolderRequest,
+    ) -> anyhow::Result<Option<DocumentFolder>> {
+        let name = folder_name(&req.name).ok_or(DocumentError::InvalidFolderName)?;
+        let schema = schema_name(tenant);
+        if let Some(parent_id) = req.parent_id {
+            Self::ensure_folder(pool, &schema, parent_id).await?;
+            let cycle: bool = sqlx::query_scalar(&format!(
+                "WITH RECURSIVE ancestors AS (
+                     SELECT id, parent_id FROM {schema}.document_folders WHERE id = $1
+                     UNION
+                     SELECT f.id, f.parent_id FROM {schema}.document_folders f
+                     JOIN ancestors a ON f.id = a.parent_id
+                 )
+                 SELECT EXISTS (SELECT 1 FROM ancestors WHERE id = $2)"
+            ))
+            .bind(parent_id)
+            .bind(folder_id)
+            .fetch_one(pool)
+            .await?;
+            if cycle {
+                return Err(DocumentError::FolderCycle.into());
+            }
+        }
+
+        sqlx::query_as::<_, DocumentFolder>(&format!(
+            "UPDATE {schema}.document_folders SET name = $2, parent_id = $3
+             WHERE id = $1
+             RETURNING id, name, parent_id,
+                       (SELECT COUNT(*) FROM {schema}.documents d WHERE d.folder_id = document_folders.id) AS document_count,
+                       created_at, updated_at"
+        ))
+        .bind(folder_id)
+        .bind(name)
+        .bind(req.parent_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(folder_write_error)
+    }
+
+    /// Delete a folder and its sub-folders; their documents move back to the root.
+    pub async fn delete_folder(pool: &PgPool, tenant: &str, folder_id: Uuid) -> anyhow::Result<bool> {
+        let schema = schema_name(tenant);
+        let deleted = sqlx::query(&format!("DELETE FROM {schema}.document_folders WHERE id = $1"))
+            .bind(folder_id)
+            .execute(pool)
+            .await?;
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    async fn ensure_folder(pool: &PgPool, schema: &str, folder_id: Uuid) -> anyhow::Result<()> {
+        let exists: bool = sqlx::query_scalar(&format!(
+            "SELECT EXISTS (SELECT 1 FROM {schema}.document_folders WHERE id = $1)"
+        ))
+        .bind(folder_id)
+        .fetch_one(pool)
+        .await?;
+        if !exists {
+            return Err(DocumentError::FolderNotFound.into());
+        }
+        Ok(())
+    }
+
+    // ─── Expiry ───────────────────────────────────────────────────────────────
+
+    /// Documents expiring within `days`, already expired ones included, grouped by child.
+    pub async fn list_expiring(
+        pool: &PgPool,
+        tenant: &str,
+        days: i32,
+    ) -> anyhow::Result<Vec<ExpiringDocument>> {
+        let schema = schema_name(tenant);
+        let rows = sqlx::query_as::<_, ExpiringDocument>(&format!(
+            "SELECT d.id AS document_id, d.title, d.category::TEXT AS category,
+                    d.child_id, c.first_name AS child_first_name, c.last_name AS child_last_name,
+                    d.expires_at, (d.expires_at - CURRENT_DATE) AS days_left,
+                    d.expiry_reminder_days AS reminder_sent_days
+             FROM {schema}.documents d
+             LEFT JOIN {schema}.children c ON c.id = d.child_id
+             WHERE d.expires_at IS NOT NULL AND d.expires_at <= CURRENT_DATE + $1
+             ORDER BY c.last_name NULLS LAST, c.first_name, d.child_id, d.expires_at"
+        ))
+        .bind(days)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// The parents of each child with a document within the first reminder threshold.
+    pub async fn expiry_reminder_rows(pool: &PgPool, tenant: &str) -> anyhow::Result<Vec<ExpiryReminderRow>> {
+        let schema = schema_name(tenant);
+        let horizon = EXPIRY_REMINDER_DAYS.iter().copied().max().unwrap_or(0);
+        let rows = sqlx::query_as::<_, ExpiryReminderRow>(&format!(
+            "SELECT d.id AS document_id, d.title, d.expires_at, (d.expires_at - CURRENT_DATE) AS days_left,
+                    d.expiry_reminder_days AS reminder_sent_days, c.first_name AS child_first_name,
+                    u.id AS user_id, u.email, u.first_name, u.last_name
+             FROM {schema}.documents d
+             JOIN {schema}.children c ON c.id = d.child_id
+             JOIN {schema}.child_parents cp ON cp.child_id = c.id
+             JOIN {schema}.users u ON u.id = cp.user_id AND u.is_active = TRUE
+             WHERE d.expires_at IS NOT NULL AND d.expires_at <= CURRENT_DATE + $1
+               AND (d.expiry_reminder_days IS NULL OR d.expiry_reminder_days > 0)
+             ORDER BY d.id"
+        ))
+        .bind(horizon)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Record that the parents were reminded at `threshold` days before expiry.
+    pub async fn mark_expiry_reminded(
+        pool: &PgPool,
+        tenant: &str,
+        doc_id: Uuid,
+        threshold: i32,
+    ) -> anyhow::Result<()> {
+        let schema = schema_name(tenant);
+        sqlx::query(&format!(
+            "UPDATE {schema}.documents SET expiry_reminder_days = $2 WHERE id = $1"
+        ))
+        .bind(doc_id)
+        .bind(threshold)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    // ─── E-signatures ─────────────────────────────────────────────────────────
+
+    /// Record a parent's signature on a document they can see.
+    /// Returns `Ok(None)` if the document does not exist, is not visible to the
+    /// parent or does not require a signature, and an error if already signed.
+    pub async fn sign(
+        pool: &PgPool,
+        tenant: &str,
+        doc_id: Uuid,
+        user_id: Uuid,
+        typed_name: &str,
+        ip_address: &str,
+    ) -> anyhow::Result<Option<DocumentSignature>> {
+        let schema = schema_name(tenant);
+        let visible = parent_can_see(&schema);
+
+        let signable: Option<Uuid> = sqlx::query_scalar(&format!(
+            "SELECT d.id FROM {schema}.documents d
+             JOIN {schema}.users u ON u.id = $2
+             WHERE d.id = $1 AND d.requires_signature = TRUE AND {visible}"
+        ))
+        .bind(doc_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+        if signable.is_none() {
+            return Ok(None);
+        }
+
+        let signature = sqlx::query_as::<_, DocumentSignature>(&format!(
+            "INSERT INTO {schema}.document_signatures (document_id, user_id, typed_name, ip_address)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (document_id, user_id) DO NOTHING
+             RETURNING id, document_id, user_id, typed_name, ip_address, signed_at"
+        ))
+        .bind(doc_id)
+        .bind(user_id)
+        .bind(typed_name.trim())
+        .bind(ip_address)
+        .fetch_optional(pool)
+        .await?;
+
+        match signature {
+            Some(s) => Ok(Some(s)),
+            None => Err(DocumentError::AlreadySigned.into()),
+        }
+    }
+
+    /// All signatures recorded for a document (staff view).
+    pub async fn list_signatures(
+        pool: &PgPool,
+        tenant: &str,
+        doc_id: Uuid,
+    ) -> anyhow::Result<Vec<DocumentSignature>> {
+        let schema = schema_name(tenant);
+        let rows = sqlx::query_as::<_, DocumentSignature>(&format!(
+            "SELECT id, document_id, user_id, typed_name, ip_address, signed_at
+             FROM {schema}.document_signatures
+             WHERE document_id = $1
+             ORDER BY signed_at"
+        ))
+        .bind(doc_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// The signatures of a single parent, used to flag signed documents in the parent UI.
+    pub async fn list_signatures_for_user(
+        pool: &PgPool,
+        tenant: &str,
+        user_id: Uuid,
+    ) -> anyhow::Result<Vec<DocumentSignature>> {
+        let schema = schema_name(tenant);
+        let rows = sqlx::query_as::<_, DocumentSignature>(&format!(
+            "SELECT id, document_id, user_id, typed_name, ip_address, signed_at
+             FROM {schema}.document_signatures
+             WHERE user_id = $1
+             ORDER BY signed_at DESC"
+        ))
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Every (document, parent) pair where the parent can see a document that
+    /// requires a signature but has not signed it yet.
+    /// Pass `doc_id` to restrict the result to a single document.
+    pub async fn list_outstanding_signatures(
+        pool: &PgPool,
+        tenant: &str,
+        doc_id: Option<Uuid>,
+    ) -> anyhow::Result<Vec<OutstandingSignature>> {
+        let schema = schema_name(tenant);
+        let visible = parent_can_see(&schema);
+        let rows = sqlx::query_as::<_, OutstandingSignature>(&format!(
+            "SELECT d.id AS document_id, d.title AS document_title,
+                    u.id AS user_id, u.email, u.first_name, u.last_name,
+                    d.created_at AS document_created_at
+             FROM {schema}.documents d
+             JOIN {schema}.users u ON u.role::text = 'parent' AND u.is_active = TRUE
+             WHERE d.requires_signature = TRUE
+               AND ($1::uuid IS NULL OR d.id = $1)
+               AND {visible}
+               AND NOT EXISTS (
+                   SELECT 1 FROM {schema}.document_signatures s
+                   WHERE s.document_id = d.id AND s.user_id = u.id
+               )
+             ORDER BY d.created_at DESC, u.last_name, u.first_name"
+        ))
+        .bind(doc_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn title_only_applies_to_single_file_uploads() {
+        assert_eq!(upload_title(Some(" Menu de mars "), "menu.pdf", 1), "Menu de mars");
+        assert_eq!(upload_title(Some("Menu de mars"), "menu.pdf", 2), "menu.pdf");
+        assert_eq!(upload_title(Some("  "), "menu.pdf", 1), "menu.pdf");
+        assert_eq!(upload_title(None, "menu.pdf", 1), "menu.pdf");
+    }
+
+    #[test]
+    fn expiry_dates_parse_or_clear() {
+        assert_eq!(parse_expiry(" 2026-09-01 ").unwrap(), NaiveDate::from_ymd_opt(2026, 9, 1));
+        assert_eq!(parse_expiry("").unwrap(), None);
+        assert!(parse_expiry("01/09/2026").is_err());
+    }
+
+    #[test]
+    fn expiry_reminders_go_out_once_per_threshold() {
+        assert_eq!(due_expiry_reminder(45, None), None);
+        assert_eq!(due_expiry_reminder(30, None), Some(30));
+        assert_eq!(due_expiry_reminder(20, Some(30)), None);
+        assert_eq!(due_expiry_reminder(7, Some(30)), Some(7));
+        // A late first run only sends the closest reminder
+        assert_eq!(due_expiry_reminder(3, None), Some(7));
+        assert_eq!(due_expiry_reminder(0, Some(7)), Some(0));
+        assert_eq!(due_expiry_reminder(-12, Some(7)), Some(0));
+        assert_eq!(due_expiry_reminder(-12, Some(0)), None);
+    }
+
+    #[test]
+    fn folder_names_are_trimmed_and_validated() {
+        assert_eq!(folder_name("  Formulaires "), Some("Formulaires"));
+        assert_eq!(folder_name(""), None);
+        assert_eq!(folder_name("   "), None);
+        assert_eq!(folder_name("2025/2026"), None);
+        assert_eq!(folder_name(&"é".repeat(255)), Some("é".repeat(255).as_str()));
+        assert_eq!(folder_name(&"a".repeat(256)), None);
+    }
 }
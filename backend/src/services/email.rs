@@ -1,16 +1,94 @@
 use anyhow::Context;
 use lettre::{
-    message::{header::ContentType, Mailbox, MultiPart, SinglePart},
-    transport::smtp::authentication::Credentials,
+    message::{header::ContentType, Attachment, Mailbox, MultiPart, SinglePart},
+    address::Envelope,
+    transport::smtp::{self, authentication::Credentials, response::Response},
     AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
 };
+use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::config::Config;
+use crate::{
+    config::Config,
+    models::{
+        email_log::{EMAIL_STATUS_FAILED, EMAIL_STATUS_SENT, EMAIL_STATUS_SUPPRESSED},
+        email_template::{EmailTemplate, RenderedEmail},
+        invoice::Invoice,
+        meeting::MeetingDetails,
+        message_draft::ParentRecipient,
+        operation::{PendingOperation, OP_ARCHIVE_GARDERIE, OP_DELETE_GARDERIE, OP_RESTORE, OP_RESTORE_GARDERIE},
+        ratio::GroupRatio,
+        tenant::TenantBranding,
+    },
+    services::{
+        email_i18n::{fill, tr, Locale},
+        email_log::{replace_message_id, EmailLogError, EmailLogService, LoggedEmail},
+        email_suppressions::EmailSuppressionService,
+        email_tracking::Campaign,
+        invoices::format_cents,
+        journal_photos::JournalPhoto,
+        message_drafts::TemplateVars,
+    },
+};
+
+/// Minimal escaping for tenant-provided text placed in email HTML.
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Admin-written plain text as HTML paragraphs: blank lines split paragraphs, single
+/// newlines become line breaks.
+fn paragraphs(text: &str) -> String {
+    text.split("\n\n")
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            format!(
+                r#"<p style="margin:0 0 16px 0;font-size:15px;color:#334155;line-height:1.7">{}</p>"#,
+                escape_html(p).replace('\n', "<br>")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Emphasised inline text, as used for names and durations in the card body.
+fn strong(text: &str) -> String {
+    format!(r#"<strong style="color:#334155">{text}</strong>"#)
+}
+
+/// Where a garderie email is recorded: the tenant's `email_log`, under a template name.
+#[derive(Debug, Clone, Copy)]
+struct LogAs<'a> {
+    tenant: &'a str,
+    template: &'static str,
+}
+
+/// How a send ended, for the email log.
+enum Delivery {
+    /// With the relay's reply, which carries the provider's message id.
+    Sent(String),
+    Failed(String),
+    Suppressed,
+}
+
+impl Delivery {
+    fn of(result: &Result<Response, smtp::Error>) -> Self {
+        match result {
+            Ok(response) => Self::Sent(response.message().collect::<Vec<_>>().join(" ")),
+            Err(e) => Self::Failed(e.to_string()),
+        }
+    }
+}
 
 pub struct EmailService {
     transport: AsyncSmtpTransport<Tokio1Executor>,
     from: Mailbox,
+    /// Suppression list and email log, see [`EmailService::with_db`].
+    db: Option<PgPool>,
 }
 
 impl EmailService {
@@ -38,19 +116,104 @@ impl EmailService {
 
         let from: Mailbox = from_addr.parse().ok()?;
 
-        Some(Self { transport, from })
+        Some(Self { transport, from, db: None })
+    }
+
+    /// Skip addresses the provider reported as undeliverable (bounce or complaint) and
+    /// record garderie emails in their `email_log`.
+    pub fn with_db(mut self, pool: PgPool) -> Self {
+        self.db = Some(pool);
+        self
+    }
+
+    /// Send a logged email again to the same recipient, under a new Message-ID.
+    pub async fn resend(&self, tenant: &str, id: Uuid) -> anyhow::Result<()> {
+        let pool = self.db.as_ref().context("Email log not configured")?;
+        let original = EmailLogService::get_for_resend(pool, tenant, id)
+            .await?
+            .ok_or(EmailLogError::NotFound)?;
+        let raw = original.raw_message.ok_or(EmailLogError::NotResendable)?;
+        if EmailSuppressionService::is_suppressed(pool, &original.recipient).await? {
+            return Err(EmailLogError::Suppressed.into());
+        }
+
+        let message_id = self.new_message_id();
+        let raw = match &original.message_id {
+            Some(old) => replace_message_id(&raw, old, &message_id),
+            None => raw,
+        };
+        let envelope = Envelope::new(Some(self.from.email.clone()), vec![original.recipient.parse()?])?;
+        let result = self.transport.send_raw(&envelope, &raw).await;
+
+        let logged = LoggedEmail {
+            template: original.template,
+            recipients: vec![original.recipient],
+            subject: original.subject,
+            message_id: Some(message_id),
+            raw: Some(raw),
+            resent_from: Some(id),
+        };
+        self.log_delivery(tenant, &logged, Delivery::of(&result)).await;
+        result.context("Failed to send email")?;
+        Ok(())
     }
 
     // ─── Private helpers ─────────────────────────────────────────────────────
 
+    /// Hands a message to the SMTP relay, unless its recipient is suppressed: the
+    /// send is then dropped silently, like any email to a garderie without SMTP.
+    /// Garderie emails (`log`) are recorded with the outcome either way.
+    async fn deliver(&self, email: Message, log: Option<LogAs<'_>>) -> anyhow::Result<()> {
+        let logged = log.map(|l| (l.tenant, LoggedEmail::capture(&email, l.template)));
+
+        if let Some(pool) = &self.db {
+            for to in email.envelope().to() {
+                match EmailSuppressionService::is_suppressed(pool, to.as_ref()).await {
+                    Ok(true) => {
+                        tracing::info!("Email to suppressed address {to} not sent");
+                        if let Some((tenant, logged)) = &logged {
+                            self.log_delivery(tenant, logged, Delivery::Suppressed).await;
+                        }
+                        return Ok(());
+                    }
+                    Ok(false) => {}
+                    // Never lose an email because the check failed
+                    Err(e) => tracing::warn!("Email suppression check failed: {e}"),
+                }
+            }
+        }
+
+        let result = self.transport.send(email).await;
+        if let Some((tenant, logged)) = &logged {
+            self.log_delivery(tenant, logged, Delivery::of(&result)).await;
+        }
+        result.context("Failed to send email")?;
+        Ok(())
+    }
+
+    /// Records the outcome of a send. A failure to log never fails the send.
+    async fn log_delivery(&self, tenant: &str, logged: &LoggedEmail, outcome: Delivery) {
+        let Some(pool) = &self.db else { return };
+        let (status, response, error) = match &outcome {
+            Delivery::Sent(response) => (EMAIL_STATUS_SENT, Some(response.as_str()), None),
+            Delivery::Failed(error) => (EMAIL_STATUS_FAILED, None, Some(error.as_str())),
+            Delivery::Suppressed => (EMAIL_STATUS_SUPPRESSED, None, None),
+        };
+        if let Err(e) = EmailLogService::record(pool, tenant, logged, status, response, error).await {
+            tracing::warn!("Failed to record email in {tenant} email log: {e}");
+        }
+    }
+
     fn new_message_id(&self) -> String {
         format!("<{}@{}>", Uuid::new_v4(), self.from.email.domain())
     }
 
     /// Wraps inner HTML content in a consistent branded email layout.
-    /// Shows the tenant logo if logo_url is non-empty, otherwise shows the
-    /// garderie name as text.
-    fn wrap_html(logo_url: &str, garderie_name: &str, content: &str) -> String {
+    /// Shows the tenant logo if set, otherwise shows the garderie name as text;
+    /// the accent color tops the card and the footer text follows the name.
+    fn wrap_html(branding: &TenantBranding, garderie_name: &str, locale: Locale, content: &str) -> String {
+        let lang = locale.tag();
+        let logo_url = branding.logo_url.as_deref().unwrap_or_default();
         let header = if !logo_url.is_empty() {
             format!(
                 r#"<img src="{logo_url}" alt="{garderie_name}" style="max-height:64px;max-width:200px;width:auto;height:auto;display:block;margin:0 auto">"#
@@ -61,9 +224,25 @@ impl EmailService {
             )
         };
 
+        let accent_border = branding
+            .accent_color
+            .as_deref()
+            .map(|c| format!("border-top:4px solid {c};"))
+            .unwrap_or_default();
+        let footer_text = branding
+            .footer_text
+            .as_deref()
+            .map(|f| {
+                format!(
+                    r#"<p style="margin:8px 0 0 0;font-size:12px;color:#94a3b8;line-height:1.5">{}</p>"#,
+                    escape_html(f).replace('\n', "<br>")
+                )
+            })
+            .unwrap_or_default();
+
         format!(
             r#"<!DOCTYPE html>
-<html lang="fr">
+<html lang="{lang}">
 <head>
   <meta charset="utf-8">
   <meta name="viewport" content="width=device-width,initial-scale=1">
@@ -80,13 +259,14 @@ impl EmailService {
             </td>
           </tr>
           <tr>
-            <td style="background:#ffffff;border-radius:12px;padding:40px;box-shadow:0 1px 3px rgba(0,0,0,0.08),0 8px 24px rgba(0,0,0,0.04)">
+            <td style="background:#ffffff;border-radius:12px;{accent_border}padding:40px;box-shadow:0 1px 3px rgba(0,0,0,0.08),0 8px 24px rgba(0,0,0,0.04)">
               {content}
             </td>
           </tr>
           <tr>
             <td align="center" style="padding-top:20px">
               <p style="margin:0;font-size:12px;color:#94a3b8">{garderie_name}</p>
+              {footer_text}
             </td>
           </tr>
         </table>
@@ -98,288 +278,1007 @@ impl EmailService {
         )
     }
 
-    async fn send_email(
+    /// Replies to garderie emails go to its configured address, if any.
+    fn reply_to(branding: &TenantBranding) -> Option<Mailbox> {
+        branding.reply_to.as_deref()?.parse().ok()
+    }
+
+    fn build_message(
         &self,
         from: Mailbox,
+        reply_to: Option<Mailbox>,
         to: Mailbox,
         subject: &str,
         text: &str,
         html: &str,
-    ) -> anyhow::Result<()> {
-        let email = Message::builder()
-            .message_id(Some(self.new_message_id()))
-            .from(from)
+    ) -> anyhow::Result<Message> {
+        let mut builder = Message::builder().message_id(Some(self.new_message_id())).from(from);
+        if let Some(reply_to) = reply_to {
+            builder = builder.reply_to(reply_to);
+        }
+        builder
             .to(to)
             .subject(subject)
-            .multipart(
-                MultiPart::alternative()
-                    .singlepart(
-                        SinglePart::builder()
-                            .header(ContentType::TEXT_PLAIN)
-                            .body(text.to_string()),
-                    )
-                    .singlepart(
-                        SinglePart::builder()
-                            .header(ContentType::TEXT_HTML)
-                            .body(html.to_string()),
-                    ),
+            .multipart(Self::alternative_body(text, html))
+            .context("Failed to build email message")
+    }
+
+    fn alternative_body(text: &str, html: &str) -> MultiPart {
+        MultiPart::alternative()
+            .singlepart(
+                SinglePart::builder()
+                    .header(ContentType::TEXT_PLAIN)
+                    .body(text.to_string()),
             )
-            .context("Failed to build email message")?;
+            .singlepart(
+                SinglePart::builder()
+                    .header(ContentType::TEXT_HTML)
+                    .body(html.to_string()),
+            )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn send_email(
+        &self,
+        from: Mailbox,
+        reply_to: Option<Mailbox>,
+        to: Mailbox,
+        subject: &str,
+        text: &str,
+        html: &str,
+        log: Option<LogAs<'_>>,
+    ) -> anyhow::Result<()> {
+        let email = self.build_message(from, reply_to, to, subject, text, html)?;
+        self.deliver(email, log).await
+    }
+
+    /// Sends a garderie email in its branded layout, from the garderie's name.
+    /// `locale` only sets the layout's language; the content is already translated.
+    #[allow(clippy::too_many_arguments)]
+    async fn send_branded(
+        &self,
+        log: LogAs<'_>,
+        branding: &TenantBranding,
+        garderie_name: &str,
+        locale: Locale,
+        to: Mailbox,
+        subject: &str,
+        text: &str,
+        content: &str,
+    ) -> anyhow::Result<()> {
+        let from = Mailbox::new(Some(garderie_name.to_string()), self.from.email.clone());
+        let html = Self::wrap_html(branding, garderie_name, locale, content);
+        self.send_email(from, Self::reply_to(branding), to, subject, text, &html, Some(log)).await
+    }
+
+    /// Sends an already rendered garderie email, from the garderie's name.
+    async fn send_rendered(
+        &self,
+        log: LogAs<'_>,
+        branding: &TenantBranding,
+        garderie_name: &str,
+        to: Mailbox,
+        email: &RenderedEmail,
+    ) -> anyhow::Result<()> {
+        let from = Mailbox::new(Some(garderie_name.to_string()), self.from.email.clone());
+        self.send_email(from, Self::reply_to(branding), to, &email.subject, &email.text, &email.html, Some(log))
+            .await
+    }
+
+    // ─── Public methods ───────────────────────────────────────────────────────
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_password_reset(
+        &self,
+        tenant: &str,
+        to_email: &str,
+        to_name: &str,
+        reset_url: &str,
+        garderie_name: &str,
+        branding: &TenantBranding,
+        locale: Locale,
+    ) -> anyhow::Result<()> {
+        let to: Mailbox = format!("{to_name} <{to_email}>")
+            .parse()
+            .unwrap_or_else(|_| to_email.parse().expect("valid email address"));
+
+        let subject = tr(locale, "reset.subject", &[("garderie", garderie_name)]);
+        let text = tr(
+            locale,
+            "reset.text",
+            &[("name", to_name), ("garderie", garderie_name), ("url", reset_url)],
+        );
+
+        let primary = branding.primary_color();
+        let heading = tr(locale, "reset.heading", &[]);
+        let greeting = tr(locale, "reset.greeting", &[("name", &strong(to_name))]);
+        let intro = tr(locale, "reset.intro", &[]);
+        let button = tr(locale, "reset.button", &[]);
+        let expiry = tr(locale, "reset.expiry", &[("duration", &strong(&tr(locale, "reset.duration", &[])))]);
+        let content = format!(
+            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">{heading}</h1>
+<p style="margin:0 0 28px 0;font-size:15px;color:#64748b;line-height:1.6">{greeting}<br><br>{intro}</p>
+<table role="presentation" cellpadding="0" cellspacing="0" style="margin-bottom:28px">
+  <tr>
+    <td style="border-radius:8px;background:{primary}">
+      <a href="{reset_url}" style="display:inline-block;padding:13px 28px;color:#ffffff;text-decoration:none;font-weight:600;font-size:15px;border-radius:8px">{button}</a>
+    </td>
+  </tr>
+</table>
+<p style="margin:0;font-size:13px;color:#94a3b8;border-top:1px solid #f1f5f9;padding-top:20px;line-height:1.5">{expiry}</p>"#
+        );
+
+        self.send_branded(LogAs { tenant, template: "password_reset" }, branding, garderie_name, locale, to, &subject, &text, &content).await
+    }
+
+    pub async fn send_2fa_code(
+        &self,
+        tenant: &str,
+        to_email: &str,
+        code: &str,
+        garderie_name: &str,
+        branding: &TenantBranding,
+        locale: Locale,
+    ) -> anyhow::Result<()> {
+        let to: Mailbox = to_email.parse()?;
+
+        let subject = tr(locale, "2fa.subject", &[("garderie", garderie_name)]);
+        let text = tr(locale, "2fa.text", &[("garderie", garderie_name), ("code", code)]);
+
+        let heading = tr(locale, "2fa.heading", &[]);
+        let intro = tr(locale, "2fa.intro", &[]);
+        let expiry = tr(locale, "2fa.expiry", &[("duration", &strong(&tr(locale, "2fa.duration", &[])))]);
+        let content = format!(
+            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">{heading}</h1>
+<p style="margin:0 0 24px 0;font-size:15px;color:#64748b;line-height:1.6">{intro}</p>
+<table role="presentation" width="100%" cellpadding="0" cellspacing="0" style="margin-bottom:24px">
+  <tr>
+    <td align="center" style="background:#f8fafc;border-radius:10px;border:1px solid #e2e8f0;padding:24px 16px">
+      <span style="font-size:44px;font-weight:800;letter-spacing:14px;color:#0f172a;font-variant-numeric:tabular-nums">{code}</span>
+    </td>
+  </tr>
+</table>
+<p style="margin:0;font-size:13px;color:#94a3b8;border-top:1px solid #f1f5f9;padding-top:20px;line-height:1.5">{expiry}</p>"#
+        );
+
+        self.send_branded(LogAs { tenant, template: "2fa_code" }, branding, garderie_name, locale, to, &subject, &text, &content).await
+    }
+
+    /// Security alert for a successful login from a device the user never used before.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_new_device_alert(
+        &self,
+        tenant: &str,
+        to_email: &str,
+        first_name: &str,
+        garderie_name: &str,
+        branding: &TenantBranding,
+        locale: Locale,
+        at: chrono::DateTime<chrono::Utc>,
+        ip_address: &str,
+        user_agent: Option<&str>,
+    ) -> anyhow::Result<()> {
+        self.send_session_alert(
+            tenant, "login_alert", to_email, first_name, garderie_name, branding, locale, at, ip_address, user_agent,
+        )
+        .await
+    }
+
+    /// Security alert for a replayed refresh token; the session it belonged to was revoked.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_token_reuse_alert(
+        &self,
+        tenant: &str,
+        to_email: &str,
+        first_name: &str,
+        garderie_name: &str,
+        branding: &TenantBranding,
+        locale: Locale,
+        at: chrono::DateTime<chrono::Utc>,
+        ip_address: &str,
+        user_agent: Option<&str>,
+    ) -> anyhow::Result<()> {
+        self.send_session_alert(
+            tenant, "token_reuse", to_email, first_name, garderie_name, branding, locale, at, ip_address, user_agent,
+        )
+        .await
+    }
+
+    /// Alert about an event on the user's sessions, worded by the `{prefix}.*` strings,
+    /// with the date, IP address and device it came from.
+    #[allow(clippy::too_many_arguments)]
+    async fn send_session_alert(
+        &self,
+        tenant: &str,
+        prefix: &'static str,
+        to_email: &str,
+        first_name: &str,
+        garderie_name: &str,
+        branding: &TenantBranding,
+        locale: Locale,
+        at: chrono::DateTime<chrono::Utc>,
+        ip_address: &str,
+        user_agent: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let to: Mailbox = to_email.parse()?;
+
+        let local = at.with_timezone(&chrono::Local);
+        let when = format!("{} {}", locale.long_date(local.date_naive()), local.format("%H:%M"));
+        let unknown = tr(locale, "login_alert.unknown_device", &[]);
+        let device = user_agent.filter(|ua| !ua.trim().is_empty()).unwrap_or(&unknown);
+        let args = [
+            ("name", first_name),
+            ("garderie", garderie_name),
+            ("when", when.as_str()),
+            ("ip", ip_address),
+            ("device", device),
+        ];
+        let subject = tr(locale, &format!("{prefix}.subject"), &[("garderie", garderie_name)]);
+        let text = tr(locale, &format!("{prefix}.text"), &args);
+
+        let heading = tr(locale, &format!("{prefix}.heading"), &[]);
+        let intro = escape_html(&tr(locale, &format!("{prefix}.intro"), &args));
+        let advice = tr(locale, &format!("{prefix}.advice"), &[]);
+        let rows: String = [
+            (tr(locale, "login_alert.when", &[]), when.clone()),
+            (tr(locale, "login_alert.ip", &[]), ip_address.to_string()),
+            (tr(locale, "login_alert.device", &[]), device.to_string()),
+        ]
+        .iter()
+        .map(|(label, value)| {
+            format!(
+                r#"<tr><td style="padding:6px 12px;color:#64748b;font-size:13px;white-space:nowrap">{label}</td><td style="padding:6px 12px;color:#0f172a;font-size:13px">{}</td></tr>"#,
+                escape_html(value)
+            )
+        })
+        .collect();
+        let content = format!(
+            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">{heading}</h1>
+<p style="margin:0 0 24px 0;font-size:15px;color:#64748b;line-height:1.6">{intro}</p>
+<table role="presentation" width="100%" cellpadding="0" cellspacing="0" style="margin-bottom:24px;background:#f8fafc;border-radius:10px;border:1px solid #e2e8f0">
+  {rows}
+</table>
+<p style="margin:0;font-size:13px;color:#94a3b8;border-top:1px solid #f1f5f9;padding-top:20px;line-height:1.5">{advice}</p>"#
+        );
+
+        self.send_branded(LogAs { tenant, template: prefix }, branding, garderie_name, locale, to, &subject, &text, &content).await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_invitation(
+        &self,
+        tenant: &str,
+        to_email: &str,
+        invite_url: &str,
+        garderie_name: &str,
+        role: &str,
+        branding: &TenantBranding,
+        locale: Locale,
+        template: Option<&EmailTemplate>,
+    ) -> anyhow::Result<()> {
+        let to: Mailbox = to_email.parse()?;
+        let email = Self::render_invitation(invite_url, garderie_name, role, branding, locale, template);
+        self.send_rendered(LogAs { tenant, template: "invitation" }, branding, garderie_name, to, &email).await
+    }
+
+    /// Invitation email in the garderie's wording if it has a template, else the built-in one.
+    pub fn render_invitation(
+        invite_url: &str,
+        garderie_name: &str,
+        role: &str,
+        branding: &TenantBranding,
+        locale: Locale,
+        template: Option<&EmailTemplate>,
+    ) -> RenderedEmail {
+        let role_label = match role {
+            "admin_garderie" => tr(locale, "role.admin_garderie", &[]),
+            "educateur" => tr(locale, "role.educateur", &[]),
+            _ => tr(locale, "role.parent", &[]),
+        };
+
+        let (subject, text, intro) = match template {
+            Some(t) => {
+                let args = [("garderie", garderie_name), ("role", role_label.as_str()), ("invite_url", invite_url)];
+                let body = fill(&t.body, &args);
+                let text = if body.contains(invite_url) { body.clone() } else { format!("{body}\n\n{invite_url}") };
+                (fill(&t.subject, &args), text, paragraphs(&body))
+            }
+            None => {
+                let subject = tr(locale, "invite.subject", &[("garderie", garderie_name)]);
+                let text = tr(
+                    locale,
+                    "invite.text",
+                    &[("garderie", garderie_name), ("role", &role_label), ("url", invite_url)],
+                );
+                let heading = tr(locale, "invite.heading", &[]);
+                let intro = tr(
+                    locale,
+                    "invite.intro",
+                    &[("garderie", &strong(garderie_name)), ("role", &strong(&role_label))],
+                );
+                let cta = tr(locale, "invite.cta", &[]);
+                let html = format!(
+                    r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">{heading}</h1>
+<p style="margin:0 0 28px 0;font-size:15px;color:#64748b;line-height:1.6">{intro}<br><br>{cta}</p>"#
+                );
+                (subject, text, html)
+            }
+        };
+
+        let primary = branding.primary_color();
+        let button = tr(locale, "invite.button", &[]);
+        let expiry = tr(locale, "invite.expiry", &[("duration", &strong(&tr(locale, "invite.duration", &[])))]);
+        let content = format!(
+            r#"{intro}
+<table role="presentation" cellpadding="0" cellspacing="0" style="margin-bottom:28px">
+  <tr>
+    <td style="border-radius:8px;background:{primary}">
+      <a href="{invite_url}" style="display:inline-block;padding:13px 28px;color:#ffffff;text-decoration:none;font-weight:600;font-size:15px;border-radius:8px">{button}</a>
+    </td>
+  </tr>
+</table>
+<p style="margin:0;font-size:13px;color:#94a3b8;border-top:1px solid #f1f5f9;padding-top:20px;line-height:1.5">{expiry}</p>"#
+        );
+
+        RenderedEmail {
+            subject,
+            text,
+            html: Self::wrap_html(branding, garderie_name, locale, &content),
+        }
+    }
+
+    pub async fn send_message_notification(
+        &self,
+        tenant: &str,
+        to_email: &str,
+        to_name: &str,
+        sender_name: &str,
+        thread_name: &str,
+        app_url: &str,
+        garderie_name: &str,
+        branding: &TenantBranding,
+    ) -> anyhow::Result<()> {
+        let to: Mailbox = format!("{to_name} <{to_email}>")
+            .parse()
+            .unwrap_or_else(|_| to_email.parse().expect("valid email address"));
+
+        let subject = format!("Nouveau message — {thread_name}");
+
+        let text = format!(
+            "Bonjour {to_name},\n\n\
+            Vous avez reçu un nouveau message de {sender_name} dans {thread_name}.\n\n\
+            Connectez-vous pour voir le message :\n\
+            {app_url}\n\n\
+            {garderie_name}"
+        );
+
+        let primary = branding.primary_color();
+        let content = format!(
+            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">Nouveau message</h1>
+<p style="margin:0 0 28px 0;font-size:15px;color:#64748b;line-height:1.6">Bonjour <strong style="color:#334155">{to_name}</strong>,<br><br><strong style="color:#334155">{sender_name}</strong> vous a envoyé un message dans <strong style="color:#334155">{thread_name}</strong>.</p>
+<table role="presentation" cellpadding="0" cellspacing="0">
+  <tr>
+    <td style="border-radius:8px;background:{primary}">
+      <a href="{app_url}" style="display:inline-block;padding:13px 28px;color:#ffffff;text-decoration:none;font-weight:600;font-size:15px;border-radius:8px">Voir le message</a>
+    </td>
+  </tr>
+</table>"#
+        );
+
+        self.send_branded(LogAs { tenant, template: "message_notification" }, branding, garderie_name, Locale::Fr, to, &subject, &text, &content).await
+    }
+
+    pub async fn send_media_notification(
+        &self,
+        tenant: &str,
+        to_email: &str,
+        to_name: &str,
+        uploader_name: &str,
+        content_kind: &str,
+        app_url: &str,
+        garderie_name: &str,
+        branding: &TenantBranding,
+    ) -> anyhow::Result<()> {
+        let to: Mailbox = format!("{to_name} <{to_email}>")
+            .parse()
+            .unwrap_or_else(|_| to_email.parse().expect("valid email address"));
+
+        let subject = format!("Nouveau contenu partagé — {garderie_name}");
+
+        let text = format!(
+            "Bonjour {to_name},\n\n\
+            {uploader_name} a partagé {content_kind} vous concernant sur {garderie_name}.\n\n\
+            Connectez-vous pour voir le contenu :\n\
+            {app_url}\n\n\
+            {garderie_name}"
+        );
+
+        let primary = branding.primary_color();
+        let content = format!(
+            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">Nouveau contenu partagé</h1>
+<p style="margin:0 0 28px 0;font-size:15px;color:#64748b;line-height:1.6">Bonjour <strong style="color:#334155">{to_name}</strong>,<br><br><strong style="color:#334155">{uploader_name}</strong> a partagé {content_kind} vous concernant.</p>
+<table role="presentation" cellpadding="0" cellspacing="0">
+  <tr>
+    <td style="border-radius:8px;background:{primary}">
+      <a href="{app_url}" style="display:inline-block;padding:13px 28px;color:#ffffff;text-decoration:none;font-weight:600;font-size:15px;border-radius:8px">Voir le contenu</a>
+    </td>
+  </tr>
+</table>"#
+        );
+
+        self.send_branded(LogAs { tenant, template: "media_notification" }, branding, garderie_name, Locale::Fr, to, &subject, &text, &content).await
+    }
+
+    /// Annonce à un parent qu'un album photo lui a été partagé.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_album_shared(
+        &self,
+        tenant: &str,
+        to_email: &str,
+        to_name: &str,
+        sharer_name: &str,
+        album_title: &str,
+        message: Option<&str>,
+        app_url: &str,
+        garderie_name: &str,
+        branding: &TenantBranding,
+    ) -> anyhow::Result<()> {
+        let to: Mailbox = format!("{to_name} <{to_email}>")
+            .parse()
+            .unwrap_or_else(|_| to_email.parse().expect("valid email address"));
+
+        let subject = format!("Nouvel album : {album_title} — {garderie_name}");
+
+        let message = message.map(str::trim).filter(|m| !m.is_empty());
+        let text = format!(
+            "Bonjour {to_name},\n\n\
+            {sharer_name} a partagé l'album « {album_title} » avec vous sur {garderie_name}.\n\n\
+            {}\
+            Connectez-vous pour voir les photos :\n\
+            {app_url}\n\n\
+            {garderie_name}",
+            message.map(|m| format!("{m}\n\n")).unwrap_or_default()
+        );
+
+        let primary = branding.primary_color();
+        let content = format!(
+            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">Nouvel album partagé</h1>
+<p style="margin:0 0 24px 0;font-size:15px;color:#64748b;line-height:1.6">Bonjour {},<br><br>{} a partagé l'album {} avec vous.</p>
+{}
+<table role="presentation" cellpadding="0" cellspacing="0">
+  <tr>
+    <td style="border-radius:8px;background:{primary}">
+      <a href="{app_url}" style="display:inline-block;padding:13px 28px;color:#ffffff;text-decoration:none;font-weight:600;font-size:15px;border-radius:8px">Voir l'album</a>
+    </td>
+  </tr>
+</table>"#,
+            strong(&escape_html(to_name)),
+            strong(&escape_html(sharer_name)),
+            strong(&escape_html(album_title)),
+            message.map(paragraphs).unwrap_or_default()
+        );
+
+        self.send_branded(LogAs { tenant, template: "album_shared" }, branding, garderie_name, Locale::Fr, to, &subject, &text, &content).await
+    }
+
+    /// Rappel envoyé à un parent qui n'a pas encore signé un document.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_signature_reminder(
+        &self,
+        tenant: &str,
+        to_email: &str,
+        to_name: &str,
+        document_title: &str,
+        app_url: &str,
+        garderie_name: &str,
+        branding: &TenantBranding,
+    ) -> anyhow::Result<()> {
+        let to: Mailbox = format!("{to_name} <{to_email}>")
+            .parse()
+            .unwrap_or_else(|_| to_email.parse().expect("valid email address"));
+
+        let subject = format!("Signature requise — {document_title}");
+
+        let text = format!(
+            "Bonjour {to_name},\n\n\
+            Le document « {document_title} » de {garderie_name} attend votre signature.\n\n\
+            Connectez-vous pour le consulter et le signer :\n\
+            {app_url}\n\n\
+            {garderie_name}"
+        );
+
+        let primary = branding.primary_color();
+        let content = format!(
+            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">Signature requise</h1>
+<p style="margin:0 0 28px 0;font-size:15px;color:#64748b;line-height:1.6">Bonjour <strong style="color:#334155">{name}</strong>,<br><br>Le document <strong style="color:#334155">« {title} »</strong> attend votre signature.</p>
+<table role="presentation" cellpadding="0" cellspacing="0">
+  <tr>
+    <td style="border-radius:8px;background:{primary}">
+      <a href="{app_url}" style="display:inline-block;padding:13px 28px;color:#ffffff;text-decoration:none;font-weight:600;font-size:15px;border-radius:8px">Signer le document</a>
+    </td>
+  </tr>
+</table>"#,
+            name = escape_html(to_name),
+            title = escape_html(document_title),
+        );
+
+        self.send_branded(LogAs { tenant, template: "signature_reminder" }, branding, garderie_name, Locale::Fr, to, &subject, &text, &content).await
+    }
+
+    /// Demande à un parent de confirmer l'absence de son enfant, qui n'a pas été accueilli
+    /// à l'heure limite de la garderie.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_absence_check(
+        &self,
+        tenant: &str,
+        to_email: &str,
+        to_name: &str,
+        child_name: &str,
+        date: chrono::NaiveDate,
+        app_url: &str,
+        garderie_name: &str,
+        branding: &TenantBranding,
+    ) -> anyhow::Result<()> {
+        let to: Mailbox = format!("{to_name} <{to_email}>")
+            .parse()
+            .unwrap_or_else(|_| to_email.parse().expect("valid email address"));
+
+        let date = date.format("%d/%m/%Y");
+        let subject = format!("Absence de {child_name} à confirmer");
+
+        let text = format!(
+            "Bonjour {to_name},\n\n\
+            {child_name} n'a pas été accueilli(e) à {garderie_name} ce {date} et a été noté(e) absent(e).\n\n\
+            Confirmez l'absence, ou prévenez la garderie si votre enfant arrive plus tard :\n\
+            {app_url}\n\n\
+            {garderie_name}"
+        );
+
+        let primary = branding.primary_color();
+        let content = format!(
+            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">Absence à confirmer</h1>
+<p style="margin:0 0 28px 0;font-size:15px;color:#64748b;line-height:1.6">Bonjour <strong style="color:#334155">{to_name}</strong>,<br><br><strong style="color:#334155">{child_name}</strong> n'a pas été accueilli(e) ce {date} et a été noté(e) absent(e). Confirmez l'absence, ou prévenez la garderie si votre enfant arrive plus tard.</p>
+<table role="presentation" cellpadding="0" cellspacing="0">
+  <tr>
+    <td style="border-radius:8px;background:{primary}">
+      <a href="{app_url}" style="display:inline-block;padding:13px 28px;color:#ffffff;text-decoration:none;font-weight:600;font-size:15px;border-radius:8px">Confirmer l'absence</a>
+    </td>
+  </tr>
+</table>"#
+        );
+
+        self.send_branded(LogAs { tenant, template: "absence_check" }, branding, garderie_name, Locale::Fr, to, &subject, &text, &content).await
+    }
+
+    /// Prévient un administrateur que des groupes sont sous le ratio enfants/éducatrice.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_ratio_alert(
+        &self,
+        tenant: &str,
+        to_email: &str,
+        to_name: &str,
+        groups: &[GroupRatio],
+        app_url: &str,
+        garderie_name: &str,
+        branding: &TenantBranding,
+    ) -> anyhow::Result<()> {
+        let to: Mailbox = format!("{to_name} <{to_email}>")
+            .parse()
+            .unwrap_or_else(|_| to_email.parse().expect("valid email address"));
 
-        self.transport
-            .send(email)
-            .await
-            .context("Failed to send email")?;
+        let subject = format!("Ratio non respecté — {garderie_name}");
+        let lines: Vec<String> = groups
+            .iter()
+            .map(|g| {
+                format!(
+                    "{} : {} enfant(s), {} éducatrice(s) en poste, {} requise(s)",
+                    g.group_name, g.children, g.educators, g.required_educators
+                )
+            })
+            .collect();
 
-        Ok(())
-    }
+        let text = format!(
+            "Bonjour {to_name},\n\n\
+            Le ratio enfants/éducatrice n'est pas respecté en ce moment :\n\n\
+            {}\n\n\
+            Consultez le tableau de bord :\n\
+            {app_url}\n\n\
+            {garderie_name}",
+            lines.iter().map(|l| format!("- {l}")).collect::<Vec<_>>().join("\n")
+        );
 
-    // ─── Public methods ───────────────────────────────────────────────────────
+        let primary = branding.primary_color();
+        let items: String = lines
+            .iter()
+            .map(|l| format!(r#"<li style="margin:0 0 6px 0">{l}</li>"#))
+            .collect();
+        let content = format!(
+            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">Ratio non respecté</h1>
+<p style="margin:0 0 16px 0;font-size:15px;color:#64748b;line-height:1.6">Bonjour <strong style="color:#334155">{to_name}</strong>,<br><br>Le ratio enfants/éducatrice n'est pas respecté en ce moment :</p>
+<ul style="margin:0 0 28px 0;padding-left:20px;font-size:15px;color:#dc2626;line-height:1.6">{items}</ul>
+<table role="presentation" cellpadding="0" cellspacing="0">
+  <tr>
+    <td style="border-radius:8px;background:{primary}">
+      <a href="{app_url}" style="display:inline-block;padding:13px 28px;color:#ffffff;text-decoration:none;font-weight:600;font-size:15px;border-radius:8px">Voir le tableau de bord</a>
+    </td>
+  </tr>
+</table>"#
+        );
 
-    pub async fn send_password_reset(
+        self.send_branded(LogAs { tenant, template: "ratio_alert" }, branding, garderie_name, Locale::Fr, to, &subject, &text, &content).await
+    }
+
+    /// Rappelle à un parent qu'un document de son enfant arrive à échéance (ou est échu).
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_document_expiry_reminder(
         &self,
+        tenant: &str,
         to_email: &str,
         to_name: &str,
-        reset_url: &str,
+        child_name: &str,
+        document_title: &str,
+        expires_at: chrono::NaiveDate,
+        app_url: &str,
         garderie_name: &str,
-        logo_url: &str,
+        branding: &TenantBranding,
     ) -> anyhow::Result<()> {
-        let from = Mailbox::new(Some(garderie_name.to_string()), self.from.email.clone());
         let to: Mailbox = format!("{to_name} <{to_email}>")
             .parse()
             .unwrap_or_else(|_| to_email.parse().expect("valid email address"));
 
-        let subject = format!("Réinitialisation de mot de passe — {garderie_name}");
+        let date = expires_at.format("%d/%m/%Y");
+        let expired = expires_at <= chrono::Local::now().date_naive();
+        let (subject, status) = if expired {
+            (
+                format!("Document échu — {document_title}"),
+                format!("est échu depuis le {date}"),
+            )
+        } else {
+            (
+                format!("Document à renouveler — {document_title}"),
+                format!("expire le {date}"),
+            )
+        };
 
         let text = format!(
             "Bonjour {to_name},\n\n\
-            Vous avez demandé une réinitialisation de mot de passe pour {garderie_name}.\n\n\
-            Cliquez sur ce lien pour créer un nouveau mot de passe (valide 1 heure) :\n\
-            {reset_url}\n\n\
-            Si vous n'avez pas fait cette demande, ignorez cet email.\n\n\
+            Le document « {document_title} » de {child_name} {status}.\n\
+            Merci de transmettre une version à jour à {garderie_name} :\n\
+            {app_url}\n\n\
             {garderie_name}"
         );
 
+        let primary = branding.primary_color();
         let content = format!(
-            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">Réinitialisation de mot de passe</h1>
-<p style="margin:0 0 28px 0;font-size:15px;color:#64748b;line-height:1.6">Bonjour <strong style="color:#334155">{to_name}</strong>,<br><br>Vous avez demandé une réinitialisation de votre mot de passe. Cliquez sur le bouton ci-dessous pour en créer un nouveau.</p>
-<table role="presentation" cellpadding="0" cellspacing="0" style="margin-bottom:28px">
+            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">Document à renouveler</h1>
+<p style="margin:0 0 28px 0;font-size:15px;color:#64748b;line-height:1.6">Bonjour <strong style="color:#334155">{to_name}</strong>,<br><br>Le document <strong style="color:#334155">« {document_title} »</strong> de {child_name} {status}. Merci de transmettre une version à jour.</p>
+<table role="presentation" cellpadding="0" cellspacing="0">
   <tr>
-    <td style="border-radius:8px;background:#2563eb">
-      <a href="{reset_url}" style="display:inline-block;padding:13px 28px;color:#ffffff;text-decoration:none;font-weight:600;font-size:15px;border-radius:8px">Réinitialiser mon mot de passe</a>
+    <td style="border-radius:8px;background:{primary}">
+      <a href="{app_url}" style="display:inline-block;padding:13px 28px;color:#ffffff;text-decoration:none;font-weight:600;font-size:15px;border-radius:8px">Voir les documents</a>
     </td>
   </tr>
-</table>
-<p style="margin:0;font-size:13px;color:#94a3b8;border-top:1px solid #f1f5f9;padding-top:20px;line-height:1.5">Ce lien expire dans <strong style="color:#64748b">1 heure</strong>. Si vous n'avez pas fait cette demande, ignorez cet email.</p>"#
+</table>"#
         );
 
-        let html = Self::wrap_html(logo_url, garderie_name, &content);
-        self.send_email(from, to, &subject, &text, &html).await
+        self.send_branded(LogAs { tenant, template: "document_expiry" }, branding, garderie_name, Locale::Fr, to, &subject, &text, &content).await
     }
 
-    pub async fn send_2fa_code(
+    /// Annonce à un parent de la liste d'attente qu'une place est offerte à son enfant.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_waitlist_offer(
         &self,
+        tenant: &str,
         to_email: &str,
-        code: &str,
+        to_name: &str,
+        child_name: &str,
+        start_date: Option<&str>,
         garderie_name: &str,
-        logo_url: &str,
+        branding: &TenantBranding,
     ) -> anyhow::Result<()> {
-        let from = Mailbox::new(Some(garderie_name.to_string()), self.from.email.clone());
-        let to: Mailbox = to_email.parse()?;
+        let to: Mailbox = format!("{to_name} <{to_email}>")
+            .parse()
+            .unwrap_or_else(|_| to_email.parse().expect("valid email address"));
+
+        let subject = format!("Une place est disponible pour {child_name} — {garderie_name}");
 
-        let subject = format!("Code de connexion — {garderie_name}");
+        let start_text = start_date
+            .map(|d| format!(" à compter du {d}"))
+            .unwrap_or_default();
 
         let text = format!(
-            "Votre code de connexion pour {garderie_name} est : {code}\n\n\
-            Ce code est valide pendant 15 minutes.\n\n\
-            Si vous n'avez pas tenté de vous connecter, ignorez cet email."
+            "Bonjour {to_name},\n\n\
+            Bonne nouvelle : {garderie_name} peut accueillir {child_name}{start_text}.\n\n\
+            Merci de nous confirmer votre réponse en répondant à ce courriel ou en contactant directement la garderie.\n\n\
+            {garderie_name}"
         );
 
         let content = format!(
-            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">Code de connexion</h1>
-<p style="margin:0 0 24px 0;font-size:15px;color:#64748b;line-height:1.6">Votre code de vérification à usage unique :</p>
-<table role="presentation" width="100%" cellpadding="0" cellspacing="0" style="margin-bottom:24px">
-  <tr>
-    <td align="center" style="background:#f8fafc;border-radius:10px;border:1px solid #e2e8f0;padding:24px 16px">
-      <span style="font-size:44px;font-weight:800;letter-spacing:14px;color:#0f172a;font-variant-numeric:tabular-nums">{code}</span>
-    </td>
-  </tr>
-</table>
-<p style="margin:0;font-size:13px;color:#94a3b8;border-top:1px solid #f1f5f9;padding-top:20px;line-height:1.5">Ce code expire dans <strong style="color:#64748b">15 minutes</strong>. Si vous n'avez pas tenté de vous connecter, ignorez cet email.</p>"#
+            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">Une place est disponible</h1>
+<p style="margin:0 0 24px 0;font-size:15px;color:#64748b;line-height:1.6">Bonjour <strong style="color:#334155">{to_name}</strong>,<br><br>Bonne nouvelle : <strong style="color:#334155">{garderie_name}</strong> peut accueillir <strong style="color:#334155">{child_name}</strong>{start_text}.</p>
+<p style="margin:0;font-size:13px;color:#94a3b8;border-top:1px solid #f1f5f9;padding-top:20px;line-height:1.5">Merci de nous confirmer votre réponse en répondant à ce courriel ou en contactant directement la garderie.</p>"#
         );
 
-        let html = Self::wrap_html(logo_url, garderie_name, &content);
-        self.send_email(from, to, &subject, &text, &html).await
+        self.send_branded(LogAs { tenant, template: "waitlist_offer" }, branding, garderie_name, Locale::Fr, to, &subject, &text, &content).await
     }
 
-    pub async fn send_invitation(
+    /// Annonce à un parent le changement de groupe de son enfant (passage de l'année).
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_group_change(
         &self,
+        tenant: &str,
         to_email: &str,
-        invite_url: &str,
+        to_name: &str,
+        child_name: &str,
+        from_group: Option<&str>,
+        to_group: Option<&str>,
         garderie_name: &str,
-        role: &str,
-        logo_url: &str,
+        branding: &TenantBranding,
     ) -> anyhow::Result<()> {
-        let from = Mailbox::new(Some(garderie_name.to_string()), self.from.email.clone());
-        let to: Mailbox = to_email.parse()?;
+        let to: Mailbox = format!("{to_name} <{to_email}>")
+            .parse()
+            .unwrap_or_else(|_| to_email.parse().expect("valid email address"));
 
-        let role_fr = match role {
-            "admin_garderie" => "Administrateur",
-            "educateur" => "Éducateur / Éducatrice",
-            _ => "Parent",
+        let subject = format!("Changement de groupe pour {child_name} — {garderie_name}");
+
+        let change = match (to_group, from_group) {
+            (Some(group), _) => format!("{child_name} fait maintenant partie du groupe {group}."),
+            (None, Some(group)) => format!("{child_name} ne fait plus partie du groupe {group}."),
+            (None, None) => format!("{child_name} ne fait plus partie d'un groupe."),
         };
 
-        let subject = format!("Invitation à rejoindre {garderie_name}");
+        let text = format!(
+            "Bonjour {to_name},\n\n\
+            {change}\n\n\
+            Pour toute question, répondez à ce courriel ou contactez directement la garderie.\n\n\
+            {garderie_name}"
+        );
+
+        let content = format!(
+            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">Changement de groupe</h1>
+<p style="margin:0 0 24px 0;font-size:15px;color:#64748b;line-height:1.6">Bonjour <strong style="color:#334155">{}</strong>,<br><br>{}</p>
+<p style="margin:0;font-size:13px;color:#94a3b8;border-top:1px solid #f1f5f9;padding-top:20px;line-height:1.5">Pour toute question, répondez à ce courriel ou contactez directement la garderie.</p>"#,
+            escape_html(to_name),
+            escape_html(&change),
+        );
+
+        self.send_branded(LogAs { tenant, template: "group_change" }, branding, garderie_name, Locale::Fr, to, &subject, &text, &content).await
+    }
+
+    /// Envoie à un parent, au départ de son enfant, le lien vers l'exportation finale
+    /// de ses journaux, photos et vidéos.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_departure_export(
+        &self,
+        tenant: &str,
+        to_email: &str,
+        to_name: &str,
+        child_name: &str,
+        export_url: &str,
+        available_until: chrono::NaiveDate,
+        garderie_name: &str,
+        branding: &TenantBranding,
+    ) -> anyhow::Result<()> {
+        let to: Mailbox = format!("{to_name} <{to_email}>")
+            .parse()
+            .unwrap_or_else(|_| to_email.parse().expect("valid email address"));
+
+        let subject = format!("Souvenirs de {child_name} — {garderie_name}");
+        let date = available_until.format("%d/%m/%Y");
 
         let text = format!(
-            "Vous êtes invité(e) à rejoindre {garderie_name} en tant que {role_fr}.\n\n\
-            Cliquez sur le lien pour créer votre compte :\n\
-            {invite_url}\n\n\
-            Ce lien expire dans 7 jours."
+            "Bonjour {to_name},\n\n\
+            Merci d'avoir confié {child_name} à {garderie_name}. Ses journaux, photos et vidéos \
+            restent disponibles jusqu'au {date} à l'adresse suivante :\n\
+            {export_url}\n\n\
+            Pensez à les télécharger avant cette date : ils seront ensuite supprimés selon \
+            la politique de conservation de la garderie.\n\n\
+            {garderie_name}"
         );
 
+        let primary = branding.primary_color();
         let content = format!(
-            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">Vous êtes invité(e) !</h1>
-<p style="margin:0 0 28px 0;font-size:15px;color:#64748b;line-height:1.6">Vous avez été invité(e) à rejoindre <strong style="color:#334155">{garderie_name}</strong> en tant que <strong style="color:#334155">{role_fr}</strong>.<br><br>Créez votre compte gratuitement en cliquant sur le bouton ci-dessous.</p>
-<table role="presentation" cellpadding="0" cellspacing="0" style="margin-bottom:28px">
+            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">Souvenirs de {child}</h1>
+<p style="margin:0 0 28px 0;font-size:15px;color:#64748b;line-height:1.6">Bonjour <strong style="color:#334155">{name}</strong>,<br><br>Merci d'avoir confié {child} à {garderie}. Ses journaux, photos et vidéos restent disponibles jusqu'au <strong style="color:#334155">{date}</strong>.</p>
+<table role="presentation" cellpadding="0" cellspacing="0">
   <tr>
-    <td style="border-radius:8px;background:#2563eb">
-      <a href="{invite_url}" style="display:inline-block;padding:13px 28px;color:#ffffff;text-decoration:none;font-weight:600;font-size:15px;border-radius:8px">Créer mon compte</a>
+    <td style="border-radius:8px;background:{primary}">
+      <a href="{export_url}" style="display:inline-block;padding:13px 28px;color:#ffffff;text-decoration:none;font-weight:600;font-size:15px;border-radius:8px">Télécharger</a>
     </td>
   </tr>
 </table>
-<p style="margin:0;font-size:13px;color:#94a3b8;border-top:1px solid #f1f5f9;padding-top:20px;line-height:1.5">Ce lien expire dans <strong style="color:#64748b">7 jours</strong>.</p>"#
+<p style="margin:28px 0 0 0;font-size:13px;color:#94a3b8;border-top:1px solid #f1f5f9;padding-top:20px;line-height:1.5">Pensez à les télécharger avant cette date : ils seront ensuite supprimés selon la politique de conservation de la garderie.</p>"#,
+            child = escape_html(child_name),
+            name = escape_html(to_name),
+            garderie = escape_html(garderie_name),
         );
 
-        let html = Self::wrap_html(logo_url, garderie_name, &content);
-        self.send_email(from, to, &subject, &text, &html).await
+        self.send_branded(LogAs { tenant, template: "departure_export" }, branding, garderie_name, Locale::Fr, to, &subject, &text, &content).await
     }
 
-    pub async fn send_message_notification(
+    /// Message d'urgence (évacuation, fermeture) : envoyé sans égard aux fils mis en
+    /// sourdine, avec un lien pour confirmer la lecture dans l'application.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_emergency(
         &self,
+        tenant: &str,
         to_email: &str,
         to_name: &str,
-        sender_name: &str,
-        thread_name: &str,
-        app_url: &str,
+        subject: &str,
+        body: &str,
+        acknowledge_url: &str,
         garderie_name: &str,
-        logo_url: &str,
+        branding: &TenantBranding,
     ) -> anyhow::Result<()> {
-        let from = Mailbox::new(Some(garderie_name.to_string()), self.from.email.clone());
         let to: Mailbox = format!("{to_name} <{to_email}>")
             .parse()
             .unwrap_or_else(|_| to_email.parse().expect("valid email address"));
 
-        let subject = format!("Nouveau message — {thread_name}");
+        let subject = format!("[URGENT] {subject} — {garderie_name}");
 
         let text = format!(
             "Bonjour {to_name},\n\n\
-            Vous avez reçu un nouveau message de {sender_name} dans {thread_name}.\n\n\
-            Connectez-vous pour voir le message :\n\
-            {app_url}\n\n\
+            {body}\n\n\
+            Merci de confirmer que vous avez bien reçu ce message : {acknowledge_url}\n\n\
             {garderie_name}"
         );
 
         let content = format!(
-            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">Nouveau message</h1>
-<p style="margin:0 0 28px 0;font-size:15px;color:#64748b;line-height:1.6">Bonjour <strong style="color:#334155">{to_name}</strong>,<br><br><strong style="color:#334155">{sender_name}</strong> vous a envoyé un message dans <strong style="color:#334155">{thread_name}</strong>.</p>
-<table role="presentation" cellpadding="0" cellspacing="0">
-  <tr>
-    <td style="border-radius:8px;background:#2563eb">
-      <a href="{app_url}" style="display:inline-block;padding:13px 28px;color:#ffffff;text-decoration:none;font-weight:600;font-size:15px;border-radius:8px">Voir le message</a>
-    </td>
-  </tr>
-</table>"#
+            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#b91c1c">Message urgent</h1>
+<p style="margin:0 0 24px 0;font-size:15px;color:#64748b;line-height:1.6">Bonjour <strong style="color:#334155">{}</strong>,</p>
+<p style="margin:0 0 28px 0;font-size:15px;color:#334155;line-height:1.7">{}</p>
+<a href="{}" style="display:inline-block;background:#b91c1c;color:#ffffff;text-decoration:none;padding:12px 24px;border-radius:8px;font-weight:600;font-size:14px">J'ai bien reçu ce message</a>"#,
+            escape_html(to_name),
+            escape_html(body).replace('\n', "<br>"),
+            escape_html(acknowledge_url),
         );
 
-        let html = Self::wrap_html(logo_url, garderie_name, &content);
+        self.send_branded(LogAs { tenant, template: "emergency" }, branding, garderie_name, Locale::Fr, to, &subject, &text, &content).await
+    }
 
-        let email = Message::builder()
-            .message_id(Some(self.new_message_id()))
-            .from(from)
-            .to(to)
-            .subject(&subject)
-            .multipart(
-                MultiPart::alternative()
-                    .singlepart(
-                        SinglePart::builder()
-                            .header(ContentType::TEXT_PLAIN)
-                            .body(text),
-                    )
-                    .singlepart(
-                        SinglePart::builder()
-                            .header(ContentType::TEXT_HTML)
-                            .body(html),
-                    ),
-            )
-            .context("Failed to build email message")?;
+    /// Confirme au parent la réservation d'une rencontre parent-éducatrice.
+    pub async fn send_meeting_confirmation(
+        &self,
+        tenant: &str,
+        meeting: &MeetingDetails,
+        garderie_name: &str,
+        branding: &TenantBranding,
+    ) -> anyhow::Result<()> {
+        let subject = format!("Rencontre confirmée — {}", meeting.child_name);
+        self.send_meeting_notice(LogAs { tenant, template: "meeting_confirmation" }, meeting, &subject, "Rencontre confirmée", "Votre rencontre est confirmée.", garderie_name, branding)
+            .await
+    }
 
-        self.transport
-            .send(email)
+    /// Rappelle au parent une rencontre prévue dans les prochaines 24 heures.
+    pub async fn send_meeting_reminder(
+        &self,
+        tenant: &str,
+        meeting: &MeetingDetails,
+        garderie_name: &str,
+        branding: &TenantBranding,
+    ) -> anyhow::Result<()> {
+        let subject = format!("Rappel : rencontre pour {}", meeting.child_name);
+        self.send_meeting_notice(LogAs { tenant, template: "meeting_reminder" }, meeting, &subject, "Rappel de rencontre", "Petit rappel de votre rencontre à venir.", garderie_name, branding)
             .await
-            .context("Failed to send email")?;
+    }
 
-        Ok(())
+    /// Informe le parent de l'annulation d'une rencontre.
+    pub async fn send_meeting_cancellation(
+        &self,
+        tenant: &str,
+        meeting: &MeetingDetails,
+        garderie_name: &str,
+        branding: &TenantBranding,
+    ) -> anyhow::Result<()> {
+        let subject = format!("Rencontre annulée — {}", meeting.child_name);
+        self.send_meeting_notice(LogAs { tenant, template: "meeting_cancellation" }, meeting, &subject, "Rencontre annulée", "La rencontre suivante a été annulée.", garderie_name, branding)
+            .await
     }
 
-    pub async fn send_media_notification(
+    #[allow(clippy::too_many_arguments)]
+    async fn send_meeting_notice(
         &self,
-        to_email: &str,
-        to_name: &str,
-        uploader_name: &str,
-        content_kind: &str,
-        app_url: &str,
+        log: LogAs<'_>,
+        meeting: &MeetingDetails,
+        subject: &str,
+        heading: &str,
+        intro: &str,
         garderie_name: &str,
-        logo_url: &str,
+        branding: &TenantBranding,
     ) -> anyhow::Result<()> {
-        let from = Mailbox::new(Some(garderie_name.to_string()), self.from.email.clone());
+        let to_name = &meeting.parent_name;
+        let to_email = &meeting.parent_email;
         let to: Mailbox = format!("{to_name} <{to_email}>")
             .parse()
             .unwrap_or_else(|_| to_email.parse().expect("valid email address"));
 
-        let subject = format!("Nouveau contenu partagé — {garderie_name}");
+        let starts = meeting.starts_at.with_timezone(&chrono::Local);
+        let ends = meeting.ends_at.with_timezone(&chrono::Local);
+        let when = format!("{} de {} à {}", starts.format("%d/%m/%Y"), starts.format("%H:%M"), ends.format("%H:%M"));
+        let child = &meeting.child_name;
+        let educator = &meeting.educator_name;
+        let location = meeting.location.as_deref().unwrap_or("à la garderie");
 
         let text = format!(
             "Bonjour {to_name},\n\n\
-            {uploader_name} a partagé {content_kind} vous concernant sur {garderie_name}.\n\n\
-            Connectez-vous pour voir le contenu :\n\
-            {app_url}\n\n\
+            {intro}\n\n\
+            Enfant : {child}\n\
+            Avec : {educator}\n\
+            Quand : {when}\n\
+            Lieu : {location}\n\n\
             {garderie_name}"
         );
 
+        let location = escape_html(location);
         let content = format!(
-            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">Nouveau contenu partagé</h1>
-<p style="margin:0 0 28px 0;font-size:15px;color:#64748b;line-height:1.6">Bonjour <strong style="color:#334155">{to_name}</strong>,<br><br><strong style="color:#334155">{uploader_name}</strong> a partagé {content_kind} vous concernant.</p>
-<table role="presentation" cellpadding="0" cellspacing="0">
-  <tr>
-    <td style="border-radius:8px;background:#2563eb">
-      <a href="{app_url}" style="display:inline-block;padding:13px 28px;color:#ffffff;text-decoration:none;font-weight:600;font-size:15px;border-radius:8px">Voir le contenu</a>
-    </td>
-  </tr>
+            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">{heading}</h1>
+<p style="margin:0 0 20px 0;font-size:15px;color:#64748b;line-height:1.6">Bonjour <strong style="color:#334155">{to_name}</strong>,<br><br>{intro}</p>
+<table role="presentation" cellpadding="0" cellspacing="0" style="font-size:14px;color:#334155;line-height:1.8">
+  <tr><td style="padding-right:16px;color:#94a3b8">Enfant</td><td><strong>{child}</strong></td></tr>
+  <tr><td style="padding-right:16px;color:#94a3b8">Avec</td><td>{educator}</td></tr>
+  <tr><td style="padding-right:16px;color:#94a3b8">Quand</td><td>{when}</td></tr>
+  <tr><td style="padding-right:16px;color:#94a3b8">Lieu</td><td>{location}</td></tr>
 </table>"#
         );
 
-        let html = Self::wrap_html(logo_url, garderie_name, &content);
-        self.send_email(from, to, &subject, &text, &html).await
+        self.send_branded(log, branding, garderie_name, Locale::Fr, to, subject, &text, &content).await
     }
 
-    pub async fn send_to_parents(
+    /// Avertit un admin que la garderie approche de son quota de stockage.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_storage_quota_warning(
         &self,
-        recipients: Vec<(String, String)>,
-        subject: &str,
-        body: &str,
+        tenant: &str,
+        to_email: &str,
+        to_name: &str,
+        percent: u8,
+        usage_label: &str,
         garderie_name: &str,
-        logo_url: &str,
+        branding: &TenantBranding,
     ) -> anyhow::Result<()> {
-        let from_with_name = Mailbox::new(Some(garderie_name.to_string()), self.from.email.clone());
+        let from = Mailbox::new(Some("minispace.app".to_string()), self.from.email.clone());
+        let to: Mailbox = format!("{to_name} <{to_email}>")
+            .parse()
+            .unwrap_or_else(|_| to_email.parse().expect("valid email address"));
+
+        let subject = format!("Stockage utilisé à {percent} % — {garderie_name}");
+
+        let text = format!(
+            "Bonjour {to_name},\n\n\
+            L'espace de stockage de {garderie_name} est utilisé à {percent} % ({usage_label}).\n\n\
+            Une fois le quota atteint, l'ajout de photos, vidéos et documents sera bloqué. \
+            Supprimez d'anciens fichiers ou contactez-nous à contact@minispace.app pour changer de forfait.\n\n\
+            L'équipe minispace.app"
+        );
 
+        let urgency_color = if percent >= 95 { "#dc2626" } else { "#d97706" };
         let content = format!(
-            r#"<p style="margin:0;font-size:15px;color:#334155;line-height:1.7">{}</p>"#,
-            body.replace('\n', "<br>")
+            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">Espace de stockage presque plein</h1>
+<p style="margin:0 0 24px 0;font-size:15px;color:#64748b;line-height:1.6">Bonjour <strong style="color:#334155">{to_name}</strong>,<br><br>L'espace de stockage de <strong style="color:#334155">{garderie_name}</strong> est utilisé à <strong style="color:{urgency_color}">{percent} %</strong> ({usage_label}).</p>
+<p style="margin:0;font-size:13px;color:#94a3b8;border-top:1px solid #f1f5f9;padding-top:20px;line-height:1.5">Une fois le quota atteint, l'ajout de photos, vidéos et documents sera bloqué. Supprimez d'anciens fichiers ou écrivez-nous à <a href="mailto:contact@minispace.app" style="color:#2563eb">contact@minispace.app</a> pour changer de forfait.</p>"#
         );
-        let html = Self::wrap_html(logo_url, garderie_name, &content);
 
-        for (email, name) in &recipients {
+        let html = Self::wrap_html(branding, garderie_name, Locale::Fr, &content);
+        self.send_email(from, None, to, &subject, &text, &html, Some(LogAs { tenant, template: "storage_quota" })).await
+    }
+
+    /// Sends an announcement to each `(email, name, preferred_locale)` recipient, in the
+    /// garderie's template for that locale when one exists. With a `campaign`, each email
+    /// carries its own open pixel and tracked links.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_to_parents(
+        &self,
+        tenant: &str,
+        recipients: Vec<ParentRecipient>,
+        subject: &str,
+        body: &str,
+        garderie_name: &str,
+        branding: &TenantBranding,
+        templates: &[EmailTemplate],
+        mut campaign: Option<Campaign>,
+    ) -> anyhow::Result<()> {
+        for recipient in &recipients {
+            let ParentRecipient { email, name, locale, .. } = recipient;
             let to: Mailbox = match format!("{name} <{email}>").parse() {
                 Ok(m) => m,
                 Err(_) => match email.parse() {
@@ -391,33 +1290,63 @@ impl EmailService {
                 },
             };
 
-            let email_msg = Message::builder()
-                .message_id(Some(self.new_message_id()))
-                .from(from_with_name.clone())
-                .to(to)
-                .subject(subject)
-                .multipart(
-                    MultiPart::alternative()
-                        .singlepart(
-                            SinglePart::builder()
-                                .header(ContentType::TEXT_PLAIN)
-                                .body(body.to_string()),
-                        )
-                        .singlepart(
-                            SinglePart::builder()
-                                .header(ContentType::TEXT_HTML)
-                                .body(html.clone()),
-                        ),
-                )
-                .context("Failed to build email message")?;
-
-            if let Err(e) = self.transport.send(email_msg).await {
+            let locale = Locale::from_tag(locale);
+            let template = templates.iter().find(|t| t.locale == locale.tag());
+            let vars = TemplateVars::for_recipient(recipient);
+            let (subject, body) = (vars.expand(subject), vars.expand(body));
+            let mut rendered = Self::render_announcement(name, &subject, &body, garderie_name, branding, locale, template);
+            if let Some(campaign) = campaign.as_mut() {
+                match campaign.instrument(&rendered.html).await {
+                    Ok(html) => rendered.html = html,
+                    Err(e) => tracing::warn!("Email tracking skipped for campaign {}: {e}", campaign.id()),
+                }
+            }
+            if let Err(e) = self.send_rendered(LogAs { tenant, template: "announcement" }, branding, garderie_name, to, &rendered).await {
                 tracing::warn!("Failed to send email to {email}: {e}");
             }
         }
         Ok(())
     }
 
+    /// Announcement email in the garderie's wording if it has a template, else the message as written.
+    pub fn render_announcement(
+        recipient_name: &str,
+        subject: &str,
+        body: &str,
+        garderie_name: &str,
+        branding: &TenantBranding,
+        locale: Locale,
+        template: Option<&EmailTemplate>,
+    ) -> RenderedEmail {
+        let (subject, text, content) = match template {
+            Some(t) => {
+                let args = [
+                    ("garderie", garderie_name),
+                    ("recipient_name", recipient_name),
+                    ("subject", subject),
+                    ("body", body),
+                ];
+                let text = fill(&t.body, &args);
+                let content = paragraphs(&text);
+                (fill(&t.subject, &args), text, content)
+            }
+            None => (
+                subject.to_string(),
+                body.to_string(),
+                format!(
+                    r#"<p style="margin:0;font-size:15px;color:#334155;line-height:1.7">{}</p>"#,
+                    body.replace('\n', "<br>")
+                ),
+            ),
+        };
+
+        RenderedEmail {
+            subject,
+            text,
+            html: Self::wrap_html(branding, garderie_name, locale, &content),
+        }
+    }
+
     pub async fn send_contact_request(
         &self,
         name: &str,
@@ -446,9 +1375,109 @@ impl EmailService {
 </table>"#
         );
 
-        let html = Self::wrap_html("", "minispace.app", &content);
+        let html = Self::wrap_html(&TenantBranding::default(), "minispace.app", Locale::Fr, &content);
+        let from = Mailbox::new(Some("minispace.app".to_string()), self.from.email.clone());
+        self.send_email(from, None, to, &subject, &text, &html, None).await
+    }
+
+    /// Alerts a super-admin that a destructive operation was requested and when it will run.
+    pub async fn send_pending_operation_alert(
+        &self,
+        to_email: &str,
+        op: &PendingOperation,
+    ) -> anyhow::Result<()> {
+        let to: Mailbox = to_email.parse().context("Invalid super-admin email")?;
+        let action = match (op.kind.as_str(), op.target_slug.as_deref()) {
+            (OP_DELETE_GARDERIE, Some(slug)) => format!("Suppression de la garderie {slug}"),
+            (OP_ARCHIVE_GARDERIE, Some(slug)) => format!("Archivage de la garderie {slug}"),
+            (OP_RESTORE_GARDERIE, Some(slug)) => format!(
+                "Restauration de la garderie {slug} (sauvegarde {})",
+                op.payload["backup_id"].as_str().unwrap_or("?")
+            ),
+            (OP_RESTORE, _) => format!(
+                "Restauration de la sauvegarde {}",
+                op.payload["db_file"].as_str().unwrap_or("?")
+            ),
+            (kind, _) => kind.to_string(),
+        };
+        let requested_by = op.requested_by.as_deref().unwrap_or("clé super-admin partagée");
+        let execute_after = op
+            .execute_after
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string();
+        let id = op.id;
+
+        let subject = format!("⚠️ Opération en attente — {action}");
+        let text = format!(
+            "{action}
+
+            Demandée par : {requested_by}
+            Exécution prévue : {execute_after}
+            Identifiant : {id}
+
+            Un autre super-administrateur peut la confirmer dès maintenant             (POST /super-admin/operations/{id}/confirm) ou l'annuler avant l'échéance             (POST /super-admin/operations/{id}/cancel).
+
+            Si vous n'êtes pas à l'origine de cette demande, annulez-la immédiatement."
+        );
+
+        let content = format!(
+            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#dc2626">Opération destructive en attente</h1>
+<p style="margin:0 0 20px 0;font-size:15px;color:#334155;line-height:1.6"><strong>{action}</strong></p>
+<table role="presentation" width="100%" cellpadding="0" cellspacing="0">
+  <tr><td style="padding:10px 12px;border-bottom:1px solid #f1f5f9;font-size:14px;color:#64748b;width:160px">Demandée par</td><td style="padding:10px 12px;border-bottom:1px solid #f1f5f9;font-size:14px;color:#0f172a">{requested_by}</td></tr>
+  <tr><td style="padding:10px 12px;border-bottom:1px solid #f1f5f9;font-size:14px;color:#64748b">Exécution prévue</td><td style="padding:10px 12px;border-bottom:1px solid #f1f5f9;font-size:14px;color:#0f172a;font-weight:600">{execute_after}</td></tr>
+  <tr><td style="padding:10px 12px;font-size:14px;color:#64748b">Identifiant</td><td style="padding:10px 12px;font-size:14px;color:#0f172a;font-family:monospace">{id}</td></tr>
+</table>
+<p style="margin:24px 0 0 0;font-size:13px;color:#94a3b8;border-top:1px solid #f1f5f9;padding-top:20px;line-height:1.5">Un autre super-administrateur peut la confirmer dès maintenant ou l'annuler avant l'échéance depuis la console. Si vous n'êtes pas à l'origine de cette demande, annulez-la immédiatement.</p>"#
+        );
+
+        let html = Self::wrap_html(&TenantBranding::default(), "minispace.app", Locale::Fr, &content);
+        let from = Mailbox::new(Some("minispace.app".to_string()), self.from.email.clone());
+        self.send_email(from, None, to, &subject, &text, &html, None).await
+    }
+
+    /// Tells a super-admin which garderies the nightly backup could not save.
+    pub async fn send_backup_failure_alert(
+        &self,
+        to_email: &str,
+        failures: &[(String, String)],
+        total: usize,
+    ) -> anyhow::Result<()> {
+        let to: Mailbox = to_email.parse().context("Invalid super-admin email")?;
+        let count = failures.len();
+        let subject = format!("❌ Sauvegarde nocturne — {count} échec(s) sur {total}");
+
+        let lines: String = failures
+            .iter()
+            .map(|(slug, error)| format!("- {slug} : {error}\n"))
+            .collect();
+        let text = format!(
+            "La sauvegarde nocturne a échoué pour {count} garderie(s) sur {total} :\n\n{lines}\n\
+            Les autres garderies ont été sauvegardées. Relancez une sauvegarde manuelle \
+            une fois le problème corrigé."
+        );
+
+        let rows: String = failures
+            .iter()
+            .map(|(slug, error)| {
+                format!(
+                    r#"<tr><td style="padding:10px 12px;border-bottom:1px solid #f1f5f9;font-size:14px;color:#0f172a;font-weight:600;width:160px">{}</td><td style="padding:10px 12px;border-bottom:1px solid #f1f5f9;font-size:13px;color:#64748b;font-family:monospace">{}</td></tr>"#,
+                    escape_html(slug),
+                    escape_html(error)
+                )
+            })
+            .collect();
+        let content = format!(
+            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#dc2626">Échec de la sauvegarde nocturne</h1>
+<p style="margin:0 0 20px 0;font-size:15px;color:#64748b;line-height:1.6">La sauvegarde a échoué pour <strong style="color:#334155">{count} garderie(s) sur {total}</strong>.</p>
+<table role="presentation" width="100%" cellpadding="0" cellspacing="0">{rows}</table>
+<p style="margin:24px 0 0 0;font-size:13px;color:#94a3b8;border-top:1px solid #f1f5f9;padding-top:20px;line-height:1.5">Les autres garderies ont été sauvegardées. Relancez une sauvegarde manuelle une fois le problème corrigé.</p>"#
+        );
+
+        let html = Self::wrap_html(&TenantBranding::default(), "minispace.app", Locale::Fr, &content);
         let from = Mailbox::new(Some("minispace.app".to_string()), self.from.email.clone());
-        self.send_email(from, to, &subject, &text, &html).await
+        self.send_email(from, None, to, &subject, &text, &html, None).await
     }
 
     /// Notifie contact@minispace.app qu'une nouvelle garderie vient d'être créée via inscription libre.
@@ -509,9 +1538,9 @@ impl EmailService {
 </table>"#
         );
 
-        let html = Self::wrap_html("", "minispace.app", &content);
+        let html = Self::wrap_html(&TenantBranding::default(), "minispace.app", Locale::Fr, &content);
         let from = Mailbox::new(Some("minispace.app".to_string()), self.from.email.clone());
-        self.send_email(from, to, &subject, &text, &html).await
+        self.send_email(from, None, to, &subject, &text, &html, None).await
     }
 
     /// Email de bienvenue envoyé à l'admin de la nouvelle garderie.
@@ -612,9 +1641,9 @@ impl EmailService {
 </div>"#
         );
 
-        let html = Self::wrap_html("", garderie_name, &content);
+        let html = Self::wrap_html(&TenantBranding::default(), garderie_name, Locale::Fr, &content);
         let from = Mailbox::new(Some("minispace.app".to_string()), self.from.email.clone());
-        self.send_email(from, to, &subject, &text, &html).await
+        self.send_email(from, None, to, &subject, &text, &html, Some(LogAs { tenant: slug, template: "welcome" })).await
     }
 
     /// Envoie un rappel d'expiration d'essai à contact@minispace.app et à l'admin de la garderie.
@@ -668,9 +1697,18 @@ impl EmailService {
 </div>"#
         );
 
-        let html_admin = Self::wrap_html("", garderie_name, &content_admin);
+        let html_admin = Self::wrap_html(&TenantBranding::default(), garderie_name, Locale::Fr, &content_admin);
         let from = Mailbox::new(Some("minispace.app".to_string()), self.from.email.clone());
-        self.send_email(from.clone(), to_admin, &subject_admin, &text_admin, &html_admin).await?;
+        self.send_email(
+            from.clone(),
+            None,
+            to_admin,
+            &subject_admin,
+            &text_admin,
+            &html_admin,
+            Some(LogAs { tenant: slug, template: "trial_expiry" }),
+        )
+        .await?;
 
         // 2. Copie interne à contact@minispace.app
         let to_internal = self.from.clone();
@@ -692,37 +1730,110 @@ impl EmailService {
   <tr><td style="padding:10px 12px;font-size:14px;color:#64748b">Expire</td><td style="padding:10px 12px;font-size:14px;font-weight:700;color:{urgency_color}">{days_label}</td></tr>
 </table>"#
         );
-        let html_internal = Self::wrap_html("", "minispace.app", &content_internal);
-        self.send_email(from, to_internal, &subject_internal, &text_internal, &html_internal).await
+        let html_internal = Self::wrap_html(&TenantBranding::default(), "minispace.app", Locale::Fr, &content_internal);
+        self.send_email(from, None, to_internal, &subject_internal, &text_internal, &html_internal, None).await
+    }
+
+    /// Envoie une facture à un parent, le PDF en pièce jointe.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_invoice(
+        &self,
+        tenant: &str,
+        to_email: &str,
+        to_name: &str,
+        invoice: &Invoice,
+        pdf: Vec<u8>,
+        garderie_name: &str,
+        branding: &TenantBranding,
+    ) -> anyhow::Result<()> {
+        let to: Mailbox = format!("{to_name} <{to_email}>")
+            .parse()
+            .unwrap_or_else(|_| to_email.parse().expect("valid email address"));
+
+        let number = &invoice.number;
+        let child_name = &invoice.child_name;
+        let total = format_cents(invoice.total_cents);
+        let due_date = Locale::Fr.short_date(invoice.due_date);
+        let subject = format!("Facture {number} — {child_name}");
+
+        let text = format!(
+            "Bonjour {to_name},\n\n\
+            Vous trouverez ci-joint la facture {number} de {garderie_name} pour {child_name}.\n\n\
+            Montant : {total}\n\
+            Échéance : {due_date}\n\n\
+            {garderie_name}"
+        );
+
+        let content = format!(
+            r#"<h1 style="margin:0 0 8px 0;font-size:22px;font-weight:700;color:#0f172a">Facture {number}</h1>
+<p style="margin:0 0 24px 0;font-size:15px;color:#64748b;line-height:1.6">Bonjour <strong style="color:#334155">{to_name}</strong>,<br><br>Vous trouverez ci-joint la facture de <strong style="color:#334155">{child_name}</strong>.</p>
+<p style="margin:0 0 24px 0;font-size:15px;color:#334155;line-height:1.8">Montant : <strong>{total}</strong><br>Échéance : <strong>{due_date}</strong></p>
+<p style="margin:0;font-size:13px;color:#94a3b8;border-top:1px solid #f1f5f9;padding-top:20px;line-height:1.5">Pour toute question concernant cette facture, répondez simplement à ce courriel.</p>"#,
+            number = escape_html(number),
+            child_name = escape_html(child_name),
+        );
+
+        let from = Mailbox::new(Some(garderie_name.to_string()), self.from.email.clone());
+        let html = Self::wrap_html(branding, garderie_name, Locale::Fr, &content);
+        let mut builder = Message::builder().message_id(Some(self.new_message_id())).from(from);
+        if let Some(reply_to) = Self::reply_to(branding) {
+            builder = builder.reply_to(reply_to);
+        }
+        let attachment = Attachment::new(format!("facture-{number}.pdf"))
+            .body(pdf, ContentType::parse("application/pdf").expect("valid content type"));
+        let email = builder
+            .to(to)
+            .subject(subject)
+            .multipart(
+                MultiPart::mixed()
+                    .multipart(Self::alternative_body(&text, &html))
+                    .singlepart(attachment),
+            )
+            .context("Failed to build email message")?;
+
+        self.deliver(email, Some(LogAs { tenant, template: "invoice" })).await
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn send_journal(
         &self,
+        tenant: &str,
         to_email: &str,
         to_name: &str,
         html_body: &str,
         subject: &str,
         from_name: &str,
+        photos: &[&JournalPhoto],
     ) -> anyhow::Result<()> {
         let from = Mailbox::new(Some(from_name.to_string()), self.from.email.clone());
         let to: Mailbox = format!("{to_name} <{to_email}>")
             .parse()
             .unwrap_or_else(|_| to_email.parse().expect("valid email address"));
 
+        let html = MultiPart::alternative().singlepart(SinglePart::html(html_body.to_string()));
+        let body = if photos.is_empty() {
+            html
+        } else {
+            // Thumbnails travel as inline parts referenced by `cid:` from the HTML;
+            // a photo of two siblings is attached once
+            let jpeg = ContentType::parse("image/jpeg").expect("valid content type");
+            let mut related = MultiPart::related().multipart(html);
+            let mut attached = std::collections::HashSet::new();
+            for photo in photos.iter().filter(|p| attached.insert(p.cid.as_str())) {
+                related = related.singlepart(Attachment::new_inline(photo.cid.clone()).body(photo.bytes.clone(), jpeg.clone()));
+            }
+            related
+        };
+
         let email = Message::builder()
             .message_id(Some(self.new_message_id()))
             .from(from)
             .to(to)
             .subject(subject)
-            .multipart(MultiPart::alternative().singlepart(SinglePart::html(html_body.to_string())))
+            .multipart(body)
             .context("Failed to build email message")?;
 
-        self.transport
-            .send(email)
-            .await
-            .context("Failed to send email")?;
-
-        Ok(())
+        self.deliver(email, Some(LogAs { tenant, template: "journal" })).await
     }
 
     pub async fn send_account_deletion_request(
@@ -733,7 +1844,7 @@ impl EmailService {
         user_email: &str,
         user_id: &str,
         timestamp: &str,
-        _tenant: &str,
+        tenant: &str,
     ) -> anyhow::Result<()> {
         let to = admin_email.parse::<Mailbox>()
             .context("Invalid admin email")?;
@@ -809,11 +1920,6 @@ impl EmailService {
                 .singlepart(SinglePart::html(html_body)))
             .context("Failed to build email message")?;
 
-        self.transport
-            .send(email)
-            .await
-            .context("Failed to send email")?;
-
-        Ok(())
+        self.deliver(email, Some(LogAs { tenant, template: "account_deletion_request" })).await
     }
 }
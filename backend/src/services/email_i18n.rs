@@ -0,0 +1,285 @@
+use chrono::{Datelike, NaiveDate};
+
+/// Language of a transactional email, picked from the recipient's `preferred_locale`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    Fr,
+    En,
+}
+
+impl Locale {
+    /// Any `en*` tag selects English; everything else (including unknown tags) falls back to French.
+    pub fn from_tag(tag: &str) -> Self {
+        if tag.trim().to_ascii_lowercase().starts_with("en") {
+            Locale::En
+        } else {
+            Locale::Fr
+        }
+    }
+
+    /// Short language tag, as stored in `preferred_locale` and used for `<html lang>`.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::Fr => "fr",
+            Locale::En => "en",
+        }
+    }
+
+    /// Numeric date format used in subjects and headers.
+    pub fn short_date(self, date: NaiveDate) -> String {
+        match self {
+            Locale::Fr => date.format("%d/%m/%Y").to_string(),
+            Locale::En => date.format("%Y-%m-%d").to_string(),
+        }
+    }
+
+    fn month_name(self, date: NaiveDate) -> &'static str {
+        let months = match self {
+            Locale::Fr => ["janvier", "février", "mars", "avril", "mai", "juin",
+                           "juillet", "août", "septembre", "octobre", "novembre", "décembre"],
+            Locale::En => ["January", "February", "March", "April", "May", "June",
+                           "July", "August", "September", "October", "November", "December"],
+        };
+        months[date.month0() as usize]
+    }
+
+    /// "Lundi 17 octobre 2026" / "Monday, October 17, 2026".
+    pub fn long_date(self, date: NaiveDate) -> String {
+        let weekday = date.weekday().num_days_from_monday() as usize;
+        let month = self.month_name(date);
+        match self {
+            Locale::Fr => {
+                let days = ["Lundi", "Mardi", "Mercredi", "Jeudi", "Vendredi", "Samedi", "Dimanche"];
+                format!("{} {} {} {}", days[weekday], date.day(), month, date.year())
+            }
+            Locale::En => {
+                let days = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+                format!("{}, {} {}, {}", days[weekday], month, date.day(), date.year())
+            }
+        }
+    }
+
+    /// "octobre 2026" / "October 2026".
+    pub fn month_year(self, date: NaiveDate) -> String {
+        format!("{} {}", self.month_name(date), date.year())
+    }
+}
+
+/// Message catalogue as (key, French, English). `{name}` marks a placeholder filled by `tr`.
+const MESSAGES: &[(&str, &str, &str)] = &[
+    ("common.regards", "Cordialement,", "Kind regards,"),
+
+    // ─── Password reset ───
+    ("reset.subject", "Réinitialisation de mot de passe — {garderie}", "Password reset — {garderie}"),
+    ("reset.heading", "Réinitialisation de mot de passe", "Reset your password"),
+    ("reset.greeting", "Bonjour {name},", "Hello {name},"),
+    ("reset.intro",
+        "Vous avez demandé une réinitialisation de votre mot de passe. Cliquez sur le bouton ci-dessous pour en créer un nouveau.",
+        "You asked to reset your password. Click the button below to choose a new one."),
+    ("reset.button", "Réinitialiser mon mot de passe", "Reset my password"),
+    ("reset.duration", "1 heure", "1 hour"),
+    ("reset.expiry",
+        "Ce lien expire dans {duration}. Si vous n'avez pas fait cette demande, ignorez cet email.",
+        "This link expires in {duration}. If you did not make this request, you can ignore this email."),
+    ("reset.text",
+        "Bonjour {name},\n\nVous avez demandé une réinitialisation de mot de passe pour {garderie}.\n\nCliquez sur ce lien pour créer un nouveau mot de passe (valide 1 heure) :\n{url}\n\nSi vous n'avez pas fait cette demande, ignorez cet email.\n\n{garderie}",
+        "Hello {name},\n\nYou asked to reset your password for {garderie}.\n\nFollow this link to choose a new password (valid for 1 hour):\n{url}\n\nIf you did not make this request, you can ignore this email.\n\n{garderie}"),
+
+    // ─── Two-factor code ───
+    ("2fa.subject", "Code de connexion — {garderie}", "Sign-in code — {garderie}"),
+    ("2fa.heading", "Code de connexion", "Sign-in code"),
+    ("2fa.intro", "Votre code de vérification à usage unique :", "Your one-time verification code:"),
+    ("2fa.duration", "15 minutes", "15 minutes"),
+    ("2fa.expiry",
+        "Ce code expire dans {duration}. Si vous n'avez pas tenté de vous connecter, ignorez cet email.",
+        "This code expires in {duration}. If you did not try to sign in, you can ignore this email."),
+    ("2fa.text",
+        "Votre code de connexion pour {garderie} est : {code}\n\nCe code est valide pendant 15 minutes.\n\nSi vous n'avez pas tenté de vous connecter, ignorez cet email.",
+        "Your sign-in code for {garderie} is: {code}\n\nThis code is valid for 15 minutes.\n\nIf you did not try to sign in, you can ignore this email."),
+
+    // ─── New device alert ───
+    ("login_alert.subject", "Nouvelle connexion à votre compte — {garderie}", "New sign-in to your account — {garderie}"),
+    ("login_alert.heading", "Nouvelle connexion détectée", "New sign-in detected"),
+    ("login_alert.intro",
+        "Bonjour {name}, votre compte {garderie} vient d'être utilisé depuis un appareil qui ne s'y était jamais connecté.",
+        "Hello {name}, your {garderie} account was just used from a device that had never signed in to it."),
+    ("login_alert.when", "Date", "Date"),
+    ("login_alert.ip", "Adresse IP", "IP address"),
+    ("login_alert.device", "Appareil", "Device"),
+    ("login_alert.unknown_device", "Inconnu", "Unknown"),
+    ("login_alert.advice",
+        "Si c'est vous, aucune action n'est requise. Sinon, changez votre mot de passe et déconnectez les autres appareils depuis votre profil.",
+        "If this was you, there is nothing to do. Otherwise, change your password and sign out the other devices from your profile."),
+    ("login_alert.text",
+        "Bonjour {name},\n\nVotre compte {garderie} vient d'être utilisé depuis un nouvel appareil.\n\nDate : {when}\nAdresse IP : {ip}\nAppareil : {device}\n\nSi c'est vous, aucune action n'est requise. Sinon, changez votre mot de passe et déconnectez les autres appareils depuis votre profil.\n\n{garderie}",
+        "Hello {name},\n\nYour {garderie} account was just used from a new device.\n\nDate: {when}\nIP address: {ip}\nDevice: {device}\n\nIf this was you, there is nothing to do. Otherwise, change your password and sign out the other devices from your profile.\n\n{garderie}"),
+
+    // ─── Refresh token reuse ───
+    ("token_reuse.subject", "Alerte de sécurité sur votre compte — {garderie}", "Security alert on your account — {garderie}"),
+    ("token_reuse.heading", "Session déconnectée par sécurité", "Session signed out for your security"),
+    ("token_reuse.intro",
+        "Bonjour {name}, un ancien jeton de session de votre compte {garderie} vient d'être réutilisé. Il a peut-être été copié : nous avons déconnecté cette session sur tous les appareils concernés.",
+        "Hello {name}, an old session token of your {garderie} account was just used again. It may have been copied, so we signed that session out on every device using it."),
+    ("token_reuse.advice",
+        "Reconnectez-vous avec votre mot de passe. Si vous ne reconnaissez pas cette activité, changez votre mot de passe et déconnectez les autres appareils depuis votre profil.",
+        "Sign in again with your password. If you do not recognize this activity, change your password and sign out the other devices from your profile."),
+    ("token_reuse.text",
+        "Bonjour {name},\n\nUn ancien jeton de session de votre compte {garderie} vient d'être réutilisé. Il a peut-être été copié : nous avons déconnecté cette session.\n\nDate : {when}\nAdresse IP : {ip}\nAppareil : {device}\n\nReconnectez-vous avec votre mot de passe. Si vous ne reconnaissez pas cette activité, changez votre mot de passe et déconnectez les autres appareils depuis votre profil.\n\n{garderie}",
+        "Hello {name},\n\nAn old session token of your {garderie} account was just used again. It may have been copied, so we signed that session out.\n\nDate: {when}\nIP address: {ip}\nDevice: {device}\n\nSign in again with your password. If you do not recognize this activity, change your password and sign out the other devices from your profile.\n\n{garderie}"),
+
+    // ─── Invitation ───
+    ("invite.subject", "Invitation à rejoindre {garderie}", "Invitation to join {garderie}"),
+    ("invite.heading", "Vous êtes invité(e) !", "You're invited!"),
+    ("invite.intro",
+        "Vous avez été invité(e) à rejoindre {garderie} en tant que {role}.",
+        "You have been invited to join {garderie} as {role}."),
+    ("invite.cta", "Créez votre compte gratuitement en cliquant sur le bouton ci-dessous.", "Create your free account with the button below."),
+    ("invite.button", "Créer mon compte", "Create my account"),
+    ("invite.duration", "7 jours", "7 days"),
+    ("invite.expiry", "Ce lien expire dans {duration}.", "This link expires in {duration}."),
+    ("invite.text",
+        "Vous êtes invité(e) à rejoindre {garderie} en tant que {role}.\n\nCliquez sur le lien pour créer votre compte :\n{url}\n\nCe lien expire dans 7 jours.",
+        "You are invited to join {garderie} as {role}.\n\nFollow this link to create your account:\n{url}\n\nThis link expires in 7 days."),
+    ("role.admin_garderie", "Administrateur", "Administrator"),
+    ("role.educateur", "Éducateur / Éducatrice", "Educator"),
+    ("role.parent", "Parent", "Parent"),
+
+    // ─── Journals (weekly) and daily digest ───
+    ("journal.subject_week", "Journal de bord de {child} - Semaine du {date}", "Daily report for {child} - Week of {date}"),
+    ("journal.subject_day", "Journal de bord de {children} — {date}", "Daily report for {children} — {date}"),
+    ("journal.subject_amended", "Journal de bord corrigé : {children} — {date}", "Corrected daily report: {children} — {date}"),
+    ("journal.amended", "Ce journal a été corrigé :", "This report was corrected:"),
+    ("journal.and", " et ", " and "),
+    ("journal.title", "Journal de bord", "Daily report"),
+    ("journal.title_child", "Journal de bord — {child}", "Daily report — {child}"),
+    ("journal.period_day", "Journal du {date} — {garderie}", "Report of {date} — {garderie}"),
+    ("journal.period_week", "Semaine du {from} au {to} — {garderie}", "Week of {from} to {to} — {garderie}"),
+    ("journal.absent", "Absent ce jour", "Absent today"),
+    ("journal.weather", "Météo :", "Weather:"),
+    ("journal.menu", "Menu du jour", "Today's menu"),
+    ("journal.collation_matin", "Collation matin :", "Morning snack:"),
+    ("journal.diner", "Dîner :", "Lunch:"),
+    ("journal.collation_apres_midi", "Collation après-midi :", "Afternoon snack:"),
+    ("journal.theme", "Thème :", "Theme:"),
+    ("journal.theme_week", "Thème de la semaine :", "Theme of the week:"),
+    ("journal.allergens", "Allergènes au menu :", "Allergens on the menu:"),
+    ("journal.appetit", "Appétit", "Appetite"),
+    ("journal.humeur", "Humeur", "Mood"),
+    ("journal.sommeil", "Sommeil", "Sleep"),
+    ("journal.sante", "Santé", "Health"),
+    ("journal.medicaments", "Médicaments", "Medication"),
+    ("journal.food_note", "Note alimentaire :", "Food note:"),
+    ("journal.educator_message", "Message de l'éducatrice :", "Message from the educator:"),
+    ("journal.observations", "Observations :", "Observations:"),
+    ("journal.pending_parent", "Parent en attente", "Pending parent"),
+    ("journal.photos", "Photos de la journée", "Photos of the day"),
+    ("journal.timeline", "Déroulement de la journée", "Timeline of the day"),
+
+    ("event.sieste", "😴 Sieste", "😴 Nap"),
+    ("event.couche", "🧷 Changement de couche", "🧷 Diaper change"),
+    ("event.repas", "🍽️ Repas", "🍽️ Meal"),
+    ("event.biberon", "🍼 Biberon", "🍼 Bottle"),
+    ("event.toilette", "🚽 Toilette", "🚽 Potty"),
+    ("event.activite", "🎨 Activité", "🎨 Activity"),
+    ("event.medicament", "💊 Médicament", "💊 Medication"),
+    ("event.autre", "📌 Autre", "📌 Other"),
+
+    ("weather.ensoleille", "☀️ Ensoleillé", "☀️ Sunny"),
+    ("weather.nuageux", "⛅ Nuageux", "⛅ Cloudy"),
+    ("weather.pluie", "🌧️ Pluie", "🌧️ Rain"),
+    ("weather.neige", "❄️ Neige", "❄️ Snow"),
+    ("weather.orageux", "⛈️ Orageux", "⛈️ Stormy"),
+
+    ("appetit.comme_habitude", "Normal", "As usual"),
+    ("appetit.peu", "Peu", "A little"),
+    ("appetit.beaucoup", "Beaucoup", "A lot"),
+    ("appetit.refuse", "Refuse", "Refused"),
+
+    ("humeur.tres_bien", "😄 Très bien", "😄 Very good"),
+    ("humeur.bien", "🙂 Bien", "🙂 Good"),
+    ("humeur.difficile", "😕 Difficile", "😕 Difficult"),
+    ("humeur.pleurs", "😢 Pleurs", "😢 Tearful"),
+
+    ("allergen.arachides", "arachides", "peanuts"),
+    ("allergen.noix", "noix", "tree nuts"),
+    ("allergen.lait", "lait", "milk"),
+    ("allergen.oeufs", "œufs", "eggs"),
+    ("allergen.ble", "blé", "wheat"),
+    ("allergen.soya", "soya", "soy"),
+    ("allergen.poisson", "poisson", "fish"),
+    ("allergen.crustaces", "crustacés", "crustaceans"),
+    ("allergen.mollusques", "mollusques", "molluscs"),
+    ("allergen.sesame", "sésame", "sesame"),
+    ("allergen.moutarde", "moutarde", "mustard"),
+    ("allergen.sulfites", "sulfites", "sulphites"),
+];
+
+/// Catalogue entry for `key`, if any.
+pub fn lookup(locale: Locale, key: &str) -> Option<&'static str> {
+    MESSAGES.iter().find(|(k, _, _)| *k == key).map(|(_, fr, en)| match locale {
+        Locale::Fr => *fr,
+        Locale::En => *en,
+    })
+}
+
+/// Message `key` in `locale` with its `{placeholders}` filled from `args`.
+/// An unknown key renders as itself so the gap is visible in the email.
+pub fn tr(locale: Locale, key: &str, args: &[(&str, &str)]) -> String {
+    fill(lookup(locale, key).unwrap_or(key), args)
+}
+
+/// `text` with each `{name}` replaced by its value in `args`; other braces are left alone.
+pub fn fill(text: &str, args: &[(&str, &str)]) -> String {
+    let mut text = text.to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{name}}}"), value);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut found: Vec<&str> = text
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+            .collect();
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    #[test]
+    fn locale_tags_fall_back_to_french() {
+        assert_eq!(Locale::from_tag("en"), Locale::En);
+        assert_eq!(Locale::from_tag("en-CA"), Locale::En);
+        assert_eq!(Locale::from_tag("fr-CA"), Locale::Fr);
+        assert_eq!(Locale::from_tag(""), Locale::Fr);
+        assert_eq!(Locale::from_tag("es"), Locale::Fr);
+    }
+
+    #[test]
+    fn catalogue_is_consistent_across_locales() {
+        for (i, (key, fr, en)) in MESSAGES.iter().enumerate() {
+            assert!(!fr.is_empty() && !en.is_empty(), "{key} has an empty translation");
+            assert_eq!(placeholders(fr), placeholders(en), "{key} placeholders differ");
+            assert!(MESSAGES[..i].iter().all(|(k, _, _)| k != key), "{key} is duplicated");
+        }
+    }
+
+    #[test]
+    fn tr_fills_placeholders() {
+        assert_eq!(
+            tr(Locale::En, "invite.intro", &[("garderie", "Les Petits"), ("role", "Parent")]),
+            "You have been invited to join Les Petits as Parent."
+        );
+        assert_eq!(tr(Locale::Fr, "missing.key", &[]), "missing.key");
+        let date = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+        assert_eq!(Locale::Fr.long_date(date), "Samedi 17 octobre 2026");
+        assert_eq!(Locale::En.long_date(date), "Saturday, October 17, 2026");
+    }
+}
@@ -0,0 +1,211 @@
+use lettre::Message;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::email_log::{EmailLogEntry, EmailLogQuery},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum EmailLogError {
+    #[error("Courriel introuvable")]
+    NotFound,
+    #[error("Ce courriel ne peut pas être renvoyé")]
+    NotResendable,
+    #[error("Cette adresse est bloquée (rejet ou plainte) : réactivez-la d'abord")]
+    Suppressed,
+}
+
+/// Sign-in emails carry one-time codes and links: a resend would replay a stale or
+/// already used secret, so their body is never kept.
+const NOT_RESENDABLE: &[&str] = &["password_reset", "2fa_code", "login_alert", "token_reuse", "invitation"];
+
+/// Larger messages (journals full of photos, big invoices) are logged without their body.
+const MAX_RAW_BYTES: usize = 2 * 1024 * 1024;
+
+/// Whether the body of a `template` email is kept for a resend.
+pub fn keeps_body(template: &str) -> bool {
+    !NOT_RESENDABLE.contains(&template)
+}
+
+/// What is logged about a message, captured before it is handed to the relay.
+#[derive(Debug, Clone)]
+pub struct LoggedEmail {
+    pub template: String,
+    pub recipients: Vec<String>,
+    pub subject: String,
+    pub message_id: Option<String>,
+    pub raw: Option<Vec<u8>>,
+    pub resent_from: Option<Uuid>,
+}
+
+impl LoggedEmail {
+    pub fn capture(email: &Message, template: &str) -> Self {
+        let raw = keeps_body(template)
+            .then(|| email.formatted())
+            .filter(|raw| raw.len() <= MAX_RAW_BYTES);
+        Self {
+            template: template.to_string(),
+            recipients: email.envelope().to().iter().map(|a| a.to_string()).collect(),
+            subject: email.headers().get_raw("Subject").unwrap_or_default().to_string(),
+            message_id: email.headers().get_raw("Message-ID").map(str::to_string),
+            raw,
+            resent_from: None,
+        }
+    }
+}
+
+/// A logged email that can be sent again.
+#[derive(Debug, sqlx::FromRow)]
+pub struct ResendSource {
+    pub recipient: String,
+    pub template: String,
+    pub subject: String,
+    pub message_id: Option<String>,
+    pub raw_message: Option<Vec<u8>>,
+}
+
+/// Swap the Message-ID of a formatted message, so mail clients do not fold a resend
+/// into the original. The id only appears in the headers.
+pub fn replace_message_id(raw: &[u8], old: &str, new: &str) -> Vec<u8> {
+    let old = old.as_bytes();
+    match raw.windows(old.len()).position(|w| w == old) {
+        Some(at) => [&raw[..at], new.as_bytes(), &raw[at + old.len()..]].concat(),
+        None => raw.to_vec(),
+    }
+}
+
+/// Per-garderie record of outgoing emails and their delivery status.
+pub struct EmailLogService;
+
+impl EmailLogService {
+    /// One row per recipient; returns their ids.
+    pub async fn record(
+        pool: &PgPool,
+        tenant: &str,
+        email: &LoggedEmail,
+        status: &str,
+        provider_response: Option<&str>,
+        error: Option<&str>,
+    ) -> anyhow::Result<Vec<Uuid>> {
+        let schema = schema_name(tenant);
+        let mut ids = Vec::with_capacity(email.recipients.len());
+        for recipient in &email.recipients {
+            let id: Uuid = sqlx::query_scalar(&format!(
+                r#"INSERT INTO "{schema}".email_log
+                   (recipient, template, subject, status, message_id, provider_response, error, raw_message, resent_from)
+                   VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                   RETURNING id"#
+            ))
+            .bind(recipient)
+            .bind(&email.template)
+            .bind(&email.subject)
+            .bind(status)
+            .bind(&email.message_id)
+            .bind(provider_response)
+            .bind(error)
+            .bind(&email.raw)
+            .bind(email.resent_from)
+            .fetch_one(pool)
+            .await?;
+            ids.push(id);
+        }
+        Ok(ids)
+    }
+
+    /// Newest first, with the total count of matching entries.
+    pub async fn list(pool: &PgPool, tenant: &str, q: &EmailLogQuery) -> anyhow::Result<(Vec<EmailLogEntry>, i64)> {
+        let schema = schema_name(tenant);
+        let limit = q.limit.unwrap_or(50).clamp(1, 200);
+        let offset = (q.page.unwrap_or(1).max(1) - 1) * limit;
+        let filter = r#"($1::TEXT IS NULL OR recipient ILIKE '%' || $1 || '%')
+              AND ($2::TEXT IS NULL OR template = $2)
+              AND ($3::TEXT IS NULL OR status = $3)
+              AND ($4::TIMESTAMPTZ IS NULL OR created_at >= $4)
+              AND ($5::TIMESTAMPTZ IS NULL OR created_at < $5)"#;
+        let recipient = q.recipient.as_deref().map(str::trim).filter(|r| !r.is_empty());
+
+        let entries: Vec<EmailLogEntry> = sqlx::query_as(&format!(
+            r#"SELECT id, recipient, template, subject, status, message_id, provider_response, error,
+                      raw_message IS NOT NULL AS resendable, resent_from, created_at
+               FROM "{schema}".email_log
+               WHERE {filter}
+               ORDER BY created_at DESC
+               LIMIT $6 OFFSET $7"#
+        ))
+        .bind(recipient)
+        .bind(&q.template)
+        .bind(&q.status)
+        .bind(q.from)
+        .bind(q.to)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar(&format!(r#"SELECT COUNT(*) FROM "{schema}".email_log WHERE {filter}"#))
+            .bind(recipient)
+            .bind(&q.template)
+            .bind(&q.status)
+            .bind(q.from)
+            .bind(q.to)
+            .fetch_one(pool)
+            .await?;
+
+        Ok((entries, total))
+    }
+
+    pub async fn get_for_resend(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<Option<ResendSource>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as(&format!(
+            r#"SELECT recipient, template, subject, message_id, raw_message FROM "{schema}".email_log WHERE id = $1"#
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_in_emails_are_not_kept() {
+        assert!(!keeps_body("password_reset"));
+        assert!(!keeps_body("2fa_code"));
+        assert!(!keeps_body("invitation"));
+        assert!(keeps_body("journal"));
+        assert!(keeps_body("invoice"));
+    }
+
+    #[test]
+    fn capture_reads_the_headers() {
+        let email = Message::builder()
+            .message_id(Some("<abc@minispace.app>".into()))
+            .from("Garderie <noreply@minispace.app>".parse().unwrap())
+            .to("Mme Roy <roy@exemple.ca>".parse().unwrap())
+            .subject("Journal de Léa")
+            .body(String::from("Bonjour"))
+            .unwrap();
+
+        let logged = LoggedEmail::capture(&email, "journal");
+        assert_eq!(logged.recipients, vec!["roy@exemple.ca"]);
+        assert_eq!(logged.subject, "Journal de Léa");
+        assert_eq!(logged.message_id.as_deref(), Some("<abc@minispace.app>"));
+        assert!(logged.raw.is_some());
+
+        assert!(LoggedEmail::capture(&email, "password_reset").raw.is_none());
+    }
+
+    #[test]
+    fn replace_message_id_swaps_the_header() {
+        let raw = b"Message-ID: <old@x>\r\nSubject: Hi\r\n\r\nBody";
+        assert_eq!(
+            replace_message_id(raw, "<old@x>", "<new@x>"),
+            b"Message-ID: <new@x>\r\nSubject: Hi\r\n\r\nBody".to_vec()
+        );
+        assert_eq!(replace_message_id(raw, "<missing@x>", "<new@x>"), raw.to_vec());
+    }
+}
@@ -0,0 +1,299 @@
+use serde_json::Value;
+use sqlx::PgPool;
+
+#[derive(Debug, thiserror::Error)]
+pub enum EmailWebhookError {
+    #[error("Fournisseur de courriel inconnu")]
+    UnknownProvider,
+    #[error("Événement de courriel invalide")]
+    InvalidPayload,
+}
+
+/// Providers whose delivery webhooks are understood.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailProvider {
+    /// Amazon SES notifications delivered through SNS.
+    Ses,
+    Mailgun,
+    Postmark,
+}
+
+impl EmailProvider {
+    pub fn parse(name: &str) -> Result<Self, EmailWebhookError> {
+        match name {
+            "ses" => Ok(Self::Ses),
+            "mailgun" => Ok(Self::Mailgun),
+            "postmark" => Ok(Self::Postmark),
+            _ => Err(EmailWebhookError::UnknownProvider),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ses => "ses",
+            Self::Mailgun => "mailgun",
+            Self::Postmark => "postmark",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuppressionReason {
+    /// Permanent failure: unknown mailbox or domain. Soft bounces are ignored.
+    Bounce,
+    /// The recipient marked an email as spam.
+    Complaint,
+}
+
+impl SuppressionReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Bounce => "bounce",
+            Self::Complaint => "complaint",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryFailure {
+    /// Lowercase address.
+    pub email: String,
+    pub reason: SuppressionReason,
+    pub detail: Option<String>,
+}
+
+/// What a webhook call asks of us.
+#[derive(Debug, PartialEq, Eq)]
+pub enum WebhookEvent {
+    Failures(Vec<DeliveryFailure>),
+    /// SNS subscription handshake: the URL must be fetched once to start receiving.
+    ConfirmSubscription(String),
+    /// Deliveries, opens, soft bounces...
+    Ignored,
+}
+
+fn failure(email: &str, reason: SuppressionReason, detail: Option<&str>) -> DeliveryFailure {
+    DeliveryFailure {
+        email: email.trim().to_lowercase(),
+        reason,
+        detail: detail.filter(|d| !d.is_empty()).map(str::to_string),
+    }
+}
+
+fn recipients(list: Option<&Value>, reason: SuppressionReason, detail: Option<&str>) -> Vec<DeliveryFailure> {
+    list.and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|r| r["emailAddress"].as_str())
+        .map(|email| failure(email, reason, detail))
+        .collect()
+}
+
+/// SES notification (`notificationType`) or event publishing record (`eventType`).
+fn parse_ses_message(msg: &Value) -> WebhookEvent {
+    let kind = msg["notificationType"].as_str().or_else(|| msg["eventType"].as_str());
+    let failures = match kind {
+        Some("Bounce") if msg["bounce"]["bounceType"] == "Permanent" => recipients(
+            msg["bounce"].get("bouncedRecipients"),
+            SuppressionReason::Bounce,
+            msg["bounce"]["bounceSubType"].as_str(),
+        ),
+        Some("Complaint") => recipients(
+            msg["complaint"].get("complainedRecipients"),
+            SuppressionReason::Complaint,
+            msg["complaint"]["complaintFeedbackType"].as_str(),
+        ),
+        _ => return WebhookEvent::Ignored,
+    };
+    WebhookEvent::Failures(failures)
+}
+
+/// Turn a provider webhook body into the addresses to suppress.
+pub fn parse_event(provider: EmailProvider, body: &Value) -> Result<WebhookEvent, EmailWebhookError> {
+    match provider {
+        EmailProvider::Ses => match body["Type"].as_str() {
+            Some("SubscriptionConfirmation") => body["SubscribeURL"]
+                .as_str()
+                .map(|url| WebhookEvent::ConfirmSubscription(url.to_string()))
+                .ok_or(EmailWebhookError::InvalidPayload),
+            Some("Notification") => {
+                let msg: Value = body["Message"]
+                    .as_str()
+                    .and_then(|m| serde_json::from_str(m).ok())
+                    .ok_or(EmailWebhookError::InvalidPayload)?;
+                Ok(parse_ses_message(&msg))
+            }
+            Some(_) => Ok(WebhookEvent::Ignored),
+            // SNS raw message delivery posts the SES record itself
+            None => Ok(parse_ses_message(body)),
+        },
+        EmailProvider::Mailgun => {
+            let data = body.get("event-data").ok_or(EmailWebhookError::InvalidPayload)?;
+            let recipient = data["recipient"].as_str().ok_or(EmailWebhookError::InvalidPayload)?;
+            Ok(match data["event"].as_str() {
+                Some("failed") if data["severity"] == "permanent" => WebhookEvent::Failures(vec![failure(
+                    recipient,
+                    SuppressionReason::Bounce,
+                    data["delivery-status"]["message"].as_str(),
+                )]),
+                Some("complained") => {
+                    WebhookEvent::Failures(vec![failure(recipient, SuppressionReason::Complaint, None)])
+                }
+                _ => WebhookEvent::Ignored,
+            })
+        }
+        EmailProvider::Postmark => {
+            let email = body["Email"].as_str().ok_or(EmailWebhookError::InvalidPayload)?;
+            Ok(match (body["RecordType"].as_str(), body["Type"].as_str()) {
+                (Some("Bounce"), Some(kind @ ("HardBounce" | "BadEmailAddress"))) => {
+                    WebhookEvent::Failures(vec![failure(email, SuppressionReason::Bounce, Some(kind))])
+                }
+                (Some("SpamComplaint"), _) => {
+                    WebhookEvent::Failures(vec![failure(email, SuppressionReason::Complaint, None)])
+                }
+                _ => WebhookEvent::Ignored,
+            })
+        }
+    }
+}
+
+/// Only SNS itself may be asked to confirm a subscription.
+pub fn is_sns_url(url: &str) -> bool {
+    url.strip_prefix("https://")
+        .and_then(|rest| rest.split('/').next())
+        .is_some_and(|host| host.starts_with("sns.") && host.ends_with(".amazonaws.com"))
+}
+
+/// Addresses the email provider reported as undeliverable. Nothing is sent to them
+/// until an admin clears the flag, typically after correcting the address.
+pub struct EmailSuppressionService;
+
+impl EmailSuppressionService {
+    /// Record failures reported by `provider`; a complaint is never downgraded to a bounce.
+    pub async fn record(pool: &PgPool, provider: EmailProvider, failures: &[DeliveryFailure]) -> anyhow::Result<()> {
+        for f in failures {
+            sqlx::query(
+                "INSERT INTO public.email_suppressions (email, reason, provider, detail)
+                 VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (email) DO UPDATE SET
+                    reason = CASE WHEN email_suppressions.reason = 'complaint' THEN 'complaint' ELSE EXCLUDED.reason END,
+                    provider = EXCLUDED.provider,
+                    detail = EXCLUDED.detail,
+                    events = email_suppressions.events + 1,
+                    updated_at = NOW()",
+            )
+            .bind(&f.email)
+            .bind(f.reason.as_str())
+            .bind(provider.as_str())
+            .bind(&f.detail)
+            .execute(pool)
+            .await?;
+            tracing::warn!("Email address suppressed ({}): {}", f.reason.as_str(), f.email);
+        }
+        Ok(())
+    }
+
+    pub async fn is_suppressed(pool: &PgPool, email: &str) -> anyhow::Result<bool> {
+        let suppressed = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM public.email_suppressions WHERE email = LOWER($1))",
+        )
+        .bind(email.trim())
+        .fetch_one(pool)
+        .await?;
+        Ok(suppressed)
+    }
+
+    /// Returns false when the address was not suppressed.
+    pub async fn clear(pool: &PgPool, email: &str) -> anyhow::Result<bool> {
+        let result = sqlx::query("DELETE FROM public.email_suppressions WHERE email = LOWER($1)")
+            .bind(email.trim())
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn ses_permanent_bounce_through_sns() {
+        let message = json!({
+            "notificationType": "Bounce",
+            "bounce": {
+                "bounceType": "Permanent",
+                "bounceSubType": "NoEmail",
+                "bouncedRecipients": [{ "emailAddress": "Parent@Exemple.ca" }]
+            }
+        });
+        let body = json!({ "Type": "Notification", "Message": message.to_string() });
+        assert_eq!(
+            parse_event(EmailProvider::Ses, &body).unwrap(),
+            WebhookEvent::Failures(vec![DeliveryFailure {
+                email: "parent@exemple.ca".into(),
+                reason: SuppressionReason::Bounce,
+                detail: Some("NoEmail".into()),
+            }])
+        );
+    }
+
+    #[test]
+    fn ses_transient_bounce_is_ignored() {
+        let body = json!({
+            "eventType": "Bounce",
+            "bounce": { "bounceType": "Transient", "bouncedRecipients": [{ "emailAddress": "a@b.ca" }] }
+        });
+        assert_eq!(parse_event(EmailProvider::Ses, &body).unwrap(), WebhookEvent::Ignored);
+    }
+
+    #[test]
+    fn ses_subscription_confirmation() {
+        let body = json!({
+            "Type": "SubscriptionConfirmation",
+            "SubscribeURL": "https://sns.ca-central-1.amazonaws.com/?Action=ConfirmSubscription"
+        });
+        let WebhookEvent::ConfirmSubscription(url) = parse_event(EmailProvider::Ses, &body).unwrap() else {
+            panic!("expected a subscription confirmation");
+        };
+        assert!(is_sns_url(&url));
+        assert!(!is_sns_url("https://sns.evil.example/?amazonaws.com"));
+        assert!(!is_sns_url("http://sns.ca-central-1.amazonaws.com/"));
+    }
+
+    #[test]
+    fn mailgun_events() {
+        let failed = json!({ "event-data": {
+            "event": "failed", "severity": "permanent", "recipient": "x@y.ca",
+            "delivery-status": { "message": "No such user" }
+        }});
+        assert_eq!(
+            parse_event(EmailProvider::Mailgun, &failed).unwrap(),
+            WebhookEvent::Failures(vec![failure("x@y.ca", SuppressionReason::Bounce, Some("No such user"))])
+        );
+
+        let temporary = json!({ "event-data": { "event": "failed", "severity": "temporary", "recipient": "x@y.ca" } });
+        assert_eq!(parse_event(EmailProvider::Mailgun, &temporary).unwrap(), WebhookEvent::Ignored);
+
+        let complained = json!({ "event-data": { "event": "complained", "recipient": "x@y.ca" } });
+        assert_eq!(
+            parse_event(EmailProvider::Mailgun, &complained).unwrap(),
+            WebhookEvent::Failures(vec![failure("x@y.ca", SuppressionReason::Complaint, None)])
+        );
+    }
+
+    #[test]
+    fn postmark_events() {
+        let hard = json!({ "RecordType": "Bounce", "Type": "HardBounce", "Email": "x@y.ca" });
+        assert_eq!(
+            parse_event(EmailProvider::Postmark, &hard).unwrap(),
+            WebhookEvent::Failures(vec![failure("x@y.ca", SuppressionReason::Bounce, Some("HardBounce"))])
+        );
+
+        let soft = json!({ "RecordType": "Bounce", "Type": "SoftBounce", "Email": "x@y.ca" });
+        assert_eq!(parse_event(EmailProvider::Postmark, &soft).unwrap(), WebhookEvent::Ignored);
+
+        assert!(parse_event(EmailProvider::Postmark, &json!({ "RecordType": "Bounce" })).is_err());
+    }
+}
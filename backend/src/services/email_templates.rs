@@ -0,0 +1,239 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::email_template::{
+        CreateEmailTemplate, EmailTemplate, UpdateEmailTemplate, TEMPLATE_ANNOUNCEMENT, TEMPLATE_INVITATION,
+        TEMPLATE_KINDS, TEMPLATE_LOCALES,
+    },
+    services::email_i18n::{lookup, Locale},
+};
+
+const MAX_SUBJECT_CHARS: usize = 255;
+const MAX_BODY_CHARS: usize = 10_000;
+
+const TEMPLATE_COLS: &str = "id, kind, locale, subject, body, updated_by, created_at, updated_at";
+
+#[derive(Debug, thiserror::Error)]
+pub enum TemplateError {
+    #[error("{0}")]
+    Invalid(String),
+    #[error("Un modèle existe déjà pour cet email dans cette langue")]
+    AlreadyExists,
+}
+
+/// Placeholders an email kind may use, or None for an unknown kind.
+pub fn placeholders(kind: &str) -> Option<&'static [&'static str]> {
+    TEMPLATE_KINDS.iter().find(|(k, _)| *k == kind).map(|(_, p)| *p)
+}
+
+/// Built-in subject and body of an email kind, in the same placeholder syntax as overrides.
+pub fn builtin(kind: &str, locale: Locale) -> Option<(String, String)> {
+    let text = |key| lookup(locale, key).unwrap_or_default();
+    match kind {
+        TEMPLATE_INVITATION => Some((
+            text("invite.subject").to_string(),
+            format!("{}\n\n{}", text("invite.intro"), text("invite.cta")),
+        )),
+        TEMPLATE_ANNOUNCEMENT => Some(("{subject}".to_string(), "{body}".to_string())),
+        _ => None,
+    }
+}
+
+pub fn parse_locale(locale: Option<&str>) -> Result<String, TemplateError> {
+    let locale = locale.map(str::trim).filter(|l| !l.is_empty()).unwrap_or("fr").to_lowercase();
+    if TEMPLATE_LOCALES.contains(&locale.as_str()) {
+        Ok(locale)
+    } else {
+        Err(TemplateError::Invalid(format!("Langue non prise en charge : {locale}")))
+    }
+}
+
+/// Names between braces in `text`, in order of appearance.
+pub(crate) fn used_placeholders(text: &str) -> Vec<&str> {
+    text.split('{')
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+        .collect()
+}
+
+pub fn validate(kind: &str, subject: &str, body: &str) -> Result<(), TemplateError> {
+    let allowed = placeholders(kind)
+        .ok_or_else(|| TemplateError::Invalid(format!("Type d'email inconnu : {kind}")))?;
+    if subject.trim().is_empty() || body.trim().is_empty() {
+        return Err(TemplateError::Invalid("Le sujet et le contenu sont requis".into()));
+    }
+    if subject.chars().count() > MAX_SUBJECT_CHARS {
+        return Err(TemplateError::Invalid(format!(
+            "Le sujet ne doit pas dépasser {MAX_SUBJECT_CHARS} caractères"
+        )));
+    }
+    if body.chars().count() > MAX_BODY_CHARS {
+        return Err(TemplateError::Invalid(format!(
+            "Le contenu ne doit pas dépasser {MAX_BODY_CHARS} caractères"
+        )));
+    }
+    if let Some(unknown) = used_placeholders(subject)
+        .into_iter()
+        .chain(used_placeholders(body))
+        .find(|name| !allowed.contains(name))
+    {
+        return Err(TemplateError::Invalid(format!(
+            "Variable inconnue {{{unknown}}} — variables disponibles : {}",
+            allowed.iter().map(|p| format!("{{{p}}}")).collect::<Vec<_>>().join(", ")
+        )));
+    }
+    Ok(())
+}
+
+pub struct EmailTemplateService;
+
+impl EmailTemplateService {
+    pub async fn list(pool: &PgPool, tenant: &str) -> anyhow::Result<Vec<EmailTemplate>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, EmailTemplate>(&format!(
+            r#"SELECT {TEMPLATE_COLS} FROM "{schema}".email_templates ORDER BY kind, locale"#
+        ))
+        .fetch_all(pool)
+        .await?)
+    }
+
+    pub async fn get(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<Option<EmailTemplate>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, EmailTemplate>(&format!(
+            r#"SELECT {TEMPLATE_COLS} FROM "{schema}".email_templates WHERE id = $1"#
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    pub async fn find(
+        pool: &PgPool,
+        tenant: &str,
+        kind: &str,
+        locale: &str,
+    ) -> anyhow::Result<Option<EmailTemplate>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, EmailTemplate>(&format!(
+            r#"SELECT {TEMPLATE_COLS} FROM "{schema}".email_templates WHERE kind = $1 AND locale = $2"#
+        ))
+        .bind(kind)
+        .bind(locale)
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    /// The garderie's wording for an email, if it has one; senders fall back to the
+    /// built-in text otherwise, including when the lookup fails.
+    pub async fn override_for(pool: &PgPool, tenant: &str, kind: &str, locale: Locale) -> Option<EmailTemplate> {
+        Self::find(pool, tenant, kind, locale.tag())
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Could not load '{kind}' email template for '{tenant}': {e}");
+                None
+            })
+    }
+
+    /// Every override of one email kind, for sends to recipients in several languages.
+    pub async fn overrides_for(pool: &PgPool, tenant: &str, kind: &str) -> Vec<EmailTemplate> {
+        let schema = schema_name(tenant);
+        sqlx::query_as::<_, EmailTemplate>(&format!(
+            r#"SELECT {TEMPLATE_COLS} FROM "{schema}".email_templates WHERE kind = $1"#
+        ))
+        .bind(kind)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!("Could not load '{kind}' email templates for '{tenant}': {e}");
+            Vec::new()
+        })
+    }
+
+    pub async fn create(
+        pool: &PgPool,
+        tenant: &str,
+        req: &CreateEmailTemplate,
+        user_id: Uuid,
+    ) -> anyhow::Result<EmailTemplate> {
+        let locale = parse_locale(req.locale.as_deref())?;
+        validate(&req.kind, &req.subject, &req.body)?;
+        if Self::find(pool, tenant, &req.kind, &locale).await?.is_some() {
+            return Err(TemplateError::AlreadyExists.into());
+        }
+
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, EmailTemplate>(&format!(
+            r#"INSERT INTO "{schema}".email_templates (kind, locale, subject, body, updated_by)
+               VALUES ($1, $2, $3, $4, $5)
+               RETURNING {TEMPLATE_COLS}"#
+        ))
+        .bind(&req.kind)
+        .bind(&locale)
+        .bind(req.subject.trim())
+        .bind(req.body.trim())
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?)
+    }
+
+    pub async fn update(
+        pool: &PgPool,
+        tenant: &str,
+        existing: &EmailTemplate,
+        req: &UpdateEmailTemplate,
+        user_id: Uuid,
+    ) -> anyhow::Result<EmailTemplate> {
+        validate(&existing.kind, &req.subject, &req.body)?;
+
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, EmailTemplate>(&format!(
+            r#"UPDATE "{schema}".email_templates
+               SET subject = $1, body = $2, updated_by = $3
+               WHERE id = $4
+               RETURNING {TEMPLATE_COLS}"#
+        ))
+        .bind(req.subject.trim())
+        .bind(req.body.trim())
+        .bind(user_id)
+        .bind(existing.id)
+        .fetch_one(pool)
+        .await?)
+    }
+
+    /// Remove an override; the built-in wording applies again.
+    pub async fn delete(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<bool> {
+        let schema = schema_name(tenant);
+        let deleted = sqlx::query(&format!(r#"DELETE FROM "{schema}".email_templates WHERE id = $1"#))
+            .bind(id)
+            .execute(pool)
+            .await?
+            .rows_affected();
+        Ok(deleted > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_kind_placeholders_are_accepted() {
+        assert!(validate(TEMPLATE_INVITATION, "Bienvenue à {garderie}", "Rejoignez-nous : {invite_url}").is_ok());
+        assert!(validate(TEMPLATE_INVITATION, "Bonjour {recipient_name}", "…").is_err());
+        assert!(validate(TEMPLATE_ANNOUNCEMENT, "{subject}", "Bonjour {recipient_name},\n\n{body}").is_ok());
+        assert!(validate(TEMPLATE_ANNOUNCEMENT, " ", "{body}").is_err());
+        assert!(validate("newsletter", "Sujet", "Contenu").is_err());
+    }
+
+    #[test]
+    fn builtin_templates_pass_their_own_validation() {
+        for (kind, _) in TEMPLATE_KINDS {
+            for locale in [Locale::Fr, Locale::En] {
+                let (subject, body) = builtin(kind, locale).unwrap();
+                assert!(validate(kind, &subject, &body).is_ok(), "{kind} built-in is invalid");
+            }
+        }
+    }
+}
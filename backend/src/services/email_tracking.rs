@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::email_campaign::{EmailCampaignLink, EmailCampaignStats},
+};
+
+/// 1x1 transparent GIF served for opens.
+pub const PIXEL_GIF: &[u8] = &[
+    0x47, 0x49, 0x46, 0x38, 0x39, 0x61, 0x01, 0x00, 0x01, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff,
+    0x21, 0xf9, 0x04, 0x01, 0x00, 0x00, 0x00, 0x00, 0x2c, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00, 0x02,
+    0x02, 0x44, 0x01, 0x00, 0x3b,
+];
+
+/// Web links of an email body, each once and in order. mailto: and tel: links are left alone.
+pub fn tracked_links(html: &str) -> Vec<&str> {
+    let mut links: Vec<&str> = Vec::new();
+    for (at, attr) in html.match_indices(r#"href=""#) {
+        let rest = &html[at + attr.len()..];
+        let url = &rest[..rest.find('"').unwrap_or(rest.len())];
+        if (url.starts_with("https://") || url.starts_with("http://")) && !links.contains(&url) {
+            links.push(url);
+        }
+    }
+    links
+}
+
+/// Point the links of `html` to their tracked address (`tracked` maps each link of
+/// [`tracked_links`] to it) and add the open pixel at the end of the body.
+pub fn instrument(html: &str, tracked: &HashMap<&str, String>, pixel_url: &str) -> String {
+    let mut out = String::with_capacity(html.len() + 256);
+    let mut rest = html;
+    while let Some(at) = rest.find(r#"href=""#) {
+        let start = at + r#"href=""#.len();
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find('"').unwrap_or(rest.len());
+        match tracked.get(&rest[..end]) {
+            Some(url) => out.push_str(url),
+            None => out.push_str(&rest[..end]),
+        }
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+
+    let pixel = format!(r#"<img src="{pixel_url}" width="1" height="1" alt="" style="display:block;border:0">"#);
+    match out.rfind("</body>") {
+        Some(at) => out.insert_str(at, &pixel),
+        None => out.push_str(&pixel),
+    }
+    out
+}
+
+/// A tracked announcement being sent: each email gets its own token.
+pub struct Campaign {
+    pool: PgPool,
+    schema: String,
+    id: Uuid,
+    /// `{app_base_url}/api/t/{slug}`
+    base_url: String,
+    links: HashMap<String, Uuid>,
+}
+
+impl Campaign {
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Tracked copy of one recipient's email body.
+    pub async fn instrument(&mut self, html: &str) -> anyhow::Result<String> {
+        let schema = &self.schema;
+        let token: Uuid = sqlx::query_scalar(&format!(
+            r#"INSERT INTO "{schema}".email_campaign_recipients (campaign_id) VALUES ($1) RETURNING id"#
+        ))
+        .bind(self.id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let mut tracked = HashMap::new();
+        for link in tracked_links(html) {
+            let link_id = match self.links.get(link) {
+                Some(id) => *id,
+                None => {
+                    let id: Uuid = sqlx::query_scalar(&format!(
+                        r#"INSERT INTO "{schema}".email_campaign_links (campaign_id, url) VALUES ($1, $2)
+                           ON CONFLICT (campaign_id, url) DO UPDATE SET url = EXCLUDED.url
+                           RETURNING id"#
+                    ))
+                    .bind(self.id)
+                    .bind(link.replace("&amp;", "&"))
+                    .fetch_one(&self.pool)
+                    .await?;
+                    self.links.insert(link.to_string(), id);
+                    id
+                }
+            };
+            tracked.insert(link, format!("{}/c/{token}/{link_id}", self.base_url));
+        }
+
+        Ok(instrument(html, &tracked, &format!("{}/o/{token}", self.base_url)))
+    }
+}
+
+/// Open and click tracking of announcements to parents, when the garderie allows it.
+/// Transactional emails (journals, invoices, sign-in...) are never tracked.
+pub struct EmailTrackingService;
+
+impl EmailTrackingService {
+    pub async fn is_enabled(pool: &PgPool, tenant: &str) -> anyhow::Result<bool> {
+        let enabled: Option<bool> =
+            sqlx::query_scalar("SELECT email_tracking_enabled FROM public.garderies WHERE slug = $1")
+                .bind(tenant)
+                .fetch_optional(pool)
+                .await?;
+        Ok(enabled.unwrap_or(false))
+    }
+
+    /// Start a campaign for an announcement, `None` when the garderie has tracking off.
+    pub async fn start(
+        pool: &PgPool,
+        tenant: &str,
+        app_base_url: &str,
+        subject: &str,
+        sent_by: Uuid,
+        message_id: Option<Uuid>,
+    ) -> anyhow::Result<Option<Campaign>> {
+        if !Self::is_enabled(pool, tenant).await? {
+            return Ok(None);
+        }
+        let schema = schema_name(tenant);
+        let id: Uuid = sqlx::query_scalar(&format!(
+            r#"INSERT INTO "{schema}".email_campaigns (subject, message_id, sent_by) VALUES ($1, $2, $3) RETURNING id"#
+        ))
+        .bind(subject)
+        .bind(message_id)
+        .bind(sent_by)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(Some(Campaign {
+            pool: pool.clone(),
+            schema,
+            id,
+            base_url: format!("{}/api/t/{tenant}", app_base_url.trim_end_matches('/')),
+            links: HashMap::new(),
+        }))
+    }
+
+    /// Count an open. Nothing is recorded once the garderie turned tracking off.
+    pub async fn record_open(pool: &PgPool, tenant: &str, token: Uuid) -> anyhow::Result<()> {
+        if !Self::is_enabled(pool, tenant).await? {
+            return Ok(());
+        }
+        let schema = schema_name(tenant);
+        sqlx::query(&format!(
+            r#"UPDATE "{schema}".email_campaign_recipients
+               SET opens = opens + 1, opened_at = COALESCE(opened_at, NOW())
+               WHERE id = $1"#
+        ))
+        .bind(token)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Where a tracked link leads, counting the click (which implies an open). Only
+    /// links stored for the recipient's campaign are followed: no open redirect.
+    pub async fn record_click(pool: &PgPool, tenant: &str, token: Uuid, link_id: Uuid) -> anyhow::Result<Option<String>> {
+        let schema = schema_name(tenant);
+        let url: Option<String> = sqlx::query_scalar(&format!(
+            r#"SELECT l.url FROM "{schema}".email_campaign_links l
+               JOIN "{schema}".email_campaign_recipients r ON r.campaign_id = l.campaign_id
+               WHERE l.id = $1 AND r.id = $2"#
+        ))
+        .bind(link_id)
+        .bind(token)
+        .fetch_optional(pool)
+        .await?;
+
+        if url.is_some() && Self::is_enabled(pool, tenant).await? {
+            sqlx::query(&format!(r#"UPDATE "{schema}".email_campaign_links SET clicks = clicks + 1 WHERE id = $1"#))
+                .bind(link_id)
+                .execute(pool)
+                .await?;
+            sqlx::query(&format!(
+                r#"UPDATE "{schema}".email_campaign_recipients
+                   SET clicks = clicks + 1,
+                       clicked_at = COALESCE(clicked_at, NOW()),
+                       opened_at = COALESCE(opened_at, NOW())
+                   WHERE id = $1"#
+            ))
+            .bind(token)
+            .execute(pool)
+            .await?;
+        }
+        Ok(url)
+    }
+
+    fn stats_query(schema: &str, filter: &str) -> String {
+        format!(
+            r#"SELECT c.id, c.subject, c.message_id, c.sent_by,
+                      COUNT(r.id) AS recipients,
+                      COUNT(r.opened_at) AS opened,
+                      COALESCE(SUM(r.opens), 0)::BIGINT AS opens,
+                      COUNT(r.clicked_at) AS clicked,
+                      COALESCE(SUM(r.clicks), 0)::BIGINT AS clicks,
+                      c.created_at
+               FROM "{schema}".email_campaigns c
+               LEFT JOIN "{schema}".email_campaign_recipients r ON r.campaign_id = c.id
+               {filter}
+               GROUP BY c.id
+               ORDER BY c.created_at DESC"#
+        )
+    }
+
+    /// Newest first.
+    pub async fn list(pool: &PgPool, tenant: &str, limit: i64) -> anyhow::Result<Vec<EmailCampaignStats>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as(&format!("{} LIMIT $1", Self::stats_query(&schema, "")))
+            .bind(limit)
+            .fetch_all(pool)
+            .await?)
+    }
+
+    pub async fn get(
+        pool: &PgPool,
+        tenant: &str,
+        id: Uuid,
+    ) -> anyhow::Result<Option<(EmailCampaignStats, Vec<EmailCampaignLink>)>> {
+        let schema = schema_name(tenant);
+        let stats: Option<EmailCampaignStats> = sqlx::query_as(&Self::stats_query(&schema, "WHERE c.id = $1"))
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        let Some(stats) = stats else { return Ok(None) };
+
+        let links: Vec<EmailCampaignLink> = sqlx::query_as(&format!(
+            r#"SELECT url, clicks FROM "{schema}".email_campaign_links WHERE campaign_id = $1 ORDER BY clicks DESC, url"#
+        ))
+        .bind(id)
+        .fetch_all(pool)
+        .await?;
+        Ok(Some((stats, links)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_web_links_are_tracked() {
+        let html = r#"<a href="https://a.ca/x">A</a> <a href="mailto:g@a.ca">M</a> <a href="https://a.ca/x">A</a> <a href="http://b.ca">B</a>"#;
+        assert_eq!(tracked_links(html), vec!["https://a.ca/x", "http://b.ca"]);
+    }
+
+    #[test]
+    fn instrument_rewrites_links_and_adds_the_pixel() {
+        let html = r#"<html><body><a href="https://a.ca/x">A</a><a href="mailto:g@a.ca">M</a></body></html>"#;
+        let tracked = HashMap::from([("https://a.ca/x", "https://app/api/t/g/c/1/2".to_string())]);
+        assert_eq!(
+            instrument(html, &tracked, "https://app/api/t/g/o/1"),
+            r#"<html><body><a href="https://app/api/t/g/c/1/2">A</a><a href="mailto:g@a.ca">M</a><img src="https://app/api/t/g/o/1" width="1" height="1" alt="" style="display:block;border:0"></body></html>"#
+        );
+    }
+
+    #[test]
+    fn pixel_is_a_gif() {
+        assert!(PIXEL_GIF.starts_with(b"GIF89a"));
+        assert_eq!(PIXEL_GIF.last(), Some(&0x3b));
+    }
+}
@@ -0,0 +1,400 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::{
+        emergency::{EmergencyDetail, EmergencyRecipient, EmergencyRequest, EmergencySummary},
+        message::{CreateMessageRequest, MessageType, MessageWithSender},
+    },
+    services::{
+        branding::BrandingService,
+        email::EmailService,
+        entitlements::EntitlementService,
+        messages::MessageService,
+        notifications::{has_active_child_sql, NotificationService},
+        sms::{normalize_phone, SmsService},
+    },
+};
+
+/// Attempts per delivery before it is given up as failed.
+pub const MAX_ATTEMPTS: i32 = 3;
+/// Deliveries claimed from the queue at a time.
+const BATCH_SIZE: i64 = 200;
+
+/// Why an emergency broadcast operation was refused.
+#[derive(Debug, thiserror::Error)]
+pub enum EmergencyError {
+    #[error("Message d'urgence introuvable")]
+    NotFound,
+}
+
+/// What became of one delivery attempt.
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Sent,
+    /// Worth retrying (relay down, timeout).
+    Error(String),
+    /// Will never go through (no SMS provider, invalid number).
+    Skipped(String),
+}
+
+/// Status of a delivery after its `attempts`-th attempt.
+pub fn next_status(outcome: &Outcome, attempts: i32) -> &'static str {
+    match outcome {
+        Outcome::Sent => "sent",
+        Outcome::Skipped(_) => "skipped",
+        Outcome::Error(_) if attempts >= MAX_ATTEMPTS => "failed",
+        Outcome::Error(_) => "pending",
+    }
+}
+
+/// A delivery claimed from the queue, with what it takes to send it.
+#[derive(Debug, sqlx::FromRow)]
+struct Job {
+    id: Uuid,
+    broadcast_id: Uuid,
+    user_id: Uuid,
+    channel: String,
+    attempts: i32,
+    email: String,
+    name: String,
+    phone: Option<String>,
+    subject: String,
+    content: String,
+}
+
+/// Senders available to the queue; a missing one skips its channel.
+#[derive(Clone)]
+pub struct Channels {
+    pub email: Option<Arc<EmailService>>,
+    pub sms: Option<Arc<SmsService>>,
+    pub notifications: Arc<NotificationService>,
+    pub app_base_url: String,
+}
+
+fn summary_select(schema: &str) -> String {
+    format!(
+        r#"SELECT b.id, b.message_id, b.sender_id, b.subject, b.content, b.created_at,
+                  (SELECT COUNT(DISTINCT d.user_id) FROM "{schema}".emergency_deliveries d WHERE d.broadcast_id = b.id) AS recipients,
+                  (SELECT COUNT(*) FROM "{schema}".emergency_acknowledgements a WHERE a.broadcast_id = b.id) AS acknowledged,
+                  (SELECT COUNT(*) FROM "{schema}".emergency_deliveries d WHERE d.broadcast_id = b.id AND d.status = 'sent') AS sent,
+                  (SELECT COUNT(*) FROM "{schema}".emergency_deliveries d WHERE d.broadcast_id = b.id AND d.status = 'pending') AS pending,
+                  (SELECT COUNT(*) FROM "{schema}".emergency_deliveries d WHERE d.broadcast_id = b.id AND d.status = 'failed') AS failed,
+                  (SELECT a.acknowledged_at FROM "{schema}".emergency_acknowledgements a
+                   WHERE a.broadcast_id = b.id AND a.user_id = $1) AS acknowledged_at
+           FROM "{schema}".emergency_broadcasts b"#
+    )
+}
+
+pub struct EmergencyService;
+
+impl EmergencyService {
+    /// Post the broadcast in the broadcast thread and queue one delivery per active
+    /// parent and channel: email always, push for parents with a registered device,
+    /// SMS for those with a phone number. Thread mutes and notification cooldowns do
+    /// not apply.
+    pub async fn create(
+        pool: &PgPool,
+        tenant: &str,
+        sender_id: Uuid,
+        req: &EmergencyRequest,
+    ) -> anyhow::Result<(Uuid, MessageWithSender)> {
+        let schema = schema_name(tenant);
+        let message = MessageService::create_message(pool, tenant, sender_id, &CreateMessageRequest {
+            message_type: MessageType::Broadcast,
+            group_id: None,
+            recipient_id: None,
+            content: format!("{}\n\n{}", req.subject.trim(), req.content.trim()),
+            urgent: true,
+        })
+        .await?;
+
+        let mut tx = pool.begin().await?;
+        let id: Uuid = sqlx::query_scalar(&format!(
+            r#"INSERT INTO "{schema}".emergency_broadcasts (message_id, sender_id, subject, content)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id"#
+        ))
+        .bind(message.id)
+        .bind(sender_id)
+        .bind(req.subject.trim())
+        .bind(req.content.trim())
+        .fetch_one(&mut *tx)
+        .await?;
+        sqlx::query(&format!(
+            r#"INSERT INTO "{schema}".emergency_deliveries (broadcast_id, user_id, channel)
+               SELECT $1, u.id, ch.channel
+               FROM "{schema}".users u
+               CROSS JOIN (VALUES ('email'), ('push'), ('sms')) AS ch(channel)
+               WHERE u.role::text = 'parent' AND u.is_active = TRUE AND {}
+                 AND CASE ch.channel
+                       WHEN 'push' THEN EXISTS(SELECT 1 FROM "{schema}".push_tokens pt WHERE pt.user_id = u.id)
+                       WHEN 'sms' THEN COALESCE(u.phone, '') <> ''
+                       ELSE TRUE
+                     END"#,
+            has_active_child_sql(&schema, "u")
+        ))
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok((id, message))
+    }
+
+    /// Broadcasts, newest first. `user_id` fills in `acknowledged_at`; with
+    /// `parent_only`, only the broadcasts that parent was sent.
+    pub async fn list(pool: &PgPool, tenant: &str, user_id: Uuid, parent_only: bool) -> anyhow::Result<Vec<EmergencySummary>> {
+        let schema = schema_name(tenant);
+        let select = summary_select(&schema);
+        Ok(sqlx::query_as::<_, EmergencySummary>(&format!(
+            r#"{select}
+               WHERE NOT $2 OR EXISTS(
+                   SELECT 1 FROM "{schema}".emergency_deliveries d WHERE d.broadcast_id = b.id AND d.user_id = $1)
+               ORDER BY b.created_at DESC
+               LIMIT 100"#
+        ))
+        .bind(user_id)
+        .bind(parent_only)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// The broadcast with each parent's delivery and acknowledgement, unacknowledged first.
+    pub async fn get(pool: &PgPool, tenant: &str, id: Uuid, user_id: Uuid) -> anyhow::Result<EmergencyDetail> {
+        let schema = schema_name(tenant);
+        let select = summary_select(&schema);
+        let summary = sqlx::query_as::<_, EmergencySummary>(&format!("{select} WHERE b.id = $2"))
+            .bind(user_id)
+            .bind(id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(EmergencyError::NotFound)?;
+        let parents = sqlx::query_as::<_, EmergencyRecipient>(&format!(
+            r#"SELECT u.id AS user_id, CONCAT(u.first_name, ' ', u.last_name) AS name, u.email, u.phone,
+                      MAX(d.status) FILTER (WHERE d.channel = 'email') AS email_status,
+                      MAX(d.status) FILTER (WHERE d.channel = 'push') AS push_status,
+                      MAX(d.status) FILTER (WHERE d.channel = 'sms') AS sms_status,
+                      MAX(d.error) FILTER (WHERE d.status = 'failed') AS error,
+                      a.acknowledged_at
+               FROM "{schema}".emergency_deliveries d
+               JOIN "{schema}".users u ON u.id = d.user_id
+               LEFT JOIN "{schema}".emergency_acknowledgements a ON a.broadcast_id = d.broadcast_id AND a.user_id = u.id
+               WHERE d.broadcast_id = $1
+               GROUP BY u.id, a.acknowledged_at
+               ORDER BY a.acknowledged_at IS NOT NULL, u.last_name, u.first_name"#
+        ))
+        .bind(id)
+        .fetch_all(pool)
+        .await?;
+        Ok(EmergencyDetail { summary, parents })
+    }
+
+    /// Record that a parent saw the broadcast; acknowledging twice keeps the first time.
+    pub async fn acknowledge(pool: &PgPool, tenant: &str, id: Uuid, user_id: Uuid) -> anyhow::Result<()> {
+        let schema = schema_name(tenant);
+        let recipient: bool = sqlx::query_scalar(&format!(
+            r#"SELECT EXISTS(SELECT 1 FROM "{schema}".emergency_deliveries WHERE broadcast_id = $1 AND user_id = $2)"#
+        ))
+        .bind(id)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+        if !recipient {
+            return Err(EmergencyError::NotFound.into());
+        }
+        sqlx::query(&format!(
+            r#"INSERT INTO "{schema}".emergency_acknowledgements (broadcast_id, user_id)
+               VALUES ($1, $2)
+               ON CONFLICT DO NOTHING"#
+        ))
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Whether the garderie has deliveries due.
+    pub async fn has_due(pool: &PgPool, tenant: &str) -> anyhow::Result<bool> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_scalar(&format!(
+            r#"SELECT EXISTS(SELECT 1 FROM "{schema}".emergency_deliveries
+                             WHERE status = 'pending' AND next_attempt_at <= NOW())"#
+        ))
+        .fetch_one(pool)
+        .await?)
+    }
+
+    /// Work through the due deliveries of a garderie, the three channels side by
+    /// side. Claimed rows are locked (`SKIP LOCKED`) and pushed back before being
+    /// sent, so concurrent workers never send the same delivery twice and a crash
+    /// only delays a retry. Returns how many were sent.
+    pub async fn process_due(pool: &PgPool, tenant: &str, channels: &Channels) -> anyhow::Result<usize> {
+        let (garderie_name, branding) = BrandingService::for_email(pool, tenant).await;
+        let ack_base = parent_messages_url(&channels.app_base_url, tenant);
+        let mut sent = 0;
+        loop {
+            let jobs = Self::claim(pool, tenant).await?;
+            if jobs.is_empty() {
+                return Ok(sent);
+            }
+            let (mut email_jobs, mut push_jobs, mut sms_jobs) = (Vec::new(), Vec::new(), Vec::new());
+            for job in jobs {
+                match job.channel.as_str() {
+                    "email" => email_jobs.push(job),
+                    "push" => push_jobs.push(job),
+                    _ => sms_jobs.push(job),
+                }
+            }
+
+            let email = async {
+                let mut outcomes = Vec::new();
+                for job in &email_jobs {
+                    let outcome = match &channels.email {
+                        None => Outcome::Skipped("Service de courriel non configuré".into()),
+                        Some(email) => {
+                            let ack_url = format!("{ack_base}?emergency={}", job.broadcast_id);
+                            let result = email
+                                .send_emergency(tenant, &job.email, &job.name, &job.subject, &job.content, &ack_url, &garderie_name, &branding)
+                                .await;
+                            result.map_or_else(|e| Outcome::Error(e.to_string()), |_| Outcome::Sent)
+                        }
+                    };
+                    outcomes.push((job, outcome));
+                }
+                outcomes
+            };
+            let push = async {
+                let mut outcomes = Vec::new();
+                for job in &push_jobs {
+                    let outcome = if channels.notifications.fcm_api_key.is_none() {
+                        Outcome::Skipped("Notifications push non configurées".into())
+                    } else {
+                        // FCM data values must be strings.
+                        let data = json!({ "type": "emergency", "id": job.broadcast_id.to_string() });
+                        let title = format!("URGENT — {}", job.subject);
+                        let result = channels
+                            .notifications
+                            .notify_user(pool, tenant, job.user_id, &title, &job.content, Some(data), None)
+                            .await;
+                        result.map_or_else(|e| Outcome::Error(e.to_string()), |_| Outcome::Sent)
+                    };
+                    outcomes.push((job, outcome));
+                }
+                outcomes
+            };
+            let sms = async {
+                let mut outcomes = Vec::new();
+                for job in &sms_jobs {
+                    let phone = job.phone.as_deref().and_then(normalize_phone);
+                    let outcome = match (&channels.sms, phone) {
+                        (None, _) => Outcome::Skipped("Service SMS non configuré".into()),
+                        (_, None) => Outcome::Skipped("Numéro de téléphone invalide".into()),
+                        (Some(sms), Some(phone)) => {
+                            let text = format!("{} — {}", job.subject, job.content);
+                            let result = sms.send_urgent_message(&phone, &garderie_name, &text).await;
+                            result.map_or_else(|e| Outcome::Error(e.to_string()), |_| Outcome::Sent)
+                        }
+                    };
+                    outcomes.push((job, outcome));
+                }
+                outcomes
+            };
+            let (email, push, sms) = tokio::join!(email, push, sms);
+
+            // Emergencies go out whatever the plan's SMS credits, but still count against them
+            let texted = sms.iter().filter(|(_, outcome)| *outcome == Outcome::Sent).count();
+            if let Err(e) = EntitlementService::record_sms(pool, tenant, texted as i64).await {
+                tracing::warn!("Failed to record emergency SMS usage for tenant {tenant}: {e}");
+            }
+
+            for (job, outcome) in email.into_iter().chain(push).chain(sms) {
+                if outcome == Outcome::Sent {
+                    sent += 1;
+                }
+                Self::record(pool, tenant, job, &outcome).await?;
+            }
+        }
+    }
+
+    /// Take a batch of due deliveries, pushing their next attempt back.
+    async fn claim(pool: &PgPool, tenant: &str) -> anyhow::Result<Vec<Job>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, Job>(&format!(
+            r#"WITH due AS (
+                   SELECT id FROM "{schema}".emergency_deliveries
+                   WHERE status = 'pending' AND next_attempt_at <= NOW()
+                   ORDER BY next_attempt_at
+                   LIMIT $1
+                   FOR UPDATE SKIP LOCKED
+               )
+               UPDATE "{schema}".emergency_deliveries d
+               SET attempts = d.attempts + 1,
+                   next_attempt_at = NOW() + make_interval(secs => 30 * (d.attempts + 1))
+               FROM due, "{schema}".users u, "{schema}".emergency_broadcasts b
+               WHERE d.id = due.id AND u.id = d.user_id AND b.id = d.broadcast_id
+               RETURNING d.id, d.broadcast_id, d.user_id, d.channel, d.attempts, u.email,
+                         CONCAT(u.first_name, ' ', u.last_name) AS name, u.phone, b.subject, b.content"#
+        ))
+        .bind(BATCH_SIZE)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    async fn record(pool: &PgPool, tenant: &str, job: &Job, outcome: &Outcome) -> anyhow::Result<()> {
+        let schema = schema_name(tenant);
+        let error = match outcome {
+            Outcome::Sent => None,
+            Outcome::Error(e) | Outcome::Skipped(e) => Some(e.as_str()),
+        };
+        if let Outcome::Error(e) = outcome {
+            tracing::warn!("Emergency {} delivery to {} in '{tenant}' failed: {e}", job.channel, job.user_id);
+        }
+        sqlx::query(&format!(
+            r#"UPDATE "{schema}".emergency_deliveries
+               SET status = $2, error = $3, sent_at = $4
+               WHERE id = $1"#
+        ))
+        .bind(job.id)
+        .bind(next_status(outcome, job.attempts))
+        .bind(error)
+        .bind((*outcome == Outcome::Sent).then(Utc::now))
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+/// The parent messages page of a garderie, where emergencies are acknowledged.
+fn parent_messages_url(app_base_url: &str, tenant: &str) -> String {
+    match app_base_url.find("://") {
+        Some(idx) => format!("{}://{tenant}.{}/fr/parent/messages", &app_base_url[..idx], &app_base_url[idx + 3..]),
+        None => format!("https://{tenant}.{app_base_url}/fr/parent/messages"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_are_retried_until_the_last_attempt() {
+        let error = Outcome::Error("timeout".into());
+        assert_eq!(next_status(&error, 1), "pending");
+        assert_eq!(next_status(&error, MAX_ATTEMPTS - 1), "pending");
+        assert_eq!(next_status(&error, MAX_ATTEMPTS), "failed");
+        assert_eq!(next_status(&Outcome::Sent, 1), "sent");
+        assert_eq!(next_status(&Outcome::Skipped("SMS".into()), 1), "skipped");
+    }
+
+    #[test]
+    fn acknowledgement_links_point_to_the_garderie_subdomain() {
+        assert_eq!(parent_messages_url("https://minispace.app", "lucioles"), "https://lucioles.minispace.app/fr/parent/messages");
+        assert_eq!(parent_messages_url("minispace.app", "lucioles"), "https://lucioles.minispace.app/fr/parent/messages");
+    }
+}
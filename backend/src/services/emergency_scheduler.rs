@@ -0,0 +1,54 @@
+use sqlx::PgPool;
+use tracing::{info, warn};
+
+use crate::db::tenant_db::TenantDb;
+use crate::services::emergency::{Channels, EmergencyService};
+
+/// How often the emergency delivery queue is checked for retries.
+const CHECK_INTERVAL_SECS: u64 = 15;
+
+/// Spawn a background task that sends the emergency deliveries still pending:
+/// retries after a failed attempt, and broadcasts interrupted by a restart.
+pub fn start(pool: PgPool, channels: Channels) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(CHECK_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let Some(_job) = crate::services::worker::job().await else {
+                break;
+            };
+
+            let tenants: Vec<String> = match sqlx::query_scalar(
+                "SELECT slug FROM public.garderies WHERE is_active = TRUE AND slug != 'demo'",
+            )
+            .fetch_all(&pool)
+            .await
+            {
+                Ok(rows) => rows,
+                Err(e) => {
+                    warn!("Emergency scheduler: failed to query tenants: {e}");
+                    continue;
+                }
+            };
+
+            for slug in tenants {
+                TenantDb::scope(slug.clone(), async {
+                    match EmergencyService::has_due(&pool, &slug).await {
+                        Ok(true) => {}
+                        Ok(false) => return,
+                        Err(e) => {
+                            warn!("Emergency scheduler: failed to check deliveries for '{slug}': {e}");
+                            return;
+                        }
+                    }
+                    match EmergencyService::process_due(&pool, &slug, &channels).await {
+                        Ok(sent) if sent > 0 => info!("Emergency scheduler: {sent} deliveries sent for '{slug}'"),
+                        Ok(_) => {}
+                        Err(e) => warn!("Emergency scheduler: failed to process deliveries for '{slug}': {e}"),
+                    }
+                })
+                .await;
+            }
+        }
+    });
+}
@@ -6,6 +6,9 @@ use anyhow::{Context, Result};
 use hkdf::Hkdf;
 use rand::RngCore;
 use sha2::Sha256;
+use std::collections::HashMap;
+
+use crate::config::Config;
 
 /// Dérive une clé de chiffrement spécifique au tenant à partir de la clé maître
 /// 
@@ -94,6 +97,81 @@ pub fn decrypt_file(ciphertext: &[u8], iv: &[u8], tag: &[u8], key: &[u8; 32]) ->
     Ok(plaintext)
 }
 
+/// Décode une clé maître hexadécimale (64 caractères → 32 bytes)
+pub fn decode_master_key(hex_key: &str) -> Result<[u8; 32]> {
+    let bytes = hex::decode(hex_key.trim()).context("Master key must be hex-encoded")?;
+    if bytes.len() != 32 {
+        anyhow::bail!("Master key must be exactly 32 bytes");
+    }
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&bytes);
+    Ok(key)
+}
+
+/// Clés maîtres connues, indexées par version.
+///
+/// Les nouveaux fichiers sont chiffrés avec la version courante ; les versions
+/// précédentes ne servent qu'à relire les fichiers pas encore re-chiffrés
+/// (voir `services::key_rotation`).
+#[derive(Clone)]
+pub struct KeyRing {
+    current_version: i32,
+    keys: HashMap<i32, [u8; 32]>,
+}
+
+impl KeyRing {
+    /// # Arguments
+    /// * `current_key` - Clé maître courante (hex)
+    /// * `current_version` - Version de la clé courante
+    /// * `previous_keys` - Anciennes clés au format `version:hex,version:hex`
+    pub fn new(current_key: &str, current_version: i32, previous_keys: Option<&str>) -> Result<Self> {
+        let mut keys = HashMap::new();
+        for entry in previous_keys.unwrap_or_default().split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (version, key) = entry
+                .split_once(':')
+                .context("Previous keys must be formatted as version:hex")?;
+            let version: i32 = version.trim().parse().context("Invalid previous key version")?;
+            keys.insert(version, decode_master_key(key)?);
+        }
+        if keys.contains_key(&current_version) {
+            anyhow::bail!("Key version {current_version} is both current and previous");
+        }
+        keys.insert(current_version, decode_master_key(current_key)?);
+        Ok(Self { current_version, keys })
+    }
+
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Self::new(
+            &config.encryption_master_key,
+            config.encryption_key_version,
+            config.encryption_previous_keys.as_deref(),
+        )
+    }
+
+    pub fn current_version(&self) -> i32 {
+        self.current_version
+    }
+
+    /// True when older keys are still configured (a rotation may be in progress).
+    pub fn has_previous(&self) -> bool {
+        self.keys.len() > 1
+    }
+
+    /// Clé du tenant pour une version donnée de la clé maître.
+    pub fn tenant_key(&self, version: i32, tenant: &str) -> Result<[u8; 32]> {
+        let master_key = self
+            .keys
+            .get(&version)
+            .with_context(|| format!("Unknown encryption key version {version}"))?;
+        derive_tenant_key(master_key, tenant)
+    }
+
+    /// Clé courante du tenant et sa version (pour chiffrer).
+    pub fn current_tenant_key(&self, tenant: &str) -> Result<(i32, [u8; 32])> {
+        Ok((self.current_version, self.tenant_key(self.current_version, tenant)?))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -155,4 +233,20 @@ mod tests {
         let result = decrypt_file(&ciphertext, &iv, &tag, &key);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_key_ring_versions() {
+        let old = hex::encode([1u8; 32]);
+        let new = hex::encode([2u8; 32]);
+        let ring = KeyRing::new(&new, 2, Some(&format!("1:{old}"))).unwrap();
+
+        assert_eq!(ring.current_version(), 2);
+        assert!(ring.has_previous());
+        assert_eq!(ring.tenant_key(1, "t").unwrap(), derive_tenant_key(&[1u8; 32], "t").unwrap());
+        assert_eq!(ring.current_tenant_key("t").unwrap().1, derive_tenant_key(&[2u8; 32], "t").unwrap());
+        assert!(ring.tenant_key(3, "t").is_err());
+
+        // La version courante ne peut pas aussi figurer parmi les anciennes
+        assert!(KeyRing::new(&new, 1, Some(&format!("1:{old}"))).is_err());
+    }
 }
@@ -0,0 +1,240 @@
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::{
+    db::tenant::schema_name,
+    models::tenant::PlanType,
+    services::{storage, upload_types},
+};
+
+/// What a plan grants; `None` is unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Limits {
+    pub children: Option<i64>,
+    /// Administrators and educators.
+    pub staff: Option<i64>,
+    pub storage_bytes: i64,
+    /// SMS sent per calendar month (urgent messages; emergencies are never held back).
+    pub sms_per_month: i64,
+    pub api_keys: i64,
+    pub video_bytes: u64,
+    pub video_secs: u32,
+}
+
+pub fn limits(plan: &PlanType) -> Limits {
+    let (children, staff, sms_per_month, api_keys) = match plan {
+        PlanType::Free => (Some(15), Some(3), 0, 0),
+        PlanType::Standard => (Some(80), Some(15), 500, 2),
+        PlanType::Premium => (None, None, 2000, 10),
+    };
+    Limits {
+        children,
+        staff,
+        storage_bytes: storage::quota_bytes(plan),
+        sms_per_month,
+        api_keys,
+        video_bytes: upload_types::max_video_bytes(plan),
+        video_secs: upload_types::max_video_secs(plan),
+    }
+}
+
+/// The plan to suggest when a limit of `plan` is reached.
+fn upgrade(plan: &PlanType) -> Option<PlanType> {
+    match plan {
+        PlanType::Free => Some(PlanType::Standard),
+        PlanType::Standard => Some(PlanType::Premium),
+        PlanType::Premium => None,
+    }
+}
+
+fn plan_name(plan: &PlanType) -> &'static str {
+    match plan {
+        PlanType::Free => "Gratuit",
+        PlanType::Standard => "Standard",
+        PlanType::Premium => "Premium",
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    Children,
+    Staff,
+}
+
+impl Resource {
+    fn label(self) -> &'static str {
+        match self {
+            Resource::Children => "enfants actifs",
+            Resource::Staff => "membres du personnel",
+        }
+    }
+}
+
+/// How to get past a limit, appended to the error message.
+fn upgrade_hint(plan: &PlanType) -> String {
+    match upgrade(plan) {
+        Some(next) => format!("Passez au forfait {} pour aller plus loin.", plan_name(&next)),
+        None => "Contactez-nous pour augmenter cette limite.".to_string(),
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EntitlementError {
+    #[error("Limite de {limit} {} du forfait {} atteinte. {}", resource.label(), plan_name(plan), upgrade_hint(plan))]
+    LimitReached { resource: Resource, limit: i64, plan: PlanType },
+}
+
+/// Current consumption of what plans limit.
+#[derive(Debug, Clone, Serialize)]
+pub struct Usage {
+    pub children: i64,
+    pub staff: i64,
+    pub storage_bytes: i64,
+    pub sms_this_month: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Entitlements {
+    pub plan: PlanType,
+    pub limits: Limits,
+    pub usage: Usage,
+    /// Plan lifting the limits, absent on the highest one.
+    pub upgrade_to: Option<PlanType>,
+}
+
+pub struct EntitlementService;
+
+impl EntitlementService {
+    pub async fn plan(pool: &PgPool, tenant: &str) -> anyhow::Result<PlanType> {
+        Ok(sqlx::query_scalar("SELECT plan FROM public.garderies WHERE slug = $1")
+            .bind(tenant)
+            .fetch_one(pool)
+            .await?)
+    }
+
+    async fn count(pool: &PgPool, tenant: &str, resource: Resource) -> anyhow::Result<i64> {
+        let schema = schema_name(tenant);
+        let sql = match resource {
+            Resource::Children => format!("SELECT COUNT(*) FROM {schema}.children WHERE is_active = TRUE"),
+            Resource::Staff => format!(
+                "SELECT COUNT(*) FROM {schema}.users
+                 WHERE is_active = TRUE AND role::TEXT IN ('admin_garderie', 'educateur')"
+            ),
+        };
+        Ok(sqlx::query_scalar(&sql).fetch_one(pool).await?)
+    }
+
+    /// Refuse adding `adding` children or staff members past the plan's limit.
+    pub async fn ensure_can_add(pool: &PgPool, tenant: &str, resource: Resource, adding: i64) -> anyhow::Result<()> {
+        let plan = Self::plan(pool, tenant).await?;
+        let limits = limits(&plan);
+        let limit = match resource {
+            Resource::Children => limits.children,
+            Resource::Staff => limits.staff,
+        };
+        let Some(limit) = limit else {
+            return Ok(());
+        };
+        if Self::count(pool, tenant, resource).await? + adding > limit {
+            return Err(EntitlementError::LimitReached { resource, limit, plan }.into());
+        }
+        Ok(())
+    }
+
+    /// Take up to `wanted` SMS credits of the current month and return how many
+    /// were granted.
+    pub async fn take_sms_credits(pool: &PgPool, tenant: &str, wanted: i64) -> anyhow::Result<i64> {
+        let plan = Self::plan(pool, tenant).await?;
+        Self::add_sms(pool, tenant, wanted, Some(limits(&plan).sms_per_month)).await
+    }
+
+    /// Count SMS sent regardless of credits (emergency broadcasts).
+    pub async fn record_sms(pool: &PgPool, tenant: &str, sent: i64) -> anyhow::Result<()> {
+        Self::add_sms(pool, tenant, sent, None).await.map(|_| ())
+    }
+
+    async fn add_sms(pool: &PgPool, tenant: &str, wanted: i64, limit: Option<i64>) -> anyhow::Result<i64> {
+        if wanted <= 0 {
+            return Ok(0);
+        }
+        let mut tx = pool.begin().await?;
+        let sent: i64 = sqlx::query_scalar(
+            "INSERT INTO public.sms_usage (garderie_id, month)
+             SELECT id, date_trunc('month', NOW())::DATE FROM public.garderies WHERE slug = $1
+             ON CONFLICT (garderie_id, month) DO UPDATE SET sent = public.sms_usage.sent
+             RETURNING sent::BIGINT",
+        )
+        .bind(tenant)
+        .fetch_one(&mut *tx)
+        .await?;
+        let granted = match limit {
+            Some(limit) => wanted.min((limit - sent).max(0)),
+            None => wanted,
+        };
+        sqlx::query(
+            "UPDATE public.sms_usage SET sent = sent + $2
+             WHERE garderie_id = (SELECT id FROM public.garderies WHERE slug = $1)
+               AND month = date_trunc('month', NOW())::DATE",
+        )
+        .bind(tenant)
+        .bind(granted as i32)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(granted)
+    }
+
+    /// Plan, limits and what the garderie uses of them, for /tenant/info.
+    pub async fn current(
+        pool: &PgPool,
+        redis: &mut redis::aio::MultiplexedConnection,
+        tenant: &str,
+    ) -> anyhow::Result<Entitlements> {
+        let plan = Self::plan(pool, tenant).await?;
+        let storage = storage::StorageService::usage(pool, redis, tenant).await?;
+        let sms_this_month: Option<i32> = sqlx::query_scalar(
+            "SELECT u.sent FROM public.sms_usage u
+             JOIN public.garderies g ON g.id = u.garderie_id
+             WHERE g.slug = $1 AND u.month = date_trunc('month', NOW())::DATE",
+        )
+        .bind(tenant)
+        .fetch_optional(pool)
+        .await?;
+        Ok(Entitlements {
+            limits: limits(&plan),
+            usage: Usage {
+                children: Self::count(pool, tenant, Resource::Children).await?,
+                staff: Self::count(pool, tenant, Resource::Staff).await?,
+                storage_bytes: storage.used_bytes,
+                sms_this_month: sms_this_month.unwrap_or(0) as i64,
+            },
+            upgrade_to: upgrade(&plan),
+            plan,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn higher_plans_grant_more() {
+        let (free, standard, premium) = (limits(&PlanType::Free), limits(&PlanType::Standard), limits(&PlanType::Premium));
+        assert!(free.children < standard.children && standard.staff > free.staff);
+        assert_eq!(premium.children, None);
+        assert!(free.storage_bytes < standard.storage_bytes && standard.storage_bytes < premium.storage_bytes);
+        assert!(free.sms_per_month < standard.sms_per_month && standard.sms_per_month < premium.sms_per_month);
+    }
+
+    #[test]
+    fn limit_errors_suggest_the_next_plan() {
+        let e = EntitlementError::LimitReached { resource: Resource::Children, limit: 15, plan: PlanType::Free };
+        assert_eq!(
+            e.to_string(),
+            "Limite de 15 enfants actifs du forfait Gratuit atteinte. Passez au forfait Standard pour aller plus loin."
+        );
+        let e = EntitlementError::LimitReached { resource: Resource::Staff, limit: 15, plan: PlanType::Premium };
+        assert!(e.to_string().ends_with("Contactez-nous pour augmenter cette limite."));
+    }
+}
@@ -0,0 +1,184 @@
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::erasure::ErasureCertificate,
+    services::{identities::IdentityService, messages::TOMBSTONE},
+};
+
+/// Why a user could not be erased.
+#[derive(Debug, thiserror::Error)]
+pub enum ErasureError {
+    #[error("Utilisateur non trouvé")]
+    NotFound,
+    #[error("Les données de cet utilisateur ont déjà été effacées")]
+    AlreadyErased,
+}
+
+/// Right-to-be-forgotten erasure (Loi 25, art. 28.1). The user row stays, since
+/// messages, media and documents reference it, but everything identifying is removed.
+pub struct ErasureService;
+
+impl ErasureService {
+    /// Erase a user's personal data in one transaction and record a certificate.
+    ///
+    /// Their links to children, sessions, devices, push tokens and pending codes are
+    /// deleted; with `scrub_messages`, the content of the messages they wrote (and its
+    /// edit history) is replaced by the deletion tombstone and their media comments
+    /// are deleted.
+    pub async fn erase(
+        pool: &PgPool,
+        tenant: &str,
+        user_id: Uuid,
+        erased_by: Uuid,
+        scrub_messages: bool,
+    ) -> anyhow::Result<ErasureCertificate> {
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+
+        let (email, role, erased_at): (String, String, Option<chrono::DateTime<chrono::Utc>>) =
+            sqlx::query_as(&format!(
+                "SELECT email, role::TEXT, erased_at FROM {schema}.users WHERE id = $1 FOR UPDATE"
+            ))
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(ErasureError::NotFound)?;
+        if erased_at.is_some() {
+            return Err(ErasureError::AlreadyErased.into());
+        }
+
+        let mut summary = Map::new();
+        let by_user: &[(&str, &str)] = &[
+            ("children_unlinked", "child_parents"),
+            ("push_tokens", "push_tokens"),
+            ("media_marks", "media_user_state"),
+            ("trusted_devices", "trusted_devices"),
+            ("sessions", "refresh_tokens"),
+            ("two_factor_codes", "two_factor_codes"),
+            ("password_reset_tokens", "password_reset_tokens"),
+        ];
+        for (key, table) in by_user {
+            let n = delete_where(&mut tx, &format!("DELETE FROM {schema}.{table} WHERE user_id = $1"), user_id).await?;
+            summary.insert(key.to_string(), n.into());
+        }
+
+        // Pending links and invitations are keyed by address, not by account
+        let pending = sqlx::query(&format!(
+            "DELETE FROM {schema}.child_pending_parents WHERE LOWER(email) = LOWER($1)"
+        ))
+        .bind(&email)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        summary.insert("pending_child_links".into(), pending.into());
+
+        let invitations = sqlx::query(&format!(
+            "DELETE FROM {schema}.invitation_tokens WHERE LOWER(email) = LOWER($1)"
+        ))
+        .bind(&email)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        summary.insert("invitations".into(), invitations.into());
+
+        let emails = sqlx::query(&format!("DELETE FROM {schema}.email_log WHERE LOWER(recipient) = LOWER($1)"))
+            .bind(&email)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        summary.insert("email_log".into(), emails.into());
+
+        // Consent records stay as proof of past consent, minus the address they came from
+        sqlx::query(&format!("UPDATE {schema}.consent_records SET ip_address = NULL WHERE user_id = $1"))
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        if scrub_messages {
+            let edits = delete_where(
+                &mut tx,
+                &format!(
+                    "DELETE FROM {schema}.message_edits
+                     WHERE edited_by = $1
+                        OR message_id IN (SELECT id FROM {schema}.messages WHERE sender_id = $1)"
+                ),
+                user_id,
+            )
+            .await?;
+            summary.insert("message_edits".into(), edits.into());
+
+            let scrubbed = sqlx::query(&format!(
+                "UPDATE {schema}.messages
+                 SET content = $2, subject = NULL, is_deleted = TRUE, deleted_at = COALESCE(deleted_at, NOW())
+                 WHERE sender_id = $1"
+            ))
+            .bind(user_id)
+            .bind(TOMBSTONE)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            summary.insert("messages".into(), scrubbed.into());
+
+            let comments = delete_where(
+                &mut tx,
+                &format!("DELETE FROM {schema}.media_comments WHERE author_id = $1"),
+                user_id,
+            )
+            .await?;
+            summary.insert("media_comments".into(), comments.into());
+        }
+
+        // An unusable hash: bcrypt never produces "!", so no password can match
+        sqlx::query(&format!(
+            "UPDATE {schema}.users
+             SET email = $2, password_hash = '!', first_name = 'Utilisateur', last_name = 'supprimé',
+                 phone = NULL, avatar_url = NULL, is_active = FALSE, erased_at = NOW()
+             WHERE id = $1"
+        ))
+        .bind(user_id)
+        .bind(format!("erased-{user_id}@invalid"))
+        .execute(&mut *tx)
+        .await?;
+
+        let email_sha256 = hex::encode(Sha256::digest(email.trim().to_lowercase().as_bytes()));
+        let certificate = sqlx::query_as::<_, ErasureCertificate>(&format!(
+            "INSERT INTO {schema}.erasure_certificates
+                (user_id, email_sha256, role, erased_by, messages_scrubbed, summary)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             RETURNING id, user_id, email_sha256, role, erased_by, messages_scrubbed, summary, erased_at"
+        ))
+        .bind(user_id)
+        .bind(&email_sha256)
+        .bind(&role)
+        .bind(erased_by)
+        .bind(scrub_messages)
+        .bind(Value::Object(summary))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        IdentityService::unlink(pool, tenant, user_id).await?;
+        Ok(certificate)
+    }
+
+    /// Erasure certificates of the garderie, most recent first.
+    pub async fn certificates(pool: &PgPool, tenant: &str) -> anyhow::Result<Vec<ErasureCertificate>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, ErasureCertificate>(&format!(
+            "SELECT id, user_id, email_sha256, role, erased_by, messages_scrubbed, summary, erased_at
+             FROM {schema}.erasure_certificates
+             ORDER BY erased_at DESC"
+        ))
+        .fetch_all(pool)
+        .await?)
+    }
+}
+
+async fn delete_where(tx: &mut Transaction<'_, Postgres>, sql: &str, user_id: Uuid) -> anyhow::Result<u64> {
+    Ok(sqlx::query(sql).bind(user_id).execute(&mut **tx).await?.rows_affected())
+}
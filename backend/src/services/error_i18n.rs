@@ -0,0 +1,204 @@
+use axum::http::HeaderMap;
+
+use crate::services::email_i18n::Locale;
+
+/// An API error message: a stable key clients can rely on, and its text in each language.
+#[derive(Debug)]
+pub struct ErrorMessage {
+    pub key: &'static str,
+    pub fr: &'static str,
+    pub en: &'static str,
+}
+
+impl ErrorMessage {
+    pub fn text(&self, locale: Locale) -> &'static str {
+        match locale {
+            Locale::Fr => self.fr,
+            Locale::En => self.en,
+        }
+    }
+}
+
+const fn msg(key: &'static str, fr: &'static str, en: &'static str) -> ErrorMessage {
+    ErrorMessage { key, fr, en }
+}
+
+/// Catalog of API error messages. Errors are built from a key
+/// ([`ApiError::from_key`](crate::error::ApiError::from_key), or
+/// [`ErrorStatus::key`](crate::error::ErrorStatus::key) for typed errors) and go out in
+/// French; [`crate::middleware::error_i18n::localize_errors`] translates them by key on
+/// the way out. Messages with variable parts are not listed and go out as they are.
+pub const MESSAGES: &[ErrorMessage] = &[
+    msg("forbidden", "Accès refusé", "Access denied"),
+    msg("not_found", "Introuvable", "Not found"),
+    msg("internal_error", "Erreur interne du serveur", "Internal server error"),
+    msg("database_error", "Erreur de base de données", "Database error"),
+    msg("authentication_required", "Authentification requise", "Authentication required"),
+    msg("tenant_not_found", "Garderie introuvable", "Daycare not found"),
+    msg("invalid_tenant", "Identifiant de garderie invalide", "Invalid tenant identifier"),
+    msg("missing_tenant", "En-tête X-Tenant manquant", "Missing X-Tenant header"),
+    msg("account_inactive", "Compte désactivé", "Account is inactive"),
+    msg(
+        "garderie_archived",
+        "Cette garderie a été archivée. Contactez le support pour la réactiver.",
+        "This daycare has been archived. Contact support to have it reactivated.",
+    ),
+    msg(
+        "trial_read_only",
+        "La période d'essai est terminée : la garderie est en lecture seule. Contactez le support pour la prolonger.",
+        "The trial period is over: the daycare is read-only. Contact support to extend it.",
+    ),
+    msg(
+        "maintenance",
+        "Minispace est en maintenance. Le service sera de retour dans quelques instants.",
+        "Minispace is down for maintenance. The service will be back shortly.",
+    ),
+    msg(
+        "too_many_attempts",
+        "Trop de tentatives. Réessayez dans quelques minutes.",
+        "Too many attempts. Try again in a few minutes.",
+    ),
+    msg(
+        "storage_quota_reached",
+        "Quota de stockage atteint. Supprimez des fichiers ou changez de forfait.",
+        "Storage quota reached. Delete files or change plan.",
+    ),
+    msg("captcha_required", "Vérification anti-robot requise.", "Anti-bot verification required."),
+    msg(
+        "captcha_unavailable",
+        "Vérification anti-robot indisponible. Réessayez dans quelques minutes.",
+        "Anti-bot verification unavailable. Try again in a few minutes.",
+    ),
+    msg("wrong_password", "Mot de passe incorrect", "Incorrect password"),
+    msg("wrong_current_password", "Mot de passe actuel incorrect", "Current password is incorrect"),
+    msg("invalid_credentials", "Identifiants invalides", "Invalid credentials"),
+    msg(
+        "unknown_garderie",
+        "Garderie introuvable : vérifiez l'identifiant garderie",
+        "Daycare not found: check the daycare identifier",
+    ),
+    msg(
+        "two_factor_unavailable",
+        "Service email non configuré (SMTP requis pour la 2FA)",
+        "Email service not configured (SMTP required for 2FA)",
+    ),
+    msg(
+        "two_factor_code_expired",
+        "Code invalide ou expiré. Veuillez vous reconnecter.",
+        "Invalid or expired code. Please sign in again.",
+    ),
+    msg(
+        "two_factor_too_many_attempts",
+        "Trop de tentatives. Veuillez vous reconnecter pour obtenir un nouveau code.",
+        "Too many attempts. Sign in again to get a new code.",
+    ),
+    msg("two_factor_invalid_code", "Code invalide", "Invalid code"),
+    msg("session_invalid", "Session invalide ou révoquée", "Invalid or revoked session"),
+    msg("session_expired", "Session expirée", "Session expired"),
+    msg("invalid_access_token", "Jeton d'accès invalide ou expiré", "Invalid or expired access token"),
+    msg("no_linked_account", "Aucun compte lié dans cette garderie", "No linked account in this daycare"),
+    msg("reset_token_invalid", "Token invalide ou expiré", "Invalid or expired token"),
+    msg("invitation_invalid", "Invitation invalide ou déjà utilisée", "Invalid or already used invitation"),
+    msg("invitation_expired", "Invitation expirée", "Invitation expired"),
+    msg("email_taken", "Cet email est déjà utilisé", "This email is already in use"),
+    msg("already_exists", "Cet élément existe déjà", "This item already exists"),
+    msg("invalid_reference", "Référence à un élément inexistant", "Reference to a missing item"),
+    msg("out_of_range", "Valeur hors des limites permises", "Value out of the allowed range"),
+    msg("user_not_found", "Utilisateur introuvable", "User not found"),
+    msg("invalid_role", "Rôle invalide", "Invalid role"),
+    msg("invalid_status", "Statut invalide", "Invalid status"),
+    msg("message_not_found", "Message introuvable", "Message not found"),
+    msg("invitation_not_found", "Invitation non trouvée", "Invitation not found"),
+    msg("session_not_found", "Session introuvable", "Session not found"),
+    msg("invalid_phone", "Numéro de téléphone invalide", "Invalid phone number"),
+    msg("email_not_configured", "Service de courriel non configuré", "Email service not configured"),
+    msg("missing_file", "Aucun fichier fourni (champ 'file' manquant)", "No file provided ('file' field missing)"),
+    msg("invalid_date", "Date invalide", "Invalid date"),
+    msg("invalid_month", "Mois invalide (format AAAA-MM)", "Invalid month format. Use YYYY-MM"),
+    msg("invalid_year", "Année invalide", "Invalid year"),
+    msg("child_not_linked", "Cet enfant n'est pas lié à votre compte", "Child not linked to parent"),
+    msg(
+        "attendance_past_date",
+        "Impossible de modifier la présence d'une date passée",
+        "Cannot change attendance for past dates",
+    ),
+    msg(
+        "parent_attendance_status",
+        "Les parents peuvent seulement indiquer une absence ou une présence",
+        "Parents can only set absent or present status",
+    ),
+    msg("staff_only", "Réservé au personnel", "Only staff can access this endpoint"),
+    msg("activity_not_found", "Activité introuvable", "Activity not found"),
+    msg("validation_failed", "Données invalides", "Invalid data"),
+    msg("invalid_json", "Corps de requête JSON invalide", "Invalid JSON request body"),
+    msg("payload_too_large", "Requête trop volumineuse", "Request body too large"),
+    msg(
+        "version_conflict",
+        "Modifié entre-temps par quelqu'un d'autre : rechargez avant d'enregistrer",
+        "Changed by someone else in the meantime: reload before saving",
+    ),
+    msg("invalid_if_match", "En-tête If-Match invalide", "Invalid If-Match header"),
+    msg("invalid_api_version", "En-tête X-Api-Version invalide", "Invalid X-Api-Version header"),
+    msg("invalid_idempotency_key", "En-tête Idempotency-Key invalide", "Invalid Idempotency-Key header"),
+    msg(
+        "idempotency_in_progress",
+        "Cette requête est déjà en cours de traitement",
+        "This request is already being processed",
+    ),
+    msg(
+        "idempotency_key_reused",
+        "Cette clé Idempotency-Key a déjà servi pour une autre requête",
+        "This Idempotency-Key was already used for a different request",
+    ),
+];
+
+pub fn by_key(key: &str) -> Option<&'static ErrorMessage> {
+    MESSAGES.iter().find(|m| m.key == key)
+}
+
+/// Language of an error response: the user's `preferred_locale` (from the access token)
+/// when signed in, else the
+/// first language of `Accept-Language`, else French.
+pub fn response_locale(headers: &HeaderMap, preferred_locale: Option<&str>) -> Locale {
+    if let Some(tag) = preferred_locale {
+        return Locale::from_tag(tag);
+    }
+    headers
+        .get(axum::http::header::ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(',').next())
+        .map(|tag| Locale::from_tag(tag.split(';').next().unwrap_or(tag)))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_catalog_keys_are_unique() {
+        let mut keys = HashSet::new();
+        for m in MESSAGES {
+            assert!(keys.insert(m.key), "duplicate key {}", m.key);
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(by_key("forbidden").unwrap().text(Locale::En), "Access denied");
+        assert_eq!(by_key("forbidden").unwrap().text(Locale::Fr), "Accès refusé");
+        assert!(by_key("Accès refusé").is_none());
+    }
+
+    #[test]
+    fn test_response_locale() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(response_locale(&headers, None), Locale::Fr);
+        headers.insert("accept-language", "en-CA,en;q=0.9,fr;q=0.8".parse().unwrap());
+        assert_eq!(response_locale(&headers, None), Locale::En);
+        assert_eq!(response_locale(&headers, Some("fr")), Locale::Fr);
+        headers.insert("accept-language", "fr-CA;q=1".parse().unwrap());
+        assert_eq!(response_locale(&headers, Some("en")), Locale::En);
+    }
+}
@@ -0,0 +1,146 @@
+use chrono::NaiveDate;
+use redis::{aio::MultiplexedConnection, Value};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::message::MessageWithSender;
+
+/// Redis stream the domain events are appended to, for all garderies.
+pub const STREAM: &str = "minispace:events";
+/// Entries kept in the stream (approximately) once consumed.
+const MAX_LEN: u64 = 10_000;
+
+/// Something that happened in a garderie which people should hear about. Request
+/// handlers only publish these; emails, push notifications, SMS and WebSocket
+/// updates are sent by the notification consumer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DomainEvent {
+    /// A photo or video was uploaded, or its visibility changed, and parents may see it.
+    MediaPublished { media_id: Uuid, uploader_id: Uuid },
+    /// A message was posted. `urgent` also texts it to parents; `notify_email` is off
+    /// when the message was already emailed as such (send-to-parents).
+    MessageSent {
+        message: Box<MessageWithSender>,
+        urgent: bool,
+        notify_email: bool,
+    },
+    /// Weekly journals were sent for these children.
+    JournalSent { child_ids: Vec<Uuid>, week_start: NaiveDate },
+    /// Someone commented on a photo or video.
+    MediaCommented { media_id: Uuid, comment_id: Uuid },
+    /// A child's journal entry or one of their events was saved during the day, in a
+    /// garderie with live journal updates. `event_type` is set for events (sieste…).
+    JournalUpdated {
+        child_id: Uuid,
+        date: NaiveDate,
+        event_type: Option<String>,
+    },
+}
+
+/// A stream entry: the event and the garderie it happened in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope {
+    pub tenant: String,
+    #[serde(flatten)]
+    pub event: DomainEvent,
+}
+
+/// Append an event to the stream. The change that caused it is already stored, so
+/// a failure only costs its notifications and is logged.
+pub async fn publish(redis: &mut MultiplexedConnection, tenant: &str, event: DomainEvent) {
+    let envelope = Envelope { tenant: tenant.to_string(), event };
+    let payload = match serde_json::to_string(&envelope) {
+        Ok(payload) => payload,
+        Err(e) => {
+            tracing::warn!("Event for '{tenant}' not serialized: {e}");
+            return;
+        }
+    };
+    let added: redis::RedisResult<String> = redis::cmd("XADD")
+        .arg(STREAM)
+        .arg("MAXLEN")
+        .arg("~")
+        .arg(MAX_LEN)
+        .arg("*")
+        .arg("event")
+        .arg(&payload)
+        .query_async(redis)
+        .await;
+    if let Err(e) = added {
+        tracing::warn!("Event for '{tenant}' not published, its notifications are lost: {e}");
+    }
+}
+
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::BulkString(bytes) => String::from_utf8(bytes.clone()).ok(),
+        Value::SimpleString(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+/// (entry id, `event` field) of the entries in an XREADGROUP reply:
+/// `[[stream, [[id, [field, value, …]], …]], …]`. A timed out read replies nil.
+pub fn entries(reply: &Value) -> Vec<(String, Option<String>)> {
+    let Value::Array(streams) = reply else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    for stream in streams {
+        let Value::Array(parts) = stream else { continue };
+        let Some(Value::Array(items)) = parts.get(1) else { continue };
+        for item in items {
+            let Value::Array(item) = item else { continue };
+            let Some(id) = item.first().and_then(text) else { continue };
+            // Entries deleted while pending come back with nil fields.
+            let payload = match item.get(1) {
+                Some(Value::Array(fields)) => fields
+                    .chunks(2)
+                    .find(|kv| kv.first().and_then(text).as_deref() == Some("event"))
+                    .and_then(|kv| kv.get(1).and_then(text)),
+                _ => None,
+            };
+            out.push((id, payload));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bulk(s: &str) -> Value {
+        Value::BulkString(s.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn envelopes_round_trip_through_json() {
+        let envelope = Envelope {
+            tenant: "lucioles".into(),
+            event: DomainEvent::JournalSent {
+                child_ids: vec![Uuid::nil()],
+                week_start: NaiveDate::from_ymd_opt(2026, 10, 12).unwrap(),
+            },
+        };
+        let json = serde_json::to_string(&envelope).unwrap();
+        assert!(json.contains(r#""type":"journal_sent""#));
+        let back: Envelope = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.tenant, "lucioles");
+        assert!(matches!(back.event, DomainEvent::JournalSent { ref child_ids, .. } if child_ids == &[Uuid::nil()]));
+    }
+
+    #[test]
+    fn reads_entries_from_a_stream_reply() {
+        let reply = Value::Array(vec![Value::Array(vec![
+            bulk(STREAM),
+            Value::Array(vec![
+                Value::Array(vec![bulk("1-0"), Value::Array(vec![bulk("event"), bulk("{}")])]),
+                Value::Array(vec![bulk("2-0"), Value::Nil]),
+            ]),
+        ])]);
+        assert_eq!(entries(&reply), vec![("1-0".into(), Some("{}".into())), ("2-0".into(), None)]);
+        assert!(entries(&Value::Nil).is_empty());
+    }
+}
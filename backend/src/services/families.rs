@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::family::{
+        CreateFamilyRequest, Family, FamilyChild, FamilyGuardian, FamilySuggestion, FamilyView, UpdateFamilyRequest,
+    },
+};
+
+/// Why a family operation was refused.
+#[derive(Debug, thiserror::Error)]
+pub enum FamilyError {
+    #[error("Famille introuvable")]
+    NotFound,
+    #[error("Enfant introuvable")]
+    ChildNotFound,
+}
+
+/// Children linked through shared parents, from (child, parent) links: two children
+/// with a parent in common are siblings, and so are the siblings of a sibling. Only
+/// sets of two children or more are returned, in the order of the links.
+pub fn sibling_sets(links: &[(Uuid, Uuid)]) -> Vec<Vec<Uuid>> {
+    let mut children: Vec<Uuid> = Vec::new();
+    let mut index: HashMap<Uuid, usize> = HashMap::new();
+    let mut root: Vec<usize> = Vec::new();
+    let mut first_child_of: HashMap<Uuid, usize> = HashMap::new();
+
+    fn find(root: &mut [usize], mut i: usize) -> usize {
+        while root[i] != i {
+            root[i] = root[root[i]];
+            i = root[i];
+        }
+        i
+    }
+
+    for &(child_id, parent_id) in links {
+        let i = *index.entry(child_id).or_insert_with(|| {
+            children.push(child_id);
+            root.push(root.len());
+            root.len() - 1
+        });
+        let j = *first_child_of.entry(parent_id).or_insert(i);
+        let (a, b) = (find(&mut root, i), find(&mut root, j));
+        if a != b {
+            root[a.max(b)] = a.min(b);
+        }
+    }
+
+    let mut sets: Vec<Vec<Uuid>> = Vec::new();
+    let mut set_of_root: HashMap<usize, usize> = HashMap::new();
+    for (i, child_id) in children.iter().enumerate() {
+        let r = find(&mut root, i);
+        let set = *set_of_root.entry(r).or_insert_with(|| {
+            sets.push(Vec::new());
+            sets.len() - 1
+        });
+        sets[set].push(*child_id);
+    }
+    sets.retain(|s| s.len() > 1);
+    sets
+}
+
+pub struct FamilyService;
+
+impl FamilyService {
+    /// Families with their children and guardians, by name. With `parent_id`, only
+    /// the families of that parent's children.
+    pub async fn list(pool: &PgPool, tenant: &str, parent_id: Option<Uuid>) -> anyhow::Result<Vec<FamilyView>> {
+        let schema = schema_name(tenant);
+        let families = sqlx::query_as::<_, Family>(&format!(
+            r#"SELECT f.* FROM "{schema}".families f
+               WHERE $1::UUID IS NULL OR EXISTS (
+                   SELECT 1 FROM "{schema}".children c
+                   JOIN "{schema}".child_parents cp ON cp.child_id = c.id
+                   WHERE c.family_id = f.id AND cp.user_id = $1)
+               ORDER BY f.name"#
+        ))
+        .bind(parent_id)
+        .fetch_all(pool)
+        .await?;
+        Self::with_members(pool, &schema, families).await
+    }
+
+    pub async fn get(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<FamilyView> {
+        let schema = schema_name(tenant);
+        let family = sqlx::query_as::<_, Family>(&format!(r#"SELECT * FROM "{schema}".families WHERE id = $1"#))
+            .bind(id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(FamilyError::NotFound)?;
+        let mut views = Self::with_members(pool, &schema, vec![family]).await?;
+        Ok(views.remove(0))
+    }
+
+    async fn with_members(pool: &PgPool, schema: &str, families: Vec<Family>) -> anyhow::Result<Vec<FamilyView>> {
+        let ids: Vec<Uuid> = families.iter().map(|f| f.id).collect();
+        let children = sqlx::query_as::<_, FamilyChild>(&format!(
+            r#"SELECT id, family_id, first_name, last_name, birth_date, group_id, is_active
+               FROM "{schema}".children
+               WHERE family_id = ANY($1)
+               ORDER BY birth_date, first_name"#
+        ))
+        .bind(&ids)
+        .fetch_all(pool)
+        .await?;
+        let guardians = sqlx::query_as::<_, FamilyGuardian>(&format!(
+            r#"SELECT c.family_id, u.id AS user_id, u.first_name, u.last_name, u.email, u.phone,
+                      array_agg(c.id ORDER BY c.birth_date) AS child_ids
+               FROM "{schema}".children c
+               JOIN "{schema}".child_parents cp ON cp.child_id = c.id
+               JOIN "{schema}".users u ON u.id = cp.user_id
+               WHERE c.family_id = ANY($1) AND u.is_active = TRUE
+               GROUP BY c.family_id, u.id
+               ORDER BY u.last_name, u.first_name"#
+        ))
+        .bind(&ids)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(families
+            .into_iter()
+            .map(|family| FamilyView {
+                children: children.iter().filter(|c| c.family_id == family.id).cloned().collect(),
+                guardians: guardians.iter().filter(|g| g.family_id == family.id).cloned().collect(),
+                family,
+            })
+            .collect())
+    }
+
+    /// Whether the user is a parent of one of the family's children.
+    pub async fn is_guardian(pool: &PgPool, tenant: &str, id: Uuid, user_id: Uuid) -> anyhow::Result<bool> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_scalar(&format!(
+            r#"SELECT EXISTS(
+                   SELECT 1 FROM "{schema}".children c
+                   JOIN "{schema}".child_parents cp ON cp.child_id = c.id
+                   WHERE c.family_id = $1 AND cp.user_id = $2)"#
+        ))
+        .bind(id)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?)
+    }
+
+    pub async fn create(pool: &PgPool, tenant: &str, req: &CreateFamilyRequest) -> anyhow::Result<FamilyView> {
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+        let id: Uuid = sqlx::query_scalar(&format!(
+            r#"INSERT INTO "{schema}".families (name, notes, combined_billing)
+               VALUES ($1, $2, $3)
+               RETURNING id"#
+        ))
+        .bind(req.name.trim())
+        .bind(&req.notes)
+        .bind(req.combined_billing)
+        .fetch_one(&mut *tx)
+        .await?;
+        Self::assign(&mut tx, &schema, id, &req.child_ids).await?;
+        tx.commit().await?;
+        Self::get(pool, tenant, id).await
+    }
+
+    pub async fn update(pool: &PgPool, tenant: &str, id: Uuid, req: &UpdateFamilyRequest) -> anyhow::Result<FamilyView> {
+        let schema = schema_name(tenant);
+        let updated = sqlx::query(&format!(
+            r#"UPDATE "{schema}".families
+               SET name = COALESCE($2, name), notes = COALESCE($3, notes),
+                   combined_billing = COALESCE($4, combined_billing)
+               WHERE id = $1"#
+        ))
+        .bind(id)
+        .bind(req.name.as_deref().map(str::trim))
+        .bind(&req.notes)
+        .bind(req.combined_billing)
+        .execute(pool)
+        .await?
+        .rows_affected();
+        if updated == 0 {
+            return Err(FamilyError::NotFound.into());
+        }
+        Self::get(pool, tenant, id).await
+    }
+
+    /// Delete the family; its children stay enrolled, without a family.
+    pub async fn delete(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<()> {
+        let schema = schema_name(tenant);
+        let deleted = sqlx::query(&format!(r#"DELETE FROM "{schema}".families WHERE id = $1"#))
+            .bind(id)
+            .execute(pool)
+            .await?
+            .rows_affected();
+        if deleted == 0 {
+            return Err(FamilyError::NotFound.into());
+        }
+        Ok(())
+    }
+
+    /// Replace the family's children. A child moves out of its previous family.
+    pub async fn set_children(pool: &PgPool, tenant: &str, id: Uuid, child_ids: &[Uuid]) -> anyhow::Result<FamilyView> {
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+        sqlx::query_scalar::<_, Uuid>(&format!(r#"SELECT id FROM "{schema}".families WHERE id = $1 FOR UPDATE"#))
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(FamilyError::NotFound)?;
+        sqlx::query(&format!(r#"UPDATE "{schema}".children SET family_id = NULL WHERE family_id = $1"#))
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        Self::assign(&mut tx, &schema, id, child_ids).await?;
+        tx.commit().await?;
+        Self::get(pool, tenant, id).await
+    }
+
+    async fn assign(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        schema: &str,
+        id: Uuid,
+        child_ids: &[Uuid],
+    ) -> anyhow::Result<()> {
+        let mut unique = child_ids.to_vec();
+        unique.sort();
+        unique.dedup();
+        let assigned = sqlx::query(&format!(
+            r#"UPDATE "{schema}".children SET family_id = $1 WHERE id = ANY($2)"#
+        ))
+        .bind(id)
+        .bind(&unique)
+        .execute(&mut **tx)
+        .await?
+        .rows_affected();
+        if assigned != unique.len() as u64 {
+            return Err(FamilyError::ChildNotFound.into());
+        }
+        Ok(())
+    }
+
+    /// Active children without a family who share a parent, grouped as the
+    /// families an admin would likely create.
+    pub async fn suggestions(pool: &PgPool, tenant: &str) -> anyhow::Result<Vec<FamilySuggestion>> {
+        let schema = schema_name(tenant);
+        let rows: Vec<(Uuid, Uuid, String)> = sqlx::query_as(&format!(
+            r#"SELECT c.id, cp.user_id, c.last_name
+               FROM "{schema}".children c
+               JOIN "{schema}".child_parents cp ON cp.child_id = c.id
+               WHERE c.family_id IS NULL AND c.is_active = TRUE
+               ORDER BY c.birth_date, c.id"#
+        ))
+        .fetch_all(pool)
+        .await?;
+        let last_names: HashMap<Uuid, &str> = rows.iter().map(|(c, _, name)| (*c, name.as_str())).collect();
+        let links: Vec<(Uuid, Uuid)> = rows.iter().map(|(c, p, _)| (*c, *p)).collect();
+        Ok(sibling_sets(&links)
+            .into_iter()
+            .map(|child_ids| FamilySuggestion {
+                name: last_names.get(&child_ids[0]).copied().unwrap_or_default().to_string(),
+                child_ids,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn children_sharing_a_parent_form_one_set() {
+        let [a, b, c, d, e] = [(); 5].map(|_| Uuid::new_v4());
+        let [mom, dad, step, other] = [(); 4].map(|_| Uuid::new_v4());
+        // a and b share mom; c is b's half-sibling through dad; d is alone; e shares step with c
+        let links = [(a, mom), (b, mom), (b, dad), (d, other), (c, step), (c, dad), (e, step)];
+        assert_eq!(sibling_sets(&links), vec![vec![a, b, c, e]]);
+    }
+
+    #[test]
+    fn only_children_are_not_suggested() {
+        let [a, b] = [(); 2].map(|_| Uuid::new_v4());
+        let [mom, dad] = [(); 2].map(|_| Uuid::new_v4());
+        assert!(sibling_sets(&[(a, mom), (a, dad), (b, Uuid::new_v4())]).is_empty());
+        assert_eq!(sibling_sets(&[(a, mom), (b, dad), (b, mom)]), vec![vec![a, b]]);
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::models::feature_flag::FeatureFlag;
+
+/// Platform-wide flag putting the API in maintenance: 503 for everything but super-admin traffic.
+pub const MAINTENANCE_MODE: &str = "maintenance_mode";
+/// Per-garderie flag streaming journal changes to parents during the day (WebSocket and push).
+pub const LIVE_JOURNAL_UPDATES: &str = "live_journal_updates";
+
+/// Flags are read on every request: the table is kept in memory this long, so a change
+/// made on another instance takes effect within this delay.
+const CACHE_TTL: Duration = Duration::from_secs(15);
+
+static CACHE: RwLock<Option<(Instant, Vec<FeatureFlag>)>> = RwLock::new(None);
+
+const FLAG_COLS: &str = "f.id, f.key, g.slug, f.enabled, f.updated_by, f.updated_at";
+
+#[derive(Debug, thiserror::Error)]
+pub enum FeatureFlagError {
+    #[error("Nom de drapeau invalide (lettres minuscules, chiffres et _, 64 caractères au plus)")]
+    InvalidKey,
+    #[error("Garderie introuvable")]
+    UnknownGarderie,
+    #[error("Le mode maintenance ne peut pas être défini par garderie")]
+    MaintenancePerGarderie,
+    #[error("Drapeau introuvable")]
+    NotFound,
+}
+
+/// Flags in effect for a request: platform-wide values, overridden by the garderie's.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(transparent)]
+pub struct FeatureFlags(BTreeMap<String, bool>);
+
+impl FeatureFlags {
+    /// Resolve the flags of `slug` (platform-wide only when `None`).
+    pub fn resolve(flags: &[FeatureFlag], slug: Option<&str>) -> Self {
+        let mut resolved = BTreeMap::new();
+        for f in flags.iter().filter(|f| f.slug.is_none()) {
+            resolved.insert(f.key.clone(), f.enabled);
+        }
+        if let Some(slug) = slug {
+            for f in flags.iter().filter(|f| f.slug.as_deref() == Some(slug)) {
+                resolved.insert(f.key.clone(), f.enabled);
+            }
+        }
+        FeatureFlags(resolved)
+    }
+
+    /// Unknown flags are off.
+    pub fn is_enabled(&self, key: &str) -> bool {
+        self.0.get(key).copied().unwrap_or(false)
+    }
+}
+
+pub fn is_valid_key(key: &str) -> bool {
+    (1..=64).contains(&key.len()) && key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+pub struct FeatureFlagService;
+
+impl FeatureFlagService {
+    /// Every flag row, from the in-memory copy when fresh.
+    pub async fn all(pool: &PgPool) -> anyhow::Result<Vec<FeatureFlag>> {
+        if let Some((at, flags)) = CACHE.read().unwrap().as_ref() {
+            if at.elapsed() < CACHE_TTL {
+                return Ok(flags.clone());
+            }
+        }
+        let flags = sqlx::query_as::<_, FeatureFlag>(&format!(
+            "SELECT {FLAG_COLS} FROM public.feature_flags f
+             LEFT JOIN public.garderies g ON g.id = f.garderie_id
+             ORDER BY f.key, g.slug NULLS FIRST"
+        ))
+        .fetch_all(pool)
+        .await?;
+        *CACHE.write().unwrap() = Some((Instant::now(), flags.clone()));
+        Ok(flags)
+    }
+
+    fn invalidate() {
+        *CACHE.write().unwrap() = None;
+    }
+
+    pub async fn set(
+        pool: &PgPool,
+        key: &str,
+        slug: Option<&str>,
+        enabled: bool,
+        operator: Option<&str>,
+    ) -> anyhow::Result<()> {
+        if !is_valid_key(key) {
+            return Err(FeatureFlagError::InvalidKey.into());
+        }
+        match slug {
+            None => {
+                sqlx::query(
+                    "INSERT INTO public.feature_flags (key, enabled, updated_by) VALUES ($1, $2, $3)
+                     ON CONFLICT (key) WHERE garderie_id IS NULL
+                     DO UPDATE SET enabled = EXCLUDED.enabled, updated_by = EXCLUDED.updated_by, updated_at = NOW()",
+                )
+                .bind(key)
+                .bind(enabled)
+                .bind(operator)
+                .execute(pool)
+                .await?;
+            }
+            Some(_) if key == MAINTENANCE_MODE => return Err(FeatureFlagError::MaintenancePerGarderie.into()),
+            Some(slug) => {
+                let inserted = sqlx::query(
+                    "INSERT INTO public.feature_flags (key, garderie_id, enabled, updated_by)
+                     SELECT $1, id, $3, $4 FROM public.garderies WHERE slug = $2
+                     ON CONFLICT (key, garderie_id) WHERE garderie_id IS NOT NULL
+                     DO UPDATE SET enabled = EXCLUDED.enabled, updated_by = EXCLUDED.updated_by, updated_at = NOW()",
+                )
+                .bind(key)
+                .bind(slug)
+                .bind(enabled)
+                .bind(operator)
+                .execute(pool)
+                .await?;
+                if inserted.rows_affected() == 0 {
+                    return Err(FeatureFlagError::UnknownGarderie.into());
+                }
+            }
+        }
+        Self::invalidate();
+        Ok(())
+    }
+
+    /// Remove a platform-wide value or a garderie override.
+    pub async fn remove(pool: &PgPool, key: &str, slug: Option<&str>) -> anyhow::Result<()> {
+        let deleted = match slug {
+            None => {
+                sqlx::query("DELETE FROM public.feature_flags WHERE key = $1 AND garderie_id IS NULL")
+                    .bind(key)
+                    .execute(pool)
+                    .await?
+            }
+            Some(slug) => {
+                sqlx::query(
+                    "DELETE FROM public.feature_flags
+                     WHERE key = $1 AND garderie_id = (SELECT id FROM public.garderies WHERE slug = $2)",
+                )
+                .bind(key)
+                .bind(slug)
+                .execute(pool)
+                .await?
+            }
+        };
+        if deleted.rows_affected() == 0 {
+            return Err(FeatureFlagError::NotFound.into());
+        }
+        Self::invalidate();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn flag(key: &str, slug: Option<&str>, enabled: bool) -> FeatureFlag {
+        FeatureFlag {
+            id: Uuid::new_v4(),
+            key: key.into(),
+            slug: slug.map(Into::into),
+            enabled,
+            updated_by: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_garderie_overrides_global() {
+        let flags = [flag("gallery", None, true), flag("gallery", Some("a"), false), flag("beta", Some("b"), true)];
+        let a = FeatureFlags::resolve(&flags, Some("a"));
+        assert!(!a.is_enabled("gallery"));
+        assert!(!a.is_enabled("beta"));
+        let b = FeatureFlags::resolve(&flags, Some("b"));
+        assert!(b.is_enabled("gallery"));
+        assert!(b.is_enabled("beta"));
+        assert!(FeatureFlags::resolve(&flags, None).is_enabled("gallery"));
+        assert!(!FeatureFlags::resolve(&flags, None).is_enabled("unknown"));
+    }
+
+    #[test]
+    fn test_valid_key() {
+        assert!(is_valid_key("maintenance_mode"));
+        assert!(is_valid_key("v2"));
+        assert!(!is_valid_key(""));
+        assert!(!is_valid_key("Beta"));
+        assert!(!is_valid_key("new-ui"));
+    }
+}
@@ -1,19 +1,124 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
 use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::{
     db::tenant::schema_name,
-    models::group::{CreateGroupRequest, Group, UpdateGroupRequest},
+    models::{
+        auth::AuthenticatedUser,
+        group::{
+            ChildGroupHistory, ChildMove, CreateGroupRequest, Group, GroupEducator, RolloverRequest, RolloverResult,
+            UpdateGroupRequest,
+        },
+        user::UserRole,
+    },
+    services::{branding::BrandingService, email::EmailService, ratios::age_in_months},
 };
 
+/// An educator's assignment doesn't cover the requested group or child.
+#[derive(Debug, thiserror::Error)]
+#[error("Accès refusé : ce groupe ne vous est pas assigné")]
+pub struct OutOfScope;
+
+/// Why a group could not be changed.
+#[derive(Debug, thiserror::Error)]
+pub enum GroupError {
+    #[error("Groupe introuvable")]
+    NotFound,
+    #[error("Ce groupe est archivé")]
+    Archived,
+    #[error("Des enfants font encore partie de ce groupe : déplacez-les avant de l'archiver")]
+    HasChildren,
+    #[error("Ce groupe a un historique (messages, photos, documents…) : archivez-le plutôt que de le supprimer")]
+    HasHistory,
+}
+
+/// Why a rollover was refused.
+#[derive(Debug, thiserror::Error)]
+pub enum RolloverError {
+    #[error("Indiquez soit des correspondances de groupes, soit des tranches d'âge")]
+    InvalidMode,
+    #[error("Un groupe source apparaît plusieurs fois")]
+    DuplicateSource,
+    #[error("Tranches d'âge invalides ou qui se chevauchent")]
+    InvalidAgeRules,
+    #[error("Groupe introuvable")]
+    UnknownGroup,
+}
+
+/// An active child as a rollover sees it: id, current group and birth date.
+pub type RolloverChild = (Uuid, Option<Uuid>, NaiveDate);
+
+/// Moves of a rollover, computed from the assignments before it: with A → B and
+/// B → C, the children of A land in B and those of B in C. Children already in
+/// their target group are not listed.
+pub fn plan_rollover(
+    children: &[RolloverChild],
+    req: &RolloverRequest,
+    as_of: NaiveDate,
+) -> Result<Vec<ChildMove>, RolloverError> {
+    let end = |max: Option<i32>| max.unwrap_or(i32::MAX);
+    // Source → target groups, or `None` for age bands
+    let mapping = match (req.mappings.is_empty(), req.age_rules.is_empty()) {
+        (false, true) => {
+            let mut map = HashMap::new();
+            for m in &req.mappings {
+                if map.insert(m.source_group_id, m.target_group_id).is_some() {
+                    return Err(RolloverError::DuplicateSource);
+                }
+            }
+            Some(map)
+        }
+        (true, false) => {
+            let rules = &req.age_rules;
+            for (i, a) in rules.iter().enumerate() {
+                if a.min_age_months < 0 || end(a.max_age_months) <= a.min_age_months {
+                    return Err(RolloverError::InvalidAgeRules);
+                }
+                if rules[i + 1..]
+                    .iter()
+                    .any(|b| a.min_age_months < end(b.max_age_months) && b.min_age_months < end(a.max_age_months))
+                {
+                    return Err(RolloverError::InvalidAgeRules);
+                }
+            }
+            None
+        }
+        _ => return Err(RolloverError::InvalidMode),
+    };
+    let target = |from: Option<Uuid>, birth: NaiveDate| match &mapping {
+        Some(map) => from.and_then(|g| map.get(&g).copied()),
+        None => {
+            let age = age_in_months(birth, as_of);
+            req.age_rules
+                .iter()
+                .find(|r| age >= r.min_age_months && age < end(r.max_age_months))
+                .map(|r| Some(r.group_id))
+        }
+    };
+
+    Ok(children
+        .iter()
+        .filter_map(|&(child_id, from, birth)| {
+            let to = target(from, birth)?;
+            (to != from).then_some(ChildMove { child_id, from_group_id: from, to_group_id: to })
+        })
+        .collect())
+}
+
 pub struct GroupService;
 
 impl GroupService {
-    pub async fn list(pool: &PgPool, tenant: &str) -> anyhow::Result<Vec<Group>> {
+    pub async fn list(pool: &PgPool, tenant: &str, include_archived: bool) -> anyhow::Result<Vec<Group>> {
         let schema = schema_name(tenant);
         let groups = sqlx::query_as::<_, Group>(&format!(
-            "SELECT * FROM {schema}.groups ORDER BY name"
+            "SELECT * FROM {schema}.groups
+             WHERE $1 OR archived_at IS NULL
+             ORDER BY archived_at IS NOT NULL, name"
         ))
+        .bind(include_archived)
         .fetch_all(pool)
         .await?;
         Ok(groups)
@@ -26,13 +131,14 @@ impl GroupService {
     ) -> anyhow::Result<Group> {
         let schema = schema_name(tenant);
         let group = sqlx::query_as::<_, Group>(&format!(
-            "INSERT INTO {schema}.groups (name, description, color)
-             VALUES ($1, $2, $3)
+            "INSERT INTO {schema}.groups (name, description, color, allow_parent_replies)
+             VALUES ($1, $2, $3, $4)
              RETURNING *"
         ))
         .bind(&req.name)
         .bind(&req.description)
         .bind(&req.color)
+        .bind(req.allow_parent_replies)
         .fetch_one(pool)
         .await?;
         Ok(group)
@@ -49,7 +155,8 @@ impl GroupService {
             "UPDATE {schema}.groups
              SET name = COALESCE($1, name),
                  description = COALESCE($2, description),
-                 color = COALESCE($3, color)
+                 color = COALESCE($3, color),
+                 allow_parent_replies = COALESCE($5, allow_parent_replies)
              WHERE id = $4
              RETURNING *"
         ))
@@ -57,20 +164,103 @@ impl GroupService {
         .bind(&req.description)
         .bind(&req.color)
         .bind(id)
+        .bind(req.allow_parent_replies)
         .fetch_one(pool)
         .await?;
         Ok(group)
     }
 
+    /// Whether parents may post in the group's thread (see `Group::allow_parent_replies`).
+    pub async fn parent_replies_allowed(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<bool> {
+        let schema = schema_name(tenant);
+        let allowed: Option<bool> = sqlx::query_scalar(&format!(
+            "SELECT allow_parent_replies FROM {schema}.groups WHERE id = $1"
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(allowed.unwrap_or(false))
+    }
+
+    /// Delete a group with no history; one with messages, media, documents, albums,
+    /// activities or former children can only be archived.
     pub async fn delete(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<()> {
         let schema = schema_name(tenant);
-        sqlx::query(&format!("DELETE FROM {schema}.groups WHERE id = $1"))
+        let has_history: bool = sqlx::query_scalar(&format!(
+            "SELECT EXISTS(SELECT 1 FROM {schema}.messages WHERE group_id = $1 OR send_to_parents_group = $1)
+                 OR EXISTS(SELECT 1 FROM {schema}.media WHERE group_id = $1)
+                 OR EXISTS(SELECT 1 FROM {schema}.documents WHERE group_id = $1)
+                 OR EXISTS(SELECT 1 FROM {schema}.albums WHERE group_id = $1)
+                 OR EXISTS(SELECT 1 FROM {schema}.activities WHERE group_id = $1)
+                 OR EXISTS(SELECT 1 FROM {schema}.child_group_history WHERE group_id = $1)"
+        ))
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+        if has_history {
+            return Err(GroupError::HasHistory.into());
+        }
+        let deleted = sqlx::query(&format!("DELETE FROM {schema}.groups WHERE id = $1"))
             .bind(id)
             .execute(pool)
-            .await?;
+            .await?
+            .rows_affected();
+        if deleted == 0 {
+            return Err(GroupError::NotFound.into());
+        }
         Ok(())
     }
 
+    /// Archive a group. Its children must have been moved out first.
+    pub async fn archive(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<Group> {
+        let schema = schema_name(tenant);
+        let has_children: bool = sqlx::query_scalar(&format!(
+            "SELECT EXISTS(SELECT 1 FROM {schema}.children WHERE group_id = $1 AND is_active = TRUE)"
+        ))
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+        if has_children {
+            return Err(GroupError::HasChildren.into());
+        }
+        Self::set_archived(pool, tenant, id, true).await
+    }
+
+    pub async fn restore(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<Group> {
+        Self::set_archived(pool, tenant, id, false).await
+    }
+
+    async fn set_archived(pool: &PgPool, tenant: &str, id: Uuid, archived: bool) -> anyhow::Result<Group> {
+        let schema = schema_name(tenant);
+        sqlx::query_as::<_, Group>(&format!(
+            "UPDATE {schema}.groups
+             SET archived_at = CASE WHEN $2 THEN COALESCE(archived_at, NOW()) END
+             WHERE id = $1
+             RETURNING *"
+        ))
+        .bind(id)
+        .bind(archived)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| GroupError::NotFound.into())
+    }
+
+    /// Fails with [`GroupError::Archived`] if the group is archived (missing groups pass:
+    /// foreign keys catch them).
+    pub async fn ensure_not_archived(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<()> {
+        let schema = schema_name(tenant);
+        let archived: Option<bool> = sqlx::query_scalar(&format!(
+            "SELECT archived_at IS NOT NULL FROM {schema}.groups WHERE id = $1"
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+        match archived {
+            Some(true) => Err(GroupError::Archived.into()),
+            _ => Ok(()),
+        }
+    }
+
     /// Replace the children list for a group: detach all current children,
     /// then attach the provided ones.
     pub async fn set_children(
@@ -79,6 +269,7 @@ impl GroupService {
         id: Uuid,
         child_ids: &[Uuid],
     ) -> anyhow::Result<()> {
+        Self::ensure_not_archived(pool, tenant, id).await?;
         let schema = schema_name(tenant);
         // Detach all children currently in this group
         sqlx::query(&format!(
@@ -99,4 +290,335 @@ impl GroupService {
         }
         Ok(())
     }
+
+    /// Educators currently assigned to a group.
+    pub async fn list_educators(
+        pool: &PgPool,
+        tenant: &str,
+        group_id: Uuid,
+    ) -> anyhow::Result<Vec<GroupEducator>> {
+        let schema = schema_name(tenant);
+        let educators = sqlx::query_as::<_, GroupEducator>(&format!(
+            "SELECT u.id AS user_id, u.first_name, u.last_name, u.email
+             FROM {schema}.educator_groups eg
+             JOIN {schema}.users u ON u.id = eg.user_id
+             WHERE eg.group_id = $1
+             ORDER BY u.last_name, u.first_name"
+        ))
+        .bind(group_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(educators)
+    }
+
+    /// Replace the educators assigned to a group. Ids that are not active
+    /// educators are ignored.
+    pub async fn set_educators(
+        pool: &PgPool,
+        tenant: &str,
+        group_id: Uuid,
+        user_ids: &[Uuid],
+    ) -> anyhow::Result<Vec<GroupEducator>> {
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+        sqlx::query(&format!("DELETE FROM {schema}.educator_groups WHERE group_id = $1"))
+            .bind(group_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(&format!(
+            "INSERT INTO {schema}.educator_groups (user_id, group_id)
+             SELECT id, $1 FROM {schema}.users
+             WHERE id = ANY($2) AND role = 'educateur' AND is_active = TRUE"
+        ))
+        .bind(group_id)
+        .bind(user_ids)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Self::list_educators(pool, tenant, group_id).await
+    }
+
+    /// Groups an educator is restricted to. `None` means unrestricted: admins,
+    /// and educators with no assignment yet (so existing garderies keep working
+    /// until an admin sets assignments up).
+    pub async fn educator_scope(
+        pool: &PgPool,
+        tenant: &str,
+        user: &AuthenticatedUser,
+    ) -> anyhow::Result<Option<Vec<Uuid>>> {
+        if user.role != UserRole::Educateur {
+            return Ok(None);
+        }
+        let schema = schema_name(tenant);
+        let groups: Vec<Uuid> = sqlx::query_scalar(&format!(
+            "SELECT group_id FROM {schema}.educator_groups WHERE user_id = $1"
+        ))
+        .bind(user.user_id)
+        .fetch_all(pool)
+        .await?;
+        Ok((!groups.is_empty()).then_some(groups))
+    }
+
+    /// Fails with [`OutOfScope`] unless the user may act on this group.
+    pub async fn ensure_group_access(
+        pool: &PgPool,
+        tenant: &str,
+        user: &AuthenticatedUser,
+        group_id: Uuid,
+    ) -> anyhow::Result<()> {
+        match Self::educator_scope(pool, tenant, user).await? {
+            Some(groups) if !groups.contains(&group_id) => Err(OutOfScope.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fails with [`OutOfScope`] unless the user may act on this child.
+    /// A scoped educator cannot act on children outside any group.
+    pub async fn ensure_child_access(
+        pool: &PgPool,
+        tenant: &str,
+        user: &AuthenticatedUser,
+        child_id: Uuid,
+    ) -> anyhow::Result<()> {
+        let Some(groups) = Self::educator_scope(pool, tenant, user).await? else {
+            return Ok(());
+        };
+        let schema = schema_name(tenant);
+        let group_id: Option<Uuid> = sqlx::query_scalar(&format!(
+            "SELECT group_id FROM {schema}.children WHERE id = $1"
+        ))
+        .bind(child_id)
+        .fetch_optional(pool)
+        .await?
+        .flatten();
+        match group_id {
+            Some(g) if groups.contains(&g) => Ok(()),
+            _ => Err(OutOfScope.into()),
+        }
+    }
+
+    /// Move active children between groups in one transaction (see [`plan_rollover`]).
+    /// The group each child leaves is kept in `child_group_history`. Archived groups
+    /// count as unknown.
+    pub async fn rollover(
+        pool: &PgPool,
+        tenant: &str,
+        req: &RolloverRequest,
+        as_of: NaiveDate,
+        run_by: Uuid,
+    ) -> anyhow::Result<RolloverResult> {
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+
+        let mut group_ids: Vec<Uuid> = req
+            .mappings
+            .iter()
+            .flat_map(|m| [Some(m.source_group_id), m.target_group_id])
+            .flatten()
+            .chain(req.age_rules.iter().map(|r| r.group_id))
+            .collect();
+        group_ids.sort();
+        group_ids.dedup();
+        let known: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {schema}.groups WHERE id = ANY($1) AND archived_at IS NULL"))
+            .bind(&group_ids)
+            .fetch_one(&mut *tx)
+            .await?;
+        if known != group_ids.len() as i64 {
+            return Err(RolloverError::UnknownGroup.into());
+        }
+
+        let children: Vec<RolloverChild> = sqlx::query_as(&format!(
+            "SELECT id, group_id, birth_date FROM {schema}.children WHERE is_active = TRUE FOR UPDATE"
+        ))
+        .fetch_all(&mut *tx)
+        .await?;
+        let moves = plan_rollover(&children, req, as_of)?;
+        if req.dry_run || moves.is_empty() {
+            return Ok(RolloverResult { rollover_id: None, moves });
+        }
+
+        let rollover_id: Uuid = sqlx::query_scalar(&format!(
+            "INSERT INTO {schema}.group_rollovers (mode, as_of, moved_count, created_by)
+             VALUES ($1, $2, $3, $4)
+             RETURNING id"
+        ))
+        .bind(if req.mappings.is_empty() { "age" } else { "mapping" })
+        .bind(as_of)
+        .bind(moves.len() as i32)
+        .bind(run_by)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let child_ids: Vec<Uuid> = moves.iter().map(|m| m.child_id).collect();
+        let targets: Vec<Option<Uuid>> = moves.iter().map(|m| m.to_group_id).collect();
+        sqlx::query(&format!(
+            "INSERT INTO {schema}.child_group_history (child_id, group_id, group_name, rollover_id)
+             SELECT c.id, g.id, g.name, $2
+             FROM {schema}.children c
+             JOIN {schema}.groups g ON g.id = c.group_id
+             WHERE c.id = ANY($1)"
+        ))
+        .bind(&child_ids)
+        .bind(rollover_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(&format!(
+            "UPDATE {schema}.children c SET group_id = m.group_id
+             FROM UNNEST($1::uuid[], $2::uuid[]) AS m(child_id, group_id)
+             WHERE c.id = m.child_id"
+        ))
+        .bind(&child_ids)
+        .bind(&targets)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(RolloverResult { rollover_id: Some(rollover_id), moves })
+    }
+
+    /// Tell the parents of each moved child about the new group. Returns the number of
+    /// emails sent; failures are logged and skipped.
+    pub async fn notify_rollover(
+        pool: &PgPool,
+        email_svc: &EmailService,
+        tenant: &str,
+        moves: &[ChildMove],
+    ) -> anyhow::Result<usize> {
+        let schema = schema_name(tenant);
+        let child_ids: Vec<Uuid> = moves.iter().map(|m| m.child_id).collect();
+        let groups: HashMap<Uuid, String> = sqlx::query_as::<_, (Uuid, String)>(&format!("SELECT id, name FROM {schema}.groups"))
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .collect();
+        let parents: Vec<(Uuid, String, String, String)> = sqlx::query_as(&format!(
+            "SELECT c.id, c.first_name, u.email, u.first_name || ' ' || u.last_name
+             FROM {schema}.children c
+             JOIN {schema}.child_parents cp ON cp.child_id = c.id
+             JOIN {schema}.users u ON u.id = cp.user_id
+             WHERE c.id = ANY($1) AND u.is_active = TRUE"
+        ))
+        .bind(&child_ids)
+        .fetch_all(pool)
+        .await?;
+
+        let (garderie_name, branding) = BrandingService::for_email(pool, tenant).await;
+        let mut sent = 0;
+        for (child_id, child_name, email, parent_name) in parents {
+            let Some(m) = moves.iter().find(|m| m.child_id == child_id) else { continue };
+            let name = |id: Option<Uuid>| id.and_then(|g| groups.get(&g)).map(String::as_str);
+            match email_svc
+                .send_group_change(
+                    tenant,
+                    &email,
+                    &parent_name,
+                    &child_name,
+                    name(m.from_group_id),
+                    name(m.to_group_id),
+                    &garderie_name,
+                    &branding,
+                )
+                .await
+            {
+                Ok(()) => sent += 1,
+                Err(e) => tracing::warn!("Group change email to {email} failed: {e}"),
+            }
+        }
+        Ok(sent)
+    }
+
+    /// Groups a child has left, most recent first.
+    pub async fn child_history(pool: &PgPool, tenant: &str, child_id: Uuid) -> anyhow::Result<Vec<ChildGroupHistory>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, ChildGroupHistory>(&format!(
+            "SELECT group_id, group_name, left_at, rollover_id
+             FROM {schema}.child_group_history
+             WHERE child_id = $1
+             ORDER BY left_at DESC"
+        ))
+        .bind(child_id)
+        .fetch_all(pool)
+        .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::group::{AgeRule, GroupMapping};
+
+    fn request(mappings: Vec<GroupMapping>, age_rules: Vec<AgeRule>) -> RolloverRequest {
+        RolloverRequest { mappings, age_rules, as_of: None, dry_run: false, notify_parents: None }
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_plan_mappings_use_previous_groups() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let (x, y, z) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let children = [(x, Some(a), date("2022-01-01")), (y, Some(b), date("2021-01-01")), (z, None, date("2021-01-01"))];
+        let req = request(
+            vec![
+                GroupMapping { source_group_id: a, target_group_id: Some(b) },
+                GroupMapping { source_group_id: b, target_group_id: Some(c) },
+            ],
+            vec![],
+        );
+        let moves = plan_rollover(&children, &req, date("2026-09-01")).unwrap();
+        assert_eq!(
+            moves,
+            vec![
+                ChildMove { child_id: x, from_group_id: Some(a), to_group_id: Some(b) },
+                ChildMove { child_id: y, from_group_id: Some(b), to_group_id: Some(c) },
+            ]
+        );
+
+        let dup = request(
+            vec![
+                GroupMapping { source_group_id: a, target_group_id: Some(b) },
+                GroupMapping { source_group_id: a, target_group_id: None },
+            ],
+            vec![],
+        );
+        assert!(matches!(plan_rollover(&children, &dup, date("2026-09-01")), Err(RolloverError::DuplicateSource)));
+    }
+
+    #[test]
+    fn test_plan_age_rules() {
+        let (toddlers, preschool) = (Uuid::new_v4(), Uuid::new_v4());
+        let (x, y, z) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let children = [
+            (x, None, date("2025-03-01")),            // 18 months
+            (y, Some(toddlers), date("2023-06-15")),  // 38 months
+            (z, Some(preschool), date("2020-01-01")), // outside every band
+        ];
+        let rules = vec![
+            AgeRule { min_age_months: 0, max_age_months: Some(36), group_id: toddlers },
+            AgeRule { min_age_months: 36, max_age_months: Some(72), group_id: preschool },
+        ];
+        let moves = plan_rollover(&children, &request(vec![], rules), date("2026-09-01")).unwrap();
+        assert_eq!(
+            moves,
+            vec![
+                ChildMove { child_id: x, from_group_id: None, to_group_id: Some(toddlers) },
+                ChildMove { child_id: y, from_group_id: Some(toddlers), to_group_id: Some(preschool) },
+            ]
+        );
+
+        let overlapping = vec![
+            AgeRule { min_age_months: 0, max_age_months: Some(40), group_id: toddlers },
+            AgeRule { min_age_months: 36, max_age_months: None, group_id: preschool },
+        ];
+        assert!(matches!(
+            plan_rollover(&children, &request(vec![], overlapping), date("2026-09-01")),
+            Err(RolloverError::InvalidAgeRules)
+        ));
+        assert!(matches!(
+            plan_rollover(&children, &request(vec![], vec![]), date("2026-09-01")),
+            Err(RolloverError::InvalidMode)
+        ));
+    }
 }
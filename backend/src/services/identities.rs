@@ -0,0 +1,106 @@
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::tenant::schema_name;
+use crate::db::tenant_db::TenantDb;
+
+/// A garderie account of an identity, as listed by GET /auth/tenants.
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkedAccount {
+    pub slug: String,
+    pub name: String,
+    #[serde(skip)]
+    pub user_id: Uuid,
+    pub role: String,
+    pub current: bool,
+}
+
+/// Links the accounts a person holds in several garderies under one identity, so a
+/// parent can switch between them without logging in again. Accounts are only linked
+/// once their email address is proven (invitation link or emailed 2FA code).
+pub struct IdentityService;
+
+impl IdentityService {
+    /// Link a tenant account to the identity of `email`, creating the identity if needed.
+    pub async fn link(pool: &PgPool, tenant: &str, user_id: Uuid, email: &str) -> anyhow::Result<Uuid> {
+        let email = email.trim().to_lowercase();
+        let mut tx = pool.begin().await?;
+
+        let identity_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO public.identities (email) VALUES ($1)
+             ON CONFLICT (email) DO UPDATE SET email = EXCLUDED.email
+             RETURNING id",
+        )
+        .bind(&email)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // The account may still be linked under a former address
+        sqlx::query("DELETE FROM public.identity_links WHERE slug = $1 AND user_id = $2 AND identity_id <> $3")
+            .bind(tenant)
+            .bind(user_id)
+            .bind(identity_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            "INSERT INTO public.identity_links (identity_id, slug, user_id) VALUES ($1, $2, $3)
+             ON CONFLICT (identity_id, slug) DO UPDATE SET user_id = EXCLUDED.user_id, linked_at = NOW()",
+        )
+        .bind(identity_id)
+        .bind(tenant)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(identity_id)
+    }
+
+    /// Identity a tenant account is linked to, if any.
+    pub async fn identity_of(pool: &PgPool, tenant: &str, user_id: Uuid) -> anyhow::Result<Option<Uuid>> {
+        Ok(sqlx::query_scalar("SELECT identity_id FROM public.identity_links WHERE slug = $1 AND user_id = $2")
+            .bind(tenant)
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?)
+    }
+
+    /// Detach a tenant account from its identity, e.g. after its email changed.
+    pub async fn unlink(pool: &PgPool, tenant: &str, user_id: Uuid) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM public.identity_links WHERE slug = $1 AND user_id = $2")
+            .bind(tenant)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Active accounts of an identity in active garderies, by garderie name.
+    pub async fn accounts(pool: &PgPool, identity_id: Uuid, current_tenant: &str) -> anyhow::Result<Vec<LinkedAccount>> {
+        let links: Vec<(String, String, Uuid)> = sqlx::query_as(
+            "SELECT l.slug, g.name, l.user_id
+             FROM public.identity_links l
+             JOIN public.garderies g ON g.slug = l.slug
+             WHERE l.identity_id = $1 AND g.is_active = TRUE AND g.archived_at IS NULL
+             ORDER BY g.name",
+        )
+        .bind(identity_id)
+        .fetch_all(pool)
+        .await?;
+
+        let mut accounts = Vec::with_capacity(links.len());
+        for (slug, name, user_id) in links {
+            let schema = schema_name(&slug);
+            let query = format!(r#"SELECT role::TEXT FROM "{schema}".users WHERE id = $1 AND is_active = TRUE"#);
+            let role: Option<String> =
+                TenantDb::scope(slug.clone(), sqlx::query_scalar(&query).bind(user_id).fetch_optional(pool)).await?;
+            if let Some(role) = role {
+                let current = slug == current_tenant;
+                accounts.push(LinkedAccount { slug, name, user_id, role, current });
+            }
+        }
+        Ok(accounts)
+    }
+}
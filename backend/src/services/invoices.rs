@@ -0,0 +1,880 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::invoice::{
+        CreateInvoiceRequest, GenerateInvoicesResult, Invoice, InvoiceItem, InvoiceItemInput, InvoiceQuery,
+        MarkPaidRequest, UpdateInvoiceRequest, INVOICE_ITEM_KINDS,
+    },
+    services::{
+        branding::BrandingService,
+        email::EmailService,
+        email_i18n::Locale,
+        pdf::{text_width, Font, PdfDocument, PAGE_HEIGHT, PAGE_WIDTH},
+    },
+};
+
+/// Days between issue and due date when none is given.
+pub const DEFAULT_DUE_DAYS: i64 = 30;
+
+/// Why an invoice operation was refused.
+#[derive(Debug, thiserror::Error)]
+pub enum InvoiceError {
+    #[error("Facture introuvable")]
+    NotFound,
+    #[error("Enfant introuvable")]
+    ChildNotFound,
+    #[error("Seules les factures en brouillon peuvent être modifiées")]
+    NotDraft,
+    #[error("Cette facture est déjà payée")]
+    AlreadyPaid,
+    #[error("Cette facture est annulée")]
+    Voided,
+    #[error("Une facture doit contenir au moins une ligne")]
+    NoItems,
+    #[error("Ligne de facture invalide : {0}")]
+    InvalidItem(String),
+    #[error("Mois invalide, format attendu AAAA-MM")]
+    InvalidMonth,
+    #[error("Aucun parent avec une adresse courriel pour cet enfant")]
+    NoRecipients,
+    #[error("Le payeur doit être un parent de l'enfant")]
+    InvalidPayer,
+}
+
+/// "1 234,56 $", as amounts are written in Québec.
+pub fn format_cents(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    let cents = cents.unsigned_abs();
+    let digits = (cents / 100).to_string();
+    let mut dollars = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            dollars.push(' ');
+        }
+        dollars.push(c);
+    }
+    format!("{sign}{dollars},{:02} $", cents % 100)
+}
+
+/// First day of a "YYYY-MM" month.
+pub fn parse_month(month: &str) -> Result<NaiveDate, InvoiceError> {
+    NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d").map_err(|_| InvoiceError::InvalidMonth)
+}
+
+fn month_end(start: NaiveDate) -> NaiveDate {
+    let next = if start.month() == 12 {
+        NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+    };
+    next.expect("valid month") - Duration::days(1)
+}
+
+fn validate_items(items: &[InvoiceItemInput]) -> Result<(), InvoiceError> {
+    if items.is_empty() {
+        return Err(InvoiceError::NoItems);
+    }
+    for item in items {
+        if !INVOICE_ITEM_KINDS.contains(&item.kind.as_str()) {
+            return Err(InvoiceError::InvalidItem(format!("type « {} » inconnu", item.kind)));
+        }
+        let description = item.description.trim();
+        if description.is_empty() || description.chars().count() > 255 {
+            return Err(InvoiceError::InvalidItem("description vide ou trop longue".into()));
+        }
+        if item.quantity.is_some_and(|q| q < 1) {
+            return Err(InvoiceError::InvalidItem("la quantité doit être d'au moins 1".into()));
+        }
+    }
+    Ok(())
+}
+
+fn invoice_select(schema: &str) -> String {
+    format!(
+        r#"SELECT i.id, i.number, i.child_id, c.first_name || ' ' || c.last_name AS child_name,
+                  i.family_id, f.name AS family_name, i.period, i.issue_date, i.due_date, i.status,
+                  (i.status = 'sent' AND i.due_date < CURRENT_DATE) AS overdue,
+                  i.total_cents, i.notes, i.sent_at, i.paid_at, i.payment_method, i.payment_reference,
+                  i.payer_id,
+                  i.created_at, i.updated_at
+           FROM "{schema}".invoices i
+           JOIN "{schema}".children c ON c.id = i.child_id
+           LEFT JOIN "{schema}".families f ON f.id = i.family_id"#
+    )
+}
+
+/// SQL condition: `user` (an SQL expression) is a parent of a child billed by invoice `i`,
+/// the invoice's child or, on family invoices, the child of one of its lines.
+fn billed_to(schema: &str, user: &str) -> String {
+    format!(
+        r#"EXISTS (
+               SELECT 1 FROM "{schema}".child_parents cp
+               WHERE cp.user_id = {user}
+                 AND (cp.child_id = i.child_id OR cp.child_id IN (
+                        SELECT it.child_id FROM "{schema}".invoice_items it WHERE it.invoice_id = i.id)))"#
+    )
+}
+
+/// Children billed together, in order: each child alone, except those of a family
+/// billed as one, who share the entry of their first member.
+fn billing_units(children: &[(Uuid, Option<Uuid>)]) -> Vec<(Option<Uuid>, Vec<Uuid>)> {
+    let mut units: Vec<(Option<Uuid>, Vec<Uuid>)> = Vec::new();
+    let mut family_unit: HashMap<Uuid, usize> = HashMap::new();
+    for &(child_id, family_id) in children {
+        match family_id {
+            Some(family_id) => {
+                let unit = *family_unit.entry(family_id).or_insert_with(|| {
+                    units.push((Some(family_id), Vec::new()));
+                    units.len() - 1
+                });
+                units[unit].1.push(child_id);
+            }
+            None => units.push((None, vec![child_id])),
+        }
+    }
+    units
+}
+
+/// What a new invoice is made of, whether typed in by an admin or built by the batch.
+struct NewInvoice<'a> {
+    child_id: Uuid,
+    family_id: Option<Uuid>,
+    period: Option<NaiveDate>,
+    due_date: NaiveDate,
+    notes: Option<&'a str>,
+    items: &'a [InvoiceItemInput],
+}
+
+async fn insert_invoice(
+    tx: &mut Transaction<'_, Postgres>,
+    schema: &str,
+    invoice: NewInvoice<'_>,
+    created_by: Uuid,
+) -> anyhow::Result<Uuid> {
+    let issue_date = Local::now().date_naive();
+    let seq: i64 = sqlx::query_scalar(&format!(r#"SELECT nextval('"{schema}".invoice_number_seq')"#))
+        .fetch_one(&mut **tx)
+        .await?;
+    let number = format!("F{}-{seq:05}", issue_date.year());
+
+    let id: Uuid = sqlx::query_scalar(&format!(
+        r#"INSERT INTO "{schema}".invoices (number, child_id, family_id, period, issue_date, due_date, notes, created_by)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+           RETURNING id"#
+    ))
+    .bind(&number)
+    .bind(invoice.child_id)
+    .bind(invoice.family_id)
+    .bind(invoice.period)
+    .bind(issue_date)
+    .bind(invoice.due_date)
+    .bind(invoice.notes)
+    .bind(created_by)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    replace_items(tx, schema, id, invoice.items).await?;
+    Ok(id)
+}
+
+/// Replace the lines of an invoice and recompute its total.
+async fn replace_items(
+    tx: &mut Transaction<'_, Postgres>,
+    schema: &str,
+    invoice_id: Uuid,
+    items: &[InvoiceItemInput],
+) -> anyhow::Result<()> {
+    sqlx::query(&format!(r#"DELETE FROM "{schema}".invoice_items WHERE invoice_id = $1"#))
+        .bind(invoice_id)
+        .execute(&mut **tx)
+        .await?;
+    for (position, item) in items.iter().enumerate() {
+        sqlx::query(&format!(
+            r#"INSERT INTO "{schema}".invoice_items (invoice_id, kind, description, quantity, unit_cents, position, child_id)
+               VALUES ($1, $2, $3, $4, $5, $6, $7)"#
+        ))
+        .bind(invoice_id)
+        .bind(&item.kind)
+        .bind(item.description.trim())
+        .bind(item.quantity.unwrap_or(1))
+        .bind(item.unit_cents)
+        .bind(position as i32)
+        .bind(item.child_id)
+        .execute(&mut **tx)
+        .await?;
+    }
+    sqlx::query(&format!(
+        r#"UPDATE "{schema}".invoices
+           SET total_cents = (SELECT COALESCE(SUM(quantity * unit_cents), 0)
+                              FROM "{schema}".invoice_items WHERE invoice_id = $1)
+           WHERE id = $1"#
+    ))
+    .bind(invoice_id)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Status of an invoice, locked for the rest of the transaction.
+async fn lock_status(tx: &mut Transaction<'_, Postgres>, schema: &str, id: Uuid) -> anyhow::Result<String> {
+    let status: Option<String> = sqlx::query_scalar(&format!(
+        r#"SELECT status FROM "{schema}".invoices WHERE id = $1 FOR UPDATE"#
+    ))
+    .bind(id)
+    .fetch_optional(&mut **tx)
+    .await?;
+    Ok(status.ok_or(InvoiceError::NotFound)?)
+}
+
+/// The garderie as printed at the top of its invoices.
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+pub struct Issuer {
+    pub name: String,
+    pub address_line1: Option<String>,
+    pub city: Option<String>,
+    pub province: Option<String>,
+    pub postal_code: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+}
+
+impl Issuer {
+    pub async fn load(pool: &PgPool, tenant: &str) -> anyhow::Result<Self> {
+        Ok(sqlx::query_as::<_, Issuer>(
+            "SELECT name, address_line1, city, province, postal_code, phone, email
+             FROM public.garderies WHERE slug = $1",
+        )
+        .bind(tenant)
+        .fetch_optional(pool)
+        .await?
+        .unwrap_or_else(|| Issuer { name: tenant.to_string(), ..Default::default() }))
+    }
+
+    pub fn address_lines(&self) -> Vec<String> {
+        let locality = [self.city.as_deref(), self.province.as_deref(), self.postal_code.as_deref()]
+            .into_iter()
+            .flatten()
+            .filter(|s| !s.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        [self.address_line1.clone(), Some(locality), self.phone.clone(), self.email.clone()]
+            .into_iter()
+            .flatten()
+            .filter(|s| !s.trim().is_empty())
+            .collect()
+    }
+}
+
+pub struct InvoiceService;
+
+impl InvoiceService {
+    /// Invoices matching the filters, newest first, without their lines.
+    /// With `parent_id`, only that parent's children's invoices, drafts excluded.
+    pub async fn list(
+        pool: &PgPool,
+        tenant: &str,
+        query: &InvoiceQuery,
+        parent_id: Option<Uuid>,
+    ) -> anyhow::Result<Vec<Invoice>> {
+        let schema = schema_name(tenant);
+        let month = query.month.as_deref().map(parse_month).transpose()?;
+        let select = invoice_select(&schema);
+        let billed_to_parent = billed_to(&schema, "$5");
+        Ok(sqlx::query_as::<_, Invoice>(&format!(
+            r#"{select}
+               WHERE ($1::UUID IS NULL OR i.child_id = $1)
+                 AND ($2::TEXT IS NULL OR i.status = $2)
+                 AND ($3::DATE IS NULL OR i.issue_date BETWEEN $3 AND $4)
+                 AND ($5::UUID IS NULL OR (i.status <> 'draft' AND {billed_to_parent}))
+               ORDER BY i.issue_date DESC, i.number DESC"#
+        ))
+        .bind(query.child_id)
+        .bind(&query.status)
+        .bind(month)
+        .bind(month.map(month_end))
+        .bind(parent_id)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// One invoice with its lines.
+    pub async fn get(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<Invoice> {
+        let schema = schema_name(tenant);
+        let select = invoice_select(&schema);
+        let mut invoice = sqlx::query_as::<_, Invoice>(&format!("{select} WHERE i.id = $1"))
+            .bind(id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(InvoiceError::NotFound)?;
+        invoice.items = sqlx::query_as::<_, InvoiceItem>(&format!(
+            r#"SELECT id, kind, description, quantity, unit_cents, quantity * unit_cents AS amount_cents, child_id
+               FROM "{schema}".invoice_items
+               WHERE invoice_id = $1
+               ORDER BY position"#
+        ))
+        .bind(id)
+        .fetch_all(pool)
+        .await?;
+        Ok(invoice)
+    }
+
+    /// Create a one-off draft invoice.
+    pub async fn create(
+        pool: &PgPool,
+        tenant: &str,
+        req: &CreateInvoiceRequest,
+        created_by: Uuid,
+    ) -> anyhow::Result<Invoice> {
+        validate_items(&req.items)?;
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+
+        let exists: bool = sqlx::query_scalar(&format!(
+            r#"SELECT EXISTS(SELECT 1 FROM "{schema}".children WHERE id = $1)"#
+        ))
+        .bind(req.child_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if !exists {
+            return Err(InvoiceError::ChildNotFound.into());
+        }
+
+        let due_date = req
+            .due_date
+            .unwrap_or_else(|| Local::now().date_naive() + Duration::days(DEFAULT_DUE_DAYS));
+        let new = NewInvoice {
+            child_id: req.child_id,
+            family_id: None,
+            period: None,
+            due_date,
+            notes: req.notes.as_deref(),
+            items: &req.items,
+        };
+        let id = insert_invoice(&mut tx, &schema, new, created_by).await?;
+        tx.commit().await?;
+        Self::get(pool, tenant, id).await
+    }
+
+    /// Edit a draft; sent invoices are voided and reissued instead.
+    pub async fn update(pool: &PgPool, tenant: &str, id: Uuid, req: &UpdateInvoiceRequest) -> anyhow::Result<Invoice> {
+        if let Some(items) = &req.items {
+            validate_items(items)?;
+        }
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+        if lock_status(&mut tx, &schema, id).await? != "draft" {
+            return Err(InvoiceError::NotDraft.into());
+        }
+
+        sqlx::query(&format!(
+            r#"UPDATE "{schema}".invoices
+               SET due_date = COALESCE($2, due_date), notes = COALESCE($3, notes)
+               WHERE id = $1"#
+        ))
+        .bind(id)
+        .bind(req.due_date)
+        .bind(&req.notes)
+        .execute(&mut *tx)
+        .await?;
+        if let Some(items) = &req.items {
+            replace_items(&mut tx, &schema, id, items).await?;
+        }
+
+        tx.commit().await?;
+        Self::get(pool, tenant, id).await
+    }
+
+    /// Record that the invoice was emailed; a draft becomes sent.
+    pub async fn mark_sent(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<()> {
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+        if lock_status(&mut tx, &schema, id).await? == "void" {
+            return Err(InvoiceError::Voided.into());
+        }
+        sqlx::query(&format!(
+            r#"UPDATE "{schema}".invoices
+               SET status = CASE WHEN status = 'draft' THEN 'sent' ELSE status END, sent_at = NOW()
+               WHERE id = $1"#
+        ))
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Record a payment received outside the app (cheque, transfer, cash).
+    pub async fn mark_paid(pool: &PgPool, tenant: &str, id: Uuid, req: &MarkPaidRequest) -> anyhow::Result<Invoice> {
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+        match lock_status(&mut tx, &schema, id).await?.as_str() {
+            "paid" => return Err(InvoiceError::AlreadyPaid.into()),
+            "void" => return Err(InvoiceError::Voided.into()),
+            _ => {}
+        }
+        if let Some(payer_id) = req.payer_id {
+            let billed_to_payer = billed_to(&schema, "$2");
+            let is_parent: bool = sqlx::query_scalar(&format!(
+                r#"SELECT {billed_to_payer} FROM "{schema}".invoices i WHERE i.id = $1"#
+            ))
+            .bind(id)
+            .bind(payer_id)
+            .fetch_one(&mut *tx)
+            .await?;
+            if !is_parent {
+                return Err(InvoiceError::InvalidPayer.into());
+            }
+        }
+        sqlx::query(&format!(
+            r#"UPDATE "{schema}".invoices
+               SET status = 'paid', paid_at = COALESCE($2, NOW()), payment_method = $3, payment_reference = $4,
+                   payer_id = $5
+               WHERE id = $1"#
+        ))
+        .bind(id)
+        .bind(req.paid_at)
+        .bind(&req.method)
+        .bind(&req.reference)
+        .bind(req.payer_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Self::get(pool, tenant, id).await
+    }
+
+    /// Cancel an unpaid invoice. It keeps its number; its month can be billed again.
+    pub async fn void(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<Invoice> {
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+        match lock_status(&mut tx, &schema, id).await?.as_str() {
+            "paid" => return Err(InvoiceError::AlreadyPaid.into()),
+            "void" => return Err(InvoiceError::Voided.into()),
+            _ => {}
+        }
+        sqlx::query(&format!(r#"UPDATE "{schema}".invoices SET status = 'void' WHERE id = $1"#))
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Self::get(pool, tenant, id).await
+    }
+
+    /// Draft the month's invoice of every active child not yet billed for it: the
+    /// child's monthly fee (or `default_tuition_cents`) plus the fees of the paid
+    /// activities it is registered to that month. The children of a family with
+    /// combined billing share one invoice, each line naming its child.
+    pub async fn generate_month(
+        pool: &PgPool,
+        tenant: &str,
+        month: NaiveDate,
+        default_tuition_cents: Option<i64>,
+        due_days: i64,
+        created_by: Uuid,
+    ) -> anyhow::Result<GenerateInvoicesResult> {
+        let schema = schema_name(tenant);
+        let last_day = month_end(month);
+        let period_label = Locale::Fr.month_year(month);
+
+        let children: Vec<(Uuid, String, Option<i64>, Option<Uuid>)> = sqlx::query_as(&format!(
+            r#"SELECT c.id, c.first_name, c.monthly_fee_cents, f.id
+               FROM "{schema}".children c
+               LEFT JOIN "{schema}".families f ON f.id = c.family_id AND f.combined_billing
+               WHERE c.is_active = TRUE AND (c.start_date IS NULL OR c.start_date <= $1)
+               ORDER BY c.last_name, c.first_name"#
+        ))
+        .bind(last_day)
+        .fetch_all(pool)
+        .await?;
+
+        let invoiced: HashSet<Uuid> = sqlx::query_scalar::<_, Uuid>(&format!(
+            r#"SELECT child_id FROM "{schema}".invoices WHERE period = $1 AND status <> 'void'
+               UNION
+               SELECT it.child_id FROM "{schema}".invoice_items it
+               JOIN "{schema}".invoices i ON i.id = it.invoice_id
+               WHERE i.period = $1 AND i.status <> 'void' AND it.child_id IS NOT NULL"#
+        ))
+        .bind(month)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .collect();
+
+        let activity_rows: Vec<(Uuid, String, NaiveDate, i64)> = sqlx::query_as(&format!(
+            r#"SELECT ar.child_id, a.title, a.date, a.fee_cents
+               FROM "{schema}".activity_registrations ar
+               JOIN "{schema}".activities a ON a.id = ar.activity_id
+               WHERE a.fee_cents > 0 AND a.date BETWEEN $1 AND $2
+               ORDER BY a.date"#
+        ))
+        .bind(month)
+        .bind(last_day)
+        .fetch_all(pool)
+        .await?;
+        let mut activities: HashMap<Uuid, Vec<InvoiceItemInput>> = HashMap::new();
+        for (child_id, title, date, fee_cents) in activity_rows {
+            activities.entry(child_id).or_default().push(InvoiceItemInput {
+                kind: "activity".into(),
+                description: format!("Activité : {title} ({})", Locale::Fr.short_date(date)),
+                quantity: Some(1),
+                unit_cents: fee_cents,
+                child_id: Some(child_id),
+            });
+        }
+
+        let due_date = Local::now().date_naive() + Duration::days(due_days);
+        let mut created_ids = Vec::new();
+        let (mut already_invoiced, mut nothing_to_bill) = (0, 0);
+        let mut tx = pool.begin().await?;
+        let details: HashMap<Uuid, (&str, Option<i64>)> =
+            children.iter().map(|(id, first_name, fee, _)| (*id, (first_name.as_str(), *fee))).collect();
+        let units: Vec<(Uuid, Option<Uuid>)> = children.iter().map(|(id, _, _, family)| (*id, *family)).collect();
+        for (family_id, members) in billing_units(&units) {
+            let mut items = Vec::new();
+            let mut billed = Vec::new();
+            for child_id in members {
+                if invoiced.contains(&child_id) {
+                    already_invoiced += 1;
+                    continue;
+                }
+                let (first_name, fee) = details[&child_id];
+                let mut child_items = Vec::new();
+                if let Some(tuition) = fee.or(default_tuition_cents).filter(|c| *c > 0) {
+                    child_items.push(InvoiceItemInput {
+                        kind: "tuition".into(),
+                        description: format!("Frais de garde — {period_label}"),
+                        quantity: Some(1),
+                        unit_cents: tuition,
+                        child_id: Some(child_id),
+                    });
+                }
+                child_items.extend(activities.remove(&child_id).unwrap_or_default());
+                if child_items.is_empty() {
+                    nothing_to_bill += 1;
+                    continue;
+                }
+                if family_id.is_some() {
+                    for item in &mut child_items {
+                        item.description = format!("{first_name} — {}", item.description).chars().take(255).collect();
+                    }
+                }
+                billed.push(child_id);
+                items.extend(child_items);
+            }
+            let Some(&child_id) = billed.first() else { continue };
+            let new = NewInvoice { child_id, family_id, period: Some(month), due_date, notes: None, items: &items };
+            created_ids.push(insert_invoice(&mut tx, &schema, new, created_by).await?);
+        }
+        tx.commit().await?;
+
+        let mut created = Vec::with_capacity(created_ids.len());
+        for id in created_ids {
+            created.push(Self::get(pool, tenant, id).await?);
+        }
+        Ok(GenerateInvoicesResult { created, already_invoiced, nothing_to_bill })
+    }
+
+    /// Whether the user is a parent of a child billed by the invoice.
+    pub async fn is_billed_to(pool: &PgPool, tenant: &str, id: Uuid, user_id: Uuid) -> anyhow::Result<bool> {
+        let schema = schema_name(tenant);
+        let billed_to_user = billed_to(&schema, "$2");
+        let billed: Option<bool> = sqlx::query_scalar(&format!(
+            r#"SELECT {billed_to_user} FROM "{schema}".invoices i WHERE i.id = $1"#
+        ))
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(billed.unwrap_or(false))
+    }
+
+    /// Active parents of the children billed by the invoice with their email, as (name, email).
+    pub async fn recipients(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<Vec<(String, String)>> {
+        let schema = schema_name(tenant);
+        let billed_to_user = billed_to(&schema, "u.id");
+        Ok(sqlx::query_as(&format!(
+            r#"SELECT u.first_name || ' ' || u.last_name, u.email
+               FROM "{schema}".users u
+               JOIN "{schema}".invoices i ON i.id = $1
+               WHERE u.is_active = TRUE AND {billed_to_user}
+               ORDER BY u.last_name, u.first_name"#
+        ))
+        .bind(id)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Email the invoice to every parent of the child, then mark it sent.
+    /// Returns how many parents received it.
+    pub async fn send(pool: &PgPool, email_svc: &EmailService, tenant: &str, id: Uuid) -> anyhow::Result<usize> {
+        let invoice = Self::get(pool, tenant, id).await?;
+        if invoice.status == "void" {
+            return Err(InvoiceError::Voided.into());
+        }
+        let recipients = Self::recipients(pool, tenant, invoice.id).await?;
+        if recipients.is_empty() {
+            return Err(InvoiceError::NoRecipients.into());
+        }
+
+        let (garderie_name, branding) = BrandingService::for_email(pool, tenant).await;
+        let pdf = Self::render_pdf(pool, tenant, &invoice).await?;
+        let mut sent = 0;
+        for (name, email) in &recipients {
+            match email_svc
+                .send_invoice(tenant, email, name, &invoice, pdf.clone(), &garderie_name, &branding)
+                .await
+            {
+                Ok(()) => sent += 1,
+                Err(e) => tracing::warn!("Failed to send invoice {} to {email}: {e}", invoice.number),
+            }
+        }
+        if sent == 0 {
+            anyhow::bail!("Échec de l'envoi de la facture {}", invoice.number);
+        }
+        Self::mark_sent(pool, tenant, id).await?;
+        Ok(sent)
+    }
+
+    /// The invoice as a PDF, with the garderie's details and the billed children's parents.
+    pub async fn render_pdf(pool: &PgPool, tenant: &str, invoice: &Invoice) -> anyhow::Result<Vec<u8>> {
+        let issuer = Issuer::load(pool, tenant).await?;
+        let bill_to: Vec<String> = Self::recipients(pool, tenant, invoice.id)
+            .await?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        Ok(invoice_pdf(invoice, &issuer, &bill_to))
+    }
+}
+
+pub(crate) const MARGIN: f32 = 56.0;
+const QTY_RIGHT: f32 = 390.0;
+const UNIT_RIGHT: f32 = 475.0;
+
+/// `text` cut with an ellipsis so it fits in `width` points.
+pub(crate) fn fit(text: &str, size: f32, width: f32) -> String {
+    if text_width(text, size) <= width {
+        return text.to_string();
+    }
+    let mut fitted: String = text.to_string();
+    while !fitted.is_empty() && text_width(&format!("{fitted}…"), size) > width {
+        fitted.pop();
+    }
+    format!("{}…", fitted.trim_end())
+}
+
+/// Lines of at most `width` points, breaking between words.
+pub(crate) fn wrap(text: &str, size: f32, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() { word.to_string() } else { format!("{line} {word}") };
+            if text_width(&candidate, size) > width && !line.is_empty() {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+            } else {
+                line = candidate;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+fn table_header(doc: &mut PdfDocument, y: f32) {
+    doc.text(MARGIN, y, 9.5, Font::Bold, "Description");
+    doc.text_right(QTY_RIGHT, y, 9.5, Font::Bold, "Qté");
+    doc.text_right(UNIT_RIGHT, y, 9.5, Font::Bold, "Prix unitaire");
+    doc.text_right(PAGE_WIDTH - MARGIN, y, 9.5, Font::Bold, "Montant");
+    doc.line(MARGIN, y - 6.0, PAGE_WIDTH - MARGIN, y - 6.0);
+}
+
+fn invoice_pdf(invoice: &Invoice, issuer: &Issuer, bill_to: &[String]) -> Vec<u8> {
+    let right = PAGE_WIDTH - MARGIN;
+    let fr = Locale::Fr;
+    let mut doc = PdfDocument::new();
+
+    // Garderie on the left, invoice reference on the right
+    let top = PAGE_HEIGHT - MARGIN - 8.0;
+    doc.text(MARGIN, top, 16.0, Font::Bold, &fit(&issuer.name, 16.0, 300.0));
+    let mut left_y = top - 18.0;
+    for line in issuer.address_lines() {
+        doc.text(MARGIN, left_y, 9.5, Font::Regular, &fit(&line, 9.5, 300.0));
+        left_y -= 13.0;
+    }
+    doc.text_right(right, top, 20.0, Font::Bold, "FACTURE");
+    let mut right_y = top - 22.0;
+    for line in [
+        format!("No {}", invoice.number),
+        format!("Date : {}", fr.short_date(invoice.issue_date)),
+        format!("Échéance : {}", fr.short_date(invoice.due_date)),
+    ] {
+        doc.text_right(right, right_y, 10.0, Font::Regular, &line);
+        right_y -= 14.0;
+    }
+
+    let mut y = left_y.min(right_y) - 20.0;
+    doc.text(MARGIN, y, 10.0, Font::Bold, "Facturé à");
+    y -= 14.0;
+    for name in bill_to {
+        doc.text(MARGIN, y, 10.0, Font::Regular, name);
+        y -= 13.0;
+    }
+    let billed = match &invoice.family_name {
+        Some(family) => format!("Famille : {family}"),
+        None => format!("Enfant : {}", invoice.child_name),
+    };
+    doc.text(MARGIN, y, 10.0, Font::Regular, &billed);
+    y -= 13.0;
+    if let Some(period) = invoice.period {
+        doc.text(MARGIN, y, 10.0, Font::Regular, &format!("Période : {}", fr.month_year(period)));
+        y -= 13.0;
+    }
+
+    y -= 20.0;
+    table_header(&mut doc, y);
+    y -= 22.0;
+    for item in &invoice.items {
+        if y < MARGIN + 60.0 {
+            doc.new_page();
+            y = PAGE_HEIGHT - MARGIN;
+            table_header(&mut doc, y);
+            y -= 22.0;
+        }
+        let description = fit(&item.description, 10.0, QTY_RIGHT - MARGIN - 40.0);
+        doc.text(MARGIN, y, 10.0, Font::Regular, &description);
+        doc.text_right(QTY_RIGHT, y, 10.0, Font::Regular, &item.quantity.to_string());
+        doc.text_right(UNIT_RIGHT, y, 10.0, Font::Regular, &format_cents(item.unit_cents));
+        doc.text_right(right, y, 10.0, Font::Regular, &format_cents(item.amount_cents));
+        y -= 18.0;
+    }
+
+    doc.line(UNIT_RIGHT - 80.0, y + 8.0, right, y + 8.0);
+    y -= 8.0;
+    doc.text_right(UNIT_RIGHT, y, 11.0, Font::Bold, "Total");
+    doc.text_right(right, y, 11.0, Font::Bold, &format_cents(invoice.total_cents));
+
+    let status = match invoice.status.as_str() {
+        "paid" => invoice.paid_at.map(|paid| {
+            let date = fr.short_date(paid.with_timezone(&Local).date_naive());
+            match invoice.payment_method.as_deref() {
+                Some(method) => format!("PAYÉE le {date} ({method})"),
+                None => format!("PAYÉE le {date}"),
+            }
+        }),
+        "void" => Some("ANNULÉE".to_string()),
+        _ => None,
+    };
+    if let Some(status) = status {
+        y -= 28.0;
+        doc.text(MARGIN, y, 12.0, Font::Bold, &status);
+    }
+
+    if let Some(notes) = invoice.notes.as_deref().filter(|n| !n.trim().is_empty()) {
+        y -= 28.0;
+        doc.text(MARGIN, y, 10.0, Font::Bold, "Notes");
+        for line in wrap(notes, 9.5, right - MARGIN) {
+            y -= 13.0;
+            if y < MARGIN {
+                doc.new_page();
+                y = PAGE_HEIGHT - MARGIN;
+            }
+            doc.text(MARGIN, y, 9.5, Font::Regular, &line);
+        }
+    }
+
+    doc.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn item(kind: &str, description: &str, quantity: Option<i32>) -> InvoiceItemInput {
+        InvoiceItemInput { kind: kind.into(), description: description.into(), quantity, unit_cents: 2500, child_id: None }
+    }
+
+    #[test]
+    fn amounts_are_written_the_quebec_way() {
+        assert_eq!(format_cents(0), "0,00 $");
+        assert_eq!(format_cents(5), "0,05 $");
+        assert_eq!(format_cents(123_456), "1 234,56 $");
+        assert_eq!(format_cents(100_000_000), "1 000 000,00 $");
+        assert_eq!(format_cents(-2_500), "-25,00 $");
+    }
+
+    #[test]
+    fn months_parse_to_their_first_and_last_day() {
+        let start = parse_month("2026-12").unwrap();
+        assert_eq!(start, NaiveDate::from_ymd_opt(2026, 12, 1).unwrap());
+        assert_eq!(month_end(start), NaiveDate::from_ymd_opt(2026, 12, 31).unwrap());
+        assert_eq!(month_end(parse_month("2028-02").unwrap()), NaiveDate::from_ymd_opt(2028, 2, 29).unwrap());
+        assert!(matches!(parse_month("2026-13"), Err(InvoiceError::InvalidMonth)));
+        assert!(matches!(parse_month("octobre"), Err(InvoiceError::InvalidMonth)));
+    }
+
+    #[test]
+    fn items_need_a_known_kind_a_description_and_a_quantity() {
+        assert!(validate_items(&[item("tuition", "Frais de garde", None), item("late_fee", "Retard", Some(2))]).is_ok());
+        assert!(matches!(validate_items(&[]), Err(InvoiceError::NoItems)));
+        assert!(matches!(validate_items(&[item("repas", "Repas", None)]), Err(InvoiceError::InvalidItem(_))));
+        assert!(matches!(validate_items(&[item("other", "  ", None)]), Err(InvoiceError::InvalidItem(_))));
+        assert!(matches!(validate_items(&[item("other", "Divers", Some(0))]), Err(InvoiceError::InvalidItem(_))));
+    }
+
+    #[test]
+    fn family_children_share_one_billing_unit() {
+        let [a, b, c, d] = [(); 4].map(|_| Uuid::new_v4());
+        let family = Uuid::new_v4();
+        let units = billing_units(&[(a, None), (b, Some(family)), (c, None), (d, Some(family))]);
+        assert_eq!(units, vec![(None, vec![a]), (Some(family), vec![b, d]), (None, vec![c])]);
+    }
+
+    #[test]
+    fn pdf_lists_every_line_and_the_total() {
+        let invoice = Invoice {
+            id: Uuid::new_v4(),
+            number: "F2026-00042".into(),
+            child_id: Uuid::new_v4(),
+            child_name: "Léa Tremblay".into(),
+            family_id: None,
+            family_name: None,
+            period: NaiveDate::from_ymd_opt(2026, 10, 1),
+            issue_date: NaiveDate::from_ymd_opt(2026, 10, 1).unwrap(),
+            due_date: NaiveDate::from_ymd_opt(2026, 10, 31).unwrap(),
+            status: "sent".into(),
+            overdue: false,
+            total_cents: 95_000,
+            notes: Some("Merci !".into()),
+            sent_at: Some(Utc::now()),
+            paid_at: None,
+            payment_method: None,
+            payment_reference: None,
+            payer_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            items: (0..60)
+                .map(|i| InvoiceItem {
+                    id: Uuid::new_v4(),
+                    kind: "activity".into(),
+                    description: format!("Sortie {i}"),
+                    quantity: 1,
+                    unit_cents: 1_000,
+                    amount_cents: 1_000,
+                    child_id: None,
+                })
+                .collect(),
+        };
+        let pdf = invoice_pdf(&invoice, &Issuer { name: "CPE Les Lucioles".into(), ..Default::default() }, &[]);
+        let text = String::from_utf8(pdf).unwrap();
+        assert!(text.contains("(F2026-00042)") || text.contains("(No F2026-00042)"));
+        assert!(text.contains("(Sortie 59)"));
+        assert!(text.contains("(950,00 $)"));
+        assert!(!text.contains("/Count 1 >>"), "60 lines do not fit on one page");
+    }
+}
@@ -1,21 +1,103 @@
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
 use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::{
+    config::Config,
     db::tenant::schema_name,
-    models::journal::{
-        DailyJournal, UpsertJournalRequest, APPETIT_LEVELS, HUMEUR_LEVELS, WEATHER_CONDITIONS,
+    middleware::precondition::StaleVersion,
+    models::{
+        journal::{
+            AmendJournalRequest, CreateJournalEventRequest, DailyJournal, JournalAmendment, JournalEvent,
+            JournalEventFields, JournalTimeline, UpsertJournalRequest, APPETIT_LEVELS, HUMEUR_LEVELS, JOURNAL_EVENT_TYPES, WEATHER_CONDITIONS,
+        },
+        menu::MenuItem,
+    },
+    services::{
+        children::ChildService,
+        email::escape_html,
+        email_i18n::{lookup, tr, Locale},
+        journal_photos::{self, photos_for, JournalPhoto},
+        menu::MenuService,
     },
-    services::children::ChildService,
 };
 
+/// Why weekly journals could not be sent.
+#[derive(Debug, thiserror::Error)]
+pub enum JournalSendError {
+    #[error("Aucun enfant actif trouvé")]
+    NoActiveChildren,
+    #[error("Enfant non trouvé")]
+    ChildNotFound,
+    #[error("Aucun parent assigné à cet enfant")]
+    NoParents,
+    #[error("Aucun journal disponible pour cette semaine")]
+    NoEntries,
+}
+
+/// Why a journal entry could not be written or amended.
+#[derive(Debug, thiserror::Error)]
+pub enum JournalAmendError {
+    #[error("Ce journal a déjà été envoyé aux parents : utilisez la correction pour le modifier")]
+    AlreadySent,
+    #[error("Ce journal n'a pas encore été envoyé : modifiez-le directement")]
+    NotSent,
+    #[error("Journal introuvable")]
+    NotFound,
+    #[error("Une note expliquant la correction est requise")]
+    NoteRequired,
+}
+
+/// Longest amendment note, shown as is in the parents' email.
+const MAX_AMENDMENT_NOTE_CHARS: usize = 1000;
+
+/// Why a journal event was rejected.
+#[derive(Debug, thiserror::Error)]
+pub enum JournalEventError {
+    #[error("Événement introuvable")]
+    NotFound,
+    #[error("Type d'événement invalide: {0}")]
+    InvalidType(String),
+    #[error("L'heure de fin doit suivre l'heure de début")]
+    EndBeforeStart,
+}
+
+const EVENT_COLUMNS: &str =
+    "id, child_id, date, event_type, start_time, end_time, notes, created_by, created_at";
+
+fn validate_event(event: &JournalEventFields) -> Result<(), JournalEventError> {
+    if !JOURNAL_EVENT_TYPES.contains(&event.event_type.as_str()) {
+        return Err(JournalEventError::InvalidType(event.event_type.clone()));
+    }
+    if event.end_time.is_some_and(|end| end < event.start_time) {
+        return Err(JournalEventError::EndBeforeStart);
+    }
+    Ok(())
+}
+
+/// Minutes slept across the day's naps; naps still in progress do not count yet.
+fn sleep_minutes(events: &[JournalEvent]) -> i64 {
+    events
+        .iter()
+        .filter(|e| e.event_type == "sieste")
+        .filter_map(JournalEvent::duration_minutes)
+        .sum()
+}
+
+/// What the daily digest shows for a child besides its journal row.
+struct ChildDay {
+    allergies: Vec<String>,
+    events: Vec<JournalEvent>,
+}
+
 #[derive(Clone, Debug, sqlx::FromRow)]
 struct MenuDuJour {
     weather: Option<String>, // garderie-wide weather
     collation_matin: Option<String>,
     diner: Option<String>,
     collation_apres_midi: Option<String>,
+    #[sqlx(skip)]
+    items: Vec<MenuItem>,
 }
 
 #[derive(Clone, Debug, sqlx::FromRow)]
@@ -32,7 +114,7 @@ async fn fetch_menu_du_jour(
     date: NaiveDate,
 ) -> anyhow::Result<Option<MenuDuJour>> {
     let schema = schema_name(tenant);
-    let menu: Option<MenuDuJour> = sqlx::query_as(&format!(
+    let mut menu: Option<MenuDuJour> = sqlx::query_as(&format!(
         r#"SELECT weather::TEXT AS weather, collation_matin, diner, collation_apres_midi
            FROM "{schema}".daily_menus
            WHERE date = $1"#
@@ -40,6 +122,9 @@ async fn fetch_menu_du_jour(
     .bind(date)
     .fetch_optional(pool)
     .await?;
+    if let Some(ref mut m) = menu {
+        m.items = MenuService::items_for_date(pool, tenant, date).await?;
+    }
     Ok(menu)
 }
 
@@ -63,6 +148,35 @@ async fn fetch_themes_for_date(
     Ok(themes)
 }
 
+/// Validate enum values server-side before sending to DB.
+fn validate_entry(req: &UpsertJournalRequest) -> anyhow::Result<()> {
+    if let Some(ref v) = req.temperature {
+        anyhow::ensure!(
+            WEATHER_CONDITIONS.contains(&v.as_str()),
+            "Valeur de température invalide: {v}"
+        );
+    }
+    if let Some(ref v) = req.appetit {
+        anyhow::ensure!(
+            APPETIT_LEVELS.contains(&v.as_str()),
+            "Valeur d'appétit invalide: {v}"
+        );
+    }
+    if let Some(ref v) = req.humeur {
+        anyhow::ensure!(
+            HUMEUR_LEVELS.contains(&v.as_str()),
+            "Valeur d'humeur invalide: {v}"
+        );
+    }
+    if let Some(m) = req.sommeil_minutes {
+        anyhow::ensure!(
+            (0..=180).contains(&m),
+            "sommeil_minutes doit être entre 0 et 180"
+        );
+    }
+    Ok(())
+}
+
 pub struct JournalService;
 
 impl JournalService {
@@ -84,7 +198,7 @@ impl JournalService {
                       sommeil_minutes,
                       absent,
                       sante, medicaments, message_educatrice, observations,
-                      created_by, created_at, updated_at
+                      created_by, created_at, updated_at, sent_at, amended_at
                FROM "{schema}".daily_journals
                WHERE child_id = $1 AND date BETWEEN $2 AND $3
                ORDER BY date"#
@@ -97,38 +211,16 @@ impl JournalService {
         Ok(entries)
     }
 
-    /// Insert or update a journal entry for (child_id, date).
+    /// Insert or update a journal entry for (child_id, date). With `expected`, an
+    /// existing entry is only overwritten if it is still at that version.
     pub async fn upsert(
         pool: &PgPool,
         tenant: &str,
         req: &UpsertJournalRequest,
         created_by: Uuid,
+        expected: Option<DateTime<Utc>>,
     ) -> anyhow::Result<DailyJournal> {
-        // Validate enum values server-side before sending to DB.
-        if let Some(ref v) = req.temperature {
-            anyhow::ensure!(
-                WEATHER_CONDITIONS.contains(&v.as_str()),
-                "Valeur de température invalide: {v}"
-            );
-        }
-        if let Some(ref v) = req.appetit {
-            anyhow::ensure!(
-                APPETIT_LEVELS.contains(&v.as_str()),
-                "Valeur d'appétit invalide: {v}"
-            );
-        }
-        if let Some(ref v) = req.humeur {
-            anyhow::ensure!(
-                HUMEUR_LEVELS.contains(&v.as_str()),
-                "Valeur d'humeur invalide: {v}"
-            );
-        }
-        if let Some(m) = req.sommeil_minutes {
-            anyhow::ensure!(
-                (0..=180).contains(&m),
-                "sommeil_minutes doit être entre 0 et 180"
-            );
-        }
+        validate_entry(req)?;
 
         let schema = schema_name(tenant);
         let entry = sqlx::query_as::<_, DailyJournal>(&format!(
@@ -153,6 +245,8 @@ impl JournalService {
                    medicaments        = EXCLUDED.medicaments,
                    message_educatrice = EXCLUDED.message_educatrice,
                    observations       = EXCLUDED.observations
+               WHERE "{schema}".daily_journals.sent_at IS NULL
+                 AND ($14::timestamptz IS NULL OR "{schema}".daily_journals.updated_at = $14)
                RETURNING
                    id, child_id, date,
                    temperature::TEXT AS temperature,
@@ -162,7 +256,7 @@ impl JournalService {
                    sommeil_minutes,
                    absent,
                    sante, medicaments, message_educatrice, observations,
-                   created_by, created_at, updated_at"#
+                   created_by, created_at, updated_at, sent_at, amended_at"#
         ))
         .bind(req.child_id)
         .bind(req.date)
@@ -177,11 +271,247 @@ impl JournalService {
         .bind(&req.message_educatrice)
         .bind(&req.observations)
         .bind(created_by)
-        .fetch_one(pool)
+        .bind(expected)
+        .fetch_optional(pool)
+        .await?;
+        if let Some(entry) = entry {
+            return Ok(entry);
+        }
+        // No row back: the entry exists and was either already emailed or changed since
+        match Self::get_day(pool, tenant, req.child_id, req.date).await? {
+            Some(current) if current.sent_at.is_none() => {
+                Err(StaleVersion { current: serde_json::to_value(current)? }.into())
+            }
+            _ => Err(JournalAmendError::AlreadySent.into()),
+        }
+    }
+
+    /// Correct an entry already emailed: the previous version is kept with the note,
+    /// the entry is updated, then emailed again to the parents with the note on top.
+    /// Returns the updated entry and the number of emails sent.
+    pub async fn amend(
+        pool: &PgPool,
+        email_svc: Option<&crate::services::email::EmailService>,
+        config: &Config,
+        tenant: &str,
+        req: &AmendJournalRequest,
+        amended_by: Uuid,
+    ) -> anyhow::Result<(DailyJournal, usize)> {
+        let note = req.note.trim();
+        if note.is_empty() || note.chars().count() > MAX_AMENDMENT_NOTE_CHARS {
+            return Err(JournalAmendError::NoteRequired.into());
+        }
+        let entry = &req.entry;
+        validate_entry(entry)?;
+
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+        let current: Option<(Uuid, bool, serde_json::Value)> = sqlx::query_as(&format!(
+            r#"SELECT id, sent_at IS NOT NULL, to_jsonb(j) - 'id' - 'child_id' - 'date'
+               FROM "{schema}".daily_journals j
+               WHERE child_id = $1 AND date = $2
+               FOR UPDATE"#
+        ))
+        .bind(entry.child_id)
+        .bind(entry.date)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let (journal_id, sent, previous) = current.ok_or(JournalAmendError::NotFound)?;
+        if !sent {
+            return Err(JournalAmendError::NotSent.into());
+        }
+
+        sqlx::query(&format!(
+            r#"INSERT INTO "{schema}".journal_amendments (journal_id, amended_by, note, previous)
+               VALUES ($1, $2, $3, $4)"#
+        ))
+        .bind(journal_id)
+        .bind(amended_by)
+        .bind(note)
+        .bind(&previous)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(&format!(
+            r#"UPDATE "{schema}".daily_journals SET
+                   temperature        = $2::"{schema}".weather_condition,
+                   menu               = $3,
+                   appetit            = $4::"{schema}".appetit_level,
+                   humeur             = $5::"{schema}".humeur_level,
+                   sommeil_minutes    = $6,
+                   absent             = $7,
+                   sante              = $8,
+                   medicaments        = $9,
+                   message_educatrice = $10,
+                   observations       = $11,
+                   amended_at         = NOW()
+               WHERE id = $1"#
+        ))
+        .bind(journal_id)
+        .bind(&entry.temperature)
+        .bind(&entry.menu)
+        .bind(&entry.appetit)
+        .bind(&entry.humeur)
+        .bind(entry.sommeil_minutes)
+        .bind(entry.absent)
+        .bind(&entry.sante)
+        .bind(&entry.medicaments)
+        .bind(&entry.message_educatrice)
+        .bind(&entry.observations)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let sent = match email_svc {
+            Some(svc) => {
+                Self::send_day_to_parents(pool, svc, config, tenant, entry.child_id, entry.date, Some(note)).await?
+            }
+            None => 0,
+        };
+        let journal = Self::get_day(pool, tenant, entry.child_id, entry.date)
+            .await?
+            .ok_or(JournalAmendError::NotFound)?;
+        Ok((journal, sent))
+    }
+
+    /// Corrections of a child's day, newest first.
+    pub async fn list_amendments(
+        pool: &PgPool,
+        tenant: &str,
+        child_id: Uuid,
+        date: NaiveDate,
+    ) -> anyhow::Result<Vec<JournalAmendment>> {
+        let schema = schema_name(tenant);
+        let amendments = sqlx::query_as::<_, JournalAmendment>(&format!(
+            r#"SELECT a.id, a.journal_id, a.amended_by,
+                      CONCAT(u.first_name, ' ', u.last_name) AS amended_by_name,
+                      a.note, a.previous, a.created_at
+               FROM "{schema}".journal_amendments a
+               JOIN "{schema}".daily_journals j ON j.id = a.journal_id
+               LEFT JOIN "{schema}".users u ON u.id = a.amended_by
+               WHERE j.child_id = $1 AND j.date = $2
+               ORDER BY a.created_at DESC"#
+        ))
+        .bind(child_id)
+        .bind(date)
+        .fetch_all(pool)
+        .await?;
+        Ok(amendments)
+    }
+
+    async fn get_day(
+        pool: &PgPool,
+        tenant: &str,
+        child_id: Uuid,
+        date: NaiveDate,
+    ) -> anyhow::Result<Option<DailyJournal>> {
+        let schema = schema_name(tenant);
+        let entry = sqlx::query_as::<_, DailyJournal>(&format!(
+            r#"SELECT id, child_id, date,
+                      temperature::TEXT AS temperature,
+                      menu,
+                      appetit::TEXT     AS appetit,
+                      humeur::TEXT      AS humeur,
+                      sommeil_minutes,
+                      absent,
+                      sante, medicaments, message_educatrice, observations,
+                      created_by, created_at, updated_at, sent_at, amended_at
+               FROM "{schema}".daily_journals
+               WHERE child_id = $1 AND date = $2"#
+        ))
+        .bind(child_id)
+        .bind(date)
+        .fetch_optional(pool)
         .await?;
         Ok(entry)
     }
 
+    /// Email one child's day to its parents, as the daily digest does, and mark it sent.
+    async fn send_day_to_parents(
+        pool: &PgPool,
+        svc: &crate::services::email::EmailService,
+        config: &Config,
+        tenant: &str,
+        child_id: Uuid,
+        date: NaiveDate,
+        amendment: Option<&str>,
+    ) -> anyhow::Result<usize> {
+        let schema = schema_name(tenant);
+        let (first, last, allergies): (String, String, Vec<String>) = sqlx::query_as(&format!(
+            r#"SELECT first_name, last_name, allergies FROM "{schema}".children WHERE id = $1"#
+        ))
+        .bind(child_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(JournalSendError::ChildNotFound)?;
+        let entry = Self::get_day(pool, tenant, child_id, date)
+            .await?
+            .ok_or(JournalSendError::NoEntries)?;
+        let events = Self::list_events(pool, tenant, child_id, date).await?;
+        let child_days = std::collections::HashMap::from([(child_id, ChildDay { allergies, events })]);
+
+        let mut parents: Vec<(String, String, Locale)> = sqlx::query_as::<_, (String, String, String)>(&format!(
+            r#"SELECT u.email, CONCAT(u.first_name, ' ', u.last_name), u.preferred_locale
+               FROM "{schema}".users u
+               INNER JOIN "{schema}".child_parents cp ON u.id = cp.user_id
+               WHERE cp.child_id = $1 AND u.is_active = TRUE"#
+        ))
+        .bind(child_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|(email, name, locale)| (email, name, Locale::from_tag(&locale)))
+        .collect();
+        let pending = ChildService::get_pending_parent_emails_for_children(pool, tenant, &[child_id])
+            .await
+            .unwrap_or_default();
+        parents.extend(
+            pending
+                .into_iter()
+                .map(|(_, email)| (email, tr(Locale::Fr, "journal.pending_parent", &[]), Locale::Fr)),
+        );
+
+        let garderie_name: String = sqlx::query_scalar("SELECT name FROM public.garderies WHERE slug = $1")
+            .bind(tenant)
+            .fetch_optional(pool)
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| tenant.to_string());
+        let themes = fetch_themes_for_date(pool, tenant, date).await.unwrap_or_default();
+        let menu = fetch_menu_du_jour(pool, tenant, date).await.unwrap_or(None);
+
+        let photos = journal_photos::load(pool, config, tenant, &[child_id], date, date).await;
+        let children_entries = [(first.clone(), last.clone(), entry)];
+        let shown = shown_photos(&photos, children_entries.iter().map(|(_, _, e)| e));
+        let mut sent = 0;
+        for (email, name, locale) in &parents {
+            let key = if amendment.is_some() { "journal.subject_amended" } else { "journal.subject_day" };
+            let subject = tr(
+                *locale,
+                key,
+                &[("children", &format!("{first} {last}")), ("date", &locale.short_date(date))],
+            );
+            let html = build_journal_email_html_multi(
+                &children_entries, date, &garderie_name, &themes, menu.as_ref(), &child_days, &photos, *locale,
+                amendment,
+            );
+            match svc.send_journal(tenant, email, name, &html, &subject, &garderie_name, &shown).await {
+                Ok(_) => sent += 1,
+                Err(e) => tracing::warn!("Journal email to {email} failed: {e}"),
+            }
+        }
+
+        sqlx::query(&format!(
+            r#"UPDATE "{schema}".daily_journals SET sent_at = NOW() WHERE child_id = $1 AND date = $2"#
+        ))
+        .bind(child_id)
+        .bind(date)
+        .execute(pool)
+        .await?;
+        Ok(sent)
+    }
+
     /// Auto-send today's journal entries for all children of a tenant.
     /// Groups journals by parent email to send one email per parent with all their children's journals.
     /// Only sends entries that have content (or are absent) and haven't been sent yet.
@@ -189,6 +519,7 @@ impl JournalService {
     pub async fn auto_send_today(
         pool: &PgPool,
         email_svc: Option<&crate::services::email::EmailService>,
+        config: &Config,
         tenant: &str,
         today: NaiveDate,
     ) -> anyhow::Result<usize> {
@@ -205,7 +536,9 @@ impl JournalService {
                       OR sommeil_minutes IS NOT NULL
                       OR sante IS NOT NULL        OR medicaments IS NOT NULL
                       OR message_educatrice IS NOT NULL
-                      OR observations IS NOT NULL)"#
+                      OR observations IS NOT NULL
+                      OR EXISTS (SELECT 1 FROM "{schema}".journal_events e
+                                 WHERE e.child_id = daily_journals.child_id AND e.date = daily_journals.date))"#
         ))
         .bind(today)
         .fetch_all(pool)
@@ -228,17 +561,23 @@ impl JournalService {
         // Build a map of child_id -> (first_name, last_name, DailyJournal)
         let mut child_data: std::collections::HashMap<Uuid, (String, String, DailyJournal)> =
             std::collections::HashMap::new();
+        let mut child_days: std::collections::HashMap<Uuid, ChildDay> =
+            std::collections::HashMap::new();
 
         for child_id in &child_ids {
-            let child_name: Option<(String, String)> = sqlx::query_as(&format!(
-                r#"SELECT first_name, last_name FROM "{schema}".children WHERE id = $1"#
+            let child_name: Option<(String, String, Vec<String>)> = sqlx::query_as(&format!(
+                r#"SELECT first_name, last_name, allergies FROM "{schema}".children WHERE id = $1"#
             ))
             .bind(child_id)
             .fetch_optional(pool)
             .await?;
 
             let (first, last) = match child_name {
-                Some(n) => n,
+                Some((first, last, allergies)) => {
+                    let events = Self::list_events(pool, tenant, *child_id, today).await?;
+                    child_days.insert(*child_id, ChildDay { allergies, events });
+                    (first, last)
+                }
                 None => continue,
             };
 
@@ -252,7 +591,7 @@ impl JournalService {
                           sommeil_minutes,
                           absent,
                           sante, medicaments, message_educatrice, observations,
-                          created_by, created_at, updated_at
+                          created_by, created_at, updated_at, sent_at, amended_at
                    FROM "{schema}".daily_journals
                    WHERE child_id = $1 AND date = $2"#
             ))
@@ -271,8 +610,8 @@ impl JournalService {
         }
 
         // Fetch all parent-child relationships for the children we have entries for
-        let parent_child_rows: Vec<(String, String, Uuid)> = sqlx::query_as(&format!(
-            r#"SELECT u.email, CONCAT(u.first_name, ' ', u.last_name), cp.child_id
+        let parent_child_rows: Vec<(String, String, String, Uuid)> = sqlx::query_as(&format!(
+            r#"SELECT u.email, CONCAT(u.first_name, ' ', u.last_name), u.preferred_locale, cp.child_id
                FROM "{schema}".users u
                INNER JOIN "{schema}".child_parents cp ON u.id = cp.user_id
                WHERE cp.child_id = ANY($1) AND u.is_active = TRUE"#
@@ -288,26 +627,27 @@ impl JournalService {
                 .unwrap_or_default();
 
         // Group by parent_email
-        let mut parent_children: std::collections::HashMap<String, (String, Vec<(String, String, DailyJournal)>)> =
+        type ParentDigest = (String, Locale, Vec<(String, String, DailyJournal)>);
+        let mut parent_children: std::collections::HashMap<String, ParentDigest> =
             std::collections::HashMap::new();
 
-        for (parent_email, parent_name, child_id) in parent_child_rows {
+        for (parent_email, parent_name, locale, child_id) in parent_child_rows {
             if let Some((child_first, child_last, entry)) = child_data.get(&child_id) {
                 parent_children
                     .entry(parent_email)
-                    .or_insert_with(|| (parent_name.clone(), Vec::new()))
-                    .1
+                    .or_insert_with(|| (parent_name.clone(), Locale::from_tag(&locale), Vec::new()))
+                    .2
                     .push((child_first.clone(), child_last.clone(), entry.clone()));
             }
         }
 
-        // Add pending parents to the same grouping
+        // Add pending parents to the same grouping; they have no locale yet
         for (child_id, pending_email) in pending_parent_rows {
             if let Some((child_first, child_last, entry)) = child_data.get(&child_id) {
                 parent_children
                     .entry(pending_email.clone())
-                    .or_insert_with(|| ("Parent en attente".to_string(), Vec::new()))
-                    .1
+                    .or_insert_with(|| (tr(Locale::Fr, "journal.pending_parent", &[]), Locale::Fr, Vec::new()))
+                    .2
                     .push((child_first.clone(), child_last.clone(), entry.clone()));
             }
         }
@@ -316,33 +656,35 @@ impl JournalService {
 
         // Send one email per parent with all their children's journals
         if let Some(svc) = email_svc {
-            for (parent_email, (parent_name, children_entries)) in parent_children {
+            let photos = journal_photos::load(pool, config, tenant, &child_ids, today, today).await;
+            for (parent_email, (parent_name, locale, children_entries)) in parent_children {
                 // Build subject: if 1 child, use child's name; if multiple, use first names
-                let subject = if children_entries.len() == 1 {
-                    format!(
-                        "Journal de bord de {} {} — {}",
-                        children_entries[0].0, children_entries[0].1,
-                        today.format("%d/%m/%Y")
-                    )
+                let children = if children_entries.len() == 1 {
+                    format!("{} {}", children_entries[0].0, children_entries[0].1)
                 } else {
                     let first_names: Vec<&str> =
                         children_entries.iter().map(|(f, _, _)| f.as_str()).collect();
-                    format!(
-                        "Journal de bord de {} — {}",
-                        first_names.join(" et "),
-                        today.format("%d/%m/%Y")
-                    )
+                    first_names.join(&tr(locale, "journal.and", &[]))
                 };
+                let subject = tr(
+                    locale,
+                    "journal.subject_day",
+                    &[("children", &children), ("date", &locale.short_date(today))],
+                );
 
                 // Fetch theme activities and menu for today
                 let themes = fetch_themes_for_date(pool, tenant, today).await.unwrap_or_default();
                 let menu = fetch_menu_du_jour(pool, tenant, today).await.unwrap_or(None);
 
                 // Build HTML with all children's journals
-                let html = build_journal_email_html_multi(&children_entries, today, &garderie_name, &themes, menu.as_ref());
+                let html = build_journal_email_html_multi(
+                    &children_entries, today, &garderie_name, &themes, menu.as_ref(), &child_days, &photos, locale,
+                    None,
+                );
+                let shown = shown_photos(&photos, children_entries.iter().map(|(_, _, e)| e));
 
                 let _ = svc
-                    .send_journal(&parent_email, &parent_name, &html, &subject, &garderie_name)
+                    .send_journal(tenant, &parent_email, &parent_name, &html, &subject, &garderie_name, &shown)
                     .await;
                 total_sent += 1;
             }
@@ -364,6 +706,156 @@ impl JournalService {
         Ok(total_sent)
     }
 
+    /// Events of one child's day, in time order.
+    pub async fn list_events(
+        pool: &PgPool,
+        tenant: &str,
+        child_id: Uuid,
+        date: NaiveDate,
+    ) -> anyhow::Result<Vec<JournalEvent>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, JournalEvent>(&format!(
+            r#"SELECT {EVENT_COLUMNS} FROM "{schema}".journal_events
+               WHERE child_id = $1 AND date = $2
+               ORDER BY start_time, created_at"#
+        ))
+        .bind(child_id)
+        .bind(date)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Child the event belongs to, for access checks before changing it.
+    pub async fn event_child(pool: &PgPool, tenant: &str, event_id: Uuid) -> anyhow::Result<Uuid> {
+        let schema = schema_name(tenant);
+        let child_id: Option<Uuid> = sqlx::query_scalar(&format!(
+            r#"SELECT child_id FROM "{schema}".journal_events WHERE id = $1"#
+        ))
+        .bind(event_id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(child_id.ok_or(JournalEventError::NotFound)?)
+    }
+
+    /// Log an event. The day's journal row is created if needed so the day is sent
+    /// in the evening email even when only events were recorded.
+    pub async fn add_event(
+        pool: &PgPool,
+        tenant: &str,
+        req: &CreateJournalEventRequest,
+        created_by: Uuid,
+    ) -> anyhow::Result<JournalEvent> {
+        validate_event(&req.event)?;
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(&format!(
+            r#"INSERT INTO "{schema}".daily_journals (child_id, date, created_by)
+               VALUES ($1, $2, $3)
+               ON CONFLICT (child_id, date) DO NOTHING"#
+        ))
+        .bind(req.child_id)
+        .bind(req.date)
+        .bind(created_by)
+        .execute(&mut *tx)
+        .await?;
+
+        let event = sqlx::query_as::<_, JournalEvent>(&format!(
+            r#"INSERT INTO "{schema}".journal_events
+                   (child_id, date, event_type, start_time, end_time, notes, created_by)
+               VALUES ($1, $2, $3, $4, $5, $6, $7)
+               RETURNING {EVENT_COLUMNS}"#
+        ))
+        .bind(req.child_id)
+        .bind(req.date)
+        .bind(&req.event.event_type)
+        .bind(req.event.start_time)
+        .bind(req.event.end_time)
+        .bind(&req.event.notes)
+        .bind(created_by)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(event)
+    }
+
+    /// Replace an event's type, times and notes (e.g. to close a nap in progress).
+    pub async fn update_event(
+        pool: &PgPool,
+        tenant: &str,
+        event_id: Uuid,
+        fields: &JournalEventFields,
+    ) -> anyhow::Result<JournalEvent> {
+        validate_event(fields)?;
+        let schema = schema_name(tenant);
+        let event = sqlx::query_as::<_, JournalEvent>(&format!(
+            r#"UPDATE "{schema}".journal_events
+               SET event_type = $2, start_time = $3, end_time = $4, notes = $5
+               WHERE id = $1
+               RETURNING {EVENT_COLUMNS}"#
+        ))
+        .bind(event_id)
+        .bind(&fields.event_type)
+        .bind(fields.start_time)
+        .bind(fields.end_time)
+        .bind(&fields.notes)
+        .fetch_optional(pool)
+        .await?;
+        Ok(event.ok_or(JournalEventError::NotFound)?)
+    }
+
+    pub async fn delete_event(pool: &PgPool, tenant: &str, event_id: Uuid) -> anyhow::Result<()> {
+        let schema = schema_name(tenant);
+        let deleted = sqlx::query(&format!(
+            r#"DELETE FROM "{schema}".journal_events WHERE id = $1"#
+        ))
+        .bind(event_id)
+        .execute(pool)
+        .await?
+        .rows_affected();
+        if deleted == 0 {
+            return Err(JournalEventError::NotFound.into());
+        }
+        Ok(())
+    }
+
+    /// The day's journal entry together with its events in time order.
+    pub async fn timeline(
+        pool: &PgPool,
+        tenant: &str,
+        child_id: Uuid,
+        date: NaiveDate,
+    ) -> anyhow::Result<JournalTimeline> {
+        let schema = schema_name(tenant);
+        let journal: Option<DailyJournal> = sqlx::query_as(&format!(
+            r#"SELECT id, child_id, date,
+                      temperature::TEXT AS temperature,
+                      menu,
+                      appetit::TEXT     AS appetit,
+                      humeur::TEXT      AS humeur,
+                      sommeil_minutes,
+                      absent,
+                      sante, medicaments, message_educatrice, observations,
+                      created_by, created_at, updated_at, sent_at, amended_at
+               FROM "{schema}".daily_journals
+               WHERE child_id = $1 AND date = $2"#
+        ))
+        .bind(child_id)
+        .bind(date)
+        .fetch_optional(pool)
+        .await?;
+
+        let events = Self::list_events(pool, tenant, child_id, date).await?;
+        Ok(JournalTimeline {
+            child_id,
+            date,
+            journal,
+            sleep_minutes: sleep_minutes(&events),
+            events,
+        })
+    }
+
     /// Returns true if the given user is a parent of the child.
     pub async fn assert_parent_access(
         pool: &PgPool,
@@ -385,156 +877,126 @@ impl JournalService {
         Ok(exists)
     }
 
-    /// Send weekly journals for ALL active children to their parents in one shot.
-    pub async fn send_all_journals_to_parents(
+    /// Weekly journals ready to send for ALL active children: the children with
+    /// entries and parents this week, and how many parents they have. Children with
+    /// nothing to send are counted as skipped.
+    pub async fn weeks_to_send(
         pool: &PgPool,
-        email_svc: Option<&crate::services::email::EmailService>,
         tenant: &str,
         week_start: NaiveDate,
-    ) -> anyhow::Result<String> {
+    ) -> anyhow::Result<(Vec<Uuid>, usize, usize)> {
         let schema = schema_name(tenant);
 
-        let children: Vec<(Uuid, String, String)> = sqlx::query_as(&format!(
-            r#"SELECT id, first_name, last_name FROM "{schema}".children WHERE is_active = TRUE"#
+        let children: Vec<Uuid> = sqlx::query_scalar(&format!(
+            r#"SELECT id FROM "{schema}".children WHERE is_active = TRUE"#
         ))
         .fetch_all(pool)
         .await?;
 
         if children.is_empty() {
-            anyhow::bail!("Aucun enfant actif trouvé");
+            return Err(JournalSendError::NoActiveChildren.into());
         }
 
-        let garderie_name: String = sqlx::query_scalar(
-            "SELECT name FROM public.garderies WHERE slug = $1",
-        )
-        .bind(tenant)
-        .fetch_optional(pool)
-        .await
-        .ok()
-        .flatten()
-        .unwrap_or_else(|| tenant.to_string());
-
-        let week_end = week_start + chrono::Duration::days(4);
-        let mut total_sent: usize = 0;
+        let mut ready = Vec::new();
+        let mut total: usize = 0;
         let mut skipped: usize = 0;
-
-        for (child_id, child_first, child_last) in &children {
-            let entries = Self::list_week(pool, tenant, *child_id, week_start).await?;
-            if entries.is_empty() {
-                skipped += 1;
-                continue;
+        for child_id in children {
+            match Self::journal_recipients(pool, tenant, child_id, week_start).await {
+                Ok(n) => {
+                    ready.push(child_id);
+                    total += n;
+                }
+                Err(e) => match e.downcast_ref::<JournalSendError>() {
+                    Some(JournalSendError::NoParents | JournalSendError::NoEntries) => skipped += 1,
+                    _ => return Err(e),
+                },
             }
+        }
+        Ok((ready, total, skipped))
+    }
 
-            let parents: Vec<(String, String)> = sqlx::query_as(&format!(
-                r#"SELECT u.email, CONCAT(u.first_name, ' ', u.last_name)
-                   FROM "{schema}".users u
-                   INNER JOIN "{schema}".child_parents cp ON u.id = cp.user_id
-                   WHERE cp.child_id = $1 AND u.is_active = TRUE"#
-            ))
-            .bind(child_id)
-            .fetch_all(pool)
-            .await?;
-
-            // Fetch pending parents for this child
-            let pending_parents: Vec<(Uuid, String)> =
-                ChildService::get_pending_parent_emails_for_children(pool, tenant, &[*child_id])
-                    .await
-                    .unwrap_or_default();
+    /// How many parents (registered and pending) the child's weekly journal goes to,
+    /// or why it cannot be sent.
+    pub async fn journal_recipients(
+        pool: &PgPool,
+        tenant: &str,
+        child_id: Uuid,
+        week_start: NaiveDate,
+    ) -> anyhow::Result<usize> {
+        let schema = schema_name(tenant);
 
-            if parents.is_empty() && pending_parents.is_empty() {
-                skipped += 1;
-                continue;
-            }
+        let exists: bool = sqlx::query_scalar(&format!(
+            r#"SELECT EXISTS(SELECT 1 FROM "{schema}".children WHERE id = $1)"#
+        ))
+        .bind(child_id)
+        .fetch_one(pool)
+        .await?;
+        if !exists {
+            return Err(JournalSendError::ChildNotFound.into());
+        }
 
-            if let Some(svc) = email_svc {
-                // Fetch theme activities and menus for the week
-                let mut themes_for_week = Vec::new();
-                let mut menus_for_week = std::collections::HashMap::new();
-                for i in 0..5 {
-                    let day = week_start + chrono::Duration::days(i);
-                    if let Ok(day_themes) = fetch_themes_for_date(pool, tenant, day).await {
-                        themes_for_week.extend(day_themes);
-                    }
-                    if let Ok(menu) = fetch_menu_du_jour(pool, tenant, day).await {
-                        if let Some(m) = menu {
-                            menus_for_week.insert(day, m);
-                        }
-                    }
-                }
+        let parents = Self::journal_parents(pool, &schema, child_id).await?;
+        let pending_parents: Vec<(Uuid, String)> =
+            ChildService::get_pending_parent_emails_for_children(pool, tenant, &[child_id])
+                .await
+                .unwrap_or_default();
+        if parents.is_empty() && pending_parents.is_empty() {
+            return Err(JournalSendError::NoParents.into());
+        }
 
-                let html = build_journal_email_html(
-                    child_first, child_last, week_start, week_end, &entries, &garderie_name, &themes_for_week, &menus_for_week,
-                );
-                let subject = format!(
-                    "Journal de bord de {} {} - Semaine du {}",
-                    child_first,
-                    child_last,
-                    week_start.format("%d/%m/%Y")
-                );
-                // Send to registered parents
-                for (parent_email, parent_name) in &parents {
-                    let _ = svc.send_journal(parent_email, parent_name, &html, &subject, &garderie_name).await;
-                    total_sent += 1;
-                }
-                // Send to pending parents
-                for (_child_id, parent_email) in &pending_parents {
-                    let _ = svc.send_journal(parent_email, "Parent", &html, &subject, &garderie_name).await;
-                    total_sent += 1;
-                }
-            }
+        let entries = Self::list_week(pool, tenant, child_id, week_start).await?;
+        if entries.is_empty() {
+            return Err(JournalSendError::NoEntries.into());
         }
 
-        Ok(format!(
-            "Journaux envoyés à {} parent(s) ({} enfant(s) ignoré(s))",
-            total_sent, skipped
+        Ok(parents.len() + pending_parents.len())
+    }
+
+    /// Registered parents of a child: (email, name, preferred locale).
+    async fn journal_parents(pool: &PgPool, schema: &str, child_id: Uuid) -> anyhow::Result<Vec<(String, String, String)>> {
+        Ok(sqlx::query_as(&format!(
+            r#"SELECT u.email, CONCAT(u.first_name, ' ', u.last_name), u.preferred_locale
+               FROM "{schema}".users u
+               INNER JOIN "{schema}".child_parents cp ON u.id = cp.user_id
+               WHERE cp.child_id = $1 AND u.is_active = TRUE"#
         ))
+        .bind(child_id)
+        .fetch_all(pool)
+        .await?)
     }
 
-    /// Send weekly journal entries for a child to all parents as HTML email.
-    pub async fn send_journal_to_parents(
+    /// Email a child's weekly journal to all their parents as HTML, each in their
+    /// language. Run by the notification consumer once the journal is sent; a week
+    /// without entries sends nothing.
+    pub async fn email_week(
         pool: &PgPool,
-        email_svc: Option<&crate::services::email::EmailService>,
+        svc: &crate::services::email::EmailService,
+        config: &Config,
         tenant: &str,
         child_id: Uuid,
         week_start: NaiveDate,
-    ) -> anyhow::Result<String> {
+    ) -> anyhow::Result<()> {
         let schema = schema_name(tenant);
 
-        // Fetch child info
-        let (child_first_name, child_last_name): (String, String) = sqlx::query_as(&format!(
+        let Some((child_first_name, child_last_name)): Option<(String, String)> = sqlx::query_as(&format!(
             r#"SELECT first_name, last_name FROM "{schema}".children WHERE id = $1"#
         ))
         .bind(child_id)
         .fetch_optional(pool)
         .await?
-        .ok_or_else(|| anyhow::anyhow!("Enfant non trouvé"))?;
-
-        // Fetch parents
-        let parents: Vec<(String, String)> = sqlx::query_as(&format!(
-            r#"SELECT u.email, CONCAT(u.first_name, ' ', u.last_name)
-               FROM "{schema}".users u
-               INNER JOIN "{schema}".child_parents cp ON u.id = cp.user_id
-               WHERE cp.child_id = $1 AND u.is_active = TRUE"#
-        ))
-        .bind(child_id)
-        .fetch_all(pool)
-        .await?;
+        else {
+            return Ok(());
+        };
 
-        // Fetch pending parents
+        let parents = Self::journal_parents(pool, &schema, child_id).await?;
         let pending_parents: Vec<(Uuid, String)> =
             ChildService::get_pending_parent_emails_for_children(pool, tenant, &[child_id])
                 .await
                 .unwrap_or_default();
 
-        if parents.is_empty() && pending_parents.is_empty() {
-            anyhow::bail!("Aucun parent assigné à cet enfant");
-        }
-
-        // Fetch journal entries for the week
         let entries = Self::list_week(pool, tenant, child_id, week_start).await?;
-
         if entries.is_empty() {
-            anyhow::bail!("Aucun journal disponible pour cette semaine");
+            return Ok(());
         }
 
         let garderie_name: String = sqlx::query_scalar(
@@ -564,75 +1026,81 @@ impl JournalService {
             }
         }
 
-        // Build HTML email
-        let html = build_journal_email_html(&child_first_name, &child_last_name, week_start, week_end, &entries, &garderie_name, &themes_for_week, &menus_for_week);
+        let photos = journal_photos::load(pool, config, tenant, &[child_id], week_start, week_end).await;
+        let shown = shown_photos(&photos, entries.iter());
 
-        let subject = format!(
-            "Journal de bord de {} {} - Semaine du {}",
-            child_first_name, child_last_name,
-            week_start.format("%d/%m/%Y")
-        );
+        // Build HTML email in the recipient's language
+        let render = |locale: Locale| {
+            let html = build_journal_email_html(
+                &child_first_name, &child_last_name, week_start, week_end, &entries, &garderie_name,
+                &themes_for_week, &menus_for_week, &photos, locale,
+            );
+            (html, weekly_subject(locale, &child_first_name, &child_last_name, week_start))
+        };
 
-        // Send to all registered and pending parents
-        if let Some(svc) = email_svc {
-            // Send to registered parents
-            for (parent_email, parent_name) in &parents {
-                // Ignore send errors — graceful degradation
-                let _ = svc.send_journal(parent_email, parent_name, &html, &subject, &garderie_name).await;
-            }
-            // Send to pending parents
-            for (_child_id, parent_email) in &pending_parents {
-                let _ = svc.send_journal(parent_email, "Parent", &html, &subject, &garderie_name).await;
-            }
+        // Send to registered parents
+        for (parent_email, parent_name, locale) in &parents {
+            let (html, subject) = render(Locale::from_tag(locale));
+            // Ignore send errors — graceful degradation
+            let _ = svc.send_journal(tenant, parent_email, parent_name, &html, &subject, &garderie_name, &shown).await;
         }
-
-        let total = parents.len() + pending_parents.len();
-        Ok(format!(
-            "Journal envoyé à {} parent(s)",
-            total
-        ))
+        // Send to pending parents
+        let (html, subject) = render(Locale::Fr);
+        for (_child_id, parent_email) in &pending_parents {
+            let _ = svc.send_journal(tenant, parent_email, "Parent", &html, &subject, &garderie_name, &shown).await;
+        }
+        Ok(())
     }
 }
 
-fn fmt_temperature(v: &str) -> &str {
-    match v {
-        "ensoleille" => "☀️ Ensoleillé",
-        "nuageux"    => "⛅ Nuageux",
-        "pluie"      => "🌧️ Pluie",
-        "neige"      => "❄️ Neige",
-        "orageux"    => "⛈️ Orageux",
-        _ => v,
-    }
+fn weekly_subject(locale: Locale, child_first: &str, child_last: &str, week_start: NaiveDate) -> String {
+    tr(
+        locale,
+        "journal.subject_week",
+        &[("child", &format!("{child_first} {child_last}")), ("date", &locale.short_date(week_start))],
+    )
 }
 
-fn fmt_appetit(v: &str) -> &str {
-    match v {
-        "comme_habitude" => "Normal",
-        "peu"            => "Peu",
-        "beaucoup"       => "Beaucoup",
-        "refuse"         => "Refuse",
-        _ => v,
-    }
+fn fmt_temperature(locale: Locale, v: &str) -> &str {
+    lookup(locale, &format!("weather.{v}")).unwrap_or(v)
 }
 
-fn fmt_humeur(v: &str) -> &str {
-    match v {
-        "tres_bien" => "😄 Très bien",
-        "bien"      => "🙂 Bien",
-        "difficile" => "😕 Difficile",
-        "pleurs"    => "😢 Pleurs",
-        _ => v,
-    }
+fn fmt_appetit(locale: Locale, v: &str) -> &str {
+    lookup(locale, &format!("appetit.{v}")).unwrap_or(v)
 }
 
-fn fmt_date_fr(date: NaiveDate) -> String {
-    let days = ["Lundi", "Mardi", "Mercredi", "Jeudi", "Vendredi", "Samedi", "Dimanche"];
-    let months = ["janvier", "février", "mars", "avril", "mai", "juin",
-                  "juillet", "août", "septembre", "octobre", "novembre", "décembre"];
-    use chrono::Datelike;
-    let day_name = days[date.weekday().num_days_from_monday() as usize];
-    let month = months[date.month0() as usize];
-    format!("{} {} {} {}", day_name, date.day(), month, date.year())
+fn fmt_humeur(locale: Locale, v: &str) -> &str {
+    lookup(locale, &format!("humeur.{v}")).unwrap_or(v)
+}
+
+fn fmt_event_type(locale: Locale, v: &str) -> &str {
+    lookup(locale, &format!("event.{v}")).unwrap_or(v)
+}
+
+fn fmt_allergen(locale: Locale, v: &str) -> &str {
+    lookup(locale, &format!("allergen.{v}")).unwrap_or(v)
+}
+
+/// Structured items of one meal with their allergens, falling back to the free-text column.
+fn meal_text(locale: Locale, menu: &MenuDuJour, meal: &str, legacy: Option<&str>) -> Option<String> {
+    let dishes: Vec<String> = menu
+        .items
+        .iter()
+        .filter(|i| i.meal == meal)
+        .map(|i| {
+            if i.allergens.is_empty() {
+                i.name.clone()
+            } else {
+                let tags: Vec<&str> = i.allergens.iter().map(|a| fmt_allergen(locale, a)).collect();
+                format!("{} <em style=\"color:#6b7280\">({})</em>", i.name, tags.join(", "))
+            }
+        })
+        .collect();
+    if dishes.is_empty() {
+        legacy.map(str::to_string)
+    } else {
+        Some(dishes.join(", "))
+    }
 }
 
 fn opt_str(v: Option<&str>) -> &str {
@@ -662,38 +1130,44 @@ fn build_journal_email_html(
     garderie_name: &str,
     themes: &[ThemeActivity],
     menus: &std::collections::HashMap<NaiveDate, MenuDuJour>,
+    photos: &[JournalPhoto],
+    locale: Locale,
 ) -> String {
     let period = if week_start == week_end {
-        format!("Journal du {} — {}", fmt_date_fr(week_start), garderie_name)
+        tr(locale, "journal.period_day", &[("date", &locale.long_date(week_start)), ("garderie", garderie_name)])
     } else {
-        format!(
-            "Semaine du {} au {} — {}",
-            week_start.format("%d/%m/%Y"),
-            week_end.format("%d/%m/%Y"),
-            garderie_name
+        tr(
+            locale,
+            "journal.period_week",
+            &[
+                ("from", &locale.short_date(week_start)),
+                ("to", &locale.short_date(week_end)),
+                ("garderie", garderie_name),
+            ],
         )
     };
 
     let mut html = format!(
-        r#"<html><body style="font-family:sans-serif;max-width:800px;margin:auto;background:#f9fafb">
+        r#"<html lang="{lang}"><body style="font-family:sans-serif;max-width:800px;margin:auto;background:#f9fafb">
         <div style="background:white;border-radius:8px;padding:24px;box-shadow:0 1px 3px rgba(0,0,0,0.1)">
-            <h2 style="color:#1f2937;margin-bottom:8px">Journal de bord — {first} {last}</h2>
+            <h2 style="color:#1f2937;margin-bottom:8px">{title}</h2>
             <p style="color:#6b7280;margin-bottom:24px;font-size:14px">{period}</p>"#,
-        first = child_first,
-        last = child_last,
+        title = tr(locale, "journal.title_child", &[("child", &format!("{child_first} {child_last}"))]),
         period = period,
+        lang = locale.tag(),
     );
 
     for entry in entries {
-        let date_fr = fmt_date_fr(entry.date);
+        let date_fr = locale.long_date(entry.date);
 
         if entry.absent {
             html.push_str(&format!(
                 r#"<div style="border:1px solid #e5e7eb;border-radius:6px;padding:16px;margin-bottom:12px;background:#f9fafb">
                 <h3 style="color:#6b7280;margin:0 0 8px 0;font-size:16px">{date}</h3>
-                <span style="display:inline-block;background:#fee2e2;color:#b91c1c;font-size:13px;font-weight:600;padding:4px 12px;border-radius:20px">🏠 Absent ce jour</span>
+                <span style="display:inline-block;background:#fee2e2;color:#b91c1c;font-size:13px;font-weight:600;padding:4px 12px;border-radius:20px">🏠 {absent}</span>
                 </div>"#,
-                date = date_fr
+                date = date_fr,
+                absent = tr(locale, "journal.absent", &[]),
             ));
             continue;
         }
@@ -716,45 +1190,49 @@ fn build_journal_email_html(
                 if !w.trim().is_empty() {
                     html.push_str(&format!(
                         r#"<div style="background:#dbeafe;border:1px solid #3b82f6;border-radius:6px;padding:10px;margin-bottom:12px;font-size:14px">
-                        <span style="color:#1d4ed8;font-weight:600">🌤️ Météo :</span> <span style="color:#1e3a8a">{}</span>
+                        <span style="color:#1d4ed8;font-weight:600">🌤️ {}</span> <span style="color:#1e3a8a">{}</span>
                         </div>"#,
-                        fmt_temperature(w)
+                        tr(locale, "journal.weather", &[]),
+                        fmt_temperature(locale, w)
                     ));
                 }
             }
 
-            let has_menu = menu.collation_matin.is_some() || menu.diner.is_some() || menu.collation_apres_midi.is_some();
+            let has_menu = !menu.items.is_empty() || menu.collation_matin.is_some() || menu.diner.is_some() || menu.collation_apres_midi.is_some();
             if has_menu {
                 html.push_str(r#"<div style="margin-bottom:12px;font-size:13px">"#);
 
-                if let Some(ref m) = menu.collation_matin {
+                if let Some(ref m) = meal_text(locale, menu, "collation_matin", menu.collation_matin.as_deref()) {
                     if !m.trim().is_empty() {
                         html.push_str(&format!(
                             r#"<div style="background:#fef3c7;border:1px solid #fcd34d;border-radius:6px;padding:10px;margin-bottom:8px">
-                            <span style="color:#d97706;font-weight:600">🌅 Collation matin :</span> <span style="color:#92400e">{}</span>
+                            <span style="color:#d97706;font-weight:600">🌅 {}</span> <span style="color:#92400e">{}</span>
                             </div>"#,
+                            tr(locale, "journal.collation_matin", &[]),
                             m
                         ));
                     }
                 }
 
-                if let Some(ref m) = menu.diner {
+                if let Some(ref m) = meal_text(locale, menu, "diner", menu.diner.as_deref()) {
                     if !m.trim().is_empty() {
                         html.push_str(&format!(
                             r#"<div style="background:#fed7aa;border:1px solid #fdba74;border-radius:6px;padding:10px;margin-bottom:8px">
-                            <span style="color:#c2410c;font-weight:600">🍽️ Dîner :</span> <span style="color:#7c2d12">{}</span>
+                            <span style="color:#c2410c;font-weight:600">🍽️ {}</span> <span style="color:#7c2d12">{}</span>
                             </div>"#,
+                            tr(locale, "journal.diner", &[]),
                             m
                         ));
                     }
                 }
 
-                if let Some(ref m) = menu.collation_apres_midi {
+                if let Some(ref m) = meal_text(locale, menu, "collation_apres_midi", menu.collation_apres_midi.as_deref()) {
                     if !m.trim().is_empty() {
                         html.push_str(&format!(
                             r#"<div style="background:#e9d5ff;border:1px solid #d8b4fe;border-radius:6px;padding:10px;margin-bottom:8px">
-                            <span style="color:#9333ea;font-weight:600">🌙 Collation après-midi :</span> <span style="color:#5b21b6">{}</span>
+                            <span style="color:#9333ea;font-weight:600">🌙 {}</span> <span style="color:#5b21b6">{}</span>
                             </div>"#,
+                            tr(locale, "journal.collation_apres_midi", &[]),
                             m
                         ));
                     }
@@ -768,22 +1246,28 @@ fn build_journal_email_html(
         if let Some(theme) = get_theme_for_date(entry.date, themes) {
             html.push_str(&format!(
                 r#"<div style="background:#f3e8ff;border:1px solid #e9d5ff;border-radius:6px;padding:12px;margin-bottom:12px;font-size:14px">
-                <span style="color:#7c3aed;font-weight:600">📚 Thème :</span> <span style="color:#6d28d9">{}</span>
+                <span style="color:#7c3aed;font-weight:600">📚 {}</span> <span style="color:#6d28d9">{}</span>
                 </div>"#,
+                tr(locale, "journal.theme", &[]),
                 theme
             ));
         }
 
         html.push_str(&format!(
             r#"<table style="width:100%;font-size:14px;color:#374151;border-collapse:collapse">
-                <tr><td style="padding:5px 8px 5px 0;width:140px;color:#6b7280"><strong>Appétit</strong></td><td style="padding:5px 0">{appetit}</td></tr>
-                <tr><td style="padding:5px 8px 5px 0;color:#6b7280"><strong>Humeur</strong></td><td style="padding:5px 0">{humeur}</td></tr>
-                <tr><td style="padding:5px 8px 5px 0;color:#6b7280"><strong>Sommeil</strong></td><td style="padding:5px 0">{sommeil}</td></tr>
-                <tr><td style="padding:5px 8px 5px 0;color:#6b7280"><strong>Santé</strong></td><td style="padding:5px 0">{sante}</td></tr>
-                <tr><td style="padding:5px 8px 5px 0;color:#6b7280"><strong>Médicaments</strong></td><td style="padding:5px 0">{med}</td></tr>
+                <tr><td style="padding:5px 8px 5px 0;width:140px;color:#6b7280"><strong>{appetit_label}</strong></td><td style="padding:5px 0">{appetit}</td></tr>
+                <tr><td style="padding:5px 8px 5px 0;color:#6b7280"><strong>{humeur_label}</strong></td><td style="padding:5px 0">{humeur}</td></tr>
+                <tr><td style="padding:5px 8px 5px 0;color:#6b7280"><strong>{sommeil_label}</strong></td><td style="padding:5px 0">{sommeil}</td></tr>
+                <tr><td style="padding:5px 8px 5px 0;color:#6b7280"><strong>{sante_label}</strong></td><td style="padding:5px 0">{sante}</td></tr>
+                <tr><td style="padding:5px 8px 5px 0;color:#6b7280"><strong>{medicaments_label}</strong></td><td style="padding:5px 0">{med}</td></tr>
             </table>"#,
-            appetit = entry.appetit.as_deref().map(fmt_appetit).unwrap_or("—"),
-            humeur  = entry.humeur.as_deref().map(fmt_humeur).unwrap_or("—"),
+            appetit_label = tr(locale, "journal.appetit", &[]),
+            humeur_label = tr(locale, "journal.humeur", &[]),
+            sommeil_label = tr(locale, "journal.sommeil", &[]),
+            sante_label = tr(locale, "journal.sante", &[]),
+            medicaments_label = tr(locale, "journal.medicaments", &[]),
+            appetit = entry.appetit.as_deref().map(|v| fmt_appetit(locale, v)).unwrap_or("—"),
+            humeur  = entry.humeur.as_deref().map(|v| fmt_humeur(locale, v)).unwrap_or("—"),
             sommeil = sommeil,
             sante   = opt_str(entry.sante.as_deref()),
             med     = opt_str(entry.medicaments.as_deref()),
@@ -793,8 +1277,9 @@ fn build_journal_email_html(
             if !food_note.trim().is_empty() {
                 html.push_str(&format!(
                     r#"<div style="background:#f0fdf4;border-left:3px solid #16a34a;padding:10px 12px;margin-top:12px;font-size:13px;border-radius:0 4px 4px 0">
-                    <strong style="color:#15803d">📝 Note alimentaire :</strong><br><span style="color:#374151">{}</span>
+                    <strong style="color:#15803d">📝 {}</strong><br><span style="color:#374151">{}</span>
                 </div>"#,
+                    tr(locale, "journal.food_note", &[]),
                     food_note
                 ));
             }
@@ -804,8 +1289,9 @@ fn build_journal_email_html(
             if !msg.trim().is_empty() {
                 html.push_str(&format!(
                     r#"<div style="background:#eff6ff;border-left:3px solid #2563eb;padding:8px 12px;margin-top:10px;font-size:13px;border-radius:0 4px 4px 0">
-                    <strong style="color:#1d4ed8">💬 Message de l'éducatrice :</strong><br><span style="color:#374151">{msg}</span>
-                </div>"#
+                    <strong style="color:#1d4ed8">💬 {label}</strong><br><span style="color:#374151">{msg}</span>
+                </div>"#,
+                    label = tr(locale, "journal.educator_message", &[]),
                 ));
             }
         }
@@ -814,52 +1300,106 @@ fn build_journal_email_html(
             if !obs.trim().is_empty() {
                 html.push_str(&format!(
                     r#"<div style="background:#f0fdf4;border-left:3px solid #16a34a;padding:8px 12px;margin-top:8px;font-size:13px;border-radius:0 4px 4px 0">
-                    <strong style="color:#15803d">📝 Observations :</strong><br><span style="color:#374151">{obs}</span>
-                </div>"#
+                    <strong style="color:#15803d">📝 {label}</strong><br><span style="color:#374151">{obs}</span>
+                </div>"#,
+                    label = tr(locale, "journal.observations", &[]),
                 ));
             }
         }
 
+        html.push_str(&photos_html(locale, &photos_for(photos, entry.child_id, entry.date)));
         html.push_str("</div>");
     }
 
     html.push_str(&format!(
         r#"<div style="margin-top:24px;padding-top:16px;border-top:1px solid #e5e7eb;font-size:12px;color:#6b7280">
-        <p style="margin:0">Cordialement,<br><strong>{}</strong></p>
+        <p style="margin:0">{}<br><strong>{}</strong></p>
     </div>
     </div></body></html>"#,
+        tr(locale, "common.regards", &[]),
         garderie_name
     ));
 
     html
 }
 
+/// Photos an email shows for `entries`, to attach inline; absent days show none.
+fn shown_photos<'a>(
+    photos: &'a [JournalPhoto],
+    entries: impl Iterator<Item = &'a DailyJournal>,
+) -> Vec<&'a JournalPhoto> {
+    entries
+        .filter(|e| !e.absent)
+        .flat_map(|e| photos_for(photos, e.child_id, e.date))
+        .collect()
+}
+
+/// Inline thumbnails of one child's day, shown under its entry.
+fn photos_html(locale: Locale, photos: &[&JournalPhoto]) -> String {
+    if photos.is_empty() {
+        return String::new();
+    }
+    let mut html = format!(
+        r#"<div style="margin-top:8px"><strong style="color:#374151;font-size:13px">📷 {}</strong><div style="margin-top:6px">"#,
+        tr(locale, "journal.photos", &[])
+    );
+    for photo in photos {
+        html.push_str(&format!(
+            r#"<img src="cid:{}" alt="{}" width="160" style="width:160px;max-width:48%;height:auto;border-radius:6px;margin:0 6px 6px 0;vertical-align:top">"#,
+            photo.cid,
+            photo.caption.as_deref().map(escape_html).unwrap_or_default()
+        ));
+    }
+    html.push_str("</div></div>");
+    html
+}
+
 /// Builds email HTML for multiple children's journals (grouped by parent).
 /// Each child is displayed in its own section.
 /// Structure: Header > Theme (if present) > Menu (if present) > Each child's data
+/// `amendment` is the note of a correction, shown above the entries when a sent
+/// journal is emailed again.
+#[allow(clippy::too_many_arguments)]
 fn build_journal_email_html_multi(
     children_entries: &[(String, String, crate::models::journal::DailyJournal)],
     today: NaiveDate,
     garderie_name: &str,
     themes: &[ThemeActivity],
     menu: Option<&MenuDuJour>,
+    days: &std::collections::HashMap<Uuid, ChildDay>,
+    photos: &[JournalPhoto],
+    locale: Locale,
+    amendment: Option<&str>,
 ) -> String {
-    let period = format!("Journal du {} — {}", fmt_date_fr(today), garderie_name);
+    let period = tr(locale, "journal.period_day", &[("date", &locale.long_date(today)), ("garderie", garderie_name)]);
 
     let mut html = format!(
-        r#"<html><body style="font-family:sans-serif;max-width:800px;margin:auto;background:#f9fafb">
+        r#"<html lang="{lang}"><body style="font-family:sans-serif;max-width:800px;margin:auto;background:#f9fafb">
         <div style="background:white;border-radius:8px;padding:24px;box-shadow:0 1px 3px rgba(0,0,0,0.1)">
-            <h2 style="color:#1f2937;margin-bottom:8px">Journal de bord</h2>
+            <h2 style="color:#1f2937;margin-bottom:8px">{title}</h2>
             <p style="color:#6b7280;margin-bottom:24px;font-size:14px">{period}</p>"#,
+        title = tr(locale, "journal.title", &[]),
         period = period,
+        lang = locale.tag(),
     );
 
+    if let Some(note) = amendment {
+        html.push_str(&format!(
+            r#"<div style="background:#fef2f2;border:2px solid #fca5a5;border-radius:8px;padding:16px;margin-bottom:24px;font-size:14px">
+            <span style="color:#b91c1c;font-weight:700">✏️ {}</span> <span style="color:#7f1d1d">{}</span>
+            </div>"#,
+            tr(locale, "journal.amended", &[]),
+            escape_html(note).replace('\n', "<br>")
+        ));
+    }
+
     // ─── THEME SECTION (displayed once at top) ───
     if let Some(theme) = get_theme_for_date(today, themes) {
         html.push_str(&format!(
             r#"<div style="background:#f3e8ff;border:2px solid #d8b4fe;border-radius:8px;padding:16px;margin-bottom:24px;font-size:14px">
-            <span style="color:#7c3aed;font-weight:700;font-size:15px">📚 Thème de la semaine :</span> <span style="color:#5b21b6;font-weight:600">{}</span>
+            <span style="color:#7c3aed;font-weight:700;font-size:15px">📚 {}</span> <span style="color:#5b21b6;font-weight:600">{}</span>
             </div>"#,
+            tr(locale, "journal.theme_week", &[]),
             theme
         ));
     }
@@ -871,46 +1411,53 @@ fn build_journal_email_html_multi(
             if !w.trim().is_empty() {
                 html.push_str(&format!(
                     r#"<div style="background:#dbeafe;border:2px solid #3b82f6;border-radius:8px;padding:16px;margin-bottom:16px;font-size:14px">
-                    <span style="color:#1d4ed8;font-weight:700;font-size:15px">🌤️ Météo :</span> <span style="color:#1e3a8a;font-weight:600">{}</span>
+                    <span style="color:#1d4ed8;font-weight:700;font-size:15px">🌤️ {}</span> <span style="color:#1e3a8a;font-weight:600">{}</span>
                     </div>"#,
-                    fmt_temperature(w)
+                    tr(locale, "journal.weather", &[]),
+                        fmt_temperature(locale, w)
                 ));
             }
         }
 
-        let has_menu = menu.collation_matin.is_some() || menu.diner.is_some() || menu.collation_apres_midi.is_some();
+        let has_menu = !menu.items.is_empty() || menu.collation_matin.is_some() || menu.diner.is_some() || menu.collation_apres_midi.is_some();
         if has_menu {
             html.push_str(r#"<div style="background:#fffbeb;border:2px solid #fcd34d;border-radius:8px;padding:16px;margin-bottom:24px">"#);
-            html.push_str(r#"<div style="color:#d97706;font-weight:700;font-size:15px;margin-bottom:12px">🍽️ Menu du jour</div>"#);
+            html.push_str(&format!(
+                r#"<div style="color:#d97706;font-weight:700;font-size:15px;margin-bottom:12px">🍽️ {}</div>"#,
+                tr(locale, "journal.menu", &[])
+            ));
 
-            if let Some(ref m) = menu.collation_matin {
+            if let Some(ref m) = meal_text(locale, menu, "collation_matin", menu.collation_matin.as_deref()) {
                 if !m.trim().is_empty() {
                     html.push_str(&format!(
                         r#"<div style="background:#fef3c7;border-left:4px solid #fbbf24;padding:10px 12px;margin-bottom:8px;border-radius:4px">
-                        <span style="color:#d97706;font-weight:600">🌅 Collation matin :</span> <span style="color:#92400e">{}</span>
+                        <span style="color:#d97706;font-weight:600">🌅 {}</span> <span style="color:#92400e">{}</span>
                         </div>"#,
+                        tr(locale, "journal.collation_matin", &[]),
                         m
                     ));
                 }
             }
 
-            if let Some(ref m) = menu.diner {
+            if let Some(ref m) = meal_text(locale, menu, "diner", menu.diner.as_deref()) {
                 if !m.trim().is_empty() {
                     html.push_str(&format!(
                         r#"<div style="background:#fed7aa;border-left:4px solid #fb923c;padding:10px 12px;margin-bottom:8px;border-radius:4px">
-                        <span style="color:#c2410c;font-weight:600">🍽️ Dîner :</span> <span style="color:#7c2d12">{}</span>
+                        <span style="color:#c2410c;font-weight:600">🍽️ {}</span> <span style="color:#7c2d12">{}</span>
                         </div>"#,
+                        tr(locale, "journal.diner", &[]),
                         m
                     ));
                 }
             }
 
-            if let Some(ref m) = menu.collation_apres_midi {
+            if let Some(ref m) = meal_text(locale, menu, "collation_apres_midi", menu.collation_apres_midi.as_deref()) {
                 if !m.trim().is_empty() {
                     html.push_str(&format!(
                         r#"<div style="background:#e9d5ff;border-left:4px solid #d8b4fe;padding:10px 12px;margin-bottom:8px;border-radius:4px">
-                        <span style="color:#9333ea;font-weight:600">🌙 Collation après-midi :</span> <span style="color:#5b21b6">{}</span>
+                        <span style="color:#9333ea;font-weight:600">🌙 {}</span> <span style="color:#5b21b6">{}</span>
                         </div>"#,
+                        tr(locale, "journal.collation_apres_midi", &[]),
                         m
                     ));
                 }
@@ -929,19 +1476,23 @@ fn build_journal_email_html_multi(
             last = child_last,
         ));
 
-        let date_fr = fmt_date_fr(entry.date);
+        let date_fr = locale.long_date(entry.date);
 
         if entry.absent {
             html.push_str(&format!(
                 r#"<div style="border:1px solid #e5e7eb;border-radius:6px;padding:16px;margin-bottom:12px;background:#f9fafb">
                 <h4 style="color:#6b7280;margin:0 0 8px 0;font-size:14px">{date}</h4>
-                <span style="display:inline-block;background:#fee2e2;color:#b91c1c;font-size:13px;font-weight:600;padding:4px 12px;border-radius:20px">🏠 Absent ce jour</span>
+                <span style="display:inline-block;background:#fee2e2;color:#b91c1c;font-size:13px;font-weight:600;padding:4px 12px;border-radius:20px">🏠 {absent}</span>
                 </div>"#,
-                date = date_fr
+                date = date_fr,
+                absent = tr(locale, "journal.absent", &[]),
             ));
         } else {
-            let sommeil = match entry.sommeil_minutes {
-                Some(m) if m > 0 => format!("{} min", m),
+            let day = days.get(&entry.child_id);
+            let events = day.map(|d| d.events.as_slice()).unwrap_or_default();
+            // Naps logged as events stand in for the sleep field when it was left empty
+            let sommeil = match entry.sommeil_minutes.map(i64::from).unwrap_or_else(|| sleep_minutes(events)) {
+                m if m > 0 => format!("{} min", m),
                 _ => "—".to_string(),
             };
 
@@ -951,29 +1502,87 @@ fn build_journal_email_html_multi(
                 date = date_fr,
             ));
 
+            // Allergen warning when today's menu contains something on the child's profile
+            let child_allergies = day.map(|d| d.allergies.as_slice()).unwrap_or_default();
+            let flagged: Vec<String> = menu
+                .map(|m| m.items.as_slice())
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|item| {
+                    let hits: Vec<&str> = item
+                        .allergens
+                        .iter()
+                        .filter(|a| child_allergies.contains(a))
+                        .map(|a| fmt_allergen(locale, a))
+                        .collect();
+                    (!hits.is_empty()).then(|| format!("{} ({})", item.name, hits.join(", ")))
+                })
+                .collect();
+            if !flagged.is_empty() {
+                html.push_str(&format!(
+                    r#"<div style="background:#fef2f2;border:1px solid #fca5a5;border-radius:6px;padding:10px 12px;margin-bottom:12px;font-size:13px">
+                    <strong style="color:#b91c1c">⚠️ {}</strong> <span style="color:#7f1d1d">{}</span>
+                </div>"#,
+                    tr(locale, "journal.allergens", &[]),
+                    flagged.join(" · ")
+                ));
+            }
+
             // Main data table
             html.push_str(&format!(
                 r#"<table style="width:100%;font-size:13px;color:#374151;border-collapse:collapse;margin-bottom:12px">
-                    <tr><td style="padding:4px 8px 4px 0;width:120px;color:#6b7280"><strong>Appétit</strong></td><td style="padding:4px 0">{appetit}</td></tr>
-                    <tr><td style="padding:4px 8px 4px 0;color:#6b7280"><strong>Humeur</strong></td><td style="padding:4px 0">{humeur}</td></tr>
-                    <tr><td style="padding:4px 8px 4px 0;color:#6b7280"><strong>Sommeil</strong></td><td style="padding:4px 0">{sommeil}</td></tr>
-                    <tr><td style="padding:4px 8px 4px 0;color:#6b7280"><strong>Santé</strong></td><td style="padding:4px 0">{sante}</td></tr>
-                    <tr><td style="padding:4px 8px 4px 0;color:#6b7280"><strong>Médicaments</strong></td><td style="padding:4px 0">{med}</td></tr>
+                    <tr><td style="padding:4px 8px 4px 0;width:120px;color:#6b7280"><strong>{appetit_label}</strong></td><td style="padding:4px 0">{appetit}</td></tr>
+                    <tr><td style="padding:4px 8px 4px 0;color:#6b7280"><strong>{humeur_label}</strong></td><td style="padding:4px 0">{humeur}</td></tr>
+                    <tr><td style="padding:4px 8px 4px 0;color:#6b7280"><strong>{sommeil_label}</strong></td><td style="padding:4px 0">{sommeil}</td></tr>
+                    <tr><td style="padding:4px 8px 4px 0;color:#6b7280"><strong>{sante_label}</strong></td><td style="padding:4px 0">{sante}</td></tr>
+                    <tr><td style="padding:4px 8px 4px 0;color:#6b7280"><strong>{medicaments_label}</strong></td><td style="padding:4px 0">{med}</td></tr>
                 </table>"#,
-                appetit = entry.appetit.as_deref().map(fmt_appetit).unwrap_or("—"),
-                humeur  = entry.humeur.as_deref().map(fmt_humeur).unwrap_or("—"),
+                appetit_label = tr(locale, "journal.appetit", &[]),
+                humeur_label = tr(locale, "journal.humeur", &[]),
+                sommeil_label = tr(locale, "journal.sommeil", &[]),
+                sante_label = tr(locale, "journal.sante", &[]),
+                medicaments_label = tr(locale, "journal.medicaments", &[]),
+                appetit = entry.appetit.as_deref().map(|v| fmt_appetit(locale, v)).unwrap_or("—"),
+                humeur  = entry.humeur.as_deref().map(|v| fmt_humeur(locale, v)).unwrap_or("—"),
                 sommeil = sommeil,
                 sante   = opt_str(entry.sante.as_deref()),
                 med     = opt_str(entry.medicaments.as_deref()),
             ));
 
+            // Timeline of the day's events
+            if !events.is_empty() {
+                html.push_str(&format!(
+                    r#"<div style="background:#f8fafc;border-left:3px solid #64748b;padding:10px 12px;margin-bottom:12px;font-size:13px;border-radius:0 4px 4px 0">
+                    <strong style="color:#334155">🕒 {}</strong>"#,
+                    tr(locale, "journal.timeline", &[])
+                ));
+                for event in events {
+                    let time = match event.end_time {
+                        Some(end) => format!("{}–{}", event.start_time.format("%H:%M"), end.format("%H:%M")),
+                        None => event.start_time.format("%H:%M").to_string(),
+                    };
+                    let notes = event
+                        .notes
+                        .as_deref()
+                        .filter(|n| !n.trim().is_empty())
+                        .map(|n| format!(" — {n}"))
+                        .unwrap_or_default();
+                    html.push_str(&format!(
+                        r#"<br><span style="color:#64748b">{time}</span> <span style="color:#374151">{label}{notes}</span>"#,
+                        label = fmt_event_type(locale, &event.event_type),
+                    ));
+                }
+                html.push_str("</div>");
+            }
+
             // Note alimentaire (food note from child's journal.menu)
             if let Some(ref food_note) = entry.menu {
                 if !food_note.trim().is_empty() {
                     html.push_str(&format!(
                         r#"<div style="background:#f0fdf4;border-left:3px solid #16a34a;padding:10px 12px;margin-bottom:12px;font-size:13px;border-radius:0 4px 4px 0">
-                        <strong style="color:#15803d">📝 Note alimentaire :</strong><br><span style="color:#374151">{}</span>
+                        <strong style="color:#15803d">📝 {}</strong><br><span style="color:#374151">{}</span>
                     </div>"#,
+                        tr(locale, "journal.food_note", &[]),
                         food_note
                     ));
                 }
@@ -983,8 +1592,9 @@ fn build_journal_email_html_multi(
                 if !msg.trim().is_empty() {
                     html.push_str(&format!(
                         r#"<div style="background:#eff6ff;border-left:3px solid #2563eb;padding:10px 12px;margin-bottom:12px;font-size:13px;border-radius:0 4px 4px 0">
-                        <strong style="color:#1d4ed8">💬 Message de l'éducatrice :</strong><br><span style="color:#374151">{}</span>
+                        <strong style="color:#1d4ed8">💬 {}</strong><br><span style="color:#374151">{}</span>
                     </div>"#,
+                        tr(locale, "journal.educator_message", &[]),
                         msg
                     ));
                 }
@@ -994,25 +1604,145 @@ fn build_journal_email_html_multi(
                 if !obs.trim().is_empty() {
                     html.push_str(&format!(
                         r#"<div style="background:#fef2f2;border-left:3px solid #dc2626;padding:10px 12px;font-size:13px;border-radius:0 4px 4px 0">
-                        <strong style="color:#991b1b">🔍 Observations :</strong><br><span style="color:#374151">{}</span>
+                        <strong style="color:#991b1b">🔍 {}</strong><br><span style="color:#374151">{}</span>
                     </div>"#,
+                        tr(locale, "journal.observations", &[]),
                         obs
                     ));
                 }
             }
 
+            html.push_str(&photos_html(locale, &photos_for(photos, entry.child_id, entry.date)));
             html.push_str("</div>");
         }
     }
 
     html.push_str(&format!(
         r#"<div style="margin-top:24px;padding-top:16px;border-top:1px solid #e5e7eb;font-size:12px;color:#6b7280">
-        <p style="margin:0">Cordialement,<br><strong>{}</strong></p>
+        <p style="margin:0">{}<br><strong>{}</strong></p>
     </div>
     </div></body></html>"#,
+        tr(locale, "common.regards", &[]),
         garderie_name
     ));
 
     html
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveTime, Utc};
+
+    fn event(event_type: &str, start: (u32, u32), end: Option<(u32, u32)>) -> JournalEvent {
+        JournalEvent {
+            id: Uuid::new_v4(),
+            child_id: Uuid::nil(),
+            date: NaiveDate::from_ymd_opt(2026, 10, 16).unwrap(),
+            event_type: event_type.to_string(),
+            start_time: NaiveTime::from_hms_opt(start.0, start.1, 0).unwrap(),
+            end_time: end.map(|(h, m)| NaiveTime::from_hms_opt(h, m, 0).unwrap()),
+            notes: None,
+            created_by: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn sleep_counts_finished_naps_only() {
+        let events = [
+            event("couche", (10, 15), None),
+            event("sieste", (12, 30), Some((14, 0))),
+            event("activite", (14, 30), Some((15, 30))),
+            event("sieste", (16, 0), None),
+        ];
+        assert_eq!(sleep_minutes(&events), 90);
+    }
+
+    #[test]
+    fn events_need_a_known_type_and_ordered_times() {
+        let fields = |event_type: &str, end: Option<NaiveTime>| JournalEventFields {
+            event_type: event_type.to_string(),
+            start_time: NaiveTime::from_hms_opt(12, 30, 0).unwrap(),
+            end_time: end,
+            notes: None,
+        };
+        assert!(validate_event(&fields("sieste", NaiveTime::from_hms_opt(14, 0, 0))).is_ok());
+        assert!(matches!(validate_event(&fields("bain", None)), Err(JournalEventError::InvalidType(_))));
+        assert!(matches!(
+            validate_event(&fields("sieste", NaiveTime::from_hms_opt(11, 0, 0))),
+            Err(JournalEventError::EndBeforeStart)
+        ));
+    }
+
+    fn journal(child_id: Uuid, date: NaiveDate, absent: bool) -> DailyJournal {
+        DailyJournal {
+            id: Uuid::new_v4(),
+            child_id,
+            date,
+            temperature: None,
+            menu: None,
+            appetit: None,
+            humeur: None,
+            sommeil_minutes: None,
+            absent,
+            sante: None,
+            medicaments: None,
+            message_educatrice: None,
+            observations: None,
+            created_by: Uuid::nil(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            sent_at: Some(Utc::now()),
+            amended_at: Some(Utc::now()),
+        }
+    }
+
+    #[test]
+    fn amended_digest_shows_the_escaped_note() {
+        let date = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let entries = [("Emma".to_string(), "Tremblay".to_string(), journal(Uuid::nil(), date, false))];
+        let days = std::collections::HashMap::new();
+        let render = |note| {
+            build_journal_email_html_multi(&entries, date, "Les Lucioles", &[], None, &days, &[], Locale::Fr, note)
+        };
+
+        let html = render(Some("Sieste de 2 h <pas 1 h>"));
+        assert!(html.contains("Ce journal a été corrigé"));
+        assert!(html.contains("Sieste de 2 h &lt;pas 1 h&gt;"));
+        assert!(!render(None).contains("Ce journal a été corrigé"));
+    }
+
+    #[test]
+    fn digest_embeds_the_photos_of_present_children() {
+        let date = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let (emma, leo) = (Uuid::new_v4(), Uuid::new_v4());
+        let photo = |child_id, date, caption: &str| JournalPhoto {
+            child_id,
+            date,
+            cid: crate::services::journal_photos::photo_cid(Uuid::new_v4()),
+            caption: Some(caption.to_string()),
+            bytes: vec![0xFF, 0xD8],
+        };
+        let photos = [
+            photo(emma, date, "Bricolage <citrouille>"),
+            photo(emma, date.pred_opt().unwrap(), "Hier"),
+            photo(leo, date, "Parc"),
+        ];
+        let entries = [
+            ("Emma".to_string(), "Tremblay".to_string(), journal(emma, date, false)),
+            ("Léo".to_string(), "Tremblay".to_string(), journal(leo, date, true)),
+        ];
+        let html = build_journal_email_html_multi(
+            &entries, date, "Les Lucioles", &[], None, &std::collections::HashMap::new(), &photos, Locale::Fr, None,
+        );
+
+        assert!(html.contains(&format!("cid:{}", photos[0].cid)));
+        assert!(html.contains("Bricolage &lt;citrouille&gt;"));
+        assert!(!html.contains(&photos[1].cid));
+        assert!(!html.contains(&photos[2].cid));
+        let shown = shown_photos(&photos, entries.iter().map(|(_, _, e)| e));
+        assert_eq!(shown.iter().map(|p| &p.cid).collect::<Vec<_>>(), vec![&photos[0].cid]);
+    }
+}
@@ -0,0 +1,166 @@
+use std::path::Path;
+
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::tenant::schema_name,
+    services::{
+        antivirus::SCAN_INFECTED,
+        consents::photo_consent_sql,
+        encryption::{self, KeyRing},
+    },
+};
+
+/// Photos embedded per child and per day; the others stay in the gallery.
+pub const MAX_PHOTOS_PER_DAY: i64 = 4;
+
+/// A thumbnail of the day, attached inline to the journal email under `cid`.
+#[derive(Debug, Clone)]
+pub struct JournalPhoto {
+    pub child_id: Uuid,
+    pub date: NaiveDate,
+    pub cid: String,
+    pub caption: Option<String>,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(sqlx::FromRow)]
+struct PhotoRow {
+    id: Uuid,
+    child_id: Uuid,
+    day: NaiveDate,
+    caption: Option<String>,
+    thumbnail_path: String,
+    is_encrypted: bool,
+    thumbnail_encryption_iv: Option<Vec<u8>>,
+    thumbnail_encryption_tag: Option<Vec<u8>>,
+    key_version: i32,
+    is_redacted: bool,
+}
+
+/// Content-ID of a media thumbnail; siblings tagged on the same photo share it.
+pub fn photo_cid(media_id: Uuid) -> String {
+    format!("journal-{media_id}@minispace.app")
+}
+
+/// Photos of `date` for one child, in the order they were taken.
+pub fn photos_for(photos: &[JournalPhoto], child_id: Uuid, date: NaiveDate) -> Vec<&JournalPhoto> {
+    photos.iter().filter(|p| p.child_id == child_id && p.date == date).collect()
+}
+
+/// Thumbnails of the photos the children were tagged in between `from` and `to`
+/// (inclusive). Private media are left out; photos showing another child without
+/// photo consent come as their blurred copy, or not at all when there is none.
+/// Files that cannot be read are skipped: the journal is still sent without them.
+pub async fn load(
+    pool: &PgPool,
+    config: &Config,
+    tenant: &str,
+    child_ids: &[Uuid],
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<JournalPhoto> {
+    if child_ids.is_empty() {
+        return Vec::new();
+    }
+    let schema = schema_name(tenant);
+    // Another child without photo consent: the blurred copy is sent instead, if any
+    let unconsented = format!(
+        r#"EXISTS (
+               SELECT 1 FROM "{schema}".media_children other
+               WHERE other.media_id = m.id AND other.child_id <> mc.child_id
+                 AND NOT {consent}
+           )"#,
+        consent = photo_consent_sql(&schema, "other.child_id"),
+    );
+    let rows = sqlx::query_as::<_, PhotoRow>(&format!(
+        r#"SELECT id, child_id, day, caption, thumbnail_path, is_encrypted,
+                  thumbnail_encryption_iv, thumbnail_encryption_tag, key_version, is_redacted
+           FROM (
+               SELECT m.id, mc.child_id, m.created_at::DATE AS day, m.caption,
+                      CASE WHEN u.hidden THEN r.redacted_path ELSE m.thumbnail_path END AS thumbnail_path,
+                      u.hidden OR m.is_encrypted AS is_encrypted,
+                      CASE WHEN u.hidden THEN r.encryption_iv ELSE m.thumbnail_encryption_iv END AS thumbnail_encryption_iv,
+                      CASE WHEN u.hidden THEN r.encryption_tag ELSE m.thumbnail_encryption_tag END AS thumbnail_encryption_tag,
+                      CASE WHEN u.hidden THEN r.key_version ELSE m.key_version END AS key_version,
+                      u.hidden AS is_redacted,
+                      ROW_NUMBER() OVER (PARTITION BY mc.child_id, m.created_at::DATE ORDER BY m.created_at) AS n
+               FROM "{schema}".media m
+               JOIN "{schema}".media_children mc ON mc.media_id = m.id
+               LEFT JOIN "{schema}".media_redactions r ON r.media_id = m.id
+               CROSS JOIN LATERAL (SELECT {unconsented} AS hidden) u
+               WHERE mc.child_id = ANY($1)
+                 AND m.created_at >= $2 AND m.created_at < $3
+                 AND m.media_type = 'photo'
+                 AND m.visibility <> 'private'
+                 AND m.is_deleted = FALSE
+                 AND m.scan_status <> $4
+                 AND m.thumbnail_path IS NOT NULL
+                 AND (NOT u.hidden OR r.redacted_path IS NOT NULL)
+           ) p
+           WHERE n <= $5
+           ORDER BY child_id, day, n"#
+    ))
+    .bind(child_ids)
+    .bind(from)
+    .bind(to + chrono::Duration::days(1))
+    .bind(SCAN_INFECTED)
+    .bind(MAX_PHOTOS_PER_DAY)
+    .fetch_all(pool)
+    .await;
+    let rows = match rows {
+        Ok(rows) => rows,
+        Err(e) => {
+            warn!("Journal photos of '{tenant}' unavailable: {e}");
+            return Vec::new();
+        }
+    };
+    if rows.is_empty() {
+        return Vec::new();
+    }
+
+    let keys = KeyRing::from_config(config);
+    let mut photos = Vec::with_capacity(rows.len());
+    for row in rows {
+        let bytes = match read_thumbnail(&config.media_dir, keys.as_ref().ok(), tenant, &row).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!("Journal photo {} of '{tenant}' skipped: {e}", row.id);
+                continue;
+            }
+        };
+        photos.push(JournalPhoto {
+            child_id: row.child_id,
+            date: row.day,
+            // A sibling in the same email may get the photo unblurred
+            cid: if row.is_redacted { photo_cid(row.id).replacen('@', "-blurred@", 1) } else { photo_cid(row.id) },
+            caption: row.caption,
+            bytes,
+        });
+    }
+    photos
+}
+
+async fn read_thumbnail(
+    media_dir: &str,
+    keys: Option<&KeyRing>,
+    tenant: &str,
+    row: &PhotoRow,
+) -> anyhow::Result<Vec<u8>> {
+    let bytes = tokio::fs::read(Path::new(media_dir).join(&row.thumbnail_path)).await?;
+    if !row.is_encrypted {
+        return Ok(bytes);
+    }
+    let (Some(iv), Some(tag)) = (&row.thumbnail_encryption_iv, &row.thumbnail_encryption_tag) else {
+        anyhow::bail!("missing encryption metadata");
+    };
+    let Some(keys) = keys else {
+        anyhow::bail!("encryption keys not configured");
+    };
+    let key = keys.tenant_key(row.key_version, tenant)?;
+    encryption::decrypt_file(&bytes, iv, tag, &key)
+}
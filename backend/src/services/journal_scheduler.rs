@@ -5,6 +5,8 @@ use std::collections::HashMap;
 use tokio::sync::Mutex;
 use tracing::{info, warn};
 
+use crate::config::Config;
+use crate::db::tenant_db::TenantDb;
 use crate::services::email::EmailService;
 use crate::services::journal::JournalService;
 
@@ -12,7 +14,7 @@ use crate::services::journal::JournalService;
 /// for any tenant whose `journal_auto_send_time` matches the current local time.
 /// Weekends are skipped automatically.
 /// Uses a HashMap to track the last execution minute per tenant to prevent duplicate sends.
-pub fn start(pool: PgPool, email: Option<Arc<EmailService>>) {
+pub fn start(pool: PgPool, email: Option<Arc<EmailService>>, config: Arc<Config>) {
     tokio::spawn(async move {
         // Track the last minute we executed for each tenant (tenant_slug -> "HH:MM")
         let last_executed = Arc::new(Mutex::new(HashMap::new()));
@@ -22,6 +24,9 @@ pub fn start(pool: PgPool, email: Option<Arc<EmailService>>) {
             let secs_past = Local::now().second() as u64;
             let sleep_secs = if secs_past == 0 { 60 } else { 60 - secs_past };
             tokio::time::sleep(tokio::time::Duration::from_secs(sleep_secs)).await;
+            let Some(_job) = crate::services::worker::job().await else {
+                break;
+            };
 
             let now = Local::now();
             let weekday = now.weekday();
@@ -51,31 +56,35 @@ pub fn start(pool: PgPool, email: Option<Arc<EmailService>>) {
             let mut last_exec = last_executed.lock().await;
 
             for (slug, send_time) in tenants {
-                if send_time == current_time {
-                    // Check if we've already executed for this tenant at this minute
-                    let last = last_exec.get(&slug);
-                    if last == Some(&current_time) {
-                        // Already executed for this tenant at this minute, skip
-                        continue;
-                    }
+                TenantDb::scope(slug.clone(), async {
+                    if send_time == current_time {
+                        // Check if we've already executed for this tenant at this minute
+                        let last = last_exec.get(&slug);
+                        if last == Some(&current_time) {
+                            // Already executed for this tenant at this minute, skip
+                            return;
+                        }
 
-                    info!("Journal auto-send: firing for tenant '{}'", slug);
-                    match JournalService::auto_send_today(
-                        &pool,
-                        email.as_deref(),
-                        &slug,
-                        today,
-                    )
-                    .await
-                    {
-                        Ok(n) => {
-                            info!("Journal auto-send: {} email(s) sent for '{}'", n, slug);
-                            // Mark this tenant as executed at this minute
-                            last_exec.insert(slug.clone(), current_time.clone());
+                        info!("Journal auto-send: firing for tenant '{}'", slug);
+                        match JournalService::auto_send_today(
+                            &pool,
+                            email.as_deref(),
+                            &config,
+                            &slug,
+                            today,
+                        )
+                        .await
+                        {
+                            Ok(n) => {
+                                info!("Journal auto-send: {} email(s) sent for '{}'", n, slug);
+                                // Mark this tenant as executed at this minute
+                                last_exec.insert(slug.clone(), current_time.clone());
+                            }
+                            Err(e) => warn!("Journal auto-send error for '{}': {}", slug, e),
                         }
-                        Err(e) => warn!("Journal auto-send error for '{}': {}", slug, e),
                     }
-                }
+                })
+                .await;
             }
         }
     });
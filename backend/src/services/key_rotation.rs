@@ -0,0 +1,572 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::db::tenant::schema_name;
+use crate::db::tenant_db::TenantDb;
+use crate::services::encryption::{self, KeyRing};
+
+/// Rows re-encrypted per table and per query; bounds memory, not total work.
+pub const DEFAULT_BATCH_SIZE: i64 = 100;
+
+/// Pause between two passes of the background re-encryption job.
+const JOB_INTERVAL_SECS: u64 = 3600;
+
+/// Encrypted rows still written under an older master key.
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct PendingCounts {
+    pub media: i64,
+    pub documents: i64,
+    pub avatars: i64,
+}
+
+impl PendingCounts {
+    pub fn total(&self) -> i64 {
+        self.media + self.documents + self.avatars
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct RotationStats {
+    pub media: usize,
+    pub documents: usize,
+    pub avatars: usize,
+    pub failed: usize,
+}
+
+impl RotationStats {
+    pub fn rotated(&self) -> usize {
+        self.media + self.documents + self.avatars
+    }
+
+    fn add(&mut self, other: RotationStats) {
+        self.media += other.media;
+        self.documents += other.documents;
+        self.avatars += other.avatars;
+        self.failed += other.failed;
+    }
+}
+
+/// Where a pass over one tenant stands in each table. Batches resume after the last
+/// row seen, so rows that keep failing (missing file, bad IV or tag) are skipped
+/// instead of filling every following batch.
+#[derive(Debug, Default, Clone)]
+pub struct RotationCursor {
+    /// Last `storage_path` seen in `media`, the table being walked by path.
+    media: Option<String>,
+    /// Last id seen in each single-file table.
+    files: HashMap<&'static str, Uuid>,
+    /// Set once a batch came back short in every table: nothing is left after it.
+    done: bool,
+}
+
+impl RotationCursor {
+    /// True once every table was read to its end.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+/// A file re-encrypted under the new key, written next to the original until swapped in.
+/// Once swapped, the original is kept aside until the new metadata is committed.
+struct Staged {
+    tmp: PathBuf,
+    dest: PathBuf,
+    backup: PathBuf,
+    iv: Vec<u8>,
+    tag: Vec<u8>,
+}
+
+impl Staged {
+    async fn write(
+        media_dir: &str,
+        rel_path: &str,
+        iv: &[u8],
+        tag: &[u8],
+        old_key: &[u8; 32],
+        new_key: &[u8; 32],
+    ) -> anyhow::Result<Self> {
+        let dest = Path::new(media_dir).join(rel_path);
+        let ciphertext = tokio::fs::read(&dest).await?;
+        let plaintext = encryption::decrypt_file(&ciphertext, iv, tag, old_key)?;
+        let (ciphertext, iv, tag) = encryption::encrypt_file(&plaintext, new_key)?;
+
+        let with_suffix = |suffix: &str| {
+            let mut path = dest.clone().into_os_string();
+            path.push(suffix);
+            PathBuf::from(path)
+        };
+        let (tmp, backup) = (with_suffix(".rewrap"), with_suffix(".rewrap-old"));
+        tokio::fs::write(&tmp, &ciphertext).await?;
+
+        Ok(Self { tmp, dest, backup, iv, tag })
+    }
+
+    async fn discard(&self) {
+        let _ = tokio::fs::remove_file(&self.tmp).await;
+    }
+
+    /// Put the new ciphertext in place, moving the original aside. On error the
+    /// original is back in place.
+    async fn swap_in(&self) -> std::io::Result<()> {
+        tokio::fs::rename(&self.dest, &self.backup).await?;
+        if let Err(e) = tokio::fs::rename(&self.tmp, &self.dest).await {
+            self.roll_back().await;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Restore the original ciphertext after `swap_in`, the metadata still pointing at it.
+    async fn roll_back(&self) {
+        if let Err(e) = tokio::fs::rename(&self.backup, &self.dest).await {
+            error!("Key rotation could not restore {}: {e}", self.dest.display());
+        }
+    }
+
+    /// Drop the original once the new metadata is committed.
+    async fn finish(&self) {
+        let _ = tokio::fs::remove_file(&self.backup).await;
+    }
+}
+
+/// One row's files plus the columns that record their encryption metadata.
+struct RowRewrap<'a> {
+    table: &'a str,
+    version_col: &'a str,
+    id: Uuid,
+    from_version: i32,
+    /// Column through which other rows share this row's files (duplicate media uploads);
+    /// they are re-pointed at the new key together.
+    shared_by: Option<&'a str>,
+    /// (staged file, iv column, tag column)
+    files: Vec<(Staged, &'a str, &'a str)>,
+}
+
+pub struct KeyRotationService;
+
+impl KeyRotationService {
+    /// Count encrypted rows of a tenant not yet under `current_version`.
+    pub async fn pending(pool: &PgPool, tenant: &str, current_version: i32) -> anyhow::Result<PendingCounts> {
+        let schema = schema_name(tenant);
+        let (media, documents, avatars): (i64, i64, i64) = sqlx::query_as(&format!(
+            r#"SELECT
+                 (SELECT COUNT(*) FROM "{schema}".media
+                  WHERE is_encrypted = TRUE AND key_version <> $1)
+                 + (SELECT COUNT(*) FROM "{schema}".media_redactions
+                  WHERE redacted_path IS NOT NULL AND key_version <> $1),
+                 (SELECT COUNT(*) FROM "{schema}".documents
+                  WHERE is_encrypted = TRUE AND key_version <> $1)
+                 + (SELECT COUNT(*) FROM "{schema}".document_versions
+                  WHERE is_encrypted = TRUE AND key_version <> $1),
+                 (SELECT COUNT(*) FROM "{schema}".children
+                  WHERE avatar_iv IS NOT NULL AND avatar_key_version <> $1)"#
+        ))
+        .bind(current_version)
+        .fetch_one(pool)
+        .await?;
+        Ok(PendingCounts { media, documents, avatars })
+    }
+
+    /// Re-encrypt up to `batch_size` rows per table of one tenant under the current key,
+    /// after the rows `cursor` has already seen; call again until [`RotationCursor::is_done`].
+    ///
+    /// Files are rewritten next to the original then swapped in while the row is locked;
+    /// the originals are kept until the new metadata is committed and put back if
+    /// anything fails, so a file is never left under a key its row does not record.
+    /// Rows that fail (e.g. missing file) are counted in `failed` and retried on the next pass.
+    pub async fn rotate_tenant(
+        pool: &PgPool,
+        media_dir: &str,
+        keys: &KeyRing,
+        tenant: &str,
+        batch_size: i64,
+        cursor: &mut RotationCursor,
+    ) -> anyhow::Result<RotationStats> {
+        let schema = schema_name(tenant);
+        let current = keys.current_version();
+        let (_, new_key) = keys.current_tenant_key(tenant)?;
+        let mut stats = RotationStats::default();
+        // Whether a batch filled up, i.e. rows may remain after it
+        let mut more = false;
+
+        #[derive(sqlx::FromRow)]
+        struct MediaRow {
+            id: Uuid,
+            storage_path: String,
+            thumbnail_path: Option<String>,
+            encryption_iv: Vec<u8>,
+            encryption_tag: Vec<u8>,
+            thumbnail_encryption_iv: Option<Vec<u8>>,
+            thumbnail_encryption_tag: Option<Vec<u8>>,
+            sprite_path: Option<String>,
+            sprite_encryption_iv: Option<Vec<u8>>,
+            sprite_encryption_tag: Option<Vec<u8>>,
+            key_version: i32,
+        }
+
+        let media: Vec<MediaRow> = sqlx::query_as(&format!(
+            r#"SELECT DISTINCT ON (storage_path)
+                      id, storage_path, thumbnail_path, encryption_iv, encryption_tag,
+                      thumbnail_encryption_iv, thumbnail_encryption_tag,
+                      sprite_path, sprite_encryption_iv, sprite_encryption_tag, key_version
+               FROM "{schema}".media
+               WHERE is_encrypted = TRUE AND key_version <> $1
+                 AND encryption_iv IS NOT NULL AND encryption_tag IS NOT NULL
+                 AND ($3::TEXT IS NULL OR storage_path > $3)
+               ORDER BY storage_path, id
+               LIMIT $2"#
+        ))
+        .bind(current)
+        .bind(batch_size)
+        .bind(&cursor.media)
+        .fetch_all(pool)
+        .await?;
+        more |= media.len() as i64 == batch_size;
+        if let Some(last) = media.last() {
+            cursor.media = Some(last.storage_path.clone());
+        }
+
+        for row in media {
+            let result = async {
+                let old_key = keys.tenant_key(row.key_version, tenant)?;
+                let mut files = vec![(
+                    Staged::write(media_dir, &row.storage_path, &row.encryption_iv, &row.encryption_tag, &old_key, &new_key).await?,
+                    "encryption_iv",
+                    "encryption_tag",
+                )];
+                // Derived files: the thumbnail (or video poster) and the video sprite
+                for (path, iv, tag, iv_col, tag_col) in [
+                    (&row.thumbnail_path, &row.thumbnail_encryption_iv, &row.thumbnail_encryption_tag,
+                     "thumbnail_encryption_iv", "thumbnail_encryption_tag"),
+                    (&row.sprite_path, &row.sprite_encryption_iv, &row.sprite_encryption_tag,
+                     "sprite_encryption_iv", "sprite_encryption_tag"),
+                ] {
+                    let (Some(path), Some(iv), Some(tag)) = (path, iv, tag) else { continue };
+                    match Staged::write(media_dir, path, iv, tag, &old_key, &new_key).await {
+                        Ok(staged) => files.push((staged, iv_col, tag_col)),
+                        Err(e) => {
+                            for (staged, _, _) in &files {
+                                staged.discard().await;
+                            }
+                            return Err(e);
+                        }
+                    }
+                }
+                let rewrap = RowRewrap {
+                    table: "media",
+                    version_col: "key_version",
+                    id: row.id,
+                    from_version: row.key_version,
+                    shared_by: Some("storage_path"),
+                    files,
+                };
+                Self::commit(pool, &schema, current, rewrap).await
+            }
+            .await;
+            match result {
+                Ok(true) => stats.media += 1,
+                Ok(false) => {}
+                Err(e) => {
+                    warn!("Key rotation failed for media {} in '{tenant}': {e}", row.id);
+                    stats.failed += 1;
+                }
+            }
+        }
+
+        // Documents (current and replaced files), blurred photo copies and avatars each
+        // have a single file per row.
+        #[derive(sqlx::FromRow)]
+        struct FileRow {
+            id: Uuid,
+            path: String,
+            iv: Vec<u8>,
+            tag: Vec<u8>,
+            key_version: i32,
+        }
+
+        for (table, version_col, path_col, iv_col, tag_col, filter) in [
+            ("documents", "key_version", "storage_path", "encryption_iv", "encryption_tag", "is_encrypted = TRUE"),
+            ("document_versions", "key_version", "storage_path", "encryption_iv", "encryption_tag", "is_encrypted = TRUE"),
+            ("media_redactions", "key_version", "redacted_path", "encryption_iv", "encryption_tag", "redacted_path IS NOT NULL"),
+            ("children", "avatar_key_version", "photo_url", "avatar_iv", "avatar_tag", "photo_url IS NOT NULL"),
+        ] {
+            let rows: Vec<FileRow> = sqlx::query_as(&format!(
+                r#"SELECT id, {path_col} AS path, {iv_col} AS iv, {tag_col} AS tag, {version_col} AS key_version
+                   FROM "{schema}".{table}
+                   WHERE {filter} AND {version_col} <> $1
+                     AND {iv_col} IS NOT NULL AND {tag_col} IS NOT NULL
+                     AND ($3::UUID IS NULL OR id > $3)
+                   ORDER BY id
+                   LIMIT $2"#
+            ))
+            .bind(current)
+            .bind(batch_size)
+            .bind(cursor.files.get(table))
+            .fetch_all(pool)
+            .await?;
+            more |= rows.len() as i64 == batch_size;
+            if let Some(last) = rows.last() {
+                cursor.files.insert(table, last.id);
+            }
+
+            for row in rows {
+                let result = async {
+                    let old_key = keys.tenant_key(row.key_version, tenant)?;
+                    let staged = Staged::write(media_dir, &row.path, &row.iv, &row.tag, &old_key, &new_key).await?;
+                    let rewrap = RowRewrap {
+                        table,
+                        version_col,
+                        id: row.id,
+                        from_version: row.key_version,
+                        files: vec![(staged, iv_col, tag_col)],
+                        shared_by: None,
+                    };
+                    Self::commit(pool, &schema, current, rewrap).await
+                }
+                .await;
+                match result {
+                    Ok(true) if table == "children" => stats.avatars += 1,
+                    Ok(true) if table == "media_redactions" => stats.media += 1,
+                    Ok(true) => stats.documents += 1,
+                    Ok(false) => {}
+                    Err(e) => {
+                        warn!("Key rotation failed for {table} {} in '{tenant}': {e}", row.id);
+                        stats.failed += 1;
+                    }
+                }
+            }
+        }
+
+        cursor.done = !more;
+        Ok(stats)
+    }
+
+    /// Swap the staged files in and record their new metadata, unless the row changed
+    /// (deleted, replaced or already rotated) since it was read. Returns whether it was applied.
+    async fn commit(pool: &PgPool, schema: &str, to_version: i32, rewrap: RowRewrap<'_>) -> anyhow::Result<bool> {
+        let RowRewrap { table, version_col, id, from_version, files, shared_by } = rewrap;
+        let mut tx = pool.begin().await?;
+
+        let locked: Option<i32> = sqlx::query_scalar(&format!(
+            r#"SELECT {version_col} FROM "{schema}".{table} WHERE id = $1 FOR UPDATE"#
+        ))
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if locked != Some(from_version) {
+            for (staged, _, _) in &files {
+                staged.discard().await;
+            }
+            return Ok(false);
+        }
+
+        let mut sets = vec![format!("{version_col} = $2")];
+        for (i, (_, iv_col, tag_col)) in files.iter().enumerate() {
+            sets.push(format!("{iv_col} = ${}", 3 + i * 2));
+            sets.push(format!("{tag_col} = ${}", 4 + i * 2));
+        }
+        // Rows sharing the rewrapped blob must follow it to the new key.
+        let filter = match shared_by {
+            Some(col) => format!(r#"{col} = (SELECT {col} FROM "{schema}".{table} WHERE id = $1)"#),
+            None => "id = $1".to_string(),
+        };
+        let sql = format!(r#"UPDATE "{schema}".{table} SET {} WHERE {filter}"#, sets.join(", "));
+        let mut query = sqlx::query(&sql).bind(id).bind(to_version);
+        for (staged, _, _) in &files {
+            query = query.bind(&staged.iv).bind(&staged.tag);
+        }
+        query.execute(&mut *tx).await?;
+
+        for (i, (staged, _, _)) in files.iter().enumerate() {
+            if let Err(e) = staged.swap_in().await {
+                Self::roll_back(&files[..i]).await;
+                for (rest, _, _) in &files[i..] {
+                    rest.discard().await;
+                }
+                return Err(e.into());
+            }
+        }
+
+        if let Err(e) = tx.commit().await {
+            Self::roll_back(&files).await;
+            return Err(e.into());
+        }
+        for (staged, _, _) in &files {
+            staged.finish().await;
+        }
+        Ok(true)
+    }
+
+    async fn roll_back(swapped: &[(Staged, &str, &str)]) {
+        for (staged, _, _) in swapped {
+            staged.roll_back().await;
+        }
+    }
+
+    /// Rotate every garderie (active or not, archives aside) batch by batch, each
+    /// through to the end of its tables.
+    pub async fn rotate_all(
+        pool: &PgPool,
+        media_dir: &str,
+        keys: &KeyRing,
+        batch_size: i64,
+    ) -> anyhow::Result<RotationStats> {
+        let tenants: Vec<String> =
+            sqlx::query_scalar("SELECT slug FROM public.garderies WHERE archived_at IS NULL ORDER BY slug")
+                .fetch_all(pool)
+                .await?;
+
+        let mut total = RotationStats::default();
+        for tenant in tenants {
+            TenantDb::scope(tenant.clone(), async {
+                let mut cursor = RotationCursor::default();
+                while !cursor.is_done() {
+                    match Self::rotate_tenant(pool, media_dir, keys, &tenant, batch_size, &mut cursor).await {
+                        Ok(stats) => total.add(stats),
+                        Err(e) => {
+                            error!("Key rotation error for tenant '{tenant}': {e}");
+                            break;
+                        }
+                    }
+                }
+            })
+            .await;
+        }
+        Ok(total)
+    }
+}
+
+/// Spawn the background re-encryption job. It only runs while previous master keys
+/// are configured (ENCRYPTION_PREVIOUS_KEYS), re-wrapping files hourly until none
+/// remain under an old key; uploads and downloads keep working meanwhile.
+pub fn start(pool: PgPool, config: Arc<Config>) {
+    tokio::spawn(async move {
+        let keys = match KeyRing::from_config(&config) {
+            Ok(k) => k,
+            Err(e) => {
+                error!("Key rotation job disabled: {e}");
+                return;
+            }
+        };
+        if !keys.has_previous() {
+            return;
+        }
+
+        loop {
+            let Some(job) = crate::services::worker::job().await else {
+                break;
+            };
+            match KeyRotationService::rotate_all(&pool, &config.media_dir, &keys, DEFAULT_BATCH_SIZE).await {
+                Ok(stats) if stats.rotated() > 0 || stats.failed > 0 => info!(
+                    "Key rotation: re-encrypted {} media, {} documents, {} avatars under key v{} ({} failed)",
+                    stats.media,
+                    stats.documents,
+                    stats.avatars,
+                    keys.current_version(),
+                    stats.failed
+                ),
+                Ok(_) => {}
+                Err(e) => error!("Key rotation job error: {e}"),
+            }
+            drop(job);
+            tokio::time::sleep(tokio::time::Duration::from_secs(JOB_INTERVAL_SECS)).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn swapped_files_roll_back_to_the_original_ciphertext() {
+        let dir = std::env::temp_dir().join(format!("minispace-rewrap-{}", Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let (old_key, new_key) = ([1u8; 32], [2u8; 32]);
+        let (original, iv, tag) = encryption::encrypt_file(b"photo", &old_key).unwrap();
+        tokio::fs::write(dir.join("a.jpg"), &original).await.unwrap();
+
+        let staged = Staged::write(dir.to_str().unwrap(), "a.jpg", &iv, &tag, &old_key, &new_key).await.unwrap();
+        staged.swap_in().await.unwrap();
+        let swapped = tokio::fs::read(dir.join("a.jpg")).await.unwrap();
+        assert_eq!(encryption::decrypt_file(&swapped, &staged.iv, &staged.tag, &new_key).unwrap(), b"photo");
+
+        staged.roll_back().await;
+        assert_eq!(tokio::fs::read(dir.join("a.jpg")).await.unwrap(), original);
+        assert!(!staged.backup.exists());
+
+        // A failed swap (the staged file is gone) leaves the original in place
+        assert!(staged.swap_in().await.is_err());
+        assert_eq!(tokio::fs::read(dir.join("a.jpg")).await.unwrap(), original);
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    /// Rows failing at the head of every batch must not keep the rows after them
+    /// under the old key.
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn failing_rows_do_not_block_the_rows_after_them() {
+        let tenant = crate::db::test_support::scratch_tenant("rot").await;
+        let schema = &tenant.schema;
+        let dir = std::env::temp_dir().join(format!("minispace-rotate-{}", Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let keys = KeyRing::new(&hex::encode([2u8; 32]), 2, Some(&format!("1:{}", hex::encode([1u8; 32])))).unwrap();
+        let old_key = keys.tenant_key(1, &tenant.slug).unwrap();
+        let batch_size = 2;
+
+        // The first batch: avatars whose file is missing
+        for n in 1..=batch_size {
+            sqlx::query(&format!(
+                "INSERT INTO {schema}.children (id, first_name, last_name, birth_date, photo_url, avatar_iv, avatar_tag)
+                 VALUES ($1, 'Perdu', 'Test', '2022-01-01', $2, '\\x00', '\\x00')"
+            ))
+            .bind(Uuid::from_u128(n as u128))
+            .bind(format!("missing-{n}.jpg"))
+            .execute(&tenant.pool)
+            .await
+            .unwrap();
+        }
+        let (ciphertext, iv, tag) = encryption::encrypt_file(b"avatar", &old_key).unwrap();
+        tokio::fs::write(dir.join("ok.jpg"), &ciphertext).await.unwrap();
+        sqlx::query(&format!(
+            "INSERT INTO {schema}.children (id, first_name, last_name, birth_date, photo_url, avatar_iv, avatar_tag)
+             VALUES ($1, 'Léa', 'Test', '2022-01-01', 'ok.jpg', $2, $3)"
+        ))
+        .bind(Uuid::from_u128(u128::MAX))
+        .bind(&iv)
+        .bind(&tag)
+        .execute(&tenant.pool)
+        .await
+        .unwrap();
+
+        let mut cursor = RotationCursor::default();
+        let mut total = RotationStats::default();
+        let mut batches = 0;
+        while !cursor.is_done() {
+            let media_dir = dir.to_str().unwrap();
+            total.add(
+                KeyRotationService::rotate_tenant(&tenant.pool, media_dir, &keys, &tenant.slug, batch_size, &mut cursor)
+                    .await
+                    .unwrap(),
+            );
+            batches += 1;
+            assert!(batches <= 3, "rotation does not move past the failing rows");
+        }
+        assert_eq!((total.avatars, total.failed), (1, batch_size as usize));
+
+        let pending = KeyRotationService::pending(&tenant.pool, &tenant.slug, 2).await.unwrap();
+        assert_eq!(pending.avatars, batch_size);
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+        tenant.drop().await;
+    }
+}
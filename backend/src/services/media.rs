@@ -2,46 +2,299 @@ use std::path::{Path, PathBuf};
 
 use axum::extract::Multipart;
 use chrono::{Datelike, NaiveDate, Utc};
-use image::imageops::FilterType;
+use image::{codecs::jpeg::JpegEncoder, imageops::FilterType, DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+use chrono::DateTime;
+use redis::{aio::MultiplexedConnection, AsyncCommands};
+use sha2::{Digest, Sha256};
 use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::{
     db::tenant::schema_name,
-    models::media::{BulkMediaRequest, Media, MediaQuery, MediaType, UpdateMediaRequest},
-    services::encryption,
+    middleware::precondition::StaleVersion,
+    models::{
+        media::{BulkMediaRequest, Media, MediaMark, MediaPage, MediaQuery, MediaTag, MediaType, UpdateMediaRequest},
+        tenant::PlanType,
+    },
+    services::{
+        consents::photo_consent_sql,
+        encryption::{self, KeyRing},
+        redaction::RedactionService,
+        storage::StorageService,
+        upload_types::{self, UploadKind, UploadRejected},
+        video_previews::{self, PREVIEW_PENDING},
+    },
 };
 
 /// Explicit column list for Media — casts enums to TEXT, includes child_ids subquery.
 /// All queries must alias the media table as `m`.
 /// Generate a cryptographically random 32-char hex filename (no extension).
 /// Decouples storage paths from DB UUIDs and hides file types.
-fn random_storage_name() -> String {
+pub(crate) fn random_storage_name() -> String {
     use rand::Rng;
     let bytes: [u8; 16] = rand::thread_rng().gen();
     hex::encode(bytes)
 }
 
 fn media_cols(schema: &str) -> String {
+    media_cols_with_paths(schema, "m.storage_path", "m.thumbnail_path")
+}
+
+/// `media_cols` with other expressions for the file paths (blurred copies).
+fn media_cols_with_paths(schema: &str, storage_path: &str, thumbnail_path: &str) -> String {
     format!(
-        "m.id, m.uploader_id, m.media_type::TEXT as media_type, m.original_filename, m.storage_path,
-         m.thumbnail_path, m.content_type, m.size_bytes, m.width, m.height, m.duration_secs,
+        "m.id, m.uploader_id, m.media_type::TEXT as media_type, m.original_filename, {storage_path} AS storage_path,
+         {thumbnail_path} AS thumbnail_path, m.content_type, m.size_bytes, m.width, m.height, m.duration_secs,
+         m.sprite_path, m.sprite_columns, m.sprite_frames, m.sprite_interval_secs, m.sprite_frame_width,
+         m.sprite_frame_height, m.preview_status,
          m.group_id, m.child_id, m.caption, m.visibility::TEXT as visibility,
          ARRAY(SELECT mc.child_id FROM \"{schema}\".media_children mc WHERE mc.media_id = m.id) as child_ids,
-         m.created_at, m.is_encrypted, m.encryption_iv, m.encryption_tag,
+         ARRAY(SELECT mt.tag FROM \"{schema}\".media_tags mt WHERE mt.media_id = m.id ORDER BY mt.tag) as tags,
+         m.created_at, m.updated_at, m.is_encrypted, m.encryption_iv, m.encryption_tag,
          m.thumbnail_encryption_iv, m.thumbnail_encryption_tag"
     )
 }
 
+/// Whether `m` shows a child without photo consent who is not one of the user's own.
+fn unconsented_children_sql(schema: &str, user_id: Uuid) -> String {
+    format!(
+        "EXISTS (
+          SELECT 1 FROM \"{schema}\".media_children mc
+          WHERE mc.media_id = m.id
+            AND NOT {consent}
+            AND mc.child_id NOT IN (
+                SELECT cp.child_id FROM \"{schema}\".child_parents cp WHERE cp.user_id = '{user_id}'
+            )
+        )",
+        consent = photo_consent_sql(schema, "mc.child_id"),
+    )
+}
+
+/// Conditions on `m` for the media a user may see.
+fn visibility_conditions(schema: &str, user_id: Uuid, is_staff: bool, group_scope: Option<&[Uuid]>) -> Vec<String> {
+    if is_staff {
+        // Staff see everything (scoped educators: public media, their own uploads
+        // and media of their groups); optional filters apply
+        let mut conditions = vec!["TRUE".to_string()];
+
+        if let Some(groups) = group_scope {
+            let groups = groups.iter().map(|g| format!("'{g}'")).collect::<Vec<_>>().join(",");
+            conditions.push(format!(
+                "(
+                  m.visibility = 'public'
+                  OR m.uploader_id = '{user_id}'
+                  OR m.group_id IN ({groups})
+                  OR EXISTS (
+                      SELECT 1 FROM \"{schema}\".media_children mc
+                      JOIN \"{schema}\".children c ON c.id = mc.child_id
+                      WHERE mc.media_id = m.id AND c.group_id IN ({groups})
+                  )
+                )"
+            ));
+        }
+        conditions
+    } else {
+        // Parents: only non-private media, filtered by their children/groups
+        vec![
+            "m.visibility != 'private'".to_string(),
+            format!(
+                "(
+                  -- Public
+                  m.visibility = 'public'
+                  OR
+                  -- Group: parent has a child in this group
+                  (m.visibility = 'group' AND m.group_id IN (
+                      SELECT DISTINCT c.group_id
+                      FROM \"{schema}\".child_parents cp
+                      JOIN \"{schema}\".children c ON c.id = cp.child_id
+                      WHERE cp.user_id = '{user_id}' AND c.group_id IS NOT NULL
+                  ))
+                  OR
+                  -- Child-specific: parent linked to at least one of the assigned children
+                  (m.visibility = 'child' AND EXISTS (
+                      SELECT 1 FROM \"{schema}\".media_children mc
+                      JOIN \"{schema}\".child_parents cp ON cp.child_id = mc.child_id
+                      WHERE mc.media_id = m.id AND cp.user_id = '{user_id}'
+                  ))
+                )"
+            ),
+            // Loi 25: group and public photos showing a child without photo consent
+            // are only shared with that child's own parents, or as a blurred copy
+            format!(
+                "(m.visibility = 'child'
+                  OR EXISTS (
+                      SELECT 1 FROM \"{schema}\".media_redactions r
+                      WHERE r.media_id = m.id AND r.redacted_path IS NOT NULL
+                  )
+                  OR NOT {})",
+                unconsented_children_sql(schema, user_id),
+            ),
+        ]
+    }
+}
+
+/// Quality of re-encoded JPEG photos.
+const JPEG_QUALITY: u8 = 90;
+
+/// Media counts are cached this long; a page total may lag behind uploads by as much.
+const COUNT_CACHE_TTL_SECS: u64 = 60;
+
+#[derive(Debug, thiserror::Error)]
+#[error("Curseur de pagination invalide")]
+pub struct InvalidCursor;
+
+/// Position after the last media of a page, in the (created_at DESC, id DESC) order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MediaCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl MediaCursor {
+    pub fn encode(&self) -> String {
+        format!("{}_{}", self.created_at.timestamp_micros(), self.id)
+    }
+
+    pub fn decode(s: &str) -> Option<Self> {
+        let (micros, id) = s.split_once('_')?;
+        Some(Self {
+            created_at: DateTime::from_timestamp_micros(micros.parse().ok()?)?,
+            id: id.parse().ok()?,
+        })
+    }
+}
+
+/// A photo that cannot be turned into a format browsers display.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct UnsupportedImage(pub String);
+
+fn is_heic(content_type: &str, filename: &str) -> bool {
+    let ext = Path::new(filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    matches!(content_type, "image/heic" | "image/heif") || ext == "heic" || ext == "heif"
+}
+
+/// Convert a HEIC/HEIF photo (the iPhone default) to JPEG with libheif's `heif-convert`.
+/// The plain files only live in the temp directory for the duration of the call.
+async fn heic_to_jpeg(bytes: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let name = random_storage_name();
+    let input = std::env::temp_dir().join(format!("{name}.heic"));
+    let output = std::env::temp_dir().join(format!("{name}.jpg"));
+    tokio::fs::write(&input, bytes).await?;
+
+    let converted = match tokio::process::Command::new("heif-convert")
+        .arg("-q")
+        .arg(JPEG_QUALITY.to_string())
+        .arg(&input)
+        .arg(&output)
+        .output()
+        .await
+    {
+        Ok(out) if out.status.success() => tokio::fs::read(&output).await.map_err(anyhow::Error::from),
+        Ok(out) => Err(anyhow::anyhow!("{}", String::from_utf8_lossy(&out.stderr).trim())),
+        Err(e) => Err(e.into()),
+    };
+    let _ = tokio::fs::remove_file(&input).await;
+    let _ = tokio::fs::remove_file(&output).await;
+
+    converted.map_err(|e| {
+        tracing::warn!("HEIC conversion failed: {e}");
+        UnsupportedImage("Impossible de convertir cette photo HEIC, envoyez-la en JPEG".into()).into()
+    })
+}
+
+/// Re-encode a photo upright and without its metadata (GPS position, camera details),
+/// scaled down to fit within `max_dimension` pixels when that is not 0.
+/// Returns None for formats kept as uploaded: GIF (animations) and anything the
+/// decoder does not read.
+pub fn normalize_photo(bytes: &[u8], max_dimension: u32) -> anyhow::Result<Option<Vec<u8>>> {
+    let reader = ImageReader::new(std::io::Cursor::new(bytes)).with_guessed_format()?;
+    let format = match reader.format() {
+        Some(format @ (ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::WebP)) => format,
+        _ => return Ok(None),
+    };
+    let mut decoder = reader.into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let mut img = DynamicImage::from_decoder(decoder)?;
+    img.apply_orientation(orientation);
+
+    if max_dimension > 0 && img.width().max(img.height()) > max_dimension {
+        img = img.resize(max_dimension, max_dimension, FilterType::Lanczos3);
+    }
+
+    // Encoders only write pixels, so EXIF, XMP and ICC chunks are left behind
+    let mut out = Vec::new();
+    match format {
+        ImageFormat::Jpeg => img.write_with_encoder(JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY))?,
+        _ => img.write_to(&mut std::io::Cursor::new(&mut out), format)?,
+    }
+    Ok(Some(out))
+}
+
+const MAX_TAG_CHARS: usize = 50;
+const MAX_TAGS: usize = 20;
+
+/// Tags as stored: trimmed, lower-cased, without a leading `#` and with inner
+/// whitespace collapsed. Blanks and duplicates are dropped.
+pub fn normalize_tags<S: AsRef<str>>(raw: &[S]) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in raw {
+        let tag = tag.as_ref().trim().trim_start_matches('#').split_whitespace().collect::<Vec<_>>().join(" ");
+        let tag: String = tag.to_lowercase().chars().take(MAX_TAG_CHARS).collect();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+        if tags.len() == MAX_TAGS {
+            break;
+        }
+    }
+    tags
+}
+
+/// Comma-separated tags, as sent in query strings and upload forms.
+pub fn parse_tag_list(raw: &str) -> Vec<String> {
+    normalize_tags(&raw.split(',').collect::<Vec<_>>())
+}
+
 pub struct MediaService;
 
 impl MediaService {
+    /// Size and duration caps of the garderie's plan. Without ffprobe the duration
+    /// is left to the preview worker.
+    async fn check_video_limits(pool: &PgPool, tenant: &str, bytes: &[u8]) -> anyhow::Result<()> {
+        let plan: PlanType = sqlx::query_scalar("SELECT plan FROM public.garderies WHERE slug = $1")
+            .bind(tenant)
+            .fetch_one(pool)
+            .await?;
+        let max_bytes = upload_types::max_video_bytes(&plan);
+        if bytes.len() as u64 > max_bytes {
+            return Err(UploadRejected::VideoTooLarge { max_mb: max_bytes / (1024 * 1024) }.into());
+        }
+        let max_secs = upload_types::max_video_secs(&plan);
+        match video_previews::probe(bytes).await {
+            Ok(info) if info.duration_secs > max_secs as f64 => Err(UploadRejected::VideoTooLong { max_secs }.into()),
+            Ok(_) => Ok(()),
+            Err(e) => {
+                tracing::warn!("Could not probe uploaded video of {tenant}: {e}");
+                Ok(())
+            }
+        }
+    }
+
+    /// `max_dimension` bounds the longest side of stored photos (0 keeps them as is).
+    #[allow(clippy::too_many_arguments)]
     pub async fn upload(
         pool: &PgPool,
+        redis: &mut redis::aio::MultiplexedConnection,
         tenant: &str,
         uploader_id: Uuid,
         media_dir: &str,
-        encryption_master_key: &str,
+        max_dimension: u32,
+        keys: &KeyRing,
         mut multipart: Multipart,
     ) -> anyhow::Result<Media> {
         let now = Utc::now();
@@ -58,6 +311,7 @@ impl MediaService {
         let mut caption: Option<String> = None;
         let mut group_id: Option<Uuid> = None;
         let mut child_ids: Vec<Uuid> = Vec::new();
+        let mut tags: Vec<String> = Vec::new();
         let mut visibility = "private".to_string();
 
         while let Some(field) = multipart.next_field().await? {
@@ -87,51 +341,54 @@ impl MediaService {
                         child_ids.push(id);
                     }
                 }
+                // Accept "tags" as a comma-separated list, or repeated "tags[]"
+                n if n == "tags[]" || n == "tags" => {
+                    tags.extend(parse_tag_list(&field.text().await?));
+                }
                 _ => {}
             }
         }
 
-        let (bytes, original_filename, content_type) =
+        let (mut bytes, mut original_filename, declared_type) =
             file_data.ok_or_else(|| anyhow::anyhow!("No file field in upload"))?;
+        // The stored type is the one read from the bytes, not the client's
+        let mut content_type =
+            upload_types::validate(UploadKind::Media, &bytes, &declared_type, &original_filename)?.content_type.to_string();
+
+        if is_heic(&content_type, &original_filename) {
+            bytes = heic_to_jpeg(&bytes).await?;
+            content_type = "image/jpeg".to_string();
+            original_filename = Path::new(&original_filename)
+                .with_extension("jpg")
+                .to_string_lossy()
+                .into_owned();
+        }
 
         let media_type = if content_type.starts_with("video/") {
             MediaType::Video
         } else {
             MediaType::Photo
         };
-
-        let ext = Path::new(&original_filename)
-            .extension()
-            .and_then(|e| e.to_str())
-            .unwrap_or("bin");
-
-        let file_id = Uuid::new_v4();
-        let storage_filename = random_storage_name();
-        let storage_path_full = tenant_dir.join(&storage_filename);
-        let storage_path_rel = format!("{}/{}/{}/{}", tenant, year, month, storage_filename);
-
-        // Decode master key and derive tenant key
-        let master_key_bytes = hex::decode(encryption_master_key)?;
-        if master_key_bytes.len() != 32 {
-            anyhow::bail!("Master key must be 32 bytes");
+        if media_type == MediaType::Video {
+            Self::check_video_limits(pool, tenant, &bytes).await?;
         }
-        let mut master_key = [0u8; 32];
-        master_key.copy_from_slice(&master_key_bytes);
-        let tenant_key = encryption::derive_tenant_key(&master_key, tenant)?;
 
-        // Encrypt file data
-        let (encrypted_bytes, iv, tag) = encryption::encrypt_file(&bytes, &tenant_key)?;
-
-        // Write encrypted file to disk
-        tokio::fs::write(&storage_path_full, &encrypted_bytes).await?;
-
-        let (width, height, thumbnail_path, thumb_iv, thumb_tag) = if media_type == MediaType::Photo {
-            Self::process_image(&bytes, &tenant_dir, &storage_path_rel, &tenant_key)
-                .await
-                .unwrap_or((None, None, None, None, None))
-        } else {
-            (None, None, None, None, None)
-        };
+        // Strip metadata and fix orientation before anything is written to disk
+        if media_type == MediaType::Photo {
+            let (original, normalized) = tokio::task::spawn_blocking(move || {
+                let normalized = normalize_photo(&bytes, max_dimension);
+                (bytes, normalized)
+            })
+            .await?;
+            bytes = match normalized {
+                Ok(Some(clean)) => clean,
+                Ok(None) => original,
+                Err(e) => {
+                    tracing::warn!("Could not normalize uploaded photo '{original_filename}': {e}");
+                    original
+                }
+            };
+        }
 
         // Resolve group_id based on visibility
         let db_group_id = if visibility == "group" { group_id } else { None };
@@ -139,36 +396,107 @@ impl MediaService {
         let schema = schema_name(tenant);
         let cols = media_cols(&schema);
 
-        // INSERT with encryption metadata
-        let (inserted_id,): (Uuid,) = sqlx::query_as(&format!(
+        // A file this garderie already stored (same batch shared with another group) gets a
+        // new row pointing at the existing encrypted blob instead of a second copy
+        let content_sha256 = hex::encode(Sha256::digest(&bytes));
+        let duplicate: Option<(Uuid,)> = sqlx::query_as(&format!(
             "INSERT INTO \"{schema}\".media
-             (uploader_id, media_type, original_filename, storage_path, thumbnail_path,
-              content_type, size_bytes, width, height, group_id, child_id, caption, visibility,
-              is_encrypted, encryption_iv, encryption_tag, thumbnail_encryption_iv, thumbnail_encryption_tag)
-             VALUES ($1, $2::\"{schema}\".media_type, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13::\"{schema}\".media_visibility, $14, $15, $16, $17, $18)
+             (uploader_id, original_filename, group_id, caption, visibility,
+              media_type, storage_path, thumbnail_path, content_type, size_bytes, width, height,
+              is_encrypted, encryption_iv, encryption_tag, thumbnail_encryption_iv, thumbnail_encryption_tag,
+              key_version, content_sha256, scan_status, scan_signature, scanned_at,
+              duration_secs, preview_status, sprite_path, sprite_encryption_iv, sprite_encryption_tag,
+              sprite_columns, sprite_frames, sprite_interval_secs, sprite_frame_width, sprite_frame_height)
+             SELECT $1, $2, $3, $4, $5::\"{schema}\".media_visibility,
+                    media_type, storage_path, thumbnail_path, content_type, size_bytes, width, height,
+                    is_encrypted, encryption_iv, encryption_tag, thumbnail_encryption_iv, thumbnail_encryption_tag,
+                    key_version, content_sha256, scan_status, scan_signature, scanned_at,
+                    duration_secs, preview_status, sprite_path, sprite_encryption_iv, sprite_encryption_tag,
+                    sprite_columns, sprite_frames, sprite_interval_secs, sprite_frame_width, sprite_frame_height
+             FROM \"{schema}\".media
+             WHERE content_sha256 = $6
+             ORDER BY created_at
+             LIMIT 1
              RETURNING id"
         ))
         .bind(uploader_id)
-        .bind(media_type.to_string())
         .bind(&original_filename)
-        .bind(&storage_path_rel)
-        .bind(&thumbnail_path)
-        .bind(&content_type)
-        .bind(encrypted_bytes.len() as i64) // Size of encrypted data
-        .bind(width)
-        .bind(height)
         .bind(db_group_id)
-        .bind(Option::<Uuid>::None) // child_id legacy column unused
-        .bind(caption)
+        .bind(&caption)
         .bind(&visibility)
-        .bind(true) // is_encrypted
-        .bind(&iv)
-        .bind(&tag)
-        .bind(&thumb_iv)
-        .bind(&thumb_tag)
-        .fetch_one(pool)
+        .bind(&content_sha256)
+        .fetch_optional(pool)
         .await?;
 
+        let inserted_id = if let Some((id,)) = duplicate {
+            id
+        } else {
+            StorageService::ensure_capacity(pool, redis, tenant, bytes.len() as i64).await?;
+
+            let ext = Path::new(&original_filename)
+                .extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or("bin");
+
+            let file_id = Uuid::new_v4();
+            let storage_filename = random_storage_name();
+            let storage_path_full = tenant_dir.join(&storage_filename);
+            let storage_path_rel = format!("{}/{}/{}/{}", tenant, year, month, storage_filename);
+
+            // Derive tenant key from the current master key
+            let (key_version, tenant_key) = keys.current_tenant_key(tenant)?;
+
+            // Encrypt file data
+            let (encrypted_bytes, iv, tag) = encryption::encrypt_file(&bytes, &tenant_key)?;
+
+            // Write encrypted file to disk
+            tokio::fs::write(&storage_path_full, &encrypted_bytes).await?;
+
+            let (width, height, thumbnail_path, thumb_iv, thumb_tag) = if media_type == MediaType::Photo {
+                Self::process_image(&bytes, &tenant_dir, &storage_path_rel, &tenant_key)
+                    .await
+                    .unwrap_or((None, None, None, None, None))
+            } else {
+                (None, None, None, None, None)
+            };
+
+            // INSERT with encryption metadata
+            let (inserted_id,): (Uuid,) = sqlx::query_as(&format!(
+                "INSERT INTO \"{schema}\".media
+                 (uploader_id, media_type, original_filename, storage_path, thumbnail_path,
+                  content_type, size_bytes, width, height, group_id, child_id, caption, visibility,
+                  is_encrypted, encryption_iv, encryption_tag, thumbnail_encryption_iv, thumbnail_encryption_tag, key_version,
+                  content_sha256, preview_status)
+                 VALUES ($1, $2::\"{schema}\".media_type, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13::\"{schema}\".media_visibility, $14, $15, $16, $17, $18, $19, $20, $21)
+                 RETURNING id"
+            ))
+            .bind(uploader_id)
+            .bind(media_type.to_string())
+            .bind(&original_filename)
+            .bind(&storage_path_rel)
+            .bind(&thumbnail_path)
+            .bind(&content_type)
+            .bind(encrypted_bytes.len() as i64) // Size of encrypted data
+            .bind(width)
+            .bind(height)
+            .bind(db_group_id)
+            .bind(Option::<Uuid>::None) // child_id legacy column unused
+            .bind(caption)
+            .bind(&visibility)
+            .bind(true) // is_encrypted
+            .bind(&iv)
+            .bind(&tag)
+            .bind(&thumb_iv)
+            .bind(&thumb_tag)
+            .bind(key_version)
+            .bind(&content_sha256)
+            // Poster, sprite and duration come from the video preview worker
+            .bind((media_type == MediaType::Video).then_some(PREVIEW_PENDING))
+            .fetch_one(pool)
+            .await?;
+            inserted_id
+        };
+
         // Insert media_children entries
         for child_id in &child_ids {
             sqlx::query(&format!(
@@ -181,7 +509,9 @@ impl MediaService {
             .await?;
         }
 
-        // Fetch full record with child_ids populated
+        Self::add_tags(pool, &schema, &[inserted_id], &normalize_tags(&tags)).await?;
+
+        // Fetch full record with child_ids and tags populated
         let media = sqlx::query_as::<_, Media>(&format!(
             "SELECT {cols} FROM \"{schema}\".media m WHERE m.id = $1"
         ))
@@ -256,17 +586,39 @@ impl MediaService {
         }
     }
 
+    /// One page of the media the user may see. With a cursor the page starts right
+    /// after it (keyset); otherwise `page` is used.
     pub async fn list(
         pool: &PgPool,
+        redis: &mut MultiplexedConnection,
         tenant: &str,
         user_id: Uuid,
         is_staff: bool,
+        group_scope: Option<&[Uuid]>,
         query: &MediaQuery,
-    ) -> anyhow::Result<Vec<Media>> {
+    ) -> anyhow::Result<MediaPage> {
         let schema = schema_name(tenant);
-        let cols = media_cols(&schema);
+        // Parents get the blurred copy of group and public photos showing another
+        // child without photo consent (the visibility conditions require one)
+        let blurred = if is_staff {
+            "FALSE".to_string()
+        } else {
+            format!("m.visibility <> 'child' AND {}", unconsented_children_sql(&schema, user_id))
+        };
+        let cols = media_cols_with_paths(
+            &schema,
+            "CASE WHEN b.blurred THEN red.redacted_path ELSE m.storage_path END",
+            "CASE WHEN b.blurred THEN red.redacted_path ELSE m.thumbnail_path END",
+        );
         let per_page = query.per_page.unwrap_or(50).clamp(1, 200);
-        let offset = (query.page.unwrap_or(1).max(1) - 1) * per_page;
+        let cursor = match query.cursor.as_deref().filter(|c| !c.is_empty()) {
+            Some(c) => Some(MediaCursor::decode(c).ok_or(InvalidCursor)?),
+            None => None,
+        };
+        let offset = match cursor {
+            Some(_) => 0,
+            None => (query.page.unwrap_or(1).max(1) - 1) * per_page,
+        };
 
         // Parse child_ids filter
         let filter_child_ids: Vec<Uuid> = query
@@ -276,7 +628,7 @@ impl MediaService {
             .split(',')
             .filter_map(|s| s.trim().parse::<Uuid>().ok())
             .collect();
-        let has_child_filter = !filter_child_ids.is_empty();
+        let filter_tags = parse_tag_list(query.tags.as_deref().unwrap_or(""));
 
         // Parse period range
         let period_range = query
@@ -284,124 +636,218 @@ impl MediaService {
             .as_deref()
             .and_then(|p| Self::period_range(p, query.date.as_deref()));
 
-        if is_staff {
-            // Staff see everything; optional filters apply
-            let mut conditions = vec!["TRUE".to_string()];
+        let mut conditions = visibility_conditions(&schema, user_id, is_staff, group_scope);
+        let marked = |mark: MediaMark| {
+            format!(
+                "EXISTS (SELECT 1 FROM \"{schema}\".media_user_state mus
+                         WHERE mus.media_id = m.id AND mus.user_id = '{user_id}' AND mus.{})",
+                mark.column()
+            )
+        };
+        if query.favorites.unwrap_or(0) != 0 {
+            conditions.push(marked(MediaMark::Favorite));
+        }
+        if query.hidden.unwrap_or(0) != 0 {
+            conditions.push(marked(MediaMark::Hidden));
+        } else {
+            conditions.push(format!("NOT {}", marked(MediaMark::Hidden)));
+        }
 
-            if let Some(gid) = query.group_id {
-                conditions.push(format!("m.group_id = '{}'", gid));
-            }
-            if has_child_filter {
-                conditions.push(format!(
-                    "EXISTS (SELECT 1 FROM \"{schema}\".media_children mc WHERE mc.media_id = m.id AND mc.child_id = ANY($1::uuid[]))"
-                ));
-            }
-            if let Some((from, to)) = &period_range {
-                conditions.push(format!(
-                    "m.created_at >= '{}' AND m.created_at < '{}'",
-                    from, to
-                ));
-            }
+        if let Some(gid) = query.group_id {
+            conditions.push(format!("m.group_id = '{}'", gid));
+        }
+        if !filter_child_ids.is_empty() {
+            conditions.push(format!(
+                "EXISTS (SELECT 1 FROM \"{schema}\".media_children mc WHERE mc.media_id = m.id AND mc.child_id = ANY($1::uuid[]))"
+            ));
+        }
+        if !filter_tags.is_empty() {
+            conditions.push(format!(
+                "(SELECT COUNT(*) FROM \"{schema}\".media_tags mt WHERE mt.media_id = m.id AND mt.tag = ANY($2::text[]))
+                 = cardinality($2::text[])"
+            ));
+        }
+        if let Some(album_id) = query.album_id {
+            conditions.push(format!(
+                "EXISTS (SELECT 1 FROM \"{schema}\".album_media am WHERE am.media_id = m.id AND am.album_id = '{album_id}')"
+            ));
+        }
+        if let Some((from, to)) = &period_range {
+            conditions.push(format!(
+                "m.created_at >= '{}' AND m.created_at < '{}'",
+                from, to
+            ));
+        }
 
-            let where_clause = conditions.join(" AND ");
+        let where_clause = conditions.join(" AND ");
+        let total_count =
+            Self::count(pool, redis, tenant, &schema, &where_clause, &filter_child_ids, &filter_tags).await?;
 
-            if has_child_filter {
-                sqlx::query_as::<_, Media>(&format!(
-                    "SELECT {cols} FROM \"{schema}\".media m
-                     WHERE {where_clause}
-                     ORDER BY m.created_at DESC
-                     LIMIT $2 OFFSET $3"
-                ))
-                .bind(&filter_child_ids)
-                .bind(per_page)
-                .bind(offset)
-                .fetch_all(pool)
-                .await
-                .map_err(Into::into)
-            } else {
-                sqlx::query_as::<_, Media>(&format!(
-                    "SELECT {cols} FROM \"{schema}\".media m
-                     WHERE {where_clause}
-                     ORDER BY m.created_at DESC
-                     LIMIT $1 OFFSET $2"
-                ))
-                .bind(per_page)
-                .bind(offset)
-                .fetch_all(pool)
-                .await
-                .map_err(Into::into)
-            }
+        // One extra row tells whether there is a next page
+        let keyset = if cursor.is_some() { "AND (m.created_at, m.id) < ($5, $6)" } else { "" };
+        // The user's own marks, and how many starred each media for staff
+        let favorite_count = if is_staff {
+            format!(
+                ", (SELECT COUNT(*) FROM \"{schema}\".media_user_state mus
+                    WHERE mus.media_id = m.id AND mus.favorite) AS favorite_count"
+            )
         } else {
-            // Parents: only non-private media, filtered by their children/groups
-            let mut conditions = vec![
-                "m.visibility != 'private'".to_string(),
-                format!(
-                    "(
-                      -- Public
-                      m.visibility = 'public'
-                      OR
-                      -- Group: parent has a child in this group
-                      (m.visibility = 'group' AND m.group_id IN (
-                          SELECT DISTINCT c.group_id
-                          FROM \"{schema}\".child_parents cp
-                          JOIN \"{schema}\".children c ON c.id = cp.child_id
-                          WHERE cp.user_id = '{user_id}' AND c.group_id IS NOT NULL
-                      ))
-                      OR
-                      -- Child-specific: parent linked to at least one of the assigned children
-                      (m.visibility = 'child' AND EXISTS (
-                          SELECT 1 FROM \"{schema}\".media_children mc
-                          JOIN \"{schema}\".child_parents cp ON cp.child_id = mc.child_id
-                          WHERE mc.media_id = m.id AND cp.user_id = '{user_id}'
-                      ))
-                    )"
-                ),
-            ];
-
-            if let Some(gid) = query.group_id {
-                conditions.push(format!("m.group_id = '{}'", gid));
-            }
-            if has_child_filter {
-                conditions.push(format!(
-                    "EXISTS (SELECT 1 FROM \"{schema}\".media_children mc WHERE mc.media_id = m.id AND mc.child_id = ANY($1::uuid[]))"
-                ));
-            }
-            if let Some((from, to)) = &period_range {
-                conditions.push(format!(
-                    "m.created_at >= '{}' AND m.created_at < '{}'",
-                    from, to
-                ));
-            }
+            String::new()
+        };
+        let mut items = sqlx::query_as::<_, Media>(&format!(
+            "SELECT {cols},
+                    COALESCE(own.favorite, FALSE) AS is_favorite,
+                    COALESCE(own.hidden, FALSE) AS is_hidden,
+                    b.blurred AS is_redacted
+                    {favorite_count}
+             FROM \"{schema}\".media m
+             LEFT JOIN \"{schema}\".media_user_state own ON own.media_id = m.id AND own.user_id = '{user_id}'
+             LEFT JOIN \"{schema}\".media_redactions red ON red.media_id = m.id
+             CROSS JOIN LATERAL (SELECT {blurred} AS blurred) b
+             WHERE {where_clause} {keyset}
+             ORDER BY m.created_at DESC, m.id DESC
+             LIMIT $3 OFFSET $4"
+        ))
+        .bind(&filter_child_ids)
+        .bind(&filter_tags)
+        .bind(per_page + 1)
+        .bind(offset)
+        .bind(cursor.map(|c| c.created_at))
+        .bind(cursor.map(|c| c.id))
+        .fetch_all(pool)
+        .await?;
+
+        let next_cursor = if items.len() as i64 > per_page {
+            items.truncate(per_page as usize);
+            items.last().map(|m| MediaCursor { created_at: m.created_at, id: m.id }.encode())
+        } else {
+            None
+        };
+        Ok(MediaPage { items, next_cursor, total_count })
+    }
 
-            let where_clause = conditions.join(" AND ");
+    /// Media matching a listing's filters, cached per filter set (the visibility
+    /// conditions name the user, so each user gets their own entry).
+    async fn count(
+        pool: &PgPool,
+        redis: &mut MultiplexedConnection,
+        tenant: &str,
+        schema: &str,
+        where_clause: &str,
+        child_ids: &[Uuid],
+        tags: &[String],
+    ) -> anyhow::Result<i64> {
+        let mut hasher = Sha256::new();
+        hasher.update(where_clause.as_bytes());
+        for id in child_ids {
+            hasher.update(id.as_bytes());
+        }
+        for tag in tags {
+            hasher.update(tag.as_bytes());
+            hasher.update([0]);
+        }
+        let key = format!("media:count:{tenant}:{}", hex::encode(hasher.finalize()));
 
-            if has_child_filter {
-                sqlx::query_as::<_, Media>(&format!(
-                    "SELECT {cols} FROM \"{schema}\".media m
-                     WHERE {where_clause}
-                     ORDER BY m.created_at DESC
-                     LIMIT $2 OFFSET $3"
-                ))
-                .bind(&filter_child_ids)
-                .bind(per_page)
-                .bind(offset)
-                .fetch_all(pool)
-                .await
-                .map_err(Into::into)
-            } else {
-                sqlx::query_as::<_, Media>(&format!(
-                    "SELECT {cols} FROM \"{schema}\".media m
-                     WHERE {where_clause}
-                     ORDER BY m.created_at DESC
-                     LIMIT $1 OFFSET $2"
-                ))
-                .bind(per_page)
-                .bind(offset)
-                .fetch_all(pool)
-                .await
-                .map_err(Into::into)
-            }
+        if let Ok(Some(count)) = redis.get::<_, Option<i64>>(&key).await {
+            return Ok(count);
+        }
+        let count: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM \"{schema}\".media m WHERE {where_clause}"
+        ))
+        .bind(child_ids)
+        .bind(tags)
+        .fetch_one(pool)
+        .await?;
+        let _: Result<(), _> = redis.set_ex(&key, count, COUNT_CACHE_TTL_SECS).await;
+        Ok(count)
+    }
+
+    /// Whether the user may see the media, as in their listings.
+    pub async fn is_visible(
+        pool: &PgPool,
+        tenant: &str,
+        media_id: Uuid,
+        user_id: Uuid,
+        is_staff: bool,
+        group_scope: Option<&[Uuid]>,
+    ) -> anyhow::Result<bool> {
+        let schema = schema_name(tenant);
+        let conditions = visibility_conditions(&schema, user_id, is_staff, group_scope).join(" AND ");
+        Ok(sqlx::query_scalar(&format!(
+            "SELECT EXISTS (SELECT 1 FROM \"{schema}\".media m WHERE m.id = $1 AND {conditions})"
+        ))
+        .bind(media_id)
+        .fetch_one(pool)
+        .await?)
+    }
+
+    /// Set or clear one of the user's marks on a media.
+    pub async fn mark(
+        pool: &PgPool,
+        tenant: &str,
+        media_id: Uuid,
+        user_id: Uuid,
+        mark: MediaMark,
+        on: bool,
+    ) -> anyhow::Result<()> {
+        let schema = schema_name(tenant);
+        let column = mark.column();
+        sqlx::query(&format!(
+            "INSERT INTO \"{schema}\".media_user_state (media_id, user_id, {column}) VALUES ($1, $2, $3)
+             ON CONFLICT (media_id, user_id) DO UPDATE SET {column} = EXCLUDED.{column}, updated_at = NOW()"
+        ))
+        .bind(media_id)
+        .bind(user_id)
+        .bind(on)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Clean photos and videos showing only this child, oldest first: what their
+    /// parents may take with them when the child leaves.
+    pub async fn of_child_alone(pool: &PgPool, tenant: &str, child_id: Uuid) -> anyhow::Result<Vec<Media>> {
+        let schema = schema_name(tenant);
+        let cols = media_cols(&schema);
+        Ok(sqlx::query_as::<_, Media>(&format!(
+            "SELECT {cols} FROM \"{schema}\".media m
+             WHERE m.is_deleted = FALSE AND m.scan_status = 'clean'
+               AND (SELECT array_agg(mc.child_id) FROM \"{schema}\".media_children mc WHERE mc.media_id = m.id) = ARRAY[$1]
+             ORDER BY m.created_at"
+        ))
+        .bind(child_id)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Tags in use across the garderie's media, most used first.
+    pub async fn list_tags(pool: &PgPool, tenant: &str) -> anyhow::Result<Vec<MediaTag>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, MediaTag>(&format!(
+            "SELECT tag, COUNT(*) AS count FROM \"{schema}\".media_tags
+             GROUP BY tag ORDER BY count DESC, tag"
+        ))
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Add `tags` to every media in `media_ids`; unknown ids are ignored.
+    async fn add_tags(pool: &PgPool, schema: &str, media_ids: &[Uuid], tags: &[String]) -> anyhow::Result<()> {
+        if tags.is_empty() {
+            return Ok(());
         }
+        sqlx::query(&format!(
+            "INSERT INTO \"{schema}\".media_tags (media_id, tag)
+             SELECT m.id, t.tag FROM \"{schema}\".media m
+             CROSS JOIN UNNEST($2::text[]) AS t(tag)
+             WHERE m.id = ANY($1)
+             ON CONFLICT DO NOTHING"
+        ))
+        .bind(media_ids)
+        .bind(tags)
+        .execute(pool)
+        .await?;
+        Ok(())
     }
 
     pub async fn update(
@@ -411,40 +857,46 @@ impl MediaService {
         user_id: Uuid,
         is_staff: bool,
         req: &UpdateMediaRequest,
+        expected: Option<DateTime<Utc>>,
     ) -> anyhow::Result<Option<Media>> {
         let schema = schema_name(tenant);
         let cols = media_cols(&schema);
+        // Non-staff may only edit their own uploads
+        let owner = (!is_staff).then_some(user_id);
 
         let db_group_id = if req.visibility == "group" { req.group_id } else { None };
 
-        let media = if is_staff {
-            sqlx::query_as::<_, Media>(&format!(
-                "UPDATE \"{schema}\".media m
-                 SET caption = $2, group_id = $3, visibility = $4::\"{schema}\".media_visibility
-                 WHERE id = $1
-                 RETURNING {cols}"
-            ))
-            .bind(media_id)
-            .bind(&req.caption)
-            .bind(db_group_id)
-            .bind(&req.visibility)
-            .fetch_optional(pool)
-            .await?
-        } else {
-            sqlx::query_as::<_, Media>(&format!(
-                "UPDATE \"{schema}\".media m
-                 SET caption = $2, group_id = $3, visibility = $4::\"{schema}\".media_visibility
-                 WHERE id = $1 AND uploader_id = $5
-                 RETURNING {cols}"
+        let media = sqlx::query_as::<_, Media>(&format!(
+            "UPDATE \"{schema}\".media m
+             SET caption = $2, group_id = $3, visibility = $4::\"{schema}\".media_visibility
+             WHERE id = $1
+               AND ($5::timestamptz IS NULL OR m.updated_at = $5)
+               AND ($6::uuid IS NULL OR m.uploader_id = $6)
+             RETURNING {cols}"
+        ))
+        .bind(media_id)
+        .bind(&req.caption)
+        .bind(db_group_id)
+        .bind(&req.visibility)
+        .bind(expected)
+        .bind(owner)
+        .fetch_optional(pool)
+        .await?;
+
+        if media.is_none() && expected.is_some() {
+            // Tell a stale version from a missing (or someone else's) media
+            let current = sqlx::query_as::<_, Media>(&format!(
+                "SELECT {cols} FROM \"{schema}\".media m
+                 WHERE m.id = $1 AND ($2::uuid IS NULL OR m.uploader_id = $2)"
             ))
             .bind(media_id)
-            .bind(&req.caption)
-            .bind(db_group_id)
-            .bind(&req.visibility)
-            .bind(user_id)
+            .bind(owner)
             .fetch_optional(pool)
-            .await?
-        };
+            .await?;
+            if let Some(current) = current {
+                return Err(StaleVersion { current: serde_json::to_value(current)? }.into());
+            }
+        }
 
         if media.is_some() {
             // Sync media_children
@@ -468,7 +920,17 @@ impl MediaService {
                 }
             }
 
-            // Re-fetch with updated child_ids
+            if let Some(tags) = &req.tags {
+                sqlx::query(&format!(
+                    "DELETE FROM \"{schema}\".media_tags WHERE media_id = $1"
+                ))
+                .bind(media_id)
+                .execute(pool)
+                .await?;
+                Self::add_tags(pool, &schema, &[media_id], &normalize_tags(tags)).await?;
+            }
+
+            // Re-fetch with updated child_ids and tags
             let updated = sqlx::query_as::<_, Media>(&format!(
                 "SELECT {cols} FROM \"{schema}\".media m WHERE m.id = $1"
             ))
@@ -492,16 +954,16 @@ impl MediaService {
         let schema = schema_name(tenant);
 
         // Fetch paths before deleting
-        let row: Option<(String, Option<String>)> = if is_staff {
+        let row: Option<(String, Option<String>, Option<String>)> = if is_staff {
             sqlx::query_as(&format!(
-                "SELECT storage_path, thumbnail_path FROM \"{schema}\".media WHERE id = $1"
+                "SELECT storage_path, thumbnail_path, sprite_path FROM \"{schema}\".media WHERE id = $1"
             ))
             .bind(media_id)
             .fetch_optional(pool)
             .await?
         } else {
             sqlx::query_as(&format!(
-                "SELECT storage_path, thumbnail_path FROM \"{schema}\".media
+                "SELECT storage_path, thumbnail_path, sprite_path FROM \"{schema}\".media
                  WHERE id = $1 AND uploader_id = $2"
             ))
             .bind(media_id)
@@ -510,9 +972,10 @@ impl MediaService {
             .await?
         };
 
-        let Some((storage_path, thumbnail_path)) = row else {
+        let Some((storage_path, thumbnail_path, sprite_path)) = row else {
             return Ok(false);
         };
+        RedactionService::discard(pool, tenant, media_dir, &[media_id]).await?;
 
         // Delete DB record (media_children cascade)
         if is_staff {
@@ -531,15 +994,38 @@ impl MediaService {
         }
 
         // Delete physical files
-        let base = PathBuf::from(media_dir);
-        let _ = tokio::fs::remove_file(base.join(&storage_path)).await;
-        if let Some(thumb) = thumbnail_path {
-            let _ = tokio::fs::remove_file(base.join(&thumb)).await;
-        }
+        Self::release_files(pool, &schema, media_dir, &storage_path, &[thumbnail_path, sprite_path]).await?;
 
         Ok(true)
     }
 
+    /// Delete a stored file and those derived from it (thumbnail, video sprite) once no
+    /// media row points at it any more; duplicate uploads share one encrypted copy.
+    async fn release_files(
+        pool: &PgPool,
+        schema: &str,
+        media_dir: &str,
+        storage_path: &str,
+        derived: &[Option<String>],
+    ) -> anyhow::Result<()> {
+        let still_used: bool = sqlx::query_scalar(&format!(
+            "SELECT EXISTS (SELECT 1 FROM \"{schema}\".media WHERE storage_path = $1)"
+        ))
+        .bind(storage_path)
+        .fetch_one(pool)
+        .await?;
+        if still_used {
+            return Ok(());
+        }
+
+        let base = PathBuf::from(media_dir);
+        let _ = tokio::fs::remove_file(base.join(storage_path)).await;
+        for path in derived.iter().flatten() {
+            let _ = tokio::fs::remove_file(base.join(path)).await;
+        }
+        Ok(())
+    }
+
     pub async fn bulk(
         pool: &PgPool,
         tenant: &str,
@@ -551,8 +1037,8 @@ impl MediaService {
         match req.action.as_str() {
             "delete" => {
                 // Fetch paths for all media ids
-                let rows: Vec<(Uuid, String, Option<String>)> = sqlx::query_as(&format!(
-                    "SELECT id, storage_path, thumbnail_path FROM \"{schema}\".media
+                let rows: Vec<(String, Option<String>, Option<String>)> = sqlx::query_as(&format!(
+                    "SELECT storage_path, thumbnail_path, sprite_path FROM \"{schema}\".media
                      WHERE id = ANY($1)"
                 ))
                 .bind(&req.media_ids)
@@ -560,6 +1046,7 @@ impl MediaService {
                 .await?;
 
                 let count = rows.len();
+                RedactionService::discard(pool, tenant, media_dir, &req.media_ids).await?;
 
                 // Delete DB records
                 sqlx::query(&format!(
@@ -569,13 +1056,9 @@ impl MediaService {
                 .execute(pool)
                 .await?;
 
-                // Delete physical files
-                let base = PathBuf::from(media_dir);
-                for (_, storage_path, thumbnail_path) in rows {
-                    let _ = tokio::fs::remove_file(base.join(&storage_path)).await;
-                    if let Some(thumb) = thumbnail_path {
-                        let _ = tokio::fs::remove_file(base.join(&thumb)).await;
-                    }
+                // Delete physical files no longer shared with other media
+                for (storage_path, thumbnail_path, sprite_path) in rows {
+                    Self::release_files(pool, &schema, media_dir, &storage_path, &[thumbnail_path, sprite_path]).await?;
                 }
 
                 Ok(count)
@@ -620,7 +1103,84 @@ impl MediaService {
 
                 Ok(req.media_ids.len())
             }
+            "tag" => {
+                let tags = normalize_tags(req.tags.as_deref().unwrap_or_default());
+                Self::add_tags(pool, &schema, &req.media_ids, &tags).await?;
+                Ok(req.media_ids.len())
+            }
             _ => Err(anyhow::anyhow!("Unknown bulk action: {}", req.action)),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn media_cursor_round_trips() {
+        let cursor = MediaCursor {
+            created_at: DateTime::from_timestamp_micros(1_767_225_600_123_456).unwrap(),
+            id: Uuid::new_v4(),
+        };
+        assert_eq!(MediaCursor::decode(&cursor.encode()), Some(cursor));
+    }
+
+    #[test]
+    fn media_cursor_rejects_garbage() {
+        assert_eq!(MediaCursor::decode(""), None);
+        assert_eq!(MediaCursor::decode("123"), None);
+        assert_eq!(MediaCursor::decode("abc_00000000-0000-0000-0000-000000000000"), None);
+        assert_eq!(MediaCursor::decode("123_not-a-uuid"), None);
+    }
+
+    /// A JPEG of `width`×`height` carrying an EXIF orientation tag (6 = rotate 90° clockwise).
+    fn jpeg_with_orientation(width: u32, height: u32, orientation: u8) -> Vec<u8> {
+        let mut plain = Vec::new();
+        DynamicImage::new_rgb8(width, height)
+            .write_to(&mut std::io::Cursor::new(&mut plain), ImageFormat::Jpeg)
+            .unwrap();
+        let mut exif = b"Exif\0\0MM\0\x2a\0\0\0\x08\0\x01\x01\x12\0\x03\0\0\0\x01\0".to_vec();
+        exif.extend([orientation, 0, 0, 0, 0, 0, 0]);
+        let mut jpeg = plain[..2].to_vec();
+        jpeg.extend([0xFF, 0xE1]);
+        jpeg.extend(((exif.len() + 2) as u16).to_be_bytes());
+        jpeg.extend(exif);
+        jpeg.extend(&plain[2..]);
+        jpeg
+    }
+
+    #[test]
+    fn photos_are_rotated_upright_and_stripped_of_exif() {
+        let upload = jpeg_with_orientation(40, 20, 6);
+        assert!(upload.windows(4).any(|w| w == b"Exif"));
+
+        let clean = normalize_photo(&upload, 0).unwrap().unwrap();
+        assert!(!clean.windows(4).any(|w| w == b"Exif"));
+        let img = image::load_from_memory(&clean).unwrap();
+        assert_eq!((img.width(), img.height()), (20, 40));
+
+        let small = normalize_photo(&upload, 10).unwrap().unwrap();
+        let img = image::load_from_memory(&small).unwrap();
+        assert_eq!((img.width(), img.height()), (5, 10));
+    }
+
+    #[test]
+    fn heic_is_detected_by_type_or_extension() {
+        assert!(is_heic("image/heic", "IMG_0001"));
+        assert!(is_heic("application/octet-stream", "IMG_0001.HEIC"));
+        assert!(!is_heic("image/jpeg", "photo.jpg"));
+    }
+
+    #[test]
+    fn tags_are_normalized_and_deduplicated() {
+        assert_eq!(
+            normalize_tags(&["  Sortie au  Parc ", "#sortie au parc", "", "Peinture"]),
+            vec!["sortie au parc", "peinture"]
+        );
+        assert_eq!(parse_tag_list("hiver, #Neige,,hiver"), vec!["hiver", "neige"]);
+        assert_eq!(normalize_tags(&["x".repeat(80)])[0].chars().count(), MAX_TAG_CHARS);
+        let many: Vec<String> = (0..30).map(|i| format!("t{i}")).collect();
+        assert_eq!(normalize_tags(&many).len(), MAX_TAGS);
+    }
+}
@@ -0,0 +1,164 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::{auth::AuthenticatedUser, media::MediaComment, user::UserRole},
+    services::{groups::GroupService, media::MediaService},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum MediaCommentError {
+    #[error("Média introuvable")]
+    MediaNotFound,
+    #[error("Seuls les parents des enfants de ce média peuvent le commenter")]
+    NotAParent,
+    #[error("Commentaire introuvable")]
+    NotFound,
+    #[error("Seuls l'auteur et les administrateurs peuvent retirer ce commentaire")]
+    NotAuthor,
+    #[error("Le commentaire est vide")]
+    Empty,
+}
+
+fn comment_cols(schema: &str) -> String {
+    format!(
+        "mc.id, mc.media_id, mc.author_id,
+         (SELECT CONCAT(u.first_name, ' ', u.last_name) FROM {schema}.users u WHERE u.id = mc.author_id) AS author_name,
+         (SELECT u.role::text FROM {schema}.users u WHERE u.id = mc.author_id) AS author_role,
+         mc.content, mc.created_at"
+    )
+}
+
+fn is_admin(user: &AuthenticatedUser) -> bool {
+    matches!(user.role, UserRole::AdminGarderie | UserRole::SuperAdmin)
+}
+
+pub struct MediaCommentService;
+
+impl MediaCommentService {
+    /// Comments are for staff who can see the media and for the parents of the
+    /// children in it — not every parent a group or public photo is shown to.
+    async fn ensure_access(pool: &PgPool, tenant: &str, user: &AuthenticatedUser, media_id: Uuid) -> anyhow::Result<()> {
+        let is_staff = user.role != UserRole::Parent;
+        let scope = GroupService::educator_scope(pool, tenant, user).await?;
+        if !MediaService::is_visible(pool, tenant, media_id, user.user_id, is_staff, scope.as_deref()).await? {
+            return Err(MediaCommentError::MediaNotFound.into());
+        }
+        if is_staff {
+            return Ok(());
+        }
+        let schema = schema_name(tenant);
+        let parent: bool = sqlx::query_scalar(&format!(
+            "SELECT EXISTS (
+                SELECT 1 FROM {schema}.media_children mch
+                JOIN {schema}.child_parents cp ON cp.child_id = mch.child_id
+                WHERE mch.media_id = $1 AND cp.user_id = $2
+             )"
+        ))
+        .bind(media_id)
+        .bind(user.user_id)
+        .fetch_one(pool)
+        .await?;
+        if !parent {
+            return Err(MediaCommentError::NotAParent.into());
+        }
+        Ok(())
+    }
+
+    /// Comments of a media, oldest first.
+    pub async fn list(
+        pool: &PgPool,
+        tenant: &str,
+        user: &AuthenticatedUser,
+        media_id: Uuid,
+    ) -> anyhow::Result<Vec<MediaComment>> {
+        Self::ensure_access(pool, tenant, user, media_id).await?;
+        let schema = schema_name(tenant);
+        let cols = comment_cols(&schema);
+        Ok(sqlx::query_as::<_, MediaComment>(&format!(
+            "SELECT {cols} FROM {schema}.media_comments mc
+             WHERE mc.media_id = $1
+             ORDER BY mc.created_at, mc.id"
+        ))
+        .bind(media_id)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    pub async fn create(
+        pool: &PgPool,
+        tenant: &str,
+        user: &AuthenticatedUser,
+        media_id: Uuid,
+        content: &str,
+    ) -> anyhow::Result<MediaComment> {
+        let content = content.trim();
+        if content.is_empty() {
+            return Err(MediaCommentError::Empty.into());
+        }
+        Self::ensure_access(pool, tenant, user, media_id).await?;
+        let schema = schema_name(tenant);
+        let id: Uuid = sqlx::query_scalar(&format!(
+            "INSERT INTO {schema}.media_comments (media_id, author_id, content) VALUES ($1, $2, $3) RETURNING id"
+        ))
+        .bind(media_id)
+        .bind(user.user_id)
+        .bind(content)
+        .fetch_one(pool)
+        .await?;
+        Self::get(pool, tenant, id).await?.ok_or_else(|| MediaCommentError::NotFound.into())
+    }
+
+    pub async fn get(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<Option<MediaComment>> {
+        let schema = schema_name(tenant);
+        let cols = comment_cols(&schema);
+        Ok(sqlx::query_as::<_, MediaComment>(&format!(
+            "SELECT {cols} FROM {schema}.media_comments mc WHERE mc.id = $1"
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    /// Delete a comment of the media: authors their own, admins any (moderation).
+    /// Returns the deleted comment.
+    pub async fn delete(
+        pool: &PgPool,
+        tenant: &str,
+        user: &AuthenticatedUser,
+        media_id: Uuid,
+        id: Uuid,
+    ) -> anyhow::Result<MediaComment> {
+        let comment = Self::get(pool, tenant, id)
+            .await?
+            .filter(|c| c.media_id == media_id)
+            .ok_or(MediaCommentError::NotFound)?;
+        if comment.author_id != Some(user.user_id) && !is_admin(user) {
+            return Err(MediaCommentError::NotAuthor.into());
+        }
+        let schema = schema_name(tenant);
+        sqlx::query(&format!("DELETE FROM {schema}.media_comments WHERE id = $1"))
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(comment)
+    }
+
+    /// Who hears about a new comment: the uploader and the earlier commenters,
+    /// except its author and inactive accounts.
+    pub async fn recipients(pool: &PgPool, tenant: &str, comment: &MediaComment) -> anyhow::Result<Vec<Uuid>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_scalar(&format!(
+            "SELECT u.id FROM {schema}.users u
+             WHERE u.is_active = TRUE AND u.id IS DISTINCT FROM $2
+               AND (u.id = (SELECT uploader_id FROM {schema}.media WHERE id = $1)
+                    OR u.id IN (SELECT author_id FROM {schema}.media_comments WHERE media_id = $1 AND id != $3))"
+        ))
+        .bind(comment.media_id)
+        .bind(comment.author_id)
+        .bind(comment.id)
+        .fetch_all(pool)
+        .await?)
+    }
+}
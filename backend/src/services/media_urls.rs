@@ -0,0 +1,147 @@
+use chrono::Utc;
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{
+    config::Config,
+    models::{document::Document, media::Media},
+    services::encryption::decode_master_key,
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Expiries are rounded up to the hour so that a file keeps the same URL (and
+/// stays in the browser cache) across list calls.
+const EXPIRY_STEP_SECS: i64 = 3600;
+
+/// Why a request to `/media/files` was not signed correctly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UrlDenied {
+    Unsigned,
+    Expired,
+    BadSignature,
+}
+
+impl UrlDenied {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UrlDenied::Unsigned => "unsigned",
+            UrlDenied::Expired => "expired",
+            UrlDenied::BadSignature => "bad_signature",
+        }
+    }
+}
+
+/// Signs `/media/files/{path}` URLs with an expiry, so that knowing a file's path
+/// is no longer enough to read it.
+#[derive(Clone)]
+pub struct MediaUrlSigner {
+    key: [u8; 32],
+    ttl_secs: i64,
+}
+
+impl MediaUrlSigner {
+    pub fn new(master_key: &str, ttl_secs: i64) -> anyhow::Result<Self> {
+        let master_key = decode_master_key(master_key)?;
+        let mut key = [0u8; 32];
+        Hkdf::<Sha256>::new(None, &master_key)
+            .expand(b"minispace-media-urls", &mut key)
+            .map_err(|_| anyhow::anyhow!("Failed to derive media URL key"))?;
+        Ok(Self { key, ttl_secs: ttl_secs.max(60) })
+    }
+
+    pub fn from_config(config: &Config) -> anyhow::Result<Self> {
+        Self::new(&config.encryption_master_key, config.media_url_ttl_secs)
+    }
+
+    fn mac(&self, path: &str, expires: i64) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts any key length");
+        mac.update(path.as_bytes());
+        mac.update(b"\n");
+        mac.update(expires.to_string().as_bytes());
+        mac
+    }
+
+    fn expiry(&self, now: i64) -> i64 {
+        (now + self.ttl_secs + EXPIRY_STEP_SECS - 1) / EXPIRY_STEP_SECS * EXPIRY_STEP_SECS
+    }
+
+    fn sign_at(&self, path: &str, now: i64) -> String {
+        let expires = self.expiry(now);
+        let sig = hex::encode(self.mac(path, expires).finalize().into_bytes());
+        format!("/media/files/{path}?exp={expires}&sig={sig}")
+    }
+
+    /// Signed URL of a stored file (path relative to the media directory).
+    pub fn sign(&self, path: &str) -> String {
+        self.sign_at(path, Utc::now().timestamp())
+    }
+
+    fn verify_at(&self, path: &str, expires: Option<i64>, sig: Option<&str>, now: i64) -> Result<(), UrlDenied> {
+        let (Some(expires), Some(sig)) = (expires, sig) else {
+            return Err(UrlDenied::Unsigned);
+        };
+        let sig = hex::decode(sig).map_err(|_| UrlDenied::BadSignature)?;
+        self.mac(path, expires).verify_slice(&sig).map_err(|_| UrlDenied::BadSignature)?;
+        if expires <= now {
+            return Err(UrlDenied::Expired);
+        }
+        Ok(())
+    }
+
+    /// Check the `exp` and `sig` query parameters of a request for `path`.
+    pub fn verify(&self, path: &str, expires: Option<i64>, sig: Option<&str>) -> Result<(), UrlDenied> {
+        self.verify_at(path, expires, sig, Utc::now().timestamp())
+    }
+
+    /// Fill the URLs of a media listed to a user.
+    pub fn sign_media(&self, media: &mut Media) {
+        media.url = Some(self.sign(&media.storage_path));
+        media.thumbnail_url = media.thumbnail_path.as_deref().map(|p| self.sign(p));
+        media.sprite_url = media.sprite_path.as_deref().map(|p| self.sign(p));
+    }
+
+    pub fn sign_document(&self, doc: &mut Document) {
+        doc.url = Some(self.sign(&doc.storage_path));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+
+    fn parts(url: &str) -> (i64, String) {
+        let query = url.split_once('?').unwrap().1;
+        let (exp, sig) = query.split_once('&').unwrap();
+        (exp.trim_start_matches("exp=").parse().unwrap(), sig.trim_start_matches("sig=").to_string())
+    }
+
+    #[test]
+    fn signed_urls_verify_until_they_expire() {
+        let signer = MediaUrlSigner::new(KEY, 6 * 3600).unwrap();
+        let now = 1_800_000_123;
+        let url = signer.sign_at("demo/2026/02/a.jpg", now);
+        assert!(url.starts_with("/media/files/demo/2026/02/a.jpg?exp="));
+        let (exp, sig) = parts(&url);
+        assert_eq!(exp % EXPIRY_STEP_SECS, 0);
+        assert!(exp >= now + 6 * 3600);
+        assert_eq!(signer.verify_at("demo/2026/02/a.jpg", Some(exp), Some(&sig), now), Ok(()));
+        assert_eq!(signer.verify_at("demo/2026/02/a.jpg", Some(exp), Some(&sig), exp), Err(UrlDenied::Expired));
+    }
+
+    #[test]
+    fn tampered_or_missing_signatures_are_denied() {
+        let signer = MediaUrlSigner::new(KEY, 3600).unwrap();
+        let now = 1_800_000_100;
+        let (exp, sig) = parts(&signer.sign_at("demo/a.jpg", now));
+        assert_eq!(signer.verify_at("demo/b.jpg", Some(exp), Some(&sig), now), Err(UrlDenied::BadSignature));
+        assert_eq!(signer.verify_at("demo/a.jpg", Some(exp + 3600), Some(&sig), now), Err(UrlDenied::BadSignature));
+        assert_eq!(signer.verify_at("demo/a.jpg", Some(exp), Some("zz"), now), Err(UrlDenied::BadSignature));
+        assert_eq!(signer.verify_at("demo/a.jpg", None, None, now), Err(UrlDenied::Unsigned));
+        // Same hour, same URL
+        assert_eq!(signer.sign_at("demo/a.jpg", now), signer.sign_at("demo/a.jpg", now + 60));
+    }
+}
@@ -0,0 +1,78 @@
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::db::tenant_db::TenantDb;
+use crate::services::branding::BrandingService;
+use crate::services::email::EmailService;
+use crate::services::meetings::MeetingService;
+
+/// How often upcoming meetings are checked for reminders.
+const CHECK_INTERVAL_SECS: u64 = 15 * 60;
+
+/// Spawn a background task that emails parents a reminder once their booked
+/// meeting is less than `REMINDER_LEAD_HOURS` away. Each booking is reminded once.
+pub fn start(pool: PgPool, email: Option<Arc<EmailService>>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(CHECK_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let Some(_job) = crate::services::worker::job().await else {
+                break;
+            };
+
+            let Some(ref email_svc) = email else {
+                continue;
+            };
+
+            let tenants: Vec<String> = match sqlx::query_scalar(
+                "SELECT slug FROM public.garderies WHERE is_active = TRUE AND slug != 'demo'",
+            )
+            .fetch_all(&pool)
+            .await
+            {
+                Ok(rows) => rows,
+                Err(e) => {
+                    warn!("Meeting scheduler: failed to query tenants: {e}");
+                    continue;
+                }
+            };
+
+            for slug in tenants {
+                TenantDb::scope(slug.clone(), async {
+                    let due = match MeetingService::due_reminders(&pool, &slug).await {
+                        Ok(due) => due,
+                        Err(e) => {
+                            warn!("Meeting scheduler: failed to list reminders for '{slug}': {e}");
+                            return;
+                        }
+                    };
+                    if due.is_empty() {
+                        return;
+                    }
+
+                    let (garderie_name, branding) = BrandingService::for_email(&pool, &slug).await;
+                    let mut sent = 0;
+                    for meeting in due {
+                        // Mark first so a failing SMTP relay doesn't cause repeated attempts.
+                        if let Err(e) = MeetingService::mark_reminded(&pool, &slug, meeting.booking_id).await {
+                            warn!("Meeting scheduler: failed to mark booking {}: {e}", meeting.booking_id);
+                            continue;
+                        }
+                        match email_svc.send_meeting_reminder(&slug, &meeting, &garderie_name, &branding).await {
+                            Ok(_) => sent += 1,
+                            Err(e) => warn!(
+                                "Meeting scheduler: failed to remind {} for booking {}: {e}",
+                                meeting.parent_email, meeting.booking_id
+                            ),
+                        }
+                    }
+                    if sent > 0 {
+                        info!("Meeting scheduler: {sent} reminder(s) sent for '{slug}'");
+                    }
+                })
+                .await;
+            }
+        }
+    });
+}
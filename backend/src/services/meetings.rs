@@ -0,0 +1,373 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::meeting::{
+        BookSlotRequest, MeetingDetails, MeetingSlot, PublishSlotsRequest, SlotInput, SlotQuery,
+    },
+};
+
+/// Reminders go out once a booked meeting is less than this far away.
+pub const REMINDER_LEAD_HOURS: i64 = 24;
+
+/// Why a slot or booking operation was refused.
+#[derive(Debug, thiserror::Error)]
+pub enum MeetingError {
+    #[error("Créneau introuvable")]
+    SlotNotFound,
+    #[error("Rendez-vous introuvable")]
+    BookingNotFound,
+    #[error("Ce créneau vient d'être réservé, veuillez en choisir un autre")]
+    SlotTaken,
+    #[error("Ce créneau est déjà passé")]
+    SlotInPast,
+    #[error("Créneau invalide : la fin doit suivre le début, dans le futur")]
+    InvalidSlot,
+    #[error("Ce créneau chevauche un autre créneau de l'éducatrice")]
+    Overlap,
+    #[error("Cet utilisateur ne fait pas partie du personnel")]
+    NotStaff,
+    #[error("Ce créneau est réservé — annulez d'abord le rendez-vous")]
+    SlotBooked,
+}
+
+fn details_select(schema: &str) -> String {
+    format!(
+        r#"SELECT b.id AS booking_id, s.id AS slot_id, s.starts_at, s.ends_at, s.location, b.notes, b.status,
+                  c.id AS child_id, c.first_name || ' ' || c.last_name AS child_name,
+                  e.id AS educator_id, e.first_name || ' ' || e.last_name AS educator_name,
+                  p.id AS parent_id, p.first_name || ' ' || p.last_name AS parent_name, p.email AS parent_email
+           FROM "{schema}".meeting_bookings b
+           JOIN "{schema}".meeting_slots s ON s.id = b.slot_id
+           JOIN "{schema}".children c ON c.id = b.child_id
+           JOIN "{schema}".users e ON e.id = s.educator_id
+           JOIN "{schema}".users p ON p.id = b.parent_id"#
+    )
+}
+
+/// Start of a local calendar day, as UTC.
+fn local_midnight(date: NaiveDate) -> DateTime<Utc> {
+    Local
+        .from_local_datetime(&date.and_time(NaiveTime::MIN))
+        .earliest()
+        .map(|d| d.with_timezone(&Utc))
+        .unwrap_or_else(|| date.and_time(NaiveTime::MIN).and_utc())
+}
+
+/// Slots must be in the future and must not overlap each other.
+fn validate_slots(slots: &[SlotInput], now: DateTime<Utc>) -> Result<(), MeetingError> {
+    if slots.iter().any(|s| s.ends_at <= s.starts_at || s.starts_at <= now) {
+        return Err(MeetingError::InvalidSlot);
+    }
+    let mut sorted: Vec<&SlotInput> = slots.iter().collect();
+    sorted.sort_by_key(|s| s.starts_at);
+    if sorted.windows(2).any(|w| w[1].starts_at < w[0].ends_at) {
+        return Err(MeetingError::Overlap);
+    }
+    Ok(())
+}
+
+pub struct MeetingService;
+
+impl MeetingService {
+    /// Slots between `from` and `to` (default: the next 30 days), earliest first.
+    /// Booking details are only filled in for staff.
+    pub async fn list_slots(
+        pool: &PgPool,
+        tenant: &str,
+        query: &SlotQuery,
+        is_staff: bool,
+    ) -> anyhow::Result<Vec<MeetingSlot>> {
+        let schema = schema_name(tenant);
+        let from = query.from.unwrap_or_else(|| Local::now().date_naive());
+        let to = query.to.unwrap_or(from + Duration::days(30));
+        let slots = sqlx::query_as::<_, MeetingSlot>(&format!(
+            r#"SELECT s.id, s.educator_id, e.first_name || ' ' || e.last_name AS educator_name,
+                      s.starts_at, s.ends_at, s.location,
+                      b.id IS NOT NULL AS booked,
+                      CASE WHEN $4 THEN b.id END AS booking_id,
+                      CASE WHEN $4 THEN c.first_name || ' ' || c.last_name END AS child_name,
+                      CASE WHEN $4 THEN p.first_name || ' ' || p.last_name END AS parent_name
+               FROM "{schema}".meeting_slots s
+               JOIN "{schema}".users e ON e.id = s.educator_id
+               LEFT JOIN "{schema}".meeting_bookings b ON b.slot_id = s.id AND b.status = 'booked'
+               LEFT JOIN "{schema}".children c ON c.id = b.child_id
+               LEFT JOIN "{schema}".users p ON p.id = b.parent_id
+               WHERE s.starts_at >= $1 AND s.starts_at < $2
+                 AND ($3::UUID IS NULL OR s.educator_id = $3)
+               ORDER BY s.starts_at, educator_name"#
+        ))
+        .bind(local_midnight(from))
+        .bind(local_midnight(to + Duration::days(1)))
+        .bind(query.educator_id)
+        .bind(is_staff)
+        .fetch_all(pool)
+        .await?;
+        Ok(slots)
+    }
+
+    /// Publish a batch of slots for one educator. The batch is rejected as a
+    /// whole if any slot overlaps another one of the same educator.
+    pub async fn publish_slots(
+        pool: &PgPool,
+        tenant: &str,
+        req: &PublishSlotsRequest,
+        created_by: Uuid,
+    ) -> anyhow::Result<usize> {
+        validate_slots(&req.slots, Utc::now())?;
+
+        let schema = schema_name(tenant);
+        let is_staff: bool = sqlx::query_scalar(&format!(
+            r#"SELECT EXISTS(SELECT 1 FROM "{schema}".users
+                             WHERE id = $1 AND is_active = TRUE AND role::TEXT <> 'parent')"#
+        ))
+        .bind(req.educator_id)
+        .fetch_one(pool)
+        .await?;
+        if !is_staff {
+            return Err(MeetingError::NotStaff.into());
+        }
+
+        let mut tx = pool.begin().await?;
+        // Serialize publications for this educator so concurrent batches can't overlap.
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(format!("{schema}:meeting_slots:{}", req.educator_id))
+            .execute(&mut *tx)
+            .await?;
+
+        for slot in &req.slots {
+            let overlaps: bool = sqlx::query_scalar(&format!(
+                r#"SELECT EXISTS(SELECT 1 FROM "{schema}".meeting_slots
+                                 WHERE educator_id = $1 AND starts_at < $3 AND ends_at > $2)"#
+            ))
+            .bind(req.educator_id)
+            .bind(slot.starts_at)
+            .bind(slot.ends_at)
+            .fetch_one(&mut *tx)
+            .await?;
+            if overlaps {
+                return Err(MeetingError::Overlap.into());
+            }
+
+            sqlx::query(&format!(
+                r#"INSERT INTO "{schema}".meeting_slots (educator_id, starts_at, ends_at, location, created_by)
+                   VALUES ($1, $2, $3, $4, $5)"#
+            ))
+            .bind(req.educator_id)
+            .bind(slot.starts_at)
+            .bind(slot.ends_at)
+            .bind(req.location.as_deref().map(str::trim).filter(|l| !l.is_empty()))
+            .bind(created_by)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(req.slots.len())
+    }
+
+    /// Remove an unbooked slot.
+    pub async fn delete_slot(pool: &PgPool, tenant: &str, slot_id: Uuid) -> anyhow::Result<()> {
+        let schema = schema_name(tenant);
+        let booked: Option<bool> = sqlx::query_scalar(&format!(
+            r#"SELECT EXISTS(SELECT 1 FROM "{schema}".meeting_bookings WHERE slot_id = s.id AND status = 'booked')
+               FROM "{schema}".meeting_slots s WHERE s.id = $1"#
+        ))
+        .bind(slot_id)
+        .fetch_optional(pool)
+        .await?;
+        match booked {
+            None => return Err(MeetingError::SlotNotFound.into()),
+            Some(true) => return Err(MeetingError::SlotBooked.into()),
+            Some(false) => {}
+        }
+        sqlx::query(&format!(r#"DELETE FROM "{schema}".meeting_slots WHERE id = $1"#))
+            .bind(slot_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Book a slot for a child. The slot row is locked for the duration of the
+    /// transaction, and the partial unique index catches any remaining race.
+    /// The caller must have checked that the parent is linked to the child.
+    pub async fn book(
+        pool: &PgPool,
+        tenant: &str,
+        slot_id: Uuid,
+        parent_id: Uuid,
+        req: &BookSlotRequest,
+    ) -> anyhow::Result<MeetingDetails> {
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+
+        let starts_at: Option<DateTime<Utc>> = sqlx::query_scalar(&format!(
+            r#"SELECT starts_at FROM "{schema}".meeting_slots WHERE id = $1 FOR UPDATE"#
+        ))
+        .bind(slot_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        match starts_at {
+            None => return Err(MeetingError::SlotNotFound.into()),
+            Some(t) if t <= Utc::now() => return Err(MeetingError::SlotInPast.into()),
+            Some(_) => {}
+        }
+
+        let inserted = sqlx::query_scalar::<_, Uuid>(&format!(
+            r#"INSERT INTO "{schema}".meeting_bookings (slot_id, child_id, parent_id, notes)
+               VALUES ($1, $2, $3, $4)
+               RETURNING id"#
+        ))
+        .bind(slot_id)
+        .bind(req.child_id)
+        .bind(parent_id)
+        .bind(req.notes.as_deref().map(str::trim).filter(|n| !n.is_empty()))
+        .fetch_one(&mut *tx)
+        .await;
+
+        let booking_id = match inserted {
+            Ok(id) => id,
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+                return Err(MeetingError::SlotTaken.into())
+            }
+            Err(e) => return Err(e.into()),
+        };
+        tx.commit().await?;
+
+        Self::get_booking(pool, tenant, booking_id)
+            .await?
+            .ok_or_else(|| MeetingError::BookingNotFound.into())
+    }
+
+    pub async fn get_booking(
+        pool: &PgPool,
+        tenant: &str,
+        booking_id: Uuid,
+    ) -> anyhow::Result<Option<MeetingDetails>> {
+        let schema = schema_name(tenant);
+        let booking = sqlx::query_as::<_, MeetingDetails>(&format!(
+            "{} WHERE b.id = $1",
+            details_select(&schema)
+        ))
+        .bind(booking_id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(booking)
+    }
+
+    /// Cancel an active booking, freeing its slot. Returns the cancelled meeting.
+    pub async fn cancel(
+        pool: &PgPool,
+        tenant: &str,
+        booking_id: Uuid,
+    ) -> anyhow::Result<MeetingDetails> {
+        let schema = schema_name(tenant);
+        let updated = sqlx::query(&format!(
+            r#"UPDATE "{schema}".meeting_bookings
+               SET status = 'cancelled', cancelled_at = NOW()
+               WHERE id = $1 AND status = 'booked'"#
+        ))
+        .bind(booking_id)
+        .execute(pool)
+        .await?;
+        if updated.rows_affected() == 0 {
+            return Err(MeetingError::BookingNotFound.into());
+        }
+        Self::get_booking(pool, tenant, booking_id)
+            .await?
+            .ok_or_else(|| MeetingError::BookingNotFound.into())
+    }
+
+    /// Upcoming active bookings, optionally restricted to one parent.
+    pub async fn list_upcoming(
+        pool: &PgPool,
+        tenant: &str,
+        parent_id: Option<Uuid>,
+    ) -> anyhow::Result<Vec<MeetingDetails>> {
+        let schema = schema_name(tenant);
+        let bookings = sqlx::query_as::<_, MeetingDetails>(&format!(
+            "{} WHERE b.status = 'booked' AND s.ends_at > NOW()
+                 AND ($1::UUID IS NULL OR b.parent_id = $1)
+             ORDER BY s.starts_at",
+            details_select(&schema)
+        ))
+        .bind(parent_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(bookings)
+    }
+
+    /// Booked meetings of one day, for one educator or for everyone.
+    pub async fn agenda(
+        pool: &PgPool,
+        tenant: &str,
+        educator_id: Option<Uuid>,
+        date: NaiveDate,
+    ) -> anyhow::Result<Vec<MeetingDetails>> {
+        let schema = schema_name(tenant);
+        let meetings = sqlx::query_as::<_, MeetingDetails>(&format!(
+            "{} WHERE b.status = 'booked' AND s.starts_at >= $1 AND s.starts_at < $2
+                 AND ($3::UUID IS NULL OR s.educator_id = $3)
+             ORDER BY s.starts_at, educator_name",
+            details_select(&schema)
+        ))
+        .bind(local_midnight(date))
+        .bind(local_midnight(date + Duration::days(1)))
+        .bind(educator_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(meetings)
+    }
+
+    /// Booked meetings starting within the reminder window that haven't been reminded yet.
+    pub async fn due_reminders(pool: &PgPool, tenant: &str) -> anyhow::Result<Vec<MeetingDetails>> {
+        let schema = schema_name(tenant);
+        let due = sqlx::query_as::<_, MeetingDetails>(&format!(
+            "{} WHERE b.status = 'booked' AND b.reminder_sent_at IS NULL
+                 AND s.starts_at > NOW() AND s.starts_at <= NOW() + make_interval(hours => $1)
+             ORDER BY s.starts_at",
+            details_select(&schema)
+        ))
+        .bind(REMINDER_LEAD_HOURS as i32)
+        .fetch_all(pool)
+        .await?;
+        Ok(due)
+    }
+
+    pub async fn mark_reminded(pool: &PgPool, tenant: &str, booking_id: Uuid) -> anyhow::Result<()> {
+        let schema = schema_name(tenant);
+        sqlx::query(&format!(
+            r#"UPDATE "{schema}".meeting_bookings SET reminder_sent_at = NOW() WHERE id = $1"#
+        ))
+        .bind(booking_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slot(now: DateTime<Utc>, start_min: i64, end_min: i64) -> SlotInput {
+        SlotInput {
+            starts_at: now + Duration::minutes(start_min),
+            ends_at: now + Duration::minutes(end_min),
+        }
+    }
+
+    #[test]
+    fn slot_batches_must_be_future_and_disjoint() {
+        let now = Utc::now();
+        assert!(validate_slots(&[slot(now, 60, 75), slot(now, 75, 90)], now).is_ok());
+        assert!(matches!(
+            validate_slots(&[slot(now, 60, 90), slot(now, 75, 105)], now),
+            Err(MeetingError::Overlap)
+        ));
+        assert!(matches!(validate_slots(&[slot(now, -30, 15)], now), Err(MeetingError::InvalidSlot)));
+        assert!(matches!(validate_slots(&[slot(now, 60, 60)], now), Err(MeetingError::InvalidSlot)));
+    }
+}
@@ -1,12 +1,37 @@
+use std::collections::HashMap;
+
 use chrono::NaiveDate;
 use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::{
     db::tenant::schema_name,
-    models::menu::{DailyMenu, UpsertMenuRequest},
+    models::{
+        child::Child,
+        journal::WEATHER_CONDITIONS,
+        menu::{
+            AllergenConflict, DailyMenu, MenuItem, MenuWeekView, PlanDayRequest, PlanWeekRequest,
+            UpsertMenuRequest, ALLERGENS, MEALS,
+        },
+    },
 };
 
+const MENU_COLS: &str = "id, date, weather::TEXT AS weather, menu, collation_matin, diner, \
+     collation_apres_midi, created_by, created_at, updated_at";
+
+/// Rejected menu or allergy data, with a message for the user.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct InvalidMenu(pub String);
+
+/// Ensure every tag is a known allergen.
+pub fn validate_allergens(tags: &[String]) -> Result<(), InvalidMenu> {
+    match tags.iter().find(|t| !ALLERGENS.contains(&t.as_str())) {
+        Some(t) => Err(InvalidMenu(format!("Allergène inconnu : {t}"))),
+        None => Ok(()),
+    }
+}
+
 pub struct MenuService;
 
 impl MenuService {
@@ -18,9 +43,8 @@ impl MenuService {
     ) -> anyhow::Result<Vec<DailyMenu>> {
         let schema = schema_name(tenant);
         let week_end = week_start + chrono::Duration::days(4);
-        let entries = sqlx::query_as::<_, DailyMenu>(&format!(
-            r#"SELECT id, date, weather::TEXT AS weather, menu, collation_matin, diner, collation_apres_midi,
-                      created_by, created_at, updated_at
+        let mut entries = sqlx::query_as::<_, DailyMenu>(&format!(
+            r#"SELECT {MENU_COLS}
                FROM "{schema}".daily_menus
                WHERE date BETWEEN $1 AND $2
                ORDER BY date"#
@@ -29,9 +53,42 @@ impl MenuService {
         .bind(week_end)
         .fetch_all(pool)
         .await?;
+        Self::attach_items(pool, &schema, &mut entries).await?;
         Ok(entries)
     }
 
+    /// Week's menus plus the allergen conflicts for the given children.
+    pub async fn week_view(
+        pool: &PgPool,
+        tenant: &str,
+        week_start: NaiveDate,
+        children: &[Child],
+    ) -> anyhow::Result<MenuWeekView> {
+        let days = Self::list_week(pool, tenant, week_start).await?;
+        let conflicts = find_conflicts(&days, children);
+        Ok(MenuWeekView { week_start, days, conflicts })
+    }
+
+    /// Structured items planned for one date, in serving order.
+    pub async fn items_for_date(
+        pool: &PgPool,
+        tenant: &str,
+        date: NaiveDate,
+    ) -> anyhow::Result<Vec<MenuItem>> {
+        let schema = schema_name(tenant);
+        let items = sqlx::query_as::<_, MenuItem>(&format!(
+            r#"SELECT i.id, i.menu_id, i.meal, i.name, i.allergens, i.position
+               FROM "{schema}".menu_items i
+               JOIN "{schema}".daily_menus m ON m.id = i.menu_id
+               WHERE m.date = $1
+               ORDER BY i.position"#
+        ))
+        .bind(date)
+        .fetch_all(pool)
+        .await?;
+        Ok(items)
+    }
+
     /// Insert or update the garderie-level menu for a specific date.
     pub async fn upsert(
         pool: &PgPool,
@@ -40,17 +97,17 @@ impl MenuService {
         created_by: Uuid,
     ) -> anyhow::Result<DailyMenu> {
         let schema = schema_name(tenant);
-        let entry = sqlx::query_as::<_, DailyMenu>(&format!(
+        let mut entry = sqlx::query_as::<_, DailyMenu>(&format!(
             r#"INSERT INTO "{schema}".daily_menus (date, weather, menu, collation_matin, diner, collation_apres_midi, created_by)
                VALUES ($1, $2::TEXT::"{schema}".weather_condition, $3, $4, $5, $6, $7)
-               ON CONFLICT (date) DO UPDATE SET
+               ON CONFLICT ON CONSTRAINT daily_menus_date_key DO UPDATE SET
                    weather = COALESCE(EXCLUDED.weather, daily_menus.weather),
                    menu = COALESCE(EXCLUDED.menu, daily_menus.menu),
                    collation_matin = COALESCE(EXCLUDED.collation_matin, daily_menus.collation_matin),
                    diner = COALESCE(EXCLUDED.diner, daily_menus.diner),
                    collation_apres_midi = COALESCE(EXCLUDED.collation_apres_midi, daily_menus.collation_apres_midi),
                    updated_at = NOW()
-               RETURNING id, date, weather::TEXT AS weather, menu, collation_matin, diner, collation_apres_midi, created_by, created_at, updated_at"#
+               RETURNING {MENU_COLS}"#
         ))
         .bind(req.date)
         .bind(&req.weather)
@@ -61,6 +118,230 @@ impl MenuService {
         .bind(created_by)
         .fetch_one(pool)
         .await?;
+        Self::attach_items(pool, &schema, std::slice::from_mut(&mut entry)).await?;
         Ok(entry)
     }
+
+    /// Replace the structured items of each listed day in one transaction.
+    /// The per-meal text columns are regenerated from the items so older
+    /// clients and emails keep showing the same menu.
+    pub async fn plan_week(
+        pool: &PgPool,
+        tenant: &str,
+        req: &PlanWeekRequest,
+        created_by: Uuid,
+    ) -> anyhow::Result<Vec<DailyMenu>> {
+        for day in &req.days {
+            validate_day(day)?;
+        }
+
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+        let mut menus = Vec::with_capacity(req.days.len());
+
+        for day in &req.days {
+            let text_for = |meal: &str| -> Option<String> {
+                let names: Vec<&str> = day
+                    .items
+                    .iter()
+                    .filter(|i| i.meal == meal)
+                    .map(|i| i.name.trim())
+                    .collect();
+                (!names.is_empty()).then(|| names.join(", "))
+            };
+
+            let mut menu = sqlx::query_as::<_, DailyMenu>(&format!(
+                r#"INSERT INTO "{schema}".daily_menus (date, weather, collation_matin, diner, collation_apres_midi, created_by)
+                   VALUES ($1, $2::TEXT::"{schema}".weather_condition, $3, $4, $5, $6)
+                   ON CONFLICT ON CONSTRAINT daily_menus_date_key DO UPDATE SET
+                       weather = COALESCE(EXCLUDED.weather, daily_menus.weather),
+                       collation_matin = EXCLUDED.collation_matin,
+                       diner = EXCLUDED.diner,
+                       collation_apres_midi = EXCLUDED.collation_apres_midi,
+                       updated_at = NOW()
+                   RETURNING {MENU_COLS}"#
+            ))
+            .bind(day.date)
+            .bind(&day.weather)
+            .bind(text_for("collation_matin"))
+            .bind(text_for("diner"))
+            .bind(text_for("collation_apres_midi"))
+            .bind(created_by)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            sqlx::query(&format!(r#"DELETE FROM "{schema}".menu_items WHERE menu_id = $1"#))
+                .bind(menu.id)
+                .execute(&mut *tx)
+                .await?;
+
+            for (position, item) in day.items.iter().enumerate() {
+                let mut allergens = item.allergens.clone();
+                allergens.sort();
+                allergens.dedup();
+                let row = sqlx::query_as::<_, MenuItem>(&format!(
+                    r#"INSERT INTO "{schema}".menu_items (menu_id, meal, name, allergens, position)
+                       VALUES ($1, $2, $3, $4, $5)
+                       RETURNING id, menu_id, meal, name, allergens, position"#
+                ))
+                .bind(menu.id)
+                .bind(&item.meal)
+                .bind(item.name.trim())
+                .bind(&allergens)
+                .bind(position as i32)
+                .fetch_one(&mut *tx)
+                .await?;
+                menu.items.push(row);
+            }
+            menus.push(menu);
+        }
+
+        tx.commit().await?;
+        menus.sort_by_key(|m| m.date);
+        Ok(menus)
+    }
+
+    async fn attach_items(pool: &PgPool, schema: &str, menus: &mut [DailyMenu]) -> anyhow::Result<()> {
+        if menus.is_empty() {
+            return Ok(());
+        }
+        let ids: Vec<Uuid> = menus.iter().map(|m| m.id).collect();
+        let items = sqlx::query_as::<_, MenuItem>(&format!(
+            r#"SELECT id, menu_id, meal, name, allergens, position
+               FROM "{schema}".menu_items
+               WHERE menu_id = ANY($1)
+               ORDER BY position"#
+        ))
+        .bind(&ids)
+        .fetch_all(pool)
+        .await?;
+
+        let mut by_menu: HashMap<Uuid, Vec<MenuItem>> = HashMap::new();
+        for item in items {
+            by_menu.entry(item.menu_id).or_default().push(item);
+        }
+        for menu in menus {
+            menu.items = by_menu.remove(&menu.id).unwrap_or_default();
+        }
+        Ok(())
+    }
+}
+
+fn validate_day(day: &PlanDayRequest) -> Result<(), InvalidMenu> {
+    if let Some(ref w) = day.weather {
+        if !WEATHER_CONDITIONS.contains(&w.as_str()) {
+            return Err(InvalidMenu(format!("Valeur de météo invalide : {w}")));
+        }
+    }
+    for item in &day.items {
+        if !MEALS.contains(&item.meal.as_str()) {
+            return Err(InvalidMenu(format!("Repas invalide : {}", item.meal)));
+        }
+        if item.name.trim().is_empty() {
+            return Err(InvalidMenu(format!("Plat sans nom le {}", day.date)));
+        }
+        validate_allergens(&item.allergens)?;
+    }
+    Ok(())
+}
+
+/// Every planned item containing at least one allergen from a child's profile.
+pub fn find_conflicts(days: &[DailyMenu], children: &[Child]) -> Vec<AllergenConflict> {
+    let mut conflicts = Vec::new();
+    for child in children.iter().filter(|c| !c.allergies.is_empty()) {
+        for day in days {
+            for item in &day.items {
+                let allergens: Vec<String> = item
+                    .allergens
+                    .iter()
+                    .filter(|a| child.allergies.contains(a))
+                    .cloned()
+                    .collect();
+                if !allergens.is_empty() {
+                    conflicts.push(AllergenConflict {
+                        child_id: child.id,
+                        child_name: format!("{} {}", child.first_name, child.last_name),
+                        date: day.date,
+                        meal: item.meal.clone(),
+                        item: item.name.clone(),
+                        allergens,
+                    });
+                }
+            }
+        }
+    }
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn item(menu_id: Uuid, meal: &str, name: &str, allergens: &[&str]) -> MenuItem {
+        MenuItem {
+            id: Uuid::new_v4(),
+            menu_id,
+            meal: meal.into(),
+            name: name.into(),
+            allergens: allergens.iter().map(|a| a.to_string()).collect(),
+            position: 0,
+        }
+    }
+
+    fn child(first_name: &str, allergies: &[&str]) -> Child {
+        Child {
+            id: Uuid::new_v4(),
+            first_name: first_name.into(),
+            last_name: "Tremblay".into(),
+            birth_date: NaiveDate::from_ymd_opt(2022, 3, 1).unwrap(),
+            photo_url: None,
+            group_id: None,
+            notes: None,
+            is_active: true,
+            start_date: None,
+            schedule_days: None,
+            allergies: allergies.iter().map(|a| a.to_string()).collect(),
+            monthly_fee_cents: None,
+            family_id: None,
+            avatar_iv: None,
+            avatar_tag: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn conflicts_match_child_allergies_only() {
+        let menu_id = Uuid::new_v4();
+        let day = DailyMenu {
+            id: menu_id,
+            date: NaiveDate::from_ymd_opt(2026, 10, 19).unwrap(),
+            weather: None,
+            menu: None,
+            collation_matin: None,
+            diner: None,
+            collation_apres_midi: None,
+            created_by: Uuid::new_v4(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            items: vec![
+                item(menu_id, "collation_matin", "Muffin", &["ble", "oeufs", "lait"]),
+                item(menu_id, "diner", "Poulet et riz", &[]),
+            ],
+        };
+        let children = [child("Léa", &["oeufs", "arachides"]), child("Noah", &[])];
+
+        let conflicts = find_conflicts(&[day], &children);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].child_name, "Léa Tremblay");
+        assert_eq!(conflicts[0].item, "Muffin");
+        assert_eq!(conflicts[0].allergens, vec!["oeufs".to_string()]);
+    }
+
+    #[test]
+    fn rejects_unknown_allergens() {
+        assert!(validate_allergens(&["lait".into(), "sesame".into()]).is_ok());
+        assert!(validate_allergens(&["chocolat".into()]).is_err());
+    }
 }
@@ -0,0 +1,124 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::message::{MessageDelivery, MessageDeliveryReport},
+    services::emergency::Outcome,
+};
+
+/// Skip reasons shown in the delivery panel.
+pub const MUTED: &str = "Fil mis en sourdine";
+pub const NO_DEVICE: &str = "Aucun appareil enregistré";
+pub const COOLDOWN: &str = "Regroupé avec un courriel récent du même fil";
+pub const NO_SMS_CREDITS: &str = "Crédits SMS du mois épuisés";
+
+/// Stored status and detail of an outcome.
+fn status(outcome: &Outcome) -> (&'static str, Option<&str>) {
+    match outcome {
+        Outcome::Sent => ("sent", None),
+        Outcome::Error(e) => ("failed", Some(e)),
+        Outcome::Skipped(reason) => ("skipped", Some(reason)),
+    }
+}
+
+impl MessageDeliveryReport {
+    fn new(message_id: Uuid, deliveries: Vec<MessageDelivery>) -> Self {
+        let count = |status: &str| deliveries.iter().filter(|d| d.status == status).count() as i64;
+        Self { message_id, sent: count("sent"), failed: count("failed"), skipped: count("skipped"), deliveries }
+    }
+}
+
+pub struct MessageDeliveryService;
+
+impl MessageDeliveryService {
+    /// Record what became of a message's notifications on one channel. A redelivered
+    /// event overwrites the earlier attempt.
+    pub async fn record(
+        pool: &PgPool,
+        tenant: &str,
+        message_id: Uuid,
+        channel: &str,
+        outcomes: &[(Uuid, Outcome)],
+    ) -> anyhow::Result<()> {
+        if outcomes.is_empty() {
+            return Ok(());
+        }
+        let schema = schema_name(tenant);
+        let (mut users, mut statuses, mut details) = (Vec::new(), Vec::new(), Vec::new());
+        for (user_id, outcome) in outcomes {
+            let (status, detail) = status(outcome);
+            users.push(*user_id);
+            statuses.push(status);
+            details.push(detail);
+        }
+        sqlx::query(&format!(
+            "INSERT INTO {schema}.message_deliveries (message_id, user_id, channel, status, detail)
+             SELECT $1, d.user_id, $2, d.status, d.detail
+             FROM UNNEST($3::uuid[], $4::text[], $5::text[]) AS d(user_id, status, detail)
+             JOIN {schema}.users u ON u.id = d.user_id
+             ON CONFLICT (message_id, user_id, channel) DO UPDATE
+             SET status = EXCLUDED.status, detail = EXCLUDED.detail, attempted_at = NOW()"
+        ))
+        .bind(message_id)
+        .bind(channel)
+        .bind(&users)
+        .bind(&statuses)
+        .bind(&details)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// GET /messages/:id/deliveries — `None` if the message does not exist.
+    pub async fn report(pool: &PgPool, tenant: &str, message_id: Uuid) -> anyhow::Result<Option<MessageDeliveryReport>> {
+        let schema = schema_name(tenant);
+        let exists: bool = sqlx::query_scalar(&format!("SELECT EXISTS(SELECT 1 FROM {schema}.messages WHERE id = $1)"))
+            .bind(message_id)
+            .fetch_one(pool)
+            .await?;
+        if !exists {
+            return Ok(None);
+        }
+
+        let deliveries = sqlx::query_as::<_, MessageDelivery>(&format!(
+            "SELECT d.user_id, u.first_name, u.last_name, d.channel, d.status, d.detail, d.attempted_at
+             FROM {schema}.message_deliveries d
+             JOIN {schema}.users u ON u.id = d.user_id
+             WHERE d.message_id = $1
+             ORDER BY u.last_name, u.first_name, d.channel"
+        ))
+        .bind(message_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(Some(MessageDeliveryReport::new(message_id, deliveries)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_counts_each_status() {
+        let delivery = |channel: &str, outcome: Outcome| {
+            let (status, detail) = status(&outcome);
+            MessageDelivery {
+                user_id: Uuid::nil(),
+                first_name: "Julie".into(),
+                last_name: "Tremblay".into(),
+                channel: channel.into(),
+                status: status.into(),
+                detail: detail.map(Into::into),
+                attempted_at: chrono::Utc::now(),
+            }
+        };
+        let report = MessageDeliveryReport::new(Uuid::nil(), vec![
+            delivery("email", Outcome::Sent),
+            delivery("push", Outcome::Skipped(NO_DEVICE.into())),
+            delivery("sms", Outcome::Error("Twilio returned 500".into())),
+        ]);
+        assert_eq!((report.sent, report.failed, report.skipped), (1, 1, 1));
+        assert_eq!(report.deliveries[1].detail.as_deref(), Some(NO_DEVICE));
+    }
+}
@@ -0,0 +1,323 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::{
+        auth::AuthenticatedUser,
+        message_draft::{MessageDraft, MessageDraftRequest, ParentRecipient, DRAFT_SCOPES, DRAFT_VARIABLES},
+        user::UserRole,
+    },
+    services::{email_i18n::fill, email_templates::used_placeholders},
+};
+
+const MAX_NAME_CHARS: usize = 255;
+const MAX_SUBJECT_CHARS: usize = 255;
+const MAX_CONTENT_CHARS: usize = 10_000;
+
+/// Used when a variable has no single value for the message, e.g. `{child_name}`
+/// in a group thread read by every family.
+const DEFAULT_CHILD_NAME: &str = "votre enfant";
+const DEFAULT_GROUP_NAME: &str = "le groupe";
+
+const DRAFT_COLS: &str =
+    "id, owner_id, name, is_template, subject, content, scope, group_id, child_id, recipient_id, created_at, updated_at";
+
+#[derive(Debug, thiserror::Error)]
+pub enum DraftError {
+    #[error("{0}")]
+    Invalid(String),
+    #[error("Brouillon introuvable")]
+    NotFound,
+    #[error("Seul l'auteur ou un administrateur peut modifier ce modèle")]
+    Forbidden,
+}
+
+/// Values of the draft variables for one message or recipient.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TemplateVars {
+    pub child_name: Option<String>,
+    pub group_name: Option<String>,
+}
+
+impl TemplateVars {
+    pub fn for_recipient(recipient: &ParentRecipient) -> Self {
+        let non_empty = |s: &str| (!s.is_empty()).then(|| s.to_string());
+        Self {
+            child_name: non_empty(&recipient.child_names),
+            group_name: non_empty(&recipient.group_names),
+        }
+    }
+
+    /// Values every recipient shares, for the copy of an announcement kept in the thread.
+    pub fn shared(recipients: &[ParentRecipient]) -> Self {
+        let common = |field: fn(&ParentRecipient) -> &str| {
+            let first = field(recipients.first()?);
+            (!first.is_empty() && recipients.iter().all(|r| field(r) == first)).then(|| first.to_string())
+        };
+        Self {
+            child_name: common(|r| &r.child_names),
+            group_name: common(|r| &r.group_names),
+        }
+    }
+
+    /// Replaces `{child_name}` and `{group_name}`; other braces are left as written.
+    pub fn expand(&self, text: &str) -> String {
+        fill(
+            text,
+            &[
+                ("child_name", self.child_name.as_deref().unwrap_or(DEFAULT_CHILD_NAME)),
+                ("group_name", self.group_name.as_deref().unwrap_or(DEFAULT_GROUP_NAME)),
+            ],
+        )
+    }
+}
+
+/// Columns of a `ParentRecipient` selected from `users u`. `child_filter` narrows the
+/// children whose names are used, e.g. `AND c.group_id = $1` for a group announcement.
+pub fn recipient_cols(schema: &str, child_filter: &str) -> String {
+    format!(
+        "u.email, CONCAT(u.first_name, ' ', u.last_name) AS name, u.preferred_locale AS locale,
+         COALESCE((SELECT string_agg(c.first_name, ', ' ORDER BY c.first_name)
+                   FROM {schema}.child_parents cp
+                   JOIN {schema}.children c ON c.id = cp.child_id
+                   WHERE cp.user_id = u.id AND c.is_active = TRUE {child_filter}), '') AS child_names,
+         COALESCE((SELECT string_agg(DISTINCT g.name, ', ')
+                   FROM {schema}.child_parents cp
+                   JOIN {schema}.children c ON c.id = cp.child_id
+                   JOIN {schema}.groups g ON g.id = c.group_id
+                   WHERE cp.user_id = u.id AND c.is_active = TRUE {child_filter}), '') AS group_names"
+    )
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+pub fn validate(req: &MessageDraftRequest) -> Result<(), DraftError> {
+    if req.name.trim().is_empty() || req.content.trim().is_empty() {
+        return Err(DraftError::Invalid("Le nom et le contenu sont requis".into()));
+    }
+    if req.name.chars().count() > MAX_NAME_CHARS {
+        return Err(DraftError::Invalid(format!("Le nom ne doit pas dépasser {MAX_NAME_CHARS} caractères")));
+    }
+    if req.subject.as_deref().is_some_and(|s| s.chars().count() > MAX_SUBJECT_CHARS) {
+        return Err(DraftError::Invalid(format!(
+            "Le sujet ne doit pas dépasser {MAX_SUBJECT_CHARS} caractères"
+        )));
+    }
+    if req.content.chars().count() > MAX_CONTENT_CHARS {
+        return Err(DraftError::Invalid(format!(
+            "Le contenu ne doit pas dépasser {MAX_CONTENT_CHARS} caractères"
+        )));
+    }
+    if let Some(scope) = req.scope.as_deref().filter(|s| !DRAFT_SCOPES.contains(s)) {
+        return Err(DraftError::Invalid(format!("Destinataires inconnus : {scope}")));
+    }
+    if let Some(unknown) = used_placeholders(req.subject.as_deref().unwrap_or_default())
+        .into_iter()
+        .chain(used_placeholders(&req.content))
+        .find(|name| is_identifier(name) && !DRAFT_VARIABLES.contains(name))
+    {
+        return Err(DraftError::Invalid(format!(
+            "Variable inconnue {{{unknown}}} — variables disponibles : {}",
+            DRAFT_VARIABLES.iter().map(|v| format!("{{{v}}}")).collect::<Vec<_>>().join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// Drafts are only seen by their author; templates by all staff.
+pub fn can_view(user: &AuthenticatedUser, draft: &MessageDraft) -> bool {
+    draft.is_template || draft.owner_id == user.user_id
+}
+
+/// Templates may also be changed by admins, e.g. when their author has left.
+pub fn can_edit(user: &AuthenticatedUser, draft: &MessageDraft) -> bool {
+    draft.owner_id == user.user_id
+        || (draft.is_template && matches!(user.role, UserRole::AdminGarderie | UserRole::SuperAdmin))
+}
+
+pub struct MessageDraftService;
+
+impl MessageDraftService {
+    pub async fn list(
+        pool: &PgPool,
+        tenant: &str,
+        user_id: Uuid,
+        templates: Option<bool>,
+    ) -> anyhow::Result<Vec<MessageDraft>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, MessageDraft>(&format!(
+            r#"SELECT {DRAFT_COLS} FROM "{schema}".message_drafts
+               WHERE (is_template OR owner_id = $1)
+                 AND ($2::BOOLEAN IS NULL OR is_template = $2)
+               ORDER BY is_template DESC, LOWER(name), updated_at DESC"#
+        ))
+        .bind(user_id)
+        .bind(templates)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    pub async fn get(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<Option<MessageDraft>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, MessageDraft>(&format!(
+            r#"SELECT {DRAFT_COLS} FROM "{schema}".message_drafts WHERE id = $1"#
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    pub async fn create(
+        pool: &PgPool,
+        tenant: &str,
+        owner_id: Uuid,
+        req: &MessageDraftRequest,
+    ) -> anyhow::Result<MessageDraft> {
+        validate(req)?;
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, MessageDraft>(&format!(
+            r#"INSERT INTO "{schema}".message_drafts
+               (owner_id, name, is_template, subject, content, scope, group_id, child_id, recipient_id)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+               RETURNING {DRAFT_COLS}"#
+        ))
+        .bind(owner_id)
+        .bind(req.name.trim())
+        .bind(req.is_template)
+        .bind(req.subject.as_deref().map(str::trim).filter(|s| !s.is_empty()))
+        .bind(&req.content)
+        .bind(&req.scope)
+        .bind(req.group_id)
+        .bind(req.child_id)
+        .bind(req.recipient_id)
+        .fetch_one(pool)
+        .await?)
+    }
+
+    pub async fn update(
+        pool: &PgPool,
+        tenant: &str,
+        id: Uuid,
+        req: &MessageDraftRequest,
+    ) -> anyhow::Result<MessageDraft> {
+        validate(req)?;
+        let schema = schema_name(tenant);
+        sqlx::query_as::<_, MessageDraft>(&format!(
+            r#"UPDATE "{schema}".message_drafts
+               SET name = $1, is_template = $2, subject = $3, content = $4, scope = $5,
+                   group_id = $6, child_id = $7, recipient_id = $8
+               WHERE id = $9
+               RETURNING {DRAFT_COLS}"#
+        ))
+        .bind(req.name.trim())
+        .bind(req.is_template)
+        .bind(req.subject.as_deref().map(str::trim).filter(|s| !s.is_empty()))
+        .bind(&req.content)
+        .bind(&req.scope)
+        .bind(req.group_id)
+        .bind(req.child_id)
+        .bind(req.recipient_id)
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| DraftError::NotFound.into())
+    }
+
+    pub async fn delete(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<()> {
+        let schema = schema_name(tenant);
+        sqlx::query(&format!(r#"DELETE FROM "{schema}".message_drafts WHERE id = $1"#))
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Variables of a message posted in a group thread or to one parent. A group
+    /// thread has no single child, so `{child_name}` keeps its generic wording there.
+    pub async fn variables_for(
+        pool: &PgPool,
+        tenant: &str,
+        group_id: Option<Uuid>,
+        parent_id: Option<Uuid>,
+    ) -> anyhow::Result<TemplateVars> {
+        let schema = schema_name(tenant);
+        if let Some(parent_id) = parent_id {
+            let cols = recipient_cols(&schema, "");
+            let recipient: Option<ParentRecipient> = sqlx::query_as(&format!(
+                "SELECT {cols} FROM {schema}.users u WHERE u.id = $1"
+            ))
+            .bind(parent_id)
+            .fetch_optional(pool)
+            .await?;
+            return Ok(recipient.as_ref().map(TemplateVars::for_recipient).unwrap_or_default());
+        }
+        let group_name = match group_id {
+            Some(group_id) => sqlx::query_scalar(&format!("SELECT name FROM {schema}.groups WHERE id = $1"))
+                .bind(group_id)
+                .fetch_optional(pool)
+                .await?,
+            None => None,
+        };
+        Ok(TemplateVars { child_name: None, group_name })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipient(child_names: &str, group_names: &str) -> ParentRecipient {
+        ParentRecipient {
+            email: "parent@example.com".into(),
+            name: "Parent".into(),
+            locale: "fr".into(),
+            child_names: child_names.into(),
+            group_names: group_names.into(),
+        }
+    }
+
+    fn request(content: &str) -> MessageDraftRequest {
+        MessageDraftRequest {
+            name: "Rappel du vendredi".into(),
+            is_template: true,
+            subject: Some("Rappel pour {child_name}".into()),
+            content: content.into(),
+            scope: Some("group_parents".into()),
+            group_id: None,
+            child_id: None,
+            recipient_id: None,
+        }
+    }
+
+    #[test]
+    fn variables_expand_with_generic_fallbacks() {
+        let vars = TemplateVars::for_recipient(&recipient("Emma, Léo", "Papillons"));
+        assert_eq!(
+            vars.expand("N'oubliez pas les bottes de {child_name} ({group_name}) {demain}"),
+            "N'oubliez pas les bottes de Emma, Léo (Papillons) {demain}"
+        );
+        let vars = TemplateVars::for_recipient(&recipient("", ""));
+        assert_eq!(vars.expand("{child_name} — {group_name}"), "votre enfant — le groupe");
+    }
+
+    #[test]
+    fn shared_variables_keep_only_common_values() {
+        let vars = TemplateVars::shared(&[recipient("Emma", "Papillons"), recipient("Léo", "Papillons")]);
+        assert_eq!(vars.child_name, None);
+        assert_eq!(vars.group_name.as_deref(), Some("Papillons"));
+        assert_eq!(TemplateVars::shared(&[]), TemplateVars::default());
+    }
+
+    #[test]
+    fn drafts_only_accept_known_variables() {
+        assert!(validate(&request("Bonjour, {child_name} aura besoin de crème solaire.")).is_ok());
+        assert!(validate(&request("Bonjour {parent_name}")).is_err());
+        // Braces that are not variable names are plain text
+        assert!(validate(&request("Horaire : {8 h – 17 h}")).is_ok());
+        assert!(validate(&MessageDraftRequest { scope: Some("everyone".into()), ..request("Bonjour") }).is_err());
+        assert!(validate(&MessageDraftRequest { name: " ".into(), ..request("Bonjour") }).is_err());
+    }
+}
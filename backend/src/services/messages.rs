@@ -3,16 +3,307 @@ use uuid::Uuid;
 
 use crate::{
     db::tenant::schema_name,
-    models::message::{
-        ConversationItem, CreateMessageRequest, Message, MessageWithSender, SendToParentsRequest,
-        SendToParentsScope,
+    models::{
+        auth::AuthenticatedUser,
+        message::{
+            ConversationItem, CreateMessageRequest, Message, MessageEdit, MessageReadReceipts, MessageReader,
+            MessageType, MessageWithSender, SendToParentsRequest, SendToParentsScope,
+        },
+        message_draft::ParentRecipient,
+    },
+    services::{
+        groups::GroupService,
+        message_drafts::{recipient_cols, TemplateVars},
+        notifications::has_active_child_sql,
+        unread::thread_field,
     },
 };
 
+/// Shown instead of the content of deleted messages.
+pub const TOMBSTONE: &str = "Message supprimé";
+
+/// Why an edit or delete was refused.
+#[derive(Debug, thiserror::Error)]
+pub enum MessageChangeError {
+    #[error("Message introuvable")]
+    NotFound,
+    #[error("Seul l'auteur peut modifier ou supprimer ce message")]
+    NotSender,
+    #[error("Le délai de modification est dépassé")]
+    WindowExpired,
+}
+
+/// Why a new message has no thread to go to.
+#[derive(Debug, thiserror::Error)]
+pub enum MessageSendError {
+    #[error("group_id requis pour un message de groupe")]
+    MissingGroup,
+    #[error("recipient_id requis pour un message individuel")]
+    MissingRecipient,
+    #[error("{0} requis pour ce groupe de destinataires")]
+    MissingScopeTarget(&'static str),
+    #[error("Aucun parent trouvé pour ce scope")]
+    NoParents,
+}
+
+/// What marking a message read changed.
+pub struct ReadChange {
+    pub message: Message,
+    /// `is_read` went from false to true (individual messages received by the user).
+    pub flagged_read: bool,
+    /// A read receipt was recorded for the user.
+    pub receipt_added: bool,
+}
+
+/// A conversation as read by [`shared_threads`]: its latest message and how many
+/// the viewer has not read.
+#[derive(sqlx::FromRow)]
+struct ThreadRow {
+    kind: String,
+    id: Option<Uuid>,
+    /// `None` until the thread's first message.
+    thread_id: Option<Uuid>,
+    name: Option<String>,
+    color: Option<String>,
+    last_message: Option<String>,
+    last_at: Option<chrono::DateTime<chrono::Utc>>,
+    unread_count: i64,
+}
+
+impl From<ThreadRow> for ConversationItem {
+    fn from(row: ThreadRow) -> Self {
+        ConversationItem {
+            name: row.name.unwrap_or_else(|| "Tous les parents".to_string()),
+            kind: row.kind,
+            id: row.id.map(|id| id.to_string()),
+            thread_id: row.thread_id,
+            color: row.color,
+            last_message: row.last_message,
+            last_at: row.last_at,
+            unread_count: row.unread_count,
+            online: None,
+            last_seen_at: None,
+            away: None,
+            archived: false,
+            muted: false,
+            pinned: false,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ParentThreadRow {
+    id: Uuid,
+    thread_id: Uuid,
+    first_name: String,
+    last_name: String,
+    last_message: String,
+    last_at: chrono::DateTime<chrono::Utc>,
+    unread_count: i64,
+}
+
+/// Who the threads of [`shared_threads`] are shared with.
+#[derive(Clone, Copy)]
+enum Audience {
+    /// The broadcast thread and the group threads, read by parents and staff.
+    Families,
+    /// The all-staff channel and the staff channel of each group.
+    Staff,
+}
+
+impl Audience {
+    /// Key (and kind) of the thread for everyone, its name, and the kind of the
+    /// per-group threads.
+    fn threads(self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            Audience::Families => ("broadcast", "Tous les parents", "group"),
+            Audience::Staff => ("staff", "Équipe", "staff"),
+        }
+    }
+}
+
+/// The thread for everyone then the group threads of an audience (`group_filter`
+/// over `groups g`, `$1` is the viewer), with their latest message and unread count,
+/// in one query.
+async fn shared_threads(
+    pool: &PgPool,
+    schema: &str,
+    user_id: Uuid,
+    audience: Audience,
+    group_filter: &str,
+) -> anyhow::Result<Vec<ConversationItem>> {
+    let (all, all_name, group_kind) = audience.threads();
+    let m_content = visible_content("m.");
+    let unread = unread_by("m.", schema, "$1");
+    let rows: Vec<ThreadRow> = sqlx::query_as(&format!(
+        "WITH unread AS (
+             SELECT m.thread_id, COUNT(*) AS n
+             FROM {schema}.messages m
+             JOIN {schema}.threads t ON t.id = m.thread_id
+             WHERE t.kind IN ('{all}', '{group_kind}') AND m.sender_id != $1
+               AND {unread}
+             GROUP BY m.thread_id
+         )
+         SELECT * FROM (
+             SELECT '{all}' AS kind, NULL::uuid AS id, t.id AS thread_id, '{all_name}' AS name,
+                    NULL::text AS color, last.content AS last_message, last.created_at AS last_at,
+                    COALESCE(u.n, 0) AS unread_count
+             FROM (SELECT 1) AS one
+             LEFT JOIN {schema}.threads t ON t.key = '{all}'
+             LEFT JOIN LATERAL (
+                 SELECT {m_content} AS content, m.created_at FROM {schema}.messages m
+                 WHERE m.thread_id = t.id
+                 ORDER BY m.created_at DESC LIMIT 1
+             ) last ON TRUE
+             LEFT JOIN unread u ON u.thread_id = t.id
+             UNION ALL
+             SELECT '{group_kind}', g.id, t.id, g.name, g.color, last.content, last.created_at, COALESCE(u.n, 0)
+             FROM {schema}.groups g
+             LEFT JOIN {schema}.threads t ON t.key = '{group_kind}:' || g.id
+             LEFT JOIN LATERAL (
+                 SELECT {m_content} AS content, m.created_at FROM {schema}.messages m
+                 WHERE m.thread_id = t.id
+                 ORDER BY m.created_at DESC LIMIT 1
+             ) last ON TRUE
+             LEFT JOIN unread u ON u.thread_id = t.id
+             {group_filter}
+         ) threads
+         ORDER BY id IS NOT NULL, name"
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(ConversationItem::from).collect())
+}
+
+/// SQL expression for a message's content, with deleted messages replaced by the tombstone.
+fn visible_content(alias: &str) -> String {
+    format!("CASE WHEN {alias}is_deleted THEN '{TOMBSTONE}' ELSE {alias}content END")
+}
+
+/// Extra `read_count` column (parents who read it) over `messages m`, only for staff views.
+fn read_count_col(schema: &str, enabled: bool) -> String {
+    if enabled {
+        format!(",
+         (SELECT COUNT(*) FROM {schema}.message_reads r
+          JOIN {schema}.users ru ON ru.id = r.user_id
+          WHERE r.message_id = m.id AND ru.role::text = 'parent') AS read_count")
+    } else {
+        String::new()
+    }
+}
+
+/// SQL condition: the message has no read receipt from the given user.
+fn unread_by(alias: &str, schema: &str, user_param: &str) -> String {
+    format!(
+        "{alias}is_deleted = FALSE
+         AND NOT EXISTS (SELECT 1 FROM {schema}.message_reads r
+                         WHERE r.message_id = {alias}id AND r.user_id = {user_param})"
+    )
+}
+
 /// Explicit column list for Message — casts message_type enum to TEXT.
-const MSG_COLS: &str =
-    "id, sender_id, message_type::TEXT as message_type, group_id, recipient_id,
-     content, is_read, created_at, updated_at";
+fn msg_cols() -> String {
+    format!(
+        "id, sender_id, message_type::TEXT as message_type, group_id, recipient_id,
+         {} AS content, is_read, created_at, updated_at, edited_at, is_deleted, thread_id",
+        visible_content("")
+    )
+}
+
+/// Column list for MessageWithSender over `messages m JOIN users u`.
+fn with_sender_cols() -> String {
+    format!(
+        "m.id, m.sender_id,
+         u.first_name AS sender_first_name, u.last_name AS sender_last_name,
+         m.message_type::TEXT AS message_type,
+         m.group_id, m.recipient_id, {} AS content, m.is_read, m.created_at,
+         m.edited_at, m.is_deleted, m.thread_id",
+        visible_content("m.")
+    )
+}
+
+/// Id of the thread with this key, created on its first message.
+async fn upsert_thread(
+    pool: &PgPool,
+    schema: &str,
+    kind: &str,
+    group_id: Option<Uuid>,
+    parent_id: Option<Uuid>,
+) -> anyhow::Result<Uuid> {
+    let key = thread_field(kind, group_id.or(parent_id).map(|id| id.to_string()).as_deref());
+    let id = sqlx::query_scalar(&format!(
+        "INSERT INTO {schema}.threads (key, kind, group_id, parent_id)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT ON CONSTRAINT threads_key_key DO UPDATE SET key = EXCLUDED.key
+         RETURNING id"
+    ))
+    .bind(&key)
+    .bind(kind)
+    .bind(group_id)
+    .bind(parent_id)
+    .fetch_one(pool)
+    .await?;
+    Ok(id)
+}
+
+/// The thread a new message goes to. Individual messages belong to the parent's
+/// thread: the sender's when a parent writes to the garderie, the recipient's when
+/// the staff answers. Staff messages go to the group's staff channel, or to the
+/// all-staff one without a group.
+async fn thread_for(
+    pool: &PgPool,
+    schema: &str,
+    sender_id: Uuid,
+    req: &CreateMessageRequest,
+) -> anyhow::Result<Uuid> {
+    match req.message_type {
+        MessageType::Broadcast => upsert_thread(pool, schema, "broadcast", None, None).await,
+        MessageType::Group => {
+            let group_id = req.group_id.ok_or(MessageSendError::MissingGroup)?;
+            upsert_thread(pool, schema, "group", Some(group_id), None).await
+        }
+        MessageType::Staff => upsert_thread(pool, schema, "staff", req.group_id, None).await,
+        MessageType::Individual => {
+            let sender_is_parent: bool = sqlx::query_scalar(&format!(
+                "SELECT EXISTS(SELECT 1 FROM {schema}.users WHERE id = $1 AND role::text = 'parent')"
+            ))
+            .bind(sender_id)
+            .fetch_one(pool)
+            .await?;
+            let parent_id = if sender_is_parent { Some(sender_id) } else { req.recipient_id };
+            let parent_id = parent_id.ok_or(MessageSendError::MissingRecipient)?;
+            upsert_thread(pool, schema, "individual", None, Some(parent_id)).await
+        }
+    }
+}
+
+/// A page of a thread by key, oldest first.
+async fn thread_page(
+    pool: &PgPool,
+    schema: &str,
+    key: &str,
+    per_page: i64,
+    offset: i64,
+    with_read_counts: bool,
+) -> anyhow::Result<Vec<MessageWithSender>> {
+    let cols = with_sender_cols() + &read_count_col(schema, with_read_counts);
+    let msgs = sqlx::query_as::<_, MessageWithSender>(&format!(
+        "SELECT {cols}
+         FROM {schema}.threads t
+         JOIN {schema}.messages m ON m.thread_id = t.id
+         JOIN {schema}.users u ON u.id = m.sender_id
+         WHERE t.key = $1
+         ORDER BY m.created_at ASC
+         LIMIT $2 OFFSET $3"
+    ))
+    .bind(key)
+    .bind(per_page)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+    Ok(msgs)
+}
 
 pub struct MessageService;
 
@@ -24,31 +315,56 @@ impl MessageService {
         req: &CreateMessageRequest,
     ) -> anyhow::Result<MessageWithSender> {
         let schema = schema_name(tenant);
+        let cols = with_sender_cols();
+        let thread_id = thread_for(pool, &schema, sender_id, req).await?;
 
         let msg = sqlx::query_as::<_, MessageWithSender>(&format!(
             "WITH inserted AS (
-                 INSERT INTO {schema}.messages (sender_id, message_type, group_id, recipient_id, content)
-                 VALUES ($1, $2::\"{schema}\".message_type, $3, $4, $5)
+                 INSERT INTO {schema}.messages (sender_id, message_type, group_id, recipient_id, content, thread_id)
+                 VALUES ($1, $2::\"{schema}\".message_type, $3, $4, $5, $6)
                  RETURNING *
              )
-             SELECT i.id, i.sender_id,
-                 u.first_name AS sender_first_name, u.last_name AS sender_last_name,
-                 i.message_type::TEXT AS message_type,
-                 i.group_id, i.recipient_id, i.content, i.is_read, i.created_at
-             FROM inserted i
-             JOIN {schema}.users u ON u.id = i.sender_id"
+             SELECT {cols}
+             FROM inserted m
+             JOIN {schema}.users u ON u.id = m.sender_id"
         ))
         .bind(sender_id)
         .bind(req.message_type.to_string())
         .bind(req.group_id)
         .bind(req.recipient_id)
         .bind(&req.content)
+        .bind(thread_id)
         .fetch_one(pool)
         .await?;
 
         Ok(msg)
     }
 
+    /// Phone numbers of the active parents reached by a broadcast or group message,
+    /// with the parent of each (one per number when parents share a phone).
+    pub async fn parent_phones(
+        pool: &PgPool,
+        tenant: &str,
+        group_id: Option<Uuid>,
+    ) -> anyhow::Result<Vec<(Uuid, String)>> {
+        let schema = schema_name(tenant);
+        let phones = sqlx::query_as(&format!(
+            "SELECT DISTINCT ON (u.phone) u.id, u.phone
+             FROM {schema}.users u
+             WHERE u.role::text = 'parent' AND u.is_active = TRUE AND u.phone IS NOT NULL AND {}
+               AND ($1::UUID IS NULL OR EXISTS(
+                   SELECT 1 FROM {schema}.child_parents cp
+                   JOIN {schema}.children c ON c.id = cp.child_id
+                   WHERE cp.user_id = u.id AND c.group_id = $1 AND c.is_active = TRUE
+               ))",
+            has_active_child_sql(&schema, "u")
+        ))
+        .bind(group_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(phones)
+    }
+
     pub async fn list_messages(
         pool: &PgPool,
         tenant: &str,
@@ -57,6 +373,7 @@ impl MessageService {
         per_page: i64,
     ) -> anyhow::Result<Vec<Message>> {
         let schema = schema_name(tenant);
+        let cols = msg_cols();
 
         let group_ids: Vec<Uuid> = sqlx::query_scalar(&format!(
             "SELECT DISTINCT c.group_id
@@ -68,11 +385,13 @@ impl MessageService {
         .fetch_all(pool)
         .await?;
 
+        // The user's threads, and for staff the individual messages they wrote or received
         let msgs = sqlx::query_as::<_, Message>(&format!(
-            "SELECT {MSG_COLS} FROM {schema}.messages
-             WHERE message_type::text = 'broadcast'
-                OR (message_type::text = 'group' AND group_id = ANY($1))
-                OR (message_type::text = 'individual' AND (sender_id = $2 OR recipient_id = $2))
+            "SELECT {cols} FROM {schema}.messages
+             WHERE thread_id IN (SELECT t.id FROM {schema}.threads t
+                                 WHERE t.kind = 'broadcast' OR (t.kind = 'group' AND t.group_id = ANY($1))
+                                    OR t.parent_id = $2)
+                OR (message_type = 'individual' AND (sender_id = $2 OR recipient_id = $2))
              ORDER BY created_at DESC
              LIMIT $3 OFFSET $4"
         ))
@@ -86,26 +405,55 @@ impl MessageService {
         Ok(msgs)
     }
 
+    /// Mark a message read for the user. Returns the message with what changed,
+    /// or None if it was already read (or does not exist).
     pub async fn mark_read(
         pool: &PgPool,
         tenant: &str,
         message_id: Uuid,
         user_id: Uuid,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<Option<ReadChange>> {
         let schema = schema_name(tenant);
-        sqlx::query(&format!(
+        let flagged_read = sqlx::query(&format!(
             "UPDATE {schema}.messages SET is_read = TRUE
-             WHERE id = $1 AND (recipient_id = $2 OR message_type::text != 'individual')"
+             WHERE id = $1 AND is_read = FALSE
+               AND (recipient_id = $2 OR message_type::text != 'individual')"
         ))
         .bind(message_id)
         .bind(user_id)
         .execute(pool)
+        .await?
+        .rows_affected()
+            > 0;
+
+        let receipt_added = sqlx::query(&format!(
+            "INSERT INTO {schema}.message_reads (message_id, user_id)
+             SELECT id, $2 FROM {schema}.messages
+             WHERE id = $1 AND sender_id != $2
+             ON CONFLICT (message_id, user_id) DO NOTHING"
+        ))
+        .bind(message_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?
+        .rows_affected()
+            > 0;
+
+        if !flagged_read && !receipt_added {
+            return Ok(None);
+        }
+        let cols = msg_cols();
+        let message = sqlx::query_as::<_, Message>(&format!(
+            "SELECT {cols} FROM {schema}.messages WHERE id = $1"
+        ))
+        .bind(message_id)
+        .fetch_optional(pool)
         .await?;
-        Ok(())
+        Ok(message.map(|message| ReadChange { message, flagged_read, receipt_added }))
     }
 
     /// Mark all unread messages in a thread as read for the current user.
-    /// - broadcast/group: marks messages not sent by the user
+    /// - broadcast/group/staff: records a read receipt for every message not sent by the user
     /// - individual: marks messages sent by the other party
     pub async fn mark_thread_read(
         pool: &PgPool,
@@ -115,59 +463,49 @@ impl MessageService {
         thread_id: Option<Uuid>,
     ) -> anyhow::Result<()> {
         let schema = schema_name(tenant);
+        let key = thread_field(kind, thread_id.map(|id| id.to_string()).as_deref());
         match kind {
-            "broadcast" => {
+            "broadcast" | "group" | "staff" => {
                 sqlx::query(&format!(
-                    "UPDATE {schema}.messages SET is_read = TRUE
-                     WHERE message_type::text = 'broadcast'
-                       AND is_read = FALSE AND sender_id != $1"
+                    "UPDATE {schema}.messages m SET is_read = TRUE
+                     FROM {schema}.threads t
+                     WHERE t.key = $1 AND m.thread_id = t.id
+                       AND m.is_read = FALSE AND m.sender_id != $2"
                 ))
+                .bind(&key)
+                .bind(user_id)
+                .execute(pool)
+                .await?;
+
+                sqlx::query(&format!(
+                    "INSERT INTO {schema}.message_reads (message_id, user_id)
+                     SELECT m.id, $2 FROM {schema}.threads t
+                     JOIN {schema}.messages m ON m.thread_id = t.id
+                     WHERE t.key = $1 AND m.sender_id != $2
+                     ON CONFLICT (message_id, user_id) DO NOTHING"
+                ))
+                .bind(&key)
                 .bind(user_id)
                 .execute(pool)
                 .await?;
             }
-            "group" => {
-                if let Some(gid) = thread_id {
+            "individual" => {
+                // The thread id is the parent's: a parent reading their "Garderie"
+                // thread marks the staff's messages, staff (any of them, whoever
+                // the original recipient) mark the parent's
+                if let Some(parent_id) = thread_id {
                     sqlx::query(&format!(
-                        "UPDATE {schema}.messages SET is_read = TRUE
-                         WHERE message_type::text = 'group' AND group_id = $1
-                           AND is_read = FALSE AND sender_id != $2"
+                        "UPDATE {schema}.messages m SET is_read = TRUE
+                         FROM {schema}.threads t
+                         WHERE t.key = $1 AND m.thread_id = t.id AND m.is_read = FALSE
+                           AND (m.sender_id = t.parent_id) = $2"
                     ))
-                    .bind(gid)
-                    .bind(user_id)
+                    .bind(&key)
+                    .bind(parent_id != user_id)
                     .execute(pool)
                     .await?;
                 }
             }
-            "individual" => {
-                if let Some(other_id) = thread_id {
-                    if other_id == user_id {
-                        // Parent viewing their "Garderie" thread: the conversation id is
-                        // their own user_id. Mark all unread individual messages received
-                        // by this user as read.
-                        sqlx::query(&format!(
-                            "UPDATE {schema}.messages SET is_read = TRUE
-                             WHERE message_type::text = 'individual'
-                               AND recipient_id = $1 AND is_read = FALSE"
-                        ))
-                        .bind(user_id)
-                        .execute(pool)
-                        .await?;
-                    } else {
-                        // Staff/admin marking a conversation with a specific parent as read.
-                        // Mark all unread messages FROM this parent regardless of which staff
-                        // member is the original recipient (admin can view any conversation).
-                        sqlx::query(&format!(
-                            "UPDATE {schema}.messages SET is_read = TRUE
-                             WHERE message_type::text = 'individual'
-                               AND sender_id = $1 AND is_read = FALSE"
-                        ))
-                        .bind(other_id)
-                        .execute(pool)
-                        .await?;
-                    }
-                }
-            }
             _ => {}
         }
         Ok(())
@@ -182,8 +520,9 @@ impl MessageService {
         per_page: i64,
     ) -> anyhow::Result<Vec<Message>> {
         let schema = schema_name(tenant);
+        let cols = msg_cols();
         let msgs = sqlx::query_as::<_, Message>(&format!(
-            "SELECT {MSG_COLS} FROM {schema}.messages
+            "SELECT {cols} FROM {schema}.messages
              WHERE message_type::text = 'individual'
                AND ((sender_id = $1 AND recipient_id = $2)
                  OR (sender_id = $2 AND recipient_id = $1))
@@ -199,119 +538,77 @@ impl MessageService {
         Ok(msgs)
     }
 
-    /// GET /messages/conversations — liste des fils de discussion pour l'admin
+    /// GET /messages/conversations — fils du personnel : ceux de l'admin, réduits aux
+    /// fils (et canaux internes) de leurs groupes pour les éducateurs affectés
+    pub async fn get_conversations_staff(
+        pool: &PgPool,
+        tenant: &str,
+        user: &AuthenticatedUser,
+    ) -> anyhow::Result<Vec<ConversationItem>> {
+        let scope = GroupService::educator_scope(pool, tenant, user).await?;
+        let mut items = Self::get_conversations_admin(pool, tenant, user.user_id).await?;
+        if let Some(groups) = scope {
+            items.retain(|i| {
+                !matches!(i.kind.as_str(), "group" | "staff")
+                    || i.id.is_none()
+                    || i.id.as_deref().and_then(|id| id.parse::<Uuid>().ok()).is_some_and(|id| groups.contains(&id))
+            });
+        }
+        Ok(items)
+    }
+
+    /// Liste des fils de discussion pour l'admin
     pub async fn get_conversations_admin(
         pool: &PgPool,
         tenant: &str,
         user_id: Uuid,
     ) -> anyhow::Result<Vec<ConversationItem>> {
         let schema = schema_name(tenant);
-        let mut items = Vec::new();
-
-        // 1. Item broadcast (toujours présent)
-        let broadcast_last: Option<(String, chrono::DateTime<chrono::Utc>)> =
-            sqlx::query_as(&format!(
-                "SELECT content, created_at FROM {schema}.messages
-                 WHERE message_type::text = 'broadcast'
-                 ORDER BY created_at DESC LIMIT 1"
-            ))
-            .fetch_optional(pool)
-            .await?;
 
-        let (last_msg, last_at) = match broadcast_last {
-            Some((c, a)) => (Some(c), Some(a)),
-            None => (None, None),
-        };
+        // 1. Item broadcast (toujours présent) et tous les groupes, puis les canaux
+        //    internes du personnel
+        let mut items = shared_threads(pool, &schema, user_id, Audience::Families, "").await?;
+        items.extend(shared_threads(pool, &schema, user_id, Audience::Staff, "").await?);
 
-        let broadcast_unread: i64 = sqlx::query_scalar(&format!(
-            "SELECT COUNT(*) FROM {schema}.messages
-             WHERE message_type::text = 'broadcast'
-               AND is_read = FALSE AND sender_id != $1"
+        // 2. Parents ayant un fil individuel, avec son dernier message
+        let parents: Vec<ParentThreadRow> = sqlx::query_as(&format!(
+            "SELECT u.id, u.first_name, u.last_name, t.id AS thread_id,
+                    {m_content} AS last_message, m.created_at AS last_at,
+                    unread.n AS unread_count
+             FROM {schema}.threads t
+             JOIN {schema}.users u ON u.id = t.parent_id
+             JOIN LATERAL (
+                 SELECT * FROM {schema}.messages m
+                 WHERE m.thread_id = t.id
+                 ORDER BY m.created_at DESC LIMIT 1
+             ) m ON TRUE
+             CROSS JOIN LATERAL (
+                 SELECT COUNT(*) AS n FROM {schema}.messages r
+                 WHERE r.thread_id = t.id AND r.sender_id = t.parent_id AND r.is_read = FALSE
+             ) unread
+             WHERE t.kind = 'individual' AND u.role::text = 'parent' AND u.is_active = TRUE
+             ORDER BY m.created_at DESC",
+            m_content = visible_content("m."),
         ))
-        .bind(user_id)
-        .fetch_one(pool)
+        .fetch_all(pool)
         .await?;
 
-        items.push(ConversationItem {
-            kind: "broadcast".to_string(),
-            id: None,
-            name: "Tous les parents".to_string(),
+        items.extend(parents.into_iter().map(|p| ConversationItem {
+            kind: "individual".to_string(),
+            id: Some(p.id.to_string()),
+            thread_id: Some(p.thread_id),
+            name: format!("{} {}", p.first_name, p.last_name),
             color: None,
-            last_message: last_msg,
-            last_at,
-            unread_count: broadcast_unread,
-        });
-
-        // 2. Tous les groupes avec dernier message
-        let groups: Vec<(Uuid, String, Option<String>, Option<String>, Option<chrono::DateTime<chrono::Utc>>, i64)> =
-            sqlx::query_as(&format!(
-                "SELECT g.id, g.name, g.color,
-                   (SELECT m.content FROM {schema}.messages m
-                    WHERE m.message_type::text = 'group' AND m.group_id = g.id
-                    ORDER BY m.created_at DESC LIMIT 1) AS last_message,
-                   (SELECT m.created_at FROM {schema}.messages m
-                    WHERE m.message_type::text = 'group' AND m.group_id = g.id
-                    ORDER BY m.created_at DESC LIMIT 1) AS last_at,
-                   (SELECT COUNT(*) FROM {schema}.messages m
-                    WHERE m.message_type::text = 'group' AND m.group_id = g.id
-                      AND m.is_read = FALSE AND m.sender_id != $1) AS unread_count
-                 FROM {schema}.groups g
-                 ORDER BY g.name"
-            ))
-            .bind(user_id)
-            .fetch_all(pool)
-            .await?;
-
-        for (id, name, color, last_msg, last_at, unread) in groups {
-            items.push(ConversationItem {
-                kind: "group".to_string(),
-                id: Some(id.to_string()),
-                name,
-                color,
-                last_message: last_msg,
-                last_at,
-                unread_count: unread,
-            });
-        }
-
-        // 3. Parents ayant un fil individuel
-        let parents: Vec<(Uuid, String, String, Option<String>, Option<chrono::DateTime<chrono::Utc>>, i64)> =
-            sqlx::query_as(&format!(
-                "SELECT u.id, u.first_name, u.last_name,
-                   (SELECT m.content FROM {schema}.messages m
-                    WHERE m.message_type::text = 'individual'
-                      AND (m.sender_id = u.id OR m.recipient_id = u.id)
-                    ORDER BY m.created_at DESC LIMIT 1) AS last_message,
-                   (SELECT m.created_at FROM {schema}.messages m
-                    WHERE m.message_type::text = 'individual'
-                      AND (m.sender_id = u.id OR m.recipient_id = u.id)
-                    ORDER BY m.created_at DESC LIMIT 1) AS last_at,
-                   (SELECT COUNT(*) FROM {schema}.messages m
-                    WHERE m.message_type::text = 'individual'
-                      AND m.sender_id = u.id AND m.is_read = FALSE) AS unread_count
-                 FROM {schema}.users u
-                 WHERE u.role::text = 'parent' AND u.is_active = TRUE
-                   AND EXISTS (
-                     SELECT 1 FROM {schema}.messages mm
-                     WHERE mm.message_type::text = 'individual'
-                       AND (mm.sender_id = u.id OR mm.recipient_id = u.id)
-                   )
-                 ORDER BY last_at DESC NULLS LAST"
-            ))
-            .fetch_all(pool)
-            .await?;
-
-        for (id, first, last, last_msg, last_at, unread) in parents {
-            items.push(ConversationItem {
-                kind: "individual".to_string(),
-                id: Some(id.to_string()),
-                name: format!("{first} {last}"),
-                color: None,
-                last_message: last_msg,
-                last_at,
-                unread_count: unread,
-            });
-        }
+            last_message: Some(p.last_message),
+            last_at: Some(p.last_at),
+            unread_count: p.unread_count,
+            online: None,
+            last_seen_at: None,
+            away: None,
+            archived: false,
+            muted: false,
+            pinned: false,
+        }));
 
         Ok(items)
     }
@@ -323,114 +620,44 @@ impl MessageService {
         user_id: Uuid,
     ) -> anyhow::Result<Vec<ConversationItem>> {
         let schema = schema_name(tenant);
-        let mut items = Vec::new();
-
-        // 1. Item broadcast (lecture seule)
-        let broadcast_last: Option<(String, chrono::DateTime<chrono::Utc>)> =
-            sqlx::query_as(&format!(
-                "SELECT content, created_at FROM {schema}.messages
-                 WHERE message_type::text = 'broadcast'
-                 ORDER BY created_at DESC LIMIT 1"
-            ))
-            .fetch_optional(pool)
-            .await?;
 
-        let (last_msg, last_at) = match broadcast_last {
-            Some((c, a)) => (Some(c), Some(a)),
-            None => (None, None),
-        };
-
-        let broadcast_unread: i64 = sqlx::query_scalar(&format!(
-            "SELECT COUNT(*) FROM {schema}.messages
-             WHERE message_type::text = 'broadcast'
-               AND is_read = FALSE AND sender_id != $1"
-        ))
-        .bind(user_id)
-        .fetch_one(pool)
+        // 1. Item broadcast (lecture seule) et groupes des enfants du parent
+        let mut items = shared_threads(
+            pool,
+            &schema,
+            user_id,
+            Audience::Families,
+            &format!(
+                "WHERE g.id IN (SELECT c.group_id FROM {schema}.children c
+                                JOIN {schema}.child_parents cp ON cp.child_id = c.id
+                                WHERE cp.user_id = $1)"
+            ),
+        )
         .await?;
 
-        items.push(ConversationItem {
-            kind: "broadcast".to_string(),
-            id: None,
-            name: "Tous les parents".to_string(),
-            color: None,
-            last_message: last_msg,
-            last_at,
-            unread_count: broadcast_unread,
-        });
-
-        // 2. Groupes des enfants du parent
-        let groups: Vec<(Uuid, String, Option<String>, Option<String>, Option<chrono::DateTime<chrono::Utc>>, i64)> =
-            sqlx::query_as(&format!(
-                "SELECT DISTINCT g.id, g.name, g.color,
-                   (SELECT m.content FROM {schema}.messages m
-                    WHERE m.message_type::text = 'group' AND m.group_id = g.id
-                    ORDER BY m.created_at DESC LIMIT 1) AS last_message,
-                   (SELECT m.created_at FROM {schema}.messages m
-                    WHERE m.message_type::text = 'group' AND m.group_id = g.id
-                    ORDER BY m.created_at DESC LIMIT 1) AS last_at,
-                   (SELECT COUNT(*) FROM {schema}.messages m
-                    WHERE m.message_type::text = 'group' AND m.group_id = g.id
-                      AND m.is_read = FALSE AND m.sender_id != $1) AS unread_count
-                 FROM {schema}.groups g
-                 JOIN {schema}.children c ON c.group_id = g.id
-                 JOIN {schema}.child_parents cp ON cp.child_id = c.id
-                 WHERE cp.user_id = $1
-                 ORDER BY g.name"
-            ))
-            .bind(user_id)
-            .fetch_all(pool)
-            .await?;
-
-        for (id, name, color, last_msg, last_at, unread) in groups {
-            items.push(ConversationItem {
-                kind: "group".to_string(),
-                id: Some(id.to_string()),
-                name,
-                color,
-                last_message: last_msg,
-                last_at,
-                unread_count: unread,
-            });
-        }
-
-        // 3. Fil individuel "Garderie"
-        let last_msg: Option<String> = sqlx::query_scalar(&format!(
-            "SELECT content FROM {schema}.messages
-             WHERE message_type::text = 'individual'
-               AND (sender_id = $1 OR recipient_id = $1)
-             ORDER BY created_at DESC LIMIT 1"
-        ))
-        .bind(user_id)
-        .fetch_optional(pool)
-        .await?;
-
-        let last_at: Option<chrono::DateTime<chrono::Utc>> = sqlx::query_scalar(&format!(
-            "SELECT created_at FROM {schema}.messages
-             WHERE message_type::text = 'individual'
-               AND (sender_id = $1 OR recipient_id = $1)
-             ORDER BY created_at DESC LIMIT 1"
-        ))
-        .bind(user_id)
-        .fetch_optional(pool)
-        .await?;
-
-        let unread: i64 = sqlx::query_scalar(&format!(
-            "SELECT COUNT(*) FROM {schema}.messages
-             WHERE message_type::text = 'individual'
-               AND recipient_id = $1 AND is_read = FALSE"
+        // 2. Fil individuel "Garderie"
+        let own: ThreadRow = sqlx::query_as(&format!(
+            "SELECT 'individual' AS kind, NULL::uuid AS id, t.id AS thread_id, NULL::text AS name,
+                    NULL::text AS color, last.content AS last_message, last.created_at AS last_at,
+                    (SELECT COUNT(*) FROM {schema}.messages m
+                     WHERE m.thread_id = t.id AND m.sender_id != $1 AND m.is_read = FALSE) AS unread_count
+             FROM (SELECT 1) AS one
+             LEFT JOIN {schema}.threads t ON t.key = $2
+             LEFT JOIN LATERAL (
+                 SELECT {m_content} AS content, m.created_at FROM {schema}.messages m
+                 WHERE m.thread_id = t.id
+                 ORDER BY m.created_at DESC LIMIT 1
+             ) last ON TRUE",
+            m_content = visible_content("m."),
         ))
         .bind(user_id)
+        .bind(thread_field("individual", Some(&user_id.to_string())))
         .fetch_one(pool)
         .await?;
         items.push(ConversationItem {
-            kind: "individual".to_string(),
             id: Some(user_id.to_string()),
             name: "Garderie".to_string(),
-            color: None,
-            last_message: last_msg,
-            last_at,
-            unread_count: unread,
+            ..own.into()
         });
 
         Ok(items)
@@ -442,24 +669,10 @@ impl MessageService {
         tenant: &str,
         per_page: i64,
         offset: i64,
+        with_read_counts: bool,
     ) -> anyhow::Result<Vec<MessageWithSender>> {
         let schema = schema_name(tenant);
-        let msgs = sqlx::query_as::<_, MessageWithSender>(&format!(
-            "SELECT m.id, m.sender_id,
-                 u.first_name AS sender_first_name, u.last_name AS sender_last_name,
-                 m.message_type::TEXT AS message_type,
-                 m.group_id, m.recipient_id, m.content, m.is_read, m.created_at
-             FROM {schema}.messages m
-             JOIN {schema}.users u ON u.id = m.sender_id
-             WHERE m.message_type::text = 'broadcast'
-             ORDER BY m.created_at ASC
-             LIMIT $1 OFFSET $2"
-        ))
-        .bind(per_page)
-        .bind(offset)
-        .fetch_all(pool)
-        .await?;
-        Ok(msgs)
+        thread_page(pool, &schema, "broadcast", per_page, offset, with_read_counts).await
     }
 
     /// GET /messages/thread/group/:group_id
@@ -469,25 +682,11 @@ impl MessageService {
         group_id: Uuid,
         per_page: i64,
         offset: i64,
+        with_read_counts: bool,
     ) -> anyhow::Result<Vec<MessageWithSender>> {
         let schema = schema_name(tenant);
-        let msgs = sqlx::query_as::<_, MessageWithSender>(&format!(
-            "SELECT m.id, m.sender_id,
-                 u.first_name AS sender_first_name, u.last_name AS sender_last_name,
-                 m.message_type::TEXT AS message_type,
-                 m.group_id, m.recipient_id, m.content, m.is_read, m.created_at
-             FROM {schema}.messages m
-             JOIN {schema}.users u ON u.id = m.sender_id
-             WHERE m.message_type::text = 'group' AND m.group_id = $1
-             ORDER BY m.created_at ASC
-             LIMIT $2 OFFSET $3"
-        ))
-        .bind(group_id)
-        .bind(per_page)
-        .bind(offset)
-        .fetch_all(pool)
-        .await?;
-        Ok(msgs)
+        let key = thread_field("group", Some(&group_id.to_string()));
+        thread_page(pool, &schema, &key, per_page, offset, with_read_counts).await
     }
 
     /// GET /messages/thread/individual/:parent_id
@@ -499,24 +698,257 @@ impl MessageService {
         offset: i64,
     ) -> anyhow::Result<Vec<MessageWithSender>> {
         let schema = schema_name(tenant);
-        let msgs = sqlx::query_as::<_, MessageWithSender>(&format!(
-            "SELECT m.id, m.sender_id,
-                 u.first_name AS sender_first_name, u.last_name AS sender_last_name,
-                 m.message_type::TEXT AS message_type,
-                 m.group_id, m.recipient_id, m.content, m.is_read, m.created_at
+        let key = thread_field("individual", Some(&parent_id.to_string()));
+        thread_page(pool, &schema, &key, per_page, offset, false).await
+    }
+
+    /// GET /messages/thread/staff and /messages/thread/staff/:group_id
+    pub async fn get_staff_thread(
+        pool: &PgPool,
+        tenant: &str,
+        group_id: Option<Uuid>,
+        per_page: i64,
+        offset: i64,
+    ) -> anyhow::Result<Vec<MessageWithSender>> {
+        let schema = schema_name(tenant);
+        let key = thread_field("staff", group_id.map(|id| id.to_string()).as_deref());
+        thread_page(pool, &schema, &key, per_page, offset, false).await
+    }
+
+    pub async fn get_with_sender(
+        pool: &PgPool,
+        tenant: &str,
+        message_id: Uuid,
+    ) -> anyhow::Result<Option<MessageWithSender>> {
+        let schema = schema_name(tenant);
+        let cols = with_sender_cols();
+        let msg = sqlx::query_as::<_, MessageWithSender>(&format!(
+            "SELECT {cols}
              FROM {schema}.messages m
              JOIN {schema}.users u ON u.id = m.sender_id
-             WHERE m.message_type::text = 'individual'
-               AND (m.sender_id = $1 OR m.recipient_id = $1)
-             ORDER BY m.created_at ASC
-             LIMIT $2 OFFSET $3"
+             WHERE m.id = $1"
         ))
-        .bind(parent_id)
-        .bind(per_page)
-        .bind(offset)
+        .bind(message_id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(msg)
+    }
+
+    /// Lock a live message for change by its sender, enforcing the edit window.
+    /// Returns the current content.
+    async fn lock_own_message(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        schema: &str,
+        message_id: Uuid,
+        user_id: Uuid,
+        window_minutes: i64,
+    ) -> anyhow::Result<String> {
+        let row: Option<(Uuid, String, chrono::DateTime<chrono::Utc>)> = sqlx::query_as(&format!(
+            "SELECT sender_id, content, created_at FROM {schema}.messages
+             WHERE id = $1 AND is_deleted = FALSE
+             FOR UPDATE"
+        ))
+        .bind(message_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let (sender_id, content, created_at) = row.ok_or(MessageChangeError::NotFound)?;
+        if sender_id != user_id {
+            return Err(MessageChangeError::NotSender.into());
+        }
+        if chrono::Utc::now() - created_at > chrono::Duration::minutes(window_minutes) {
+            return Err(MessageChangeError::WindowExpired.into());
+        }
+        Ok(content)
+    }
+
+    /// PUT /messages/:id — the previous content is kept in message_edits.
+    pub async fn edit_message(
+        pool: &PgPool,
+        tenant: &str,
+        message_id: Uuid,
+        user_id: Uuid,
+        content: &str,
+        window_minutes: i64,
+    ) -> anyhow::Result<MessageWithSender> {
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+
+        let previous =
+            Self::lock_own_message(&mut tx, &schema, message_id, user_id, window_minutes).await?;
+
+        sqlx::query(&format!(
+            "INSERT INTO {schema}.message_edits (message_id, previous_content, edited_by)
+             VALUES ($1, $2, $3)"
+        ))
+        .bind(message_id)
+        .bind(&previous)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(&format!(
+            "UPDATE {schema}.messages SET content = $2, edited_at = NOW() WHERE id = $1"
+        ))
+        .bind(message_id)
+        .bind(content)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Self::get_with_sender(pool, tenant, message_id)
+            .await?
+            .ok_or_else(|| MessageChangeError::NotFound.into())
+    }
+
+    /// DELETE /messages/:id — soft delete; the row is purged later by the retention job.
+    pub async fn delete_message(
+        pool: &PgPool,
+        tenant: &str,
+        message_id: Uuid,
+        user_id: Uuid,
+        window_minutes: i64,
+    ) -> anyhow::Result<MessageWithSender> {
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+
+        Self::lock_own_message(&mut tx, &schema, message_id, user_id, window_minutes).await?;
+
+        sqlx::query(&format!(
+            "UPDATE {schema}.messages SET is_deleted = TRUE, deleted_at = NOW() WHERE id = $1"
+        ))
+        .bind(message_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Self::get_with_sender(pool, tenant, message_id)
+            .await?
+            .ok_or_else(|| MessageChangeError::NotFound.into())
+    }
+
+    /// Staff removal of a parent's message from a group thread they can access,
+    /// outside of the edit window; threads show the same tombstone.
+    pub async fn moderate_message(
+        pool: &PgPool,
+        tenant: &str,
+        moderator: &AuthenticatedUser,
+        message_id: Uuid,
+    ) -> anyhow::Result<MessageWithSender> {
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+
+        let row: Option<(String, Option<Uuid>, String)> = sqlx::query_as(&format!(
+            "SELECT m.message_type::text, m.group_id, u.role::text
+             FROM {schema}.messages m
+             JOIN {schema}.users u ON u.id = m.sender_id
+             WHERE m.id = $1 AND m.is_deleted = FALSE
+             FOR UPDATE OF m"
+        ))
+        .bind(message_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let (message_type, group_id, sender_role) = row.ok_or(MessageChangeError::NotFound)?;
+        let Some(group_id) = group_id.filter(|_| message_type == "group" && sender_role == "parent") else {
+            return Err(MessageChangeError::NotSender.into());
+        };
+        if GroupService::ensure_group_access(pool, tenant, moderator, group_id).await.is_err() {
+            return Err(MessageChangeError::NotSender.into());
+        }
+
+        sqlx::query(&format!(
+            "UPDATE {schema}.messages SET is_deleted = TRUE, deleted_at = NOW() WHERE id = $1"
+        ))
+        .bind(message_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Self::get_with_sender(pool, tenant, message_id)
+            .await?
+            .ok_or_else(|| MessageChangeError::NotFound.into())
+    }
+
+    /// GET /messages/:id/reads — parents who read a message and the size of its audience.
+    pub async fn list_reads(
+        pool: &PgPool,
+        tenant: &str,
+        message_id: Uuid,
+    ) -> anyhow::Result<Option<MessageReadReceipts>> {
+        let schema = schema_name(tenant);
+
+        let target: Option<(String, Option<Uuid>, Uuid)> = sqlx::query_as(&format!(
+            "SELECT message_type::text, group_id, sender_id FROM {schema}.messages WHERE id = $1"
+        ))
+        .bind(message_id)
+        .fetch_optional(pool)
+        .await?;
+        let Some((message_type, group_id, sender_id)) = target else {
+            return Ok(None);
+        };
+
+        let recipient_count: i64 = match message_type.as_str() {
+            "broadcast" => sqlx::query_scalar(&format!(
+                "SELECT COUNT(*) FROM {schema}.users
+                 WHERE role::text = 'parent' AND is_active = TRUE AND id != $1"
+            ))
+            .bind(sender_id)
+            .fetch_one(pool)
+            .await?,
+            "group" => sqlx::query_scalar(&format!(
+                "SELECT COUNT(DISTINCT u.id) FROM {schema}.users u
+                 JOIN {schema}.child_parents cp ON cp.user_id = u.id
+                 JOIN {schema}.children c ON c.id = cp.child_id
+                 WHERE c.group_id = $1 AND u.is_active = TRUE AND u.id != $2"
+            ))
+            .bind(group_id)
+            .bind(sender_id)
+            .fetch_one(pool)
+            .await?,
+            _ => 1,
+        };
+
+        let readers = sqlx::query_as::<_, MessageReader>(&format!(
+            "SELECT r.user_id, u.first_name, u.last_name, r.read_at
+             FROM {schema}.message_reads r
+             JOIN {schema}.users u ON u.id = r.user_id
+             WHERE r.message_id = $1 AND u.role::text = 'parent'
+             ORDER BY r.read_at"
+        ))
+        .bind(message_id)
         .fetch_all(pool)
         .await?;
-        Ok(msgs)
+
+        Ok(Some(MessageReadReceipts {
+            message_id,
+            read_count: readers.len() as i64,
+            recipient_count,
+            readers,
+        }))
+    }
+
+    /// GET /messages/:id/edits — empty once the message is deleted.
+    pub async fn list_edits(
+        pool: &PgPool,
+        tenant: &str,
+        message_id: Uuid,
+    ) -> anyhow::Result<Vec<MessageEdit>> {
+        let schema = schema_name(tenant);
+        let edits = sqlx::query_as::<_, MessageEdit>(&format!(
+            "SELECT e.id, e.message_id, e.previous_content, e.edited_by, e.edited_at
+             FROM {schema}.message_edits e
+             JOIN {schema}.messages m ON m.id = e.message_id
+             WHERE e.message_id = $1 AND m.is_deleted = FALSE
+             ORDER BY e.edited_at ASC"
+        ))
+        .bind(message_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(edits)
     }
 
     /// Envoyer un message à des parents avec notification email automatique.
@@ -526,25 +958,29 @@ impl MessageService {
         tenant: &str,
         sender_id: Uuid,
         req: &SendToParentsRequest,
-    ) -> anyhow::Result<(Message, Vec<(String, String)>)> {
+    ) -> anyhow::Result<(Message, Vec<ParentRecipient>)> {
         let schema = schema_name(tenant);
 
-        // Récupérer la liste des parents selon le scope
-        let recipients: Vec<(String, String)> = match req.scope {
+        // Récupérer la liste des parents selon le scope, avec les noms de leurs
+        // enfants concernés pour les variables {child_name} et {group_name}
+        let recipients: Vec<ParentRecipient> = match req.scope {
             SendToParentsScope::AllParents => {
+                let cols = recipient_cols(&schema, "");
                 sqlx::query_as(&format!(
-                    "SELECT u.email, CONCAT(u.first_name, ' ', u.last_name)
+                    "SELECT {cols}
                      FROM {schema}.users u
-                     WHERE u.role = 'parent' AND u.is_active = TRUE
-                     ORDER BY u.first_name, u.last_name"
+                     WHERE u.role = 'parent' AND u.is_active = TRUE AND {}
+                     ORDER BY u.first_name, u.last_name",
+                    has_active_child_sql(&schema, "u")
                 ))
                 .fetch_all(pool)
                 .await?
             }
             SendToParentsScope::ChildParents => {
-                let child_id = req.child_id.ok_or_else(|| anyhow::anyhow!("child_id required for ChildParents scope"))?;
+                let child_id = req.child_id.ok_or(MessageSendError::MissingScopeTarget("child_id"))?;
+                let cols = recipient_cols(&schema, "AND c.id = $1");
                 sqlx::query_as(&format!(
-                    "SELECT u.email, CONCAT(u.first_name, ' ', u.last_name)
+                    "SELECT {cols}
                      FROM {schema}.users u
                      INNER JOIN {schema}.child_parents cp ON u.id = cp.user_id
                      WHERE cp.child_id = $1 AND u.is_active = TRUE
@@ -555,41 +991,237 @@ impl MessageService {
                 .await?
             }
             SendToParentsScope::GroupParents => {
-                let group_id = req.group_id.ok_or_else(|| anyhow::anyhow!("group_id required for GroupParents scope"))?;
+                let group_id = req.group_id.ok_or(MessageSendError::MissingScopeTarget("group_id"))?;
+                let cols = recipient_cols(&schema, "AND c.group_id = $1");
                 sqlx::query_as(&format!(
-                    "SELECT DISTINCT u.email, CONCAT(u.first_name, ' ', u.last_name)
+                    "SELECT {cols}
                      FROM {schema}.users u
-                     INNER JOIN {schema}.child_parents cp ON u.id = cp.user_id
-                     INNER JOIN {schema}.children c ON c.id = cp.child_id
-                     WHERE c.group_id = $1 AND u.is_active = TRUE
+                     WHERE u.is_active = TRUE AND EXISTS (
+                         SELECT 1 FROM {schema}.child_parents cp
+                         INNER JOIN {schema}.children c ON c.id = cp.child_id
+                         WHERE cp.user_id = u.id AND c.group_id = $1 AND c.is_active = TRUE
+                     )
                      ORDER BY u.first_name, u.last_name"
                 ))
                 .bind(group_id)
                 .fetch_all(pool)
                 .await?
             }
+            SendToParentsScope::FamilyParents => {
+                let family_id = req.family_id.ok_or(MessageSendError::MissingScopeTarget("family_id"))?;
+                let cols = recipient_cols(&schema, "AND c.family_id = $1");
+                sqlx::query_as(&format!(
+                    "SELECT {cols}
+                     FROM {schema}.users u
+                     WHERE u.is_active = TRUE AND EXISTS (
+                         SELECT 1 FROM {schema}.child_parents cp
+                         INNER JOIN {schema}.children c ON c.id = cp.child_id
+                         WHERE cp.user_id = u.id AND c.family_id = $1
+                     )
+                     ORDER BY u.first_name, u.last_name"
+                ))
+                .bind(family_id)
+                .fetch_all(pool)
+                .await?
+            }
         };
 
         if recipients.is_empty() {
-            anyhow::bail!("Aucun parent trouvé pour ce scope");
+            return Err(MessageSendError::NoParents.into());
         }
 
-        // Créer l'enregistrement du message avec le scope
+        // Créer l'enregistrement du message avec le scope ; la copie du fil ne remplace
+        // que les variables communes à tous les destinataires
+        let shared = TemplateVars::shared(&recipients);
+        let cols = msg_cols();
+        let thread_id = upsert_thread(pool, &schema, "broadcast", None, None).await?;
         let msg = sqlx::query_as::<_, Message>(&format!(
             "INSERT INTO {schema}.messages
-             (sender_id, message_type, subject, send_to_parents_scope, send_to_parents_child, send_to_parents_group, content, email_sent)
-             VALUES ($1, 'broadcast'::\"{schema}\".message_type, $2, $3::\"{schema}\".send_to_parents_scope, $4, $5, $6, FALSE)
-             RETURNING {MSG_COLS}"
+             (sender_id, message_type, subject, send_to_parents_scope, send_to_parents_child, send_to_parents_group, send_to_parents_family, content, email_sent, thread_id)
+             VALUES ($1, 'broadcast'::\"{schema}\".message_type, $2, $3::\"{schema}\".send_to_parents_scope, $4, $5, $7, $6, FALSE, $8)
+             RETURNING {cols}"
         ))
         .bind(sender_id)
-        .bind(&req.subject)
+        .bind(shared.expand(&req.subject))
         .bind(req.scope.to_string())
         .bind(req.child_id)
         .bind(req.group_id)
-        .bind(&req.content)
+        .bind(shared.expand(&req.content))
+        .bind(req.family_id)
+        .bind(thread_id)
         .fetch_one(pool)
         .await?;
 
         Ok((msg, recipients))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::scratch_tenant;
+    use crate::models::user::UserRole;
+    use std::time::Instant;
+
+    /// Fixture of 1k parents with an individual thread each, 10 groups and a
+    /// broadcast history.
+    #[tokio::test]
+    #[ignore]
+    async fn conversations_with_1k_parents() {
+        let tenant = scratch_tenant("bench").await;
+        let (pool, slug, schema) = (tenant.pool.clone(), tenant.slug.clone(), tenant.schema.clone());
+
+        let admin: Uuid = sqlx::query_scalar(&format!(
+            "INSERT INTO {schema}.users (email, password_hash, first_name, last_name, role)
+             VALUES ('admin@bench.test', '-', 'Ada', 'Admin', 'admin_garderie') RETURNING id"
+        ))
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        sqlx::raw_sql(&format!(
+            "INSERT INTO {schema}.users (email, password_hash, first_name, last_name, role)
+             SELECT 'parent' || i || '@bench.test', '-', 'Parent', i::text, 'parent'
+             FROM generate_series(1, 1000) i;
+             INSERT INTO {schema}.groups (name) SELECT 'Groupe ' || i FROM generate_series(1, 10) i;
+             INSERT INTO {schema}.threads (key, kind, parent_id)
+             SELECT 'individual:' || id, 'individual', id FROM {schema}.users WHERE role = 'parent';
+             INSERT INTO {schema}.threads (key, kind, group_id)
+             SELECT 'group:' || id, 'group', id FROM {schema}.groups;
+             -- 20 messages per parent, every other one from the parent and the last two unread
+             INSERT INTO {schema}.messages (sender_id, recipient_id, message_type, content, is_read, created_at, thread_id)
+             SELECT CASE WHEN i % 2 = 0 THEN t.parent_id ELSE '{admin}' END,
+                    CASE WHEN i % 2 = 0 THEN '{admin}' ELSE t.parent_id END,
+                    'individual', 'Message ' || i, i < 19, NOW() - (20 - i) * INTERVAL '1 minute', t.id
+             FROM {schema}.threads t CROSS JOIN generate_series(1, 20) i
+             WHERE t.kind = 'individual';
+             INSERT INTO {schema}.messages (sender_id, group_id, message_type, content, created_at, thread_id)
+             SELECT '{admin}', t.group_id, 'group', 'Groupe ' || i, NOW() - i * INTERVAL '1 hour', t.id
+             FROM {schema}.threads t CROSS JOIN generate_series(1, 200) i
+             WHERE t.kind = 'group';
+             INSERT INTO {schema}.messages (sender_id, message_type, content, created_at, thread_id)
+             SELECT '{admin}', 'broadcast', 'Annonce ' || i, NOW() - i * INTERVAL '1 day', t.id
+             FROM {schema}.threads t CROSS JOIN generate_series(1, 100) i
+             WHERE t.key = 'broadcast';
+             ANALYZE {schema}.users, {schema}.threads, {schema}.messages;"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let started = Instant::now();
+        let items = MessageService::get_conversations_admin(&pool, &slug, admin).await.unwrap();
+        // Seconds with the per-thread subqueries; well under one with the lateral joins
+        assert!(started.elapsed() < std::time::Duration::from_secs(1), "took {:?}", started.elapsed());
+
+        assert_eq!(items.len(), 1 + 10 + 1 + 10 + 1000);
+        assert_eq!(items[0].kind, "broadcast");
+        assert_eq!(items[0].last_message.as_deref(), Some("Annonce 1"));
+        assert_eq!(items[1].name, "Groupe 1");
+        assert_eq!(items[1].last_message.as_deref(), Some("Groupe 1"));
+        assert_eq!((items[11].kind.as_str(), items[11].name.as_str()), ("staff", "Équipe"));
+        assert_eq!((items[12].kind.as_str(), items[12].last_message.as_ref()), ("staff", None));
+        let parent = &items[22];
+        assert_eq!(parent.kind, "individual");
+        assert_eq!(parent.last_message.as_deref(), Some("Message 20"));
+        // Messages 19 and 20 are unread, only 20 comes from the parent
+        assert_eq!(parent.unread_count, 1);
+
+        let parent_id: Uuid = parent.id.as_deref().unwrap().parse().unwrap();
+        let own = MessageService::get_conversations_parent(&pool, &slug, parent_id).await.unwrap();
+        let thread = own.last().unwrap();
+        assert_eq!((thread.kind.as_str(), thread.unread_count), ("individual", 1));
+        assert_eq!(thread.last_message.as_deref(), Some("Message 20"));
+
+        // A parent's answer lands in the same thread as the staff's messages
+        let reply = MessageService::create_message(&pool, &slug, parent_id, &CreateMessageRequest {
+            message_type: MessageType::Individual,
+            group_id: None,
+            recipient_id: None,
+            content: "Merci".into(),
+            urgent: false,
+        })
+        .await
+        .unwrap();
+        assert_eq!(reply.thread_id, thread.thread_id);
+        MessageService::mark_thread_read(&pool, &slug, admin, "individual", Some(parent_id)).await.unwrap();
+        let page = MessageService::get_individual_thread(&pool, &slug, parent_id, 50, 0).await.unwrap();
+        assert_eq!(page.len(), 21);
+        assert!(page.iter().filter(|m| m.sender_id == parent_id).all(|m| m.is_read));
+
+        // Staff channels stay out of the parents' views
+        let group_id: Uuid = items[1].id.as_deref().unwrap().parse().unwrap();
+        let note = MessageService::create_message(&pool, &slug, admin, &CreateMessageRequest {
+            message_type: MessageType::Staff,
+            group_id: Some(group_id),
+            recipient_id: None,
+            content: "Sortie au parc demain".into(),
+            urgent: false,
+        })
+        .await
+        .unwrap();
+        sqlx::query(&format!(
+            "WITH child AS (
+                 INSERT INTO {schema}.children (first_name, last_name, birth_date, group_id)
+                 VALUES ('Léa', 'Test', '2022-01-01', $1) RETURNING id
+             )
+             INSERT INTO {schema}.child_parents (child_id, user_id) SELECT id, $2 FROM child"
+        ))
+        .bind(group_id)
+        .bind(parent_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let items = MessageService::get_conversations_admin(&pool, &slug, admin).await.unwrap();
+        let channel = items.iter().find(|i| i.kind == "staff" && i.id == Some(group_id.to_string())).unwrap();
+        assert_eq!((channel.thread_id, channel.last_message.as_deref()), (note.thread_id, Some("Sortie au parc demain")));
+        let feed = MessageService::list_messages(&pool, &slug, parent_id, 0, 100).await.unwrap();
+        assert!(feed.iter().all(|m| m.message_type != "staff"));
+        let own = MessageService::get_conversations_parent(&pool, &slug, parent_id).await.unwrap();
+        assert!(own.iter().all(|i| i.kind != "staff"));
+
+        tenant.drop().await;
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn scoped_educators_only_list_their_groups() {
+        let tenant = scratch_tenant("scope").await;
+        let (pool, slug, schema) = (tenant.pool.clone(), tenant.slug.clone(), tenant.schema.clone());
+        let groups: Vec<Uuid> = sqlx::query_scalar(&format!(
+            "INSERT INTO {schema}.groups (name) VALUES ('Poussins'), ('Lutins') RETURNING id"
+        ))
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let educator = AuthenticatedUser {
+            user_id: tenant.user("edu@scope.test", "educateur").await,
+            tenant: slug.clone(),
+            role: UserRole::Educateur,
+            session_id: None,
+            identity_id: None,
+            locale: None,
+        };
+        let listed = |items: &[ConversationItem], kind: &str| -> Vec<Option<String>> {
+            items.iter().filter(|i| i.kind == kind).map(|i| i.id.clone()).collect()
+        };
+
+        // Without assignments an educator still sees every group
+        let items = MessageService::get_conversations_staff(&pool, &slug, &educator).await.unwrap();
+        assert_eq!(listed(&items, "group").len(), 2);
+
+        sqlx::query(&format!("INSERT INTO {schema}.educator_groups (user_id, group_id) VALUES ($1, $2)"))
+            .bind(educator.user_id)
+            .bind(groups[0])
+            .execute(&pool)
+            .await
+            .unwrap();
+        let items = MessageService::get_conversations_staff(&pool, &slug, &educator).await.unwrap();
+        let own = Some(groups[0].to_string());
+        assert_eq!(listed(&items, "group"), vec![own.clone()]);
+        // The garderie-wide staff channel, then their group's
+        assert_eq!(listed(&items, "staff"), vec![None, own]);
+        assert_eq!(listed(&items, "broadcast").len(), 1);
+
+        tenant.drop().await;
+    }
+}
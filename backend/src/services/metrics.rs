@@ -3,6 +3,9 @@ use prometheus::{register_counter_vec, register_gauge_vec, register_gauge, Count
 use sqlx::PgPool;
 use tracing::{info, warn};
 
+use crate::db::tenant::schema_name;
+use crate::db::tenant_db::TenantDb;
+
 lazy_static! {
     // ── Event counters (increment on each event) ────────────────────────────
     pub static ref LOGINS_COUNTER: CounterVec = register_counter_vec!(
@@ -53,6 +56,12 @@ lazy_static! {
         &["tenant"]
     ).unwrap();
 
+    pub static ref MEDIA_URL_DENIED_COUNTER: CounterVec = register_counter_vec!(
+        "api_media_url_denied_total",
+        "Requêtes /media/files sans signature valide par tenant, raison et refus effectif",
+        &["tenant", "reason", "enforced"]
+    ).unwrap();
+
     // ── Business metrics ────────────────────────────────────────────────────
     pub static ref USERS_GAUGE: GaugeVec = register_gauge_vec!(
         "garderie_users_total",
@@ -121,76 +130,79 @@ async fn collect(pool: &PgPool) -> anyhow::Result<()> {
     TENANTS_GAUGE.set(tenants.len() as f64);
 
     for slug in &tenants {
-        let schema = format!("garderie_{}", slug);
-
-        // Pre-initialize event counters so they appear in /metrics even before first event.
-        // Calling with_label_values() creates the time series at 0 if not yet present.
-        let _ = LOGINS_COUNTER.with_label_values(&[slug, "success"]);
-        let _ = LOGINS_COUNTER.with_label_values(&[slug, "failed"]);
-        let _ = TWO_FA_COUNTER.with_label_values(&[slug]);
-        let _ = INVITATIONS_COUNTER.with_label_values(&[slug]);
-        let _ = PASSWORD_RESETS_COUNTER.with_label_values(&[slug]);
-        let _ = MEDIA_UPLOADS_COUNTER.with_label_values(&[slug]);
-        let _ = DOCUMENT_UPLOADS_COUNTER.with_label_values(&[slug]);
-        let _ = MESSAGES_COUNTER.with_label_values(&[slug]);
-        let _ = JOURNAL_EMAILS_COUNTER.with_label_values(&[slug]);
-
-        // Users by role
-        let user_counts: Vec<(String, i64)> = sqlx::query_as(&format!(
-            r#"SELECT role::TEXT, COUNT(*)::BIGINT FROM "{schema}".users WHERE is_active = TRUE GROUP BY role"#
-        ))
-        .fetch_all(pool)
-        .await
-        .unwrap_or_default();
-
-        for (role, count) in user_counts {
-            USERS_GAUGE.with_label_values(&[slug, &role]).set(count as f64);
-        }
+        TenantDb::scope(slug.clone(), async {
+            let schema = schema_name(slug);
+
+            // Pre-initialize event counters so they appear in /metrics even before first event.
+            // Calling with_label_values() creates the time series at 0 if not yet present.
+            let _ = LOGINS_COUNTER.with_label_values(&[slug, "success"]);
+            let _ = LOGINS_COUNTER.with_label_values(&[slug, "failed"]);
+            let _ = TWO_FA_COUNTER.with_label_values(&[slug]);
+            let _ = INVITATIONS_COUNTER.with_label_values(&[slug]);
+            let _ = PASSWORD_RESETS_COUNTER.with_label_values(&[slug]);
+            let _ = MEDIA_UPLOADS_COUNTER.with_label_values(&[slug]);
+            let _ = DOCUMENT_UPLOADS_COUNTER.with_label_values(&[slug]);
+            let _ = MESSAGES_COUNTER.with_label_values(&[slug]);
+            let _ = JOURNAL_EMAILS_COUNTER.with_label_values(&[slug]);
+
+            // Users by role
+            let user_counts: Vec<(String, i64)> = sqlx::query_as(&format!(
+                r#"SELECT role::TEXT, COUNT(*)::BIGINT FROM "{schema}".users WHERE is_active = TRUE GROUP BY role"#
+            ))
+            .fetch_all(pool)
+            .await
+            .unwrap_or_default();
+
+            for (role, count) in user_counts {
+                USERS_GAUGE.with_label_values(&[slug, &role]).set(count as f64);
+            }
 
-        // Active children
-        let children: i64 = sqlx::query_scalar(&format!(
-            r#"SELECT COUNT(*)::BIGINT FROM "{schema}".children WHERE is_active = TRUE"#
-        ))
-        .fetch_one(pool)
-        .await
-        .unwrap_or(0);
-        CHILDREN_GAUGE.with_label_values(&[slug]).set(children as f64);
-
-        // Journals sent
-        let journals: i64 = sqlx::query_scalar(&format!(
-            r#"SELECT COUNT(*)::BIGINT FROM "{schema}".daily_journals WHERE sent_at IS NOT NULL"#
-        ))
-        .fetch_one(pool)
-        .await
-        .unwrap_or(0);
-        JOURNALS_SENT_GAUGE.with_label_values(&[slug]).set(journals as f64);
-
-        // Messages
-        let messages: i64 = sqlx::query_scalar(&format!(
-            r#"SELECT COUNT(*)::BIGINT FROM "{schema}".messages"#
-        ))
-        .fetch_one(pool)
-        .await
-        .unwrap_or(0);
-        MESSAGES_GAUGE.with_label_values(&[slug]).set(messages as f64);
-
-        // Media files
-        let media: i64 = sqlx::query_scalar(&format!(
-            r#"SELECT COUNT(*)::BIGINT FROM "{schema}".media"#
-        ))
-        .fetch_one(pool)
-        .await
-        .unwrap_or(0);
-        MEDIA_GAUGE.with_label_values(&[slug]).set(media as f64);
-
-        // Documents
-        let documents: i64 = sqlx::query_scalar(&format!(
-            r#"SELECT COUNT(*)::BIGINT FROM "{schema}".documents"#
-        ))
-        .fetch_one(pool)
-        .await
-        .unwrap_or(0);
-        DOCUMENTS_GAUGE.with_label_values(&[slug]).set(documents as f64);
+            // Active children
+            let children: i64 = sqlx::query_scalar(&format!(
+                r#"SELECT COUNT(*)::BIGINT FROM "{schema}".children WHERE is_active = TRUE"#
+            ))
+            .fetch_one(pool)
+            .await
+            .unwrap_or(0);
+            CHILDREN_GAUGE.with_label_values(&[slug]).set(children as f64);
+
+            // Journals sent
+            let journals: i64 = sqlx::query_scalar(&format!(
+                r#"SELECT COUNT(*)::BIGINT FROM "{schema}".daily_journals WHERE sent_at IS NOT NULL"#
+            ))
+            .fetch_one(pool)
+            .await
+            .unwrap_or(0);
+            JOURNALS_SENT_GAUGE.with_label_values(&[slug]).set(journals as f64);
+
+            // Messages
+            let messages: i64 = sqlx::query_scalar(&format!(
+                r#"SELECT COUNT(*)::BIGINT FROM "{schema}".messages"#
+            ))
+            .fetch_one(pool)
+            .await
+            .unwrap_or(0);
+            MESSAGES_GAUGE.with_label_values(&[slug]).set(messages as f64);
+
+            // Media files
+            let media: i64 = sqlx::query_scalar(&format!(
+                r#"SELECT COUNT(*)::BIGINT FROM "{schema}".media"#
+            ))
+            .fetch_one(pool)
+            .await
+            .unwrap_or(0);
+            MEDIA_GAUGE.with_label_values(&[slug]).set(media as f64);
+
+            // Documents
+            let documents: i64 = sqlx::query_scalar(&format!(
+                r#"SELECT COUNT(*)::BIGINT FROM "{schema}".documents"#
+            ))
+            .fetch_one(pool)
+            .await
+            .unwrap_or(0);
+            DOCUMENTS_GAUGE.with_label_values(&[slug]).set(documents as f64);
+        })
+        .await;
     }
 
     info!("Metrics: collected for {} tenant(s)", tenants.len());
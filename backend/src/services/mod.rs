@@ -1,16 +1,88 @@
+pub mod absence_scheduler;
+pub mod absences;
+pub mod albums;
+pub mod antivirus;
 pub mod audit;
+pub mod branding;
+pub mod captcha;
 pub mod auth;
+pub mod auto_absences;
+pub mod auto_replies;
+pub mod backup_scheduler;
+pub mod backups;
+pub mod child_transfers;
 pub mod children;
+pub mod cloning;
+pub mod consents;
 pub mod cron;
+pub mod departure_scheduler;
+pub mod departures;
+pub mod development;
 pub mod metrics;
+pub mod document_expiry_scheduler;
+pub mod document_shares;
 pub mod documents;
 pub mod email;
+pub mod email_i18n;
+pub mod error_i18n;
+pub mod email_log;
+pub mod email_suppressions;
+pub mod email_templates;
+pub mod email_tracking;
+pub mod emergency;
+pub mod emergency_scheduler;
 pub mod encryption;
+pub mod entitlements;
+pub mod erasure;
+pub mod events;
+pub mod families;
+pub mod feature_flags;
 pub mod groups;
+pub mod identities;
+pub mod invoices;
 pub mod journal;
+pub mod journal_photos;
 pub mod journal_scheduler;
+pub mod jwt_keys;
+pub mod key_rotation;
 pub mod trial_scheduler;
 pub mod menu;
 pub mod media;
+pub mod media_comments;
+pub mod media_urls;
+pub mod meeting_scheduler;
+pub mod meetings;
+pub mod message_deliveries;
+pub mod message_drafts;
 pub mod messages;
+pub mod notification_consumer;
 pub mod notifications;
+pub mod object_store;
+pub mod oidc;
+pub mod operation_scheduler;
+pub mod operations;
+pub mod password_policy;
+pub mod platform_stats;
+pub mod pdf;
+pub mod presence;
+pub mod push_token_scheduler;
+pub mod ratio_scheduler;
+pub mod ratios;
+pub mod realtime;
+pub mod reactions;
+pub mod redaction;
+pub mod retention;
+pub mod retention_scheduler;
+pub mod search;
+pub mod signature_scheduler;
+pub mod stats;
+pub mod stats_scheduler;
+pub mod sms;
+pub mod tax_receipts;
+pub mod thread_states;
+pub mod storage;
+pub mod unread;
+pub mod upload_types;
+pub mod video_previews;
+pub mod waitlist;
+pub mod worker;
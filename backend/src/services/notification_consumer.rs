@@ -0,0 +1,807 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use chrono::NaiveDate;
+use redis::{aio::MultiplexedConnection, AsyncCommands};
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::{tenant::schema_name, tenant_db::TenantDb},
+    models::{auth::AuthenticatedUser, message::MessageWithSender, user::UserRole},
+    services::{
+        branding::BrandingService,
+        email::EmailService,
+        emergency::Outcome,
+        entitlements::EntitlementService,
+        events::{self, DomainEvent, Envelope, STREAM},
+        journal::JournalService,
+        media_comments::MediaCommentService,
+        message_deliveries::{self, MessageDeliveryService},
+        messages::MessageService,
+        notifications::{has_active_child_sql, NotificationService},
+        realtime::RealtimeService,
+        sms::SmsService,
+        thread_states::ThreadStateService,
+        unread::{Delivery, UnreadService},
+        worker,
+    },
+};
+
+/// Consumer group reading the event stream; each event is handled by one API instance.
+const GROUP: &str = "notifications";
+/// Events read at a time.
+const BATCH: usize = 50;
+/// How long a read waits for new events, in milliseconds.
+const BLOCK_MS: u64 = 5_000;
+/// Longest message excerpt shown in a push notification.
+const PUSH_PREVIEW_CHARS: usize = 120;
+
+/// Notifications sent at most once per window, so a burst of messages or uploads
+/// makes one email. Keys are shared by all API instances through Redis.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cooldown {
+    /// Message emails for the broadcast thread.
+    Broadcast,
+    /// Message emails for a group thread.
+    Group(Uuid),
+    /// Message emails for the private thread of a parent.
+    Individual(Uuid),
+    /// New photo and video emails to a parent.
+    MediaUpload(Uuid),
+    /// Push of the live journal updates of a child, sent at the end of the window.
+    JournalLive(Uuid),
+}
+
+impl Cooldown {
+    /// The thread cooldown of a message, if its thread is known.
+    pub fn for_message(msg: &MessageWithSender) -> Option<Self> {
+        match msg.message_type.as_str() {
+            "broadcast" => Some(Self::Broadcast),
+            "group" => msg.group_id.map(Self::Group),
+            // The thread is identified by the parent: recipient when staff write,
+            // sender when the parent does.
+            "individual" => Some(Self::Individual(msg.recipient_id.unwrap_or(msg.sender_id))),
+            _ => None,
+        }
+    }
+
+    pub fn key(&self, tenant: &str) -> String {
+        match self {
+            Self::Broadcast => format!("notif_cooldown:{tenant}:broadcast"),
+            Self::Group(id) => format!("notif_cooldown:{tenant}:group:{id}"),
+            Self::Individual(id) => format!("notif_cooldown:{tenant}:individual:{id}"),
+            Self::MediaUpload(parent_id) => format!("notif_cooldown:{tenant}:media_upload:{parent_id}"),
+            Self::JournalLive(child_id) => format!("notif_cooldown:{tenant}:journal_live:{child_id}"),
+        }
+    }
+
+    pub fn seconds(&self) -> u64 {
+        match self {
+            Self::Broadcast | Self::Group(_) | Self::Individual(_) => 15 * 60,
+            Self::MediaUpload(_) => 60 * 60,
+            Self::JournalLive(_) => 10 * 60,
+        }
+    }
+
+    /// Start the window; false when a notification already went out within it.
+    pub async fn claim(&self, redis: &mut MultiplexedConnection, tenant: &str) -> bool {
+        // SET NX EX answers "OK" only when the key was not set yet.
+        let newly_set: Option<String> = redis::cmd("SET")
+            .arg(self.key(tenant))
+            .arg("1")
+            .arg("NX")
+            .arg("EX")
+            .arg(self.seconds())
+            .query_async(redis)
+            .await
+            .unwrap_or(None);
+        newly_set.is_some()
+    }
+}
+
+/// Journal updates of a child waiting for the push that ends their window.
+fn journal_pending_key(tenant: &str, child_id: Uuid) -> String {
+    format!("journal_live_pending:{tenant}:{child_id}")
+}
+
+/// Body of the push summing up the journal updates of a window.
+fn journal_push_body(first_name: &str, updates: i64) -> String {
+    if updates <= 1 {
+        format!("La journée de {first_name} vient d'être mise à jour")
+    } else {
+        format!("{updates} nouvelles mises à jour de la journée de {first_name}")
+    }
+}
+
+/// URL of a page of the garderie's app.
+pub(crate) fn app_url(base: &str, tenant: &str, path: &str) -> String {
+    match base.find("://") {
+        Some(idx) => format!("{}://{tenant}.{}{path}", &base[..idx], &base[idx + 3..]),
+        None => format!("https://{tenant}.{base}{path}"),
+    }
+}
+
+/// Sends the emails, push notifications, SMS and WebSocket updates for the domain
+/// events published by request handlers (see `services::events`).
+#[derive(Clone)]
+pub struct NotificationConsumer {
+    pub pool: PgPool,
+    pub redis: MultiplexedConnection,
+    pub email: Option<Arc<EmailService>>,
+    pub sms: Option<Arc<SmsService>>,
+    pub notifications: Arc<NotificationService>,
+    pub config: Arc<Config>,
+}
+
+/// Spawn the consumer: read the event stream in the `notifications` group and handle
+/// each event in its own task, acknowledging it once handled. Events left
+/// unacknowledged by a previous run of this instance are handled again first.
+/// Reading stops once the process is shutting down.
+pub fn start(
+    pool: PgPool,
+    client: redis::Client,
+    email: Option<Arc<EmailService>>,
+    sms: Option<Arc<SmsService>>,
+    notifications: Arc<NotificationService>,
+    config: Arc<Config>,
+) {
+    tokio::spawn(async move {
+        // Stable across restarts, so that this instance picks up its own pending events.
+        let consumer_name = std::env::var("HOSTNAME").unwrap_or_else(|_| "api".to_string());
+        loop {
+            // Blocking reads get their own connection, handlers share another.
+            let (reader, redis) = match (
+                client.get_multiplexed_async_connection().await,
+                client.get_multiplexed_async_connection().await,
+            ) {
+                (Ok(reader), Ok(redis)) => (reader, redis),
+                (Err(e), _) | (_, Err(e)) => {
+                    tracing::warn!("Notification consumer: Redis unavailable: {e}");
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    continue;
+                }
+            };
+            let consumer = NotificationConsumer {
+                pool: pool.clone(),
+                redis,
+                email: email.clone(),
+                sms: sms.clone(),
+                notifications: notifications.clone(),
+                config: config.clone(),
+            };
+            match consumer.run(reader, &consumer_name).await {
+                Ok(()) => break,
+                Err(e) => {
+                    tracing::warn!("Notification consumer: stream read failed, reconnecting: {e}");
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+    });
+}
+
+impl NotificationConsumer {
+    async fn run(&self, mut reader: MultiplexedConnection, consumer_name: &str) -> redis::RedisResult<()> {
+        let created: redis::RedisResult<()> = redis::cmd("XGROUP")
+            .arg("CREATE")
+            .arg(STREAM)
+            .arg(GROUP)
+            .arg("$")
+            .arg("MKSTREAM")
+            .query_async(&mut reader)
+            .await;
+        if let Err(e) = created {
+            if e.code() != Some("BUSYGROUP") {
+                return Err(e);
+            }
+        }
+
+        // This consumer's pending events are read once, by id, before new ones (">").
+        let mut pending_after = Some("0".to_string());
+        loop {
+            let from = pending_after.clone().unwrap_or_else(|| ">".to_string());
+            let reply: redis::Value = redis::cmd("XREADGROUP")
+                .arg("GROUP")
+                .arg(GROUP)
+                .arg(consumer_name)
+                .arg("COUNT")
+                .arg(BATCH)
+                .arg("BLOCK")
+                .arg(BLOCK_MS)
+                .arg("STREAMS")
+                .arg(STREAM)
+                .arg(&from)
+                .query_async(&mut reader)
+                .await?;
+            let entries = events::entries(&reply);
+            if pending_after.is_some() {
+                pending_after = entries.last().map(|(id, _)| id.clone()).filter(|_| entries.len() == BATCH);
+            }
+            for (id, payload) in entries {
+                // Left unacknowledged when stopping, so handled again on the next start
+                let Some(job) = worker::job().await else {
+                    return Ok(());
+                };
+                let consumer = self.clone();
+                tokio::spawn(async move {
+                    let _job = job;
+                    match payload.as_deref().map(serde_json::from_str::<Envelope>) {
+                        Some(Ok(envelope)) => consumer.handle(envelope).await,
+                        Some(Err(e)) => tracing::warn!("Notification consumer: unreadable event {id}: {e}"),
+                        None => {}
+                    }
+                    let mut redis = consumer.redis.clone();
+                    let acked: redis::RedisResult<i64> = redis.xack(STREAM, GROUP, &[&id]).await;
+                    if let Err(e) = acked {
+                        tracing::warn!("Notification consumer: event {id} not acknowledged: {e}");
+                    }
+                });
+            }
+        }
+    }
+
+    async fn handle(&self, envelope: Envelope) {
+        let tenant = envelope.tenant;
+        TenantDb::scope(tenant.clone(), async {
+            match envelope.event {
+                DomainEvent::MediaPublished { media_id, uploader_id } => self.media_published(&tenant, media_id, uploader_id).await,
+                DomainEvent::MessageSent { message, urgent, notify_email } => {
+                    self.message_sent(&tenant, *message, urgent, notify_email).await
+                }
+                DomainEvent::JournalSent { child_ids, week_start } => self.journal_sent(&tenant, &child_ids, week_start).await,
+                DomainEvent::MediaCommented { media_id, comment_id } => self.media_commented(&tenant, media_id, comment_id).await,
+                DomainEvent::JournalUpdated { child_id, date, event_type } => {
+                    self.journal_updated(&tenant, child_id, date, event_type).await
+                }
+            }
+        })
+        .await
+    }
+
+    /// Email the parents who can see the media, once an hour at most each.
+    /// Disabled for the demo garderie (fake addresses).
+    async fn media_published(&self, tenant: &str, media_id: Uuid, uploader_id: Uuid) {
+        let Some(email_svc) = self.email.as_ref().filter(|_| tenant != "demo") else {
+            return;
+        };
+        let pool = &self.pool;
+        let s = schema_name(tenant);
+
+        let media: Option<(String, Option<Uuid>, String)> = sqlx::query_as(&format!(
+            "SELECT visibility::text, group_id, media_type::text FROM {s}.media WHERE id = $1"
+        ))
+        .bind(media_id)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or_default();
+        let Some((visibility, group_id, media_type)) = media.filter(|(v, ..)| v != "private") else {
+            return;
+        };
+
+        let (garderie_name, branding) = BrandingService::for_email(pool, tenant).await;
+
+        let uploader_name: String = sqlx::query_scalar(&format!(
+            "SELECT CONCAT(first_name, ' ', last_name) FROM {s}.users WHERE id = $1"
+        ))
+        .bind(uploader_id)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or_default()
+        .unwrap_or_else(|| "Un éducateur".to_string());
+
+        let url = app_url(&self.config.app_base_url, tenant, "/fr/parent/media");
+
+        let recipients: Vec<(Uuid, String, String)> = match visibility.as_str() {
+            "public" => sqlx::query_as(&format!(
+                "SELECT id, email, CONCAT(first_name, ' ', last_name)
+                 FROM {s}.users WHERE role::text = 'parent' AND is_active = TRUE AND {}",
+                has_active_child_sql(&s, "users")
+            ))
+            .fetch_all(pool)
+            .await
+            .unwrap_or_default(),
+
+            "group" => match group_id {
+                Some(gid) => sqlx::query_as(&format!(
+                    "SELECT DISTINCT u.id, u.email, CONCAT(u.first_name, ' ', u.last_name)
+                     FROM {s}.users u
+                     JOIN {s}.child_parents cp ON cp.user_id = u.id
+                     JOIN {s}.children c ON c.id = cp.child_id
+                     WHERE c.group_id = $1 AND c.is_active = TRUE AND u.is_active = TRUE"
+                ))
+                .bind(gid)
+                .fetch_all(pool)
+                .await
+                .unwrap_or_default(),
+                None => vec![],
+            },
+
+            "child" => sqlx::query_as(&format!(
+                "SELECT DISTINCT u.id, u.email, CONCAT(u.first_name, ' ', u.last_name)
+                 FROM {s}.users u
+                 JOIN {s}.child_parents cp ON cp.user_id = u.id
+                 JOIN {s}.media_children mc ON mc.child_id = cp.child_id
+                 WHERE mc.media_id = $1 AND u.is_active = TRUE"
+            ))
+            .bind(media_id)
+            .fetch_all(pool)
+            .await
+            .unwrap_or_default(),
+
+            _ => vec![],
+        };
+
+        let content_kind = if media_type == "video" { "une vidéo" } else { "de nouvelles photos" };
+
+        let mut redis = self.redis.clone();
+        for (parent_id, email, name) in recipients {
+            if Cooldown::MediaUpload(parent_id).claim(&mut redis, tenant).await {
+                let _ = email_svc
+                    .send_media_notification(tenant, &email, &name, &uploader_name, content_kind, &url, &garderie_name, &branding)
+                    .await;
+            }
+        }
+    }
+
+    /// Push a new comment to the media's uploader and earlier commenters.
+    async fn media_commented(&self, tenant: &str, media_id: Uuid, comment_id: Uuid) {
+        if self.notifications.fcm_api_key.is_none() || tenant == "demo" {
+            return;
+        }
+        let pool = &self.pool;
+        let comment = match MediaCommentService::get(pool, tenant, comment_id).await {
+            Ok(Some(comment)) => comment,
+            // Deleted before it was handled
+            Ok(None) => return,
+            Err(e) => {
+                tracing::warn!("Comment {comment_id} in '{tenant}' not notified: {e}");
+                return;
+            }
+        };
+        let recipients = MediaCommentService::recipients(pool, tenant, &comment).await.unwrap_or_else(|e| {
+            tracing::warn!("Recipients of comment {comment_id} in '{tenant}' not read: {e}");
+            Vec::new()
+        });
+        let title = comment.author_name.clone().unwrap_or_else(|| "Nouveau commentaire".to_string());
+        let preview: String = comment.content.chars().take(PUSH_PREVIEW_CHARS).collect();
+        // FCM data values must be strings.
+        let data = json!({ "type": "media_comment", "media_id": media_id.to_string() });
+        for user_id in recipients {
+            if let Err(e) = self.notifications.notify_user(pool, tenant, user_id, &title, &preview, Some(data.clone()), None).await {
+                tracing::warn!("Comment push to {user_id} in '{tenant}' failed: {e}");
+            }
+        }
+    }
+
+    /// Real-time update, unread counters and push for the recipients, SMS for urgent
+    /// messages, then email with a cooldown per thread. Recipients who muted the
+    /// thread get neither push nor email.
+    async fn message_sent(&self, tenant: &str, msg: MessageWithSender, urgent: bool, notify_email: bool) {
+        let mut redis = self.redis.clone();
+        let payload = serde_json::to_string(&msg).unwrap_or_default();
+        RealtimeService::publish_message(&self.pool, &mut redis, tenant, &msg, &payload).await;
+
+        let muted = self.deliver(tenant, &msg).await;
+
+        if urgent && tenant != "demo" {
+            self.text_urgent(tenant, &msg).await;
+        }
+
+        if notify_email && tenant != "demo" {
+            self.email_message(tenant, &msg, &muted).await;
+        }
+    }
+
+    /// Count the message unread for its recipients and push it to their devices,
+    /// except those who muted the thread, who are returned.
+    async fn deliver(&self, tenant: &str, msg: &MessageWithSender) -> HashSet<Uuid> {
+        let pool = &self.pool;
+        let mut redis = self.redis.clone();
+        let delivery =
+            UnreadService::delivery(pool, tenant, &msg.message_type, msg.sender_id, msg.group_id, msg.recipient_id).await;
+        let mut delivery = match delivery {
+            Ok(Some(d)) => d,
+            Ok(None) => return HashSet::new(),
+            Err(e) => {
+                tracing::warn!("Unread counters not updated for a message in '{tenant}': {e}");
+                return HashSet::new();
+            }
+        };
+        UnreadService::message_sent(&mut redis, tenant, &delivery).await;
+
+        let muted = ThreadStateService::message_delivered(pool, tenant, delivery.thread_key(), &delivery.recipients)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Thread states not read for a message in '{tenant}': {e}");
+                HashSet::new()
+            });
+        delivery.recipients.retain(|id| !muted.contains(id));
+        self.push_message(tenant, delivery, msg, &muted).await;
+        muted
+    }
+
+    /// Push notifications for devices registered by the recipients, with their unread
+    /// count as the iOS badge.
+    async fn push_message(&self, tenant: &str, delivery: Delivery, msg: &MessageWithSender, muted: &HashSet<Uuid>) {
+        if self.notifications.fcm_api_key.is_none() || tenant == "demo" {
+            return;
+        }
+        let pool = &self.pool;
+        let mut redis = self.redis.clone();
+        let sender_id = msg.sender_id;
+        let preview: String = msg.content.chars().take(PUSH_PREVIEW_CHARS).collect();
+        let s = schema_name(tenant);
+        let sender_name: String = sqlx::query_scalar(&format!(
+            "SELECT CONCAT(first_name, ' ', last_name) FROM {s}.users WHERE id = $1"
+        ))
+        .bind(sender_id)
+        .fetch_optional(pool)
+        .await
+        .unwrap_or_default()
+        .unwrap_or_else(|| "Nouveau message".to_string());
+
+        let devices: Vec<(Uuid, String)> = sqlx::query_as(&format!(
+            "SELECT DISTINCT u.id, u.role::text
+             FROM {s}.users u
+             JOIN {s}.push_tokens pt ON pt.user_id = u.id
+             WHERE u.id = ANY($1)"
+        ))
+        .bind(&delivery.recipients)
+        .fetch_all(pool)
+        .await
+        .unwrap_or_default();
+
+        let mut outcomes: Vec<(Uuid, Outcome)> =
+            muted.iter().map(|id| (*id, Outcome::Skipped(message_deliveries::MUTED.into()))).collect();
+        outcomes.extend(
+            delivery
+                .recipients
+                .iter()
+                .filter(|id| !devices.iter().any(|(user_id, _)| user_id == *id))
+                .map(|id| (*id, Outcome::Skipped(message_deliveries::NO_DEVICE.into()))),
+        );
+
+        let (kind, thread_id) = delivery.thread();
+        for (user_id, role) in devices {
+            let Ok(role) = role.parse::<UserRole>() else { continue };
+            let recipient =
+                AuthenticatedUser { user_id, tenant: tenant.to_string(), role, session_id: None, identity_id: None, locale: None };
+            let badge = UnreadService::counts(pool, &mut redis, tenant, &recipient)
+                .await
+                .ok()
+                .map(|c| c.total);
+            // FCM data values must be strings.
+            let data = json!({
+                "type": "message",
+                "kind": kind,
+                "id": thread_id.clone().unwrap_or_default(),
+                "unread_count": badge.unwrap_or_default().to_string(),
+            });
+            let outcome = match self
+                .notifications
+                .notify_user(pool, tenant, user_id, &sender_name, &preview, Some(data), badge)
+                .await
+            {
+                Ok(()) => Outcome::Sent,
+                Err(e) => {
+                    tracing::warn!("Message push to {user_id} in '{tenant}' failed: {e}");
+                    Outcome::Error(e.to_string())
+                }
+            };
+            outcomes.push((user_id, outcome));
+        }
+        self.record_deliveries(tenant, msg.id, "push", &outcomes).await;
+    }
+
+    async fn record_deliveries(&self, tenant: &str, message_id: Uuid, channel: &str, outcomes: &[(Uuid, Outcome)]) {
+        if let Err(e) = MessageDeliveryService::record(&self.pool, tenant, message_id, channel, outcomes).await {
+            tracing::warn!("Message {channel} deliveries not recorded in '{tenant}': {e}");
+        }
+    }
+
+    /// SMS for urgent broadcast and group messages, without cooldown.
+    async fn text_urgent(&self, tenant: &str, msg: &MessageWithSender) {
+        // Cible : Some(None) = tous les parents, Some(Some(id)) = parents du groupe
+        let target = match msg.message_type.as_str() {
+            "broadcast" => Some(None),
+            "group" => msg.group_id.map(Some),
+            _ => None,
+        };
+        let (Some(group_id), Some(sms_svc)) = (target, self.sms.as_ref()) else {
+            return;
+        };
+        let garderie_name: String = sqlx::query_scalar("SELECT name FROM public.garderies WHERE slug = $1")
+            .bind(tenant)
+            .fetch_optional(&self.pool)
+            .await
+            .unwrap_or_default()
+            .unwrap_or_else(|| tenant.to_string());
+
+        let phones = MessageService::parent_phones(&self.pool, tenant, group_id).await.unwrap_or_default();
+        // Parents past the month's SMS credits still get the email and push notification
+        let granted = match EntitlementService::take_sms_credits(&self.pool, tenant, phones.len() as i64).await {
+            Ok(granted) => granted as usize,
+            Err(e) => {
+                tracing::warn!("SMS credits unavailable for tenant {tenant}: {e}");
+                0
+            }
+        };
+        if granted < phones.len() {
+            tracing::info!("SMS credits exhausted for tenant {tenant}: {} of {} urgent SMS sent", granted, phones.len());
+        }
+        let mut outcomes = Vec::with_capacity(phones.len());
+        for (i, (parent_id, phone)) in phones.into_iter().enumerate() {
+            let outcome = if i >= granted {
+                Outcome::Skipped(message_deliveries::NO_SMS_CREDITS.into())
+            } else {
+                match sms_svc.send_urgent_message(&phone, &garderie_name, &msg.content).await {
+                    Ok(()) => Outcome::Sent,
+                    Err(e) => {
+                        tracing::warn!("Urgent SMS failed for tenant {tenant}: {e}");
+                        Outcome::Error(e.to_string())
+                    }
+                }
+            };
+            outcomes.push((parent_id, outcome));
+        }
+        self.record_deliveries(tenant, msg.id, "sms", &outcomes).await;
+    }
+
+    /// Email the other members of the thread, once per thread every 15 minutes.
+    async fn email_message(&self, tenant: &str, msg: &MessageWithSender, muted: &HashSet<Uuid>) {
+        let Some(email_svc) = self.email.as_ref() else {
+            return;
+        };
+        let Some(cooldown) = Cooldown::for_message(msg) else {
+            return;
+        };
+        let pool = &self.pool;
+        let s = schema_name(tenant);
+
+        let (recipients, thread_name): (Vec<(Uuid, String, String)>, String) = match cooldown {
+            Cooldown::Broadcast => (
+                sqlx::query_as(&format!(
+                    "SELECT id, email, CONCAT(first_name, ' ', last_name)
+                     FROM {s}.users
+                     WHERE role::text = 'parent' AND is_active = TRUE AND {}",
+                    has_active_child_sql(&s, "users")
+                ))
+                .fetch_all(pool)
+                .await
+                .unwrap_or_default(),
+                "Tous les parents".to_string(),
+            ),
+            Cooldown::Group(group_id) => {
+                // Les autres parents du groupe (l'auteur d'une réponse est exclu)
+                let recipients = sqlx::query_as(&format!(
+                    "SELECT DISTINCT u.id, u.email, CONCAT(u.first_name, ' ', u.last_name)
+                     FROM {s}.users u
+                     JOIN {s}.child_parents cp ON cp.user_id = u.id
+                     JOIN {s}.children c ON c.id = cp.child_id
+                     WHERE c.group_id = $1 AND c.is_active = TRUE AND u.is_active = TRUE AND u.id != $2"
+                ))
+                .bind(group_id)
+                .bind(msg.sender_id)
+                .fetch_all(pool)
+                .await
+                .unwrap_or_default();
+                let group_name: String = sqlx::query_scalar(&format!("SELECT name FROM {s}.groups WHERE id = $1"))
+                    .bind(group_id)
+                    .fetch_optional(pool)
+                    .await
+                    .unwrap_or_default()
+                    .unwrap_or_else(|| "Groupe".to_string());
+                (recipients, group_name)
+            }
+            Cooldown::Individual(_) => {
+                let recipients = match msg.recipient_id {
+                    // Admin → parent
+                    Some(recipient_id) => sqlx::query_as(&format!(
+                        "SELECT id, email, CONCAT(first_name, ' ', last_name)
+                         FROM {s}.users WHERE id = $1"
+                    ))
+                    .bind(recipient_id)
+                    .fetch_all(pool)
+                    .await
+                    .unwrap_or_default(),
+                    // Parent → admin (recipient_id IS NULL)
+                    None => sqlx::query_as(&format!(
+                        "SELECT id, email, CONCAT(first_name, ' ', last_name)
+                         FROM {s}.users
+                         WHERE role::text = 'admin_garderie' AND is_active = TRUE"
+                    ))
+                    .fetch_all(pool)
+                    .await
+                    .unwrap_or_default(),
+                };
+                (recipients, "Message privé".to_string())
+            }
+            Cooldown::MediaUpload(_) | Cooldown::JournalLive(_) => return,
+        };
+
+        let mut redis = self.redis.clone();
+        let claimed = cooldown.claim(&mut redis, tenant).await;
+        let mut outcomes = Vec::with_capacity(recipients.len());
+        if claimed {
+            let (garderie_name, branding) = BrandingService::for_email(pool, tenant).await;
+            let url = app_url(&self.config.app_base_url, tenant, "/fr/dashboard/messages");
+            let sender_name = format!("{} {}", msg.sender_first_name, msg.sender_last_name);
+            for (user_id, email, name) in recipients {
+                let outcome = if muted.contains(&user_id) {
+                    Outcome::Skipped(message_deliveries::MUTED.into())
+                } else {
+                    email_svc
+                        .send_message_notification(tenant, &email, &name, &sender_name, &thread_name, &url, &garderie_name, &branding)
+                        .await
+                        .map_or_else(|e| Outcome::Error(e.to_string()), |_| Outcome::Sent)
+                };
+                outcomes.push((user_id, outcome));
+            }
+        } else {
+            // Notification déjà envoyée récemment pour ce fil
+            outcomes.extend(recipients.into_iter().map(|(user_id, ..)| {
+                let reason = if muted.contains(&user_id) { message_deliveries::MUTED } else { message_deliveries::COOLDOWN };
+                (user_id, Outcome::Skipped(reason.into()))
+            }));
+        }
+        self.record_deliveries(tenant, msg.id, "email", &outcomes).await;
+    }
+
+    /// Email the weekly journals to the children's parents.
+    async fn journal_sent(&self, tenant: &str, child_ids: &[Uuid], week_start: NaiveDate) {
+        let Some(email_svc) = self.email.as_ref() else {
+            return;
+        };
+        for child_id in child_ids {
+            if let Err(e) = JournalService::email_week(&self.pool, email_svc, &self.config, tenant, *child_id, week_start).await {
+                tracing::warn!("Weekly journal of {child_id} in '{tenant}' not emailed: {e}");
+            }
+        }
+    }
+
+    /// Active parents of a child.
+    async fn child_parents(&self, tenant: &str, child_id: Uuid) -> Vec<Uuid> {
+        let s = schema_name(tenant);
+        sqlx::query_scalar(&format!(
+            "SELECT u.id FROM {s}.users u
+             JOIN {s}.child_parents cp ON cp.user_id = u.id
+             WHERE cp.child_id = $1 AND u.is_active = TRUE"
+        ))
+        .bind(child_id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap_or_default()
+    }
+
+    /// Refresh the parents' journal right away. Pushes are batched: the first update
+    /// opens a 10-minute window and one push at its end counts every update made in
+    /// it, so a morning of naps and diaper changes doesn't buzz the phone each time.
+    async fn journal_updated(&self, tenant: &str, child_id: Uuid, date: NaiveDate, event_type: Option<String>) {
+        let parents = self.child_parents(tenant, child_id).await;
+        if parents.is_empty() {
+            return;
+        }
+        let mut redis = self.redis.clone();
+        let payload = json!({
+            "type": "journal_updated",
+            "payload": { "child_id": child_id, "date": date, "event_type": event_type },
+        });
+        RealtimeService::publish_to_users(&mut redis, tenant, &parents, &payload.to_string()).await;
+
+        if self.notifications.fcm_api_key.is_none() || tenant == "demo" {
+            return;
+        }
+        let window = Cooldown::JournalLive(child_id);
+        let key = journal_pending_key(tenant, child_id);
+        // Outlives the window, so updates counted as it closes go with the next push.
+        let counted: redis::RedisResult<()> = redis::pipe()
+            .incr(&key, 1)
+            .ignore()
+            .expire(&key, 2 * window.seconds() as i64)
+            .ignore()
+            .query_async(&mut redis)
+            .await;
+        if counted.is_err() || !window.claim(&mut redis, tenant).await {
+            return;
+        }
+        // A push pending when the instance stops is lost; its updates are counted
+        // in the next window.
+        let consumer = self.clone();
+        let tenant = tenant.to_string();
+        tokio::spawn(TenantDb::scope(tenant.clone(), async move {
+            tokio::time::sleep(Duration::from_secs(window.seconds())).await;
+            consumer.push_journal_updates(&tenant, child_id, date).await;
+        }));
+    }
+
+    async fn push_journal_updates(&self, tenant: &str, child_id: Uuid, date: NaiveDate) {
+        let mut redis = self.redis.clone();
+        let updates: Option<i64> = redis::cmd("GETDEL")
+            .arg(journal_pending_key(tenant, child_id))
+            .query_async(&mut redis)
+            .await
+            .unwrap_or(None);
+        let Some(updates) = updates.filter(|n| *n > 0) else {
+            return;
+        };
+        let s = schema_name(tenant);
+        let first_name: Option<String> = sqlx::query_scalar(&format!("SELECT first_name FROM {s}.children WHERE id = $1"))
+            .bind(child_id)
+            .fetch_optional(&self.pool)
+            .await
+            .unwrap_or_default();
+        let Some(first_name) = first_name else {
+            return;
+        };
+        let title = format!("Journal de {first_name}");
+        let body = journal_push_body(&first_name, updates);
+        // FCM data values must be strings.
+        let data = json!({ "type": "journal", "child_id": child_id.to_string(), "date": date.to_string() });
+        for parent_id in self.child_parents(tenant, child_id).await {
+            if let Err(e) = self
+                .notifications
+                .notify_user(&self.pool, tenant, parent_id, &title, &body, Some(data.clone()), None)
+                .await
+            {
+                tracing::warn!("Journal push to {parent_id} in '{tenant}' failed: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(message_type: &str, group_id: Option<Uuid>, recipient_id: Option<Uuid>) -> MessageWithSender {
+        serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "sender_id": Uuid::nil(),
+            "message_type": message_type,
+            "group_id": group_id,
+            "recipient_id": recipient_id,
+            "content": "Bonjour",
+            "is_read": false,
+            "is_deleted": false,
+            "created_at": "2026-10-18T08:00:00Z",
+            "sender_first_name": "Julie",
+            "sender_last_name": "Tremblay",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn messages_share_a_cooldown_per_thread() {
+        let (group, parent) = (Uuid::new_v4(), Uuid::new_v4());
+        assert_eq!(Cooldown::for_message(&message("broadcast", None, None)), Some(Cooldown::Broadcast));
+        assert_eq!(Cooldown::for_message(&message("group", Some(group), None)), Some(Cooldown::Group(group)));
+        assert_eq!(Cooldown::for_message(&message("group", None, None)), None);
+        // Staff writing to a parent and the parent answering are the same thread.
+        assert_eq!(
+            Cooldown::for_message(&message("individual", None, Some(parent))),
+            Some(Cooldown::Individual(parent))
+        );
+        assert_eq!(Cooldown::for_message(&message("individual", None, None)), Some(Cooldown::Individual(Uuid::nil())));
+    }
+
+    #[test]
+    fn cooldown_keys_and_windows() {
+        let parent = Uuid::nil();
+        assert_eq!(Cooldown::Broadcast.key("lucioles"), "notif_cooldown:lucioles:broadcast");
+        assert_eq!(
+            Cooldown::MediaUpload(parent).key("lucioles"),
+            format!("notif_cooldown:lucioles:media_upload:{parent}")
+        );
+        assert_eq!(Cooldown::Group(parent).seconds(), 900);
+        assert_eq!(Cooldown::MediaUpload(parent).seconds(), 3600);
+        assert_eq!(Cooldown::JournalLive(parent).seconds(), 600);
+    }
+
+    #[test]
+    fn journal_pushes_count_the_updates_of_the_window() {
+        assert_eq!(journal_push_body("Léa", 1), "La journée de Léa vient d'être mise à jour");
+        assert_eq!(journal_push_body("Léa", 4), "4 nouvelles mises à jour de la journée de Léa");
+    }
+}
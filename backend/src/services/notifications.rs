@@ -5,6 +5,32 @@ use uuid::Uuid;
 
 use crate::db::tenant::schema_name;
 
+/// Devices kept per user; registering another drops the least recently used.
+pub const MAX_TOKENS_PER_USER: i64 = 10;
+/// Days without registration or delivery after which a token is pruned.
+pub const STALE_AFTER_DAYS: i32 = 60;
+
+/// Condition keeping the parents (rows of `users` under `alias`) who still have a
+/// child at the garderie: once their last child left, parents only keep access to
+/// the final export and are no longer notified.
+pub fn has_active_child_sql(schema: &str, alias: &str) -> String {
+    format!(
+        "EXISTS (SELECT 1 FROM {schema}.child_parents acp
+                 JOIN {schema}.children ac ON ac.id = acp.child_id
+                 WHERE acp.user_id = {alias}.id AND ac.is_active = TRUE)"
+    )
+}
+
+/// What FCM made of a push to one token.
+#[derive(Debug, PartialEq)]
+pub enum PushOutcome {
+    Delivered,
+    /// The token will never work again and is deleted.
+    InvalidToken,
+    /// Refused or not sent; the token is kept.
+    Failed,
+}
+
 pub struct NotificationService {
     pub client: Client,
     pub fcm_api_key: Option<String>,
@@ -19,6 +45,8 @@ impl NotificationService {
     }
 
     /// Send a push notification to a specific user's registered devices.
+    /// `badge` sets the app icon count on iOS (the user's unread messages).
+    #[allow(clippy::too_many_arguments)]
     pub async fn notify_user(
         &self,
         pool: &PgPool,
@@ -27,6 +55,7 @@ impl NotificationService {
         title: &str,
         body: &str,
         data: Option<serde_json::Value>,
+        badge: Option<i64>,
     ) -> anyhow::Result<()> {
         let schema = schema_name(tenant);
         let tokens: Vec<(String, String)> = sqlx::query_as(&format!(
@@ -37,16 +66,13 @@ impl NotificationService {
         .await?;
 
         for (platform, token) in tokens {
-            match platform.as_str() {
-                "android" => {
-                    self.send_fcm(&token, title, body, data.clone()).await?;
-                }
-                "ios" => {
-                    // APNS — send via FCM for simplicity, or implement direct APNS
-                    self.send_fcm(&token, title, body, data.clone()).await?;
-                }
-                _ => {}
-            }
+            let outcome = match platform.as_str() {
+                "android" => self.send_fcm(&token, title, body, data.clone(), None).await?,
+                // APNS — send via FCM for simplicity, or implement direct APNS
+                "ios" => self.send_fcm(&token, title, body, data.clone(), badge).await?,
+                _ => continue,
+            };
+            Self::record_outcome(pool, &schema, &token, outcome).await?;
         }
         Ok(())
     }
@@ -65,29 +91,47 @@ impl NotificationService {
             "SELECT pt.platform, pt.token
              FROM {schema}.push_tokens pt
              JOIN {schema}.users u ON u.id = pt.user_id
-             WHERE u.role = 'parent' AND u.is_active = TRUE"
+             WHERE u.role = 'parent' AND u.is_active = TRUE AND {}",
+            has_active_child_sql(&schema, "u")
         ))
         .fetch_all(pool)
         .await?;
 
         for (_, token) in tokens {
-            let _ = self.send_fcm(&token, title, body, data.clone()).await;
+            if let Ok(outcome) = self.send_fcm(&token, title, body, data.clone(), None).await {
+                Self::record_outcome(pool, &schema, &token, outcome).await?;
+            }
         }
         Ok(())
     }
 
+    /// Keep the token's last use current, or drop it when FCM no longer knows it.
+    async fn record_outcome(pool: &PgPool, schema: &str, token: &str, outcome: PushOutcome) -> anyhow::Result<()> {
+        let sql = match outcome {
+            PushOutcome::Delivered => format!("UPDATE {schema}.push_tokens SET last_used_at = NOW() WHERE token = $1"),
+            PushOutcome::InvalidToken => {
+                tracing::info!("Push token no longer valid, removed");
+                format!("DELETE FROM {schema}.push_tokens WHERE token = $1")
+            }
+            PushOutcome::Failed => return Ok(()),
+        };
+        sqlx::query(&sql).bind(token).execute(pool).await?;
+        Ok(())
+    }
+
     async fn send_fcm(
         &self,
         token: &str,
         title: &str,
         body: &str,
         data: Option<serde_json::Value>,
-    ) -> anyhow::Result<()> {
+        badge: Option<i64>,
+    ) -> anyhow::Result<PushOutcome> {
         let api_key = match &self.fcm_api_key {
             Some(k) => k,
             None => {
                 tracing::debug!("FCM not configured, skipping push notification");
-                return Ok(());
+                return Ok(PushOutcome::Failed);
             }
         };
 
@@ -99,6 +143,11 @@ impl NotificationService {
             }
         });
 
+        if let Some(count) = badge {
+            // FCM relays it to APNs as aps.badge; it expects a string.
+            payload["notification"]["badge"] = json!(count.to_string());
+        }
+
         if let Some(d) = data {
             payload["data"] = d;
         }
@@ -112,15 +161,23 @@ impl NotificationService {
             .send()
             .await?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        if !status.is_success() {
             tracing::warn!("FCM error {}: {}", status, text);
+            return Ok(PushOutcome::Failed);
         }
-
-        Ok(())
+        let reply: serde_json::Value = serde_json::from_str(&text).unwrap_or_default();
+        let outcome = fcm_outcome(&reply);
+        if outcome == PushOutcome::Failed {
+            tracing::warn!("FCM refused a push: {}", text);
+        }
+        Ok(outcome)
     }
 
+    /// Register (or refresh) a device token for the user. A token identifies a
+    /// device, so another account that signed in on it before loses it; the user
+    /// keeps their [`MAX_TOKENS_PER_USER`] most recently used devices.
     pub async fn register_push_token(
         pool: &PgPool,
         tenant: &str,
@@ -129,16 +186,88 @@ impl NotificationService {
         token: &str,
     ) -> anyhow::Result<()> {
         let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+        sqlx::query(&format!("DELETE FROM {schema}.push_tokens WHERE token = $1 AND user_id <> $2"))
+            .bind(token)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
         sqlx::query(&format!(
             "INSERT INTO {schema}.push_tokens (user_id, platform, token)
              VALUES ($1, $2, $3)
-             ON CONFLICT (user_id, token) DO NOTHING"
+             ON CONFLICT (user_id, token) DO UPDATE SET platform = EXCLUDED.platform, last_used_at = NOW()"
         ))
         .bind(user_id)
         .bind(platform)
         .bind(token)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
+        sqlx::query(&format!(
+            "DELETE FROM {schema}.push_tokens
+             WHERE user_id = $1 AND id NOT IN (
+                 SELECT id FROM {schema}.push_tokens WHERE user_id = $1
+                 ORDER BY last_used_at DESC, created_at DESC
+                 LIMIT $2)"
+        ))
+        .bind(user_id)
+        .bind(MAX_TOKENS_PER_USER)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
         Ok(())
     }
+
+    /// Forget a device token of the user (sign-out). Returns whether it was registered.
+    pub async fn unregister_push_token(pool: &PgPool, tenant: &str, user_id: Uuid, token: &str) -> anyhow::Result<bool> {
+        let schema = schema_name(tenant);
+        let deleted = sqlx::query(&format!("DELETE FROM {schema}.push_tokens WHERE user_id = $1 AND token = $2"))
+            .bind(user_id)
+            .bind(token)
+            .execute(pool)
+            .await?
+            .rows_affected();
+        Ok(deleted > 0)
+    }
+
+    /// Delete the tokens neither registered again nor pushed to for
+    /// [`STALE_AFTER_DAYS`] days: the app was uninstalled or the device replaced.
+    pub async fn prune_stale_tokens(pool: &PgPool, tenant: &str) -> anyhow::Result<u64> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query(&format!(
+            "DELETE FROM {schema}.push_tokens WHERE last_used_at < NOW() - make_interval(days => $1)"
+        ))
+        .bind(STALE_AFTER_DAYS)
+        .execute(pool)
+        .await?
+        .rows_affected())
+    }
+}
+
+/// Reading of an FCM reply to a single-token send.
+fn fcm_outcome(reply: &serde_json::Value) -> PushOutcome {
+    let error = reply["results"][0]["error"].as_str();
+    match error {
+        None if reply["success"].as_i64() == Some(1) => PushOutcome::Delivered,
+        // The app was uninstalled, or the token is not one FCM issued.
+        Some("NotRegistered" | "InvalidRegistration" | "MissingRegistration") => PushOutcome::InvalidToken,
+        _ => PushOutcome::Failed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_fcm_replies() {
+        assert_eq!(fcm_outcome(&json!({ "success": 1, "failure": 0, "results": [{ "message_id": "0:1" }] })), PushOutcome::Delivered);
+        assert_eq!(fcm_outcome(&json!({ "success": 0, "failure": 1, "results": [{ "error": "NotRegistered" }] })), PushOutcome::InvalidToken);
+        assert_eq!(
+            fcm_outcome(&json!({ "success": 0, "failure": 1, "results": [{ "error": "InvalidRegistration" }] })),
+            PushOutcome::InvalidToken
+        );
+        // Transient: the token is kept.
+        assert_eq!(fcm_outcome(&json!({ "success": 0, "failure": 1, "results": [{ "error": "Unavailable" }] })), PushOutcome::Failed);
+        assert_eq!(fcm_outcome(&json!(null)), PushOutcome::Failed);
+    }
 }
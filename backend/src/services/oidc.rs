@@ -0,0 +1,652 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use rand::Rng;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::tenant::schema_name,
+    models::{
+        oidc::{OidcSettings, UpdateOidcSettingsRequest, JIT_ROLES, OIDC_PROVIDERS},
+        user::User,
+    },
+    services::encryption::{self, KeyRing},
+};
+
+const GOOGLE_ISSUER: &str = "https://accounts.google.com";
+
+/// Seconds the user has to come back from the identity provider.
+const STATE_TTL_SECS: u64 = 600;
+
+/// Algorithms accepted on ID tokens; the shared-secret ones could be forged by anyone
+/// holding the client secret.
+const ID_TOKEN_ALGORITHMS: &[Algorithm] = &[
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum OidcError {
+    #[error("La connexion unique n'est pas activée pour cette garderie")]
+    NotConfigured,
+    #[error("{0}")]
+    InvalidSettings(String),
+    #[error("Session de connexion expirée, veuillez réessayer")]
+    InvalidState,
+    #[error("Le fournisseur d'identité a refusé la connexion : {0}")]
+    Provider(String),
+    #[error("Le fournisseur d'identité n'a transmis aucune adresse courriel")]
+    NoEmail,
+    #[error("Adresse courriel non vérifiée par le fournisseur d'identité")]
+    EmailNotVerified,
+    #[error("Ce domaine de courriel n'est pas autorisé pour cette garderie")]
+    DomainNotAllowed,
+    #[error("Aucun compte du personnel actif pour {0}")]
+    UnknownUser(String),
+    #[error("La connexion unique est réservée au personnel")]
+    ParentAccount,
+}
+
+/// Settings as validated for storage.
+#[derive(Debug, PartialEq)]
+pub struct ValidSettings {
+    pub provider: String,
+    pub issuer_url: String,
+    pub client_id: String,
+    pub allowed_domains: Vec<String>,
+    pub jit_role: String,
+}
+
+/// Checks an admin's SSO configuration. `has_secret` tells whether a client secret is
+/// already stored or sent with the request.
+pub fn validate_settings(req: &UpdateOidcSettingsRequest, has_secret: bool) -> Result<ValidSettings, OidcError> {
+    let invalid = |msg: &str| OidcError::InvalidSettings(msg.to_string());
+
+    let provider = req.provider.trim().to_lowercase();
+    if !OIDC_PROVIDERS.contains(&provider.as_str()) {
+        return Err(invalid("Fournisseur inconnu (google, microsoft ou custom)"));
+    }
+    let issuer_url = match req.issuer_url.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        Some(url) => url.trim_end_matches('/').to_string(),
+        None if provider == "google" => GOOGLE_ISSUER.to_string(),
+        None => return Err(invalid("L'URL de l'émetteur est requise pour ce fournisseur")),
+    };
+    if !issuer_url.starts_with("https://") {
+        return Err(invalid("L'URL de l'émetteur doit commencer par https://"));
+    }
+    let client_id = req.client_id.trim().to_string();
+    if client_id.is_empty() {
+        return Err(invalid("L'identifiant client est requis"));
+    }
+
+    let mut allowed_domains = Vec::new();
+    for domain in &req.allowed_domains {
+        let domain = domain.trim().trim_start_matches('@').to_lowercase();
+        if domain.is_empty() {
+            continue;
+        }
+        if !domain.contains('.') || domain.contains(|c: char| c.is_whitespace() || c == '@') {
+            return Err(OidcError::InvalidSettings(format!("Domaine invalide : {domain}")));
+        }
+        if !allowed_domains.contains(&domain) {
+            allowed_domains.push(domain);
+        }
+    }
+
+    let jit_role = req.jit_role.as_deref().unwrap_or("educateur").to_string();
+    if !JIT_ROLES.contains(&jit_role.as_str()) {
+        return Err(invalid("Rôle de création automatique invalide (educateur ou admin_garderie)"));
+    }
+    if req.jit_provisioning && allowed_domains.is_empty() {
+        return Err(invalid("La création automatique des comptes exige au moins un domaine autorisé"));
+    }
+    if req.enabled && !has_secret {
+        return Err(invalid("Le secret client est requis pour activer la connexion unique"));
+    }
+
+    Ok(ValidSettings { provider, issuer_url, client_id, allowed_domains, jit_role })
+}
+
+fn email_domain(email: &str) -> Option<&str> {
+    email.rsplit_once('@').map(|(_, domain)| domain).filter(|d| !d.is_empty())
+}
+
+/// Whether a garderie accepts `email` from its identity provider: the provider must
+/// vouch for the address, and its domain must be allowed when the garderie lists some.
+pub fn check_email(email: &str, verified: bool, allowed_domains: &[String]) -> Result<(), OidcError> {
+    let domain = email_domain(email).ok_or(OidcError::NoEmail)?;
+    if !verified {
+        return Err(OidcError::EmailNotVerified);
+    }
+    if allowed_domains.is_empty() || allowed_domains.iter().any(|d| d == domain) {
+        Ok(())
+    } else {
+        Err(OidcError::DomainNotAllowed)
+    }
+}
+
+/// PKCE S256 challenge of a code verifier (RFC 7636).
+pub fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+fn random_token() -> String {
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Web app page of the garderie the identity provider sends the user back to.
+pub fn redirect_uri(base_url: &str, tenant: &str, locale: &str) -> String {
+    let locale = if locale == "en" { "en" } else { "fr" };
+    if let Some(idx) = base_url.find("://") {
+        let scheme = &base_url[..idx];
+        let domain = &base_url[idx + 3..];
+        format!("{scheme}://{tenant}.{domain}/{locale}/oidc/callback")
+    } else {
+        format!("https://{tenant}.{base_url}/{locale}/oidc/callback")
+    }
+}
+
+fn state_key(state: &str) -> String {
+    format!("oidc:state:{state}")
+}
+
+/// What the callback needs from the login that started it.
+#[derive(Serialize, Deserialize)]
+struct PendingLogin {
+    tenant: String,
+    nonce: String,
+    verifier: String,
+    redirect_uri: String,
+}
+
+#[derive(Deserialize)]
+struct Discovery {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct IdClaims {
+    nonce: Option<String>,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: Option<serde_json::Value>,
+    /// The UPN on Microsoft Entra, always in one of the directory's verified domains.
+    preferred_username: Option<String>,
+    given_name: Option<String>,
+    family_name: Option<String>,
+}
+
+impl IdClaims {
+    /// The address to match and whether the provider vouches for it. Entra lets users
+    /// edit their `email` claim, so the UPN is used there instead; the issuer check pins
+    /// the directory it comes from.
+    fn email(&self, provider: &str) -> Option<(String, bool)> {
+        if provider == "microsoft" {
+            let upn = self.preferred_username.as_deref().filter(|u| u.contains('@'))?;
+            return Some((upn.trim().to_lowercase(), true));
+        }
+        // Some providers send the flag as a string.
+        let verified = match &self.email_verified {
+            Some(serde_json::Value::Bool(b)) => *b,
+            Some(serde_json::Value::String(s)) => s == "true",
+            _ => false,
+        };
+        Some((self.email.as_deref()?.trim().to_lowercase(), verified))
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct StoredSettings {
+    provider: String,
+    issuer_url: String,
+    client_id: String,
+    client_secret: Option<Vec<u8>>,
+    client_secret_iv: Option<Vec<u8>>,
+    client_secret_tag: Option<Vec<u8>>,
+    secret_key_version: i32,
+    enabled: bool,
+    allowed_domains: Vec<String>,
+    jit_provisioning: bool,
+    jit_role: String,
+}
+
+pub struct OidcService;
+
+impl OidcService {
+    fn http() -> Client {
+        Client::builder().timeout(Duration::from_secs(10)).build().unwrap_or_default()
+    }
+
+    pub async fn get_settings(pool: &PgPool, tenant: &str) -> anyhow::Result<Option<OidcSettings>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, OidcSettings>(&format!(
+            r#"SELECT provider, issuer_url, client_id, client_secret IS NOT NULL AS has_client_secret,
+                      enabled, allowed_domains, jit_provisioning, jit_role, updated_at
+               FROM "{schema}".oidc_settings"#
+        ))
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    /// Whether staff can sign in with the garderie's identity provider.
+    pub async fn is_enabled(pool: &PgPool, tenant: &str) -> bool {
+        let schema = schema_name(tenant);
+        sqlx::query_scalar::<_, bool>(&format!(r#"SELECT enabled FROM "{schema}".oidc_settings"#))
+            .fetch_optional(pool)
+            .await
+            .ok()
+            .flatten()
+            .unwrap_or(false)
+    }
+
+    pub async fn update_settings(
+        pool: &PgPool,
+        config: &Config,
+        tenant: &str,
+        admin_id: Uuid,
+        req: &UpdateOidcSettingsRequest,
+    ) -> anyhow::Result<OidcSettings> {
+        let new_secret = req.client_secret.as_deref().map(str::trim).filter(|s| !s.is_empty());
+        let stored_secret = Self::get_settings(pool, tenant).await?.is_some_and(|s| s.has_client_secret);
+        let valid = validate_settings(req, new_secret.is_some() || stored_secret)?;
+
+        let encrypted = match new_secret {
+            Some(secret) => {
+                let keys = KeyRing::from_config(config)?;
+                let (version, key) = keys.current_tenant_key(tenant)?;
+                let (ct, iv, tag) = encryption::encrypt_file(secret.as_bytes(), &key)?;
+                Some((ct, iv, tag, version))
+            }
+            None => None,
+        };
+        let (ct, iv, tag, version) = match encrypted {
+            Some((ct, iv, tag, version)) => (Some(ct), Some(iv), Some(tag), Some(version)),
+            None => (None, None, None, None),
+        };
+
+        let schema = schema_name(tenant);
+        sqlx::query(&format!(
+            r#"INSERT INTO "{schema}".oidc_settings
+                   (provider, issuer_url, client_id, client_secret, client_secret_iv, client_secret_tag,
+                    secret_key_version, enabled, allowed_domains, jit_provisioning, jit_role, updated_by)
+               VALUES ($1, $2, $3, $4, $5, $6, COALESCE($7, 1), $8, $9, $10, $11, $12)
+               ON CONFLICT ON CONSTRAINT oidc_settings_pkey DO UPDATE SET
+                   provider = EXCLUDED.provider,
+                   issuer_url = EXCLUDED.issuer_url,
+                   client_id = EXCLUDED.client_id,
+                   client_secret = COALESCE($4, oidc_settings.client_secret),
+                   client_secret_iv = COALESCE($5, oidc_settings.client_secret_iv),
+                   client_secret_tag = COALESCE($6, oidc_settings.client_secret_tag),
+                   secret_key_version = COALESCE($7, oidc_settings.secret_key_version),
+                   enabled = EXCLUDED.enabled,
+                   allowed_domains = EXCLUDED.allowed_domains,
+                   jit_provisioning = EXCLUDED.jit_provisioning,
+                   jit_role = EXCLUDED.jit_role,
+                   updated_by = EXCLUDED.updated_by,
+                   updated_at = NOW()"#
+        ))
+        .bind(&valid.provider)
+        .bind(&valid.issuer_url)
+        .bind(&valid.client_id)
+        .bind(ct)
+        .bind(iv)
+        .bind(tag)
+        .bind(version)
+        .bind(req.enabled)
+        .bind(&valid.allowed_domains)
+        .bind(req.jit_provisioning)
+        .bind(&valid.jit_role)
+        .bind(admin_id)
+        .execute(pool)
+        .await?;
+
+        Self::get_settings(pool, tenant)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Configuration introuvable après enregistrement"))
+    }
+
+    pub async fn delete_settings(pool: &PgPool, tenant: &str) -> anyhow::Result<bool> {
+        let schema = schema_name(tenant);
+        let result = sqlx::query(&format!(r#"DELETE FROM "{schema}".oidc_settings"#))
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn enabled_settings(pool: &PgPool, tenant: &str) -> anyhow::Result<StoredSettings> {
+        let schema = schema_name(tenant);
+        sqlx::query_as::<_, StoredSettings>(&format!(
+            r#"SELECT provider, issuer_url, client_id, client_secret, client_secret_iv, client_secret_tag,
+                      secret_key_version, enabled, allowed_domains, jit_provisioning, jit_role
+               FROM "{schema}".oidc_settings"#
+        ))
+        .fetch_optional(pool)
+        .await?
+        .filter(|s| s.enabled)
+        .ok_or_else(|| OidcError::NotConfigured.into())
+    }
+
+    async fn discover(client: &Client, issuer_url: &str) -> anyhow::Result<Discovery> {
+        let discovery: Discovery = client
+            .get(format!("{issuer_url}/.well-known/openid-configuration"))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| OidcError::Provider(format!("découverte impossible ({e})")))?
+            .json()
+            .await
+            .map_err(|e| OidcError::Provider(format!("découverte invalide ({e})")))?;
+        Ok(discovery)
+    }
+
+    /// Starts a login: remembers the state for the callback and returns the
+    /// provider's authorization URL.
+    pub async fn start(
+        pool: &PgPool,
+        redis: &mut redis::aio::MultiplexedConnection,
+        config: &Config,
+        tenant: &str,
+        locale: &str,
+    ) -> anyhow::Result<String> {
+        let settings = Self::enabled_settings(pool, tenant).await?;
+        let discovery = Self::discover(&Self::http(), &settings.issuer_url).await?;
+
+        let state = random_token();
+        let pending = PendingLogin {
+            tenant: tenant.to_string(),
+            nonce: random_token(),
+            verifier: random_token(),
+            redirect_uri: redirect_uri(&config.app_base_url, tenant, locale),
+        };
+        let _: () = redis::cmd("SET")
+            .arg(state_key(&state))
+            .arg(serde_json::to_string(&pending)?)
+            .arg("EX")
+            .arg(STATE_TTL_SECS)
+            .query_async(redis)
+            .await
+            .context("Failed to store the OIDC state")?;
+
+        let mut url = reqwest::Url::parse(&discovery.authorization_endpoint)
+            .map_err(|e| OidcError::Provider(format!("URL d'autorisation invalide ({e})")))?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &settings.client_id)
+            .append_pair("redirect_uri", &pending.redirect_uri)
+            .append_pair("scope", "openid email profile")
+            .append_pair("state", &state)
+            .append_pair("nonce", &pending.nonce)
+            .append_pair("code_challenge", &pkce_challenge(&pending.verifier))
+            .append_pair("code_challenge_method", "S256");
+        Ok(url.into())
+    }
+
+    /// Completes a login from the provider's callback and returns the staff user it
+    /// maps to, creating it when just-in-time provisioning allows.
+    pub async fn finish(
+        pool: &PgPool,
+        redis: &mut redis::aio::MultiplexedConnection,
+        config: &Config,
+        tenant: &str,
+        code: &str,
+        state: &str,
+    ) -> anyhow::Result<User> {
+        let pending: Option<String> = redis::cmd("GETDEL")
+            .arg(state_key(state))
+            .query_async(redis)
+            .await
+            .context("Failed to read the OIDC state")?;
+        let pending: PendingLogin = pending
+            .and_then(|p| serde_json::from_str(&p).ok())
+            .filter(|p: &PendingLogin| p.tenant == tenant)
+            .ok_or(OidcError::InvalidState)?;
+
+        let settings = Self::enabled_settings(pool, tenant).await?;
+        let (Some(ct), Some(iv), Some(tag)) =
+            (&settings.client_secret, &settings.client_secret_iv, &settings.client_secret_tag)
+        else {
+            return Err(OidcError::NotConfigured.into());
+        };
+        let key = KeyRing::from_config(config)?.tenant_key(settings.secret_key_version, tenant)?;
+        let client_secret = String::from_utf8(encryption::decrypt_file(ct, iv, tag, &key)?)?;
+
+        let client = Self::http();
+        let discovery = Self::discover(&client, &settings.issuer_url).await?;
+        let tokens: TokenResponse = client
+            .post(&discovery.token_endpoint)
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", &pending.redirect_uri),
+                ("client_id", &settings.client_id),
+                ("client_secret", &client_secret),
+                ("code_verifier", &pending.verifier),
+            ])
+            .send()
+            .await
+            .map_err(|e| OidcError::Provider(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| OidcError::Provider(format!("réponse invalide ({e})")))?;
+        let id_token = match (tokens.id_token, tokens.error) {
+            (Some(token), None) => token,
+            (_, error) => {
+                let reason = tokens.error_description.or(error).unwrap_or_else(|| "aucun jeton".into());
+                return Err(OidcError::Provider(reason).into());
+            }
+        };
+
+        let claims = Self::verify_id_token(&client, &discovery, &settings.client_id, &id_token).await?;
+        if claims.nonce.as_deref() != Some(pending.nonce.as_str()) {
+            return Err(OidcError::InvalidState.into());
+        }
+        let (email, verified) = claims.email(&settings.provider).ok_or(OidcError::NoEmail)?;
+        check_email(&email, verified, &settings.allowed_domains)?;
+
+        Self::resolve_user(pool, tenant, &settings, &email, &claims).await
+    }
+
+    async fn verify_id_token(
+        client: &Client,
+        discovery: &Discovery,
+        client_id: &str,
+        id_token: &str,
+    ) -> anyhow::Result<IdClaims> {
+        let header = decode_header(id_token).map_err(|e| OidcError::Provider(format!("jeton illisible ({e})")))?;
+        if !ID_TOKEN_ALGORITHMS.contains(&header.alg) {
+            return Err(OidcError::Provider(format!("algorithme {:?} refusé", header.alg)).into());
+        }
+        let jwks: JwkSet = client
+            .get(&discovery.jwks_uri)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| OidcError::Provider(format!("clés introuvables ({e})")))?
+            .json()
+            .await
+            .map_err(|e| OidcError::Provider(format!("clés invalides ({e})")))?;
+        let jwk = match header.kid.as_deref() {
+            Some(kid) => jwks.find(kid),
+            None => jwks.keys.first(),
+        }
+        .ok_or_else(|| OidcError::Provider("clé de signature inconnue".into()))?;
+        let key = DecodingKey::from_jwk(jwk).map_err(|e| OidcError::Provider(format!("clé invalide ({e})")))?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_audience(&[client_id]);
+        validation.set_issuer(&[&discovery.issuer]);
+        let data = decode::<IdClaims>(id_token, &key, &validation)
+            .map_err(|e| OidcError::Provider(format!("jeton refusé ({e})")))?;
+        Ok(data.claims)
+    }
+
+    async fn resolve_user(
+        pool: &PgPool,
+        tenant: &str,
+        settings: &StoredSettings,
+        email: &str,
+        claims: &IdClaims,
+    ) -> anyhow::Result<User> {
+        let schema = schema_name(tenant);
+        let existing = sqlx::query_as::<_, User>(&format!(
+            r#"SELECT id, email, password_hash, first_name, last_name,
+                   role::TEXT as role, avatar_url, is_active, force_password_change, preferred_locale, phone,
+                   created_at, updated_at
+               FROM "{schema}".users WHERE LOWER(email) = $1 AND is_active = TRUE"#
+        ))
+        .bind(email)
+        .fetch_optional(pool)
+        .await?;
+        if let Some(user) = existing {
+            if user.role == "parent" {
+                return Err(OidcError::ParentAccount.into());
+            }
+            return Ok(user);
+        }
+
+        if !settings.jit_provisioning {
+            return Err(OidcError::UnknownUser(email.to_string()).into());
+        }
+        // A deactivated account keeps its email: it is not recreated behind the admin's back.
+        let deactivated: bool = sqlx::query_scalar(&format!(
+            r#"SELECT EXISTS(SELECT 1 FROM "{schema}".users WHERE LOWER(email) = $1)"#
+        ))
+        .bind(email)
+        .fetch_one(pool)
+        .await?;
+        if deactivated {
+            return Err(OidcError::UnknownUser(email.to_string()).into());
+        }
+
+        // The account signs in through the provider only; nobody knows this password.
+        let password_hash = bcrypt::hash(random_token(), 8)?;
+        let local_part = email.split('@').next().unwrap_or(email);
+        let first_name = claims.given_name.clone().unwrap_or_else(|| local_part.to_string());
+        let last_name = claims.family_name.clone().unwrap_or_default();
+        let user = sqlx::query_as::<_, User>(&format!(
+            r#"INSERT INTO "{schema}".users (email, password_hash, first_name, last_name, role)
+               VALUES ($1, $2, $3, $4, $5::"{schema}".user_role)
+               RETURNING id, email, password_hash, first_name, last_name,
+                   role::TEXT as role, avatar_url, is_active, force_password_change, preferred_locale, phone,
+                   created_at, updated_at"#
+        ))
+        .bind(email)
+        .bind(&password_hash)
+        .bind(&first_name)
+        .bind(&last_name)
+        .bind(&settings.jit_role)
+        .fetch_one(pool)
+        .await?;
+        tracing::info!("OIDC: provisioned {} staff account {} in '{tenant}'", settings.jit_role, user.id);
+        Ok(user)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(provider: &str) -> UpdateOidcSettingsRequest {
+        UpdateOidcSettingsRequest {
+            provider: provider.to_string(),
+            issuer_url: None,
+            client_id: "client-123".to_string(),
+            client_secret: None,
+            enabled: false,
+            allowed_domains: Vec::new(),
+            jit_provisioning: false,
+            jit_role: None,
+        }
+    }
+
+    #[test]
+    fn google_defaults_its_issuer() {
+        let valid = validate_settings(&request("Google"), false).unwrap();
+        assert_eq!(valid.provider, "google");
+        assert_eq!(valid.issuer_url, GOOGLE_ISSUER);
+        assert_eq!(valid.jit_role, "educateur");
+    }
+
+    #[test]
+    fn microsoft_and_custom_need_an_https_issuer() {
+        assert!(validate_settings(&request("microsoft"), false).is_err());
+        let mut req = request("custom");
+        req.issuer_url = Some("http://idp.example.com".into());
+        assert!(validate_settings(&req, false).is_err());
+        req.issuer_url = Some("https://idp.example.com/".into());
+        assert_eq!(validate_settings(&req, false).unwrap().issuer_url, "https://idp.example.com");
+    }
+
+    #[test]
+    fn domains_are_normalized_and_required_for_provisioning() {
+        let mut req = request("google");
+        req.jit_provisioning = true;
+        assert!(validate_settings(&req, false).is_err());
+        req.allowed_domains = vec!["@CPE-Soleil.ca".into(), "cpe-soleil.ca".into(), " ".into()];
+        assert_eq!(validate_settings(&req, false).unwrap().allowed_domains, vec!["cpe-soleil.ca"]);
+        req.jit_role = Some("parent".into());
+        assert!(validate_settings(&req, false).is_err());
+    }
+
+    #[test]
+    fn enabling_needs_a_client_secret() {
+        let mut req = request("google");
+        req.enabled = true;
+        assert!(validate_settings(&req, false).is_err());
+        assert!(validate_settings(&req, true).is_ok());
+    }
+
+    #[test]
+    fn emails_are_checked_against_the_allowed_domains() {
+        let domains = vec!["cpe-soleil.ca".to_string()];
+        assert!(check_email("marie@cpe-soleil.ca", true, &domains).is_ok());
+        assert!(matches!(check_email("marie@cpe-soleil.ca", false, &domains), Err(OidcError::EmailNotVerified)));
+        assert!(matches!(check_email("marie@gmail.com", true, &domains), Err(OidcError::DomainNotAllowed)));
+        assert!(check_email("marie@gmail.com", true, &[]).is_ok());
+    }
+
+    #[test]
+    fn pkce_challenge_matches_rfc_7636() {
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn redirect_uri_points_to_the_tenant_app() {
+        assert_eq!(
+            redirect_uri("https://minispace.app", "soleil", "en"),
+            "https://soleil.minispace.app/en/oidc/callback"
+        );
+        assert_eq!(
+            redirect_uri("minispace.app", "soleil", "de"),
+            "https://soleil.minispace.app/fr/oidc/callback"
+        );
+    }
+}
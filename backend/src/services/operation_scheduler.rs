@@ -0,0 +1,30 @@
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::config::Config;
+use crate::services::operations::OperationService;
+
+/// How often pending destructive operations are checked.
+const CHECK_INTERVAL_SECS: u64 = 60;
+
+/// Spawn a background task that executes super-admin operations whose cancel
+/// window has elapsed without being cancelled or confirmed early, and purges
+/// archived garderies past their retention period.
+pub fn start(pool: PgPool, config: Arc<Config>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(CHECK_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let Some(_job) = crate::services::worker::job().await else {
+                break;
+            };
+            if let Err(e) = OperationService::run_due(&pool, &config).await {
+                warn!("Operation scheduler: failed to run due operations: {e}");
+            }
+            if let Err(e) = OperationService::purge_expired_archives(&pool, &config).await {
+                warn!("Operation scheduler: failed to purge expired archives: {e}");
+            }
+        }
+    });
+}
@@ -0,0 +1,475 @@
+use std::path::PathBuf;
+
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::{
+        tenant::{provision_tenant_schema, schema_name},
+        tenant_db::TenantDb,
+    },
+    models::{
+        backup::TenantRestoreRequest,
+        operation::{
+            PendingOperation, RestoreRequest, OP_ARCHIVE_GARDERIE, OP_DELETE_GARDERIE, OP_RESTORE,
+            OP_RESTORE_GARDERIE, OP_TRANSFER_CHILD,
+        },
+        transfer::TransferChildRequest,
+    },
+    services::{
+        backups::{BackupError, BackupService},
+        child_transfers::TransferService,
+        encryption::KeyRing,
+        object_store::ObjectStore,
+    },
+};
+
+/// Host directory holding the nightly database and media archives.
+pub const BACKUP_DIR: &str = "/backup/host/backups";
+
+const OP_COLS: &str = "id, kind, target_slug, payload, status, requested_by, confirmed_by, \
+     cancelled_by, execute_after, error, created_at, executed_at, cancelled_at";
+
+#[derive(Debug, thiserror::Error)]
+pub enum OperationError {
+    #[error("Opération introuvable")]
+    NotFound,
+    #[error("Cette opération n'est plus en attente")]
+    NotPending,
+    #[error("Une opération identique est déjà en attente")]
+    AlreadyPending,
+    #[error("Identifiez-vous (X-Super-Admin-Email et X-Super-Admin-Password) pour confirmer ou annuler")]
+    OperatorRequired,
+    #[error("Identifiez-vous (X-Super-Admin-Email et X-Super-Admin-Password) pour demander cette opération")]
+    RequesterRequired,
+    #[error("L'opération doit être confirmée par un autre super-administrateur")]
+    SameOperator,
+    #[error("Cette opération n'a pas de demandeur identifié : annulez-la ou attendez son échéance")]
+    UnidentifiedRequester,
+}
+
+/// Two-person approval: the operator confirming must be identified, and differ
+/// from the identified super-admin who requested the operation.
+fn check_confirmer<'a>(requested_by: Option<&str>, operator: Option<&'a str>) -> Result<&'a str, OperationError> {
+    let operator = operator.ok_or(OperationError::OperatorRequired)?;
+    match requested_by {
+        None => Err(OperationError::UnidentifiedRequester),
+        Some(r) if r.eq_ignore_ascii_case(operator) => Err(OperationError::SameOperator),
+        Some(_) => Ok(operator),
+    }
+}
+
+pub struct OperationService;
+
+impl OperationService {
+    /// Queue an operation; it runs once confirmed or after `delay_minutes`. The
+    /// requester must be identified, so that someone else can confirm it.
+    pub async fn request(
+        pool: &PgPool,
+        kind: &str,
+        target_slug: Option<&str>,
+        payload: serde_json::Value,
+        requested_by: Option<&str>,
+        delay_minutes: i64,
+    ) -> anyhow::Result<PendingOperation> {
+        let requested_by = requested_by
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+            .ok_or(OperationError::RequesterRequired)?;
+        let execute_after = Utc::now() + Duration::minutes(delay_minutes);
+        sqlx::query_as::<_, PendingOperation>(&format!(
+            "INSERT INTO public.pending_operations (kind, target_slug, payload, requested_by, execute_after)
+             VALUES ($1, $2, $3, $4, $5)
+             RETURNING {OP_COLS}"
+        ))
+        .bind(kind)
+        .bind(target_slug)
+        .bind(payload)
+        .bind(requested_by)
+        .bind(execute_after)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.is_unique_violation() => OperationError::AlreadyPending.into(),
+            e => e.into(),
+        })
+    }
+
+    /// Open operations first, then the most recent history.
+    pub async fn list(pool: &PgPool) -> anyhow::Result<Vec<PendingOperation>> {
+        let ops = sqlx::query_as::<_, PendingOperation>(&format!(
+            "SELECT {OP_COLS} FROM public.pending_operations
+             ORDER BY (status IN ('pending', 'running')) DESC, created_at DESC
+             LIMIT 100"
+        ))
+        .fetch_all(pool)
+        .await?;
+        Ok(ops)
+    }
+
+    pub async fn get(pool: &PgPool, id: Uuid) -> anyhow::Result<Option<PendingOperation>> {
+        let op = sqlx::query_as::<_, PendingOperation>(&format!(
+            "SELECT {OP_COLS} FROM public.pending_operations WHERE id = $1"
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(op)
+    }
+
+    /// Withdraw a pending operation; the operator cancelling must be identified.
+    pub async fn cancel(pool: &PgPool, id: Uuid, operator: Option<&str>) -> anyhow::Result<PendingOperation> {
+        let operator = operator.ok_or(OperationError::OperatorRequired)?;
+        let cancelled = sqlx::query_as::<_, PendingOperation>(&format!(
+            "UPDATE public.pending_operations
+             SET status = 'cancelled', cancelled_by = $2, cancelled_at = NOW()
+             WHERE id = $1 AND status = 'pending'
+             RETURNING {OP_COLS}"
+        ))
+        .bind(id)
+        .bind(operator)
+        .fetch_optional(pool)
+        .await?;
+        match cancelled {
+            Some(op) => Ok(op),
+            None => Err(Self::missing_or_closed(pool, id).await),
+        }
+    }
+
+    /// Second-person approval: a different, identified super-admin runs the operation now.
+    pub async fn confirm(
+        pool: &PgPool,
+        config: &Config,
+        id: Uuid,
+        operator: Option<&str>,
+    ) -> anyhow::Result<PendingOperation> {
+        operator.ok_or(OperationError::OperatorRequired)?;
+        let op = Self::get(pool, id).await?.ok_or(OperationError::NotFound)?;
+        let operator = check_confirmer(op.requested_by.as_deref(), operator)?;
+
+        let claimed = sqlx::query_as::<_, PendingOperation>(&format!(
+            "UPDATE public.pending_operations
+             SET status = 'running', confirmed_by = $2
+             WHERE id = $1 AND status = 'pending'
+             RETURNING {OP_COLS}"
+        ))
+        .bind(id)
+        .bind(operator)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(OperationError::NotPending)?;
+
+        Ok(Self::run(pool, config, claimed).await)
+    }
+
+    /// Run every operation whose cancel window has elapsed. Returns how many ran.
+    pub async fn run_due(pool: &PgPool, config: &Config) -> anyhow::Result<usize> {
+        let due = sqlx::query_as::<_, PendingOperation>(&format!(
+            "UPDATE public.pending_operations
+             SET status = 'running'
+             WHERE status = 'pending' AND execute_after <= NOW()
+             RETURNING {OP_COLS}"
+        ))
+        .fetch_all(pool)
+        .await?;
+        let count = due.len();
+        for op in due {
+            let op = Self::run(pool, config, op).await;
+            if let Some(ref e) = op.error {
+                tracing::error!("Pending operation {} ({}) failed: {e}", op.id, op.kind);
+            } else {
+                tracing::info!("Pending operation {} ({}) executed", op.id, op.kind);
+            }
+        }
+        Ok(count)
+    }
+
+    /// Permanently remove archived garderies whose retention period is over: their
+    /// registry row and every backup of them in S3. Returns how many were purged.
+    pub async fn purge_expired_archives(pool: &PgPool, config: &Config) -> anyhow::Result<usize> {
+        let expired: Vec<String> = sqlx::query_scalar(
+            "SELECT slug FROM public.garderies WHERE archived_at IS NOT NULL AND purge_after <= NOW()",
+        )
+        .fetch_all(pool)
+        .await?;
+        if expired.is_empty() {
+            return Ok(0);
+        }
+        let store = ObjectStore::new(config).ok_or(BackupError::NotConfigured)?;
+
+        for slug in &expired {
+            BackupService::delete_all(&store, slug).await?;
+            sqlx::query("DELETE FROM public.garderies WHERE slug = $1 AND archived_at IS NOT NULL")
+                .bind(slug)
+                .execute(pool)
+                .await?;
+            tracing::info!("Archived garderie '{slug}' purged after its retention period");
+        }
+        Ok(expired.len())
+    }
+
+    /// Addresses alerted when a destructive operation is requested.
+    pub async fn alert_recipients(pool: &PgPool) -> anyhow::Result<Vec<String>> {
+        let emails = sqlx::query_scalar(
+            "SELECT email FROM public.super_admins WHERE is_active = TRUE ORDER BY email",
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(emails)
+    }
+
+    /// Execute a claimed (`running`) operation and record its outcome.
+    async fn run(pool: &PgPool, config: &Config, op: PendingOperation) -> PendingOperation {
+        let outcome = match op.kind.as_str() {
+            OP_DELETE_GARDERIE => match op.target_slug.as_deref() {
+                Some(slug) => delete_garderie(pool, config, slug).await,
+                None => Err(anyhow::anyhow!("missing target garderie")),
+            },
+            OP_ARCHIVE_GARDERIE => match op.target_slug.as_deref() {
+                Some(slug) => archive_garderie(pool, config, slug).await,
+                None => Err(anyhow::anyhow!("missing target garderie")),
+            },
+            OP_RESTORE => match serde_json::from_value::<RestoreRequest>(op.payload.clone()) {
+                Ok(req) => restore(config, &req).await,
+                Err(e) => Err(anyhow::anyhow!("invalid restore payload: {e}")),
+            },
+            OP_RESTORE_GARDERIE => restore_garderie(pool, config, &op).await,
+            OP_TRANSFER_CHILD => transfer_child(pool, config, &op).await,
+            other => Err(anyhow::anyhow!("unknown operation kind {other}")),
+        };
+
+        let (status, error) = match outcome {
+            Ok(()) => ("executed", None),
+            Err(e) => ("failed", Some(e.to_string())),
+        };
+        let finished = sqlx::query_as::<_, PendingOperation>(&format!(
+            "UPDATE public.pending_operations
+             SET status = $2, error = $3, executed_at = NOW()
+             WHERE id = $1
+             RETURNING {OP_COLS}"
+        ))
+        .bind(op.id)
+        .bind(status)
+        .bind(&error)
+        .fetch_one(pool)
+        .await;
+
+        match finished {
+            Ok(op) => op,
+            Err(e) => {
+                tracing::error!("Could not record outcome of operation {}: {e}", op.id);
+                PendingOperation { status: status.into(), error, ..op }
+            }
+        }
+    }
+
+    async fn missing_or_closed(pool: &PgPool, id: Uuid) -> anyhow::Error {
+        match Self::get(pool, id).await {
+            Ok(Some(_)) => OperationError::NotPending.into(),
+            Ok(None) => OperationError::NotFound.into(),
+            Err(e) => e,
+        }
+    }
+}
+
+/// Reject backup file names that could escape the backup directory.
+pub fn validate_restore(req: &RestoreRequest) -> Result<(), &'static str> {
+    let invalid = |f: &str| f.contains('/') || f.contains("..") || f.contains('\0');
+    if invalid(&req.db_file) || !req.db_file.ends_with(".sql.gz") {
+        return Err("Invalid db_file");
+    }
+    if let Some(ref mf) = req.media_file {
+        if invalid(mf) || !mf.ends_with(".tar.gz") {
+            return Err("Invalid media_file");
+        }
+    }
+    Ok(())
+}
+
+/// Drop the tenant schema, its registry row and its media files.
+async fn delete_garderie(pool: &PgPool, config: &Config, slug: &str) -> anyhow::Result<()> {
+    drop_tenant_data(pool, config, slug).await?;
+
+    // Remove from garderies registry
+    sqlx::query("DELETE FROM garderies WHERE slug = $1")
+        .bind(slug)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Export the garderie to S3, then remove its schema and media while keeping the
+/// registry row, so logins are refused and it can be restored until `purge_after`.
+async fn archive_garderie(pool: &PgPool, config: &Config, slug: &str) -> anyhow::Result<()> {
+    let store = ObjectStore::new(config).ok_or(BackupError::NotConfigured)?;
+    let manifest = BackupService::run_and_record(pool, config, &store, slug, "archive").await?;
+
+    sqlx::query(
+        "UPDATE public.garderies
+         SET is_active = FALSE, archived_at = NOW(), archive_backup_id = $2,
+             purge_after = NOW() + make_interval(days => $3), updated_at = NOW()
+         WHERE slug = $1",
+    )
+    .bind(slug)
+    .bind(&manifest.id)
+    .bind(config.archive_retention_days as i32)
+    .execute(pool)
+    .await?;
+
+    drop_tenant_data(pool, config, slug).await
+}
+
+/// Drop the tenant schema and its media directory.
+async fn drop_tenant_data(pool: &PgPool, config: &Config, slug: &str) -> anyhow::Result<()> {
+    // Drop tenant schema (cascades to all tables/types/functions in it). A shared
+    // schema is kept: the garderie's rows go with its registry row.
+    if !TenantDb::is_shared() {
+        let schema = schema_name(slug);
+        sqlx::raw_sql(&format!("DROP SCHEMA IF EXISTS {schema} CASCADE"))
+            .execute(pool)
+            .await?;
+    }
+
+    // Delete physical files (photos, videos, documents) for this tenant
+    let tenant_media_dir = PathBuf::from(&config.media_dir).join(slug);
+    if tenant_media_dir.exists() {
+        if let Err(e) = tokio::fs::remove_dir_all(&tenant_media_dir).await {
+            // Log but don't fail — DB is already cleaned up
+            tracing::warn!("Could not delete media directory {:?}: {}", tenant_media_dir, e);
+        }
+    }
+    Ok(())
+}
+
+async fn restore_garderie(pool: &PgPool, config: &Config, op: &PendingOperation) -> anyhow::Result<()> {
+    let slug = op.target_slug.as_deref().ok_or_else(|| anyhow::anyhow!("missing target garderie"))?;
+    let req: TenantRestoreRequest = serde_json::from_value(op.payload.clone())
+        .map_err(|e| anyhow::anyhow!("invalid restore payload: {e}"))?;
+    let store = ObjectStore::new(config).ok_or(BackupError::NotConfigured)?;
+    BackupService::restore_tenant(pool, config, &store, slug, &req).await?;
+
+    // Restoring an archived garderie brings it back; its dump may predate newer tables.
+    let unarchived = sqlx::query(
+        "UPDATE public.garderies
+         SET is_active = TRUE, archived_at = NULL, archive_backup_id = NULL, purge_after = NULL, updated_at = NOW()
+         WHERE slug = $1 AND archived_at IS NOT NULL",
+    )
+    .bind(slug)
+    .execute(pool)
+    .await?
+    .rows_affected();
+    if unarchived > 0 {
+        provision_tenant_schema(pool, slug).await?;
+    }
+    Ok(())
+}
+
+/// Move a child of `target_slug` to the garderie named in the payload, then invite
+/// the parents who have no account there yet.
+async fn transfer_child(pool: &PgPool, config: &Config, op: &PendingOperation) -> anyhow::Result<()> {
+    let source = op.target_slug.as_deref().ok_or_else(|| anyhow::anyhow!("missing source garderie"))?;
+    let req: TransferChildRequest = serde_json::from_value(op.payload.clone())
+        .map_err(|e| anyhow::anyhow!("invalid transfer payload: {e}"))?;
+    let keys = KeyRing::from_config(config)?;
+    let transfer = TransferService::transfer(pool, &config.media_dir, &keys, source, &req, op).await?;
+    if let Err(e) = TransferService::invite_parents(pool, config, &transfer).await {
+        tracing::warn!("Could not invite the parents of transferred child {}: {e}", transfer.target_child_id);
+    }
+    Ok(())
+}
+
+/// Restore the database dump (gunzip | psql) and, if given, the media archive.
+async fn restore(config: &Config, req: &RestoreRequest) -> anyhow::Result<()> {
+    validate_restore(req).map_err(anyhow::Error::msg)?;
+    let backup_dir = PathBuf::from(BACKUP_DIR);
+
+    let db_path = backup_dir.join(&req.db_file);
+    if !db_path.exists() {
+        anyhow::bail!("DB backup file not found");
+    }
+    let media_path = req.media_file.as_ref().map(|f| backup_dir.join(f));
+    if media_path.as_ref().is_some_and(|p| !p.exists()) {
+        anyhow::bail!("Media backup file not found");
+    }
+
+    let restore_cmd = format!(
+        "gunzip -c '{}' | psql '{}'",
+        db_path.display(),
+        &config.database_url
+    );
+    let db_output = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(&restore_cmd)
+        .output()
+        .await
+        .map_err(|e| anyhow::anyhow!("restore failed: {e}"))?;
+    if !db_output.status.success() {
+        let stderr = String::from_utf8_lossy(&db_output.stderr);
+        anyhow::bail!("DB restore failed: {}", stderr.trim());
+    }
+
+    if let Some(media_path) = media_path {
+        let tar_output = tokio::process::Command::new("tar")
+            .arg("-xzf")
+            .arg(&media_path)
+            .arg("-C")
+            .arg("/data")
+            .output()
+            .await
+            .map_err(|e| anyhow::anyhow!("tar failed: {e}"))?;
+        if !tar_output.status.success() {
+            let stderr = String::from_utf8_lossy(&tar_output.stderr);
+            anyhow::bail!("Media restore failed: {}", stderr.trim());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn req(db_file: &str, media_file: Option<&str>) -> RestoreRequest {
+        RestoreRequest {
+            db_file: db_file.into(),
+            media_file: media_file.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn confirmation_needs_two_identified_operators() {
+        assert_eq!(check_confirmer(Some("a@ops.test"), Some("b@ops.test")).unwrap(), "b@ops.test");
+        assert!(matches!(check_confirmer(Some("a@ops.test"), None), Err(OperationError::OperatorRequired)));
+        assert!(matches!(check_confirmer(Some("A@ops.test"), Some("a@ops.test")), Err(OperationError::SameOperator)));
+        // Queued before requesters had to identify: anyone could be the requester
+        assert!(matches!(check_confirmer(None, Some("b@ops.test")), Err(OperationError::UnidentifiedRequester)));
+    }
+
+    #[tokio::test]
+    async fn unidentified_requests_are_refused_before_queueing() {
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        for requester in [None, Some(""), Some("  ")] {
+            let e = OperationService::request(&pool, OP_DELETE_GARDERIE, Some("soleil"), serde_json::json!({}), requester, 60)
+                .await
+                .unwrap_err();
+            assert!(matches!(e.downcast_ref(), Some(OperationError::RequesterRequired)));
+        }
+    }
+
+    #[tokio::test]
+    async fn unidentified_cancellations_are_refused() {
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let e = OperationService::cancel(&pool, Uuid::new_v4(), None).await.unwrap_err();
+        assert!(matches!(e.downcast_ref(), Some(OperationError::OperatorRequired)));
+    }
+
+    #[test]
+    fn restore_rejects_path_traversal() {
+        assert!(validate_restore(&req("db_20261017_020000.sql.gz", Some("media_20261017_020000.tar.gz"))).is_ok());
+        assert!(validate_restore(&req("../etc/passwd.sql.gz", None)).is_err());
+        assert!(validate_restore(&req("db.sql", None)).is_err());
+        assert!(validate_restore(&req("db.sql.gz", Some("sub/media.tar.gz"))).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+use std::time::Duration;
+
+use reqwest::Client;
+use sha1::{Digest, Sha1};
+use tracing::warn;
+
+use crate::config::Config;
+
+/// bcrypt only hashes the first 72 bytes; anything longer would be silently truncated.
+const MAX_BYTES: usize = 72;
+
+/// Passwords rejected outright, compared case-insensitively.
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456", "12345678", "123456789", "1234567890", "12345678910", "111111", "000000",
+    "11111111", "00000000", "123123123", "987654321", "password", "password1",
+    "password123", "passw0rd", "motdepasse", "motdepasse1", "motdepasse123", "azerty",
+    "azerty123", "azertyuiop", "qwerty", "qwerty123", "qwertyuiop", "abc12345",
+    "abcd1234", "iloveyou", "jetaime", "soleil123", "bonjour123", "welcome1",
+    "welcome123", "admin123", "administrator", "letmein123", "sunshine1", "princess1",
+    "football1", "baseball1", "superman1", "dragon123", "monkey123", "trustno1",
+    "minispace", "minispace1", "minispace123", "garderie", "garderie1", "garderie123",
+    "changeme", "changeme123", "bienvenue", "bienvenue1", "bienvenue123",
+];
+
+/// Why a password was refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyViolation {
+    TooShort { min: usize },
+    TooLong,
+    TooCommon,
+    Breached,
+}
+
+/// A refused password, carrying the locale its message should be rendered in.
+#[derive(Debug, thiserror::Error)]
+#[error("{}", self.message())]
+pub struct PasswordRejected {
+    pub violation: PolicyViolation,
+    pub locale: String,
+}
+
+impl PasswordRejected {
+    /// Stable identifier for clients, e.g. "password_too_short".
+    pub fn code(&self) -> &'static str {
+        match self.violation {
+            PolicyViolation::TooShort { .. } => "password_too_short",
+            PolicyViolation::TooLong => "password_too_long",
+            PolicyViolation::TooCommon => "password_too_common",
+            PolicyViolation::Breached => "password_breached",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        let en = self.locale.starts_with("en");
+        match (&self.violation, en) {
+            (PolicyViolation::TooShort { min }, false) => {
+                format!("Le mot de passe doit contenir au moins {min} caractères")
+            }
+            (PolicyViolation::TooShort { min }, true) => {
+                format!("Password must be at least {min} characters long")
+            }
+            (PolicyViolation::TooLong, false) => {
+                format!("Le mot de passe ne doit pas dépasser {MAX_BYTES} octets")
+            }
+            (PolicyViolation::TooLong, true) => format!("Password must not exceed {MAX_BYTES} bytes"),
+            (PolicyViolation::TooCommon, false) => {
+                "Ce mot de passe est trop courant, veuillez en choisir un autre".to_string()
+            }
+            (PolicyViolation::TooCommon, true) => {
+                "This password is too common, please choose another one".to_string()
+            }
+            (PolicyViolation::Breached, false) => {
+                "Ce mot de passe figure dans une fuite de données connue, veuillez en choisir un autre"
+                    .to_string()
+            }
+            (PolicyViolation::Breached, true) => {
+                "This password appears in a known data breach, please choose another one".to_string()
+            }
+        }
+    }
+}
+
+/// Rules applied whenever a user chooses a password.
+pub struct PasswordPolicy {
+    min_length: usize,
+    /// Present when the Have I Been Pwned range check is enabled.
+    hibp: Option<Client>,
+}
+
+impl PasswordPolicy {
+    pub fn new(config: &Config) -> Self {
+        let hibp = config.password_breach_check.then(|| {
+            Client::builder()
+                .timeout(Duration::from_secs(3))
+                .user_agent("minispace-api")
+                .build()
+                .unwrap_or_default()
+        });
+        Self { min_length: config.password_min_length, hibp }
+    }
+
+    /// Fails with [`PasswordRejected`] (localized for `locale`) if the password is not acceptable.
+    pub async fn check(&self, password: &str, locale: &str) -> anyhow::Result<()> {
+        let violation = match self.check_local(password) {
+            Some(v) => Some(v),
+            None if self.is_breached(password).await => Some(PolicyViolation::Breached),
+            None => None,
+        };
+        match violation {
+            Some(violation) => Err(PasswordRejected { violation, locale: locale.to_string() }.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Length and common-password rules, no network involved.
+    fn check_local(&self, password: &str) -> Option<PolicyViolation> {
+        if password.chars().count() < self.min_length {
+            return Some(PolicyViolation::TooShort { min: self.min_length });
+        }
+        if password.len() > MAX_BYTES {
+            return Some(PolicyViolation::TooLong);
+        }
+        let lowered = password.to_lowercase();
+        let first = lowered.chars().next();
+        if COMMON_PASSWORDS.contains(&lowered.as_str()) || lowered.chars().all(|c| Some(c) == first) {
+            return Some(PolicyViolation::TooCommon);
+        }
+        None
+    }
+
+    /// k-anonymity lookup: only the first 5 hex chars of the SHA-1 leave the server.
+    /// Any failure of the remote service lets the password through.
+    async fn is_breached(&self, password: &str) -> bool {
+        let Some(client) = &self.hibp else {
+            return false;
+        };
+        let digest = hex::encode_upper(Sha1::digest(password.as_bytes()));
+        let (prefix, suffix) = digest.split_at(5);
+
+        let body = match client
+            .get(format!("https://api.pwnedpasswords.com/range/{prefix}"))
+            .header("Add-Padding", "true")
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+        {
+            Ok(res) => match res.text().await {
+                Ok(body) => body,
+                Err(e) => {
+                    warn!("HIBP range response unreadable: {e}");
+                    return false;
+                }
+            },
+            Err(e) => {
+                warn!("HIBP range check failed: {e}");
+                return false;
+            }
+        };
+        range_contains(&body, suffix)
+    }
+}
+
+/// True if the `SUFFIX:COUNT` range body lists `suffix` with a non-zero count (padding rows are 0).
+fn range_contains(body: &str, suffix: &str) -> bool {
+    body.lines().any(|line| {
+        let mut parts = line.trim().split(':');
+        parts.next().is_some_and(|s| s.eq_ignore_ascii_case(suffix))
+            && parts.next().and_then(|c| c.parse::<u64>().ok()).unwrap_or(0) > 0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> PasswordPolicy {
+        PasswordPolicy { min_length: 10, hibp: None }
+    }
+
+    #[test]
+    fn rejects_short_long_and_common_passwords() {
+        let p = policy();
+        assert_eq!(p.check_local("court1"), Some(PolicyViolation::TooShort { min: 10 }));
+        assert_eq!(p.check_local(&"a1".repeat(40)), Some(PolicyViolation::TooLong));
+        assert_eq!(p.check_local("MotDePasse123"), Some(PolicyViolation::TooCommon));
+        assert_eq!(p.check_local("zzzzzzzzzzzz"), Some(PolicyViolation::TooCommon));
+        assert_eq!(p.check_local("cheval-agrafe-batterie"), None);
+    }
+
+    #[test]
+    fn messages_follow_locale() {
+        let fr = PasswordRejected { violation: PolicyViolation::TooShort { min: 10 }, locale: "fr".into() };
+        let en = PasswordRejected { violation: PolicyViolation::TooShort { min: 10 }, locale: "en".into() };
+        assert_eq!(fr.to_string(), "Le mot de passe doit contenir au moins 10 caractères");
+        assert_eq!(en.to_string(), "Password must be at least 10 characters long");
+        assert_eq!(en.code(), "password_too_short");
+    }
+
+    #[test]
+    fn range_lookup_ignores_padding() {
+        let body = "0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n00D4F6E8FA6EECAD2A3AA415EEC418D38EC:0\r\n";
+        assert!(range_contains(body, "0018A45C4D1DEF81644B54AB7F969B88D65"));
+        assert!(!range_contains(body, "00D4F6E8FA6EECAD2A3AA415EEC418D38EC"));
+        assert!(!range_contains(body, "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF"));
+    }
+}
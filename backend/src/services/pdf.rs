@@ -0,0 +1,204 @@
+//! Minimal PDF writer for generated documents (invoices). Text only, with the
+//! standard Helvetica fonts so no font file has to be embedded; text is encoded in
+//! WinAnsi, which covers French.
+
+/// US Letter, in points.
+pub const PAGE_WIDTH: f32 = 612.0;
+pub const PAGE_HEIGHT: f32 = 792.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+}
+
+/// Helvetica advance widths (1/1000 em) for ASCII 32..=126, from the Adobe metrics.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, // space - /
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, // 0 - 9
+    278, 278, 584, 584, 584, 556, 1015, // : - @
+    667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, // A - M
+    722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, // N - Z
+    278, 278, 278, 469, 556, 333, // [ - `
+    556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, // a - m
+    556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, // n - z
+    334, 260, 334, 584, // { - ~
+];
+
+/// Width of `text` in points. Bold and accented letters are approximated with the
+/// regular metrics, which is close enough to right-align amounts.
+pub fn text_width(text: &str, size: f32) -> f32 {
+    let units: u32 = text
+        .chars()
+        .map(|c| match c as u32 {
+            n @ 32..=126 => HELVETICA_WIDTHS[(n - 32) as usize] as u32,
+            _ => 556,
+        })
+        .sum();
+    units as f32 * size / 1000.0
+}
+
+/// Byte of `c` in WinAnsiEncoding; characters outside it become '?'.
+fn win_ansi(c: char) -> u8 {
+    match c {
+        ' '..='~' => c as u8,
+        '\u{a0}'..='\u{ff}' => c as u32 as u8,
+        '\u{202f}' => b' ',
+        '€' => 0x80,
+        '‚' => 0x82,
+        '„' => 0x84,
+        '…' => 0x85,
+        'Œ' => 0x8c,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        'œ' => 0x9c,
+        _ => b'?',
+    }
+}
+
+/// `text` as a PDF literal string, non-ASCII bytes written as octal escapes.
+fn literal(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('(');
+    for byte in text.chars().map(win_ansi) {
+        match byte {
+            b'(' | b')' | b'\\' => {
+                out.push('\\');
+                out.push(byte as char);
+            }
+            0x20..=0x7e => out.push(byte as char),
+            _ => out.push_str(&format!("\\{byte:03o}")),
+        }
+    }
+    out.push(')');
+    out
+}
+
+/// A document built page by page; coordinates are in points from the bottom-left corner.
+pub struct PdfDocument {
+    pages: Vec<String>,
+}
+
+impl Default for PdfDocument {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PdfDocument {
+    /// A document with one empty page.
+    pub fn new() -> Self {
+        Self { pages: vec![String::new()] }
+    }
+
+    pub fn new_page(&mut self) {
+        self.pages.push(String::new());
+    }
+
+    fn page(&mut self) -> &mut String {
+        self.pages.last_mut().expect("a document always has a page")
+    }
+
+    pub fn text(&mut self, x: f32, y: f32, size: f32, font: Font, text: &str) {
+        let op = format!("BT /{} {size} Tf {x:.2} {y:.2} Td {} Tj ET\n", font.resource(), literal(text));
+        self.page().push_str(&op);
+    }
+
+    /// Text ending at `right`, for amounts in a column.
+    pub fn text_right(&mut self, right: f32, y: f32, size: f32, font: Font, text: &str) {
+        self.text(right - text_width(text, size), y, size, font, text);
+    }
+
+    pub fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
+        let op = format!("0.5 w {x1:.2} {y1:.2} m {x2:.2} {y2:.2} l S\n");
+        self.page().push_str(&op);
+    }
+
+    /// Serialize the document.
+    pub fn finish(self) -> Vec<u8> {
+        // Objects: 1 catalog, 2 page tree, 3-4 fonts, then a page and its content per page
+        let page_count = self.pages.len();
+        let mut objects: Vec<String> = Vec::with_capacity(4 + 2 * page_count);
+        let kids: Vec<String> = (0..page_count).map(|i| format!("{} 0 R", 5 + 2 * i)).collect();
+        objects.push("<< /Type /Catalog /Pages 2 0 R >>".to_string());
+        objects.push(format!("<< /Type /Pages /Kids [{}] /Count {page_count} >>", kids.join(" ")));
+        for name in ["Helvetica", "Helvetica-Bold"] {
+            objects.push(format!(
+                "<< /Type /Font /Subtype /Type1 /BaseFont /{name} /Encoding /WinAnsiEncoding >>"
+            ));
+        }
+        for (i, content) in self.pages.iter().enumerate() {
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] \
+                 /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                6 + 2 * i
+            ));
+            objects.push(format!("<< /Length {} >>\nstream\n{content}endstream", content.len()));
+        }
+
+        let mut out = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n{object}\nendobj\n", i + 1).as_bytes());
+        }
+        let xref = out.len();
+        out.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+        for offset in offsets {
+            out.extend_from_slice(format!("{offset:010} 00000 n \n").as_bytes());
+        }
+        out.extend_from_slice(
+            format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n", objects.len() + 1).as_bytes(),
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_is_escaped_and_encoded_in_win_ansi() {
+        assert_eq!(literal("Reçu (octobre) \\"), "(Re\\347u \\(octobre\\) \\\\)");
+        assert_eq!(literal("Frais — 5 €"), "(Frais \\227 5 \\200)");
+        assert_eq!(literal("日"), "(?)");
+    }
+
+    #[test]
+    fn xref_offsets_point_at_objects() {
+        let mut doc = PdfDocument::new();
+        doc.text(72.0, 720.0, 12.0, Font::Bold, "Facture");
+        doc.new_page();
+        doc.line(72.0, 700.0, 540.0, 700.0);
+        let bytes = doc.finish();
+        let text = String::from_utf8(bytes).unwrap();
+        assert!(text.starts_with("%PDF-1.4\n") && text.ends_with("%%EOF\n"));
+
+        let xref_at: usize = text.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+        let entries: Vec<usize> = text[xref_at..]
+            .lines()
+            .skip(3)
+            .take_while(|l| l.ends_with(" n "))
+            .map(|l| l[..10].parse().unwrap())
+            .collect();
+        assert_eq!(entries.len(), 8);
+        for (i, offset) in entries.iter().enumerate() {
+            assert!(text[*offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use tracing::warn;
+
+use crate::{
+    db::{tenant::schema_name, tenant_db::TenantDb},
+    models::{
+        stats::{HealthLevel, PlatformTotals, TenantHealth},
+        tenant::PlanType,
+    },
+    services::storage::StorageService,
+};
+
+type GarderieRow = (String, String, PlanType, bool, Option<DateTime<Utc>>);
+
+/// Active users, DAU, WAU, last admin login, emails and failed emails over 7 days.
+type ActivityRow = (i64, i64, i64, Option<DateTime<Utc>>, i64, i64);
+
+/// Score of a garderie from its activity signals, with the signals that lowered it.
+/// A quiet admin weighs most: garderies that churn stop signing in well before their
+/// trial lapses.
+pub fn health_score(h: &TenantHealth, now: DateTime<Utc>) -> (u8, Vec<&'static str>) {
+    let mut score: i32 = 100;
+    let mut risks = Vec::new();
+    let mut lower = |points: i32, risk: &'static str| {
+        score -= points;
+        risks.push(risk);
+    };
+
+    match h.last_admin_login {
+        Some(at) if now - at <= Duration::days(7) => {}
+        Some(at) if now - at <= Duration::days(14) => lower(20, "no_admin_login_7d"),
+        _ => lower(35, "no_admin_login_14d"),
+    }
+    if h.wau == 0 {
+        lower(30, "no_activity_7d");
+    } else if h.active_users > 0 && h.wau * 4 < h.active_users {
+        lower(15, "low_activity_7d");
+    }
+    if h.email_failures_7d >= 3 && h.email_failures_7d * 10 > h.emails_7d {
+        lower(15, "email_failures");
+    }
+    if h.storage_percent >= 95.0 {
+        lower(10, "storage_full");
+    } else if h.storage_percent >= 80.0 {
+        lower(5, "storage_high");
+    }
+    match h.trial_days_left {
+        Some(days) if days < 0 => lower(10, "trial_expired"),
+        Some(days) if days <= 7 => lower(10, "trial_ending"),
+        _ => {}
+    }
+
+    (score.clamp(0, 100) as u8, risks)
+}
+
+pub fn health_level(score: u8) -> HealthLevel {
+    match score {
+        70.. => HealthLevel::Healthy,
+        40.. => HealthLevel::AtRisk,
+        _ => HealthLevel::Critical,
+    }
+}
+
+pub struct PlatformStatsService;
+
+impl PlatformStatsService {
+    /// Health of every garderie not archived (demo excluded), riskiest first.
+    pub async fn tenants(
+        pool: &PgPool,
+        redis: &mut redis::aio::MultiplexedConnection,
+    ) -> anyhow::Result<(PlatformTotals, Vec<TenantHealth>)> {
+        let garderies: Vec<GarderieRow> = sqlx::query_as(
+            "SELECT slug, name, plan, is_active, trial_expires_at FROM public.garderies
+             WHERE archived_at IS NULL AND slug != 'demo' ORDER BY name",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let now = Utc::now();
+        let mut tenants = Vec::with_capacity(garderies.len());
+        for (slug, name, plan, is_active, trial_expires_at) in garderies {
+            TenantDb::scope(slug.clone(), async {
+                let schema = schema_name(&slug);
+                let activity: Result<ActivityRow, _> = sqlx::query_as(&format!(
+                    r#"SELECT
+                         (SELECT COUNT(*) FROM "{schema}".users WHERE is_active),
+                         (SELECT COUNT(DISTINCT user_id) FROM "{schema}".login_events
+                           WHERE success AND created_at > NOW() - INTERVAL '1 day'),
+                         (SELECT COUNT(DISTINCT user_id) FROM "{schema}".login_events
+                           WHERE success AND created_at > NOW() - INTERVAL '7 days'),
+                         (SELECT MAX(e.created_at) FROM "{schema}".login_events e
+                            JOIN "{schema}".users u ON u.id = e.user_id AND u.role = 'admin_garderie'
+                           WHERE e.success),
+                         (SELECT COUNT(*) FROM "{schema}".email_log WHERE created_at > NOW() - INTERVAL '7 days'),
+                         (SELECT COUNT(*) FROM "{schema}".email_log
+                           WHERE status = 'failed' AND created_at > NOW() - INTERVAL '7 days')"#
+                ))
+                .fetch_one(pool)
+                .await;
+                let (active_users, dau, wau, last_admin_login, emails_7d, email_failures_7d) = match activity {
+                    Ok(row) => row,
+                    Err(e) => {
+                        warn!("Platform stats unavailable for '{slug}': {e}");
+                        return;
+                    }
+                };
+                let (storage_percent, used_bytes) = match StorageService::usage(pool, redis, &slug).await {
+                    Ok(usage) => (usage.percent, usage.used_bytes),
+                    Err(e) => {
+                        warn!("Storage usage unavailable for '{slug}': {e}");
+                        (0.0, 0)
+                    }
+                };
+
+                let mut health = TenantHealth {
+                    slug,
+                    name,
+                    plan,
+                    is_active,
+                    trial_expires_at,
+                    trial_days_left: trial_expires_at.map(|at| (at - now).num_days()),
+                    active_users,
+                    dau,
+                    wau,
+                    last_admin_login,
+                    storage_percent,
+                    used_bytes,
+                    emails_7d,
+                    email_failures_7d,
+                    health_score: 100,
+                    health: HealthLevel::Healthy,
+                    risks: Vec::new(),
+                };
+                let (score, risks) = health_score(&health, now);
+                health.health_score = score;
+                health.health = health_level(score);
+                health.risks = risks;
+                tenants.push(health);
+            })
+            .await;
+        }
+        tenants.sort_by_key(|t| t.health_score);
+
+        let mut totals = PlatformTotals { garderies: tenants.len() as i64, ..Default::default() };
+        for t in &tenants {
+            totals.in_trial += t.trial_days_left.is_some_and(|d| d >= 0) as i64;
+            totals.dau += t.dau;
+            totals.wau += t.wau;
+            totals.used_bytes += t.used_bytes;
+            totals.email_failures_7d += t.email_failures_7d;
+            totals.at_risk += (t.health == HealthLevel::AtRisk) as i64;
+            totals.critical += (t.health == HealthLevel::Critical) as i64;
+        }
+        Ok((totals, tenants))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tenant(now: DateTime<Utc>) -> TenantHealth {
+        TenantHealth {
+            slug: "test".into(),
+            name: "Test".into(),
+            plan: PlanType::Standard,
+            is_active: true,
+            trial_expires_at: None,
+            trial_days_left: None,
+            active_users: 20,
+            dau: 8,
+            wau: 15,
+            last_admin_login: Some(now - Duration::days(1)),
+            storage_percent: 40.0,
+            used_bytes: 0,
+            emails_7d: 100,
+            email_failures_7d: 1,
+            health_score: 100,
+            health: HealthLevel::Healthy,
+            risks: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_active_garderie_is_healthy() {
+        let now = Utc::now();
+        let (score, risks) = health_score(&tenant(now), now);
+        assert_eq!(score, 100);
+        assert!(risks.is_empty());
+        assert_eq!(health_level(score), HealthLevel::Healthy);
+    }
+
+    #[test]
+    fn test_silent_trial_is_critical() {
+        let now = Utc::now();
+        let mut t = tenant(now);
+        t.last_admin_login = None;
+        t.wau = 0;
+        t.dau = 0;
+        t.trial_days_left = Some(3);
+        let (score, risks) = health_score(&t, now);
+        assert_eq!(score, 25);
+        assert_eq!(risks, vec!["no_admin_login_14d", "no_activity_7d", "trial_ending"]);
+        assert_eq!(health_level(score), HealthLevel::Critical);
+    }
+
+    #[test]
+    fn test_email_failures_and_storage() {
+        let now = Utc::now();
+        let mut t = tenant(now);
+        t.last_admin_login = Some(now - Duration::days(10));
+        t.email_failures_7d = 20;
+        t.storage_percent = 85.0;
+        let (score, risks) = health_score(&t, now);
+        assert_eq!(score, 60);
+        assert_eq!(risks, vec!["no_admin_login_7d", "email_failures", "storage_high"]);
+        assert_eq!(health_level(score), HealthLevel::AtRisk);
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+
+use anyhow::bail;
+use chrono::{DateTime, Utc};
+use redis::aio::MultiplexedConnection;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::message::{ConversationItem, TypingEvent, WsMessage},
+    services::realtime::RealtimeService,
+};
+
+/// A user is online while this key lives; every open socket refreshes it.
+const ONLINE_TTL_SECS: u64 = 60;
+
+/// How often an open socket refreshes its presence (must stay below ONLINE_TTL_SECS).
+pub const HEARTBEAT_SECS: u64 = 25;
+
+/// Last-seen timestamps are kept for a month after the last disconnect.
+const LAST_SEEN_TTL_SECS: u64 = 30 * 24 * 3600;
+
+/// Number of open sockets for the user (several tabs or devices).
+fn online_key(tenant: &str, user_id: Uuid) -> String {
+    format!("presence:{tenant}:{user_id}:online")
+}
+
+fn last_seen_key(tenant: &str, user_id: Uuid) -> String {
+    format!("presence:{tenant}:{user_id}:last_seen")
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Presence {
+    pub online: bool,
+    pub last_seen_at: Option<DateTime<Utc>>,
+}
+
+pub struct PresenceService;
+
+impl PresenceService {
+    /// A socket opened: count it and announce the user online on the first one.
+    pub async fn connect(redis: &mut MultiplexedConnection, tenant: &str, user_id: Uuid) {
+        let key = online_key(tenant, user_id);
+        let (count,): (i64,) = match redis::pipe()
+            .incr(&key, 1)
+            .expire(&key, ONLINE_TTL_SECS as i64)
+            .ignore()
+            .query_async(redis)
+            .await
+        {
+            Ok(r) => r,
+            Err(_) => return,
+        };
+        if count == 1 {
+            Self::publish_presence(redis, tenant, user_id, true, None).await;
+        }
+    }
+
+    /// Keep the user online while a socket stays open.
+    pub async fn heartbeat(redis: &mut MultiplexedConnection, tenant: &str, user_id: Uuid) {
+        let key = online_key(tenant, user_id);
+        // Re-create the counter if it expired while the socket was still open.
+        let _: Result<(), _> = redis::pipe()
+            .cmd("SET")
+            .arg(&key)
+            .arg(1)
+            .arg("EX")
+            .arg(ONLINE_TTL_SECS)
+            .arg("NX")
+            .ignore()
+            .expire(&key, ONLINE_TTL_SECS as i64)
+            .ignore()
+            .query_async(redis)
+            .await;
+    }
+
+    /// A socket closed: record last-seen and announce the user offline once no socket remains.
+    pub async fn disconnect(redis: &mut MultiplexedConnection, tenant: &str, user_id: Uuid) {
+        let key = online_key(tenant, user_id);
+        let now = Utc::now();
+        let _: Result<(), _> = redis::cmd("SET")
+            .arg(last_seen_key(tenant, user_id))
+            .arg(now.to_rfc3339())
+            .arg("EX")
+            .arg(LAST_SEEN_TTL_SECS)
+            .query_async(redis)
+            .await;
+
+        let remaining: i64 = redis::cmd("DECR").arg(&key).query_async(redis).await.unwrap_or(0);
+        if remaining <= 0 {
+            let _: Result<(), _> = redis::cmd("DEL").arg(&key).query_async(redis).await;
+            Self::publish_presence(redis, tenant, user_id, false, Some(now)).await;
+        }
+    }
+
+    /// Fan a typing event out to the sockets of the thread's audience. Events for an
+    /// unknown kind, or a thread the user may not write to, are refused.
+    pub async fn publish_typing(
+        pool: &PgPool,
+        redis: &mut MultiplexedConnection,
+        tenant: &str,
+        user_id: Uuid,
+        event: &TypingEvent,
+    ) -> anyhow::Result<()> {
+        let s = schema_name(tenant);
+        let role: Option<String> =
+            sqlx::query_scalar(&format!("SELECT role::text FROM {s}.users WHERE id = $1 AND is_active = TRUE"))
+                .bind(user_id)
+                .fetch_optional(pool)
+                .await?;
+        let Some(role) = role else { bail!("inactive or unknown user") };
+        let is_parent = role == "parent";
+
+        let (group_id, recipient_id) = match (event.thread_kind.as_str(), event.thread_id) {
+            ("broadcast", _) if !is_parent => (None, None),
+            ("staff", group_id) if !is_parent => (group_id, None),
+            ("group", Some(group_id)) => {
+                if is_parent {
+                    let in_group: bool = sqlx::query_scalar(&format!(
+                        "SELECT EXISTS(SELECT 1 FROM {s}.child_parents cp
+                         JOIN {s}.children c ON c.id = cp.child_id
+                         WHERE cp.user_id = $1 AND c.group_id = $2)"
+                    ))
+                    .bind(user_id)
+                    .bind(group_id)
+                    .fetch_one(pool)
+                    .await?;
+                    if !in_group {
+                        bail!("not a member of group {group_id}");
+                    }
+                }
+                (Some(group_id), None)
+            }
+            // The thread id of an individual conversation is the parent's
+            ("individual", Some(parent_id)) if is_parent => {
+                if parent_id != user_id {
+                    bail!("not a member of the conversation of {parent_id}");
+                }
+                (None, None)
+            }
+            ("individual", Some(parent_id)) => (None, Some(parent_id)),
+            (kind, _) => bail!("no typing allowed in thread '{kind}'"),
+        };
+
+        let payload = json!({
+            "user_id": user_id,
+            "thread_kind": event.thread_kind,
+            "thread_id": event.thread_id,
+            "is_typing": event.is_typing,
+        });
+        let payload = serde_json::to_string(&WsMessage { kind: "typing".to_string(), payload }).unwrap_or_default();
+        match RealtimeService::thread_audience(pool, tenant, &event.thread_kind, user_id, group_id, recipient_id).await? {
+            None => RealtimeService::publish_to_tenant(redis, tenant, &payload).await,
+            Some(users) => RealtimeService::publish_to_users(redis, tenant, &users, &payload).await,
+        }
+        Ok(())
+    }
+
+    /// Online state and last-seen time for each of the given users.
+    pub async fn lookup(
+        redis: &mut MultiplexedConnection,
+        tenant: &str,
+        user_ids: &[Uuid],
+    ) -> HashMap<Uuid, Presence> {
+        if user_ids.is_empty() {
+            return HashMap::new();
+        }
+        let keys: Vec<String> = user_ids
+            .iter()
+            .flat_map(|id| [online_key(tenant, *id), last_seen_key(tenant, *id)])
+            .collect();
+        let values: Vec<Option<String>> = redis::cmd("MGET")
+            .arg(&keys)
+            .query_async(redis)
+            .await
+            .unwrap_or_default();
+
+        user_ids
+            .iter()
+            .zip(values.chunks(2))
+            .map(|(id, pair)| {
+                let online = pair[0].as_deref().and_then(|c| c.parse::<i64>().ok()).unwrap_or(0) > 0;
+                let last_seen_at = pair
+                    .get(1)
+                    .and_then(|v| v.as_deref())
+                    .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+                    .map(|d| d.with_timezone(&Utc));
+                (*id, Presence { online, last_seen_at })
+            })
+            .collect()
+    }
+
+    /// Fill `online` / `last_seen_at` on the individual threads of a staff conversation list.
+    pub async fn annotate(redis: &mut MultiplexedConnection, tenant: &str, items: &mut [ConversationItem]) {
+        let ids: Vec<Uuid> = items
+            .iter()
+            .filter(|i| i.kind == "individual")
+            .filter_map(|i| i.id.as_deref().and_then(|id| id.parse().ok()))
+            .collect();
+        let presence = Self::lookup(redis, tenant, &ids).await;
+
+        for item in items.iter_mut().filter(|i| i.kind == "individual") {
+            let Some(id) = item.id.as_deref().and_then(|id| id.parse::<Uuid>().ok()) else {
+                continue;
+            };
+            let p = presence.get(&id).cloned().unwrap_or_default();
+            item.online = Some(p.online);
+            item.last_seen_at = p.last_seen_at;
+        }
+    }
+
+    async fn publish_presence(
+        redis: &mut MultiplexedConnection,
+        tenant: &str,
+        user_id: Uuid,
+        online: bool,
+        last_seen_at: Option<DateTime<Utc>>,
+    ) {
+        let payload = json!({ "user_id": user_id, "online": online, "last_seen_at": last_seen_at });
+        Self::publish(redis, tenant, "presence", payload).await;
+    }
+
+    async fn publish(redis: &mut MultiplexedConnection, tenant: &str, kind: &str, payload: serde_json::Value) {
+        let event = WsMessage { kind: kind.to_string(), payload };
+        let payload = serde_json::to_string(&event).unwrap_or_default();
+        RealtimeService::publish_to_tenant(redis, tenant, &payload).await;
+    }
+}
@@ -0,0 +1,50 @@
+use chrono::{Local, Timelike};
+use sqlx::PgPool;
+use tracing::{info, warn};
+
+use crate::db::tenant_db::TenantDb;
+use crate::services::backup_scheduler::secs_until;
+use crate::services::notifications::NotificationService;
+
+/// Local time of the nightly pruning (seconds after midnight): 3:45.
+const PRUNE_AT: u32 = 3 * 3600 + 45 * 60;
+
+/// Spawn a background task that deletes, every night, the push tokens of each
+/// garderie unused for [`crate::services::notifications::STALE_AFTER_DAYS`] days.
+pub fn start(pool: PgPool) {
+    tokio::spawn(async move {
+        loop {
+            let now = Local::now();
+            let secs_today = now.hour() * 3600 + now.minute() * 60 + now.second();
+            tokio::time::sleep(tokio::time::Duration::from_secs(secs_until(secs_today, PRUNE_AT))).await;
+            let Some(_job) = crate::services::worker::job().await else {
+                break;
+            };
+
+            let tenants: Vec<String> = match sqlx::query_scalar(
+                "SELECT slug FROM public.garderies WHERE is_active = TRUE AND slug != 'demo'",
+            )
+            .fetch_all(&pool)
+            .await
+            {
+                Ok(rows) => rows,
+                Err(e) => {
+                    warn!("Push token scheduler: failed to query tenants: {e}");
+                    continue;
+                }
+            };
+
+            let mut pruned = 0;
+            for slug in &tenants {
+                TenantDb::scope(slug.clone(), async {
+                    match NotificationService::prune_stale_tokens(&pool, slug).await {
+                        Ok(n) => pruned += n,
+                        Err(e) => warn!("Push token scheduler: pruning failed for '{slug}': {e}"),
+                    }
+                })
+                .await;
+            }
+            info!("Push token scheduler: pruned {pruned} stale token(s)");
+        }
+    });
+}
@@ -0,0 +1,110 @@
+use chrono::{Local, Timelike};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::db::tenant::schema_name;
+use crate::db::tenant_db::TenantDb;
+use crate::models::ratio::GroupRatio;
+use crate::services::branding::BrandingService;
+use crate::services::email::EmailService;
+use crate::services::ratios::RatioService;
+
+/// Spawn a background task that checks every 15 minutes, from 6:00 to 19:00, the
+/// child:educator ratio of each group with children present, and emails the admins
+/// the first time in the day a group falls out of compliance.
+pub fn start(pool: PgPool, email: Option<Arc<EmailService>>, app_base_url: String) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(tokio::time::Duration::from_secs(15 * 60)).await;
+            let Some(_job) = crate::services::worker::job().await else {
+                break;
+            };
+
+            let now = Local::now();
+            if !(6..19).contains(&now.hour()) {
+                continue;
+            }
+            let Some(ref email_svc) = email else {
+                continue;
+            };
+
+            let tenants: Vec<String> = match sqlx::query_scalar(
+                "SELECT slug FROM public.garderies WHERE is_active = TRUE AND archived_at IS NULL AND slug != 'demo'",
+            )
+            .fetch_all(&pool)
+            .await
+            {
+                Ok(rows) => rows,
+                Err(e) => {
+                    warn!("Ratio scheduler: failed to query tenants: {e}");
+                    continue;
+                }
+            };
+
+            let today = now.date_naive();
+            for slug in tenants {
+                TenantDb::scope(slug.clone(), async {
+                    let ratios = match RatioService::snapshots(&pool, &slug, today, today, Some(now.time()), None).await {
+                        Ok(ratios) => ratios,
+                        Err(e) => {
+                            warn!("Ratio scheduler: failed to compute ratios for '{slug}': {e}");
+                            return;
+                        }
+                    };
+
+                    let mut newly_out: Vec<GroupRatio> = Vec::new();
+                    for ratio in ratios.into_iter().filter(|r| !r.compliant && r.children > 0) {
+                        match RatioService::record_alert(&pool, &slug, &ratio).await {
+                            Ok(true) => newly_out.push(ratio),
+                            Ok(false) => {}
+                            Err(e) => warn!("Ratio scheduler: failed to record alert for '{slug}': {e}"),
+                        }
+                    }
+                    if newly_out.is_empty() {
+                        return;
+                    }
+                    info!("Ratio scheduler: {} group(s) out of ratio for '{slug}'", newly_out.len());
+                    alert_admins(&pool, email_svc, &app_base_url, &slug, &newly_out).await;
+                })
+                .await;
+            }
+        }
+    });
+}
+
+async fn alert_admins(pool: &PgPool, email_svc: &EmailService, app_base_url: &str, slug: &str, groups: &[GroupRatio]) {
+    let schema = schema_name(slug);
+    let admins: Vec<(String, String, String)> = match sqlx::query_as(&format!(
+        r#"SELECT email, first_name, last_name FROM "{schema}".users
+           WHERE role = 'admin_garderie' AND is_active = TRUE"#
+    ))
+    .fetch_all(pool)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            warn!("Ratio scheduler: failed to list admins for '{slug}': {e}");
+            return;
+        }
+    };
+
+    let app_url = if let Some(idx) = app_base_url.find("://") {
+        let scheme = &app_base_url[..idx];
+        let domain = &app_base_url[idx + 3..];
+        format!("{scheme}://{slug}.{domain}/fr/dashboard")
+    } else {
+        format!("https://{slug}.{app_base_url}/fr/dashboard")
+    };
+    let (garderie_name, branding) = BrandingService::for_email(pool, slug).await;
+
+    for (email, first_name, last_name) in admins {
+        let name = format!("{first_name} {last_name}");
+        if let Err(e) = email_svc
+            .send_ratio_alert(slug, &email, &name, groups, &app_url, &garderie_name, &branding)
+            .await
+        {
+            warn!("Ratio scheduler: failed to alert {email}: {e}");
+        }
+    }
+}
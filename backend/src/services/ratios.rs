@@ -0,0 +1,368 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, NaiveDate, NaiveTime};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::ratio::{BandCount, CreateShiftRequest, GroupRatio, RatioRule, RatioRuleInput, StaffShift},
+};
+
+/// Longest period of a ratio history.
+pub const MAX_HISTORY_DAYS: i64 = 93;
+
+const RULE_COLS: &str = "id, label, min_age_months, max_age_months, children_per_educator, created_at";
+
+#[derive(Debug, thiserror::Error)]
+pub enum RatioError {
+    #[error("Règle de ratio invalide : {0}")]
+    InvalidRule(String),
+    #[error("Les tranches d'âge « {0} » et « {1} » se chevauchent")]
+    OverlappingBands(String, String),
+    #[error("Période invalide (au plus {MAX_HISTORY_DAYS} jours)")]
+    InvalidPeriod,
+    #[error("La fin du quart doit suivre son début")]
+    ShiftEndBeforeStart,
+    #[error("Seul un membre du personnel peut être affecté à un quart")]
+    NotStaff,
+    #[error("Quart introuvable")]
+    ShiftNotFound,
+}
+
+/// Completed months of age on `on`.
+pub fn age_in_months(birth: NaiveDate, on: NaiveDate) -> i32 {
+    let mut months = (on.year() - birth.year()) * 12 + on.month() as i32 - birth.month() as i32;
+    if on.day() < birth.day() {
+        months -= 1;
+    }
+    months.max(0)
+}
+
+pub fn validate_rules(rules: &[RatioRuleInput]) -> Result<(), RatioError> {
+    if rules.is_empty() {
+        return Err(RatioError::InvalidRule("au moins une tranche d'âge est requise".into()));
+    }
+    for r in rules {
+        if r.label.trim().is_empty() || r.label.chars().count() > 64 {
+            return Err(RatioError::InvalidRule("libellé vide ou trop long".into()));
+        }
+        if r.min_age_months < 0 || r.max_age_months.is_some_and(|max| max <= r.min_age_months) {
+            return Err(RatioError::InvalidRule(format!("tranche d'âge de « {} »", r.label)));
+        }
+        if r.children_per_educator <= 0 {
+            return Err(RatioError::InvalidRule(format!("nombre d'enfants par éducatrice de « {} »", r.label)));
+        }
+    }
+    for (i, a) in rules.iter().enumerate() {
+        for b in &rules[i + 1..] {
+            let a_end = a.max_age_months.unwrap_or(i32::MAX);
+            let b_end = b.max_age_months.unwrap_or(i32::MAX);
+            if a.min_age_months < b_end && b.min_age_months < a_end {
+                return Err(RatioError::OverlappingBands(a.label.clone(), b.label.clone()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Children per band and the educators they require. A child outside every band
+/// counts in the strictest one.
+pub fn evaluate(rules: &[RatioRule], ages_in_months: &[i32]) -> (Vec<BandCount>, i64) {
+    let mut bands: Vec<BandCount> = rules
+        .iter()
+        .map(|r| BandCount { label: r.label.clone(), children_per_educator: r.children_per_educator, children: 0 })
+        .collect();
+    let strictest = rules
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, r)| r.children_per_educator)
+        .map(|(i, _)| i);
+
+    for &age in ages_in_months {
+        let band = rules
+            .iter()
+            .position(|r| age >= r.min_age_months && r.max_age_months.is_none_or(|max| age < max))
+            .or(strictest);
+        if let Some(i) = band {
+            bands[i].children += 1;
+        }
+    }
+
+    // Mixed groups: each child takes 1/ratio of an educator.
+    let load: f64 = bands
+        .iter()
+        .map(|b| b.children as f64 / b.children_per_educator as f64)
+        .sum();
+    let required = (load - 1e-9).ceil().max(0.0) as i64;
+    (bands.into_iter().filter(|b| b.children > 0).collect(), required)
+}
+
+/// Child:educator ratios per group, from the attendance (children marked present) and
+/// the staff shifts.
+pub struct RatioService;
+
+impl RatioService {
+    pub async fn rules(pool: &PgPool, tenant: &str) -> anyhow::Result<Vec<RatioRule>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as(&format!(
+            r#"SELECT {RULE_COLS} FROM "{schema}".ratio_rules ORDER BY min_age_months"#
+        ))
+        .fetch_all(pool)
+        .await?)
+    }
+
+    pub async fn replace_rules(pool: &PgPool, tenant: &str, rules: &[RatioRuleInput]) -> anyhow::Result<Vec<RatioRule>> {
+        validate_rules(rules)?;
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+        sqlx::query(&format!(r#"DELETE FROM "{schema}".ratio_rules"#))
+            .execute(&mut *tx)
+            .await?;
+        for r in rules {
+            sqlx::query(&format!(
+                r#"INSERT INTO "{schema}".ratio_rules (label, min_age_months, max_age_months, children_per_educator)
+                   VALUES ($1, $2, $3, $4)"#
+            ))
+            .bind(r.label.trim())
+            .bind(r.min_age_months)
+            .bind(r.max_age_months)
+            .bind(r.children_per_educator)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Self::rules(pool, tenant).await
+    }
+
+    /// Ratios of every group on each day of `[from, to]`. With `at`, only educators on
+    /// duty at that time count; without, those with a shift that day.
+    pub async fn snapshots(
+        pool: &PgPool,
+        tenant: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+        at: Option<NaiveTime>,
+        group_id: Option<Uuid>,
+    ) -> anyhow::Result<Vec<GroupRatio>> {
+        if to < from || (to - from).num_days() >= MAX_HISTORY_DAYS {
+            return Err(RatioError::InvalidPeriod.into());
+        }
+        let schema = schema_name(tenant);
+        let rules = Self::rules(pool, tenant).await?;
+
+        let groups: Vec<(Uuid, String)> = sqlx::query_as(&format!(
+            r#"SELECT id, name FROM "{schema}".groups WHERE $1::UUID IS NULL OR id = $1 ORDER BY name"#
+        ))
+        .bind(group_id)
+        .fetch_all(pool)
+        .await?;
+
+        let present: Vec<(NaiveDate, Uuid, NaiveDate)> = sqlx::query_as(&format!(
+            r#"SELECT a.date, c.group_id, c.birth_date
+               FROM "{schema}".attendance a
+               JOIN "{schema}".children c ON c.id = a.child_id
+               WHERE a.date BETWEEN $1 AND $2 AND c.group_id IS NOT NULL
+                 AND a.status::TEXT IN ('present', 'present_hors_contrat')"#
+        ))
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool)
+        .await?;
+
+        let on_duty: Vec<(NaiveDate, Uuid, i64)> = sqlx::query_as(&format!(
+            r#"SELECT date, group_id, COUNT(DISTINCT user_id)
+               FROM "{schema}".staff_shifts
+               WHERE date BETWEEN $1 AND $2
+                 AND ($3::TIME IS NULL OR (start_time <= $3 AND end_time > $3))
+               GROUP BY date, group_id"#
+        ))
+        .bind(from)
+        .bind(to)
+        .bind(at)
+        .fetch_all(pool)
+        .await?;
+
+        let mut ages: HashMap<(NaiveDate, Uuid), Vec<i32>> = HashMap::new();
+        for (date, group, birth) in present {
+            ages.entry((date, group)).or_default().push(age_in_months(birth, date));
+        }
+        let educators: HashMap<(NaiveDate, Uuid), i64> =
+            on_duty.into_iter().map(|(date, group, n)| ((date, group), n)).collect();
+
+        let mut ratios = Vec::new();
+        for date in from.iter_days().take_while(|d| *d <= to) {
+            for (group_id, group_name) in &groups {
+                let key = (date, *group_id);
+                let group_ages = ages.get(&key).map(Vec::as_slice).unwrap_or_default();
+                let (bands, required) = evaluate(&rules, group_ages);
+                let educators = educators.get(&key).copied().unwrap_or(0);
+                ratios.push(GroupRatio {
+                    group_id: *group_id,
+                    group_name: group_name.clone(),
+                    date,
+                    children: group_ages.len() as i64,
+                    educators,
+                    required_educators: required,
+                    compliant: educators >= required,
+                    bands,
+                });
+            }
+        }
+        Ok(ratios)
+    }
+
+    pub async fn list_shifts(
+        pool: &PgPool,
+        tenant: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+        group_id: Option<Uuid>,
+    ) -> anyhow::Result<Vec<StaffShift>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as(&format!(
+            r#"SELECT s.id, s.user_id, u.first_name || ' ' || u.last_name AS user_name, s.group_id,
+                      s.date, s.start_time, s.end_time, s.created_at
+               FROM "{schema}".staff_shifts s
+               JOIN "{schema}".users u ON u.id = s.user_id
+               WHERE s.date BETWEEN $1 AND $2 AND ($3::UUID IS NULL OR s.group_id = $3)
+               ORDER BY s.date, s.start_time"#
+        ))
+        .bind(from)
+        .bind(to)
+        .bind(group_id)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    pub async fn create_shift(
+        pool: &PgPool,
+        tenant: &str,
+        req: &CreateShiftRequest,
+        created_by: Uuid,
+    ) -> anyhow::Result<StaffShift> {
+        if req.end_time <= req.start_time {
+            return Err(RatioError::ShiftEndBeforeStart.into());
+        }
+        let schema = schema_name(tenant);
+        let is_staff: bool = sqlx::query_scalar(&format!(
+            r#"SELECT EXISTS (SELECT 1 FROM "{schema}".users
+                              WHERE id = $1 AND is_active = TRUE AND role::TEXT <> 'parent')"#
+        ))
+        .bind(req.user_id)
+        .fetch_one(pool)
+        .await?;
+        if !is_staff {
+            return Err(RatioError::NotStaff.into());
+        }
+
+        let id: Uuid = sqlx::query_scalar(&format!(
+            r#"INSERT INTO "{schema}".staff_shifts (user_id, group_id, date, start_time, end_time, created_by)
+               VALUES ($1, $2, $3, $4, $5, $6)
+               RETURNING id"#
+        ))
+        .bind(req.user_id)
+        .bind(req.group_id)
+        .bind(req.date)
+        .bind(req.start_time)
+        .bind(req.end_time)
+        .bind(created_by)
+        .fetch_one(pool)
+        .await?;
+
+        let shifts = Self::list_shifts(pool, tenant, req.date, req.date, Some(req.group_id)).await?;
+        Ok(shifts.into_iter().find(|s| s.id == id).ok_or(RatioError::ShiftNotFound)?)
+    }
+
+    pub async fn delete_shift(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<()> {
+        let schema = schema_name(tenant);
+        let result = sqlx::query(&format!(r#"DELETE FROM "{schema}".staff_shifts WHERE id = $1"#))
+            .bind(id)
+            .execute(pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(RatioError::ShiftNotFound.into());
+        }
+        Ok(())
+    }
+
+    /// Remember the alert of a group for the day; false when one was already sent.
+    pub async fn record_alert(pool: &PgPool, tenant: &str, ratio: &GroupRatio) -> anyhow::Result<bool> {
+        let schema = schema_name(tenant);
+        let result = sqlx::query(&format!(
+            r#"INSERT INTO "{schema}".ratio_alerts (group_id, date, children, educators, required)
+               VALUES ($1, $2, $3, $4, $5)
+               ON CONFLICT (group_id, date) DO NOTHING"#
+        ))
+        .bind(ratio.group_id)
+        .bind(ratio.date)
+        .bind(ratio.children as i32)
+        .bind(ratio.educators as i32)
+        .bind(ratio.required_educators as i32)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn input(label: &str, min: i32, max: Option<i32>, ratio: i32) -> RatioRuleInput {
+        RatioRuleInput { label: label.into(), min_age_months: min, max_age_months: max, children_per_educator: ratio }
+    }
+
+    fn quebec() -> Vec<RatioRule> {
+        [("0-18", 0, Some(18), 5), ("18-48", 18, Some(48), 8), ("48-72", 48, Some(72), 10), ("72+", 72, None, 20)]
+            .into_iter()
+            .map(|(label, min, max, ratio)| RatioRule {
+                id: Uuid::new_v4(),
+                label: label.into(),
+                min_age_months: min,
+                max_age_months: max,
+                children_per_educator: ratio,
+                created_at: Utc::now(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn age_in_completed_months() {
+        assert_eq!(age_in_months(date("2025-04-18"), date("2026-10-18")), 18);
+        assert_eq!(age_in_months(date("2025-04-19"), date("2026-10-18")), 17);
+        assert_eq!(age_in_months(date("2026-10-20"), date("2026-10-18")), 0);
+    }
+
+    #[test]
+    fn mixed_groups_add_up_fractions_of_educators() {
+        let rules = quebec();
+        // 5 babies: exactly one educator
+        assert_eq!(evaluate(&rules, &[6; 5]).1, 1);
+        // 6 babies: two
+        assert_eq!(evaluate(&rules, &[6; 6]).1, 2);
+        // 3 babies (0.6) + 4 toddlers (0.5): two
+        let ages = [6, 6, 6, 24, 24, 24, 24];
+        let (bands, required) = evaluate(&rules, &ages);
+        assert_eq!(required, 2);
+        assert_eq!(bands.iter().map(|b| b.children).collect::<Vec<_>>(), vec![3, 4]);
+        assert_eq!(evaluate(&rules, &[]).1, 0);
+    }
+
+    #[test]
+    fn rules_must_not_overlap() {
+        assert!(validate_rules(&[input("a", 0, Some(18), 5), input("b", 18, None, 8)]).is_ok());
+        assert!(matches!(
+            validate_rules(&[input("a", 0, Some(24), 5), input("b", 18, None, 8)]),
+            Err(RatioError::OverlappingBands(_, _))
+        ));
+        assert!(matches!(validate_rules(&[input("a", 12, Some(6), 5)]), Err(RatioError::InvalidRule(_))));
+        assert!(matches!(validate_rules(&[input("a", 0, None, 0)]), Err(RatioError::InvalidRule(_))));
+        assert!(validate_rules(&[]).is_err());
+    }
+}
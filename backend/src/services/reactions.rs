@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::{
+        auth::AuthenticatedUser,
+        message::{MessageWithSender, ReactionCount, ReactionEvent},
+        user::UserRole,
+    },
+    services::groups::GroupService,
+};
+
+/// Emojis offered under a message, in display order.
+pub const REACTIONS: &[&str] = &["👍", "❤️", "😂", "😮", "😢", "🙏", "✅"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReactionError {
+    #[error("Message introuvable")]
+    NotFound,
+    #[error("Réaction non prise en charge")]
+    UnsupportedEmoji,
+}
+
+/// The palette entry for a client-supplied emoji. Keyboards differ on the
+/// variation selector (U+FE0F), so it is ignored in the comparison.
+pub fn canonical_emoji(emoji: &str) -> Option<&'static str> {
+    let bare = |s: &str| s.trim().replace('\u{FE0F}', "");
+    let wanted = bare(emoji);
+    REACTIONS.iter().copied().find(|r| bare(r) == wanted)
+}
+
+/// Palette position, so that counts always come back in the same order.
+fn rank(emoji: &str) -> usize {
+    REACTIONS.iter().position(|r| *r == emoji).unwrap_or(REACTIONS.len())
+}
+
+pub struct ReactionService;
+
+impl ReactionService {
+    /// Add (or remove) the user's reaction to a message they can see.
+    /// Adding twice or removing a missing reaction is not an error.
+    pub async fn set(
+        pool: &PgPool,
+        tenant: &str,
+        user: &AuthenticatedUser,
+        message_id: Uuid,
+        emoji: &str,
+        added: bool,
+    ) -> anyhow::Result<ReactionEvent> {
+        let emoji = canonical_emoji(emoji).ok_or(ReactionError::UnsupportedEmoji)?;
+        Self::ensure_visible(pool, tenant, user, message_id).await?;
+
+        let schema = schema_name(tenant);
+        let sql = if added {
+            format!(
+                "INSERT INTO {schema}.message_reactions (message_id, user_id, emoji)
+                 VALUES ($1, $2, $3)
+                 ON CONFLICT DO NOTHING"
+            )
+        } else {
+            format!(
+                "DELETE FROM {schema}.message_reactions
+                 WHERE message_id = $1 AND user_id = $2 AND emoji = $3"
+            )
+        };
+        sqlx::query(&sql)
+            .bind(message_id)
+            .bind(user.user_id)
+            .bind(emoji)
+            .execute(pool)
+            .await?;
+
+        let reactions = Self::counts(pool, tenant, user.user_id, &[message_id])
+            .await?
+            .remove(&message_id)
+            .unwrap_or_default();
+
+        Ok(ReactionEvent {
+            message_id,
+            user_id: user.user_id,
+            emoji: emoji.to_string(),
+            added,
+            reactions,
+        })
+    }
+
+    /// Fill in the reactions of a page of messages, as seen by the viewer.
+    pub async fn annotate(
+        pool: &PgPool,
+        tenant: &str,
+        viewer: Uuid,
+        msgs: &mut [MessageWithSender],
+    ) -> anyhow::Result<()> {
+        let ids: Vec<Uuid> = msgs.iter().map(|m| m.id).collect();
+        let mut counts = Self::counts(pool, tenant, viewer, &ids).await?;
+        for msg in msgs.iter_mut() {
+            msg.reactions = counts.remove(&msg.id).unwrap_or_default();
+        }
+        Ok(())
+    }
+
+    /// Reaction counts per message, deleted messages excluded.
+    async fn counts(
+        pool: &PgPool,
+        tenant: &str,
+        viewer: Uuid,
+        message_ids: &[Uuid],
+    ) -> anyhow::Result<HashMap<Uuid, Vec<ReactionCount>>> {
+        if message_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let schema = schema_name(tenant);
+        let rows: Vec<(Uuid, String, i64, bool)> = sqlx::query_as(&format!(
+            "SELECT r.message_id, r.emoji, COUNT(*), BOOL_OR(r.user_id = $2)
+             FROM {schema}.message_reactions r
+             JOIN {schema}.messages m ON m.id = r.message_id
+             WHERE r.message_id = ANY($1) AND m.is_deleted = FALSE
+             GROUP BY r.message_id, r.emoji"
+        ))
+        .bind(message_ids)
+        .bind(viewer)
+        .fetch_all(pool)
+        .await?;
+
+        let mut counts: HashMap<Uuid, Vec<ReactionCount>> = HashMap::new();
+        for (message_id, emoji, count, reacted) in rows {
+            counts.entry(message_id).or_default().push(ReactionCount { emoji, count, reacted });
+        }
+        for list in counts.values_mut() {
+            list.sort_by_key(|c| rank(&c.emoji));
+        }
+        Ok(counts)
+    }
+
+    /// Fails with [`ReactionError::NotFound`] unless the message is live and part of
+    /// a thread the user can read.
+    async fn ensure_visible(
+        pool: &PgPool,
+        tenant: &str,
+        user: &AuthenticatedUser,
+        message_id: Uuid,
+    ) -> anyhow::Result<()> {
+        let schema = schema_name(tenant);
+        let row: Option<(String, Option<Uuid>, Uuid, Option<Uuid>)> = sqlx::query_as(&format!(
+            "SELECT message_type::text, group_id, sender_id, recipient_id
+             FROM {schema}.messages
+             WHERE id = $1 AND is_deleted = FALSE"
+        ))
+        .bind(message_id)
+        .fetch_optional(pool)
+        .await?;
+        let (message_type, group_id, sender_id, recipient_id) = row.ok_or(ReactionError::NotFound)?;
+
+        let visible = match (message_type.as_str(), &user.role) {
+            ("broadcast", _) => true,
+            ("group", UserRole::Parent) => {
+                sqlx::query_scalar(&format!(
+                    "SELECT EXISTS (
+                       SELECT 1 FROM {schema}.child_parents cp
+                       JOIN {schema}.children c ON c.id = cp.child_id
+                       WHERE cp.user_id = $1 AND c.group_id = $2
+                     )"
+                ))
+                .bind(user.user_id)
+                .bind(group_id)
+                .fetch_one(pool)
+                .await?
+            }
+            ("staff", UserRole::Parent) => false,
+            ("group" | "staff", _) => match group_id {
+                Some(g) => GroupService::ensure_group_access(pool, tenant, user, g).await.is_ok(),
+                None => true,
+            },
+            (_, UserRole::Parent) => {
+                sender_id == user.user_id || recipient_id == Some(user.user_id)
+            }
+            _ => true,
+        };
+        if !visible {
+            return Err(ReactionError::NotFound.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_emoji_ignores_variation_selector_and_whitespace() {
+        assert_eq!(canonical_emoji("❤"), Some("❤️"));
+        assert_eq!(canonical_emoji(" ❤️ "), Some("❤️"));
+        assert_eq!(canonical_emoji("👍"), Some("👍"));
+    }
+
+    #[test]
+    fn canonical_emoji_rejects_anything_off_the_palette() {
+        assert_eq!(canonical_emoji("🍕"), None);
+        assert_eq!(canonical_emoji(""), None);
+        assert_eq!(canonical_emoji("👍👍"), None);
+    }
+
+    #[test]
+    fn rank_follows_the_palette() {
+        assert!(rank("👍") < rank("✅"));
+        assert_eq!(rank("🍕"), REACTIONS.len());
+    }
+}
@@ -0,0 +1,184 @@
+use rand::RngCore;
+use redis::aio::MultiplexedConnection;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::{auth::AuthenticatedUser, message::MessageWithSender},
+    services::unread::UnreadService,
+};
+
+/// Seconds a WebSocket ticket can be redeemed after being issued.
+const TICKET_TTL_SECS: u64 = 30;
+/// Open sockets allowed per user (tabs and devices together).
+pub const MAX_CONNECTIONS_PER_USER: i64 = 5;
+/// The connection counter outlives a crashed instance by at most this long; open
+/// sockets refresh it on every ping.
+const CONNECTIONS_TTL_SECS: i64 = 120;
+
+/// Events every socket of the garderie receives: broadcasts, presence, typing.
+pub fn tenant_channel(tenant: &str) -> String {
+    format!("tenant:{tenant}:messages")
+}
+
+/// Events for one user only: messages of threads they take part in.
+pub fn user_channel(tenant: &str, user_id: Uuid) -> String {
+    format!("tenant:{tenant}:user:{user_id}")
+}
+
+fn ticket_key(ticket: &str) -> String {
+    format!("ws_ticket:{ticket}")
+}
+
+fn connections_key(tenant: &str, user_id: Uuid) -> String {
+    format!("ws_connections:{tenant}:{user_id}")
+}
+
+/// Who a WebSocket ticket was issued to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WsTicket {
+    pub user_id: Uuid,
+    pub tenant: String,
+}
+
+pub struct RealtimeService;
+
+impl RealtimeService {
+    /// Issue a single-use ticket opening a WebSocket as the user, so that the access
+    /// token never travels in a URL (and into proxy logs).
+    pub async fn issue_ticket(redis: &mut MultiplexedConnection, user: &AuthenticatedUser) -> anyhow::Result<String> {
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let ticket = hex::encode(bytes);
+        let value = serde_json::to_string(&WsTicket { user_id: user.user_id, tenant: user.tenant.clone() })?;
+        redis::cmd("SET")
+            .arg(ticket_key(&ticket))
+            .arg(value)
+            .arg("EX")
+            .arg(TICKET_TTL_SECS)
+            .query_async::<()>(redis)
+            .await?;
+        Ok(ticket)
+    }
+
+    /// The ticket's holder, once: a redeemed or expired ticket gives nothing.
+    pub async fn redeem_ticket(redis: &mut MultiplexedConnection, ticket: &str) -> Option<WsTicket> {
+        let value: Option<String> = redis::cmd("GETDEL").arg(ticket_key(ticket)).query_async(redis).await.ok()?;
+        serde_json::from_str(&value?).ok()
+    }
+
+    /// Count a new socket of the user; false (and not counted) past the limit.
+    pub async fn open_connection(redis: &mut MultiplexedConnection, tenant: &str, user_id: Uuid) -> bool {
+        let key = connections_key(tenant, user_id);
+        let (count,): (i64,) = match redis::pipe()
+            .incr(&key, 1)
+            .expire(&key, CONNECTIONS_TTL_SECS)
+            .ignore()
+            .query_async(redis)
+            .await
+        {
+            Ok(r) => r,
+            // Redis down: the socket could not subscribe anyway.
+            Err(_) => return true,
+        };
+        if count > MAX_CONNECTIONS_PER_USER {
+            Self::close_connection(redis, tenant, user_id).await;
+            return false;
+        }
+        true
+    }
+
+    /// Keep the connection counter alive while the socket is open.
+    pub async fn refresh_connection(redis: &mut MultiplexedConnection, tenant: &str, user_id: Uuid) {
+        let _: Result<(), _> = redis::cmd("EXPIRE")
+            .arg(connections_key(tenant, user_id))
+            .arg(CONNECTIONS_TTL_SECS)
+            .query_async(redis)
+            .await;
+    }
+
+    pub async fn close_connection(redis: &mut MultiplexedConnection, tenant: &str, user_id: Uuid) {
+        let _: Result<i64, _> = redis::cmd("DECR").arg(connections_key(tenant, user_id)).query_async(redis).await;
+    }
+
+    /// Users who may see a message live; `None` for broadcasts, seen by everyone.
+    pub async fn message_audience(pool: &PgPool, tenant: &str, msg: &MessageWithSender) -> anyhow::Result<Option<Vec<Uuid>>> {
+        Self::thread_audience(pool, tenant, &msg.message_type, msg.sender_id, msg.group_id, msg.recipient_id).await
+    }
+
+    /// Users who follow a thread live, the sender included; `None` for the broadcast
+    /// thread. Staff see every individual thread, so they follow them all.
+    pub async fn thread_audience(
+        pool: &PgPool,
+        tenant: &str,
+        kind: &str,
+        sender_id: Uuid,
+        group_id: Option<Uuid>,
+        recipient_id: Option<Uuid>,
+    ) -> anyhow::Result<Option<Vec<Uuid>>> {
+        if kind == "broadcast" {
+            return Ok(None);
+        }
+        let mut users = vec![sender_id];
+        if let Some(delivery) = UnreadService::delivery(pool, tenant, kind, sender_id, group_id, recipient_id).await? {
+            users.extend(delivery.recipients);
+        }
+        if kind == "individual" {
+            let s = schema_name(tenant);
+            let staff: Vec<Uuid> = sqlx::query_scalar(&format!(
+                "SELECT id FROM {s}.users WHERE role::text != 'parent' AND is_active = TRUE"
+            ))
+            .fetch_all(pool)
+            .await?;
+            users.extend(staff);
+        }
+        users.sort();
+        users.dedup();
+        Ok(Some(users))
+    }
+
+    /// Publish a WebSocket payload about a message to the sockets allowed to see it.
+    pub async fn publish_message(
+        pool: &PgPool,
+        redis: &mut MultiplexedConnection,
+        tenant: &str,
+        msg: &MessageWithSender,
+        payload: &str,
+    ) {
+        match Self::message_audience(pool, tenant, msg).await {
+            Ok(None) => Self::publish_to_tenant(redis, tenant, payload).await,
+            Ok(Some(users)) => Self::publish_to_users(redis, tenant, &users, payload).await,
+            Err(e) => tracing::warn!("Message {} in '{tenant}' not published live: {e}", msg.id),
+        }
+    }
+
+    pub async fn publish_to_tenant(redis: &mut MultiplexedConnection, tenant: &str, payload: &str) {
+        let _: Result<(), _> = redis::cmd("PUBLISH").arg(tenant_channel(tenant)).arg(payload).query_async(redis).await;
+    }
+
+    pub async fn publish_to_users(redis: &mut MultiplexedConnection, tenant: &str, users: &[Uuid], payload: &str) {
+        if users.is_empty() {
+            return;
+        }
+        let mut pipe = redis::pipe();
+        for user_id in users {
+            pipe.cmd("PUBLISH").arg(user_channel(tenant, *user_id)).arg(payload).ignore();
+        }
+        let _: Result<(), _> = pipe.query_async(redis).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_channels_are_scoped_to_the_garderie() {
+        let user = Uuid::nil();
+        assert_eq!(tenant_channel("lucioles"), "tenant:lucioles:messages");
+        assert_eq!(user_channel("lucioles", user), format!("tenant:lucioles:user:{user}"));
+        assert_ne!(user_channel("lucioles", user), user_channel("papillons", user));
+    }
+}
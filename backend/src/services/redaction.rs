@@ -0,0 +1,508 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use image::{codecs::jpeg::JpegEncoder, imageops::FilterType, DynamicImage};
+use serde::Deserialize;
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::{tenant::schema_name, tenant_db::TenantDb},
+    models::media::{MediaRedaction, RedactionRegion},
+    services::encryption::{self, KeyRing},
+};
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_READY: &str = "ready";
+pub const STATUS_FAILED: &str = "failed";
+
+const MAX_REGIONS: usize = 50;
+
+/// Renders given up after this many failures; the request is left `failed`.
+const MAX_ATTEMPTS: i32 = 3;
+
+/// Redactions rendered per tenant in one pass.
+const BATCH_SIZE: i64 = 10;
+
+/// Pause between two passes over the pending redactions.
+const JOB_INTERVAL_SECS: u64 = 60;
+
+/// Longest side of the blurred copy; it is shown in galleries and the lightbox only.
+const MAX_DIMENSION: u32 = 1600;
+
+const JPEG_QUALITY: u8 = 85;
+
+/// Blocks along the longest side of a pixelated region: few enough that a face
+/// cannot be recognised, and that the original cannot be recovered from the copy.
+const PIXEL_BLOCKS: u32 = 8;
+
+/// Detected faces are widened by this fraction of their size on each side, to
+/// cover hair and ears.
+const FACE_MARGIN: f64 = 0.2;
+
+const DETECTION_TIMEOUT_SECS: u64 = 30;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RedactionError {
+    #[error("Photo introuvable")]
+    MediaNotFound,
+    #[error("Seules les photos peuvent être floutées")]
+    NotAPhoto,
+    #[error("Aucune zone à flouter")]
+    NoRegions,
+    #[error("Trop de zones à flouter (maximum {MAX_REGIONS})")]
+    TooManyRegions,
+    #[error("Zone à flouter invalide : les coordonnées vont de 0 à 1")]
+    InvalidRegion,
+    #[error("La détection automatique des visages n'est pas configurée")]
+    DetectionDisabled,
+    #[error("Aucune version floutée pour cette photo")]
+    NotFound,
+}
+
+/// Check regions sent by the client: finite fractions starting inside the photo
+/// with a positive size. Regions running past an edge are cut at it.
+pub fn validate_regions(regions: &[RedactionRegion]) -> Result<Vec<RedactionRegion>, RedactionError> {
+    if regions.len() > MAX_REGIONS {
+        return Err(RedactionError::TooManyRegions);
+    }
+    regions
+        .iter()
+        .map(|r| {
+            let valid = [r.x, r.y, r.width, r.height].iter().all(|v| v.is_finite())
+                && (0.0..1.0).contains(&r.x)
+                && (0.0..1.0).contains(&r.y)
+                && r.width > 0.0
+                && r.height > 0.0;
+            if !valid {
+                return Err(RedactionError::InvalidRegion);
+            }
+            Ok(RedactionRegion {
+                x: r.x,
+                y: r.y,
+                width: r.width.min(1.0 - r.x),
+                height: r.height.min(1.0 - r.y),
+            })
+        })
+        .collect()
+}
+
+/// Grow a detected face by `margin` of its size on each side, within the photo.
+fn widen(region: RedactionRegion, margin: f64) -> RedactionRegion {
+    let x = (region.x - region.width * margin).max(0.0);
+    let y = (region.y - region.height * margin).max(0.0);
+    RedactionRegion {
+        x,
+        y,
+        width: (region.x + region.width * (1.0 + margin)).min(1.0) - x,
+        height: (region.y + region.height * (1.0 + margin)).min(1.0) - y,
+    }
+}
+
+/// A region in pixels of a `width`×`height` image, rounded outwards; None when empty.
+fn pixel_rect(region: &RedactionRegion, width: u32, height: u32) -> Option<(u32, u32, u32, u32)> {
+    let x0 = ((region.x * width as f64).floor() as u32).min(width);
+    let y0 = ((region.y * height as f64).floor() as u32).min(height);
+    let x1 = (((region.x + region.width) * width as f64).ceil() as u32).min(width);
+    let y1 = (((region.y + region.height) * height as f64).ceil() as u32).min(height);
+    (x1 > x0 && y1 > y0).then(|| (x0, y0, x1 - x0, y1 - y0))
+}
+
+/// Pixelate each region of the image: averaged down to a few blocks, then scaled
+/// back up without interpolation.
+pub fn blur_regions(img: &mut DynamicImage, regions: &[RedactionRegion]) {
+    for region in regions {
+        let Some((x, y, w, h)) = pixel_rect(region, img.width(), img.height()) else {
+            continue;
+        };
+        let block = (w.max(h) / PIXEL_BLOCKS).max(1);
+        let small = img
+            .crop_imm(x, y, w, h)
+            .resize_exact(w.div_ceil(block), h.div_ceil(block), FilterType::Triangle);
+        let pixelated = small.resize_exact(w, h, FilterType::Nearest);
+        image::imageops::replace(img, &pixelated, x as i64, y as i64);
+    }
+}
+
+fn encode_jpeg(img: &DynamicImage) -> anyhow::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    img.to_rgb8().write_with_encoder(JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY))?;
+    Ok(out)
+}
+
+/// Client of the face detection service (FACE_DETECTION_URL).
+///
+/// It receives a JPEG as the request body and answers
+/// `{"faces": [{"x", "y", "width", "height"}]}` in fractions of the photo's size.
+pub struct FaceDetector {
+    client: reqwest::Client,
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct Detection {
+    faces: Vec<RedactionRegion>,
+}
+
+impl FaceDetector {
+    pub fn new(config: &Config) -> Option<Self> {
+        let url = config.face_detection_url.clone()?;
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(DETECTION_TIMEOUT_SECS))
+            .build()
+            .ok()?;
+        Some(Self { client, url })
+    }
+
+    /// Faces found in the photo, widened to cover the whole head.
+    pub async fn detect(&self, jpeg: Vec<u8>) -> anyhow::Result<Vec<RedactionRegion>> {
+        let detection: Detection = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "image/jpeg")
+            .body(jpeg)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let faces = validate_regions(&detection.faces)
+            .map_err(|e| anyhow::anyhow!("réponse de détection invalide : {e}"))?;
+        Ok(faces.into_iter().map(|f| widen(f, FACE_MARGIN)).collect())
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct PendingRedaction {
+    id: Uuid,
+    media_id: Uuid,
+    regions: serde_json::Value,
+    source: String,
+    updated_at: chrono::DateTime<chrono::Utc>,
+    redacted_path: Option<String>,
+    storage_path: String,
+    is_encrypted: bool,
+    encryption_iv: Option<Vec<u8>>,
+    encryption_tag: Option<Vec<u8>>,
+    key_version: i32,
+}
+
+fn redaction_cols() -> &'static str {
+    "r.media_id, r.regions, r.source, r.status, r.attempts, r.last_error, r.requested_by,
+     r.created_at, r.updated_at"
+}
+
+pub struct RedactionService;
+
+impl RedactionService {
+    pub async fn get(pool: &PgPool, tenant: &str, media_id: Uuid) -> anyhow::Result<Option<MediaRedaction>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, MediaRedaction>(&format!(
+            "SELECT {} FROM \"{schema}\".media_redactions r WHERE r.media_id = $1",
+            redaction_cols()
+        ))
+        .bind(media_id)
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    /// Ask for a blurred copy of a photo, replacing any earlier request. The copy
+    /// already rendered, if any, is served until the new one is ready.
+    pub async fn request(
+        pool: &PgPool,
+        tenant: &str,
+        media_id: Uuid,
+        user_id: Uuid,
+        regions: &[RedactionRegion],
+        detect: bool,
+        detection_enabled: bool,
+    ) -> anyhow::Result<MediaRedaction> {
+        let schema = schema_name(tenant);
+        let media_type: Option<String> = sqlx::query_scalar(&format!(
+            "SELECT media_type::TEXT FROM \"{schema}\".media WHERE id = $1"
+        ))
+        .bind(media_id)
+        .fetch_optional(pool)
+        .await?;
+        match media_type.as_deref() {
+            None => return Err(RedactionError::MediaNotFound.into()),
+            Some("photo") => {}
+            Some(_) => return Err(RedactionError::NotAPhoto.into()),
+        }
+        if detect && !detection_enabled {
+            return Err(RedactionError::DetectionDisabled.into());
+        }
+        let regions = validate_regions(regions)?;
+        if regions.is_empty() && !detect {
+            return Err(RedactionError::NoRegions.into());
+        }
+
+        sqlx::query(&format!(
+            "INSERT INTO \"{schema}\".media_redactions (media_id, regions, source, requested_by)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (media_id) DO UPDATE
+             SET regions = EXCLUDED.regions, source = EXCLUDED.source, requested_by = EXCLUDED.requested_by,
+                 status = '{STATUS_PENDING}', attempts = 0, last_error = NULL, updated_at = NOW()"
+        ))
+        .bind(media_id)
+        .bind(serde_json::to_value(&regions)?)
+        .bind(if detect { "detected" } else { "manual" })
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+        Self::get(pool, tenant, media_id).await?.ok_or_else(|| RedactionError::NotFound.into())
+    }
+
+    /// Remove a photo's blurred copy; the photo is then hidden again wherever a
+    /// child without consent appears.
+    pub async fn remove(pool: &PgPool, tenant: &str, media_dir: &str, media_id: Uuid) -> anyhow::Result<()> {
+        if Self::discard(pool, tenant, media_dir, &[media_id]).await? == 0 {
+            return Err(RedactionError::NotFound.into());
+        }
+        Ok(())
+    }
+
+    /// Delete the redactions of the given media and their files. Returns how many there were.
+    pub async fn discard(pool: &PgPool, tenant: &str, media_dir: &str, media_ids: &[Uuid]) -> anyhow::Result<usize> {
+        let schema = schema_name(tenant);
+        let paths: Vec<Option<String>> = sqlx::query_scalar(&format!(
+            "DELETE FROM \"{schema}\".media_redactions WHERE media_id = ANY($1) RETURNING redacted_path"
+        ))
+        .bind(media_ids)
+        .fetch_all(pool)
+        .await?;
+        for path in paths.iter().flatten() {
+            let _ = tokio::fs::remove_file(Path::new(media_dir).join(path)).await;
+        }
+        Ok(paths.len())
+    }
+
+    /// Render up to [`BATCH_SIZE`] pending redactions of one tenant. Failures are
+    /// recorded on the request and retried on later passes, up to [`MAX_ATTEMPTS`].
+    pub async fn process_pending(
+        pool: &PgPool,
+        media_dir: &str,
+        keys: &KeyRing,
+        detector: Option<&FaceDetector>,
+        tenant: &str,
+    ) -> anyhow::Result<usize> {
+        let schema = schema_name(tenant);
+        let pending: Vec<PendingRedaction> = sqlx::query_as(&format!(
+            "SELECT r.id, r.media_id, r.regions, r.source, r.updated_at, r.redacted_path,
+                    m.storage_path, m.is_encrypted, m.encryption_iv, m.encryption_tag, m.key_version
+             FROM \"{schema}\".media_redactions r
+             JOIN \"{schema}\".media m ON m.id = r.media_id
+             WHERE r.status = '{STATUS_PENDING}'
+             ORDER BY r.updated_at
+             LIMIT $1"
+        ))
+        .bind(BATCH_SIZE)
+        .fetch_all(pool)
+        .await?;
+
+        let mut rendered = 0;
+        for row in pending {
+            match Self::render(pool, &schema, media_dir, keys, detector, tenant, &row).await {
+                Ok(true) => rendered += 1,
+                Ok(false) => {}
+                Err(e) => {
+                    warn!("Redaction of media {} in '{tenant}' failed: {e}", row.media_id);
+                    sqlx::query(&format!(
+                        "UPDATE \"{schema}\".media_redactions
+                         SET attempts = attempts + 1, last_error = $2,
+                             status = CASE WHEN attempts + 1 >= $3 THEN '{STATUS_FAILED}' ELSE status END
+                         WHERE id = $1 AND updated_at = $4"
+                    ))
+                    .bind(row.id)
+                    .bind(e.to_string())
+                    .bind(MAX_ATTEMPTS)
+                    .bind(row.updated_at)
+                    .execute(pool)
+                    .await?;
+                }
+            }
+        }
+        Ok(rendered)
+    }
+
+    /// Write the blurred copy next to the original, under the current key, and swap it
+    /// in. Returns false when the request changed meanwhile (the copy is dropped).
+    async fn render(
+        pool: &PgPool,
+        schema: &str,
+        media_dir: &str,
+        keys: &KeyRing,
+        detector: Option<&FaceDetector>,
+        tenant: &str,
+        row: &PendingRedaction,
+    ) -> anyhow::Result<bool> {
+        let mut bytes = tokio::fs::read(Path::new(media_dir).join(&row.storage_path)).await?;
+        if row.is_encrypted {
+            let (Some(iv), Some(tag)) = (&row.encryption_iv, &row.encryption_tag) else {
+                anyhow::bail!("missing encryption metadata");
+            };
+            bytes = encryption::decrypt_file(&bytes, iv, tag, &keys.tenant_key(row.key_version, tenant)?)?;
+        }
+        let img = tokio::task::spawn_blocking(move || -> anyhow::Result<DynamicImage> {
+            let img = image::load_from_memory(&bytes)?;
+            Ok(if img.width().max(img.height()) > MAX_DIMENSION {
+                img.resize(MAX_DIMENSION, MAX_DIMENSION, FilterType::Lanczos3)
+            } else {
+                img
+            })
+        })
+        .await??;
+
+        let mut regions: Vec<RedactionRegion> = serde_json::from_value(row.regions.clone())?;
+        if row.source == "detected" {
+            let detector = detector.ok_or(RedactionError::DetectionDisabled)?;
+            let probe = encode_jpeg(&img)?;
+            regions = detector.detect(probe).await?;
+        }
+        let found = serde_json::to_value(&regions)?;
+        let jpeg = tokio::task::spawn_blocking(move || {
+            let mut img = img;
+            blur_regions(&mut img, &regions);
+            encode_jpeg(&img)
+        })
+        .await??;
+
+        let (key_version, key) = keys.current_tenant_key(tenant)?;
+        let (ciphertext, iv, tag) = encryption::encrypt_file(&jpeg, &key)?;
+        let name = {
+            use rand::Rng;
+            hex::encode(rand::thread_rng().gen::<[u8; 16]>())
+        };
+        let rel_path = match row.storage_path.rsplit_once('/') {
+            Some((dir, _)) => format!("{dir}/{name}"),
+            None => name,
+        };
+        let full_path = Path::new(media_dir).join(&rel_path);
+        tokio::fs::write(&full_path, &ciphertext).await?;
+
+        let swapped = sqlx::query(&format!(
+            "UPDATE \"{schema}\".media_redactions
+             SET status = '{STATUS_READY}', regions = $2, redacted_path = $3, encryption_iv = $4,
+                 encryption_tag = $5, key_version = $6, last_error = NULL, updated_at = NOW()
+             WHERE id = $1 AND updated_at = $7 AND status = '{STATUS_PENDING}'"
+        ))
+        .bind(row.id)
+        .bind(found)
+        .bind(&rel_path)
+        .bind(&iv)
+        .bind(&tag)
+        .bind(key_version)
+        .bind(row.updated_at)
+        .execute(pool)
+        .await?
+        .rows_affected()
+            == 1;
+
+        let stale = if swapped { row.redacted_path.as_deref() } else { Some(rel_path.as_str()) };
+        if let Some(path) = stale {
+            let _ = tokio::fs::remove_file(Path::new(media_dir).join(path)).await;
+        }
+        Ok(swapped)
+    }
+}
+
+/// Spawn a background task rendering the blurred copies requested in every garderie.
+pub fn start(pool: PgPool, config: Arc<Config>) {
+    let detector = FaceDetector::new(&config);
+    if detector.is_none() {
+        info!("Face detection disabled (FACE_DETECTION_URL not set): only drawn regions are blurred");
+    }
+
+    tokio::spawn(async move {
+        let keys = match KeyRing::from_config(&config) {
+            Ok(k) => k,
+            Err(e) => {
+                error!("Redaction job disabled: {e}");
+                return;
+            }
+        };
+
+        loop {
+            tokio::time::sleep(Duration::from_secs(JOB_INTERVAL_SECS)).await;
+            let Some(_job) = crate::services::worker::job().await else {
+                break;
+            };
+
+            let tenants: Vec<String> = match sqlx::query_scalar("SELECT slug FROM public.garderies WHERE archived_at IS NULL ORDER BY slug")
+                .fetch_all(&pool)
+                .await
+            {
+                Ok(rows) => rows,
+                Err(e) => {
+                    warn!("Redaction job: failed to query tenants: {e}");
+                    continue;
+                }
+            };
+
+            for slug in &tenants {
+                TenantDb::scope(slug.clone(), async {
+                    match RedactionService::process_pending(&pool, &config.media_dir, &keys, detector.as_ref(), slug).await {
+                        Ok(n) if n > 0 => info!("Redaction: {n} blurred copies rendered for '{slug}'"),
+                        Ok(_) => {}
+                        Err(e) => warn!("Redaction job: pass over '{slug}' failed: {e}"),
+                    }
+                })
+                .await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    fn region(x: f64, y: f64, width: f64, height: f64) -> RedactionRegion {
+        RedactionRegion { x, y, width, height }
+    }
+
+    #[test]
+    fn regions_are_checked_and_cut_at_the_edges() {
+        let cut = validate_regions(&[region(0.5, 0.8, 0.8, 0.5)]).unwrap();
+        assert_eq!((cut[0].x, cut[0].y, cut[0].width), (0.5, 0.8, 0.5));
+        assert!((cut[0].height - 0.2).abs() < 1e-9);
+
+        for bad in [region(-0.1, 0.0, 0.5, 0.5), region(0.0, 1.0, 0.5, 0.5), region(0.1, 0.1, 0.0, 0.2), region(f64::NAN, 0.0, 0.1, 0.1)] {
+            assert!(matches!(validate_regions(&[bad]), Err(RedactionError::InvalidRegion)));
+        }
+        let many = vec![region(0.1, 0.1, 0.1, 0.1); MAX_REGIONS + 1];
+        assert!(matches!(validate_regions(&many), Err(RedactionError::TooManyRegions)));
+    }
+
+    #[test]
+    fn detected_faces_are_widened_within_the_photo() {
+        let face = widen(region(0.0, 0.5, 0.2, 0.2), 0.25);
+        assert_eq!(face.x, 0.0);
+        assert!((face.y - 0.45).abs() < 1e-9);
+        assert!((face.width - 0.25).abs() < 1e-9);
+        assert!((face.height - 0.3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn only_the_regions_are_pixelated() {
+        // A noisy photo: every pixel differs from its neighbours
+        let mut img = DynamicImage::ImageRgb8(RgbImage::from_fn(64, 64, |x, y| {
+            Rgb([(x * 37 % 256) as u8, (y * 53 % 256) as u8, ((x * y) % 256) as u8])
+        }));
+        let original = img.clone();
+        blur_regions(&mut img, &[region(0.0, 0.0, 0.5, 0.5)]);
+
+        let (blurred, kept) = (img.to_rgb8(), original.to_rgb8());
+        // Inside: 8×8 blocks of one colour, no longer the original pixels
+        assert_eq!(blurred.get_pixel(0, 0), blurred.get_pixel(3, 3));
+        assert_ne!(blurred.get_pixel(1, 2), kept.get_pixel(1, 2));
+        // Outside: untouched
+        assert_eq!(blurred.get_pixel(40, 40), kept.get_pixel(40, 40));
+        assert_eq!(blurred.get_pixel(10, 50), kept.get_pixel(10, 50));
+    }
+}
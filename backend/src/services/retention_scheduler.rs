@@ -0,0 +1,58 @@
+use chrono::{Local, Timelike};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::config::Config;
+use crate::db::tenant_db::TenantDb;
+use crate::services::backup_scheduler::secs_until;
+use crate::services::retention::RetentionService;
+
+/// Local time of the nightly purge (seconds after midnight): 4:15.
+const PURGE_AT: u32 = 4 * 3600 + 15 * 60;
+
+/// Spawn a background task that applies, every night, the retention periods of each
+/// garderie, or only reports what is due where purging is not turned on.
+pub fn start(pool: PgPool, config: Arc<Config>) {
+    tokio::spawn(async move {
+        loop {
+            let now = Local::now();
+            let secs_today = now.hour() * 3600 + now.minute() * 60 + now.second();
+            tokio::time::sleep(tokio::time::Duration::from_secs(secs_until(secs_today, PURGE_AT))).await;
+            let Some(_job) = crate::services::worker::job().await else {
+                break;
+            };
+
+            let tenants: Vec<String> = match sqlx::query_scalar(
+                "SELECT slug FROM public.garderies WHERE is_active = TRUE AND slug != 'demo'",
+            )
+            .fetch_all(&pool)
+            .await
+            {
+                Ok(rows) => rows,
+                Err(e) => {
+                    warn!("Retention scheduler: failed to query tenants: {e}");
+                    continue;
+                }
+            };
+
+            for slug in &tenants {
+                TenantDb::scope(slug.clone(), async {
+                    match RetentionService::purge(&pool, slug, &config.media_dir).await {
+                        Ok(r) => info!(
+                            "Retention scheduler: '{slug}'{}{} {} journals, {} events, {} media, {} messages",
+                            if r.held { " (legal hold)" } else { "" },
+                            if r.dry_run { " would delete" } else { " deleted" },
+                            r.journals,
+                            r.journal_events,
+                            r.media,
+                            r.messages
+                        ),
+                        Err(e) => warn!("Retention scheduler: purge failed for '{slug}': {e}"),
+                    }
+                })
+                .await;
+            }
+        }
+    });
+}
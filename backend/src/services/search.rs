@@ -0,0 +1,170 @@
+use sqlx::PgPool;
+
+use crate::{
+    db::tenant::{
+        schema_name, CHILD_SEARCH_VECTOR, DOCUMENT_SEARCH_VECTOR, MESSAGE_SEARCH_VECTOR, USER_SEARCH_VECTOR,
+    },
+    models::{
+        auth::AuthenticatedUser,
+        search::{SearchHit, SEARCH_KINDS},
+    },
+    services::groups::GroupService,
+};
+
+pub const DEFAULT_LIMIT: i64 = 20;
+pub const MAX_LIMIT: i64 = 50;
+
+/// Words of a query beyond this are ignored.
+const MAX_TERMS: usize = 8;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SearchError {
+    #[error("La recherche est vide")]
+    EmptyQuery,
+    #[error("Type de résultat inconnu : {0}")]
+    UnknownKind(String),
+}
+
+/// Turns free text into a prefix `tsquery` matching every word, so that "emm tre"
+/// finds "Emma Tremblay" while typing. Punctuation is dropped, which also keeps
+/// tsquery operators out of user input.
+pub fn prefix_tsquery(q: &str) -> Option<String> {
+    let terms: Vec<String> = q
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .take(MAX_TERMS)
+        .map(|t| format!("{}:*", t.to_lowercase()))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" & "))
+}
+
+/// Kinds requested in `types`, in the order of `SEARCH_KINDS`; all of them when omitted.
+pub fn parse_kinds(types: Option<&str>) -> Result<Vec<&'static str>, SearchError> {
+    let Some(types) = types.map(str::trim).filter(|t| !t.is_empty()) else {
+        return Ok(SEARCH_KINDS.to_vec());
+    };
+    let requested: Vec<&str> = types.split(',').map(str::trim).filter(|t| !t.is_empty()).collect();
+    if let Some(unknown) = requested.iter().find(|t| !SEARCH_KINDS.contains(t)) {
+        return Err(SearchError::UnknownKind(unknown.to_string()));
+    }
+    Ok(SEARCH_KINDS.iter().copied().filter(|k| requested.contains(k)).collect())
+}
+
+/// One ranked query per kind, each bound to `$1` (tsquery text), `$2` (groups of
+/// an assigned educator, NULL for full access) and `$3` (limit).
+fn kind_query(kind: &str, schema: &str) -> String {
+    match kind {
+        "child" => format!(
+            "SELECT 'child' AS kind, c.id, c.first_name || ' ' || c.last_name AS title, g.name AS snippet,
+                    ts_rank({CHILD_SEARCH_VECTOR}, to_tsquery('simple', $1)) AS rank, c.created_at AS date,
+                    NULL::TEXT AS thread_kind, NULL::UUID AS thread_id
+             FROM {schema}.children c
+             LEFT JOIN {schema}.groups g ON g.id = c.group_id
+             WHERE {CHILD_SEARCH_VECTOR} @@ to_tsquery('simple', $1)
+               AND c.is_deleted = FALSE
+               AND ($2::UUID[] IS NULL OR c.group_id = ANY($2))
+             ORDER BY rank DESC LIMIT $3"
+        ),
+        "parent" => format!(
+            "SELECT 'parent' AS kind, u.id, u.first_name || ' ' || u.last_name AS title, u.email AS snippet,
+                    ts_rank({USER_SEARCH_VECTOR}, to_tsquery('simple', $1)) AS rank, u.created_at AS date,
+                    NULL::TEXT AS thread_kind, NULL::UUID AS thread_id
+             FROM {schema}.users u
+             WHERE {USER_SEARCH_VECTOR} @@ to_tsquery('simple', $1)
+               AND u.role = 'parent' AND u.is_active = TRUE
+               AND ($2::UUID[] IS NULL OR EXISTS (
+                   SELECT 1 FROM {schema}.child_parents cp
+                   JOIN {schema}.children c ON c.id = cp.child_id
+                   WHERE cp.user_id = u.id AND c.group_id = ANY($2)
+               ))
+             ORDER BY rank DESC LIMIT $3"
+        ),
+        "document" => format!(
+            "SELECT 'document' AS kind, d.id, d.title, d.original_filename AS snippet,
+                    ts_rank({DOCUMENT_SEARCH_VECTOR}, to_tsquery('french', $1)) AS rank, d.created_at AS date,
+                    NULL::TEXT AS thread_kind, NULL::UUID AS thread_id
+             FROM {schema}.documents d
+             WHERE {DOCUMENT_SEARCH_VECTOR} @@ to_tsquery('french', $1)
+               AND d.is_deleted = FALSE
+               AND ($2::UUID[] IS NULL OR d.group_id IS NULL OR d.group_id = ANY($2))
+             ORDER BY rank DESC LIMIT $3"
+        ),
+        _ => format!(
+            "SELECT 'message' AS kind, m.id, s.first_name || ' ' || s.last_name AS title,
+                    ts_headline('french', m.content, to_tsquery('french', $1),
+                                'StartSel=**, StopSel=**, MaxWords=25, MinWords=10') AS snippet,
+                    ts_rank({MESSAGE_SEARCH_VECTOR}, to_tsquery('french', $1)) AS rank, m.created_at AS date,
+                    m.message_type::TEXT AS thread_kind,
+                    CASE m.message_type::TEXT
+                        WHEN 'group' THEN m.group_id
+                        WHEN 'staff' THEN m.group_id
+                        WHEN 'individual' THEN CASE WHEN s.role = 'parent' THEN m.sender_id ELSE m.recipient_id END
+                    END AS thread_id
+             FROM {schema}.messages m
+             JOIN {schema}.users s ON s.id = m.sender_id
+             WHERE {MESSAGE_SEARCH_VECTOR} @@ to_tsquery('french', $1)
+               AND m.is_deleted = FALSE
+               AND ($2::UUID[] IS NULL OR m.message_type::TEXT NOT IN ('group', 'staff')
+                    OR m.group_id IS NULL OR m.group_id = ANY($2))
+             ORDER BY rank DESC LIMIT $3"
+        ),
+    }
+}
+
+pub struct SearchService;
+
+impl SearchService {
+    /// Children, parents, document titles and messages matching `q`, best first.
+    /// Educators assigned to groups only find what belongs to their groups.
+    pub async fn search(
+        pool: &PgPool,
+        tenant: &str,
+        user: &AuthenticatedUser,
+        q: &str,
+        kinds: &[&str],
+        limit: i64,
+    ) -> anyhow::Result<Vec<SearchHit>> {
+        let tsquery = prefix_tsquery(q).ok_or(SearchError::EmptyQuery)?;
+        if kinds.is_empty() {
+            return Ok(Vec::new());
+        }
+        let scope = GroupService::educator_scope(pool, tenant, user).await?;
+
+        let schema = schema_name(tenant);
+        let union = kinds
+            .iter()
+            .map(|kind| format!("({})", kind_query(kind, &schema)))
+            .collect::<Vec<_>>()
+            .join(" UNION ALL ");
+        Ok(sqlx::query_as::<_, SearchHit>(&format!(
+            "SELECT * FROM ({union}) hits ORDER BY rank DESC, date DESC LIMIT $3"
+        ))
+        .bind(&tsquery)
+        .bind(scope)
+        .bind(limit.clamp(1, MAX_LIMIT))
+        .fetch_all(pool)
+        .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queries_become_prefix_terms() {
+        assert_eq!(prefix_tsquery("Emm  Tre").as_deref(), Some("emm:* & tre:*"));
+        assert_eq!(prefix_tsquery("l'hôpital").as_deref(), Some("l:* & hôpital:*"));
+        assert_eq!(prefix_tsquery("a & !b | c:*").as_deref(), Some("a:* & b:* & c:*"));
+        assert_eq!(prefix_tsquery(" ' & ").as_deref(), None);
+        assert_eq!(prefix_tsquery("a b c d e f g h i j").unwrap().matches(":*").count(), MAX_TERMS);
+    }
+
+    #[test]
+    fn type_filters_are_validated() {
+        assert_eq!(parse_kinds(None).unwrap(), SEARCH_KINDS);
+        assert_eq!(parse_kinds(Some(" ")).unwrap(), SEARCH_KINDS);
+        assert_eq!(parse_kinds(Some("message, child")).unwrap(), vec!["child", "message"]);
+        assert!(matches!(parse_kinds(Some("child,invoice")), Err(SearchError::UnknownKind(k)) if k == "invoice"));
+    }
+}
@@ -0,0 +1,159 @@
+use chrono::{Duration, Local, Timelike, Utc};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::models::tenant::TenantBranding;
+use crate::db::tenant_db::TenantDb;
+use crate::services::branding::BrandingService;
+use crate::services::documents::DocumentService;
+use crate::services::email::EmailService;
+
+/// Minimum delay between two reminders for the same (document, parent) pair.
+const REMINDER_INTERVAL_SECS: u64 = 3 * 86_400;
+
+/// Spawn a background task that wakes up daily at 10:00 AM and emails parents
+/// who still have unsigned documents requiring a signature.
+/// Documents younger than 24 hours are skipped (the upload notification covers them).
+/// Redis keys (TTL 3 days) space out reminders for the same document and parent.
+pub fn start(
+    pool: PgPool,
+    email: Option<Arc<EmailService>>,
+    redis: redis::Client,
+    app_base_url: String,
+) {
+    tokio::spawn(async move {
+        loop {
+            // Sleep until next 10:00 AM
+            let now = Local::now();
+            let target_secs = 10 * 3600;
+            let secs_today = now.hour() * 3600 + now.minute() * 60 + now.second();
+            let sleep_secs = if secs_today < target_secs {
+                (target_secs - secs_today) as u64
+            } else {
+                (86400 - secs_today + target_secs) as u64
+            };
+            tokio::time::sleep(tokio::time::Duration::from_secs(sleep_secs)).await;
+            let Some(_job) = crate::services::worker::job().await else {
+                break;
+            };
+
+            let Some(ref email_svc) = email else {
+                continue;
+            };
+
+            let mut redis_conn = match redis.get_multiplexed_async_connection().await {
+                Ok(c) => c,
+                Err(e) => {
+                    warn!("Signature scheduler: Redis unavailable: {e}");
+                    continue;
+                }
+            };
+
+            let tenants: Vec<String> = match sqlx::query_scalar(
+                "SELECT slug FROM public.garderies WHERE is_active = TRUE AND slug != 'demo'",
+            )
+            .fetch_all(&pool)
+            .await
+            {
+                Ok(rows) => rows,
+                Err(e) => {
+                    warn!("Signature scheduler: failed to query tenants: {e}");
+                    continue;
+                }
+            };
+
+            for slug in tenants {
+                TenantDb::scope(slug.clone(), async {
+                    let (garderie_name, branding) = BrandingService::for_email(&pool, &slug).await;
+                    let sent = remind_tenant(
+                        &pool,
+                        email_svc,
+                        &mut redis_conn,
+                        &app_base_url,
+                        &slug,
+                        &garderie_name,
+                        &branding,
+                    )
+                    .await;
+                    if sent > 0 {
+                        info!("Signature scheduler: {sent} reminder(s) sent for '{slug}'");
+                    }
+                })
+                .await;
+            }
+        }
+    });
+}
+
+async fn remind_tenant(
+    pool: &PgPool,
+    email_svc: &EmailService,
+    redis: &mut redis::aio::MultiplexedConnection,
+    app_base_url: &str,
+    slug: &str,
+    garderie_name: &str,
+    branding: &TenantBranding,
+) -> usize {
+    let outstanding = match DocumentService::list_outstanding_signatures(pool, slug, None).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            warn!("Signature scheduler: failed to list outstanding signatures for '{slug}': {e}");
+            return 0;
+        }
+    };
+
+    let app_url = if let Some(idx) = app_base_url.find("://") {
+        let scheme = &app_base_url[..idx];
+        let domain = &app_base_url[idx + 3..];
+        format!("{scheme}://{slug}.{domain}/fr/parent/documents")
+    } else {
+        format!("https://{slug}.{app_base_url}/fr/parent/documents")
+    };
+
+    let cutoff = Utc::now() - Duration::hours(24);
+    let mut sent = 0;
+
+    for row in outstanding {
+        if row.document_created_at > cutoff {
+            continue;
+        }
+
+        let key = format!("signature:reminder:{slug}:{}:{}", row.document_id, row.user_id);
+        let newly_set: Option<String> = redis::cmd("SET")
+            .arg(&key)
+            .arg("1")
+            .arg("NX")
+            .arg("EX")
+            .arg(REMINDER_INTERVAL_SECS)
+            .query_async(redis)
+            .await
+            .unwrap_or(None);
+
+        if newly_set.is_none() {
+            continue;
+        }
+
+        let name = format!("{} {}", row.first_name, row.last_name);
+        match email_svc
+            .send_signature_reminder(
+                slug,
+                &row.email,
+                &name,
+                &row.document_title,
+                &app_url,
+                garderie_name,
+                branding,
+            )
+            .await
+        {
+            Ok(_) => sent += 1,
+            Err(e) => warn!(
+                "Signature scheduler: failed to remind {} for document {}: {e}",
+                row.email, row.document_id
+            ),
+        }
+    }
+
+    sent
+}
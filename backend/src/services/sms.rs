@@ -0,0 +1,100 @@
+use anyhow::Context;
+use reqwest::Client;
+
+use crate::config::Config;
+
+/// Text messages sent through the Twilio REST API.
+pub struct SmsService {
+    client: Client,
+    account_sid: String,
+    auth_token: String,
+    from: String,
+}
+
+impl SmsService {
+    /// Returns None if Twilio is not fully configured.
+    pub fn new(config: &Config) -> Option<Self> {
+        Some(Self {
+            client: Client::new(),
+            account_sid: config.twilio_account_sid.clone()?,
+            auth_token: config.twilio_auth_token.clone()?,
+            from: config.twilio_from_number.clone()?,
+        })
+    }
+
+    async fn send(&self, to: &str, body: &str) -> anyhow::Result<()> {
+        let url = format!(
+            "https://api.twilio.com/2010-04-01/Accounts/{}/Messages.json",
+            self.account_sid
+        );
+        let res = self
+            .client
+            .post(&url)
+            .basic_auth(&self.account_sid, Some(&self.auth_token))
+            .form(&[("To", to), ("From", self.from.as_str()), ("Body", body)])
+            .send()
+            .await
+            .context("Twilio request failed")?;
+
+        if !res.status().is_success() {
+            let status = res.status();
+            let detail = res.text().await.unwrap_or_default();
+            anyhow::bail!("Twilio returned {status}: {detail}");
+        }
+        Ok(())
+    }
+
+    pub async fn send_2fa_code(&self, to: &str, code: &str, garderie_name: &str) -> anyhow::Result<()> {
+        self.send(
+            to,
+            &format!("{garderie_name} : votre code de connexion est {code}. Il expire dans 15 minutes."),
+        )
+        .await
+    }
+
+    /// Urgent broadcast from the garderie; the full message stays in the app.
+    pub async fn send_urgent_message(&self, to: &str, garderie_name: &str, content: &str) -> anyhow::Result<()> {
+        const MAX_CHARS: usize = 300;
+        let excerpt: String = if content.chars().count() > MAX_CHARS {
+            format!("{}…", content.chars().take(MAX_CHARS).collect::<String>())
+        } else {
+            content.to_string()
+        };
+        self.send(to, &format!("[URGENT] {garderie_name} : {excerpt}")).await
+    }
+}
+
+/// Normalizes a phone number to E.164. Ten-digit numbers are taken as North American.
+pub fn normalize_phone(raw: &str) -> Option<String> {
+    let trimmed = raw.trim();
+    let has_plus = trimmed.starts_with('+');
+    if trimmed
+        .chars()
+        .any(|c| !(c.is_ascii_digit() || " -.()+".contains(c)))
+    {
+        return None;
+    }
+    let digits: String = trimmed.chars().filter(|c| c.is_ascii_digit()).collect();
+
+    let e164 = match (has_plus, digits.len()) {
+        (true, 8..=15) => format!("+{digits}"),
+        (false, 10) => format!("+1{digits}"),
+        (false, 11) if digits.starts_with('1') => format!("+{digits}"),
+        _ => return None,
+    };
+    Some(e164)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_phone() {
+        assert_eq!(normalize_phone("(514) 555-0100").as_deref(), Some("+15145550100"));
+        assert_eq!(normalize_phone("1 514 555 0100").as_deref(), Some("+15145550100"));
+        assert_eq!(normalize_phone("+33 6 12 34 56 78").as_deref(), Some("+33612345678"));
+        assert_eq!(normalize_phone("555-0100"), None);
+        assert_eq!(normalize_phone("514-555-O100"), None);
+    }
+}
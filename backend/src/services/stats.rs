@@ -0,0 +1,243 @@
+use std::collections::HashSet;
+
+use chrono::{Duration, NaiveDate};
+use sqlx::PgPool;
+
+use crate::{
+    db::tenant::schema_name,
+    models::stats::{EducatorCompletion, StatsDay, StatsTotals},
+};
+
+/// Longest period served by GET /stats.
+pub const MAX_PERIOD_DAYS: i64 = 366;
+
+/// Days recomputed by the nightly run: invitations accepted and journals sent late
+/// still land in the day they belong to.
+pub const REFRESH_DAYS: i64 = 7;
+
+const DAY_COLS: &str = "date, active_parents, messages, media_bytes, logins, failed_logins, \
+                        invitations_sent, invitations_accepted";
+
+#[derive(Debug, thiserror::Error)]
+pub enum StatsError {
+    #[error("Période invalide (au plus {MAX_PERIOD_DAYS} jours, terminée avant aujourd'hui)")]
+    InvalidPeriod,
+}
+
+/// `part / whole` in percent, one decimal.
+pub fn rate(part: i64, whole: i64) -> Option<f64> {
+    (whole > 0).then(|| (part as f64 / whole as f64 * 1000.0).round() / 10.0)
+}
+
+/// Days of `[from, to]` without a rollup.
+pub fn missing_days(from: NaiveDate, to: NaiveDate, have: &HashSet<NaiveDate>) -> Vec<NaiveDate> {
+    from.iter_days().take_while(|d| *d <= to).filter(|d| !have.contains(d)).collect()
+}
+
+/// Sums over the period; gauges (active parents, media) are taken on its last day.
+pub fn totals(days: &[StatsDay]) -> StatsTotals {
+    let sum = |f: fn(&StatsDay) -> i32| days.iter().map(|d| f(d) as i64).sum::<i64>();
+    let invitations_sent = sum(|d| d.invitations_sent);
+    let invitations_accepted = sum(|d| d.invitations_accepted);
+    let last = days.iter().max_by_key(|d| d.date);
+    StatsTotals {
+        active_parents: last.map_or(0, |d| d.active_parents),
+        messages: sum(|d| d.messages),
+        media_bytes: last.map_or(0, |d| d.media_bytes),
+        logins: sum(|d| d.logins),
+        failed_logins: sum(|d| d.failed_logins),
+        invitations_sent,
+        invitations_accepted,
+        invitation_acceptance_rate: rate(invitations_accepted, invitations_sent),
+    }
+}
+
+pub struct StatsService;
+
+impl StatsService {
+    /// Compute (or recompute) the rollups of `date`, which should be over.
+    pub async fn compute_day(pool: &PgPool, tenant: &str, date: NaiveDate) -> anyhow::Result<()> {
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(&format!(
+            r#"INSERT INTO "{schema}".stats_daily ({DAY_COLS})
+               SELECT $1,
+                   (SELECT COUNT(DISTINCT e.user_id) FROM "{schema}".login_events e
+                      JOIN "{schema}".users u ON u.id = e.user_id AND u.role = 'parent'
+                     WHERE e.success AND e.created_at >= $1::date - 29 AND e.created_at < $1::date + 1),
+                   (SELECT COUNT(*) FROM "{schema}".messages
+                     WHERE created_at >= $1::date AND created_at < $1::date + 1),
+                   (SELECT COALESCE(SUM(size_bytes), 0)::BIGINT FROM (
+                       SELECT DISTINCT ON (storage_path) size_bytes FROM "{schema}".media
+                        WHERE created_at < $1::date + 1 AND NOT is_deleted
+                   ) stored),
+                   (SELECT COUNT(*) FILTER (WHERE success) FROM "{schema}".login_events
+                     WHERE created_at >= $1::date AND created_at < $1::date + 1),
+                   (SELECT COUNT(*) FILTER (WHERE NOT success) FROM "{schema}".login_events
+                     WHERE created_at >= $1::date AND created_at < $1::date + 1),
+                   (SELECT COUNT(*) FROM "{schema}".invitation_tokens
+                     WHERE created_at >= $1::date AND created_at < $1::date + 1),
+                   (SELECT COUNT(*) FILTER (WHERE used) FROM "{schema}".invitation_tokens
+                     WHERE created_at >= $1::date AND created_at < $1::date + 1)
+               ON CONFLICT ON CONSTRAINT stats_daily_pkey DO UPDATE SET
+                   active_parents = EXCLUDED.active_parents, messages = EXCLUDED.messages,
+                   media_bytes = EXCLUDED.media_bytes, logins = EXCLUDED.logins,
+                   failed_logins = EXCLUDED.failed_logins, invitations_sent = EXCLUDED.invitations_sent,
+                   invitations_accepted = EXCLUDED.invitations_accepted, computed_at = NOW()"#
+        ))
+        .bind(date)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(&format!(r#"DELETE FROM "{schema}".stats_daily_educators WHERE date = $1"#))
+            .bind(date)
+            .execute(&mut *tx)
+            .await?;
+
+        // Expected: children of the educator's groups checked in that day; completed: those
+        // whose journal was sent.
+        sqlx::query(&format!(
+            r#"INSERT INTO "{schema}".stats_daily_educators (date, user_id, expected, completed)
+               SELECT $1, eg.user_id, COUNT(DISTINCT a.child_id), COUNT(DISTINCT j.child_id)
+               FROM "{schema}".educator_groups eg
+               JOIN "{schema}".users u ON u.id = eg.user_id AND u.role = 'educateur'
+               JOIN "{schema}".children c ON c.group_id = eg.group_id
+               JOIN "{schema}".attendance a ON a.child_id = c.id AND a.date = $1
+                    AND a.status IN ('present', 'present_hors_contrat')
+               LEFT JOIN "{schema}".daily_journals j ON j.child_id = c.id AND j.date = $1
+                    AND j.sent_at IS NOT NULL
+               GROUP BY eg.user_id"#
+        ))
+        .bind(date)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Rollups of `[from, to]`, computing the days not rolled up yet. Days before the
+    /// garderie was created are skipped.
+    pub async fn days(pool: &PgPool, tenant: &str, from: NaiveDate, to: NaiveDate) -> anyhow::Result<Vec<StatsDay>> {
+        let schema = schema_name(tenant);
+        let created: Option<NaiveDate> =
+            sqlx::query_scalar("SELECT created_at::date FROM public.garderies WHERE slug = $1")
+                .bind(tenant)
+                .fetch_optional(pool)
+                .await?;
+        let from = created.map_or(from, |c| from.max(c));
+
+        let have: HashSet<NaiveDate> = sqlx::query_scalar(&format!(
+            r#"SELECT date FROM "{schema}".stats_daily WHERE date BETWEEN $1 AND $2"#
+        ))
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .collect();
+        for date in missing_days(from, to, &have) {
+            Self::compute_day(pool, tenant, date).await?;
+        }
+
+        Ok(sqlx::query_as::<_, StatsDay>(&format!(
+            r#"SELECT {DAY_COLS} FROM "{schema}".stats_daily WHERE date BETWEEN $1 AND $2 ORDER BY date"#
+        ))
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Journal completion per educator over `[from, to]`, from the rollups (call
+    /// [`Self::days`] first).
+    pub async fn educators(
+        pool: &PgPool,
+        tenant: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> anyhow::Result<Vec<EducatorCompletion>> {
+        let schema = schema_name(tenant);
+        let mut rows = sqlx::query_as::<_, EducatorCompletion>(&format!(
+            r#"SELECT s.user_id, u.first_name, u.last_name,
+                      SUM(s.expected)::BIGINT AS expected, SUM(s.completed)::BIGINT AS completed
+               FROM "{schema}".stats_daily_educators s
+               JOIN "{schema}".users u ON u.id = s.user_id
+               WHERE s.date BETWEEN $1 AND $2
+               GROUP BY s.user_id, u.first_name, u.last_name
+               ORDER BY u.last_name, u.first_name"#
+        ))
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool)
+        .await?;
+        for row in &mut rows {
+            row.completion_rate = rate(row.completed, row.expected);
+        }
+        Ok(rows)
+    }
+
+    /// Validate a requested period: it must end before `today` (an unfinished day has no
+    /// rollup) and span at most [`MAX_PERIOD_DAYS`].
+    pub fn check_period(from: NaiveDate, to: NaiveDate, today: NaiveDate) -> Result<(), StatsError> {
+        if from > to || to >= today || (to - from) >= Duration::days(MAX_PERIOD_DAYS) {
+            return Err(StatsError::InvalidPeriod);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(date: &str, active_parents: i32, messages: i32, sent: i32, accepted: i32) -> StatsDay {
+        StatsDay {
+            date: date.parse().unwrap(),
+            active_parents,
+            messages,
+            media_bytes: active_parents as i64 * 100,
+            logins: 2,
+            failed_logins: 1,
+            invitations_sent: sent,
+            invitations_accepted: accepted,
+        }
+    }
+
+    #[test]
+    fn test_rate() {
+        assert_eq!(rate(0, 0), None);
+        assert_eq!(rate(1, 3), Some(33.3));
+        assert_eq!(rate(3, 3), Some(100.0));
+    }
+
+    #[test]
+    fn test_missing_days() {
+        let d = |s: &str| -> NaiveDate { s.parse().unwrap() };
+        let have: HashSet<_> = [d("2026-10-02")].into();
+        assert_eq!(missing_days(d("2026-10-01"), d("2026-10-03"), &have), vec![d("2026-10-01"), d("2026-10-03")]);
+        assert!(missing_days(d("2026-10-03"), d("2026-10-01"), &have).is_empty());
+    }
+
+    #[test]
+    fn test_totals_sum_counters_and_keep_last_gauges() {
+        let t = totals(&[day("2026-10-02", 12, 5, 1, 1), day("2026-10-01", 10, 3, 3, 1)]);
+        assert_eq!(t.active_parents, 12);
+        assert_eq!(t.media_bytes, 1200);
+        assert_eq!(t.messages, 8);
+        assert_eq!(t.logins, 4);
+        assert_eq!(t.invitations_sent, 4);
+        assert_eq!(t.invitation_acceptance_rate, Some(50.0));
+        assert_eq!(totals(&[]).invitation_acceptance_rate, None);
+    }
+
+    #[test]
+    fn test_check_period() {
+        let d = |s: &str| -> NaiveDate { s.parse().unwrap() };
+        let today = d("2026-10-18");
+        assert!(StatsService::check_period(d("2026-09-18"), d("2026-10-17"), today).is_ok());
+        assert!(StatsService::check_period(d("2026-09-18"), d("2026-10-18"), today).is_err());
+        assert!(StatsService::check_period(d("2025-01-01"), d("2026-10-17"), today).is_err());
+    }
+}
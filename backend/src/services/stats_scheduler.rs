@@ -0,0 +1,52 @@
+use chrono::{Duration, Local, Timelike};
+use sqlx::PgPool;
+use tracing::{info, warn};
+
+use crate::db::tenant_db::TenantDb;
+use crate::services::backup_scheduler::secs_until;
+use crate::services::stats::{StatsService, REFRESH_DAYS};
+
+/// Local time of the nightly rollup (seconds after midnight): 1:30.
+const ROLLUP_AT: u32 = 3600 + 30 * 60;
+
+/// Spawn a background task that rolls up, every night, the statistics of the last
+/// [`REFRESH_DAYS`] days of each garderie, so GET /stats reads precomputed figures.
+pub fn start(pool: PgPool) {
+    tokio::spawn(async move {
+        loop {
+            let now = Local::now();
+            let secs_today = now.hour() * 3600 + now.minute() * 60 + now.second();
+            tokio::time::sleep(tokio::time::Duration::from_secs(secs_until(secs_today, ROLLUP_AT))).await;
+            let Some(_job) = crate::services::worker::job().await else {
+                break;
+            };
+
+            let tenants: Vec<String> = match sqlx::query_scalar(
+                "SELECT slug FROM public.garderies WHERE is_active = TRUE AND archived_at IS NULL AND slug != 'demo'",
+            )
+            .fetch_all(&pool)
+            .await
+            {
+                Ok(rows) => rows,
+                Err(e) => {
+                    warn!("Stats scheduler: failed to query tenants: {e}");
+                    continue;
+                }
+            };
+
+            let today = Local::now().date_naive();
+            for slug in &tenants {
+                TenantDb::scope(slug.clone(), async {
+                    for back in 1..=REFRESH_DAYS {
+                        if let Err(e) = StatsService::compute_day(&pool, slug, today - Duration::days(back)).await {
+                            warn!("Stats scheduler: rollup failed for '{slug}': {e}");
+                            break;
+                        }
+                    }
+                })
+                .await;
+            }
+            info!("Stats scheduler: rolled up {} garderie(s)", tenants.len());
+        }
+    });
+}
@@ -0,0 +1,266 @@
+use serde::Serialize;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::db::tenant::schema_name;
+use crate::db::tenant_db::TenantDb;
+use crate::models::tenant::PlanType;
+use crate::services::branding::BrandingService;
+use crate::services::email::EmailService;
+
+const GIB: i64 = 1024 * 1024 * 1024;
+
+/// Cached usage is recomputed from the database after this many seconds.
+const USAGE_CACHE_TTL_SECS: u64 = 600;
+
+/// Usage thresholds (percent of quota) that trigger a warning email to tenant admins.
+const WARN_THRESHOLDS: &[u8] = &[95, 80];
+
+/// Storage quota (encrypted bytes on disk) granted by each plan.
+pub fn quota_bytes(plan: &PlanType) -> i64 {
+    match plan {
+        PlanType::Free => 2 * GIB,
+        PlanType::Standard => 25 * GIB,
+        PlanType::Premium => 100 * GIB,
+    }
+}
+
+/// Returned by uploads that would push a tenant over its storage quota.
+#[derive(Debug, thiserror::Error)]
+#[error("Quota de stockage atteint ({used_bytes} / {quota_bytes} octets)")]
+pub struct QuotaExceeded {
+    pub used_bytes: i64,
+    pub quota_bytes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, async_graphql::SimpleObject)]
+pub struct StorageUsage {
+    pub media_bytes: i64,
+    pub documents_bytes: i64,
+    pub used_bytes: i64,
+    pub quota_bytes: i64,
+    pub percent: f64,
+}
+
+impl StorageUsage {
+    fn new(media_bytes: i64, documents_bytes: i64, quota_bytes: i64) -> Self {
+        let used_bytes = media_bytes + documents_bytes;
+        let percent = if quota_bytes > 0 {
+            (used_bytes as f64 / quota_bytes as f64 * 1000.0).round() / 10.0
+        } else {
+            0.0
+        };
+        Self { media_bytes, documents_bytes, used_bytes, quota_bytes, percent }
+    }
+}
+
+fn cache_key(tenant: &str) -> String {
+    format!("storage:usage:{tenant}")
+}
+
+pub struct StorageService;
+
+impl StorageService {
+    /// Compute usage straight from the tenant schema (media + documents size_bytes, replaced
+    /// document files included).
+    /// Media rows sharing one stored file (duplicate uploads) count it once.
+    pub async fn compute_usage(pool: &PgPool, tenant: &str) -> anyhow::Result<StorageUsage> {
+        let schema = schema_name(tenant);
+
+        let plan: PlanType = sqlx::query_scalar("SELECT plan FROM public.garderies WHERE slug = $1")
+            .bind(tenant)
+            .fetch_one(pool)
+            .await?;
+
+        let (media_bytes, documents_bytes): (i64, i64) = sqlx::query_as(&format!(
+            r#"SELECT
+                 (SELECT COALESCE(SUM(size_bytes), 0)::BIGINT FROM (
+                     SELECT DISTINCT ON (storage_path) size_bytes FROM "{schema}".media
+                 ) stored),
+                 (SELECT COALESCE(SUM(size_bytes), 0)::BIGINT FROM "{schema}".documents)
+                 + (SELECT COALESCE(SUM(size_bytes), 0)::BIGINT FROM "{schema}".document_versions)"#
+        ))
+        .fetch_one(pool)
+        .await?;
+
+        Ok(StorageUsage::new(media_bytes, documents_bytes, quota_bytes(&plan)))
+    }
+
+    /// Current usage, served from Redis when cached.
+    pub async fn usage(
+        pool: &PgPool,
+        redis: &mut redis::aio::MultiplexedConnection,
+        tenant: &str,
+    ) -> anyhow::Result<StorageUsage> {
+        let key = cache_key(tenant);
+        let cached: Option<String> = redis::cmd("GET")
+            .arg(&key)
+            .query_async(redis)
+            .await
+            .unwrap_or(None);
+
+        if let Some((media, docs, quota)) = cached.as_deref().and_then(parse_cached) {
+            return Ok(StorageUsage::new(media, docs, quota));
+        }
+
+        let usage = Self::compute_usage(pool, tenant).await?;
+        let _: Result<(), _> = redis::cmd("SET")
+            .arg(&key)
+            .arg(format!("{}:{}:{}", usage.media_bytes, usage.documents_bytes, usage.quota_bytes))
+            .arg("EX")
+            .arg(USAGE_CACHE_TTL_SECS)
+            .query_async(redis)
+            .await;
+
+        Ok(usage)
+    }
+
+    /// Fails with [`QuotaExceeded`] if storing `incoming_bytes` more would exceed the quota.
+    pub async fn ensure_capacity(
+        pool: &PgPool,
+        redis: &mut redis::aio::MultiplexedConnection,
+        tenant: &str,
+        incoming_bytes: i64,
+    ) -> anyhow::Result<()> {
+        let usage = Self::usage(pool, redis, tenant).await?;
+        if usage.used_bytes + incoming_bytes > usage.quota_bytes {
+            return Err(QuotaExceeded {
+                used_bytes: usage.used_bytes,
+                quota_bytes: usage.quota_bytes,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Drop the cached usage so the next read recomputes it (call after uploads/deletes).
+    pub async fn invalidate(redis: &mut redis::aio::MultiplexedConnection, tenant: &str) {
+        let _: Result<(), _> = redis::cmd("DEL")
+            .arg(cache_key(tenant))
+            .query_async(redis)
+            .await;
+    }
+
+    /// Fire-and-forget: refresh usage after an upload and email tenant admins the
+    /// first time usage crosses 80% or 95% (deduplicated for 7 days via Redis).
+    pub fn check_thresholds(
+        pool: PgPool,
+        mut redis: redis::aio::MultiplexedConnection,
+        email: Option<Arc<EmailService>>,
+        tenant: String,
+    ) {
+        tokio::spawn(TenantDb::scope(tenant.clone(), async move {
+            Self::invalidate(&mut redis, &tenant).await;
+
+            let Some(email_svc) = email else {
+                return;
+            };
+            let usage = match Self::usage(&pool, &mut redis, &tenant).await {
+                Ok(u) => u,
+                Err(e) => {
+                    warn!("Storage usage check failed for '{tenant}': {e}");
+                    return;
+                }
+            };
+
+            let Some(threshold) = WARN_THRESHOLDS
+                .iter()
+                .copied()
+                .find(|t| usage.percent >= f64::from(*t))
+            else {
+                return;
+            };
+
+            let key = format!("storage:warned:{tenant}:{threshold}");
+            let newly_set: Option<String> = redis::cmd("SET")
+                .arg(&key)
+                .arg("1")
+                .arg("NX")
+                .arg("EX")
+                .arg(7 * 86_400u64)
+                .query_async(&mut redis)
+                .await
+                .unwrap_or(None);
+            if newly_set.is_none() {
+                return;
+            }
+
+            let schema = schema_name(&tenant);
+            let (garderie_name, branding) = BrandingService::for_email(&pool, &tenant).await;
+
+            let admins: Vec<(String, String)> = sqlx::query_as(&format!(
+                r#"SELECT email, CONCAT(first_name, ' ', last_name)
+                   FROM "{schema}".users
+                   WHERE role::text = 'admin_garderie' AND is_active = TRUE"#
+            ))
+            .fetch_all(&pool)
+            .await
+            .unwrap_or_default();
+
+            for (admin_email, admin_name) in admins {
+                if let Err(e) = email_svc
+                    .send_storage_quota_warning(
+                        &tenant,
+                        &admin_email,
+                        &admin_name,
+                        threshold,
+                        &format!("{} / {}", format_bytes(usage.used_bytes), format_bytes(usage.quota_bytes)),
+                        &garderie_name,
+                        &branding,
+                    )
+                    .await
+                {
+                    warn!("Failed to send storage warning to {admin_email}: {e}");
+                }
+            }
+        }));
+    }
+}
+
+fn parse_cached(s: &str) -> Option<(i64, i64, i64)> {
+    let mut it = s.split(':').map(|p| p.parse::<i64>().ok());
+    Some((it.next()??, it.next()??, it.next()??))
+}
+
+/// Human-readable size in the French convention used in emails (e.g. "1,5 Go").
+pub fn format_bytes(bytes: i64) -> String {
+    const UNITS: &[&str] = &["o", "Ko", "Mo", "Go", "To"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} o")
+    } else {
+        format!("{:.1} {}", value, UNITS[unit]).replace('.', ",")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_percent() {
+        let usage = StorageUsage::new(GIB, GIB / 2, 2 * GIB);
+        assert_eq!(usage.used_bytes, GIB + GIB / 2);
+        assert_eq!(usage.percent, 75.0);
+    }
+
+    #[test]
+    fn test_parse_cached() {
+        assert_eq!(parse_cached("1:2:3"), Some((1, 2, 3)));
+        assert_eq!(parse_cached("1:x:3"), None);
+        assert_eq!(parse_cached("1:2"), None);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 o");
+        assert_eq!(format_bytes(1536), "1,5 Ko");
+        assert_eq!(format_bytes(2 * GIB), "2,0 Go");
+    }
+}
@@ -0,0 +1,416 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, Local, NaiveDate};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::{tenant::schema_name, tenant_db::TenantDb},
+    models::tax_receipt::{GenerateTaxReceiptsResult, TaxReceipt},
+    services::{
+        email_i18n::Locale,
+        invoices::{fit, format_cents, wrap, Issuer, MARGIN},
+        pdf::{Font, PdfDocument, PAGE_HEIGHT, PAGE_WIDTH},
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum TaxReceiptError {
+    #[error("Relevé introuvable")]
+    NotFound,
+    #[error("Année invalide")]
+    InvalidYear,
+}
+
+/// "RL24-2026-0001".
+pub fn receipt_number(year: i32, sequence: i32) -> String {
+    format!("RL24-{year}-{sequence:04}")
+}
+
+/// First day of `year` and of the next one; receipts can't be made for a year not yet begun.
+fn year_bounds(year: i32) -> Result<(NaiveDate, NaiveDate), TaxReceiptError> {
+    if !(2000..=Local::now().year()).contains(&year) {
+        return Err(TaxReceiptError::InvalidYear);
+    }
+    let start = NaiveDate::from_ymd_opt(year, 1, 1).ok_or(TaxReceiptError::InvalidYear)?;
+    let end = NaiveDate::from_ymd_opt(year + 1, 1, 1).ok_or(TaxReceiptError::InvalidYear)?;
+    Ok((start, end))
+}
+
+/// Dollars with a decimal point, for spreadsheets.
+fn csv_amount(cents: i64) -> String {
+    format!("{}.{:02}", cents / 100, (cents % 100).abs())
+}
+
+#[derive(sqlx::FromRow)]
+struct PaidTotal {
+    payer_id: Option<Uuid>,
+    payer_name: Option<String>,
+    child_id: Uuid,
+    child_name: String,
+    child_birth_date: NaiveDate,
+    amount_cents: i64,
+    invoice_count: i64,
+}
+
+pub struct TaxReceiptService;
+
+impl TaxReceiptService {
+    /// Build or refresh the receipts of `year` from the invoices paid during it.
+    ///
+    /// Late fees are not childcare expenses and are left out. An invoice paid without
+    /// a recorded payer is attributed to the child's first registered parent.
+    /// Existing receipts keep their number; new ones continue the year's sequence.
+    pub async fn generate(
+        pool: &PgPool,
+        tenant: &str,
+        year: i32,
+        generated_by: Uuid,
+    ) -> anyhow::Result<GenerateTaxReceiptsResult> {
+        let (start, end) = year_bounds(year)?;
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+
+        // Numbers are handed out from MAX(sequence): one generation at a time
+        sqlx::query(&format!(r#"LOCK TABLE "{schema}".tax_receipts IN SHARE ROW EXCLUSIVE MODE"#))
+            .execute(&mut *tx)
+            .await?;
+
+        let totals: Vec<PaidTotal> = sqlx::query_as(&format!(
+            r#"WITH paid AS (
+                   SELECT COALESCE(it.child_id, i.child_id) AS child_id, i.id AS invoice_id,
+                          COALESCE(i.payer_id, (
+                              SELECT cp.user_id FROM "{schema}".child_parents cp
+                              JOIN "{schema}".users u ON u.id = cp.user_id
+                              WHERE cp.child_id = i.child_id
+                              ORDER BY u.created_at, u.id
+                              LIMIT 1)) AS payer_id,
+                          COALESCE(it.quantity * it.unit_cents, 0) AS eligible_cents
+                   FROM "{schema}".invoices i
+                   LEFT JOIN "{schema}".invoice_items it ON it.invoice_id = i.id AND it.kind <> 'late_fee'
+                   WHERE i.status = 'paid' AND i.paid_at >= $1 AND i.paid_at < $2
+               )
+               SELECT p.payer_id, u.first_name || ' ' || u.last_name AS payer_name,
+                      p.child_id, c.first_name || ' ' || c.last_name AS child_name,
+                      c.birth_date AS child_birth_date,
+                      SUM(p.eligible_cents)::BIGINT AS amount_cents, COUNT(DISTINCT p.invoice_id) AS invoice_count
+               FROM paid p
+               JOIN "{schema}".children c ON c.id = p.child_id
+               LEFT JOIN "{schema}".users u ON u.id = p.payer_id
+               GROUP BY p.payer_id, u.first_name, u.last_name, p.child_id, c.first_name, c.last_name, c.birth_date
+               ORDER BY c.last_name, c.first_name, payer_name"#
+        ))
+        .bind(start)
+        .bind(end)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let existing: HashMap<(Uuid, Uuid), Uuid> = sqlx::query_as::<_, (Uuid, Uuid, Uuid)>(&format!(
+            r#"SELECT payer_id, child_id, id FROM "{schema}".tax_receipts
+               WHERE year = $1 AND payer_id IS NOT NULL AND child_id IS NOT NULL"#
+        ))
+        .bind(year)
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|(payer_id, child_id, id)| ((payer_id, child_id), id))
+        .collect();
+        let mut sequence: i32 = sqlx::query_scalar(&format!(
+            r#"SELECT COALESCE(MAX(sequence), 0) FROM "{schema}".tax_receipts WHERE year = $1"#
+        ))
+        .bind(year)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let (mut created, mut updated, mut unassigned_invoices) = (0, 0, 0);
+        let mut current = Vec::new();
+        for total in totals {
+            let Some(payer_id) = total.payer_id else {
+                unassigned_invoices += total.invoice_count;
+                continue;
+            };
+            if total.amount_cents <= 0 {
+                continue;
+            }
+            let payer_name = total.payer_name.unwrap_or_default();
+            if let Some(&id) = existing.get(&(payer_id, total.child_id)) {
+                sqlx::query(&format!(
+                    r#"UPDATE "{schema}".tax_receipts
+                       SET payer_name = $2, child_name = $3, child_birth_date = $4,
+                           amount_cents = $5, invoice_count = $6, generated_by = $7, generated_at = NOW()
+                       WHERE id = $1"#
+                ))
+                .bind(id)
+                .bind(&payer_name)
+                .bind(&total.child_name)
+                .bind(total.child_birth_date)
+                .bind(total.amount_cents)
+                .bind(total.invoice_count as i32)
+                .bind(generated_by)
+                .execute(&mut *tx)
+                .await?;
+                current.push(id);
+                updated += 1;
+            } else {
+                sequence += 1;
+                let id: Uuid = sqlx::query_scalar(&format!(
+                    r#"INSERT INTO "{schema}".tax_receipts
+                           (year, sequence, number, payer_id, payer_name, child_id, child_name, child_birth_date,
+                            amount_cents, invoice_count, generated_by)
+                       VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                       RETURNING id"#
+                ))
+                .bind(year)
+                .bind(sequence)
+                .bind(receipt_number(year, sequence))
+                .bind(payer_id)
+                .bind(&payer_name)
+                .bind(total.child_id)
+                .bind(&total.child_name)
+                .bind(total.child_birth_date)
+                .bind(total.amount_cents)
+                .bind(total.invoice_count as i32)
+                .bind(generated_by)
+                .fetch_one(&mut *tx)
+                .await?;
+                current.push(id);
+                created += 1;
+            }
+        }
+
+        let zeroed = sqlx::query(&format!(
+            r#"UPDATE "{schema}".tax_receipts
+               SET amount_cents = 0, invoice_count = 0, generated_by = $3, generated_at = NOW()
+               WHERE year = $1 AND id <> ALL($2) AND amount_cents <> 0"#
+        ))
+        .bind(year)
+        .bind(&current)
+        .bind(generated_by)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected() as usize;
+
+        tx.commit().await?;
+
+        let receipts = Self::list(pool, tenant, Some(year), None).await?;
+        Ok(GenerateTaxReceiptsResult { receipts, created, updated, zeroed, unassigned_invoices })
+    }
+
+    /// Receipts by year and number. With `payer_id`, only that payer's non-zero receipts.
+    pub async fn list(
+        pool: &PgPool,
+        tenant: &str,
+        year: Option<i32>,
+        payer_id: Option<Uuid>,
+    ) -> anyhow::Result<Vec<TaxReceipt>> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, TaxReceipt>(&format!(
+            r#"SELECT id, year, number, payer_id, payer_name, child_id, child_name, child_birth_date,
+                      amount_cents, invoice_count, generated_at, updated_at
+               FROM "{schema}".tax_receipts
+               WHERE ($1::INT IS NULL OR year = $1)
+                 AND ($2::UUID IS NULL OR (payer_id = $2 AND amount_cents > 0))
+               ORDER BY year DESC, sequence"#
+        ))
+        .bind(year)
+        .bind(payer_id)
+        .fetch_all(pool)
+        .await?)
+    }
+
+    pub async fn get(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<TaxReceipt> {
+        let schema = schema_name(tenant);
+        Ok(sqlx::query_as::<_, TaxReceipt>(&format!(
+            r#"SELECT id, year, number, payer_id, payer_name, child_id, child_name, child_birth_date,
+                      amount_cents, invoice_count, generated_at, updated_at
+               FROM "{schema}".tax_receipts
+               WHERE id = $1"#
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(TaxReceiptError::NotFound)?)
+    }
+
+    pub async fn render_pdf(pool: &PgPool, tenant: &str, receipt: &TaxReceipt) -> anyhow::Result<Vec<u8>> {
+        let issuer = Issuer::load(pool, tenant).await?;
+        Ok(receipt_pdf(receipt, &issuer))
+    }
+
+    /// CSV of the year's receipts across garderies, for the accountant.
+    pub async fn export_csv(pool: &PgPool, year: i32, garderie: Option<&str>) -> anyhow::Result<Vec<u8>> {
+        let garderies: Vec<(String, String)> = sqlx::query_as(
+            "SELECT slug, name FROM public.garderies
+             WHERE is_active = TRUE AND ($1::TEXT IS NULL OR slug = $1)
+             ORDER BY name",
+        )
+        .bind(garderie)
+        .fetch_all(pool)
+        .await?;
+
+        let mut wtr = csv::WriterBuilder::new().from_writer(Vec::new());
+        wtr.write_record([
+            "Garderie",
+            "Année",
+            "Numéro",
+            "Payeur",
+            "Courriel du payeur",
+            "Enfant",
+            "Date de naissance",
+            "Montant",
+            "Factures",
+            "Généré le",
+        ])?;
+
+        for (slug, name) in garderies {
+            let schema = schema_name(&slug);
+            let (receipts, emails) = TenantDb::scope(slug.clone(), async {
+                let receipts: Vec<TaxReceipt> = sqlx::query_as(&format!(
+                    r#"SELECT id, year, number, payer_id, payer_name, child_id, child_name, child_birth_date,
+                              amount_cents, invoice_count, generated_at, updated_at
+                       FROM "{schema}".tax_receipts
+                       WHERE year = $1
+                       ORDER BY sequence"#
+                ))
+                .bind(year)
+                .fetch_all(pool)
+                .await?;
+
+                let emails: HashMap<Uuid, String> = sqlx::query_as::<_, (Uuid, String)>(&format!(
+                    r#"SELECT u.id, u.email FROM "{schema}".users u
+                       WHERE u.id IN (SELECT payer_id FROM "{schema}".tax_receipts WHERE year = $1)"#
+                ))
+                .bind(year)
+                .fetch_all(pool)
+                .await?
+                .into_iter()
+                .collect();
+                anyhow::Ok((receipts, emails))
+            })
+            .await?;
+
+            for receipt in receipts {
+                let email = receipt.payer_id.and_then(|id| emails.get(&id)).cloned().unwrap_or_default();
+                wtr.write_record([
+                    name.clone(),
+                    receipt.year.to_string(),
+                    receipt.number,
+                    receipt.payer_name,
+                    email,
+                    receipt.child_name,
+                    receipt.child_birth_date.format("%Y-%m-%d").to_string(),
+                    csv_amount(receipt.amount_cents),
+                    receipt.invoice_count.to_string(),
+                    receipt.generated_at.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string(),
+                ])?;
+            }
+        }
+
+        Ok(wtr.into_inner()?)
+    }
+}
+
+fn receipt_pdf(receipt: &TaxReceipt, issuer: &Issuer) -> Vec<u8> {
+    let right = PAGE_WIDTH - MARGIN;
+    let fr = Locale::Fr;
+    let mut doc = PdfDocument::new();
+
+    let mut y = PAGE_HEIGHT - MARGIN - 8.0;
+    doc.text(MARGIN, y, 16.0, Font::Bold, "Relevé 24 — Frais de garde d'enfants");
+    doc.text_right(right, y, 10.0, Font::Regular, &format!("No {}", receipt.number));
+    y -= 18.0;
+    doc.text(MARGIN, y, 11.0, Font::Regular, &format!("Année d'imposition {}", receipt.year));
+    y -= 36.0;
+
+    doc.text(MARGIN, y, 10.0, Font::Bold, "Prestataire des services de garde");
+    y -= 14.0;
+    doc.text(MARGIN, y, 10.0, Font::Regular, &fit(&issuer.name, 10.0, right - MARGIN));
+    for line in issuer.address_lines() {
+        y -= 13.0;
+        doc.text(MARGIN, y, 9.5, Font::Regular, &fit(&line, 9.5, right - MARGIN));
+    }
+    y -= 28.0;
+
+    doc.text(MARGIN, y, 10.0, Font::Bold, "Bénéficiaire du relevé");
+    y -= 14.0;
+    doc.text(MARGIN, y, 10.0, Font::Regular, &receipt.payer_name);
+    y -= 28.0;
+
+    doc.text(MARGIN, y, 10.0, Font::Bold, "Enfant");
+    y -= 14.0;
+    let child = format!("{}, né(e) le {}", receipt.child_name, fr.short_date(receipt.child_birth_date));
+    doc.text(MARGIN, y, 10.0, Font::Regular, &child);
+    y -= 32.0;
+
+    doc.line(MARGIN, y + 14.0, right, y + 14.0);
+    doc.text(MARGIN, y, 11.0, Font::Bold, &format!("Frais de garde payés en {}", receipt.year));
+    doc.text_right(right, y, 11.0, Font::Bold, &format_cents(receipt.amount_cents));
+    y -= 16.0;
+    doc.text(MARGIN, y, 9.5, Font::Regular, &format!("Factures payées : {}", receipt.invoice_count));
+    doc.line(MARGIN, y - 8.0, right, y - 8.0);
+    y -= 36.0;
+
+    let note = format!(
+        "Montant des factures payées du 1er janvier au 31 décembre {}, frais de retard exclus. \
+         Conservez ce relevé pour votre déclaration de revenus du Québec.",
+        receipt.year
+    );
+    for line in wrap(&note, 9.0, right - MARGIN) {
+        doc.text(MARGIN, y, 9.0, Font::Regular, &line);
+        y -= 12.0;
+    }
+    y -= 12.0;
+    let issued = receipt.generated_at.with_timezone(&Local).date_naive();
+    doc.text(MARGIN, y, 9.0, Font::Regular, &format!("Délivré le {}", fr.short_date(issued)));
+
+    doc.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn receipts_are_numbered_within_their_year() {
+        assert_eq!(receipt_number(2026, 1), "RL24-2026-0001");
+        assert_eq!(receipt_number(2026, 1234), "RL24-2026-1234");
+    }
+
+    #[test]
+    fn only_begun_years_can_be_receipted() {
+        let (start, end) = year_bounds(2025).unwrap();
+        assert_eq!(start, NaiveDate::from_ymd_opt(2025, 1, 1).unwrap());
+        assert_eq!(end, NaiveDate::from_ymd_opt(2026, 1, 1).unwrap());
+        assert!(year_bounds(1999).is_err());
+        assert!(year_bounds(Local::now().year() + 1).is_err());
+    }
+
+    #[test]
+    fn csv_amounts_use_a_decimal_point() {
+        assert_eq!(csv_amount(123_456), "1234.56");
+        assert_eq!(csv_amount(5), "0.05");
+    }
+
+    #[test]
+    fn pdf_shows_the_year_total() {
+        let receipt = TaxReceipt {
+            id: Uuid::new_v4(),
+            year: 2025,
+            number: receipt_number(2025, 7),
+            payer_id: Some(Uuid::new_v4()),
+            payer_name: "Marie Tremblay".into(),
+            child_id: Some(Uuid::new_v4()),
+            child_name: "Léa Tremblay".into(),
+            child_birth_date: NaiveDate::from_ymd_opt(2022, 3, 14).unwrap(),
+            amount_cents: 1_140_000,
+            invoice_count: 12,
+            generated_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let text = String::from_utf8(receipt_pdf(&receipt, &Issuer::default())).unwrap();
+        assert!(text.contains("(No RL24-2025-0007)"));
+        assert!(text.contains("(11 400,00 $)"));
+        assert!(text.contains("(Marie Tremblay)"));
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::{HashMap, HashSet};
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::message::{ConversationItem, ConversationQuery, ThreadState, UpdateThreadStateRequest},
+    services::unread::thread_field,
+};
+
+#[derive(Debug, thiserror::Error)]
+#[error("Fil de discussion inconnu")]
+pub struct UnknownThread;
+
+/// Whether a conversation item passes the filters of GET /messages/conversations.
+pub fn matches(item: &ConversationItem, query: &ConversationQuery) -> bool {
+    item.archived == query.archived
+        && query.muted.is_none_or(|m| item.muted == m)
+        && query.pinned.is_none_or(|p| item.pinned == p)
+}
+
+/// Each user's own archived / muted / pinned flags on their threads. A thread
+/// without a row has all flags off.
+pub struct ThreadStateService;
+
+impl ThreadStateService {
+    /// Set the flags given in the request, keeping the others.
+    pub async fn update(
+        pool: &PgPool,
+        tenant: &str,
+        user_id: Uuid,
+        req: &UpdateThreadStateRequest,
+    ) -> anyhow::Result<ThreadState> {
+        let key = match (req.kind.as_str(), req.id.as_deref()) {
+            ("broadcast" | "staff", None) => thread_field(&req.kind, None),
+            ("group" | "individual" | "staff", Some(id)) if id.parse::<Uuid>().is_ok() => thread_field(&req.kind, Some(id)),
+            _ => return Err(UnknownThread.into()),
+        };
+        let schema = schema_name(tenant);
+        let state = sqlx::query_as::<_, ThreadState>(&format!(
+            "INSERT INTO {schema}.thread_states (user_id, thread_key, archived, muted, pinned)
+             VALUES ($1, $2, COALESCE($3, FALSE), COALESCE($4, FALSE), COALESCE($5, FALSE))
+             ON CONFLICT (user_id, thread_key) DO UPDATE SET
+                 archived   = COALESCE($3, thread_states.archived),
+                 muted      = COALESCE($4, thread_states.muted),
+                 pinned     = COALESCE($5, thread_states.pinned),
+                 updated_at = NOW()
+             RETURNING archived, muted, pinned"
+        ))
+        .bind(user_id)
+        .bind(&key)
+        .bind(req.archived)
+        .bind(req.muted)
+        .bind(req.pinned)
+        .fetch_one(pool)
+        .await?;
+        Ok(state)
+    }
+
+    /// Copy the user's flags onto their conversation list, pinned threads first.
+    pub async fn annotate(
+        pool: &PgPool,
+        tenant: &str,
+        user_id: Uuid,
+        items: &mut [ConversationItem],
+    ) -> anyhow::Result<()> {
+        let schema = schema_name(tenant);
+        let rows: Vec<(String, bool, bool, bool)> = sqlx::query_as(&format!(
+            "SELECT thread_key, archived, muted, pinned FROM {schema}.thread_states WHERE user_id = $1"
+        ))
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+        let states: HashMap<String, (bool, bool, bool)> =
+            rows.into_iter().map(|(k, a, m, p)| (k, (a, m, p))).collect();
+
+        for item in items.iter_mut() {
+            if let Some(&(archived, muted, pinned)) = states.get(&thread_field(&item.kind, item.id.as_deref())) {
+                item.archived = archived;
+                item.muted = muted;
+                item.pinned = pinned;
+            }
+        }
+        // Stable: the service order is kept within pinned and unpinned threads
+        items.sort_by_key(|i| !i.pinned);
+        Ok(())
+    }
+
+    /// A message was delivered to a thread: it comes back out of the archive of its
+    /// recipients, unless they muted it. Returns the recipients who muted it.
+    pub async fn message_delivered(
+        pool: &PgPool,
+        tenant: &str,
+        thread_key: &str,
+        recipients: &[Uuid],
+    ) -> anyhow::Result<HashSet<Uuid>> {
+        let schema = schema_name(tenant);
+        sqlx::query(&format!(
+            "UPDATE {schema}.thread_states SET archived = FALSE, updated_at = NOW()
+             WHERE thread_key = $1 AND user_id = ANY($2) AND archived = TRUE AND muted = FALSE"
+        ))
+        .bind(thread_key)
+        .bind(recipients)
+        .execute(pool)
+        .await?;
+
+        let muted: Vec<Uuid> = sqlx::query_scalar(&format!(
+            "SELECT user_id FROM {schema}.thread_states
+             WHERE thread_key = $1 AND user_id = ANY($2) AND muted = TRUE"
+        ))
+        .bind(thread_key)
+        .bind(recipients)
+        .fetch_all(pool)
+        .await?;
+        Ok(muted.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(archived: bool, muted: bool, pinned: bool) -> ConversationItem {
+        ConversationItem {
+            kind: "broadcast".into(),
+            id: None,
+            thread_id: None,
+            name: "Tous les parents".into(),
+            color: None,
+            last_message: None,
+            last_at: None,
+            unread_count: 0,
+            online: None,
+            last_seen_at: None,
+            away: None,
+            archived,
+            muted,
+            pinned,
+        }
+    }
+
+    #[test]
+    fn archived_threads_are_hidden_unless_asked_for() {
+        let default = ConversationQuery::default();
+        assert!(matches(&item(false, false, false), &default));
+        assert!(!matches(&item(true, false, false), &default));
+
+        let archived = ConversationQuery { archived: true, ..Default::default() };
+        assert!(matches(&item(true, true, false), &archived));
+        assert!(!matches(&item(false, false, false), &archived));
+    }
+
+    #[test]
+    fn muted_and_pinned_filters_apply_only_when_given() {
+        let muted = ConversationQuery { muted: Some(true), ..Default::default() };
+        assert!(matches(&item(false, true, false), &muted));
+        assert!(!matches(&item(false, false, false), &muted));
+
+        let unpinned = ConversationQuery { pinned: Some(false), ..Default::default() };
+        assert!(matches(&item(false, true, false), &unpinned));
+        assert!(!matches(&item(false, false, true), &unpinned));
+    }
+}
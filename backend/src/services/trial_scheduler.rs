@@ -1,14 +1,59 @@
-use chrono::{Local, Timelike};
+use chrono::{DateTime, Duration, Local, Timelike, Utc};
 use sqlx::PgPool;
 use std::sync::Arc;
 use tracing::{info, warn};
 
 use crate::db::tenant::schema_name;
+use crate::db::tenant_db::TenantDb;
 use crate::services::email::EmailService;
 
 /// Jours avant expiration pour lesquels on envoie un rappel.
 const WARN_DAYS: &[i64] = &[7, 3, 1];
 
+/// Where a garderie stands in its trial.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrialPhase {
+    /// No trial (permanent account) or trial not yet expired.
+    Active,
+    /// Expired, but everything keeps working until `read_only_at`.
+    Grace { read_only_at: DateTime<Utc> },
+    /// Expired for longer than the grace period: reads only.
+    ReadOnly,
+}
+
+impl TrialPhase {
+    pub fn of(trial_expires_at: Option<DateTime<Utc>>, grace_days: i64, now: DateTime<Utc>) -> Self {
+        let Some(expires_at) = trial_expires_at.filter(|e| *e <= now) else {
+            return TrialPhase::Active;
+        };
+        let read_only_at = expires_at + Duration::days(grace_days);
+        if now < read_only_at {
+            TrialPhase::Grace { read_only_at }
+        } else {
+            TrialPhase::ReadOnly
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TrialPhase::Active => "active",
+            TrialPhase::Grace { .. } => "grace",
+            TrialPhase::ReadOnly => "read_only",
+        }
+    }
+}
+
+/// Forget the warnings already sent to a garderie, so a new expiry date gets its own.
+/// Called when a super-admin changes the trial.
+pub async fn reset_warnings(redis: &mut redis::aio::MultiplexedConnection, slug: &str) {
+    for days in WARN_DAYS {
+        let _: Result<(), _> = redis::cmd("DEL")
+            .arg(format!("trial:notif:{days}d:{slug}"))
+            .query_async(redis)
+            .await;
+    }
+}
+
 /// Spawn a background task that wakes up daily at 9:00 AM and sends trial
 /// expiry warnings to tenant admins and to contact@minispace.app.
 /// Redis keys (TTL 2 days) prevent duplicate sends if the server restarts.
@@ -29,6 +74,9 @@ pub fn start(pool: PgPool, email: Option<Arc<EmailService>>, redis: redis::Clien
                 }
             };
             tokio::time::sleep(tokio::time::Duration::from_secs(secs_until_9am)).await;
+            let Some(_job) = crate::services::worker::job().await else {
+                break;
+            };
 
             let Some(ref email_svc) = email else {
                 continue;
@@ -97,16 +145,17 @@ async fn check_and_notify(
 
         // Look up the first admin user of this tenant
         let schema = schema_name(&slug);
-        let admin: Option<(String, String, String)> = sqlx::query_as(&format!(
+        let query = format!(
             r#"SELECT email, first_name, last_name
                FROM "{schema}".users
                WHERE role = 'admin_garderie' AND is_active = TRUE
                ORDER BY created_at ASC
                LIMIT 1"#
-        ))
-        .fetch_optional(pool)
-        .await
-        .unwrap_or(None);
+        );
+        let admin: Option<(String, String, String)> =
+            TenantDb::scope(slug.clone(), sqlx::query_as(&query).fetch_optional(pool))
+                .await
+                .unwrap_or(None);
 
         let (admin_email, admin_first, admin_last) = match admin {
             Some(row) => row,
@@ -139,3 +188,23 @@ async fn check_and_notify(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trial_goes_read_only_after_the_grace_period() {
+        let now = Utc::now();
+        assert_eq!(TrialPhase::of(None, 7, now), TrialPhase::Active);
+        assert_eq!(TrialPhase::of(Some(now + Duration::days(1)), 7, now), TrialPhase::Active);
+
+        let expired = now - Duration::days(2);
+        assert_eq!(
+            TrialPhase::of(Some(expired), 7, now),
+            TrialPhase::Grace { read_only_at: expired + Duration::days(7) }
+        );
+        assert_eq!(TrialPhase::of(Some(now - Duration::days(8)), 7, now), TrialPhase::ReadOnly);
+        assert_eq!(TrialPhase::of(Some(expired), 0, now), TrialPhase::ReadOnly);
+    }
+}
@@ -0,0 +1,411 @@
+use std::collections::HashMap;
+
+use redis::aio::MultiplexedConnection;
+use redis::AsyncCommands;
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::{auth::AuthenticatedUser, message::MessageWithSender, user::UserRole},
+    services::{
+        groups::GroupService,
+        messages::{MessageService, ReadChange},
+    },
+};
+
+/// Counters are rebuilt from the database once they expire, which bounds any drift
+/// (a parent joining a group, a deactivated account…).
+const COUNTERS_TTL_SECS: i64 = 7 * 24 * 3600;
+
+/// Adds ARGV[2] to field ARGV[1] of every existing hash in KEYS, never below zero.
+/// Missing hashes are left alone: they are rebuilt from the database on the next read.
+const INCR_SCRIPT: &str = r"
+for _, key in ipairs(KEYS) do
+    if redis.call('EXISTS', key) == 1 then
+        if redis.call('HINCRBY', key, ARGV[1], ARGV[2]) < 0 then
+            redis.call('HSET', key, ARGV[1], 0)
+        end
+    end
+end
+return 0
+";
+
+/// Sets field ARGV[1] to zero in every existing hash in KEYS.
+const RESET_SCRIPT: &str = r"
+for _, key in ipairs(KEYS) do
+    if redis.call('EXISTS', key) == 1 then
+        redis.call('HSET', key, ARGV[1], 0)
+    end
+end
+return 0
+";
+
+/// Redis hash of a user's unread counts, one field per thread.
+fn counters_key(tenant: &str, user_id: Uuid) -> String {
+    format!("unread:{tenant}:{user_id}")
+}
+
+/// Hash field of a thread, built from the `kind` and `id` of its conversation item.
+/// Also the key of the thread in `thread_states`.
+pub(crate) fn thread_field(kind: &str, id: Option<&str>) -> String {
+    match id {
+        Some(id) => format!("{kind}:{id}"),
+        None => kind.to_string(),
+    }
+}
+
+fn parse_field(field: &str) -> (String, Option<String>) {
+    match field.split_once(':') {
+        Some((kind, id)) => (kind.to_string(), Some(id.to_string())),
+        None => (field.to_string(), None),
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThreadUnread {
+    /// "broadcast" | "group" | "individual" | "staff", as in GET /messages/conversations
+    pub kind: String,
+    pub id: Option<String>,
+    pub unread_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UnreadCounts {
+    pub total: i64,
+    pub threads: Vec<ThreadUnread>,
+}
+
+/// Per-thread counts visible to a user; scoped educators only count their groups.
+fn summarize(fields: HashMap<String, i64>, scope: Option<&[Uuid]>) -> UnreadCounts {
+    let mut threads: Vec<ThreadUnread> = fields
+        .into_iter()
+        .map(|(field, unread_count)| {
+            let (kind, id) = parse_field(&field);
+            ThreadUnread { kind, id, unread_count: unread_count.max(0) }
+        })
+        .filter(|t| match (t.kind.as_str(), scope) {
+            ("group", Some(groups)) | ("staff", Some(groups)) if t.id.is_some() => t
+                .id
+                .as_deref()
+                .and_then(|id| id.parse::<Uuid>().ok())
+                .is_some_and(|id| groups.contains(&id)),
+            _ => true,
+        })
+        .collect();
+    threads.sort_by(|a, b| (&a.kind, &a.id).cmp(&(&b.kind, &b.id)));
+    UnreadCounts {
+        total: threads.iter().map(|t| t.unread_count).sum(),
+        threads,
+    }
+}
+
+/// The thread a new message belongs to and the users it is unread for.
+pub struct Delivery {
+    field: String,
+    pub recipients: Vec<Uuid>,
+}
+
+impl Delivery {
+    /// (kind, id) of the thread, as in GET /messages/conversations.
+    pub fn thread(&self) -> (String, Option<String>) {
+        parse_field(&self.field)
+    }
+
+    /// Key of the thread in `thread_states`.
+    pub fn thread_key(&self) -> &str {
+        &self.field
+    }
+}
+
+/// Unread message counters kept in Redis, one hash per user, so clients and push
+/// badges need not count whole threads. The counts follow the rules of the
+/// conversation list: read receipts for broadcast, group and staff threads, the
+/// `is_read` flag (shared by all staff) for individual threads.
+pub struct UnreadService;
+
+impl UnreadService {
+    /// Unread counts of a user, rebuilt from the database when not cached.
+    pub async fn counts(
+        pool: &PgPool,
+        redis: &mut MultiplexedConnection,
+        tenant: &str,
+        user: &AuthenticatedUser,
+    ) -> anyhow::Result<UnreadCounts> {
+        let key = counters_key(tenant, user.user_id);
+        let cached: HashMap<String, i64> = redis.hgetall(&key).await.unwrap_or_default();
+        let fields = if cached.is_empty() {
+            Self::rebuild(pool, redis, tenant, user.user_id, user.role != UserRole::Parent).await?
+        } else {
+            cached
+        };
+        let scope = GroupService::educator_scope(pool, tenant, user).await?;
+        Ok(summarize(fields, scope.as_deref()))
+    }
+
+    async fn rebuild(
+        pool: &PgPool,
+        redis: &mut MultiplexedConnection,
+        tenant: &str,
+        user_id: Uuid,
+        is_staff: bool,
+    ) -> anyhow::Result<HashMap<String, i64>> {
+        let items = if is_staff {
+            MessageService::get_conversations_admin(pool, tenant, user_id).await?
+        } else {
+            MessageService::get_conversations_parent(pool, tenant, user_id).await?
+        };
+        let fields: HashMap<String, i64> = items
+            .iter()
+            .map(|i| (thread_field(&i.kind, i.id.as_deref()), i.unread_count))
+            .collect();
+
+        let key = counters_key(tenant, user_id);
+        let pairs: Vec<(&String, &i64)> = fields.iter().collect();
+        let _: Result<(), _> = redis::pipe()
+            .atomic()
+            .del(&key)
+            .ignore()
+            .hset_multiple(&key, &pairs)
+            .ignore()
+            .expire(&key, COUNTERS_TTL_SECS)
+            .ignore()
+            .query_async(redis)
+            .await;
+        Ok(fields)
+    }
+
+    /// Thread and recipients of a new message: everyone it reaches but its sender.
+    /// Individual threads are identified by their parent, whichever side wrote.
+    pub async fn delivery(
+        pool: &PgPool,
+        tenant: &str,
+        message_type: &str,
+        sender_id: Uuid,
+        group_id: Option<Uuid>,
+        recipient_id: Option<Uuid>,
+    ) -> anyhow::Result<Option<Delivery>> {
+        let s = schema_name(tenant);
+        let (field, recipients) = match (message_type, group_id) {
+            ("broadcast", _) => {
+                let users: Vec<Uuid> = sqlx::query_scalar(&format!(
+                    "SELECT id FROM {s}.users WHERE is_active = TRUE AND id != $1"
+                ))
+                .bind(sender_id)
+                .fetch_all(pool)
+                .await?;
+                (thread_field("broadcast", None), users)
+            }
+            ("group", Some(group_id)) => {
+                let users: Vec<Uuid> = sqlx::query_scalar(&format!(
+                    "SELECT u.id FROM {s}.users u
+                     WHERE u.is_active = TRUE AND u.id != $2
+                       AND (u.role::text != 'parent' OR EXISTS (
+                           SELECT 1 FROM {s}.child_parents cp
+                           JOIN {s}.children c ON c.id = cp.child_id
+                           WHERE cp.user_id = u.id AND c.group_id = $1
+                       ))"
+                ))
+                .bind(group_id)
+                .bind(sender_id)
+                .fetch_all(pool)
+                .await?;
+                (thread_field("group", Some(&group_id.to_string())), users)
+            }
+            ("staff", _) => {
+                let mut staff = Self::staff(pool, tenant).await?;
+                staff.retain(|id| *id != sender_id);
+                (thread_field("staff", group_id.map(|id| id.to_string()).as_deref()), staff)
+            }
+            ("individual", _) => {
+                let sender_is_parent: bool = sqlx::query_scalar(&format!(
+                    "SELECT EXISTS(SELECT 1 FROM {s}.users WHERE id = $1 AND role::text = 'parent')"
+                ))
+                .bind(sender_id)
+                .fetch_one(pool)
+                .await?;
+                if sender_is_parent {
+                    (thread_field("individual", Some(&sender_id.to_string())), Self::staff(pool, tenant).await?)
+                } else if let Some(parent_id) = recipient_id {
+                    (thread_field("individual", Some(&parent_id.to_string())), vec![parent_id])
+                } else {
+                    return Ok(None);
+                }
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(Delivery { field, recipients }))
+    }
+
+    /// Count a new message unread for its recipients.
+    pub async fn message_sent(redis: &mut MultiplexedConnection, tenant: &str, delivery: &Delivery) {
+        Self::run(redis, INCR_SCRIPT, tenant, &delivery.recipients, &delivery.field, 1).await;
+    }
+
+    /// Follow up on [`MessageService::mark_read`]. An individual message read by staff
+    /// is read for all staff, as in their conversation list.
+    pub async fn message_read(
+        pool: &PgPool,
+        redis: &mut MultiplexedConnection,
+        tenant: &str,
+        user: &AuthenticatedUser,
+        change: &ReadChange,
+    ) -> anyhow::Result<()> {
+        let ReadChange { message, flagged_read, receipt_added } = change;
+        let (flagged_read, receipt_added) = (*flagged_read, *receipt_added);
+        match (message.message_type.as_str(), message.group_id) {
+            ("broadcast", _) if receipt_added => {
+                Self::run(redis, INCR_SCRIPT, tenant, &[user.user_id], &thread_field("broadcast", None), -1).await;
+            }
+            ("group", Some(group_id)) if receipt_added => {
+                let field = thread_field("group", Some(&group_id.to_string()));
+                Self::run(redis, INCR_SCRIPT, tenant, &[user.user_id], &field, -1).await;
+            }
+            ("staff", group_id) if receipt_added => {
+                let field = thread_field("staff", group_id.map(|id| id.to_string()).as_deref());
+                Self::run(redis, INCR_SCRIPT, tenant, &[user.user_id], &field, -1).await;
+            }
+            ("individual", _) if flagged_read => {
+                if user.role == UserRole::Parent {
+                    let field = thread_field("individual", Some(&user.user_id.to_string()));
+                    Self::run(redis, INCR_SCRIPT, tenant, &[user.user_id], &field, -1).await;
+                } else {
+                    let field = thread_field("individual", Some(&message.sender_id.to_string()));
+                    Self::run(redis, INCR_SCRIPT, tenant, &Self::staff(pool, tenant).await?, &field, -1).await;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// A deleted broadcast, group or staff message no longer counts for the recipients
+    /// who had not read it.
+    pub async fn message_removed(
+        pool: &PgPool,
+        redis: &mut MultiplexedConnection,
+        tenant: &str,
+        message: &MessageWithSender,
+    ) -> anyhow::Result<()> {
+        if message.message_type == "individual" {
+            return Ok(());
+        }
+        let Some(delivery) = Self::delivery(
+            pool,
+            tenant,
+            &message.message_type,
+            message.sender_id,
+            message.group_id,
+            message.recipient_id,
+        )
+        .await?
+        else {
+            return Ok(());
+        };
+        let s = schema_name(tenant);
+        let readers: Vec<Uuid> = sqlx::query_scalar(&format!(
+            "SELECT user_id FROM {s}.message_reads WHERE message_id = $1"
+        ))
+        .bind(message.id)
+        .fetch_all(pool)
+        .await?;
+        let unread: Vec<Uuid> = delivery
+            .recipients
+            .into_iter()
+            .filter(|id| !readers.contains(id))
+            .collect();
+        Self::run(redis, INCR_SCRIPT, tenant, &unread, &delivery.field, -1).await;
+        Ok(())
+    }
+
+    /// Follow up on [`MessageService::mark_thread_read`].
+    pub async fn thread_read(
+        pool: &PgPool,
+        redis: &mut MultiplexedConnection,
+        tenant: &str,
+        user_id: Uuid,
+        kind: &str,
+        thread_id: Option<Uuid>,
+    ) -> anyhow::Result<()> {
+        match (kind, thread_id) {
+            ("broadcast", _) => {
+                Self::run(redis, RESET_SCRIPT, tenant, &[user_id], &thread_field("broadcast", None), 0).await;
+            }
+            ("group", Some(group_id)) => {
+                let field = thread_field("group", Some(&group_id.to_string()));
+                Self::run(redis, RESET_SCRIPT, tenant, &[user_id], &field, 0).await;
+            }
+            ("staff", group_id) => {
+                let field = thread_field("staff", group_id.map(|id| id.to_string()).as_deref());
+                Self::run(redis, RESET_SCRIPT, tenant, &[user_id], &field, 0).await;
+            }
+            ("individual", Some(parent_id)) => {
+                let field = thread_field("individual", Some(&parent_id.to_string()));
+                let users = if parent_id == user_id { vec![user_id] } else { Self::staff(pool, tenant).await? };
+                Self::run(redis, RESET_SCRIPT, tenant, &users, &field, 0).await;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn staff(pool: &PgPool, tenant: &str) -> anyhow::Result<Vec<Uuid>> {
+        let s = schema_name(tenant);
+        Ok(sqlx::query_scalar(&format!(
+            "SELECT id FROM {s}.users WHERE role::text != 'parent' AND is_active = TRUE"
+        ))
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Counters are a cache: a Redis failure only costs a rebuild once the hash expires.
+    async fn run(
+        redis: &mut MultiplexedConnection,
+        script: &str,
+        tenant: &str,
+        users: &[Uuid],
+        field: &str,
+        delta: i64,
+    ) {
+        if users.is_empty() {
+            return;
+        }
+        let script = redis::Script::new(script);
+        let mut invocation = script.prepare_invoke();
+        for user_id in users {
+            invocation.key(counters_key(tenant, *user_id));
+        }
+        invocation.arg(field).arg(delta);
+        if let Err(e) = invocation.invoke_async::<()>(redis).await {
+            tracing::warn!("Unread counters update failed for '{tenant}': {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scoped_educators_only_count_their_groups() {
+        let mine = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let fields = HashMap::from([
+            ("broadcast".to_string(), 2),
+            (thread_field("group", Some(&mine.to_string())), 3),
+            (thread_field("group", Some(&other.to_string())), 5),
+            (thread_field("individual", Some(&other.to_string())), -1),
+            ("staff".to_string(), 1),
+            (thread_field("staff", Some(&other.to_string())), 4),
+        ]);
+
+        let all = summarize(fields.clone(), None);
+        assert_eq!(all.total, 15);
+        assert_eq!(all.threads.len(), 6);
+
+        let scoped = summarize(fields, Some(&[mine]));
+        assert_eq!(scoped.total, 6);
+        assert!(scoped.threads.iter().all(|t| t.id.as_deref() != Some(other.to_string().as_str()) || t.kind == "individual"));
+    }
+}
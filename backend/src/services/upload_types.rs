@@ -0,0 +1,208 @@
+use std::path::Path;
+
+use crate::models::tenant::PlanType;
+
+const MIB: u64 = 1024 * 1024;
+
+/// A file format recognised from its first bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileType {
+    pub content_type: &'static str,
+    /// Extensions a file of this type may carry (lower case, first is the usual one).
+    pub extensions: &'static [&'static str],
+    /// Other content types clients send for it.
+    aliases: &'static [&'static str],
+}
+
+pub const JPEG: FileType = FileType { content_type: "image/jpeg", extensions: &["jpg", "jpeg", "jfif"], aliases: &["image/jpg", "image/pjpeg"] };
+pub const PNG: FileType = FileType { content_type: "image/png", extensions: &["png"], aliases: &[] };
+pub const GIF: FileType = FileType { content_type: "image/gif", extensions: &["gif"], aliases: &[] };
+pub const WEBP: FileType = FileType { content_type: "image/webp", extensions: &["webp"], aliases: &[] };
+pub const HEIC: FileType = FileType { content_type: "image/heic", extensions: &["heic", "heif"], aliases: &["image/heif"] };
+pub const MP4: FileType = FileType { content_type: "video/mp4", extensions: &["mp4", "m4v"], aliases: &["video/x-m4v", "video/quicktime"] };
+pub const MOV: FileType = FileType { content_type: "video/quicktime", extensions: &["mov", "qt"], aliases: &["video/mp4"] };
+pub const WEBM: FileType = FileType { content_type: "video/webm", extensions: &["webm"], aliases: &[] };
+pub const PDF: FileType = FileType { content_type: "application/pdf", extensions: &["pdf"], aliases: &["application/x-pdf"] };
+pub const DOCX: FileType = FileType {
+    content_type: "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    extensions: &["docx"],
+    aliases: &[],
+};
+
+/// What an upload endpoint accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadKind {
+    /// Gallery photos and videos.
+    Media,
+    /// Documents shared with parents.
+    Document,
+    /// Child photos.
+    Image,
+    /// The garderie's logo.
+    Logo,
+}
+
+impl UploadKind {
+    pub fn allowed(self) -> &'static [FileType] {
+        match self {
+            UploadKind::Media => &[JPEG, PNG, GIF, WEBP, HEIC, MP4, MOV, WEBM],
+            UploadKind::Document => &[PDF, DOCX],
+            UploadKind::Image => &[JPEG, PNG, WEBP],
+            UploadKind::Logo => &[JPEG, PNG, WEBP, GIF],
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            UploadKind::Media => "JPEG, PNG, GIF, WebP, HEIC, MP4, MOV ou WebM",
+            UploadKind::Document => "PDF ou DOCX",
+            UploadKind::Image => "JPEG, PNG ou WebP",
+            UploadKind::Logo => "PNG, JPEG, WebP ou GIF",
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UploadRejected {
+    #[error("Format de fichier non reconnu ({0} attendu)")]
+    Unrecognized(&'static str),
+    #[error("Fichier {detected} non accepté ici ({allowed} attendu)")]
+    NotAllowed { detected: &'static str, allowed: &'static str },
+    #[error("Le fichier ne correspond pas à son type déclaré ({declared}), c'est un {detected}")]
+    Mismatch { declared: String, detected: &'static str },
+    #[error("Vidéo trop volumineuse pour votre forfait ({max_mb} Mo maximum)")]
+    VideoTooLarge { max_mb: u64 },
+    #[error("Vidéo trop longue pour votre forfait ({max_secs} secondes maximum)")]
+    VideoTooLong { max_secs: u32 },
+}
+
+fn ftyp_brand(bytes: &[u8]) -> Option<&[u8]> {
+    (bytes.len() >= 12 && &bytes[4..8] == b"ftyp").then(|| &bytes[8..12])
+}
+
+/// Recognise a file from its magic bytes; the client's content type and extension
+/// are not looked at.
+pub fn sniff(bytes: &[u8]) -> Option<FileType> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Some(JPEG);
+    }
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some(PNG);
+    }
+    if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        return Some(GIF);
+    }
+    if bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == b"WEBP" {
+        return Some(WEBP);
+    }
+    if bytes.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        return Some(WEBM);
+    }
+    if bytes.starts_with(b"%PDF-") {
+        return Some(PDF);
+    }
+    // Word documents are zip archives with their parts under `word/`; entry names
+    // are stored uncompressed in the local headers.
+    if bytes.starts_with(b"PK\x03\x04") {
+        let has_word_part = bytes.windows(5).any(|w| w == b"word/");
+        return has_word_part.then_some(DOCX);
+    }
+    match ftyp_brand(bytes)? {
+        b"heic" | b"heix" | b"heim" | b"heis" | b"hevc" | b"hevx" | b"mif1" | b"msf1" => Some(HEIC),
+        b"qt  " => Some(MOV),
+        b"isom" | b"iso2" | b"iso4" | b"iso5" | b"iso6" | b"mp41" | b"mp42" | b"avc1" | b"M4V " | b"M4VP"
+        | b"dash" | b"3gp4" | b"3gp5" | b"3g2a" => Some(MP4),
+        _ => None,
+    }
+}
+
+/// Check an upload against what the endpoint accepts and return its real type.
+/// A declared content type or extension that disagrees with the bytes is refused;
+/// a missing or generic one is not.
+pub fn validate(kind: UploadKind, bytes: &[u8], declared_type: &str, filename: &str) -> Result<FileType, UploadRejected> {
+    let detected = sniff(bytes).ok_or(UploadRejected::Unrecognized(kind.label()))?;
+    if !kind.allowed().contains(&detected) {
+        return Err(UploadRejected::NotAllowed { detected: detected.content_type, allowed: kind.label() });
+    }
+
+    let declared = declared_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    let generic = declared.is_empty() || declared == "application/octet-stream";
+    if !generic && declared != detected.content_type && !detected.aliases.contains(&declared.as_str()) {
+        return Err(UploadRejected::Mismatch { declared, detected: detected.content_type });
+    }
+
+    let ext = Path::new(filename).extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+    if let Some(ext) = ext {
+        if !detected.extensions.contains(&ext.as_str()) {
+            return Err(UploadRejected::Mismatch { declared: format!(".{ext}"), detected: detected.content_type });
+        }
+    }
+    Ok(detected)
+}
+
+/// Largest video each plan may upload. Encrypted videos are decrypted in memory to
+/// be served, so this stays under `MEDIA_MAX_DECRYPT_MB`.
+pub fn max_video_bytes(plan: &PlanType) -> u64 {
+    match plan {
+        PlanType::Free => 50 * MIB,
+        PlanType::Standard => 150 * MIB,
+        PlanType::Premium => 250 * MIB,
+    }
+}
+
+/// Longest video each plan may upload, in seconds.
+pub fn max_video_secs(plan: &PlanType) -> u32 {
+    match plan {
+        PlanType::Free => 60,
+        PlanType::Standard => 3 * 60,
+        PlanType::Premium => 10 * 60,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JPEG_BYTES: &[u8] = &[0xFF, 0xD8, 0xFF, 0xE0, 0, 0x10, b'J', b'F', b'I', b'F'];
+
+    fn ftyp(brand: &[u8; 4]) -> Vec<u8> {
+        [&[0, 0, 0, 0x18][..], b"ftyp", brand, &[0, 0, 0, 0]].concat()
+    }
+
+    #[test]
+    fn files_are_recognised_by_their_bytes() {
+        assert_eq!(sniff(JPEG_BYTES), Some(JPEG));
+        assert_eq!(sniff(b"%PDF-1.7\n"), Some(PDF));
+        assert_eq!(sniff(b"PK\x03\x04....[Content_Types].xml....word/document.xml"), Some(DOCX));
+        assert_eq!(sniff(b"PK\x03\x04....xl/workbook.xml"), None);
+        assert_eq!(sniff(&ftyp(b"heic")), Some(HEIC));
+        assert_eq!(sniff(&ftyp(b"qt  ")), Some(MOV));
+        assert_eq!(sniff(&ftyp(b"isom")), Some(MP4));
+        assert_eq!(sniff(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>"), None);
+    }
+
+    #[test]
+    fn uploads_are_checked_against_the_endpoint_and_their_declared_type() {
+        assert_eq!(validate(UploadKind::Media, JPEG_BYTES, "image/jpeg", "a.JPG").unwrap(), JPEG);
+        assert_eq!(validate(UploadKind::Media, JPEG_BYTES, "application/octet-stream", "upload").unwrap(), JPEG);
+        // iPhones label MOV files as MP4 and the reverse
+        assert_eq!(validate(UploadKind::Media, &ftyp(b"qt  "), "video/mp4", "IMG_0001.MOV").unwrap(), MOV);
+        assert!(matches!(
+            validate(UploadKind::Document, JPEG_BYTES, "image/jpeg", "scan.jpg"),
+            Err(UploadRejected::NotAllowed { .. })
+        ));
+        assert!(matches!(
+            validate(UploadKind::Media, b"%PDF-1.4", "image/jpeg", "a.jpg"),
+            Err(UploadRejected::NotAllowed { .. })
+        ));
+        assert!(matches!(
+            validate(UploadKind::Document, b"%PDF-1.4", "text/html", "a.pdf"),
+            Err(UploadRejected::Mismatch { .. })
+        ));
+        assert!(matches!(
+            validate(UploadKind::Document, b"%PDF-1.4", "application/pdf", "a.exe"),
+            Err(UploadRejected::Mismatch { .. })
+        ));
+        assert!(matches!(validate(UploadKind::Image, b"hello", "image/png", "a.png"), Err(UploadRejected::Unrecognized(_))));
+    }
+}
@@ -0,0 +1,453 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::Deserialize;
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::db::tenant::schema_name;
+use crate::db::tenant_db::TenantDb;
+use crate::services::{
+    antivirus::SCAN_INFECTED,
+    encryption::{self, KeyRing},
+};
+
+pub const PREVIEW_PENDING: &str = "pending";
+pub const PREVIEW_READY: &str = "ready";
+pub const PREVIEW_FAILED: &str = "failed";
+
+/// Videos processed per tenant in one pass; each one is decoded in full.
+const BATCH_SIZE: i64 = 5;
+
+/// Pause between two passes over the pending videos.
+const JOB_INTERVAL_SECS: u64 = 30;
+
+/// A video still failing after this many passes is left `failed`.
+const MAX_ATTEMPTS: i32 = 3;
+
+/// Width of the poster shown before playback.
+const POSTER_WIDTH: u32 = 640;
+
+/// Sprite frames are this wide, at most one per `SPRITE_MIN_INTERVAL_SECS`.
+const SPRITE_FRAME_WIDTH: u32 = 160;
+const SPRITE_MAX_FRAMES: u32 = 100;
+const SPRITE_MIN_INTERVAL_SECS: f64 = 1.0;
+const SPRITE_COLUMNS: u32 = 10;
+
+/// What ffprobe reports about a video, upright (rotated phone videos have their
+/// width and height swapped).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VideoInfo {
+    pub duration_secs: f64,
+    pub width: i32,
+    pub height: i32,
+}
+
+#[derive(Deserialize)]
+struct Probe {
+    #[serde(default)]
+    streams: Vec<ProbeStream>,
+    format: ProbeFormat,
+}
+
+#[derive(Deserialize)]
+struct ProbeStream {
+    width: Option<i32>,
+    height: Option<i32>,
+    #[serde(default)]
+    tags: std::collections::HashMap<String, String>,
+    #[serde(default)]
+    side_data_list: Vec<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct ProbeFormat {
+    duration: Option<String>,
+}
+
+/// Parse `ffprobe -show_entries format=duration:stream=width,height:stream_tags=rotate:stream_side_data=rotation -of json`.
+pub fn parse_probe(json: &str) -> anyhow::Result<VideoInfo> {
+    let probe: Probe = serde_json::from_str(json)?;
+    let duration_secs: f64 = probe
+        .format
+        .duration
+        .as_deref()
+        .and_then(|d| d.parse().ok())
+        .filter(|d: &f64| d.is_finite() && *d > 0.0)
+        .ok_or_else(|| anyhow::anyhow!("durée inconnue"))?;
+    let stream = probe
+        .streams
+        .into_iter()
+        .find(|s| s.width.is_some() && s.height.is_some())
+        .ok_or_else(|| anyhow::anyhow!("aucune piste vidéo"))?;
+    let rotation = stream
+        .side_data_list
+        .iter()
+        .find_map(|d| d.get("rotation").and_then(|r| r.as_f64()))
+        .or_else(|| stream.tags.get("rotate").and_then(|r| r.parse().ok()))
+        .unwrap_or(0.0);
+    let (width, height) = (stream.width.unwrap_or(0), stream.height.unwrap_or(0));
+    let quarter_turn = (rotation.abs() as i64 / 90) % 2 == 1;
+    Ok(VideoInfo {
+        duration_secs,
+        width: if quarter_turn { height } else { width },
+        height: if quarter_turn { width } else { height },
+    })
+}
+
+/// How the frames of a video are laid out on its sprite sheet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpriteLayout {
+    pub frames: u32,
+    pub columns: u32,
+    pub rows: u32,
+    pub interval_secs: f64,
+}
+
+/// Evenly spaced frames, one per second for short videos and at most
+/// `SPRITE_MAX_FRAMES` for long ones.
+pub fn sprite_layout(duration_secs: f64) -> SpriteLayout {
+    let frames = ((duration_secs / SPRITE_MIN_INTERVAL_SECS).ceil() as u32).clamp(1, SPRITE_MAX_FRAMES);
+    let columns = frames.min(SPRITE_COLUMNS);
+    SpriteLayout {
+        frames,
+        columns,
+        rows: frames.div_ceil(columns),
+        interval_secs: duration_secs / frames as f64,
+    }
+}
+
+/// Run an ffmpeg tool, returning its standard output.
+async fn run(program: &str, args: &[&str]) -> anyhow::Result<Vec<u8>> {
+    let out = tokio::process::Command::new(program).args(args).output().await?;
+    if !out.status.success() {
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        anyhow::bail!("{program} : {}", stderr.lines().last().unwrap_or("").trim());
+    }
+    Ok(out.stdout)
+}
+
+async fn probe_file(input: &str) -> anyhow::Result<VideoInfo> {
+    let probe = run(
+        "ffprobe",
+        &[
+            "-v", "error",
+            "-select_streams", "v:0",
+            "-show_entries", "format=duration:stream=width,height:stream_tags=rotate:stream_side_data=rotation",
+            "-of", "json",
+            input,
+        ],
+    )
+    .await?;
+    parse_probe(&String::from_utf8_lossy(&probe))
+}
+
+/// Probe a plain video, written to the temp directory for the duration of the call.
+pub async fn probe(video: &[u8]) -> anyhow::Result<VideoInfo> {
+    let input = std::env::temp_dir().join(format!("{}.video", hex::encode(rand::random::<[u8; 16]>())));
+    tokio::fs::write(&input, video).await?;
+    let info = probe_file(&input.to_string_lossy()).await;
+    let _ = tokio::fs::remove_file(&input).await;
+    info
+}
+
+/// The poster and sprite sheet of a video, as JPEG, with what was learnt about it.
+struct Extracted {
+    info: VideoInfo,
+    layout: SpriteLayout,
+    poster: Vec<u8>,
+    sprite: Vec<u8>,
+    frame_width: i32,
+    frame_height: i32,
+}
+
+/// Extract everything from a plain video written to the temp directory; the plain
+/// files only live there for the duration of the call.
+async fn extract(video: &[u8]) -> anyhow::Result<Extracted> {
+    let name = hex::encode(rand::random::<[u8; 16]>());
+    let dir = std::env::temp_dir();
+    let input = dir.join(format!("{name}.video"));
+    let poster_file = dir.join(format!("{name}-poster.jpg"));
+    let sprite_file = dir.join(format!("{name}-sprite.jpg"));
+    tokio::fs::write(&input, video).await?;
+
+    let result = async {
+        let input = input.to_string_lossy();
+        let info = probe_file(&input).await?;
+
+        // A second in, so the poster is not a black fade-in frame
+        let at = format!("{:.2}", (info.duration_secs / 2.0).min(1.0));
+        let scale = format!("scale='min({POSTER_WIDTH},iw)':-2");
+        run("ffmpeg", &["-v", "error", "-y", "-ss", &at, "-i", &input, "-frames:v", "1", "-vf", &scale, "-q:v", "3", &poster_file.to_string_lossy()]).await?;
+
+        let layout = sprite_layout(info.duration_secs);
+        let filter = format!(
+            "fps={}/{:.3},scale={SPRITE_FRAME_WIDTH}:-2,tile={}x{}",
+            layout.frames, info.duration_secs, layout.columns, layout.rows
+        );
+        run("ffmpeg", &["-v", "error", "-y", "-i", &input, "-vf", &filter, "-frames:v", "1", "-q:v", "5", &sprite_file.to_string_lossy()]).await?;
+
+        let poster = tokio::fs::read(&poster_file).await?;
+        let sprite = tokio::fs::read(&sprite_file).await?;
+        let (sheet_width, sheet_height) = image::ImageReader::new(std::io::Cursor::new(&sprite))
+            .with_guessed_format()?
+            .into_dimensions()?;
+        Ok(Extracted {
+            info,
+            layout,
+            poster,
+            sprite,
+            frame_width: (sheet_width / layout.columns) as i32,
+            frame_height: (sheet_height / layout.rows) as i32,
+        })
+    }
+    .await;
+
+    for file in [&input, &poster_file, &sprite_file] {
+        let _ = tokio::fs::remove_file(file).await;
+    }
+    result
+}
+
+#[derive(sqlx::FromRow)]
+struct PendingVideo {
+    id: Uuid,
+    storage_path: String,
+    is_encrypted: bool,
+    encryption_iv: Option<Vec<u8>>,
+    encryption_tag: Option<Vec<u8>>,
+    key_version: i32,
+}
+
+/// A file made from a video, ready to be written.
+struct Derived {
+    rel_path: String,
+    full_path: PathBuf,
+    bytes: Vec<u8>,
+    iv: Option<Vec<u8>>,
+    tag: Option<Vec<u8>>,
+}
+
+pub struct VideoPreviewService;
+
+impl VideoPreviewService {
+    /// Extract the duration, poster and sprite of up to [`BATCH_SIZE`] pending videos of
+    /// one tenant. Videos sharing a stored file (duplicate uploads) are done together.
+    pub async fn process_pending(pool: &PgPool, media_dir: &str, keys: &KeyRing, tenant: &str) -> anyhow::Result<usize> {
+        let schema = schema_name(tenant);
+        let pending: Vec<PendingVideo> = sqlx::query_as(&format!(
+            r#"SELECT * FROM (
+                   SELECT DISTINCT ON (storage_path)
+                          id, storage_path, is_encrypted, encryption_iv, encryption_tag, key_version, created_at
+                   FROM "{schema}".media
+                   WHERE preview_status = $1 AND scan_status <> $2
+                   ORDER BY storage_path, created_at
+               ) v
+               ORDER BY created_at
+               LIMIT $3"#
+        ))
+        .bind(PREVIEW_PENDING)
+        .bind(SCAN_INFECTED)
+        .bind(BATCH_SIZE)
+        .fetch_all(pool)
+        .await?;
+
+        let mut done = 0;
+        for video in pending {
+            match Self::process(pool, &schema, media_dir, keys, tenant, &video).await {
+                Ok(true) => done += 1,
+                Ok(false) => {}
+                Err(e) => {
+                    warn!("Video preview of media {} in '{tenant}' failed: {e}", video.id);
+                    sqlx::query(&format!(
+                        r#"UPDATE "{schema}".media
+                           SET preview_attempts = preview_attempts + 1, preview_error = $2,
+                               preview_status = CASE WHEN preview_attempts + 1 >= $3 THEN $4 ELSE preview_status END
+                           WHERE storage_path = $1 AND preview_status = $5"#
+                    ))
+                    .bind(&video.storage_path)
+                    .bind(e.to_string())
+                    .bind(MAX_ATTEMPTS)
+                    .bind(PREVIEW_FAILED)
+                    .bind(PREVIEW_PENDING)
+                    .execute(pool)
+                    .await?;
+                }
+            }
+        }
+        Ok(done)
+    }
+
+    /// Returns false when the video changed meanwhile (deleted, or re-encrypted under
+    /// another key); the files written are then dropped and it is retried next pass.
+    async fn process(
+        pool: &PgPool,
+        schema: &str,
+        media_dir: &str,
+        keys: &KeyRing,
+        tenant: &str,
+        video: &PendingVideo,
+    ) -> anyhow::Result<bool> {
+        let mut bytes = tokio::fs::read(Path::new(media_dir).join(&video.storage_path)).await?;
+        // Derived files share the row's key version
+        let key = keys.tenant_key(video.key_version, tenant)?;
+        if video.is_encrypted {
+            let (Some(iv), Some(tag)) = (&video.encryption_iv, &video.encryption_tag) else {
+                anyhow::bail!("missing encryption metadata");
+            };
+            bytes = encryption::decrypt_file(&bytes, iv, tag, &key)?;
+        }
+        let extracted = extract(&bytes).await?;
+
+        // Written next to the video, encrypted like it
+        let dir = video.storage_path.rsplit_once('/').map(|(dir, _)| dir);
+        let derive = |plain: &[u8]| -> anyhow::Result<Derived> {
+            let name = hex::encode(rand::random::<[u8; 16]>());
+            let rel_path = match dir {
+                Some(dir) => format!("{dir}/{name}"),
+                None => name,
+            };
+            let (bytes, iv, tag) = if video.is_encrypted {
+                let (ciphertext, iv, tag) = encryption::encrypt_file(plain, &key)?;
+                (ciphertext, Some(iv), Some(tag))
+            } else {
+                (plain.to_vec(), None, None)
+            };
+            Ok(Derived { full_path: Path::new(media_dir).join(&rel_path), rel_path, bytes, iv, tag })
+        };
+        let poster = derive(&extracted.poster)?;
+        let sprite = derive(&extracted.sprite)?;
+
+        let updated = async {
+            tokio::fs::write(&poster.full_path, &poster.bytes).await?;
+            tokio::fs::write(&sprite.full_path, &sprite.bytes).await?;
+            let VideoInfo { duration_secs, width, height } = extracted.info;
+            let result = sqlx::query(&format!(
+                r#"UPDATE "{schema}".media
+                   SET duration_secs = $3, width = $4, height = $5,
+                       thumbnail_path = $6, thumbnail_encryption_iv = $7, thumbnail_encryption_tag = $8,
+                       sprite_path = $9, sprite_encryption_iv = $10, sprite_encryption_tag = $11,
+                       sprite_columns = $12, sprite_frames = $13, sprite_interval_secs = $14,
+                       sprite_frame_width = $15, sprite_frame_height = $16,
+                       preview_status = $17, preview_error = NULL
+                   WHERE storage_path = $1 AND key_version = $2 AND preview_status = $18"#
+            ))
+            .bind(&video.storage_path)
+            .bind(video.key_version)
+            .bind(duration_secs)
+            .bind(width)
+            .bind(height)
+            .bind(&poster.rel_path)
+            .bind(&poster.iv)
+            .bind(&poster.tag)
+            .bind(&sprite.rel_path)
+            .bind(&sprite.iv)
+            .bind(&sprite.tag)
+            .bind(extracted.layout.columns as i32)
+            .bind(extracted.layout.frames as i32)
+            .bind(extracted.layout.interval_secs)
+            .bind(extracted.frame_width)
+            .bind(extracted.frame_height)
+            .bind(PREVIEW_READY)
+            .bind(PREVIEW_PENDING)
+            .execute(pool)
+            .await?;
+            anyhow::Ok(result.rows_affected() > 0)
+        }
+        .await;
+
+        if !matches!(updated, Ok(true)) {
+            for file in [&poster.full_path, &sprite.full_path] {
+                let _ = tokio::fs::remove_file(file).await;
+            }
+        }
+        updated
+    }
+}
+
+/// Spawn the transcoding worker: duration, poster and scrubbing sprite of new videos in
+/// every garderie. Does nothing when ffmpeg is not installed.
+pub fn start(pool: PgPool, config: Arc<Config>) {
+    tokio::spawn(async move {
+        if run("ffprobe", &["-version"]).await.is_err() {
+            info!("Video previews disabled (ffmpeg not installed)");
+            return;
+        }
+        let keys = match KeyRing::from_config(&config) {
+            Ok(k) => k,
+            Err(e) => {
+                error!("Video preview job disabled: {e}");
+                return;
+            }
+        };
+
+        loop {
+            tokio::time::sleep(tokio::time::Duration::from_secs(JOB_INTERVAL_SECS)).await;
+            let Some(_job) = crate::services::worker::job().await else {
+                break;
+            };
+
+            let tenants: Vec<String> = match sqlx::query_scalar("SELECT slug FROM public.garderies WHERE archived_at IS NULL ORDER BY slug")
+                .fetch_all(&pool)
+                .await
+            {
+                Ok(rows) => rows,
+                Err(e) => {
+                    warn!("Video preview job: failed to query tenants: {e}");
+                    continue;
+                }
+            };
+
+            for slug in &tenants {
+                TenantDb::scope(slug.clone(), async {
+                    match VideoPreviewService::process_pending(&pool, &config.media_dir, &keys, slug).await {
+                        Ok(n) if n > 0 => info!("Video previews: {n} videos processed for '{slug}'"),
+                        Ok(_) => {}
+                        Err(e) => warn!("Video preview job: pass over '{slug}' failed: {e}"),
+                    }
+                })
+                .await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probe_output_is_parsed_upright() {
+        let landscape = r#"{"programs": [], "streams": [{"width": 1920, "height": 1080}], "format": {"duration": "12.480000"}}"#;
+        assert_eq!(
+            parse_probe(landscape).unwrap(),
+            VideoInfo { duration_secs: 12.48, width: 1920, height: 1080 }
+        );
+
+        // A phone held upright records a -90° rotation
+        let portrait = r#"{"streams": [{"width": 1920, "height": 1080,
+            "side_data_list": [{"side_data_type": "Display Matrix", "rotation": -90}]}],
+            "format": {"duration": "3.0"}}"#;
+        let info = parse_probe(portrait).unwrap();
+        assert_eq!((info.width, info.height), (1080, 1920));
+
+        let legacy = r#"{"streams": [{"width": 640, "height": 480, "tags": {"rotate": "270"}}], "format": {"duration": "1"}}"#;
+        assert_eq!(parse_probe(legacy).unwrap().width, 480);
+
+        assert!(parse_probe(r#"{"streams": [], "format": {"duration": "3.0"}}"#).is_err());
+        assert!(parse_probe(r#"{"streams": [{"width": 1, "height": 1}], "format": {"duration": "N/A"}}"#).is_err());
+    }
+
+    #[test]
+    fn sprites_have_one_frame_per_second_up_to_a_cap() {
+        let short = sprite_layout(4.2);
+        assert_eq!((short.frames, short.columns, short.rows), (5, 5, 1));
+        assert!((short.interval_secs - 0.84).abs() < 1e-9);
+        let long = sprite_layout(600.0);
+        assert_eq!((long.frames, long.columns, long.rows), (SPRITE_MAX_FRAMES, SPRITE_COLUMNS, 10));
+        assert_eq!(long.interval_secs, 6.0);
+        assert_eq!(sprite_layout(0.3).frames, 1);
+    }
+}
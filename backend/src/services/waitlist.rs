@@ -0,0 +1,266 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    db::tenant::schema_name,
+    models::{
+        child::Child,
+        waitlist::{ConvertWaitlistRequest, CreateWaitlistEntryRequest, UpdateWaitlistEntryRequest, WaitlistEntry, WaitlistQuery},
+    },
+    services::{
+        branding::BrandingService,
+        email::EmailService,
+        entitlements::{EntitlementService, Resource},
+    },
+};
+
+/// Why a waitlist entry could not be converted into a child record.
+#[derive(Debug, thiserror::Error)]
+pub enum WaitlistError {
+    #[error("Inscription introuvable")]
+    NotFound,
+    #[error("Cet enfant est déjà inscrit")]
+    AlreadyEnrolled,
+}
+
+/// Result of converting a waitlist entry.
+pub struct Conversion {
+    pub entry: WaitlistEntry,
+    pub child: Child,
+    /// False when the parent already has an account or an open invitation.
+    pub needs_invitation: bool,
+}
+
+pub struct WaitlistService;
+
+impl WaitlistService {
+    /// Highest priority first, then first come, first served.
+    pub async fn list(pool: &PgPool, tenant: &str, query: &WaitlistQuery) -> anyhow::Result<Vec<WaitlistEntry>> {
+        let schema = schema_name(tenant);
+        let entries = sqlx::query_as::<_, WaitlistEntry>(&format!(
+            "SELECT * FROM {schema}.waitlist_entries
+             WHERE ($1::TEXT IS NULL OR status = $1)
+               AND ($2::TEXT IS NULL OR age_group = $2)
+             ORDER BY priority DESC, created_at"
+        ))
+        .bind(&query.status)
+        .bind(&query.age_group)
+        .fetch_all(pool)
+        .await?;
+        Ok(entries)
+    }
+
+    pub async fn get(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<Option<WaitlistEntry>> {
+        let schema = schema_name(tenant);
+        let entry = sqlx::query_as::<_, WaitlistEntry>(&format!(
+            "SELECT * FROM {schema}.waitlist_entries WHERE id = $1"
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(entry)
+    }
+
+    pub async fn create(
+        pool: &PgPool,
+        tenant: &str,
+        req: &CreateWaitlistEntryRequest,
+    ) -> anyhow::Result<WaitlistEntry> {
+        let schema = schema_name(tenant);
+        let entry = sqlx::query_as::<_, WaitlistEntry>(&format!(
+            "INSERT INTO {schema}.waitlist_entries
+                (child_first_name, child_last_name, child_birth_date, parent_first_name, parent_last_name,
+                 parent_email, parent_phone, desired_start_date, age_group, priority, notes)
+             VALUES ($1, $2, $3, $4, $5, LOWER($6), $7, $8, $9, COALESCE($10, 0), $11)
+             RETURNING *"
+        ))
+        .bind(&req.child_first_name)
+        .bind(&req.child_last_name)
+        .bind(req.child_birth_date)
+        .bind(&req.parent_first_name)
+        .bind(&req.parent_last_name)
+        .bind(req.parent_email.trim())
+        .bind(&req.parent_phone)
+        .bind(req.desired_start_date)
+        .bind(&req.age_group)
+        .bind(req.priority)
+        .bind(&req.notes)
+        .fetch_one(pool)
+        .await?;
+        Ok(entry)
+    }
+
+    /// Moving an entry to `offered` stamps `offered_at`.
+    pub async fn update(
+        pool: &PgPool,
+        tenant: &str,
+        id: Uuid,
+        req: &UpdateWaitlistEntryRequest,
+    ) -> anyhow::Result<WaitlistEntry> {
+        let schema = schema_name(tenant);
+        let entry = sqlx::query_as::<_, WaitlistEntry>(&format!(
+            "UPDATE {schema}.waitlist_entries
+             SET child_first_name   = COALESCE($1, child_first_name),
+                 child_last_name    = COALESCE($2, child_last_name),
+                 child_birth_date   = COALESCE($3, child_birth_date),
+                 parent_first_name  = COALESCE($4, parent_first_name),
+                 parent_last_name   = COALESCE($5, parent_last_name),
+                 parent_email       = COALESCE(LOWER($6), parent_email),
+                 parent_phone       = COALESCE($7, parent_phone),
+                 desired_start_date = COALESCE($8, desired_start_date),
+                 age_group          = COALESCE($9, age_group),
+                 priority           = COALESCE($10, priority),
+                 notes              = COALESCE($12, notes),
+                 offered_at         = CASE WHEN $11 = 'offered' AND status <> 'offered' THEN NOW() ELSE offered_at END,
+                 status             = COALESCE($11, status)
+             WHERE id = $13
+             RETURNING *"
+        ))
+        .bind(&req.child_first_name)
+        .bind(&req.child_last_name)
+        .bind(req.child_birth_date)
+        .bind(&req.parent_first_name)
+        .bind(&req.parent_last_name)
+        .bind(req.parent_email.as_deref().map(str::trim))
+        .bind(&req.parent_phone)
+        .bind(req.desired_start_date)
+        .bind(&req.age_group)
+        .bind(req.priority)
+        .bind(&req.status)
+        .bind(&req.notes)
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+        Ok(entry)
+    }
+
+    pub async fn delete(pool: &PgPool, tenant: &str, id: Uuid) -> anyhow::Result<()> {
+        let schema = schema_name(tenant);
+        sqlx::query(&format!("DELETE FROM {schema}.waitlist_entries WHERE id = $1"))
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Create the child record from the entry and link the parent in one transaction.
+    ///
+    /// A parent who already has an account is linked directly; otherwise the email is
+    /// recorded as a pending parent, promoted when the invitation is accepted.
+    pub async fn convert(
+        pool: &PgPool,
+        tenant: &str,
+        id: Uuid,
+        req: &ConvertWaitlistRequest,
+    ) -> anyhow::Result<Conversion> {
+        let schema = schema_name(tenant);
+        let mut tx = pool.begin().await?;
+
+        let entry = sqlx::query_as::<_, WaitlistEntry>(&format!(
+            "SELECT * FROM {schema}.waitlist_entries WHERE id = $1 FOR UPDATE"
+        ))
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(WaitlistError::NotFound)?;
+
+        if entry.status == "enrolled" {
+            return Err(WaitlistError::AlreadyEnrolled.into());
+        }
+        EntitlementService::ensure_can_add(pool, tenant, Resource::Children, 1).await?;
+
+        let child = sqlx::query_as::<_, Child>(&format!(
+            "INSERT INTO {schema}.children (first_name, last_name, birth_date, group_id, notes, start_date, schedule_days)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             RETURNING *"
+        ))
+        .bind(&entry.child_first_name)
+        .bind(&entry.child_last_name)
+        .bind(entry.child_birth_date)
+        .bind(req.group_id)
+        .bind(&entry.notes)
+        .bind(req.start_date.or(entry.desired_start_date))
+        .bind(&req.schedule_days)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let parent_id: Option<Uuid> = sqlx::query_scalar(&format!(
+            "SELECT id FROM {schema}.users WHERE LOWER(email) = $1"
+        ))
+        .bind(&entry.parent_email)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let needs_invitation = match parent_id {
+            Some(user_id) => {
+                sqlx::query(&format!(
+                    "INSERT INTO {schema}.child_parents (child_id, user_id, relationship)
+                     VALUES ($1, $2, 'parent')
+                     ON CONFLICT (child_id, user_id) DO NOTHING"
+                ))
+                .bind(child.id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+                false
+            }
+            None => {
+                sqlx::query(&format!(
+                    "INSERT INTO {schema}.child_pending_parents (child_id, email, relationship)
+                     VALUES ($1, $2, 'parent')
+                     ON CONFLICT (child_id, email) DO NOTHING"
+                ))
+                .bind(child.id)
+                .bind(&entry.parent_email)
+                .execute(&mut *tx)
+                .await?;
+
+                let invited: bool = sqlx::query_scalar(&format!(
+                    "SELECT EXISTS(SELECT 1 FROM {schema}.invitation_tokens
+                                   WHERE email = $1 AND used = FALSE AND expires_at > NOW())"
+                ))
+                .bind(&entry.parent_email)
+                .fetch_one(&mut *tx)
+                .await?;
+                !invited
+            }
+        };
+
+        let entry = sqlx::query_as::<_, WaitlistEntry>(&format!(
+            "UPDATE {schema}.waitlist_entries SET status = 'enrolled', child_id = $1 WHERE id = $2 RETURNING *"
+        ))
+        .bind(child.id)
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Conversion { entry, child, needs_invitation })
+    }
+
+    /// Tell the parent a spot is available.
+    pub async fn send_offer(
+        pool: &PgPool,
+        email_svc: &EmailService,
+        tenant: &str,
+        entry: &WaitlistEntry,
+    ) -> anyhow::Result<()> {
+        let (garderie_name, branding) = BrandingService::for_email(pool, tenant).await;
+
+        let parent_name = format!("{} {}", entry.parent_first_name, entry.parent_last_name);
+        let child_name = format!("{} {}", entry.child_first_name, entry.child_last_name);
+        let start_date = entry.desired_start_date.map(|d| d.format("%Y-%m-%d").to_string());
+
+        email_svc
+            .send_waitlist_offer(
+                tenant,
+                &entry.parent_email,
+                &parent_name,
+                &child_name,
+                start_date.as_deref(),
+                &garderie_name,
+                &branding,
+            )
+            .await
+    }
+}
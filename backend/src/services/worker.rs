@@ -0,0 +1,215 @@
+//! Background jobs: schedulers, the notification consumer, transcoding, scans and
+//! backups. The `worker` binary runs them; the API process does too unless
+//! `RUN_BACKGROUND_JOBS=false`.
+//!
+//! Each job loop takes a slot with [`job`] before doing its work, which caps how
+//! many run at once (`WORKER_CONCURRENCY`) and lets [`shutdown`] stop new work and
+//! wait for the running jobs.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, OnceLock,
+};
+use std::time::Duration;
+
+use sqlx::PgPool;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{
+    config::Config,
+    services::{
+        absence_scheduler, antivirus, backup_scheduler, departure_scheduler, document_expiry_scheduler, email::EmailService,
+        emergency::Channels, emergency_scheduler, journal_scheduler, key_rotation, meeting_scheduler,
+        notification_consumer, notifications::NotificationService, operation_scheduler, push_token_scheduler,
+        ratio_scheduler, redaction, retention_scheduler, signature_scheduler, sms::SmsService, stats_scheduler,
+        trial_scheduler,
+        video_previews,
+    },
+};
+
+const DEFAULT_CONCURRENCY: usize = 8;
+
+struct Limiter {
+    permits: Arc<Semaphore>,
+    size: u32,
+    stopping: AtomicBool,
+}
+
+impl Limiter {
+    fn new(size: usize) -> Self {
+        let size = size.clamp(1, Semaphore::MAX_PERMITS.min(u32::MAX as usize));
+        Self { permits: Arc::new(Semaphore::new(size)), size: size as u32, stopping: AtomicBool::new(false) }
+    }
+
+    async fn job(&self) -> Option<Job> {
+        if self.stopping.load(Ordering::Acquire) {
+            return None;
+        }
+        let permit = self.permits.clone().acquire_owned().await.ok()?;
+        // Shutdown may have started while waiting for the slot
+        if self.stopping.load(Ordering::Acquire) {
+            return None;
+        }
+        Some(Job { _permit: permit })
+    }
+
+    async fn shutdown(&self, grace: Duration) -> bool {
+        self.stopping.store(true, Ordering::Release);
+        tokio::time::timeout(grace, self.permits.acquire_many(self.size)).await.is_ok()
+    }
+}
+
+static LIMITER: OnceLock<Limiter> = OnceLock::new();
+
+fn limiter() -> &'static Limiter {
+    LIMITER.get_or_init(|| Limiter::new(DEFAULT_CONCURRENCY))
+}
+
+/// A running background job; dropping it frees its slot.
+pub struct Job {
+    _permit: OwnedSemaphorePermit,
+}
+
+/// Wait for a free slot before doing a unit of background work. `None` once the
+/// process is shutting down: the job loop should stop.
+pub async fn job() -> Option<Job> {
+    limiter().job().await
+}
+
+/// Stop handing out slots and wait up to `grace` for the running jobs; `false` if
+/// some were still running.
+pub async fn shutdown(grace: Duration) -> bool {
+    limiter().shutdown(grace).await
+}
+
+/// Resolves on Ctrl-C or SIGTERM (sent by Docker on `stop`).
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+/// What the background jobs need, built the same way by the API and the worker.
+pub struct Context {
+    pub pool: PgPool,
+    pub redis_client: redis::Client,
+    pub config: Arc<Config>,
+    pub email: Option<Arc<EmailService>>,
+    pub sms: Option<Arc<SmsService>>,
+    pub notifications: Arc<NotificationService>,
+}
+
+/// Spawn every background job. The first call sets the concurrency limit.
+pub fn start_all(ctx: Context) {
+    let _ = LIMITER.set(Limiter::new(ctx.config.worker_concurrency));
+    let Context { pool, redis_client, config, email, sms, notifications } = ctx;
+
+    // Email, push, SMS and WebSocket fan-out of the events published to the Redis
+    // stream by request handlers
+    notification_consumer::start(
+        pool.clone(),
+        redis_client.clone(),
+        email.clone(),
+        sms.clone(),
+        notifications.clone(),
+        config.clone(),
+    );
+
+    // Journal auto-send
+    journal_scheduler::start(pool.clone(), email.clone(), config.clone());
+
+    // Automatic absence marking at each garderie's cutoff time (checked every minute)
+    absence_scheduler::start(pool.clone(), email.clone(), notifications.clone(), config.app_base_url.clone());
+
+    // Child:educator ratio checks with alerts to admins (every 15 minutes)
+    ratio_scheduler::start(pool.clone(), email.clone(), config.app_base_url.clone());
+
+    // Nightly statistics rollups for GET /stats (daily at 1:30 AM)
+    stats_scheduler::start(pool.clone());
+
+    // Nightly pruning of push tokens unused for 60 days (daily at 3:45 AM)
+    push_token_scheduler::start(pool.clone());
+
+    // Departures of the day, final export links and end of parent access (daily at 0:30 AM)
+    departure_scheduler::start(pool.clone(), email.clone(), config.clone());
+
+    // Retention periods of each garderie, or a dry-run report (daily at 4:15 AM)
+    retention_scheduler::start(pool.clone(), config.clone());
+
+    // Trial expiry warnings (daily at 9 AM)
+    trial_scheduler::start(pool.clone(), email.clone(), redis_client.clone());
+
+    // Unsigned document reminders (daily at 10 AM)
+    signature_scheduler::start(pool.clone(), email.clone(), redis_client.clone(), config.app_base_url.clone());
+
+    // Document expiry reminders (daily at 8 AM)
+    document_expiry_scheduler::start(pool.clone(), email.clone(), config.app_base_url.clone());
+
+    // Parent-teacher meeting reminders (every 15 minutes)
+    meeting_scheduler::start(pool.clone(), email.clone());
+
+    // Emergency broadcast delivery retries (every 15 seconds)
+    emergency_scheduler::start(
+        pool.clone(),
+        Channels { email: email.clone(), sms, notifications, app_base_url: config.app_base_url.clone() },
+    );
+
+    // Super-admin operations once their cancel window elapses (every minute)
+    operation_scheduler::start(pool.clone(), config.clone());
+
+    // Nightly per-garderie S3 backups (BACKUP_SCHEDULE, default 02:00)
+    backup_scheduler::start(pool.clone(), config.clone(), email);
+
+    // Re-encryption of files still under a previous master key
+    key_rotation::start(pool.clone(), config.clone());
+
+    // Antivirus scanning of new uploads (every 30 seconds, when CLAMAV_ADDR is set)
+    antivirus::start(pool.clone(), config.clone());
+
+    // Transcoding: video duration, poster and scrubbing sprite (every 30 seconds)
+    video_previews::start(pool.clone(), config.clone());
+
+    // Blurred photo copies (every minute; faces found by FACE_DETECTION_URL)
+    redaction::start(pool, config);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn shutdown_waits_for_running_jobs_and_refuses_new_ones() {
+        let limiter = Limiter::new(2);
+        let running = limiter.job().await.unwrap();
+        let _other = limiter.job().await.unwrap();
+        drop(running);
+
+        // The second job is still running
+        assert!(!limiter.shutdown(Duration::from_millis(20)).await);
+        assert!(limiter.job().await.is_none());
+
+        let limiter = Limiter::new(1);
+        let running = limiter.job().await.unwrap();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            drop(running);
+        });
+        assert!(limiter.shutdown(Duration::from_secs(5)).await);
+    }
+}
@@ -0,0 +1,11 @@
+-- Login history (successful and failed attempts of known users)
+CREATE TABLE IF NOT EXISTS "{schema}".login_events (
+    id         UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    user_id    UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
+    success    BOOLEAN NOT NULL,
+    method     VARCHAR(16) NOT NULL,
+    ip_address VARCHAR(64),
+    user_agent TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS login_events_user_idx ON "{schema}".login_events (user_id, created_at DESC);
@@ -0,0 +1,18 @@
+-- Single sign-on (OIDC) for staff; the client secret is encrypted with the tenant key
+CREATE TABLE IF NOT EXISTS "{schema}".oidc_settings (
+    id                   BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    provider             VARCHAR(16) NOT NULL,
+    issuer_url           TEXT NOT NULL,
+    client_id            TEXT NOT NULL,
+    client_secret        BYTEA,
+    client_secret_iv     BYTEA,
+    client_secret_tag    BYTEA,
+    secret_key_version   INT NOT NULL DEFAULT 1,
+    enabled              BOOLEAN NOT NULL DEFAULT FALSE,
+    allowed_domains      TEXT[] NOT NULL DEFAULT '{}',
+    jit_provisioning     BOOLEAN NOT NULL DEFAULT FALSE,
+    jit_role             VARCHAR(32) NOT NULL DEFAULT 'educateur',
+    updated_by           UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+    created_at           TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at           TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,8 @@
+-- Token families for reuse detection. Rotated tokens keep the family of the login
+-- that opened the session; rotated_at tells a replay apart from a logout.
+ALTER TABLE "{schema}".refresh_tokens
+    ADD COLUMN IF NOT EXISTS family_id  UUID,
+    ADD COLUMN IF NOT EXISTS parent_id  UUID,
+    ADD COLUMN IF NOT EXISTS rotated_at TIMESTAMPTZ;
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_family
+    ON "{schema}".refresh_tokens(family_id) WHERE revoked = FALSE;
@@ -0,0 +1,17 @@
+-- Every email sent on behalf of the garderie, one row per recipient. raw_message keeps
+-- the sent message for a resend; it is not kept for sign-in emails (codes and links).
+CREATE TABLE "{schema}".email_log (
+    id                UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    recipient         VARCHAR(255) NOT NULL,
+    template          VARCHAR(48) NOT NULL,
+    subject           TEXT NOT NULL,
+    status            VARCHAR(16) NOT NULL CHECK (status IN ('sent', 'failed', 'suppressed')),
+    message_id        TEXT,
+    provider_response TEXT,
+    error             TEXT,
+    raw_message       BYTEA,
+    resent_from       UUID REFERENCES "{schema}".email_log(id) ON DELETE SET NULL,
+    created_at        TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX email_log_created_idx ON "{schema}".email_log (created_at DESC);
+CREATE INDEX email_log_recipient_idx ON "{schema}".email_log (LOWER(recipient), created_at DESC);
@@ -0,0 +1,29 @@
+-- Announcements sent with open and click tracking. A recipient row is only a random
+-- token carried by the pixel and links of one email: stats are aggregated and never
+-- tell who opened what.
+CREATE TABLE "{schema}".email_campaigns (
+    id         UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    subject    TEXT NOT NULL,
+    message_id UUID REFERENCES "{schema}".messages(id) ON DELETE SET NULL,
+    sent_by    UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX email_campaigns_created_idx ON "{schema}".email_campaigns (created_at DESC);
+
+CREATE TABLE "{schema}".email_campaign_recipients (
+    id          UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    campaign_id UUID NOT NULL REFERENCES "{schema}".email_campaigns(id) ON DELETE CASCADE,
+    opened_at   TIMESTAMPTZ,
+    opens       INT NOT NULL DEFAULT 0,
+    clicked_at  TIMESTAMPTZ,
+    clicks      INT NOT NULL DEFAULT 0
+);
+CREATE INDEX email_campaign_recipients_campaign_idx ON "{schema}".email_campaign_recipients (campaign_id);
+
+CREATE TABLE "{schema}".email_campaign_links (
+    id          UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    campaign_id UUID NOT NULL REFERENCES "{schema}".email_campaigns(id) ON DELETE CASCADE,
+    url         TEXT NOT NULL,
+    clicks      INT NOT NULL DEFAULT 0,
+    UNIQUE (campaign_id, url)
+);
@@ -0,0 +1,13 @@
+-- Children marked absent because they were not checked in by the garderie's cutoff
+-- time. A parent (or staff) confirms the absence; a late check-in reconciles it.
+CREATE TABLE "{schema}".auto_absences (
+    id            UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    child_id      UUID NOT NULL REFERENCES "{schema}".children(id) ON DELETE CASCADE,
+    date          DATE NOT NULL,
+    marked_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    confirmed_at  TIMESTAMPTZ,
+    confirmed_by  UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+    reconciled_at TIMESTAMPTZ,
+    UNIQUE (child_id, date)
+);
+CREATE INDEX auto_absences_date_idx ON "{schema}".auto_absences (date);
@@ -0,0 +1,39 @@
+-- Child:educator ratios. Each rule covers an age band [min, max) in months; the
+-- defaults follow the Quebec regulation and can be changed by the garderie.
+CREATE TABLE "{schema}".ratio_rules (
+    id                    UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    label                 VARCHAR(64) NOT NULL,
+    min_age_months        INT NOT NULL CHECK (min_age_months >= 0),
+    max_age_months        INT CHECK (max_age_months > min_age_months),
+    children_per_educator INT NOT NULL CHECK (children_per_educator > 0),
+    created_at            TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+INSERT INTO "{schema}".ratio_rules (label, min_age_months, max_age_months, children_per_educator) VALUES
+    ('0 à 18 mois', 0, 18, 5),
+    ('18 mois à 4 ans', 18, 48, 8),
+    ('4 à 5 ans', 48, 72, 10),
+    ('Âge scolaire', 72, NULL, 20);
+
+-- Educators on duty in a group.
+CREATE TABLE "{schema}".staff_shifts (
+    id         UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    user_id    UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
+    group_id   UUID NOT NULL REFERENCES "{schema}".groups(id) ON DELETE CASCADE,
+    date       DATE NOT NULL,
+    start_time TIME NOT NULL,
+    end_time   TIME NOT NULL CHECK (end_time > start_time),
+    created_by UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX staff_shifts_date_group_idx ON "{schema}".staff_shifts (date, group_id);
+
+-- One email to the admins per group and day out of compliance.
+CREATE TABLE "{schema}".ratio_alerts (
+    group_id  UUID NOT NULL REFERENCES "{schema}".groups(id) ON DELETE CASCADE,
+    date      DATE NOT NULL,
+    children  INT NOT NULL,
+    educators INT NOT NULL,
+    required  INT NOT NULL,
+    sent_at   TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (group_id, date)
+);
@@ -0,0 +1,23 @@
+-- Daily rollups behind GET /stats, computed once a day is over so the dashboard never
+-- scans messages, media or login history live. Login events are purged after 90 days;
+-- the rollups keep the trend.
+CREATE TABLE "{schema}".stats_daily (
+    date                 DATE PRIMARY KEY,
+    active_parents       INT NOT NULL,
+    messages             INT NOT NULL,
+    media_bytes          BIGINT NOT NULL,
+    logins               INT NOT NULL,
+    failed_logins        INT NOT NULL,
+    invitations_sent     INT NOT NULL,
+    invitations_accepted INT NOT NULL,
+    computed_at          TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Journals sent by educator and day, out of the children present in their groups.
+CREATE TABLE "{schema}".stats_daily_educators (
+    date      DATE NOT NULL,
+    user_id   UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
+    expected  INT NOT NULL,
+    completed INT NOT NULL,
+    PRIMARY KEY (date, user_id)
+);
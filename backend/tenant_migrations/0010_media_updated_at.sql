@@ -0,0 +1,11 @@
+-- Version of a media for conditional updates (If-Match); existing rows start at
+-- their upload time.
+ALTER TABLE "{schema}".media ADD COLUMN updated_at TIMESTAMPTZ;
+UPDATE "{schema}".media SET updated_at = created_at;
+ALTER TABLE "{schema}".media
+    ALTER COLUMN updated_at SET DEFAULT NOW(),
+    ALTER COLUMN updated_at SET NOT NULL;
+
+CREATE TRIGGER "media_updated_at"
+    BEFORE UPDATE ON "{schema}"."media"
+    FOR EACH ROW EXECUTE FUNCTION "{schema}".update_updated_at();
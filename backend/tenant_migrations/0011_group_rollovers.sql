@@ -0,0 +1,21 @@
+-- School-year rollovers: each run moves children between groups at once.
+CREATE TABLE "{schema}".group_rollovers (
+    id            UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    -- "mapping" (source → target groups) or "age" (age bands)
+    mode          VARCHAR(16) NOT NULL,
+    as_of         DATE NOT NULL,
+    moved_count   INT NOT NULL,
+    created_by    UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Groups a child has left, latest last. The group name is kept for groups deleted since.
+CREATE TABLE "{schema}".child_group_history (
+    id          UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    child_id    UUID NOT NULL REFERENCES "{schema}".children(id) ON DELETE CASCADE,
+    group_id    UUID REFERENCES "{schema}".groups(id) ON DELETE SET NULL,
+    group_name  VARCHAR(255) NOT NULL,
+    left_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    rollover_id UUID REFERENCES "{schema}".group_rollovers(id) ON DELETE SET NULL
+);
+CREATE INDEX child_group_history_child_idx ON "{schema}".child_group_history (child_id, left_at);
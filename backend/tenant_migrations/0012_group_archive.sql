@@ -0,0 +1,2 @@
+-- Archived groups are hidden from pickers but keep their threads, media and documents.
+ALTER TABLE "{schema}".groups ADD COLUMN archived_at TIMESTAMPTZ;
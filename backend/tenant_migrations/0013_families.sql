@@ -0,0 +1,23 @@
+-- Households: siblings grouped together, with the parents linked to any of them.
+CREATE TABLE "{schema}".families (
+    id               UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    name             VARCHAR(255) NOT NULL,
+    notes            TEXT,
+    -- The monthly batch bills the family's children on one invoice
+    combined_billing BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at       TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE TRIGGER "families_updated_at"
+BEFORE UPDATE ON "{schema}"."families"
+FOR EACH ROW EXECUTE FUNCTION "{schema}".update_updated_at();
+
+ALTER TABLE "{schema}".children ADD COLUMN family_id UUID REFERENCES "{schema}".families(id) ON DELETE SET NULL;
+CREATE INDEX children_family_idx ON "{schema}".children (family_id);
+
+-- Family invoices: the lines say which child they bill, for parents' access and tax receipts.
+ALTER TABLE "{schema}".invoices ADD COLUMN family_id UUID REFERENCES "{schema}".families(id) ON DELETE SET NULL;
+ALTER TABLE "{schema}".invoice_items ADD COLUMN child_id UUID REFERENCES "{schema}".children(id) ON DELETE SET NULL;
+
+ALTER TYPE "{schema}".send_to_parents_scope ADD VALUE IF NOT EXISTS 'family_parents';
+ALTER TABLE "{schema}".messages ADD COLUMN send_to_parents_family UUID REFERENCES "{schema}".families(id) ON DELETE SET NULL;
@@ -0,0 +1,32 @@
+-- Emergency broadcasts (evacuation, closure): every parent is reached by email, push and SMS.
+CREATE TABLE "{schema}".emergency_broadcasts (
+    id          UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    -- Copy posted in the broadcast thread
+    message_id  UUID REFERENCES "{schema}".messages(id) ON DELETE SET NULL,
+    sender_id   UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+    subject     VARCHAR(255) NOT NULL,
+    content     TEXT NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- One row per parent and channel, worked through by the emergency job queue.
+CREATE TABLE "{schema}".emergency_deliveries (
+    id              UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    broadcast_id    UUID NOT NULL REFERENCES "{schema}".emergency_broadcasts(id) ON DELETE CASCADE,
+    user_id         UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
+    channel         VARCHAR(8) NOT NULL CHECK (channel IN ('email', 'push', 'sms')),
+    status          VARCHAR(16) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'sent', 'failed', 'skipped')),
+    attempts        INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    error           TEXT,
+    sent_at         TIMESTAMPTZ,
+    UNIQUE (broadcast_id, user_id, channel)
+);
+CREATE INDEX emergency_deliveries_pending_idx ON "{schema}".emergency_deliveries (next_attempt_at) WHERE status = 'pending';
+
+CREATE TABLE "{schema}".emergency_acknowledgements (
+    broadcast_id    UUID NOT NULL REFERENCES "{schema}".emergency_broadcasts(id) ON DELETE CASCADE,
+    user_id         UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
+    acknowledged_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (broadcast_id, user_id)
+);
@@ -0,0 +1,4 @@
+-- When a device last registered or received a push; tokens unused for long are pruned.
+ALTER TABLE "{schema}".push_tokens ADD COLUMN last_used_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+UPDATE "{schema}".push_tokens SET last_used_at = created_at;
+CREATE INDEX idx_push_tokens_last_used_at ON "{schema}".push_tokens (last_used_at);
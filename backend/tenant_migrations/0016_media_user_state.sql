@@ -0,0 +1,10 @@
+-- Each user's own marks on media: starred favorites, and photos hidden from their gallery.
+CREATE TABLE "{schema}".media_user_state (
+    media_id   UUID NOT NULL REFERENCES "{schema}".media(id) ON DELETE CASCADE,
+    user_id    UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
+    favorite   BOOLEAN NOT NULL DEFAULT FALSE,
+    hidden     BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (media_id, user_id)
+);
+CREATE INDEX media_user_state_user_idx ON "{schema}".media_user_state (user_id);
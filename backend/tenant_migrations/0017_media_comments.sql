@@ -0,0 +1,9 @@
+-- Comments under photos and videos, by the parents of the children shown and by staff.
+CREATE TABLE "{schema}".media_comments (
+    id         UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    media_id   UUID NOT NULL REFERENCES "{schema}".media(id) ON DELETE CASCADE,
+    author_id  UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+    content    TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX media_comments_media_idx ON "{schema}".media_comments (media_id, created_at);
@@ -0,0 +1,21 @@
+-- Blurred copies of group photos, shown where a child without photo consent must not
+-- be recognisable. Regions are fractions of the photo (0–1), drawn by staff or found
+-- by the face detection service; the copy is rendered by a background job.
+CREATE TABLE "{schema}".media_redactions (
+    id             UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    media_id       UUID NOT NULL UNIQUE REFERENCES "{schema}".media(id) ON DELETE CASCADE,
+    regions        JSONB NOT NULL DEFAULT '[]',
+    source         TEXT NOT NULL DEFAULT 'manual' CHECK (source IN ('manual', 'detected')),
+    status         TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'ready', 'failed')),
+    attempts       INT NOT NULL DEFAULT 0,
+    last_error     TEXT,
+    redacted_path  TEXT,
+    encryption_iv  BYTEA,
+    encryption_tag BYTEA,
+    key_version    INT NOT NULL DEFAULT 1,
+    requested_by   UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at     TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX media_redactions_pending_idx ON "{schema}".media_redactions (updated_at) WHERE status = 'pending';
+CREATE INDEX media_redactions_path_idx ON "{schema}".media_redactions (redacted_path);
@@ -0,0 +1,18 @@
+-- Video previews made by the transcoding worker: the poster goes in thumbnail_path,
+-- and a sprite sheet of evenly spaced frames serves the player's scrubbing preview.
+ALTER TABLE "{schema}".media
+    ADD COLUMN preview_status        VARCHAR(16),
+    ADD COLUMN preview_attempts      INT NOT NULL DEFAULT 0,
+    ADD COLUMN preview_error         TEXT,
+    ADD COLUMN sprite_path           TEXT,
+    ADD COLUMN sprite_encryption_iv  BYTEA,
+    ADD COLUMN sprite_encryption_tag BYTEA,
+    ADD COLUMN sprite_columns        INT,
+    ADD COLUMN sprite_frames         INT,
+    ADD COLUMN sprite_interval_secs  DOUBLE PRECISION,
+    ADD COLUMN sprite_frame_width    INT,
+    ADD COLUMN sprite_frame_height   INT;
+
+UPDATE "{schema}".media SET preview_status = 'pending' WHERE media_type = 'video';
+
+CREATE INDEX media_preview_pending_idx ON "{schema}".media (created_at) WHERE preview_status = 'pending';
@@ -0,0 +1,25 @@
+-- Expiring links giving someone without an account (an inspector…) access to one
+-- document. Only the SHA-256 of the token is kept; the link is shown once.
+CREATE TABLE "{schema}".document_shares (
+    id               UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    document_id      UUID NOT NULL REFERENCES "{schema}".documents(id) ON DELETE CASCADE,
+    token_sha256     TEXT NOT NULL UNIQUE,
+    scope            TEXT NOT NULL DEFAULT 'view' CHECK (scope IN ('view', 'download')),
+    recipient        TEXT,
+    expires_at       TIMESTAMPTZ NOT NULL,
+    revoked_at       TIMESTAMPTZ,
+    created_by       UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    access_count     INT NOT NULL DEFAULT 0,
+    last_accessed_at TIMESTAMPTZ
+);
+CREATE INDEX document_shares_document_idx ON "{schema}".document_shares (document_id, created_at DESC);
+
+CREATE TABLE "{schema}".document_share_accesses (
+    id          UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    share_id    UUID NOT NULL REFERENCES "{schema}".document_shares(id) ON DELETE CASCADE,
+    accessed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ip_address  TEXT,
+    user_agent  TEXT
+);
+CREATE INDEX document_share_accesses_share_idx ON "{schema}".document_share_accesses (share_id, accessed_at DESC);
@@ -0,0 +1,2 @@
+-- Documents (forms, policies…) copied into the garderies cloned from this one.
+ALTER TABLE "{schema}".documents ADD COLUMN is_template BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,14 @@
+-- GET /messages/conversations: latest message and unread count of each thread.
+-- The INCLUDE columns let the unread counts run as index-only scans.
+CREATE INDEX messages_broadcast_thread_idx ON "{schema}".messages (created_at DESC)
+    INCLUDE (id, sender_id, is_deleted)
+    WHERE message_type = 'broadcast';
+CREATE INDEX messages_group_thread_idx ON "{schema}".messages (group_id, created_at DESC)
+    INCLUDE (id, sender_id, is_deleted)
+    WHERE message_type = 'group';
+CREATE INDEX messages_individual_sender_idx ON "{schema}".messages (sender_id, created_at DESC)
+    INCLUDE (id, is_read)
+    WHERE message_type = 'individual';
+CREATE INDEX messages_individual_recipient_idx ON "{schema}".messages (recipient_id, created_at DESC)
+    INCLUDE (id, is_read)
+    WHERE message_type = 'individual';
@@ -0,0 +1,55 @@
+-- One row per conversation: the broadcast thread, one per group and one per parent
+-- talking with the staff. `key` is the thread's name in thread_states and in the
+-- unread counters ("broadcast", "group:<group id>", "individual:<parent id>").
+CREATE TABLE "{schema}".threads (
+    id         UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    key        VARCHAR(64) NOT NULL UNIQUE,
+    kind       VARCHAR(16) NOT NULL CHECK (kind IN ('broadcast', 'group', 'individual')),
+    group_id   UUID REFERENCES "{schema}".groups(id) ON DELETE CASCADE,
+    parent_id  UUID REFERENCES "{schema}".users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((kind = 'group') = (group_id IS NOT NULL)),
+    CHECK ((kind = 'individual') = (parent_id IS NOT NULL))
+);
+
+-- Left NULL only on legacy rows no thread can hold (a group message without a
+-- group, a staff message without a recipient), which no view ever showed.
+ALTER TABLE "{schema}".messages ADD COLUMN thread_id UUID REFERENCES "{schema}".threads(id);
+
+-- Backfill
+INSERT INTO "{schema}".threads (key, kind) VALUES ('broadcast', 'broadcast');
+
+INSERT INTO "{schema}".threads (key, kind, group_id)
+SELECT 'group:' || g.id, 'group', g.id
+FROM "{schema}".groups g
+WHERE EXISTS (SELECT 1 FROM "{schema}".messages m WHERE m.message_type = 'group' AND m.group_id = g.id);
+
+-- The parent of an individual message is its sender when a parent wrote it (to the
+-- garderie, without a recipient), its recipient otherwise
+INSERT INTO "{schema}".threads (key, kind, parent_id)
+SELECT DISTINCT 'individual:' || p.id, 'individual', p.id
+FROM "{schema}".messages m
+JOIN "{schema}".users s ON s.id = m.sender_id
+JOIN "{schema}".users p ON p.id = CASE WHEN s.role = 'parent' THEN m.sender_id ELSE m.recipient_id END
+WHERE m.message_type = 'individual';
+
+UPDATE "{schema}".messages m SET thread_id = t.id
+FROM "{schema}".threads t
+WHERE t.kind = 'broadcast' AND m.message_type = 'broadcast';
+
+UPDATE "{schema}".messages m SET thread_id = t.id
+FROM "{schema}".threads t
+WHERE t.group_id = m.group_id AND m.message_type = 'group';
+
+UPDATE "{schema}".messages m SET thread_id = t.id
+FROM "{schema}".users s, "{schema}".threads t
+WHERE s.id = m.sender_id AND m.message_type = 'individual'
+  AND t.parent_id = CASE WHEN s.role = 'parent' THEN m.sender_id ELSE m.recipient_id END;
+
+-- Thread pages, latest message and unread counts; supersedes the per-kind indexes
+CREATE INDEX messages_thread_idx ON "{schema}".messages (thread_id, created_at DESC)
+    INCLUDE (id, sender_id, is_read, is_deleted);
+DROP INDEX "{schema}".messages_broadcast_thread_idx;
+DROP INDEX "{schema}".messages_group_thread_idx;
+DROP INDEX "{schema}".messages_individual_sender_idx;
+DROP INDEX "{schema}".messages_individual_recipient_idx;
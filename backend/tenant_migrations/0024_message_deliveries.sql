@@ -0,0 +1,12 @@
+-- What became of the email, push and SMS notifications of each message, per
+-- recipient. Written by the notification worker, read by the staff's delivery panel.
+CREATE TABLE "{schema}".message_deliveries (
+    message_id   UUID NOT NULL REFERENCES "{schema}".messages(id) ON DELETE CASCADE,
+    user_id      UUID NOT NULL REFERENCES "{schema}".users(id) ON DELETE CASCADE,
+    channel      VARCHAR(8) NOT NULL CHECK (channel IN ('email', 'push', 'sms')),
+    status       VARCHAR(16) NOT NULL CHECK (status IN ('sent', 'failed', 'skipped')),
+    -- Why it failed or was skipped
+    detail       TEXT,
+    attempted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (message_id, user_id, channel)
+);
@@ -0,0 +1,18 @@
+-- Out-of-office of staff members: parents writing to them between starts_on and
+-- ends_on get the message back in their thread.
+CREATE TABLE "{schema}".auto_replies (
+    user_id    UUID PRIMARY KEY REFERENCES "{schema}".users(id) ON DELETE CASCADE,
+    starts_on  DATE NOT NULL,
+    ends_on    DATE NOT NULL CHECK (ends_on >= starts_on),
+    message    TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Threads already answered during the current absence: one auto-reply per thread.
+-- Cleared when the out-of-office is changed.
+CREATE TABLE "{schema}".auto_reply_threads (
+    user_id    UUID NOT NULL REFERENCES "{schema}".auto_replies(user_id) ON DELETE CASCADE,
+    thread_id  UUID NOT NULL REFERENCES "{schema}".threads(id) ON DELETE CASCADE,
+    replied_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, thread_id)
+);
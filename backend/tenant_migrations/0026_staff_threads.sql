@@ -0,0 +1,11 @@
+-- Staff-only channels: one for the whole staff ("staff") and one per group
+-- ("staff:<group id>"), never shown to parents. Messages in them have the 'staff'
+-- type; the new value is not used before this transaction commits.
+ALTER TYPE "{schema}".message_type ADD VALUE IF NOT EXISTS 'staff';
+
+ALTER TABLE "{schema}".threads
+    DROP CONSTRAINT threads_kind_check,
+    DROP CONSTRAINT threads_check,
+    ADD CONSTRAINT threads_kind_check CHECK (kind IN ('broadcast', 'group', 'individual', 'staff')),
+    ADD CONSTRAINT threads_group_check CHECK (kind <> 'group' OR group_id IS NOT NULL),
+    ADD CONSTRAINT threads_group_kind_check CHECK (kind IN ('group', 'staff') OR group_id IS NULL);
@@ -0,0 +1,43 @@
+-- Retention periods of the garderie (Loi 25). The nightly job only reports what it
+-- would delete until `purge_enabled` is turned on.
+CREATE TABLE "{schema}".retention_settings (
+    id                     BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    journal_years          SMALLINT NOT NULL DEFAULT 3 CHECK (journal_years BETWEEN 1 AND 50),
+    media_years            SMALLINT NOT NULL DEFAULT 1 CHECK (media_years BETWEEN 0 AND 50),
+    message_years          SMALLINT NOT NULL DEFAULT 2 CHECK (message_years BETWEEN 1 AND 50),
+    purge_enabled          BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_by             UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+    updated_at             TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Day the child left the garderie; their media are kept `media_years` after it.
+ALTER TABLE "{schema}".children ADD COLUMN departed_on DATE;
+UPDATE "{schema}".children SET departed_on = updated_at::DATE WHERE is_active = FALSE;
+
+-- Legal holds suspend the purge, for one child or (child_id NULL) the whole garderie.
+CREATE TABLE "{schema}".legal_holds (
+    id          UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    child_id    UUID REFERENCES "{schema}".children(id) ON DELETE CASCADE,
+    reason      TEXT NOT NULL,
+    created_by  UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    released_by UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+    released_at TIMESTAMPTZ
+);
+CREATE INDEX legal_holds_active_idx ON "{schema}".legal_holds (child_id) WHERE released_at IS NULL;
+
+-- One line per nightly pass, dry runs included.
+CREATE TABLE "{schema}".retention_runs (
+    id             UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    dry_run        BOOLEAN NOT NULL,
+    journals       INT NOT NULL DEFAULT 0,
+    journal_events INT NOT NULL DEFAULT 0,
+    media          INT NOT NULL DEFAULT 0,
+    messages       INT NOT NULL DEFAULT 0,
+    held           BOOLEAN NOT NULL DEFAULT FALSE,
+    ran_at         TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX retention_runs_ran_at_idx ON "{schema}".retention_runs (ran_at DESC);
+
+CREATE INDEX daily_journals_date_idx ON "{schema}".daily_journals (date);
+CREATE INDEX journal_events_date_idx ON "{schema}".journal_events (date);
@@ -0,0 +1,24 @@
+-- Departure flow: `departed_on` (0027) may now be set ahead of time. The child is
+-- deactivated that day (`departure_applied_at`), and parents who have no other
+-- child at the garderie keep their account until `parent_access_until`
+-- (`parent_access_ended_at` once the nightly job closed it).
+ALTER TABLE "{schema}".children
+    ADD COLUMN parent_access_until    DATE,
+    ADD COLUMN departure_applied_at   TIMESTAMPTZ,
+    ADD COLUMN parent_access_ended_at TIMESTAMPTZ;
+CREATE INDEX children_departure_idx ON "{schema}".children (departed_on)
+    WHERE departure_applied_at IS NULL AND parent_access_until IS NOT NULL;
+
+-- Final export links sent to the parents; only the SHA-256 of the token is kept.
+CREATE TABLE "{schema}".departure_exports (
+    id               UUID PRIMARY KEY DEFAULT public.uuid_generate_v4(),
+    child_id         UUID NOT NULL REFERENCES "{schema}".children(id) ON DELETE CASCADE,
+    token_sha256     TEXT NOT NULL UNIQUE,
+    expires_at       TIMESTAMPTZ NOT NULL,
+    created_by       UUID REFERENCES "{schema}".users(id) ON DELETE SET NULL,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    emailed_to       INT NOT NULL DEFAULT 0,
+    access_count     INT NOT NULL DEFAULT 0,
+    last_accessed_at TIMESTAMPTZ
+);
+CREATE INDEX departure_exports_child_idx ON "{schema}".departure_exports (child_id, created_at DESC);
@@ -3,7 +3,7 @@
 import { useState, useEffect, useRef, useCallback } from "react";
 import { useTranslations } from "next-intl";
 import useSWR, { useSWRConfig } from "swr";
-import { childrenApi, groupsApi, usersApi, attendanceApi, journalApi, activitiesApi, menusApi, settingsApi } from "../../../../lib/api";
+import { childrenApi, groupsApi, usersApi, attendanceApi, journalApi, activitiesApi, menusApi, settingsApi, childPhotoSrc } from "../../../../lib/api";
 import { useAuth } from "../../../../hooks/useAuth";
 import { getTodayInMontreal, formatDateInMontreal } from "../../../../lib/dateUtils";
 import { Plus, ChevronDown, ChevronUp, UserPlus, X, Pencil, ChevronLeft, ChevronRight, Loader2, Check, BookOpen, Clock, CheckCircle, XCircle, AlertCircle, ThermometerSun, FileText, UserX, UserCheck, Notebook, CircleCheck, CircleX } from "lucide-react";
@@ -1944,7 +1944,7 @@ function ChildCard({
     return `${Math.floor(months / 12)} ${t("years")}`;
   };
 
-  const photoUrl = child.photo_url ? `${process.env.NEXT_PUBLIC_API_URL}/media/files/${child.photo_url}` : null;
+  const photoUrl = childPhotoSrc(child.photo_url);
 
   return (
     <div className="bg-white border border-slate-200 rounded-xl overflow-hidden p-5">
@@ -2140,7 +2140,7 @@ export default function ChildrenPage() {
               : isWeekday;
             const todayStatus = childAttendance?.[today] ??
               (isScheduled ? "attendu" : undefined);
-            const photoUrl = child.photo_url ? `${process.env.NEXT_PUBLIC_API_URL}/media/files/${child.photo_url}` : null;
+            const photoUrl = childPhotoSrc(child.photo_url);
 
             return (
               <button
@@ -2295,7 +2295,7 @@ export default function ChildrenPage() {
               const todayStatus = childAttendance?.[today] ??
                 (isScheduled ? "attendu" : undefined);
 
-              const photoUrl = child.photo_url ? `${process.env.NEXT_PUBLIC_API_URL}/media/files/${child.photo_url}` : null;
+              const photoUrl = childPhotoSrc(child.photo_url);
               return (
                 <button
                   key={child.id}
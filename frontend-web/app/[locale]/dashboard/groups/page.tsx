@@ -5,13 +5,14 @@ import { useTranslations } from "next-intl";
 import useSWR from "swr";
 import { groupsApi, childrenApi } from "../../../../lib/api";
 import { useAuth } from "../../../../hooks/useAuth";
-import { Plus, Trash2, Pencil, X, Users, Loader2 } from "lucide-react";
+import { Plus, Trash2, Pencil, X, Users, Loader2, Archive, ArchiveRestore } from "lucide-react";
 
 interface Group {
   id: string;
   name: string;
   description: string | null;
   color: string | null;
+  archived_at: string | null;
 }
 
 interface Child {
@@ -58,6 +59,8 @@ function GroupDetails({
   canWrite,
   onEdit,
   onDelete,
+  onArchive,
+  onRestore,
 }: {
   group: Group;
   allChildren: Child[];
@@ -65,6 +68,8 @@ function GroupDetails({
   canWrite: boolean;
   onEdit: (group: Group) => void;
   onDelete: (id: string) => void;
+  onArchive: (id: string) => void;
+  onRestore: (id: string) => void;
 }) {
   const t = useTranslations("groups");
   const tc = useTranslations("common");
@@ -81,6 +86,11 @@ function GroupDetails({
               style={{ backgroundColor: group.color || "#3b82f6" }}
             />
             <h3 className="text-sm font-semibold text-slate-800">{group.name}</h3>
+            {group.archived_at && (
+              <span className="px-2 py-0.5 rounded-full bg-slate-100 text-xs text-slate-500">
+                {t("archived")}
+              </span>
+            )}
           </div>
           {canWrite && (
             <div className="flex gap-1">
@@ -91,6 +101,23 @@ function GroupDetails({
               >
                 <Pencil className="w-4 h-4" />
               </button>
+              {group.archived_at ? (
+                <button
+                  onClick={() => onRestore(group.id)}
+                  className="p-1.5 text-slate-400 hover:text-blue-600 hover:bg-blue-50 rounded-lg transition"
+                  title={t("restore")}
+                >
+                  <ArchiveRestore className="w-4 h-4" />
+                </button>
+              ) : (
+                <button
+                  onClick={() => onArchive(group.id)}
+                  className="p-1.5 text-slate-400 hover:text-amber-600 hover:bg-amber-50 rounded-lg transition"
+                  title={t("archive")}
+                >
+                  <Archive className="w-4 h-4" />
+                </button>
+              )}
               <button
                 onClick={() => onDelete(group.id)}
                 className="p-1.5 text-slate-400 hover:text-red-500 hover:bg-red-50 rounded-lg transition"
@@ -157,7 +184,7 @@ export default function GroupsPage() {
   const [editState, setEditState] = useState<EditState | null>(null);
   const [editSaving, setEditSaving] = useState(false);
 
-  const { data: groupsData, mutate: mutateGroups } = useSWR("groups-list", () => groupsApi.list());
+  const { data: groupsData, mutate: mutateGroups } = useSWR("groups-list", () => groupsApi.list(true));
   const { data: childrenData, mutate: mutateChildren } = useSWR("children-groups-page", () => childrenApi.list());
 
   const groups: Group[] = (groupsData as { data: Group[] } | undefined)?.data ?? [];
@@ -237,6 +264,28 @@ export default function GroupsPage() {
     }
   };
 
+  // Archive / restore
+  const handleArchive = async (id: string) => {
+    if (!confirm(t("confirmArchive"))) return;
+    try {
+      await groupsApi.archive(id);
+      mutateGroups();
+    } catch (err: unknown) {
+      const msg = (err as { response?: { data?: { error?: string } } })?.response?.data?.error;
+      alert(msg || tc("error"));
+    }
+  };
+
+  const handleRestore = async (id: string) => {
+    try {
+      await groupsApi.restore(id);
+      mutateGroups();
+    } catch (err: unknown) {
+      const msg = (err as { response?: { data?: { error?: string } } })?.response?.data?.error;
+      alert(msg || tc("error"));
+    }
+  };
+
   const selectedGroup = groups.find((g) => g.id === selectedGroupId);
 
   return (
@@ -304,6 +353,8 @@ export default function GroupsPage() {
               canWrite={canWrite}
               onEdit={openEdit}
               onDelete={handleDelete}
+              onArchive={handleArchive}
+              onRestore={handleRestore}
             />
           </div>
         ) : null}
@@ -369,6 +420,8 @@ export default function GroupsPage() {
                 canWrite={canWrite}
                 onEdit={openEdit}
                 onDelete={handleDelete}
+                onArchive={handleArchive}
+                onRestore={handleRestore}
               />
             </div>
           </div>
@@ -6,9 +6,12 @@ import useSWR from "swr";
 import { mediaApi, childrenApi, groupsApi } from "../../../../lib/api";
 import {
   Upload, X, Globe, Users, Baby, Pencil, Trash2,
-  Lock, ChevronLeft, ChevronRight, Download, CheckSquare, Square
+  Lock, ChevronLeft, ChevronRight, Download, CheckSquare, Square, Star
 } from "lucide-react";
 import { BottomSheet } from "../../../../components/BottomSheet";
+import { MediaComments } from "../../../../components/media/MediaComments";
+import { MediaRedaction } from "../../../../components/media/MediaRedaction";
+import { formatDuration } from "../../../../lib/dateUtils";
 
 interface MediaItem {
   id: string;
@@ -16,12 +19,16 @@ interface MediaItem {
   storage_path: string;
   thumbnail_path: string | null;
   media_type: "photo" | "video";
+  /** Videos: filled in with the poster (thumbnail_path) once processed. */
+  duration_secs: number | null;
   caption: string | null;
   group_id: string | null;
   child_id: string | null;
   child_ids: string[];
   visibility: "private" | "public" | "group" | "child";
   created_at: string;
+  /** Parents (and staff) who starred it */
+  favorite_count?: number;
 }
 
 interface Child { id: string; first_name: string; last_name: string; }
@@ -120,9 +127,9 @@ export default function MediaPage() {
     })
   );
   const { data: childrenData } = useSWR("children-media", () => childrenApi.list());
-  const { data: groupsData } = useSWR("groups-media", () => groupsApi.list());
+  const { data: groupsData } = useSWR("groups-media", () => groupsApi.list(true));
 
-  const mediaItems: MediaItem[] = (data as { data: MediaItem[] } | undefined)?.data ?? [];
+  const mediaItems: MediaItem[] = (data as { data: { items: MediaItem[] } } | undefined)?.data.items ?? [];
   const children: Child[] = (childrenData as { data: Child[] } | undefined)?.data ?? [];
   const groups: Group[] = (groupsData as { data: Group[] } | undefined)?.data ?? [];
 
@@ -525,6 +532,7 @@ export default function MediaPage() {
             {lightboxItem.media_type === "video" ? (
               <video
                 src={`${API_URL}/media/files/${lightboxItem.storage_path}`}
+                poster={lightboxItem.thumbnail_path ? `${API_URL}/media/files/${lightboxItem.thumbnail_path}` : undefined}
                 controls
                 className="max-h-[80vh] max-w-full rounded-lg"
               />
@@ -574,6 +582,11 @@ export default function MediaPage() {
                 {tc("delete")}
               </button>
             </div>
+
+            <MediaComments mediaId={lightboxItem.id} />
+            {lightboxItem.media_type === "photo" && (
+              <MediaRedaction mediaId={lightboxItem.id} src={`${API_URL}/media/files/${lightboxItem.storage_path}`} />
+            )}
           </div>
 
           {/* Close */}
@@ -955,6 +968,11 @@ function MediaCard({
             </div>
           </div>
         )}
+        {item.duration_secs != null && (
+          <span className="absolute bottom-2 right-2 px-1.5 py-0.5 rounded bg-black/60 text-white text-xs font-medium tabular-nums pointer-events-none">
+            {formatDuration(item.duration_secs)}
+          </span>
+        )}
 
         {/* Checkbox */}
         <button
@@ -999,9 +1017,15 @@ function MediaCard({
           </button>
         </div>
       </div>
-      {item.caption && (
+      {(item.caption || !!item.favorite_count) && (
         <div className="px-3 py-2">
-          <p className="text-xs text-slate-600 line-clamp-2">{item.caption}</p>
+          {item.caption && <p className="text-xs text-slate-600 line-clamp-2">{item.caption}</p>}
+          {!!item.favorite_count && (
+            <p className="flex items-center gap-1 text-xs text-amber-600 mt-0.5">
+              <Star className="w-3 h-3" fill="currentColor" />
+              {t("favoriteCount", { n: item.favorite_count })}
+            </p>
+          )}
         </div>
       )}
     </div>
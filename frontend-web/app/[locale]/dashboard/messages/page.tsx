@@ -119,7 +119,11 @@ export default function MessagesPage() {
   const handleWsMessage = useCallback(
     (data: unknown) => {
       const msg = data as { type: string };
-      if (msg?.type === "new_message") {
+      if (
+        msg?.type === "new_message" ||
+        msg?.type === "message_updated" ||
+        msg?.type === "message_deleted"
+      ) {
         refreshThread();
         refreshConversations();
       }
@@ -4,16 +4,25 @@ import { useState } from "react";
 import { useTranslations } from "next-intl";
 import { useAuth } from "../../../hooks/useAuth";
 import useSWR from "swr";
-import { groupsApi, childrenApi, messagesApi, attendanceApi } from "../../../lib/api";
+import { groupsApi, childrenApi, messagesApi, attendanceApi, ratiosApi, childPhotoSrc } from "../../../lib/api";
 import { format, startOfWeek, endOfWeek, eachDayOfInterval, getISODay, isSameDay } from "date-fns";
 import { fr, enUS } from "date-fns/locale";
 import { useParams } from "next/navigation";
-import { AlertCircle, ChevronLeft, ChevronRight } from "lucide-react";
+import { AlertCircle, AlertTriangle, ChevronLeft, ChevronRight } from "lucide-react";
 import { ChildAvatar } from "../../../components/ChildAvatar";
 import { DayTabBar } from "../../../components/journal/DayTabBar";
 
 const fetcher = (fn: () => Promise<{ data: unknown }>) => fn().then((r) => r.data);
 
+interface GroupRatio {
+  group_id: string;
+  group_name: string;
+  children: number;
+  educators: number;
+  required_educators: number;
+  compliant: boolean;
+}
+
 interface Child {
   id: string;
   first_name: string;
@@ -38,6 +47,13 @@ export default function DashboardPage() {
     () => attendanceApi.getMonthAllChildren(currentMonth)
   );
 
+  const { data: ratios } = useSWR(
+    user?.role === "admin_garderie" ? "ratios-current" : null,
+    () => ratiosApi.current().then((r) => r.data as { groups: GroupRatio[] }),
+    { refreshInterval: 5 * 60 * 1000 }
+  );
+  const outOfRatio = ratios?.groups.filter((g) => !g.compliant) ?? [];
+
   const recentMessages = (messages as { data: { data?: unknown[] } } | undefined)?.data as { id: string; content: string; created_at: string; message_type: string }[] | undefined;
   const childrenList = (children as { data: unknown[] } | undefined)?.data as Child[] | undefined;
   const groupsList = (groups as { data: unknown[] } | undefined)?.data as { id: string }[] | undefined;
@@ -117,6 +133,27 @@ export default function DashboardPage() {
         />
       </div>
 
+      {outOfRatio.length > 0 && (
+        <div className="rounded-xl border border-red-200 bg-red-50 p-5 mb-8">
+          <div className="flex items-center gap-2 mb-3">
+            <AlertTriangle size={18} strokeWidth={1.5} className="text-red-600" />
+            <h2 className="text-h3 font-semibold text-red-700">{t("ratioWarning")}</h2>
+          </div>
+          <ul className="space-y-1 text-body text-red-700">
+            {outOfRatio.map((g) => (
+              <li key={g.group_id}>
+                {t("ratioGroup", {
+                  group: g.group_name,
+                  children: g.children,
+                  educators: g.educators,
+                  required: g.required_educators,
+                })}
+              </li>
+            ))}
+          </ul>
+        </div>
+      )}
+
       {/* Absent children section - Week view with summary table */}
       {(user?.role === "educateur" || user?.role === "admin_garderie") && (
         <div className="bg-surface-card/80 backdrop-blur-sm rounded-xl p-6 mb-8 shadow-soft">
@@ -191,7 +228,7 @@ export default function DashboardPage() {
                                     firstName={child.first_name}
                                     lastName={child.last_name}
                                     size="sm"
-                                    photoUrl={child.photo_url ? `${process.env.NEXT_PUBLIC_API_URL}/media/files/${child.photo_url}` : null}
+                                    photoUrl={childPhotoSrc(child.photo_url)}
                                   />
                                   <span className="text-caption font-medium text-status-danger truncate">
                                     {child.first_name}
@@ -276,7 +313,7 @@ export default function DashboardPage() {
                               firstName={child.first_name}
                               lastName={child.last_name}
                               size="md"
-                              photoUrl={child.photo_url ? `${process.env.NEXT_PUBLIC_API_URL}/media/files/${child.photo_url}` : null}
+                              photoUrl={childPhotoSrc(child.photo_url)}
                             />
                             <div className="flex-1">
                               <span className="text-body-lg font-semibold text-status-danger">
@@ -6,7 +6,7 @@ import useSWR from "swr";
 import { useAuth } from "../../../../hooks/useAuth";
 import { useTenantInfo } from "../../../../hooks/useTenantInfo";
 import { authApi, tenantApi, settingsApi, childrenApi } from "../../../../lib/api";
-import { Eye, EyeOff, Save, AlertCircle, Check, Upload, Trash2, Clock, Download, FileUp, FileDown } from "lucide-react";
+import { Eye, EyeOff, Save, AlertCircle, Check, Upload, Trash2, Clock, Download, FileUp, FileDown, MailOpen, UserX } from "lucide-react";
 
 export default function ProfilePage() {
   const t = useTranslations("profile");
@@ -22,7 +22,7 @@ export default function ProfilePage() {
   // Journal auto-send settings
   const { data: settingsData, mutate: mutateSettings } = useSWR(
     isStaff ? "settings" : null,
-    () => settingsApi.get().then((r) => r.data as { journal_auto_send_time: string })
+    () => settingsApi.get().then((r) => r.data as { journal_auto_send_time: string; email_tracking_enabled: boolean; auto_absence_cutoff: string | null })
   );
   const [sendTime, setSendTime] = useState("16:30");
   const [savingTime, setSavingTime] = useState(false);
@@ -49,6 +49,47 @@ export default function ProfilePage() {
     }
   };
 
+  const [cutoff, setCutoff] = useState("");
+  const [savingCutoff, setSavingCutoff] = useState(false);
+
+  useEffect(() => {
+    setCutoff(settingsData?.auto_absence_cutoff ?? "");
+  }, [settingsData]);
+
+  const handleSaveCutoff = async () => {
+    setSavingCutoff(true);
+    setError("");
+    try {
+      await settingsApi.update({ auto_absence_cutoff: cutoff });
+      mutateSettings();
+      setSuccess(t("autoAbsenceSaved"));
+      setTimeout(() => setSuccess(""), 3000);
+    } catch (err: unknown) {
+      const e = err as { response?: { data?: { error?: string } } };
+      setError(e?.response?.data?.error || t("updateError"));
+    } finally {
+      setSavingCutoff(false);
+    }
+  };
+
+  const [savingTracking, setSavingTracking] = useState(false);
+
+  const handleToggleTracking = async (enabled: boolean) => {
+    setSavingTracking(true);
+    setError("");
+    try {
+      await settingsApi.update({ email_tracking_enabled: enabled });
+      mutateSettings();
+      setSuccess(t("emailTrackingSaved"));
+      setTimeout(() => setSuccess(""), 3000);
+    } catch (err: unknown) {
+      const e = err as { response?: { data?: { error?: string } } };
+      setError(e?.response?.data?.error || t("updateError"));
+    } finally {
+      setSavingTracking(false);
+    }
+  };
+
   const [logoFile, setLogoFile] = useState<File | null>(null);
   const [logoPreview, setLogoPreview] = useState<string | null>(null);
   const [logoLoading, setLogoLoading] = useState(false);
@@ -420,6 +461,55 @@ export default function ProfilePage() {
         </div>
       )}
 
+      {isAdmin && (
+        <div className="mt-6 bg-white rounded-xl border border-slate-200 p-6">
+          <div className="flex items-center gap-2 mb-1">
+            <UserX className="w-5 h-5 text-slate-600" />
+            <h2 className="text-xl font-bold text-slate-800">{t("autoAbsence")}</h2>
+          </div>
+          <p className="mt-2 text-sm text-slate-500">{t("autoAbsenceDesc")}</p>
+          <div className="mt-4 flex items-center gap-3">
+            <label className="text-sm font-medium text-slate-700 whitespace-nowrap">{t("autoAbsenceCutoff")}</label>
+            <input
+              type="time"
+              value={cutoff}
+              onChange={(e) => setCutoff(e.target.value)}
+              className="px-3 py-2 border border-slate-300 rounded-lg text-sm focus:outline-none focus:ring-2 focus:ring-blue-500"
+            />
+            <button
+              type="button"
+              onClick={handleSaveCutoff}
+              disabled={savingCutoff}
+              className="flex items-center gap-2 px-4 py-2 bg-ink text-white rounded-pill text-body hover:opacity-90 transition-all duration-[180ms] disabled:opacity-50"
+            >
+              <Save className="w-4 h-4" />
+              {savingCutoff ? "..." : t("update")}
+            </button>
+          </div>
+          <p className="text-xs text-slate-400 mt-1">{t("autoAbsenceOffHint")}</p>
+        </div>
+      )}
+
+      {isAdmin && (
+        <div className="mt-6 bg-white rounded-xl border border-slate-200 p-6">
+          <div className="flex items-center gap-2 mb-1">
+            <MailOpen className="w-5 h-5 text-slate-600" />
+            <h2 className="text-xl font-bold text-slate-800">{t("emailTracking")}</h2>
+          </div>
+          <p className="mt-2 text-sm text-slate-500">{t("emailTrackingDesc")}</p>
+          <label className="mt-4 flex items-center gap-3 text-sm font-medium text-slate-700">
+            <input
+              type="checkbox"
+              checked={settingsData?.email_tracking_enabled ?? false}
+              disabled={!settingsData || savingTracking}
+              onChange={(e) => handleToggleTracking(e.target.checked)}
+              className="w-4 h-4 rounded border-slate-300"
+            />
+            {t("emailTrackingEnabled")}
+          </label>
+        </div>
+      )}
+
       {(user?.role === "admin_garderie" || user?.role === "super_admin") && (
         <div className="mt-6 bg-white rounded-xl border border-slate-200 p-6">
           <h2 className="text-xl font-bold text-slate-800 mb-4">{t("logoSection")}</h2>
@@ -4,7 +4,7 @@ import { useState, useCallback } from "react";
 import { useTranslations } from "next-intl";
 import useSWR from "swr";
 import { usersApi, authApi } from "../../../../lib/api";
-import { Mail, UserCheck, UserX, Pencil, X, Check, KeyRound, Lock, Camera, AlertCircle } from "lucide-react";
+import { Mail, MailX, UserCheck, UserX, Pencil, X, Check, KeyRound, Lock, Camera, AlertCircle } from "lucide-react";
 import PendingInvitationsTable from "../../../../components/PendingInvitationsTable";
 
 interface TenantUser {
@@ -18,6 +18,7 @@ interface TenantUser {
   privacy_accepted?: boolean;
   photos_accepted?: boolean;
   deletion_requested?: boolean;
+  email_undeliverable?: "bounce" | "complaint" | null;
 }
 
 const ROLE_COLORS: Record<string, string> = {
@@ -104,6 +105,11 @@ export default function UsersPage() {
     setDeleteModalOpen(true);
   }, []);
 
+  const handleClearSuppression = useCallback(async (id: string) => {
+    await usersApi.clearEmailSuppression(id);
+    mutate();
+  }, [mutate]);
+
   const confirmDelete = useCallback(async () => {
     if (!deleteTargetId) return;
     setSaving(true);
@@ -300,7 +306,20 @@ export default function UsersPage() {
                     <td className="px-4 py-3 font-medium text-slate-800">
                       {u.first_name} {u.last_name}
                     </td>
-                    <td className="px-4 py-3 text-slate-500">{u.email}</td>
+                    <td className="px-4 py-3 text-slate-500">
+                      {u.email}
+                      {u.email_undeliverable && (
+                        <span className="flex items-center gap-1 mt-0.5 text-xs text-red-600"
+                          title={u.email_undeliverable === "complaint" ? t("emailComplaint") : t("emailBounce")}>
+                          <MailX className="w-3.5 h-3.5" />
+                          {t("emailUndeliverable")}
+                          <button onClick={() => handleClearSuppression(u.id)}
+                            className="ml-1 underline hover:text-red-800">
+                            {t("emailUndeliverableClear")}
+                          </button>
+                        </span>
+                      )}
+                    </td>
                     <td className="px-4 py-3">
                       <span className={`px-2 py-0.5 rounded-full text-xs font-medium ${ROLE_COLORS[u.role] || "bg-slate-100 text-slate-600"}`}>
                         {roleLabels[u.role] || u.role}
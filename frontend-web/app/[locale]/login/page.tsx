@@ -18,9 +18,11 @@ export default function LoginPage() {
   const params = useParams();
   const searchParams = useSearchParams();
   const locale = params.locale as string;
-  const { name: tenantName, logo_url: tenantLogoUrl, notFound } = useTenantInfo();
+  const { name: tenantName, logo_url: tenantLogoUrl, notFound, archived: tenantArchived, ssoEnabled, maintenance: tenantMaintenance } = useTenantInfo();
 
   const trialExpired = searchParams.get("reason") === "trial_expired";
+  const archived = tenantArchived || searchParams.get("reason") === "archived";
+  const maintenance = tenantMaintenance || searchParams.get("reason") === "maintenance";
 
   // Step 1 state
   const [email, setEmail] = useState("");
@@ -81,6 +83,19 @@ export default function LoginPage() {
     }
   };
 
+  const handleSso = async () => {
+    setError("");
+    setLoading(true);
+    try {
+      const res = await authApi.oidcLogin(locale);
+      window.location.href = res.data.authorization_url;
+    } catch (err: unknown) {
+      const axiosErr = err as { response?: { data?: { error?: string } } };
+      setError(axiosErr?.response?.data?.error || t("ssoError"));
+      setLoading(false);
+    }
+  };
+
   const handleResend = async () => {
     setError("");
     setResending(true);
@@ -120,6 +135,20 @@ export default function LoginPage() {
           </p>
         </div>
 
+        {archived && (
+          <div className="mb-5 p-4 bg-amber-50 border border-amber-200 rounded-xl">
+            <p className="text-sm font-semibold text-amber-800">{t("archivedTitle")}</p>
+            <p className="text-xs text-amber-700 mt-1">{t("archivedDesc")}</p>
+          </div>
+        )}
+
+        {maintenance && (
+          <div className="mb-5 p-4 bg-amber-50 border border-amber-200 rounded-xl">
+            <p className="text-sm font-semibold text-amber-800">{t("maintenanceTitle")}</p>
+            <p className="text-xs text-amber-700 mt-1">{t("maintenanceDesc")}</p>
+          </div>
+        )}
+
         {trialExpired && (
           <div className="mb-5 p-4 bg-amber-50 border border-amber-200 rounded-xl">
             <p className="text-sm font-semibold text-amber-800">{t("trialExpiredTitle")}</p>
@@ -177,6 +206,17 @@ export default function LoginPage() {
                 {t("forgotPassword")}
               </a>
             </div>
+
+            {ssoEnabled && (
+              <button
+                type="button"
+                onClick={handleSso}
+                disabled={loading}
+                className="w-full py-3 border border-slate-200 hover:bg-slate-50 text-slate-700 font-medium rounded-lg transition disabled:opacity-50"
+              >
+                {t("ssoButton")}
+              </button>
+            )}
           </form>
         ) : (
           <form onSubmit={handleStep2} className="space-y-4">
@@ -0,0 +1,67 @@
+"use client";
+
+import { Suspense, useEffect, useRef, useState } from "react";
+import { useTranslations } from "next-intl";
+import { useParams, useSearchParams, useRouter } from "next/navigation";
+import { authApi } from "../../../../lib/api";
+import { storeAuthData } from "../../../../lib/auth";
+
+function OidcCallback() {
+  const t = useTranslations("auth");
+  const params = useParams();
+  const searchParams = useSearchParams();
+  const router = useRouter();
+  const locale = params.locale as string;
+  const [error, setError] = useState("");
+  // The code is single-use: strict mode must not post it twice.
+  const started = useRef(false);
+
+  useEffect(() => {
+    if (started.current) return;
+    started.current = true;
+
+    const code = searchParams.get("code");
+    const state = searchParams.get("state");
+    if (!code || !state) {
+      setError(searchParams.get("error_description") || t("ssoError"));
+      return;
+    }
+    authApi
+      .oidcCallback(code, state)
+      .then((res) => {
+        storeAuthData(res.data);
+        router.replace(`/${locale}/dashboard`);
+      })
+      .catch((err: unknown) => {
+        const axiosErr = err as { response?: { data?: { error?: string } } };
+        setError(axiosErr?.response?.data?.error || t("ssoError"));
+      });
+  }, [searchParams, router, locale, t]);
+
+  if (!error) return <p className="text-center text-slate-500">{t("ssoInProgress")}</p>;
+
+  return (
+    <div className="space-y-4">
+      <div className="p-3 bg-red-50 border border-red-200 rounded-lg text-red-600 text-sm">{error}</div>
+      <div className="text-center">
+        <a href={`/${locale}/login`} className="text-sm text-blue-600 hover:underline">
+          {t("loginButton")}
+        </a>
+      </div>
+    </div>
+  );
+}
+
+export default function OidcCallbackPage() {
+  const tc = useTranslations("common");
+
+  return (
+    <div className="min-h-screen flex items-center justify-center bg-slate-50">
+      <div className="w-full max-w-md bg-white rounded-2xl shadow-lg p-8">
+        <Suspense fallback={<p className="text-center text-slate-500">{tc("loading")}</p>}>
+          <OidcCallback />
+        </Suspense>
+      </div>
+    </div>
+  );
+}
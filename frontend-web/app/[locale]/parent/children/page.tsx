@@ -4,7 +4,7 @@ import { useState, useEffect, useRef } from "react";
 import { useTranslations } from "next-intl";
 import { useParams } from "next/navigation";
 import useSWR, { mutate as globalMutate } from "swr";
-import { childrenApi, groupsApi, attendanceApi, journalApi, activitiesApi } from "../../../../lib/api";
+import { childrenApi, groupsApi, attendanceApi, journalApi, activitiesApi, childPhotoSrc } from "../../../../lib/api";
 import { ChildAvatar, childAvatarColor } from "../../../../components/ChildAvatar";
 import { Users, Pencil, Check, X, ChevronLeft, ChevronRight, Loader2 } from "lucide-react";
 import { format, startOfMonth, endOfMonth, eachDayOfInterval, isSameDay, addMonths, subMonths, parseISO, getISODay, startOfWeek } from "date-fns";
@@ -142,7 +142,7 @@ function ParentJournalsSection({ child }: { child: Child }) {
 
 function ChildCard({ child, groupMap }: { child: Child; groupMap: Record<string, string> }) {
   const t = useTranslations("children");
-  const photoUrl = child.photo_url ? `${process.env.NEXT_PUBLIC_API_URL}/media/files/${child.photo_url}` : null;
+  const photoUrl = childPhotoSrc(child.photo_url);
 
   return (
     <div className="bg-surface-card rounded-xl shadow-card p-5">
@@ -965,7 +965,7 @@ export default function ParentChildrenPage() {
           )}
           {children.map((child) => {
             const isActive = selectedChildId === child.id;
-            const photoUrl = child.photo_url ? `${process.env.NEXT_PUBLIC_API_URL}/media/files/${child.photo_url}` : null;
+            const photoUrl = childPhotoSrc(child.photo_url);
             return (
               <button
                 key={child.id}
@@ -1069,7 +1069,7 @@ export default function ParentChildrenPage() {
           <div className="flex gap-2 overflow-x-auto px-4 py-2.5 flex-shrink-0 scrollbar-none">
             {children.map((child) => {
               const isActive = selectedChildId === child.id;
-              const photoUrl = child.photo_url ? `${process.env.NEXT_PUBLIC_API_URL}/media/files/${child.photo_url}` : null;
+              const photoUrl = childPhotoSrc(child.photo_url);
               return (
                 <button
                   key={child.id}
@@ -1,10 +1,10 @@
 "use client";
 
-import { useState } from "react";
+import { useState, useCallback } from "react";
 import { useTranslations } from "next-intl";
 import useSWR from "swr";
-import { ChevronLeft, ChevronRight, BookOpen } from "lucide-react";
-import { childrenApi, journalApi, menusApi } from "../../../../lib/api";
+import { ChevronLeft, ChevronRight, BookOpen, AlertCircle } from "lucide-react";
+import { attendanceApi, childrenApi, journalApi, menusApi, childPhotoSrc } from "../../../../lib/api";
 import { ChildAvatar, childAvatarColor } from "../../../../components/ChildAvatar";
 import { WeatherPicker } from "../../../../components/journal/WeatherPicker";
 import { EmojiPicker } from "../../../../components/journal/EmojiPicker";
@@ -26,6 +26,13 @@ import {
   addDays,
   getDefaultActiveDayIndex,
 } from "../../../../components/journal/journalUtils";
+import { useWebSocket } from "../../../../hooks/useWebSocket";
+
+interface AutoAbsence {
+  id: string;
+  child_id: string;
+  date: string;
+}
 
 interface Child {
   id: string;
@@ -71,10 +78,22 @@ export default function ParentJournalPage() {
   const effectiveChildId = selectedChildId || (children.length > 0 ? children[0].id : "");
 
   const swrKey = effectiveChildId ? ["journal-week", effectiveChildId, weekStartStr] : null;
-  const { data: journalData } = useSWR(swrKey, () =>
+  const { data: journalData, mutate: mutateJournal } = useSWR(swrKey, () =>
     journalApi.getWeek(effectiveChildId, weekStartStr)
   );
 
+  // Garderies with live journal updates push changes as the educators write them
+  const handleWsMessage = useCallback(
+    (data: unknown) => {
+      const msg = data as { type?: string; payload?: { child_id?: string } };
+      if (msg?.type === "journal_updated" && msg.payload?.child_id === effectiveChildId) {
+        mutateJournal();
+      }
+    },
+    [effectiveChildId, mutateJournal]
+  );
+  useWebSocket(handleWsMessage);
+
   const serverEntries: DailyJournal[] =
     (journalData as { data: DailyJournal[] } | undefined)?.data ?? [];
 
@@ -102,6 +121,23 @@ export default function ParentJournalPage() {
     };
   };
 
+  const { data: autoAbsencesData, mutate: mutateAutoAbsences } = useSWR("auto-absences-parent", () =>
+    attendanceApi.listAutoAbsences()
+  );
+  const autoAbsences: AutoAbsence[] =
+    (autoAbsencesData as { data: AutoAbsence[] } | undefined)?.data ?? [];
+  const [confirmingId, setConfirmingId] = useState<string | null>(null);
+
+  const confirmAutoAbsence = async (id: string) => {
+    setConfirmingId(id);
+    try {
+      await attendanceApi.confirmAutoAbsence(id);
+    } finally {
+      setConfirmingId(null);
+      mutateAutoAbsences();
+    }
+  };
+
   const getDayData = (dateStr: string): DailyJournal =>
     serverEntries.find((e) => e.date === dateStr) ?? emptyEntry(dateStr);
 
@@ -145,6 +181,36 @@ export default function ParentJournalPage() {
     </div>
   );
 
+  // ── Automatic absences awaiting confirmation ───────────────────────────────
+  const AutoAbsenceBanner = () =>
+    autoAbsences.length === 0 ? null : (
+      <div className="mx-4 md:mx-6 mt-3 space-y-2 flex-shrink-0">
+        {autoAbsences.map((absence) => {
+          const child = children.find((c) => c.id === absence.child_id);
+          const date = new Date(`${absence.date}T12:00:00`).toLocaleDateString("fr-CA", {
+            weekday: "long",
+            day: "numeric",
+            month: "long",
+          });
+          return (
+            <div key={absence.id} className="flex items-center gap-3 rounded-lg border border-amber-200 bg-amber-50 px-4 py-3">
+              <AlertCircle className="w-4 h-4 text-amber-600 flex-shrink-0" />
+              <p className="flex-1 text-sm text-amber-800">
+                {t("autoAbsenceNotice", { name: child?.first_name ?? "", date })}
+              </p>
+              <button
+                onClick={() => confirmAutoAbsence(absence.id)}
+                disabled={confirmingId === absence.id}
+                className="px-3 py-1.5 rounded-lg bg-amber-600 text-white text-xs font-medium hover:bg-amber-700 disabled:opacity-50"
+              >
+                {t("autoAbsenceConfirm")}
+              </button>
+            </div>
+          );
+        })}
+      </div>
+    );
+
   // ── Desktop content ────────────────────────────────────────────────────────
   const DesktopContent = () => (
     <div className="flex flex-col h-full overflow-hidden">
@@ -246,7 +312,7 @@ export default function ParentJournalPage() {
           <div className="flex-1 overflow-y-auto py-2">
             {children.map((child) => {
               const isActive = effectiveChildId === child.id;
-              const photoUrl = child.photo_url ? `${process.env.NEXT_PUBLIC_API_URL}/media/files/${child.photo_url}` : null;
+              const photoUrl = childPhotoSrc(child.photo_url);
               return (
                 <button
                   key={child.id}
@@ -274,6 +340,7 @@ export default function ParentJournalPage() {
             <h1 className="text-base font-semibold text-slate-800">{t("title")}</h1>
           </div>
         )}
+        <AutoAbsenceBanner />
         <DesktopContent />
       </div>
 
@@ -285,12 +352,14 @@ export default function ParentJournalPage() {
           <WeekNav />
         </div>
 
+        <AutoAbsenceBanner />
+
         {/* Child chips (only if multiple) */}
         {children.length > 1 && (
           <div className="flex gap-2 overflow-x-auto px-4 py-2.5 border-b border-slate-100 flex-shrink-0 scrollbar-none">
             {children.map((child) => {
               const isActive = effectiveChildId === child.id;
-              const photoUrl = child.photo_url ? `${process.env.NEXT_PUBLIC_API_URL}/media/files/${child.photo_url}` : null;
+              const photoUrl = childPhotoSrc(child.photo_url);
               return (
                 <button
                   key={child.id}
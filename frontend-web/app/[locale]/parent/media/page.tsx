@@ -4,7 +4,9 @@ import { useState, useRef, useCallback, useEffect } from "react";
 import { useTranslations } from "next-intl";
 import useSWR from "swr";
 import { mediaApi, childrenApi, groupsApi } from "../../../../lib/api";
-import { ChevronLeft, ChevronRight, Download, X, Globe, Users, Baby, Lock } from "lucide-react";
+import { MediaComments } from "../../../../components/media/MediaComments";
+import { formatDuration } from "../../../../lib/dateUtils";
+import { ChevronLeft, ChevronRight, Download, X, Globe, Users, Baby, Lock, Star, EyeOff, Eye } from "lucide-react";
 
 interface MediaItem {
   id: string;
@@ -12,18 +14,25 @@ interface MediaItem {
   storage_path: string;
   thumbnail_path: string | null;
   media_type: "photo" | "video";
+  /** Videos: filled in with the poster (thumbnail_path) once processed. */
+  duration_secs: number | null;
   caption: string | null;
   group_id: string | null;
   child_id: string | null;
   child_ids: string[];
   visibility: "private" | "public" | "group" | "child";
   created_at: string;
+  is_favorite: boolean;
+  is_hidden: boolean;
+  /** Shown as a blurred copy: a child in the photo has no photo consent. */
+  is_redacted: boolean;
 }
 
 interface Child { id: string; first_name: string; last_name: string; }
 interface Group { id: string; name: string; }
 
 type Period = "day" | "week" | "month";
+type Marked = "" | "favorites" | "hidden";
 
 const API_URL = process.env.NEXT_PUBLIC_API_URL || "http://localhost/api";
 
@@ -63,6 +72,7 @@ export default function ParentMediaPage() {
   const [filterDate, setFilterDate] = useState(new Date());
   const [filterGroupId, setFilterGroupId] = useState("");
   const [filterChildId, setFilterChildId] = useState("");
+  const [filterMarked, setFilterMarked] = useState<Marked>("");
 
   const [lightboxIndex, setLightboxIndex] = useState<number | null>(null);
 
@@ -71,20 +81,32 @@ export default function ParentMediaPage() {
     child_ids: filterChildId || undefined,
     period: filterPeriod || undefined,
     date: filterPeriod ? formatDateParam(filterDate) : undefined,
+    marked: filterMarked || undefined,
   });
 
-  const { data } = useSWR(`media-list-parent-${swrKey}`, () =>
+  const { data, mutate } = useSWR(`media-list-parent-${swrKey}`, () =>
     mediaApi.list({
       group_id: filterGroupId || undefined,
       child_ids: filterChildId || undefined,
       period: filterPeriod || undefined,
       date: filterPeriod ? formatDateParam(filterDate) : undefined,
+      favorites: filterMarked === "favorites" ? 1 : undefined,
+      hidden: filterMarked === "hidden" ? 1 : undefined,
     })
   );
+
+  const toggleFavorite = async (item: MediaItem) => {
+    await mediaApi.setFavorite(item.id, !item.is_favorite);
+    mutate();
+  };
+  const toggleHidden = async (item: MediaItem) => {
+    await mediaApi.setHidden(item.id, !item.is_hidden);
+    mutate();
+  };
   const { data: childrenData } = useSWR("children-parent-media", () => childrenApi.list());
   const { data: groupsData } = useSWR("groups-parent-media", () => groupsApi.list());
 
-  const mediaItems: MediaItem[] = (data as { data: MediaItem[] } | undefined)?.data ?? [];
+  const mediaItems: MediaItem[] = (data as { data: { items: MediaItem[] } } | undefined)?.data.items ?? [];
   const children: Child[] = (childrenData as { data: Child[] } | undefined)?.data ?? [];
   const groups: Group[] = (groupsData as { data: Group[] } | undefined)?.data ?? [];
 
@@ -200,9 +222,27 @@ export default function ParentMediaPage() {
           </select>
         )}
 
-        {(filterPeriod || filterGroupId || filterChildId) && (
+        {/* Favorites and hidden media */}
+        <div className="flex gap-1">
+          {(["favorites", "hidden"] as const).map((m) => (
+            <button
+              key={m}
+              onClick={() => setFilterMarked(filterMarked === m ? "" : m)}
+              className={`flex items-center gap-1 px-3 py-1.5 text-xs rounded-lg font-medium border transition ${
+                filterMarked === m
+                  ? "bg-blue-600 text-white border-blue-600"
+                  : "border-slate-200 text-slate-600 hover:bg-slate-50"
+              }`}
+            >
+              {m === "favorites" ? <Star className="w-3.5 h-3.5" /> : <EyeOff className="w-3.5 h-3.5" />}
+              {m === "favorites" ? t("favorites") : t("hiddenMedia")}
+            </button>
+          ))}
+        </div>
+
+        {(filterPeriod || filterGroupId || filterChildId || filterMarked) && (
           <button
-            onClick={() => { setFilterPeriod(""); setFilterGroupId(""); setFilterChildId(""); setFilterDate(new Date()); }}
+            onClick={() => { setFilterPeriod(""); setFilterGroupId(""); setFilterChildId(""); setFilterMarked(""); setFilterDate(new Date()); }}
             className="text-xs text-slate-400 hover:text-slate-600 underline"
           >
             {tc("reset")}
@@ -222,6 +262,8 @@ export default function ParentMediaPage() {
               childMap={childMap}
               groupMap={groupMap}
               onClick={() => setLightboxIndex(idx)}
+              onToggleFavorite={() => toggleFavorite(item)}
+              onToggleHidden={() => toggleHidden(item)}
             />
           ))}
         </div>
@@ -262,6 +304,7 @@ export default function ParentMediaPage() {
             {lightboxItem.media_type === "video" ? (
               <video
                 src={`${API_URL}/media/files/${lightboxItem.storage_path}`}
+                poster={lightboxItem.thumbnail_path ? `${API_URL}/media/files/${lightboxItem.thumbnail_path}` : undefined}
                 controls
                 className="max-h-[80vh] max-w-full rounded-lg"
               />
@@ -274,6 +317,9 @@ export default function ParentMediaPage() {
               />
             )}
 
+            {lightboxItem.is_redacted && (
+              <p className="mt-3 text-white/60 text-xs text-center">{t("redactedNotice")}</p>
+            )}
             {lightboxItem.caption && (
               <p className="mt-3 text-white text-sm text-center">{lightboxItem.caption}</p>
             )}
@@ -288,6 +334,9 @@ export default function ParentMediaPage() {
               <Download className="w-4 h-4" />
               {t("download")}
             </a>
+
+            {/* Only the parents of the children shown may comment */}
+            {lightboxItem.child_ids.length > 0 && <MediaComments mediaId={lightboxItem.id} />}
           </div>
 
           {/* Close */}
@@ -308,11 +357,15 @@ function ParentMediaCard({
   childMap,
   groupMap,
   onClick,
+  onToggleFavorite,
+  onToggleHidden,
 }: {
   item: MediaItem;
   childMap: Record<string, string>;
   groupMap: Record<string, string>;
   onClick: () => void;
+  onToggleFavorite: () => void;
+  onToggleHidden: () => void;
 }) {
   const tc = useTranslations("common");
   const t = useTranslations("media");
@@ -349,6 +402,11 @@ function ParentMediaCard({
             </div>
           </div>
         )}
+        {item.duration_secs != null && (
+          <span className="absolute bottom-2 right-2 group-hover:hidden px-1.5 py-0.5 rounded bg-black/60 text-white text-xs font-medium tabular-nums pointer-events-none">
+            {formatDuration(item.duration_secs)}
+          </span>
+        )}
         {/* Visibility badge */}
         <div className="absolute bottom-2 left-2">
           <span className={`inline-flex items-center gap-1 px-1.5 py-0.5 rounded-full text-xs font-medium ${badge.color}`}>
@@ -366,6 +424,24 @@ function ParentMediaCard({
         >
           <Download className="w-3.5 h-3.5" />
         </a>
+        {/* Star — always shown once starred */}
+        <button
+          onClick={(e) => { e.stopPropagation(); onToggleFavorite(); }}
+          className={`absolute top-2 left-2 items-center p-1.5 bg-white/90 rounded-lg shadow-sm transition ${
+            item.is_favorite ? "flex text-amber-500" : "hidden group-hover:flex text-slate-600 hover:text-amber-500"
+          }`}
+          title={item.is_favorite ? t("removeFavorite") : t("addFavorite")}
+        >
+          <Star className="w-3.5 h-3.5" fill={item.is_favorite ? "currentColor" : "none"} />
+        </button>
+        {/* Hide / show again */}
+        <button
+          onClick={(e) => { e.stopPropagation(); onToggleHidden(); }}
+          className="absolute bottom-2 right-2 hidden group-hover:flex items-center p-1.5 bg-white/90 rounded-lg text-slate-600 hover:text-blue-600 shadow-sm transition"
+          title={item.is_hidden ? t("unhideMedia") : t("hideMedia")}
+        >
+          {item.is_hidden ? <Eye className="w-3.5 h-3.5" /> : <EyeOff className="w-3.5 h-3.5" />}
+        </button>
       </div>
       {item.caption && (
         <div className="px-3 py-2">
@@ -3,7 +3,7 @@
 import { useState, useEffect } from "react";
 import { useTranslations } from "next-intl";
 import useSWR from "swr";
-import { authApi, childrenApi } from "../../../../lib/api";
+import { authApi, childrenApi, childPhotoSrc } from "../../../../lib/api";
 import { childAvatarColor, ChildAvatar } from "../../../../components/ChildAvatar";
 import { Download, AlertCircle, Check, Lock, Trash2 } from "lucide-react";
 
@@ -248,7 +248,7 @@ export default function ParentPrivacyPage() {
                       firstName={child.first_name}
                       lastName={child.last_name}
                       size="md"
-                      photoUrl={child.photo_url ? `${process.env.NEXT_PUBLIC_API_URL}/media/files/${child.photo_url}` : null}
+                      photoUrl={childPhotoSrc(child.photo_url)}
                     />
                     <div>
                       <p className="font-medium text-slate-900">
@@ -4,7 +4,7 @@ import { useState } from "react";
 import { useTranslations } from "next-intl";
 import useSWR from "swr";
 import { useAuth } from "../../../../hooks/useAuth";
-import { authApi, childrenApi } from "../../../../lib/api";
+import { authApi, childrenApi, childPhotoSrc } from "../../../../lib/api";
 import { childAvatarColor, ChildAvatar } from "../../../../components/ChildAvatar";
 import { Eye, EyeOff, Save, AlertCircle, Check } from "lucide-react";
 
@@ -257,7 +257,7 @@ export default function ParentProfilePage() {
         ) : (
           <div className="flex flex-wrap gap-2">
             {childrenList.map((child) => {
-              const photoUrl = child.photo_url ? `${process.env.NEXT_PUBLIC_API_URL}/media/files/${child.photo_url}` : null;
+              const photoUrl = childPhotoSrc(child.photo_url);
               return (
                 <div
                   key={child.id}
@@ -0,0 +1,100 @@
+"use client";
+
+import { useState } from "react";
+import { useTranslations } from "next-intl";
+import useSWR from "swr";
+import { Send, Trash2 } from "lucide-react";
+import { mediaApi } from "../../lib/api";
+import { getStoredUser } from "../../lib/auth";
+
+interface MediaComment {
+  id: string;
+  media_id: string;
+  author_id: string | null;
+  author_name: string | null;
+  author_role: string | null;
+  content: string;
+  created_at: string;
+}
+
+/**
+ * Comments under a media in a lightbox. Only staff and the parents of the children
+ * in the media may read them; for anyone else the API answers 403 and nothing shows.
+ */
+export function MediaComments({ mediaId }: { mediaId: string }) {
+  const t = useTranslations("media");
+  const tc = useTranslations("common");
+  const user = getStoredUser();
+  const isAdmin = user?.role === "admin_garderie" || user?.role === "super_admin";
+
+  const { data, error, mutate } = useSWR(["media-comments", mediaId], () => mediaApi.comments(mediaId));
+  const comments: MediaComment[] = (data as { data: MediaComment[] } | undefined)?.data ?? [];
+  const [draft, setDraft] = useState("");
+  const [sending, setSending] = useState(false);
+
+  if (error) return null;
+
+  const send = async () => {
+    if (!draft.trim()) return;
+    setSending(true);
+    try {
+      await mediaApi.addComment(mediaId, draft.trim());
+      setDraft("");
+      mutate();
+    } finally {
+      setSending(false);
+    }
+  };
+
+  const remove = async (id: string) => {
+    await mediaApi.deleteComment(mediaId, id);
+    mutate();
+  };
+
+  return (
+    <div className="mt-4 w-full max-w-lg bg-white/10 rounded-lg p-3 text-left">
+      <p className="text-xs font-medium text-white/70 mb-2">{t("comments")}</p>
+      {comments.length === 0 ? (
+        <p className="text-xs text-white/50 mb-2">{t("noComments")}</p>
+      ) : (
+        <ul className="space-y-2 mb-2 max-h-48 overflow-y-auto">
+          {comments.map((c) => (
+            <li key={c.id} className="flex items-start gap-2 text-sm text-white">
+              <div className="flex-1 min-w-0">
+                <span className="font-medium">{c.author_name ?? t("formerUser")}</span>{" "}
+                <span className="text-white/80 break-words">{c.content}</span>
+              </div>
+              {(c.author_id === user?.id || isAdmin) && (
+                <button
+                  onClick={() => remove(c.id)}
+                  className="p-1 text-white/50 hover:text-red-400 transition"
+                  title={tc("delete")}
+                >
+                  <Trash2 className="w-3.5 h-3.5" />
+                </button>
+              )}
+            </li>
+          ))}
+        </ul>
+      )}
+      <div className="flex gap-2">
+        <input
+          value={draft}
+          onChange={(e) => setDraft(e.target.value)}
+          onKeyDown={(e) => { if (e.key === "Enter") send(); }}
+          placeholder={t("commentPlaceholder")}
+          maxLength={5000}
+          className="flex-1 px-3 py-1.5 rounded-lg bg-white/90 text-sm text-slate-800 focus:outline-none focus:ring-2 focus:ring-blue-500"
+        />
+        <button
+          onClick={send}
+          disabled={sending || !draft.trim()}
+          className="p-2 bg-blue-600 hover:bg-blue-700 disabled:opacity-50 rounded-lg text-white transition"
+          title={tc("send")}
+        >
+          <Send className="w-4 h-4" />
+        </button>
+      </div>
+    </div>
+  );
+}
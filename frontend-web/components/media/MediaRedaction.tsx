@@ -0,0 +1,177 @@
+"use client";
+
+import { useRef, useState } from "react";
+import { useTranslations } from "next-intl";
+import useSWR from "swr";
+import { EyeOff, ScanFace, Square, Trash2 } from "lucide-react";
+import { mediaApi, RedactionRegion } from "../../lib/api";
+
+interface Redaction {
+  media_id: string;
+  regions: RedactionRegion[];
+  source: "manual" | "detected";
+  status: "pending" | "ready" | "failed";
+  attempts: number;
+  last_error: string | null;
+}
+
+interface RedactionState {
+  redaction: Redaction | null;
+  detection_enabled: boolean;
+}
+
+/**
+ * Staff control, in the lightbox, for the blurred copy of a group photo: parents of
+ * other children get it instead of the photo when a child shown has no photo consent.
+ * Regions are drawn over the photo, or found by the face detection service.
+ */
+export function MediaRedaction({ mediaId, src }: { mediaId: string; src: string }) {
+  const t = useTranslations("media");
+  const tc = useTranslations("common");
+  const { data, mutate } = useSWR(["media-redaction", mediaId], () => mediaApi.redaction(mediaId), {
+    refreshInterval: (latest) =>
+      (latest as { data: RedactionState } | undefined)?.data?.redaction?.status === "pending" ? 3000 : 0,
+  });
+  const state = (data as { data: RedactionState } | undefined)?.data;
+  const redaction = state?.redaction ?? null;
+
+  const [drawing, setDrawing] = useState(false);
+  const [regions, setRegions] = useState<RedactionRegion[]>([]);
+  const [start, setStart] = useState<{ x: number; y: number } | null>(null);
+  const [current, setCurrent] = useState<RedactionRegion | null>(null);
+  const [busy, setBusy] = useState(false);
+  const frame = useRef<HTMLDivElement>(null);
+
+  if (!state) return null;
+
+  const point = (e: React.MouseEvent) => {
+    const rect = frame.current!.getBoundingClientRect();
+    return {
+      x: Math.min(Math.max((e.clientX - rect.left) / rect.width, 0), 1),
+      y: Math.min(Math.max((e.clientY - rect.top) / rect.height, 0), 1),
+    };
+  };
+
+  const box = (a: { x: number; y: number }, b: { x: number; y: number }): RedactionRegion => ({
+    x: Math.min(a.x, b.x),
+    y: Math.min(a.y, b.y),
+    width: Math.abs(a.x - b.x),
+    height: Math.abs(a.y - b.y),
+  });
+
+  const submit = async (body: { regions?: RedactionRegion[]; detect?: boolean }) => {
+    setBusy(true);
+    try {
+      await mediaApi.requestRedaction(mediaId, body);
+      setDrawing(false);
+      setRegions([]);
+      mutate();
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const remove = async () => {
+    setBusy(true);
+    try {
+      await mediaApi.deleteRedaction(mediaId);
+      mutate();
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const status = redaction
+    ? redaction.status === "failed"
+      ? t("redactionFailed", { error: redaction.last_error ?? "" })
+      : t(redaction.status === "ready" ? "redactionReady" : "redactionPending")
+    : t("redactionNone");
+
+  return (
+    <div className="mt-4 w-full max-w-lg bg-white/10 rounded-lg p-3 text-left" onClick={(e) => e.stopPropagation()}>
+      <p className="flex items-center gap-1.5 text-xs font-medium text-white/70 mb-1">
+        <EyeOff className="w-3.5 h-3.5" />
+        {t("redaction")}
+      </p>
+      <p className="text-xs text-white/60 mb-2">{status}</p>
+
+      {drawing && (
+        <div className="mb-2">
+          <p className="text-xs text-white/60 mb-1">{t("redactionDrawHint")}</p>
+          <div
+            ref={frame}
+            className="relative select-none cursor-crosshair"
+            onMouseDown={(e) => { const p = point(e); setStart(p); setCurrent(box(p, p)); }}
+            onMouseMove={(e) => { if (start) setCurrent(box(start, point(e))); }}
+            onMouseUp={() => {
+              if (current && current.width > 0.01 && current.height > 0.01) setRegions([...regions, current]);
+              setStart(null);
+              setCurrent(null);
+            }}
+          >
+            {/* eslint-disable-next-line @next/next/no-img-element */}
+            <img src={src} alt="" draggable={false} className="w-full rounded" />
+            {[...regions, ...(current ? [current] : [])].map((r, i) => (
+              <div
+                key={i}
+                className="absolute border-2 border-red-500 bg-red-500/30"
+                style={{ left: `${r.x * 100}%`, top: `${r.y * 100}%`, width: `${r.width * 100}%`, height: `${r.height * 100}%` }}
+              />
+            ))}
+          </div>
+        </div>
+      )}
+
+      <div className="flex flex-wrap gap-2">
+        {drawing ? (
+          <>
+            <button
+              onClick={() => submit({ regions })}
+              disabled={busy || regions.length === 0}
+              className="px-3 py-1.5 bg-blue-600 hover:bg-blue-700 disabled:opacity-50 rounded-lg text-white text-xs font-medium transition"
+            >
+              {t("redactionBlur", { n: regions.length })}
+            </button>
+            <button
+              onClick={() => { setDrawing(false); setRegions([]); }}
+              className="px-3 py-1.5 bg-white/10 hover:bg-white/20 rounded-lg text-white text-xs font-medium transition"
+            >
+              {tc("cancel")}
+            </button>
+          </>
+        ) : (
+          <>
+            {state.detection_enabled && (
+              <button
+                onClick={() => submit({ detect: true })}
+                disabled={busy}
+                className="flex items-center gap-1.5 px-3 py-1.5 bg-white/10 hover:bg-white/20 disabled:opacity-50 rounded-lg text-white text-xs font-medium transition"
+              >
+                <ScanFace className="w-3.5 h-3.5" />
+                {t("redactionDetect")}
+              </button>
+            )}
+            <button
+              onClick={() => setDrawing(true)}
+              disabled={busy}
+              className="flex items-center gap-1.5 px-3 py-1.5 bg-white/10 hover:bg-white/20 disabled:opacity-50 rounded-lg text-white text-xs font-medium transition"
+            >
+              <Square className="w-3.5 h-3.5" />
+              {t("redactionDraw")}
+            </button>
+            {redaction && (
+              <button
+                onClick={remove}
+                disabled={busy}
+                className="flex items-center gap-1.5 px-3 py-1.5 bg-white/10 hover:bg-red-600/80 disabled:opacity-50 rounded-lg text-white text-xs font-medium transition"
+              >
+                <Trash2 className="w-3.5 h-3.5" />
+                {t("redactionRemove")}
+              </button>
+            )}
+          </>
+        )}
+      </div>
+    </div>
+  );
+}
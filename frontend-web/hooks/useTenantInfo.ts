@@ -8,6 +8,10 @@ export interface TenantInfo {
   logo_url: string | null;
   loading: boolean;
   notFound: boolean;
+  archived: boolean;
+  ssoEnabled: boolean;
+  maintenance: boolean;
+  features: Record<string, boolean>;
 }
 
 export function useTenantInfo(): TenantInfo {
@@ -15,6 +19,10 @@ export function useTenantInfo(): TenantInfo {
   const [logo_url, setLogoUrl] = useState<string | null>(null);
   const [loading, setLoading] = useState(true);
   const [notFound, setNotFound] = useState(false);
+  const [archived, setArchived] = useState(false);
+  const [ssoEnabled, setSsoEnabled] = useState(false);
+  const [maintenance, setMaintenance] = useState(false);
+  const [features, setFeatures] = useState<Record<string, boolean>>({});
 
   useEffect(() => {
     apiClient
@@ -22,12 +30,16 @@ export function useTenantInfo(): TenantInfo {
       .then((res) => {
         setName(res.data.name);
         if (res.data.logo_url) setLogoUrl(res.data.logo_url);
+        setSsoEnabled(!!res.data.sso_enabled);
+        setFeatures(res.data.features ?? {});
       })
       .catch((err) => {
         if (err?.response?.status === 404) setNotFound(true);
+        if (err?.response?.status === 410) setArchived(true);
+        if (err?.response?.data?.code === "maintenance") setMaintenance(true);
       })
       .finally(() => setLoading(false));
   }, []);
 
-  return { name, logo_url, loading, notFound };
+  return { name, logo_url, loading, notFound, archived, ssoEnabled, maintenance, features };
 }
@@ -2,6 +2,7 @@
 
 import { useEffect, useRef, useCallback } from "react";
 import Cookies from "js-cookie";
+import { authApi } from "../lib/api";
 
 function getWsUrl(): string {
   if (typeof window === "undefined") return "ws://localhost/ws";
@@ -12,13 +13,22 @@ function getWsUrl(): string {
 export function useWebSocket(onMessage: (data: unknown) => void) {
   const wsRef = useRef<WebSocket | null>(null);
   const reconnectTimeout = useRef<ReturnType<typeof setTimeout> | null>(null);
+  const closed = useRef(false);
 
-  const connect = useCallback(() => {
-    const token = Cookies.get("access_token");
-    if (!token) return;
+  const connect = useCallback(async () => {
+    if (!Cookies.get("access_token")) return;
 
-    const url = `${getWsUrl()}?token=${token}`;
-    const ws = new WebSocket(url);
+    // The socket is opened with a short-lived ticket, never the access token itself
+    let ticket: string;
+    try {
+      ticket = (await authApi.wsTicket()).data.ticket;
+    } catch {
+      if (!closed.current) reconnectTimeout.current = setTimeout(connect, 3000);
+      return;
+    }
+    if (closed.current) return;
+
+    const ws = new WebSocket(`${getWsUrl()}?ticket=${encodeURIComponent(ticket)}`);
     ws.onopen = () => {
       if (reconnectTimeout.current) clearTimeout(reconnectTimeout.current);
     };
@@ -29,15 +39,17 @@ export function useWebSocket(onMessage: (data: unknown) => void) {
       } catch {}
     };
     ws.onclose = () => {
-      reconnectTimeout.current = setTimeout(connect, 3000);
+      if (!closed.current) reconnectTimeout.current = setTimeout(connect, 3000);
     };
     ws.onerror = () => ws.close();
     wsRef.current = ws;
   }, [onMessage]);
 
   useEffect(() => {
+    closed.current = false;
     connect();
     return () => {
+      closed.current = true;
       if (reconnectTimeout.current) clearTimeout(reconnectTimeout.current);
       wsRef.current?.close();
     };
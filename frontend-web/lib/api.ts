@@ -10,11 +10,27 @@ export function getTenantSlug(): string {
 }
 
 // Super-admin axios instance — sends X-Super-Admin-Key, no tenant/JWT headers
+// Child photos are served only to the child's parents and staff; images load
+// without headers, so the access token goes in the query string
+export function childPhotoSrc(photoUrl?: string | null): string | null {
+  if (!photoUrl) return null;
+  const token = Cookies.get("access_token") || "";
+  return `${API_URL}/media/files/${photoUrl}?token=${encodeURIComponent(token)}`;
+}
+
 export const superAdminClient = axios.create({ baseURL: API_URL });
 superAdminClient.interceptors.request.use((config) => {
   if (typeof window !== "undefined") {
     const key = localStorage.getItem("super_admin_key") || "";
     config.headers["X-Super-Admin-Key"] = key;
+    const email = localStorage.getItem("super_admin_email");
+    // Identifies the operator for pending operations (request, confirm, cancel);
+    // the password is kept for the tab only
+    const password = sessionStorage.getItem("super_admin_password");
+    if (email && password) {
+      config.headers["X-Super-Admin-Email"] = email;
+      config.headers["X-Super-Admin-Password"] = password;
+    }
   }
   return config;
 });
@@ -31,6 +47,11 @@ apiClient.interceptors.request.use((config) => {
 
   if (token) config.headers["Authorization"] = `Bearer ${token}`;
   if (tenant) config.headers["X-Tenant"] = tenant;
+  // Language of error messages when not signed in (the profile's locale wins otherwise)
+  if (typeof window !== "undefined") {
+    const locale = window.location.pathname.split("/")[1];
+    if (locale === "fr" || locale === "en") config.headers["Accept-Language"] = locale;
+  }
 
   // For FormData, remove the default Content-Type so the browser sets it
   // automatically with the correct multipart boundary
@@ -41,13 +62,15 @@ apiClient.interceptors.request.use((config) => {
   return config;
 });
 
-// Auto-refresh on 401, redirect on 402 (trial expired)
+// Auto-refresh on 401, redirect on 402 (trial expired), 410 (garderie archived) and
+// 503 "maintenance".
+// A 402 "trial_read_only" only rejects the write: the page shows the error.
 apiClient.interceptors.response.use(
   (res) => res,
   async (error) => {
     const original = error.config;
 
-    if (error.response?.status === 402) {
+    if (error.response?.status === 402 && error.response?.data?.code !== 'trial_read_only') {
       const locale = window.location.pathname.split('/')[1] || 'fr';
       if (!window.location.pathname.includes('/login')) {
         window.location.href = `/${locale}/login?reason=trial_expired`;
@@ -55,6 +78,22 @@ apiClient.interceptors.response.use(
       return Promise.reject(error);
     }
 
+    if (error.response?.status === 410) {
+      const locale = window.location.pathname.split('/')[1] || 'fr';
+      if (!window.location.pathname.includes('/login')) {
+        window.location.href = `/${locale}/login?reason=archived`;
+      }
+      return Promise.reject(error);
+    }
+
+    if (error.response?.status === 503 && error.response?.data?.code === 'maintenance') {
+      const locale = window.location.pathname.split('/')[1] || 'fr';
+      if (!window.location.pathname.includes('/login')) {
+        window.location.href = `/${locale}/login?reason=maintenance`;
+      }
+      return Promise.reject(error);
+    }
+
     if (error.response?.status === 401 && !original._retry) {
       original._retry = true;
       try {
@@ -80,17 +119,26 @@ apiClient.interceptors.response.use(
   }
 );
 
+/** Makes an update conditional on the version (`updated_at`) the page loaded. */
+const ifMatch = (version?: string) => (version ? { headers: { "If-Match": version } } : undefined);
+
+/** One key per send: the request can then be retried without posting or emailing twice. */
+const idempotent = () => ({ headers: { "Idempotency-Key": crypto.randomUUID() } });
+
 // Auth endpoints
 export const authApi = {
-  login: (email: string, password: string) =>
-    apiClient.post("/auth/login", { email, password }, {
+  login: (email: string, password: string, two_factor_channel?: "email" | "sms") =>
+    apiClient.post("/auth/login", { email, password, two_factor_channel }, {
       headers: { "X-Tenant": getTenantSlug() },
     }),
   logout: (refreshToken: string) =>
     apiClient.post("/auth/logout", { refresh_token: refreshToken }),
   me: () => apiClient.get("/auth/me"),
-  invite: (email: string, role: string) =>
-    apiClient.post("/auth/invite", { email, role }, {
+  linkedTenants: () => apiClient.get("/auth/tenants"),
+  switchTenant: (tenant: string) =>
+    apiClient.post("/auth/switch-tenant", { tenant }),
+  invite: (email: string, role: string, preferredLocale?: string) =>
+    apiClient.post("/auth/invite", { email, role, preferred_locale: preferredLocale }, {
       headers: { "X-Tenant": getTenantSlug() },
     }),
   listPendingInvitations: () =>
@@ -99,6 +147,10 @@ export const authApi = {
     apiClient.post(`/auth/invitations/${id}/resend`),
   deleteInvitation: (id: string) =>
     apiClient.delete(`/auth/invitations/${id}`),
+  extendInvitation: (id: string, days: number) =>
+    apiClient.patch(`/auth/invitations/${id}`, { days }),
+  bulkInvite: (invitations: { email: string; role: string; preferred_locale?: string }[]) =>
+    apiClient.post("/auth/invitations/bulk", { invitations }),
   register: (data: {
     token: string;
     first_name: string;
@@ -115,10 +167,16 @@ export const authApi = {
   }) => apiClient.post("/auth/register", data),
   registerPushToken: (platform: string, token: string) =>
     apiClient.post("/auth/push-token", { platform, token }),
+  /** On sign-out, so the device stops receiving this account's notifications. */
+  unregisterPushToken: (token: string) => apiClient.delete("/auth/push-token", { data: { token } }),
+  /** Single-use ticket, valid 30 seconds, for opening the WebSocket. */
+  wsTicket: () => apiClient.post<{ ticket: string }>("/ws/ticket"),
   changePassword: (current_password: string, new_password: string) =>
     apiClient.post("/auth/change-password", { current_password, new_password }),
   updateEmail: (new_email: string, password: string) =>
     apiClient.post("/auth/update-email", { new_email, password }),
+  updatePhone: (phone: string | null) =>
+    apiClient.put("/auth/phone", { phone }),
   forgotPassword: (email: string) =>
     apiClient.post("/auth/forgot-password", { email }, {
       headers: { "X-Tenant": getTenantSlug() },
@@ -134,8 +192,27 @@ export const authApi = {
   getConsent: () => apiClient.get("/auth/consent"),
   updateConsent: (photos_accepted: boolean) =>
     apiClient.put("/auth/consent", { photos_accepted }),
+  recordConsent: (consent: {
+    privacy_accepted: boolean;
+    photos_accepted: boolean;
+    accepted_at: string;
+    policy_version: string;
+    language?: string;
+  }) => apiClient.post("/auth/consent", consent),
+  consentReport: () => apiClient.get("/consents/report"),
   requestAccountDeletion: () =>
     apiClient.post("/auth/account/deletion-request"),
+  loginHistory: (limit?: number) =>
+    apiClient.get("/auth/login-history", { params: limit ? { limit } : {} }),
+  oidcLogin: (locale: string) =>
+    apiClient.get("/auth/oidc/login", {
+      params: { locale },
+      headers: { "X-Tenant": getTenantSlug() },
+    }),
+  oidcCallback: (code: string, state: string) =>
+    apiClient.post("/auth/oidc/callback", { code, state }, {
+      headers: { "X-Tenant": getTenantSlug() },
+    }),
   validateToken: (token: string) =>
     apiClient.get(`/auth/validate-token/${token}`, {
       headers: { "X-Tenant": getTenantSlug() },
@@ -143,6 +220,17 @@ export const authApi = {
 };
 
 // Messages
+export interface MessageDraftInput {
+  name: string;
+  is_template?: boolean;
+  subject?: string;
+  content: string;
+  scope?: "broadcast" | "group" | "individual" | "all_parents" | "child_parents" | "group_parents";
+  group_id?: string;
+  child_id?: string;
+  recipient_id?: string;
+}
+
 export const messagesApi = {
   list: (page = 1, perPage = 20) =>
     apiClient.get("/messages", { params: { page, per_page: perPage } }),
@@ -151,20 +239,43 @@ export const messagesApi = {
     content: string;
     group_id?: string;
     recipient_id?: string;
-  }) => apiClient.post("/messages", data),
+    urgent?: boolean;
+  }) => apiClient.post("/messages", data, idempotent()),
   sendToParents: (data: {
     subject: string;
     content: string;
-    scope: "all_parents" | "child_parents" | "group_parents";
+    scope: "all_parents" | "child_parents" | "group_parents" | "family_parents";
     child_id?: string;
     group_id?: string;
-  }) => apiClient.post("/messages/send-to-parents", data),
+    family_id?: string;
+  }) => apiClient.post("/messages/send-to-parents", data, idempotent()),
+  drafts: (templates?: boolean) =>
+    apiClient.get("/messages/drafts", { params: { templates } }),
+  getDraft: (id: string) => apiClient.get(`/messages/drafts/${id}`),
+  createDraft: (data: MessageDraftInput) => apiClient.post("/messages/drafts", data),
+  updateDraft: (id: string, data: MessageDraftInput) => apiClient.put(`/messages/drafts/${id}`, data),
+  deleteDraft: (id: string) => apiClient.delete(`/messages/drafts/${id}`),
   markRead: (id: string) => apiClient.post(`/messages/${id}/read`),
+  edit: (id: string, content: string) => apiClient.put(`/messages/${id}`, { content }),
+  delete: (id: string) => apiClient.delete(`/messages/${id}`),
+  edits: (id: string) => apiClient.get(`/messages/${id}/edits`),
+  react: (id: string, emoji: string) => apiClient.post(`/messages/${id}/reactions`, { emoji }),
+  unreact: (id: string, emoji: string) =>
+    apiClient.delete(`/messages/${id}/reactions`, { params: { emoji } }),
   conversation: (userId: string, page = 1) =>
     apiClient.get(`/messages/conversation/${userId}`, {
       params: { page },
     }),
-  getConversations: () => apiClient.get("/messages/conversations"),
+  getConversations: (filters?: { archived?: boolean; muted?: boolean; pinned?: boolean }) =>
+    apiClient.get("/messages/conversations", { params: filters }),
+  updateThreadState: (data: {
+    kind: string;
+    id?: string | null;
+    archived?: boolean;
+    muted?: boolean;
+    pinned?: boolean;
+  }) => apiClient.put("/messages/thread/state", data),
+  unreadCount: () => apiClient.get("/messages/unread-count"),
   getBroadcastThread: (page = 1, perPage = 100) =>
     apiClient.get("/messages/thread/broadcast", { params: { page, per_page: perPage } }),
   getGroupThread: (groupId: string, page = 1, perPage = 100) =>
@@ -175,19 +286,96 @@ export const messagesApi = {
     apiClient.post("/messages/thread/mark-read", { kind, id: id ?? null }),
 };
 
+// Emergency broadcasts (email + push + SMS to every parent, with acknowledgements)
+export const emergencyApi = {
+  list: () => apiClient.get("/messages/emergency"),
+  get: (id: string) => apiClient.get(`/messages/emergency/${id}`),
+  send: (data: { subject: string; content: string }) =>
+    apiClient.post("/messages/emergency", data, idempotent()),
+  acknowledge: (id: string) => apiClient.post(`/messages/emergency/${id}/acknowledge`),
+};
+
 // Media
+/** An area of a photo to blur, in fractions (0–1) of its width and height. */
+export interface RedactionRegion {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
 export const mediaApi = {
-  list: (params?: { group_id?: string; child_ids?: string; page?: number; period?: string; date?: string }) =>
+  // Returns { items, next_cursor, total_count }; pass next_cursor back as cursor for the next page
+  list: (params?: { group_id?: string; child_ids?: string; page?: number; per_page?: number; cursor?: string; period?: string; date?: string; tags?: string; album_id?: string; favorites?: number; hidden?: number }) =>
     apiClient.get("/media", { params }),
   upload: (formData: FormData) =>
     apiClient.post("/media", formData, {
-      headers: { "Content-Type": "multipart/form-data" },
+      headers: { "Content-Type": "multipart/form-data", ...idempotent().headers },
     }),
-  update: (id: string, data: { caption?: string; visibility: string; group_id?: string; child_ids?: string[] }) =>
-    apiClient.put(`/media/${id}`, data),
+  /** `version` is the `updated_at` last read: a 409 "version_conflict" means someone saved in between. */
+  update: (id: string, data: { caption?: string; visibility: string; group_id?: string; child_ids?: string[]; tags?: string[] }, version?: string) =>
+    apiClient.put(`/media/${id}`, data, ifMatch(version)),
   delete: (id: string) => apiClient.delete(`/media/${id}`),
-  bulk: (data: { action: string; media_ids: string[]; visibility?: string; group_id?: string; child_ids?: string[] }) =>
+  bulk: (data: { action: string; media_ids: string[]; visibility?: string; group_id?: string; child_ids?: string[]; tags?: string[] }) =>
     apiClient.post("/media/bulk", data),
+  tags: () => apiClient.get("/media/tags"),
+  /** The user's own star and hide marks; hidden media leave the gallery unless `hidden: 1` is listed. */
+  setFavorite: (id: string, favorite: boolean) =>
+    favorite ? apiClient.post(`/media/${id}/favorite`) : apiClient.delete(`/media/${id}/favorite`),
+  setHidden: (id: string, hidden: boolean) =>
+    hidden ? apiClient.post(`/media/${id}/hide`) : apiClient.delete(`/media/${id}/hide`),
+  comments: (id: string) => apiClient.get(`/media/${id}/comments`),
+  addComment: (id: string, content: string) => apiClient.post(`/media/${id}/comments`, { content }, idempotent()),
+  /** Authors remove their own comments, admins any. */
+  deleteComment: (id: string, commentId: string) => apiClient.delete(`/media/${id}/comments/${commentId}`),
+  /** Staff only: the blurred copy parents of other children get when a child shown has no photo consent. */
+  redaction: (id: string) => apiClient.get(`/media/${id}/redaction`),
+  requestRedaction: (id: string, data: { regions?: RedactionRegion[]; detect?: boolean }) =>
+    apiClient.put(`/media/${id}/redaction`, data),
+  deleteRedaction: (id: string) => apiClient.delete(`/media/${id}/redaction`),
+};
+
+// Albums
+export const albumsApi = {
+  list: () => apiClient.get("/albums"),
+  get: (id: string) => apiClient.get(`/albums/${id}`),
+  create: (data: { title: string; description?: string; visibility?: string; group_id?: string; child_id?: string }) =>
+    apiClient.post("/albums", data),
+  update: (id: string, data: { title: string; description?: string; cover_media_id?: string; visibility?: string; group_id?: string; child_id?: string }) =>
+    apiClient.put(`/albums/${id}`, data),
+  delete: (id: string) => apiClient.delete(`/albums/${id}`),
+  addMedia: (id: string, media_ids: string[]) => apiClient.post(`/albums/${id}/media`, { media_ids }),
+  removeMedia: (id: string, mediaId: string) => apiClient.delete(`/albums/${id}/media/${mediaId}`),
+  share: (id: string, message?: string) => apiClient.post(`/albums/${id}/share`, { message }),
+};
+
+// Public self-serve signup (no tenant)
+export interface SignupInput {
+  slug: string;
+  name: string;
+  phone?: string;
+  address_line1?: string;
+  city?: string;
+  province?: string;
+  postal_code?: string;
+  first_name: string;
+  last_name: string;
+  email: string;
+  password: string;
+  consent?: {
+    privacy_accepted: boolean;
+    parents_commitment_accepted?: boolean;
+    accepted_at: string;
+    policy_version: string;
+    language?: string;
+  };
+  /** Cloudflare Turnstile token, when the widget is enabled. */
+  captcha_token?: string;
+}
+
+export const signupApi = {
+  checkSlug: (slug: string) => apiClient.get("/signup/check-slug", { params: { slug } }),
+  signup: (data: SignupInput) => apiClient.post("/signup", data),
 };
 
 // Tenant settings
@@ -204,32 +392,104 @@ export const tenantApi = {
 
 // Documents
 export const documentsApi = {
-  list: (params?: { category?: string; group_id?: string; page?: number }) =>
+  list: (params?: { category?: string; group_id?: string; folder_id?: string; page?: number }) =>
     apiClient.get("/documents", { params }),
+  // Several "file" fields create one document each
   upload: (formData: FormData) =>
     apiClient.post("/documents", formData, {
       headers: { "Content-Type": "multipart/form-data" },
     }),
-  update: (id: string, data: { title: string; category: string; visibility: string; group_id?: string; child_id?: string }) =>
+  // expires_at: "YYYY-MM-DD", or "" to clear it
+  update: (id: string, data: { title: string; category: string; visibility: string; group_id?: string; child_id?: string; folder_id?: string; expires_at?: string }) =>
     apiClient.put(`/documents/${id}`, data),
+  expiring: (days?: number) => apiClient.get("/documents/expiring", { params: { days } }),
   delete: (id: string) => apiClient.delete(`/documents/${id}`),
+  bulk: (data: {
+    action: "delete" | "assign" | "move";
+    document_ids: string[];
+    visibility?: string;
+    group_id?: string;
+    child_id?: string;
+    folder_id?: string | null;
+  }) => apiClient.post("/documents/bulk", data),
+  replace: (id: string, file: File, expiresAt?: string) => {
+    const form = new FormData();
+    form.append("file", file);
+    if (expiresAt !== undefined) form.append("expires_at", expiresAt);
+    return apiClient.post(`/documents/${id}/replace`, form, {
+      headers: { "Content-Type": "multipart/form-data" },
+    });
+  },
+  versions: (id: string) => apiClient.get(`/documents/${id}/versions`),
+  listFolders: () => apiClient.get("/documents/folders"),
+  createFolder: (data: { name: string; parent_id?: string | null }) =>
+    apiClient.post("/documents/folders", data),
+  updateFolder: (id: string, data: { name: string; parent_id?: string | null }) =>
+    apiClient.put(`/documents/folders/${id}`, data),
+  deleteFolder: (id: string) => apiClient.delete(`/documents/folders/${id}`),
+};
+
+// Antivirus quarantine (admin)
+export const quarantineApi = {
+  list: () => apiClient.get("/quarantine"),
 };
 
 // Groups
+// Staff search
+export type SearchKind = "child" | "parent" | "document" | "message";
+
+export const searchApi = {
+  search: (q: string, types?: SearchKind[], limit?: number) =>
+    apiClient.get("/search", { params: { q, types: types?.join(","), limit } }),
+};
+
 export const groupsApi = {
-  list: () => apiClient.get("/groups"),
+  /** Archived groups are left out unless `includeArchived` (admin screens). */
+  list: (includeArchived = false) =>
+    apiClient.get("/groups", { params: includeArchived ? { include_archived: true } : undefined }),
+  archive: (id: string) => apiClient.post(`/groups/${id}/archive`),
+  restore: (id: string) => apiClient.post(`/groups/${id}/restore`),
   setChildren: (id: string, child_ids: string[]) =>
     apiClient.put(`/groups/${id}/children`, { child_ids }),
-  create: (data: { name: string; description?: string; color?: string }) =>
+  create: (data: { name: string; description?: string; color?: string; allow_parent_replies?: boolean }) =>
     apiClient.post("/groups", data),
-  update: (id: string, data: Partial<{ name: string; description: string; color: string }>) =>
+  update: (id: string, data: Partial<{ name: string; description: string; color: string; allow_parent_replies: boolean }>) =>
     apiClient.put(`/groups/${id}`, data),
   delete: (id: string) => apiClient.delete(`/groups/${id}`),
+  /** School-year change: either `mappings` or `age_rules`; `dry_run` only lists the moves. */
+  rollover: (data: {
+    mappings?: { source_group_id: string; target_group_id: string | null }[];
+    age_rules?: { min_age_months: number; max_age_months?: number | null; group_id: string }[];
+    as_of?: string;
+    dry_run?: boolean;
+    notify_parents?: boolean;
+  }) => apiClient.post("/groups/rollover", data),
+  childHistory: (childId: string) => apiClient.get(`/children/${childId}/group-history`),
+};
+
+// Families (siblings and the parents they share)
+export const familiesApi = {
+  list: () => apiClient.get("/families"),
+  get: (id: string) => apiClient.get(`/families/${id}`),
+  /** Children without a family who share a parent. */
+  suggestions: () => apiClient.get("/families/suggestions"),
+  create: (data: { name: string; notes?: string; combined_billing?: boolean; child_ids?: string[] }) =>
+    apiClient.post("/families", data),
+  update: (id: string, data: Partial<{ name: string; notes: string; combined_billing: boolean }>) =>
+    apiClient.put(`/families/${id}`, data),
+  delete: (id: string) => apiClient.delete(`/families/${id}`),
+  setChildren: (id: string, child_ids: string[]) =>
+    apiClient.put(`/families/${id}/children`, { child_ids }),
+  sendToParents: (id: string, data: { subject: string; content: string }) =>
+    apiClient.post(`/families/${id}/send-to-parents`, data, idempotent()),
 };
 
 // Children
 export const childrenApi = {
   list: () => apiClient.get("/children"),
+  getConsent: (id: string) => apiClient.get(`/children/${id}/consent`),
+  updateConsent: (id: string, photos_accepted: boolean) =>
+    apiClient.put(`/children/${id}/consent`, { photos_accepted }),
   create: (data: {
     first_name: string;
     last_name: string;
@@ -239,7 +499,7 @@ export const childrenApi = {
     start_date?: string;
     schedule_days?: number[];
   }) => apiClient.post("/children", data),
-  update: (id: string, data: Partial<{ first_name: string; last_name: string; birth_date: string; group_id: string | null; is_active: boolean; start_date: string; schedule_days: number[] }>) =>
+  update: (id: string, data: Partial<{ first_name: string; last_name: string; birth_date: string; group_id: string | null; is_active: boolean; start_date: string; schedule_days: number[]; monthly_fee_cents: number }>) =>
     apiClient.put(`/children/${id}`, data),
   listParents: (childId: string) => apiClient.get(`/children/${childId}/parents`),
   assignParent: (childId: string, userId: string, relationship: string) =>
@@ -264,9 +524,68 @@ export const childrenApi = {
   exportCsv: () =>
     apiClient.get("/children/export", { responseType: "blob" }),
   uploadAvatar: (childId: string, formData: FormData) =>
-    apiClient.post(`/children/${childId}/avatar`, formData),
+    apiClient.post(`/children/${childId}/photo`, formData),
   deleteAvatar: (childId: string) =>
-    apiClient.delete(`/children/${childId}/avatar`),
+    apiClient.delete(`/children/${childId}/photo`),
+};
+
+// Waitlist (admin_garderie); `submit` is the public form
+export interface WaitlistEntryInput {
+  child_first_name: string;
+  child_last_name: string;
+  child_birth_date: string;
+  parent_first_name: string;
+  parent_last_name: string;
+  parent_email: string;
+  parent_phone?: string;
+  desired_start_date?: string;
+  age_group?: string;
+  priority?: number;
+  notes?: string;
+}
+
+export const waitlistApi = {
+  submit: (data: WaitlistEntryInput) => apiClient.post("/tenant/waitlist", data),
+  list: (params?: { status?: string; age_group?: string }) =>
+    apiClient.get("/waitlist", { params }),
+  create: (data: WaitlistEntryInput) => apiClient.post("/waitlist", data),
+  update: (id: string, data: Partial<WaitlistEntryInput & { status: string }>) =>
+    apiClient.put(`/waitlist/${id}`, data),
+  delete: (id: string) => apiClient.delete(`/waitlist/${id}`),
+  convert: (id: string, data: { group_id?: string; start_date?: string; schedule_days?: number[] }) =>
+    apiClient.post(`/waitlist/${id}/convert`, data),
+};
+
+export interface InvoiceItemInput {
+  kind: "tuition" | "late_fee" | "activity" | "other";
+  description: string;
+  quantity?: number;
+  unit_cents: number;
+}
+
+// Parent billing (admin_garderie; parents read their children's invoices)
+export const invoicesApi = {
+  list: (params?: { child_id?: string; status?: string; month?: string }) =>
+    apiClient.get("/invoices", { params }),
+  get: (id: string) => apiClient.get(`/invoices/${id}`),
+  pdf: (id: string) => apiClient.get(`/invoices/${id}/pdf`, { responseType: "blob" }),
+  create: (data: { child_id: string; due_date?: string; notes?: string; items: InvoiceItemInput[] }) =>
+    apiClient.post("/invoices", data),
+  update: (id: string, data: { due_date?: string; notes?: string; items?: InvoiceItemInput[] }) =>
+    apiClient.put(`/invoices/${id}`, data),
+  send: (id: string) => apiClient.post(`/invoices/${id}/send`),
+  markPaid: (id: string, data?: { paid_at?: string; method?: string; reference?: string; payer_id?: string }) =>
+    apiClient.post(`/invoices/${id}/mark-paid`, data ?? {}),
+  void: (id: string) => apiClient.post(`/invoices/${id}/void`),
+  generate: (data: { month: string; default_tuition_cents?: number; due_days?: number; send?: boolean }) =>
+    apiClient.post("/invoices/generate", data),
+};
+
+// Year-end childcare expense receipts (Relevé 24)
+export const taxReceiptsApi = {
+  list: (year?: number) => apiClient.get("/tax-receipts", { params: { year } }),
+  generate: (year: number) => apiClient.post("/tax-receipts/generate", { year }),
+  pdf: (id: string) => apiClient.get(`/tax-receipts/${id}/pdf`, { responseType: "blob" }),
 };
 
 // Tenant user management (admin_garderie)
@@ -274,37 +593,64 @@ export const usersApi = {
   list: () => apiClient.get("/users"),
   create: (data: { email: string; first_name: string; last_name: string; password: string; role?: string; preferred_locale?: string }) =>
     apiClient.post("/users", data),
-  update: (id: string, data: { first_name?: string; last_name?: string; role?: string; is_active?: boolean; preferred_locale?: string }) =>
-    apiClient.put(`/users/${id}`, data),
+  update: (id: string, data: { first_name?: string; last_name?: string; role?: string; is_active?: boolean; preferred_locale?: string; phone?: string }, version?: string) =>
+    apiClient.put(`/users/${id}`, data, ifMatch(version)),
   deactivate: (id: string, password?: string, hard?: boolean) =>
     apiClient.delete(`/users/${id}`, { params: { hard }, data: { password } }),
   resetPassword: (id: string, method: "email" | "temp_password" = "email") =>
     apiClient.post(`/users/${id}/reset-password`, { method }),
+  erase: (id: string, password: string, scrub_messages?: boolean) =>
+    apiClient.post(`/users/${id}/erase`, { password, scrub_messages }),
+  erasureCertificates: () => apiClient.get("/users/erasure-certificates"),
+  clearEmailSuppression: (id: string) => apiClient.delete(`/users/${id}/email-suppression`),
 };
 
 // Journal de bord
+export interface JournalEntryInput {
+  child_id: string;
+  date: string;
+  temperature?: string | null;
+  menu?: string | null;
+  appetit?: string | null;
+  humeur?: string | null;
+  sommeil_minutes?: number | null;
+  absent?: boolean;
+  sante?: string | null;
+  medicaments?: string | null;
+  message_educatrice?: string | null;
+  observations?: string | null;
+}
+
 export const journalApi = {
   getWeek: (childId: string, weekStart: string) =>
     apiClient.get("/journals", { params: { child_id: childId, week_start: weekStart } }),
   getMonthSummary: (childId: string, month: string) =>
     apiClient.get("/journals/month", { params: { child_id: childId, month } }),
-  upsert: (data: {
-    child_id: string;
-    date: string;
-    temperature?: string | null;
-    menu?: string | null;
-    appetit?: string | null;
-    humeur?: string | null;
-    sommeil_minutes?: number | null;
-    sante?: string | null;
-    medicaments?: string | null;
-    message_educatrice?: string | null;
-    observations?: string | null;
-  }) => apiClient.put("/journals", data),
+  /** `version` is the entry's `updated_at` when editing an existing one. */
+  upsert: (data: JournalEntryInput, version?: string) => apiClient.put("/journals", data, ifMatch(version)),
+  /** For entries already emailed (upsert answers 409 with code "journal_sent"). */
+  amend: (data: JournalEntryInput & { note: string }) => apiClient.post("/journals/amend", data),
+  amendments: (childId: string, date: string) =>
+    apiClient.get("/journals/amendments", { params: { child_id: childId, date } }),
   sendToParents: (childId: string, weekStart: string) =>
-    apiClient.post(`/journals/${childId}/send-to-parents`, { week_start: weekStart }),
+    apiClient.post(`/journals/${childId}/send-to-parents`, { week_start: weekStart }, idempotent()),
   sendAllToParents: (weekStart: string) =>
-    apiClient.post("/journals/send-all-to-parents", { week_start: weekStart }),
+    apiClient.post("/journals/send-all-to-parents", { week_start: weekStart }, idempotent()),
+  listEvents: (childId: string, date: string) =>
+    apiClient.get("/journals/events", { params: { child_id: childId, date } }),
+  createEvent: (data: {
+    child_id: string;
+    date: string;
+    event_type: string;
+    start_time: string;
+    end_time?: string | null;
+    notes?: string | null;
+  }) => apiClient.post("/journals/events", data),
+  updateEvent: (id: string, data: { event_type: string; start_time: string; end_time?: string | null; notes?: string | null }) =>
+    apiClient.put(`/journals/events/${id}`, data),
+  deleteEvent: (id: string) => apiClient.delete(`/journals/events/${id}`),
+  getTimeline: (childId: string, date: string) =>
+    apiClient.get("/journals/timeline", { params: { child_id: childId, date } }),
 };
 
 // Attendance
@@ -317,15 +663,27 @@ export const attendanceApi = {
     apiClient.put("/attendance/bulk", { child_id: childId, dates, status }),
   getMonthAllChildren: (month: string) =>
     apiClient.get("/attendance/month", { params: { month } }),
+  listAbsences: (childId: string) => apiClient.get(`/children/${childId}/absences`),
+  declareAbsence: (
+    childId: string,
+    data: {
+      start_date: string;
+      end_date?: string;
+      status?: "absent" | "malade" | "vacances";
+      reason?: string;
+    }
+  ) => apiClient.post(`/children/${childId}/absences`, data),
+  listAutoAbsences: (date?: string) => apiClient.get("/auto-absences", { params: { date } }),
+  confirmAutoAbsence: (id: string) => apiClient.post(`/auto-absences/${id}/confirm`),
 };
 
 // Activities
 export const activitiesApi = {
   list: (month: string, childId?: string) =>
     apiClient.get("/activities", { params: { month, child_id: childId } }),
-  create: (data: { title: string; description?: string; date: string; end_date?: string; capacity?: number; group_id?: string; type?: string }) =>
+  create: (data: { title: string; description?: string; date: string; end_date?: string; capacity?: number; group_id?: string; type?: string; fee_cents?: number }) =>
     apiClient.post("/activities", data),
-  update: (id: string, data: { title?: string; description?: string; date?: string; end_date?: string; capacity?: number; group_id?: string; type?: string }) =>
+  update: (id: string, data: { title?: string; description?: string; date?: string; end_date?: string; capacity?: number; group_id?: string; type?: string; fee_cents?: number }) =>
     apiClient.put(`/activities/${id}`, data),
   delete: (id: string) => apiClient.delete(`/activities/${id}`),
   register: (activityId: string, childId: string) =>
@@ -347,7 +705,7 @@ export const menusApi = {
 // Email (admin/educateur → parents)
 export const emailApi = {
   sendToParents: (data: { subject: string; body: string; recipient_id?: string }) =>
-    apiClient.post("/email/send-to-parents", data),
+    apiClient.post("/email/send-to-parents", data, idempotent()),
 };
 
 // Super-admin management
@@ -363,16 +721,32 @@ export const superAdminApi = {
     superAdminClient.post(`/super-admin/garderies/${slug}/users`, data),
   deactivateGarderieUser: (slug: string, userId: string) =>
     superAdminClient.delete(`/super-admin/garderies/${slug}/users/${userId}`),
-  deleteGarderie: (slug: string) =>
-    superAdminClient.delete(`/super-admin/garderies/${slug}`),
-  inviteGarderieUser: (slug: string, email: string, role: string) =>
-    superAdminClient.post(`/super-admin/garderies/${slug}/invite`, { email, role }),
+  deleteGarderie: (slug: string, mode: "delete" | "archive" = "delete") =>
+    superAdminClient.delete(`/super-admin/garderies/${slug}`, { params: { mode } }),
+  unarchiveGarderie: (slug: string) =>
+    superAdminClient.post(`/super-admin/garderies/${slug}/unarchive`),
+  inviteGarderieUser: (slug: string, email: string, role: string, preferredLocale?: string) =>
+    superAdminClient.post(`/super-admin/garderies/${slug}/invite`, { email, role, preferred_locale: preferredLocale }),
   triggerBackupAll: () =>
     superAdminClient.post(`/super-admin/backup`),
   listBackups: () =>
     superAdminClient.get(`/super-admin/backups`),
   triggerRestore: (db_file: string, media_file?: string) =>
     superAdminClient.post(`/super-admin/restore`, { db_file, media_file }),
+  backupGarderie: (slug: string) =>
+    superAdminClient.post(`/super-admin/garderies/${slug}/backup`),
+  listGarderieBackups: (slug: string) =>
+    superAdminClient.get(`/super-admin/garderies/${slug}/backups`),
+  restoreGarderie: (slug: string, backup_id: string, include_media = true) =>
+    superAdminClient.post(`/super-admin/garderies/${slug}/restore`, { backup_id, include_media }),
+  listBackupRuns: (slug?: string) =>
+    superAdminClient.get(`/super-admin/backup-runs`, { params: { slug } }),
+  listOperations: () =>
+    superAdminClient.get(`/super-admin/operations`),
+  confirmOperation: (id: string) =>
+    superAdminClient.post(`/super-admin/operations/${id}/confirm`),
+  cancelOperation: (id: string) =>
+    superAdminClient.post(`/super-admin/operations/${id}/cancel`),
   getAnnouncement: () =>
     superAdminClient.get("/announcement"),
   setAnnouncement: (message: string, color: "yellow" | "red") =>
@@ -383,6 +757,16 @@ export const superAdminApi = {
     superAdminClient.get(`/super-admin/audit-log/${slug}`, { params }),
   getGlobalAuditLog: (params?: { page?: number; limit?: number; action?: string; tenant?: string }) =>
     superAdminClient.get(`/super-admin/audit-log`, { params }),
+  exportTaxReceipts: (year: number, garderie?: string) =>
+    superAdminClient.get("/super-admin/tax-receipts.csv", { params: { year, garderie }, responseType: "blob" }),
+  getStats: () =>
+    superAdminClient.get("/super-admin/stats"),
+  listFeatureFlags: () =>
+    superAdminClient.get("/super-admin/feature-flags"),
+  setFeatureFlag: (key: string, enabled: boolean, slug?: string) =>
+    superAdminClient.put(`/super-admin/feature-flags/${key}`, { enabled, slug }),
+  deleteFeatureFlag: (key: string, slug?: string) =>
+    superAdminClient.delete(`/super-admin/feature-flags/${key}`, { params: { slug } }),
 };
 
 export const auditApi = {
@@ -390,9 +774,52 @@ export const auditApi = {
     apiClient.get("/audit-log", { params }),
 };
 
+export const emailLogApi = {
+  list: (params?: { page?: number; limit?: number; recipient?: string; template?: string; status?: string; from?: string; to?: string }) =>
+    apiClient.get("/email-log", { params }),
+  resend: (id: string) => apiClient.post(`/email-log/${id}/resend`),
+};
+
+export const ratiosApi = {
+  current: () => apiClient.get("/ratios"),
+  history: (params?: { from?: string; to?: string; group_id?: string }) =>
+    apiClient.get("/ratios/history", { params }),
+  getRules: () => apiClient.get("/ratios/rules"),
+  updateRules: (
+    rules: { label: string; min_age_months: number; max_age_months: number | null; children_per_educator: number }[]
+  ) => apiClient.put("/ratios/rules", { rules }),
+  listShifts: (params?: { from?: string; to?: string; group_id?: string }) =>
+    apiClient.get("/staff-shifts", { params }),
+  createShift: (data: { user_id: string; group_id: string; date: string; start_time: string; end_time: string }) =>
+    apiClient.post("/staff-shifts", data),
+  deleteShift: (id: string) => apiClient.delete(`/staff-shifts/${id}`),
+};
+
+export const statsApi = {
+  get: (params?: { from?: string; to?: string }) => apiClient.get("/stats", { params }),
+};
+
+export const emailCampaignsApi = {
+  list: (params?: { limit?: number }) => apiClient.get("/email-campaigns", { params }),
+  get: (id: string) => apiClient.get(`/email-campaigns/${id}`),
+};
+
 export const settingsApi = {
   get: () => apiClient.get("/settings"),
-  update: (data: { journal_auto_send_time: string }) => apiClient.put("/settings", data),
+  update: (data: { journal_auto_send_time?: string; email_tracking_enabled?: boolean; auto_absence_cutoff?: string }) =>
+    apiClient.put("/settings", data),
+  getOidc: () => apiClient.get("/settings/oidc"),
+  updateOidc: (data: {
+    provider: "google" | "microsoft" | "custom";
+    issuer_url?: string;
+    client_id: string;
+    client_secret?: string;
+    enabled: boolean;
+    allowed_domains: string[];
+    jit_provisioning: boolean;
+    jit_role?: "educateur" | "admin_garderie";
+  }) => apiClient.put("/settings/oidc", data),
+  deleteOidc: () => apiClient.delete("/settings/oidc"),
 };
 
 export const userApi = {
@@ -69,3 +69,14 @@ export function formatDateInMontreal(date: Date): string {
 
   return `${dateObj.year}-${dateObj.month}-${dateObj.day}`;
 }
+
+/**
+ * Format a video length as m:ss (h:mm:ss past an hour)
+ */
+export function formatDuration(secs: number): string {
+  const total = Math.round(secs);
+  const h = Math.floor(total / 3600);
+  const m = Math.floor((total % 3600) / 60);
+  const s = String(total % 60).padStart(2, "0");
+  return h > 0 ? `${h}:${String(m).padStart(2, "0")}:${s}` : `${m}:${s}`;
+}